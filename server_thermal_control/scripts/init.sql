@@ -117,6 +117,19 @@ CREATE TABLE IF NOT EXISTS analysis_results (
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
+-- 温度聚合表（压缩后的历史温度）
+CREATE TABLE IF NOT EXISTS temperature_aggregates (
+    sensor_id VARCHAR(50) NOT NULL,
+    resolution VARCHAR(10) NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    min_temperature DECIMAL(5,2) NOT NULL,
+    avg_temperature DECIMAL(5,2) NOT NULL,
+    max_temperature DECIMAL(5,2) NOT NULL,
+    sample_count BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (sensor_id, resolution, bucket_start)
+);
+
 -- 系统事件表
 CREATE TABLE IF NOT EXISTS system_events (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
@@ -132,6 +145,72 @@ CREATE TABLE IF NOT EXISTS system_events (
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
+-- 告警模板表
+CREATE TABLE IF NOT EXISTS alert_templates (
+    id VARCHAR(50) PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    alert_type VARCHAR(50) NOT NULL,
+    severity VARCHAR(20) NOT NULL,
+    title_template VARCHAR(200) NOT NULL,
+    message_template TEXT NOT NULL,
+    metadata_template JSONB,
+    usage_count BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 告警规则启用状态表
+CREATE TABLE IF NOT EXISTS alert_rule_states (
+    rule_id VARCHAR(100) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 告警累计统计表，只保存 id = 1 一行
+CREATE TABLE IF NOT EXISTS alert_statistics (
+    id SMALLINT PRIMARY KEY CHECK (id = 1),
+    lifetime TEXT NOT NULL,
+    last_reset TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 风扇转速快照表
+CREATE TABLE IF NOT EXISTS fan_speed_snapshots (
+    id VARCHAR(50) PRIMARY KEY,
+    speeds TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- 控制决策表，供事故后回放控制器的输入与输出
+CREATE TABLE IF NOT EXISTS control_decisions (
+    id BIGSERIAL PRIMARY KEY,
+    timestamp TIMESTAMPTZ NOT NULL,
+    sensor_id VARCHAR(100) NOT NULL,
+    fan_id VARCHAR(100) NOT NULL,
+    input_temperature DOUBLE PRECISION NOT NULL,
+    setpoint DOUBLE PRECISION NOT NULL,
+    proportional DOUBLE PRECISION NOT NULL,
+    integral DOUBLE PRECISION NOT NULL,
+    derivative DOUBLE PRECISION NOT NULL,
+    raw_output DOUBLE PRECISION NOT NULL,
+    limited_output DOUBLE PRECISION NOT NULL,
+    quiet_cap DOUBLE PRECISION,
+    readback_percent DOUBLE PRECISION,
+    action_type VARCHAR(50) NOT NULL DEFAULT 'automatic_control'
+);
+
+-- 操作注释表，在历史曲线上标出更换风扇、负载高峰等操作事件
+CREATE TABLE IF NOT EXISTS annotations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ,
+    text TEXT NOT NULL,
+    author VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
 -- 创建索引
 CREATE INDEX IF NOT EXISTS idx_temperature_data_sensor_timestamp ON temperature_data(sensor_id, timestamp DESC);
 CREATE INDEX IF NOT EXISTS idx_temperature_data_timestamp ON temperature_data(timestamp DESC);
@@ -156,9 +235,16 @@ CREATE INDEX IF NOT EXISTS idx_monitoring_metrics_name_timestamp ON monitoring_m
 CREATE INDEX IF NOT EXISTS idx_monitoring_metrics_type_timestamp ON monitoring_metrics(metric_type, timestamp DESC);
 CREATE INDEX IF NOT EXISTS idx_monitoring_metrics_timestamp ON monitoring_metrics(timestamp DESC);
 
+CREATE INDEX IF NOT EXISTS idx_control_decisions_timestamp ON control_decisions(timestamp);
+CREATE INDEX IF NOT EXISTS idx_control_decisions_fan_timestamp ON control_decisions(fan_id, timestamp DESC);
+
+CREATE INDEX IF NOT EXISTS idx_annotations_start_time ON annotations(start_time);
+
 CREATE INDEX IF NOT EXISTS idx_analysis_results_type_timestamp ON analysis_results(analysis_type, timestamp DESC);
 CREATE INDEX IF NOT EXISTS idx_analysis_results_target ON analysis_results(target_id, target_type);
 
+CREATE INDEX IF NOT EXISTS idx_temperature_aggregates_bucket ON temperature_aggregates(bucket_start DESC);
+
 CREATE INDEX IF NOT EXISTS idx_system_events_type_timestamp ON system_events(event_type, timestamp DESC);
 CREATE INDEX IF NOT EXISTS idx_system_events_category_timestamp ON system_events(event_category, timestamp DESC);
 CREATE INDEX IF NOT EXISTS idx_system_events_timestamp ON system_events(timestamp DESC);
@@ -206,6 +292,9 @@ BEGIN
     -- 清理90天前的已解决告警
     DELETE FROM alerts WHERE resolved = true AND resolved_at < NOW() - INTERVAL '90 days';
     
+    -- 清理30天前的控制决策
+    DELETE FROM control_decisions WHERE timestamp < NOW() - INTERVAL '30 days';
+    
     -- 清理7天前的监控指标
     DELETE FROM monitoring_metrics WHERE created_at < NOW() - INTERVAL '7 days';
     
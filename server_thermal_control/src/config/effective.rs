@@ -0,0 +1,168 @@
+use super::AppConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// 配置项来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// 内置默认值
+    Default,
+    /// 配置文件
+    File,
+    /// 环境变量
+    Env,
+    /// 运行时修改
+    Runtime,
+}
+
+/// 各配置项的来源
+///
+/// 键为点分字段路径，如 `server.port`；未记录的字段视为默认值
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    fields: BTreeMap<String, ConfigSource>,
+}
+
+impl ConfigSources {
+    /// 记录配置项来源，后记录的来源覆盖先记录的
+    ///
+    /// # 参数
+    /// * `path` - 点分字段路径
+    /// * `source` - 来源
+    pub fn record(&mut self, path: impl Into<String>, source: ConfigSource) {
+        self.fields.insert(path.into(), source);
+    }
+
+    /// 将配置文件中与默认值不同的字段记为来自配置文件
+    ///
+    /// # 参数
+    /// * `file_config` - 从配置文件解析出的配置
+    pub fn record_file(&mut self, file_config: &AppConfig) {
+        let defaults = leaf_values(&AppConfig::default());
+        for (path, value) in leaf_values(file_config) {
+            if defaults.get(&path) != Some(&value) {
+                self.record(path, ConfigSource::File);
+            }
+        }
+    }
+
+    /// 获取配置项来源
+    ///
+    /// # 参数
+    /// * `path` - 点分字段路径
+    pub fn source_of(&self, path: &str) -> ConfigSource {
+        self.fields.get(path).copied().unwrap_or(ConfigSource::Default)
+    }
+}
+
+/// 实际生效的配置
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+    /// 合并后的配置，敏感字段已脱敏
+    pub config: Value,
+    /// 各配置项的来源，键为点分字段路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sources: Option<BTreeMap<String, ConfigSource>>,
+}
+
+impl EffectiveConfig {
+    /// 生成生效配置视图
+    ///
+    /// # 参数
+    /// * `config` - 合并后的配置
+    /// * `sources` - 配置项来源
+    /// * `include_sources` - 是否附带各配置项来源
+    pub fn build(config: &AppConfig, sources: &ConfigSources, include_sources: bool) -> Self {
+        let sources = include_sources.then(|| {
+            leaf_values(config)
+                .into_keys()
+                .map(|path| {
+                    let source = sources.source_of(&path);
+                    (path, source)
+                })
+                .collect()
+        });
+
+        Self {
+            config: config.redacted(),
+            sources,
+        }
+    }
+}
+
+/// 将配置展开为 `点分路径 -> 叶子值`，数组整体视为一个叶子
+fn leaf_values(config: &AppConfig) -> BTreeMap<String, Value> {
+    fn walk(value: Value, prefix: String, out: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map {
+                    let path = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(field, path, out);
+                }
+            }
+            leaf => {
+                out.insert(prefix, leaf);
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    walk(serde_json::to_value(config).unwrap_or(Value::Null), String::new(), &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logging::REDACTED;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_env_override_reports_source_and_redacts_secrets() {
+        let mut file_config = AppConfig::default();
+        file_config.server.host = "10.0.0.5".to_string();
+        file_config.security.api_key = "file-api-key".to_string();
+
+        let mut sources = ConfigSources::default();
+        sources.record_file(&file_config);
+
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("APP_PORT", "9090"),
+            ("DATABASE_URL", "postgres://thermal:env-db-pass@db/thermal"),
+        ]);
+        let mut config = file_config;
+        config
+            .apply_env_overrides(&mut sources, |name| env.get(name).map(|v| v.to_string()))
+            .unwrap();
+
+        let effective = EffectiveConfig::build(&config, &sources, true);
+        let sources = effective.sources.as_ref().unwrap();
+
+        assert_eq!(effective.config["server"]["port"], 9090);
+        assert_eq!(sources["server.port"], ConfigSource::Env);
+        assert_eq!(
+            effective.config["database"]["url"],
+            format!("postgres://thermal:{}@db/thermal", REDACTED)
+        );
+        assert_eq!(sources["database.url"], ConfigSource::Env);
+        assert_eq!(effective.config["server"]["host"], "10.0.0.5");
+        assert_eq!(sources["server.host"], ConfigSource::File);
+        assert_eq!(sources["server.workers"], ConfigSource::Default);
+
+        assert_eq!(effective.config["security"]["api_key"], REDACTED);
+        let payload = serde_json::to_string(&effective).unwrap();
+        assert!(!payload.contains("file-api-key"));
+        assert!(!payload.contains("env-db-pass"));
+
+        // 默认不附带来源
+        assert!(EffectiveConfig::build(&config, &ConfigSources::default(), false)
+            .sources
+            .is_none());
+    }
+}
@@ -0,0 +1,115 @@
+use super::AppConfig;
+use crate::utils::logging::redact_json_with;
+
+/// 模板中敏感字段的占位内容
+pub const SECRET_PLACEHOLDER: &str = "CHANGE_ME";
+
+/// 模板文件头
+const TEMPLATE_HEADER: &str = "# 服务器热控制系统配置文件\n# 由当前生效配置生成，密码、密钥等敏感字段需替换为实际值后使用\n";
+
+/// 各配置段及其说明，按在模板中出现的顺序排列
+const SECTIONS: [(&str, &str); 16] = [
+    ("server", "HTTP服务器"),
+    ("database", "PostgreSQL数据库"),
+    ("redis", "Redis"),
+    ("ipmi", "IPMI/BMC连接；vendor_profile：auto（按BMC厂商识别）、dell、supermicro、custom"),
+    ("monitoring", "温度与风扇监控"),
+    ("control", "风扇自动控制"),
+    ("alert", "告警通知"),
+    ("logging", "日志"),
+    ("analytics", "数据分析"),
+    ("cache", "缓存；backend：memory | redis（redis需启用 redis-cache 特性）"),
+    ("security", "安全"),
+    ("performance", "性能"),
+    ("health", "外部依赖健康探针；probes：name、url、expected_status、timeout_ms"),
+    ("metrics_push", "指标推送：按间隔将OpenMetrics文本POST到url"),
+    ("report_email", "定时报告邮件；cadence：daily | weekly，weekday仅weekly时生效，SMTP使用alert.email"),
+    ("downloads", "导出文件下载；导出写入dir，下载链接url_ttl_secs秒后过期"),
+];
+
+/// 将配置序列化为带段说明的TOML模板，敏感字段以 [`SECRET_PLACEHOLDER`] 代替
+///
+/// 生成的内容可直接保存为 `config/app.toml`，由 [`AppConfig::load`] 读取
+///
+/// # 参数
+/// * `config` - 当前生效的配置
+pub fn render_template(config: &AppConfig) -> Result<String, Box<dyn std::error::Error>> {
+    // 经JSON脱敏后重新解析为配置，保证模板仍满足配置结构
+    let mut value = serde_json::to_value(config)?;
+    redact_json_with(&mut value, SECRET_PLACEHOLDER);
+    let redacted: AppConfig = serde_json::from_value(value)?;
+
+    let toml::Value::Table(mut table) = toml::Value::try_from(&redacted)? else {
+        return Err("配置未能序列化为TOML表".into());
+    };
+
+    let mut out = String::from(TEMPLATE_HEADER);
+
+    // 顶层标量需写在所有段之前
+    let top_level: Vec<String> = table
+        .iter()
+        .filter(|(_, value)| !value.is_table())
+        .map(|(key, _)| key.clone())
+        .collect();
+    if !top_level.is_empty() {
+        let mut scalars = toml::Table::new();
+        for key in top_level {
+            if let Some(value) = table.remove(&key) {
+                scalars.insert(key, value);
+            }
+        }
+        out.push_str("\n# 只读监控模式：为true时不向BMC下发任何风扇转速\n");
+        out.push_str(&toml::to_string(&scalars)?);
+    }
+
+    let mut sections: Vec<(String, Option<&str>)> = SECTIONS
+        .iter()
+        .map(|(name, comment)| (name.to_string(), Some(*comment)))
+        .collect();
+    sections.extend(
+        table
+            .keys()
+            .filter(|key| !SECTIONS.iter().any(|(name, _)| name == key))
+            .map(|key| (key.clone(), None)),
+    );
+
+    for (name, comment) in sections {
+        let Some(section) = table.remove(&name) else {
+            continue;
+        };
+        out.push('\n');
+        if let Some(comment) = comment {
+            out.push_str(&format!("# {}\n", comment));
+        }
+        let mut wrapper = toml::Table::new();
+        wrapper.insert(name, section);
+        out.push_str(&toml::to_string(&wrapper)?);
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_round_trips_with_secrets_replaced() {
+        let mut config = AppConfig::default();
+        config.server.port = 9090;
+        config.database.url = "postgres://thermal:db-pass@db/thermal".to_string();
+        config.security.api_key = "real-api-key".to_string();
+
+        let template = render_template(&config).unwrap();
+        assert!(template.contains("# 风扇自动控制\n[control]"));
+        assert!(!template.contains("db-pass"));
+        assert!(!template.contains("real-api-key"));
+
+        let parsed = toml::from_str::<AppConfig>(&template).unwrap();
+        assert_eq!(parsed.server.port, 9090);
+        assert_eq!(parsed.database.url, format!("postgres://thermal:{}@db/thermal", SECRET_PLACEHOLDER));
+        assert_eq!(parsed.security.api_key, SECRET_PLACEHOLDER);
+        assert_eq!(parsed.ipmi.password, SECRET_PLACEHOLDER);
+        assert_eq!(parsed.control.temp_target, config.control.temp_target);
+    }
+}
@@ -1,17 +1,25 @@
 use crate::models::{
-    error::{AppError, AppResult},
+    error::{ApiResult, AppError},
     alert::*,
     api::{ApiResponse, PaginationParams, TimeRangeParams},
 };
-use crate::services::AlertService;
+use crate::services::{AlertService, FanService, SensorService, ThermalService};
+use crate::services::alert_service::{AlertStreamEvent, RuleReplayReadings};
+use crate::services::download_store::{DownloadStore, SignedDownload};
+use crate::services::event_broadcast::BroadcastItem;
+use crate::services::event_store::SystemEventQuery;
+use crate::services::template_store::AlertTemplateSort;
+use crate::controllers::{fan_controller::FanHistoryParams, sensor_controller::SensorHistoryParams};
+use crate::utils::crypto::CryptoUtils;
+use crate::models::thermal::TemperatureQuery;
 use actix_web::{
     web::{Path, Query, Data, ServiceConfig},
-    HttpResponse, Result as ActixResult,
+    HttpResponse,
     get, post, put, delete,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{info, warn, error};
+use tracing::{info, warn};
 
 /// 告警控制器
 /// 
@@ -36,17 +44,43 @@ impl AlertController {
             actix_web::web::scope("/alerts")
                 .route("", actix_web::web::get().to(Self::get_active_alerts))
                 .route("", actix_web::web::post().to(Self::create_alert))
+                .route("/stream", actix_web::web::get().to(Self::stream_alerts))
+                .route("/evaluate", actix_web::web::post().to(Self::evaluate_rules))
+                .route("/selftest", actix_web::web::post().to(Self::run_selftest))
+                .route("/templates", actix_web::web::get().to(Self::get_alert_templates))
+                .route("/templates", actix_web::web::post().to(Self::create_alert_template))
+                .route("/templates/{template_id}", actix_web::web::get().to(Self::get_alert_template))
+                .route("/templates/{template_id}", actix_web::web::put().to(Self::update_alert_template))
+                .route("/templates/{template_id}", actix_web::web::delete().to(Self::delete_alert_template))
+                .route("/templates/{template_id}/alerts", actix_web::web::post().to(Self::create_alert_from_template))
+                .route("/channels/routing", actix_web::web::get().to(Self::get_notification_routing))
+                .route("/channels/routing", actix_web::web::put().to(Self::set_notification_routing))
+                .route("/mutes", actix_web::web::get().to(Self::get_alert_mutes))
+                .route("/mutes", actix_web::web::post().to(Self::create_alert_mute))
+                .route("/mutes/{mute_id}", actix_web::web::delete().to(Self::remove_alert_mute))
                 .route("/{alert_id}", actix_web::web::get().to(Self::get_alert))
+                .route("/{alert_id}/rule", actix_web::web::get().to(Self::get_alert_trigger_rule))
+                .route("/{alert_id}/notifications", actix_web::web::get().to(Self::get_notification_deliveries))
                 .route("/{alert_id}/acknowledge", actix_web::web::post().to(Self::acknowledge_alert))
                 .route("/{alert_id}/resolve", actix_web::web::post().to(Self::resolve_alert))
                 .route("/history", actix_web::web::get().to(Self::get_alert_history))
                 .route("/statistics", actix_web::web::get().to(Self::get_alert_statistics))
+                .route("/statistics/reset", actix_web::web::post().to(Self::reset_alert_statistics))
                 .route("/rules", actix_web::web::get().to(Self::get_alert_rules))
                 .route("/rules", actix_web::web::post().to(Self::add_alert_rule))
+                .route("/rules", actix_web::web::put().to(Self::apply_alert_rule))
                 .route("/rules/{rule_id}", actix_web::web::get().to(Self::get_alert_rule))
                 .route("/rules/{rule_id}", actix_web::web::put().to(Self::update_alert_rule))
+                .route("/rules/{rule_id}", actix_web::web::patch().to(Self::patch_alert_rule))
                 .route("/rules/{rule_id}", actix_web::web::delete().to(Self::remove_alert_rule))
+                .route("/rules/{rule_id}/test", actix_web::web::post().to(Self::test_alert_rule))
+                .route("/rules/{rule_id}/toggle", actix_web::web::post().to(Self::toggle_alert_rule))
                 .route("/channels", actix_web::web::get().to(Self::get_notification_channels))
+                .route("/channels/{channel_id}/preview", actix_web::web::post().to(Self::preview_notification))
+        );
+        cfg.service(
+            actix_web::web::scope("/events")
+                .route("", actix_web::web::get().to(Self::get_system_events))
         );
     }
 
@@ -56,20 +90,15 @@ impl AlertController {
     async fn get_active_alerts(
         service: Data<AlertService>,
         Query(params): Query<AlertQueryParams>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("获取活跃告警");
 
-        match service.get_active_alerts(
+        let alerts = service.get_active_alerts(
             params.severity,
             params.alert_type.as_deref(),
             params.limit,
-        ).await {
-            Ok(alerts) => Ok(HttpResponse::Ok().json(ApiResponse::success(alerts))),
-            Err(e) => {
-                error!("获取活跃告警失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        ).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(alerts)))
     }
 
     /// 创建告警
@@ -78,23 +107,22 @@ impl AlertController {
     async fn create_alert(
         service: Data<AlertService>,
         actix_web::web::Json(request): actix_web::web::Json<CreateAlertRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("创建告警: {}", request.title);
 
-        match service.create_alert(
+        if request.title.trim().is_empty() {
+            return Err(AppError::validation_error("title", "告警标题不能为空").into());
+        }
+
+        let alert = service.create_alert(
             request.alert_type,
             request.severity,
             request.title,
             request.message,
             request.source.as_deref(),
             request.metadata,
-        ).await {
-            Ok(alert) => Ok(HttpResponse::Ok().json(ApiResponse::success(alert))),
-            Err(e) => {
-                error!("创建告警失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        ).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(alert)))
     }
 
     /// 获取指定告警
@@ -103,17 +131,77 @@ impl AlertController {
     async fn get_alert(
         service: Data<AlertService>,
         path: Path<String>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let alert_id = path.into_inner();
         info!("获取告警: {}", alert_id);
 
-        match service.get_alert(&alert_id).await {
-            Ok(alert) => Ok(HttpResponse::Ok().json(ApiResponse::success(alert))),
-            Err(e) => {
-                error!("获取告警失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        let alert = service.get_alert(&alert_id).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(alert)))
+    }
+
+    /// 获取触发指定告警的规则
+    /// 
+    /// GET /api/v1/alerts/:alert_id/rule
+    async fn get_alert_trigger_rule(
+        service: Data<AlertService>,
+        path: Path<String>,
+    ) -> ApiResult<HttpResponse> {
+        let alert_id = path.into_inner();
+        info!("获取告警触发规则: {}", alert_id);
+
+        let trigger = service.get_alert_trigger_rule(&alert_id).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(trigger)))
+    }
+
+    /// 获取告警的通知投递结果
+    ///
+    /// GET /api/v1/alerts/:alert_id/notifications
+    async fn get_notification_deliveries(
+        service: Data<AlertService>,
+        path: Path<String>,
+    ) -> ApiResult<HttpResponse> {
+        let alert_id = path.into_inner();
+        info!("获取告警通知投递结果: {}", alert_id);
+
+        let deliveries = service.get_notification_deliveries(&alert_id).await;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(deliveries)))
+    }
+
+    /// 获取有效的告警静默
+    ///
+    /// GET /api/v1/alerts/mutes
+    async fn get_alert_mutes(service: Data<AlertService>) -> ApiResult<HttpResponse> {
+        info!("获取告警静默");
+
+        let mutes = service.get_alert_mutes().await;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(mutes)))
+    }
+
+    /// 创建告警静默
+    ///
+    /// POST /api/v1/alerts/mutes
+    async fn create_alert_mute(
+        service: Data<AlertService>,
+        actix_web::web::Json(request): actix_web::web::Json<CreateAlertMuteRequest>,
+    ) -> ApiResult<HttpResponse> {
+        info!("创建告警静默: {:?} / {:?}", request.source, request.rule_id);
+
+        let mute = service.create_alert_mute(request).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(mute)))
+    }
+
+    /// 解除告警静默
+    ///
+    /// DELETE /api/v1/alerts/mutes/:mute_id
+    async fn remove_alert_mute(
+        service: Data<AlertService>,
+        path: Path<String>,
+    ) -> ApiResult<HttpResponse> {
+        let mute_id = path.into_inner();
+        info!("解除告警静默: {}", mute_id);
+
+        let mute = service.remove_alert_mute(&mute_id).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(mute)))
     }
 
     /// 确认告警
@@ -123,21 +211,16 @@ impl AlertController {
         service: Data<AlertService>,
         path: Path<String>,
         actix_web::web::Json(request): actix_web::web::Json<AcknowledgeAlertRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let alert_id = path.into_inner();
         info!("确认告警: {}", alert_id);
 
-        match service.acknowledge_alert(
+        service.acknowledge_alert(
             &alert_id,
             request.acknowledged_by.as_deref(),
             request.note.as_deref(),
-        ).await {
-            Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::success(()))),
-            Err(e) => {
-                error!("确认告警失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        ).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
 
     /// 解决告警
@@ -147,21 +230,16 @@ impl AlertController {
         service: Data<AlertService>,
         path: Path<String>,
         actix_web::web::Json(request): actix_web::web::Json<ResolveAlertRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let alert_id = path.into_inner();
         info!("解决告警: {}", alert_id);
 
-        match service.resolve_alert(
+        service.resolve_alert(
             &alert_id,
             request.resolved_by.as_deref(),
             request.resolution_note.as_deref(),
-        ).await {
-            Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::success(()))),
-            Err(e) => {
-                error!("解决告警失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        ).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
 
     /// 获取告警历史
@@ -170,22 +248,17 @@ impl AlertController {
     async fn get_alert_history(
         service: Data<AlertService>,
         Query(params): Query<AlertHistoryParams>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("获取告警历史");
 
-        match service.get_alert_history(
+        let history = service.get_alert_history(
             params.start_time,
             params.end_time,
             params.severity,
             params.alert_type.as_deref(),
             params.limit,
-        ).await {
-            Ok(history) => Ok(HttpResponse::Ok().json(ApiResponse::success(history))),
-            Err(e) => {
-                error!("获取告警历史失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        ).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(history)))
     }
 
     /// 获取告警统计
@@ -194,18 +267,88 @@ impl AlertController {
     async fn get_alert_statistics(
         service: Data<AlertService>,
         Query(params): Query<AlertStatisticsParams>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("获取告警统计");
 
-        match service.get_alert_statistics(
+        let statistics = service.get_alert_statistics(
             params.time_window_hours.unwrap_or(24),
-        ).await {
-            Ok(statistics) => Ok(HttpResponse::Ok().json(ApiResponse::success(statistics))),
-            Err(e) => {
-                error!("获取告警统计失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        ).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(statistics)))
+    }
+
+    /// 重置告警累计统计
+    /// 
+    /// POST /api/v1/alerts/statistics/reset
+    async fn reset_alert_statistics(
+        service: Data<AlertService>,
+        actix_web::web::Json(request): actix_web::web::Json<ResetAlertStatisticsRequest>,
+    ) -> ApiResult<HttpResponse> {
+        info!("重置告警统计");
+
+        let record = service.reset_alert_statistics(request.reset_by, request.reason).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(record)))
+    }
+
+    /// 订阅告警状态变化推送（Server-Sent Events）
+    ///
+    /// 每条事件的 `event` 为状态变化类型（created/acknowledged/resolved/escalated），
+    /// `data` 为包含完整告警的JSON。客户端读取过慢时服务端丢弃最旧的事件，
+    /// 并推送一条 `event: lagged`，`data` 为 `{"dropped": 丢弃数量}`
+    ///
+    /// GET /api/v1/alerts/stream
+    async fn stream_alerts(service: Data<AlertService>) -> HttpResponse {
+        info!("订阅告警推送");
+
+        let receiver = service.subscribe_alert_events();
+        let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+            let frame = match receiver.recv().await? {
+                BroadcastItem::Event(event) => Self::sse_frame(&event),
+                BroadcastItem::Lagged(dropped) => {
+                    warn!("告警推送订阅者落后，丢弃 {} 条事件", dropped);
+                    Self::lagged_frame(dropped)
+                }
+            };
+            Some((Ok::<_, actix_web::Error>(frame), receiver))
+        });
+
+        HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header(("Cache-Control", "no-cache"))
+            .streaming(stream)
+    }
+
+    /// 将告警推送事件编码为SSE帧
+    fn sse_frame(event: &AlertStreamEvent) -> actix_web::web::Bytes {
+        let data = serde_json::to_string(event).unwrap_or_else(|_| "{}".to_string());
+        actix_web::web::Bytes::from(format!(
+            "event: {}\ndata: {}\n\n",
+            event.transition.as_str(),
+            data
+        ))
+    }
+
+    /// 将丢弃通知编码为SSE帧
+    fn lagged_frame(dropped: u64) -> actix_web::web::Bytes {
+        actix_web::web::Bytes::from(format!("event: lagged\ndata: {{\"dropped\":{}}}\n\n", dropped))
+    }
+
+    /// 查询系统事件
+    /// 
+    /// GET /api/v1/events
+    async fn get_system_events(
+        service: Data<AlertService>,
+        Query(params): Query<SystemEventParams>,
+    ) -> ApiResult<HttpResponse> {
+        info!("查询系统事件");
+
+        let query = SystemEventQuery {
+            level: params.level,
+            event_type: params.event_type,
+            page: params.page.unwrap_or(1),
+            page_size: params.page_size.unwrap_or(20),
+        };
+        let events = service.query_events(&query).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(events)))
     }
 
     /// 获取告警规则
@@ -213,16 +356,11 @@ impl AlertController {
     /// GET /api/v1/alerts/rules
     async fn get_alert_rules(
         service: Data<AlertService>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("获取告警规则");
 
-        match service.get_alert_rules().await {
-            Ok(rules) => Ok(HttpResponse::Ok().json(ApiResponse::success(rules))),
-            Err(e) => {
-                error!("获取告警规则失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        let rules = service.get_alert_rules().await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(rules)))
     }
 
     /// 添加告警规则
@@ -231,16 +369,24 @@ impl AlertController {
     async fn add_alert_rule(
         service: Data<AlertService>,
         actix_web::web::Json(rule): actix_web::web::Json<AlertRule>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("添加告警规则: {}", rule.name);
 
-        match service.add_alert_rule(rule).await {
-            Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::success(()))),
-            Err(e) => {
-                error!("添加告警规则失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        service.add_alert_rule(rule).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(())))
+    }
+
+    /// 按名称创建或更新告警规则
+    ///
+    /// PUT /api/v1/alerts/rules
+    async fn apply_alert_rule(
+        service: Data<AlertService>,
+        actix_web::web::Json(rule): actix_web::web::Json<AlertRule>,
+    ) -> ApiResult<HttpResponse> {
+        info!("按名称应用告警规则: {}", rule.name);
+
+        let rule = service.apply_alert_rule(rule).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(rule)))
     }
 
     /// 获取指定告警规则
@@ -249,17 +395,12 @@ impl AlertController {
     async fn get_alert_rule(
         service: Data<AlertService>,
         path: Path<String>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let rule_id = path.into_inner();
         info!("获取告警规则: {}", rule_id);
 
-        match service.get_alert_rule(&rule_id).await {
-            Ok(rule) => Ok(HttpResponse::Ok().json(ApiResponse::success(rule))),
-            Err(e) => {
-                error!("获取告警规则失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        let rule = service.get_alert_rule(&rule_id).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(rule)))
     }
 
     /// 更新告警规则
@@ -269,17 +410,41 @@ impl AlertController {
         service: Data<AlertService>,
         path: Path<String>,
         actix_web::web::Json(rule): actix_web::web::Json<AlertRule>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let rule_id = path.into_inner();
         info!("更新告警规则: {}", rule_id);
 
-        match service.update_alert_rule(&rule_id, rule).await {
-            Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::success(()))),
-            Err(e) => {
-                error!("更新告警规则失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        service.update_alert_rule(&rule_id, rule).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(())))
+    }
+
+    /// 部分更新告警规则
+    ///
+    /// PATCH /api/v1/alerts/rules/:rule_id
+    async fn patch_alert_rule(
+        service: Data<AlertService>,
+        path: Path<String>,
+        actix_web::web::Json(patch): actix_web::web::Json<AlertRulePatch>,
+    ) -> ApiResult<HttpResponse> {
+        let rule_id = path.into_inner();
+        info!("部分更新告警规则: {}", rule_id);
+
+        let rule = service.patch_alert_rule(&rule_id, patch).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(rule)))
+    }
+
+    /// 切换告警规则启用状态
+    ///
+    /// POST /api/v1/alerts/rules/:rule_id/toggle
+    async fn toggle_alert_rule(
+        service: Data<AlertService>,
+        path: Path<String>,
+    ) -> ApiResult<HttpResponse> {
+        let rule_id = path.into_inner();
+        info!("切换告警规则启用状态: {}", rule_id);
+
+        let rule = service.toggle_alert_rule(&rule_id).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(rule)))
     }
 
     /// 删除告警规则
@@ -288,17 +453,106 @@ impl AlertController {
     async fn remove_alert_rule(
         service: Data<AlertService>,
         path: Path<String>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let rule_id = path.into_inner();
         info!("删除告警规则: {}", rule_id);
 
-        match service.remove_alert_rule(&rule_id).await {
-            Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::success(()))),
-            Err(e) => {
-                error!("移除告警规则失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        service.remove_alert_rule(&rule_id).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(())))
+    }
+
+    /// 试运行告警规则
+    /// 
+    /// POST /api/v1/alerts/rules/:rule_id/test
+    /// 
+    /// 使用历史读数回放规则，返回预计触发次数和样本，不会产生真实告警
+    async fn test_alert_rule(
+        service: Data<AlertService>,
+        thermal_service: Data<ThermalService>,
+        fan_service: Data<FanService>,
+        sensor_service: Data<SensorService>,
+        path: Path<String>,
+        actix_web::web::Json(request): actix_web::web::Json<RuleDryRunRequest>,
+    ) -> ApiResult<HttpResponse> {
+        let rule_id = path.into_inner();
+        info!("试运行告警规则: {}", rule_id);
+
+        // 请求体中的规则优先，便于在保存前验证新规则
+        let rule = match request.rule {
+            Some(rule) => rule,
+            None => service.get_alert_rule(&rule_id).await?,
+        };
+
+        let end_time = request.end_time.unwrap_or_else(chrono::Utc::now);
+        let start_time = request.start_time.unwrap_or(end_time - chrono::Duration::hours(24));
+
+        let mut readings = RuleReplayReadings::default();
+        match rule.rule_type {
+            AlertRuleType::Temperature => thermal_service
+                .get_temperature_history(&TemperatureQuery {
+                    start_time: Some(start_time),
+                    end_time: Some(end_time),
+                    limit: None,
+                    ..Default::default()
+                })
+                .await
+                .map(|data| readings.temperature = data),
+            AlertRuleType::Fan | AlertRuleType::FanUtilization => fan_service
+                .get_fan_history(&FanHistoryParams {
+                    fan_id: None,
+                    start_time: Some(start_time),
+                    end_time: Some(end_time),
+                    limit: None,
+                })
+                .await
+                .map(|data| readings.fan = data),
+            AlertRuleType::Sensor => sensor_service
+                .get_sensor_history(&SensorHistoryParams {
+                    sensor_id: None,
+                    start_time: Some(start_time),
+                    end_time: Some(end_time),
+                    min_value: None,
+                    max_value: None,
+                    sensor_type: None,
+                    status: None,
+                    limit: None,
+                })
+                .await
+                .map(|data| readings.sensor = data),
+        }?;
+
+        let result = service.dry_run_rule(&rule, start_time, end_time, &readings).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
+    }
+
+    /// 立即评估全部启用的告警规则
+    ///
+    /// POST /api/v1/alerts/evaluate
+    ///
+    /// 使用各告警源最近一次读数评估，与监控周期中的评估一样会产生真实告警和通知
+    async fn evaluate_rules(
+        service: Data<AlertService>,
+    ) -> ApiResult<HttpResponse> {
+        info!("手动评估告警规则");
+
+        let result = service.evaluate_rules_now().await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
+    }
+
+    /// 端到端自检告警链路，返回各渠道的结果
+    ///
+    /// POST /api/v1/alerts/selftest
+    ///
+    /// 请求体可省略；`dry_run` 为true时只检查渠道配置，不发送给真实接收人
+    async fn run_selftest(
+        service: Data<AlertService>,
+        request: Option<actix_web::web::Json<AlertSelfTestRequest>>,
+    ) -> ApiResult<HttpResponse> {
+        let dry_run = request.map_or(false, |request| request.dry_run);
+        info!("告警链路自检 (dry_run: {})", dry_run);
+
+        let report = service.run_selftest(dry_run).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(report)))
     }
 
     /// 获取通知渠道
@@ -306,16 +560,36 @@ impl AlertController {
     /// GET /api/v1/alerts/channels
     async fn get_notification_channels(
         service: Data<AlertService>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("获取通知渠道");
 
-        match service.get_notification_channels().await {
-            Ok(channels) => Ok(HttpResponse::Ok().json(ApiResponse::success(channels))),
-            Err(e) => {
-                error!("获取通知渠道失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
-            }
-        }
+        let channels = service.get_notification_channels().await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(channels)))
+    }
+
+    /// 获取通知路由矩阵
+    ///
+    /// GET /api/v1/alerts/channels/routing
+    async fn get_notification_routing(
+        service: Data<AlertService>,
+    ) -> ApiResult<HttpResponse> {
+        info!("获取通知路由矩阵");
+
+        let routing = service.get_notification_routing().await;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(routing)))
+    }
+
+    /// 设置通知路由矩阵，请求体为 `null` 时清除
+    ///
+    /// PUT /api/v1/alerts/channels/routing
+    async fn set_notification_routing(
+        service: Data<AlertService>,
+        routing: actix_web::web::Json<Option<NotificationRoutingMatrix>>,
+    ) -> ApiResult<HttpResponse> {
+        info!("设置通知路由矩阵");
+
+        service.set_notification_routing(routing.into_inner()).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
 
     /// 添加通知渠道
@@ -324,11 +598,10 @@ impl AlertController {
     async fn add_notification_channel(
         service: Data<Arc<AlertService>>,
         channel: actix_web::web::Json<NotificationChannel>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("添加通知渠道: {}", channel.name);
 
-        service.add_notification_channel(channel.into_inner()).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        service.add_notification_channel(channel.into_inner()).await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
 
@@ -338,12 +611,11 @@ impl AlertController {
     async fn get_notification_channel(
         service: Data<Arc<AlertService>>,
         path: Path<String>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let channel_id = path.into_inner();
         info!("获取通知渠道: {}", channel_id);
 
-        let channel = service.get_notification_channel(&channel_id).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        let channel = service.get_notification_channel(&channel_id).await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(channel)))
     }
 
@@ -354,12 +626,11 @@ impl AlertController {
         service: Data<Arc<AlertService>>,
         path: Path<String>,
         channel: actix_web::web::Json<NotificationChannel>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let channel_id = path.into_inner();
         info!("更新通知渠道: {}", channel_id);
 
-        service.update_notification_channel(&channel_id, channel.into_inner()).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        service.update_notification_channel(&channel_id, channel.into_inner()).await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
 
@@ -369,12 +640,11 @@ impl AlertController {
     async fn remove_notification_channel(
         service: Data<Arc<AlertService>>,
         path: Path<String>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let channel_id = path.into_inner();
         info!("移除通知渠道: {}", channel_id);
 
-        service.remove_notification_channel(&channel_id).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        service.remove_notification_channel(&channel_id).await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
 
@@ -385,34 +655,45 @@ impl AlertController {
         service: Data<Arc<AlertService>>,
         path: Path<String>,
         request: actix_web::web::Json<TestNotificationRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let channel_id = path.into_inner();
         info!("测试通知渠道: {}", channel_id);
 
         let result = service.test_notification_channel(
             &channel_id,
             request.test_message.as_deref(),
-        ).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        ).await?;
 
         Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
     }
 
+    /// 预览渠道将发送的通知内容，不实际发送
+    ///
+    /// POST /api/v1/alerts/channels/:channel_id/preview
+    async fn preview_notification(
+        service: Data<Arc<AlertService>>,
+        path: Path<String>,
+        actix_web::web::Json(request): actix_web::web::Json<PreviewNotificationRequest>,
+    ) -> ApiResult<HttpResponse> {
+        let channel_id = path.into_inner();
+        let preview = service.preview_notification(&channel_id, request).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(preview)))
+    }
+
     /// 批量确认告警
     /// 
     /// POST /api/v1/alerts/bulk/acknowledge
     async fn bulk_acknowledge_alerts(
         service: Data<Arc<AlertService>>,
         request: actix_web::web::Json<BulkAcknowledgeRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("批量确认告警: {} 个", request.alert_ids.len());
 
         let result = service.bulk_acknowledge_alerts(
             request.alert_ids.clone(),
             request.acknowledged_by.as_deref(),
             request.note.as_deref(),
-        ).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        ).await?;
 
         Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
     }
@@ -423,26 +704,28 @@ impl AlertController {
     async fn bulk_resolve_alerts(
         service: Data<Arc<AlertService>>,
         request: actix_web::web::Json<BulkResolveRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("批量解决告警: {} 个", request.alert_ids.len());
 
         let result = service.bulk_resolve_alerts(
             request.alert_ids.clone(),
             request.resolved_by.as_deref(),
             request.resolution_note.as_deref(),
-        ).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        ).await?;
 
         Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
     }
 
     /// 导出告警
     /// 
+    /// 导出内容写入导出目录，响应中只返回带签名的下载链接，避免大文件嵌入JSON
+    ///
     /// GET /api/v1/alerts/export
     async fn export_alerts(
         service: Data<Arc<AlertService>>,
+        downloads: Data<DownloadStore>,
         params: Query<ExportAlertsParams>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("导出告警");
 
         let export_data = service.export_alerts(
@@ -450,10 +733,16 @@ impl AlertController {
             params.end_time,
             params.format.unwrap_or(ExportFormat::Json),
             params.include_resolved.unwrap_or(false),
-        ).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
-
-        Ok(HttpResponse::Ok().json(ApiResponse::success(export_data)))
+        ).await?;
+        let contents = CryptoUtils::base64_decode(&export_data.data)?;
+        let download = downloads.store(&export_data.filename, &contents).await?;
+
+        Ok(HttpResponse::Ok().json(ApiResponse::success(AlertExportResponse {
+            format: export_data.format,
+            alert_count: export_data.alert_count,
+            download,
+            exported_at: export_data.exported_at,
+        })))
     }
 
     /// 导入告警
@@ -462,29 +751,28 @@ impl AlertController {
     async fn import_alerts(
         service: Data<Arc<AlertService>>,
         request: actix_web::web::Json<ImportAlertsRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("导入告警");
 
         let result = service.import_alerts(
             request.data.clone(),
             request.format,
             request.merge_strategy.unwrap_or(MergeStrategy::Skip),
-        ).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        ).await?;
 
         Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
     }
 
     /// 获取告警模板
     /// 
-    /// GET /api/v1/alerts/templates
+    /// GET /api/v1/alerts/templates?sort=usage_count
     async fn get_alert_templates(
-        service: Data<Arc<AlertService>>,
-    ) -> ActixResult<HttpResponse> {
+        service: Data<AlertService>,
+        Query(params): Query<AlertTemplateListParams>,
+    ) -> ApiResult<HttpResponse> {
         info!("获取告警模板");
 
-        let templates = service.get_alert_templates().await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        let templates = service.get_alert_templates(params.sort.unwrap_or_default()).await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(templates)))
     }
 
@@ -492,21 +780,20 @@ impl AlertController {
     /// 
     /// POST /api/v1/alerts/templates
     async fn create_alert_template(
-        service: Data<Arc<AlertService>>,
+        service: Data<AlertService>,
         template: actix_web::web::Json<CreateAlertTemplateRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("创建告警模板: {}", template.name);
 
         let created_template = service.create_alert_template(
             template.name.clone(),
             template.description.clone(),
-            template.alert_type,
-            template.severity,
+            template.alert_type.clone(),
+            template.severity.clone(),
             template.title_template.clone(),
             template.message_template.clone(),
             template.metadata_template.clone(),
-        ).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        ).await?;
 
         Ok(HttpResponse::Ok().json(ApiResponse::success(created_template)))
     }
@@ -515,14 +802,13 @@ impl AlertController {
     /// 
     /// GET /api/v1/alerts/templates/:template_id
     async fn get_alert_template(
-        service: Data<Arc<AlertService>>,
+        service: Data<AlertService>,
         path: Path<String>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let template_id = path.into_inner();
         info!("获取告警模板: {}", template_id);
 
-        let template = service.get_alert_template(&template_id).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        let template = service.get_alert_template(&template_id).await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(template)))
     }
 
@@ -530,10 +816,10 @@ impl AlertController {
     /// 
     /// PUT /api/v1/alerts/templates/:template_id
     async fn update_alert_template(
-        service: Data<Arc<AlertService>>,
+        service: Data<AlertService>,
         path: Path<String>,
         request: actix_web::web::Json<UpdateAlertTemplateRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let template_id = path.into_inner();
         info!("更新告警模板: {}", template_id);
 
@@ -542,12 +828,11 @@ impl AlertController {
             request.name.clone(),
             request.description.clone(),
             request.alert_type.clone(),
-            request.severity,
+            request.severity.clone(),
             request.title_template.clone(),
             request.message_template.clone(),
             request.metadata_template.clone(),
-        ).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        ).await?;
 
         Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
@@ -556,16 +841,34 @@ impl AlertController {
     /// 
     /// DELETE /api/v1/alerts/templates/:template_id
     async fn delete_alert_template(
-        service: Data<Arc<AlertService>>,
+        service: Data<AlertService>,
         path: Path<String>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         let template_id = path.into_inner();
         info!("删除告警模板: {}", template_id);
 
-        service.delete_alert_template(&template_id).await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        service.delete_alert_template(&template_id).await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
+
+    /// 使用模板创建告警
+    /// 
+    /// POST /api/v1/alerts/templates/:template_id/alerts
+    async fn create_alert_from_template(
+        service: Data<AlertService>,
+        path: Path<String>,
+        actix_web::web::Json(request): actix_web::web::Json<CreateAlertFromTemplateRequest>,
+    ) -> ApiResult<HttpResponse> {
+        let template_id = path.into_inner();
+        info!("使用模板创建告警: {}", template_id);
+
+        let alert_id = service.create_alert_from_template(
+            &template_id,
+            request.source,
+            request.variables.unwrap_or_default(),
+        ).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(alert_id)))
+    }
 }
 
 // 请求和响应结构体
@@ -587,6 +890,10 @@ pub enum AlertType {
     Network,
     /// 硬件告警
     Hardware,
+    /// 维护告警，如散热能力不足
+    Maintenance,
+    /// 功率告警，如风扇提速与功率上限冲突
+    PowerCap,
 }
 
 /// 告警过滤器
@@ -676,6 +983,47 @@ pub struct AlertStatisticsParams {
     pub time_window_hours: Option<u32>,
 }
 
+/// 重置告警统计请求
+#[derive(Debug, Deserialize)]
+pub struct ResetAlertStatisticsRequest {
+    /// 操作人
+    pub reset_by: Option<String>,
+    /// 重置原因
+    pub reason: Option<String>,
+}
+
+/// 系统事件查询参数
+#[derive(Debug, Deserialize)]
+pub struct SystemEventParams {
+    /// 事件级别（info/warning/critical）
+    pub level: Option<String>,
+    /// 事件类型，如 alert_created
+    pub event_type: Option<String>,
+    /// 页码（从1开始）
+    pub page: Option<i32>,
+    /// 每页大小
+    pub page_size: Option<i32>,
+}
+
+/// 告警规则试运行请求
+#[derive(Debug, Deserialize)]
+pub struct RuleDryRunRequest {
+    /// 回放开始时间（默认结束时间前24小时）
+    pub start_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// 回放结束时间（默认当前时间）
+    pub end_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// 待验证的规则（不提供时使用路径中的已有规则）
+    pub rule: Option<AlertRule>,
+}
+
+/// 告警链路自检请求
+#[derive(Debug, Default, Deserialize)]
+pub struct AlertSelfTestRequest {
+    /// 只检查渠道配置，不实际发送
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
 /// 测试通知请求
 #[derive(Debug, Deserialize)]
 pub struct TestNotificationRequest {
@@ -683,6 +1031,53 @@ pub struct TestNotificationRequest {
     pub test_message: Option<String>,
 }
 
+/// 预览通知请求：示例告警，或模板与变量
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PreviewNotificationRequest {
+    /// 用模板渲染告警消息
+    Template {
+        /// 模板ID
+        template_id: String,
+        /// 告警源
+        #[serde(default = "default_preview_source")]
+        source: String,
+        /// 模板变量
+        #[serde(default)]
+        variables: std::collections::HashMap<String, String>,
+    },
+    /// 示例告警
+    Alert {
+        /// 告警类型
+        alert_type: AlertType,
+        /// 严重程度
+        severity: AlertSeverity,
+        /// 告警源
+        source: String,
+        /// 告警消息
+        message: String,
+    },
+}
+
+fn default_preview_source() -> String {
+    "preview".to_string()
+}
+
+/// 通知预览
+#[derive(Debug, Serialize)]
+pub struct NotificationPreview {
+    /// 渠道ID
+    pub channel_id: String,
+    /// 渠道类型
+    pub channel_type: ChannelType,
+    /// 内容类型，如 `application/json`、`text/markdown`
+    pub content_type: String,
+    /// 邮件主题，其他渠道为空
+    pub subject: Option<String>,
+    /// 渲染后的消息体
+    pub body: String,
+}
+
 /// 测试通知结果
 #[derive(Debug, Serialize)]
 pub struct TestNotificationResult {
@@ -779,6 +1174,19 @@ pub struct AlertExportData {
     pub exported_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// 告警导出响应
+#[derive(Debug, Serialize)]
+pub struct AlertExportResponse {
+    /// 导出格式
+    pub format: ExportFormat,
+    /// 导出的告警数量
+    pub alert_count: usize,
+    /// 导出文件的下载链接
+    pub download: SignedDownload,
+    /// 导出时间
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+}
+
 /// 导入告警请求
 #[derive(Debug, Deserialize)]
 pub struct ImportAlertsRequest {
@@ -865,31 +1273,20 @@ pub struct UpdateAlertTemplateRequest {
     pub metadata_template: Option<serde_json::Value>,
 }
 
-/// 告警模板
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AlertTemplate {
-    /// 模板ID
-    pub id: String,
-    /// 名称
-    pub name: String,
-    /// 描述
-    pub description: Option<String>,
-    /// 告警类型
-    pub alert_type: String,
-    /// 严重程度
-    pub severity: AlertSeverity,
-    /// 标题模板
-    pub title_template: String,
-    /// 消息模板
-    pub message_template: String,
-    /// 元数据模板
-    pub metadata_template: Option<serde_json::Value>,
-    /// 创建时间
-    pub created_at: chrono::DateTime<chrono::Utc>,
-    /// 更新时间
-    pub updated_at: chrono::DateTime<chrono::Utc>,
-    /// 使用次数
-    pub usage_count: u64,
+/// 告警模板列表查询参数
+#[derive(Debug, Deserialize)]
+pub struct AlertTemplateListParams {
+    /// 排序方式（name/usage_count），默认按名称
+    pub sort: Option<AlertTemplateSort>,
+}
+
+/// 使用模板创建告警请求
+#[derive(Debug, Deserialize)]
+pub struct CreateAlertFromTemplateRequest {
+    /// 告警源
+    pub source: String,
+    /// 模板变量
+    pub variables: Option<std::collections::HashMap<String, String>>,
 }
 
 #[cfg(test)]
@@ -924,6 +1321,49 @@ mod tests {
         assert!(resp.status().is_success());
     }
 
+    #[actix_web::test]
+    async fn test_get_unknown_alert_returns_not_found() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(AlertService::new()))
+                .configure(AlertController::configure)
+        ).await;
+
+        let req = test::TestRequest::get()
+            .uri("/alerts/00000000-0000-0000-0000-000000000000")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+        let body: ApiResponse<()> = test::read_body_json(resp).await;
+        assert!(!body.success);
+        assert!(body.error.is_some());
+    }
+
+    #[actix_web::test]
+    async fn test_create_alert_with_empty_title_returns_bad_request() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(AlertService::new()))
+                .configure(AlertController::configure)
+        ).await;
+
+        let req = test::TestRequest::post()
+            .uri("/alerts")
+            .set_json(serde_json::json!({
+                "alert_type": "temperature",
+                "severity": "Critical",
+                "title": "  ",
+                "message": "Temperature exceeded threshold"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+        let body: ApiResponse<()> = test::read_body_json(resp).await;
+        assert!(!body.success);
+    }
+
     #[test]
     fn test_create_alert_request_deserialization() {
         let json = r#"{
@@ -938,6 +1378,16 @@ mod tests {
         assert_eq!(request.title, "High Temperature");
     }
 
+    #[test]
+    fn test_rule_dry_run_request_deserialization() {
+        let json = r#"{"start_time": "2024-01-01T00:00:00Z", "end_time": "2024-01-02T00:00:00Z"}"#;
+        let request: RuleDryRunRequest = serde_json::from_str(json).unwrap();
+
+        assert!(request.start_time.is_some());
+        assert!(request.end_time.is_some());
+        assert!(request.rule.is_none());
+    }
+
     #[test]
     fn test_export_format_deserialization() {
         let json = r#""Json""#;
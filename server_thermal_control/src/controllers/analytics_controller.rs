@@ -1,12 +1,17 @@
+use crate::config::AppConfig;
+use crate::middleware::auth::require_api_key;
 use crate::models::{
-    error::{AppError, AppResult},
+    error::{ApiResult, AppError, AppResult},
     analytics::*,
     api::{ApiResponse, PaginationParams, TimeRangeParams},
 };
 use crate::services::{AnalyticsService, analytics_service::OptimizationRecommendation};
+use crate::services::analytics_service::{PeriodicReport, RangeComparison};
+use crate::services::result_cache::CacheClearScope;
+use chrono::{DateTime, Utc};
 use actix_web::{
     web::{Data, Path, Query, ServiceConfig},
-    HttpResponse, Result as ActixResult,
+    HttpRequest, HttpResponse, Result as ActixResult,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -40,6 +45,8 @@ impl AnalyticsController {
                 .route("/aggregation", actix_web::web::get().to(Self::aggregate_data))
                 .route("/recommendations", actix_web::web::get().to(Self::get_optimization_recommendations))
                 .route("/predictions", actix_web::web::get().to(Self::get_predictions))
+                .route("/report", actix_web::web::get().to(Self::get_periodic_report))
+                .route("/compare", actix_web::web::get().to(Self::compare_ranges))
                 .route("/reports", actix_web::web::get().to(Self::generate_reports))
                 .route("/reports/{report_id}", actix_web::web::get().to(Self::get_report))
                 .route("/reports/{report_id}", actix_web::web::delete().to(Self::delete_report))
@@ -188,6 +195,45 @@ impl AnalyticsController {
         Ok(HttpResponse::Ok().json(ApiResponse::success(report)))
     }
 
+    /// 生成周期综合报告
+    ///
+    /// GET /api/v1/analytics/report
+    async fn get_periodic_report(
+        service: Data<AnalyticsService>,
+        Query(params): Query<PeriodicReportParams>,
+    ) -> ActixResult<HttpResponse> {
+        let duration_hours = params.duration_hours.unwrap_or(DEFAULT_REPORT_HOURS);
+        info!("生成周期综合报告: {}小时, 格式: {:?}", duration_hours, params.format);
+
+        let report: PeriodicReport = service.generate_periodic_report(duration_hours).await?;
+
+        Ok(match params.format {
+            ReportFormat::Json => HttpResponse::Ok().json(ApiResponse::success(report)),
+            ReportFormat::Markdown => HttpResponse::Ok()
+                .content_type("text/markdown; charset=utf-8")
+                .body(report.to_markdown()),
+        })
+    }
+
+    /// 对比两个时间段
+    ///
+    /// GET /api/v1/analytics/compare
+    async fn compare_ranges(
+        service: Data<AnalyticsService>,
+        Query(params): Query<RangeComparisonParams>,
+    ) -> ActixResult<HttpResponse> {
+        info!(
+            "对比时间段: {} ~ {} 与 {} ~ {}",
+            params.baseline_start, params.baseline_end, params.current_start, params.current_end
+        );
+
+        let comparison: RangeComparison = service
+            .compare_ranges(params.baseline_start, params.baseline_end, params.current_start, params.current_end)
+            .await?;
+
+        Ok(HttpResponse::Ok().json(ApiResponse::success(comparison)))
+    }
+
     /// 获取指定报告
     /// 
     /// GET /api/v1/analytics/reports/:report_id
@@ -214,21 +260,28 @@ impl AnalyticsController {
         Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
 
-    /// 清理缓存
+    /// 清理缓存（需要API密钥）
+    /// 
+    /// 可按 `?type=` 或 `?sensor_id=` 限定范围，都不指定时清理全部分析缓存
     /// 
     /// POST /api/v1/analytics/cache/clear
     async fn clear_cache(
+        req: HttpRequest,
         service: Data<AnalyticsService>,
-        Query(params): Query<CacheClearParams>,
-    ) -> ActixResult<HttpResponse> {
-        info!("清理分析缓存");
+        config: Option<Data<AppConfig>>,
+        Query(scope): Query<CacheClearScope>,
+    ) -> ApiResult<HttpResponse> {
+        let config = config
+            .ok_or_else(|| AppError::authentication_error("未配置API密钥，拒绝访问受保护的端点"))?;
+        require_api_key(&req, &config.security)?;
 
-        let result = service.clear_cache(
-            params.cache_type.as_deref(),
-            params.older_than_hours,
-        ).await.map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        info!("清理分析缓存");
+        let cleared_entries = service.clear_cache(&scope).await?;
 
-        Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
+        Ok(HttpResponse::Ok().json(ApiResponse::success(CacheClearResult {
+            cleared_entries,
+            cache_types: scope.analysis_types(),
+        })))
     }
 
     /// 获取缓存状态
@@ -343,13 +396,41 @@ pub struct ReportGenerationParams {
     pub include_recommendations: Option<bool>,
 }
 
-/// 缓存清理参数
+/// 周期报告默认周期（小时），即一周
+const DEFAULT_REPORT_HOURS: u64 = 24 * 7;
+
+/// 周期报告输出格式
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    /// JSON（默认）
+    #[default]
+    Json,
+    /// Markdown文档
+    Markdown,
+}
+
+/// 周期报告参数
+#[derive(Debug, Deserialize)]
+pub struct PeriodicReportParams {
+    /// 报告周期（小时），默认一周
+    pub duration_hours: Option<u64>,
+    /// 输出格式
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// 时间段对比参数
 #[derive(Debug, Deserialize)]
-pub struct CacheClearParams {
-    /// 缓存类型
-    pub cache_type: Option<String>,
-    /// 清理多少小时前的缓存
-    pub older_than_hours: Option<u32>,
+pub struct RangeComparisonParams {
+    /// 基准时间段开始时间
+    pub baseline_start: DateTime<Utc>,
+    /// 基准时间段结束时间
+    pub baseline_end: DateTime<Utc>,
+    /// 对比时间段开始时间
+    pub current_start: DateTime<Utc>,
+    /// 对比时间段结束时间
+    pub current_end: DateTime<Utc>,
 }
 
 /// 分析数据导出参数
@@ -412,9 +493,7 @@ pub enum ExportFormat {
 pub struct CacheClearResult {
     /// 清理的条目数
     pub cleared_entries: usize,
-    /// 释放的内存大小（字节）
-    pub freed_memory_bytes: usize,
-    /// 清理的缓存类型
+    /// 清理范围涉及的分析类型
     pub cache_types: Vec<String>,
 }
 
@@ -564,6 +643,7 @@ pub struct AnalyticsReport {
 mod tests {
     use super::*;
     use crate::services::{AnalyticsService, ThermalService, FanService, SensorService};
+    use crate::services::result_cache::{InMemoryResultCache, ANALYSIS_CACHE_TTL_SECS};
     use actix_web::{test, App};
 
     #[actix_web::test]
@@ -572,8 +652,12 @@ mod tests {
         let fan_service = Arc::new(FanService::new().await.unwrap());
         let sensor_service = Arc::new(SensorService::new().await.unwrap());
         let analytics_service = Arc::new(
-            AnalyticsService::new(thermal_service, fan_service, sensor_service)
-                .await.unwrap()
+            AnalyticsService::new(
+                thermal_service,
+                fan_service,
+                sensor_service,
+                Arc::new(InMemoryResultCache::new(ANALYSIS_CACHE_TTL_SECS)),
+            )
         );
         
         let app = test::init_service(
@@ -607,6 +691,17 @@ mod tests {
         assert_eq!(params.prediction_hours, Some(6));
     }
 
+    #[test]
+    fn test_periodic_report_params_deserialization() {
+        let json = r#"{"duration_hours": 72, "format": "markdown"}"#;
+        let params: PeriodicReportParams = serde_json::from_str(json).unwrap();
+        assert_eq!(params.duration_hours, Some(72));
+        assert!(matches!(params.format, ReportFormat::Markdown));
+
+        let params: PeriodicReportParams = serde_json::from_str("{}").unwrap();
+        assert!(matches!(params.format, ReportFormat::Json));
+    }
+
     #[test]
     fn test_aggregation_type_deserialization() {
         let json = r#""Hourly""#;
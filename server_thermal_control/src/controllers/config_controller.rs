@@ -4,6 +4,7 @@ use crate::models::{
     api::{ApiResponse, PaginationParams},
 };
 use crate::services::{ConfigService, config_service::{ConfigExportFormat, ConfigTemplateType, ConfigValidationResult}};
+use crate::services::{ConfigSnapshotService, config_snapshot::ConfigSnapshot};
 use actix_web::{
     web::{Data, Path, Query, ServiceConfig},
     HttpResponse, Result as ActixResult,
@@ -62,6 +63,8 @@ impl ConfigController {
                 .route("/backups/{backup_id}", actix_web::web::get().to(Self::get_config_backup))
                 .route("/backups/{backup_id}/restore", actix_web::web::post().to(Self::restore_config_backup))
                 .route("/backups/{backup_id}", actix_web::web::delete().to(Self::delete_config_backup))
+                .route("/snapshot", actix_web::web::get().to(Self::get_config_snapshot))
+                .route("/restore", actix_web::web::post().to(Self::restore_config_snapshot))
         );
     }
 
@@ -492,6 +495,31 @@ impl ConfigController {
             .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
+
+    /// 导出运行时配置快照
+    ///
+    /// GET /api/v1/config/snapshot
+    async fn get_config_snapshot(
+        service: Data<ConfigSnapshotService>,
+    ) -> ActixResult<HttpResponse> {
+        info!("导出运行时配置快照");
+
+        let snapshot = service.snapshot().await;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(snapshot)))
+    }
+
+    /// 恢复运行时配置快照
+    ///
+    /// POST /api/v1/config/restore
+    async fn restore_config_snapshot(
+        service: Data<ConfigSnapshotService>,
+        actix_web::web::Json(snapshot): actix_web::web::Json<ConfigSnapshot>,
+    ) -> ActixResult<HttpResponse> {
+        info!("恢复运行时配置快照: 版本 {}", snapshot.version);
+
+        let restored = service.restore(snapshot).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(restored)))
+    }
 }
 
 // 请求和响应结构体
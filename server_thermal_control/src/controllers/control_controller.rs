@@ -1,13 +1,15 @@
+use crate::config::AppConfig;
+use crate::middleware::auth::require_api_key;
 use crate::models::{
     api::{ApiResponse, PaginationParams, TimeRangeParams},
     control::*,
-    error::{AppError, AppResult},
+    error::{ApiResult, AppError, AppResult},
 };
-use crate::services::ControlService;
+use crate::services::{control_service::ControlHistoryQuery, parameter_diff::ParameterChangeSource, ControlService};
 use actix_web::{
     delete, get, post, put,
     web::{Data, Path, Query, ServiceConfig},
-    HttpResponse, Result as ActixResult,
+    HttpRequest, HttpResponse, Result as ActixResult,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -51,10 +53,26 @@ impl ControlController {
                     "/config",
                     actix_web::web::put().to(Self::update_control_config),
                 )
+                .route(
+                    "/config",
+                    actix_web::web::patch().to(Self::patch_control_config),
+                )
                 .route(
                     "/fan/{fan_id}/speed",
                     actix_web::web::post().to(Self::set_fan_speed),
                 )
+                .route(
+                    "/fans/snapshot",
+                    actix_web::web::post().to(Self::snapshot_fan_speeds),
+                )
+                .route(
+                    "/fans/restore/{snapshot_id}",
+                    actix_web::web::post().to(Self::restore_fan_snapshot),
+                )
+                .route(
+                    "/fan/{fan_id}/curve/validate",
+                    actix_web::web::post().to(Self::validate_fan_curve),
+                )
                 .route(
                     "/strategy",
                     actix_web::web::post().to(Self::apply_control_strategy),
@@ -82,8 +100,53 @@ impl ControlController {
                 .route(
                     "/parameters",
                     actix_web::web::get().to(Self::get_control_parameters),
+                )
+                .route(
+                    "/parameters",
+                    actix_web::web::put().to(Self::apply_optimized_parameters),
+                )
+                .route(
+                    "/parameters/history",
+                    actix_web::web::get().to(Self::get_parameter_history),
+                )
+                .route(
+                    "/decisions",
+                    actix_web::web::get().to(Self::get_control_decisions),
+                )
+                .route(
+                    "/decisions/replay",
+                    actix_web::web::get().to(Self::replay_control_decisions),
+                )
+                .route(
+                    "/schedule",
+                    actix_web::web::get().to(Self::get_quiet_schedule),
+                )
+                .route(
+                    "/schedule",
+                    actix_web::web::put().to(Self::update_quiet_schedule),
                 ),
         );
+        cfg.route(
+            "/system/restart-control",
+            actix_web::web::post().to(Self::restart_control_loop),
+        );
+    }
+
+    /// 重启控制循环
+    ///
+    /// POST /api/v1/system/restart-control（需要API密钥）
+    async fn restart_control_loop(
+        req: HttpRequest,
+        service: Data<ControlService>,
+        config: Option<Data<AppConfig>>,
+    ) -> ApiResult<HttpResponse> {
+        let config = config
+            .ok_or_else(|| AppError::authentication_error("未配置API密钥，拒绝访问受保护的端点"))?;
+        require_api_key(&req, &config.security)?;
+
+        info!("重启控制循环");
+        let loop_started_at = service.restart_control_loop().await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(RestartControlResponse { loop_started_at })))
     }
 
     /// 启动自动控制
@@ -157,6 +220,19 @@ impl ControlController {
         Ok(HttpResponse::Ok().json(ApiResponse::success(())))
     }
 
+    /// 部分更新控制配置
+    ///
+    /// PATCH /api/v1/control/config
+    async fn patch_control_config(
+        service: Data<ControlService>,
+        patch: actix_web::web::Json<ControlConfigPatch>,
+    ) -> ApiResult<HttpResponse> {
+        info!("部分更新控制配置");
+
+        let config = service.patch_config(patch.into_inner()).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(config)))
+    }
+
     /// 设置风扇转速
     ///
     /// POST /api/v1/control/fan/:fan_id/speed
@@ -164,29 +240,71 @@ impl ControlController {
         service: Data<ControlService>,
         fan_id: Path<String>,
         request: actix_web::web::Json<SetFanSpeedRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("设置风扇转速: {}", fan_id);
 
         let result = service
             .set_fan_speed(&fan_id, request.into_inner())
-            .await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+            .await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
     }
 
+    /// 记录所有风扇的当前转速快照
+    ///
+    /// POST /api/v1/control/fans/snapshot
+    async fn snapshot_fan_speeds(service: Data<ControlService>) -> ApiResult<HttpResponse> {
+        info!("记录风扇转速快照");
+
+        let snapshot = service.snapshot_fan_speeds().await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(snapshot)))
+    }
+
+    /// 将风扇恢复到快照中的转速
+    ///
+    /// POST /api/v1/control/fans/restore/:snapshot_id
+    async fn restore_fan_snapshot(
+        service: Data<ControlService>,
+        snapshot_id: Path<String>,
+    ) -> ApiResult<HttpResponse> {
+        info!("恢复风扇转速快照: {}", snapshot_id);
+
+        let snapshot = service.restore_fan_snapshot(&snapshot_id).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(snapshot)))
+    }
+
+    /// 用历史温度回放候选风扇曲线
+    ///
+    /// POST /api/v1/control/fan/:fan_id/curve/validate
+    async fn validate_fan_curve(
+        service: Data<ControlService>,
+        fan_id: Path<String>,
+        request: actix_web::web::Json<ValidateFanCurveRequest>,
+    ) -> ApiResult<HttpResponse> {
+        info!("回放风扇曲线: {}", fan_id);
+
+        let request = request.into_inner();
+        let end_time = request.end_time.unwrap_or_else(chrono::Utc::now);
+        let start_time = request
+            .start_time
+            .unwrap_or(end_time - chrono::Duration::days(DEFAULT_CURVE_REPLAY_DAYS));
+        let replay = service
+            .validate_fan_curve(&fan_id, &request.curve, start_time, end_time)
+            .await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(replay)))
+    }
+
     /// 应用控制策略
     ///
     /// POST /api/v1/control/strategy
     async fn apply_control_strategy(
         service: Data<ControlService>,
         strategy: actix_web::web::Json<ControlStrategy>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         info!("应用控制策略");
 
         let result = service
             .apply_control_strategy(strategy.into_inner())
-            .await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+            .await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
     }
 
@@ -196,26 +314,27 @@ impl ControlController {
     async fn emergency_cooling(
         service: Data<ControlService>,
         request: actix_web::web::Json<EmergencyCoolingRequest>,
-    ) -> ActixResult<HttpResponse> {
+    ) -> ApiResult<HttpResponse> {
         warn!("启动紧急冷却");
 
         let result = service
             .emergency_cooling(request.into_inner())
-            .await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+            .await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
     }
 
     /// 退出紧急模式
     ///
+    /// 温度未持续低于退出阈值时返回409，`force=true` 时跳过确认
+    ///
     /// POST /api/v1/control/emergency/exit
-    async fn exit_emergency_mode(service: Data<ControlService>) -> ActixResult<HttpResponse> {
+    async fn exit_emergency_mode(
+        service: Data<ControlService>,
+        Query(params): Query<EmergencyExitParams>,
+    ) -> ApiResult<HttpResponse> {
         info!("退出紧急模式");
 
-        let result = service
-            .exit_emergency_mode()
-            .await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        let result = service.exit_emergency_mode(params.force).await?;
         Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
     }
 
@@ -224,19 +343,26 @@ impl ControlController {
     /// GET /api/v1/control/history
     async fn get_control_history(
         service: Data<ControlService>,
-        params: Query<ControlHistoryParams>,
-    ) -> ActixResult<HttpResponse> {
+        Query(params): Query<ControlHistoryParams>,
+    ) -> ApiResult<HttpResponse> {
         info!("获取控制历史");
 
-        let history = service
-            .get_control_history(
-                params.limit.unwrap_or(100),
-                params.offset.unwrap_or(0),
-                params.start_time,
-                params.end_time,
-            )
-            .await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        let action_types = params
+            .action_type
+            .unwrap_or_default()
+            .iter()
+            .map(|action_type| action_type.parse())
+            .collect::<AppResult<Vec<ControlActionType>>>()?;
+
+        let query = ControlHistoryQuery {
+            action_types,
+            target_id: params.target_id,
+            start_time: params.start_time,
+            end_time: params.end_time,
+            page: params.page.unwrap_or(1),
+            page_size: params.page_size.unwrap_or(100),
+        };
+        let history = service.get_control_history(&query).await?;
 
         Ok(HttpResponse::Ok().json(ApiResponse::success(history)))
     }
@@ -287,20 +413,82 @@ impl ControlController {
         Ok(HttpResponse::Ok().json(ApiResponse::success(parameters)))
     }
 
-    /// 应用优化后的参数
+    /// 获取最近的控制决策
+    ///
+    /// GET /api/v1/control/decisions
+    async fn get_control_decisions(
+        service: Data<ControlService>,
+        Query(params): Query<ControlDecisionParams>,
+    ) -> ApiResult<HttpResponse> {
+        info!("获取控制决策");
+
+        let decisions = service.get_recent_decisions(params.limit).await;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(decisions)))
+    }
+
+    /// 回放时间窗口内的控制决策，按时间正序返回
+    ///
+    /// GET /api/v1/control/decisions/replay?start=&end=
+    async fn replay_control_decisions(
+        service: Data<ControlService>,
+        Query(params): Query<DecisionReplayParams>,
+    ) -> ApiResult<HttpResponse> {
+        info!("回放控制决策: {} - {}", params.start, params.end);
+
+        let decisions = service.replay_decisions(params.start, params.end).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(decisions)))
+    }
+
+    /// 获取静音时段计划
     ///
-    /// PUT /api/v1/control/parameters
+    /// GET /api/v1/control/schedule
+    async fn get_quiet_schedule(service: Data<ControlService>) -> ApiResult<HttpResponse> {
+        info!("获取静音时段计划");
+
+        let schedule = service.get_quiet_schedule().await;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(schedule)))
+    }
+
+    /// 更新静音时段计划
+    ///
+    /// PUT /api/v1/control/schedule
+    async fn update_quiet_schedule(
+        service: Data<ControlService>,
+        request: actix_web::web::Json<QuietHoursSchedule>,
+    ) -> ApiResult<HttpResponse> {
+        info!("更新静音时段计划");
+
+        let schedule = service.update_quiet_schedule(request.into_inner()).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(schedule)))
+    }
+
+    /// 应用优化后的参数，返回应用后的参数及变化的字段
+    ///
+    /// PUT /api/v1/control/parameters?source=optimization
     async fn apply_optimized_parameters(
         service: Data<ControlService>,
+        Query(params): Query<ApplyParametersParams>,
         parameters: actix_web::web::Json<ControlParameters>,
-    ) -> ActixResult<HttpResponse> {
-        info!("应用优化后的参数");
+    ) -> ApiResult<HttpResponse> {
+        info!("应用优化后的参数（来源: {:?}）", params.source);
 
-        service
-            .apply_optimized_parameters(parameters.into_inner())
-            .await
-            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
-        Ok(HttpResponse::Ok().json(ApiResponse::success(())))
+        let applied = service
+            .apply_optimized_parameters(parameters.into_inner(), params.source)
+            .await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(applied)))
+    }
+
+    /// 获取控制参数变更记录
+    ///
+    /// GET /api/v1/control/parameters/history?limit=20
+    async fn get_parameter_history(
+        service: Data<ControlService>,
+        Query(params): Query<ParameterHistoryParams>,
+    ) -> ApiResult<HttpResponse> {
+        info!("获取控制参数变更记录");
+
+        let history = service.get_parameter_history(params.limit);
+        Ok(HttpResponse::Ok().json(ApiResponse::success(history)))
     }
 
     /// 获取控制分析
@@ -449,6 +637,20 @@ pub struct SetFanSpeedRequest {
     pub speed: u32,
 }
 
+/// 未指定开始时间时风扇曲线回放的天数
+const DEFAULT_CURVE_REPLAY_DAYS: i64 = 7;
+
+/// 风扇曲线回放请求
+#[derive(Debug, Deserialize)]
+pub struct ValidateFanCurveRequest {
+    /// 候选风扇曲线
+    pub curve: FanCurve,
+    /// 回放开始时间，默认为结束时间前7天
+    pub start_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// 回放结束时间，默认为当前时间
+    pub end_time: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 /// 应用策略请求
 #[derive(Debug, Deserialize)]
 pub struct ApplyStrategyRequest {
@@ -465,6 +667,29 @@ pub struct EmergencyCoolingRequest {
     pub max_fan_speed: Option<u32>,
 }
 
+/// 重启控制循环响应
+#[derive(Debug, Serialize)]
+pub struct RestartControlResponse {
+    /// 新的控制循环启动时间
+    pub loop_started_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 控制决策查询参数
+#[derive(Debug, Deserialize)]
+pub struct ControlDecisionParams {
+    /// 返回记录数限制
+    pub limit: Option<usize>,
+}
+
+/// 控制决策回放参数
+#[derive(Debug, Deserialize)]
+pub struct DecisionReplayParams {
+    /// 开始时间（包含）
+    pub start: chrono::DateTime<chrono::Utc>,
+    /// 结束时间（不包含）
+    pub end: chrono::DateTime<chrono::Utc>,
+}
+
 /// 控制历史参数
 #[derive(Debug, Deserialize)]
 pub struct ControlHistoryParams {
@@ -472,10 +697,14 @@ pub struct ControlHistoryParams {
     pub start_time: Option<chrono::DateTime<chrono::Utc>>,
     /// 结束时间
     pub end_time: Option<chrono::DateTime<chrono::Utc>>,
-    /// 动作类型
+    /// 动作类型（如 EmergencyCooling 或 emergency_cooling），多个取并集
     pub action_type: Option<Vec<String>>,
-    /// 返回记录数限制
-    pub limit: Option<usize>,
+    /// 目标组件ID，如风扇ID
+    pub target_id: Option<String>,
+    /// 页码（从1开始）
+    pub page: Option<i32>,
+    /// 每页大小
+    pub page_size: Option<i32>,
 }
 
 /// 控制性能参数
@@ -687,7 +916,7 @@ pub struct ExpectedImprovements {
 }
 
 /// 控制参数
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControlParameters {
     /// PID参数
     pub pid_parameters: PidParameters,
@@ -699,8 +928,49 @@ pub struct ControlParameters {
     pub control_interval_ms: u64,
 }
 
+impl ControlParameters {
+    /// 验证控制参数
+    pub fn validate(&self) -> AppResult<()> {
+        let pid = &self.pid_parameters;
+        if [pid.kp, pid.ki, pid.kd].iter().any(|gain| !gain.is_finite() || *gain < 0.0) {
+            return Err(AppError::validation_error("pid_parameters", "PID系数必须为非负数"));
+        }
+        let (min_output, max_output) = pid.output_limits;
+        if !(0.0..=100.0).contains(&min_output)
+            || !(0.0..=100.0).contains(&max_output)
+            || min_output > max_output
+        {
+            return Err(AppError::validation_error(
+                "output_limits",
+                "输出限制必须在0-100之间且下限不高于上限",
+            ));
+        }
+
+        let thresholds = &self.temperature_thresholds;
+        validate_temperature_order(&[
+            ("target_temperature", thresholds.target_temperature),
+            ("warning_temperature", thresholds.warning_temperature),
+            ("critical_temperature", thresholds.critical_temperature),
+            ("emergency_temperature", thresholds.emergency_temperature),
+        ])?;
+
+        for (fan_id, curve) in &self.fan_curves {
+            curve.validate(fan_id)?;
+        }
+
+        if !(1_000..=3_600_000).contains(&self.control_interval_ms) {
+            return Err(AppError::validation_error(
+                "control_interval_ms",
+                "控制间隔必须在1秒到1小时之间",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 /// PID参数
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PidParameters {
     /// 比例系数
     pub kp: f64,
@@ -712,8 +982,31 @@ pub struct PidParameters {
     pub output_limits: (f64, f64),
 }
 
+/// 应用控制参数的查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct ApplyParametersParams {
+    /// 变更来源：manual（默认）、optimization、preset
+    #[serde(default)]
+    pub source: ParameterChangeSource,
+}
+
+/// 控制参数变更记录查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct ParameterHistoryParams {
+    /// 返回条数上限
+    pub limit: Option<usize>,
+}
+
+/// 退出紧急模式查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct EmergencyExitParams {
+    /// 跳过温度确认强制退出
+    #[serde(default)]
+    pub force: bool,
+}
+
 /// 温度阈值
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemperatureThresholds {
     /// 目标温度
     pub target_temperature: f64,
@@ -726,7 +1019,7 @@ pub struct TemperatureThresholds {
 }
 
 /// 风扇曲线
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FanCurve {
     /// 曲线点
     pub points: Vec<FanCurvePoint>,
@@ -736,8 +1029,67 @@ pub struct FanCurve {
     pub max_speed: u32,
 }
 
+impl FanCurve {
+    /// 验证风扇曲线
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID，用于错误消息
+    pub fn validate(&self, fan_id: &str) -> AppResult<()> {
+        if self.min_speed > self.max_speed || self.max_speed > 100 {
+            return Err(AppError::validation_error(
+                "fan_curves",
+                &format!("风扇 {} 的转速范围无效", fan_id),
+            ));
+        }
+        let sorted = self
+            .points
+            .windows(2)
+            .all(|pair| pair[0].temperature < pair[1].temperature);
+        let in_range = self
+            .points
+            .iter()
+            .all(|point| (0.0..=100.0).contains(&point.speed_percent));
+        if !sorted || !in_range {
+            return Err(AppError::validation_error(
+                "fan_curves",
+                &format!("风扇 {} 的曲线点必须按温度递增且转速在0-100之间", fan_id),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 按曲线计算温度对应的转速百分比
+    ///
+    /// 曲线点之间线性插值，超出曲线范围时取端点值，结果限制在 `min_speed`-`max_speed` 之间；
+    /// 没有曲线点时返回最大转速
+    ///
+    /// # 参数
+    /// * `temperature` - 温度
+    pub fn speed_at(&self, temperature: f64) -> f64 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return self.max_speed as f64;
+        };
+
+        let speed = if temperature <= first.temperature {
+            first.speed_percent
+        } else if temperature >= last.temperature {
+            last.speed_percent
+        } else {
+            self.points
+                .windows(2)
+                .find(|pair| temperature <= pair[1].temperature)
+                .map(|pair| {
+                    let ratio = (temperature - pair[0].temperature) / (pair[1].temperature - pair[0].temperature);
+                    pair[0].speed_percent + ratio * (pair[1].speed_percent - pair[0].speed_percent)
+                })
+                .unwrap_or(last.speed_percent)
+        };
+        speed.clamp(self.min_speed as f64, self.max_speed as f64)
+    }
+}
+
 /// 风扇曲线点
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FanCurvePoint {
     /// 温度
     pub temperature: f64,
@@ -944,7 +1296,7 @@ pub struct ControlAction {
 }
 
 /// 控制动作类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ControlActionType {
     /// 手动风扇控制
     ManualFanControl,
@@ -954,6 +1306,30 @@ pub enum ControlActionType {
     ParameterOptimization,
     /// 自动控制
     AutomaticControl,
+    /// 应用控制策略
+    StrategyApplication,
+    /// 转速与上次下发相同，未写入BMC
+    NoOp,
+}
+
+impl std::str::FromStr for ControlActionType {
+    type Err = AppError;
+
+    /// 解析动作类型，同时接受 `EmergencyCooling` 与 `emergency_cooling` 两种写法
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.replace('_', "").to_ascii_lowercase().as_str() {
+            "manualfancontrol" => Ok(ControlActionType::ManualFanControl),
+            "emergencycooling" => Ok(ControlActionType::EmergencyCooling),
+            "parameteroptimization" => Ok(ControlActionType::ParameterOptimization),
+            "automaticcontrol" => Ok(ControlActionType::AutomaticControl),
+            "strategyapplication" => Ok(ControlActionType::StrategyApplication),
+            "noop" => Ok(ControlActionType::NoOp),
+            _ => Err(AppError::validation_error(
+                "action_type",
+                format!("未知的控制动作类型: {}", s),
+            )),
+        }
+    }
 }
 
 /// 控制优化结果
@@ -0,0 +1,101 @@
+use crate::controllers::control_controller::ControlStatus;
+use crate::controllers::monitoring_controller::SystemHealthStatus;
+use crate::models::{api::ApiResponse, error::ApiResult, thermal::TemperatureReading};
+use crate::services::{annotation_store::Annotation, ControlService, MonitoringService};
+use actix_web::{
+    web::{Data, ServiceConfig},
+    HttpResponse,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::info;
+
+/// 仪表盘控制器
+///
+/// 将传感器、告警、控制状态和健康评分合并为一次响应，供前端仪表盘使用
+pub struct DashboardController;
+
+impl DashboardController {
+    /// 配置路由
+    pub fn configure(cfg: &mut ServiceConfig) {
+        cfg.service(
+            actix_web::web::scope("/dashboard")
+                .route("", actix_web::web::get().to(Self::get_dashboard)),
+        );
+    }
+
+    /// 获取仪表盘汇总数据
+    ///
+    /// GET /api/v1/dashboard
+    async fn get_dashboard(
+        monitoring_service: Data<MonitoringService>,
+        control_service: Data<ControlService>,
+    ) -> ApiResult<HttpResponse> {
+        info!("获取仪表盘汇总数据");
+
+        let control = DashboardControlSummary::from(&control_service.get_status().await);
+        let dashboard = monitoring_service.get_dashboard(control).await;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(dashboard)))
+    }
+}
+
+/// 仪表盘汇总数据
+#[derive(Debug, Serialize)]
+pub struct DashboardSummary {
+    /// 生成时间
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    /// 最新温度读数
+    pub sensors: Vec<TemperatureReading>,
+    /// 活跃告警统计
+    pub alerts: DashboardAlertSummary,
+    /// 控制状态摘要
+    pub control: DashboardControlSummary,
+    /// 整体健康状况
+    pub health: DashboardHealthSummary,
+    /// 最近24小时的操作注释
+    pub annotations: Vec<Annotation>,
+}
+
+/// 活跃告警统计
+#[derive(Debug, Default, Serialize)]
+pub struct DashboardAlertSummary {
+    /// 活跃告警总数
+    pub total: usize,
+    /// 按严重程度统计（小写）
+    pub by_severity: HashMap<String, usize>,
+}
+
+/// 控制状态摘要
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DashboardControlSummary {
+    /// 是否启用自动控制
+    pub auto_control_enabled: bool,
+    /// 是否处于紧急模式
+    pub emergency_mode: bool,
+    /// 运行时长（秒）
+    pub uptime_seconds: Option<u64>,
+    /// 最后错误
+    pub last_error: Option<String>,
+}
+
+impl From<&ControlStatus> for DashboardControlSummary {
+    fn from(status: &ControlStatus) -> Self {
+        Self {
+            auto_control_enabled: status.auto_control_enabled,
+            emergency_mode: status.emergency_mode,
+            uptime_seconds: status.uptime_seconds,
+            last_error: status.last_error.clone(),
+        }
+    }
+}
+
+/// 整体健康状况
+#[derive(Debug, Serialize)]
+pub struct DashboardHealthSummary {
+    /// 健康分数（0-100）
+    pub score: f64,
+    /// 健康状态
+    pub status: SystemHealthStatus,
+    /// 识别的问题
+    pub issues: Vec<String>,
+}
@@ -13,6 +13,15 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{info, warn, error};
 
+/// 风扇写操作失败时的响应
+///
+/// 只读模式拒绝（403）与参数错误按错误本身的状态码返回，其余返回500
+fn mutation_error_response(e: &AppError) -> HttpResponse {
+    let status = actix_web::http::StatusCode::from_u16(e.status_code())
+        .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+    HttpResponse::build(status).json(ApiResponse::<()>::error(&e.to_string()))
+}
+
 /// 风扇控制器
 /// 
 /// 提供风扇控制和监控的HTTP API接口
@@ -114,7 +123,7 @@ impl FanController {
             Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::success(()))),
             Err(e) => {
                 error!("设置风扇转速失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
+                Ok(mutation_error_response(&e))
             }
         }
     }
@@ -132,7 +141,7 @@ impl FanController {
             Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::success(()))),
             Err(e) => {
                 error!("设置所有风扇转速失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
+                Ok(mutation_error_response(&e))
             }
         }
     }
@@ -405,7 +414,7 @@ impl FanController {
             Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::success(()))),
             Err(e) => {
                 error!("应用风扇曲线失败: {}", e);
-                Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&e.to_string())))
+                Ok(mutation_error_response(&e))
             }
         }
     }
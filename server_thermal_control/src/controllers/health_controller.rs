@@ -2,6 +2,12 @@ use crate::models::{
     error::{AppError, AppResult},
     api::ApiResponse,
 };
+use crate::services::{
+    control_service::ControlLoopLiveness,
+    health_probe::{HealthProbeService, ProbeResult},
+    ipmi_service::{BmcSelfTestResult, BmcSelfTestStatus, IpmiService},
+    AlertService, ControlService,
+};
 use actix_web::{
     web::{Query, Data, ServiceConfig},
     HttpResponse, Result as ActixResult,
@@ -111,20 +117,50 @@ impl HealthController {
 
     /// 存活检查
     /// 
+    /// 控制服务已注册时校验控制循环在两个控制周期内有执行，否则返回503
+    /// 
     /// GET /api/v1/health/liveness
-    async fn liveness_check() -> ActixResult<HttpResponse> {
+    async fn liveness_check(
+        control_service: Option<Data<ControlService>>,
+    ) -> ActixResult<HttpResponse> {
         info!("执行存活检查");
 
-        let is_alive = check_liveness().await;
-        
-        if is_alive {
-            Ok(HttpResponse::Ok().json(ApiResponse::success(LivenessStatus {
-                alive: true,
-                timestamp: Utc::now(),
-                process_id: std::process::id(),
-            })))
+        let mut checks = HashMap::new();
+        let control_loop = match control_service {
+            Some(service) => {
+                let started = std::time::Instant::now();
+                let liveness = service.check_loop_liveness().await;
+                checks.insert(
+                    "control_loop".to_string(),
+                    HealthCheck {
+                        name: "control_loop".to_string(),
+                        passed: liveness.is_healthy(),
+                        message: format!(
+                            "控制循环状态 {:?}，距上次执行 {:?} 秒",
+                            liveness.state, liveness.tick_age_seconds
+                        ),
+                        duration_ms: started.elapsed().as_millis() as u64,
+                        timestamp: Utc::now(),
+                    },
+                );
+                Some(liveness)
+            }
+            None => None,
+        };
+
+        let alive = checks.values().all(|check| check.passed);
+        let status = LivenessStatus {
+            alive,
+            timestamp: Utc::now(),
+            checks,
+            control_loop,
+        };
+
+        if alive {
+            Ok(HttpResponse::Ok().json(ApiResponse::success(status)))
         } else {
-            Ok(HttpResponse::ServiceUnavailable().json(ApiResponse::<()>::error("Service not alive")))
+            warn!("存活检查失败: 控制循环未按周期执行");
+            Ok(HttpResponse::ServiceUnavailable().json(ApiResponse::success(status)))
         }
     }
 
@@ -133,13 +169,18 @@ impl HealthController {
     /// GET /api/v1/health/components
     async fn component_health(
         Query(params): Query<ComponentHealthParams>,
+        ipmi_service: Option<Data<IpmiService>>,
+        alert_service: Option<Data<AlertService>>,
     ) -> ActixResult<HttpResponse> {
         info!("获取组件健康状态");
 
+        let ipmi_service = ipmi_service.as_deref();
+        let alert_service = alert_service.as_deref();
         let components = if let Some(component_name) = params.component {
             let health = match component_name.as_str() {
                 "database" => check_database_health().await,
                 "ipmi" => check_ipmi_health().await,
+                "bmc" => Self::check_bmc_health(ipmi_service, alert_service).await,
                 "sensors" => check_sensors_health().await,
                 "fans" => check_fans_health().await,
                 "thermal" => check_thermal_health().await,
@@ -155,6 +196,10 @@ impl HealthController {
             let mut components = HashMap::new();
             components.insert("database".to_string(), check_database_health().await);
             components.insert("ipmi".to_string(), check_ipmi_health().await);
+            components.insert(
+                "bmc".to_string(),
+                Self::check_bmc_health(ipmi_service, alert_service).await,
+            );
             components.insert("sensors".to_string(), check_sensors_health().await);
             components.insert("fans".to_string(), check_fans_health().await);
             components.insert("thermal".to_string(), check_thermal_health().await);
@@ -167,9 +212,16 @@ impl HealthController {
     /// 健康指标
     /// 
     /// GET /api/v1/health/metrics
-    async fn health_metrics() -> ActixResult<HttpResponse> {
+    async fn health_metrics(
+        control_service: Option<Data<ControlService>>,
+    ) -> ActixResult<HttpResponse> {
         info!("获取健康指标");
 
+        let control_loop_tick_age_seconds = match control_service {
+            Some(service) => service.check_loop_liveness().await.tick_age_seconds,
+            None => None,
+        };
+
         let metrics = HealthMetrics {
             cpu_usage: get_cpu_usage().await,
             memory_usage: get_memory_usage().await,
@@ -178,6 +230,7 @@ impl HealthController {
             active_connections: get_active_connections().await,
             response_times: get_response_times().await,
             error_rates: get_error_rates().await,
+            control_loop_tick_age_seconds,
             timestamp: Utc::now(),
         };
 
@@ -186,8 +239,12 @@ impl HealthController {
 
     /// 依赖健康状态
     /// 
+    /// 除内置依赖外，还包含 `health.probes` 中配置的HTTP探针
+    /// 
     /// GET /api/v1/health/dependencies
-    async fn dependency_health() -> ActixResult<HttpResponse> {
+    async fn dependency_health(
+        probe_service: Option<Data<HealthProbeService>>,
+    ) -> ActixResult<HttpResponse> {
         info!("检查依赖健康状态");
 
         let mut dependencies = HashMap::new();
@@ -197,16 +254,14 @@ impl HealthController {
         dependencies.insert("ipmi_service".to_string(), check_ipmi_dependency().await);
         dependencies.insert("monitoring_service".to_string(), check_monitoring_dependency().await);
 
-        let overall_status = if dependencies.values().all(|d| d.status == DependencyStatus::Available) {
-            DependencyStatus::Available
-        } else if dependencies.values().any(|d| d.status == DependencyStatus::Critical) {
-            DependencyStatus::Critical
-        } else {
-            DependencyStatus::Degraded
-        };
+        if let Some(probe_service) = probe_service {
+            for result in probe_service.check_all().await {
+                dependencies.insert(result.name.clone(), Self::probe_dependency_health(result));
+            }
+        }
 
         let dependency_health = DependencyHealthResponse {
-            overall_status,
+            overall_status: Self::overall_dependency_status(&dependencies),
             dependencies,
             timestamp: Utc::now(),
         };
@@ -269,6 +324,99 @@ impl HealthController {
         }
     }
 
+    /// 检查BMC自检状态
+    ///
+    /// 自检失败时产生严重告警，避免继续信任异常BMC上报的传感器数据
+    async fn check_bmc_health(
+        ipmi_service: Option<&IpmiService>,
+        alert_service: Option<&AlertService>,
+    ) -> ComponentHealth {
+        let Some(ipmi_service) = ipmi_service else {
+            return ComponentHealth {
+                status: ComponentStatus::Warning,
+                message: "IPMI service unavailable, BMC self-test not checked".to_string(),
+                last_check: Utc::now(),
+                details: HashMap::new(),
+            };
+        };
+
+        let status = match ipmi_service.get_self_test_status().map_err(|e| e.to_string()) {
+            Ok(status) => status,
+            Err(e) => {
+                return ComponentHealth {
+                    status: ComponentStatus::Error,
+                    message: format!("Failed to read BMC self-test status: {}", e),
+                    last_check: Utc::now(),
+                    details: HashMap::new(),
+                };
+            }
+        };
+
+        if let Some(alert_service) = alert_service {
+            if let Err(e) = alert_service.raise_bmc_self_test_alert(&status).await {
+                warn!("创建BMC自检告警失败: {}", e);
+            }
+        }
+
+        Self::bmc_component_health(&status)
+    }
+
+    /// HTTP探针结果转换为依赖健康状态
+    fn probe_dependency_health(result: ProbeResult) -> DependencyHealth {
+        DependencyHealth {
+            name: result.name,
+            status: if result.available {
+                DependencyStatus::Available
+            } else {
+                DependencyStatus::Unavailable
+            },
+            endpoint: result.url,
+            response_time_ms: result.response_time_ms,
+            last_check: result.checked_at,
+            error_message: result.error_message,
+        }
+    }
+
+    /// 汇总依赖状态：全部可用为可用，全部不可用为不可用，其余为降级
+    fn overall_dependency_status(dependencies: &HashMap<String, DependencyHealth>) -> DependencyStatus {
+        if dependencies.values().all(|d| d.status == DependencyStatus::Available) {
+            DependencyStatus::Available
+        } else if dependencies.values().all(|d| d.status == DependencyStatus::Unavailable) {
+            DependencyStatus::Unavailable
+        } else {
+            DependencyStatus::Degraded
+        }
+    }
+
+    /// 将BMC自检状态映射为组件健康状态
+    fn bmc_component_health(status: &BmcSelfTestStatus) -> ComponentHealth {
+        let (component_status, message) = match status.result {
+            BmcSelfTestResult::Passed => (ComponentStatus::Healthy, "BMC self-test passed".to_string()),
+            BmcSelfTestResult::NotImplemented => (
+                ComponentStatus::Warning,
+                "BMC does not implement self-test".to_string(),
+            ),
+            BmcSelfTestResult::Failed => (
+                ComponentStatus::Critical,
+                format!("BMC self-test failed: {}", status.summary),
+            ),
+        };
+
+        let mut details = HashMap::new();
+        details.insert("selftest".to_string(), serde_json::json!(status.summary));
+        details.insert(
+            "failed_components".to_string(),
+            serde_json::json!(status.failed_components),
+        );
+
+        ComponentHealth {
+            status: component_status,
+            message,
+            last_check: status.timestamp,
+            details,
+        }
+    }
+
     /// 检查温度传感器健康状态
     async fn check_thermal_sensors_health() -> ComponentHealth {
         // 实现温度传感器健康检查逻辑
@@ -847,6 +995,8 @@ pub struct LivenessStatus {
     pub timestamp: DateTime<Utc>,
     /// 检查项
     pub checks: HashMap<String, HealthCheck>,
+    /// 控制循环存活状态
+    pub control_loop: Option<ControlLoopLiveness>,
 }
 
 /// 健康检查项
@@ -902,6 +1052,8 @@ pub struct HealthMetrics {
     pub error_count: u64,
     /// 平均响应时间（毫秒）
     pub response_time_ms: f64,
+    /// 距控制循环上次执行的秒数（控制服务未注册或从未执行时为空）
+    pub control_loop_tick_age_seconds: Option<i64>,
 }
 
 /// 依赖健康状态
@@ -1045,4 +1197,18 @@ mod tests {
         let json = serde_json::to_string(&status).unwrap();
         assert_eq!(json, r#""Warning""#);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_failed_bmc_self_test_is_critical_component() {
+        let status = BmcSelfTestStatus::parse("Selftest: Fatal hardware error\n");
+        let health = HealthController::bmc_component_health(&status);
+        assert_eq!(health.status, ComponentStatus::Critical);
+        assert!(health.message.contains("Fatal hardware error"));
+
+        let passed = BmcSelfTestStatus::parse("Selftest: passed\n");
+        assert_eq!(
+            HealthController::bmc_component_health(&passed).status,
+            ComponentStatus::Healthy
+        );
+    }
+}
@@ -11,6 +11,7 @@
 // pub mod alert_controller;
 // pub mod config_controller;
 // pub mod health_controller;
+// pub mod dashboard_controller;
 
 // 重新导出常用类型
 // pub use thermal_controller::ThermalController;
@@ -22,5 +23,6 @@
 // pub use alert_controller::AlertController;
 // pub use config_controller::ConfigController;
 // pub use health_controller::HealthController;
+// pub use dashboard_controller::DashboardController;
 
 mod test;
\ No newline at end of file
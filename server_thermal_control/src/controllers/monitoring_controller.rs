@@ -1,14 +1,18 @@
+use crate::config::AppConfig;
 use crate::models::{
     api::{ApiResponse, PaginationParams, TimeRangeParams},
     config::MonitoringConfig,
-    error::{AppError, AppResult},
+    error::{ApiResult, AppError, AppResult},
     monitoring::*,
 };
 use crate::services::MonitoringService;
+use crate::utils::negotiation::negotiated_response;
+use crate::utils::ring_buffer::RecentReadings;
 use actix_web::{
     delete, get, post, put,
+    http::StatusCode,
     web::{Data, Path, Query, ServiceConfig},
-    HttpResponse, Result as ActixResult,
+    HttpRequest, HttpResponse, Result as ActixResult,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -77,7 +81,8 @@ impl MonitoringController {
                 .route(
                     "/collect",
                     actix_web::web::post().to(Self::trigger_data_collection),
-                ),
+                )
+                .route("/poll", actix_web::web::post().to(Self::force_poll)),
         );
     }
 
@@ -159,9 +164,13 @@ impl MonitoringController {
 
     /// 获取实时监控数据
     ///
+    /// 请求头 `Accept: application/msgpack` 时以MessagePack编码响应
+    ///
     /// GET /api/v1/monitoring/data/realtime
     async fn get_realtime_data(
+        req: HttpRequest,
         service: Data<Arc<MonitoringService>>,
+        config: Option<Data<AppConfig>>,
         params: Query<RealtimeDataParams>,
     ) -> ActixResult<HttpResponse> {
         info!("获取实时监控数据");
@@ -176,7 +185,10 @@ impl MonitoringController {
             )
             .await
         {
-            Ok(data) => Ok(HttpResponse::Ok().json(ApiResponse::success(data))),
+            Ok(data) => {
+                let msgpack_enabled = config.map_or(true, |config| config.server.msgpack_enabled);
+                Ok(negotiated_response(&req, msgpack_enabled, StatusCode::OK, &ApiResponse::success(data))?)
+            }
             Err(e) => Ok(HttpResponse::InternalServerError().json(format!("Error: {}", e))),
         }
     }
@@ -186,22 +198,12 @@ impl MonitoringController {
     /// GET /api/v1/monitoring/data/history
     async fn get_historical_data(
         service: Data<Arc<MonitoringService>>,
-        params: Query<HistoricalDataParams>,
-    ) -> ActixResult<HttpResponse> {
+        Query(params): Query<HistoricalDataParams>,
+    ) -> ApiResult<HttpResponse> {
         info!("获取历史监控数据");
 
-        match service
-            .get_historical_data(
-                params.start_time,
-                params.end_time,
-                params.data_types.as_deref(),
-                params.limit,
-            )
-            .await
-        {
-            Ok(data) => Ok(HttpResponse::Ok().json(ApiResponse::success(data))),
-            Err(e) => Ok(HttpResponse::InternalServerError().json(format!("Error: {}", e))),
-        }
+        let data = service.query_historical_data(params).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(data)))
     }
 
     /// 获取监控指标
@@ -299,6 +301,16 @@ impl MonitoringController {
         }
     }
 
+    /// 立即执行一轮轮询并返回最新数据
+    ///
+    /// POST /api/v1/monitoring/poll
+    async fn force_poll(service: Data<Arc<MonitoringService>>) -> ActixResult<HttpResponse> {
+        info!("强制轮询监控数据");
+
+        let data = service.force_poll().await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(data)))
+    }
+
     /// 获取监控告警
     ///
     /// GET /api/v1/monitoring/alerts
@@ -394,8 +406,10 @@ pub struct HistoricalDataParams {
     pub start_time: Option<chrono::DateTime<chrono::Utc>>,
     /// 结束时间
     pub end_time: Option<chrono::DateTime<chrono::Utc>>,
-    /// 数据类型
-    pub data_types: Option<Vec<String>>,
+    /// 数据类型（默认全部）
+    pub data_type: Option<MonitoringDataType>,
+    /// 降采样分辨率，如 "30s"、"5m"、"1h"
+    pub resolution: Option<String>,
     /// 返回记录数限制
     pub limit: Option<usize>,
 }
@@ -462,6 +476,47 @@ pub struct MonitoringDataCache {
     pub latest_system_health: Option<SystemHealth>,
     /// 活跃告警
     pub active_alerts: Option<Vec<MonitoringAlert>>,
+    /// 最近一次更新时间
+    pub last_update: chrono::DateTime<chrono::Utc>,
+    /// 各温度传感器的最近读数，供推送和仪表盘使用
+    pub recent_temperature: RecentReadings<crate::models::thermal::TemperatureReading>,
+    /// 各风扇的最近读数
+    pub recent_fan: RecentReadings<crate::models::fan::FanStatus>,
+}
+
+impl MonitoringDataCache {
+    /// 更新最新温度数据，并追加到各传感器的最近读数
+    ///
+    /// # 参数
+    /// * `readings` - 本周期的温度读数
+    pub fn update_temperature(&mut self, readings: Vec<crate::models::thermal::TemperatureReading>) {
+        for reading in &readings {
+            self.recent_temperature.push(&reading.sensor_id, reading.clone());
+        }
+        self.latest_temperature_data = Some(readings);
+        self.last_update = chrono::Utc::now();
+    }
+
+    /// 更新最新风扇数据，并追加到各风扇的最近读数
+    ///
+    /// # 参数
+    /// * `readings` - 本周期的风扇读数
+    pub fn update_fans(&mut self, readings: Vec<crate::models::fan::FanStatus>) {
+        for reading in &readings {
+            self.recent_fan.push(&reading.fan_id, reading.clone());
+        }
+        self.latest_fan_data = Some(readings);
+        self.last_update = chrono::Utc::now();
+    }
+
+    /// 修改每个组件保留的最近读数条数
+    ///
+    /// # 参数
+    /// * `capacity` - 条数
+    pub fn set_recent_capacity(&mut self, capacity: usize) {
+        self.recent_temperature.set_capacity(capacity);
+        self.recent_fan.set_capacity(capacity);
+    }
 }
 
 /// 系统健康状态
@@ -489,7 +544,8 @@ pub enum SystemHealthStatus {
 }
 
 /// 监控数据类型
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MonitoringDataType {
     /// 温度
     Temperature,
@@ -502,7 +558,7 @@ pub enum MonitoringDataType {
 }
 
 /// 监控数据点
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MonitoringDataPoint {
     /// 时间戳
     pub timestamp: chrono::DateTime<chrono::Utc>,
@@ -750,6 +806,8 @@ pub struct SystemHealthReport {
     pub component_health: std::collections::HashMap<String, ComponentHealth>,
     /// 识别的问题
     pub identified_issues: Vec<HealthIssue>,
+    /// 健康分数明细：每个组件扣了多少分、触发的规则及处理建议
+    pub score_breakdown: crate::services::health_breakdown::HealthBreakdown,
     /// 建议
     pub recommendations: Vec<String>,
     /// 生成时间
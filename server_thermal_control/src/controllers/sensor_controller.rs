@@ -1,5 +1,5 @@
 use crate::models::{
-    error::{AppError, AppResult},
+    error::{ApiResult, AppError, AppResult},
     sensor::*,
     api::{ApiResponse, PaginationParams, TimeRangeParams},
 };
@@ -35,6 +35,9 @@ impl SensorController {
         cfg.service(
             actix_web::web::scope("/sensors")
                 .route("", actix_web::web::get().to(Self::get_all_sensors))
+                .route("/calibration", actix_web::web::get().to(Self::get_sensor_calibrations))
+                .route("/calibration/{sensor_id}", actix_web::web::put().to(Self::set_sensor_calibration))
+                .route("/calibration/{sensor_id}", actix_web::web::delete().to(Self::delete_sensor_calibration))
                 .route("/{sensor_id}", actix_web::web::get().to(Self::get_sensor_data))
                 .route("/type/{sensor_type}", actix_web::web::get().to(Self::get_sensors_by_type))
                 .route("/history", actix_web::web::get().to(Self::get_sensor_history))
@@ -214,6 +217,47 @@ impl SensorController {
         }
     }
 
+    /// 获取传感器校准参数
+    /// 
+    /// GET /api/v1/sensors/calibration
+    async fn get_sensor_calibrations(service: Data<SensorService>) -> ApiResult<HttpResponse> {
+        let calibrations = service.get_calibrations().await;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(calibrations)))
+    }
+
+    /// 设置传感器校准参数
+    /// 
+    /// PUT /api/v1/sensors/calibration/{sensor_id}
+    async fn set_sensor_calibration(
+        service: Data<SensorService>,
+        sensor_id: Path<String>,
+        request: actix_web::web::Json<SetSensorCalibrationRequest>,
+    ) -> ApiResult<HttpResponse> {
+        info!("设置传感器校准参数: {} 偏移 {:+.2}", sensor_id, request.offset);
+
+        let request = request.into_inner();
+        let calibration = service
+            .set_calibration(
+                &sensor_id,
+                SensorCalibration::new(request.offset, request.scale_factor, request.reference_value),
+            )
+            .await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(calibration)))
+    }
+
+    /// 删除传感器校准参数
+    /// 
+    /// DELETE /api/v1/sensors/calibration/{sensor_id}
+    async fn delete_sensor_calibration(
+        service: Data<SensorService>,
+        sensor_id: Path<String>,
+    ) -> ApiResult<HttpResponse> {
+        info!("删除传感器校准参数: {}", sensor_id);
+
+        service.remove_calibration(&sensor_id).await?;
+        Ok(HttpResponse::Ok().json(ApiResponse::success(())))
+    }
+
     /// 检测传感器异常
     /// 
     /// GET /api/v1/sensors/anomalies
@@ -463,6 +507,17 @@ pub struct CalibrateSensorRequest {
     pub calibration_value: f64,
 }
 
+/// 设置传感器校准参数请求
+#[derive(Debug, Deserialize)]
+pub struct SetSensorCalibrationRequest {
+    /// 偏移量（°C）
+    pub offset: f64,
+    /// 缩放因子，默认1.0
+    pub scale_factor: Option<f64>,
+    /// 参考探头读数
+    pub reference_value: Option<f64>,
+}
+
 /// 设置传感器配置请求
 #[derive(Debug, Deserialize)]
 pub struct SetSensorConfigurationRequest {
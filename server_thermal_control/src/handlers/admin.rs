@@ -0,0 +1,38 @@
+use crate::middleware::auth::require_api_key;
+use crate::models::api::ApiResponse;
+use crate::models::error::{ApiResult, AppError};
+use crate::AppState;
+use actix_web::{web, HttpRequest, HttpResponse};
+
+/// 立即清理超过保留期限的历史数据（需要API密钥）
+///
+/// POST /api/v1/admin/retention/run
+pub async fn run_retention(req: HttpRequest, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let service = data
+        .retention_service
+        .as_ref()
+        .ok_or_else(|| AppError::ServiceUnavailableError {
+            service: "数据库".to_string(),
+        })?;
+    let report = service.run().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(report)))
+}
+
+/// 轮换JWT签名密钥（需要API密钥）
+///
+/// 生成新的签名密钥并返回，旧密钥签发的令牌在 `security.jwt_rotation_grace_secs` 内仍然有效
+///
+/// POST /api/v1/admin/rotate-jwt-secret
+pub async fn rotate_jwt_secret(req: HttpRequest, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let rotation = data.jwt_secrets.rotate().await?;
+    tracing::info!(
+        target: "audit",
+        "JWT签名密钥已轮换，旧密钥有效至 {}",
+        rotation.previous_valid_until.to_rfc3339()
+    );
+    Ok(HttpResponse::Ok().json(ApiResponse::success(rotation)))
+}
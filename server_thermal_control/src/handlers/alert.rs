@@ -1,69 +1,1307 @@
+use crate::config::AppConfig;
+use crate::middleware::auth::jwt_subject;
+use crate::models::error::{ApiResult, AppError, AppResult};
+use crate::middleware::auth::require_api_key;
 use crate::models::AlertStatus;
+use crate::services::alert_monitor::AlertMonitor;
+use crate::services::alert_notifier;
+use crate::services::alert_preview;
+use crate::services::alert_rules::{self, AlertRuleStore};
+use crate::services::alert_store::AlertStore;
+use crate::services::channel_validation;
+use crate::services::event_broadcast::BroadcastItem;
+use crate::services::incident_store::IncidentStore;
+use crate::services::ipmi_service::TemperatureSensor;
+use crate::services::jwt_secrets::JwtSecrets;
+use crate::services::alert_thresholds::{SensorAlertThreshold, SensorSelector};
 use crate::{models, AppState};
-use actix_web::{web, HttpResponse, Result};
-use chrono::Utc;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use serde_json::json;
 use uuid;
 
+/// 告警列表查询参数
+#[derive(Debug, Deserialize)]
+pub struct AlertListQuery {
+    /// 按状态过滤：`active`、`acknowledged`、`resolved`
+    pub status: Option<String>,
+    /// 页码，从1开始
+    pub page: Option<u32>,
+    /// 每页条数
+    pub limit: Option<u32>,
+}
+
 /// 获取告警列表
+///
+/// 返回内存中保留的最近告警，最近的在前
+///
+/// GET /alerts
 pub async fn list_alerts(
-    _query: web::Query<models::PaginationParams>,
-    _data: web::Data<AppState>,
-) -> Result<HttpResponse> {
-    // TODO: 从数据库获取真实的告警数据
-    let alerts = vec![
-        models::Alert {
-            id: uuid::Uuid::new_v4(),
-            alert_type: "temperature".to_string(),
-            severity: "warning".to_string(),
-            title: "High Temperature Alert".to_string(),
-            message: "CPU temperature exceeded 80°C".to_string(),
-            source: "CPU_TEMP_1".to_string(),
-            source_id: "sensor_001".to_string(),
-            status: AlertStatus::Ignored,
-            acknowledged: false,
-            acknowledged_by: None,
-            acknowledged_at: None,
-            resolved_at: None,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-        },
-    ];
-
-    let response = models::PaginatedResponse {
-        data: alerts,
-        total: 1,
-        page: 1,
-        limit: 10,
-        total_pages: 1,
+    query: web::Query<AlertListQuery>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let status = match query.status.as_deref().map(str::to_ascii_lowercase).as_deref() {
+        None => None,
+        Some("active") => Some(AlertStatus::Triggered),
+        Some("acknowledged") => Some(AlertStatus::Acknowledged),
+        Some("resolved") => Some(AlertStatus::Resolved),
+        Some(other) => return Err(AppError::validation_error("status", format!("未知的告警状态: {}", other)).into()),
     };
+    let alerts: Vec<models::Alert> = data
+        .alerts
+        .list()
+        .into_iter()
+        .filter(|alert| {
+            status
+                .as_ref()
+                .is_none_or(|status| std::mem::discriminant(status) == std::mem::discriminant(&alert.status))
+        })
+        .collect();
+
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(50).clamp(1, 1000);
+    let total = alerts.len() as u64;
+    let alerts = alerts
+        .into_iter()
+        .skip(((page - 1) * limit) as usize)
+        .take(limit as usize)
+        .collect();
 
     Ok(HttpResponse::Ok().json(models::ApiResponse::success(
-        response,
+        models::PaginatedResponse::new(alerts, total, page, limit),
         "Alerts retrieved successfully"
     )))
 }
 
+/// 获取单个告警
+///
+/// 先查内存中的最近告警，已淘汰的告警从告警历史中读取
+///
+/// GET /alerts/{alert_id}
+pub async fn get_alert(path: web::Path<uuid::Uuid>, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let alert = find_alert(&data, path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        alert,
+        "Alert retrieved successfully"
+    )))
+}
+
+/// 获取触发告警的规则的当前定义
+///
+/// 非规则触发的告警或规则已删除时返回404
+///
+/// GET /alerts/{alert_id}/rule
+pub async fn get_alert_rule_for_alert(
+    path: web::Path<uuid::Uuid>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let alert = find_alert(&data, path.into_inner()).await?;
+    let rule_id = alert
+        .rule_id
+        .ok_or_else(|| AppError::not_found_error("告警规则", format!("告警 {} 不是由规则触发的", alert.id)))?;
+    let rule = data.alert_rules.get(&rule_id)?;
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({ "alert_id": alert.id, "rule_id": rule_id, "rule": rule }),
+        "Alert rule retrieved successfully"
+    )))
+}
+
+/// 按ID查找告警，先查内存中的最近告警，已淘汰的告警从告警历史中读取
+async fn find_alert(data: &AppState, alert_id: uuid::Uuid) -> ApiResult<models::Alert> {
+    match data.alerts.get(alert_id) {
+        Ok(alert) => Ok(alert),
+        Err(_) => Ok(data
+            .alert_history
+            .get(&alert_id.to_string())
+            .await?
+            .ok_or_else(|| AppError::not_found_error("告警", alert_id.to_string()))?),
+    }
+}
+
 /// 确认告警
+///
+/// POST /alerts/{alert_id}/acknowledge
 pub async fn acknowledge_alert(
+    req: HttpRequest,
     path: web::Path<uuid::Uuid>,
     ack_data: web::Json<serde_json::Value>,
-    _data: web::Data<AppState>,
-) -> Result<HttpResponse> {
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
     let alert_id = path.into_inner();
-    let acknowledged_by = ack_data.get("acknowledged_by")
-        .and_then(|v| v.as_str())
-        .unwrap_or("system");
-    
-    // TODO: 在数据库中更新告警状态
-    tracing::info!("Acknowledging alert {} by {}", alert_id, acknowledged_by);
+    let acknowledged_by = request_actor(
+        &req,
+        &data.config,
+        &data.jwt_secrets,
+        ack_data.get("acknowledged_by").and_then(|v| v.as_str()),
+        "acknowledged_by",
+    )?;
+
+    let alert = data.alerts.acknowledge(alert_id, &acknowledged_by)?;
+    data.alert_history.upsert(&alert).await?;
+    tracing::info!(target: "audit", "告警已确认: alert_id={}, 操作人={}", alert_id, acknowledged_by);
 
     Ok(HttpResponse::Ok().json(models::ApiResponse::success(
         json!({
             "alert_id": alert_id,
+            "status": "acknowledged",
             "acknowledged_by": acknowledged_by,
-            "acknowledged_at": Utc::now().to_rfc3339()
+            "acknowledged_at": alert.acknowledged_at,
+            "note": ack_data.get("note"),
         }),
         "Alert acknowledged successfully"
     )))
-}
\ No newline at end of file
+}
+
+/// 解决告警
+///
+/// POST /alerts/{alert_id}/resolve
+pub async fn resolve_alert(
+    req: HttpRequest,
+    path: web::Path<uuid::Uuid>,
+    resolve_data: web::Json<serde_json::Value>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let alert_id = path.into_inner();
+    let resolved_by = request_actor(
+        &req,
+        &data.config,
+        &data.jwt_secrets,
+        resolve_data.get("resolved_by").and_then(|v| v.as_str()),
+        "resolved_by",
+    )?;
+
+    let alert = data.alerts.resolve(alert_id)?;
+    data.alert_history.upsert(&alert).await?;
+    tracing::info!(target: "audit", "告警已解决: alert_id={}, 操作人={}", alert_id, resolved_by);
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({
+            "alert_id": alert_id,
+            "status": "resolved",
+            "resolved_by": resolved_by,
+            "resolved_at": alert.resolved_at,
+            "resolution_note": resolve_data.get("resolution_note"),
+        }),
+        "Alert resolved successfully"
+    )))
+}
+
+/// 告警统计查询参数
+#[derive(Debug, Deserialize)]
+pub struct AlertStatisticsQuery {
+    /// 滚动窗口长度（小时），默认24
+    pub time_window_hours: Option<u32>,
+}
+
+/// 获取告警统计
+///
+/// 返回跨重启保留的累计统计和内存中最近告警的滚动窗口统计
+///
+/// GET /api/v1/alerts/statistics
+pub async fn get_alert_statistics(
+    query: web::Query<AlertStatisticsQuery>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let statistics = data.alerts.statistics(query.time_window_hours.unwrap_or(24))?;
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        statistics,
+        "Alert statistics retrieved successfully"
+    )))
+}
+
+/// 重置告警累计统计请求
+#[derive(Debug, Default, Deserialize)]
+pub struct ResetAlertStatisticsRequest {
+    /// 操作人，请求携带有效JWT时取JWT的 `sub`
+    pub reset_by: Option<String>,
+    /// 重置原因
+    pub reason: Option<String>,
+}
+
+/// 重置告警累计统计（需要API密钥）
+///
+/// 只清零累计计数并立即保存，内存中的告警和滚动窗口统计不受影响
+///
+/// POST /api/v1/alerts/statistics/reset
+pub async fn reset_alert_statistics(
+    req: HttpRequest,
+    body: web::Json<ResetAlertStatisticsRequest>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+    let body = body.into_inner();
+    let reset_by = request_actor(&req, &data.config, &data.jwt_secrets, body.reset_by.as_deref(), "reset_by")?;
+
+    let record = data.alerts.reset_statistics(Some(reset_by), body.reason);
+    data.alert_statistics_store.save(&data.alerts.statistics_snapshot()).await?;
+    tracing::warn!(
+        target: "audit",
+        "告警累计统计已重置: 操作人={}, 原因={}, 重置前总数={}",
+        record.reset_by.as_deref().unwrap_or("unknown"),
+        record.reason.as_deref().unwrap_or("-"),
+        record.previous.total_alerts
+    );
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        record,
+        "Alert statistics reset successfully"
+    )))
+}
+
+/// 格式化一条SSE事件
+///
+/// # 参数
+/// * `event` - 事件名
+/// * `data` - 事件数据，序列化为单行JSON
+fn sse_frame(event: &str, data: &impl serde::Serialize) -> web::Bytes {
+    let data = serde_json::to_string(data).unwrap_or_else(|_| "null".to_string());
+    web::Bytes::from(format!("event: {}\ndata: {}\n\n", event, data))
+}
+
+/// 以Server-Sent Events推送告警状态变化
+///
+/// 订阅者读取过慢时丢弃其最旧的事件并推送一条 `lagged` 事件，不阻塞告警处理
+///
+/// GET /api/v1/alerts/stream
+pub async fn stream_alerts(data: web::Data<AppState>) -> HttpResponse {
+    let subscriber = data.alerts.subscribe();
+    let stream = futures::stream::unfold(subscriber, |mut subscriber| async move {
+        let frame = match subscriber.recv().await? {
+            BroadcastItem::Event(event) => sse_frame(event.transition.as_str(), &event),
+            BroadcastItem::Lagged(dropped) => sse_frame("lagged", &json!({ "dropped": dropped })),
+        };
+        Some((Ok::<_, actix_web::Error>(frame), subscriber))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((actix_web::http::header::CACHE_CONTROL, "no-cache"))
+        .streaming(stream)
+}
+
+/// 批量设置告警阈值请求体
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkThresholdRequest {
+    /// 传感器ID通配符，如 `CPU*`
+    pub pattern: Option<String>,
+    /// 归一化位置，如 `cpu`、`inlet`
+    pub location: Option<String>,
+    /// 警告阈值（°C）
+    pub warning: f64,
+    /// 严重阈值（°C）
+    pub critical: f64,
+}
+
+impl BulkThresholdRequest {
+    /// 解析传感器选择方式，`pattern` 与 `location` 必须且只能提供一个
+    fn selector(&self) -> AppResult<SensorSelector> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        match (non_empty(&self.pattern), non_empty(&self.location)) {
+            (Some(pattern), None) => Ok(SensorSelector::Pattern(pattern)),
+            (None, Some(location)) => Ok(SensorSelector::Location(location)),
+            _ => Err(AppError::validation_error("pattern", "pattern 与 location 必须且只能提供一个")),
+        }
+    }
+}
+
+/// 为匹配的传感器批量设置告警阈值（需要API密钥）
+///
+/// 所有匹配的传感器在一次写入中同时更新，返回受影响的传感器数量
+///
+/// PUT /api/v1/alerts/thresholds/bulk
+pub async fn bulk_apply_thresholds(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<BulkThresholdRequest>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let selector = body.selector()?;
+    let threshold = SensorAlertThreshold {
+        warning: body.warning,
+        critical: body.critical,
+    };
+    let sensors = data
+        .ipmi_service
+        .get_temperature_sensors()
+        .map_err(|e| AppError::ipmi_error(format!("读取温度传感器失败: {}", e)))?;
+
+    let sensor_ids = data.alert_thresholds.bulk_apply(&sensors, &selector, threshold)?;
+    tracing::info!(target: "audit", "批量设置告警阈值: {:?}, 传感器={:?}", selector, sensor_ids);
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({
+            "affected": sensor_ids.len(),
+            "sensor_ids": sensor_ids,
+            "threshold": threshold,
+        }),
+        "Alert thresholds applied successfully"
+    )))
+}
+
+/// 获取学习得到的告警阈值建议
+///
+/// 学习样本不足时按温度分布直方图统计窗口的p95/p99给出建议；建议不会自动生效，需通过阈值接口应用
+///
+/// GET /api/v1/alerts/thresholds/suggest
+pub async fn suggest_thresholds(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let learner = &data.threshold_learner;
+    let config = learner.config();
+    let recent = data.temperature_histograms.histograms(Utc::now(), None)?;
+    let suggestions = learner.suggest(&recent)?;
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({
+            "enabled": config.enabled,
+            "learning": learner.is_learning(Utc::now()),
+            "started_at": learner.started_at(),
+            "learning_until": learner.learning_until(),
+            "warning_percentile": config.warning_percentile,
+            "critical_percentile": config.critical_percentile,
+            "warning_margin": config.warning_margin,
+            "critical_margin": config.critical_margin,
+            "min_samples": config.min_samples,
+            "recent_window_secs": data.temperature_histograms.config().window_secs,
+            "suggestions": suggestions,
+        }),
+        "Threshold suggestions retrieved successfully"
+    )))
+}
+
+/// 按当前读数预览各传感器和风扇的告警级别，不创建告警
+///
+/// 温度使用按传感器设置的阈值，未设置时警告阈值取 `monitoring.alert_threshold_temp`、
+/// 严重阈值取 `control.emergency_exit.critical_temperature`；BMC读取失败时使用最后一次成功的温度读数。
+/// 风扇读取失败时风扇列表为空并在 `unavailable` 中列出
+///
+/// GET /api/v1/alerts/preview
+pub async fn preview_alerts(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let now = Utc::now();
+    let readings = data
+        .sensor_cache
+        .read_through(now, || data.ipmi_service.get_temperature_sensors().map_err(|e| e.to_string()))
+        .map_err(|e| AppError::ipmi_error(format!("读取温度传感器失败: {}", e)))?;
+
+    let mut unavailable = Vec::new();
+    let fans = data.ipmi_service.get_fan_sensors().unwrap_or_else(|e| {
+        tracing::warn!("告警预览读取风扇失败: {}", e);
+        unavailable.push("fans");
+        Vec::new()
+    });
+
+    let default_threshold = SensorAlertThreshold {
+        warning: data.config.monitoring.alert_threshold_temp,
+        critical: data.config.control.emergency_exit.critical_temperature,
+    };
+    let preview = alert_preview::preview_alerts(
+        &readings,
+        &fans,
+        &data.alert_thresholds,
+        default_threshold,
+        data.config.monitoring.alert_threshold_fan,
+        &data.sensor_blacklist,
+    );
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({
+            "generated_at": now,
+            "sensors": preview.sensors,
+            "fans": preview.fans,
+            "summary": preview.summary,
+            "unavailable": unavailable,
+        }),
+        "Alert preview generated successfully"
+    )))
+}
+
+/// 获取告警规则列表
+///
+/// GET /api/v1/alerts/rules
+pub async fn list_alert_rules(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        data.alert_rules.all(),
+        "Alert rules retrieved successfully"
+    )))
+}
+
+/// 部分更新告警规则（需要API密钥）
+///
+/// 只修改请求体中提交的字段，合并后的规则整体校验，校验失败时规则不变
+///
+/// PATCH /api/v1/alerts/rules/{rule_id}
+pub async fn patch_alert_rule(
+    req: HttpRequest,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+    body: web::Json<models::alert::AlertRulePatch>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let rule_id = path.into_inner();
+    let rule = data.alert_rules.patch(&rule_id, &body)?;
+    if body.enabled.is_some() {
+        data.alert_rule_states.set_enabled(&rule_id, rule.enabled).await?;
+    }
+    tracing::info!(target: "audit", "告警规则已部分更新: {}, 修改={:?}", rule_id, body.into_inner());
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        rule,
+        "Alert rule updated successfully"
+    )))
+}
+
+/// 翻转告警规则的启用状态（需要API密钥）
+///
+/// 状态写入规则状态存储，重启后保持；下一轮评估即生效
+///
+/// POST /api/v1/alerts/rules/{rule_id}/toggle
+pub async fn toggle_alert_rule(
+    req: HttpRequest,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let rule_id = path.into_inner();
+    let rule = data.alert_rules.toggle(&rule_id)?;
+    data.alert_rule_states.set_enabled(&rule_id, rule.enabled).await?;
+    tracing::info!(target: "audit", "告警规则已{}: {}", if rule.enabled { "启用" } else { "停用" }, rule_id);
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        rule,
+        "Alert rule toggled successfully"
+    )))
+}
+
+/// 仍然有效的告警静默，按到期时间排序
+///
+/// GET /api/v1/alerts/mutes
+pub async fn list_alert_mutes(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        data.alerts.mutes(Utc::now()),
+        "Alert mutes retrieved successfully"
+    )))
+}
+
+/// 添加告警静默（需要API密钥）
+///
+/// 到期前匹配的告警以 `Ignored` 状态记入告警历史，不作为活跃告警、不推送、不计入统计
+///
+/// POST /api/v1/alerts/mutes
+pub async fn create_alert_mute(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<models::alert::CreateAlertMuteRequest>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let mute = data
+        .alerts
+        .add_mute(models::alert::AlertMute::new(body.into_inner(), Utc::now())?);
+    tracing::info!(
+        target: "audit",
+        "添加告警静默 {}: source={:?}, rule_id={:?}, 到期 {}",
+        mute.id,
+        mute.source,
+        mute.rule_id,
+        mute.expires_at.to_rfc3339()
+    );
+
+    Ok(HttpResponse::Created().json(models::ApiResponse::success(mute, "Alert mute created successfully")))
+}
+
+/// 提前解除告警静默（需要API密钥）
+///
+/// DELETE /api/v1/alerts/mutes/{mute_id}
+pub async fn delete_alert_mute(
+    req: HttpRequest,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let mute = data.alerts.remove_mute(&path.into_inner(), Utc::now())?;
+    tracing::info!(target: "audit", "解除告警静默 {}，静默期间抑制告警 {} 条", mute.id, mute.suppressed_count);
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(mute, "Alert mute deleted successfully")))
+}
+
+/// 立即评估告警规则（需要API密钥）
+///
+/// 不等待下一个监控周期，用各传感器、风扇最近一次读数评估全部启用的规则；温度读取失败时使用最后一次成功读数。
+/// 持续时间与后台监控共用计时，已满足持续时间的规则创建真实告警
+///
+/// POST /api/v1/alerts/evaluate
+pub async fn evaluate_alert_rules(req: HttpRequest, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let now = Utc::now();
+    let temperatures: Vec<_> = data
+        .sensor_cache
+        .read_through(now, || data.ipmi_service.get_temperature_sensors().map_err(|e| e.to_string()))
+        .map_err(|e| AppError::ipmi_error(format!("读取温度传感器失败: {}", e)))?
+        .into_iter()
+        .map(|cached| cached.sensor)
+        .filter(|sensor| !data.sensor_blacklist.contains(&sensor.sensor_id))
+        .collect();
+    let fans = data.ipmi_service.get_fan_sensors().unwrap_or_else(|e| {
+        tracing::warn!("手动评估告警规则读取风扇失败: {}", e);
+        Vec::new()
+    });
+
+    let evaluation = data.alert_monitor.evaluate(&temperatures, &fans, now);
+    for alert in &evaluation.created_alerts {
+        data.alert_history.upsert(alert).await?;
+    }
+    tracing::info!(
+        target: "audit",
+        "手动评估告警规则: 规则 {} 条, 命中 {} 条, 创建告警 {} 条",
+        evaluation.evaluated_rules,
+        evaluation.matches.len(),
+        evaluation.created_alerts.len()
+    );
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        evaluation,
+        "Alert rules evaluated"
+    )))
+}
+
+/// 告警链路自检的合成温度阈值，真实读数不会超过
+const SELFTEST_THRESHOLD: f64 = 1000.0;
+
+/// 告警链路自检请求
+#[derive(Debug, Default, Deserialize)]
+pub struct SelfTestRequest {
+    /// 只渲染通知并检查渠道配置，不发送给真实接收人
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 单个渠道的自检结果
+#[derive(Debug, Serialize)]
+pub struct ChannelSelfTestResult {
+    pub channel_id: String,
+    pub channel_type: models::alert::ChannelType,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// 告警链路自检（需要API密钥）
+///
+/// 用一条临时温度规则评估必然超限的合成读数，确认规则触发并创建告警后投递到每个已启用的渠道。
+/// 临时规则和告警只存在于本次自检，不进入告警列表和事件，也不影响真实规则的持续时间计时
+///
+/// POST /api/v1/alerts/selftest
+pub async fn run_alert_selftest(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: Option<web::Json<SelfTestRequest>>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let dry_run = body.map(web::Json::into_inner).unwrap_or_default().dry_run;
+    let started_at = Utc::now();
+    let rules = AlertRuleStore::default();
+    rules.replace(BTreeMap::from([(
+        "selftest".to_string(),
+        models::alert::AlertRule {
+            name: "告警链路自检".to_string(),
+            description: "自检临时规则，自检结束后移除".to_string(),
+            metric: "temperature".to_string(),
+            condition: models::alert::AlertCondition {
+                operator: ">".to_string(),
+                threshold: SELFTEST_THRESHOLD,
+                duration_seconds: 0,
+            },
+            threshold_unit: None,
+            severity: models::alert::AlertSeverity::Info,
+            enabled: true,
+        },
+    )]));
+    let monitor = AlertMonitor::new(rules, AlertStore::new(1), IncidentStore::default());
+    let reading = TemperatureSensor {
+        id: "selftest".to_string(),
+        sensor_id: "selftest".to_string(),
+        temperature: SELFTEST_THRESHOLD + 1.0,
+        unit: "°C".to_string(),
+        raw_unit: String::new(),
+        location: "unknown".to_string(),
+        entity: String::new(),
+        status: "ok".to_string(),
+        timestamp: started_at,
+    };
+    let evaluation = monitor.evaluate(&[reading], &[], started_at);
+    let rule_triggered = !evaluation.matches.is_empty();
+    let alert = evaluation.created_alerts.into_iter().next();
+
+    let mut channels = Vec::new();
+    if let Some(alert) = &alert {
+        for (channel_id, channel) in data.alert_notifier.channels().iter().filter(|(_, channel)| channel.enabled) {
+            let started = std::time::Instant::now();
+            let result = if dry_run {
+                alert_notifier::render(channel_id, channel, alert);
+                match channel_validation::check_channel(channel).into_iter().next() {
+                    Some(problem) => Err(AppError::validation_error(problem.field, problem.message)),
+                    None => Ok(()),
+                }
+            } else {
+                data.alert_notifier.deliver(channel_id, channel, alert).await
+            };
+            channels.push(ChannelSelfTestResult {
+                channel_id: channel_id.clone(),
+                channel_type: channel.channel_type.clone(),
+                success: result.is_ok(),
+                error: result.err().map(|e| e.to_string()),
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+    }
+
+    let alert_created = alert.is_some();
+    let success = rule_triggered && alert_created && channels.iter().all(|channel| channel.success);
+    tracing::info!(
+        target: "audit",
+        "告警链路自检: {} (dry_run: {}, {} 个渠道失败)",
+        if success { "成功" } else { "失败" },
+        dry_run,
+        channels.iter().filter(|channel| !channel.success).count()
+    );
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({
+            "success": success,
+            "dry_run": dry_run,
+            "started_at": started_at,
+            "rule_triggered": rule_triggered,
+            "alert_created": alert_created,
+            "channels": channels,
+        }),
+        "Alert self-test completed"
+    )))
+}
+
+/// 按名称创建或更新告警规则请求
+#[derive(Debug, Deserialize)]
+pub struct ApplyAlertRuleRequest {
+    /// 新建时使用的规则ID（默认自动生成），已有同名规则时必须与其一致或省略
+    pub id: Option<String>,
+    /// 告警规则
+    #[serde(flatten)]
+    pub rule: models::alert::AlertRule,
+}
+
+/// 按名称创建或更新告警规则（需要API密钥）
+///
+/// 规则名称作为稳定标识，重复下发同一规则不会产生重复规则
+///
+/// PUT /api/v1/alerts/rules
+pub async fn apply_alert_rule(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<ApplyAlertRuleRequest>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let request = body.into_inner();
+    let (rule_id, rule, created) = data.alert_rules.apply(request.id.as_deref(), request.rule)?;
+    tracing::info!(
+        target: "audit",
+        "告警规则已{}: {} ({})",
+        if created { "创建" } else { "更新" },
+        rule_id,
+        rule.name
+    );
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({
+            "id": rule_id,
+            "created": created,
+            "rule": rule,
+        }),
+        "Alert rule applied successfully"
+    )))
+}
+
+/// 告警规则试运行请求
+#[derive(Debug, Default, Deserialize)]
+pub struct RuleDryRunRequest {
+    /// 回放开始时间（默认结束时间前24小时）
+    pub start_time: Option<DateTime<Utc>>,
+    /// 回放结束时间（默认当前时间）
+    pub end_time: Option<DateTime<Utc>>,
+    /// 待验证的规则（不提供时使用路径中的已有规则）
+    pub rule: Option<models::alert::AlertRule>,
+}
+
+/// 试运行告警规则
+///
+/// 用温度分布采集窗口内保留的读数回放规则，返回预计触发次数、触发时间和读数样本，不会产生真实告警。
+/// 请求体中的规则优先，便于在保存前验证新规则；请求体可省略
+///
+/// POST /api/v1/alerts/rules/{rule_id}/test
+pub async fn test_alert_rule(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+    body: Option<web::Json<RuleDryRunRequest>>,
+) -> ApiResult<HttpResponse> {
+    let rule_id = path.into_inner();
+    let request = body.map(web::Json::into_inner).unwrap_or_default();
+    let rule = match request.rule {
+        Some(rule) => rule,
+        None => data.alert_rules.get(&rule_id)?,
+    };
+
+    let end_time = request.end_time.unwrap_or_else(Utc::now);
+    let start_time = request.start_time.unwrap_or(end_time - Duration::hours(24));
+    let readings = data.temperature_histograms.readings(start_time, end_time);
+    let result = alert_rules::dry_run(&rule, &readings, start_time, end_time)?;
+    tracing::info!(
+        "告警规则试运行: {} 共评估 {} 条, 触发 {} 次",
+        rule_id,
+        result.evaluated_samples,
+        result.trigger_count
+    );
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({
+            "rule_id": rule_id,
+            "rule": rule,
+            "history_window_secs": data.temperature_histograms.config().window_secs,
+            "result": result,
+        }),
+        "Alert rule dry run completed"
+    )))
+}
+
+/// 连通性探测超时（毫秒）
+const CHANNEL_PROBE_TIMEOUT_MS: u64 = 3000;
+
+/// 通知渠道校验请求
+#[derive(Debug, Deserialize)]
+pub struct ValidateChannelRequest {
+    /// 待校验的通知渠道
+    #[serde(flatten)]
+    pub channel: models::alert::NotificationChannel,
+    /// 配置无误时是否探测渠道地址能否连通
+    #[serde(default)]
+    pub probe: bool,
+}
+
+/// 校验通知渠道配置，不保存（需要API密钥）
+///
+/// 返回配置中的全部问题；`probe` 为true时再探测渠道地址的TCP连通性，探测失败同样视为无效
+///
+/// POST /api/v1/alerts/channels/validate
+pub async fn validate_channel(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<ValidateChannelRequest>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let result = channel_validation::validate_channel(&body.channel, body.probe, CHANNEL_PROBE_TIMEOUT_MS).await;
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        result,
+        "Notification channel validated"
+    )))
+}
+
+/// 通知预览请求，即示例告警
+#[derive(Debug, Deserialize)]
+pub struct NotificationPreviewRequest {
+    /// 告警类型，如 `temperature`
+    pub alert_type: String,
+    /// 严重程度
+    pub severity: models::alert::AlertSeverity,
+    /// 告警源
+    pub source: String,
+    /// 消息
+    pub message: String,
+    /// 标题（默认取告警类型）
+    pub title: Option<String>,
+}
+
+/// 预览渠道针对一条示例告警将要发送的消息，不实际发送
+///
+/// 示例告警不进入告警列表
+///
+/// POST /api/v1/alerts/channels/{channel_id}/preview
+pub async fn preview_notification(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+    body: web::Json<NotificationPreviewRequest>,
+) -> ApiResult<HttpResponse> {
+    let channel_id = path.into_inner();
+    let channel = data.alert_notifier.channel(&channel_id)?;
+
+    let request = body.into_inner();
+    let now = Utc::now();
+    let alert = models::Alert {
+        id: uuid::Uuid::new_v4(),
+        title: request.title.unwrap_or_else(|| format!("{}告警", request.alert_type)),
+        alert_type: request.alert_type,
+        severity: format!("{:?}", request.severity),
+        message: request.message,
+        source_id: request.source.clone(),
+        source: request.source,
+        rule_id: None,
+        status: AlertStatus::Triggered,
+        acknowledged: false,
+        acknowledged_by: None,
+        acknowledged_at: None,
+        resolved_at: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        alert_notifier::render(&channel_id, channel, &alert),
+        "Notification preview rendered"
+    )))
+}
+
+/// 确定确认、解决告警的操作人
+///
+/// 携带有效JWT时以其主体为准，否则取请求体中的操作人；都没有时，`alert.require_actor`
+/// 开启则返回400，关闭则记为 `system`
+///
+/// # 参数
+/// * `req` - HTTP请求
+/// * `config` - 应用配置
+/// * `jwt_secrets` - JWT签名密钥
+/// * `requested` - 请求体中的操作人
+/// * `field` - 操作人字段名
+pub(crate) fn request_actor(
+    req: &HttpRequest,
+    config: &AppConfig,
+    jwt_secrets: &JwtSecrets,
+    requested: Option<&str>,
+    field: &str,
+) -> AppResult<String> {
+    if let Some(subject) = jwt_subject(req, jwt_secrets) {
+        return Ok(subject);
+    }
+
+    match requested.map(str::trim).filter(|actor| !actor.is_empty()) {
+        Some(actor) => Ok(actor.to_string()),
+        None if config.alert.require_actor => Err(AppError::validation_error(field, "必须提供操作人")),
+        None => Ok("system".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::alert::AlertSeverity;
+    use crate::services::alert_store::NewAlert;
+    use crate::test_harness::{MockIpmiService, TestHarness};
+    use actix_web::body::MessageBody;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::App;
+
+    #[test]
+    fn test_required_actor_rejects_missing_and_accepts_present() {
+        let mut config = AppConfig::default();
+        config.alert.require_actor = true;
+        let secrets = JwtSecrets::new(config.security.jwt_secret.clone(), 0);
+        let req = TestRequest::default().to_http_request();
+
+        for missing in [None, Some(""), Some("   ")] {
+            let err = request_actor(&req, &config, &secrets, missing, "acknowledged_by").unwrap_err();
+            assert_eq!(err.status_code(), 400);
+        }
+        assert_eq!(request_actor(&req, &config, &secrets, Some("alice"), "acknowledged_by").unwrap(), "alice");
+
+        // JWT主体优先于请求体中的操作人
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &json!({ "sub": "bob", "exp": Utc::now().timestamp() + 3600 }),
+            &jsonwebtoken::EncodingKey::from_secret(config.security.jwt_secret.as_bytes()),
+        )
+        .unwrap();
+        let req = TestRequest::default()
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_http_request();
+        assert_eq!(request_actor(&req, &config, &secrets, None, "resolved_by").unwrap(), "bob");
+        assert_eq!(request_actor(&req, &config, &secrets, Some("alice"), "resolved_by").unwrap(), "bob");
+
+        // 未开启时允许匿名操作
+        config.alert.require_actor = false;
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(request_actor(&req, &config, &secrets, None, "acknowledged_by").unwrap(), "system");
+    }
+
+    #[actix_web::test]
+    async fn test_alert_stream_pushes_created_alerts() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(harness.state.clone()))
+                .configure(crate::configure_routes),
+        )
+        .await;
+
+        let resp = test::call_service(&app, TestRequest::get().uri("/api/v1/alerts/stream").to_request()).await;
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get(actix_web::http::header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+
+        let (_, alert) = harness
+            .state
+            .alerts
+            .raise(NewAlert {
+                alert_type: "temperature".to_string(),
+                severity: AlertSeverity::Critical,
+                title: "高温告警".to_string(),
+                message: "CPU1_TEMP 温度 92.0°C".to_string(),
+                source: "CPU1_TEMP".to_string(),
+                source_id: "CPU1_TEMP".to_string(),
+                rule_id: Some("high_temperature".to_string()),
+            })
+            .unwrap();
+
+        let mut body = resp.into_body();
+        let chunk = std::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        let frame = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(frame.starts_with("event: created\ndata: "));
+        let data: serde_json::Value = serde_json::from_str(frame.lines().nth(1).unwrap().trim_start_matches("data: ")).unwrap();
+        assert_eq!(data["transition"], "created");
+        assert_eq!(data["alert"]["id"], alert.id.to_string());
+
+        // 告警列表与确认接口使用同一份告警
+        let (_, body) = harness.get("/alerts?status=active").await;
+        assert_eq!(body["data"]["total"], 1);
+        let (status, _) = harness
+            .post_json(&format!("/alerts/{}/acknowledge", alert.id), json!({ "acknowledged_by": "alice" }))
+            .await;
+        assert!(status.is_success());
+        let (_, body) = harness.get("/alerts?status=acknowledged").await;
+        assert_eq!(body["data"]["data"][0]["acknowledged_by"], "alice");
+    }
+
+    #[actix_web::test]
+    async fn test_alert_transitions_are_persisted_and_evicted_alerts_read_from_history() {
+        let mut config = AppConfig::default();
+        config.security.api_key = crate::test_harness::TEST_API_KEY.to_string();
+        config.alert.history_limit = 1;
+        let harness = TestHarness::with_config(MockIpmiService::healthy_server(), config);
+        harness.ipmi.set_temperature("CPU1_TEMP", 92.0);
+        harness.ipmi.set_temperature("CPU2_TEMP", 91.0);
+        let patch = models::alert::AlertRulePatch {
+            duration_seconds: Some(0),
+            ..Default::default()
+        };
+        harness.state.alert_rules.patch("high_temperature", &patch).unwrap();
+
+        let (_, body) = harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        let created = body["data"]["created_alerts"].as_array().unwrap();
+        assert_eq!(created.len(), 2);
+        assert_eq!(harness.state.alert_history.count().await.unwrap(), 2);
+
+        // 内存只保留最近1条，较早的告警从告警历史读取
+        let evicted = created[0]["id"].as_str().unwrap();
+        assert!(harness.state.alerts.get(evicted.parse().unwrap()).is_err());
+        let (status, body) = harness.get(&format!("/alerts/{}", evicted)).await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        assert_eq!(body["data"]["source_id"], "CPU1_TEMP");
+
+        let latest = created[1]["id"].as_str().unwrap();
+        let (status, _) = harness
+            .post_json(&format!("/alerts/{}/resolve", latest), json!({ "resolved_by": "alice" }))
+            .await;
+        assert!(status.is_success());
+        let persisted = harness.state.alert_history.get(latest).await.unwrap().unwrap();
+        assert!(matches!(persisted.status, AlertStatus::Resolved));
+
+        let (status, body) = harness.get(&format!("/alerts/{}", uuid::Uuid::new_v4())).await;
+        assert_eq!(status, actix_web::http::StatusCode::NOT_FOUND);
+        assert_eq!(body["success"], false);
+
+        let (status, body) = harness.get("/alerts?status=bogus").await;
+        assert_eq!(status, actix_web::http::StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("bogus"));
+    }
+
+    #[actix_web::test]
+    async fn test_lifetime_statistics_survive_eviction_and_reset_is_saved() {
+        let mut config = AppConfig::default();
+        config.security.api_key = crate::test_harness::TEST_API_KEY.to_string();
+        config.alert.history_limit = 1;
+        let harness = TestHarness::with_config(MockIpmiService::healthy_server(), config);
+        let raise = |source_id: &str| NewAlert {
+            alert_type: "temperature".to_string(),
+            severity: AlertSeverity::Critical,
+            title: "高温告警".to_string(),
+            message: format!("{} 温度 92.0°C", source_id),
+            source: source_id.to_string(),
+            source_id: source_id.to_string(),
+            rule_id: Some("high_temperature".to_string()),
+        };
+        harness.state.alerts.raise(raise("CPU1_TEMP")).unwrap();
+        let (_, latest) = harness.state.alerts.raise(raise("CPU2_TEMP")).unwrap();
+        harness.state.alerts.resolve(latest.id).unwrap();
+
+        // 内存只保留1条，累计统计仍包含已淘汰的告警
+        let (status, body) = harness.get("/api/v1/alerts/statistics?time_window_hours=1").await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        assert_eq!(body["data"]["lifetime"]["total_alerts"], 2);
+        assert_eq!(body["data"]["lifetime"]["active_alerts"], 1);
+        assert_eq!(body["data"]["lifetime"]["resolved_alerts"], 1);
+        assert_eq!(body["data"]["lifetime"]["by_severity"]["Critical"], 2);
+        assert_eq!(body["data"]["window"]["total_alerts"], 1);
+        let (status, _) = harness.get("/api/v1/alerts/statistics?time_window_hours=0").await;
+        assert_eq!(status, actix_web::http::StatusCode::BAD_REQUEST);
+
+        let (status, body) = harness
+            .post_json("/api/v1/alerts/statistics/reset", json!({ "reset_by": "alice", "reason": "季度清零" }))
+            .await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        assert_eq!(body["data"]["previous"]["total_alerts"], 2);
+        let saved = harness.state.alert_statistics_store.load().await.unwrap().unwrap();
+        assert_eq!(saved.lifetime.total_alerts, 0);
+        assert_eq!(saved.last_reset.unwrap().reset_by.as_deref(), Some("alice"));
+
+        // 重启后从统计存储恢复
+        let restarted = AlertStore::new(10);
+        restarted.restore_statistics(harness.state.alert_statistics_store.as_ref()).await.unwrap();
+        assert!(restarted.statistics(24).unwrap().last_reset.is_some());
+    }
+
+    #[actix_web::test]
+    async fn test_toggle_rule_persists_state_and_stops_evaluation() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.ipmi.set_temperature("CPU1_TEMP", 92.0);
+
+        let (status, body) = harness.post_json("/api/v1/alerts/rules/high_temperature/toggle", json!({})).await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        assert_eq!(body["data"]["enabled"], false);
+        let states = harness.state.alert_rule_states.load().await.unwrap();
+        assert_eq!(states.get("high_temperature"), Some(&false));
+
+        // 停用的规则不参与评估
+        let (_, body) = harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        assert!(body["data"]["matches"].as_array().unwrap().is_empty());
+
+        // 重启后在默认规则之上恢复
+        let restarted = AlertRuleStore::with_defaults(80.0, 1000);
+        restarted.restore_enabled(&states);
+        assert!(!restarted.get("high_temperature").unwrap().enabled);
+
+        let (_, body) = harness.post_json("/api/v1/alerts/rules/high_temperature/toggle", json!({})).await;
+        assert_eq!(body["data"]["enabled"], true);
+        let (status, _) = harness.post_json("/api/v1/alerts/rules/missing/toggle", json!({})).await;
+        assert_eq!(status, actix_web::http::StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_temperature_alert_links_to_the_rule_that_matched() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.ipmi.set_temperature("CPU1_TEMP", 92.0);
+        let patch = models::alert::AlertRulePatch {
+            duration_seconds: Some(0),
+            ..Default::default()
+        };
+        harness.state.alert_rules.patch("high_temperature", &patch).unwrap();
+
+        let (_, body) = harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        let alert_id = body["data"]["created_alerts"][0]["id"].as_str().unwrap().to_string();
+        let (_, body) = harness.get(&format!("/alerts/{}", alert_id)).await;
+        assert_eq!(body["data"]["rule_id"], "high_temperature");
+
+        let (status, body) = harness.get(&format!("/alerts/{}/rule", alert_id)).await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        assert_eq!(body["data"]["rule_id"], "high_temperature");
+        assert_eq!(body["data"]["rule"]["metric"], "temperature");
+        assert_eq!(body["data"]["rule"]["condition"]["duration_seconds"], 0);
+
+        // 非规则触发的告警没有对应规则
+        let (_, manual) = harness
+            .state
+            .alerts
+            .raise(crate::services::alert_store::NewAlert {
+                alert_type: "system".to_string(),
+                severity: models::alert::AlertSeverity::Warning,
+                title: "手动告警".to_string(),
+                message: "手动告警".to_string(),
+                source: "operator".to_string(),
+                source_id: "operator".to_string(),
+                rule_id: None,
+            })
+            .unwrap();
+        let (status, _) = harness.get(&format!("/alerts/{}/rule", manual.id)).await;
+        assert_eq!(status, actix_web::http::StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_muted_source_breaches_create_no_active_alerts_until_expiry() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.ipmi.set_temperature("CPU1_TEMP", 92.0);
+        let patch = models::alert::AlertRulePatch {
+            duration_seconds: Some(0),
+            ..Default::default()
+        };
+        harness.state.alert_rules.patch("high_temperature", &patch).unwrap();
+
+        let expires_at = Utc::now() + Duration::milliseconds(500);
+        let (status, body) = harness
+            .post_json(
+                "/api/v1/alerts/mutes",
+                json!({ "source": "CPU1_TEMP", "expires_at": expires_at, "reason": "传感器故障待更换" }),
+            )
+            .await;
+        assert_eq!(status, actix_web::http::StatusCode::CREATED);
+        let mute_id = body["data"]["id"].as_str().unwrap().to_string();
+
+        // 静默期间只以Ignored状态记录一次
+        let (_, body) = harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        assert_eq!(body["data"]["created_alerts"].as_array().unwrap().len(), 1);
+        assert_eq!(body["data"]["created_alerts"][0]["status"], "Ignored");
+        let ignored_id = body["data"]["created_alerts"][0]["id"].as_str().unwrap().to_string();
+        let (_, body) = harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        assert!(body["data"]["created_alerts"].as_array().unwrap().is_empty());
+        assert!(harness.state.alerts.active().is_empty());
+        let (_, body) = harness.get(&format!("/alerts/{}", ignored_id)).await;
+        assert_eq!(body["data"]["status"], "Ignored");
+        let (_, body) = harness.get("/api/v1/alerts/mutes").await;
+        assert_eq!(body["data"][0]["id"], mute_id);
+        assert_eq!(body["data"][0]["suppressed_count"], 1);
+
+        tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+        harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        let active = harness.state.alerts.active();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].source_id, "CPU1_TEMP");
+        let (_, body) = harness.get("/api/v1/alerts/mutes").await;
+        assert_eq!(body["data"], json!([]));
+
+        let (status, _) = harness
+            .call(TestRequest::delete().uri(&format!("/api/v1/alerts/mutes/{}", mute_id)))
+            .await;
+        assert_eq!(status, actix_web::http::StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_evaluate_now_creates_alerts_only_for_held_breaches() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.ipmi.set_temperature("CPU1_TEMP", 92.0);
+
+        // 高温规则需持续60秒，首次评估只命中不告警
+        let (status, body) = harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        let matches = body["data"]["matches"].as_array().unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0]["source_id"], "CPU1_TEMP");
+        assert!(matches[0]["alert_id"].is_null());
+        assert_eq!(body["data"]["evaluated_readings"], 5);
+
+        let patch = models::alert::AlertRulePatch {
+            duration_seconds: Some(0),
+            ..Default::default()
+        };
+        harness.state.alert_rules.patch("high_temperature", &patch).unwrap();
+        let (_, body) = harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        let created = body["data"]["created_alerts"].as_array().unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0]["rule_id"], "high_temperature");
+        assert_eq!(harness.state.alerts.list().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_selftest_dry_run_checks_each_enabled_channel() {
+        let mut config = AppConfig::default();
+        config.security.api_key = crate::test_harness::TEST_API_KEY.to_string();
+        config.alert.email.enabled = false;
+        config.alert.webhook.enabled = true;
+        config.alert.webhook.url = "https://hooks.example.com/alert".to_string();
+        let harness = TestHarness::with_config(MockIpmiService::healthy_server(), config.clone());
+
+        let (status, body) = harness.post_json("/api/v1/alerts/selftest", json!({ "dry_run": true })).await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        assert_eq!(body["data"]["success"], true);
+        assert_eq!(body["data"]["rule_triggered"], true);
+        assert_eq!(body["data"]["alert_created"], true);
+        let channels = body["data"]["channels"].as_array().unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0]["channel_id"], "webhook");
+        // 自检告警不进入告警列表
+        assert!(harness.state.alerts.list().is_empty());
+
+        // 渠道未配置发送目标时自检失败
+        config.alert.webhook.url = String::new();
+        let harness = TestHarness::with_config(MockIpmiService::healthy_server(), config);
+        let (_, body) = harness.post_json("/api/v1/alerts/selftest", json!({ "dry_run": true })).await;
+        assert_eq!(body["data"]["success"], false);
+        assert_eq!(body["data"]["channels"][0]["success"], false);
+    }
+
+    #[actix_web::test]
+    async fn test_preview_renders_configured_channel_without_creating_alert() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let sample = json!({
+            "alert_type": "temperature",
+            "severity": "Critical",
+            "source": "CPU1_TEMP",
+            "message": "CPU温度达到92°C",
+        });
+
+        let (status, body) = harness.post_json("/api/v1/alerts/channels/email/preview", sample.clone()).await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        assert_eq!(body["data"]["channel_type"], "Email");
+        assert_eq!(body["data"]["subject"], "[Critical] temperature告警");
+        assert!(body["data"]["body"].as_str().unwrap().contains("CPU温度达到92°C"));
+
+        let (_, body) = harness.post_json("/api/v1/alerts/channels/webhook/preview", sample.clone()).await;
+        assert_eq!(body["data"]["content_type"], "application/json");
+        let payload: serde_json::Value = serde_json::from_str(body["data"]["body"].as_str().unwrap()).unwrap();
+        assert_eq!(payload["source"], "CPU1_TEMP");
+        assert!(harness.state.alerts.list().is_empty());
+
+        let (status, _) = harness.post_json("/api/v1/alerts/channels/slack/preview", sample).await;
+        assert_eq!(status, actix_web::http::StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_put_rule_by_name_updates_in_place() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let rule = json!({
+            "name": "高温告警",
+            "description": "CPU温度持续超过85°C",
+            "metric": "temperature",
+            "condition": { "operator": ">", "threshold": 85.0, "duration_seconds": 60 },
+            "severity": "Critical",
+            "enabled": true,
+        });
+
+        let (status, body) = harness
+            .call(TestRequest::put().uri("/api/v1/alerts/rules").set_json(rule.clone()))
+            .await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        assert_eq!(body["data"]["id"], "high_temperature");
+        assert_eq!(body["data"]["created"], false);
+        assert_eq!(harness.state.alert_rules.get("high_temperature").unwrap().condition.threshold, 85.0);
+
+        let count = harness.state.alert_rules.all().len();
+        harness
+            .call(TestRequest::put().uri("/api/v1/alerts/rules").set_json(rule))
+            .await;
+        assert_eq!(harness.state.alert_rules.all().len(), count);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::middleware::auth::require_api_key;
+use crate::models::analytics::{AnalysisType, AnalyticsResult};
+use crate::models::api::ApiResponse;
+use crate::models::error::{ApiResult, AppError, AppResult};
+use crate::services::alert_thresholds::SensorAlertThreshold;
+use crate::services::period_comparison::compare_periods;
+use crate::services::report_mailer::{ReportSource, ThermalReport};
+use crate::services::result_cache::CacheClearScope;
+use crate::AppState;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 报告周期上限（小时）
+const MAX_REPORT_HOURS: u64 = 744;
+
+/// 周期报告查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportQuery {
+    /// 报告周期（小时），默认168（一周）
+    #[serde(default = "default_report_hours")]
+    pub duration_hours: u64,
+    /// `json`（默认）或 `markdown`
+    pub format: Option<String>,
+}
+
+fn default_report_hours() -> u64 {
+    168
+}
+
+/// 时间段对比查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompareQuery {
+    pub baseline_start: DateTime<Utc>,
+    pub baseline_end: DateTime<Utc>,
+    pub current_start: DateTime<Utc>,
+    pub current_end: DateTime<Utc>,
+}
+
+/// 未设置阈值的传感器使用的告警阈值
+fn default_threshold(data: &AppState) -> SensorAlertThreshold {
+    SensorAlertThreshold {
+        warning: data.config.monitoring.alert_threshold_temp,
+        critical: data.config.control.emergency_exit.critical_temperature,
+    }
+}
+
+/// 将分析输出包装为可缓存的分析结果
+///
+/// # 参数
+/// * `data` - 应用状态
+/// * `(start, end)` - 分析覆盖的时间段
+/// * `output` - 分析输出，保存在 `result_data` 中
+/// * `summary` - 分析摘要
+/// * `recommendations` - 建议操作
+fn analytics_result(
+    data: &AppState,
+    (start, end): (DateTime<Utc>, DateTime<Utc>),
+    output: &impl Serialize,
+    summary: String,
+    recommendations: Vec<String>,
+) -> AppResult<AnalyticsResult> {
+    // 结果由读数直接统计得到，不涉及模型推断
+    Ok(AnalyticsResult::new(
+        data.config.ipmi.host.clone(),
+        AnalysisType::TemperatureTrend,
+        start,
+        end,
+        serde_json::to_value(output)?,
+        summary,
+        recommendations,
+        100.0,
+    ))
+}
+
+/// 周期综合散热报告
+///
+/// 按温度分布采集保留的读数生成，包含执行摘要（关键发现、按严重程度排序的重点建议）和各传感器的温度分布；
+/// 同一周期的报告在缓存有效期内直接返回缓存结果
+///
+/// GET /api/v1/analytics/report
+pub async fn get_report(data: web::Data<AppState>, query: web::Query<ReportQuery>) -> ApiResult<HttpResponse> {
+    if query.duration_hours == 0 || query.duration_hours > MAX_REPORT_HOURS {
+        return Err(AppError::validation_error(
+            "duration_hours",
+            format!("报告周期必须在1-{}小时之间", MAX_REPORT_HOURS),
+        )
+        .into());
+    }
+    let markdown = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("markdown") => true,
+        Some(other) => {
+            return Err(AppError::validation_error("format", format!("不支持的格式: {}", other)).into());
+        }
+    };
+
+    let key = format!("thermal_report_{}", query.duration_hours);
+    let result = data
+        .analysis_cache
+        .get_or_compute(&key, || async {
+            let source = ReportSource {
+                histograms: data.temperature_histograms.clone(),
+                alert_thresholds: data.alert_thresholds.clone(),
+                default_threshold: default_threshold(&data),
+            };
+            let report = source.report(query.duration_hours, Utc::now())?;
+            analytics_result(
+                &data,
+                (report.period_start, report.period_end),
+                &report,
+                report.key_findings.join("；"),
+                report.top_recommendations.iter().map(|rec| rec.title.clone()).collect(),
+            )
+        })
+        .await?;
+
+    if markdown {
+        let report: ThermalReport = serde_json::from_value(result.result_data).map_err(AppError::from)?;
+        return Ok(HttpResponse::Ok()
+            .content_type("text/markdown; charset=utf-8")
+            .body(report.to_markdown()));
+    }
+    Ok(HttpResponse::Ok().json(ApiResponse::success(result.result_data)))
+}
+
+/// 对比两个时间段的温度：平均温度、最高温度和达到警告阈值的读数数量的变化及显著性
+///
+/// 两个时间段都已结束时结果不再变化，按时间段缓存；包含当前时间的对比每次重新计算
+///
+/// GET /api/v1/analytics/compare
+pub async fn compare(data: web::Data<AppState>, query: web::Query<CompareQuery>) -> ApiResult<HttpResponse> {
+    let compute = || async {
+        let comparison = compare_periods(
+            &data.temperature_histograms,
+            &data.alert_thresholds,
+            default_threshold(&data),
+            (query.baseline_start, query.baseline_end),
+            (query.current_start, query.current_end),
+        )?;
+        let summary = match comparison.avg_temperature.delta {
+            Some(delta) => format!("平均温度变化 {:+.1}°C", delta),
+            None => "数据不足".to_string(),
+        };
+        analytics_result(
+            &data,
+            (query.baseline_start.min(query.current_start), query.baseline_end.max(query.current_end)),
+            &comparison,
+            summary,
+            Vec::new(),
+        )
+    };
+
+    let result = if query.baseline_end.max(query.current_end) <= Utc::now() {
+        let key = format!(
+            "period_comparison_{}_{}_{}_{}",
+            query.baseline_start.timestamp_millis(),
+            query.baseline_end.timestamp_millis(),
+            query.current_start.timestamp_millis(),
+            query.current_end.timestamp_millis()
+        );
+        data.analysis_cache.get_or_compute(&key, compute).await?
+    } else {
+        compute().await?
+    };
+    Ok(HttpResponse::Ok().json(ApiResponse::success(result.result_data)))
+}
+
+/// 清理分析结果缓存（需要API密钥）
+///
+/// 调整阈值等参数后让缓存的报告和对比立即失效，之后的请求重新计算
+///
+/// POST /api/v1/analytics/cache/clear
+pub async fn clear_cache(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<CacheClearScope>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let scope = query.into_inner();
+    let cleared_entries = data.analysis_cache.clear(&scope).await?;
+    let cache_types = scope.analysis_types();
+    tracing::info!(
+        target: "audit",
+        "分析缓存已清理: 类型={}, 后端={}, 清理条目={}",
+        cache_types.join(","),
+        data.analysis_cache.backend_name(),
+        cleared_entries
+    );
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+        "cleared_entries": cleared_entries,
+        "cache_types": cache_types,
+    }))))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::AppConfig;
+    use crate::services::sensor_source::SensorSource;
+    use crate::test_harness::{MockIpmiService, TestHarness};
+    use actix_web::{http::StatusCode, test};
+    use chrono::{Duration, Utc};
+
+    #[actix_web::test]
+    async fn test_report_lists_sections_and_sorts_recommendations_by_severity() {
+        let mut config = AppConfig::default();
+        config.monitoring.alert_threshold_temp = 75.0;
+        config.control.emergency_exit.critical_temperature = 85.0;
+        let harness = TestHarness::with_config(MockIpmiService::healthy_server(), config);
+        harness.ipmi.set_temperature("CPU2_TEMP", 80.0);
+        harness.ipmi.set_temperature("CPU1_TEMP", 90.0);
+
+        let now = Utc::now();
+        let sensors = harness.ipmi.get_temperature_sensors().unwrap();
+        for minute in 1..=10 {
+            harness
+                .state
+                .temperature_histograms
+                .record(&sensors, now - Duration::minutes(minute));
+        }
+
+        let (status, body) = harness.get("/api/v1/analytics/report?duration_hours=24").await;
+        assert_eq!(status, StatusCode::OK);
+        let report = &body["data"];
+        assert_eq!(report["sensors"], 3);
+        assert_eq!(report["temperature_distribution"].as_array().unwrap().len(), 3);
+        assert!(!report["key_findings"].as_array().unwrap().is_empty());
+        let levels: Vec<&str> = report["top_recommendations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|rec| rec["level"].as_str().unwrap())
+            .collect();
+        assert_eq!(levels, vec!["critical", "warning"]);
+        assert_eq!(report["top_recommendations"][0]["sensor_id"], "CPU1_TEMP");
+
+        let (status, body) = harness.get("/api/v1/analytics/report?format=pdf").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("pdf"));
+    }
+
+    #[actix_web::test]
+    async fn test_report_is_cached_until_cleared() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let now = Utc::now();
+        let sensors = harness.ipmi.get_temperature_sensors().unwrap();
+        harness.state.temperature_histograms.record(&sensors, now - Duration::minutes(1));
+
+        let (status, first) = harness.get("/api/v1/analytics/report?duration_hours=24").await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 新读数在缓存有效期内不影响同一周期的报告
+        harness.ipmi.set_temperature("CPU1_TEMP", 90.0);
+        let sensors = harness.ipmi.get_temperature_sensors().unwrap();
+        harness.state.temperature_histograms.record(&sensors, now);
+        let (_, cached) = harness.get("/api/v1/analytics/report?duration_hours=24").await;
+        assert_eq!(cached["data"]["id"], first["data"]["id"]);
+
+        let (_, other_period) = harness.get("/api/v1/analytics/report?duration_hours=12").await;
+        assert_ne!(other_period["data"]["id"], first["data"]["id"]);
+        assert_eq!(other_period["data"]["temperature_distribution"][0]["max"], 90.0);
+
+        let (status, body) = harness
+            .call(test::TestRequest::post().uri("/api/v1/analytics/cache/clear?type=report"))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["cleared_entries"], 2);
+        assert_eq!(body["data"]["cache_types"], serde_json::json!(["report"]));
+
+        let (_, recomputed) = harness.get("/api/v1/analytics/report?duration_hours=24").await;
+        assert_ne!(recomputed["data"]["id"], first["data"]["id"]);
+        assert_eq!(recomputed["data"]["temperature_distribution"][0]["max"], 90.0);
+
+        let (status, body) = harness
+            .call(test::TestRequest::post().uri("/api/v1/analytics/cache/clear?type=temperature_trend"))
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("report"));
+    }
+
+    #[actix_web::test]
+    async fn test_compare_reports_hotter_current_period() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let now = Utc::now();
+        for minute in 1..=20 {
+            if minute == 11 {
+                harness.ipmi.set_temperature("CPU1_TEMP", 60.0);
+            }
+            let sensors = harness.ipmi.get_temperature_sensors().unwrap();
+            harness
+                .state
+                .temperature_histograms
+                .record(&sensors, now - Duration::minutes(21 - minute));
+        }
+
+        let at = |minutes: i64| (now - Duration::minutes(minutes)).to_rfc3339().replace('+', "%2B");
+        let uri = format!(
+            "/api/v1/analytics/compare?baseline_start={}&baseline_end={}&current_start={}&current_end={}",
+            at(21),
+            at(11),
+            at(10),
+            at(0)
+        );
+        let (status, body) = harness.get(&uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["avg_temperature"]["delta"].as_f64().unwrap() > 0.0);
+        assert_eq!(body["data"]["sensor_deltas"]["CPU1_TEMP"], 8.0);
+
+        let uri = format!(
+            "/api/v1/analytics/compare?baseline_start={}&baseline_end={}&current_start={}&current_end={}",
+            at(0),
+            at(21),
+            at(10),
+            at(0)
+        );
+        let (status, _) = harness.get(&uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
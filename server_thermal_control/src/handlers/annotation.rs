@@ -0,0 +1,36 @@
+use crate::handlers::alert::request_actor;
+use crate::models::api::ApiResponse;
+use crate::models::error::ApiResult;
+use crate::services::annotation_store::{AnnotationQuery, CreateAnnotationRequest, MAX_RANGE_ANNOTATIONS};
+use crate::AppState;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+
+/// 添加操作注释，时间点或时间段均可
+///
+/// POST /api/v1/annotations
+pub async fn create_annotation(
+    req: HttpRequest,
+    body: web::Json<CreateAnnotationRequest>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let body = body.into_inner();
+    let author = request_actor(&req, &data.config, &data.jwt_secrets, body.author.as_deref(), "author")?;
+    let annotation = body.into_annotation(author, Utc::now())?;
+    data.annotations.save(&annotation).await?;
+    tracing::info!(target: "audit", "添加注释: id={}, 操作人={}", annotation.id, annotation.author);
+
+    Ok(HttpResponse::Created().json(ApiResponse::success(annotation)))
+}
+
+/// 获取与时间窗口有交集的注释，按开始时间正序
+///
+/// GET /api/v1/annotations?start_time=...&end_time=...
+pub async fn list_annotations(
+    query: web::Query<AnnotationQuery>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let (start, end) = query.window(Utc::now())?;
+    let annotations = data.annotations.range(start, end, MAX_RANGE_ANNOTATIONS).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(annotations)))
+}
@@ -0,0 +1,85 @@
+use crate::config::{render_template, EffectiveConfig};
+use crate::middleware::auth::require_api_key;
+use crate::models::api::ApiResponse;
+use crate::models::error::{ApiResult, AppError};
+use crate::services::config_snapshot::ConfigSnapshot;
+use crate::services::ConfigSnapshotService;
+use crate::AppState;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+
+/// 生效配置查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct EffectiveConfigParams {
+    /// 是否附带各配置项来源
+    #[serde(default)]
+    pub include_sources: bool,
+}
+
+/// 获取合并后实际生效的配置（需要API密钥）
+///
+/// GET /api/v1/config/effective
+pub async fn get_effective_config(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    params: web::Query<EffectiveConfigParams>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let effective = EffectiveConfig::build(&data.config, &data.config_sources, params.include_sources);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(effective)))
+}
+
+/// 以TOML模板形式下载当前生效配置（需要API密钥）
+///
+/// 敏感字段以占位内容代替，保存后可作为 `config/app.toml` 编辑使用
+///
+/// GET /api/v1/config/template
+pub async fn get_config_template(req: HttpRequest, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let template = render_template(&data.config)
+        .map_err(|e| AppError::config_error(format!("生成配置模板失败: {}", e)))?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/toml; charset=utf-8")
+        .insert_header(("Content-Disposition", "attachment; filename=\"app.toml\""))
+        .body(template))
+}
+
+/// 按应用状态创建配置快照服务
+///
+/// # 参数
+/// * `data` - 应用状态
+fn snapshot_service(data: &AppState) -> ConfigSnapshotService {
+    ConfigSnapshotService::new(
+        data.alert_rules.clone(),
+        data.alert_thresholds.clone(),
+        data.sensor_blacklist.clone(),
+    )
+}
+
+/// 导出运行时可调配置的快照（需要API密钥）
+///
+/// GET /api/v1/config/snapshot
+pub async fn get_config_snapshot(req: HttpRequest, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(snapshot_service(&data).snapshot())))
+}
+
+/// 恢复配置快照（需要API密钥）
+///
+/// 快照整体校验通过后才应用，任何一部分无效时不做任何修改
+///
+/// POST /api/v1/config/restore
+pub async fn restore_config_snapshot(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<ConfigSnapshot>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let restored = snapshot_service(&data).restore(body.into_inner())?;
+    tracing::info!(target: "audit", "配置快照已恢复: 告警规则={}, 告警阈值={}", restored.alert_rules.len(), restored.alert_thresholds.len());
+    Ok(HttpResponse::Ok().json(ApiResponse::success(restored)))
+}
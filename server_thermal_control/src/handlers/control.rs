@@ -0,0 +1,789 @@
+use crate::middleware::auth::require_api_key;
+use crate::models::api::{ApiResponse, PaginatedResponse};
+use crate::models::control::{ControlActionType, ControlParameters, ControlParametersPatch, QuietHoursSchedule};
+use crate::models::error::{ApiResult, AppError, AppResult};
+use crate::services::auto_control_schedule::ManualFanProfile;
+use crate::services::decision_store::ControlHistoryQuery;
+use crate::services::fan_curve_replay::{self, FanCurve};
+use crate::services::fan_snapshot_store::FanSpeedSnapshot;
+use crate::services::parameter_diff::ParameterChangeSource;
+use crate::services::pid_autotune::{self, RelayTuneRequest};
+use crate::services::target_estimate::{self, TargetEstimateQuery};
+use crate::AppState;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// 继电器自整定，返回建议的PID参数（需要API密钥）
+///
+/// 实验期间直接下发风扇转速，结束后交还BMC自动模式；结果不会自动应用。
+/// 同一时间只允许一次自整定
+///
+/// POST /api/v1/control/pid/tune
+pub async fn pid_autotune(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<RelayTuneRequest>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+    if data.config.read_only {
+        return Err(AppError::read_only_error("PID自整定").into());
+    }
+
+    let _running = data.pid_autotune.try_lock().map_err(|_| AppError::ConflictError {
+        message: "已有PID自整定正在进行".to_string(),
+    })?;
+    let critical_temperature = data.config.control.emergency_exit.critical_temperature;
+    let result = pid_autotune::run(&data.ipmi_service, &body, critical_temperature).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(result)))
+}
+
+/// 估算改用候选目标温度后的风扇占空比、功耗和噪音
+///
+/// 根据最近的控制决策推算，不修改当前配置
+///
+/// GET /api/v1/control/estimate?temp_target=60
+pub async fn estimate_target(
+    data: web::Data<AppState>,
+    query: web::Query<TargetEstimateQuery>,
+) -> ApiResult<HttpResponse> {
+    query.validate(data.config.control.emergency_exit.critical_temperature)?;
+    let decisions = data.control_decisions.recent(None).await;
+    let estimate = target_estimate::estimate(&decisions, query.temp_target)?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(estimate)))
+}
+
+/// 风扇曲线回放请求
+#[derive(Debug, Deserialize)]
+pub struct FanCurveValidateRequest {
+    /// 候选风扇曲线
+    pub curve: FanCurve,
+    /// 回放开始时间（默认结束时间前7天）
+    pub start_time: Option<DateTime<Utc>>,
+    /// 回放结束时间（默认当前时间）
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+/// 用历史温度回放候选风扇曲线
+///
+/// 读数取自温度分布采集窗口内保留的读数，黑名单中的传感器不参与；不下发任何转速
+///
+/// POST /api/v1/control/fan/{fan_id}/curve/validate
+pub async fn validate_fan_curve(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+    body: web::Json<FanCurveValidateRequest>,
+) -> ApiResult<HttpResponse> {
+    let fan_id = path.into_inner();
+    let request = body.into_inner();
+    let end_time = request.end_time.unwrap_or_else(Utc::now);
+    let start_time = request.start_time.unwrap_or(end_time - chrono::Duration::days(7));
+
+    let mut readings = data.temperature_histograms.readings(start_time, end_time);
+    readings.retain(|sensor_id, _| !data.sensor_blacklist.contains(sensor_id));
+    let target_temperature = data.control_loop.parameters().target_temperature;
+    let replay = fan_curve_replay::replay(&fan_id, &request.curve, &readings, target_temperature, start_time, end_time)?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(replay)))
+}
+
+/// 控制历史查询参数
+#[derive(Debug, Deserialize)]
+pub struct ControlHistoryParams {
+    /// 动作类型，逗号分隔，多个取并集，如 `emergency_cooling,manual_fan_control`
+    pub action_type: Option<String>,
+    /// 目标风扇ID
+    pub target_id: Option<String>,
+    /// 开始时间（含）
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间（不含）
+    pub end_time: Option<DateTime<Utc>>,
+    /// 页码，从1开始
+    pub page: Option<i32>,
+    /// 每页条数，默认100
+    pub page_size: Option<i32>,
+}
+
+/// 按动作类型、目标风扇和时间分页查询控制历史，最近的在前
+///
+/// GET /api/v1/control/history?action_type=emergency_cooling&target_id=FAN3
+pub async fn get_control_history(
+    data: web::Data<AppState>,
+    params: web::Query<ControlHistoryParams>,
+) -> ApiResult<HttpResponse> {
+    let params = params.into_inner();
+    let action_types = params
+        .action_type
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|action_type| !action_type.trim().is_empty())
+        .map(str::parse)
+        .collect::<AppResult<Vec<ControlActionType>>>()?;
+    if let (Some(start), Some(end)) = (params.start_time, params.end_time) {
+        if start >= end {
+            return Err(AppError::validation_error("start_time", "开始时间必须早于结束时间").into());
+        }
+    }
+
+    let query = ControlHistoryQuery {
+        action_types,
+        target_id: params.target_id.filter(|target_id| !target_id.is_empty()),
+        start_time: params.start_time,
+        end_time: params.end_time,
+        page: params.page.unwrap_or(1),
+        page_size: params.page_size.unwrap_or(100),
+    };
+    let (decisions, total) = data.control_decisions.history(&query).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(PaginatedResponse::new(
+        decisions,
+        total,
+        query.page,
+        query.page_size,
+    ))))
+}
+
+/// 最近控制决策查询参数
+#[derive(Debug, Deserialize)]
+pub struct RecentDecisionsParams {
+    /// 返回条数上限，为空时返回环形缓冲中的全部决策
+    pub limit: Option<usize>,
+}
+
+/// 环形缓冲中最近的控制决策，按时间倒序，包含输入温度、目标温度、PID各分量与限幅前后的输出
+///
+/// GET /api/v1/control/decisions?limit=20
+pub async fn get_recent_decisions(
+    data: web::Data<AppState>,
+    params: web::Query<RecentDecisionsParams>,
+) -> ApiResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(ApiResponse::success(data.control_decisions.recent(params.limit).await)))
+}
+
+/// 手动退出紧急冷却请求
+#[derive(Debug, Default, Deserialize)]
+pub struct EmergencyExitRequest {
+    /// 跳过温度确认强制退出
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// 手动退出紧急冷却（需要API密钥）
+///
+/// 最高温度需低于 `临界温度 - 回差` 并持续确认时间，否则返回409；`force=true` 时跳过检查
+///
+/// POST /api/v1/control/emergency/exit
+pub async fn exit_emergency(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: Option<web::Json<EmergencyExitRequest>>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+    let force = body.map(|body| body.force).unwrap_or_default();
+
+    data.control_loop.exit_emergency(force, Utc::now()).await?;
+    if force {
+        tracing::warn!(target: "audit", "强制退出紧急冷却");
+    } else {
+        tracing::info!(target: "audit", "手动退出紧急冷却");
+    }
+    Ok(HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+        "exited": true,
+        "forced": force,
+    }))))
+}
+
+/// 重启控制循环（需要API密钥）
+///
+/// 只重启控制循环，不重启进程；控制循环未启动时返回409
+///
+/// POST /api/v1/system/restart-control
+pub async fn restart_control_loop(req: HttpRequest, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let loop_started_at = data.control_loop.restart().await?;
+    tracing::info!(target: "audit", "重启控制循环，新的启动时间 {}", loop_started_at.to_rfc3339());
+    Ok(HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+        "loop_started_at": loop_started_at,
+    }))))
+}
+
+/// 当前控制参数
+///
+/// GET /api/v1/control/parameters
+pub async fn get_control_parameters(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(ApiResponse::success(data.control_loop.parameters())))
+}
+
+/// 控制参数更新查询参数
+#[derive(Debug, Deserialize)]
+pub struct ParameterUpdateParams {
+    /// 变更来源，默认 `manual`
+    #[serde(default)]
+    pub source: ParameterChangeSource,
+}
+
+/// 更新控制参数（需要API密钥）
+///
+/// 下一个控制周期起生效，返回新参数及只含变化字段的差异，差异同时记入参数变更历史
+///
+/// PUT /api/v1/control/parameters?source=optimization
+pub async fn update_control_parameters(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    params: web::Query<ParameterUpdateParams>,
+    body: web::Json<ControlParameters>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+    if data.config.read_only {
+        return Err(AppError::read_only_error("更新控制参数").into());
+    }
+
+    let applied = data
+        .control_loop
+        .update_parameters(body.into_inner(), params.source)
+        .await?;
+    let fields: Vec<&str> = applied.diff.changes.iter().map(|change| change.field.as_str()).collect();
+    tracing::info!(target: "audit", "更新控制参数: {:?}", fields);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(applied)))
+}
+
+/// 部分更新控制参数（需要API密钥）
+///
+/// 只修改请求体中给出的字段，合并结果校验失败时不生效；差异同样记入参数变更历史
+///
+/// PATCH /api/v1/control/parameters?source=manual
+pub async fn patch_control_parameters(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    params: web::Query<ParameterUpdateParams>,
+    body: web::Json<ControlParametersPatch>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+    if data.config.read_only {
+        return Err(AppError::read_only_error("更新控制参数").into());
+    }
+
+    let applied = data.control_loop.patch_parameters(&body, params.source).await?;
+    let fields: Vec<&str> = applied.diff.changes.iter().map(|change| change.field.as_str()).collect();
+    tracing::info!(target: "audit", "部分更新控制参数: {:?}", fields);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(applied)))
+}
+
+/// 参数变更历史查询参数
+#[derive(Debug, Deserialize)]
+pub struct ParameterHistoryParams {
+    /// 返回条数上限，为空时返回全部
+    pub limit: Option<usize>,
+}
+
+/// 控制参数变更历史，按时间倒序
+///
+/// GET /api/v1/control/parameters/history
+pub async fn get_parameter_history(
+    data: web::Data<AppState>,
+    query: web::Query<ParameterHistoryParams>,
+) -> ApiResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(ApiResponse::success(data.control_loop.parameter_history(query.limit))))
+}
+
+/// 当前静音时段计划
+///
+/// GET /api/v1/control/schedule
+pub async fn get_quiet_hours(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(ApiResponse::success(data.control_loop.quiet_hours().schedule())))
+}
+
+/// 更新静音时段计划（需要API密钥）
+///
+/// 写入计划文件后从下一个控制周期起生效，重启后保留
+///
+/// PUT /api/v1/control/schedule
+pub async fn update_quiet_hours(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<QuietHoursSchedule>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let schedule = data.control_loop.quiet_hours().update(body.into_inner()).await?;
+    tracing::info!(
+        target: "audit",
+        "更新静音时段计划: enabled={}，{} 个时段",
+        schedule.enabled,
+        schedule.windows.len()
+    );
+    Ok(HttpResponse::Ok().json(ApiResponse::success(schedule)))
+}
+
+/// 应用控制策略请求
+#[derive(Debug, Default, Deserialize)]
+pub struct ControlStrategyRequest {
+    /// 手动转速档案，为空时恢复自动控制
+    #[serde(default)]
+    pub manual_profile: Option<ManualFanProfile>,
+}
+
+/// 应用控制策略（需要API密钥）
+///
+/// 手动档案一次下发到全部可调速风扇，任一风扇失败时已下发的风扇恢复到原转速并返回错误；
+/// 成功后控制循环按该档案下发，直到以空档案恢复自动控制
+///
+/// POST /api/v1/control/strategy/apply
+pub async fn apply_control_strategy(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<ControlStrategyRequest>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+    if data.config.read_only {
+        return Err(AppError::read_only_error("应用控制策略").into());
+    }
+
+    let manual_profile = body.into_inner().manual_profile;
+    let mode = if manual_profile.is_some() { "manual" } else { "automatic" };
+    let applied_fans = data.control_loop.apply_manual_profile(manual_profile).await?;
+    tracing::info!(target: "audit", "应用控制策略: {}，下发风扇 {:?}", mode, applied_fans);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+        "mode": mode,
+        "applied_fans": applied_fans,
+    }))))
+}
+
+/// 保存风扇转速快照（需要API密钥）
+///
+/// 记录各可调速风扇当前的下发转速，快照在 `control.fan_snapshot_ttl_hours` 后过期
+///
+/// POST /api/v1/control/fans/snapshot
+pub async fn snapshot_fan_speeds(req: HttpRequest, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let now = Utc::now();
+    if let Err(e) = data.fan_snapshots.purge_expired(now).await {
+        tracing::warn!("清理过期风扇转速快照失败: {}", e);
+    }
+    let snapshot = FanSpeedSnapshot {
+        id: uuid::Uuid::new_v4().to_string(),
+        speeds: data.control_loop.fan_speeds().await?,
+        created_at: now,
+        expires_at: now + chrono::Duration::hours(data.config.control.fan_snapshot_ttl_hours),
+    };
+    data.fan_snapshots.save(&snapshot).await?;
+    tracing::info!(target: "audit", "保存风扇转速快照 {}: {:?}", snapshot.id, snapshot.speeds);
+    Ok(HttpResponse::Created().json(ApiResponse::success(snapshot)))
+}
+
+/// 恢复风扇转速快照（需要API密钥）
+///
+/// 快照不存在、已过期或其中的风扇已不可调速时返回404；任一风扇下发失败时已下发的风扇恢复到原转速。
+/// 成功后控制循环按快照转速保持，直到通过 `/control/strategy/apply` 恢复自动控制
+///
+/// POST /api/v1/control/fans/restore/{snapshot_id}
+pub async fn restore_fan_snapshot(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+    if data.config.read_only {
+        return Err(AppError::read_only_error("恢复风扇转速快照").into());
+    }
+
+    let snapshot_id = path.into_inner();
+    let snapshot = data
+        .fan_snapshots
+        .get(&snapshot_id, Utc::now())
+        .await?
+        .ok_or_else(|| AppError::not_found_error("风扇转速快照", snapshot_id.as_str()))?;
+    let restored_fans = data.control_loop.restore_fan_speeds(&snapshot.speeds).await?;
+    tracing::info!(target: "audit", "恢复风扇转速快照 {}，下发风扇 {:?}", snapshot.id, restored_fans);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+        "snapshot_id": snapshot.id,
+        "speeds": snapshot.speeds,
+        "restored_fans": restored_fans,
+    }))))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::control::{ControlActionType, ControlDecision};
+    use crate::test_harness::{MockIpmiService, TestHarness};
+    use actix_web::http::StatusCode;
+    use crate::services::control_loop::ControlLoopLiveness;
+    use crate::services::control_supervisor::{ControlFailSafe, ControlLoopSupervisor};
+    use crate::services::poll_ticker::PollOverruns;
+    use chrono::{DateTime, Duration, Utc};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn decision(seconds_ago: i64, fan_id: &str, action_type: ControlActionType) -> ControlDecision {
+        ControlDecision {
+            timestamp: Utc::now() - Duration::seconds(seconds_ago),
+            sensor_id: "CPU1_TEMP".to_string(),
+            fan_id: fan_id.to_string(),
+            input_temperature: 70.0,
+            setpoint: 65.0,
+            proportional: 20.0,
+            integral: 0.0,
+            derivative: 0.0,
+            raw_output: 40.0,
+            limited_output: 40.0,
+            quiet_cap: None,
+            readback_percent: None,
+            action_type,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_control_history_filters_by_action_type_and_target() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        for decision in [
+            decision(50, "FAN1", ControlActionType::AutomaticControl),
+            decision(40, "FAN3", ControlActionType::EmergencyCooling),
+            decision(30, "FAN3", ControlActionType::ManualFanControl),
+            decision(20, "FAN1", ControlActionType::EmergencyCooling),
+            decision(10, "FAN3", ControlActionType::EmergencyCooling),
+        ] {
+            harness.state.control_decisions.record(decision).await;
+        }
+
+        let (status, body) = harness
+            .get("/api/v1/control/history?action_type=emergency_cooling&page_size=2")
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["total"], 3);
+        assert_eq!(body["data"]["total_pages"], 2);
+        let items = body["data"]["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item["action_type"] == "emergency_cooling"));
+
+        // 最新的排在最前
+        let (_, body) = harness.get("/api/v1/control/history?target_id=FAN3").await;
+        assert_eq!(body["data"]["total"], 3);
+        let actions: Vec<&str> = body["data"]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["action_type"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, ["emergency_cooling", "manual_fan_control", "emergency_cooling"]);
+
+        let (_, body) = harness
+            .get("/api/v1/control/history?action_type=emergency_cooling&target_id=FAN1")
+            .await;
+        assert_eq!(body["data"]["total"], 1);
+
+        let (status, _) = harness.get("/api/v1/control/history?action_type=cooling").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_manual_emergency_exit_refused_while_hot_unless_forced() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.ipmi.set_temperature("CPU1_TEMP", 86.0);
+        assert!(harness.state.control_loop.tick(Utc::now()).await.unwrap().emergency);
+
+        let (status, body) = harness.post_json("/api/v1/control/emergency/exit", json!({})).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body["error"].as_str().unwrap().contains("force=true"));
+
+        let (status, body) = harness
+            .post_json("/api/v1/control/emergency/exit", json!({ "force": true }))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["forced"], true);
+
+        let (status, _) = harness.post_json("/api/v1/control/emergency/exit", json!({})).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    async fn test_apply_strategy_switches_loop_to_manual_profile_and_back() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let (status, body) = harness
+            .post_json(
+                "/api/v1/control/strategy/apply",
+                json!({ "manual_profile": { "default_percent": 70.0, "fans": { "FAN2": 55.0 } } }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["mode"], "manual");
+        assert_eq!(body["data"]["applied_fans"], json!(["FAN1", "FAN2"]));
+        assert_eq!(
+            harness.ipmi.fan_speed_writes(),
+            [("FAN1".to_string(), 70), ("FAN2".to_string(), 55)]
+        );
+        let tick = harness.state.control_loop.tick(Utc::now()).await.unwrap();
+        assert_eq!(tick.action_type, Some(ControlActionType::ManualFanControl));
+
+        let (status, _) = harness
+            .post_json("/api/v1/control/strategy/apply", json!({ "manual_profile": { "default_percent": 120.0 } }))
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = harness.post_json("/api/v1/control/strategy/apply", json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["mode"], "automatic");
+        let tick = harness.state.control_loop.tick(Utc::now()).await.unwrap();
+        assert_eq!(tick.action_type, Some(ControlActionType::AutomaticControl));
+    }
+
+    #[actix_web::test]
+    async fn test_restore_snapshot_returns_fans_to_snapshot_speeds() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness
+            .post_json(
+                "/api/v1/control/strategy/apply",
+                json!({ "manual_profile": { "default_percent": 40.0, "fans": { "FAN2": 35.0 } } }),
+            )
+            .await;
+        let (status, body) = harness.post_json("/api/v1/control/fans/snapshot", json!({})).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["data"]["speeds"], json!({ "FAN1": 40.0, "FAN2": 35.0 }));
+        let snapshot_id = body["data"]["id"].as_str().unwrap().to_string();
+
+        harness
+            .post_json("/api/v1/control/strategy/apply", json!({ "manual_profile": { "default_percent": 90.0 } }))
+            .await;
+        let (status, body) = harness
+            .post_json(&format!("/api/v1/control/fans/restore/{}", snapshot_id), json!({}))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["restored_fans"], json!(["FAN1", "FAN2"]));
+        assert_eq!(
+            harness.ipmi.fan_speed_writes()[4..],
+            [("FAN1".to_string(), 40), ("FAN2".to_string(), 35)]
+        );
+        assert_eq!(
+            harness.state.control_loop.fan_speeds().await.unwrap(),
+            [("FAN1".to_string(), 40.0), ("FAN2".to_string(), 35.0)].into_iter().collect()
+        );
+
+        let (status, _) = harness.post_json("/api/v1/control/fans/restore/missing", json!({})).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_loop_tick_records_decision_with_pid_breakdown() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.ipmi.set_temperature("CPU2_TEMP", 75.0);
+        harness.state.control_loop.tick(Utc::now()).await.unwrap();
+
+        let (status, body) = harness.get("/api/v1/control/decisions?limit=1").await;
+        assert_eq!(status, StatusCode::OK);
+        let decisions = body["data"].as_array().unwrap();
+        assert_eq!(decisions.len(), 1);
+        let decision = &decisions[0];
+        assert_eq!(decision["fan_id"], "FAN2");
+        assert_eq!(decision["sensor_id"], "CPU2_TEMP");
+        assert_eq!(decision["input_temperature"], 75.0);
+        assert_eq!(decision["setpoint"], 65.0);
+        assert_eq!(decision["action_type"], "automatic_control");
+        // 比例项按偏差 10°C × kp 计算，原始输出为最低转速加各分量之和
+        assert_eq!(decision["proportional"], 40.0);
+        let terms: f64 = ["proportional", "integral", "derivative"]
+            .iter()
+            .map(|term| decision[term].as_f64().unwrap())
+            .sum();
+        let floor = f64::from(harness.state.control_loop.parameters().min_fan_speed);
+        assert!((decision["raw_output"].as_f64().unwrap() - floor - terms).abs() < 1e-9);
+        assert!(decision["limited_output"].as_f64().unwrap() <= 100.0);
+
+        let (_, body) = harness.get("/api/v1/control/decisions").await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_quiet_hours_schedule_is_updated_through_the_loop() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let (status, body) = harness.get("/api/v1/control/schedule").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["enabled"], false);
+
+        let schedule = json!({
+            "enabled": true,
+            "windows": [{ "name": "night", "start": "22:00:00", "end": "07:00:00", "max_fan_percent": 45.0 }],
+            "emergency_temperature": 80.0,
+        });
+        let (status, body) = harness.put_json("/api/v1/control/schedule", schedule).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["windows"][0]["name"], "night");
+        assert!(harness.state.control_loop.quiet_hours().schedule().enabled);
+
+        let (_, body) = harness.get("/api/v1/control/schedule").await;
+        assert_eq!(body["data"]["emergency_temperature"], 80.0);
+
+        let (status, body) = harness
+            .put_json(
+                "/api/v1/control/schedule",
+                json!({
+                    "enabled": true,
+                    "windows": [{ "name": "night", "start": "22:00:00", "end": "07:00:00", "max_fan_percent": 120.0 }],
+                    "emergency_temperature": 80.0,
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("night"));
+    }
+
+    #[actix_web::test]
+    async fn test_changing_only_kp_records_a_single_field_diff() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let (_, body) = harness.get("/api/v1/control/parameters").await;
+        let mut parameters = body["data"].clone();
+        let old_kp = parameters["pid_params"]["kp"].clone();
+        parameters["pid_params"]["kp"] = json!(1.5);
+
+        let (status, body) = harness.put_json("/api/v1/control/parameters", parameters.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        let expected = json!([{ "field": "pid_params.kp", "old": old_kp, "new": 1.5 }]);
+        assert_eq!(body["data"]["diff"]["changes"], expected);
+        assert_eq!(harness.state.control_loop.parameters().pid_params.unwrap().kp, 1.5);
+
+        let (status, body) = harness
+            .put_json("/api/v1/control/parameters?source=preset", parameters.clone())
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["diff"]["source"], "preset");
+        assert_eq!(body["data"]["diff"]["changes"], json!([]));
+
+        parameters["min_fan_speed"] = json!(120);
+        let (status, _) = harness.put_json("/api/v1/control/parameters", parameters).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, body) = harness.get("/api/v1/control/parameters/history").await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+        assert_eq!(body["data"][1]["source"], "manual");
+        assert_eq!(body["data"][1]["changes"], expected);
+    }
+
+    #[actix_web::test]
+    async fn test_patch_changes_only_given_fields_and_rejects_invalid_merge() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let before = harness.state.control_loop.parameters();
+
+        let (status, body) = harness
+            .call(
+                actix_web::test::TestRequest::patch()
+                    .uri("/api/v1/control/parameters")
+                    .set_json(json!({ "target_temperature": 60.0 })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["data"]["diff"]["changes"],
+            json!([{ "field": "target_temperature", "old": before.target_temperature, "new": 60.0 }])
+        );
+        let after = harness.state.control_loop.parameters();
+        assert_eq!(after.target_temperature, 60.0);
+        assert_eq!(after.warning_temperature, before.warning_temperature);
+        assert_eq!(after.pid_params.unwrap().kp, before.pid_params.unwrap().kp);
+
+        // 合并后目标温度高于警告温度，整体拒绝
+        let (status, body) = harness
+            .call(
+                actix_web::test::TestRequest::patch()
+                    .uri("/api/v1/control/parameters")
+                    .set_json(json!({ "target_temperature": before.warning_temperature + 5.0 })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("warning_temperature"));
+        assert_eq!(harness.state.control_loop.parameters().target_temperature, 60.0);
+    }
+
+    #[actix_web::test]
+    async fn test_put_pid_gains_changes_subsequent_loop_output() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.ipmi.set_temperature("CPU2_TEMP", 75.0);
+        harness.state.control_loop.tick(Utc::now()).await.unwrap();
+        let before = harness.state.control_decisions.recent(Some(1)).await.remove(0);
+        assert_eq!(before.proportional, 40.0);
+
+        let (_, body) = harness.get("/api/v1/control/parameters").await;
+        let mut parameters = body["data"].clone();
+        parameters["pid_params"] = json!({ "kp": 8.0, "ki": 0.0, "kd": 0.0, "integral_limit": 50.0 });
+        let (status, _) = harness.put_json("/api/v1/control/parameters", parameters).await;
+        assert_eq!(status, StatusCode::OK);
+
+        harness.state.control_loop.tick(Utc::now() + Duration::seconds(5)).await.unwrap();
+        let after = harness.state.control_decisions.recent(Some(1)).await.remove(0);
+        assert_eq!((before.fan_id.as_str(), after.fan_id.as_str()), ("FAN2", "FAN2"));
+        assert_eq!(after.proportional, 80.0);
+        assert_eq!(after.integral, 0.0);
+        assert!(after.limited_output > before.limited_output);
+    }
+
+    #[actix_web::test]
+    async fn test_restart_control_advances_start_time_and_loop_resumes() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let (status, _) = harness.post_json("/api/v1/system/restart-control", json!({})).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let supervisor = Arc::new(ControlLoopSupervisor::new(
+            harness.ipmi.clone(),
+            Arc::clone(&harness.state.system_events),
+            ControlFailSafe::SafeFloor,
+            40,
+        ));
+        let first_started_at = Utc::now();
+        let running = harness.state.control_loop.start(supervisor, PollOverruns::default());
+        wait_for_running(&harness).await;
+
+        let (status, body) = harness.post_json("/api/v1/system/restart-control", json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        let restarted_at: DateTime<Utc> = serde_json::from_value(body["data"]["loop_started_at"].clone()).unwrap();
+        assert!(restarted_at > first_started_at);
+        // 间隙内风扇交还BMC自动控制
+        assert_eq!(harness.ipmi.automatic_restores(), 1);
+
+        wait_for_running(&harness).await;
+        running.abort();
+    }
+
+    async fn wait_for_running(harness: &TestHarness) {
+        for _ in 0..100 {
+            if harness.state.control_loop.liveness(Utc::now()) == ControlLoopLiveness::Running {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("控制循环未恢复运行");
+    }
+
+    #[actix_web::test]
+    async fn test_fan_curve_validate_replays_sampled_temperatures() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let start = Utc::now() - Duration::minutes(10);
+        for (minute, temperature) in [45.0, 55.0, 70.0].into_iter().enumerate() {
+            harness.ipmi.set_temperature("CPU1_TEMP", temperature);
+            let sensors = harness.state.ipmi_service.get_temperature_sensors().unwrap();
+            harness
+                .state
+                .temperature_histograms
+                .record(&sensors, start + Duration::minutes(minute as i64));
+        }
+
+        let curve = json!({
+            "points": [
+                { "temperature": 40.0, "fan_speed_percentage": 20.0 },
+                { "temperature": 80.0, "fan_speed_percentage": 100.0 }
+            ]
+        });
+        let (status, body) = harness
+            .post_json("/api/v1/control/fan/FAN1/curve/validate", json!({ "curve": curve }))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["fan_id"], "FAN1");
+        assert_eq!(body["data"]["evaluated_samples"], 3);
+        // 按最高温度 50(CPU2) 55 70°C 换算为 40 50 80%，70°C高于目标温度65°C
+        let samples: Vec<u64> = body["data"]["speed_distribution"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bucket| bucket["samples"].as_u64().unwrap())
+            .collect();
+        assert_eq!(samples, vec![0, 0, 0, 0, 1, 1, 0, 0, 1, 0]);
+        assert_eq!(body["data"]["over_target_samples"], 1);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::models::api::ApiResponse;
+use crate::models::control::{ControlDecision, ControlParameters};
+use crate::models::error::ApiResult;
+use crate::models::Alert;
+use crate::services::control_loop::ControlLoopLiveness;
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::sensor_cache::CachedTemperature;
+use crate::AppState;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 仪表盘中的传感器读数
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardSensor {
+    /// 传感器ID
+    pub sensor_id: String,
+    /// 归一化位置
+    pub location: String,
+    /// 温度（°C）
+    pub temperature: f64,
+    /// 距最后一次成功读取的秒数
+    pub age_secs: i64,
+    /// 在传感器黑名单中，不计入健康评分
+    pub excluded: bool,
+}
+
+/// 仪表盘中的告警汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardAlerts {
+    /// 未解决告警总数
+    pub active: usize,
+    /// 按严重程度统计的未解决告警数
+    pub by_severity: BTreeMap<String, usize>,
+}
+
+/// 仪表盘中的控制状态
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardControl {
+    /// 控制循环运行状态
+    pub liveness: ControlLoopLiveness,
+    /// 目标温度（°C）
+    pub target_temperature: f64,
+    /// 临界温度（°C）
+    pub critical_temperature: f64,
+    /// 最近一次控制决策
+    pub last_decision: Option<ControlDecision>,
+}
+
+/// 仪表盘中的健康评分
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardHealth {
+    /// 健康评分，0-100
+    pub score: u8,
+    /// `healthy`（80分及以上）、`warning`（50分及以上）或 `critical`
+    pub status: &'static str,
+    /// 扣分原因
+    pub issues: Vec<String>,
+}
+
+/// 仪表盘数据
+#[derive(Debug, Clone, Serialize)]
+pub struct Dashboard {
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+    /// 最近一次成功读数，按传感器ID排序
+    pub sensors: Vec<DashboardSensor>,
+    /// 告警汇总
+    pub alerts: DashboardAlerts,
+    /// 控制状态
+    pub control: DashboardControl,
+    /// 健康评分
+    pub health: DashboardHealth,
+}
+
+/// 汇总仪表盘数据
+///
+/// 健康评分从100分起扣：未排除的传感器达到临界温度扣25分、达到警告温度扣10分，
+/// 未解决告警按严重程度扣分（Critical 15、Error 10、Warning 5），控制循环卡住扣30分
+///
+/// # 参数
+/// * `now` - 当前时间
+/// * `readings` - 监控缓存中的读数
+/// * `sensor_blacklist` - 传感器黑名单
+/// * `active_alerts` - 未解决的告警
+/// * `parameters` - 当前控制参数
+/// * `liveness` - 控制循环运行状态
+/// * `last_decision` - 最近一次控制决策
+pub fn build_dashboard(
+    now: DateTime<Utc>,
+    readings: Vec<CachedTemperature>,
+    sensor_blacklist: &SensorBlacklist,
+    active_alerts: &[Alert],
+    parameters: &ControlParameters,
+    liveness: ControlLoopLiveness,
+    last_decision: Option<ControlDecision>,
+) -> Dashboard {
+    let mut penalty = 0u32;
+    let mut issues = Vec::new();
+
+    let sensors: Vec<DashboardSensor> = readings
+        .into_iter()
+        .map(|reading| {
+            let sensor = reading.sensor;
+            let excluded = sensor_blacklist.contains(&sensor.sensor_id);
+            if !excluded && sensor.temperature >= parameters.critical_temperature {
+                penalty += 25;
+                issues.push(format!("{} 达到临界温度: {:.1}°C", sensor.sensor_id, sensor.temperature));
+            } else if !excluded && sensor.temperature >= parameters.warning_temperature {
+                penalty += 10;
+                issues.push(format!("{} 温度偏高: {:.1}°C", sensor.sensor_id, sensor.temperature));
+            }
+            DashboardSensor {
+                sensor_id: sensor.sensor_id,
+                location: sensor.location,
+                temperature: sensor.temperature,
+                age_secs: reading.age_secs,
+                excluded,
+            }
+        })
+        .collect();
+
+    let mut by_severity = BTreeMap::new();
+    for alert in active_alerts {
+        *by_severity.entry(alert.severity.clone()).or_insert(0) += 1;
+        penalty += match alert.severity.as_str() {
+            "Critical" => 15,
+            "Error" => 10,
+            "Warning" => 5,
+            _ => 0,
+        };
+    }
+    if !active_alerts.is_empty() {
+        issues.push(format!("{} 条未解决告警", active_alerts.len()));
+    }
+
+    if liveness == ControlLoopLiveness::Stalled {
+        penalty += 30;
+        issues.push("控制循环超过两个控制周期未完成控制".to_string());
+    }
+
+    let score = 100u32.saturating_sub(penalty) as u8;
+    let status = match score {
+        80.. => "healthy",
+        50.. => "warning",
+        _ => "critical",
+    };
+
+    Dashboard {
+        generated_at: now,
+        sensors,
+        alerts: DashboardAlerts {
+            active: active_alerts.len(),
+            by_severity,
+        },
+        control: DashboardControl {
+            liveness,
+            target_temperature: parameters.target_temperature,
+            critical_temperature: parameters.critical_temperature,
+            last_decision,
+        },
+        health: DashboardHealth { score, status, issues },
+    }
+}
+
+/// 获取仪表盘数据：最近的传感器读数、未解决告警数、控制状态和健康评分
+///
+/// 传感器读数取自告警监控轮询维护的最后一次成功读数缓存，不读取BMC
+///
+/// GET /api/v1/dashboard
+pub async fn get_dashboard(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let now = Utc::now();
+    let dashboard = build_dashboard(
+        now,
+        data.sensor_cache.last_good(now),
+        &data.sensor_blacklist,
+        &data.alerts.active(),
+        &data.control_loop.parameters(),
+        data.control_loop.liveness(now),
+        data.control_decisions.recent(Some(1)).await.into_iter().next(),
+    );
+    Ok(HttpResponse::Ok().json(ApiResponse::success(dashboard)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::alert::AlertSeverity;
+    use crate::services::alert_store::NewAlert;
+    use crate::services::sensor_source::SensorSource;
+    use crate::test_harness::{MockIpmiService, TestHarness};
+    use actix_web::http::StatusCode;
+    use chrono::Utc;
+
+    #[actix_web::test]
+    async fn test_dashboard_reflects_cached_readings_alerts_and_control_state() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.ipmi.set_temperature("CPU1_TEMP", 78.0);
+        let sensors = harness.ipmi.get_temperature_sensors().unwrap();
+        harness.state.sensor_cache.read_through(Utc::now(), || Ok::<_, String>(sensors)).unwrap();
+        harness.state.alerts.raise(NewAlert {
+            alert_type: "temperature".to_string(),
+            severity: AlertSeverity::Warning,
+            title: "CPU1温度偏高".to_string(),
+            message: "CPU1_TEMP 78°C".to_string(),
+            source: "CPU1_TEMP".to_string(),
+            source_id: "CPU1_TEMP".to_string(),
+            rule_id: None,
+        });
+
+        // 缓存之后BMC读取失败，仪表盘不受影响
+        harness.ipmi.set_connected(false);
+        let (status, body) = harness.get("/api/v1/dashboard").await;
+        assert_eq!(status, StatusCode::OK);
+        let data = &body["data"];
+        for section in ["sensors", "alerts", "control", "health"] {
+            assert!(!data[section].is_null(), "缺少 {}", section);
+        }
+
+        let sensors = data["sensors"].as_array().unwrap();
+        assert_eq!(sensors.len(), 3);
+        let cpu1 = sensors.iter().find(|s| s["sensor_id"] == "CPU1_TEMP").unwrap();
+        assert_eq!(cpu1["temperature"], 78.0);
+
+        assert_eq!(data["alerts"]["active"], 1);
+        assert_eq!(data["alerts"]["by_severity"]["Warning"], 1);
+        assert_eq!(data["control"]["liveness"], "disabled");
+        assert_eq!(data["control"]["target_temperature"], 65.0);
+
+        // 78°C高于默认警告温度扣10分，Warning告警扣5分
+        assert_eq!(data["health"]["score"], 85);
+        assert_eq!(data["health"]["status"], "healthy");
+        assert_eq!(data["health"]["issues"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::models::error::{ApiResult, AppError};
+use crate::AppState;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use tokio_util::io::ReaderStream;
+
+/// 下载导出文件
+///
+/// 令牌由导出接口签发，签名无效或已过期返回403，文件已被清理返回404
+///
+/// GET /api/v1/downloads/{token}
+pub async fn download_export(data: web::Data<AppState>, token: web::Path<String>) -> ApiResult<HttpResponse> {
+    let path = data.downloads.resolve(&token, Utc::now())?;
+    let file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|_| AppError::not_found_error("导出文件", token.as_str()))?;
+
+    // 导出目录中的文件名为 `{随机ID}-{原文件名}`
+    let stored_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let filename = stored_name.split_once('-').map_or(stored_name, |(_, name)| name).to_string();
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/octet-stream")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(filename)],
+        })
+        .streaming(ReaderStream::new(file)))
+}
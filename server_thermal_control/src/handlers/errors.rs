@@ -0,0 +1,10 @@
+use crate::models::api::ApiResponse;
+use crate::models::error::{ApiResult, AppError};
+use actix_web::HttpResponse;
+
+/// 错误目录：所有错误代码及其HTTP状态码和说明
+///
+/// GET /api/v1/errors
+pub async fn list_error_codes() -> ApiResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(ApiResponse::success(AppError::catalog())))
+}
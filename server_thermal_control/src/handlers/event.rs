@@ -0,0 +1,97 @@
+use crate::models::error::{ApiResult, AppError};
+use crate::services::event_store::SystemEventQuery;
+use crate::{models, AppState};
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+
+/// 系统事件查询参数
+#[derive(Debug, Deserialize)]
+pub struct SystemEventListQuery {
+    /// 事件级别：`info`、`warning`、`critical`
+    pub level: Option<String>,
+    /// 事件类型，如 `alert_created`
+    pub event_type: Option<String>,
+    /// 页码，从1开始
+    pub page: Option<u32>,
+    /// 每页条数
+    pub page_size: Option<u32>,
+}
+
+/// 分页查询系统事件，最近的在前
+///
+/// GET /api/v1/events
+pub async fn list_events(
+    query: web::Query<SystemEventListQuery>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let query = query.into_inner();
+    let level = query.level.map(|level| level.to_ascii_lowercase());
+    if let Some(level) = level.as_deref() {
+        if !matches!(level, "info" | "warning" | "critical") {
+            return Err(AppError::validation_error("level", format!("未知的事件级别: {}", level)).into());
+        }
+    }
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 1000);
+
+    let (events, total) = data
+        .system_events
+        .query(&SystemEventQuery {
+            level,
+            event_type: query.event_type,
+            page: page as i32,
+            page_size: page_size as i32,
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        models::PaginatedResponse::new(events, total.max(0) as u64, page, page_size),
+        "Events retrieved successfully"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::alert::AlertSeverity;
+    use crate::services::alert_store::NewAlert;
+    use crate::test_harness::{MockIpmiService, TestHarness};
+
+    #[actix_web::test]
+    async fn test_alert_transitions_are_listed_as_system_events() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.state.alerts.start_event_task(harness.state.system_events.clone());
+        let (_, alert) = harness
+            .state
+            .alerts
+            .raise(NewAlert {
+                alert_type: "temperature".to_string(),
+                severity: AlertSeverity::Critical,
+                title: "高温告警".to_string(),
+                message: "CPU1_TEMP 温度 92.0°C".to_string(),
+                source: "CPU1_TEMP".to_string(),
+                source_id: "CPU1_TEMP".to_string(),
+                rule_id: Some("high_temperature".to_string()),
+            })
+            .unwrap();
+        harness.state.alerts.acknowledge(alert.id, "alice").unwrap();
+
+        // 事件由后台任务异步写入
+        let mut body = serde_json::Value::Null;
+        for _ in 0..50 {
+            (_, body) = harness.get("/api/v1/events?level=critical").await;
+            if body["data"]["total"] == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(body["data"]["total"], 2);
+        assert_eq!(body["data"]["data"][0]["event_type"], "alert_acknowledged");
+        assert_eq!(body["data"]["data"][0]["metadata"]["acknowledged_by"], "alice");
+        assert_eq!(body["data"]["data"][1]["event_type"], "alert_created");
+
+        let (_, body) = harness.get("/api/v1/events?event_type=alert_created&page_size=1").await;
+        assert_eq!(body["data"]["total"], 1);
+        let (status, _) = harness.get("/api/v1/events?level=fatal").await;
+        assert_eq!(status, actix_web::http::StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,6 +1,9 @@
+use crate::middleware::auth::require_api_key;
+use crate::models::error::{ApiResult, AppError};
 use crate::{models, AppState};
-use actix_web::{web, HttpResponse, Result};
+use actix_web::{web, HttpRequest, HttpResponse, Result};
 use chrono::Utc;
+use serde::Deserialize;
 use serde_json::json;
 
 /// 获取所有风扇数据
@@ -40,23 +43,73 @@ pub async fn list_fan_data(data: web::Data<AppState>) -> Result<HttpResponse> {
     }
 }
 
-/// 设置风扇转速
-/// 
-/// 注意：IPMI通常不支持直接设置风扇转速，这里返回模拟响应
+/// 设置风扇转速请求
+#[derive(Debug, Deserialize)]
+pub struct FanSpeedRequest {
+    /// 转速（百分比）
+    pub speed_percent: f64,
+}
+
+/// 设置风扇转速（需要API密钥）
+///
+/// 与上次下发的转速相同时不写入BMC，返回 `written: false`；不可调速的风扇返回400
 pub async fn set_fan_speed(
+    req: HttpRequest,
     path: web::Path<String>,
-    _body: web::Json<serde_json::Value>,
-    _data: web::Data<AppState>
-) -> Result<HttpResponse> {
+    body: web::Json<FanSpeedRequest>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+    if data.config.read_only {
+        return Err(AppError::read_only_error("设置风扇转速").into());
+    }
+
     let fan_id = path.into_inner();
-    
-    // IPMI通常不支持直接设置风扇转速，这里返回一个提示信息
-    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
-        json!({
-            "fan_id": fan_id,
-            "message": "Fan speed control via IPMI is typically not supported. Consider using system-level fan control utilities.",
-            "timestamp": Utc::now().to_rfc3339()
-        }),
-        "Fan speed control request processed"
-    )))
-}
\ No newline at end of file
+    let command = data
+        .control_loop
+        .set_fan_speed(&fan_id, body.speed_percent, Utc::now())
+        .await?;
+    if command.written {
+        tracing::info!(
+            target: "audit",
+            "设置风扇 {} 转速: {:.0}% -> {:.0}%",
+            fan_id,
+            command.previous_percent,
+            command.speed_percent
+        );
+    }
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(command, "Fan speed updated successfully")))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_harness::{MockIpmiService, TestHarness};
+    use actix_web::http::StatusCode;
+    use serde_json::json;
+
+    #[actix_web::test]
+    async fn test_commanding_current_speed_issues_no_ipmi_write() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let (status, body) = harness.post_json("/fans/FAN1/speed", json!({ "speed_percent": 60.0 })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["written"], true);
+        assert_eq!(harness.ipmi.fan_speed_writes(), [("FAN1".to_string(), 60)]);
+
+        let (status, body) = harness.post_json("/fans/FAN1/speed", json!({ "speed_percent": 60.2 })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["written"], false);
+        assert_eq!(harness.ipmi.fan_speed_writes().len(), 1);
+
+        let (_, body) = harness.get("/api/v1/control/decisions?limit=2").await;
+        let actions: Vec<&str> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|decision| decision["action_type"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, ["no_op", "manual_fan_control"]);
+
+        let (status, _) = harness.post_json("/fans/FAN9/speed", json!({ "speed_percent": 60.0 })).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::handlers::alert::request_actor;
+use crate::models::error::ApiResult;
+use crate::services::incident_store::IncidentStatus;
+use crate::{models, AppState};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+
+/// 事件列表查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct IncidentQuery {
+    /// 按状态过滤：`open`、`acknowledged`、`resolved`
+    pub status: Option<IncidentStatus>,
+}
+
+/// 获取事件列表，最近的在前
+///
+/// GET /api/v1/incidents
+pub async fn list_incidents(
+    query: web::Query<IncidentQuery>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let incidents = data.incidents.list(query.status);
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        incidents,
+        "Incidents retrieved successfully"
+    )))
+}
+
+/// 获取事件详情
+///
+/// GET /api/v1/incidents/{incident_id}
+pub async fn get_incident(
+    path: web::Path<uuid::Uuid>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let incident = data.incidents.get(path.into_inner())?;
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        incident,
+        "Incident retrieved successfully"
+    )))
+}
+
+/// 确认事件，代替逐条确认其中的告警
+///
+/// POST /api/v1/incidents/{incident_id}/acknowledge
+pub async fn acknowledge_incident(
+    req: HttpRequest,
+    path: web::Path<uuid::Uuid>,
+    ack_data: Option<web::Json<serde_json::Value>>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let incident_id = path.into_inner();
+    let acknowledged_by = request_actor(
+        &req,
+        &data.config,
+        &data.jwt_secrets,
+        ack_data.as_ref().and_then(|body| body.get("acknowledged_by")).and_then(|v| v.as_str()),
+        "acknowledged_by",
+    )?;
+
+    let incident = data.incidents.acknowledge(incident_id, acknowledged_by.clone())?;
+    tracing::info!(target: "audit", "事件已确认: incident_id={}, 操作人={}", incident_id, acknowledged_by);
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        incident,
+        "Incident acknowledged successfully"
+    )))
+}
+
+/// 解决事件
+///
+/// POST /api/v1/incidents/{incident_id}/resolve
+pub async fn resolve_incident(
+    req: HttpRequest,
+    path: web::Path<uuid::Uuid>,
+    resolve_data: Option<web::Json<serde_json::Value>>,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let incident_id = path.into_inner();
+    let resolved_by = request_actor(
+        &req,
+        &data.config,
+        &data.jwt_secrets,
+        resolve_data.as_ref().and_then(|body| body.get("resolved_by")).and_then(|v| v.as_str()),
+        "resolved_by",
+    )?;
+
+    let incident = data.incidents.resolve(incident_id, resolved_by.clone())?;
+    tracing::info!(target: "audit", "事件已解决: incident_id={}, 操作人={}", incident_id, resolved_by);
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        incident,
+        "Incident resolved successfully"
+    )))
+}
@@ -0,0 +1,116 @@
+use crate::middleware::auth::require_api_key;
+use crate::models::api::ApiResponse;
+use crate::models::error::{ApiResult, AppError, AppResult};
+use crate::services::fan_thresholds::check_fan_thresholds;
+use crate::services::ipmi_service::{ReadOnlyIpmi, ReadOnlyIpmiCommand};
+use crate::AppState;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+
+/// 原始IPMI输出查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawIpmiQuery {
+    /// 只读子命令，如 `sdr`、`mc info`
+    pub command: String,
+}
+
+/// 校验子命令并执行，不在允许列表中的子命令返回校验错误
+///
+/// # 参数
+/// * `ipmi` - 只读IPMI执行器
+/// * `command` - 请求的子命令
+pub fn run_allowed_command(ipmi: &dyn ReadOnlyIpmi, command: &str) -> AppResult<String> {
+    let command = ReadOnlyIpmiCommand::parse(command).ok_or_else(|| {
+        let allowed: Vec<String> = ReadOnlyIpmiCommand::ALL.iter().map(|c| c.as_command()).collect();
+        AppError::validation_error(
+            "command",
+            format!("不允许的IPMI命令 `{}`，仅支持: {}", command.trim(), allowed.join(", ")),
+        )
+    })?;
+
+    ipmi.run_read_only(command)
+        .map_err(|e| AppError::ipmi_error(format!("执行 `ipmitool {}` 失败: {}", command.as_command(), e)))
+}
+
+/// 执行只读ipmitool子命令并以纯文本返回原始输出（需要API密钥）
+///
+/// GET /api/v1/ipmi/raw?command=sdr
+pub async fn get_raw_output(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<RawIpmiQuery>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let ipmi_service = data.ipmi_service.clone();
+    let command = query.into_inner().command;
+    let output = web::block(move || run_allowed_command(&ipmi_service, &command))
+        .await
+        .map_err(|e| AppError::internal_server_error(format!("执行IPMI命令失败: {}", e)))??;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(output))
+}
+
+/// 获取BMC的 `mc info` 完整信息，包括厂商ID、附加设备功能和辅助固件版本
+///
+/// GET /api/v1/ipmi/info
+pub async fn get_ipmi_info(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let ipmi_service = data.ipmi_service.clone();
+    let info = web::block(move || ipmi_service.get_system_info().map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| AppError::internal_server_error(format!("读取IPMI系统信息失败: {}", e)))?
+        .map_err(|e| AppError::ipmi_error(format!("读取IPMI系统信息失败: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(info.mc_info)))
+}
+
+/// 对比配置的风扇转速范围与BMC风扇阈值，列出会触发BMC告警的配置
+///
+/// GET /api/v1/ipmi/fan-thresholds
+pub async fn get_fan_threshold_check(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let ipmi_service = data.ipmi_service.clone();
+    let min_rpm = data.config.control.fan_min_speed as f64;
+    let max_rpm = data.config.control.fan_max_speed as f64;
+    let report = web::block(move || check_fan_thresholds(&ipmi_service, min_rpm, max_rpm))
+        .await
+        .map_err(|e| AppError::internal_server_error(format!("读取BMC风扇阈值失败: {}", e)))??;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(report)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeIpmi {
+        calls: Mutex<Vec<ReadOnlyIpmiCommand>>,
+    }
+
+    impl ReadOnlyIpmi for FakeIpmi {
+        fn run_read_only(&self, command: ReadOnlyIpmiCommand) -> Result<String, Box<dyn std::error::Error>> {
+            self.calls.lock().unwrap().push(command);
+            Ok(format!("output of {}\n", command.as_command()))
+        }
+    }
+
+    #[test]
+    fn test_allowed_command_returns_output_and_others_rejected() {
+        let ipmi = FakeIpmi::default();
+
+        assert_eq!(run_allowed_command(&ipmi, "sdr").unwrap(), "output of sdr\n");
+        assert_eq!(run_allowed_command(&ipmi, "  MC   info ").unwrap(), "output of mc info\n");
+
+        for rejected in ["chassis power off", "sdr; reboot", "sel clear", "mc", "sdr elist", "raw 0x30 0x30", ""] {
+            let err = run_allowed_command(&ipmi, rejected).unwrap_err();
+            assert_eq!(err.status_code(), 400, "{:?}", rejected);
+        }
+        assert_eq!(
+            *ipmi.calls.lock().unwrap(),
+            vec![ReadOnlyIpmiCommand::Sdr, ReadOnlyIpmiCommand::McInfo]
+        );
+    }
+}
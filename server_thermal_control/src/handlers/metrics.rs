@@ -0,0 +1,38 @@
+use crate::middleware::auth::require_api_key;
+use crate::models::api::ApiResponse;
+use crate::models::error::{ApiResult, AppError};
+use crate::services::metrics_export::{collect_openmetrics, OPENMETRICS_CONTENT_TYPE};
+use crate::AppState;
+use actix_web::{web, HttpRequest, HttpResponse};
+
+/// 以OpenMetrics文本格式导出当前指标，供Prometheus抓取
+///
+/// GET /metrics
+pub async fn export_metrics(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let ipmi_service = data.ipmi_service.clone();
+    let sensor_blacklist = data.sensor_blacklist.clone();
+    let poll_overruns = data.poll_overruns.clone();
+    let body = web::block(move || collect_openmetrics(ipmi_service.as_ref(), &sensor_blacklist, &poll_overruns))
+        .await
+        .map_err(|e| AppError::internal_server_error(format!("采集指标失败: {}", e)))?;
+
+    Ok(HttpResponse::Ok().content_type(OPENMETRICS_CONTENT_TYPE).body(body))
+}
+
+/// 立即将当前指标推送到远程端点（需要API密钥）
+///
+/// POST /api/v1/admin/metrics/push
+pub async fn push_metrics(req: HttpRequest, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let service = data
+        .metrics_push
+        .as_ref()
+        .ok_or_else(|| AppError::ServiceUnavailableError {
+            service: "指标推送（未配置 metrics_push.url）".to_string(),
+        })?;
+    let report = service
+        .push_current(data.ipmi_service.clone(), data.sensor_blacklist.clone(), data.poll_overruns.clone())
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(report)))
+}
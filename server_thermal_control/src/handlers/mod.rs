@@ -1,12 +1,35 @@
+use crate::models::error::{ApiResult, AppError};
 use crate::models::FanStats;
+use crate::services::control_loop::ControlLoopLiveness;
+use crate::services::health_probe::ProbeResult;
+use crate::services::ipmi_service::{BmcSelfTestResult, BmcSelfTestStatus, FanSensor, Ipmi, TemperatureSensor};
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::task_watchdog::SupervisedTaskStatus;
+use crate::services::temperature_history;
 use crate::{models, AppState};
 use actix_web::{web, HttpResponse, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use serde_json::json;
 
 // pub mod alert;
 pub mod fan;
+pub mod admin;
 pub mod alert;
+pub mod analytics;
+pub mod annotation;
+pub mod config;
+pub mod control;
+pub mod dashboard;
+pub mod downloads;
+pub mod errors;
+pub mod event;
+pub mod incident;
+pub mod ipmi;
+pub mod metrics;
+pub mod sensor;
+pub mod summary;
+pub mod support;
 pub mod temperature;
 
 /// 健康检查处理器
@@ -22,8 +45,13 @@ pub async fn health_check() -> Result<HttpResponse> {
 pub async fn system_info(data: web::Data<AppState>) -> Result<HttpResponse> {
     let cpu_count = num_cpus::get();
 
-    // 尝试获取IPMI系统信息
-    let ipmi_info = match data.ipmi_service.get_system_info() {
+    // 尝试获取IPMI系统信息；缓存过期时会调用ipmitool并在刷新期间持锁，放到阻塞线程池执行
+    let ipmi_service = data.ipmi_service.clone();
+    let ipmi_info = web::block(move || ipmi_service.get_system_info().map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result);
+    let ipmi_info = match ipmi_info {
         Ok(info) => Some(info),
         Err(e) => {
             tracing::warn!("Failed to get IPMI system info: {}", e);
@@ -31,12 +59,16 @@ pub async fn system_info(data: web::Data<AppState>) -> Result<HttpResponse> {
         }
     };
 
+    let now = Utc::now();
+    let monitoring = data.alert_monitor.statistics(now);
     let mut system_info = json!({
         "service_name": "Thermal Control Server",
         "version": "1.0.0",
         "cpu_cores": cpu_count,
-        "uptime": "running",
-        "timestamp": Utc::now().to_rfc3339()
+        "started_at": monitoring.started_at.to_rfc3339(),
+        "uptime_seconds": monitoring.uptime_seconds,
+        "monitored_components": monitoring.monitored_components,
+        "timestamp": now.to_rfc3339()
     });
 
     // 如果有IPMI信息，添加到响应中
@@ -57,106 +89,280 @@ pub async fn system_info(data: web::Data<AppState>) -> Result<HttpResponse> {
 
 /// 系统健康状态处理器
 pub async fn system_health(data: web::Data<AppState>) -> Result<HttpResponse> {
+    let system_health = assess_system_health(
+        BmcReadings::read(data.ipmi_service.as_ref()),
+        &data.sensor_blacklist,
+        data.control_loop.liveness(Utc::now()),
+        &data.task_watchdog.statuses(),
+        &data.health_probes.check_all().await,
+    );
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        system_health,
+        "System health check completed",
+    )))
+}
+
+/// 依赖健康状态处理器
+///
+/// 内置的IPMI连接检查之外，还包含 `health.probes` 中配置的HTTP探针；探针名称与内置依赖同名时覆盖内置项
+///
+/// GET /api/v1/health/dependencies
+pub async fn dependency_health(data: web::Data<AppState>) -> Result<HttpResponse> {
+    let started = std::time::Instant::now();
+    let ipmi_connection = data.ipmi_service.test_connection();
+    let ipmi = ProbeResult {
+        name: "ipmi_service".to_string(),
+        url: data.config.ipmi.host.clone(),
+        available: matches!(ipmi_connection, Ok(true)),
+        response_time_ms: started.elapsed().as_millis() as u64,
+        checked_at: Utc::now(),
+        error_message: match ipmi_connection {
+            Ok(true) => None,
+            Ok(false) => Some("connection test failed".to_string()),
+            Err(e) => Some(e.to_string()),
+        },
+    };
+
+    let mut dependencies = serde_json::Map::new();
+    for probe in std::iter::once(ipmi).chain(data.health_probes.check_all().await) {
+        dependencies.insert(
+            probe.name.clone(),
+            json!({
+                "name": probe.name,
+                "status": if probe.available { "Available" } else { "Unavailable" },
+                "endpoint": probe.url,
+                "response_time_ms": probe.response_time_ms,
+                "last_check": probe.checked_at.to_rfc3339(),
+                "error_message": probe.error_message,
+            }),
+        );
+    }
+    let available = dependencies.values().filter(|d| d["status"] == "Available").count();
+    let overall_status = match available {
+        n if n == dependencies.len() => "Available",
+        0 => "Unavailable",
+        _ => "Degraded",
+    };
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({
+            "overall_status": overall_status,
+            "dependencies": dependencies,
+            "timestamp": Utc::now().to_rfc3339()
+        }),
+        "Dependency health check completed",
+    )))
+}
+
+/// 将整体状态提升到不低于 `status`（healthy < warning < critical）
+fn escalate(overall_status: &mut &'static str, status: &'static str) {
+    let rank = |status: &str| match status {
+        "critical" => 2,
+        "warning" => 1,
+        _ => 0,
+    };
+    if rank(status) > rank(overall_status) {
+        *overall_status = status;
+    }
+}
+
+/// 一次健康检查从BMC读到的结果，读取失败时为错误消息
+struct BmcReadings {
+    ipmi_connection: std::result::Result<bool, String>,
+    bmc_self_test: std::result::Result<BmcSelfTestStatus, String>,
+    temperatures: std::result::Result<Vec<TemperatureSensor>, String>,
+    fans: std::result::Result<Vec<FanSensor>, String>,
+}
+
+impl BmcReadings {
+    /// 依次读取连接状态、自检状态、温度和风扇，调用会阻塞
+    ///
+    /// # 参数
+    /// * `ipmi` - IPMI服务
+    fn read(ipmi: &dyn Ipmi) -> Self {
+        Self {
+            ipmi_connection: ipmi.test_connection().map_err(|e| e.to_string()),
+            bmc_self_test: ipmi.get_self_test_status().map_err(|e| e.to_string()),
+            temperatures: ipmi.get_temperature_sensors().map_err(|e| e.to_string()),
+            fans: ipmi.get_fan_sensors().map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// 根据各子系统的读取结果汇总系统健康状态
+///
+/// 部分数据不能视为健康：任何子系统读取失败时整体状态至少为 `warning`，
+/// 读取失败的子系统列在 `unavailable_subsystems` 中
+///
+/// # 参数
+/// * `readings` - 从BMC读到的结果，BMC自检失败时传感器数据已不可信，为 `critical`
+/// * `sensor_blacklist` - 传感器黑名单
+/// * `control_loop` - 控制循环运行状态，卡住时为 `critical`
+/// * `background_tasks` - 看门狗监督的后台任务，被放弃的任务为 `critical`
+/// * `dependencies` - `health.probes` 的探测结果，不可用时至少为 `warning`
+fn assess_system_health(
+    readings: BmcReadings,
+    sensor_blacklist: &SensorBlacklist,
+    control_loop: ControlLoopLiveness,
+    background_tasks: &[SupervisedTaskStatus],
+    dependencies: &[ProbeResult],
+) -> serde_json::Value {
+    let BmcReadings {
+        ipmi_connection,
+        bmc_self_test,
+        temperatures,
+        fans,
+    } = readings;
     let mut overall_status = "healthy";
     let mut issues = Vec::new();
+    let mut unavailable_subsystems = Vec::new();
 
     // 检查IPMI连接状态
-    let ipmi_status = if let Ok(_) = data.ipmi_service.test_connection() {
-        "connected"
-    } else if let Err(e) = data.ipmi_service.test_connection() {
-        tracing::warn!("IPMI connection test failed: {}", e);
-        issues.push(format!("IPMI connection issue: {}", e));
-        overall_status = "warning";
-        "disconnected"
-    } else {
-        unreachable!()
+    let ipmi_status = match ipmi_connection {
+        Ok(true) => "connected",
+        failed => {
+            let reason = failed.err().unwrap_or_else(|| "connection test failed".to_string());
+            tracing::warn!("IPMI connection test failed: {}", reason);
+            issues.push(format!("IPMI connection issue: {}", reason));
+            unavailable_subsystems.push("ipmi");
+            escalate(&mut overall_status, "warning");
+            "disconnected"
+        }
+    };
+
+    // 检查BMC自检状态，BMC异常时传感器读数可能是过期数据
+    let bmc_status = match bmc_self_test {
+        Ok(status) => match status.result {
+            BmcSelfTestResult::Passed => "passed",
+            BmcSelfTestResult::NotImplemented => "not_implemented",
+            BmcSelfTestResult::Failed => {
+                tracing::warn!("BMC self-test failed: {}", status.summary);
+                let mut issue = format!("BMC self-test failed: {}", status.summary);
+                if !status.failed_components.is_empty() {
+                    issue.push_str(&format!(" ({})", status.failed_components.join(", ")));
+                }
+                issues.push(issue);
+                escalate(&mut overall_status, "critical");
+                "failed"
+            }
+        },
+        Err(e) => {
+            tracing::warn!("Failed to read BMC self-test status: {}", e);
+            issues.push(format!("Unable to read BMC self-test status: {}", e));
+            unavailable_subsystems.push("bmc");
+            escalate(&mut overall_status, "warning");
+            "unknown"
+        }
     };
 
     // 检查温度传感器状态
-    let temperature_status = match data.ipmi_service.get_temperature_sensors() {
+    let temperature_status = match temperatures {
         Ok(sensors) => {
             let mut temp_issues = Vec::new();
             for sensor in sensors {
+                if sensor_blacklist.contains(&sensor.sensor_id) {
+                    continue;
+                }
                 if sensor.temperature > 80.0 {
                     temp_issues.push(format!(
                         "High temperature on {}: {:.1}°C",
                         sensor.sensor_id, sensor.temperature
                     ));
-                    overall_status = "critical";
+                    escalate(&mut overall_status, "critical");
                 } else if sensor.temperature > 70.0 {
                     temp_issues.push(format!(
                         "Elevated temperature on {}: {:.1}°C",
                         sensor.sensor_id, sensor.temperature
                     ));
-                    if overall_status == "healthy" {
-                        overall_status = "warning";
-                    }
+                    escalate(&mut overall_status, "warning");
                 }
             }
+            let status = if temp_issues.is_empty() { "normal" } else { "elevated" };
             issues.extend(temp_issues);
-            if issues.is_empty() {
-                "normal"
-            } else {
-                "elevated"
-            }
+            status
         }
-        Err(_) => {
-            issues.push("Unable to read temperature sensors".to_string());
-            if overall_status == "healthy" {
-                overall_status = "warning";
-            }
+        Err(e) => {
+            tracing::warn!("Failed to read temperature sensors: {}", e);
+            issues.push(format!("Unable to read temperature sensors: {}", e));
+            unavailable_subsystems.push("temperature_sensors");
+            escalate(&mut overall_status, "warning");
             "unknown"
         }
     };
 
     // 检查风扇状态
-    let fan_status = match data.ipmi_service.get_fan_sensors() {
+    let fan_status = match fans {
         Ok(fans) => {
             let mut fan_issues = Vec::new();
             for fan in fans {
                 if fan.speed_rpm == 0 {
                     fan_issues.push(format!("Fan {} not running", fan.fan_id));
-                    overall_status = "critical";
+                    escalate(&mut overall_status, "critical");
                 } else if fan.speed_rpm < 500 {
                     fan_issues.push(format!("Fan {} running slow: {} RPM", fan.fan_id, fan.speed_rpm));
-                    if overall_status == "healthy" {
-                        overall_status = "warning";
-                    }
+                    escalate(&mut overall_status, "warning");
                 }
             }
-            if fan_issues.is_empty() {
-                issues.extend(fan_issues);
-                "operational"
-            } else {
-                "issues"
-            }
+            let status = if fan_issues.is_empty() { "operational" } else { "issues" };
+            issues.extend(fan_issues);
+            status
         }
-        Err(_) => {
-            issues.push("Unable to read fan status".to_string());
-            if overall_status == "healthy" {
-                overall_status = "warning";
-            }
+        Err(e) => {
+            tracing::warn!("Failed to read fan sensors: {}", e);
+            issues.push(format!("Unable to read fan status: {}", e));
+            unavailable_subsystems.push("fans");
+            escalate(&mut overall_status, "warning");
             "unknown"
         }
     };
 
-    let system_health = json!({
+    // 检查控制循环，卡住时风扇转速不再跟随温度
+    if control_loop == ControlLoopLiveness::Stalled {
+        tracing::warn!("Control loop has not completed a cycle within two update intervals");
+        issues.push("Control loop stalled: no control cycle completed within two update intervals".to_string());
+        escalate(&mut overall_status, "critical");
+    }
+
+    // 检查后台任务，重启过于频繁而被放弃的任务不会再运行
+    for task in background_tasks.iter().filter(|task| task.abandoned) {
+        tracing::warn!("Background task {} was abandoned by the watchdog", task.name);
+        issues.push(format!(
+            "Background task {} abandoned after {} restarts: {}",
+            task.name,
+            task.restarts,
+            task.last_restart_reason.as_deref().unwrap_or("unknown")
+        ));
+        escalate(&mut overall_status, "critical");
+    }
+
+    // 检查外部依赖探针
+    for probe in dependencies.iter().filter(|probe| !probe.available) {
+        issues.push(format!(
+            "Dependency {} unavailable: {}",
+            probe.name,
+            probe.error_message.as_deref().unwrap_or("unknown")
+        ));
+        escalate(&mut overall_status, "warning");
+    }
+
+    json!({
         "overall_status": overall_status,
         "components": {
             "ipmi": ipmi_status,
+            "bmc": bmc_status,
             "temperature_sensors": temperature_status,
             "fans": fan_status,
+            "control_loop": control_loop.as_str(),
             "database": "not_configured",
             "redis": "not_configured"
         },
+        "background_tasks": background_tasks,
+        "dependencies": dependencies,
+        "unavailable_subsystems": unavailable_subsystems,
         "issues": issues,
         "timestamp": Utc::now().to_rfc3339()
-    });
-
-    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
-        system_health,
-        "System health check completed",
-    )))
+    })
 }
 
 /// 温度统计处理器
@@ -176,6 +382,90 @@ pub async fn temperature_stats(_data: web::Data<AppState>) -> Result<HttpRespons
     )))
 }
 
+/// 温度分布查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemperatureHistogramQuery {
+    /// 只返回该传感器的分布
+    pub sensor_id: Option<String>,
+    /// 统计窗口（秒），不超过 `monitoring.temperature_histogram.window_secs`
+    pub window_secs: Option<u64>,
+}
+
+/// 各温度传感器最近一段时间的温度分布
+///
+/// GET /api/v1/stats/temperature/histogram
+pub async fn temperature_histogram(
+    data: web::Data<AppState>,
+    query: web::Query<TemperatureHistogramQuery>,
+) -> ApiResult<HttpResponse> {
+    let store = &data.temperature_histograms;
+    let config = store.config();
+    let window_secs = query.window_secs.map_or(config.window_secs, |secs| secs.min(config.window_secs));
+    let mut histograms = store.histograms(Utc::now(), Some(window_secs))?;
+    if let Some(sensor_id) = &query.sensor_id {
+        histograms.retain(|histogram| &histogram.sensor_id == sensor_id);
+        if histograms.is_empty() {
+            return Err(AppError::not_found_error("温度分布", sensor_id.as_str()).into());
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        json!({
+            "enabled": config.enabled,
+            "window_secs": window_secs,
+            "boundaries": store.boundaries(),
+            "histograms": histograms,
+        }),
+        "Temperature histograms retrieved successfully",
+    )))
+}
+
+/// 历史温度查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemperatureHistoryQuery {
+    /// 开始时间，默认结束时间前1小时
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间，默认当前时间
+    pub end_time: Option<DateTime<Utc>>,
+    /// 只返回该传感器的读数
+    pub sensor_id: Option<String>,
+    /// 降采样分辨率，如 "30s"、"5m"、"1h"
+    pub resolution: Option<String>,
+}
+
+/// 温度分布采集保留的历史温度读数
+///
+/// 时间范围颠倒或超过 `monitoring.max_history_span_hours` 时返回400
+///
+/// GET /api/v1/stats/temperature/history
+pub async fn temperature_history(
+    data: web::Data<AppState>,
+    query: web::Query<TemperatureHistoryQuery>,
+) -> ApiResult<HttpResponse> {
+    let end_time = query.end_time.unwrap_or_else(Utc::now);
+    let start_time = query.start_time.unwrap_or(end_time - chrono::Duration::hours(1));
+    temperature_history::validate_history_range(start_time, end_time, data.config.monitoring.max_history_span_hours)?;
+    let resolution = query
+        .resolution
+        .as_deref()
+        .map(|resolution| {
+            crate::utils::time::parse_duration(resolution)
+                .map_err(|_| AppError::validation_error("resolution", format!("无效的分辨率: {}", resolution)))
+        })
+        .transpose()?;
+
+    let history = temperature_history::query_history(
+        &data.temperature_histograms,
+        (start_time, end_time),
+        query.sensor_id.as_deref(),
+        resolution,
+    )?;
+    Ok(HttpResponse::Ok().json(models::ApiResponse::success(
+        history,
+        "Temperature history retrieved successfully",
+    )))
+}
+
 /// 风扇统计处理器
 pub async fn fan_stats(_data: web::Data<AppState>) -> Result<HttpResponse> {
     // TODO: 从数据库获取真实的风扇统计数据
@@ -199,3 +489,279 @@ pub async fn fan_stats(_data: web::Data<AppState>) -> Result<HttpResponse> {
         "Fan statistics retrieved successfully",
     )))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fan(fan_id: &str, speed_rpm: u32) -> FanSensor {
+        FanSensor {
+            id: fan_id.to_string(),
+            fan_id: fan_id.to_string(),
+            speed_rpm,
+            unit: "RPM".to_string(),
+            raw_unit: "RPM".to_string(),
+            speed_percent: 50,
+            status: "ok".to_string(),
+            location: "System".to_string(),
+            control_mode: "auto".to_string(),
+            target_temp: None,
+            controllable: true,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_failed_temperature_read_downgrades_healthy_fans_to_warning() {
+        let health = assess_system_health(
+            BmcReadings {
+                ipmi_connection: Ok(true),
+                bmc_self_test: Ok(BmcSelfTestStatus::parse("Selftest: passed\n")),
+                temperatures: Err("sdr list timed out".to_string()),
+                fans: Ok(vec![fan("FAN1", 1800), fan("FAN2", 1750)]),
+            },
+            &SensorBlacklist::default(),
+            ControlLoopLiveness::Running,
+            &[],
+            &[],
+        );
+
+        assert_eq!(health["overall_status"], "warning");
+        assert_eq!(health["components"]["temperature_sensors"], "unknown");
+        assert_eq!(health["components"]["fans"], "operational");
+        assert_eq!(health["unavailable_subsystems"], json!(["temperature_sensors"]));
+        assert_eq!(health["issues"], json!(["Unable to read temperature sensors: sdr list timed out"]));
+
+        // 读取失败不会掩盖已确认的严重问题
+        let health = assess_system_health(
+            BmcReadings {
+                ipmi_connection: Ok(true),
+                bmc_self_test: Ok(BmcSelfTestStatus::parse("Selftest: passed\n")),
+                temperatures: Err("sdr list timed out".to_string()),
+                fans: Ok(vec![fan("FAN1", 0)]),
+            },
+            &SensorBlacklist::default(),
+            ControlLoopLiveness::Running,
+            &[],
+            &[],
+        );
+        assert_eq!(health["overall_status"], "critical");
+    }
+
+    #[test]
+    fn test_stalled_control_loop_is_critical_even_when_sensors_are_healthy() {
+        let health = assess_system_health(
+            BmcReadings {
+                ipmi_connection: Ok(true),
+                bmc_self_test: Ok(BmcSelfTestStatus::parse("Selftest: passed\n")),
+                temperatures: Ok(vec![]),
+                fans: Ok(vec![fan("FAN1", 1800)]),
+            },
+            &SensorBlacklist::default(),
+            ControlLoopLiveness::Stalled,
+            &[],
+            &[],
+        );
+
+        assert_eq!(health["overall_status"], "critical");
+        assert_eq!(health["components"]["control_loop"], "stalled");
+        assert_eq!(
+            health["issues"],
+            json!(["Control loop stalled: no control cycle completed within two update intervals"])
+        );
+
+        // 控制关闭时不影响整体状态
+        let health = assess_system_health(
+            BmcReadings {
+                ipmi_connection: Ok(true),
+                bmc_self_test: Ok(BmcSelfTestStatus::parse("Selftest: passed\n")),
+                temperatures: Ok(vec![]),
+                fans: Ok(vec![fan("FAN1", 1800)]),
+            },
+            &SensorBlacklist::default(),
+            ControlLoopLiveness::Disabled,
+            &[],
+            &[],
+        );
+        assert_eq!(health["overall_status"], "healthy");
+        assert_eq!(health["components"]["control_loop"], "disabled");
+    }
+
+    #[test]
+    fn test_abandoned_background_task_is_critical() {
+        let tasks = vec![
+            SupervisedTaskStatus {
+                name: "alert_monitor".to_string(),
+                restarts: 5,
+                last_restart_at: Some(Utc::now()),
+                last_restart_reason: Some("panic: sdr list".to_string()),
+                abandoned: true,
+            },
+            SupervisedTaskStatus {
+                name: "temperature_histogram".to_string(),
+                restarts: 1,
+                last_restart_at: Some(Utc::now()),
+                last_restart_reason: Some("任务被中止".to_string()),
+                abandoned: false,
+            },
+        ];
+        let health = assess_system_health(
+            BmcReadings {
+                ipmi_connection: Ok(true),
+                bmc_self_test: Ok(BmcSelfTestStatus::parse("Selftest: passed\n")),
+                temperatures: Ok(vec![]),
+                fans: Ok(vec![fan("FAN1", 1800)]),
+            },
+            &SensorBlacklist::default(),
+            ControlLoopLiveness::Running,
+            &tasks,
+            &[],
+        );
+
+        assert_eq!(health["overall_status"], "critical");
+        assert_eq!(health["background_tasks"][1]["restarts"], 1);
+        assert_eq!(
+            health["issues"],
+            json!(["Background task alert_monitor abandoned after 5 restarts: panic: sdr list"])
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_unreachable_probe_degrades_dependencies_and_system_health() {
+        use crate::config::{AppConfig, HealthProbeConfig};
+        use crate::test_harness::{MockIpmiService, TestHarness};
+
+        let mut config = AppConfig::default();
+        config.health.probes = vec![HealthProbeConfig {
+            name: "metrics_backend".to_string(),
+            // 端口1上没有服务，连接被拒绝
+            url: "http://127.0.0.1:1/-/healthy".to_string(),
+            expected_status: 200,
+            timeout_ms: 500,
+        }];
+        let harness = TestHarness::with_config(MockIpmiService::healthy_server(), config);
+
+        let (_, body) = harness.get("/api/v1/health/dependencies").await;
+        assert_eq!(body["data"]["overall_status"], "Degraded");
+        assert_eq!(body["data"]["dependencies"]["ipmi_service"]["status"], "Available");
+        let probe = &body["data"]["dependencies"]["metrics_backend"];
+        assert_eq!(probe["status"], "Unavailable");
+        assert!(probe["error_message"].as_str().unwrap().starts_with("请求失败"));
+
+        let (_, body) = harness.get("/api/v1/system/health").await;
+        assert_eq!(body["data"]["overall_status"], "warning");
+        assert_eq!(body["data"]["dependencies"][0]["available"], false);
+        assert!(body["data"]["issues"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("Dependency metrics_backend unavailable"));
+    }
+
+    #[test]
+    fn test_failed_bmc_self_test_is_critical() {
+        let self_test = BmcSelfTestStatus::parse("Selftest: device error\nFailed components:\n SDR Repository empty\n");
+        let health = assess_system_health(
+            BmcReadings {
+                ipmi_connection: Ok(true),
+                bmc_self_test: Ok(self_test),
+                temperatures: Ok(vec![]),
+                fans: Ok(vec![fan("FAN1", 1800)]),
+            },
+            &SensorBlacklist::default(),
+            ControlLoopLiveness::Running,
+            &[],
+            &[],
+        );
+
+        assert_eq!(health["overall_status"], "critical");
+        assert_eq!(health["components"]["bmc"], "failed");
+        assert_eq!(
+            health["issues"],
+            json!(["BMC self-test failed: device error (SDR Repository empty)"])
+        );
+
+        // 读取自检失败只降级为warning
+        let health = assess_system_health(
+            BmcReadings {
+                ipmi_connection: Ok(true),
+                bmc_self_test: Err("mc selftest timed out".to_string()),
+                temperatures: Ok(vec![]),
+                fans: Ok(vec![fan("FAN1", 1800)]),
+            },
+            &SensorBlacklist::default(),
+            ControlLoopLiveness::Running,
+            &[],
+            &[],
+        );
+        assert_eq!(health["overall_status"], "warning");
+        assert_eq!(health["components"]["bmc"], "unknown");
+        assert_eq!(health["unavailable_subsystems"], json!(["bmc"]));
+    }
+
+    #[actix_web::test]
+    async fn test_system_health_reads_bmc_self_test() {
+        use crate::test_harness::{MockIpmiService, TestHarness};
+
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let (_, body) = harness.get("/api/v1/system/health").await;
+        assert_eq!(body["data"]["components"]["bmc"], "passed");
+
+        harness.ipmi.set_self_test_output("Selftest: device error\nFailed components:\n SDR Repository empty\n");
+        let (_, body) = harness.get("/api/v1/system/health").await;
+        assert_eq!(body["data"]["overall_status"], "critical");
+        assert_eq!(body["data"]["components"]["bmc"], "failed");
+    }
+
+    #[actix_web::test]
+    async fn test_temperature_history_rejects_inverted_range_and_downsamples() {
+        use crate::services::sensor_source::SensorSource;
+        use crate::test_harness::{MockIpmiService, TestHarness};
+
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let sensors = harness.ipmi.get_temperature_sensors().unwrap();
+        for minute in 0..60 {
+            harness.state.temperature_histograms.record(&sensors, start + chrono::Duration::minutes(minute));
+        }
+        let range = |start: DateTime<Utc>, end: DateTime<Utc>| {
+            format!(
+                "start_time={}&end_time={}",
+                start.format("%Y-%m-%dT%H:%M:%S%.fZ"),
+                end.format("%Y-%m-%dT%H:%M:%S%.fZ")
+            )
+        };
+        let end = start + chrono::Duration::hours(1);
+
+        let (status, raw) = harness
+            .get(&format!("/api/v1/stats/temperature/history?sensor_id=CPU1_TEMP&{}", range(start, end)))
+            .await;
+        assert_eq!(status, actix_web::http::StatusCode::OK);
+        assert_eq!(raw["data"]["sensors"]["CPU1_TEMP"].as_array().unwrap().len(), 60);
+        assert!(raw["data"]["sensors"].get("INLET_TEMP").is_none());
+
+        let (_, downsampled) = harness
+            .get(&format!(
+                "/api/v1/stats/temperature/history?sensor_id=CPU1_TEMP&resolution=10m&{}",
+                range(start, end)
+            ))
+            .await;
+        let points = downsampled["data"]["sensors"]["CPU1_TEMP"].as_array().unwrap();
+        assert_eq!(points.len(), 6);
+        assert_eq!(points[0]["samples"], 10);
+        assert_eq!(downsampled["data"]["resolution_secs"], 600);
+
+        let (status, body) = harness
+            .get(&format!("/api/v1/stats/temperature/history?{}", range(end, start)))
+            .await;
+        assert_eq!(status, actix_web::http::StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("开始时间"));
+
+        let (status, _) = harness
+            .get(&format!(
+                "/api/v1/stats/temperature/history?{}",
+                range(end - chrono::Duration::days(30), end)
+            ))
+            .await;
+        assert_eq!(status, actix_web::http::StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,476 @@
+use crate::middleware::auth::require_api_key;
+use crate::models::api::ApiResponse;
+use crate::models::error::{ApiError, ApiResult, AppError};
+use crate::models::sensor::SensorCalibration;
+use crate::services::ipmi_service::TemperatureSensor;
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::sensor_cache::CachedTemperature;
+use crate::utils::negotiation::negotiated_response;
+use crate::AppState;
+use actix_web::{http::StatusCode, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+
+/// 传感器黑名单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensorBlacklistBody {
+    /// 排除的传感器ID
+    pub sensor_ids: Vec<String>,
+}
+
+/// 传感器列表过滤条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SensorFilterQuery {
+    /// 归一化位置，如 `inlet`、`cpu`，不区分大小写
+    pub location: Option<String>,
+    /// 实体ID（`3`）或实体ID与实例（`3.1`）
+    pub entity: Option<String>,
+}
+
+impl SensorFilterQuery {
+    /// 传感器是否满足过滤条件
+    ///
+    /// # 参数
+    /// * `sensor` - 温度传感器读数
+    pub fn matches(&self, sensor: &TemperatureSensor) -> bool {
+        let location_matches = self
+            .location
+            .as_deref()
+            .map_or(true, |location| sensor.location.eq_ignore_ascii_case(location.trim()));
+        let entity_matches = self.entity.as_deref().map_or(true, |entity| {
+            let entity = entity.trim();
+            sensor.entity == entity || sensor.entity.split('.').next() == Some(entity)
+        });
+        location_matches && entity_matches
+    }
+}
+
+/// 按过滤条件筛选传感器
+///
+/// # 参数
+/// * `sensors` - 温度传感器读数，也可以是带新鲜度标记的缓存读数
+/// * `query` - 过滤条件
+pub fn filter_sensors<T: Borrow<TemperatureSensor>>(sensors: Vec<T>, query: &SensorFilterQuery) -> Vec<T> {
+    sensors.into_iter().filter(|sensor| query.matches(sensor.borrow())).collect()
+}
+
+/// 传感器读数中出现的位置，按字母排序去重
+///
+/// # 参数
+/// * `sensors` - 温度传感器读数
+pub fn distinct_locations(sensors: &[TemperatureSensor]) -> Vec<String> {
+    let mut locations: Vec<String> = sensors.iter().map(|sensor| sensor.location.clone()).collect();
+    locations.sort();
+    locations.dedup();
+    locations
+}
+
+/// 读取温度传感器，BMC读取失败时回退到最后一次成功读数
+///
+/// # 参数
+/// * `data` - 应用状态
+fn read_temperature_sensors(data: &AppState) -> ApiResult<Vec<CachedTemperature>> {
+    let readings = data
+        .sensor_cache
+        .read_through(Utc::now(), || data.ipmi_service.get_temperature_sensors().map_err(|e| e.to_string()))
+        .map_err(|e| AppError::ipmi_error(format!("读取温度传感器失败: {}", e)))?;
+    Ok(readings)
+}
+
+/// 生成传感器列表响应数据，过期读数带 `stale` 标记和距最后一次成功读取的秒数
+///
+/// # 参数
+/// * `readings` - 温度传感器读数
+/// * `query` - 过滤条件
+/// * `sensor_blacklist` - 传感器黑名单
+pub fn sensor_list_body(
+    readings: Vec<CachedTemperature>,
+    query: &SensorFilterQuery,
+    sensor_blacklist: &SensorBlacklist,
+) -> serde_json::Value {
+    let sensors: Vec<_> = filter_sensors(readings, query)
+        .into_iter()
+        .map(|reading| {
+            let sensor = reading.sensor;
+            serde_json::json!({
+                "sensor_id": sensor.sensor_id,
+                "temperature": sensor.temperature,
+                "unit": sensor.unit,
+                "location": sensor.location,
+                "entity": sensor.entity,
+                "status": sensor.status,
+                "excluded": sensor_blacklist.contains(&sensor.sensor_id),
+                "stale": reading.stale,
+                "age_secs": reading.age_secs,
+                "timestamp": sensor.timestamp.to_rfc3339(),
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "total": sensors.len(),
+        "sensors": sensors,
+    })
+}
+
+/// 获取温度传感器列表，支持按位置和实体过滤
+///
+/// 请求头 `Accept: application/msgpack` 时以MessagePack编码响应。
+/// BMC读取失败时返回保留时长内的最后一次成功读数，并标记 `stale` 和 `age_secs`
+///
+/// GET /api/v1/sensors?location=inlet&entity=7.1
+pub async fn list_sensors(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<SensorFilterQuery>,
+) -> ApiResult<HttpResponse> {
+    let readings = read_temperature_sensors(&data)?;
+
+    let body = ApiResponse::success(sensor_list_body(readings, &query, &data.sensor_blacklist));
+    Ok(negotiated_response(&req, data.config.server.msgpack_enabled, StatusCode::OK, &body)?)
+}
+
+/// 获取传感器位置列表
+///
+/// GET /api/v1/sensors/locations
+pub async fn list_sensor_locations(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+) -> ApiResult<HttpResponse> {
+    let sensors: Vec<_> = read_temperature_sensors(&data)?
+        .into_iter()
+        .map(|reading| reading.sensor)
+        .collect();
+
+    let body = ApiResponse::success(serde_json::json!({
+        "locations": distinct_locations(&sensors),
+    }));
+    Ok(negotiated_response(&req, data.config.server.msgpack_enabled, StatusCode::OK, &body)?)
+}
+
+/// 强制轮询（两次之间至少间隔 `monitoring.forced_poll_min_interval_secs` 秒）
+///
+/// 立即读取温度和风扇并刷新最后一次成功读数缓存，告警监控的最后更新时间一并刷新；
+/// 间隔内的请求返回429，不访问BMC
+///
+/// POST /api/v1/monitoring/poll
+pub async fn force_poll(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let now = Utc::now();
+    data.forced_poll.acquire(now)?;
+
+    let state = data.clone();
+    let (temperatures, fans) = web::block(move || {
+        let temperatures = read_temperature_sensors(&state)?;
+        let fans = state
+            .ipmi_service
+            .get_fan_sensors()
+            .map_err(|e| AppError::ipmi_error(format!("读取风扇失败: {}", e)))?;
+        Ok::<_, ApiError>((temperatures, fans))
+    })
+    .await
+    .map_err(|e| AppError::internal_server_error(format!("强制轮询任务失败: {}", e)))??;
+
+    if temperatures.iter().any(|reading| !reading.stale) {
+        data.alert_monitor.record_update(now);
+    }
+    Ok(HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+        "timestamp": now,
+        "temperature_data": temperatures,
+        "fan_data": fans,
+    }))))
+}
+
+/// 获取传感器黑名单
+///
+/// GET /api/v1/sensors/blacklist
+pub async fn get_sensor_blacklist(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(ApiResponse::success(SensorBlacklistBody {
+        sensor_ids: data.sensor_blacklist.sensor_ids(),
+    })))
+}
+
+/// 替换传感器黑名单（需要API密钥）
+///
+/// 运行时修改，重启后恢复为 `monitoring.sensor_blacklist`
+///
+/// PUT /api/v1/sensors/blacklist
+pub async fn update_sensor_blacklist(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<SensorBlacklistBody>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    data.sensor_blacklist.replace(body.into_inner().sensor_ids);
+    tracing::info!("Sensor blacklist updated: {:?}", data.sensor_blacklist.sensor_ids());
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(SensorBlacklistBody {
+        sensor_ids: data.sensor_blacklist.sensor_ids(),
+    })))
+}
+
+/// 设置传感器校准的请求体
+#[derive(Debug, Clone, Deserialize)]
+pub struct SensorCalibrationBody {
+    /// 传感器ID
+    pub sensor_id: String,
+    /// 偏移量（°C）
+    pub offset: f64,
+    /// 缩放因子，默认为1
+    pub scale_factor: Option<f64>,
+    /// 校准时参考探头的读数
+    pub reference_value: Option<f64>,
+}
+
+/// 传感器的原始读数和校准后读数
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibratedReading {
+    /// 传感器ID
+    pub sensor_id: String,
+    /// 原始读数（°C）
+    pub raw_temperature: f64,
+    /// 校准后读数（°C），未设置校准时与原始读数相同
+    pub temperature: f64,
+    /// 是否设置了校准
+    pub calibrated: bool,
+}
+
+/// 获取传感器校准参数，以及各传感器当前的原始读数和校准后读数
+///
+/// GET /api/v1/sensors/calibration
+pub async fn get_sensor_calibration(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let calibrations = data.sensor_calibrations.list();
+    let raw = data
+        .raw_ipmi_service
+        .get_temperature_sensors()
+        .map_err(|e| AppError::ipmi_error(format!("读取温度传感器失败: {}", e)))?;
+    let mut calibrated = raw.clone();
+    data.sensor_calibrations.apply(&mut calibrated);
+
+    let readings: Vec<_> = raw
+        .into_iter()
+        .zip(calibrated)
+        .map(|(raw, calibrated)| CalibratedReading {
+            calibrated: calibrations.contains_key(&raw.sensor_id),
+            sensor_id: raw.sensor_id,
+            raw_temperature: raw.temperature,
+            temperature: calibrated.temperature,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+        "calibrations": calibrations,
+        "readings": readings,
+    }))))
+}
+
+/// 设置一个传感器的校准参数（需要API密钥）
+///
+/// 之后的监控、告警和风扇控制都使用校准后的温度，参数写入 `monitoring.sensor_calibration_file`
+///
+/// PUT /api/v1/sensors/calibration
+pub async fn set_sensor_calibration(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<SensorCalibrationBody>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let body = body.into_inner();
+    let calibration = SensorCalibration::new(body.offset, body.scale_factor, body.reference_value);
+    let calibration = data.sensor_calibrations.set(&body.sensor_id, calibration).await?;
+    tracing::info!(
+        target: "audit",
+        sensor_id = %body.sensor_id,
+        offset = calibration.offset,
+        scale_factor = calibration.scale_factor,
+        "Sensor calibration updated"
+    );
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(calibration)))
+}
+
+/// 删除一个传感器的校准参数（需要API密钥）
+///
+/// DELETE /api/v1/sensors/calibration/{sensor_id}
+pub async fn delete_sensor_calibration(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    let sensor_id = path.into_inner();
+    let removed = data
+        .sensor_calibrations
+        .remove(&sensor_id)
+        .await?
+        .ok_or_else(|| AppError::not_found_error("传感器校准", sensor_id.as_str()))?;
+    tracing::info!(target: "audit", sensor_id = %sensor_id, "Sensor calibration removed");
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(removed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ipmi_service::parse_temperature_sdr;
+    use crate::services::sensor_cache::LastGoodSensorCache;
+    use crate::test_harness::{MockIpmiService, TestHarness};
+    use serde_json::json;
+
+    const SDR_OUTPUT: &str = "\
+Inlet Temp       | 04h | ok  |  7.1 | 23 degrees C
+Exhaust Temp     | 01h | ok  |  7.1 | 35 degrees C
+CPU1 Temp        | 0Eh | ok  |  3.1 | 52 degrees C
+PSU1 Temperature | 60h | ok  | 10.1 | 41 degrees C
+Temp             | 0Fh | ok  |  3.2 | 49 degrees C
+Ambient          | 20h | ok  | 64.1 | 22 degrees C
+Fan1 RPM         | 30h | ok  |  7.1 | 3600 RPM
+";
+
+    #[test]
+    fn test_filter_by_inlet_returns_only_inlet_sensors() {
+        let sensors = parse_temperature_sdr(SDR_OUTPUT, Utc::now());
+        assert_eq!(sensors.len(), 6);
+
+        let query = SensorFilterQuery {
+            location: Some("Inlet".to_string()),
+            entity: None,
+        };
+        let inlet: Vec<_> = filter_sensors(sensors.clone(), &query)
+            .into_iter()
+            .map(|sensor| sensor.sensor_id)
+            .collect();
+        assert_eq!(inlet, vec!["INLET_TEMP", "AMBIENT"]);
+
+        // 实体ID不带实例时匹配所有实例
+        let query = SensorFilterQuery {
+            location: None,
+            entity: Some("3".to_string()),
+        };
+        let cpu: Vec<_> = filter_sensors(sensors.clone(), &query)
+            .into_iter()
+            .map(|sensor| sensor.location)
+            .collect();
+        assert_eq!(cpu, vec!["cpu", "cpu"]);
+
+        assert_eq!(distinct_locations(&sensors), vec!["cpu", "exhaust", "inlet", "psu"]);
+    }
+
+    #[test]
+    fn test_failed_poll_returns_last_good_values_marked_stale() {
+        let cache = LastGoodSensorCache::new(300);
+        let polled_at = Utc::now();
+        let blacklist = SensorBlacklist::default();
+        let query = SensorFilterQuery::default();
+
+        let readings = cache
+            .read_through(polled_at, || Ok::<_, String>(parse_temperature_sdr(SDR_OUTPUT, polled_at)))
+            .unwrap();
+        let body = sensor_list_body(readings, &query, &blacklist);
+        assert_eq!(body["total"], 6);
+        assert!(body["sensors"].as_array().unwrap().iter().all(|s| s["stale"] == false));
+
+        let readings = cache
+            .read_through(polled_at + chrono::Duration::seconds(45), || Err("BMC无响应".to_string()))
+            .unwrap();
+        let body = sensor_list_body(readings, &query, &blacklist);
+        assert_eq!(body["total"], 6);
+        let inlet = &body["sensors"][0];
+        assert_eq!(inlet["sensor_id"], "AMBIENT");
+        assert_eq!(inlet["temperature"], 22.0);
+        assert_eq!(inlet["stale"], true);
+        assert_eq!(inlet["age_secs"], 45);
+
+        // 超过保留时长后不再回退，返回原错误
+        let err = cache
+            .read_through(polled_at + chrono::Duration::seconds(301), || Err("BMC无响应".to_string()))
+            .unwrap_err();
+        assert_eq!(err, "BMC无响应");
+    }
+
+    #[actix_web::test]
+    async fn test_calibration_offset_shifts_readings_and_alert_evaluation() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        harness.ipmi.set_temperature("CPU1_TEMP", 79.0);
+
+        // 默认阈值80°C，原始读数不触发
+        let (_, body) = harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        assert!(body["data"]["matches"].as_array().unwrap().is_empty());
+
+        let (status, body) = harness
+            .put_json("/api/v1/sensors/calibration", json!({ "sensor_id": "CPU1_TEMP", "offset": 2.0 }))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["scale_factor"], 1.0);
+
+        let (_, body) = harness.get("/api/v1/sensors/calibration").await;
+        let cpu1 = body["data"]["readings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|reading| reading["sensor_id"] == "CPU1_TEMP")
+            .unwrap()
+            .clone();
+        assert_eq!(cpu1["raw_temperature"], 79.0);
+        assert_eq!(cpu1["temperature"], 81.0);
+        assert_eq!(cpu1["calibrated"], true);
+
+        let (_, body) = harness.get("/api/v1/sensors?location=cpu").await;
+        let sensors = body["data"]["sensors"].as_array().unwrap();
+        assert!(sensors.iter().any(|s| s["sensor_id"] == "CPU1_TEMP" && s["temperature"] == 81.0));
+
+        // 告警按校准后的温度评估
+        let (_, body) = harness.post_json("/api/v1/alerts/evaluate", json!({})).await;
+        let matches = body["data"]["matches"].as_array().unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0]["source_id"], "CPU1_TEMP");
+
+        let (status, _) = harness
+            .put_json("/api/v1/sensors/calibration", json!({ "sensor_id": "CPU1_TEMP", "offset": 80.0 }))
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = harness
+            .call(actix_web::test::TestRequest::delete().uri("/api/v1/sensors/calibration/CPU1_TEMP"))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = harness
+            .call(actix_web::test::TestRequest::delete().uri("/api/v1/sensors/calibration/CPU1_TEMP"))
+            .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_forced_poll_refreshes_cache_and_rejects_repeat_within_window() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+        assert!(harness.state.sensor_cache.last_good(Utc::now()).is_empty());
+        assert!(harness.state.alert_monitor.last_update().is_none());
+
+        let (status, body) = harness.post_json("/api/v1/monitoring/poll", json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["temperature_data"].as_array().unwrap().len(), 3);
+        assert_eq!(body["data"]["fan_data"].as_array().unwrap().len(), 2);
+        let polled_at = harness.state.alert_monitor.last_update().unwrap();
+        let cached = harness.state.sensor_cache.last_good(polled_at);
+        assert_eq!(cached.len(), 3);
+        assert!(cached.iter().all(|reading| reading.age_secs == 0));
+
+        // 间隔内的第二次请求不访问BMC
+        harness.ipmi.set_temperature("CPU1_TEMP", 70.0);
+        let (status, body) = harness.post_json("/api/v1/monitoring/poll", json!({})).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert!(body["error"].as_str().unwrap().contains("强制轮询过于频繁"));
+        let cpu1 = harness
+            .state
+            .sensor_cache
+            .last_good(Utc::now())
+            .into_iter()
+            .find(|reading| reading.sensor.sensor_id == "CPU1_TEMP")
+            .unwrap();
+        assert_eq!(cpu1.sensor.temperature, 52.0);
+        assert_eq!(harness.state.alert_monitor.last_update(), Some(polled_at));
+    }
+}
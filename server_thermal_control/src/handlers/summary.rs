@@ -0,0 +1,18 @@
+use crate::models::api::ApiResponse;
+use crate::models::error::{ApiResult, AppError};
+use crate::AppState;
+use actix_web::{web, HttpResponse};
+
+/// 获取全部机箱的散热汇总：各机箱最热传感器、风扇平均占空比、告警数和健康状况
+///
+/// GET /api/v1/summary
+pub async fn get_fleet_summary(data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    let service = data
+        .fleet_summary
+        .as_ref()
+        .ok_or_else(|| AppError::ServiceUnavailableError {
+            service: "多机箱汇总（未配置 fleet.chassis）".to_string(),
+        })?;
+    let summary = service.summary().await;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(summary)))
+}
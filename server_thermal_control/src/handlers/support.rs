@@ -0,0 +1,91 @@
+use crate::config::EffectiveConfig;
+use crate::middleware::auth::require_api_key;
+use crate::models::error::{ApiResult, AppError};
+use crate::services::support_bundle::{secret_values, SupportBundle};
+use crate::AppState;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use serde_json::json;
+use std::io::{Seek, SeekFrom};
+use tokio_util::io::ReaderStream;
+
+/// 支持包中最多包含的事件数
+const SUPPORT_BUNDLE_MAX_INCIDENTS: usize = 200;
+
+/// 支持包中最多包含的控制决策数
+const SUPPORT_BUNDLE_MAX_DECISIONS: usize = 200;
+
+/// 下载支持包（需要API密钥）
+///
+/// 打包脱敏后的生效配置、最近日志、最近的告警事件、控制决策、健康报告和系统/IPMI信息，
+/// 以zip附件流式返回。支持包先写入匿名临时文件，下载结束后自动删除
+///
+/// GET /api/v1/support-bundle
+pub async fn download_support_bundle(req: HttpRequest, data: web::Data<AppState>) -> ApiResult<HttpResponse> {
+    require_api_key(&req, &data.config.security)?;
+
+    // IPMI调用会阻塞，放到线程池中执行
+    let ipmi_service = data.ipmi_service.clone();
+    let sensor_blacklist = data.sensor_blacklist.clone();
+    let control_loop = data.control_loop.liveness(Utc::now());
+    let background_tasks = data.task_watchdog.statuses();
+    let dependencies = data.health_probes.check_all().await;
+    let (health, system) = web::block(move || {
+        let health = super::assess_system_health(
+            super::BmcReadings::read(ipmi_service.as_ref()),
+            &sensor_blacklist,
+            control_loop,
+            &background_tasks,
+            &dependencies,
+        );
+        let ipmi_info = match ipmi_service.get_system_info() {
+            Ok(info) => json!(info),
+            Err(e) => json!({ "error": e.to_string() }),
+        };
+        let system = json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "cpu_cores": num_cpus::get(),
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+            "ipmi_info": ipmi_info,
+        });
+        (health, system)
+    })
+    .await
+    .map_err(|e| AppError::internal_server_error(format!("收集支持包信息失败: {}", e)))?;
+
+    let incidents: Vec<_> = data.incidents.list(None).into_iter().take(SUPPORT_BUNDLE_MAX_INCIDENTS).collect();
+    let decisions = data.control_decisions.recent(Some(SUPPORT_BUNDLE_MAX_DECISIONS)).await;
+    let bundle = SupportBundle {
+        generated_at: Utc::now(),
+        config: json!(EffectiveConfig::build(&data.config, &data.config_sources, true)),
+        logs: data.recent_logs.lines(),
+        alerts: json!({ "incidents": incidents }),
+        decisions: json!(decisions),
+        health,
+        system,
+    };
+    let filename = bundle.file_name();
+    let secrets = secret_values(&data.config);
+
+    let file = web::block(move || -> std::result::Result<std::fs::File, AppError> {
+        let file = tempfile::tempfile()
+            .map_err(|e| AppError::internal_server_error(format!("创建支持包临时文件失败: {}", e)))?;
+        let mut file = bundle.write_zip(file, &secrets)?;
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| AppError::internal_server_error(format!("读取支持包失败: {}", e)))?;
+        Ok(file)
+    })
+    .await
+    .map_err(|e| AppError::internal_server_error(format!("生成支持包失败: {}", e)))??;
+
+    tracing::info!(target: "audit", "导出支持包: {}", filename);
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(filename)],
+        })
+        .streaming(ReaderStream::new(tokio::fs::File::from_std(file))))
+}
@@ -6,7 +6,7 @@ use serde_json::json;
 
 /// 获取所有温度数据
 /// 
-/// 从IPMI服务获取真实的温度传感器数据
+/// 从IPMI服务获取真实的温度传感器数据，黑名单中的传感器以 `excluded` 标记列出
 pub async fn list_temperature_data(data: web::Data<AppState>) -> Result<HttpResponse> {
     match data.ipmi_service.get_temperature_sensors() {
         Ok(sensors) => {
@@ -18,6 +18,7 @@ pub async fn list_temperature_data(data: web::Data<AppState>) -> Result<HttpResp
                     "unit": "°C",
                     "location": sensor.location,
                     "status": sensor.status,
+                    "excluded": data.sensor_blacklist.contains(&sensor.sensor_id),
                     "timestamp": Utc::now().to_rfc3339()
                 })
             }).collect();
@@ -53,6 +54,7 @@ pub async fn get_sensor_temperature(
     match data.ipmi_service.get_temperature_sensors() {
         Ok(sensors) => {
             if let Some(sensor) = sensors.into_iter().find(|s| s.sensor_id == sensor_id) {
+                let excluded = data.sensor_blacklist.contains(&sensor.sensor_id);
                 let sensor_data = json!({
                     "id": uuid::Uuid::new_v4().to_string(),
                     "sensor_id": sensor.sensor_id,
@@ -60,6 +62,7 @@ pub async fn get_sensor_temperature(
                     "unit": "°C",
                     "location": sensor.location,
                     "status": sensor.status,
+                    "excluded": excluded,
                     "timestamp": Utc::now().to_rfc3339(),
                     "history": []
                 });
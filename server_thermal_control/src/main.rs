@@ -1,7 +1,7 @@
 use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpResponse, HttpServer, Result};
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod config;
@@ -10,17 +10,131 @@ mod handlers;
 mod middleware;
 mod models;
 mod services;
+#[cfg(test)]
+mod test_harness;
 mod utils;
 
 use crate::services::ipmi_service::IpmiConfig;
-use config::AppConfig;
-use services::ipmi_service::IpmiService;
+use config::{AppConfig, ConfigSources, VendorProfile};
+use middleware::api_version::ApiVersioning;
+use middleware::cache_control::CacheControl;
+use middleware::json_content_type::RequireJsonContentType;
+use middleware::timeout::RequestTimeout;
+use utils::log_shipper::LogShipperLayer;
+use utils::logging::{self, LogFormat, RecentLogBuffer};
+use services::alert_history_store::{AlertHistoryStore, InMemoryAlertHistoryStore, PgAlertHistoryStore};
+use services::alert_stats_store::{AlertStatisticsStore, InMemoryAlertStatisticsStore, PgAlertStatisticsStore};
+use services::rule_state_store::{AlertRuleStateStore, InMemoryAlertRuleStateStore, PgAlertRuleStateStore};
+use services::event_store::{InMemorySystemEventStore, PgSystemEventStore, SystemEventStore};
+use services::annotation_store::{AnnotationStore, InMemoryAnnotationStore, PgAnnotationStore};
+use services::fan_snapshot_store::{FanSnapshotStore, InMemoryFanSnapshotStore, PgFanSnapshotStore};
+use services::decision_store::{ControlDecisionLog, PgControlDecisionStore, DEFAULT_RECENT_DECISIONS};
+use services::download_store::DownloadStore;
+use services::fleet_summary::FleetSummaryService;
+use services::forced_poll::ForcedPollGate;
+use services::health_probe::HealthProbeService;
+use services::incident_store::IncidentStore;
+use services::ipmi_service::{Ipmi, IpmiService};
+use services::jwt_secrets::JwtSecrets;
+use services::metrics_export::MetricsPushService;
+use services::poll_ticker::{PollOverruns, PollTicker};
+use services::power_cap::{PowerCapCoordinator, PowerReading};
+use services::quiet_hours::QuietHoursStore;
+use services::report_mailer::{ReportMailer, ReportSource};
+use services::result_cache::{build_result_cache, AnalysisCache, InMemoryResultCache, ANALYSIS_CACHE_TTL_SECS};
+use services::retention_service::{PgRetentionStore, RetentionService};
+use services::safe_boot;
+use services::shutdown_restore::{self, ShutdownFanAction, ShutdownRestore};
+use services::startup_self_check::{self, SelfCheckFailureAction};
+use services::alert_monitor::AlertMonitor;
+use services::alert_notifier::AlertNotifier;
+use services::stale_data_alert::StaleDataMonitor;
+use services::alert_rules::AlertRuleStore;
+use services::alert_store::AlertStore;
+use services::alert_thresholds::{AlertThresholdStore, SensorAlertThreshold};
+use services::sensor_blacklist::SensorBlacklist;
+use services::sensor_cache::LastGoodSensorCache;
+use services::sensor_calibration::{CalibratedIpmi, SensorCalibrations};
+use services::sensor_replay::SensorReplay;
+use services::sensor_source::SensorSource;
+use services::control_alert_sink::ControlAlertSink;
+use services::control_loop::FanControlLoop;
+use services::control_supervisor::ControlLoopSupervisor;
+use services::task_watchdog::TaskWatchdog;
+use services::temperature_histogram::TemperatureHistogramStore;
+use services::threshold_learning::ThresholdLearner;
 
 /// 应用程序状态
 #[derive(Clone)]
 pub struct AppState {
     pub config: Arc<AppConfig>,
-    pub ipmi_service: Arc<IpmiService>,
+    pub config_sources: Arc<ConfigSources>,
+    /// 温度读数已按传感器校准参数换算
+    pub ipmi_service: Arc<dyn Ipmi>,
+    /// 未经校准的IPMI读数
+    pub raw_ipmi_service: Arc<dyn Ipmi>,
+    /// 按传感器设置的温度校准参数
+    pub sensor_calibrations: SensorCalibrations,
+    /// 数据库不可用时为空
+    pub retention_service: Option<Arc<RetentionService>>,
+    /// 传感器黑名单
+    pub sensor_blacklist: SensorBlacklist,
+    /// 温度传感器最后一次成功读数
+    pub sensor_cache: LastGoodSensorCache,
+    /// 强制轮询限流
+    pub forced_poll: ForcedPollGate,
+    /// 按传感器设置的告警阈值
+    pub alert_thresholds: AlertThresholdStore,
+    /// 告警规则
+    pub alert_rules: AlertRuleStore,
+    /// 最近的告警及其状态变化推送
+    pub alerts: AlertStore,
+    /// 全部告警，内存中已淘汰的告警从这里查询
+    pub alert_history: Arc<dyn AlertHistoryStore>,
+    /// 告警累计统计的持久化，重置后立即写入
+    pub alert_statistics_store: Arc<dyn AlertStatisticsStore>,
+    /// 告警规则启停状态的持久化，启动时在默认规则之上恢复
+    pub alert_rule_states: Arc<dyn AlertRuleStateStore>,
+    /// 系统事件，目前记录告警状态变化
+    pub system_events: Arc<dyn SystemEventStore>,
+    /// 告警规则监控，后台任务与手动评估共用持续时间计时
+    pub alert_monitor: AlertMonitor,
+    /// 按配置的通知渠道发送告警
+    pub alert_notifier: AlertNotifier,
+    /// 关联告警汇总而成的事件
+    pub incidents: IncidentStore,
+    /// 操作人在历史曲线上添加的注释
+    pub annotations: Arc<dyn AnnotationStore>,
+    /// 未配置推送地址时为空
+    pub metrics_push: Option<Arc<MetricsPushService>>,
+    /// 导出文件及其签名下载链接
+    pub downloads: DownloadStore,
+    /// 告警阈值学习
+    pub threshold_learner: ThresholdLearner,
+    /// 各温度传感器最近一段时间的温度分布
+    pub temperature_histograms: TemperatureHistogramStore,
+    /// 各轮询任务因上一次轮询未结束而跳过的周期数
+    pub poll_overruns: PollOverruns,
+    /// PID自整定互斥锁，同一时间只允许一次实验
+    pub pid_autotune: Arc<tokio::sync::Mutex<()>>,
+    /// 后台任务看门狗，记录受监督任务的重启情况
+    pub task_watchdog: Arc<TaskWatchdog>,
+    /// `health.probes` 中配置的外部依赖HTTP探针
+    pub health_probes: HealthProbeService,
+    /// 分析接口的结果缓存，后端由 `cache.backend` 选择
+    pub analysis_cache: AnalysisCache,
+    /// 未配置 `fleet.chassis` 时为空
+    pub fleet_summary: Option<Arc<FleetSummaryService>>,
+    /// JWT签名密钥，轮换后宽限期内同时接受旧密钥
+    pub jwt_secrets: JwtSecrets,
+    /// 最近的日志行
+    pub recent_logs: RecentLogBuffer,
+    /// 最近的控制决策，由风扇控制循环记录
+    pub control_decisions: ControlDecisionLog,
+    /// 风扇自动控制循环
+    pub control_loop: FanControlLoop,
+    /// 风扇转速快照，过期后不能再恢复
+    pub fan_snapshots: Arc<dyn FanSnapshotStore>,
 }
 
 /// 配置CORS中间件
@@ -32,12 +146,14 @@ pub struct AppState {
 /// * `Cors` - 配置好的CORS中间件
 fn configure_cors(config: &AppConfig) -> Cors {
     let mut cors = Cors::default()
-        .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
+        .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"])
         .allowed_headers(vec![
             actix_web::http::header::AUTHORIZATION,
             actix_web::http::header::ACCEPT,
             actix_web::http::header::CONTENT_TYPE,
+            middleware::api_version::ACCEPT_VERSION,
         ])
+        .expose_headers(vec![middleware::api_version::X_API_VERSION])
         .max_age(3600);
 
     if config.server.cors_origins.is_empty() {
@@ -85,7 +201,8 @@ async fn api_info() -> Result<HttpResponse> {
             "/health",
             "/api/v1/temperature",
             "/api/v1/fans",
-            "/api/v1/alerts"
+            "/api/v1/alerts",
+            "/api/v1/incidents"
         ]
     })))
 }
@@ -94,14 +211,28 @@ async fn api_info() -> Result<HttpResponse> {
 ///
 /// # Arguments
 /// * `config` - 应用配置
-fn init_logging(config: &AppConfig) {
+fn init_logging(config: &AppConfig) -> RecentLogBuffer {
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| format!("{}={}", env!("CARGO_PKG_NAME"), config.logging.level).into());
 
+    let format = LogFormat::from_config(&config.logging.format);
+
+    let shipper = &config.logging.shipper;
+    let shipper_layer = (shipper.enabled && !shipper.url.is_empty())
+        .then(|| LogShipperLayer::spawn(shipper.clone()));
+
+    // 最近的日志同时保留在内存中，供支持包导出
+    let recent_logs = RecentLogBuffer::new(logging::DEFAULT_RECENT_LOG_LINES);
+    let recent_writer = recent_logs.clone();
+
     tracing_subscriber::registry()
         .with(env_filter)
-        .with(tracing_subscriber::fmt::layer())
+        .with(logging::fmt_layer(format, std::io::stdout))
+        .with(logging::fmt_layer(format, move || recent_writer.clone()))
+        .with(shipper_layer)
         .init();
+
+    recent_logs
 }
 
 /// 优雅关闭信号处理
@@ -144,14 +275,27 @@ async fn shutdown_signal() -> std::io::Result<()> {
     Ok(())
 }
 
+/// 受监督采样任务的心跳超时
+///
+/// 不低于配置的心跳超时，且至少为采样间隔的3倍，避免采样间隔较长的任务被误判为卡住
+///
+/// # 参数
+/// * `watchdog` - 后台任务看门狗
+/// * `interval_secs` - 任务的采样间隔（秒）
+fn sampling_heartbeat_timeout(watchdog: &TaskWatchdog, interval_secs: u64) -> std::time::Duration {
+    watchdog
+        .heartbeat_timeout()
+        .max(std::time::Duration::from_secs(interval_secs.max(1).saturating_mul(3)))
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     // 加载环境变量
     dotenvy::dotenv().ok();
 
     // 加载配置
-    let config = match AppConfig::load().await {
-        Ok(config) => Arc::new(config),
+    let (mut config, config_sources) = match AppConfig::load().await {
+        Ok((config, sources)) => (Arc::new(config), Arc::new(sources)),
         Err(e) => {
             eprintln!("Failed to load configuration: {}", e);
             std::process::exit(1);
@@ -159,7 +303,7 @@ async fn main() -> std::io::Result<()> {
     };
 
     // 初始化日志
-    init_logging(&config);
+    let recent_logs = init_logging(&config);
 
     info!(
         "Starting Server Thermal Control System v{}",
@@ -169,14 +313,57 @@ async fn main() -> std::io::Result<()> {
         "Server will bind to {}:{}",
         config.server.host, config.server.port
     );
+    info!("Loaded configuration: {}", config.redacted());
 
     // 创建IPMI服务
-    let ipmi_service = Arc::new(IpmiService::new(IpmiConfig {
+    let ipmi_service = IpmiService::new(IpmiConfig {
         host: config.ipmi.host.clone(),
         username: config.ipmi.username.clone(),
         password: config.ipmi.password.clone(),
         interface: config.ipmi.interface.clone(),
-    }));
+    })
+    .with_cache_config(&config.cache)
+    .with_unit_aliases(&config.ipmi.unit_aliases);
+
+    // 回放录制的传感器轨迹代替BMC，用于复现现场问题和演示
+    let ipmi_service = if config.ipmi.interface == "replay" {
+        match SensorReplay::from_config(&config.ipmi.replay) {
+            Ok(replay) => {
+                warn!(
+                    "Replaying sensor trace {} at {}x, fan commands will not reach hardware",
+                    config.ipmi.replay.trace_path, config.ipmi.replay.speed
+                );
+                ipmi_service.with_replay(Arc::new(replay))
+            }
+            Err(e) => {
+                error!("Failed to load sensor replay trace: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        ipmi_service
+    };
+
+    // 选择风扇控制厂商配置档
+    let detected_profile = match config.ipmi.vendor_profile {
+        VendorProfile::Auto => ipmi_service.detect_vendor_profile(),
+        _ => None,
+    };
+    let (vendor_profile, fan_commands) = match config.ipmi.resolve_fan_control(detected_profile) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            error!("Invalid IPMI fan control configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if config.ipmi.vendor_profile == VendorProfile::Auto && detected_profile.is_none() {
+        warn!("BMC vendor not recognized, falling back to {:?} fan control profile", vendor_profile);
+    }
+    info!("Using {:?} fan control vendor profile", vendor_profile);
+    let ipmi_service = Arc::new(ipmi_service.with_fan_control_commands(fan_commands));
+
+    // 上次运行可能在手动模式下崩溃，先将风扇拉到保护转速，再初始化其余服务
+    safe_boot::apply_safe_floor(ipmi_service.as_ref(), config.control.safe_boot_fan_percent);
 
     // 测试IPMI连接
     match ipmi_service.test_connection() {
@@ -187,10 +374,326 @@ async fn main() -> std::io::Result<()> {
         }
     }
 
+    // 启动自检：确认传感器读取与风扇调速链路可用
+    let self_check = &config.control.startup_self_check;
+    if self_check.enabled && !config.read_only {
+        let report = startup_self_check::run(
+            ipmi_service.as_ref(),
+            self_check,
+            config.control.safe_boot_fan_percent,
+        )
+        .await;
+        report.log();
+        if !report.passed() {
+            match self_check.on_failure {
+                SelfCheckFailureAction::Abort => {
+                    error!("Startup self-check failed, aborting startup");
+                    std::process::exit(1);
+                }
+                SelfCheckFailureAction::ReadOnly => {
+                    warn!("Startup self-check failed, starting in read-only monitoring mode");
+                    Arc::make_mut(&mut config).read_only = true;
+                }
+            }
+        }
+    }
+
+    // 关闭时按配置交还BMC自动控制或设为保护转速，只读模式下不下发转速
+    let shutdown_action = if config.read_only {
+        ShutdownFanAction::RestoreAutomatic
+    } else {
+        config.control.shutdown_action
+    };
+    let shutdown_restore = ShutdownRestore::new(
+        Arc::clone(&ipmi_service) as Arc<dyn safe_boot::SafeBootFans>,
+        shutdown_action,
+        config.control.safe_boot_fan_percent,
+    );
+
+    let database_pool = match sqlx::postgres::PgPoolOptions::new()
+        .max_connections(config.database.max_connections)
+        .acquire_timeout(std::time::Duration::from_secs(config.database.connect_timeout))
+        .connect_lazy(&config.database.url)
+    {
+        Ok(pool) => Some(pool),
+        Err(e) => {
+            warn!("Invalid database URL, data retention disabled and annotations and alert history kept in memory: {}", e);
+            None
+        }
+    };
+
+    // 创建数据保留服务并启动每日清理任务
+    let retention_service = database_pool.clone().map(|pool| {
+        let service = RetentionService::new(Arc::new(PgRetentionStore::new(pool)), config.monitoring.retention_days);
+        service.start_retention_task();
+        Arc::new(service)
+    });
+
+    let annotations: Arc<dyn AnnotationStore> = match database_pool.clone() {
+        Some(pool) => Arc::new(PgAnnotationStore::new(pool)),
+        None => Arc::new(InMemoryAnnotationStore::new()),
+    };
+
+    let alert_history: Arc<dyn AlertHistoryStore> = match database_pool.clone() {
+        Some(pool) => Arc::new(PgAlertHistoryStore::new(pool)),
+        None => Arc::new(InMemoryAlertHistoryStore::new()),
+    };
+
+    let alert_statistics_store: Arc<dyn AlertStatisticsStore> = match database_pool.clone() {
+        Some(pool) => Arc::new(PgAlertStatisticsStore::new(pool)),
+        None => Arc::new(InMemoryAlertStatisticsStore::new()),
+    };
+
+    let alert_rule_states: Arc<dyn AlertRuleStateStore> = match database_pool.clone() {
+        Some(pool) => Arc::new(PgAlertRuleStateStore::new(pool)),
+        None => Arc::new(InMemoryAlertRuleStateStore::new()),
+    };
+
+    let system_events: Arc<dyn SystemEventStore> = match database_pool.clone() {
+        Some(pool) => Arc::new(PgSystemEventStore::new(pool)),
+        None => Arc::new(InMemorySystemEventStore::new()),
+    };
+
+    let fan_snapshots: Arc<dyn FanSnapshotStore> = match database_pool.clone() {
+        Some(pool) => Arc::new(PgFanSnapshotStore::new(pool)),
+        None => Arc::new(InMemoryFanSnapshotStore::new()),
+    };
+
+    // 控制决策，配置数据库时同时持久化，供按动作类型和风扇查询控制历史
+    let control_decisions = match database_pool {
+        Some(pool) => ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS).with_store(Arc::new(PgControlDecisionStore::new(pool))),
+        None => ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS),
+    };
+
+    let sensor_blacklist = SensorBlacklist::new(config.monitoring.sensor_blacklist.clone());
+    let poll_overruns = PollOverruns::default();
+
+    // 后台任务看门狗，采样和告警监控任务panic或停止心跳时按退避时间重启
+    let task_watchdog = Arc::new(TaskWatchdog::new(config.monitoring.watchdog.clone(), Arc::clone(&system_events)));
+
+    // 传感器校准，此后的监控、告警、控制循环和接口读到的都是校准后的温度
+    let sensor_calibrations = SensorCalibrations::load(&config.monitoring.sensor_calibration_file).unwrap_or_else(|e| {
+        warn!("Failed to load sensor calibrations, readings are not calibrated: {}", e);
+        SensorCalibrations::default()
+    });
+    let raw_ipmi_service = ipmi_service;
+    let ipmi_service = Arc::new(CalibratedIpmi::new(
+        Arc::clone(&raw_ipmi_service) as Arc<dyn Ipmi>,
+        sensor_calibrations.clone(),
+    ));
+
+    // 创建指标推送服务，启用时按间隔推送
+    let metrics_push = if config.metrics_push.url.is_empty() {
+        None
+    } else {
+        let service = MetricsPushService::new(config.metrics_push.clone());
+        if config.metrics_push.enabled {
+            service.start_push_task(
+                Arc::clone(&ipmi_service) as Arc<dyn Ipmi>,
+                sensor_blacklist.clone(),
+                poll_overruns.clone(),
+            );
+        }
+        Some(Arc::new(service))
+    };
+
+    // 阈值学习，启用时在学习期内按间隔采样
+    let sensor_source: Arc<dyn SensorSource> = Arc::clone(&ipmi_service) as Arc<dyn SensorSource>;
+    let threshold_learner = ThresholdLearner::new(config.alert.threshold_learning.clone(), chrono::Utc::now());
+    if config.alert.threshold_learning.enabled {
+        let (learner, source, blacklist, overruns) = (
+            threshold_learner.clone(),
+            Arc::clone(&sensor_source),
+            sensor_blacklist.clone(),
+            poll_overruns.clone(),
+        );
+        let timeout = sampling_heartbeat_timeout(&task_watchdog, config.alert.threshold_learning.sample_interval_secs);
+        task_watchdog.supervise("threshold_learning", Some(timeout), move |heartbeat| {
+            learner
+                .clone()
+                .run_sampling(Arc::clone(&source), blacklist.clone(), overruns.clone(), heartbeat)
+        });
+    }
+
+    // 温度分布直方图，启用时按间隔采样
+    let temperature_histograms = TemperatureHistogramStore::new(config.monitoring.temperature_histogram.clone());
+    if config.monitoring.temperature_histogram.enabled {
+        let (histograms, source, blacklist, overruns) = (
+            temperature_histograms.clone(),
+            Arc::clone(&sensor_source),
+            sensor_blacklist.clone(),
+            poll_overruns.clone(),
+        );
+        let timeout = sampling_heartbeat_timeout(&task_watchdog, config.monitoring.temperature_histogram.sample_interval_secs);
+        task_watchdog.supervise("temperature_histogram", Some(timeout), move |heartbeat| {
+            histograms
+                .clone()
+                .run_sampling(Arc::clone(&source), blacklist.clone(), overruns.clone(), heartbeat)
+        });
+    }
+
+    // 定时报告邮件，按温度分布直方图生成，SMTP连接使用告警邮件配置
+    let alert_thresholds = AlertThresholdStore::default();
+    if config.report_email.enabled {
+        match ReportMailer::from_smtp_config(&config.alert.email, config.report_email.clone()) {
+            Ok(mailer) => {
+                Arc::new(mailer).start_task(ReportSource {
+                    histograms: temperature_histograms.clone(),
+                    alert_thresholds: alert_thresholds.clone(),
+                    default_threshold: SensorAlertThreshold {
+                        warning: config.monitoring.alert_threshold_temp,
+                        critical: config.control.emergency_exit.critical_temperature,
+                    },
+                });
+            }
+            Err(e) => warn!("Scheduled report email disabled: {}", e),
+        }
+    }
+
+    // 告警规则监控，启用时按监控间隔评估规则
+    let sensor_cache = LastGoodSensorCache::new(config.monitoring.last_good_max_age_secs);
+    let alert_rules = AlertRuleStore::with_defaults(
+        config.monitoring.alert_threshold_temp,
+        config.monitoring.alert_threshold_fan,
+    );
+    match alert_rule_states.load().await {
+        Ok(states) => alert_rules.restore_enabled(&states),
+        Err(e) => warn!("Failed to restore alert rule states, using defaults: {}", e),
+    }
+    let alerts = AlertStore::new(config.alert.history_limit);
+    if let Err(e) = alerts.restore_statistics(alert_statistics_store.as_ref()).await {
+        warn!("Failed to restore alert statistics, starting from zero: {}", e);
+    }
+    alerts.start_statistics_task(Arc::clone(&alert_statistics_store), poll_overruns.clone());
+    alerts.start_event_task(Arc::clone(&system_events));
+    let incidents = IncidentStore::new(config.alert.correlation.clone());
+    let alert_monitor = AlertMonitor::new(alert_rules.clone(), alerts.clone(), incidents.clone())
+        .with_gradient(config.alert.gradient.clone());
+    if config.alert.enabled && config.monitoring.enabled {
+        let poll_interval = std::time::Duration::from_secs(config.monitoring.interval.max(1));
+        let (monitor, source, history, blacklist, cache, overruns) = (
+            alert_monitor.clone(),
+            Arc::clone(&sensor_source),
+            Arc::clone(&alert_history),
+            sensor_blacklist.clone(),
+            sensor_cache.clone(),
+            poll_overruns.clone(),
+        );
+        let timeout = sampling_heartbeat_timeout(&task_watchdog, config.monitoring.interval);
+        task_watchdog.supervise("alert_monitor", Some(timeout), move |heartbeat| {
+            monitor.clone().run(
+                Arc::clone(&source),
+                Arc::clone(&history),
+                blacklist.clone(),
+                cache.clone(),
+                PollTicker::new("alert_monitor", poll_interval, overruns.clone()),
+                heartbeat,
+            )
+        });
+        // 监控任务本身停止时，告警规则随之失效，由独立任务检查数据是否过期
+        if config.monitoring.stale_data_alert.enabled {
+            let stale_data = StaleDataMonitor::new(
+                config.monitoring.stale_data_alert.clone(),
+                alerts.clone(),
+                Arc::clone(&alert_history),
+                AlertNotifier::from_config(&config.alert),
+            );
+            let (monitor, overruns) = (alert_monitor.clone(), poll_overruns.clone());
+            let timeout = sampling_heartbeat_timeout(&task_watchdog, config.monitoring.stale_data_alert.check_interval_secs);
+            task_watchdog.supervise("stale_data_watch", Some(timeout), move |heartbeat| {
+                stale_data
+                    .clone()
+                    .run(monitor.clone(), poll_interval, overruns.clone(), heartbeat)
+            });
+        }
+    }
+
+    // 风扇自动控制循环，启用且非只读时在监督下运行，panic后先进入安全状态再重新启动
+    let control_alerts = Arc::new(ControlAlertSink::new(alerts.clone(), Arc::clone(&alert_history)));
+    let quiet_hours = QuietHoursStore::load(&config.control.quiet_hours_file).unwrap_or_else(|e| {
+        warn!("Failed to load quiet hours schedule, quiet hours disabled: {}", e);
+        QuietHoursStore::default()
+    });
+    let mut control_loop = FanControlLoop::new(
+        Arc::clone(&sensor_source),
+        sensor_blacklist.clone(),
+        &config.control,
+        control_decisions.clone(),
+    )
+    .with_write_alerts(control_alerts.clone())
+    .with_degradation_alerts(control_alerts.clone())
+    .with_quiet_hours(quiet_hours);
+    if config.control.power_cap.enabled {
+        control_loop = control_loop.with_power_cap(
+            PowerCapCoordinator::new(config.control.power_cap.clone(), Arc::clone(&raw_ipmi_service) as Arc<dyn PowerReading>)
+                .with_alert_sink(control_alerts.clone()),
+        );
+    }
+    if config.control.enabled && !config.read_only {
+        let supervisor = Arc::new(ControlLoopSupervisor::new(
+            Arc::clone(&raw_ipmi_service) as Arc<dyn safe_boot::SafeBootFans>,
+            Arc::clone(&system_events),
+            config.control.panic_fail_safe,
+            config.control.safe_boot_fan_percent,
+        ));
+        control_loop.start(supervisor, poll_overruns.clone());
+    }
+
+    // JWT签名密钥，优先使用轮换后保存的密钥
+    let jwt_secrets = match JwtSecrets::load(&config.security) {
+        Ok(secrets) => secrets,
+        Err(e) => {
+            error!("Failed to load JWT secrets: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // 分析结果缓存，Redis不可用时退回进程内缓存，不影响风扇控制
+    let analysis_cache = match build_result_cache(&config.cache, &config.redis).await {
+        Ok(results) => AnalysisCache::new(results),
+        Err(e) => {
+            warn!("Analytics result cache unavailable, falling back to memory: {}", e);
+            AnalysisCache::new(Arc::new(InMemoryResultCache::new(ANALYSIS_CACHE_TTL_SECS)))
+        }
+    };
+
     // 创建应用状态
     let app_state = AppState {
         config: Arc::clone(&config),
-        ipmi_service,
+        config_sources,
+        ipmi_service: ipmi_service as Arc<dyn Ipmi>,
+        raw_ipmi_service: raw_ipmi_service as Arc<dyn Ipmi>,
+        sensor_calibrations,
+        retention_service,
+        sensor_blacklist,
+        sensor_cache,
+        forced_poll: ForcedPollGate::new(config.monitoring.forced_poll_min_interval_secs),
+        alert_thresholds,
+        alert_rules,
+        alerts,
+        alert_history,
+        alert_statistics_store,
+        alert_rule_states,
+        system_events,
+        alert_monitor,
+        alert_notifier: AlertNotifier::from_config(&config.alert),
+        incidents,
+        annotations,
+        metrics_push,
+        downloads: DownloadStore::from_config(&config),
+        threshold_learner,
+        temperature_histograms,
+        poll_overruns,
+        pid_autotune: Arc::new(tokio::sync::Mutex::new(())),
+        task_watchdog,
+        health_probes: HealthProbeService::new(config.health.probes.clone()),
+        analysis_cache,
+        fleet_summary: FleetSummaryService::from_config(&config.fleet, &config.ipmi).map(Arc::new),
+        jwt_secrets,
+        recent_logs,
+        control_decisions,
+        control_loop,
+        fan_snapshots,
     };
 
     // 获取服务器配置
@@ -198,79 +701,316 @@ async fn main() -> std::io::Result<()> {
     let port = config.server.port;
     let workers = config.server.workers.unwrap_or_else(|| num_cpus::get());
 
+    // 证书在启动服务器前加载，配置错误时直接退出
+    let tls_config = if config.server.tls.enabled {
+        match config.server.tls.load_server_config() {
+            Ok(tls_config) => {
+                info!("HTTPS enabled, minimum TLS version {:?}", config.server.tls.min_version);
+                Some(tls_config)
+            }
+            Err(e) => {
+                error!("Failed to load TLS certificate: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
     // 启动HTTP服务器
     let server = HttpServer::new(move || {
         let cors = configure_cors(&config);
         App::new()
             .app_data(web::Data::new(app_state.clone()))
+            .wrap(RequireJsonContentType)
+            .wrap(RequestTimeout::from_secs(config.performance.request_timeout))
+            .wrap(CacheControl::new(&config.server.cache_control))
+            .wrap(ApiVersioning::new(&config.server.api_version))
             .wrap(cors)
             .wrap(Logger::default())
-            .route("/", web::get().to(root))
-            .route("/version", web::get().to(version))
-            .route("/api", web::get().to(api_info))
-            .service(
-                web::scope("/api/v1")
-                    .route("/health", web::get().to(handlers::health_check))
-                    .route("/system/info", web::get().to(handlers::system_info))
-                    .route("/system/health", web::get().to(handlers::system_health))
-                    .route(
-                        "/stats/temperature",
-                        web::get().to(handlers::temperature_stats),
-                    )
-                    .route("/stats/fan", web::get().to(handlers::fan_stats))
-            )
-            .service(
-                web::scope("/temperature")
-                    .route(
-                        "",
-                        web::get().to(handlers::temperature::list_temperature_data),
-                    )
-                    .route(
-                        "/{sensor_id}",
-                        web::get().to(handlers::temperature::get_sensor_temperature),
-                    ),
-            )
-            .service(
-                web::scope("/fans")
-                    .route("", web::get().to(handlers::fan::list_fan_data))
-                    .route(
-                        "/{fan_id}/speed",
-                        web::post().to(handlers::fan::set_fan_speed),
-                    ),
-            )
-            .service(
-                web::scope("/alerts")
-                    .route("", web::get().to(handlers::alert::list_alerts))
-                    .route(
-                        "/{alert_id}/acknowledge",
-                        web::post().to(handlers::alert::acknowledge_alert),
-                    ),
-            )
+            .wrap(middleware::fallback::method_not_allowed())
+            .configure(configure_routes)
+            .default_service(web::to(middleware::fallback::not_found))
     })
-    .workers(workers)
-    .bind((host.as_str(), port))?;
+    .workers(workers);
+    let server = match tls_config {
+        Some(tls_config) => server.bind_rustls_0_23((host.as_str(), port), tls_config)?,
+        None => server.bind((host.as_str(), port))?,
+    };
 
     info!("Server started successfully with {} workers", workers);
 
-    // 启动服务器并等待关闭信号
-    let server_handle = server.run();
-
-    tokio::select! {
-        result = server_handle => {
-            match result {
-                Ok(_) => info!("Server stopped normally"),
-                Err(e) => error!("Server error: {}", e),
-            }
-        }
-        _ = shutdown_signal() => {
-            info!("Shutdown signal received, stopping server...");
-        }
-    }
+    // 启动服务器并等待关闭信号，退出前下发关闭时的风扇状态
+    shutdown_restore::serve_until_shutdown(server.run(), shutdown_signal(), &shutdown_restore).await;
 
     info!("Server shutdown complete");
     Ok(())
 }
 
+/// 注册所有路由
+///
+/// 同一路径的各方法注册在同一个资源上，方法不匹配时返回带 `Allow` 头的405而不是404
+fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/").route(web::get().to(root)))
+        .service(web::resource("/version").route(web::get().to(version)))
+        .service(web::resource("/api").route(web::get().to(api_info)))
+        .service(web::resource("/metrics").route(web::get().to(handlers::metrics::export_metrics)))
+        .service(
+            web::scope("/api/v1")
+                .service(web::resource("/health").route(web::get().to(handlers::health_check)))
+                .service(web::resource("/summary").route(web::get().to(handlers::summary::get_fleet_summary)))
+                .service(web::resource("/dashboard").route(web::get().to(handlers::dashboard::get_dashboard)))
+                .service(web::resource("/errors").route(web::get().to(handlers::errors::list_error_codes)))
+                .service(web::resource("/system/info").route(web::get().to(handlers::system_info)))
+                .service(web::resource("/system/health").route(web::get().to(handlers::system_health)))
+                .service(
+                    web::resource("/system/restart-control")
+                        .route(web::post().to(handlers::control::restart_control_loop)),
+                )
+                .service(web::resource("/health/dependencies").route(web::get().to(handlers::dependency_health)))
+                .service(
+                    web::resource("/stats/temperature")
+                        .route(web::get().to(handlers::temperature_stats)),
+                )
+                .service(
+                    web::resource("/stats/temperature/histogram")
+                        .route(web::get().to(handlers::temperature_histogram)),
+                )
+                .service(
+                    web::resource("/stats/temperature/history")
+                        .route(web::get().to(handlers::temperature_history)),
+                )
+                .service(web::resource("/stats/fan").route(web::get().to(handlers::fan_stats)))
+                .service(web::resource("/analytics/report").route(web::get().to(handlers::analytics::get_report)))
+                .service(web::resource("/analytics/compare").route(web::get().to(handlers::analytics::compare)))
+                .service(
+                    web::resource("/analytics/cache/clear")
+                        .route(web::post().to(handlers::analytics::clear_cache)),
+                )
+                .service(
+                    web::resource("/config/effective")
+                        .route(web::get().to(handlers::config::get_effective_config)),
+                )
+                .service(
+                    web::resource("/config/template")
+                        .route(web::get().to(handlers::config::get_config_template)),
+                )
+                .service(
+                    web::resource("/config/snapshot")
+                        .route(web::get().to(handlers::config::get_config_snapshot)),
+                )
+                .service(
+                    web::resource("/config/restore")
+                        .route(web::post().to(handlers::config::restore_config_snapshot)),
+                )
+                .service(web::resource("/monitoring/poll").route(web::post().to(handlers::sensor::force_poll)))
+                .service(web::resource("/sensors").route(web::get().to(handlers::sensor::list_sensors)))
+                .service(
+                    web::resource("/sensors/locations")
+                        .route(web::get().to(handlers::sensor::list_sensor_locations)),
+                )
+                .service(
+                    web::resource("/sensors/blacklist")
+                        .route(web::get().to(handlers::sensor::get_sensor_blacklist))
+                        .route(web::put().to(handlers::sensor::update_sensor_blacklist)),
+                )
+                .service(
+                    web::resource("/sensors/calibration")
+                        .route(web::get().to(handlers::sensor::get_sensor_calibration))
+                        .route(web::put().to(handlers::sensor::set_sensor_calibration)),
+                )
+                .service(
+                    web::resource("/sensors/calibration/{sensor_id}")
+                        .route(web::delete().to(handlers::sensor::delete_sensor_calibration)),
+                )
+                .service(
+                    web::resource("/alerts/thresholds/bulk")
+                        .route(web::put().to(handlers::alert::bulk_apply_thresholds)),
+                )
+                .service(
+                    web::resource("/alerts/thresholds/suggest")
+                        .route(web::get().to(handlers::alert::suggest_thresholds)),
+                )
+                .service(
+                    web::resource("/alerts/evaluate").route(web::post().to(handlers::alert::evaluate_alert_rules)),
+                )
+                .service(
+                    web::resource("/alerts/channels/{channel_id}/preview")
+                        .route(web::post().to(handlers::alert::preview_notification)),
+                )
+                .service(
+                    web::resource("/alerts/selftest").route(web::post().to(handlers::alert::run_alert_selftest)),
+                )
+                .service(
+                    web::resource("/alerts/statistics").route(web::get().to(handlers::alert::get_alert_statistics)),
+                )
+                .service(
+                    web::resource("/alerts/statistics/reset")
+                        .route(web::post().to(handlers::alert::reset_alert_statistics)),
+                )
+                .service(web::resource("/alerts/stream").route(web::get().to(handlers::alert::stream_alerts)))
+                .service(web::resource("/alerts/preview").route(web::get().to(handlers::alert::preview_alerts)))
+                .service(
+                    web::resource("/alerts/mutes")
+                        .route(web::get().to(handlers::alert::list_alert_mutes))
+                        .route(web::post().to(handlers::alert::create_alert_mute)),
+                )
+                .service(
+                    web::resource("/alerts/mutes/{mute_id}")
+                        .route(web::delete().to(handlers::alert::delete_alert_mute)),
+                )
+                .service(
+                    web::resource("/alerts/rules")
+                        .route(web::get().to(handlers::alert::list_alert_rules))
+                        .route(web::put().to(handlers::alert::apply_alert_rule)),
+                )
+                .service(
+                    web::resource("/alerts/rules/{rule_id}")
+                        .route(web::patch().to(handlers::alert::patch_alert_rule)),
+                )
+                .service(
+                    web::resource("/alerts/rules/{rule_id}/toggle")
+                        .route(web::post().to(handlers::alert::toggle_alert_rule)),
+                )
+                .service(
+                    web::resource("/alerts/rules/{rule_id}/test")
+                        .route(web::post().to(handlers::alert::test_alert_rule)),
+                )
+                .service(
+                    web::resource("/incidents").route(web::get().to(handlers::incident::list_incidents)),
+                )
+                .service(
+                    web::resource("/incidents/{incident_id}")
+                        .route(web::get().to(handlers::incident::get_incident)),
+                )
+                .service(
+                    web::resource("/incidents/{incident_id}/acknowledge")
+                        .route(web::post().to(handlers::incident::acknowledge_incident)),
+                )
+                .service(
+                    web::resource("/incidents/{incident_id}/resolve")
+                        .route(web::post().to(handlers::incident::resolve_incident)),
+                )
+                .service(web::resource("/events").route(web::get().to(handlers::event::list_events)))
+                .service(
+                    web::resource("/annotations")
+                        .route(web::get().to(handlers::annotation::list_annotations))
+                        .route(web::post().to(handlers::annotation::create_annotation)),
+                )
+                .service(
+                    web::resource("/control/pid/tune").route(web::post().to(handlers::control::pid_autotune)),
+                )
+                .service(
+                    web::resource("/control/estimate").route(web::get().to(handlers::control::estimate_target)),
+                )
+                .service(
+                    web::resource("/control/fan/{fan_id}/curve/validate")
+                        .route(web::post().to(handlers::control::validate_fan_curve)),
+                )
+                .service(
+                    web::resource("/control/history").route(web::get().to(handlers::control::get_control_history)),
+                )
+                .service(
+                    web::resource("/control/decisions")
+                        .route(web::get().to(handlers::control::get_recent_decisions)),
+                )
+                .service(
+                    web::resource("/control/emergency/exit")
+                        .route(web::post().to(handlers::control::exit_emergency)),
+                )
+                .service(
+                    web::resource("/control/parameters")
+                        .route(web::get().to(handlers::control::get_control_parameters))
+                        .route(web::put().to(handlers::control::update_control_parameters))
+                        .route(web::patch().to(handlers::control::patch_control_parameters)),
+                )
+                .service(
+                    web::resource("/control/parameters/history")
+                        .route(web::get().to(handlers::control::get_parameter_history)),
+                )
+                .service(
+                    web::resource("/control/schedule")
+                        .route(web::get().to(handlers::control::get_quiet_hours))
+                        .route(web::put().to(handlers::control::update_quiet_hours)),
+                )
+                .service(
+                    web::resource("/control/strategy/apply")
+                        .route(web::post().to(handlers::control::apply_control_strategy)),
+                )
+                .service(
+                    web::resource("/control/fans/snapshot")
+                        .route(web::post().to(handlers::control::snapshot_fan_speeds)),
+                )
+                .service(
+                    web::resource("/control/fans/restore/{snapshot_id}")
+                        .route(web::post().to(handlers::control::restore_fan_snapshot)),
+                )
+                .service(web::resource("/ipmi/raw").route(web::get().to(handlers::ipmi::get_raw_output)))
+                .service(web::resource("/ipmi/info").route(web::get().to(handlers::ipmi::get_ipmi_info)))
+                .service(web::resource("/ipmi/fan-thresholds").route(web::get().to(handlers::ipmi::get_fan_threshold_check)))
+                .service(
+                    web::resource("/downloads/{token}")
+                        .route(web::get().to(handlers::downloads::download_export)),
+                )
+                .service(
+                    web::resource("/admin/retention/run")
+                        .route(web::post().to(handlers::admin::run_retention)),
+                )
+                .service(
+                    web::resource("/admin/metrics/push")
+                        .route(web::post().to(handlers::metrics::push_metrics)),
+                )
+                .service(
+                    web::resource("/admin/rotate-jwt-secret")
+                        .route(web::post().to(handlers::admin::rotate_jwt_secret)),
+                )
+                .service(
+                    web::resource("/support-bundle")
+                        .route(web::get().to(handlers::support::download_support_bundle)),
+                ),
+        )
+        .service(
+            web::scope("/temperature")
+                .service(
+                    web::resource("")
+                        .route(web::get().to(handlers::temperature::list_temperature_data)),
+                )
+                .service(
+                    web::resource("/{sensor_id}")
+                        .route(web::get().to(handlers::temperature::get_sensor_temperature)),
+                ),
+        )
+        .service(
+            web::scope("/fans")
+                .service(web::resource("").route(web::get().to(handlers::fan::list_fan_data)))
+                .service(
+                    web::resource("/{fan_id}/speed")
+                        .route(web::post().to(handlers::fan::set_fan_speed)),
+                ),
+        )
+        .service(
+            web::scope("/alerts")
+                .service(web::resource("").route(web::get().to(handlers::alert::list_alerts)))
+                .service(
+                    web::resource("/channels/validate")
+                        .route(web::post().to(handlers::alert::validate_channel)),
+                )
+                .service(web::resource("/{alert_id}").route(web::get().to(handlers::alert::get_alert)))
+                .service(
+                    web::resource("/{alert_id}/rule")
+                        .route(web::get().to(handlers::alert::get_alert_rule_for_alert)),
+                )
+                .service(
+                    web::resource("/{alert_id}/acknowledge")
+                        .route(web::post().to(handlers::alert::acknowledge_alert)),
+                )
+                .service(
+                    web::resource("/{alert_id}/resolve")
+                        .route(web::post().to(handlers::alert::resolve_alert)),
+                ),
+        );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,4 +1045,43 @@ mod tests {
 
         assert!(resp.status().is_success());
     }
+
+    #[actix_web::test]
+    async fn test_unknown_path_returns_json_404() {
+        let app = test::init_service(
+            App::new()
+                .wrap(middleware::fallback::method_not_allowed())
+                .configure(configure_routes)
+                .default_service(web::to(middleware::fallback::not_found)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/api/v1/no-such-endpoint").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"], "未找到资源: GET /api/v1/no-such-endpoint");
+    }
+
+    #[actix_web::test]
+    async fn test_wrong_method_returns_405_with_allow_header() {
+        let app = test::init_service(
+            App::new()
+                .wrap(middleware::fallback::method_not_allowed())
+                .configure(configure_routes)
+                .default_service(web::to(middleware::fallback::not_found)),
+        )
+        .await;
+
+        let req = test::TestRequest::delete().uri("/api/v1/sensors/blacklist").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers().get(actix_web::http::header::ALLOW).unwrap(), "GET, PUT");
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains("DELETE"));
+    }
 }
@@ -0,0 +1,220 @@
+use crate::models::error::AppError;
+use actix_web::{
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    Error, FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::rc::Rc;
+
+/// 客户端请求的响应版本
+pub const ACCEPT_VERSION: HeaderName = HeaderName::from_static("accept-version");
+/// 本次响应使用的版本
+pub const X_API_VERSION: HeaderName = HeaderName::from_static("x-api-version");
+
+/// 响应结构版本
+///
+/// v1 的响应包装即 [`crate::models::api::ApiResponse`]。新增版本时在此添加变体，
+/// 处理函数以 `ApiVersion` 作为参数提取协商结果，按版本构造响应
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApiVersion {
+    /// 当前版本
+    #[default]
+    #[serde(rename = "v1")]
+    V1,
+}
+
+impl ApiVersion {
+    /// 支持的全部版本
+    pub const SUPPORTED: &'static [ApiVersion] = &[ApiVersion::V1];
+
+    /// 版本名称，即 `X-API-Version` 的值
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+        }
+    }
+
+    /// 解析版本号，接受 `v1` 或 `1`，不区分大小写
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let number = value.strip_prefix(['v', 'V']).unwrap_or(value);
+        Self::SUPPORTED
+            .iter()
+            .copied()
+            .find(|version| &version.as_str()[1..] == number)
+    }
+
+    /// 支持的版本列表，逗号分隔
+    fn supported_list() -> String {
+        Self::SUPPORTED.iter().map(|version| version.as_str()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// 响应版本协商配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiVersionConfig {
+    /// 是否协商版本并设置 `X-API-Version` 响应头
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 请求未携带 `Accept-Version` 时使用的版本
+    #[serde(default)]
+    pub default_version: ApiVersion,
+}
+
+impl Default for ApiVersionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            default_version: ApiVersion::default(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 选出 `Accept-Version` 中第一个支持的版本
+///
+/// 请求头可以按偏好顺序列出多个版本（逗号分隔）；都不支持时返回406错误
+fn negotiate(accept_version: Option<&str>, default_version: ApiVersion) -> Result<ApiVersion, AppError> {
+    let Some(requested) = accept_version.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(default_version);
+    };
+    requested
+        .split(',')
+        .find_map(ApiVersion::parse)
+        .ok_or_else(|| AppError::not_acceptable_error(requested, ApiVersion::supported_list()))
+}
+
+/// 响应版本协商中间件
+///
+/// 按请求头 `Accept-Version` 选定响应版本，不支持时在进入处理函数之前以
+/// [`AppError::NotAcceptableError`] 响应406；选定的版本放入请求扩展供处理函数提取，
+/// 并写入 `X-API-Version` 响应头
+#[derive(Debug, Clone)]
+pub struct ApiVersioning {
+    config: ApiVersionConfig,
+}
+
+impl ApiVersioning {
+    /// 按配置创建版本协商中间件
+    ///
+    /// # 参数
+    /// * `config` - 响应版本协商配置
+    pub fn new(config: &ApiVersionConfig) -> Self {
+        Self { config: config.clone() }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiVersioning
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ApiVersioningMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiVersioningMiddleware {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        }))
+    }
+}
+
+/// 响应版本协商中间件服务
+pub struct ApiVersioningMiddleware<S> {
+    service: Rc<S>,
+    config: ApiVersionConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiVersioningMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        if !self.config.enabled {
+            return Box::pin(async move { service.call(req).await });
+        }
+
+        let accept_version = req.headers().get(ACCEPT_VERSION).map(|value| value.to_str().unwrap_or_default());
+        let version = match negotiate(accept_version, self.config.default_version) {
+            Ok(version) => version,
+            Err(err) => return Box::pin(async move { Err(err.into()) }),
+        };
+        req.extensions_mut().insert(version);
+
+        Box::pin(async move {
+            let mut response = service.call(req).await?;
+            response
+                .headers_mut()
+                .insert(X_API_VERSION, HeaderValue::from_static(version.as_str()));
+            Ok(response)
+        })
+    }
+}
+
+/// 提取协商后的响应版本，未经过版本协商中间件时为当前版本
+impl FromRequest for ApiVersion {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(req.extensions().get::<ApiVersion>().copied().unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::api::ApiResponse;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+    use serde_json::Value;
+
+    #[actix_web::test]
+    async fn test_supported_version_echoed_and_unsupported_rejected_with_406() {
+        let app = test::init_service(
+            App::new().wrap(ApiVersioning::new(&ApiVersionConfig::default())).route(
+                "/api/v1/sensors",
+                web::get().to(|version: ApiVersion| async move {
+                    HttpResponse::Ok().json(ApiResponse::success(version.as_str()))
+                }),
+            ),
+        )
+        .await;
+
+        let request = |accept_version: Option<&str>| {
+            let request = test::TestRequest::get().uri("/api/v1/sensors");
+            match accept_version {
+                Some(value) => request.insert_header((ACCEPT_VERSION, value)).to_request(),
+                None => request.to_request(),
+            }
+        };
+
+        for accept_version in [Some("v1"), Some("1"), Some("v2, v1"), None] {
+            let response = test::call_service(&app, request(accept_version)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers().get(X_API_VERSION).unwrap(), "v1");
+            let body: Value = test::read_body_json(response).await;
+            assert_eq!(body["data"], "v1");
+        }
+
+        let err = test::try_call_service(&app, request(Some("v2"))).await.unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::NOT_ACCEPTABLE);
+    }
+}
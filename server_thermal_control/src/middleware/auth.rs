@@ -0,0 +1,71 @@
+use crate::config::SecurityConfig;
+use crate::models::error::{AppError, AppResult};
+use crate::services::jwt_secrets::JwtSecrets;
+use actix_web::HttpRequest;
+use serde::Deserialize;
+
+/// API密钥请求头
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// 校验请求携带的API密钥
+///
+/// 接受 `X-API-Key: <key>` 或 `Authorization: Bearer <key>`，与 `security.api_key` 比对
+///
+/// # 参数
+/// * `req` - HTTP请求
+/// * `security` - 安全配置
+pub fn require_api_key(req: &HttpRequest, security: &SecurityConfig) -> AppResult<()> {
+    if security.api_key.is_empty() {
+        return Err(AppError::authentication_error("未配置API密钥，拒绝访问受保护的端点"));
+    }
+
+    let headers = req.headers();
+    let provided = headers
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(actix_web::http::header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+        })
+        .ok_or_else(|| AppError::authentication_error("缺少API密钥"))?;
+
+    if constant_time_eq(provided.trim().as_bytes(), security.api_key.as_bytes()) {
+        Ok(())
+    } else {
+        Err(AppError::authentication_error("API密钥无效"))
+    }
+}
+
+/// JWT声明中用到的字段
+#[derive(Debug, Deserialize)]
+struct JwtClaims {
+    sub: String,
+}
+
+/// 取出请求所带JWT的主体（`sub`）
+///
+/// 令牌取自 `Authorization: Bearer <token>`，须以当前签名密钥或宽限期内的旧密钥按HS256签名且未过期；
+/// 未携带令牌或令牌无效时返回None
+///
+/// # 参数
+/// * `req` - HTTP请求
+/// * `secrets` - JWT签名密钥
+pub fn jwt_subject(req: &HttpRequest, secrets: &JwtSecrets) -> Option<String> {
+    let token = req
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+    let claims = secrets.decode::<JwtClaims>(token.trim())?;
+
+    let subject = claims.sub.trim();
+    (!subject.is_empty()).then(|| subject.to_string())
+}
+
+/// 长度相同时逐字节比较全部内容，避免通过响应时间推测密钥
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,259 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        header::{HeaderValue, CACHE_CONTROL},
+        Method,
+    },
+    Error,
+};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use std::sync::Arc;
+use tracing::warn;
+
+/// 缓存策略配置
+///
+/// 写操作一律使用 `mutating`；读请求按 `groups` 的顺序匹配路径，先匹配的分组生效，
+/// 都不匹配时使用 `default`。处理函数自行设置了 `Cache-Control` 的响应不做改动
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheControlConfig {
+    /// 是否设置 `Cache-Control` 响应头
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 写操作（POST/PUT/PATCH/DELETE）的缓存策略
+    #[serde(default = "default_mutating")]
+    pub mutating: String,
+    /// 未匹配任何分组的读请求的缓存策略，默认用于实时数据
+    #[serde(default = "default_policy")]
+    pub default: String,
+    /// 按路由分组的缓存策略
+    #[serde(default = "default_groups")]
+    pub groups: Vec<CacheControlGroup>,
+}
+
+impl Default for CacheControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            mutating: default_mutating(),
+            default: default_policy(),
+            groups: default_groups(),
+        }
+    }
+}
+
+/// 一组路由的缓存策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheControlGroup {
+    /// 分组名称，仅用于日志
+    pub name: String,
+    /// 路径列表，以 `/*` 结尾时匹配该前缀下的所有路径，否则须完全相同
+    pub paths: Vec<String>,
+    /// `Cache-Control` 响应头的值
+    pub value: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_mutating() -> String {
+    "no-store".to_string()
+}
+
+fn default_policy() -> String {
+    "no-cache".to_string()
+}
+
+fn default_groups() -> Vec<CacheControlGroup> {
+    let group = |name: &str, paths: &[&str], value: &str| CacheControlGroup {
+        name: name.to_string(),
+        paths: paths.iter().map(|path| path.to_string()).collect(),
+        value: value.to_string(),
+    };
+    vec![
+        group(
+            "info",
+            &["/version", "/api", "/api/v1/system/info", "/api/v1/errors", "/api/v1/config/template"],
+            "public, max-age=300",
+        ),
+        group(
+            "sensitive",
+            &["/api/v1/admin/*", "/api/v1/config/*", "/api/v1/downloads/*", "/api/v1/support-bundle"],
+            "no-store",
+        ),
+    ]
+}
+
+impl CacheControlGroup {
+    /// 路径是否属于该分组
+    fn matches(&self, path: &str) -> bool {
+        self.paths.iter().any(|pattern| match pattern.strip_suffix("/*") {
+            Some(prefix) => path == prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/')),
+            None => path == pattern,
+        })
+    }
+}
+
+/// 解析后的缓存策略
+#[derive(Debug)]
+struct CachePolicy {
+    mutating: HeaderValue,
+    default: HeaderValue,
+    groups: Vec<(CacheControlGroup, HeaderValue)>,
+}
+
+impl CachePolicy {
+    /// 请求对应的 `Cache-Control` 值
+    fn value_for(&self, method: &Method, path: &str) -> &HeaderValue {
+        if !matches!(*method, Method::GET | Method::HEAD) {
+            return &self.mutating;
+        }
+        self.groups
+            .iter()
+            .find(|(group, _)| group.matches(path))
+            .map(|(_, value)| value)
+            .unwrap_or(&self.default)
+    }
+}
+
+/// 缓存策略中间件
+///
+/// 按 [`CacheControlConfig`] 为响应设置 `Cache-Control`，避免客户端和代理随意缓存实时数据
+/// 或写操作的响应
+#[derive(Debug, Clone)]
+pub struct CacheControl {
+    policy: Option<Arc<CachePolicy>>,
+}
+
+impl CacheControl {
+    /// 按配置创建缓存策略中间件，无法作为响应头的策略值记录警告后回退为 `no-store`
+    ///
+    /// # 参数
+    /// * `config` - 缓存策略配置
+    pub fn new(config: &CacheControlConfig) -> Self {
+        if !config.enabled {
+            return Self { policy: None };
+        }
+
+        let parse = |name: &str, value: &str| {
+            HeaderValue::from_str(value.trim()).unwrap_or_else(|_| {
+                warn!("缓存策略 {} 的值无效，改用 no-store: {:?}", name, value);
+                HeaderValue::from_static("no-store")
+            })
+        };
+        let groups = config
+            .groups
+            .iter()
+            .map(|group| (group.clone(), parse(&group.name, &group.value)))
+            .collect();
+
+        Self {
+            policy: Some(Arc::new(CachePolicy {
+                mutating: parse("mutating", &config.mutating),
+                default: parse("default", &config.default),
+                groups,
+            })),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CacheControl
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = CacheControlMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CacheControlMiddleware {
+            service: Rc::new(service),
+            policy: self.policy.clone(),
+        }))
+    }
+}
+
+/// 缓存策略中间件服务
+pub struct CacheControlMiddleware<S> {
+    service: Rc<S>,
+    policy: Option<Arc<CachePolicy>>,
+}
+
+impl<S, B> Service<ServiceRequest> for CacheControlMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let Some(policy) = self.policy.clone() else {
+            return Box::pin(async move { service.call(req).await });
+        };
+
+        let value = policy.value_for(req.method(), req.path()).clone();
+        Box::pin(async move {
+            let mut response = service.call(req).await?;
+            let headers = response.headers_mut();
+            if !headers.contains_key(CACHE_CONTROL) {
+                headers.insert(CACHE_CONTROL, value);
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, web, App, HttpResponse};
+
+    #[actix_web::test]
+    async fn test_version_is_cacheable_and_realtime_data_is_not() {
+        let app = test::init_service(
+            App::new()
+                .wrap(CacheControl::new(&CacheControlConfig::default()))
+                .route("/version", web::get().to(|| async { HttpResponse::Ok().finish() }))
+                .route("/api/v1/sensors", web::get().to(|| async { HttpResponse::Ok().finish() }))
+                .route("/api/v1/admin/retention/run", web::post().to(|| async { HttpResponse::Ok().finish() }))
+                .route(
+                    "/api/v1/downloads/{token}",
+                    web::get().to(|| async {
+                        HttpResponse::Ok().insert_header((CACHE_CONTROL, "private, max-age=60")).finish()
+                    }),
+                ),
+        )
+        .await;
+
+        let cache_control = |response: &ServiceResponse| {
+            response.headers().get(CACHE_CONTROL).unwrap().to_str().unwrap().to_string()
+        };
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/version").to_request()).await;
+        assert_eq!(cache_control(&response), "public, max-age=300");
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/sensors").to_request()).await;
+        assert_eq!(cache_control(&response), "no-cache");
+
+        let response = test::call_service(
+            &app,
+            test::TestRequest::post().uri("/api/v1/admin/retention/run").to_request(),
+        )
+        .await;
+        assert_eq!(cache_control(&response), "no-store");
+
+        // 处理函数自行设置的策略保持不变
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/downloads/abc").to_request()).await;
+        assert_eq!(cache_control(&response), "private, max-age=60");
+    }
+}
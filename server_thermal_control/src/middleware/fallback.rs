@@ -0,0 +1,54 @@
+use crate::models::api::ApiResponse;
+use actix_web::{
+    dev::ServiceResponse,
+    http::{header, StatusCode},
+    middleware::{ErrorHandlerResponse, ErrorHandlers},
+    HttpRequest, HttpResponse,
+};
+
+/// 未匹配任何路由时的默认处理器，以统一的 `ApiResponse` 错误结构返回404
+///
+/// # 参数
+/// * `req` - HTTP请求
+pub async fn not_found(req: HttpRequest) -> HttpResponse {
+    HttpResponse::NotFound().json(ApiResponse::<()>::error(&format!(
+        "未找到资源: {} {}",
+        req.method(),
+        req.path()
+    )))
+}
+
+/// 为框架生成的405响应补充JSON错误体
+///
+/// 路径存在但方法不匹配时actix返回带 `Allow` 头的空响应，这里保留 `Allow` 头并写入
+/// `ApiResponse` 错误结构；处理器自行返回的405（已带Content-Type）保持原样
+pub fn method_not_allowed<B: 'static>() -> ErrorHandlers<B> {
+    ErrorHandlers::new().handler(StatusCode::METHOD_NOT_ALLOWED, render_method_not_allowed)
+}
+
+fn render_method_not_allowed<B>(res: ServiceResponse<B>) -> actix_web::Result<ErrorHandlerResponse<B>> {
+    if res.headers().contains_key(header::CONTENT_TYPE) {
+        return Ok(ErrorHandlerResponse::Response(res.map_into_left_body()));
+    }
+
+    let allowed = res
+        .headers()
+        .get(header::ALLOW)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let body = serde_json::to_string(&ApiResponse::<()>::error(&format!(
+        "不支持 {} 方法，允许的方法: {}",
+        res.request().method(),
+        allowed
+    )))?;
+
+    let (req, mut res) = res.into_parts();
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    let res = ServiceResponse::new(req, res.set_body(body))
+        .map_into_boxed_body()
+        .map_into_right_body();
+    Ok(ErrorHandlerResponse::Response(res))
+}
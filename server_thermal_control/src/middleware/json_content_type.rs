@@ -0,0 +1,144 @@
+use crate::models::error::AppError;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, Method},
+    Error,
+};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+/// JSON请求体校验中间件
+///
+/// 带请求体的POST/PUT/PATCH请求必须声明 `Content-Type: application/json`（允许 `charset` 等参数），
+/// 否则在解析请求体之前以 [`AppError::UnsupportedMediaTypeError`] 响应415。
+/// 不带请求体的请求（如触发类的POST）不受限制
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequireJsonContentType;
+
+impl<S, B> Transform<S, ServiceRequest> for RequireJsonContentType
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequireJsonContentTypeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireJsonContentTypeMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+/// JSON请求体校验中间件服务
+pub struct RequireJsonContentTypeMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireJsonContentTypeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if requires_json_body(&req) {
+            let content_type = req
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            if !is_json_content_type(content_type) {
+                let err = AppError::unsupported_media_type_error(content_type);
+                return Box::pin(async move { Err(err.into()) });
+            }
+        }
+
+        let service = Rc::clone(&self.service);
+        Box::pin(async move { service.call(req).await })
+    }
+}
+
+/// 是否为需要校验Content-Type的请求：带请求体的POST、PUT、PATCH
+fn requires_json_body(req: &ServiceRequest) -> bool {
+    if !matches!(*req.method(), Method::POST | Method::PUT | Method::PATCH) {
+        return false;
+    }
+
+    let headers = req.headers();
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+    content_length.is_some_and(|length| length > 0) || headers.contains_key(header::TRANSFER_ENCODING)
+}
+
+/// Content-Type是否为 `application/json`，忽略大小写和参数
+fn is_json_content_type(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+    use serde_json::{json, Value};
+
+    #[actix_web::test]
+    async fn test_non_json_body_rejected_with_415() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequireJsonContentType)
+                .route(
+                    "/fans/{fan_id}/speed",
+                    web::post().to(|body: web::Json<Value>| async move { HttpResponse::Ok().json(body.into_inner()) }),
+                )
+                .route("/admin/retention/run", web::post().to(|| async { HttpResponse::Ok().finish() })),
+        )
+        .await;
+
+        let err = test::try_call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/fans/FAN1/speed")
+                .insert_header((header::CONTENT_TYPE, "text/plain"))
+                .set_payload(r#"{"speed_percent": 60}"#)
+                .to_request(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let response = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/fans/FAN1/speed")
+                .insert_header((header::CONTENT_TYPE, "application/json; charset=utf-8"))
+                .set_payload(r#"{"speed_percent": 60}"#)
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body, json!({"speed_percent": 60}));
+
+        // 不带请求体的POST不要求Content-Type
+        let response = test::call_service(
+            &app,
+            test::TestRequest::post().uri("/admin/retention/run").to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
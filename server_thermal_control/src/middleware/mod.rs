@@ -0,0 +1,10 @@
+/// 中间件模块
+/// 
+/// 提供请求认证、请求超时、请求体类型校验、缓存策略、响应版本协商、未匹配路由处理等横切功能
+
+pub mod api_version;
+pub mod auth;
+pub mod cache_control;
+pub mod fallback;
+pub mod json_content_type;
+pub mod timeout;
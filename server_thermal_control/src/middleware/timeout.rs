@@ -0,0 +1,158 @@
+use crate::models::error::AppError;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error,
+};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use std::time::Duration;
+use tracing::warn;
+
+/// 流式端点路径后缀，这类连接会长期保持，不受请求超时限制
+const STREAMING_PATH_SUFFIX: &str = "/stream";
+
+/// 请求超时中间件
+///
+/// 处理函数在 `performance.request_timeout` 内未返回时以 [`AppError::TimeoutError`] 响应504。
+/// 超时后处理函数的 future 随即被丢弃，其中尚未完成的异步操作（数据库查询、等待中的
+/// IPMI调用等）在所处的 `.await` 点被取消，不会在后台继续占用连接
+#[derive(Debug, Clone, Copy)]
+pub struct RequestTimeout {
+    timeout: Duration,
+}
+
+impl RequestTimeout {
+    /// 创建请求超时中间件
+    ///
+    /// # 参数
+    /// * `timeout` - 单个请求的最长处理时间
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    /// 按配置的秒数创建，0表示不限制
+    ///
+    /// # 参数
+    /// * `seconds` - `performance.request_timeout`
+    pub fn from_secs(seconds: u64) -> Self {
+        Self::new(if seconds == 0 {
+            Duration::MAX
+        } else {
+            Duration::from_secs(seconds)
+        })
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTimeout
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestTimeoutMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTimeoutMiddleware {
+            service: Rc::new(service),
+            timeout: self.timeout,
+        }))
+    }
+}
+
+/// 请求超时中间件服务
+pub struct RequestTimeoutMiddleware<S> {
+    service: Rc<S>,
+    timeout: Duration,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTimeoutMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        if self.timeout == Duration::MAX || req.path().ends_with(STREAMING_PATH_SUFFIX) {
+            return Box::pin(async move { service.call(req).await });
+        }
+
+        let timeout = self.timeout;
+        let operation = format!("{} {}", req.method(), req.path());
+
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, service.call(req)).await {
+                Ok(response) => response,
+                Err(_) => {
+                    warn!("请求处理超时（{:?}），已取消: {}", timeout, operation);
+                    Err(AppError::TimeoutError { operation }.into())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[actix_web::test]
+    async fn test_slow_handler_times_out_with_504_and_is_cancelled() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let handler_finished = Arc::clone(&finished);
+
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestTimeout::new(Duration::from_millis(50)))
+                .route(
+                    "/slow",
+                    web::get().to(move || {
+                        let finished = Arc::clone(&handler_finished);
+                        async move {
+                            tokio::time::sleep(Duration::from_millis(300)).await;
+                            finished.store(true, Ordering::SeqCst);
+                            HttpResponse::Ok().finish()
+                        }
+                    }),
+                )
+                .route("/fast", web::get().to(|| async { HttpResponse::Ok().finish() }))
+                .route(
+                    "/alerts/stream",
+                    web::get().to(|| async {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        HttpResponse::Ok().finish()
+                    }),
+                ),
+        )
+        .await;
+
+        let err = test::try_call_service(&app, test::TestRequest::get().uri("/slow").to_request())
+            .await
+            .unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::GATEWAY_TIMEOUT);
+
+        // 超时的处理函数被取消，不会在后台运行到结束
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/fast").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 流式端点不受超时限制
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/alerts/stream").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -1,6 +1,9 @@
-use chrono::{DateTime, Utc};
+use super::error::{AppError, AppResult};
+use super::Alert;
+use chrono::{DateTime, Duration, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::BTreeSet;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
@@ -38,12 +41,241 @@ pub struct AlertRule {
     pub metric: String,
     /// 条件
     pub condition: AlertCondition,
+    /// 阈值单位，仅用于风扇规则，为空时按RPM比较
+    #[serde(default)]
+    pub threshold_unit: Option<ThresholdUnit>,
     /// 严重级别
     pub severity: AlertSeverity,
     /// 是否启用
     pub enabled: bool,
 }
 
+impl AlertRule {
+    /// 校验规则：名称和指标不能为空，操作符受支持，阈值为有限数，持续时间不为负，
+    /// 阈值单位仅用于风扇规则
+    pub fn validate(&self) -> AppResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::validation_error("name", "规则名称不能为空"));
+        }
+        if self.metric.trim().is_empty() {
+            return Err(AppError::validation_error("metric", "监控指标不能为空"));
+        }
+        if !self.condition.threshold.is_finite() {
+            return Err(AppError::validation_error("threshold", "阈值必须为有限数"));
+        }
+        self.condition.is_met(self.condition.threshold)?;
+        if self.condition.duration_seconds < 0 {
+            return Err(AppError::validation_error("duration_seconds", "持续时间不能为负数"));
+        }
+        if self.threshold_unit.is_some() && self.metric != "fan_speed" {
+            return Err(AppError::validation_error("threshold_unit", "阈值单位仅用于风扇规则"));
+        }
+        Ok(())
+    }
+}
+
+/// 告警规则的部分更新
+///
+/// 所有字段均可选，未指定的字段保持当前值；条件的各项可单独修改
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct AlertRulePatch {
+    /// 规则名称
+    pub name: Option<String>,
+    /// 规则描述
+    pub description: Option<String>,
+    /// 监控指标
+    pub metric: Option<String>,
+    /// 比较操作符
+    pub operator: Option<String>,
+    /// 阈值
+    pub threshold: Option<f64>,
+    /// 持续时间（秒）
+    pub duration_seconds: Option<i32>,
+    /// 阈值单位
+    pub threshold_unit: Option<ThresholdUnit>,
+    /// 严重级别
+    pub severity: Option<AlertSeverity>,
+    /// 是否启用
+    pub enabled: Option<bool>,
+}
+
+impl AlertRulePatch {
+    /// 是否没有指定任何字段
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.description.is_none()
+            && self.metric.is_none()
+            && self.operator.is_none()
+            && self.threshold.is_none()
+            && self.duration_seconds.is_none()
+            && self.threshold_unit.is_none()
+            && self.severity.is_none()
+            && self.enabled.is_none()
+    }
+
+    /// 将部分更新合并到当前规则并验证合并结果
+    ///
+    /// 验证失败时返回错误，当前规则不受影响
+    ///
+    /// # 参数
+    /// * `current` - 当前告警规则
+    pub fn merged(&self, current: &AlertRule) -> AppResult<AlertRule> {
+        let mut rule = current.clone();
+
+        if let Some(ref name) = self.name {
+            rule.name = name.trim().to_string();
+        }
+        if let Some(ref description) = self.description {
+            rule.description = description.clone();
+        }
+        if let Some(ref metric) = self.metric {
+            rule.metric = metric.trim().to_string();
+        }
+        if let Some(ref operator) = self.operator {
+            rule.condition.operator = operator.trim().to_string();
+        }
+        if let Some(threshold) = self.threshold {
+            rule.condition.threshold = threshold;
+        }
+        if let Some(duration_seconds) = self.duration_seconds {
+            rule.condition.duration_seconds = duration_seconds;
+        }
+        if let Some(unit) = self.threshold_unit {
+            rule.threshold_unit = Some(unit);
+        }
+        if let Some(ref severity) = self.severity {
+            rule.severity = severity.clone();
+        }
+        if let Some(enabled) = self.enabled {
+            rule.enabled = enabled;
+        }
+
+        rule.validate()?;
+        Ok(rule)
+    }
+}
+
+/// 创建告警静默的请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateAlertMuteRequest {
+    /// 告警源（如传感器ID），为空时不按告警源匹配
+    #[serde(default)]
+    pub source: Option<String>,
+    /// 规则ID，为空时不按规则匹配
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    /// 静默到期时间
+    pub expires_at: DateTime<Utc>,
+    /// 静默原因，如"传感器故障待更换"
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// 告警静默
+///
+/// 静默期间匹配的告警仍记入历史，但不发送通知，也不作为活跃告警；
+/// 同时指定告警源和规则时两者都须匹配
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AlertMute {
+    /// 静默ID
+    pub id: String,
+    /// 告警源
+    pub source: Option<String>,
+    /// 规则ID
+    pub rule_id: Option<String>,
+    /// 静默原因
+    pub reason: Option<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 到期时间
+    pub expires_at: DateTime<Utc>,
+    /// 静默期间被抑制的告警数
+    pub suppressed_count: u64,
+}
+
+impl AlertMute {
+    /// 按请求创建静默
+    ///
+    /// 告警源和规则至少指定一项，到期时间须晚于当前时间
+    ///
+    /// # 参数
+    /// * `request` - 创建请求
+    /// * `now` - 当前时间
+    pub fn new(request: CreateAlertMuteRequest, now: DateTime<Utc>) -> AppResult<Self> {
+        let non_blank = |value: Option<String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let source = non_blank(request.source);
+        let rule_id = non_blank(request.rule_id);
+        if source.is_none() && rule_id.is_none() {
+            return Err(AppError::validation_error("source", "告警源和规则ID至少指定一项"));
+        }
+        if request.expires_at <= now {
+            return Err(AppError::validation_error("expires_at", "到期时间必须晚于当前时间"));
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            source,
+            rule_id,
+            reason: request.reason,
+            created_at: now,
+            expires_at: request.expires_at,
+            suppressed_count: 0,
+        })
+    }
+
+    /// 静默在指定时间是否仍然有效
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        now < self.expires_at
+    }
+
+    /// 告警是否匹配该静默
+    ///
+    /// # 参数
+    /// * `source` - 告警源
+    /// * `rule_id` - 触发告警的规则ID
+    pub fn matches(&self, source: &str, rule_id: Option<&str>) -> bool {
+        self.source.as_deref().is_none_or(|muted| muted == source)
+            && self.rule_id.as_deref().is_none_or(|muted| Some(muted) == rule_id)
+    }
+}
+
+/// 规则阈值单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdUnit {
+    /// 转速（RPM）
+    Rpm,
+    /// 转速百分比
+    Percent,
+}
+
+impl ThresholdUnit {
+    /// 将风扇读数换算为该单位的值
+    ///
+    /// 百分比不在0-100之间（如读数缺失为NaN）时无法换算，返回错误
+    ///
+    /// # 参数
+    /// * `speed_rpm` - 转速（RPM）
+    /// * `speed_percent` - 转速百分比
+    pub fn fan_value(self, speed_rpm: f64, speed_percent: f64) -> AppResult<f64> {
+        match self {
+            ThresholdUnit::Rpm => Ok(speed_rpm),
+            ThresholdUnit::Percent if (0.0..=100.0).contains(&speed_percent) => Ok(speed_percent),
+            ThresholdUnit::Percent => Err(AppError::validation_error(
+                "threshold_unit",
+                format!("风扇转速百分比 {} 无效，无法与百分比阈值比较", speed_percent),
+            )),
+        }
+    }
+}
+
 /// 警报条件
 /// 
 /// 定义警报触发的条件
@@ -57,10 +289,33 @@ pub struct AlertCondition {
     pub duration_seconds: i32,
 }
 
+impl AlertCondition {
+    /// 读数是否满足条件，不考虑持续时间
+    ///
+    /// # 参数
+    /// * `value` - 读数值
+    pub fn is_met(&self, value: f64) -> AppResult<bool> {
+        Ok(match self.operator.trim() {
+            ">" => value > self.threshold,
+            ">=" => value >= self.threshold,
+            "<" => value < self.threshold,
+            "<=" => value <= self.threshold,
+            "==" => (value - self.threshold).abs() < f64::EPSILON,
+            "!=" => (value - self.threshold).abs() >= f64::EPSILON,
+            operator => {
+                return Err(AppError::validation_error(
+                    "condition.operator",
+                    format!("不支持的比较操作符: {}", operator),
+                ))
+            }
+        })
+    }
+}
+
 /// 警报严重级别枚举
 /// 
 /// 定义不同级别的警报严重程度
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub enum AlertSeverity {
     /// 信息
     Info,
@@ -102,6 +357,43 @@ pub enum ChannelType {
     DingTalk,
 }
 
+/// 一天中的时间段
+///
+/// 左闭右开；`start` 晚于 `end` 时表示跨越午夜，如 22:00-08:00
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TimeOfDayWindow {
+    /// 开始时间
+    pub start: NaiveTime,
+    /// 结束时间
+    pub end: NaiveTime,
+}
+
+/// 通知路由规则
+///
+/// 严重程度在 `severities` 中且告警时间落在 `time_window` 内时，通知 `channel_ids` 中的渠道
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NotificationRoute {
+    /// 匹配的严重程度
+    pub severities: Vec<AlertSeverity>,
+    /// 生效时间段，为空表示全天
+    #[serde(default)]
+    pub time_window: Option<TimeOfDayWindow>,
+    /// 通知渠道ID
+    pub channel_ids: Vec<String>,
+}
+
+/// 通知路由矩阵（严重程度 × 时间段 → 渠道）
+///
+/// 配置后取代各渠道的 `severity_filter`，告警发往所有匹配规则的渠道并集
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct NotificationRoutingMatrix {
+    /// 判断时间段所用时区相对UTC的偏移（分钟）
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    /// 路由规则
+    pub routes: Vec<NotificationRoute>,
+}
+
 /// 警报记录
 /// 
 /// 存储触发的警报记录
@@ -150,6 +442,35 @@ pub enum AlertStatus {
     Ignored,
 }
 
+/// 告警模板
+///
+/// 按模板创建告警时填充标题和消息，`usage_count` 记录模板被使用的次数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AlertTemplate {
+    /// 模板ID
+    pub id: String,
+    /// 名称
+    pub name: String,
+    /// 描述
+    pub description: Option<String>,
+    /// 告警类型
+    pub alert_type: String,
+    /// 严重程度
+    pub severity: AlertSeverity,
+    /// 标题模板
+    pub title_template: String,
+    /// 消息模板
+    pub message_template: String,
+    /// 元数据模板
+    pub metadata_template: Option<serde_json::Value>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+    /// 使用次数
+    pub usage_count: u64,
+}
+
 /// 通知记录
 /// 
 /// 记录发送的通知
@@ -191,7 +512,7 @@ pub enum NotificationStatus {
 /// 警报统计
 /// 
 /// 警报系统的统计信息
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 pub struct AlertStatistics {
     /// 总警报数
     pub total_alerts: i64,
@@ -207,6 +528,63 @@ pub struct AlertStatistics {
     pub time_range: String,
 }
 
+/// 警报统计报告
+/// 
+/// 同时包含累计统计和滚动窗口统计
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AlertStatisticsReport {
+    /// 累计统计（自上次重置起，跨服务重启保留）
+    pub lifetime: AlertStatistics,
+    /// 滚动窗口统计
+    pub window: AlertStatistics,
+    /// 滚动窗口长度（小时）
+    pub window_hours: u32,
+    /// 最近一次重置记录
+    pub last_reset: Option<AlertStatisticsReset>,
+}
+
+/// 警报统计重置记录
+/// 
+/// 用于审计累计统计的清零操作
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AlertStatisticsReset {
+    /// 重置时间
+    pub reset_at: DateTime<Utc>,
+    /// 操作人
+    pub reset_by: Option<String>,
+    /// 重置原因
+    pub reason: Option<String>,
+    /// 重置前的累计统计
+    pub previous: AlertStatistics,
+}
+
+impl AlertStatistics {
+    /// 根据告警记录计算统计
+    /// 
+    /// # 参数
+    /// * `alerts` - 告警记录
+    /// * `time_range` - 统计时间范围描述
+    pub fn from_alerts<'a>(alerts: impl IntoIterator<Item = &'a Alert>, time_range: String) -> Self {
+        let mut stats = Self {
+            time_range,
+            ..Default::default()
+        };
+
+        for alert in alerts {
+            stats.total_alerts += 1;
+            match alert.status {
+                AlertStatus::Triggered => stats.active_alerts += 1,
+                AlertStatus::Acknowledged => stats.acknowledged_alerts += 1,
+                AlertStatus::Resolved => stats.resolved_alerts += 1,
+                AlertStatus::Ignored => {}
+            }
+            *stats.by_severity.entry(alert.severity.to_string()).or_insert(0) += 1;
+        }
+
+        stats
+    }
+}
+
 impl AlertConfig {
     /// 创建新的警报配置
     /// 
@@ -296,4 +674,84 @@ impl AlertRecord {
         self.resolved_at = Some(Utc::now());
         self.resolved_by = Some(resolved_by);
     }
-}
\ No newline at end of file
+}
+
+impl NotificationChannel {
+    /// 验证渠道配置，返回发现的第一个问题
+    ///
+    /// 全部问题见 `services::channel_validation::check_channel`
+    pub fn validate(&self) -> AppResult<()> {
+        match crate::services::channel_validation::check_channel(self).into_iter().next() {
+            Some(problem) => Err(AppError::validation_error(problem.field, problem.message)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl TimeOfDayWindow {
+    /// 判断时间是否落在时间段内
+    ///
+    /// # 参数
+    /// * `time` - 一天中的时间
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl NotificationRoutingMatrix {
+    /// 时区偏移上限（分钟），与UTC±14:00一致
+    const MAX_UTC_OFFSET_MINUTES: i32 = 14 * 60;
+
+    /// 计算告警应发往的渠道ID
+    ///
+    /// # 参数
+    /// * `severity` - 告警严重程度
+    /// * `at` - 告警时间
+    pub fn channels_for(&self, severity: &AlertSeverity, at: DateTime<Utc>) -> BTreeSet<String> {
+        let local_time = (at + Duration::minutes(self.utc_offset_minutes as i64)).time();
+
+        self.routes
+            .iter()
+            .filter(|route| route.severities.contains(severity))
+            .filter(|route| route.time_window.as_ref().map_or(true, |w| w.contains(local_time)))
+            .flat_map(|route| route.channel_ids.iter().cloned())
+            .collect()
+    }
+
+    /// 验证路由矩阵
+    pub fn validate(&self) -> AppResult<()> {
+        if self.utc_offset_minutes.abs() > Self::MAX_UTC_OFFSET_MINUTES {
+            return Err(AppError::validation_error(
+                "utc_offset_minutes",
+                "时区偏移必须在-840到840分钟之间",
+            ));
+        }
+
+        for (index, route) in self.routes.iter().enumerate() {
+            if route.severities.is_empty() {
+                return Err(AppError::validation_error(
+                    format!("routes[{}].severities", index),
+                    "路由规则至少需要一个严重程度",
+                ));
+            }
+            if route.channel_ids.is_empty() {
+                return Err(AppError::validation_error(
+                    format!("routes[{}].channel_ids", index),
+                    "路由规则至少需要一个通知渠道",
+                ));
+            }
+            if route.time_window.as_ref().is_some_and(|w| w.start == w.end) {
+                return Err(AppError::validation_error(
+                    format!("routes[{}].time_window", index),
+                    "时间段的开始与结束不能相同",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -131,7 +131,7 @@ pub struct FanEfficiencyAnalysis {
 /// 噪音水平枚举
 /// 
 /// 定义风扇噪音的等级
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub enum NoiseLevel {
     /// 静音
     Silent,
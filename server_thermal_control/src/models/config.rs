@@ -73,6 +73,20 @@ pub struct IpmiConfig {
     pub retry_count: u32,
     /// 重试间隔（秒）
     pub retry_interval: u64,
+    /// 是否以单次 `sdr list full` 批量读取全部传感器，解析失败时回退为逐个读取
+    #[serde(default = "default_batch_sdr")]
+    pub batch_sdr: bool,
+    /// 多机箱轮询的最大并发数
+    #[serde(default = "default_max_concurrent_polls")]
+    pub max_concurrent_polls: usize,
+}
+
+fn default_batch_sdr() -> bool {
+    true
+}
+
+fn default_max_concurrent_polls() -> usize {
+    4
 }
 
 /// 服务器目标配置
@@ -170,6 +184,41 @@ pub struct MonitoringConfig {
     pub health_check_interval: u64,
     /// 指标导出配置
     pub metrics_export: MetricsExportConfig,
+    /// 历史数据单次查询允许的最大时间跨度（小时）
+    #[serde(default = "default_max_history_span_hours")]
+    pub max_history_span_hours: u32,
+    /// 原始读数保留时长（小时），更早的读数压缩为小时聚合
+    #[serde(default = "default_raw_retention_hours")]
+    pub raw_retention_hours: u32,
+    /// 小时聚合保留时长（天），更早的聚合合并为天聚合
+    #[serde(default = "default_hourly_retention_days")]
+    pub hourly_retention_days: u32,
+    /// 压缩任务执行间隔（秒）
+    #[serde(default = "default_compaction_interval")]
+    pub compaction_interval: u64,
+    /// 每个温度传感器、风扇在内存中保留的最近读数条数，超出后淘汰最旧的读数
+    #[serde(default = "default_recent_readings_capacity")]
+    pub recent_readings_capacity: usize,
+}
+
+fn default_max_history_span_hours() -> u32 {
+    24 * 7
+}
+
+fn default_raw_retention_hours() -> u32 {
+    24
+}
+
+fn default_hourly_retention_days() -> u32 {
+    30
+}
+
+fn default_compaction_interval() -> u64 {
+    3600
+}
+
+fn default_recent_readings_capacity() -> usize {
+    crate::utils::ring_buffer::DEFAULT_RECENT_READINGS_CAPACITY
 }
 
 impl Default for MonitoringConfig {
@@ -181,6 +230,11 @@ impl Default for MonitoringConfig {
             enable_health_checks: true,
             health_check_interval: 60,
             metrics_export: MetricsExportConfig::default(),
+            max_history_span_hours: default_max_history_span_hours(),
+            raw_retention_hours: default_raw_retention_hours(),
+            hourly_retention_days: default_hourly_retention_days(),
+            compaction_interval: default_compaction_interval(),
+            recent_readings_capacity: default_recent_readings_capacity(),
         }
     }
 }
@@ -384,6 +438,8 @@ impl Default for AppConfig {
                 default_timeout: 10,
                 retry_count: 3,
                 retry_interval: 5,
+                batch_sdr: default_batch_sdr(),
+                max_concurrent_polls: default_max_concurrent_polls(),
             },
             logging: LoggingConfig::default(),
             monitoring: MonitoringConfig {
@@ -398,6 +454,11 @@ impl Default for AppConfig {
                     enable_influxdb: false,
                     influxdb_config: None,
                 },
+                max_history_span_hours: default_max_history_span_hours(),
+                raw_retention_hours: default_raw_retention_hours(),
+                hourly_retention_days: default_hourly_retention_days(),
+                compaction_interval: default_compaction_interval(),
+                recent_readings_capacity: default_recent_readings_capacity(),
             },
             alerting: AlertingConfig {
                 enabled: true,
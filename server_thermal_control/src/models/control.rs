@@ -1,9 +1,16 @@
-use chrono::{DateTime, Utc};
+use crate::models::error::{AppError, AppResult};
+use chrono::{DateTime, Datelike, NaiveDateTime, NaiveTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+/// 控制输入平滑窗口的上限（采样数）
+pub const MAX_SMOOTHING_WINDOW: u32 = 60;
+
+/// 起转脉冲持续时间的上限（毫秒），脉冲期间控制周期被阻塞
+pub const MAX_KICKSTART_DURATION_MS: u64 = 10_000;
+
 /// 控制配置
 /// 
 /// 定义系统控制的各项配置参数
@@ -19,12 +26,283 @@ pub struct ControlConfig {
     pub auto_control_enabled: bool,
     /// 控制参数
     pub parameters: ControlParameters,
+    /// 基于进风温度的目标温度自动调整，为空时使用固定目标温度
+    #[serde(default)]
+    #[sqlx(default)]
+    pub ambient_target: Option<AmbientTargetConfig>,
+    /// 多传感器聚合控制，为空时每个传感器独立驱动对应风扇
+    #[serde(default)]
+    #[sqlx(default)]
+    pub sensor_aggregation: Option<SensorAggregationConfig>,
+    /// 风扇分区，非空时各分区按自己的传感器组独立驱动分区内风扇
+    #[serde(default)]
+    #[sqlx(default)]
+    pub fan_zones: Vec<FanZoneConfig>,
+    /// 各风扇的最低转速（百分比），自动控制输出不会低于该值，用于保护电源、硬盘仓等部件
+    #[serde(default)]
+    #[sqlx(default)]
+    pub fan_speed_floors: std::collections::HashMap<String, f64>,
+    /// 舒适区半宽（°C），温度在 `目标温度 ± comfort_band` 内时保持当前转速不做调节，避免转速随温度抖动来回变化；为0时不启用
+    #[serde(default)]
+    #[sqlx(default)]
+    pub comfort_band: f64,
+    /// 控制输入平滑窗口（采样数），PID使用传感器最近若干次读数的平均值，存储的温度仍为原始读数；为0或1时不平滑
+    #[serde(default)]
+    #[sqlx(default)]
+    pub smoothing_window: u32,
+    /// 按传感器覆盖的平滑窗口，未列出的传感器使用 `smoothing_window`
+    #[serde(default)]
+    #[sqlx(default)]
+    pub sensor_smoothing_windows: std::collections::HashMap<String, u32>,
+    /// 低速起转脉冲，为空时不检测低速停转
+    #[serde(default)]
+    #[sqlx(default)]
+    pub kickstart: Option<FanKickstartConfig>,
     /// 创建时间
     pub created_at: DateTime<Utc>,
     /// 更新时间
     pub updated_at: DateTime<Utc>,
 }
 
+/// 风扇起转脉冲配置
+/// 
+/// 部分风扇在低占空比下无法自行起转。目标转速不高于 `low_speed_threshold` 且回读转速为0时，
+/// 先以 `duty_percent` 运行 `duration_ms` 毫秒再回到目标转速；脉冲期间仍无转速则视为风扇故障
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FanKickstartConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 触发检测的目标转速上限（百分比）
+    pub low_speed_threshold: f64,
+    /// 脉冲占空比（百分比）
+    pub duty_percent: f64,
+    /// 脉冲持续时间（毫秒）
+    pub duration_ms: u64,
+}
+
+impl FanKickstartConfig {
+    /// 验证起转脉冲配置
+    pub fn validate(&self) -> AppResult<()> {
+        if !(0.0..=100.0).contains(&self.low_speed_threshold) {
+            return Err(AppError::validation_error("kickstart.low_speed_threshold", "低速阈值必须在0到100之间"));
+        }
+        if !(self.duty_percent > self.low_speed_threshold && self.duty_percent <= 100.0) {
+            return Err(AppError::validation_error(
+                "kickstart.duty_percent",
+                "脉冲占空比必须高于低速阈值且不超过100",
+            ));
+        }
+        if self.duration_ms == 0 || self.duration_ms > MAX_KICKSTART_DURATION_MS {
+            return Err(AppError::validation_error(
+                "kickstart.duration_ms",
+                format!("脉冲持续时间必须在1到{}毫秒之间", MAX_KICKSTART_DURATION_MS),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 目标转速是否需要检测低速停转，未启用时始终为false
+    ///
+    /// # 参数
+    /// * `target_percent` - 目标转速百分比
+    pub fn applies_to(&self, target_percent: f64) -> bool {
+        self.enabled && target_percent > 0.0 && target_percent <= self.low_speed_threshold
+    }
+}
+
+/// 进风温度目标调整配置
+/// 
+/// 启用后有效目标温度为 `进风温度 + delta`，并限制在 `[min_target, max_target]` 内
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AmbientTargetConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 进风温度传感器ID
+    pub inlet_sensor_id: String,
+    /// 相对进风温度的温差（°C）
+    pub delta: f64,
+    /// 有效目标温度下限（°C）
+    pub min_target: f64,
+    /// 有效目标温度上限（°C）
+    pub max_target: f64,
+}
+
+impl AmbientTargetConfig {
+    /// 计算有效目标温度
+    /// 
+    /// 未启用或进风温度不可用时回退到固定目标温度
+    /// 
+    /// # 参数
+    /// * `base_target` - 固定目标温度
+    /// * `inlet_temperature` - 进风温度
+    pub fn effective_target(&self, base_target: f64, inlet_temperature: Option<f64>) -> f64 {
+        match inlet_temperature {
+            Some(inlet) if self.enabled => (inlet + self.delta).clamp(self.min_target, self.max_target),
+            _ => base_target,
+        }
+    }
+
+    /// 验证配置
+    pub fn validate(&self) -> AppResult<()> {
+        if self.inlet_sensor_id.trim().is_empty() {
+            return Err(AppError::validation_error(
+                "inlet_sensor_id",
+                "进风温度传感器ID不能为空",
+            ));
+        }
+        if self.min_target > self.max_target {
+            return Err(AppError::validation_error(
+                "min_target",
+                "目标温度下限不能高于上限",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 传感器聚合策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationPolicy {
+    /// 取最高温度
+    Max,
+    /// 取平均温度
+    Average,
+    /// 各传感器温度乘以权重后取最大值
+    WeightedMax,
+}
+
+/// 多传感器聚合控制配置
+/// 
+/// 将选定传感器组的读数按策略聚合为单一控制输入，驱动所有风扇
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SensorAggregationConfig {
+    /// 聚合策略
+    pub policy: AggregationPolicy,
+    /// 参与聚合的传感器ID，为空时使用全部温度传感器
+    #[serde(default)]
+    pub sensor_ids: Vec<String>,
+    /// `WeightedMax` 策略下各传感器的权重，未列出的传感器权重为1.0
+    #[serde(default)]
+    pub weights: std::collections::HashMap<String, f64>,
+}
+
+impl SensorAggregationConfig {
+    /// 传感器是否参与聚合
+    /// 
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    pub fn includes(&self, sensor_id: &str) -> bool {
+        self.sensor_ids.is_empty() || self.sensor_ids.iter().any(|id| id == sensor_id)
+    }
+
+    /// 按策略将读数聚合为控制输入温度
+    /// 
+    /// 不在传感器组内的读数被忽略，没有可用读数时返回 `None`
+    /// 
+    /// # 参数
+    /// * `readings` - `(传感器ID, 温度)` 列表
+    pub fn aggregate(&self, readings: &[(String, f64)]) -> Option<f64> {
+        let selected: Vec<(&str, f64)> = readings
+            .iter()
+            .filter(|(sensor_id, _)| self.includes(sensor_id))
+            .map(|(sensor_id, temperature)| (sensor_id.as_str(), *temperature))
+            .collect();
+        if selected.is_empty() {
+            return None;
+        }
+
+        let temperatures = selected.iter().map(|(_, temperature)| *temperature);
+        match self.policy {
+            AggregationPolicy::Max => temperatures.reduce(f64::max),
+            AggregationPolicy::Average => Some(temperatures.sum::<f64>() / selected.len() as f64),
+            AggregationPolicy::WeightedMax => selected
+                .iter()
+                .map(|(sensor_id, temperature)| {
+                    temperature * self.weights.get(*sensor_id).copied().unwrap_or(1.0)
+                })
+                .reduce(f64::max),
+        }
+    }
+
+    /// 验证配置
+    pub fn validate(&self) -> AppResult<()> {
+        if self.sensor_ids.iter().any(|id| id.trim().is_empty()) {
+            return Err(AppError::validation_error("sensor_ids", "传感器ID不能为空"));
+        }
+        if let Some((sensor_id, _)) = self.weights.iter().find(|(_, w)| !(w.is_finite() && **w > 0.0)) {
+            return Err(AppError::validation_error(
+                "weights",
+                format!("传感器 {} 的权重必须为正数", sensor_id),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 风扇分区配置
+/// 
+/// 分区内的传感器读数按聚合策略计算出分区控制输入，只驱动本分区的风扇
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FanZoneConfig {
+    /// 分区ID
+    pub zone_id: String,
+    /// 分区传感器组及聚合策略，传感器组不能为空
+    #[serde(flatten)]
+    pub sensors: SensorAggregationConfig,
+    /// 分区内的风扇ID
+    pub fan_ids: Vec<String>,
+}
+
+impl FanZoneConfig {
+    /// 验证配置
+    pub fn validate(&self) -> AppResult<()> {
+        if self.zone_id.trim().is_empty() {
+            return Err(AppError::validation_error("zone_id", "风扇分区ID不能为空"));
+        }
+        if self.sensors.sensor_ids.is_empty() {
+            return Err(AppError::validation_error(
+                "sensor_ids",
+                format!("风扇分区 {} 至少需要一个传感器", self.zone_id),
+            ));
+        }
+        if self.fan_ids.is_empty() || self.fan_ids.iter().any(|id| id.trim().is_empty()) {
+            return Err(AppError::validation_error(
+                "fan_ids",
+                format!("风扇分区 {} 的风扇ID不能为空", self.zone_id),
+            ));
+        }
+        self.sensors.validate()
+    }
+
+    /// 验证一组分区：分区ID不能重复，每个风扇只能属于一个分区
+    /// 
+    /// # 参数
+    /// * `zones` - 风扇分区列表
+    pub fn validate_all(zones: &[FanZoneConfig]) -> AppResult<()> {
+        let mut zone_ids = std::collections::HashSet::new();
+        let mut fan_owners = std::collections::HashMap::new();
+        for zone in zones {
+            zone.validate()?;
+            if !zone_ids.insert(zone.zone_id.as_str()) {
+                return Err(AppError::validation_error(
+                    "fan_zones",
+                    format!("风扇分区ID {} 重复", zone.zone_id),
+                ));
+            }
+            for fan_id in &zone.fan_ids {
+                if let Some(owner) = fan_owners.insert(fan_id.as_str(), zone.zone_id.as_str()) {
+                    return Err(AppError::validation_error(
+                        "fan_zones",
+                        format!("风扇 {} 同时属于分区 {} 和 {}", fan_id, owner, zone.zone_id),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// 控制模式枚举
 /// 
 /// 定义不同的控制模式
@@ -55,6 +333,96 @@ pub struct ControlParameters {
     pub max_fan_speed: i32,
     /// 最小风扇转速
     pub min_fan_speed: i32,
+    /// 警告温度，必须高于目标温度
+    #[serde(default = "default_warning_temperature")]
+    pub warning_temperature: f64,
+    /// 临界温度，必须高于警告温度
+    #[serde(default = "default_critical_temperature")]
+    pub critical_temperature: f64,
+    /// 紧急温度，必须高于临界温度
+    #[serde(default = "default_emergency_temperature")]
+    pub emergency_temperature: f64,
+}
+
+impl ControlParameters {
+    /// 校验控制参数
+    pub fn validate(&self) -> AppResult<()> {
+        if !(0.0..=150.0).contains(&self.target_temperature) {
+            return Err(AppError::validation_error(
+                "target_temperature",
+                "目标温度必须在0-150°C之间",
+            ));
+        }
+        if self.temperature_tolerance < 0.0 {
+            return Err(AppError::validation_error(
+                "temperature_tolerance",
+                "温度容差不能为负数",
+            ));
+        }
+        validate_temperature_order(&[
+            ("target_temperature", self.target_temperature),
+            ("warning_temperature", self.warning_temperature),
+            ("critical_temperature", self.critical_temperature),
+            ("emergency_temperature", self.emergency_temperature),
+        ])?;
+        if !(0..=100).contains(&self.min_fan_speed)
+            || !(0..=100).contains(&self.max_fan_speed)
+        {
+            return Err(AppError::validation_error(
+                "fan_speed",
+                "风扇转速必须在0-100%之间",
+            ));
+        }
+        if self.min_fan_speed > self.max_fan_speed {
+            return Err(AppError::validation_error(
+                "min_fan_speed",
+                "最小风扇转速不能高于最大风扇转速",
+            ));
+        }
+        if let Some(ref pid) = self.pid_params {
+            if pid.kp < 0.0 || pid.ki < 0.0 || pid.kd < 0.0 {
+                return Err(AppError::validation_error("pid_params", "PID系数不能为负数"));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn default_warning_temperature() -> f64 {
+    75.0
+}
+
+fn default_critical_temperature() -> f64 {
+    85.0
+}
+
+fn default_emergency_temperature() -> f64 {
+    95.0
+}
+
+/// 校验温度阈值由低到高严格递增
+///
+/// 目标温度不低于警告温度时，正常控制下告警会持续触发。违反时的错误指出相邻的两项
+///
+/// # 参数
+/// * `thresholds` - 按应有的从低到高顺序排列的 `(配置项, 温度)`
+pub fn validate_temperature_order(thresholds: &[(&str, f64)]) -> AppResult<()> {
+    if let Some((field, _)) = thresholds.iter().find(|(_, value)| !value.is_finite()) {
+        return Err(AppError::validation_error(*field, format!("{} 必须为有限数", field)));
+    }
+    for pair in thresholds.windows(2) {
+        let ((lower_field, lower), (upper_field, upper)) = (pair[0], pair[1]);
+        if lower >= upper {
+            return Err(AppError::validation_error(
+                upper_field,
+                format!(
+                    "{} ({}°C) 必须低于 {} ({}°C)",
+                    lower_field, lower, upper_field, upper
+                ),
+            ));
+        }
+    }
+    Ok(())
 }
 
 /// PID控制参数
@@ -168,6 +536,189 @@ pub enum ActionType {
     EmergencyShutdown,
 }
 
+/// 静音时段计划
+/// 
+/// 定义风扇静音时段及其转速上限，超过紧急温度时不受上限约束
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuietHoursSchedule {
+    /// 是否启用
+    pub enabled: bool,
+    /// 静音时段列表
+    pub windows: Vec<QuietWindow>,
+    /// 紧急温度阈值（°C），达到后忽略静音上限
+    pub emergency_temperature: f64,
+    /// 更新时间，更新计划时由服务端设置
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 静音时段
+/// 
+/// 按本地时间定义的时间窗口，结束时间早于开始时间表示跨越午夜
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuietWindow {
+    /// 时段名称
+    pub name: String,
+    /// 开始时间（本地时间）
+    #[schema(value_type = String, example = "09:00:00")]
+    pub start: NaiveTime,
+    /// 结束时间（本地时间）
+    #[schema(value_type = String, example = "18:00:00")]
+    pub end: NaiveTime,
+    /// 生效的星期（为空表示每天）
+    #[serde(default)]
+    #[schema(value_type = Vec<String>, example = json!(["Mon", "Tue", "Wed", "Thu", "Fri"]))]
+    pub weekdays: Vec<Weekday>,
+    /// 风扇转速上限（百分比）
+    pub max_fan_percent: f64,
+}
+
+/// 控制决策记录
+/// 
+/// 记录控制循环每次调速的输入、PID分量与最终输出，用于调参排查
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ControlDecision {
+    /// 决策时间
+    pub timestamp: DateTime<Utc>,
+    /// 温度传感器ID
+    pub sensor_id: String,
+    /// 被调节的风扇ID
+    pub fan_id: String,
+    /// 输入温度（°C）
+    pub input_temperature: f64,
+    /// 目标温度（°C）
+    pub setpoint: f64,
+    /// 比例项贡献
+    pub proportional: f64,
+    /// 积分项贡献
+    pub integral: f64,
+    /// 微分项贡献
+    pub derivative: f64,
+    /// PID原始输出（未限幅）
+    pub raw_output: f64,
+    /// 经转速范围与静音时段限制后的输出（%）
+    pub limited_output: f64,
+    /// 生效的静音时段上限（%）
+    pub quiet_cap: Option<f64>,
+    /// 下发后回读的风扇转速（%），多台风扇时取平均；只读模式或回读失败时为空
+    #[serde(default)]
+    pub readback_percent: Option<f64>,
+    /// 产生该决策的控制动作
+    #[serde(default)]
+    pub action_type: ControlActionType,
+}
+
+/// 控制动作类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlActionType {
+    /// 按温度自动调速
+    #[default]
+    AutomaticControl,
+    /// 达到临界温度后的满速冷却
+    EmergencyCooling,
+    /// 手动指定的风扇转速
+    ManualFanControl,
+    /// 手动指令与上次下发的转速相同，未写入BMC
+    NoOp,
+}
+
+impl ControlActionType {
+    /// 数据库与查询参数中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ControlActionType::AutomaticControl => "automatic_control",
+            ControlActionType::EmergencyCooling => "emergency_cooling",
+            ControlActionType::ManualFanControl => "manual_fan_control",
+            ControlActionType::NoOp => "no_op",
+        }
+    }
+}
+
+impl std::str::FromStr for ControlActionType {
+    type Err = AppError;
+
+    /// 解析动作类型，同时接受 `emergency_cooling` 与 `EmergencyCooling` 两种写法
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().replace('_', "").to_ascii_lowercase().as_str() {
+            "automaticcontrol" => Ok(ControlActionType::AutomaticControl),
+            "emergencycooling" => Ok(ControlActionType::EmergencyCooling),
+            "manualfancontrol" => Ok(ControlActionType::ManualFanControl),
+            "noop" => Ok(ControlActionType::NoOp),
+            _ => Err(AppError::validation_error("action_type", format!("未知的控制动作类型: {}", s))),
+        }
+    }
+}
+
+/// 控制配置的部分更新
+/// 
+/// 所有字段均可选，未指定的字段保持当前值
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ControlConfigPatch {
+    /// 配置名称
+    pub name: Option<String>,
+    /// 控制模式
+    pub control_mode: Option<ControlMode>,
+    /// 是否启用自动控制
+    pub auto_control_enabled: Option<bool>,
+    /// 目标温度
+    pub target_temperature: Option<f64>,
+    /// 温度容差
+    pub temperature_tolerance: Option<f64>,
+    /// PID参数
+    pub pid_params: Option<PidParameters>,
+    /// 最大风扇转速
+    pub max_fan_speed: Option<i32>,
+    /// 最小风扇转速
+    pub min_fan_speed: Option<i32>,
+    /// 警告温度
+    pub warning_temperature: Option<f64>,
+    /// 临界温度
+    pub critical_temperature: Option<f64>,
+    /// 紧急温度
+    pub emergency_temperature: Option<f64>,
+    /// 进风温度目标调整
+    pub ambient_target: Option<AmbientTargetConfig>,
+    /// 多传感器聚合控制
+    pub sensor_aggregation: Option<SensorAggregationConfig>,
+    /// 风扇分区，整体替换
+    pub fan_zones: Option<Vec<FanZoneConfig>>,
+    /// 各风扇的最低转速，整体替换
+    pub fan_speed_floors: Option<std::collections::HashMap<String, f64>>,
+    /// 舒适区半宽（°C）
+    pub comfort_band: Option<f64>,
+    /// 控制输入平滑窗口（采样数）
+    pub smoothing_window: Option<u32>,
+    /// 按传感器覆盖的平滑窗口，整体替换
+    pub sensor_smoothing_windows: Option<std::collections::HashMap<String, u32>>,
+    /// 低速起转脉冲
+    pub kickstart: Option<FanKickstartConfig>,
+}
+
+/// 控制参数的部分更新
+///
+/// 所有字段均可选，未指定的字段保持当前值
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ControlParametersPatch {
+    /// 目标温度
+    pub target_temperature: Option<f64>,
+    /// 温度容差
+    pub temperature_tolerance: Option<f64>,
+    /// PID参数
+    pub pid_params: Option<PidParameters>,
+    /// 最大风扇转速
+    pub max_fan_speed: Option<i32>,
+    /// 最小风扇转速
+    pub min_fan_speed: Option<i32>,
+    /// 警告温度
+    pub warning_temperature: Option<f64>,
+    /// 临界温度
+    pub critical_temperature: Option<f64>,
+    /// 紧急温度
+    pub emergency_temperature: Option<f64>,
+}
+
 /// 控制历史记录
 /// 
 /// 记录控制操作的历史
@@ -208,6 +759,14 @@ impl ControlConfig {
             control_mode,
             auto_control_enabled: true,
             parameters,
+            ambient_target: None,
+            sensor_aggregation: None,
+            fan_zones: Vec::new(),
+            fan_speed_floors: std::collections::HashMap::new(),
+            comfort_band: 0.0,
+            smoothing_window: 0,
+            sensor_smoothing_windows: std::collections::HashMap::new(),
+            kickstart: None,
             created_at: now,
             updated_at: now,
         }
@@ -224,6 +783,87 @@ impl ControlConfig {
         self.auto_control_enabled = false;
         self.updated_at = Utc::now();
     }
+
+    /// 验证控制配置
+    pub fn validate(&self) -> AppResult<()> {
+        self.parameters.validate()?;
+        if let Some(ref ambient) = self.ambient_target {
+            ambient.validate()?;
+        }
+        if let Some(ref aggregation) = self.sensor_aggregation {
+            aggregation.validate()?;
+        }
+        FanZoneConfig::validate_all(&self.fan_zones)?;
+        for (fan_id, floor) in &self.fan_speed_floors {
+            if fan_id.trim().is_empty() {
+                return Err(AppError::validation_error("fan_speed_floors", "风扇ID不能为空"));
+            }
+            if !(0.0..=self.parameters.max_fan_speed as f64).contains(floor) {
+                return Err(AppError::validation_error(
+                    "fan_speed_floors",
+                    format!("风扇 {} 的最低转速必须在0到最大风扇转速之间", fan_id),
+                ));
+            }
+        }
+        if !self.comfort_band.is_finite() || self.comfort_band < 0.0 {
+            return Err(AppError::validation_error("comfort_band", "舒适区半宽不能为负数"));
+        }
+        if self.smoothing_window > MAX_SMOOTHING_WINDOW {
+            return Err(AppError::validation_error(
+                "smoothing_window",
+                format!("平滑窗口不能超过{}个采样", MAX_SMOOTHING_WINDOW),
+            ));
+        }
+        for (sensor_id, window) in &self.sensor_smoothing_windows {
+            if sensor_id.trim().is_empty() {
+                return Err(AppError::validation_error("sensor_smoothing_windows", "传感器ID不能为空"));
+            }
+            if *window > MAX_SMOOTHING_WINDOW {
+                return Err(AppError::validation_error(
+                    "sensor_smoothing_windows",
+                    format!("传感器 {} 的平滑窗口不能超过{}个采样", sensor_id, MAX_SMOOTHING_WINDOW),
+                ));
+            }
+        }
+        if let Some(ref kickstart) = self.kickstart {
+            kickstart.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// 温度是否处于目标温度附近的舒适区内，未启用舒适区时始终为false
+    ///
+    /// # 参数
+    /// * `temperature` - 控制输入温度
+    /// * `setpoint` - 目标温度
+    pub fn within_comfort_band(&self, temperature: f64, setpoint: f64) -> bool {
+        self.comfort_band > 0.0 && (temperature - setpoint).abs() <= self.comfort_band
+    }
+
+    /// 传感器使用的平滑窗口（采样数），1表示不平滑
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    pub fn smoothing_window_for(&self, sensor_id: &str) -> usize {
+        self.sensor_smoothing_windows
+            .get(sensor_id)
+            .copied()
+            .unwrap_or(self.smoothing_window)
+            .max(1) as usize
+    }
+
+    /// 将转速提升到风扇的最低转速，未配置最低转速的风扇保持不变
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `speed_percent` - 控制输出的转速百分比
+    pub fn floored_fan_speed(&self, fan_id: &str, speed_percent: f64) -> f64 {
+        match self.fan_speed_floors.get(fan_id) {
+            Some(floor) => speed_percent.max(*floor),
+            None => speed_percent,
+        }
+    }
 }
 
 impl ControlStrategy {
@@ -253,4 +893,278 @@ impl ControlStrategy {
             updated_at: now,
         }
     }
-}
\ No newline at end of file
+}
+impl Default for QuietHoursSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            windows: Vec::new(),
+            emergency_temperature: 85.0,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+impl QuietHoursSchedule {
+    /// 验证静音计划
+    pub fn validate(&self) -> AppResult<()> {
+        if !(0.0..=150.0).contains(&self.emergency_temperature) {
+            return Err(AppError::validation_error(
+                "emergency_temperature",
+                "紧急温度必须在0-150°C之间",
+            ));
+        }
+
+        for window in &self.windows {
+            if !(0.0..=100.0).contains(&window.max_fan_percent) {
+                return Err(AppError::validation_error(
+                    "max_fan_percent",
+                    format!("静音时段 {} 的转速上限必须在0-100%之间", window.name),
+                ));
+            }
+            if window.start == window.end {
+                return Err(AppError::validation_error(
+                    "windows",
+                    format!("静音时段 {} 的开始时间与结束时间不能相同", window.name),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 获取指定时间生效的转速上限
+    /// 
+    /// 多个时段重叠时取最严格（最低）的上限
+    /// 
+    /// # 参数
+    /// * `at` - 本地时间
+    pub fn fan_cap_at(&self, at: NaiveDateTime) -> Option<f64> {
+        if !self.enabled {
+            return None;
+        }
+
+        self.windows
+            .iter()
+            .filter(|window| window.contains(at))
+            .map(|window| window.max_fan_percent)
+            .reduce(f64::min)
+    }
+}
+
+impl QuietWindow {
+    /// 判断时间是否落在该时段内
+    /// 
+    /// 跨午夜的时段，午夜之后的部分按开始那天的星期计算
+    /// 
+    /// # 参数
+    /// * `at` - 本地时间
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        let time = at.time();
+        let (in_window, weekday) = if self.start < self.end {
+            (time >= self.start && time < self.end, at.weekday())
+        } else if time >= self.start {
+            (true, at.weekday())
+        } else {
+            (time < self.end, at.weekday().pred())
+        };
+
+        in_window && (self.weekdays.is_empty() || self.weekdays.contains(&weekday))
+    }
+}
+
+impl ControlConfigPatch {
+    /// 是否未指定任何字段
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.control_mode.is_none()
+            && self.auto_control_enabled.is_none()
+            && self.target_temperature.is_none()
+            && self.temperature_tolerance.is_none()
+            && self.pid_params.is_none()
+            && self.max_fan_speed.is_none()
+            && self.min_fan_speed.is_none()
+            && self.warning_temperature.is_none()
+            && self.critical_temperature.is_none()
+            && self.emergency_temperature.is_none()
+            && self.ambient_target.is_none()
+            && self.sensor_aggregation.is_none()
+            && self.fan_zones.is_none()
+            && self.fan_speed_floors.is_none()
+            && self.comfort_band.is_none()
+            && self.smoothing_window.is_none()
+            && self.sensor_smoothing_windows.is_none()
+            && self.kickstart.is_none()
+    }
+
+    /// 将部分更新合并到当前配置并验证合并结果
+    /// 
+    /// 验证失败时返回错误，当前配置不受影响
+    /// 
+    /// # 参数
+    /// * `current` - 当前控制配置
+    pub fn merged(&self, current: &ControlConfig) -> AppResult<ControlConfig> {
+        let mut config = current.clone();
+
+        if let Some(ref name) = self.name {
+            config.name = name.clone();
+        }
+        if let Some(ref mode) = self.control_mode {
+            config.control_mode = mode.clone();
+        }
+        if let Some(enabled) = self.auto_control_enabled {
+            config.auto_control_enabled = enabled;
+        }
+
+        let parameters = &mut config.parameters;
+        if let Some(value) = self.target_temperature {
+            parameters.target_temperature = value;
+        }
+        if let Some(value) = self.temperature_tolerance {
+            parameters.temperature_tolerance = value;
+        }
+        if let Some(ref pid) = self.pid_params {
+            parameters.pid_params = Some(pid.clone());
+        }
+        if let Some(value) = self.max_fan_speed {
+            parameters.max_fan_speed = value;
+        }
+        if let Some(value) = self.min_fan_speed {
+            parameters.min_fan_speed = value;
+        }
+        if let Some(value) = self.warning_temperature {
+            parameters.warning_temperature = value;
+        }
+        if let Some(value) = self.critical_temperature {
+            parameters.critical_temperature = value;
+        }
+        if let Some(value) = self.emergency_temperature {
+            parameters.emergency_temperature = value;
+        }
+        if let Some(ref ambient) = self.ambient_target {
+            config.ambient_target = Some(ambient.clone());
+        }
+        if let Some(ref aggregation) = self.sensor_aggregation {
+            config.sensor_aggregation = Some(aggregation.clone());
+        }
+        if let Some(ref zones) = self.fan_zones {
+            config.fan_zones = zones.clone();
+        }
+        if let Some(ref floors) = self.fan_speed_floors {
+            config.fan_speed_floors = floors.clone();
+        }
+        if let Some(band) = self.comfort_band {
+            config.comfort_band = band;
+        }
+        if let Some(window) = self.smoothing_window {
+            config.smoothing_window = window;
+        }
+        if let Some(ref windows) = self.sensor_smoothing_windows {
+            config.sensor_smoothing_windows = windows.clone();
+        }
+        if let Some(ref kickstart) = self.kickstart {
+            config.kickstart = Some(kickstart.clone());
+        }
+
+        config.validate()?;
+        config.updated_at = Utc::now();
+        Ok(config)
+    }
+}
+
+impl ControlParametersPatch {
+    /// 将部分更新合并到当前参数并校验合并结果
+    ///
+    /// # 参数
+    /// * `current` - 当前控制参数
+    pub fn merged(&self, current: &ControlParameters) -> AppResult<ControlParameters> {
+        let mut parameters = current.clone();
+        if let Some(value) = self.target_temperature {
+            parameters.target_temperature = value;
+        }
+        if let Some(value) = self.temperature_tolerance {
+            parameters.temperature_tolerance = value;
+        }
+        if let Some(ref pid) = self.pid_params {
+            parameters.pid_params = Some(pid.clone());
+        }
+        if let Some(value) = self.max_fan_speed {
+            parameters.max_fan_speed = value;
+        }
+        if let Some(value) = self.min_fan_speed {
+            parameters.min_fan_speed = value;
+        }
+        if let Some(value) = self.warning_temperature {
+            parameters.warning_temperature = value;
+        }
+        if let Some(value) = self.critical_temperature {
+            parameters.critical_temperature = value;
+        }
+        if let Some(value) = self.emergency_temperature {
+            parameters.emergency_temperature = value;
+        }
+
+        parameters.validate()?;
+        Ok(parameters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(target: f64, warning: f64, critical: f64, emergency: f64) -> ControlConfig {
+        ControlConfig::new(
+            "default".to_string(),
+            ControlMode::Pid,
+            ControlParameters {
+                target_temperature: target,
+                temperature_tolerance: 2.0,
+                pid_params: None,
+                max_fan_speed: 100,
+                min_fan_speed: 20,
+                warning_temperature: warning,
+                critical_temperature: critical,
+                emergency_temperature: emergency,
+            },
+        )
+    }
+
+    fn rejected_field(result: AppResult<ControlConfig>) -> String {
+        match result {
+            Err(AppError::ValidationError { field, message }) => {
+                assert!(message.contains("必须低于"), "{}", message);
+                field
+            }
+            other => panic!("expected validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_temperature_thresholds_must_increase() {
+        let current = config(65.0, 75.0, 85.0, 95.0);
+        assert!(current.validate().is_ok());
+
+        let patch = |target, warning, critical, emergency| ControlConfigPatch {
+            target_temperature: target,
+            warning_temperature: warning,
+            critical_temperature: critical,
+            emergency_temperature: emergency,
+            ..Default::default()
+        };
+
+        // 每一对相邻阈值颠倒时都被拒绝，并指出较高的一项
+        let cases = [
+            (patch(Some(80.0), None, None, None), "warning_temperature"),
+            (patch(None, Some(90.0), None, None), "critical_temperature"),
+            (patch(None, None, Some(96.0), None), "emergency_temperature"),
+            (patch(None, None, None, Some(85.0)), "emergency_temperature"),
+        ];
+        for (patch, field) in cases {
+            assert_eq!(rejected_field(patch.merged(&current)), field);
+        }
+
+        let valid = patch(Some(70.0), Some(78.0), Some(88.0), Some(98.0)).merged(&current).unwrap();
+        assert_eq!(valid.parameters.warning_temperature, 78.0);
+    }
+}
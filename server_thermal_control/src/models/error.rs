@@ -1,6 +1,8 @@
-use actix_web::{HttpResponse, ResponseError};
+use crate::models::api::ApiResponse;
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tracing::{error, warn};
 use utoipa::ToSchema;
 
 /// 应用程序错误类型
@@ -83,6 +85,18 @@ pub enum AppError {
     /// 业务逻辑错误
     #[error("业务逻辑错误: {message}")]
     BusinessLogicError { message: String },
+
+    /// 样本数量不足，统计结果没有意义
+    #[error("数据不足: 需要至少 {required} 个样本，实际 {actual} 个")]
+    InsufficientDataError { required: usize, actual: usize },
+
+    /// 请求体类型不受支持
+    #[error("不支持的请求体类型: {content_type}，请求体必须为 application/json")]
+    UnsupportedMediaTypeError { content_type: String },
+
+    /// 请求的API版本不受支持
+    #[error("不支持的API版本: {requested}，支持的版本: {supported}")]
+    NotAcceptableError { requested: String, supported: String },
 }
 
 /// 错误响应结构
@@ -102,6 +116,19 @@ pub struct ErrorResponse {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+/// 错误目录条目
+///
+/// 由 `AppError` 生成，与错误响应中的 `error_code` 一一对应
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ErrorCatalogEntry {
+    /// 错误代码
+    pub code: String,
+    /// HTTP状态码
+    pub status: u16,
+    /// 错误说明
+    pub description: String,
+}
+
 /// 验证错误详情
 ///
 /// 字段验证错误的详细信息
@@ -199,6 +226,67 @@ impl AppError {
         }
     }
 
+    /// 创建认证错误（401）
+    ///
+    /// # 参数
+    /// * `message` - 错误消息
+    pub fn authentication_error(message: impl Into<String>) -> Self {
+        Self::AuthenticationError {
+            message: message.into(),
+        }
+    }
+
+    /// 创建只读模式拒绝写操作的错误（403）
+    ///
+    /// # 参数
+    /// * `operation` - 被拒绝的操作
+    pub fn read_only_error(operation: impl Into<String>) -> Self {
+        Self::AuthorizationError {
+            message: format!("只读监控模式下禁止执行: {}", operation.into()),
+        }
+    }
+
+    /// 创建样本数量不足错误（422）
+    ///
+    /// # 参数
+    /// * `required` - 最少样本数
+    /// * `actual` - 实际样本数
+    pub fn insufficient_data_error(required: usize, actual: usize) -> Self {
+        Self::InsufficientDataError { required, actual }
+    }
+
+    /// 创建请求体类型不受支持错误（415）
+    ///
+    /// # 参数
+    /// * `content_type` - 请求的Content-Type，缺失时为空
+    pub fn unsupported_media_type_error(content_type: impl Into<String>) -> Self {
+        Self::UnsupportedMediaTypeError {
+            content_type: content_type.into(),
+        }
+    }
+
+    /// 创建API版本不受支持错误（406）
+    ///
+    /// # 参数
+    /// * `requested` - 请求头 `Accept-Version` 的值
+    /// * `supported` - 支持的版本，逗号分隔
+    pub fn not_acceptable_error(requested: impl Into<String>, supported: impl Into<String>) -> Self {
+        Self::NotAcceptableError {
+            requested: requested.into(),
+            supported: supported.into(),
+        }
+    }
+
+    /// 创建请求过于频繁错误（429）
+    ///
+    /// # 参数
+    /// * `message` - 错误消息
+    pub fn rate_limit_error(message: impl Into<String>) -> Self {
+        Self::RateLimitError {
+            message: message.into(),
+        }
+    }
+
     /// 创建内部服务器错误
     ///
     /// # 参数
@@ -253,6 +341,9 @@ impl AppError {
             AppError::ServiceUnavailableError { .. } => "SERVICE_UNAVAILABLE",
             AppError::RateLimitError { .. } => "RATE_LIMIT_EXCEEDED",
             AppError::BusinessLogicError { .. } => "BUSINESS_LOGIC_ERROR",
+            AppError::InsufficientDataError { .. } => "INSUFFICIENT_DATA",
+            AppError::UnsupportedMediaTypeError { .. } => "UNSUPPORTED_MEDIA_TYPE",
+            AppError::NotAcceptableError { .. } => "NOT_ACCEPTABLE",
         }
     }
 
@@ -265,7 +356,10 @@ impl AppError {
             AppError::AuthenticationError { .. } => 401,
             AppError::AuthorizationError { .. } => 403,
             AppError::NotFoundError { .. } => 404,
+            AppError::NotAcceptableError { .. } => 406,
             AppError::ConflictError { .. } => 409,
+            AppError::UnsupportedMediaTypeError { .. } => 415,
+            AppError::InsufficientDataError { .. } => 422,
             AppError::RateLimitError { .. } => 429,
             AppError::InternalServerError { .. } => 500,
             AppError::ServiceUnavailableError { .. } => 503,
@@ -282,11 +376,117 @@ impl AppError {
         ErrorResponse {
             error_code: self.error_code().to_string(),
             message: self.to_string(),
-            details: None,
+            details: match self {
+                AppError::InsufficientDataError { required, actual } => Some(serde_json::json!({
+                    "insufficient_data": true,
+                    "required_samples": required,
+                    "actual_samples": actual,
+                })),
+                _ => None,
+            },
             request_id,
             timestamp: chrono::Utc::now(),
         }
     }
+
+    /// 错误说明，用于错误目录
+    pub fn description(&self) -> &'static str {
+        match self {
+            AppError::DatabaseError { .. } => "数据库读写失败或数据库不可用",
+            AppError::IpmiError { .. } => "ipmitool执行失败或BMC返回错误",
+            AppError::ConfigError { .. } => "配置文件或配置项无效",
+            AppError::ValidationError { .. } => "请求参数未通过校验，`message` 中包含出错的字段",
+            AppError::AuthenticationError { .. } => "缺少API密钥或密钥无效",
+            AppError::AuthorizationError { .. } => "无权执行该操作，包括只读监控模式下的写操作",
+            AppError::NotFoundError { .. } => "请求的资源不存在",
+            AppError::ConflictError { .. } => "与资源当前状态冲突，如重复创建或操作正在进行",
+            AppError::ExternalServiceError { .. } => "外部服务（如Webhook接收端）调用失败",
+            AppError::NetworkError { .. } => "网络连接失败",
+            AppError::TimeoutError { .. } => "操作在限定时间内未完成",
+            AppError::ParseError { .. } => "数据解析失败，如时间或UUID格式错误",
+            AppError::SerializationError { .. } => "数据序列化或反序列化失败",
+            AppError::FileSystemError { .. } => "文件读写失败",
+            AppError::PermissionError { .. } => "缺少文件或系统权限",
+            AppError::InternalServerError { .. } => "未预期的内部错误",
+            AppError::ServiceUnavailableError { .. } => "依赖的服务暂不可用，如未配置数据库",
+            AppError::RateLimitError { .. } => "请求过于频繁，请稍后重试",
+            AppError::BusinessLogicError { .. } => "操作不满足业务条件而中止",
+            AppError::InsufficientDataError { .. } => "样本数量不足，`details` 中给出所需和实际样本数",
+            AppError::UnsupportedMediaTypeError { .. } => "请求体类型不受支持，必须为 application/json",
+            AppError::NotAcceptableError { .. } => "请求头 `Accept-Version` 指定的API版本不受支持",
+        }
+    }
+
+    /// 错误目录：每种错误的代码、HTTP状态码和说明，按代码排序
+    pub fn catalog() -> Vec<ErrorCatalogEntry> {
+        let mut entries: Vec<ErrorCatalogEntry> = Self::variants()
+            .iter()
+            .map(|error| ErrorCatalogEntry {
+                code: error.error_code().to_string(),
+                status: error.status_code(),
+                description: error.description().to_string(),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.code.cmp(&b.code));
+        entries
+    }
+
+    /// 每种错误各一个示例值
+    ///
+    /// 新增错误类型时下面的match无法通过编译，需同时在列表中加入示例
+    fn variants() -> Vec<AppError> {
+        let variants = vec![
+            AppError::DatabaseError { message: String::new() },
+            AppError::IpmiError { message: String::new() },
+            AppError::ConfigError { message: String::new() },
+            AppError::ValidationError { field: String::new(), message: String::new() },
+            AppError::AuthenticationError { message: String::new() },
+            AppError::AuthorizationError { message: String::new() },
+            AppError::NotFoundError { resource: String::new(), id: String::new() },
+            AppError::ConflictError { message: String::new() },
+            AppError::ExternalServiceError { service: String::new(), message: String::new() },
+            AppError::NetworkError { message: String::new() },
+            AppError::TimeoutError { operation: String::new() },
+            AppError::ParseError { message: String::new() },
+            AppError::SerializationError { message: String::new() },
+            AppError::FileSystemError { message: String::new() },
+            AppError::PermissionError { message: String::new() },
+            AppError::InternalServerError { message: String::new() },
+            AppError::ServiceUnavailableError { service: String::new() },
+            AppError::RateLimitError { message: String::new() },
+            AppError::BusinessLogicError { message: String::new() },
+            AppError::InsufficientDataError { required: 0, actual: 0 },
+            AppError::UnsupportedMediaTypeError { content_type: String::new() },
+            AppError::NotAcceptableError { requested: String::new(), supported: String::new() },
+        ];
+        for variant in &variants {
+            match variant {
+                AppError::DatabaseError { .. }
+                | AppError::IpmiError { .. }
+                | AppError::ConfigError { .. }
+                | AppError::ValidationError { .. }
+                | AppError::AuthenticationError { .. }
+                | AppError::AuthorizationError { .. }
+                | AppError::NotFoundError { .. }
+                | AppError::ConflictError { .. }
+                | AppError::ExternalServiceError { .. }
+                | AppError::NetworkError { .. }
+                | AppError::TimeoutError { .. }
+                | AppError::ParseError { .. }
+                | AppError::SerializationError { .. }
+                | AppError::FileSystemError { .. }
+                | AppError::PermissionError { .. }
+                | AppError::InternalServerError { .. }
+                | AppError::ServiceUnavailableError { .. }
+                | AppError::RateLimitError { .. }
+                | AppError::BusinessLogicError { .. }
+                | AppError::InsufficientDataError { .. }
+                | AppError::UnsupportedMediaTypeError { .. }
+                | AppError::NotAcceptableError { .. } => {}
+            }
+        }
+        variants
+    }
 }
 
 impl ResponseError for AppError {
@@ -298,7 +498,10 @@ impl ResponseError for AppError {
             401 => HttpResponse::Unauthorized().json(error_response),
             403 => HttpResponse::Forbidden().json(error_response),
             404 => HttpResponse::NotFound().json(error_response),
+            406 => HttpResponse::NotAcceptable().json(error_response),
             409 => HttpResponse::Conflict().json(error_response),
+            415 => HttpResponse::UnsupportedMediaType().json(error_response),
+            422 => HttpResponse::UnprocessableEntity().json(error_response),
             429 => HttpResponse::TooManyRequests().json(error_response),
             503 => HttpResponse::ServiceUnavailable().json(error_response),
             504 => HttpResponse::GatewayTimeout().json(error_response),
@@ -307,6 +510,54 @@ impl ResponseError for AppError {
     }
 }
 
+/// API错误
+///
+/// 将 `AppError` 包装为统一的 `ApiResponse` 错误响应，状态码取自错误类型，
+/// 使控制器可以直接通过 `?` 传播业务错误
+#[derive(Debug)]
+pub struct ApiError(pub AppError);
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<AppError> for ApiError {
+    fn from(err: AppError) -> Self {
+        ApiError(err)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.0.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = ResponseError::status_code(self);
+        if status.is_server_error() {
+            error!("请求处理失败: {}", self.0);
+        } else {
+            warn!("请求处理失败: {}", self.0);
+        }
+
+        // 带详情的错误（如数据不足）把详情放进 data，便于调用方按字段判断
+        match self.0.to_error_response(None).details {
+            Some(details) => HttpResponse::build(status).json(ApiResponse {
+                data: Some(details),
+                ..ApiResponse::error(&self.0.to_string())
+            }),
+            None => HttpResponse::build(status).json(ApiResponse::<()>::error(&self.0.to_string())),
+        }
+    }
+}
+
+/// API处理结果类型
+///
+/// 控制器处理函数的返回类型，错误会被转换为 `ApiResponse` 错误响应
+pub type ApiResult<T = HttpResponse> = Result<T, ApiError>;
+
 // 从其他错误类型转换为AppError
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
@@ -398,3 +649,51 @@ macro_rules! internal_error {
         AppError::internal_server_error($message)
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_lists_every_error_code_once() {
+        let catalog = AppError::catalog();
+        let errors = [
+            AppError::database_error("db"),
+            AppError::ipmi_error("ipmi"),
+            AppError::config_error("config"),
+            AppError::validation_error("field", "invalid"),
+            AppError::authentication_error("key"),
+            AppError::read_only_error("write"),
+            AppError::not_found_error("fan", "FAN9"),
+            AppError::ConflictError { message: "busy".to_string() },
+            AppError::ExternalServiceError { service: "webhook".to_string(), message: "502".to_string() },
+            AppError::network_error("reset"),
+            AppError::TimeoutError { operation: "tune".to_string() },
+            AppError::ParseError { message: "uuid".to_string() },
+            AppError::SerializationError { message: "json".to_string() },
+            AppError::FileSystemError { message: "io".to_string() },
+            AppError::PermissionError { message: "denied".to_string() },
+            AppError::internal_server_error("bug"),
+            AppError::ServiceUnavailableError { service: "数据库".to_string() },
+            AppError::rate_limit_error("slow down"),
+            AppError::BusinessLogicError { message: "aborted".to_string() },
+            AppError::insufficient_data_error(10, 2),
+            AppError::unsupported_media_type_error("text/plain"),
+            AppError::not_acceptable_error("v9", "v1"),
+        ];
+
+        for error in &errors {
+            let entry = catalog
+                .iter()
+                .find(|entry| entry.code == error.error_code())
+                .unwrap_or_else(|| panic!("{} missing from catalog", error.error_code()));
+            assert_eq!(entry.status, error.status_code());
+            assert!(!entry.description.trim().is_empty(), "{} has no description", entry.code);
+        }
+
+        let mut codes: Vec<_> = catalog.iter().map(|entry| entry.code.as_str()).collect();
+        codes.dedup();
+        assert_eq!(codes.len(), catalog.len());
+        assert_eq!(catalog.len(), errors.len());
+    }
+}
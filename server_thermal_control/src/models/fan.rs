@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
@@ -378,3 +379,151 @@ impl FanControlStrategy {
         }
     }
 }
+
+/// 散热区域
+///
+/// 描述一组传感器及为其散热的风扇，风扇故障时由同区域内的其他风扇分担负载
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CoolingZone {
+    /// 区域标识符
+    pub id: String,
+    /// 区域名称
+    pub name: String,
+    /// 区域内的温度传感器
+    pub sensor_ids: Vec<String>,
+    /// 为该区域散热的风扇
+    pub fan_ids: Vec<String>,
+    /// 区域内风扇允许的最高转速百分比
+    #[serde(default = "default_zone_max_speed_percent")]
+    pub max_speed_percent: f64,
+}
+
+fn default_zone_max_speed_percent() -> f64 {
+    100.0
+}
+
+/// 风扇故障记录
+///
+/// 风扇在有转速指令时转速为0即视为故障，直到恢复转动或维护人员解除
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FanFailure {
+    /// 故障风扇ID
+    pub fan_id: String,
+    /// 所属区域ID
+    pub zone_id: String,
+    /// 检测到故障时的指令转速百分比
+    pub commanded_percent: f64,
+    /// 检测时间
+    pub detected_at: DateTime<Utc>,
+}
+
+/// 风扇故障转移策略
+///
+/// 将故障风扇的指令负载平均分摊到同区域内的健康风扇
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FanFailoverPolicy {
+    /// 散热区域列表
+    pub zones: Vec<CoolingZone>,
+    /// 负载分摊系数，1.0表示完全补偿故障风扇的指令转速
+    pub boost_factor: f64,
+}
+
+impl Default for FanFailoverPolicy {
+    fn default() -> Self {
+        Self {
+            zones: Vec::new(),
+            boost_factor: 1.0,
+        }
+    }
+}
+
+impl FanFailoverPolicy {
+    /// 判断风扇是否故障
+    ///
+    /// # 参数
+    /// * `speed_rpm` - 实测转速
+    /// * `commanded_percent` - 指令转速百分比
+    pub fn is_fan_failed(speed_rpm: f64, commanded_percent: f64) -> bool {
+        speed_rpm <= 0.0 && commanded_percent > 0.0
+    }
+
+    /// 查找风扇所属区域
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    pub fn zone_of(&self, fan_id: &str) -> Option<&CoolingZone> {
+        self.zones
+            .iter()
+            .find(|zone| zone.fan_ids.iter().any(|id| id == fan_id))
+    }
+
+    /// 更新故障记录并计算各风扇的实际指令转速
+    ///
+    /// 新出现的故障被记录，恢复转动的风扇从故障记录中移除；
+    /// 健康风扇在基础指令上叠加同区域故障风扇的分摊负载
+    ///
+    /// # 参数
+    /// * `commanded` - 各风扇的基础指令转速百分比
+    /// * `rpm_readings` - 各风扇的实测转速
+    /// * `failures` - 当前故障记录，会被原地更新
+    ///
+    /// # 返回值
+    /// 需要下发的风扇ID到指令转速百分比的映射
+    pub fn evaluate(
+        &self,
+        commanded: &HashMap<String, f64>,
+        rpm_readings: &HashMap<String, f64>,
+        failures: &mut HashMap<String, FanFailure>,
+    ) -> HashMap<String, f64> {
+        for zone in &self.zones {
+            for fan_id in &zone.fan_ids {
+                let base = commanded.get(fan_id).copied().unwrap_or(0.0);
+                let Some(&rpm) = rpm_readings.get(fan_id) else {
+                    continue;
+                };
+
+                if Self::is_fan_failed(rpm, base) {
+                    failures.entry(fan_id.clone()).or_insert_with(|| FanFailure {
+                        fan_id: fan_id.clone(),
+                        zone_id: zone.id.clone(),
+                        commanded_percent: base,
+                        detected_at: Utc::now(),
+                    });
+                } else if rpm > 0.0 {
+                    failures.remove(fan_id);
+                }
+            }
+        }
+
+        let mut targets = HashMap::new();
+        for zone in &self.zones {
+            let healthy: Vec<&String> = zone
+                .fan_ids
+                .iter()
+                .filter(|id| !failures.contains_key(*id))
+                .collect();
+            let lost_load: f64 = failures
+                .values()
+                .filter(|failure| failure.zone_id == zone.id)
+                .map(|failure| failure.commanded_percent)
+                .sum();
+            let boost = if healthy.is_empty() {
+                0.0
+            } else {
+                lost_load * self.boost_factor / healthy.len() as f64
+            };
+
+            for fan_id in &zone.fan_ids {
+                let base = commanded.get(fan_id).copied().unwrap_or(0.0);
+                let target = if failures.contains_key(fan_id) {
+                    base
+                } else {
+                    (base + boost).min(zone.max_speed_percent)
+                };
+                targets.insert(fan_id.clone(), target);
+            }
+        }
+
+        targets
+    }
+}
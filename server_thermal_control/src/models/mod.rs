@@ -89,6 +89,10 @@ pub struct Alert {
     pub message: String,
     pub source: String,
     pub source_id: String,
+    /// 触发该告警的规则ID，非规则触发的告警为空
+    #[serde(default)]
+    #[sqlx(default)]
+    pub rule_id: Option<String>,
     pub status: AlertStatus,
     pub acknowledged: bool,
     pub acknowledged_by: Option<String>,
@@ -77,8 +77,12 @@ pub struct SensorReading {
     pub id: Uuid,
     /// 传感器标识符
     pub sensor_id: String,
-    /// 读数值
+    /// 读数值（已校准）
     pub value: f64,
+    /// 校准前的原始读数值，未配置校准时为空
+    #[serde(default)]
+    #[sqlx(default)]
+    pub raw_value: Option<f64>,
     /// 读数状态
     pub status: SensorStatus,
     /// 读数时间戳
@@ -208,6 +212,31 @@ pub struct SensorCalibration {
     pub calibrated_at: DateTime<Utc>,
 }
 
+impl SensorCalibration {
+    /// 创建校准参数
+    ///
+    /// # 参数
+    /// * `offset` - 偏移量（°C）
+    /// * `scale_factor` - 缩放因子，默认1.0
+    /// * `reference_value` - 参考探头读数
+    pub fn new(offset: f64, scale_factor: Option<f64>, reference_value: Option<f64>) -> Self {
+        Self {
+            offset,
+            scale_factor: scale_factor.unwrap_or(1.0),
+            reference_value: reference_value.unwrap_or(0.0),
+            calibrated_at: Utc::now(),
+        }
+    }
+
+    /// 将原始读数转换为校准值：`raw * scale_factor + offset`
+    ///
+    /// # 参数
+    /// * `raw_value` - 原始读数值
+    pub fn apply(&self, raw_value: f64) -> f64 {
+        raw_value * self.scale_factor + self.offset
+    }
+}
+
 /// 平滑算法枚举
 ///
 /// 定义不同的数据平滑算法
@@ -419,12 +448,38 @@ impl SensorReading {
             id: Uuid::new_v4(),
             sensor_id,
             value,
+            raw_value: None,
             status,
             timestamp: Utc::now(),
             raw_data,
         }
     }
 
+    /// 创建经过校准的传感器读数
+    ///
+    /// 告警状态按校准后的值判断，原始值保存在 `raw_value` 中
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器标识符
+    /// * `raw_value` - 原始读数值
+    /// * `calibration` - 校准参数，None表示不校准
+    /// * `thresholds` - 告警阈值配置
+    /// * `raw_data` - 原始数据
+    pub fn calibrated(
+        sensor_id: String,
+        raw_value: f64,
+        calibration: Option<&SensorCalibration>,
+        thresholds: &AlertThresholds,
+        raw_data: Option<String>,
+    ) -> Self {
+        let value = calibration.map_or(raw_value, |c| c.apply(raw_value));
+        let mut reading = Self::new(sensor_id, value, thresholds, raw_data);
+        if calibration.is_some() {
+            reading.raw_value = Some(raw_value);
+        }
+        reading
+    }
+
     /// 根据读数值和阈值确定传感器状态
     ///
     /// # 参数
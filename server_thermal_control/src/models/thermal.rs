@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::ToSchema;
@@ -110,6 +110,123 @@ pub struct TemperatureTrend {
     pub sensor_id: String,
 }
 
+/// 聚合粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregateResolution {
+    /// 按小时聚合
+    Hourly,
+    /// 按天聚合
+    Daily,
+}
+
+impl AggregateResolution {
+    /// 聚合桶时长
+    pub fn duration(&self) -> Duration {
+        match self {
+            AggregateResolution::Hourly => Duration::hours(1),
+            AggregateResolution::Daily => Duration::days(1),
+        }
+    }
+
+    /// 计算时间所在聚合桶的起始时间（UTC对齐）
+    ///
+    /// # 参数
+    /// * `timestamp` - 时间戳
+    pub fn bucket_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let bucket_secs = self.duration().num_seconds();
+        let secs = timestamp.timestamp();
+        DateTime::from_timestamp(secs - secs.rem_euclid(bucket_secs), 0).unwrap_or(timestamp)
+    }
+}
+
+/// 温度聚合数据
+///
+/// 压缩后的历史温度，对应 `temperature_aggregates` 表
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TemperatureAggregate {
+    /// 传感器标识符
+    pub sensor_id: String,
+    /// 聚合粒度
+    pub resolution: AggregateResolution,
+    /// 聚合桶起始时间
+    pub bucket_start: DateTime<Utc>,
+    /// 最低温度
+    pub min: f64,
+    /// 平均温度
+    pub avg: f64,
+    /// 最高温度
+    pub max: f64,
+    /// 原始样本数量
+    pub sample_count: u64,
+}
+
+impl TemperatureAggregate {
+    /// 合并同一聚合桶的另一份聚合，平均值按样本数加权
+    ///
+    /// # 参数
+    /// * `other` - 同一传感器、同一聚合桶的聚合数据
+    pub fn merge(&mut self, other: &TemperatureAggregate) {
+        let total = self.sample_count + other.sample_count;
+        if total > 0 {
+            self.avg = (self.avg * self.sample_count as f64 + other.avg * other.sample_count as f64)
+                / total as f64;
+        }
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sample_count = total;
+    }
+}
+
+/// 温度时间序列数据点
+///
+/// 近期范围为原始读数（`resolution` 为空，min/avg/max相同），早期范围为聚合数据
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TemperatureSeriesPoint {
+    /// 传感器标识符
+    pub sensor_id: String,
+    /// 时间戳（聚合数据为桶起始时间）
+    pub timestamp: DateTime<Utc>,
+    /// 聚合粒度，原始读数为空
+    pub resolution: Option<AggregateResolution>,
+    /// 最低温度
+    pub min: f64,
+    /// 平均温度
+    pub avg: f64,
+    /// 最高温度
+    pub max: f64,
+    /// 样本数量
+    pub sample_count: u64,
+}
+
+impl From<&TemperatureReading> for TemperatureSeriesPoint {
+    fn from(reading: &TemperatureReading) -> Self {
+        Self {
+            sensor_id: reading.sensor_id.clone(),
+            timestamp: reading.timestamp,
+            resolution: None,
+            min: reading.temperature,
+            avg: reading.temperature,
+            max: reading.temperature,
+            sample_count: 1,
+        }
+    }
+}
+
+impl From<&TemperatureAggregate> for TemperatureSeriesPoint {
+    fn from(aggregate: &TemperatureAggregate) -> Self {
+        Self {
+            sensor_id: aggregate.sensor_id.clone(),
+            timestamp: aggregate.bucket_start,
+            resolution: Some(aggregate.resolution),
+            min: aggregate.min,
+            avg: aggregate.avg,
+            max: aggregate.max,
+            sample_count: aggregate.sample_count,
+        }
+    }
+}
+
 /// 温度查询参数
 ///
 /// 用于API查询的参数结构
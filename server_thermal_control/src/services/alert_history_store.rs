@@ -0,0 +1,143 @@
+use crate::models::error::AppResult;
+use crate::models::Alert;
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+/// 告警历史存储
+///
+/// 保存全部告警，内存中的告警历史只保留最近的一部分，超出部分从这里查询
+#[async_trait]
+pub trait AlertHistoryStore: Send + Sync {
+    /// 写入告警，已存在时整体覆盖
+    ///
+    /// # 参数
+    /// * `alert` - 告警
+    async fn upsert(&self, alert: &Alert) -> AppResult<()>;
+
+    /// 按ID读取告警
+    ///
+    /// # 参数
+    /// * `alert_id` - 告警ID
+    async fn get(&self, alert_id: &str) -> AppResult<Option<Alert>>;
+
+    /// 已保存的告警数量
+    async fn count(&self) -> AppResult<u64>;
+}
+
+/// 进程内告警历史存储，不限制数量，仅用于测试和未配置数据库的场景
+#[derive(Default)]
+pub struct InMemoryAlertHistoryStore {
+    alerts: RwLock<HashMap<String, Alert>>,
+}
+
+impl InMemoryAlertHistoryStore {
+    /// 创建进程内告警历史存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AlertHistoryStore for InMemoryAlertHistoryStore {
+    async fn upsert(&self, alert: &Alert) -> AppResult<()> {
+        self.alerts.write().await.insert(alert.id.to_string(), alert.clone());
+        Ok(())
+    }
+
+    async fn get(&self, alert_id: &str) -> AppResult<Option<Alert>> {
+        Ok(self.alerts.read().await.get(alert_id).cloned())
+    }
+
+    async fn count(&self) -> AppResult<u64> {
+        Ok(self.alerts.read().await.len() as u64)
+    }
+}
+
+/// PostgreSQL告警历史存储
+///
+/// 写入 `scripts/init.sql` 中定义的 `alerts` 表，常用字段单独成列便于查询，
+/// 完整告警以JSON保存在 `metadata` 列，读取时从该列还原
+pub struct PgAlertHistoryStore {
+    pool: PgPool,
+}
+
+impl PgAlertHistoryStore {
+    /// 创建数据库告警历史存储
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AlertHistoryStore for PgAlertHistoryStore {
+    #[instrument(level = "debug", name = "db.alerts.upsert", skip_all)]
+    async fn upsert(&self, alert: &Alert) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO alerts (
+                id, alert_type, severity, title, message, source, source_id, status,
+                acknowledged, acknowledged_by, acknowledged_at, resolved, resolved_at,
+                metadata, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14::jsonb, $15, $16)
+            ON CONFLICT (id) DO UPDATE SET
+                status = EXCLUDED.status,
+                acknowledged = EXCLUDED.acknowledged,
+                acknowledged_by = EXCLUDED.acknowledged_by,
+                acknowledged_at = EXCLUDED.acknowledged_at,
+                resolved = EXCLUDED.resolved,
+                resolved_at = EXCLUDED.resolved_at,
+                metadata = EXCLUDED.metadata,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(alert.id)
+        .bind(alert.alert_type.to_string())
+        .bind(alert.severity.to_string())
+        .bind(&alert.title)
+        .bind(&alert.message)
+        .bind(&alert.source)
+        .bind(&alert.source_id)
+        .bind(format!("{:?}", alert.status).to_lowercase())
+        .bind(alert.acknowledged)
+        .bind(&alert.acknowledged_by)
+        .bind(alert.acknowledged_at)
+        .bind(alert.resolved_at.is_some())
+        .bind(alert.resolved_at)
+        .bind(serde_json::to_string(alert)?)
+        .bind(alert.created_at)
+        .bind(alert.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "db.alerts.get", skip_all)]
+    async fn get(&self, alert_id: &str) -> AppResult<Option<Alert>> {
+        let row = sqlx::query("SELECT metadata::text AS alert FROM alerts WHERE id::text = $1")
+            .bind(alert_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| -> AppResult<Alert> {
+            let alert: String = row.try_get("alert")?;
+            Ok(serde_json::from_str(&alert)?)
+        })
+        .transpose()
+    }
+
+    #[instrument(level = "debug", name = "db.alerts.count", skip_all)]
+    async fn count(&self) -> AppResult<u64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM alerts")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as u64)
+    }
+}
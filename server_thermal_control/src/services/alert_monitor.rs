@@ -0,0 +1,550 @@
+use crate::models::alert::{AlertRule, ThresholdUnit};
+use crate::models::Alert;
+use crate::services::alert_history_store::AlertHistoryStore;
+use crate::services::alert_rules::AlertRuleStore;
+use crate::services::alert_store::{AlertStore, AlertTransition, NewAlert};
+use crate::services::breach_debounce::{track_breach, BreachStarts};
+use crate::services::incident_store::IncidentStore;
+use crate::services::ipmi_service::{FanSensor, TemperatureSensor};
+use crate::services::poll_ticker::PollTicker;
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::sensor_cache::LastGoodSensorCache;
+use crate::services::sensor_source::SensorSource;
+use crate::services::task_watchdog::Heartbeat;
+use crate::services::thermal_gradient::{ThermalGradientBreach, ThermalGradientConfig, ThermalGradientMonitor};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+use uuid::Uuid;
+
+/// 一条规则对一个告警源的命中
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleMatch {
+    /// 规则ID
+    pub rule_id: String,
+    /// 规则名称
+    pub rule_name: String,
+    /// 告警源ID（传感器或风扇ID）
+    pub source_id: String,
+    /// 读数值
+    pub value: f64,
+    /// 本次评估创建或升级的告警，持续时间不足或已有同一告警时为空
+    pub alert_id: Option<Uuid>,
+}
+
+/// 一轮规则评估的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleEvaluation {
+    /// 评估时间
+    pub evaluated_at: DateTime<Utc>,
+    /// 评估的启用规则数
+    pub evaluated_rules: usize,
+    /// 评估的读数数（温度传感器和风扇）
+    pub evaluated_readings: usize,
+    /// 满足条件的规则和告警源，包括持续时间尚不足的
+    pub matches: Vec<RuleMatch>,
+    /// 本次评估创建或升级的告警
+    pub created_alerts: Vec<Alert>,
+}
+
+/// 规则对应的读数：（告警源ID, 值）
+///
+/// 温度规则取各温度传感器，风扇规则按 `threshold_unit` 取转速或百分比，
+/// 风扇负载规则取占空比百分比；其他指标没有读数
+fn metric_readings(
+    rule: &AlertRule,
+    temperatures: &[TemperatureSensor],
+    fans: &[FanSensor],
+) -> Vec<(String, f64)> {
+    match rule.metric.as_str() {
+        "temperature" => temperatures
+            .iter()
+            .map(|sensor| (sensor.sensor_id.clone(), sensor.temperature))
+            .collect(),
+        "fan_speed" => {
+            let unit = rule.threshold_unit.unwrap_or(ThresholdUnit::Rpm);
+            fans.iter()
+                .filter_map(|fan| {
+                    unit.fan_value(fan.speed_rpm as f64, fan.speed_percent as f64)
+                        .ok()
+                        .map(|value| (fan.fan_id.clone(), value))
+                })
+                .collect()
+        }
+        "fan_utilization" => fans
+            .iter()
+            .map(|fan| (fan.fan_id.clone(), fan.speed_percent as f64))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 告警类型：风扇负载规则为维护类告警，与风扇故障区分
+fn alert_type(rule: &AlertRule) -> &str {
+    match rule.metric.as_str() {
+        "fan_speed" => "fan",
+        "fan_utilization" => "maintenance",
+        metric => metric,
+    }
+}
+
+/// 告警消息
+fn alert_message(rule: &AlertRule, source_id: &str, value: f64) -> String {
+    let threshold = rule.condition.threshold;
+    match rule.metric.as_str() {
+        "temperature" => format!("温度异常: {:.1}°C (阈值: {:.1}°C)", value, threshold),
+        "fan_speed" if rule.threshold_unit == Some(ThresholdUnit::Percent) => {
+            format!("风扇转速异常: {:.0}% (阈值: {:.0}%)", value, threshold)
+        }
+        "fan_speed" => format!("风扇转速异常: {:.0} RPM (阈值: {:.0} RPM)", value, threshold),
+        "fan_utilization" => format!(
+            "风扇 {} 持续高负载: {:.1}% 超过 {:.1}% 已达{}秒，散热可能不足，请检查风道",
+            source_id, value, threshold, rule.condition.duration_seconds
+        ),
+        metric => format!("{} 异常: {:.1} (阈值: {:.1})", metric, value, threshold),
+    }
+}
+
+/// 温差告警消息
+fn gradient_message(breach: &ThermalGradientBreach) -> String {
+    format!(
+        "温差过大: {} {:.1}°C / {} {:.1}°C，温差 {:.1}°C (阈值: {:.1}°C)，请检查风道是否受阻",
+        breach.rule.sensor_a,
+        breach.temperature_a,
+        breach.rule.sensor_b,
+        breach.temperature_b,
+        breach.gradient,
+        breach.rule.threshold
+    )
+}
+
+/// 监控任务的运行统计
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitoringStatistics {
+    /// 监控启动时间
+    pub started_at: DateTime<Utc>,
+    /// 已运行时长（秒）
+    pub uptime_seconds: u64,
+    /// 监控任务读到过的不同温度传感器和风扇数，不含黑名单中的传感器
+    pub monitored_components: usize,
+    /// 最后一次从BMC读到温度的时间
+    pub last_update: Option<DateTime<Utc>>,
+}
+
+/// 告警规则监控
+///
+/// 按规则评估温度、风扇读数：条件自首次满足起持续 `duration_seconds` 后产生告警，
+/// 中途解除则重新计时，短暂的尖峰不会告警。`alert.gradient` 中的温差规则按同样方式计时。
+/// 产生的告警写入 [`AlertStore`] 并归入事件
+#[derive(Clone)]
+pub struct AlertMonitor {
+    rules: AlertRuleStore,
+    alerts: AlertStore,
+    incidents: IncidentStore,
+    breach_starts: Arc<Mutex<BreachStarts>>,
+    gradient: Arc<ThermalGradientMonitor>,
+    last_update: Arc<Mutex<Option<DateTime<Utc>>>>,
+    started_at: DateTime<Utc>,
+    components: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl AlertMonitor {
+    /// 创建告警规则监控
+    ///
+    /// # 参数
+    /// * `rules` - 告警规则
+    /// * `alerts` - 告警存储
+    /// * `incidents` - 事件存储
+    pub fn new(rules: AlertRuleStore, alerts: AlertStore, incidents: IncidentStore) -> Self {
+        Self {
+            rules,
+            alerts,
+            incidents,
+            breach_starts: Arc::default(),
+            gradient: Arc::default(),
+            last_update: Arc::default(),
+            started_at: Utc::now(),
+            components: Arc::default(),
+        }
+    }
+
+    /// 设置温差告警规则
+    ///
+    /// # 参数
+    /// * `config` - 温差告警配置
+    pub fn with_gradient(mut self, config: ThermalGradientConfig) -> Self {
+        self.gradient = Arc::new(ThermalGradientMonitor::new(config));
+        self
+    }
+
+    /// 监控任务最后一次从BMC读到温度的时间，尚未读到时为空
+    ///
+    /// 只在轮询成功时更新，回退到缓存读数的轮询不算，监控任务停止或BMC持续不可读时不再变化
+    pub fn last_update(&self) -> Option<DateTime<Utc>> {
+        *self.last_update.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 记录一次在监控任务之外成功读到温度，如强制轮询
+    ///
+    /// # 参数
+    /// * `at` - 读取时间
+    pub fn record_update(&self, at: DateTime<Utc>) {
+        let mut last_update = self.last_update.lock().unwrap_or_else(|e| e.into_inner());
+        *last_update = Some(last_update.map_or(at, |last| last.max(at)));
+    }
+
+    /// 运行统计
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn statistics(&self, now: DateTime<Utc>) -> MonitoringStatistics {
+        MonitoringStatistics {
+            started_at: self.started_at,
+            uptime_seconds: now.signed_duration_since(self.started_at).num_seconds().max(0) as u64,
+            monitored_components: self.components.lock().unwrap_or_else(|e| e.into_inner()).len(),
+            last_update: self.last_update(),
+        }
+    }
+
+    /// 记录本周期读到的温度传感器和风扇
+    fn record_components(&self, temperatures: &[TemperatureSensor], fans: &[FanSensor]) {
+        let mut components = self.components.lock().unwrap_or_else(|e| e.into_inner());
+        components.extend(temperatures.iter().map(|sensor| sensor.sensor_id.clone()));
+        components.extend(fans.iter().map(|fan| fan.fan_id.clone()));
+    }
+
+    /// 用一组读数评估全部启用的规则
+    ///
+    /// 持续时间按 `at` 计算；禁用的规则清除其计时，重新启用后从头计算
+    ///
+    /// # 参数
+    /// * `temperatures` - 温度读数
+    /// * `fans` - 风扇读数
+    /// * `at` - 读数时间
+    pub fn evaluate(&self, temperatures: &[TemperatureSensor], fans: &[FanSensor], at: DateTime<Utc>) -> RuleEvaluation {
+        let rules = self.rules.all();
+        let mut breach_starts = self.breach_starts.lock().unwrap_or_else(|e| e.into_inner());
+        let mut evaluation = RuleEvaluation {
+            evaluated_at: at,
+            evaluated_rules: 0,
+            evaluated_readings: temperatures.len() + fans.len(),
+            matches: Vec::new(),
+            created_alerts: Vec::new(),
+        };
+
+        for (rule_id, rule) in &rules {
+            if !rule.enabled {
+                breach_starts.retain(|(id, _), _| id != rule_id);
+                continue;
+            }
+            evaluation.evaluated_rules += 1;
+            let duration_secs = u64::try_from(rule.condition.duration_seconds).unwrap_or(0);
+
+            for (source_id, value) in metric_readings(rule, temperatures, fans) {
+                let breached = rule.condition.is_met(value).unwrap_or_else(|e| {
+                    warn!("告警规则 {} 评估失败: {}", rule_id, e);
+                    false
+                });
+                let held = track_breach(
+                    &mut breach_starts,
+                    (rule_id.clone(), source_id.clone()),
+                    breached,
+                    duration_secs,
+                    at,
+                );
+                if !breached {
+                    continue;
+                }
+
+                let raised = held
+                    .then(|| {
+                        self.raise(NewAlert {
+                            alert_type: alert_type(rule).to_string(),
+                            severity: rule.severity.clone(),
+                            title: rule.name.clone(),
+                            message: alert_message(rule, &source_id, value),
+                            source: source_id.clone(),
+                            source_id: source_id.clone(),
+                            rule_id: Some(rule_id.clone()),
+                        })
+                    })
+                    .flatten();
+
+                evaluation.matches.push(RuleMatch {
+                    rule_id: rule_id.clone(),
+                    rule_name: rule.name.clone(),
+                    source_id,
+                    value,
+                    alert_id: raised.as_ref().map(|alert| alert.id),
+                });
+                evaluation.created_alerts.extend(raised);
+            }
+        }
+        drop(breach_starts);
+
+        let breaches: Vec<ThermalGradientBreach> = temperatures
+            .iter()
+            .flat_map(|sensor| self.gradient.observe(&sensor.sensor_id, sensor.temperature, at))
+            .collect();
+        for breach in breaches {
+            let source = breach.rule.source();
+            let raised = self.raise(NewAlert {
+                alert_type: "temperature".to_string(),
+                severity: breach.rule.severity.clone(),
+                title: breach.rule.name.clone(),
+                message: gradient_message(&breach),
+                source: source.clone(),
+                source_id: source.clone(),
+                rule_id: Some(breach.rule.id.clone()),
+            });
+            evaluation.matches.push(RuleMatch {
+                rule_id: breach.rule.id.clone(),
+                rule_name: breach.rule.name.clone(),
+                source_id: source,
+                value: breach.gradient,
+                alert_id: raised.as_ref().map(|alert| alert.id),
+            });
+            evaluation.created_alerts.extend(raised);
+        }
+        evaluation
+    }
+
+    /// 产生告警，新告警归入事件；返回创建或升级的告警
+    fn raise(&self, new: NewAlert) -> Option<Alert> {
+        let (transition, alert) = self.alerts.raise(new)?;
+        if transition == AlertTransition::Created {
+            self.incidents.correlate(&alert);
+        }
+        Some(alert)
+    }
+
+    /// 运行告警监控，每个周期读取一次传感器并评估规则，同时上报一次心跳
+    ///
+    /// 温度读取经过最后一次成功读数缓存，BMC短暂不可用时按缓存读数继续评估；创建或升级的告警写入告警历史
+    ///
+    /// # 参数
+    /// * `ipmi_service` - 传感器数据源
+    /// * `history` - 告警历史存储
+    /// * `sensor_blacklist` - 传感器黑名单，黑名单中的传感器不参与告警
+    /// * `sensor_cache` - 温度传感器最后一次成功读数
+    /// * `ticker` - 轮询节拍
+    /// * `heartbeat` - 看门狗心跳
+    pub async fn run(
+        self,
+        ipmi_service: Arc<dyn SensorSource>,
+        history: Arc<dyn AlertHistoryStore>,
+        sensor_blacklist: SensorBlacklist,
+        sensor_cache: LastGoodSensorCache,
+        mut ticker: PollTicker,
+        heartbeat: Heartbeat,
+    ) {
+        loop {
+            ticker.tick().await;
+            heartbeat.beat();
+            let now = Utc::now();
+            let temperatures: Vec<TemperatureSensor> = match sensor_cache
+                .read_through(now, || ipmi_service.get_temperature_sensors().map_err(|e| e.to_string()))
+            {
+                Ok(cached) => {
+                    if cached.iter().any(|cached| !cached.stale) {
+                        *self.last_update.lock().unwrap_or_else(|e| e.into_inner()) = Some(now);
+                    }
+                    cached.into_iter().map(|cached| cached.sensor).collect()
+                }
+                Err(e) => {
+                    warn!("告警监控读取温度传感器失败: {}", e);
+                    Vec::new()
+                }
+            };
+            let fans = ipmi_service.get_fan_sensors().unwrap_or_else(|e| {
+                warn!("告警监控读取风扇失败: {}", e);
+                Vec::new()
+            });
+
+            let temperatures: Vec<TemperatureSensor> = temperatures
+                .into_iter()
+                .filter(|sensor| !sensor_blacklist.contains(&sensor.sensor_id))
+                .collect();
+            self.record_components(&temperatures, &fans);
+            let evaluation = self.evaluate(&temperatures, &fans, now);
+            for alert in &evaluation.created_alerts {
+                warn!("告警: [{}] {} - {}", alert.severity, alert.title, alert.message);
+                if let Err(e) = history.upsert(alert).await {
+                    warn!("写入告警历史失败: {}: {}", alert.id, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::alert::AlertSeverity;
+    use crate::services::incident_store::IncidentCorrelationConfig;
+    use chrono::Duration;
+
+    fn cpu(temperature: f64, at: DateTime<Utc>) -> TemperatureSensor {
+        TemperatureSensor {
+            id: "cpu1".to_string(),
+            sensor_id: "CPU1_TEMP".to_string(),
+            temperature,
+            unit: "°C".to_string(),
+            raw_unit: "degrees C".to_string(),
+            location: "cpu".to_string(),
+            entity: String::new(),
+            status: "ok".to_string(),
+            timestamp: at,
+        }
+    }
+
+    fn fan(speed_percent: u8, at: DateTime<Utc>) -> FanSensor {
+        FanSensor {
+            id: "fan1".to_string(),
+            fan_id: "FAN1".to_string(),
+            speed_rpm: speed_percent as u32 * 150,
+            unit: "RPM".to_string(),
+            raw_unit: "RPM".to_string(),
+            speed_percent,
+            status: "ok".to_string(),
+            location: "Unknown Location".to_string(),
+            control_mode: "auto".to_string(),
+            target_temp: None,
+            controllable: true,
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_sustained_fan_utilization_raises_maintenance_alert() {
+        let alerts = AlertStore::new(100);
+        let monitor = AlertMonitor::new(
+            AlertRuleStore::with_defaults(80.0, 1000),
+            alerts.clone(),
+            IncidentStore::new(IncidentCorrelationConfig::default()),
+        );
+        let start = Utc::now();
+
+        // 风扇占空比95%，转速正常，不触发风扇故障；持续不足600秒时不告警
+        monitor.evaluate(&[], &[fan(95, start)], start);
+        let evaluation = monitor.evaluate(&[], &[fan(96, start)], start + Duration::seconds(599));
+        assert!(evaluation.created_alerts.is_empty());
+
+        let evaluation = monitor.evaluate(&[], &[fan(96, start)], start + Duration::seconds(600));
+        assert_eq!(evaluation.created_alerts.len(), 1);
+        let alert = &evaluation.created_alerts[0];
+        assert_eq!(alert.alert_type, "maintenance");
+        assert_eq!(alert.rule_id.as_deref(), Some("fan_high_utilization"));
+        assert!(alert.message.contains("FAN1 持续高负载"));
+    }
+
+    #[test]
+    fn test_breach_must_hold_for_rule_duration_before_alerting() {
+        let alerts = AlertStore::new(100);
+        let monitor = AlertMonitor::new(
+            AlertRuleStore::with_defaults(80.0, 1000),
+            alerts.clone(),
+            IncidentStore::new(IncidentCorrelationConfig::default()),
+        );
+        let start = Utc::now();
+
+        // 短暂尖峰：超过阈值后30秒内回落，不满足60秒的持续时间
+        let evaluation = monitor.evaluate(&[cpu(88.0, start)], &[], start);
+        assert_eq!(evaluation.matches.len(), 1);
+        assert!(evaluation.matches[0].alert_id.is_none());
+        monitor.evaluate(&[cpu(70.0, start)], &[], start + Duration::seconds(30));
+        let evaluation = monitor.evaluate(&[cpu(88.0, start)], &[], start + Duration::seconds(60));
+        assert!(evaluation.created_alerts.is_empty());
+        assert!(alerts.list().is_empty());
+
+        // 从重新超限起持续满60秒后告警，之后不重复创建
+        let evaluation = monitor.evaluate(&[cpu(89.0, start)], &[], start + Duration::seconds(120));
+        assert_eq!(evaluation.created_alerts.len(), 1);
+        assert_eq!(evaluation.matches[0].alert_id, Some(evaluation.created_alerts[0].id));
+        assert_eq!(evaluation.created_alerts[0].rule_id.as_deref(), Some("high_temperature"));
+        let evaluation = monitor.evaluate(&[cpu(90.0, start)], &[], start + Duration::seconds(150));
+        assert!(evaluation.created_alerts.is_empty());
+        assert_eq!(alerts.list().len(), 1);
+    }
+
+    #[test]
+    fn test_sustained_thermal_gradient_raises_alert_below_sensor_thresholds() {
+        let alerts = AlertStore::new(100);
+        let monitor = AlertMonitor::new(
+            AlertRuleStore::with_defaults(80.0, 1000),
+            alerts.clone(),
+            IncidentStore::new(IncidentCorrelationConfig::default()),
+        )
+        .with_gradient(ThermalGradientConfig {
+            rules: vec![crate::services::thermal_gradient::ThermalGradientRule {
+                id: "cpu_imbalance".to_string(),
+                name: "CPU温差".to_string(),
+                sensor_a: "CPU1_TEMP".to_string(),
+                sensor_b: "CPU2_TEMP".to_string(),
+                threshold: 15.0,
+                duration_secs: 60,
+                severity: AlertSeverity::Warning,
+            }],
+        });
+        let start = Utc::now();
+        let readings = |cpu1: f64, cpu2: f64| {
+            let mut second = cpu(cpu2, start);
+            second.sensor_id = "CPU2_TEMP".to_string();
+            vec![cpu(cpu1, start), second]
+        };
+
+        // 两颗CPU都低于80°C的高温阈值，温差20°C持续60秒后告警
+        assert!(monitor.evaluate(&readings(75.0, 55.0), &[], start).created_alerts.is_empty());
+        let evaluation = monitor.evaluate(&readings(75.0, 55.0), &[], start + Duration::seconds(60));
+        assert_eq!(evaluation.created_alerts.len(), 1);
+        let alert = &evaluation.created_alerts[0];
+        assert_eq!(alert.source_id, "CPU1_TEMP/CPU2_TEMP");
+        assert_eq!(alert.rule_id.as_deref(), Some("cpu_imbalance"));
+        assert!(alert.message.contains("温差 20.0°C"));
+        assert!(monitor
+            .evaluate(&readings(77.0, 56.0), &[], start + Duration::seconds(90))
+            .created_alerts
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_polling_counts_distinct_components_and_uptime() {
+        use crate::services::alert_history_store::InMemoryAlertHistoryStore;
+        use crate::services::poll_ticker::PollOverruns;
+        use crate::services::sensor_cache::LastGoodSensorCache;
+        use crate::test_harness::MockIpmiService;
+
+        // 3个温度传感器、2个风扇
+        let ipmi = MockIpmiService::healthy_server();
+        let alerts = AlertStore::new(100);
+        let monitor = AlertMonitor::new(
+            AlertRuleStore::with_defaults(80.0, 500),
+            alerts.clone(),
+            IncidentStore::new(IncidentCorrelationConfig::default()),
+        );
+        let task = tokio::spawn(monitor.clone().run(
+            Arc::new(ipmi),
+            Arc::new(InMemoryAlertHistoryStore::new()),
+            SensorBlacklist::default(),
+            LastGoodSensorCache::new(300),
+            PollTicker::new("alert_monitor", std::time::Duration::from_millis(10), PollOverruns::default()),
+            Heartbeat::default(),
+        ));
+        for _ in 0..100 {
+            if monitor.statistics(Utc::now()).monitored_components > 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        // 多个周期重复读到的组件只计一次
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        task.abort();
+
+        let statistics = monitor.statistics(Utc::now() + Duration::seconds(5));
+        assert_eq!(statistics.monitored_components, 5);
+        assert!(statistics.uptime_seconds >= 5);
+        assert!(statistics.last_update.is_some());
+    }
+}
@@ -0,0 +1,294 @@
+use crate::config::{AlertConfig, EmailConfig};
+use crate::models::alert::{ChannelType, NotificationChannel};
+use crate::models::error::{AppError, AppResult};
+use crate::models::Alert;
+use crate::services::report_mailer::{parse_mailbox, smtp_error};
+use crate::services::webhook;
+use chrono::Utc;
+use lettre::message::{header, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// 单个渠道的投递超时
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 渠道将要发送的消息
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedNotification {
+    /// 渠道ID
+    pub channel_id: String,
+    /// 渠道类型
+    pub channel_type: ChannelType,
+    /// 消息体的内容类型
+    pub content_type: &'static str,
+    /// 邮件主题，其他渠道为空
+    pub subject: Option<String>,
+    /// 消息体
+    pub body: String,
+}
+
+/// 按渠道类型渲染告警通知
+///
+/// Webhook投递告警JSON，Slack、钉钉为Markdown，邮件和短信为纯文本
+///
+/// # 参数
+/// * `channel_id` - 渠道ID
+/// * `channel` - 通知渠道
+/// * `alert` - 告警
+pub fn render(channel_id: &str, channel: &NotificationChannel, alert: &Alert) -> RenderedNotification {
+    let (content_type, subject, body) = match channel.channel_type {
+        ChannelType::Webhook => (
+            "application/json",
+            None,
+            serde_json::to_string(alert).unwrap_or_default(),
+        ),
+        ChannelType::Slack | ChannelType::DingTalk => (
+            "text/markdown",
+            None,
+            format!(
+                "*[{}] {}告警*\n{}\n来源: `{}`",
+                alert.severity, alert.alert_type, alert.message, alert.source
+            ),
+        ),
+        ChannelType::Email => (
+            "text/plain",
+            Some(format!("[{}] {}", alert.severity, alert.title)),
+            format!(
+                "{}\n\n来源: {}\n时间: {}",
+                alert.message,
+                alert.source,
+                alert.created_at.to_rfc3339()
+            ),
+        ),
+        ChannelType::Sms => (
+            "text/plain",
+            None,
+            format!("[{}] {}: {}", alert.severity, alert.title, alert.message),
+        ),
+    };
+
+    RenderedNotification {
+        channel_id: channel_id.to_string(),
+        channel_type: channel.channel_type.clone(),
+        content_type,
+        subject,
+        body,
+    }
+}
+
+/// 按配置发送告警通知
+///
+/// 渠道来自 `alert.webhook`（ID `webhook`）和 `alert.email`（ID `email`），配置项名称与渠道校验一致；
+/// Webhook配置了 `secret` 时投递请求附带HMAC签名
+#[derive(Clone)]
+pub struct AlertNotifier {
+    channels: Vec<(String, NotificationChannel)>,
+    email: EmailConfig,
+    client: reqwest::Client,
+}
+
+impl AlertNotifier {
+    /// 由告警配置创建通知服务
+    ///
+    /// # 参数
+    /// * `config` - 告警配置
+    pub fn from_config(config: &AlertConfig) -> Self {
+        let channels = vec![
+            (
+                "email".to_string(),
+                NotificationChannel {
+                    channel_type: ChannelType::Email,
+                    config: json!({
+                        "email": config.email.to,
+                        "smtp_host": config.email.smtp_host,
+                        "smtp_port": config.email.smtp_port,
+                    }),
+                    enabled: config.email.enabled,
+                },
+            ),
+            (
+                "webhook".to_string(),
+                NotificationChannel {
+                    channel_type: ChannelType::Webhook,
+                    config: json!({ "url": config.webhook.url, "secret": config.webhook.secret }),
+                    enabled: config.webhook.enabled,
+                },
+            ),
+        ];
+
+        Self {
+            channels,
+            email: config.email.clone(),
+            client: reqwest::Client::builder()
+                .timeout(DELIVERY_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// 全部渠道（含未启用的），按渠道ID排序
+    pub fn channels(&self) -> &[(String, NotificationChannel)] {
+        &self.channels
+    }
+
+    /// 获取渠道
+    ///
+    /// # 参数
+    /// * `channel_id` - 渠道ID
+    pub fn channel(&self, channel_id: &str) -> AppResult<&NotificationChannel> {
+        self.channels
+            .iter()
+            .find(|(id, _)| id == channel_id)
+            .map(|(_, channel)| channel)
+            .ok_or_else(|| AppError::not_found_error("通知渠道", channel_id))
+    }
+
+    /// 向一个渠道投递告警
+    ///
+    /// # 参数
+    /// * `channel_id` - 渠道ID
+    /// * `channel` - 通知渠道
+    /// * `alert` - 告警
+    pub async fn deliver(&self, channel_id: &str, channel: &NotificationChannel, alert: &Alert) -> AppResult<()> {
+        let rendered = render(channel_id, channel, alert);
+        let config_value = |key: &str| channel.config.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+
+        match channel.channel_type {
+            ChannelType::Webhook => {
+                let secret = config_value("secret");
+                let secret = (!secret.is_empty()).then_some(secret.as_str());
+                webhook::build_request(
+                    &self.client,
+                    &config_value("url"),
+                    secret,
+                    rendered.body.into_bytes(),
+                    Utc::now().timestamp(),
+                )
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| delivery_error(channel_id, e))?;
+            }
+            ChannelType::Email => {
+                let from = parse_mailbox("alert.email.from", &self.email.from)?;
+                let mut builder = Message::builder()
+                    .from(from)
+                    .subject(rendered.subject.unwrap_or_default());
+                for address in config_value("email").split(',').map(str::trim).filter(|a| !a.is_empty()) {
+                    builder = builder.to(parse_mailbox("alert.email.to", address)?);
+                }
+                let body = SinglePart::builder()
+                    .header(header::ContentType::TEXT_PLAIN)
+                    .header(header::ContentTransferEncoding::Base64)
+                    .body(rendered.body);
+                let message = builder
+                    .singlepart(body)
+                    .map_err(|e| AppError::internal_server_error(format!("构造告警邮件失败: {}", e)))?;
+
+                let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.email.smtp_host)
+                    .map_err(|e| smtp_error(format!("无法连接SMTP服务器 {}: {}", self.email.smtp_host, e)))?
+                    .port(self.email.smtp_port)
+                    .credentials(Credentials::new(self.email.username.clone(), self.email.password.clone()))
+                    .timeout(Some(DELIVERY_TIMEOUT))
+                    .build();
+                transport
+                    .send(message)
+                    .await
+                    .map_err(|e| smtp_error(format!("发送告警邮件失败: {}", e)))?;
+            }
+            _ => {
+                return Err(AppError::validation_error(
+                    "channel_type",
+                    format!("渠道 {} 的类型 {:?} 暂不支持发送", channel_id, channel.channel_type),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+fn delivery_error(channel_id: &str, error: impl std::fmt::Display) -> AppError {
+    AppError::ExternalServiceError {
+        service: format!("webhook:{}", channel_id),
+        message: format!("投递失败: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::models::AlertStatus;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_webhook_delivery_is_signed_with_channel_secret() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let receiver = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // 读到请求体完整为止：请求头之后的字节数达到Content-Length
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length: ").map(str::to_string))
+                        .and_then(|v| v.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break;
+                    }
+                }
+            }
+            socket.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n").await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let mut config = AppConfig::default().alert;
+        config.webhook.enabled = true;
+        config.webhook.url = url;
+        config.webhook.secret = "whsec_test".to_string();
+        let notifier = AlertNotifier::from_config(&config);
+        let now = Utc::now();
+        let alert = Alert {
+            id: uuid::Uuid::new_v4(),
+            alert_type: "temperature".to_string(),
+            severity: "Critical".to_string(),
+            title: "高温告警".to_string(),
+            message: "温度异常: 92.0°C (阈值: 80.0°C)".to_string(),
+            source: "CPU1_TEMP".to_string(),
+            source_id: "CPU1_TEMP".to_string(),
+            rule_id: Some("high_temperature".to_string()),
+            status: AlertStatus::Triggered,
+            acknowledged: false,
+            acknowledged_by: None,
+            acknowledged_at: None,
+            resolved_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let channel = notifier.channel("webhook").unwrap().clone();
+        notifier.deliver("webhook", &channel, &alert).await.unwrap();
+
+        let request = receiver.await.unwrap();
+        let (head, body) = request.split_once("\r\n\r\n").unwrap();
+        let header = |name: &str| {
+            head.lines()
+                .find_map(|line| line.split_once(": ").filter(|(key, _)| key.eq_ignore_ascii_case(name)))
+                .map(|(_, value)| value.to_string())
+                .unwrap()
+        };
+        let timestamp: i64 = header(webhook::TIMESTAMP_HEADER).parse().unwrap();
+        let signature = header(webhook::SIGNATURE_HEADER);
+        assert!(webhook::verify_payload("whsec_test", timestamp, body.as_bytes(), &signature, Utc::now().timestamp()));
+        assert!(!webhook::verify_payload("other", timestamp, body.as_bytes(), &signature, Utc::now().timestamp()));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::services::alert_thresholds::{AlertThresholdStore, SensorAlertThreshold, ThresholdLevel};
+use crate::services::ipmi_service::FanSensor;
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::sensor_cache::CachedTemperature;
+use serde::Serialize;
+
+/// 阈值来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThresholdSource {
+    /// 按传感器设置的阈值
+    Sensor,
+    /// 配置中的默认阈值
+    Default,
+}
+
+/// 单个温度传感器的告警预览
+#[derive(Debug, Clone, Serialize)]
+pub struct SensorAlertPreview {
+    pub sensor_id: String,
+    pub temperature: f64,
+    pub threshold: SensorAlertThreshold,
+    pub threshold_source: ThresholdSource,
+    pub level: ThresholdLevel,
+    /// 读数来自最后一次成功读取
+    pub stale: bool,
+}
+
+/// 单个风扇的告警预览
+#[derive(Debug, Clone, Serialize)]
+pub struct FanAlertPreview {
+    pub fan_id: String,
+    pub speed_rpm: u32,
+    /// 低于该转速为警告，停转为严重
+    pub warning_below_rpm: u32,
+    pub level: ThresholdLevel,
+}
+
+/// 各级别的数量
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AlertPreviewSummary {
+    pub within: usize,
+    pub warning: usize,
+    pub critical: usize,
+}
+
+impl AlertPreviewSummary {
+    fn count(&mut self, level: ThresholdLevel) {
+        match level {
+            ThresholdLevel::Within => self.within += 1,
+            ThresholdLevel::Warning => self.warning += 1,
+            ThresholdLevel::Critical => self.critical += 1,
+        }
+    }
+}
+
+/// 按当前读数预览告警，不创建告警
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertPreview {
+    pub sensors: Vec<SensorAlertPreview>,
+    pub fans: Vec<FanAlertPreview>,
+    pub summary: AlertPreviewSummary,
+}
+
+/// 用当前阈值对读数逐个分级
+///
+/// 黑名单中的传感器不参与告警，因此不出现在预览中；未单独设置阈值的传感器使用默认阈值
+///
+/// # 参数
+/// * `readings` - 温度读数，可能包含最后一次成功读数
+/// * `fans` - 风扇读数
+/// * `thresholds` - 按传感器设置的告警阈值
+/// * `default_threshold` - 默认温度阈值
+/// * `fan_warning_below_rpm` - 风扇转速警告阈值（RPM）
+/// * `sensor_blacklist` - 传感器黑名单
+pub fn preview_alerts(
+    readings: &[CachedTemperature],
+    fans: &[FanSensor],
+    thresholds: &AlertThresholdStore,
+    default_threshold: SensorAlertThreshold,
+    fan_warning_below_rpm: u32,
+    sensor_blacklist: &SensorBlacklist,
+) -> AlertPreview {
+    let mut summary = AlertPreviewSummary::default();
+
+    let sensors = readings
+        .iter()
+        .filter(|reading| !sensor_blacklist.contains(&reading.sensor.sensor_id))
+        .map(|reading| {
+            let (threshold, threshold_source) = match thresholds.get(&reading.sensor.sensor_id) {
+                Some(threshold) => (threshold, ThresholdSource::Sensor),
+                None => (default_threshold, ThresholdSource::Default),
+            };
+            let level = threshold.classify(reading.sensor.temperature);
+            summary.count(level);
+            SensorAlertPreview {
+                sensor_id: reading.sensor.sensor_id.clone(),
+                temperature: reading.sensor.temperature,
+                threshold,
+                threshold_source,
+                level,
+                stale: reading.stale,
+            }
+        })
+        .collect();
+
+    let fans = fans
+        .iter()
+        .map(|fan| {
+            let level = if fan.speed_rpm == 0 {
+                ThresholdLevel::Critical
+            } else if fan.speed_rpm < fan_warning_below_rpm {
+                ThresholdLevel::Warning
+            } else {
+                ThresholdLevel::Within
+            };
+            summary.count(level);
+            FanAlertPreview {
+                fan_id: fan.fan_id.clone(),
+                speed_rpm: fan.speed_rpm,
+                warning_below_rpm: fan_warning_below_rpm,
+                level,
+            }
+        })
+        .collect();
+
+    AlertPreview { sensors, fans, summary }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::alert_thresholds::SensorSelector;
+    use crate::services::ipmi_service::{parse_fan_sdr, parse_temperature_sdr};
+    use crate::services::sensor_cache::LastGoodSensorCache;
+    use chrono::Utc;
+
+    #[test]
+    fn test_preview_classifies_each_sensor_and_fan() {
+        let now = Utc::now();
+        let sensors = parse_temperature_sdr(
+            "\
+CPU1 Temp        | 0Eh | ok  |  3.1 | 88 degrees C
+CPU2 Temp        | 0Fh | ok  |  3.2 | 81 degrees C
+Inlet Temp       | 04h | ok  |  7.1 | 23 degrees C
+Exhaust Temp     | 01h | ok  |  7.1 | 46 degrees C
+PSU1 Temperature | 60h | ok  | 10.1 | 99 degrees C
+",
+            now,
+        );
+        let cache = LastGoodSensorCache::new(300);
+        cache.read_through(now, || Ok::<_, String>(sensors.clone())).unwrap();
+        // BMC本次无响应，预览使用最后一次成功读数
+        let readings = cache
+            .read_through(now + chrono::Duration::seconds(10), || Err("timeout".to_string()))
+            .unwrap();
+        let fans = parse_fan_sdr(
+            "\
+FAN1             | 30h | ok  | 29.1 | 3600 RPM
+FAN2             | 31h | ok  | 29.2 | 600 RPM
+FAN3             | 32h | cr  | 29.3 | 0 RPM
+",
+            now,
+        );
+
+        let thresholds = AlertThresholdStore::default();
+        let exhaust_threshold = SensorAlertThreshold {
+            warning: 45.0,
+            critical: 55.0,
+        };
+        thresholds
+            .bulk_apply(&sensors, &SensorSelector::Location("exhaust".to_string()), exhaust_threshold)
+            .unwrap();
+        let default_threshold = SensorAlertThreshold {
+            warning: 80.0,
+            critical: 85.0,
+        };
+        let blacklist = SensorBlacklist::new(["PSU1_TEMPERATURE".to_string()]);
+
+        let preview = preview_alerts(&readings, &fans, &thresholds, default_threshold, 1000, &blacklist);
+
+        let levels: Vec<_> = preview
+            .sensors
+            .iter()
+            .map(|s| (s.sensor_id.as_str(), s.level, s.threshold_source))
+            .collect();
+        assert_eq!(
+            levels,
+            vec![
+                ("CPU1_TEMP", ThresholdLevel::Critical, ThresholdSource::Default),
+                ("CPU2_TEMP", ThresholdLevel::Warning, ThresholdSource::Default),
+                ("EXHAUST_TEMP", ThresholdLevel::Warning, ThresholdSource::Sensor),
+                ("INLET_TEMP", ThresholdLevel::Within, ThresholdSource::Default),
+            ]
+        );
+        assert!(preview.sensors.iter().all(|s| s.stale));
+
+        let fan_levels: Vec<_> = preview.fans.iter().map(|f| (f.fan_id.as_str(), f.level)).collect();
+        assert_eq!(
+            fan_levels,
+            vec![
+                ("FAN1", ThresholdLevel::Within),
+                ("FAN2", ThresholdLevel::Warning),
+                ("FAN3", ThresholdLevel::Critical),
+            ]
+        );
+        assert_eq!(
+            preview.summary,
+            AlertPreviewSummary {
+                within: 2,
+                warning: 3,
+                critical: 2,
+            }
+        );
+    }
+}
@@ -0,0 +1,436 @@
+use crate::models::alert::{AlertCondition, AlertRule, AlertRulePatch, AlertSeverity, ThresholdUnit};
+use crate::models::error::{AppError, AppResult};
+use crate::services::breach_debounce::{track_breach, BreachStarts};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+/// 试运行结果中最多返回的触发样本数
+const MAX_DRY_RUN_SAMPLES: usize = 100;
+
+/// 按规则ID保存的告警规则
+///
+/// 启动时按监控配置生成默认规则，运行时的修改只保存在内存中，重启后恢复为默认规则
+#[derive(Debug, Clone, Default)]
+pub struct AlertRuleStore {
+    rules: Arc<RwLock<BTreeMap<String, AlertRule>>>,
+}
+
+impl AlertRuleStore {
+    /// 创建包含默认规则的规则存储
+    ///
+    /// # 参数
+    /// * `temperature_threshold` - 高温告警阈值（°C）
+    /// * `fan_threshold_rpm` - 风扇故障告警阈值（RPM）
+    pub fn with_defaults(temperature_threshold: f64, fan_threshold_rpm: u32) -> Self {
+        let mut rules = BTreeMap::new();
+        rules.insert(
+            "high_temperature".to_string(),
+            AlertRule {
+                name: "高温告警".to_string(),
+                description: format!("温度持续超过 {}°C", temperature_threshold),
+                metric: "temperature".to_string(),
+                condition: AlertCondition {
+                    operator: ">".to_string(),
+                    threshold: temperature_threshold,
+                    duration_seconds: 60,
+                },
+                threshold_unit: None,
+                severity: AlertSeverity::Critical,
+                enabled: true,
+            },
+        );
+        rules.insert(
+            "fan_failure".to_string(),
+            AlertRule {
+                name: "风扇故障".to_string(),
+                description: format!("风扇转速持续低于 {} RPM", fan_threshold_rpm),
+                metric: "fan_speed".to_string(),
+                condition: AlertCondition {
+                    operator: "<".to_string(),
+                    threshold: fan_threshold_rpm as f64,
+                    duration_seconds: 30,
+                },
+                threshold_unit: Some(ThresholdUnit::Rpm),
+                severity: AlertSeverity::Warning,
+                enabled: true,
+            },
+        );
+        rules.insert(
+            "fan_high_utilization".to_string(),
+            AlertRule {
+                name: "风扇持续高负载".to_string(),
+                description: "风扇占空比持续高于90%，散热能力可能不足或风道受阻".to_string(),
+                metric: "fan_utilization".to_string(),
+                condition: AlertCondition {
+                    operator: ">".to_string(),
+                    threshold: 90.0,
+                    duration_seconds: 600,
+                },
+                threshold_unit: None,
+                severity: AlertSeverity::Warning,
+                enabled: true,
+            },
+        );
+        Self {
+            rules: Arc::new(RwLock::new(rules)),
+        }
+    }
+
+    /// 获取指定告警规则
+    ///
+    /// # 参数
+    /// * `rule_id` - 规则ID
+    pub fn get(&self, rule_id: &str) -> AppResult<AlertRule> {
+        self.rules
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(rule_id)
+            .cloned()
+            .ok_or_else(|| AppError::not_found_error("告警规则", rule_id))
+    }
+
+    /// 所有告警规则（按规则ID排序）
+    pub fn all(&self) -> BTreeMap<String, AlertRule> {
+        self.rules.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 替换全部告警规则
+    ///
+    /// 调用方负责先校验规则
+    ///
+    /// # 参数
+    /// * `rules` - 按规则ID排列的告警规则
+    pub fn replace(&self, rules: BTreeMap<String, AlertRule>) {
+        *self.rules.write().unwrap_or_else(|e| e.into_inner()) = rules;
+    }
+
+    /// 按名称创建或更新告警规则，返回规则ID、生效后的规则和是否新建
+    ///
+    /// 规则名称唯一：已有同名规则时原地更新并保留其ID，否则以 `rule_id`（为空时自动生成）新建
+    ///
+    /// # 参数
+    /// * `rule_id` - 新建时使用的规则ID
+    /// * `rule` - 告警规则
+    pub fn apply(&self, rule_id: Option<&str>, rule: AlertRule) -> AppResult<(String, AlertRule, bool)> {
+        rule.validate()?;
+        let rule_id = rule_id.map(str::trim).filter(|id| !id.is_empty());
+
+        let mut rules = self.rules.write().unwrap_or_else(|e| e.into_inner());
+        let existing = rules
+            .iter()
+            .find(|(_, current)| current.name == rule.name)
+            .map(|(id, _)| id.clone());
+        let (id, created) = match (existing, rule_id) {
+            (Some(existing), Some(requested)) if existing != requested => {
+                return Err(AppError::validation_error(
+                    "name",
+                    format!("规则名称 {} 已被规则 {} 使用", rule.name, existing),
+                ));
+            }
+            (Some(existing), _) => (existing, false),
+            (None, Some(requested)) if rules.contains_key(requested) => {
+                return Err(AppError::validation_error(
+                    "id",
+                    format!("规则 {} 已存在且名称不同，请使用PATCH修改名称", requested),
+                ));
+            }
+            (None, requested) => (
+                requested.map(str::to_string).unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                true,
+            ),
+        };
+        rules.insert(id.clone(), rule.clone());
+        Ok((id, rule, created))
+    }
+
+    /// 翻转规则的启用状态，返回更新后的规则
+    ///
+    /// # 参数
+    /// * `rule_id` - 规则ID
+    pub fn toggle(&self, rule_id: &str) -> AppResult<AlertRule> {
+        let mut rules = self.rules.write().unwrap_or_else(|e| e.into_inner());
+        let rule = rules
+            .get_mut(rule_id)
+            .ok_or_else(|| AppError::not_found_error("告警规则", rule_id))?;
+        rule.enabled = !rule.enabled;
+        Ok(rule.clone())
+    }
+
+    /// 在当前规则之上恢复已保存的启用状态，不存在的规则忽略
+    ///
+    /// # 参数
+    /// * `states` - 按规则ID保存的启用状态
+    pub fn restore_enabled(&self, states: &HashMap<String, bool>) {
+        let mut rules = self.rules.write().unwrap_or_else(|e| e.into_inner());
+        for (rule_id, enabled) in states {
+            if let Some(rule) = rules.get_mut(rule_id) {
+                rule.enabled = *enabled;
+            }
+        }
+    }
+
+    /// 部分更新告警规则，返回更新后的规则
+    ///
+    /// 在同一次写锁内合并并校验，校验失败时规则保持不变
+    ///
+    /// # 参数
+    /// * `rule_id` - 规则ID
+    /// * `patch` - 部分更新
+    pub fn patch(&self, rule_id: &str, patch: &AlertRulePatch) -> AppResult<AlertRule> {
+        if patch.is_empty() {
+            return Err(AppError::validation_error("patch", "至少需要修改一个字段"));
+        }
+
+        let mut rules = self.rules.write().unwrap_or_else(|e| e.into_inner());
+        let current = rules
+            .get(rule_id)
+            .ok_or_else(|| AppError::not_found_error("告警规则", rule_id))?;
+        let rule = patch.merged(current)?;
+        if let Some((other, _)) = rules.iter().find(|(id, other)| *id != rule_id && other.name == rule.name) {
+            return Err(AppError::validation_error(
+                "name",
+                format!("规则名称 {} 已被规则 {} 使用", rule.name, other),
+            ));
+        }
+        rules.insert(rule_id.to_string(), rule.clone());
+        Ok(rule)
+    }
+}
+
+/// 规则触发样本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTriggerSample {
+    /// 触发时间
+    pub timestamp: DateTime<Utc>,
+    /// 触发源（传感器ID）
+    pub source_id: String,
+    /// 触发时的读数值
+    pub value: f64,
+}
+
+/// 规则试运行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDryRunResult {
+    /// 回放开始时间
+    pub start_time: DateTime<Utc>,
+    /// 回放结束时间
+    pub end_time: DateTime<Utc>,
+    /// 评估的读数数量
+    pub evaluated_samples: usize,
+    /// 预计触发次数
+    pub trigger_count: usize,
+    /// 首次触发时间
+    pub first_triggered_at: Option<DateTime<Utc>>,
+    /// 最后触发时间（包括未返回样本的触发）
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    /// 触发样本（最多100条）
+    pub samples: Vec<RuleTriggerSample>,
+}
+
+/// 试运行告警规则
+///
+/// 将历史读数按时间逐条代入规则条件，条件持续满足 `duration_seconds` 后计为一次触发，
+/// 条件解除后再次满足才会重新计数；不会创建告警或发送通知。
+/// 只有温度有历史读数，其他指标的规则返回校验错误
+///
+/// # 参数
+/// * `rule` - 告警规则
+/// * `readings` - 各传感器按时间排列的读数
+/// * `start_time` - 回放开始时间
+/// * `end_time` - 回放结束时间
+pub fn dry_run(
+    rule: &AlertRule,
+    readings: &BTreeMap<String, Vec<(DateTime<Utc>, f64)>>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> AppResult<RuleDryRunResult> {
+    if start_time >= end_time {
+        return Err(AppError::validation_error("start_time", "开始时间必须早于结束时间"));
+    }
+    if rule.metric != "temperature" {
+        return Err(AppError::validation_error(
+            "metric",
+            format!("指标 {} 没有历史读数，仅支持试运行温度规则", rule.metric),
+        ));
+    }
+    let duration_secs = u64::try_from(rule.condition.duration_seconds)
+        .map_err(|_| AppError::validation_error("condition.duration_seconds", "持续时间不能为负数"))?;
+
+    let mut breach_starts = BreachStarts::new();
+    let mut evaluated_samples = 0;
+    let mut triggers = Vec::new();
+    for (sensor_id, values) in readings {
+        let mut firing = false;
+        for &(timestamp, value) in values.iter().filter(|(at, _)| *at >= start_time && *at <= end_time) {
+            evaluated_samples += 1;
+            let breached = rule.condition.is_met(value)?;
+            let sustained = track_breach(
+                &mut breach_starts,
+                (String::new(), sensor_id.clone()),
+                breached,
+                duration_secs,
+                timestamp,
+            );
+            if sustained && !firing {
+                triggers.push(RuleTriggerSample {
+                    timestamp,
+                    source_id: sensor_id.clone(),
+                    value,
+                });
+            }
+            firing = sustained;
+        }
+    }
+
+    triggers.sort_by_key(|trigger| trigger.timestamp);
+    let trigger_count = triggers.len();
+    let first_triggered_at = triggers.first().map(|t| t.timestamp);
+    let last_triggered_at = triggers.last().map(|t| t.timestamp);
+    triggers.truncate(MAX_DRY_RUN_SAMPLES);
+
+    Ok(RuleDryRunResult {
+        start_time,
+        end_time,
+        evaluated_samples,
+        trigger_count,
+        first_triggered_at,
+        last_triggered_at,
+        samples: triggers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_dry_run_counts_sustained_breaches_once_each() {
+        let store = AlertRuleStore::with_defaults(80.0, 1000);
+        let rule = store.get("high_temperature").unwrap();
+        let start = Utc::now() - Duration::hours(1);
+
+        // 每30秒一个读数：85°C持续2分钟、回落、再持续1分钟，中间一次30秒的尖峰不满足持续时间
+        let cpu: Vec<(DateTime<Utc>, f64)> = [70.0, 85.0, 86.0, 87.0, 85.0, 70.0, 90.0, 70.0, 82.0, 83.0, 84.0, 70.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| (start + Duration::seconds(i as i64 * 30), value))
+            .collect();
+        let inlet = vec![(start, 25.0), (start + Duration::seconds(30), 26.0)];
+        let readings = BTreeMap::from([("CPU1_TEMP".to_string(), cpu), ("INLET_TEMP".to_string(), inlet)]);
+
+        let result = dry_run(&rule, &readings, start, start + Duration::hours(1)).unwrap();
+        assert_eq!(result.evaluated_samples, 14);
+        assert_eq!(result.trigger_count, 2);
+        assert_eq!(result.first_triggered_at, Some(start + Duration::seconds(90)));
+        assert_eq!(result.last_triggered_at, Some(start + Duration::seconds(300)));
+        assert_eq!(result.samples[0].source_id, "CPU1_TEMP");
+        assert_eq!(result.samples[1].value, 84.0);
+
+        // 时间范围外的读数不参与回放
+        let result = dry_run(&rule, &readings, start + Duration::seconds(200), start + Duration::hours(1)).unwrap();
+        assert_eq!(result.trigger_count, 1);
+
+        let fan_rule = store.get("fan_failure").unwrap();
+        assert!(dry_run(&fan_rule, &readings, start, start + Duration::hours(1)).is_err());
+        assert!(dry_run(&rule, &readings, start, start).is_err());
+        assert!(store.get("missing").is_err());
+    }
+
+    #[test]
+    fn test_dry_run_reports_last_trigger_beyond_sample_limit() {
+        let store = AlertRuleStore::with_defaults(80.0, 1000);
+        let mut rule = store.get("high_temperature").unwrap();
+        rule.condition.duration_seconds = 0;
+        let start = Utc::now() - Duration::hours(2);
+
+        // 超限与正常交替，每次超限各计一次触发，共150次
+        let cpu: Vec<(DateTime<Utc>, f64)> = (0..300)
+            .map(|i| (start + Duration::seconds(i * 10), if i % 2 == 0 { 85.0 } else { 70.0 }))
+            .collect();
+        let last_breach = cpu[298].0;
+        let readings = BTreeMap::from([("CPU1_TEMP".to_string(), cpu)]);
+
+        let result = dry_run(&rule, &readings, start, start + Duration::hours(2)).unwrap();
+        assert_eq!(result.trigger_count, 150);
+        assert_eq!(result.samples.len(), MAX_DRY_RUN_SAMPLES);
+        assert_eq!(result.first_triggered_at, Some(start));
+        assert_eq!(result.last_triggered_at, Some(last_breach));
+    }
+
+    #[test]
+    fn test_apply_by_name_is_idempotent_and_keeps_names_unique() {
+        let store = AlertRuleStore::with_defaults(80.0, 1000);
+        let mut rule = store.get("high_temperature").unwrap();
+        rule.condition.threshold = 85.0;
+
+        // 同名规则原地更新，保留原ID
+        let (id, applied, created) = store.apply(None, rule.clone()).unwrap();
+        assert_eq!(id, "high_temperature");
+        assert!(!created);
+        assert_eq!(applied.condition.threshold, 85.0);
+        let count = store.all().len();
+        store.apply(None, rule.clone()).unwrap();
+        assert_eq!(store.all().len(), count);
+
+        // 新名称按请求的ID新建，重复下发不再新建
+        rule.name = "进风口高温".to_string();
+        let (id, _, created) = store.apply(Some("inlet_high"), rule.clone()).unwrap();
+        assert_eq!(id, "inlet_high");
+        assert!(created);
+        assert!(!store.apply(Some("inlet_high"), rule.clone()).unwrap().2);
+
+        // 同名规则不能以其他ID新建，也不能通过部分更新改成已有名称
+        assert!(store.apply(Some("other"), rule.clone()).is_err());
+        let rename = AlertRulePatch {
+            name: Some("进风口高温".to_string()),
+            ..Default::default()
+        };
+        assert!(store.patch("high_temperature", &rename).is_err());
+        rule.condition.duration_seconds = -1;
+        rule.name = "无效规则".to_string();
+        assert!(store.apply(None, rule).is_err());
+    }
+
+    #[test]
+    fn test_patch_threshold_keeps_other_fields_and_rejects_invalid_merge() {
+        let store = AlertRuleStore::with_defaults(80.0, 1000);
+        let before = store.get("high_temperature").unwrap();
+
+        let patch = AlertRulePatch {
+            threshold: Some(85.0),
+            ..Default::default()
+        };
+        let patched = store.patch("high_temperature", &patch).unwrap();
+        assert_eq!(patched.condition.threshold, 85.0);
+        assert_eq!(patched.condition.operator, before.condition.operator);
+        assert_eq!(patched.condition.duration_seconds, before.condition.duration_seconds);
+        assert_eq!(patched.name, before.name);
+        assert_eq!(patched.metric, before.metric);
+        assert_eq!(patched.severity, before.severity);
+        assert_eq!(patched.enabled, before.enabled);
+        assert_eq!(store.get("high_temperature").unwrap().condition.threshold, 85.0);
+
+        // 合并后的规则无效时整体拒绝，规则不变
+        let invalid = AlertRulePatch {
+            threshold: Some(90.0),
+            duration_seconds: Some(-1),
+            ..Default::default()
+        };
+        assert!(store.patch("high_temperature", &invalid).is_err());
+        let invalid = AlertRulePatch {
+            threshold_unit: Some(ThresholdUnit::Percent),
+            ..Default::default()
+        };
+        assert!(store.patch("high_temperature", &invalid).is_err());
+        let unchanged = store.get("high_temperature").unwrap();
+        assert_eq!(unchanged.condition.threshold, 85.0);
+        assert_eq!(unchanged.condition.duration_seconds, 60);
+        assert!(unchanged.threshold_unit.is_none());
+
+        assert!(store.patch("high_temperature", &AlertRulePatch::default()).is_err());
+        assert!(store.patch("missing", &patch).is_err());
+    }
+}
@@ -1,20 +1,129 @@
 use crate::models::{
-    alert::*, error::{AppError, AppResult}, fan::FanReading, sensor::SensorReading, thermal::TemperatureReading, Alert
+    alert::*, api::PaginatedResponse, error::{AppError, AppResult}, fan::FanReading,
+    sensor::SensorReading, thermal::TemperatureReading, Alert, SystemEvent,
+};
+use crate::services::alert_history_store::{AlertHistoryStore, InMemoryAlertHistoryStore};
+use crate::services::alert_stats_store::{AlertStatisticsStore, InMemoryAlertStatisticsStore, PersistedAlertStatistics};
+use crate::services::breach_debounce::{track_breach, BreachStarts};
+use crate::services::control_write_retry::{ControlWriteAlertSink, FailedControlWrite};
+use crate::services::fan_degradation::{FanDegradation, FanDegradationAlertSink};
+use crate::services::event_broadcast::{EventBroadcast, EventSubscriber};
+use crate::services::event_store::{InMemorySystemEventStore, SystemEventQuery, SystemEventStore};
+use crate::services::ipmi_service::BmcSelfTestStatus;
+use crate::services::power_cap::{PowerCapAlertSink, PowerCapDecision};
+use crate::services::template_store::{AlertTemplateSort, AlertTemplateStore, InMemoryAlertTemplateStore};
+use crate::services::rule_state_store::{AlertRuleStateStore, InMemoryAlertRuleStateStore};
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::task_watchdog::{Heartbeat, TaskWatchdog};
+use crate::services::thermal_gradient::{ThermalGradientBreach, ThermalGradientConfig, ThermalGradientMonitor};
+use crate::services::webhook;
+use crate::controllers::alert_controller::{
+    AlertType, AlertFilter, NotificationPreview, PreviewNotificationRequest,
 };
-use crate::controllers::alert_controller::{AlertType, AlertFilter};
 use crate::utils::{
+    math::MathUtils,
     time::TimeUtils,
     logger::LoggerManager,
     validation::ValidationUtils,
 };
-use std::collections::HashMap;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{RwLock, Mutex};
+use tokio::sync::{oneshot, RwLock, Mutex};
+use tokio::task::JoinSet;
 use tokio::time::{interval, Duration};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error, debug};
 
+/// 统计窗口最大长度（小时）
+const MAX_STATISTICS_WINDOW_HOURS: u32 = 24 * 30;
+
+/// BMC自检告警的告警源
+const BMC_ALERT_SOURCE: &str = "bmc";
+
+/// 告警推送每个订阅者最多积压的事件数，落后更多时丢弃最旧的事件
+const ALERT_STREAM_CAPACITY: usize = 256;
+
+/// 预测告警使用的每个传感器最近温度采样数
+const PREDICTIVE_WINDOW_SAMPLES: usize = 10;
+
+/// 预测告警所需的最少采样数，采样过少时趋势不可靠
+const PREDICTIVE_MIN_SAMPLES: usize = 5;
+
+/// Webhook投递超时
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 累计统计的定期保存间隔
+const STATISTICS_PERSIST_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 内存中保留的通知投递结果条数
+const NOTIFICATION_DELIVERY_LIMIT: usize = 1000;
+
+/// 自检临时规则、告警和合成传感器的ID前缀
+const SELFTEST_ID_PREFIX: &str = "selftest";
+
+/// 自检临时规则的阈值（°C），远高于任何真实读数，临时规则不会被真实读数触发
+const SELFTEST_THRESHOLD: f64 = 1000.0;
+
+/// 单个渠道的通知投递结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationDelivery {
+    /// 告警ID
+    pub alert_id: String,
+    /// 渠道ID
+    pub channel_id: String,
+    /// 是否投递成功
+    pub success: bool,
+    /// 投递失败的原因
+    pub error: Option<String>,
+    /// 投递耗时（毫秒）
+    pub duration_ms: u64,
+    /// 投递完成时间
+    pub delivered_at: DateTime<Utc>,
+}
+
+/// 告警规则、通知渠道与通知路由，用于整体导出和恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertSettings {
+    /// 告警规则
+    pub rules: Vec<AlertRule>,
+    /// 通知渠道
+    pub channels: Vec<NotificationChannel>,
+    /// 通知路由矩阵
+    pub routing: Option<NotificationRoutingMatrix>,
+}
+
+impl AlertSettings {
+    /// 验证规则、渠道和路由，规则ID、规则名称和渠道ID均不能重复
+    pub fn validate(&self) -> AppResult<()> {
+        let mut rule_ids = HashSet::new();
+        let mut rule_names = HashSet::new();
+        for rule in &self.rules {
+            rule.validate()?;
+            if !rule_ids.insert(rule.id.as_str()) {
+                return Err(AppError::validation_error("rules", format!("告警规则ID重复: {}", rule.id)));
+            }
+            if !rule_names.insert(rule.name.trim()) {
+                return Err(AppError::validation_error("rules", format!("告警规则名称重复: {}", rule.name)));
+            }
+        }
+
+        let mut channel_ids = HashSet::new();
+        for channel in &self.channels {
+            channel.validate()?;
+            if !channel_ids.insert(channel.id.as_str()) {
+                return Err(AppError::validation_error("channels", format!("通知渠道ID重复: {}", channel.id)));
+            }
+        }
+
+        if let Some(routing) = &self.routing {
+            routing.validate()?;
+        }
+        Ok(())
+    }
+}
+
 /// 告警服务
 /// 
 /// 负责系统告警、通知和事件管理
@@ -24,16 +133,62 @@ pub struct AlertService {
     config: Arc<RwLock<AlertConfig>>,
     /// 活跃告警
     active_alerts: Arc<RwLock<HashMap<String, Alert>>>,
-    /// 告警历史
+    /// 最近的告警历史，最多保留 `history_limit` 条
     alert_history: Arc<RwLock<Vec<Alert>>>,
+    /// 内存告警历史条数上限
+    history_limit: usize,
+    /// 告警历史存储，保存全部告警，不受 `history_limit` 限制
+    history_store: Arc<dyn AlertHistoryStore>,
     /// 告警规则
     alert_rules: Arc<RwLock<HashMap<String, AlertRule>>>,
+    /// 规则触发的告警在触发时的规则快照，键为告警ID
+    rule_snapshots: Arc<RwLock<HashMap<String, AlertRule>>>,
+    /// 各（规则, 告警源）条件开始持续满足的时间
+    breach_starts: Arc<RwLock<BreachStarts>>,
+    /// 各传感器最近的温度采样，用于趋势预测
+    temperature_windows: Arc<RwLock<HashMap<String, VecDeque<(DateTime<Utc>, f64)>>>>,
+    /// 已发出预测告警的（规则, 传感器），预测不再成立时清除
+    predicted_breaches: Arc<RwLock<HashSet<(String, String)>>>,
+    /// 预测告警提前量（秒），0表示不启用
+    predictive_lead_time_secs: u64,
+    /// 传感器间温差检测
+    gradient_monitor: Arc<ThermalGradientMonitor>,
+    /// 各告警源最近一次读数，供手动评估使用
+    latest_readings: Arc<RwLock<LatestReadings>>,
     /// 通知渠道
     notification_channels: Arc<RwLock<HashMap<String, NotificationChannel>>>,
+    /// 通知路由矩阵，未配置时按各渠道的严重程度过滤
+    notification_routing: Arc<RwLock<Option<NotificationRoutingMatrix>>>,
     /// 告警统计
     alert_stats: Arc<RwLock<AlertStatistics>>,
+    /// 最近一次统计重置记录
+    last_stats_reset: Arc<RwLock<Option<AlertStatisticsReset>>>,
+    /// 累计统计存储，启动时恢复，运行中定期保存，停止时再保存一次
+    statistics_store: Arc<dyn AlertStatisticsStore>,
+    /// 系统事件存储（记录告警生命周期）
+    event_store: Arc<dyn SystemEventStore>,
+    /// 告警模板存储
+    template_store: Arc<dyn AlertTemplateStore>,
+    /// 告警规则启用状态存储
+    rule_state_store: Arc<dyn AlertRuleStateStore>,
+    /// 传感器黑名单，其中的传感器不产生告警
+    sensor_blacklist: SensorBlacklist,
+    /// 告警静默，键为静默ID
+    alert_mutes: Arc<RwLock<HashMap<String, AlertMute>>>,
+    /// 告警状态变化推送通道
+    alert_events: EventBroadcast<AlertStreamEvent>,
+    /// Webhook投递使用的HTTP客户端
+    http_client: reqwest::Client,
+    /// 同一告警同时投递的渠道数上限
+    notification_concurrency: usize,
+    /// 上一条告警的通知投递完成信号，用于按告警创建顺序投递
+    last_notification: Arc<std::sync::Mutex<Option<oneshot::Receiver<()>>>>,
+    /// 最近的通知投递结果，最多保留 `NOTIFICATION_DELIVERY_LIMIT` 条
+    notification_deliveries: Arc<RwLock<VecDeque<NotificationDelivery>>>,
     /// 任务句柄
     task_handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,
+    /// 后台任务看门狗，设置后告警监控任务崩溃或卡住时自动重启
+    watchdog: Option<Arc<TaskWatchdog>>,
     /// 时间工具
     time_utils: TimeUtils,
 }
@@ -45,20 +200,159 @@ impl AlertService {
             config: Arc::new(RwLock::new(AlertConfig::default())),
             active_alerts: Arc::new(RwLock::new(HashMap::new())),
             alert_history: Arc::new(RwLock::new(Vec::new())),
+            history_limit: crate::config::DEFAULT_ALERT_HISTORY_LIMIT,
+            history_store: Arc::new(InMemoryAlertHistoryStore::new()),
             alert_rules: Arc::new(RwLock::new(HashMap::new())),
+            rule_snapshots: Arc::new(RwLock::new(HashMap::new())),
+            breach_starts: Arc::new(RwLock::new(HashMap::new())),
+            temperature_windows: Arc::new(RwLock::new(HashMap::new())),
+            predicted_breaches: Arc::new(RwLock::new(HashSet::new())),
+            predictive_lead_time_secs: crate::config::DEFAULT_PREDICTIVE_LEAD_TIME_SECS,
+            gradient_monitor: Arc::new(ThermalGradientMonitor::default()),
+            latest_readings: Arc::new(RwLock::new(LatestReadings::default())),
             notification_channels: Arc::new(RwLock::new(HashMap::new())),
+            notification_routing: Arc::new(RwLock::new(None)),
             alert_stats: Arc::new(RwLock::new(AlertStatistics::default())),
+            last_stats_reset: Arc::new(RwLock::new(None)),
+            statistics_store: Arc::new(InMemoryAlertStatisticsStore::new()),
+            event_store: Arc::new(InMemorySystemEventStore::new()),
+            template_store: Arc::new(InMemoryAlertTemplateStore::new()),
+            rule_state_store: Arc::new(InMemoryAlertRuleStateStore::new()),
+            sensor_blacklist: SensorBlacklist::default(),
+            alert_mutes: Arc::new(RwLock::new(HashMap::new())),
+            alert_events: EventBroadcast::new(ALERT_STREAM_CAPACITY),
+            http_client: reqwest::Client::new(),
+            notification_concurrency: crate::config::DEFAULT_NOTIFICATION_CONCURRENCY,
+            last_notification: Arc::new(std::sync::Mutex::new(None)),
+            notification_deliveries: Arc::new(RwLock::new(VecDeque::new())),
             task_handles: Arc::new(Mutex::new(Vec::new())),
+            watchdog: None,
             time_utils: TimeUtils,
         }
     }
 
+    /// 设置系统事件存储
+    ///
+    /// # 参数
+    /// * `event_store` - 系统事件存储，如 `PgSystemEventStore`
+    pub fn with_event_store(mut self, event_store: Arc<dyn SystemEventStore>) -> Self {
+        self.event_store = event_store;
+        self
+    }
+
+    /// 设置后台任务看门狗
+    ///
+    /// # 参数
+    /// * `watchdog` - 告警监控任务panic或超过心跳超时未上报心跳时自动重启
+    pub fn with_watchdog(mut self, watchdog: Arc<TaskWatchdog>) -> Self {
+        self.watchdog = Some(watchdog);
+        self
+    }
+
+    /// 设置告警历史存储
+    ///
+    /// # 参数
+    /// * `history_store` - 告警历史存储，如 `PgAlertHistoryStore`
+    pub fn with_history_store(mut self, history_store: Arc<dyn AlertHistoryStore>) -> Self {
+        self.history_store = history_store;
+        self
+    }
+
+    /// 设置内存告警历史条数上限
+    ///
+    /// # 参数
+    /// * `limit` - 上限，通常来自 `alert.history_limit`，至少为1
+    pub fn with_history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = limit.max(1);
+        self
+    }
+
+    /// 设置告警累计统计存储
+    ///
+    /// # 参数
+    /// * `statistics_store` - 统计存储，如 `PgAlertStatisticsStore`
+    pub fn with_statistics_store(mut self, statistics_store: Arc<dyn AlertStatisticsStore>) -> Self {
+        self.statistics_store = statistics_store;
+        self
+    }
+
+    /// 设置告警模板存储
+    ///
+    /// # 参数
+    /// * `template_store` - 告警模板存储，如 `PgAlertTemplateStore`
+    pub fn with_template_store(mut self, template_store: Arc<dyn AlertTemplateStore>) -> Self {
+        self.template_store = template_store;
+        self
+    }
+
+    /// 设置告警规则启用状态存储
+    ///
+    /// # 参数
+    /// * `rule_state_store` - 规则状态存储，如 `PgAlertRuleStateStore`
+    pub fn with_rule_state_store(mut self, rule_state_store: Arc<dyn AlertRuleStateStore>) -> Self {
+        self.rule_state_store = rule_state_store;
+        self
+    }
+
+    /// 设置传感器黑名单
+    ///
+    /// # 参数
+    /// * `sensor_blacklist` - 传感器黑名单
+    pub fn with_sensor_blacklist(mut self, sensor_blacklist: SensorBlacklist) -> Self {
+        self.sensor_blacklist = sensor_blacklist;
+        self
+    }
+
+    /// 设置预测告警提前量
+    ///
+    /// # 参数
+    /// * `lead_time_secs` - 提前量（秒），通常来自 `alert.predictive_lead_time_secs`，0表示不启用
+    pub fn with_predictive_lead_time(mut self, lead_time_secs: u64) -> Self {
+        self.predictive_lead_time_secs = lead_time_secs;
+        self
+    }
+
+    /// 设置温差规则
+    ///
+    /// # 参数
+    /// * `config` - 温差告警配置，通常来自 `alert.gradient`
+    pub fn with_gradient_rules(mut self, config: ThermalGradientConfig) -> Self {
+        self.gradient_monitor = Arc::new(ThermalGradientMonitor::new(config));
+        self
+    }
+
+    /// 设置同一告警同时投递的渠道数上限
+    ///
+    /// # 参数
+    /// * `concurrency` - 上限，通常来自 `alert.notification_concurrency`，至少为1
+    pub fn with_notification_concurrency(mut self, concurrency: usize) -> Self {
+        self.notification_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// 订阅告警状态变化事件
+    ///
+    /// 告警创建、确认、解决、升级时推送，订阅之前发生的事件不会补发；
+    /// 订阅者跟不上时丢弃最旧的事件并收到丢弃数量，不会阻塞告警处理
+    pub fn subscribe_alert_events(&self) -> EventSubscriber<AlertStreamEvent> {
+        self.alert_events.subscribe()
+    }
+
+    /// 告警推送订阅者因落后累计丢弃的事件数
+    pub fn dropped_alert_events(&self) -> u64 {
+        self.alert_events.dropped_events()
+    }
+
     /// 启动告警服务
     pub async fn start(&self) -> AppResult<()> {
         info!("启动告警服务");
 
-        // 初始化默认告警规则
+        // 初始化默认告警规则，并恢复持久化的启停状态
         self.initialize_default_rules().await?;
+        self.restore_rule_states().await?;
+
+        // 恢复上次保存的累计统计
+        self.restore_alert_statistics().await?;
 
         // 启动告警检查任务
         self.start_alert_monitoring().await?;
@@ -66,6 +360,9 @@ impl AlertService {
         // 启动告警清理任务
         self.start_alert_cleanup().await?;
 
+        // 启动统计保存任务
+        self.start_statistics_persistence().await?;
+
         info!("告警服务启动完成");
         Ok(())
     }
@@ -79,6 +376,10 @@ impl AlertService {
         for handle in handles.drain(..) {
             handle.abort();
         }
+        drop(handles);
+
+        // 保存最终统计，下次启动时继续累计
+        self.persist_alert_statistics().await?;
 
         info!("告警服务已停止");
         Ok(())
@@ -99,15 +400,38 @@ impl AlertService {
         source: String,
         message: String,
         details: Option<HashMap<String, String>>,
+    ) -> AppResult<String> {
+        self.create_alert_with_rule(alert_type, severity, source, message, details, None)
+            .await
+    }
+
+    /// 创建告警并记录触发该告警的规则
+    ///
+    /// # 参数
+    /// * `alert_type` - 告警类型
+    /// * `severity` - 告警严重程度
+    /// * `source` - 告警源
+    /// * `message` - 告警消息
+    /// * `details` - 告警详情
+    /// * `rule` - 触发告警的规则，非规则触发时为空
+    async fn create_alert_with_rule(
+        &self,
+        alert_type: AlertType,
+        severity: AlertSeverity,
+        source: String,
+        message: String,
+        details: Option<HashMap<String, String>>,
+        rule: Option<&AlertRule>,
     ) -> AppResult<String> {
         let alert_id = uuid::Uuid::new_v4();
         
-        let alert = Alert {
+        let mut alert = Alert {
             id: alert_id.clone(),
             alert_type,
             severity,
             source,
             message,
+            rule_id: rule.map(|rule| rule.id.clone()),
             status: AlertStatus::Active,
             created_at: Utc::now(),
             updated_at: Utc::now(),
@@ -119,30 +443,63 @@ impl AlertService {
             acknowledged: todo!(),
         };
 
-        info!("创建告警: {} - {} - {}", alert_id, alert.alert_type, alert.message);
+        // 静默中的告警只记入历史，不作为活跃告警，也不发送通知
+        let mute_id = self.apply_mute(&alert).await;
+        if let Some(ref mute_id) = mute_id {
+            alert.status = AlertStatus::Ignored;
+            info!("告警被静默 {} 抑制: {} - {} - {}", mute_id, alert_id, alert.alert_type, alert.message);
+        } else {
+            info!("创建告警: {} - {} - {}", alert_id, alert.alert_type, alert.message);
 
-        // 添加到活跃告警
-        {
+            // 添加到活跃告警
             let mut active_alerts = self.active_alerts.write().await;
             active_alerts.insert(alert_id.clone(), alert.clone());
+            drop(active_alerts);
+
+            if let Some(rule) = rule {
+                self.rule_snapshots
+                    .write()
+                    .await
+                    .insert(alert_id.to_string(), rule.clone());
+            }
         }
 
-        // 添加到历史记录
-        {
+        // 写入告警历史存储，写入失败不影响告警本身
+        if let Err(e) = self.history_store.upsert(&alert).await {
+            warn!("保存告警历史失败: {} - {}", alert_id, e);
+        }
+
+        // 添加到内存历史，超出上限时移除最旧的记录
+        let drained: Vec<String> = {
             let mut history = self.alert_history.write().await;
             history.push(alert.clone());
-            
-            // 限制历史记录数量
-            if history.len() > 10000 {
-                history.drain(0..1000);
+
+            let overflow = history.len().saturating_sub(self.history_limit);
+            history.drain(0..overflow).map(|alert| alert.id.to_string()).collect()
+        };
+
+        // 规则快照随历史记录一并清理，仍活跃的告警保留快照
+        if !drained.is_empty() {
+            let active_alerts = self.active_alerts.read().await;
+            let mut snapshots = self.rule_snapshots.write().await;
+            for id in drained.iter().filter(|id| !active_alerts.contains_key(*id)) {
+                snapshots.remove(id);
             }
         }
 
+        if mute_id.is_some() {
+            return Ok(alert_id);
+        }
+
         // 更新统计信息
         self.update_alert_statistics(&alert, true).await;
 
-        // 发送通知
-        self.send_alert_notification(&alert).await?;
+        // 记录系统事件
+        let level = Self::severity_event_level(&alert.severity.to_string());
+        self.emit_alert_event(&alert, AlertTransition::Created, level, None).await;
+
+        // 后台投递通知，不等待渠道响应
+        self.send_alert_notification(&alert).await;
 
         debug!("告警创建完成: {}", alert_id);
         Ok(alert_id)
@@ -161,12 +518,16 @@ impl AlertService {
         if let Some(alert) = active_alerts.get_mut(alert_id) {
             alert.status = AlertStatus::Acknowledged;
             alert.acknowledged_at = Some(Utc::now());
-            alert.acknowledged_by = Some(acknowledged_by);
+            alert.acknowledged_by = Some(acknowledged_by.clone());
             alert.updated_at = Utc::now();
 
             // 更新历史记录
             self.update_alert_in_history(alert).await;
 
+            // 记录系统事件
+            self.emit_alert_event(alert, AlertTransition::Acknowledged, "info", Some(&acknowledged_by))
+                .await;
+
             info!("告警确认完成: {}", alert_id);
             Ok(())
         } else {
@@ -188,12 +549,16 @@ impl AlertService {
             let mut resolved_alert = alert;
             resolved_alert.status = AlertStatus::Resolved;
             resolved_alert.resolved_at = Some(Utc::now());
-            resolved_alert.resolved_by = Some(resolved_by);
+            resolved_alert.resolved_by = Some(resolved_by.clone());
             resolved_alert.updated_at = Utc::now();
 
             // 更新历史记录
             self.update_alert_in_history(&resolved_alert).await;
 
+            // 记录系统事件
+            self.emit_alert_event(&resolved_alert, AlertTransition::Resolved, "info", Some(&resolved_by))
+                .await;
+
             // 更新统计信息
             self.update_alert_statistics(&resolved_alert, false).await;
 
@@ -204,12 +569,115 @@ impl AlertService {
         }
     }
 
+    /// 创建告警静默
+    ///
+    /// # 参数
+    /// * `request` - 静默的告警源、规则和到期时间
+    pub async fn create_alert_mute(&self, request: CreateAlertMuteRequest) -> AppResult<AlertMute> {
+        let mute = AlertMute::new(request, Utc::now())?;
+        info!(
+            "创建告警静默: {} (告警源: {:?}, 规则: {:?}, 到期: {})",
+            mute.id, mute.source, mute.rule_id, mute.expires_at
+        );
+        self.alert_mutes.write().await.insert(mute.id.clone(), mute.clone());
+        Ok(mute)
+    }
+
+    /// 获取仍然有效的告警静默，按到期时间排序，已到期的静默一并清理
+    pub async fn get_alert_mutes(&self) -> Vec<AlertMute> {
+        let now = Utc::now();
+        let mut mutes = self.alert_mutes.write().await;
+        mutes.retain(|_, mute| mute.is_active(now));
+        let mut active: Vec<AlertMute> = mutes.values().cloned().collect();
+        active.sort_by(|a, b| a.expires_at.cmp(&b.expires_at).then_with(|| a.id.cmp(&b.id)));
+        active
+    }
+
+    /// 提前解除告警静默，返回被解除的静默
+    ///
+    /// # 参数
+    /// * `mute_id` - 静默ID
+    pub async fn remove_alert_mute(&self, mute_id: &str) -> AppResult<AlertMute> {
+        let mute = self
+            .alert_mutes
+            .write()
+            .await
+            .remove(mute_id)
+            .filter(|mute| mute.is_active(Utc::now()))
+            .ok_or_else(|| AppError::NotFoundError(format!("告警静默不存在: {}", mute_id)))?;
+        info!("解除告警静默: {}", mute_id);
+        Ok(mute)
+    }
+
+    /// 查找匹配告警的有效静默并累计抑制次数，返回静默ID
+    async fn apply_mute(&self, alert: &Alert) -> Option<String> {
+        let now = Utc::now();
+        let mut mutes = self.alert_mutes.write().await;
+        let mute = mutes
+            .values_mut()
+            .find(|mute| mute.is_active(now) && mute.matches(&alert.source, alert.rule_id.as_deref()))?;
+        mute.suppressed_count += 1;
+        Some(mute.id.clone())
+    }
+
     /// 获取活跃告警
     pub async fn get_active_alerts(&self) -> Vec<Alert> {
         let active_alerts = self.active_alerts.read().await;
         active_alerts.values().cloned().collect()
     }
 
+    /// 获取指定告警
+    ///
+    /// 依次查找活跃告警、内存告警历史和告警历史存储
+    ///
+    /// # 参数
+    /// * `alert_id` - 告警ID
+    pub async fn get_alert(&self, alert_id: &str) -> AppResult<Alert> {
+        if let Some(alert) = self.active_alerts.read().await.get(alert_id) {
+            return Ok(alert.clone());
+        }
+
+        let recent = self.alert_history.read().await
+            .iter()
+            .rev()
+            .find(|alert| alert.id.to_string() == alert_id)
+            .cloned();
+        if let Some(alert) = recent {
+            return Ok(alert);
+        }
+
+        self.history_store
+            .get(alert_id)
+            .await?
+            .ok_or_else(|| AppError::not_found_error("告警", alert_id))
+    }
+
+    /// 获取触发指定告警的规则
+    ///
+    /// 同时返回触发时的规则快照和当前规则定义，规则在触发后被修改或删除时两者不同
+    ///
+    /// # 参数
+    /// * `alert_id` - 告警ID
+    pub async fn get_alert_trigger_rule(&self, alert_id: &str) -> AppResult<AlertTriggerRule> {
+        let alert = self.get_alert(alert_id).await?;
+        let rule_id = alert
+            .rule_id
+            .ok_or_else(|| AppError::not_found_error("告警规则", alert_id))?;
+
+        let triggered_rule = self.rule_snapshots.read().await.get(alert_id).cloned();
+        let current_rule = self.alert_rules.read().await.get(&rule_id).cloned();
+        if triggered_rule.is_none() && current_rule.is_none() {
+            return Err(AppError::not_found_error("告警规则", rule_id));
+        }
+
+        Ok(AlertTriggerRule {
+            alert_id: alert_id.to_string(),
+            rule_id,
+            triggered_rule,
+            current_rule,
+        })
+    }
+
     /// 获取告警历史
     /// 
     /// # 参数
@@ -228,8 +696,97 @@ impl AlertService {
     }
 
     /// 获取告警统计
-    pub async fn get_alert_statistics(&self) -> AlertStatistics {
-        self.alert_stats.read().await.clone()
+    /// 
+    /// 返回累计统计以及基于告警历史计算的滚动窗口统计
+    /// 
+    /// # 参数
+    /// * `window_hours` - 滚动窗口长度（小时）
+    pub async fn get_alert_statistics(&self, window_hours: u32) -> AppResult<AlertStatisticsReport> {
+        if window_hours == 0 || window_hours > MAX_STATISTICS_WINDOW_HOURS {
+            return Err(AppError::validation_error(
+                "time_window_hours",
+                format!("统计窗口必须在1-{}小时之间", MAX_STATISTICS_WINDOW_HOURS),
+            ));
+        }
+
+        let window_start = Utc::now() - chrono::Duration::hours(window_hours as i64);
+        let window = {
+            let history = self.alert_history.read().await;
+            AlertStatistics::from_alerts(
+                history.iter().filter(|alert| alert.created_at >= window_start),
+                format!("{}h", window_hours),
+            )
+        };
+
+        Ok(AlertStatisticsReport {
+            lifetime: self.alert_stats.read().await.clone(),
+            window,
+            window_hours,
+            last_reset: self.last_stats_reset.read().await.clone(),
+        })
+    }
+
+    /// 重置累计告警统计
+    /// 
+    /// 仅清零累计计数，告警历史与滚动窗口统计不受影响；重置操作会记录审计日志
+    /// 
+    /// # 参数
+    /// * `reset_by` - 操作人
+    /// * `reason` - 重置原因
+    pub async fn reset_alert_statistics(
+        &self,
+        reset_by: Option<String>,
+        reason: Option<String>,
+    ) -> AppResult<AlertStatisticsReset> {
+        let previous = std::mem::take(&mut *self.alert_stats.write().await);
+
+        let record = AlertStatisticsReset {
+            reset_at: Utc::now(),
+            reset_by,
+            reason,
+            previous,
+        };
+
+        warn!(
+            target: "audit",
+            "告警累计统计已重置: 操作人={}, 原因={}, 重置前总数={}",
+            record.reset_by.as_deref().unwrap_or("unknown"),
+            record.reason.as_deref().unwrap_or("-"),
+            record.previous.total_alerts
+        );
+
+        *self.last_stats_reset.write().await = Some(record.clone());
+        if let Err(e) = self.persist_alert_statistics().await {
+            warn!("保存重置后的告警统计失败: {}", e);
+        }
+        Ok(record)
+    }
+
+    /// 当前的累计统计和重置记录
+    async fn statistics_snapshot(
+        alert_stats: &RwLock<AlertStatistics>,
+        last_stats_reset: &RwLock<Option<AlertStatisticsReset>>,
+    ) -> PersistedAlertStatistics {
+        PersistedAlertStatistics {
+            lifetime: alert_stats.read().await.clone(),
+            last_reset: last_stats_reset.read().await.clone(),
+        }
+    }
+
+    /// 将累计统计写入统计存储
+    pub async fn persist_alert_statistics(&self) -> AppResult<()> {
+        let snapshot = Self::statistics_snapshot(&self.alert_stats, &self.last_stats_reset).await;
+        self.statistics_store.save(&snapshot).await
+    }
+
+    /// 从统计存储恢复累计统计，从未保存过时保持为零
+    async fn restore_alert_statistics(&self) -> AppResult<()> {
+        if let Some(saved) = self.statistics_store.load().await? {
+            info!("恢复告警累计统计: 总数={}", saved.lifetime.total_alerts);
+            *self.alert_stats.write().await = saved.lifetime;
+            *self.last_stats_reset.write().await = saved.last_reset;
+        }
+        Ok(())
     }
 
     /// 添加告警规则
@@ -243,12 +800,111 @@ impl AlertService {
         rule.validate()?;
 
         let mut rules = self.alert_rules.write().await;
+        Self::ensure_unique_rule_name(&rules, &rule)?;
         rules.insert(rule.id.clone(), rule);
 
         info!("告警规则添加完成");
         Ok(())
     }
 
+    /// 按规则名称创建或更新告警规则，返回生效后的规则
+    ///
+    /// 已有同名规则时保留其ID和创建时间，用提交的定义原地更新阈值等字段，
+    /// 重复提交同一定义不会报错或产生重复规则；没有同名规则时按提交的ID创建，
+    /// ID为空时自动生成
+    ///
+    /// # 参数
+    /// * `rule` - 告警规则
+    pub async fn apply_alert_rule(&self, mut rule: AlertRule) -> AppResult<AlertRule> {
+        rule.name = rule.name.trim().to_string();
+        rule.validate()?;
+
+        let mut rules = self.alert_rules.write().await;
+        let existing = rules.values().find(|r| r.name.trim() == rule.name).cloned();
+
+        let now = Utc::now();
+        match existing {
+            Some(existing) => {
+                rule.id = existing.id;
+                rule.created_at = existing.created_at;
+                rule.updated_at = now;
+                if rule.enabled != existing.enabled {
+                    self.rule_state_store.set_enabled(&rule.id, rule.enabled).await?;
+                }
+                info!("按名称更新告警规则: {} ({})", rule.name, rule.id);
+            }
+            None => {
+                if rule.id.trim().is_empty() {
+                    rule.id = uuid::Uuid::new_v4().to_string();
+                }
+                Self::ensure_unique_rule_name(&rules, &rule)?;
+                rule.created_at = now;
+                rule.updated_at = now;
+                info!("按名称创建告警规则: {} ({})", rule.name, rule.id);
+            }
+        }
+
+        rules.insert(rule.id.clone(), rule.clone());
+        drop(rules);
+
+        // 阈值可能已变化，按新定义重新计算持续时间
+        self.breach_starts
+            .write()
+            .await
+            .retain(|(id, _), _| id != &rule.id);
+
+        Ok(rule)
+    }
+
+    /// 部分更新告警规则，返回更新后的规则
+    ///
+    /// 在写锁内基于当前规则合并，只修改提交的字段；合并结果验证失败时规则保持不变
+    ///
+    /// # 参数
+    /// * `rule_id` - 规则ID
+    /// * `patch` - 需要修改的字段
+    pub async fn patch_alert_rule(&self, rule_id: &str, patch: AlertRulePatch) -> AppResult<AlertRule> {
+        if patch.is_empty() {
+            return Err(AppError::validation_error("rule", "至少需要指定一个规则字段"));
+        }
+
+        let mut rules = self.alert_rules.write().await;
+        let current = rules
+            .get(rule_id)
+            .ok_or_else(|| AppError::not_found_error("告警规则", rule_id))?;
+        let rule = patch.merged(current)?;
+        Self::ensure_unique_rule_name(&rules, &rule)?;
+        if rule.enabled != current.enabled {
+            self.rule_state_store.set_enabled(rule_id, rule.enabled).await?;
+        }
+        rules.insert(rule.id.clone(), rule.clone());
+        drop(rules);
+
+        self.breach_starts
+            .write()
+            .await
+            .retain(|(id, _), _| id != rule_id);
+
+        info!("告警规则部分更新完成: {} ({})", rule.name, rule.id);
+        Ok(rule)
+    }
+
+    /// 校验规则名称未被其他规则占用
+    ///
+    /// # 参数
+    /// * `rules` - 现有规则
+    /// * `rule` - 待写入的规则
+    fn ensure_unique_rule_name(rules: &HashMap<String, AlertRule>, rule: &AlertRule) -> AppResult<()> {
+        let name = rule.name.trim();
+        match rules.values().find(|r| r.id != rule.id && r.name.trim() == name) {
+            Some(other) => Err(AppError::validation_error(
+                "name",
+                format!("告警规则名称 {} 已被规则 {} 使用", name, other.id),
+            )),
+            None => Ok(()),
+        }
+    }
+
     /// 移除告警规则
     /// 
     /// # 参数
@@ -271,6 +927,152 @@ impl AlertService {
         rules.values().cloned().collect()
     }
 
+    /// 切换告警规则的启用状态，返回切换后的规则
+    ///
+    /// 状态先持久化再生效，下一次评估即按新状态处理；
+    /// 同时清除该规则的持续计时，重新启用后需重新满足持续时间
+    ///
+    /// # 参数
+    /// * `rule_id` - 规则ID
+    pub async fn toggle_alert_rule(&self, rule_id: &str) -> AppResult<AlertRule> {
+        let mut rules = self.alert_rules.write().await;
+        let rule = rules
+            .get_mut(rule_id)
+            .ok_or_else(|| AppError::not_found_error("告警规则", rule_id))?;
+
+        let enabled = !rule.enabled;
+        self.rule_state_store.set_enabled(rule_id, enabled).await?;
+        rule.enabled = enabled;
+        rule.updated_at = Utc::now();
+        let rule = rule.clone();
+        drop(rules);
+
+        self.breach_starts
+            .write()
+            .await
+            .retain(|(id, _), _| id != rule_id);
+
+        info!("告警规则 {} 已{}", rule_id, if enabled { "启用" } else { "停用" });
+        Ok(rule)
+    }
+
+    /// 将持久化的启停状态应用到已加载的规则
+    async fn restore_rule_states(&self) -> AppResult<()> {
+        let states = self.rule_state_store.load().await?;
+        let mut rules = self.alert_rules.write().await;
+        for (rule_id, enabled) in states {
+            if let Some(rule) = rules.get_mut(&rule_id) {
+                rule.enabled = enabled;
+            }
+        }
+        Ok(())
+    }
+
+    /// 获取指定告警规则
+    /// 
+    /// # 参数
+    /// * `rule_id` - 规则ID
+    pub async fn get_alert_rule(&self, rule_id: &str) -> AppResult<AlertRule> {
+        let rules = self.alert_rules.read().await;
+        rules.get(rule_id)
+            .cloned()
+            .ok_or_else(|| AppError::not_found_error("告警规则", rule_id))
+    }
+
+    /// 试运行告警规则
+    /// 
+    /// 将历史读数逐条代入规则评估，统计规则在该时间段内会触发的次数，
+    /// 不会创建告警或发送通知
+    /// 
+    /// # 参数
+    /// * `rule` - 告警规则
+    /// * `start_time` - 回放开始时间
+    /// * `end_time` - 回放结束时间
+    /// * `readings` - 回放使用的历史读数
+    pub async fn dry_run_rule(
+        &self,
+        rule: &AlertRule,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        readings: &RuleReplayReadings,
+    ) -> AppResult<RuleDryRunResult> {
+        if start_time >= end_time {
+            return Err(AppError::validation_error("start_time", "开始时间必须早于结束时间"));
+        }
+
+        let in_range = |timestamp: &DateTime<Utc>| *timestamp >= start_time && *timestamp <= end_time;
+        let mut evaluated_samples = 0;
+        let mut triggers = Vec::new();
+
+        match rule.rule_type {
+            AlertRuleType::Temperature => {
+                for reading in readings.temperature.iter().filter(|r| in_range(&r.timestamp)) {
+                    evaluated_samples += 1;
+                    if self.evaluate_temperature_rule(rule, reading).await? {
+                        triggers.push(RuleTriggerSample {
+                            timestamp: reading.timestamp,
+                            source_id: reading.sensor_id.clone(),
+                            value: reading.temperature,
+                        });
+                    }
+                }
+            }
+            AlertRuleType::Fan => {
+                for reading in readings.fan.iter().filter(|r| in_range(&r.timestamp)) {
+                    evaluated_samples += 1;
+                    if self.evaluate_fan_rule(rule, reading).await? {
+                        triggers.push(RuleTriggerSample {
+                            timestamp: reading.timestamp,
+                            source_id: reading.fan_id.clone(),
+                            value: reading.speed_rpm as f64,
+                        });
+                    }
+                }
+            }
+            AlertRuleType::FanUtilization => {
+                for reading in readings.fan.iter().filter(|r| in_range(&r.timestamp)) {
+                    evaluated_samples += 1;
+                    if Self::evaluate_fan_utilization_rule(rule, reading)? {
+                        triggers.push(RuleTriggerSample {
+                            timestamp: reading.timestamp,
+                            source_id: reading.fan_id.clone(),
+                            value: reading.speed_percent,
+                        });
+                    }
+                }
+            }
+            AlertRuleType::Sensor => {
+                for reading in readings.sensor.iter().filter(|r| in_range(&r.timestamp)) {
+                    evaluated_samples += 1;
+                    if self.evaluate_sensor_rule(rule, reading).await? {
+                        triggers.push(RuleTriggerSample {
+                            timestamp: reading.timestamp,
+                            source_id: reading.sensor_id.clone(),
+                            value: reading.value,
+                        });
+                    }
+                }
+            }
+        }
+
+        triggers.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        let trigger_count = triggers.len();
+        triggers.truncate(MAX_DRY_RUN_SAMPLES);
+
+        debug!("告警规则试运行完成: {} 共评估 {} 条, 触发 {} 次", rule.id, evaluated_samples, trigger_count);
+
+        Ok(RuleDryRunResult {
+            rule_id: rule.id.clone(),
+            start_time,
+            end_time,
+            evaluated_samples,
+            trigger_count,
+            first_triggered_at: triggers.first().map(|t| t.timestamp),
+            last_triggered_at: triggers.last().map(|t| t.timestamp),
+            samples: triggers,
+        })
+    }
+
     /// 添加通知渠道
     /// 
     /// # 参数
@@ -304,12 +1106,85 @@ impl AlertService {
         }
     }
 
+    /// 导出告警规则、通知渠道和通知路由，规则与渠道按ID排序
+    pub async fn export_settings(&self) -> AlertSettings {
+        let mut rules = self.get_alert_rules().await;
+        rules.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut channels = self.get_notification_channels().await;
+        channels.sort_by(|a, b| a.id.cmp(&b.id));
+
+        AlertSettings {
+            rules,
+            channels,
+            routing: self.get_notification_routing().await,
+        }
+    }
+
+    /// 整体替换告警规则、通知渠道和通知路由
+    ///
+    /// 先持久化各规则的启停状态，再在同一组写锁内替换，评估循环不会看到新旧混合的规则集
+    ///
+    /// # 参数
+    /// * `settings` - 告警设置
+    pub async fn replace_settings(&self, settings: AlertSettings) -> AppResult<()> {
+        settings.validate()?;
+
+        for rule in &settings.rules {
+            self.rule_state_store.set_enabled(&rule.id, rule.enabled).await?;
+        }
+
+        {
+            let mut rules = self.alert_rules.write().await;
+            let mut channels = self.notification_channels.write().await;
+            let mut routing = self.notification_routing.write().await;
+            *rules = settings.rules.into_iter().map(|r| (r.id.clone(), r)).collect();
+            *channels = settings.channels.into_iter().map(|c| (c.id.clone(), c)).collect();
+            *routing = settings.routing;
+        }
+        self.breach_starts.write().await.clear();
+
+        info!("告警设置已整体替换");
+        Ok(())
+    }
+
     /// 获取所有通知渠道
     pub async fn get_notification_channels(&self) -> Vec<NotificationChannel> {
         let channels = self.notification_channels.read().await;
         channels.values().cloned().collect()
     }
 
+    /// 获取通知路由矩阵
+    pub async fn get_notification_routing(&self) -> Option<NotificationRoutingMatrix> {
+        self.notification_routing.read().await.clone()
+    }
+
+    /// 设置通知路由矩阵，传入 `None` 恢复按渠道严重程度过滤
+    ///
+    /// # 参数
+    /// * `routing` - 通知路由矩阵
+    pub async fn set_notification_routing(
+        &self,
+        routing: Option<NotificationRoutingMatrix>,
+    ) -> AppResult<()> {
+        if let Some(routing) = &routing {
+            routing.validate()?;
+
+            let channels = self.notification_channels.read().await;
+            for (index, route) in routing.routes.iter().enumerate() {
+                if let Some(unknown) = route.channel_ids.iter().find(|id| !channels.contains_key(*id)) {
+                    return Err(AppError::validation_error(
+                        format!("routes[{}].channel_ids", index),
+                        format!("通知渠道不存在: {}", unknown),
+                    ));
+                }
+            }
+        }
+
+        info!("更新通知路由矩阵: {}", if routing.is_some() { "已配置" } else { "已清除" });
+        *self.notification_routing.write().await = routing;
+        Ok(())
+    }
+
     /// 测试通知渠道
     /// 
     /// # 参数
@@ -326,6 +1201,7 @@ impl AlertService {
                 severity: AlertSeverity::Info,
                 source: "test".to_string(),
                 message: "通知渠道测试消息".to_string(),
+                rule_id: None,
                 details: HashMap::new(),
                 status: AlertStatus::Active,
                 created_at: Utc::now(),
@@ -348,34 +1224,116 @@ impl AlertService {
         }
     }
 
-    /// 检查温度告警
-    /// 
+    /// 预览渠道将要发送的通知内容，不实际发送
+    ///
+    /// 示例告警不会进入活跃告警和历史，使用模板时也不计入模板使用次数
+    ///
     /// # 参数
-    /// * `temperature_reading` - 温度读数
-    pub async fn check_temperature_alert(&self, temperature_reading: &TemperatureReading) -> AppResult<()> {
-        let rules = self.alert_rules.read().await;
-        
-        for rule in rules.values() {
-            if rule.rule_type == AlertRuleType::Temperature && rule.enabled {
-                if self.evaluate_temperature_rule(rule, temperature_reading).await? {
-                    // 创建温度告警
-                    let severity = self.determine_temperature_severity(temperature_reading.temperature, rule);
-                    let mut details = HashMap::new();
-                    details.insert("sensor_id".to_string(), temperature_reading.sensor_id.clone());
-                    details.insert("temperature".to_string(), temperature_reading.temperature.to_string());
-                    details.insert("threshold".to_string(), rule.threshold.to_string());
-
-                    self.create_alert(
-                        AlertType::Temperature,
-                        severity,
-                        temperature_reading.sensor_id.clone(),
-                        format!("温度异常: {:.1}°C (阈值: {:.1}°C)", 
-                               temperature_reading.temperature, rule.threshold),
+    /// * `channel_id` - 渠道ID
+    /// * `request` - 示例告警，或模板与变量
+    pub async fn preview_notification(
+        &self,
+        channel_id: &str,
+        request: PreviewNotificationRequest,
+    ) -> AppResult<NotificationPreview> {
+        let channel = self
+            .notification_channels
+            .read()
+            .await
+            .get(channel_id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("通知渠道不存在: {}", channel_id)))?;
+
+        let (alert_type, severity, source, message) = match request {
+            PreviewNotificationRequest::Template { template_id, source, variables } => {
+                let template = self.get_alert_template(&template_id).await?;
+                let message = Self::render_template(&template.message_template, &variables);
+                (Self::parse_alert_type(&template.alert_type)?, template.severity, source, message)
+            }
+            PreviewNotificationRequest::Alert { alert_type, severity, source, message } => {
+                (alert_type, severity, source, message)
+            }
+        };
+
+        let now = Utc::now();
+        let sample_alert = Alert {
+            id: "preview".to_string(),
+            alert_type,
+            severity,
+            source,
+            message,
+            rule_id: None,
+            details: HashMap::new(),
+            status: AlertStatus::Active,
+            created_at: now,
+            updated_at: now,
+            acknowledged_at: None,
+            acknowledged_by: None,
+            resolved_at: None,
+            resolved_by: None,
+            escalation_level: 0,
+            notification_sent: false,
+        };
+
+        Self::render_notification(&channel, &sample_alert)
+    }
+
+    /// 检查温度告警
+    /// 
+    /// # 参数
+    /// * `temperature_reading` - 温度读数
+    pub async fn check_temperature_alert(&self, temperature_reading: &TemperatureReading) -> AppResult<()> {
+        if self.sensor_blacklist.contains(&temperature_reading.sensor_id) {
+            return Ok(());
+        }
+
+        self.record_temperature_sample(temperature_reading).await;
+        self.latest_readings
+            .write()
+            .await
+            .temperature
+            .insert(temperature_reading.sensor_id.clone(), temperature_reading.clone());
+        let rules = self.alert_rules.read().await;
+        
+        for rule in rules.values() {
+            if rule.rule_type == AlertRuleType::Temperature && rule.enabled {
+                let breached = self.evaluate_temperature_rule(rule, temperature_reading).await?;
+                if let Some(seconds) = self.predict_temperature_breach(rule, temperature_reading, breached).await {
+                    let mut details = HashMap::new();
+                    details.insert("sensor_id".to_string(), temperature_reading.sensor_id.clone());
+                    details.insert("temperature".to_string(), temperature_reading.temperature.to_string());
+                    details.insert("threshold".to_string(), rule.threshold.to_string());
+                    details.insert("predicted_seconds".to_string(), format!("{:.0}", seconds));
+
+                    self.create_alert_with_rule(
+                        AlertType::Temperature,
+                        AlertSeverity::Warning,
+                        temperature_reading.sensor_id.clone(),
+                        format!("{} 预计约{}分钟后超过{:.1}°C (当前: {:.1}°C)",
+                               temperature_reading.sensor_name, (seconds / 60.0).ceil().max(1.0),
+                               rule.threshold, temperature_reading.temperature),
                         Some(details),
+                        Some(rule),
                     ).await?;
                 }
+
+                if self
+                    .breach_held(rule, &temperature_reading.sensor_id, breached, temperature_reading.timestamp)
+                    .await
+                {
+                    self.raise_temperature_rule_alert(rule, temperature_reading).await?;
+                }
             }
         }
+        drop(rules);
+
+        for breach in self.gradient_monitor.observe(
+            &temperature_reading.sensor_id,
+            temperature_reading.temperature,
+            temperature_reading.timestamp,
+        ) {
+            self.raise_gradient_alert(&breach).await?;
+        }
 
         Ok(())
     }
@@ -385,26 +1343,29 @@ impl AlertService {
     /// # 参数
     /// * `fan_reading` - 风扇读数
     pub async fn check_fan_alert(&self, fan_reading: &FanReading) -> AppResult<()> {
+        self.latest_readings
+            .write()
+            .await
+            .fan
+            .insert(fan_reading.fan_id.clone(), fan_reading.clone());
         let rules = self.alert_rules.read().await;
         
         for rule in rules.values() {
             if rule.rule_type == AlertRuleType::Fan && rule.enabled {
-                if self.evaluate_fan_rule(rule, fan_reading).await? {
-                    // 创建风扇告警
-                    let severity = self.determine_fan_severity(fan_reading, rule);
-                    let mut details = HashMap::new();
-                    details.insert("fan_id".to_string(), fan_reading.fan_id.clone());
-                    details.insert("speed_rpm".to_string(), fan_reading.speed_rpm.to_string());
-                    details.insert("speed_percent".to_string(), fan_reading.speed_percent.to_string());
-
-                    self.create_alert(
-                        AlertType::Fan,
-                        severity,
-                        fan_reading.fan_id.clone(),
-                        format!("风扇异常: {} RPM ({:.1}%)", 
-                               fan_reading.speed_rpm, fan_reading.speed_percent),
-                        Some(details),
-                    ).await?;
+                let breached = self.evaluate_fan_rule(rule, fan_reading).await?;
+                if self
+                    .breach_held(rule, &fan_reading.fan_id, breached, fan_reading.timestamp)
+                    .await
+                {
+                    self.raise_fan_rule_alert(rule, fan_reading).await?;
+                }
+            } else if rule.rule_type == AlertRuleType::FanUtilization && rule.enabled {
+                let breached = Self::evaluate_fan_utilization_rule(rule, fan_reading)?;
+                if self
+                    .breach_held(rule, &fan_reading.fan_id, breached, fan_reading.timestamp)
+                    .await
+                {
+                    self.raise_fan_utilization_alert(rule, fan_reading).await?;
                 }
             }
         }
@@ -417,33 +1378,401 @@ impl AlertService {
     /// # 参数
     /// * `sensor_reading` - 传感器读数
     pub async fn check_sensor_alert(&self, sensor_reading: &SensorReading) -> AppResult<()> {
+        if self.sensor_blacklist.contains(&sensor_reading.sensor_id) {
+            return Ok(());
+        }
+
+        self.latest_readings
+            .write()
+            .await
+            .sensor
+            .insert(sensor_reading.sensor_id.clone(), sensor_reading.clone());
         let rules = self.alert_rules.read().await;
         
         for rule in rules.values() {
             if rule.rule_type == AlertRuleType::Sensor && rule.enabled {
-                if self.evaluate_sensor_rule(rule, sensor_reading).await? {
-                    // 创建传感器告警
-                    let severity = AlertSeverity::Warning; // 默认警告级别
-                    let mut details = HashMap::new();
-                    details.insert("sensor_id".to_string(), sensor_reading.sensor_id.clone());
-                    details.insert("value".to_string(), sensor_reading.value.to_string());
-                    details.insert("status".to_string(), format!("{:?}", sensor_reading.status));
-
-                    self.create_alert(
-                        AlertType::Sensor,
-                        severity,
-                        sensor_reading.sensor_id.clone(),
-                        format!("传感器异常: {} = {:.2} {}", 
-                               sensor_reading.sensor_id, sensor_reading.value, sensor_reading.unit),
-                        Some(details),
-                    ).await?;
+                let breached = self.evaluate_sensor_rule(rule, sensor_reading).await?;
+                if self
+                    .breach_held(rule, &sensor_reading.sensor_id, breached, sensor_reading.timestamp)
+                    .await
+                {
+                    self.raise_sensor_rule_alert(rule, sensor_reading).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 立即以各告警源最近一次读数评估全部启用的规则
+    ///
+    /// 持续时间按评估时刻计算：条件自首次满足起到现在已达规则的 `duration` 才创建告警，
+    /// 满足条件但持续时间不足的规则只出现在匹配列表中。不参与趋势预测
+    pub async fn evaluate_rules_now(&self) -> AppResult<RuleEvaluationResult> {
+        let now = Utc::now();
+        let latest = self.latest_readings.read().await.clone();
+        let mut rules: Vec<AlertRule> = self.alert_rules.read().await
+            .values()
+            .filter(|rule| rule.enabled)
+            .cloned()
+            .collect();
+        rules.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut matches = Vec::new();
+        for rule in &rules {
+            if rule.rule_type == AlertRuleType::Temperature {
+                for reading in latest.temperature.values() {
+                    if self.sensor_blacklist.contains(&reading.sensor_id) {
+                        continue;
+                    }
+                    let breached = self.evaluate_temperature_rule(rule, reading).await?;
+                    let alert_id = if self.breach_held(rule, &reading.sensor_id, breached, now).await {
+                        Some(self.raise_temperature_rule_alert(rule, reading).await?)
+                    } else {
+                        None
+                    };
+                    if breached {
+                        matches.push(RuleEvaluationMatch::new(rule, &reading.sensor_id, reading.temperature, alert_id));
+                    }
+                }
+            } else if rule.rule_type == AlertRuleType::Fan {
+                for reading in latest.fan.values() {
+                    let breached = self.evaluate_fan_rule(rule, reading).await?;
+                    let alert_id = if self.breach_held(rule, &reading.fan_id, breached, now).await {
+                        Some(self.raise_fan_rule_alert(rule, reading).await?)
+                    } else {
+                        None
+                    };
+                    if breached {
+                        let value = Self::fan_rule_value(rule, reading)?;
+                        matches.push(RuleEvaluationMatch::new(rule, &reading.fan_id, value, alert_id));
+                    }
+                }
+            } else if rule.rule_type == AlertRuleType::FanUtilization {
+                for reading in latest.fan.values() {
+                    let breached = Self::evaluate_fan_utilization_rule(rule, reading)?;
+                    let alert_id = if self.breach_held(rule, &reading.fan_id, breached, now).await {
+                        Some(self.raise_fan_utilization_alert(rule, reading).await?)
+                    } else {
+                        None
+                    };
+                    if breached {
+                        matches.push(RuleEvaluationMatch::new(rule, &reading.fan_id, reading.speed_percent, alert_id));
+                    }
+                }
+            } else if rule.rule_type == AlertRuleType::Sensor {
+                for reading in latest.sensor.values() {
+                    if self.sensor_blacklist.contains(&reading.sensor_id) {
+                        continue;
+                    }
+                    let breached = self.evaluate_sensor_rule(rule, reading).await?;
+                    let alert_id = if self.breach_held(rule, &reading.sensor_id, breached, now).await {
+                        Some(self.raise_sensor_rule_alert(rule, reading).await?)
+                    } else {
+                        None
+                    };
+                    if breached {
+                        matches.push(RuleEvaluationMatch::new(rule, &reading.sensor_id, reading.value, alert_id));
+                    }
                 }
             }
         }
 
+        let mut created_alerts = Vec::new();
+        for alert_id in matches.iter().filter_map(|m| m.alert_id.as_deref()) {
+            created_alerts.push(self.get_alert(alert_id).await?);
+        }
+        info!("手动评估告警规则: 匹配 {} 项, 创建告警 {} 条", matches.len(), created_alerts.len());
+
+        Ok(RuleEvaluationResult {
+            evaluated_at: now,
+            evaluated_rules: rules.len(),
+            evaluated_readings: latest.temperature.len() + latest.fan.len() + latest.sensor.len(),
+            matches,
+            created_alerts,
+        })
+    }
+
+    /// 端到端自检告警链路：规则 → 告警创建 → 通知渠道
+    ///
+    /// 注册一条临时温度规则并注入必然超过阈值的合成读数，确认规则触发、告警创建后，
+    /// 将该告警同步投递到每个已启用的渠道（不受渠道过滤和路由矩阵限制）。
+    /// `dry_run` 时只渲染通知并检查渠道是否配置了发送目标，不发送给真实接收人。
+    /// 结束后移除临时规则和告警，自检告警不进入告警历史、统计和系统事件
+    ///
+    /// # 参数
+    /// * `dry_run` - 只检查渠道，不实际发送
+    pub async fn run_selftest(&self, dry_run: bool) -> AppResult<AlertSelfTestReport> {
+        let started_at = Utc::now();
+        let test_id = format!("{}-{}", SELFTEST_ID_PREFIX, uuid::Uuid::new_v4());
+        let rule = AlertRule {
+            id: test_id.clone(),
+            name: "告警链路自检".to_string(),
+            description: "自检临时规则，自检结束后移除".to_string(),
+            rule_type: AlertRuleType::Temperature,
+            condition: AlertCondition::GreaterThan,
+            threshold: SELFTEST_THRESHOLD,
+            threshold_unit: None,
+            duration: 0,
+            severity: AlertSeverity::Info,
+            enabled: true,
+            created_at: started_at,
+            updated_at: started_at,
+        };
+        let reading = TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: test_id.clone(),
+            sensor_name: "告警链路自检".to_string(),
+            temperature: SELFTEST_THRESHOLD + 1.0,
+            status: crate::models::thermal::TemperatureStatus::Normal,
+            timestamp: started_at,
+            server_id: SELFTEST_ID_PREFIX.to_string(),
+        };
+        info!("告警链路自检开始: {} (dry_run: {})", test_id, dry_run);
+
+        // 只用临时规则评估合成读数，真实规则不会因合成读数产生告警
+        self.alert_rules.write().await.insert(rule.id.clone(), rule.clone());
+        let rule_triggered = self.evaluate_temperature_rule(&rule, &reading).await;
+
+        let mut alert_created = false;
+        let mut channels = Vec::new();
+        if matches!(rule_triggered, Ok(true)) {
+            let alert = Alert {
+                id: test_id.clone(),
+                alert_type: AlertType::System,
+                severity: AlertSeverity::Info,
+                source: reading.sensor_id.clone(),
+                message: format!(
+                    "告警链路自检: {:.1}°C (阈值: {:.1}°C)，可忽略",
+                    reading.temperature, rule.threshold
+                ),
+                rule_id: Some(rule.id.clone()),
+                details: HashMap::new(),
+                status: AlertStatus::Active,
+                created_at: started_at,
+                updated_at: started_at,
+                acknowledged_at: None,
+                acknowledged_by: None,
+                resolved_at: None,
+                resolved_by: None,
+                escalation_level: 0,
+                notification_sent: false,
+            };
+            self.active_alerts.write().await.insert(alert.id.clone(), alert.clone());
+            alert_created = self.get_alert(&alert.id).await.is_ok();
+
+            let mut targets: Vec<NotificationChannel> = self
+                .notification_channels
+                .read()
+                .await
+                .values()
+                .filter(|channel| channel.enabled)
+                .cloned()
+                .collect();
+            targets.sort_by(|a, b| a.id.cmp(&b.id));
+            for channel in targets {
+                let started = std::time::Instant::now();
+                let result = if dry_run {
+                    Self::check_selftest_channel(&channel, &alert)
+                } else {
+                    self.send_notification_to_channel(&channel, &alert).await
+                };
+                channels.push(ChannelSelfTestResult {
+                    channel_id: channel.id.clone(),
+                    channel_type: channel.channel_type.clone(),
+                    success: result.is_ok(),
+                    error: result.err().map(|e| e.to_string()),
+                    duration_ms: started.elapsed().as_millis() as u64,
+                });
+            }
+        }
+
+        // 清理临时规则和告警
+        self.alert_rules.write().await.remove(&test_id);
+        self.active_alerts.write().await.remove(&test_id);
+
+        let success = alert_created && channels.iter().all(|channel| channel.success);
+        info!(
+            "告警链路自检完成: {} - {} ({} 个渠道失败)",
+            test_id,
+            if success { "成功" } else { "失败" },
+            channels.iter().filter(|channel| !channel.success).count()
+        );
+
+        Ok(AlertSelfTestReport {
+            success,
+            dry_run,
+            started_at,
+            rule_triggered: matches!(rule_triggered, Ok(true)),
+            alert_created,
+            channels,
+        })
+    }
+
+    /// 自检试运行时检查渠道：渲染通知，并确认配置了发送目标
+    ///
+    /// 发送目标与发送时读取的配置项一致：Webhook为 `url`，Slack为 `webhook_url`，
+    /// 钉钉为 `webhook_url` 或 `access_token`，邮件为 `email`，短信为 `phone`
+    ///
+    /// # 参数
+    /// * `channel` - 通知渠道
+    /// * `alert` - 自检告警
+    fn check_selftest_channel(channel: &NotificationChannel, alert: &Alert) -> AppResult<()> {
+        Self::render_notification(channel, alert)?;
+
+        let keys: &[&str] = match channel.channel_type {
+            ChannelType::Webhook => &["url"],
+            ChannelType::Slack => &["webhook_url"],
+            ChannelType::DingTalk => &["webhook_url", "access_token"],
+            ChannelType::Email => &["email"],
+            ChannelType::Sms => &["phone"],
+        };
+        let configured = keys
+            .iter()
+            .any(|key| channel.config.get(*key).is_some_and(|value| !value.trim().is_empty()));
+        if !configured {
+            return Err(AppError::validation_error(format!("config.{}", keys[0]), "渠道未配置发送目标"));
+        }
         Ok(())
     }
 
+    /// 创建温度规则告警，返回告警ID
+    async fn raise_temperature_rule_alert(&self, rule: &AlertRule, reading: &TemperatureReading) -> AppResult<String> {
+        let severity = self.determine_temperature_severity(reading.temperature, rule);
+        let mut details = HashMap::new();
+        details.insert("sensor_id".to_string(), reading.sensor_id.clone());
+        details.insert("temperature".to_string(), reading.temperature.to_string());
+        details.insert("threshold".to_string(), rule.threshold.to_string());
+
+        self.create_alert_with_rule(
+            AlertType::Temperature,
+            severity,
+            reading.sensor_id.clone(),
+            format!("温度异常: {:.1}°C (阈值: {:.1}°C)", reading.temperature, rule.threshold),
+            Some(details),
+            Some(rule),
+        ).await
+    }
+
+    /// 创建温差告警，返回告警ID
+    async fn raise_gradient_alert(&self, breach: &ThermalGradientBreach) -> AppResult<String> {
+        let rule = &breach.rule;
+        let mut details = HashMap::new();
+        details.insert("gradient_rule_id".to_string(), rule.id.clone());
+        details.insert(rule.sensor_a.clone(), breach.temperature_a.to_string());
+        details.insert(rule.sensor_b.clone(), breach.temperature_b.to_string());
+        details.insert("gradient".to_string(), format!("{:.1}", breach.gradient));
+        details.insert("threshold".to_string(), rule.threshold.to_string());
+        details.insert("duration_secs".to_string(), rule.duration_secs.to_string());
+
+        self.create_alert(
+            AlertType::Temperature,
+            rule.severity.clone(),
+            rule.source(),
+            format!(
+                "{}: {} ({:.1}°C) 与 {} ({:.1}°C) 温差 {:.1}°C 超过 {:.1}°C 已达{}秒，请检查风道或负载分布",
+                rule.name, rule.sensor_a, breach.temperature_a, rule.sensor_b, breach.temperature_b,
+                breach.gradient, rule.threshold, rule.duration_secs
+            ),
+            Some(details),
+        ).await
+    }
+
+    /// 创建风扇规则告警，返回告警ID
+    async fn raise_fan_rule_alert(&self, rule: &AlertRule, reading: &FanReading) -> AppResult<String> {
+        let severity = self.determine_fan_severity(reading, rule);
+        let mut details = HashMap::new();
+        details.insert("fan_id".to_string(), reading.fan_id.clone());
+        details.insert("speed_rpm".to_string(), reading.speed_rpm.to_string());
+        details.insert("speed_percent".to_string(), reading.speed_percent.to_string());
+
+        self.create_alert_with_rule(
+            AlertType::Fan,
+            severity,
+            reading.fan_id.clone(),
+            format!("风扇异常: {} RPM ({:.1}%)", reading.speed_rpm, reading.speed_percent),
+            Some(details),
+            Some(rule),
+        ).await
+    }
+
+    /// 创建风扇持续高负载告警，返回告警ID
+    async fn raise_fan_utilization_alert(&self, rule: &AlertRule, reading: &FanReading) -> AppResult<String> {
+        let mut details = HashMap::new();
+        details.insert("fan_id".to_string(), reading.fan_id.clone());
+        details.insert("speed_percent".to_string(), reading.speed_percent.to_string());
+        details.insert("threshold_percent".to_string(), rule.threshold.to_string());
+        details.insert("duration_secs".to_string(), rule.duration.to_string());
+
+        self.create_alert_with_rule(
+            AlertType::Maintenance,
+            rule.severity.clone(),
+            reading.fan_id.clone(),
+            format!(
+                "风扇 {} 持续高负载: {:.1}% 超过 {:.1}% 已达{}秒，散热可能不足，请检查风道",
+                reading.fan_id, reading.speed_percent, rule.threshold, rule.duration
+            ),
+            Some(details),
+            Some(rule),
+        ).await
+    }
+
+    /// 创建传感器规则告警，返回告警ID
+    async fn raise_sensor_rule_alert(&self, rule: &AlertRule, reading: &SensorReading) -> AppResult<String> {
+        let severity = AlertSeverity::Warning; // 默认警告级别
+        let mut details = HashMap::new();
+        details.insert("sensor_id".to_string(), reading.sensor_id.clone());
+        details.insert("value".to_string(), reading.value.to_string());
+        details.insert("status".to_string(), format!("{:?}", reading.status));
+
+        self.create_alert_with_rule(
+            AlertType::Sensor,
+            severity,
+            reading.sensor_id.clone(),
+            format!("传感器异常: {} = {:.2} {}", reading.sensor_id, reading.value, reading.unit),
+            Some(details),
+            Some(rule),
+        ).await
+    }
+
+    /// BMC自检失败时创建严重告警
+    ///
+    /// 同一BMC已有活跃自检告警时不重复创建，返回新建告警的ID
+    ///
+    /// # 参数
+    /// * `status` - BMC自检状态
+    pub async fn raise_bmc_self_test_alert(
+        &self,
+        status: &BmcSelfTestStatus,
+    ) -> AppResult<Option<String>> {
+        if !status.is_failed() {
+            return Ok(None);
+        }
+
+        let already_active = self
+            .active_alerts
+            .read()
+            .await
+            .values()
+            .any(|alert| alert.source == BMC_ALERT_SOURCE);
+        if already_active {
+            return Ok(None);
+        }
+
+        let mut details = HashMap::new();
+        details.insert("selftest".to_string(), status.summary.clone());
+        details.insert("failed_components".to_string(), status.failed_components.join(", "));
+
+        let alert_id = self.create_alert(
+            AlertType::Hardware,
+            AlertSeverity::Critical,
+            BMC_ALERT_SOURCE.to_string(),
+            format!("BMC自检失败: {}，传感器数据可能不可信", status.summary),
+            Some(details),
+        ).await?;
+        Ok(Some(alert_id))
+    }
+
     /// 创建系统告警
     /// 
     /// # 参数
@@ -465,8 +1794,161 @@ impl AlertService {
         ).await
     }
 
+    /// 获取告警模板列表
+    ///
+    /// # 参数
+    /// * `sort` - 排序方式，`UsageCount` 时最常用的模板在前
+    pub async fn get_alert_templates(&self, sort: AlertTemplateSort) -> AppResult<Vec<AlertTemplate>> {
+        self.template_store.list(sort).await
+    }
+
+    /// 获取指定告警模板
+    ///
+    /// # 参数
+    /// * `template_id` - 模板ID
+    pub async fn get_alert_template(&self, template_id: &str) -> AppResult<AlertTemplate> {
+        self.template_store
+            .get(template_id)
+            .await?
+            .ok_or_else(|| AppError::not_found_error("告警模板", template_id))
+    }
+
+    /// 创建告警模板
+    ///
+    /// # 参数
+    /// * `name` - 模板名称
+    /// * `description` - 描述
+    /// * `alert_type` - 告警类型
+    /// * `severity` - 严重程度
+    /// * `title_template` - 标题模板
+    /// * `message_template` - 消息模板，`{{变量}}` 在创建告警时替换
+    /// * `metadata_template` - 元数据模板
+    pub async fn create_alert_template(
+        &self,
+        name: String,
+        description: Option<String>,
+        alert_type: String,
+        severity: AlertSeverity,
+        title_template: String,
+        message_template: String,
+        metadata_template: Option<serde_json::Value>,
+    ) -> AppResult<AlertTemplate> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation_error("name", "模板名称不能为空"));
+        }
+        Self::parse_alert_type(&alert_type)?;
+
+        let now = Utc::now();
+        let template = AlertTemplate {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            description,
+            alert_type,
+            severity,
+            title_template,
+            message_template,
+            metadata_template,
+            created_at: now,
+            updated_at: now,
+            usage_count: 0,
+        };
+        self.template_store.save(&template).await?;
+
+        info!("告警模板创建完成: {} ({})", template.name, template.id);
+        Ok(template)
+    }
+
+    /// 更新告警模板，未提供的字段保持不变
+    ///
+    /// # 参数
+    /// * `template_id` - 模板ID
+    /// * 其余参数同 `create_alert_template`
+    pub async fn update_alert_template(
+        &self,
+        template_id: &str,
+        name: Option<String>,
+        description: Option<String>,
+        alert_type: Option<String>,
+        severity: Option<AlertSeverity>,
+        title_template: Option<String>,
+        message_template: Option<String>,
+        metadata_template: Option<serde_json::Value>,
+    ) -> AppResult<()> {
+        let mut template = self.get_alert_template(template_id).await?;
+
+        if let Some(alert_type) = alert_type {
+            Self::parse_alert_type(&alert_type)?;
+            template.alert_type = alert_type;
+        }
+        if let Some(name) = name {
+            template.name = name;
+        }
+        template.description = description.or(template.description);
+        template.severity = severity.unwrap_or(template.severity);
+        template.title_template = title_template.unwrap_or(template.title_template);
+        template.message_template = message_template.unwrap_or(template.message_template);
+        template.metadata_template = metadata_template.or(template.metadata_template);
+        template.updated_at = Utc::now();
+
+        // 使用次数由存储维护，保存时不会覆盖
+        self.template_store.save(&template).await
+    }
+
+    /// 删除告警模板
+    ///
+    /// # 参数
+    /// * `template_id` - 模板ID
+    pub async fn delete_alert_template(&self, template_id: &str) -> AppResult<()> {
+        if self.template_store.delete(template_id).await? {
+            info!("告警模板删除完成: {}", template_id);
+            Ok(())
+        } else {
+            Err(AppError::not_found_error("告警模板", template_id))
+        }
+    }
+
+    /// 使用模板创建告警
+    ///
+    /// 告警创建成功后原子地递增模板使用次数
+    ///
+    /// # 参数
+    /// * `template_id` - 模板ID
+    /// * `source` - 告警源
+    /// * `variables` - 模板变量
+    pub async fn create_alert_from_template(
+        &self,
+        template_id: &str,
+        source: String,
+        variables: HashMap<String, String>,
+    ) -> AppResult<String> {
+        let template = self.get_alert_template(template_id).await?;
+        let alert_type = Self::parse_alert_type(&template.alert_type)?;
+        let message = Self::render_template(&template.message_template, &variables);
+
+        let alert_id = self
+            .create_alert(alert_type, template.severity, source, message, Some(variables))
+            .await?;
+
+        let usage_count = self.template_store.increment_usage(template_id).await?;
+        debug!("告警模板 {} 已使用 {} 次", template_id, usage_count);
+        Ok(alert_id)
+    }
+
     // 私有方法
 
+    /// 解析模板中的告警类型
+    fn parse_alert_type(alert_type: &str) -> AppResult<AlertType> {
+        serde_json::from_value(serde_json::Value::String(alert_type.to_string()))
+            .map_err(|_| AppError::validation_error("alert_type", "未知的告警类型"))
+    }
+
+    /// 将 `{{变量}}` 替换为对应的值，未提供的变量保持原样
+    fn render_template(template: &str, variables: &HashMap<String, String>) -> String {
+        variables.iter().fold(template.to_string(), |rendered, (key, value)| {
+            rendered.replace(&format!("{{{{{}}}}}", key), value)
+        })
+    }
+
     /// 初始化默认告警规则
     async fn initialize_default_rules(&self) -> AppResult<()> {
         info!("初始化默认告警规则");
@@ -481,6 +1963,7 @@ impl AlertService {
             rule_type: AlertRuleType::Temperature,
             condition: AlertCondition::GreaterThan,
             threshold: 80.0,
+            threshold_unit: None,
             duration: 60, // 持续60秒
             severity: AlertSeverity::Critical,
             enabled: true,
@@ -496,7 +1979,8 @@ impl AlertService {
             description: "当风扇转速异常时触发告警".to_string(),
             rule_type: AlertRuleType::Fan,
             condition: AlertCondition::LessThan,
-            threshold: 500.0, // RPM
+            threshold: 500.0,
+            threshold_unit: Some(ThresholdUnit::Rpm),
             duration: 30,
             severity: AlertSeverity::Critical,
             enabled: true,
@@ -513,6 +1997,7 @@ impl AlertService {
             rule_type: AlertRuleType::Temperature,
             condition: AlertCondition::GreaterThan,
             threshold: 70.0,
+            threshold_unit: None,
             duration: 120,
             severity: AlertSeverity::Warning,
             enabled: true,
@@ -521,6 +2006,23 @@ impl AlertService {
         };
         rules.insert(temp_warning_rule.id.clone(), temp_warning_rule);
 
+        // 风扇持续高负载规则
+        let fan_utilization_rule = AlertRule {
+            id: "fan_high_utilization".to_string(),
+            name: "风扇持续高负载".to_string(),
+            description: "风扇占空比长时间高于阈值，散热能力可能不足或风道受阻".to_string(),
+            rule_type: AlertRuleType::FanUtilization,
+            condition: AlertCondition::GreaterThan,
+            threshold: 90.0,
+            threshold_unit: Some(ThresholdUnit::Percent),
+            duration: 600,
+            severity: AlertSeverity::Warning,
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        rules.insert(fan_utilization_rule.id.clone(), fan_utilization_rule);
+
         info!("默认告警规则初始化完成，共创建 {} 个规则", rules.len());
         Ok(())
     }
@@ -529,26 +2031,77 @@ impl AlertService {
     async fn start_alert_monitoring(&self) -> AppResult<()> {
         let active_alerts = Arc::clone(&self.active_alerts);
         let alert_stats = Arc::clone(&self.alert_stats);
+        let event_store = Arc::clone(&self.event_store);
+        let alert_events = self.alert_events.clone();
+
+        let monitor = move |heartbeat: Heartbeat| {
+            let active_alerts = Arc::clone(&active_alerts);
+            let alert_stats = Arc::clone(&alert_stats);
+            let event_store = Arc::clone(&event_store);
+            let alert_events = alert_events.clone();
+            async move {
+                let mut interval = interval(Duration::from_secs(30)); // 每30秒检查一次
+                // 已升级的告警只记录一次事件
+                let mut escalated: HashSet<String> = HashSet::new();
+
+                loop {
+                    interval.tick().await;
+                    heartbeat.beat();
+
+                    // 检查告警升级
+                    let alerts = active_alerts.read().await;
+                    escalated.retain(|id| alerts.contains_key(id));
+                    for (alert_id, alert) in alerts.iter() {
+                        // 检查是否需要升级告警
+                        if Self::should_escalate_alert(alert) && escalated.insert(alert_id.clone()) {
+                            debug!("告警需要升级: {}", alert.id);
+                            let level = match Self::severity_event_level(&alert.severity.to_string()) {
+                                "critical" => "critical",
+                                _ => "warning",
+                            };
+                            let transition = AlertTransition::Escalated;
+                            let event = Self::alert_event(alert, transition.event_type(), level, None);
+                            if let Err(e) = event_store.record(event).await {
+                                warn!("记录告警升级事件失败: {} - {}", alert.id, e);
+                            }
+                            let _ = alert_events.send(AlertStreamEvent::new(transition, alert));
+                        }
+                    }
+
+                    // 更新统计信息
+                    let mut stats = alert_stats.write().await;
+                    stats.active_alerts_count = alerts.len();
+                }
+            }
+        };
+
+        let handle = match &self.watchdog {
+            Some(watchdog) => watchdog.supervise("alert_monitor", Some(watchdog.heartbeat_timeout()), monitor),
+            None => tokio::spawn(monitor(Heartbeat::default())),
+        };
+
+        self.task_handles.lock().await.push(handle);
+        Ok(())
+    }
+
+    /// 启动统计保存任务，按固定间隔保存累计统计
+    async fn start_statistics_persistence(&self) -> AppResult<()> {
+        let alert_stats = Arc::clone(&self.alert_stats);
+        let last_stats_reset = Arc::clone(&self.last_stats_reset);
+        let statistics_store = Arc::clone(&self.statistics_store);
 
         let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(30)); // 每30秒检查一次
+            let mut interval = interval(STATISTICS_PERSIST_INTERVAL);
+            // 第一次tick立即返回，此时刚恢复的统计无需回写
+            interval.tick().await;
 
             loop {
                 interval.tick().await;
 
-                // 检查告警升级
-                let alerts = active_alerts.read().await;
-                for alert in alerts.values() {
-                    // 检查是否需要升级告警
-                    if Self::should_escalate_alert(alert) {
-                        debug!("告警需要升级: {}", alert.id);
-                        // 这里可以实现告警升级逻辑
-                    }
+                let snapshot = Self::statistics_snapshot(&alert_stats, &last_stats_reset).await;
+                if let Err(e) = statistics_store.save(&snapshot).await {
+                    warn!("保存告警累计统计失败: {}", e);
                 }
-
-                // 更新统计信息
-                let mut stats = alert_stats.write().await;
-                stats.active_alerts_count = alerts.len();
             }
         });
 
@@ -591,20 +2144,135 @@ impl AlertService {
     }
 
     /// 发送告警通知
-    async fn send_alert_notification(&self, alert: &Alert) -> AppResult<()> {
-        let channels = self.notification_channels.read().await;
-        
-        for channel in channels.values() {
-            if channel.enabled && self.should_send_to_channel(channel, alert) {
-                if let Err(e) = self.send_notification_to_channel(channel, alert).await {
-                    error!("发送通知失败 (渠道: {}): {}", channel.name, e);
-                } else {
-                    debug!("通知发送成功 (渠道: {})", channel.name);
+    ///
+    /// 在告警创建时确定目标渠道，投递在后台进行，立即返回。
+    /// 各告警按创建顺序投递：上一条告警的所有渠道完成后才开始投递下一条，
+    /// 因此同一渠道收到的告警顺序与创建顺序一致；同一告警的各渠道并发投递
+    async fn send_alert_notification(&self, alert: &Alert) {
+        let channels = self
+            .notification_targets(&alert.severity, &alert.alert_type, alert.created_at)
+            .await;
+        if channels.is_empty() {
+            return;
+        }
+
+        let (done, next) = oneshot::channel();
+        let previous = self
+            .last_notification
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(next);
+
+        let service = self.clone();
+        let alert = alert.clone();
+        tokio::spawn(async move {
+            if let Some(previous) = previous {
+                // 上一条投递任务异常退出时发送端被丢弃，同样视为完成
+                let _ = previous.await;
+            }
+            service.deliver_notifications(&alert, channels).await;
+            let _ = done.send(());
+        });
+    }
+
+    /// 并发投递一条告警到各渠道，同时进行的投递不超过 `notification_concurrency`
+    ///
+    /// # 参数
+    /// * `alert` - 告警
+    /// * `channels` - 目标渠道
+    async fn deliver_notifications(&self, alert: &Alert, channels: Vec<NotificationChannel>) {
+        let mut pending = channels.into_iter();
+        let mut in_flight = JoinSet::new();
+
+        loop {
+            while in_flight.len() < self.notification_concurrency {
+                let Some(channel) = pending.next() else { break };
+                let service = self.clone();
+                let alert = alert.clone();
+                in_flight.spawn(async move {
+                    let started = std::time::Instant::now();
+                    let result = service.send_notification_to_channel(&channel, &alert).await;
+                    NotificationDelivery {
+                        alert_id: alert.id.to_string(),
+                        channel_id: channel.id.clone(),
+                        success: result.is_ok(),
+                        error: result.err().map(|e| e.to_string()),
+                        duration_ms: started.elapsed().as_millis() as u64,
+                        delivered_at: Utc::now(),
+                    }
+                });
+            }
+
+            let delivery = match in_flight.join_next().await {
+                Some(Ok(delivery)) => delivery,
+                Some(Err(e)) => {
+                    error!("通知投递任务异常退出 (告警: {}): {}", alert.id, e);
+                    continue;
                 }
+                None => break,
+            };
+            match delivery.error {
+                Some(ref e) => error!("发送通知失败 (渠道: {}): {}", delivery.channel_id, e),
+                None => debug!("通知发送成功 (渠道: {}, 耗时{}ms)", delivery.channel_id, delivery.duration_ms),
+            }
+
+            let mut deliveries = self.notification_deliveries.write().await;
+            deliveries.push_back(delivery);
+            while deliveries.len() > NOTIFICATION_DELIVERY_LIMIT {
+                deliveries.pop_front();
             }
         }
+    }
 
-        Ok(())
+    /// 获取告警的通知投递结果，按完成顺序排列
+    ///
+    /// 投递在后台进行，告警刚创建时结果可能尚不完整
+    ///
+    /// # 参数
+    /// * `alert_id` - 告警ID
+    pub async fn get_notification_deliveries(&self, alert_id: &str) -> Vec<NotificationDelivery> {
+        self.notification_deliveries
+            .read()
+            .await
+            .iter()
+            .filter(|delivery| delivery.alert_id == alert_id)
+            .cloned()
+            .collect()
+    }
+
+    /// 计算告警应发往的已启用渠道，按渠道ID排序
+    ///
+    /// 配置了路由矩阵时按严重程度与告警时间选择渠道，否则按各渠道的严重程度过滤
+    ///
+    /// # 参数
+    /// * `severity` - 告警严重程度
+    /// * `alert_type` - 告警类型
+    /// * `at` - 告警时间
+    async fn notification_targets(
+        &self,
+        severity: &AlertSeverity,
+        alert_type: &AlertType,
+        at: DateTime<Utc>,
+    ) -> Vec<NotificationChannel> {
+        let routed = self
+            .notification_routing
+            .read()
+            .await
+            .as_ref()
+            .map(|routing| routing.channels_for(severity, at));
+
+        let channels = self.notification_channels.read().await;
+        let mut targets: Vec<NotificationChannel> = channels
+            .values()
+            .filter(|channel| channel.enabled)
+            .filter(|channel| match &routed {
+                Some(ids) => ids.contains(&channel.id) && Self::alert_type_allowed(channel, alert_type),
+                None => self.should_send_to_channel(channel, severity, alert_type),
+            })
+            .cloned()
+            .collect();
+        targets.sort_by(|a, b| a.id.cmp(&b.id));
+        targets
     }
 
     /// 发送通知到指定渠道
@@ -628,6 +2296,51 @@ impl AlertService {
         }
     }
 
+    /// 按渠道类型渲染通知内容，发送和预览共用
+    ///
+    /// # 参数
+    /// * `channel` - 通知渠道
+    /// * `alert` - 告警
+    fn render_notification(channel: &NotificationChannel, alert: &Alert) -> AppResult<NotificationPreview> {
+        let (content_type, subject, body) = match channel.channel_type {
+            ChannelType::Webhook => {
+                ("application/json", None, Self::webhook_body(alert)?)
+            }
+            ChannelType::Slack | ChannelType::DingTalk => ("text/markdown", None, Self::markdown_message(alert)),
+            ChannelType::Email => (
+                "text/plain",
+                Some(format!("[{:?}] {:?}告警: {}", alert.severity, alert.alert_type, alert.source)),
+                alert.message.clone(),
+            ),
+            ChannelType::Sms => (
+                "text/plain",
+                None,
+                format!("[{:?}] {}: {}", alert.severity, alert.source, alert.message),
+            ),
+        };
+
+        Ok(NotificationPreview {
+            channel_id: channel.id.clone(),
+            channel_type: channel.channel_type.clone(),
+            content_type: content_type.to_string(),
+            subject,
+            body,
+        })
+    }
+
+    /// Webhook请求体：告警的JSON序列化
+    fn webhook_body(alert: &Alert) -> AppResult<String> {
+        serde_json::to_string(alert).map_err(AppError::from)
+    }
+
+    /// Slack、钉钉使用的Markdown消息
+    fn markdown_message(alert: &Alert) -> String {
+        format!(
+            "*[{:?}] {:?}告警*\n{}\n来源: `{}`",
+            alert.severity, alert.alert_type, alert.message, alert.source
+        )
+    }
+
     /// 发送邮件通知
     async fn send_email_notification(&self, channel: &NotificationChannel, alert: &Alert) -> AppResult<()> {
         // 简化实现，实际应该集成邮件发送服务
@@ -636,16 +2349,44 @@ impl AlertService {
     }
 
     /// 发送Webhook通知
+    ///
+    /// 以JSON投递告警；渠道配置了 `secret` 时附带HMAC签名头，签名方式见 `services::webhook`
     async fn send_webhook_notification(&self, channel: &NotificationChannel, alert: &Alert) -> AppResult<()> {
-        // 简化实现，实际应该发送HTTP请求
-        info!("发送Webhook通知: {} -> {}", alert.message, channel.config.get("url").unwrap_or(&"unknown".to_string()));
+        let webhook_error = |message: String| AppError::ExternalServiceError {
+            service: format!("webhook:{}", channel.id),
+            message,
+        };
+        let url = channel
+            .config
+            .get("url")
+            .ok_or_else(|| webhook_error("渠道未配置url".to_string()))?;
+        let body = Self::webhook_body(alert)
+            .map_err(|e| webhook_error(format!("序列化告警失败: {}", e)))?
+            .into_bytes();
+
+        let response = webhook::build_request(
+            &self.http_client,
+            url,
+            channel.config.get("secret").map(String::as_str),
+            body,
+            Utc::now().timestamp(),
+        )
+        .timeout(WEBHOOK_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| webhook_error(format!("投递失败: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(webhook_error(format!("接收端返回状态码 {}", response.status().as_u16())));
+        }
+
+        info!("发送Webhook通知: {} -> {}", alert.message, url);
         Ok(())
     }
 
     /// 发送Slack通知
     async fn send_slack_notification(&self, channel: &NotificationChannel, alert: &Alert) -> AppResult<()> {
         // 简化实现，实际应该调用Slack API
-        info!("发送Slack通知: {} -> {}", alert.message, channel.config.get("webhook_url").unwrap_or(&"unknown".to_string()));
+        info!("发送Slack通知: {} -> {}", Self::markdown_message(alert), channel.config.get("webhook_url").unwrap_or(&"unknown".to_string()));
         Ok(())
     }
 
@@ -657,22 +2398,28 @@ impl AlertService {
     }
 
     /// 判断是否应该发送到指定渠道
-    fn should_send_to_channel(&self, channel: &NotificationChannel, alert: &Alert) -> bool {
+    fn should_send_to_channel(
+        &self,
+        channel: &NotificationChannel,
+        severity: &AlertSeverity,
+        alert_type: &AlertType,
+    ) -> bool {
         // 检查严重程度过滤
         if let Some(min_severity) = &channel.severity_filter {
-            if !Self::severity_meets_threshold(&alert.severity, min_severity) {
+            if !Self::severity_meets_threshold(severity, min_severity) {
                 return false;
             }
         }
 
-        // 检查告警类型过滤
-        if let Some(alert_types) = &channel.alert_type_filter {
-            if !alert_types.contains(&alert.alert_type) {
-                return false;
-            }
-        }
+        Self::alert_type_allowed(channel, alert_type)
+    }
 
-        true
+    /// 检查渠道的告警类型过滤
+    fn alert_type_allowed(channel: &NotificationChannel, alert_type: &AlertType) -> bool {
+        channel
+            .alert_type_filter
+            .as_ref()
+            .map_or(true, |alert_types| alert_types.contains(alert_type))
     }
 
     /// 检查严重程度是否满足阈值
@@ -692,11 +2439,90 @@ impl AlertService {
         current_level >= threshold_level
     }
 
-    /// 判断是否应该升级告警
-    fn should_escalate_alert(alert: &Alert) -> bool {
-        // 简化的升级逻辑
-        let duration_since_created = Utc::now().signed_duration_since(alert.created_at);
-        
+    /// 查询系统事件
+    ///
+    /// # 参数
+    /// * `query` - 查询条件（级别、类型、分页）
+    pub async fn query_events(
+        &self,
+        query: &SystemEventQuery,
+    ) -> AppResult<PaginatedResponse<SystemEvent>> {
+        if query.page < 1 || !(1..=500).contains(&query.page_size) {
+            return Err(AppError::validation_error(
+                "page_size",
+                "页码必须从1开始，每页大小必须在1-500之间",
+            ));
+        }
+
+        let (events, total) = self.event_store.query(query).await?;
+        Ok(PaginatedResponse::new(events, total, query.page, query.page_size))
+    }
+
+    /// 记录告警生命周期事件并推送给订阅者
+    ///
+    /// 事件写入失败只记录日志，不影响告警本身的处理
+    ///
+    /// # 参数
+    /// * `alert` - 告警
+    /// * `transition` - 状态变化类型
+    /// * `level` - 事件级别
+    /// * `actor` - 操作人
+    async fn emit_alert_event(
+        &self,
+        alert: &Alert,
+        transition: AlertTransition,
+        level: &str,
+        actor: Option<&str>,
+    ) {
+        let event_type = transition.event_type();
+        let event = Self::alert_event(alert, event_type, level, actor);
+        if let Err(e) = self.event_store.record(event).await {
+            warn!("记录告警事件失败: {} {} - {}", event_type, alert.id, e);
+        }
+
+        // 没有订阅者时发送失败，忽略即可
+        let _ = self.alert_events.send(AlertStreamEvent::new(transition, alert));
+    }
+
+    /// 构建告警生命周期事件
+    fn alert_event(
+        alert: &Alert,
+        event_type: &str,
+        level: &str,
+        actor: Option<&str>,
+    ) -> SystemEvent {
+        SystemEvent {
+            id: uuid::Uuid::new_v4(),
+            event_type: event_type.to_string(),
+            event_level: level.to_string(),
+            title: format!("{}: {}", event_type, alert.title),
+            description: alert.message.clone(),
+            source: alert.source.clone(),
+            metadata: Some(serde_json::json!({
+                "alert_id": alert.id.to_string(),
+                "alert_type": alert.alert_type.to_string(),
+                "severity": alert.severity.to_string(),
+                "status": format!("{:?}", alert.status),
+                "actor": actor,
+            })),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// 将告警严重程度映射为事件级别
+    fn severity_event_level(severity: &str) -> &'static str {
+        match severity.to_lowercase().as_str() {
+            "critical" | "error" => "critical",
+            "warning" => "warning",
+            _ => "info",
+        }
+    }
+
+    /// 判断是否应该升级告警
+    fn should_escalate_alert(alert: &Alert) -> bool {
+        // 简化的升级逻辑
+        let duration_since_created = Utc::now().signed_duration_since(alert.created_at);
+        
         match alert.severity {
             AlertSeverity::Critical => duration_since_created.num_minutes() > 15,
             AlertSeverity::Warning => duration_since_created.num_minutes() > 60,
@@ -725,10 +2551,16 @@ impl AlertService {
 
     /// 更新历史记录中的告警
     async fn update_alert_in_history(&self, updated_alert: &Alert) {
-        let mut history = self.alert_history.write().await;
-        
-        if let Some(alert) = history.iter_mut().find(|a| a.id == updated_alert.id) {
-            *alert = updated_alert.clone();
+        {
+            let mut history = self.alert_history.write().await;
+            if let Some(alert) = history.iter_mut().find(|a| a.id == updated_alert.id) {
+                *alert = updated_alert.clone();
+            }
+        }
+
+        // 已移出内存历史的告警同样更新到存储
+        if let Err(e) = self.history_store.upsert(updated_alert).await {
+            warn!("更新告警历史失败: {} - {}", updated_alert.id, e);
         }
     }
 
@@ -778,8 +2610,101 @@ impl AlertService {
         true
     }
 
+    /// 按规则持续时间去抖，条件持续满足 `duration` 秒后才触发告警
+    ///
+    /// # 参数
+    /// * `rule` - 告警规则
+    /// * `source_id` - 告警源
+    /// * `breached` - 本次读数是否满足条件
+    /// * `at` - 读数时间
+    async fn breach_held(
+        &self,
+        rule: &AlertRule,
+        source_id: &str,
+        breached: bool,
+        at: DateTime<Utc>,
+    ) -> bool {
+        let mut breach_starts = self.breach_starts.write().await;
+        track_breach(
+            &mut breach_starts,
+            (rule.id.clone(), source_id.to_string()),
+            breached,
+            rule.duration,
+            at,
+        )
+    }
+
+    /// 记录传感器的温度采样，只保留最近的采样
+    async fn record_temperature_sample(&self, reading: &TemperatureReading) {
+        let mut windows = self.temperature_windows.write().await;
+        let window = windows.entry(reading.sensor_id.clone()).or_default();
+        window.push_back((reading.timestamp, reading.temperature));
+        while window.len() > PREDICTIVE_WINDOW_SAMPLES {
+            window.pop_front();
+        }
+    }
+
+    /// 判断是否需要为规则发出预测告警，需要时返回预计超过阈值的剩余秒数
+    ///
+    /// 仅适用于“大于”条件；当前已超过阈值时由规则本身告警。同一（规则, 传感器）
+    /// 在预测持续成立期间只告警一次
+    async fn predict_temperature_breach(
+        &self,
+        rule: &AlertRule,
+        reading: &TemperatureReading,
+        breached: bool,
+    ) -> Option<f64> {
+        let predicted = if breached
+            || self.predictive_lead_time_secs == 0
+            || !matches!(rule.condition, AlertCondition::GreaterThan)
+        {
+            None
+        } else {
+            let windows = self.temperature_windows.read().await;
+            windows
+                .get(&reading.sensor_id)
+                .and_then(|window| Self::seconds_to_threshold(window, rule.threshold))
+                .filter(|seconds| *seconds <= self.predictive_lead_time_secs as f64)
+        };
+
+        let key = (rule.id.clone(), reading.sensor_id.clone());
+        let mut predicted_breaches = self.predicted_breaches.write().await;
+        match predicted {
+            Some(seconds) if predicted_breaches.insert(key) => Some(seconds),
+            Some(_) => None,
+            None => {
+                predicted_breaches.remove(&key);
+                None
+            }
+        }
+    }
+
+    /// 按温度趋势预计达到阈值的剩余秒数
+    ///
+    /// 对采样做线性回归得到升温速率，采样不足、温度未上升或已达到阈值时返回None
+    fn seconds_to_threshold(samples: &VecDeque<(DateTime<Utc>, f64)>, threshold: f64) -> Option<f64> {
+        if samples.len() < PREDICTIVE_MIN_SAMPLES {
+            return None;
+        }
+
+        let (start, _) = *samples.front()?;
+        let (_, latest) = *samples.back()?;
+        let x_values: Vec<f64> = samples
+            .iter()
+            .map(|(at, _)| (*at - start).num_milliseconds() as f64 / 1000.0)
+            .collect();
+        let y_values: Vec<f64> = samples.iter().map(|(_, value)| *value).collect();
+        let (slope, _, _) = MathUtils::linear_regression(&x_values, &y_values).ok()?;
+
+        if slope <= 0.0 || latest >= threshold {
+            return None;
+        }
+        Some((threshold - latest) / slope)
+    }
+
     /// 评估温度规则
     async fn evaluate_temperature_rule(&self, rule: &AlertRule, reading: &TemperatureReading) -> AppResult<bool> {
+        Self::ensure_no_threshold_unit(rule)?;
         match rule.condition {
             AlertCondition::GreaterThan => Ok(reading.temperature > rule.threshold),
             AlertCondition::LessThan => Ok(reading.temperature < rule.threshold),
@@ -789,7 +2714,7 @@ impl AlertService {
 
     /// 评估风扇规则
     async fn evaluate_fan_rule(&self, rule: &AlertRule, reading: &FanReading) -> AppResult<bool> {
-        let value = reading.speed_rpm as f64;
+        let value = Self::fan_rule_value(rule, reading)?;
         match rule.condition {
             AlertCondition::GreaterThan => Ok(value > rule.threshold),
             AlertCondition::LessThan => Ok(value < rule.threshold),
@@ -797,8 +2722,15 @@ impl AlertService {
         }
     }
 
+    /// 评估风扇高负载规则，占空比高于阈值（百分比）即满足条件
+    fn evaluate_fan_utilization_rule(rule: &AlertRule, reading: &FanReading) -> AppResult<bool> {
+        let percent = ThresholdUnit::Percent.fan_value(reading.speed_rpm as f64, reading.speed_percent)?;
+        Ok(percent > rule.threshold)
+    }
+
     /// 评估传感器规则
     async fn evaluate_sensor_rule(&self, rule: &AlertRule, reading: &SensorReading) -> AppResult<bool> {
+        Self::ensure_no_threshold_unit(rule)?;
         match rule.condition {
             AlertCondition::GreaterThan => Ok(reading.value > rule.threshold),
             AlertCondition::LessThan => Ok(reading.value < rule.threshold),
@@ -820,18 +2752,321 @@ impl AlertService {
     /// 确定风扇告警严重程度
     fn determine_fan_severity(&self, reading: &FanReading, rule: &AlertRule) -> AlertSeverity {
         if reading.speed_rpm == 0 {
-            AlertSeverity::Critical
-        } else if reading.speed_rpm < rule.threshold as u32 {
-            AlertSeverity::Warning
-        } else {
-            AlertSeverity::Info
+            return AlertSeverity::Critical;
+        }
+        match Self::fan_rule_value(rule, reading) {
+            Ok(value) if value < rule.threshold => AlertSeverity::Warning,
+            _ => AlertSeverity::Info,
+        }
+    }
+
+    /// 将风扇读数换算为规则阈值的单位，未指定单位时按RPM比较
+    fn fan_rule_value(rule: &AlertRule, reading: &FanReading) -> AppResult<f64> {
+        rule.threshold_unit
+            .unwrap_or(ThresholdUnit::Rpm)
+            .fan_value(reading.speed_rpm as f64, reading.speed_percent)
+    }
+
+    /// 温度和传感器读数没有转速单位，指定了转速单位的规则无法比较
+    fn ensure_no_threshold_unit(rule: &AlertRule) -> AppResult<()> {
+        match rule.threshold_unit {
+            Some(unit) => Err(AppError::validation_error(
+                "threshold_unit",
+                format!("{:?} 规则不支持阈值单位 {:?}", rule.rule_type, unit),
+            )),
+            None => Ok(()),
         }
     }
 }
 
+#[async_trait]
+impl ControlWriteAlertSink for AlertService {
+    async fn raise_control_write_alert(&self, failed: &FailedControlWrite) -> AppResult<()> {
+        let mut details = HashMap::new();
+        details.insert("fan_id".to_string(), failed.fan_id.clone());
+        details.insert("speed_percent".to_string(), format!("{:.1}", failed.speed_percent));
+        details.insert("consecutive_failures".to_string(), failed.consecutive_failures.to_string());
+        details.insert("last_error".to_string(), failed.last_error.clone());
+
+        self.create_alert(
+            AlertType::Hardware,
+            AlertSeverity::Critical,
+            failed.fan_id.clone(),
+            format!(
+                "风扇 {} 转速下发连续失败{}次，风扇可能停留在错误转速: {}",
+                failed.fan_id, failed.consecutive_failures, failed.last_error
+            ),
+            Some(details),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+#[async_trait]
+impl PowerCapAlertSink for AlertService {
+    async fn raise_power_cap_alert(&self, fan_id: &str, decision: &PowerCapDecision) -> AppResult<()> {
+        let mut details = HashMap::new();
+        details.insert("fan_id".to_string(), fan_id.to_string());
+        details.insert("power_watts".to_string(), format!("{:.0}", decision.power_watts));
+        details.insert("projected_watts".to_string(), format!("{:.0}", decision.projected_watts));
+        details.insert("cap_watts".to_string(), format!("{:.0}", decision.cap_watts));
+        details.insert("requested_percent".to_string(), format!("{:.1}", decision.requested_percent));
+        details.insert("allowed_percent".to_string(), format!("{:.1}", decision.allowed_percent));
+
+        self.create_alert(
+            AlertType::PowerCap,
+            AlertSeverity::Warning,
+            fan_id.to_string(),
+            format!(
+                "风扇 {} 提速至 {:.1}% 预计功耗 {:.0}W 接近功率上限 {:.0}W，实际下发 {:.1}%",
+                fan_id,
+                decision.requested_percent,
+                decision.projected_watts,
+                decision.cap_watts,
+                decision.allowed_percent
+            ),
+            Some(details),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+#[async_trait]
+impl FanDegradationAlertSink for AlertService {
+    async fn raise_fan_degradation_alert(&self, degradation: &FanDegradation) -> AppResult<()> {
+        let mut details = HashMap::new();
+        details.insert("fan_id".to_string(), degradation.fan_id.clone());
+        details.insert("duty_percent".to_string(), format!("{:.1}", degradation.duty_percent));
+        details.insert("expected_rpm".to_string(), format!("{:.0}", degradation.expected_rpm));
+        details.insert("measured_rpm".to_string(), format!("{:.0}", degradation.measured_rpm));
+        details.insert("deviation_percent".to_string(), format!("{:.1}", degradation.deviation_percent));
+        details.insert("since".to_string(), degradation.since.to_rfc3339());
+
+        self.create_alert(
+            AlertType::Hardware,
+            AlertSeverity::Warning,
+            degradation.fan_id.clone(),
+            format!(
+                "风扇 {} 在 {:.1}% 占空比下实测 {:.0} RPM，偏离期望转速 {:.0} RPM {:.1}%，轴承可能磨损",
+                degradation.fan_id,
+                degradation.duty_percent,
+                degradation.measured_rpm,
+                degradation.expected_rpm,
+                degradation.deviation_percent
+            ),
+            Some(details),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+/// 试运行结果中最多返回的触发样本数
+const MAX_DRY_RUN_SAMPLES: usize = 100;
+
+/// 告警状态变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertTransition {
+    /// 新建
+    Created,
+    /// 已确认
+    Acknowledged,
+    /// 已解决
+    Resolved,
+    /// 已升级
+    Escalated,
+}
+
+impl AlertTransition {
+    /// 对应的系统事件类型
+    pub fn event_type(self) -> &'static str {
+        match self {
+            Self::Created => "alert_created",
+            Self::Acknowledged => "alert_acknowledged",
+            Self::Resolved => "alert_resolved",
+            Self::Escalated => "alert_escalated",
+        }
+    }
+
+    /// 推送流中的事件名
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Acknowledged => "acknowledged",
+            Self::Resolved => "resolved",
+            Self::Escalated => "escalated",
+        }
+    }
+}
+
+/// 告警推送事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertStreamEvent {
+    /// 状态变化类型
+    pub transition: AlertTransition,
+    /// 变化后的告警
+    pub alert: Alert,
+    /// 事件时间
+    pub timestamp: DateTime<Utc>,
+}
+
+impl AlertStreamEvent {
+    fn new(transition: AlertTransition, alert: &Alert) -> Self {
+        Self {
+            transition,
+            alert: alert.clone(),
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// 触发告警的规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertTriggerRule {
+    /// 告警ID
+    pub alert_id: String,
+    /// 规则ID
+    pub rule_id: String,
+    /// 告警触发时的规则快照
+    pub triggered_rule: Option<AlertRule>,
+    /// 当前规则定义，规则已删除时为空
+    pub current_rule: Option<AlertRule>,
+}
+
+/// 各告警源最近一次读数
+#[derive(Debug, Clone, Default)]
+struct LatestReadings {
+    temperature: HashMap<String, TemperatureReading>,
+    fan: HashMap<String, FanReading>,
+    sensor: HashMap<String, SensorReading>,
+}
+
+/// 手动评估中满足条件的（规则, 告警源）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleEvaluationMatch {
+    /// 规则ID
+    pub rule_id: String,
+    /// 规则名称
+    pub rule_name: String,
+    /// 告警源（传感器或风扇ID）
+    pub source_id: String,
+    /// 与阈值比较的读数值
+    pub value: f64,
+    /// 本次创建的告警ID，条件持续时间尚未达到规则要求时为空
+    pub alert_id: Option<String>,
+}
+
+impl RuleEvaluationMatch {
+    fn new(rule: &AlertRule, source_id: &str, value: f64, alert_id: Option<String>) -> Self {
+        Self {
+            rule_id: rule.id.clone(),
+            rule_name: rule.name.clone(),
+            source_id: source_id.to_string(),
+            value,
+            alert_id,
+        }
+    }
+}
+
+/// 手动评估结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleEvaluationResult {
+    /// 评估时间
+    pub evaluated_at: DateTime<Utc>,
+    /// 参与评估的启用规则数
+    pub evaluated_rules: usize,
+    /// 参与评估的缓存读数数
+    pub evaluated_readings: usize,
+    /// 满足条件的规则，按规则ID排序
+    pub matches: Vec<RuleEvaluationMatch>,
+    /// 本次创建的告警
+    pub created_alerts: Vec<Alert>,
+}
+
+/// 单个渠道的自检结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelSelfTestResult {
+    /// 渠道ID
+    pub channel_id: String,
+    /// 渠道类型
+    pub channel_type: ChannelType,
+    /// 发送（试运行时为检查）是否成功
+    pub success: bool,
+    /// 失败原因
+    pub error: Option<String>,
+    /// 耗时（毫秒）
+    pub duration_ms: u64,
+}
+
+/// 告警链路自检结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertSelfTestReport {
+    /// 规则触发、告警创建和全部渠道均成功
+    pub success: bool,
+    /// 是否为试运行，试运行不实际发送通知
+    pub dry_run: bool,
+    /// 自检开始时间
+    pub started_at: DateTime<Utc>,
+    /// 临时规则是否被合成读数触发
+    pub rule_triggered: bool,
+    /// 自检告警是否创建成功
+    pub alert_created: bool,
+    /// 各已启用渠道的结果，按渠道ID排序
+    pub channels: Vec<ChannelSelfTestResult>,
+}
+
+/// 规则回放读数
+/// 
+/// 试运行时按规则类型取用对应的历史读数
+#[derive(Debug, Clone, Default)]
+pub struct RuleReplayReadings {
+    /// 温度读数
+    pub temperature: Vec<TemperatureReading>,
+    /// 风扇读数
+    pub fan: Vec<FanReading>,
+    /// 传感器读数
+    pub sensor: Vec<SensorReading>,
+}
+
+/// 规则触发样本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTriggerSample {
+    /// 触发时间
+    pub timestamp: DateTime<Utc>,
+    /// 触发源（传感器或风扇ID）
+    pub source_id: String,
+    /// 触发时的读数值
+    pub value: f64,
+}
+
+/// 规则试运行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDryRunResult {
+    /// 规则ID
+    pub rule_id: String,
+    /// 回放开始时间
+    pub start_time: DateTime<Utc>,
+    /// 回放结束时间
+    pub end_time: DateTime<Utc>,
+    /// 评估的读数数量
+    pub evaluated_samples: usize,
+    /// 预计触发次数
+    pub trigger_count: usize,
+    /// 首次触发时间
+    pub first_triggered_at: Option<DateTime<Utc>>,
+    /// 最后触发时间（样本范围内）
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    /// 触发样本（最多100条）
+    pub samples: Vec<RuleTriggerSample>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::event_broadcast::BroadcastItem;
 
     #[tokio::test]
     async fn test_create_alert() {
@@ -893,10 +3128,925 @@ mod tests {
         assert_eq!(history[0].status, AlertStatus::Resolved);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_template_alerts_count_every_use() {
+        let service = AlertService::new();
+        let template = service.create_alert_template(
+            "高温".to_string(),
+            None,
+            "Temperature".to_string(),
+            AlertSeverity::Warning,
+            "温度过高".to_string(),
+            "{{sensor}} 温度过高".to_string(),
+            None,
+        ).await.unwrap();
+
+        const N: usize = 32;
+        let tasks: Vec<_> = (0..N)
+            .map(|i| {
+                let service = service.clone();
+                let template_id = template.id.clone();
+                tokio::spawn(async move {
+                    let variables = HashMap::from([("sensor".to_string(), format!("cpu{}", i))]);
+                    service
+                        .create_alert_from_template(&template_id, format!("cpu{}", i), variables)
+                        .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let stored = service.get_alert_template(&template.id).await.unwrap();
+        assert_eq!(stored.usage_count, N as u64);
+
+        let templates = service.get_alert_templates(AlertTemplateSort::UsageCount).await.unwrap();
+        assert_eq!(templates[0].id, template.id);
+    }
+
+    #[tokio::test]
+    async fn test_alert_stream_receives_created_event() {
+        let service = AlertService::new();
+        let mut events = service.subscribe_alert_events();
+
+        let alert_id = service.create_alert(
+            AlertType::Temperature,
+            AlertSeverity::Critical,
+            "cpu1".to_string(),
+            "CPU温度过高".to_string(),
+            None,
+        ).await.unwrap();
+
+        let event = match tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .expect("未收到告警推送")
+        {
+            Some(BroadcastItem::Event(event)) => event,
+            other => panic!("意外的推送: {:?}", other),
+        };
+        assert_eq!(event.transition, AlertTransition::Created);
+        assert_eq!(event.alert.id.to_string(), alert_id.to_string());
+        assert_eq!(event.alert.status, AlertStatus::Active);
+    }
+
+    #[tokio::test]
+    async fn test_bmc_self_test_failure_raises_single_critical_alert() {
+        let service = AlertService::new();
+        let failed = BmcSelfTestStatus::parse("Selftest: device error\nFailed components:\n SDR Repository empty\n");
+
+        let alert_id = service.raise_bmc_self_test_alert(&failed).await.unwrap();
+        assert!(alert_id.is_some());
+        // 告警未解决前不重复创建
+        assert!(service.raise_bmc_self_test_alert(&failed).await.unwrap().is_none());
+
+        let alerts = service.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert!(matches!(alerts[0].severity, AlertSeverity::Critical));
+        assert_eq!(alerts[0].source, "bmc");
+
+        let passed = BmcSelfTestStatus::parse("Selftest: passed\n");
+        assert!(service.raise_bmc_self_test_alert(&passed).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_alert_lifecycle_emits_system_events() {
+        let service = AlertService::new();
+
+        let alert_id = service.create_alert(
+            AlertType::Temperature,
+            AlertSeverity::Warning,
+            "cpu1".to_string(),
+            "CPU温度过高".to_string(),
+            None,
+        ).await.unwrap();
+        service.resolve_alert(&alert_id, "operator".to_string()).await.unwrap();
+
+        let query = SystemEventQuery { page: 1, page_size: 10, ..Default::default() };
+        let events = service.query_events(&query).await.unwrap();
+        assert_eq!(events.total, 2);
+
+        // 按时间倒序返回
+        assert_eq!(events.items[0].event_type, "alert_resolved");
+        assert_eq!(events.items[0].event_level, "info");
+        assert_eq!(events.items[1].event_type, "alert_created");
+        assert_eq!(events.items[1].event_level, "warning");
+
+        let warning_only = SystemEventQuery {
+            level: Some("warning".to_string()),
+            ..query
+        };
+        assert_eq!(service.query_events(&warning_only).await.unwrap().total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_rule_counts_triggers() {
+        let service = AlertService::new();
+        service.initialize_default_rules().await.unwrap();
+        let rule = service.get_alert_rule("high_temperature").await.unwrap();
+
+        let now = Utc::now();
+        let temperatures = [75.0, 82.0, 79.0, 85.5, 90.0, 60.0];
+        let readings = RuleReplayReadings {
+            temperature: temperatures.iter().enumerate().map(|(i, temp)| TemperatureReading {
+                id: uuid::Uuid::new_v4(),
+                sensor_id: "cpu1".to_string(),
+                sensor_name: "CPU1 Temp".to_string(),
+                temperature: *temp,
+                status: crate::models::thermal::TemperatureStatus::Normal,
+                timestamp: now - chrono::Duration::minutes((temperatures.len() - i) as i64),
+                server_id: "server-1".to_string(),
+            }).collect(),
+            ..Default::default()
+        };
+
+        let result = service.dry_run_rule(
+            &rule,
+            now - chrono::Duration::hours(1),
+            now,
+            &readings,
+        ).await.unwrap();
+
+        assert_eq!(result.evaluated_samples, 6);
+        assert_eq!(result.trigger_count, 3);
+        assert_eq!(result.samples.iter().map(|s| s.value).collect::<Vec<_>>(), vec![82.0, 85.5, 90.0]);
+
+        // 试运行不应产生真实告警
+        assert!(service.get_active_alerts().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rule_fires_only_after_duration_held() {
+        let service = AlertService::new();
+        service.initialize_default_rules().await.unwrap();
+        let rule = service.get_alert_rule("high_temperature").await.unwrap();
+        assert_eq!(rule.duration, 60);
+
+        let start = Utc::now();
+        let reading = |seconds: i64, temperature: f64| TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: "cpu1".to_string(),
+            sensor_name: "CPU1 Temp".to_string(),
+            temperature,
+            status: crate::models::thermal::TemperatureStatus::Normal,
+            timestamp: start + chrono::Duration::seconds(seconds),
+            server_id: "server-1".to_string(),
+        };
+
+        // 持续30秒的尖峰在恢复后不触发，且持续时间重新计算
+        for (seconds, temperature) in [(0, 85.0), (30, 86.0), (40, 70.0), (70, 85.0)] {
+            service.check_temperature_alert(&reading(seconds, temperature)).await.unwrap();
+        }
+        assert!(service.get_active_alerts().await.is_empty());
+
+        // 从70秒起持续超过60秒后触发
+        service.check_temperature_alert(&reading(100, 88.0)).await.unwrap();
+        assert!(service.get_active_alerts().await.is_empty());
+        service.check_temperature_alert(&reading(131, 88.0)).await.unwrap();
+        let alerts = service.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].source, "cpu1");
+    }
+
+    #[tokio::test]
+    async fn test_sustained_high_fan_utilization_raises_maintenance_alert() {
+        let service = AlertService::new();
+        service.initialize_default_rules().await.unwrap();
+        let rule = service.get_alert_rule("fan_high_utilization").await.unwrap();
+        assert_eq!((rule.threshold, rule.duration), (90.0, 600));
+
+        let start = Utc::now();
+        let reading = |fan_id: &str, seconds: i64, speed_percent: f64| {
+            let mut reading = FanReading::new(
+                fan_id.to_string(),
+                (speed_percent * 48.0) as u32,
+                speed_percent,
+                crate::models::fan::FanStatus::Normal,
+            );
+            reading.timestamp = start + chrono::Duration::seconds(seconds);
+            reading
+        };
+
+        // fan_2 在第300秒降到80%，持续时间从第400秒重新计算，到第700秒仍不足600秒
+        for (seconds, speed_percent) in [(0, 95.0), (300, 80.0), (400, 95.0), (700, 95.0)] {
+            service.check_fan_alert(&reading("fan_2", seconds, speed_percent)).await.unwrap();
+        }
+        assert!(service.get_active_alerts().await.is_empty());
+
+        // fan_1 持续95%超过600秒后触发维护告警
+        for seconds in [0, 300, 599] {
+            service.check_fan_alert(&reading("fan_1", seconds, 95.0)).await.unwrap();
+        }
+        assert!(service.get_active_alerts().await.is_empty());
+        service.check_fan_alert(&reading("fan_1", 610, 95.0)).await.unwrap();
+
+        let alerts = service.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].source, "fan_1");
+        assert_eq!(alerts[0].alert_type, AlertType::Maintenance);
+        assert_eq!(alerts[0].rule_id.as_deref(), Some("fan_high_utilization"));
+    }
+
+    #[tokio::test]
+    async fn test_temperature_alert_carries_matched_rule_id() {
+        let service = AlertService::new();
+        service.initialize_default_rules().await.unwrap();
+
+        let start = Utc::now();
+        let reading = |seconds: i64| TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: "cpu1".to_string(),
+            sensor_name: "CPU1 Temp".to_string(),
+            temperature: 88.0,
+            status: crate::models::thermal::TemperatureStatus::Normal,
+            timestamp: start + chrono::Duration::seconds(seconds),
+            server_id: "server-1".to_string(),
+        };
+        for seconds in [0, 61] {
+            service.check_temperature_alert(&reading(seconds)).await.unwrap();
+        }
+
+        let alerts = service.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_id.as_deref(), Some("high_temperature"));
+
+        // 规则删除后仍可查到触发时的快照
+        let alert_id = alerts[0].id.to_string();
+        service.remove_alert_rule("high_temperature").await.unwrap();
+        let trigger = service.get_alert_trigger_rule(&alert_id).await.unwrap();
+        assert_eq!(trigger.rule_id, "high_temperature");
+        assert_eq!(trigger.triggered_rule.unwrap().id, "high_temperature");
+        assert!(trigger.current_rule.is_none());
+
+        // 手动创建的告警没有对应规则
+        let manual_id = service.create_alert(
+            AlertType::System,
+            AlertSeverity::Info,
+            "manual".to_string(),
+            "手动告警".to_string(),
+            None,
+        ).await.unwrap();
+        assert!(service.get_alert_trigger_rule(&manual_id.to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manual_evaluation_fires_rule_on_cached_reading() {
+        let service = AlertService::new();
+        service.initialize_default_rules().await.unwrap();
+
+        // 90秒前的超温读数，当时高温规则的60秒持续时间尚未满足
+        service.check_temperature_alert(&TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: "cpu1".to_string(),
+            sensor_name: "CPU1 Temp".to_string(),
+            temperature: 88.0,
+            status: crate::models::thermal::TemperatureStatus::Normal,
+            timestamp: Utc::now() - chrono::Duration::seconds(90),
+            server_id: "server-1".to_string(),
+        }).await.unwrap();
+        assert!(service.get_active_alerts().await.is_empty());
+
+        let result = service.evaluate_rules_now().await.unwrap();
+        assert_eq!(result.evaluated_readings, 1);
+
+        // 高温规则已持续满足并告警，温度警告规则需持续120秒，只匹配不告警
+        let matched: Vec<(&str, bool)> = result.matches
+            .iter()
+            .map(|m| (m.rule_id.as_str(), m.alert_id.is_some()))
+            .collect();
+        assert_eq!(matched, vec![("high_temperature", true), ("temperature_warning", false)]);
+        assert_eq!(result.matches[0].source_id, "cpu1");
+        assert_eq!(result.matches[0].value, 88.0);
+        assert_eq!(result.created_alerts.len(), 1);
+        assert_eq!(result.created_alerts[0].rule_id.as_deref(), Some("high_temperature"));
+        assert_eq!(service.get_active_alerts().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rising_temperature_raises_predictive_warning_below_threshold() {
+        let service = AlertService::new().with_predictive_lead_time(300);
+        service.initialize_default_rules().await.unwrap();
+
+        // 每30秒升高1°C，即2°C/分钟
+        let start = Utc::now();
+        let reading = |step: i64| TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: "cpu1".to_string(),
+            sensor_name: "CPU".to_string(),
+            temperature: 70.0 + step as f64,
+            status: crate::models::thermal::TemperatureStatus::Normal,
+            timestamp: start + chrono::Duration::seconds(step * 30),
+            server_id: "server-1".to_string(),
+        };
+
+        // 采样不足时不预测
+        for step in 0..4 {
+            service.check_temperature_alert(&reading(step)).await.unwrap();
+        }
+        assert!(service.get_active_alerts().await.is_empty());
+
+        // 74°C时距80°C阈值约3分钟，在5分钟提前量内
+        service.check_temperature_alert(&reading(4)).await.unwrap();
+        let alerts = service.get_active_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, AlertSeverity::Warning);
+        assert_eq!(alerts[0].rule_id.as_deref(), Some("high_temperature"));
+        assert_eq!(alerts[0].message, "CPU 预计约3分钟后超过80.0°C (当前: 74.0°C)");
+
+        // 预测持续成立期间不重复告警
+        service.check_temperature_alert(&reading(5)).await.unwrap();
+        assert_eq!(service.get_active_alerts().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_blacklisted_sensor_raises_no_alert_and_is_excluded_from_average() {
+        use crate::models::control::{AggregationPolicy, SensorAggregationConfig};
+
+        let blacklist = SensorBlacklist::new(vec!["phantom".to_string()]);
+        let service = AlertService::new().with_sensor_blacklist(blacklist.clone());
+        service.initialize_default_rules().await.unwrap();
+
+        let start = Utc::now();
+        let reading = |sensor_id: &str, temperature: f64, seconds: i64| TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: sensor_id.to_string(),
+            sensor_name: sensor_id.to_string(),
+            temperature,
+            status: crate::models::thermal::TemperatureStatus::Normal,
+            timestamp: start + chrono::Duration::seconds(seconds),
+            server_id: "server-1".to_string(),
+        };
+        for seconds in [0, 61] {
+            service.check_temperature_alert(&reading("phantom", 127.0, seconds)).await.unwrap();
+            service.check_temperature_alert(&reading("cpu1", 50.0, seconds)).await.unwrap();
+        }
+        assert!(service.get_active_alerts().await.is_empty());
+
+        // 与控制循环一致：黑名单中的传感器不参与控制输入聚合
+        let readings: Vec<(String, f64)> = [("cpu1", 50.0), ("cpu2", 60.0), ("phantom", 127.0)]
+            .into_iter()
+            .filter(|(sensor_id, _)| !blacklist.contains(sensor_id))
+            .map(|(sensor_id, temperature)| (sensor_id.to_string(), temperature))
+            .collect();
+        let aggregation = SensorAggregationConfig {
+            policy: AggregationPolicy::Average,
+            sensor_ids: Vec::new(),
+            weights: HashMap::new(),
+        };
+        assert_eq!(aggregation.aggregate(&readings), Some(55.0));
+
+        // 移出黑名单后恢复告警
+        blacklist.replace(Vec::new());
+        for seconds in [120, 181] {
+            service.check_temperature_alert(&reading("phantom", 127.0, seconds)).await.unwrap();
+        }
+        assert_eq!(service.get_active_alerts().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_muted_source_raises_no_active_alert_until_mute_expires() {
+        let service = AlertService::new();
+        service.initialize_default_rules().await.unwrap();
+
+        let mute = service
+            .create_alert_mute(CreateAlertMuteRequest {
+                source: Some("cpu1".to_string()),
+                rule_id: None,
+                expires_at: Utc::now() + chrono::Duration::milliseconds(300),
+                reason: Some("传感器故障待更换".to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(service.get_alert_mutes().await.len(), 1);
+
+        let start = Utc::now();
+        let reading = |sensor_id: &str, seconds: i64| TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: sensor_id.to_string(),
+            sensor_name: sensor_id.to_string(),
+            temperature: 90.0,
+            status: crate::models::thermal::TemperatureStatus::Normal,
+            timestamp: start + chrono::Duration::seconds(seconds),
+            server_id: "server-1".to_string(),
+        };
+        for seconds in [0, 61] {
+            service.check_temperature_alert(&reading("cpu1", seconds)).await.unwrap();
+            service.check_temperature_alert(&reading("cpu2", seconds)).await.unwrap();
+        }
+
+        // 只有未静默的传感器产生活跃告警，被抑制的告警仍记入历史
+        let active = service.get_active_alerts().await;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].source, "cpu2");
+        let history = service.get_alert_history(None).await;
+        assert!(history
+            .iter()
+            .any(|alert| alert.source == "cpu1" && matches!(alert.status, AlertStatus::Ignored)));
+        assert_eq!(service.get_alert_mutes().await[0].suppressed_count, 1);
+
+        // 静默到期后恢复告警
+        tokio::time::sleep(std::time::Duration::from_millis(400)).await;
+        assert!(service.get_alert_mutes().await.is_empty());
+        assert!(service.remove_alert_mute(&mute.id).await.is_err());
+        service.check_temperature_alert(&reading("cpu1", 122)).await.unwrap();
+        assert!(service
+            .get_active_alerts()
+            .await
+            .iter()
+            .any(|alert| alert.source == "cpu1"));
+    }
+
+    #[tokio::test]
+    async fn test_toggled_rule_stops_and_resumes_firing() {
+        let state_store = Arc::new(InMemoryAlertRuleStateStore::new());
+        let service = AlertService::new().with_rule_state_store(state_store.clone());
+        service.initialize_default_rules().await.unwrap();
+
+        let start = Utc::now();
+        let reading = |seconds: i64| TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: "cpu1".to_string(),
+            sensor_name: "CPU1 Temp".to_string(),
+            temperature: 90.0,
+            status: crate::models::thermal::TemperatureStatus::Normal,
+            timestamp: start + chrono::Duration::seconds(seconds),
+            server_id: "server-1".to_string(),
+        };
+
+        // 停用后持续超温也不触发
+        let rule = service.toggle_alert_rule("high_temperature").await.unwrap();
+        assert!(!rule.enabled);
+        assert_eq!(state_store.load().await.unwrap().get("high_temperature"), Some(&false));
+        for seconds in [0, 30, 61, 120] {
+            service.check_temperature_alert(&reading(seconds)).await.unwrap();
+        }
+        assert!(service.get_active_alerts().await.is_empty());
+
+        // 重新启用后从头计时，持续满足后恢复触发
+        let rule = service.toggle_alert_rule("high_temperature").await.unwrap();
+        assert!(rule.enabled);
+        assert!(service.get_alert_rules().await.iter().any(|r| r.id == "high_temperature" && r.enabled));
+        service.check_temperature_alert(&reading(130)).await.unwrap();
+        assert!(service.get_active_alerts().await.is_empty());
+        service.check_temperature_alert(&reading(191)).await.unwrap();
+        assert_eq!(service.get_active_alerts().await.len(), 1);
+
+        // 启停状态在重新加载规则后保留
+        state_store.set_enabled("high_temperature", false).await.unwrap();
+        service.restore_rule_states().await.unwrap();
+        assert!(!service.get_alert_rule("high_temperature").await.unwrap().enabled);
+
+        assert!(service.toggle_alert_rule("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_patch_alert_rule_changes_only_given_fields() {
+        let service = AlertService::new();
+        let before = service.get_alert_rule("high_temperature").await.unwrap();
+
+        let patch = AlertRulePatch {
+            threshold: Some(85.0),
+            ..Default::default()
+        };
+        let rule = service.patch_alert_rule("high_temperature", patch).await.unwrap();
+        assert_eq!(rule.threshold, 85.0);
+        assert_eq!(rule.id, before.id);
+        assert_eq!(rule.name, before.name);
+        assert_eq!(rule.description, before.description);
+        assert_eq!(rule.duration, before.duration);
+        assert_eq!(rule.severity, before.severity);
+        assert_eq!(rule.enabled, before.enabled);
+        assert_eq!(rule.created_at, before.created_at);
+        assert_eq!(service.get_alert_rule("high_temperature").await.unwrap().threshold, 85.0);
+
+        // 合并后名称为空，整体被拒绝，阈值保持上次的修改
+        let invalid = AlertRulePatch {
+            name: Some("   ".to_string()),
+            threshold: Some(95.0),
+            ..Default::default()
+        };
+        assert!(service.patch_alert_rule("high_temperature", invalid).await.is_err());
+        let rule = service.get_alert_rule("high_temperature").await.unwrap();
+        assert_eq!((rule.name.as_str(), rule.threshold), (before.name.as_str(), 85.0));
+
+        assert!(service.patch_alert_rule("high_temperature", AlertRulePatch::default()).await.is_err());
+        let patch = AlertRulePatch {
+            threshold: Some(85.0),
+            ..Default::default()
+        };
+        assert!(service.patch_alert_rule("missing", patch).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_windowed_statistics_exclude_old_alerts() {
+        let service = AlertService::new();
+
+        for _ in 0..3 {
+            service.create_alert(
+                AlertType::Temperature,
+                AlertSeverity::Warning,
+                "test_sensor".to_string(),
+                "测试告警".to_string(),
+                None,
+            ).await.unwrap();
+        }
+
+        // 将其中一条告警移到窗口之外
+        {
+            let mut history = service.alert_history.write().await;
+            history[0].created_at = Utc::now() - chrono::Duration::hours(48);
+        }
+
+        let report = service.get_alert_statistics(24).await.unwrap();
+        assert_eq!(report.window.total_alerts, 2);
+        assert_eq!(report.window_hours, 24);
+
+        let report = service.get_alert_statistics(72).await.unwrap();
+        assert_eq!(report.window.total_alerts, 3);
+
+        // 重置只清零累计统计
+        let reset = service.reset_alert_statistics(Some("admin".to_string()), None).await.unwrap();
+        assert_eq!(reset.previous.total_alerts, 3);
+        let report = service.get_alert_statistics(72).await.unwrap();
+        assert_eq!(report.lifetime.total_alerts, 0);
+        assert_eq!(report.window.total_alerts, 3);
+        assert!(report.last_reset.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_statistics_survive_service_restart() {
+        let statistics_store = Arc::new(InMemoryAlertStatisticsStore::new());
+        let service = AlertService::new().with_statistics_store(statistics_store.clone());
+        service.start().await.unwrap();
+
+        for severity in [AlertSeverity::Critical, AlertSeverity::Warning, AlertSeverity::Critical] {
+            service.create_alert(
+                AlertType::Temperature,
+                severity,
+                "test_sensor".to_string(),
+                "测试告警".to_string(),
+                None,
+            ).await.unwrap();
+        }
+        let before = service.get_alert_statistics(24).await.unwrap().lifetime;
+        assert_eq!(before.total_alerts, 3);
+        service.stop().await.unwrap();
+
+        // 以同一存储重建服务，模拟进程重启
+        let restarted = AlertService::new().with_statistics_store(statistics_store.clone());
+        restarted.start().await.unwrap();
+        let report = restarted.get_alert_statistics(24).await.unwrap();
+        assert_eq!(report.lifetime.total_alerts, before.total_alerts);
+        assert_eq!(report.lifetime.critical_alerts, before.critical_alerts);
+        assert_eq!(report.lifetime.warning_alerts, before.warning_alerts);
+        // 告警历史不随统计保存，窗口统计从零开始
+        assert_eq!(report.window.total_alerts, 0);
+
+        // 重启后继续累计
+        restarted.create_alert(
+            AlertType::Temperature,
+            AlertSeverity::Critical,
+            "test_sensor".to_string(),
+            "测试告警".to_string(),
+            None,
+        ).await.unwrap();
+        restarted.stop().await.unwrap();
+        let saved = statistics_store.load().await.unwrap().unwrap();
+        assert_eq!(saved.lifetime.total_alerts, 4);
+        assert_eq!(saved.lifetime.critical_alerts, before.critical_alerts + 1);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_history_capped_while_store_keeps_everything() {
+        let history_store = Arc::new(InMemoryAlertHistoryStore::new());
+        let service = AlertService::new()
+            .with_history_store(history_store.clone())
+            .with_history_limit(5);
+
+        let mut alert_ids = Vec::new();
+        for i in 0..12 {
+            let alert_id = service.create_alert(
+                AlertType::Temperature,
+                AlertSeverity::Warning,
+                "test_sensor".to_string(),
+                format!("测试告警 {}", i),
+                None,
+            ).await.unwrap();
+            alert_ids.push(alert_id);
+            assert!(service.alert_history.read().await.len() <= 5);
+        }
+
+        // 内存中只剩最近5条
+        let recent: Vec<String> = service.get_alert_history(None).await
+            .iter()
+            .map(|alert| alert.id.to_string())
+            .collect();
+        assert_eq!(recent, alert_ids[7..]);
+
+        // 存储保留全部告警，移出内存的告警解决后仍能在存储中更新
+        assert_eq!(history_store.count().await.unwrap(), 12);
+        let oldest = &alert_ids[0];
+        service.resolve_alert(oldest, "admin".to_string()).await.unwrap();
+        let stored = history_store.get(oldest).await.unwrap().unwrap();
+        assert!(stored.resolved_at.is_some());
+        assert_eq!(service.get_alert(oldest).await.unwrap().id, stored.id);
+    }
+
+    fn fan_rule(threshold: f64, threshold_unit: Option<ThresholdUnit>) -> AlertRule {
+        AlertRule {
+            id: "fan_speed".to_string(),
+            name: "风扇转速告警".to_string(),
+            description: "风扇转速过低".to_string(),
+            rule_type: AlertRuleType::Fan,
+            condition: AlertCondition::LessThan,
+            threshold,
+            threshold_unit,
+            duration: 0,
+            severity: AlertSeverity::Warning,
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_applying_same_named_rule_twice_updates_it_in_place() {
+        let service = AlertService::new();
+
+        let mut rule = fan_rule(800.0, Some(ThresholdUnit::Rpm));
+        rule.id = String::new();
+        let first = service.apply_alert_rule(rule.clone()).await.unwrap();
+
+        rule.threshold = 600.0;
+        let second = service.apply_alert_rule(rule).await.unwrap();
+
+        assert_eq!(second.id, first.id);
+        let rules: Vec<_> = service
+            .get_alert_rules()
+            .await
+            .into_iter()
+            .filter(|r| r.name == "风扇转速告警")
+            .collect();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].threshold, 600.0);
+
+        // 以其他ID添加同名规则被拒绝
+        let mut duplicate = fan_rule(500.0, None);
+        duplicate.id = "another_fan_rule".to_string();
+        assert!(service.add_alert_rule(duplicate).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fan_rules_compare_in_their_threshold_unit() {
+        let service = AlertService::new();
+        // 1200 RPM 对应 25%
+        let reading = FanReading::new(
+            "fan_1".to_string(),
+            1200,
+            25.0,
+            crate::models::fan::FanStatus::Normal,
+        );
+
+        // 百分比规则按百分比比较：25% < 30% 触发，25% < 20% 不触发
+        let percent_rule = fan_rule(30.0, Some(ThresholdUnit::Percent));
+        assert!(service.evaluate_fan_rule(&percent_rule, &reading).await.unwrap());
+        let percent_rule = fan_rule(20.0, Some(ThresholdUnit::Percent));
+        assert!(!service.evaluate_fan_rule(&percent_rule, &reading).await.unwrap());
+
+        // RPM规则（含未指定单位）按RPM比较
+        let rpm_rule = fan_rule(1500.0, Some(ThresholdUnit::Rpm));
+        assert!(service.evaluate_fan_rule(&rpm_rule, &reading).await.unwrap());
+        let rpm_rule = fan_rule(30.0, None);
+        assert!(!service.evaluate_fan_rule(&rpm_rule, &reading).await.unwrap());
+
+        // 百分比读数无效时无法换算
+        let invalid = FanReading::new(
+            "fan_2".to_string(),
+            1200,
+            f64::NAN,
+            crate::models::fan::FanStatus::Normal,
+        );
+        let percent_rule = fan_rule(30.0, Some(ThresholdUnit::Percent));
+        assert!(service.evaluate_fan_rule(&percent_rule, &invalid).await.is_err());
+    }
+
     #[test]
     fn test_severity_threshold() {
         assert!(AlertService::severity_meets_threshold(&AlertSeverity::Critical, &AlertSeverity::Warning));
         assert!(AlertService::severity_meets_threshold(&AlertSeverity::Warning, &AlertSeverity::Info));
         assert!(!AlertService::severity_meets_threshold(&AlertSeverity::Info, &AlertSeverity::Warning));
     }
-}
\ No newline at end of file
+
+    fn routing_channel(id: &str, channel_type: ChannelType) -> NotificationChannel {
+        NotificationChannel {
+            id: id.to_string(),
+            name: id.to_string(),
+            channel_type,
+            config: HashMap::new(),
+            enabled: true,
+            severity_filter: None,
+            alert_type_filter: None,
+        }
+    }
+
+    /// 收到请求后延迟响应的Webhook接收端，返回其URL
+    async fn slow_webhook_receiver(delay: std::time::Duration) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    tokio::time::sleep(delay).await;
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                        .await;
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_slow_channel_does_not_delay_alert_creation() {
+        let service = AlertService::new();
+        let mut slow = routing_channel("slow-hook", ChannelType::Webhook);
+        slow.config.insert(
+            "url".to_string(),
+            slow_webhook_receiver(std::time::Duration::from_millis(500)).await,
+        );
+        service.add_notification_channel(slow).await.unwrap();
+        service.add_notification_channel(routing_channel("slack", ChannelType::Slack)).await.unwrap();
+
+        let started = std::time::Instant::now();
+        let alert_id = service.create_alert(
+            AlertType::Temperature,
+            AlertSeverity::Critical,
+            "CPU1_TEMP".to_string(),
+            "温度过高".to_string(),
+            None,
+        ).await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_millis(300));
+        assert_eq!(service.get_active_alerts().await.len(), 1);
+
+        let deliveries = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            loop {
+                let deliveries = service.get_notification_deliveries(&alert_id).await;
+                if deliveries.len() == 2 {
+                    return deliveries;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // 两个渠道都收到告警，快渠道不必等待慢渠道
+        let channels: Vec<_> = deliveries.iter().map(|d| d.channel_id.as_str()).collect();
+        assert_eq!(channels, vec!["slack", "slow-hook"]);
+        assert!(deliveries.iter().all(|d| d.success));
+        assert!(deliveries[1].duration_ms >= 500);
+    }
+
+    #[tokio::test]
+    async fn test_routing_matrix_escalates_critical_alerts_at_night() {
+        let service = AlertService::new();
+        service.add_notification_channel(routing_channel("slack", ChannelType::Slack)).await.unwrap();
+        service.add_notification_channel(routing_channel("sms", ChannelType::Sms)).await.unwrap();
+
+        let at = |hour: u32| {
+            chrono::NaiveDate::from_ymd_opt(2025, 9, 25).unwrap()
+                .and_hms_opt(hour, 0, 0).unwrap()
+                .and_utc()
+        };
+        let night = TimeOfDayWindow {
+            start: chrono::NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: chrono::NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+        };
+        service.set_notification_routing(Some(NotificationRoutingMatrix {
+            utc_offset_minutes: 0,
+            routes: vec![
+                NotificationRoute {
+                    severities: vec![AlertSeverity::Warning, AlertSeverity::Critical],
+                    time_window: None,
+                    channel_ids: vec!["slack".to_string()],
+                },
+                NotificationRoute {
+                    severities: vec![AlertSeverity::Critical],
+                    time_window: Some(night),
+                    channel_ids: vec!["sms".to_string()],
+                },
+            ],
+        })).await.unwrap();
+
+        let targets = |severity: AlertSeverity, hour: u32| {
+            let service = service.clone();
+            async move {
+                service
+                    .notification_targets(&severity, &AlertType::Temperature, at(hour))
+                    .await
+                    .into_iter()
+                    .map(|channel| channel.id)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // 白天的警告只发往Slack
+        assert_eq!(targets(AlertSeverity::Warning, 14).await, vec!["slack"]);
+        // 夜间的严重告警同时发送短信
+        assert_eq!(targets(AlertSeverity::Critical, 2).await, vec!["slack", "sms"]);
+        assert_eq!(targets(AlertSeverity::Critical, 14).await, vec!["slack"]);
+        assert!(targets(AlertSeverity::Info, 2).await.is_empty());
+
+        // 引用不存在的渠道时拒绝配置
+        let invalid = NotificationRoutingMatrix {
+            utc_offset_minutes: 0,
+            routes: vec![NotificationRoute {
+                severities: vec![AlertSeverity::Critical],
+                time_window: None,
+                channel_ids: vec!["pager".to_string()],
+            }],
+        };
+        assert!(service.set_notification_routing(Some(invalid)).await.is_err());
+    }
+    #[tokio::test]
+    async fn test_preview_renders_webhook_json_and_slack_markdown_without_sending() {
+        let service = AlertService::new();
+        service.add_notification_channel(routing_channel("ops-hook", ChannelType::Webhook)).await.unwrap();
+        service.add_notification_channel(routing_channel("slack", ChannelType::Slack)).await.unwrap();
+
+        let sample = || PreviewNotificationRequest::Alert {
+            alert_type: AlertType::Temperature,
+            severity: AlertSeverity::Critical,
+            source: "CPU1_TEMP".to_string(),
+            message: "CPU温度达到92°C".to_string(),
+        };
+
+        let webhook = service.preview_notification("ops-hook", sample()).await.unwrap();
+        assert_eq!(webhook.content_type, "application/json");
+        let body: serde_json::Value = serde_json::from_str(&webhook.body).unwrap();
+        assert_eq!(body["id"], "preview");
+        assert_eq!(body["alert_type"], "Temperature");
+        assert_eq!(body["severity"], "Critical");
+        assert_eq!(body["source"], "CPU1_TEMP");
+        assert_eq!(body["message"], "CPU温度达到92°C");
+
+        let slack = service.preview_notification("slack", sample()).await.unwrap();
+        assert_eq!(slack.content_type, "text/markdown");
+        assert_eq!(slack.body, "*[Critical] Temperature告警*\nCPU温度达到92°C\n来源: `CPU1_TEMP`");
+
+        // 预览不产生告警
+        assert!(service.get_active_alerts().await.is_empty());
+        assert!(service.preview_notification("pager", sample()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_selftest_reports_working_and_broken_channels_and_cleans_up() {
+        let service = AlertService::new();
+        let mut working = routing_channel("working-hook", ChannelType::Webhook);
+        working.config.insert(
+            "url".to_string(),
+            slow_webhook_receiver(std::time::Duration::ZERO).await,
+        );
+        // 接收端已关闭，连接被拒绝
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut broken = routing_channel("broken-hook", ChannelType::Webhook);
+        broken.config.insert("url".to_string(), format!("http://{}/hook", closed.local_addr().unwrap()));
+        drop(closed);
+        service.add_notification_channel(working).await.unwrap();
+        service.add_notification_channel(broken).await.unwrap();
+
+        let report = service.run_selftest(false).await.unwrap();
+        assert!(report.rule_triggered);
+        assert!(report.alert_created);
+        assert!(!report.success);
+        let results: Vec<_> = report
+            .channels
+            .iter()
+            .map(|channel| (channel.channel_id.as_str(), channel.success))
+            .collect();
+        assert_eq!(results, vec![("broken-hook", false), ("working-hook", true)]);
+        assert!(report.channels[0].error.is_some());
+
+        // 临时规则和告警已清理，不进入历史
+        assert!(service.get_active_alerts().await.is_empty());
+        assert!(service.get_alert_rules().await.is_empty());
+        assert!(service.get_alert_history(None).await.is_empty());
+
+        // 试运行只检查渠道配置，缺少发送目标的渠道失败
+        let dry_run = AlertService::new();
+        let mut configured = routing_channel("slack", ChannelType::Slack);
+        configured.config.insert("webhook_url".to_string(), "https://hooks.slack.com/services/T0/B0/x".to_string());
+        dry_run.add_notification_channel(configured).await.unwrap();
+        let report = dry_run.run_selftest(true).await.unwrap();
+        assert!(report.success && report.dry_run);
+
+        dry_run.add_notification_channel(routing_channel("sms", ChannelType::Sms)).await.unwrap();
+        let report = dry_run.run_selftest(true).await.unwrap();
+        assert!(!report.success);
+        assert!(!report.channels.iter().find(|channel| channel.channel_id == "sms").unwrap().success);
+    }
+}
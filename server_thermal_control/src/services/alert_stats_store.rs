@@ -0,0 +1,121 @@
+use crate::models::alert::{AlertStatistics, AlertStatisticsReset};
+use crate::models::error::AppResult;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+/// 需要跨重启保留的告警统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersistedAlertStatistics {
+    /// 累计统计
+    pub lifetime: AlertStatistics,
+    /// 最近一次重置记录
+    pub last_reset: Option<AlertStatisticsReset>,
+}
+
+/// 告警累计统计存储
+///
+/// 只保存一份最新的统计，每次保存整体覆盖
+#[async_trait]
+pub trait AlertStatisticsStore: Send + Sync {
+    /// 读取已保存的统计，从未保存过时为空
+    async fn load(&self) -> AppResult<Option<PersistedAlertStatistics>>;
+
+    /// 保存统计
+    ///
+    /// # 参数
+    /// * `statistics` - 当前的累计统计和重置记录
+    async fn save(&self, statistics: &PersistedAlertStatistics) -> AppResult<()>;
+}
+
+/// 进程内统计存储
+#[derive(Default)]
+pub struct InMemoryAlertStatisticsStore {
+    statistics: RwLock<Option<PersistedAlertStatistics>>,
+}
+
+impl InMemoryAlertStatisticsStore {
+    /// 创建进程内统计存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AlertStatisticsStore for InMemoryAlertStatisticsStore {
+    async fn load(&self) -> AppResult<Option<PersistedAlertStatistics>> {
+        Ok(self.statistics.read().await.clone())
+    }
+
+    async fn save(&self, statistics: &PersistedAlertStatistics) -> AppResult<()> {
+        *self.statistics.write().await = Some(statistics.clone());
+        Ok(())
+    }
+}
+
+/// PostgreSQL统计存储
+///
+/// 写入 `scripts/init.sql` 中定义的 `alert_statistics` 表，表中只有 `id = 1` 一行
+pub struct PgAlertStatisticsStore {
+    pool: PgPool,
+}
+
+impl PgAlertStatisticsStore {
+    /// 创建数据库统计存储
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AlertStatisticsStore for PgAlertStatisticsStore {
+    #[instrument(level = "debug", name = "db.alert_statistics.load", skip_all)]
+    async fn load(&self) -> AppResult<Option<PersistedAlertStatistics>> {
+        let row = sqlx::query("SELECT lifetime, last_reset FROM alert_statistics WHERE id = 1")
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| -> AppResult<PersistedAlertStatistics> {
+            let lifetime: String = row.try_get("lifetime")?;
+            let last_reset: Option<String> = row.try_get("last_reset")?;
+            Ok(PersistedAlertStatistics {
+                lifetime: serde_json::from_str(&lifetime)?,
+                last_reset: last_reset.map(|reset| serde_json::from_str(&reset)).transpose()?,
+            })
+        })
+        .transpose()
+    }
+
+    #[instrument(level = "debug", name = "db.alert_statistics.save", skip_all)]
+    async fn save(&self, statistics: &PersistedAlertStatistics) -> AppResult<()> {
+        let last_reset = statistics
+            .last_reset
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO alert_statistics (id, lifetime, last_reset, updated_at)
+            VALUES (1, $1, $2, $3)
+            ON CONFLICT (id) DO UPDATE SET
+                lifetime = EXCLUDED.lifetime,
+                last_reset = EXCLUDED.last_reset,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(serde_json::to_string(&statistics.lifetime)?)
+        .bind(last_reset)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,593 @@
+use crate::models::alert::{AlertMute, AlertSeverity, AlertStatistics, AlertStatisticsReport, AlertStatisticsReset};
+use crate::models::error::{AppError, AppResult};
+use crate::models::{Alert, AlertStatus, SystemEvent};
+use crate::services::alert_stats_store::{AlertStatisticsStore, PersistedAlertStatistics};
+use crate::services::event_broadcast::{BroadcastItem, EventBroadcast, EventSubscriber};
+use crate::services::event_store::SystemEventStore;
+use crate::services::incident_store::severity_rank;
+use crate::services::poll_ticker::{PollOverruns, PollTicker};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// 每个告警推送订阅者最多积压的事件数
+pub const ALERT_STREAM_CAPACITY: usize = 256;
+
+/// 滚动窗口统计的最大窗口（小时）
+const MAX_STATISTICS_WINDOW_HOURS: u32 = 24 * 30;
+
+/// 累计统计的保存间隔
+const STATISTICS_PERSIST_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// 告警状态变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertTransition {
+    /// 新告警
+    Created,
+    /// 已确认
+    Acknowledged,
+    /// 已解决
+    Resolved,
+    /// 同一告警升级到更高的严重程度
+    Escalated,
+    /// 被静默匹配，以 `Ignored` 状态只记入告警历史，不推送
+    Suppressed,
+}
+
+impl AlertTransition {
+    /// SSE事件名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertTransition::Created => "created",
+            AlertTransition::Acknowledged => "acknowledged",
+            AlertTransition::Resolved => "resolved",
+            AlertTransition::Escalated => "escalated",
+            AlertTransition::Suppressed => "suppressed",
+        }
+    }
+
+    /// 系统事件类型
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            AlertTransition::Created => "alert_created",
+            AlertTransition::Acknowledged => "alert_acknowledged",
+            AlertTransition::Resolved => "alert_resolved",
+            AlertTransition::Escalated => "alert_escalated",
+            AlertTransition::Suppressed => "alert_suppressed",
+        }
+    }
+}
+
+/// 推送给告警订阅者的状态变化
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertStreamEvent {
+    /// 状态变化
+    pub transition: AlertTransition,
+    /// 变化后的完整告警
+    pub alert: Alert,
+    /// 变化时间
+    pub timestamp: DateTime<Utc>,
+}
+
+impl AlertStreamEvent {
+    /// 转为系统事件，事件级别按告警严重程度：Critical为 `critical`，Warning为 `warning`，其余为 `info`
+    pub fn to_system_event(&self) -> SystemEvent {
+        let event_type = self.transition.event_type();
+        let level = match self.alert.severity.to_ascii_lowercase().as_str() {
+            "critical" | "error" => "critical",
+            "warning" => "warning",
+            _ => "info",
+        };
+        SystemEvent {
+            id: Uuid::new_v4(),
+            event_type: event_type.to_string(),
+            event_level: level.to_string(),
+            title: format!("{}: {}", event_type, self.alert.title),
+            description: self.alert.message.clone(),
+            source: self.alert.source.clone(),
+            metadata: Some(serde_json::json!({
+                "alert_id": self.alert.id.to_string(),
+                "alert_type": self.alert.alert_type,
+                "severity": self.alert.severity,
+                "status": format!("{:?}", self.alert.status),
+                "acknowledged_by": self.alert.acknowledged_by,
+            })),
+            created_at: self.timestamp,
+        }
+    }
+}
+
+/// 待创建的告警
+#[derive(Debug, Clone)]
+pub struct NewAlert {
+    /// 告警类型，如 `temperature`、`fan`
+    pub alert_type: String,
+    /// 严重程度
+    pub severity: AlertSeverity,
+    /// 标题
+    pub title: String,
+    /// 消息
+    pub message: String,
+    /// 告警源（显示名）
+    pub source: String,
+    /// 告警源ID，如传感器ID
+    pub source_id: String,
+    /// 触发告警的规则ID，非规则触发的告警为空
+    pub rule_id: Option<String>,
+}
+
+/// 告警静默及其已抑制的告警
+#[derive(Debug, Clone)]
+struct MuteEntry {
+    mute: AlertMute,
+    /// 已记录过的 (告警类型, 规则ID, 告警源ID)，同一告警在静默期间只记录一次
+    suppressed: HashSet<(String, Option<String>, String)>,
+}
+
+/// 告警是否仍未解决
+fn is_active(alert: &Alert) -> bool {
+    matches!(alert.status, AlertStatus::Triggered | AlertStatus::Acknowledged)
+}
+
+/// 最近告警及其状态变化推送
+///
+/// 内存中保留最近 `limit` 条告警，超出时丢弃最早的告警；状态变化通过 [`EventBroadcast`] 推送给订阅者，
+/// 订阅者读取过慢时丢弃其最旧的事件，不阻塞告警处理。累计统计随状态变化更新，不受淘汰影响
+#[derive(Clone)]
+pub struct AlertStore {
+    alerts: Arc<RwLock<VecDeque<Alert>>>,
+    limit: usize,
+    events: EventBroadcast<AlertStreamEvent>,
+    statistics: Arc<RwLock<PersistedAlertStatistics>>,
+    mutes: Arc<RwLock<Vec<MuteEntry>>>,
+}
+
+impl AlertStore {
+    /// 创建告警存储
+    ///
+    /// # 参数
+    /// * `limit` - 内存中保留的告警条数，至少为1
+    pub fn new(limit: usize) -> Self {
+        Self {
+            alerts: Arc::default(),
+            limit: limit.max(1),
+            events: EventBroadcast::new(ALERT_STREAM_CAPACITY),
+            statistics: Arc::default(),
+            mutes: Arc::default(),
+        }
+    }
+
+    /// 产生告警，返回状态变化和变化后的告警
+    ///
+    /// 同一规则、同一告警源已有未解决的告警时不重复创建：严重程度更高时原告警升级并推送 `escalated`，
+    /// 否则返回None。被有效静默匹配的告警不进入内存、不推送、不计入统计，静默期间首次匹配时
+    /// 返回 `Ignored` 状态的告警（`suppressed`）供调用方记入告警历史
+    ///
+    /// # 参数
+    /// * `new` - 待创建的告警
+    pub fn raise(&self, new: NewAlert) -> Option<(AlertTransition, Alert)> {
+        let now = Utc::now();
+        if let Some(first) = self.suppress(&new, now) {
+            return first.then(|| (AlertTransition::Suppressed, new_alert(new, AlertStatus::Ignored, now)));
+        }
+        let severity = format!("{:?}", new.severity);
+        let mut alerts = self.alerts.write().unwrap_or_else(|e| e.into_inner());
+
+        let existing = alerts.iter_mut().rev().find(|alert| {
+            is_active(alert)
+                && alert.alert_type == new.alert_type
+                && alert.rule_id == new.rule_id
+                && alert.source_id == new.source_id
+        });
+        let (transition, alert) = match existing {
+            Some(alert) if severity_rank(&severity) > severity_rank(&alert.severity) => {
+                alert.severity = severity;
+                alert.title = new.title;
+                alert.message = new.message;
+                alert.updated_at = now;
+                (AlertTransition::Escalated, alert.clone())
+            }
+            Some(_) => return None,
+            None => {
+                let alert = new_alert(new, AlertStatus::Triggered, now);
+                alerts.push_back(alert.clone());
+                while alerts.len() > self.limit {
+                    alerts.pop_front();
+                }
+                self.count(|lifetime| {
+                    lifetime.total_alerts += 1;
+                    lifetime.active_alerts += 1;
+                    *lifetime.by_severity.entry(alert.severity.clone()).or_insert(0) += 1;
+                });
+                (AlertTransition::Created, alert)
+            }
+        };
+        drop(alerts);
+
+        self.publish(transition, &alert);
+        Some((transition, alert))
+    }
+
+    /// 确认告警，已解决的告警不能再确认
+    ///
+    /// # 参数
+    /// * `id` - 告警ID
+    /// * `actor` - 操作人
+    pub fn acknowledge(&self, id: Uuid, actor: &str) -> AppResult<Alert> {
+        self.update(id, AlertTransition::Acknowledged, |alert, now| {
+            if !is_active(alert) {
+                return Err(AppError::ConflictError {
+                    message: format!("告警 {} 已解决，不能再确认", id),
+                });
+            }
+            alert.status = AlertStatus::Acknowledged;
+            alert.acknowledged = true;
+            alert.acknowledged_by = Some(actor.to_string());
+            alert.acknowledged_at = Some(now);
+            Ok(())
+        })
+        .inspect(|_| self.count(|lifetime| lifetime.acknowledged_alerts += 1))
+    }
+
+    /// 解决告警
+    ///
+    /// # 参数
+    /// * `id` - 告警ID
+    pub fn resolve(&self, id: Uuid) -> AppResult<Alert> {
+        self.update(id, AlertTransition::Resolved, |alert, now| {
+            if !is_active(alert) {
+                return Err(AppError::ConflictError {
+                    message: format!("告警 {} 已解决", id),
+                });
+            }
+            alert.status = AlertStatus::Resolved;
+            alert.resolved_at = Some(now);
+            Ok(())
+        })
+        .inspect(|_| {
+            self.count(|lifetime| {
+                lifetime.resolved_alerts += 1;
+                lifetime.active_alerts = (lifetime.active_alerts - 1).max(0);
+            })
+        })
+    }
+
+    /// 获取告警
+    ///
+    /// # 参数
+    /// * `id` - 告警ID
+    pub fn get(&self, id: Uuid) -> AppResult<Alert> {
+        self.alerts
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|alert| alert.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::not_found_error("告警", id.to_string()))
+    }
+
+    /// 内存中的告警，最近的在前
+    pub fn list(&self) -> Vec<Alert> {
+        self.alerts
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
+    }
+
+    /// 未解决（已触发或已确认）的告警，最近的在前
+    pub fn active(&self) -> Vec<Alert> {
+        self.list().into_iter().filter(is_active).collect()
+    }
+
+    /// 添加告警静默
+    ///
+    /// # 参数
+    /// * `mute` - 静默
+    pub fn add_mute(&self, mute: AlertMute) -> AlertMute {
+        self.mutes.write().unwrap_or_else(|e| e.into_inner()).push(MuteEntry {
+            mute: mute.clone(),
+            suppressed: HashSet::new(),
+        });
+        mute
+    }
+
+    /// 仍然有效的告警静默，按到期时间排序；已到期的静默同时清除
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn mutes(&self, now: DateTime<Utc>) -> Vec<AlertMute> {
+        let mut mutes = self.mutes.write().unwrap_or_else(|e| e.into_inner());
+        mutes.retain(|entry| entry.mute.is_active(now));
+        let mut active: Vec<AlertMute> = mutes.iter().map(|entry| entry.mute.clone()).collect();
+        active.sort_by_key(|mute| mute.expires_at);
+        active
+    }
+
+    /// 提前解除告警静默，静默不存在或已到期时返回404
+    ///
+    /// # 参数
+    /// * `id` - 静默ID
+    /// * `now` - 当前时间
+    pub fn remove_mute(&self, id: &str, now: DateTime<Utc>) -> AppResult<AlertMute> {
+        let mut mutes = self.mutes.write().unwrap_or_else(|e| e.into_inner());
+        mutes.retain(|entry| entry.mute.is_active(now));
+        let index = mutes
+            .iter()
+            .position(|entry| entry.mute.id == id)
+            .ok_or_else(|| AppError::not_found_error("告警静默", id))?;
+        Ok(mutes.remove(index).mute)
+    }
+
+    /// 订阅之后的告警状态变化
+    pub fn subscribe(&self) -> EventSubscriber<AlertStreamEvent> {
+        self.events.subscribe()
+    }
+
+    /// 告警统计：累计统计（自上次重置起）和内存中最近告警的滚动窗口统计
+    ///
+    /// # 参数
+    /// * `window_hours` - 滚动窗口长度（小时），1到720
+    pub fn statistics(&self, window_hours: u32) -> AppResult<AlertStatisticsReport> {
+        if window_hours == 0 || window_hours > MAX_STATISTICS_WINDOW_HOURS {
+            return Err(AppError::validation_error(
+                "time_window_hours",
+                format!("统计窗口必须在1-{}小时之间", MAX_STATISTICS_WINDOW_HOURS),
+            ));
+        }
+
+        let window_start = Utc::now() - chrono::Duration::hours(window_hours as i64);
+        let window = {
+            let alerts = self.alerts.read().unwrap_or_else(|e| e.into_inner());
+            AlertStatistics::from_alerts(
+                alerts.iter().filter(|alert| alert.created_at >= window_start),
+                format!("{}h", window_hours),
+            )
+        };
+        let saved = self.statistics_snapshot();
+
+        Ok(AlertStatisticsReport {
+            lifetime: saved.lifetime,
+            window,
+            window_hours,
+            last_reset: saved.last_reset,
+        })
+    }
+
+    /// 清零累计统计，返回重置记录；内存中的告警和滚动窗口统计不受影响
+    ///
+    /// # 参数
+    /// * `reset_by` - 操作人
+    /// * `reason` - 重置原因
+    pub fn reset_statistics(&self, reset_by: Option<String>, reason: Option<String>) -> AlertStatisticsReset {
+        let mut statistics = self.statistics.write().unwrap_or_else(|e| e.into_inner());
+        let record = AlertStatisticsReset {
+            reset_at: Utc::now(),
+            reset_by,
+            reason,
+            previous: std::mem::take(&mut statistics.lifetime),
+        };
+        statistics.lifetime.time_range = "lifetime".to_string();
+        statistics.last_reset = Some(record.clone());
+        record
+    }
+
+    /// 当前的累计统计和重置记录
+    pub fn statistics_snapshot(&self) -> PersistedAlertStatistics {
+        self.statistics.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 从统计存储恢复累计统计，从未保存过时保持为零
+    ///
+    /// # 参数
+    /// * `store` - 统计存储
+    pub async fn restore_statistics(&self, store: &dyn AlertStatisticsStore) -> AppResult<()> {
+        if let Some(saved) = store.load().await? {
+            info!("恢复告警累计统计: 总数={}", saved.lifetime.total_alerts);
+            *self.statistics.write().unwrap_or_else(|e| e.into_inner()) = saved;
+        }
+        Ok(())
+    }
+
+    /// 启动统计保存任务，按固定间隔把累计统计写入统计存储
+    ///
+    /// # 参数
+    /// * `store` - 统计存储
+    /// * `poll_overruns` - 保存超过周期时的跳过计数
+    pub fn start_statistics_task(
+        &self,
+        store: Arc<dyn AlertStatisticsStore>,
+        poll_overruns: PollOverruns,
+    ) -> tokio::task::JoinHandle<()> {
+        let alerts = self.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = PollTicker::new("alert_statistics", STATISTICS_PERSIST_INTERVAL, poll_overruns);
+
+            loop {
+                ticker.tick().await;
+                if let Err(e) = store.save(&alerts.statistics_snapshot()).await {
+                    warn!("保存告警累计统计失败: {}", e);
+                }
+            }
+        })
+    }
+
+    /// 启动系统事件记录任务，把每次告警状态变化写入系统事件存储
+    ///
+    /// 订阅告警推送，写入过慢而丢弃的状态变化只记录警告日志
+    ///
+    /// # 参数
+    /// * `store` - 系统事件存储
+    pub fn start_event_task(&self, store: Arc<dyn SystemEventStore>) -> tokio::task::JoinHandle<()> {
+        let mut subscriber = self.subscribe();
+
+        tokio::spawn(async move {
+            while let Some(item) = subscriber.recv().await {
+                match item {
+                    BroadcastItem::Event(event) => {
+                        if let Err(e) = store.record(event.to_system_event()).await {
+                            warn!("记录告警事件失败: {} - {}", event.alert.id, e);
+                        }
+                    }
+                    BroadcastItem::Lagged(dropped) => warn!("告警事件记录落后，丢弃 {} 条状态变化", dropped),
+                }
+            }
+        })
+    }
+
+    /// 告警被有效静默匹配时返回Some，静默期间首次匹配为true，并计入静默的 `suppressed_count`
+    fn suppress(&self, new: &NewAlert, now: DateTime<Utc>) -> Option<bool> {
+        let mut mutes = self.mutes.write().unwrap_or_else(|e| e.into_inner());
+        let entry = mutes.iter_mut().find(|entry| {
+            entry.mute.is_active(now) && entry.mute.matches(&new.source_id, new.rule_id.as_deref())
+        })?;
+        let first = entry
+            .suppressed
+            .insert((new.alert_type.clone(), new.rule_id.clone(), new.source_id.clone()));
+        if first {
+            entry.mute.suppressed_count += 1;
+        }
+        Some(first)
+    }
+
+    fn count(&self, apply: impl FnOnce(&mut AlertStatistics)) {
+        let mut statistics = self.statistics.write().unwrap_or_else(|e| e.into_inner());
+        statistics.lifetime.time_range = "lifetime".to_string();
+        apply(&mut statistics.lifetime);
+    }
+
+    fn update(
+        &self,
+        id: Uuid,
+        transition: AlertTransition,
+        apply: impl FnOnce(&mut Alert, DateTime<Utc>) -> AppResult<()>,
+    ) -> AppResult<Alert> {
+        let now = Utc::now();
+        let mut alerts = self.alerts.write().unwrap_or_else(|e| e.into_inner());
+        let alert = alerts
+            .iter_mut()
+            .find(|alert| alert.id == id)
+            .ok_or_else(|| AppError::not_found_error("告警", id.to_string()))?;
+        apply(alert, now)?;
+        alert.updated_at = now;
+        let alert = alert.clone();
+        drop(alerts);
+
+        self.publish(transition, &alert);
+        Ok(alert)
+    }
+
+    fn publish(&self, transition: AlertTransition, alert: &Alert) {
+        self.events.send(AlertStreamEvent {
+            transition,
+            alert: alert.clone(),
+            timestamp: alert.updated_at,
+        });
+    }
+}
+
+/// 按待创建的告警生成告警
+fn new_alert(new: NewAlert, status: AlertStatus, now: DateTime<Utc>) -> Alert {
+    Alert {
+        id: Uuid::new_v4(),
+        alert_type: new.alert_type,
+        severity: format!("{:?}", new.severity),
+        title: new.title,
+        message: new.message,
+        source: new.source,
+        source_id: new.source_id,
+        rule_id: new.rule_id,
+        status,
+        acknowledged: false,
+        acknowledged_by: None,
+        acknowledged_at: None,
+        resolved_at: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::event_broadcast::BroadcastItem;
+
+    fn cpu_alert(severity: AlertSeverity) -> NewAlert {
+        NewAlert {
+            alert_type: "temperature".to_string(),
+            severity,
+            title: "高温告警".to_string(),
+            message: "CPU1_TEMP 温度 88.0°C".to_string(),
+            source: "CPU1_TEMP".to_string(),
+            source_id: "CPU1_TEMP".to_string(),
+            rule_id: Some("high_temperature".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_window_statistics_exclude_alerts_older_than_the_window() {
+        let store = AlertStore::new(10);
+        store.raise(cpu_alert(AlertSeverity::Critical)).unwrap();
+        store
+            .raise(NewAlert {
+                source_id: "CPU2_TEMP".to_string(),
+                ..cpu_alert(AlertSeverity::Warning)
+            })
+            .unwrap();
+        // 把CPU1的告警挪到3小时前
+        for alert in store.alerts.write().unwrap().iter_mut().filter(|alert| alert.source_id == "CPU1_TEMP") {
+            alert.created_at = Utc::now() - chrono::Duration::hours(3);
+        }
+
+        let report = store.statistics(1).unwrap();
+        assert_eq!(report.lifetime.total_alerts, 2);
+        assert_eq!(report.window.total_alerts, 1);
+        assert_eq!(report.window.by_severity.get("Critical"), None);
+        assert_eq!(report.window.time_range, "1h");
+
+        assert_eq!(store.statistics(4).unwrap().window.total_alerts, 2);
+        assert!(store.statistics(MAX_STATISTICS_WINDOW_HOURS + 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transitions_are_deduplicated_and_pushed_to_subscribers() {
+        let store = AlertStore::new(10);
+        let mut subscriber = store.subscribe();
+
+        let (transition, alert) = store.raise(cpu_alert(AlertSeverity::Warning)).unwrap();
+        assert_eq!(transition, AlertTransition::Created);
+        // 同一规则和告警源的未解决告警不重复创建，严重程度升高时升级
+        assert!(store.raise(cpu_alert(AlertSeverity::Warning)).is_none());
+        let (transition, escalated) = store.raise(cpu_alert(AlertSeverity::Critical)).unwrap();
+        assert_eq!(transition, AlertTransition::Escalated);
+        assert_eq!(escalated.id, alert.id);
+        assert_eq!(escalated.severity, "Critical");
+
+        store.acknowledge(alert.id, "alice").unwrap();
+        store.resolve(alert.id).unwrap();
+        assert!(store.resolve(alert.id).is_err());
+        assert!(store.acknowledge(Uuid::new_v4(), "alice").is_err());
+
+        let mut received = Vec::new();
+        for _ in 0..4 {
+            match subscriber.recv().await {
+                Some(BroadcastItem::Event(event)) => received.push(event.transition.as_str()),
+                other => panic!("unexpected item: {:?}", other.map(|_| ())),
+            }
+        }
+        assert_eq!(received, ["created", "escalated", "acknowledged", "resolved"]);
+
+        // 解决后再次超限开启新告警
+        let (transition, reopened) = store.raise(cpu_alert(AlertSeverity::Warning)).unwrap();
+        assert_eq!(transition, AlertTransition::Created);
+        assert_ne!(reopened.id, alert.id);
+        assert_eq!(store.list()[0].id, reopened.id);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::models::error::{AppError, AppResult};
+use crate::services::ipmi_service::TemperatureSensor;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// 单个传感器的告警阈值（°C）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SensorAlertThreshold {
+    /// 警告阈值
+    pub warning: f64,
+    /// 严重阈值
+    pub critical: f64,
+}
+
+impl SensorAlertThreshold {
+    /// 校验阈值：必须为有限数且警告阈值低于严重阈值
+    pub fn validate(&self) -> AppResult<()> {
+        if !self.warning.is_finite() || !self.critical.is_finite() {
+            return Err(AppError::validation_error("warning", "阈值必须为有限数"));
+        }
+        if self.warning >= self.critical {
+            return Err(AppError::validation_error("critical", "严重阈值必须高于警告阈值"));
+        }
+        Ok(())
+    }
+
+    /// 按阈值对读数分级，达到严重阈值为 `Critical`，达到警告阈值为 `Warning`
+    ///
+    /// # 参数
+    /// * `value` - 温度（°C）
+    pub fn classify(&self, value: f64) -> ThresholdLevel {
+        if value >= self.critical {
+            ThresholdLevel::Critical
+        } else if value >= self.warning {
+            ThresholdLevel::Warning
+        } else {
+            ThresholdLevel::Within
+        }
+    }
+}
+
+/// 读数相对告警阈值的级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThresholdLevel {
+    /// 未达到警告阈值
+    Within,
+    /// 达到警告阈值
+    Warning,
+    /// 达到严重阈值
+    Critical,
+}
+
+/// 批量设置阈值时选择传感器的方式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorSelector {
+    /// 传感器ID通配符，支持 `*` 和 `?`，不区分大小写
+    Pattern(String),
+    /// 归一化位置，如 `cpu`、`inlet`
+    Location(String),
+}
+
+impl SensorSelector {
+    /// 传感器是否被选中
+    ///
+    /// # 参数
+    /// * `sensor` - 温度传感器读数
+    pub fn matches(&self, sensor: &TemperatureSensor) -> bool {
+        match self {
+            SensorSelector::Pattern(pattern) => glob_matches(pattern.trim(), &sensor.sensor_id),
+            SensorSelector::Location(location) => sensor.location.eq_ignore_ascii_case(location.trim()),
+        }
+    }
+}
+
+/// 按传感器ID保存的告警阈值
+///
+/// 运行时设置，重启后清空，未设置阈值的传感器沿用告警规则中的阈值
+#[derive(Debug, Clone, Default)]
+pub struct AlertThresholdStore {
+    thresholds: Arc<RwLock<BTreeMap<String, SensorAlertThreshold>>>,
+}
+
+impl AlertThresholdStore {
+    /// 传感器的告警阈值
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    pub fn get(&self, sensor_id: &str) -> Option<SensorAlertThreshold> {
+        self.thresholds
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(sensor_id)
+            .copied()
+    }
+
+    /// 所有已设置的阈值（按传感器ID排序）
+    pub fn all(&self) -> BTreeMap<String, SensorAlertThreshold> {
+        self.thresholds.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 替换全部阈值
+    ///
+    /// 调用方负责先校验阈值
+    ///
+    /// # 参数
+    /// * `thresholds` - 按传感器ID排列的告警阈值
+    pub fn replace(&self, thresholds: BTreeMap<String, SensorAlertThreshold>) {
+        *self.thresholds.write().unwrap_or_else(|e| e.into_inner()) = thresholds;
+    }
+
+    /// 为选中的传感器批量设置阈值，返回设置的传感器ID
+    ///
+    /// 先校验阈值再在同一次写锁内全部写入，读取方不会看到只更新了一部分的结果
+    ///
+    /// # 参数
+    /// * `sensors` - 当前的温度传感器读数
+    /// * `selector` - 传感器选择方式
+    /// * `threshold` - 告警阈值
+    pub fn bulk_apply(
+        &self,
+        sensors: &[TemperatureSensor],
+        selector: &SensorSelector,
+        threshold: SensorAlertThreshold,
+    ) -> AppResult<Vec<String>> {
+        threshold.validate()?;
+
+        let mut sensor_ids: Vec<String> = sensors
+            .iter()
+            .filter(|sensor| selector.matches(sensor))
+            .map(|sensor| sensor.sensor_id.clone())
+            .collect();
+        sensor_ids.sort();
+        sensor_ids.dedup();
+
+        let mut thresholds = self.thresholds.write().unwrap_or_else(|e| e.into_inner());
+        for sensor_id in &sensor_ids {
+            thresholds.insert(sensor_id.clone(), threshold);
+        }
+        Ok(sensor_ids)
+    }
+}
+
+/// 通配符匹配，`*` 匹配任意长度字符，`?` 匹配单个字符，不区分大小写
+///
+/// # 参数
+/// * `pattern` - 通配符
+/// * `text` - 待匹配文本
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_uppercase().chars().collect();
+    let text: Vec<char> = text.to_uppercase().chars().collect();
+
+    // 回溯到最近一个 `*`，让它多匹配一个字符
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ipmi_service::parse_temperature_sdr;
+    use chrono::Utc;
+
+    #[test]
+    fn test_bulk_apply_cpu_pattern_only_touches_cpu_sensors() {
+        let sensors = parse_temperature_sdr(
+            "\
+CPU1 Temp        | 0Eh | ok  |  3.1 | 52 degrees C
+CPU2 Temp        | 0Fh | ok  |  3.2 | 49 degrees C
+Inlet Temp       | 04h | ok  |  7.1 | 23 degrees C
+Exhaust Temp     | 01h | ok  |  7.1 | 35 degrees C
+",
+            Utc::now(),
+        );
+        let store = AlertThresholdStore::default();
+        let threshold = SensorAlertThreshold {
+            warning: 75.0,
+            critical: 85.0,
+        };
+
+        let applied = store
+            .bulk_apply(&sensors, &SensorSelector::Pattern("cpu*".to_string()), threshold)
+            .unwrap();
+
+        assert_eq!(applied, vec!["CPU1_TEMP", "CPU2_TEMP"]);
+        assert_eq!(store.get("CPU1_TEMP"), Some(threshold));
+        assert_eq!(store.get("CPU2_TEMP"), Some(threshold));
+        assert_eq!(store.get("INLET_TEMP"), None);
+        assert_eq!(store.get("EXHAUST_TEMP"), None);
+
+        // 阈值无效时不写入任何传感器
+        let invalid = SensorAlertThreshold {
+            warning: 50.0,
+            critical: 40.0,
+        };
+        assert!(store
+            .bulk_apply(&sensors, &SensorSelector::Location("inlet".to_string()), invalid)
+            .is_err());
+        assert_eq!(store.get("INLET_TEMP"), None);
+    }
+}
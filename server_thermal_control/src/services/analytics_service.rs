@@ -13,10 +13,12 @@ use crate::services::{
     thermal_service::ThermalService,
     fan_service::FanService,
     sensor_service::SensorService,
+    fan_cost::estimate_fan_power_watts,
+    result_cache::{get_or_compute, CacheClearScope, ResultCache, SingleFlight},
 };
-use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tracing::{info, warn};
 use chrono::{DateTime, Utc, Duration};
 use serde::{Deserialize, Serialize};
 
@@ -32,7 +34,9 @@ pub struct AnalyticsService {
     /// 传感器服务
     sensor_service: Arc<SensorService>,
     /// 分析结果缓存
-    analysis_cache: Arc<RwLock<HashMap<String, AnalyticsResult>>>,
+    analysis_cache: Arc<dyn ResultCache>,
+    /// 进行中的分析，按缓存键合并并发的相同请求
+    in_flight: Arc<SingleFlight>,
     /// 数学工具
     math_utils: MathUtils,
 }
@@ -44,16 +48,19 @@ impl AnalyticsService {
     /// * `thermal_service` - 温度服务
     /// * `fan_service` - 风扇服务
     /// * `sensor_service` - 传感器服务
+    /// * `analysis_cache` - 分析结果缓存（由 `build_result_cache` 按配置创建）
     pub fn new(
         thermal_service: Arc<ThermalService>,
         fan_service: Arc<FanService>,
         sensor_service: Arc<SensorService>,
+        analysis_cache: Arc<dyn ResultCache>,
     ) -> Self {
         Self {
             thermal_service,
             fan_service,
             sensor_service,
-            analysis_cache: Arc::new(RwLock::new(HashMap::new())),
+            analysis_cache,
+            in_flight: Arc::new(SingleFlight::new()),
             math_utils: MathUtils,
         }
     }
@@ -64,6 +71,12 @@ impl AnalyticsService {
     /// * `sensor_id` - 传感器ID
     /// * `duration_hours` - 分析时间范围（小时）
     pub async fn analyze_temperature_trend(&self, sensor_id: &str, duration_hours: u64) -> AppResult<AnalyticsResult> {
+        let cache_key = format!("temp_trend_{}_{}", sensor_id, duration_hours);
+        self.run_coalesced(cache_key, || self.compute_temperature_trend(sensor_id, duration_hours)).await
+    }
+
+    /// 执行温度趋势分析（实际计算）
+    async fn compute_temperature_trend(&self, sensor_id: &str, duration_hours: u64) -> AppResult<AnalyticsResult> {
         let trend = self.thermal_service.get_temperature_trend(sensor_id, duration_hours).await?;
         let stats = self.thermal_service.get_temperature_stats(sensor_id, duration_hours).await?;
 
@@ -104,9 +117,6 @@ impl AnalyticsService {
             serde_json::to_value(analysis)?,
         );
 
-        // 缓存结果
-        self.cache_result(format!("temp_trend_{}_{}", sensor_id, duration_hours), result.clone()).await;
-
         Ok(result)
     }
 
@@ -116,6 +126,12 @@ impl AnalyticsService {
     /// * `fan_id` - 风扇ID
     /// * `duration_hours` - 分析时间范围（小时）
     pub async fn analyze_fan_efficiency(&self, fan_id: &str, duration_hours: u64) -> AppResult<AnalyticsResult> {
+        let cache_key = format!("fan_efficiency_{}_{}", fan_id, duration_hours);
+        self.run_coalesced(cache_key, || self.compute_fan_efficiency(fan_id, duration_hours)).await
+    }
+
+    /// 执行风扇效率分析（实际计算）
+    async fn compute_fan_efficiency(&self, fan_id: &str, duration_hours: u64) -> AppResult<AnalyticsResult> {
         let fan_stats = self.fan_service.get_fan_stats(fan_id, duration_hours).await?;
         
         // 计算效率指标
@@ -141,9 +157,6 @@ impl AnalyticsService {
             serde_json::to_value(analysis)?,
         );
 
-        // 缓存结果
-        self.cache_result(format!("fan_efficiency_{}_{}", fan_id, duration_hours), result.clone()).await;
-
         Ok(result)
     }
 
@@ -152,6 +165,12 @@ impl AnalyticsService {
     /// # 参数
     /// * `duration_hours` - 分析时间范围（小时）
     pub async fn detect_anomalies(&self, duration_hours: u64) -> AppResult<AnalyticsResult> {
+        let cache_key = format!("anomaly_detection_{}", duration_hours);
+        self.run_coalesced(cache_key, || self.compute_anomalies(duration_hours)).await
+    }
+
+    /// 执行异常检测（实际计算）
+    async fn compute_anomalies(&self, duration_hours: u64) -> AppResult<AnalyticsResult> {
         let mut detected_anomalies = Vec::new();
 
         // 检测温度异常
@@ -210,9 +229,6 @@ impl AnalyticsService {
             serde_json::to_value(analysis)?,
         );
 
-        // 缓存结果
-        self.cache_result(format!("anomaly_detection_{}", duration_hours), result.clone()).await;
-
         Ok(result)
     }
 
@@ -221,6 +237,12 @@ impl AnalyticsService {
     /// # 参数
     /// * `duration_hours` - 分析时间范围（小时）
     pub async fn calculate_performance_metrics(&self, duration_hours: u64) -> AppResult<AnalyticsResult> {
+        let cache_key = format!("performance_metrics_{}", duration_hours);
+        self.run_coalesced(cache_key, || self.compute_performance_metrics(duration_hours)).await
+    }
+
+    /// 计算性能指标（实际计算）
+    async fn compute_performance_metrics(&self, duration_hours: u64) -> AppResult<AnalyticsResult> {
         let mut metrics = Vec::new();
 
         // 系统温度指标
@@ -274,9 +296,6 @@ impl AnalyticsService {
             serde_json::to_value(metrics)?,
         );
 
-        // 缓存结果
-        self.cache_result(format!("performance_metrics_{}", duration_hours), result.clone()).await;
-
         Ok(result)
     }
 
@@ -286,6 +305,12 @@ impl AnalyticsService {
     /// * `aggregation_type` - 聚合类型
     /// * `duration_hours` - 时间范围（小时）
     pub async fn aggregate_data(&self, aggregation_type: AggregationType, duration_hours: u64) -> AppResult<AnalyticsResult> {
+        let cache_key = format!("data_aggregation_{:?}_{}", aggregation_type, duration_hours);
+        self.run_coalesced(cache_key, || self.compute_data_aggregation(aggregation_type, duration_hours)).await
+    }
+
+    /// 执行数据聚合（实际计算）
+    async fn compute_data_aggregation(&self, aggregation_type: AggregationType, duration_hours: u64) -> AppResult<AnalyticsResult> {
         let end_time = Utc::now();
         let start_time = end_time - Duration::hours(duration_hours as i64);
 
@@ -369,9 +394,6 @@ impl AnalyticsService {
             serde_json::to_value(analysis)?,
         );
 
-        // 缓存结果
-        self.cache_result(format!("data_aggregation_{:?}_{}", aggregation_type, duration_hours), result.clone()).await;
-
         Ok(result)
     }
 
@@ -411,37 +433,163 @@ impl AnalyticsService {
         Ok(recommendations)
     }
 
+    /// 生成周期综合报告
+    ///
+    /// 依次执行温度趋势、风扇效率、异常检测和性能指标分析，单个对象分析失败只记入对应章节
+    ///
+    /// # 参数
+    /// * `duration_hours` - 报告周期（小时）
+    pub async fn generate_periodic_report(&self, duration_hours: u64) -> AppResult<PeriodicReport> {
+        if duration_hours == 0 || duration_hours > MAX_REPORT_HOURS {
+            return Err(AppError::validation_error(
+                "duration_hours",
+                format!("报告周期必须在1-{}小时之间", MAX_REPORT_HOURS),
+            ));
+        }
+
+        let period_end = Utc::now();
+        let period_start = period_end - Duration::hours(duration_hours as i64);
+
+        let mut temperature = ReportSection::new(AnalysisType::TemperatureTrend, "温度趋势");
+        for sensor_id in self.thermal_service.get_temperature_sensors().await? {
+            let result = self.analyze_temperature_trend(&sensor_id, duration_hours).await;
+            temperature.record(&sensor_id, result);
+        }
+
+        let mut fans = ReportSection::new(AnalysisType::FanEfficiency, "风扇效率");
+        for fan_id in self.fan_service.get_fan_list().await? {
+            let result = self.analyze_fan_efficiency(&fan_id, duration_hours).await;
+            fans.record(&fan_id, result);
+        }
+
+        let mut anomalies = ReportSection::new(AnalysisType::AnomalyDetection, "异常检测");
+        anomalies.record("system", self.detect_anomalies(duration_hours).await);
+
+        let mut performance = ReportSection::new(AnalysisType::PerformanceMetrics, "性能指标");
+        performance.record("system", self.calculate_performance_metrics(duration_hours).await);
+
+        let recommendations = self.generate_optimization_recommendations(duration_hours).await?;
+
+        Ok(PeriodicReport::assemble(
+            period_start,
+            period_end,
+            vec![temperature, fans, anomalies, performance],
+            recommendations,
+        ))
+    }
+
+    /// 对比两个时间段的温度、风扇和能效统计
+    ///
+    /// 复用各传感器和风扇的统计方法，某个对象在时间段内没有数据时不计入该时间段
+    ///
+    /// # 参数
+    /// * `baseline_start` - 基准时间段开始时间
+    /// * `baseline_end` - 基准时间段结束时间
+    /// * `current_start` - 对比时间段开始时间
+    /// * `current_end` - 对比时间段结束时间
+    pub async fn compare_ranges(
+        &self,
+        baseline_start: DateTime<Utc>,
+        baseline_end: DateTime<Utc>,
+        current_start: DateTime<Utc>,
+        current_end: DateTime<Utc>,
+    ) -> AppResult<RangeComparison> {
+        for (field, start, end) in [
+            ("baseline", baseline_start, baseline_end),
+            ("current", current_start, current_end),
+        ] {
+            if start >= end {
+                return Err(AppError::validation_error(field, "开始时间必须早于结束时间"));
+            }
+        }
+
+        let baseline = self.summarize_range(baseline_start, baseline_end).await?;
+        let current = self.summarize_range(current_start, current_end).await?;
+        Ok(RangeComparison::between(baseline, current))
+    }
+
+    /// 汇总一个时间段内的统计
+    ///
+    /// # 参数
+    /// * `start_time` - 开始时间
+    /// * `end_time` - 结束时间
+    async fn summarize_range(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> AppResult<RangeSummary> {
+        let mut temperature_stats = Vec::new();
+        let mut anomaly_count = 0;
+        for sensor_id in self.thermal_service.get_temperature_sensors().await? {
+            if let Ok(stats) = self.thermal_service.get_temperature_stats_in_range(&sensor_id, start_time, end_time).await {
+                anomaly_count += Self::temperature_anomalies_from_stats(&sensor_id, &stats).len();
+                temperature_stats.push(stats);
+            }
+        }
+
+        let mut fan_stats = Vec::new();
+        let mut efficiency_scores = Vec::new();
+        for fan_id in self.fan_service.get_fan_list().await? {
+            if let Ok(stats) = self.fan_service.get_fan_stats_in_range(&fan_id, start_time, end_time).await {
+                anomaly_count += Self::fan_anomalies_from_stats(&fan_id, &stats).len();
+                efficiency_scores.push(self.calculate_fan_efficiency_score(&stats).await?);
+                fan_stats.push(stats);
+            }
+        }
+
+        Ok(RangeSummary::from_stats(
+            start_time,
+            end_time,
+            &temperature_stats,
+            &fan_stats,
+            &efficiency_scores,
+            anomaly_count,
+        ))
+    }
+
     /// 获取缓存的分析结果
     /// 
     /// # 参数
     /// * `cache_key` - 缓存键
     pub async fn get_cached_result(&self, cache_key: &str) -> Option<AnalyticsResult> {
-        let cache = self.analysis_cache.read().await;
-        cache.get(cache_key).cloned()
+        match self.analysis_cache.get(cache_key).await {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("读取分析缓存失败 ({}): {}", self.analysis_cache.backend_name(), e);
+                None
+            }
+        }
     }
 
     /// 清理过期的缓存
     pub async fn cleanup_cache(&self) -> AppResult<usize> {
-        let mut cache = self.analysis_cache.write().await;
-        let cutoff_time = Utc::now() - Duration::hours(1); // 缓存1小时
-        
-        let original_size = cache.len();
-        cache.retain(|_, result| result.timestamp > cutoff_time);
-        let removed_count = original_size - cache.len();
-        
-        Ok(removed_count)
+        self.analysis_cache.cleanup().await
+    }
+
+    /// 按范围清理分析缓存，返回清理的条目数
+    ///
+    /// 调整参数后立即让旧结果失效，后续请求重新计算
+    ///
+    /// # 参数
+    /// * `scope` - 清理范围
+    pub async fn clear_cache(&self, scope: &CacheClearScope) -> AppResult<usize> {
+        scope.validate()?;
+        let cleared = self.analysis_cache.remove_matching(&|key: &str| scope.matches(key)).await?;
+        info!("清理分析缓存 {:?}: {} 条", scope, cleared);
+        Ok(cleared)
     }
 
     // 私有辅助方法
 
-    /// 缓存分析结果
+    /// 读取缓存，未命中时合并并发的相同分析并缓存结果
+    /// 
+    /// 同一缓存键的分析进行中时，后到的请求共享其结果而不重复计算
     /// 
     /// # 参数
     /// * `cache_key` - 缓存键
-    /// * `result` - 分析结果
-    async fn cache_result(&self, cache_key: String, result: AnalyticsResult) {
-        let mut cache = self.analysis_cache.write().await;
-        cache.insert(cache_key, result);
+    /// * `compute` - 分析计算
+    async fn run_coalesced<F, Fut>(&self, cache_key: String, compute: F) -> AppResult<AnalyticsResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<AnalyticsResult>>,
+    {
+        get_or_compute(self.analysis_cache.as_ref(), &self.in_flight, &cache_key, compute).await
     }
 
     /// 生成温度建议
@@ -625,10 +773,7 @@ impl AnalyticsService {
     /// # 参数
     /// * `speed_percent` - 转速百分比
     fn estimate_power_consumption(&self, speed_percent: f64) -> f64 {
-        // 简化的功耗估算（瓦特）
-        let base_power = 5.0; // 基础功耗
-        let variable_power = (speed_percent / 100.0).powi(3) * 20.0; // 立方关系
-        base_power + variable_power
+        estimate_fan_power_watts(speed_percent)
     }
 
     /// 检测温度异常
@@ -638,6 +783,15 @@ impl AnalyticsService {
     /// * `duration_hours` - 分析时间范围
     async fn detect_temperature_anomalies(&self, sensor_id: &str, duration_hours: u64) -> AppResult<Vec<DetectedAnomaly>> {
         let stats = self.thermal_service.get_temperature_stats(sensor_id, duration_hours).await?;
+        Ok(Self::temperature_anomalies_from_stats(sensor_id, &stats))
+    }
+
+    /// 根据温度统计判断异常
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    /// * `stats` - 温度统计
+    fn temperature_anomalies_from_stats(sensor_id: &str, stats: &TemperatureStats) -> Vec<DetectedAnomaly> {
         let mut anomalies = Vec::new();
 
         // 检测温度峰值
@@ -668,7 +822,7 @@ impl AnalyticsService {
             });
         }
 
-        Ok(anomalies)
+        anomalies
     }
 
     /// 检测风扇异常
@@ -678,6 +832,15 @@ impl AnalyticsService {
     /// * `duration_hours` - 分析时间范围
     async fn detect_fan_anomalies(&self, fan_id: &str, duration_hours: u64) -> AppResult<Vec<DetectedAnomaly>> {
         let stats = self.fan_service.get_fan_stats(fan_id, duration_hours).await?;
+        Ok(Self::fan_anomalies_from_stats(fan_id, &stats))
+    }
+
+    /// 根据风扇统计判断异常
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `stats` - 风扇统计
+    fn fan_anomalies_from_stats(fan_id: &str, stats: &FanStats) -> Vec<DetectedAnomaly> {
         let mut anomalies = Vec::new();
 
         // 检测风扇故障
@@ -708,7 +871,7 @@ impl AnalyticsService {
             });
         }
 
-        Ok(anomalies)
+        anomalies
     }
 
     /// 计算系统稳定性分数
@@ -802,8 +965,488 @@ pub struct OptimizationRecommendation {
     pub estimated_savings: Option<String>,
 }
 
+/// 报告周期上限（小时）
+const MAX_REPORT_HOURS: u64 = 24 * 31;
+
+/// 执行摘要中展示的建议数量
+const TOP_RECOMMENDATION_COUNT: usize = 5;
+
+/// 周期综合报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodicReport {
+    /// 报告ID
+    pub id: String,
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+    /// 周期开始时间
+    pub period_start: DateTime<Utc>,
+    /// 周期结束时间
+    pub period_end: DateTime<Utc>,
+    /// 执行摘要
+    pub summary: ReportSummary,
+    /// 各分析章节
+    pub sections: Vec<ReportSection>,
+    /// 全部优化建议，按优先级降序
+    pub recommendations: Vec<OptimizationRecommendation>,
+}
+
+/// 报告执行摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSummary {
+    /// 关键发现
+    pub key_findings: Vec<String>,
+    /// 优先级最高的建议
+    pub top_recommendations: Vec<OptimizationRecommendation>,
+}
+
+/// 报告章节
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSection {
+    /// 分析类型
+    pub analysis_type: AnalysisType,
+    /// 章节标题
+    pub title: String,
+    /// 分析结果
+    pub results: Vec<AnalyticsResult>,
+    /// 分析失败的对象及原因
+    pub failures: Vec<String>,
+}
+
+impl ReportSection {
+    /// 创建空章节
+    ///
+    /// # 参数
+    /// * `analysis_type` - 分析类型
+    /// * `title` - 章节标题
+    pub fn new(analysis_type: AnalysisType, title: &str) -> Self {
+        Self {
+            analysis_type,
+            title: title.to_string(),
+            results: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    /// 记录一个对象的分析结果
+    ///
+    /// # 参数
+    /// * `target` - 分析对象（传感器、风扇或 `system`）
+    /// * `result` - 分析结果
+    pub fn record(&mut self, target: &str, result: AppResult<AnalyticsResult>) {
+        match result {
+            Ok(result) => self.results.push(result),
+            Err(e) => {
+                warn!("报告章节 {} 分析 {} 失败: {}", self.title, target, e);
+                self.failures.push(format!("{}: {}", target, e));
+            }
+        }
+    }
+
+    /// 提炼本章节的关键发现
+    fn key_findings(&self) -> Vec<String> {
+        let mut findings = Vec::new();
+
+        if self.results.is_empty() {
+            findings.push(format!("{}：无可用数据", self.title));
+        } else {
+            match self.analysis_type {
+                AnalysisType::TemperatureTrend => {
+                    let rising: Vec<String> = self.results.iter()
+                        .filter_map(|r| serde_json::from_value::<TemperatureTrendAnalysis>(r.data.clone()).ok())
+                        .filter(|a| matches!(a.trend_direction, TrendDirection::Increasing))
+                        .map(|a| a.sensor_id)
+                        .collect();
+                    if rising.is_empty() {
+                        findings.push(format!("{} 个传感器温度趋势平稳或下降", self.results.len()));
+                    } else {
+                        findings.push(format!("{} 个传感器温度呈上升趋势: {}", rising.len(), rising.join(", ")));
+                    }
+                }
+                AnalysisType::FanEfficiency => {
+                    let scores: Vec<f64> = self.results.iter()
+                        .filter_map(|r| serde_json::from_value::<FanEfficiencyAnalysis>(r.data.clone()).ok())
+                        .map(|a| a.efficiency_score)
+                        .collect();
+                    if !scores.is_empty() {
+                        let average = scores.iter().sum::<f64>() / scores.len() as f64;
+                        findings.push(format!("{} 个风扇平均效率评分 {:.1}", scores.len(), average));
+                    }
+                }
+                AnalysisType::AnomalyDetection => {
+                    for result in &self.results {
+                        if let Ok(a) = serde_json::from_value::<AnomalyDetectionResult>(result.data.clone()) {
+                            findings.push(format!(
+                                "检测到 {} 个异常，其中高严重度 {} 个",
+                                a.total_anomalies, a.high_severity_count
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !self.failures.is_empty() {
+            findings.push(format!("{}：{} 项分析失败", self.title, self.failures.len()));
+        }
+
+        findings
+    }
+}
+
+impl PeriodicReport {
+    /// 由各章节与建议组装报告
+    ///
+    /// # 参数
+    /// * `period_start` - 周期开始时间
+    /// * `period_end` - 周期结束时间
+    /// * `sections` - 分析章节
+    /// * `recommendations` - 优化建议
+    pub fn assemble(
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        sections: Vec<ReportSection>,
+        mut recommendations: Vec<OptimizationRecommendation>,
+    ) -> Self {
+        // 稳定排序，同优先级保持分析产生的顺序
+        recommendations.sort_by(|a, b| b.priority.cmp(&a.priority));
+
+        let summary = ReportSummary {
+            key_findings: sections.iter().flat_map(ReportSection::key_findings).collect(),
+            top_recommendations: recommendations.iter().take(TOP_RECOMMENDATION_COUNT).cloned().collect(),
+        };
+
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            generated_at: Utc::now(),
+            period_start,
+            period_end,
+            summary,
+            sections,
+            recommendations,
+        }
+    }
+
+    /// 渲染为Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let time_format = "%Y-%m-%d %H:%M UTC";
+
+        md.push_str("# 散热周期报告\n\n");
+        md.push_str(&format!(
+            "周期: {} ~ {}\n\n",
+            self.period_start.format(time_format),
+            self.period_end.format(time_format),
+        ));
+
+        md.push_str("## 执行摘要\n\n### 关键发现\n\n");
+        for finding in &self.summary.key_findings {
+            md.push_str(&format!("- {}\n", finding));
+        }
+
+        md.push_str("\n### 重点建议\n\n");
+        if self.summary.top_recommendations.is_empty() {
+            md.push_str("暂无建议\n");
+        }
+        for (index, rec) in self.summary.top_recommendations.iter().enumerate() {
+            md.push_str(&format!("{}. **[{:?}] {}** — {}\n", index + 1, rec.priority, rec.title, rec.action));
+        }
+
+        for section in &self.sections {
+            md.push_str(&format!("\n## {}\n\n", section.title));
+            if section.results.is_empty() {
+                md.push_str("无可用数据\n");
+            }
+            for result in &section.results {
+                let data = serde_json::to_string_pretty(&result.data).unwrap_or_default();
+                md.push_str(&format!("```json\n{}\n```\n", data));
+            }
+            for failure in &section.failures {
+                md.push_str(&format!("- 分析失败: {}\n", failure));
+            }
+        }
+
+        md
+    }
+
+    /// 报告是否有足够数据：至少一个传感器完成了温度趋势分析
+    pub fn has_sufficient_data(&self) -> bool {
+        self.sections
+            .iter()
+            .any(|section| matches!(section.analysis_type, AnalysisType::TemperatureTrend) && !section.results.is_empty())
+    }
+
+    /// 将执行摘要渲染为HTML，用于邮件正文
+    pub fn to_html_summary(&self) -> String {
+        let mut html = String::new();
+        let time_format = "%Y-%m-%d %H:%M UTC";
+
+        html.push_str("<html><body>\n<h1>散热周期报告</h1>\n");
+        html.push_str(&format!(
+            "<p>周期: {} ~ {}</p>\n",
+            self.period_start.format(time_format),
+            self.period_end.format(time_format),
+        ));
+
+        html.push_str("<h2>关键发现</h2>\n<ul>\n");
+        for finding in &self.summary.key_findings {
+            html.push_str(&format!("<li>{}</li>\n", escape_html(finding)));
+        }
+        html.push_str("</ul>\n");
+
+        html.push_str("<h2>重点建议</h2>\n");
+        if self.summary.top_recommendations.is_empty() {
+            html.push_str("<p>暂无建议</p>\n");
+        } else {
+            html.push_str("<ol>\n");
+            for rec in &self.summary.top_recommendations {
+                html.push_str(&format!(
+                    "<li><strong>[{:?}] {}</strong> — {}</li>\n",
+                    rec.priority,
+                    escape_html(&rec.title),
+                    escape_html(&rec.action)
+                ));
+            }
+            html.push_str("</ol>\n");
+        }
+
+        html.push_str("</body></html>\n");
+        html
+    }
+}
+
+/// 转义HTML文本中的特殊字符
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 平均温度差异显著的z值阈值（约95%置信度）
+const TEMPERATURE_SIGNIFICANCE_Z: f64 = 1.96;
+
+/// 风扇平均转速变化达到该百分点时视为显著
+const FAN_SPEED_SIGNIFICANT_DELTA: f64 = 5.0;
+
+/// 能效评分变化达到该分值时视为显著
+const EFFICIENCY_SIGNIFICANT_DELTA: f64 = 5.0;
+
+/// 异常数量变化达到该数量时视为显著
+const ANOMALY_SIGNIFICANT_DELTA: f64 = 3.0;
+
+/// 一个时间段的统计汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeSummary {
+    /// 开始时间
+    pub start: DateTime<Utc>,
+    /// 结束时间
+    pub end: DateTime<Utc>,
+    /// 所有传感器读数的平均温度
+    pub avg_temperature: Option<f64>,
+    /// 所有传感器读数的温度标准差
+    pub temperature_std_deviation: Option<f64>,
+    /// 温度样本数
+    pub temperature_samples: usize,
+    /// 所有风扇的平均转速百分比
+    pub avg_fan_speed_percent: Option<f64>,
+    /// 风扇平均效率评分
+    pub efficiency_score: Option<f64>,
+    /// 异常数量
+    pub anomaly_count: usize,
+}
+
+impl RangeSummary {
+    /// 由各传感器和风扇的统计汇总
+    ///
+    /// 温度按样本数加权合并各传感器的均值与方差
+    ///
+    /// # 参数
+    /// * `start` - 开始时间
+    /// * `end` - 结束时间
+    /// * `temperature_stats` - 各传感器的温度统计
+    /// * `fan_stats` - 各风扇的统计
+    /// * `efficiency_scores` - 各风扇的效率评分
+    /// * `anomaly_count` - 异常数量
+    pub fn from_stats(
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        temperature_stats: &[TemperatureStats],
+        fan_stats: &[FanStats],
+        efficiency_scores: &[f64],
+        anomaly_count: usize,
+    ) -> Self {
+        let temperature_samples: usize = temperature_stats.iter().map(|s| s.sample_count).sum();
+        let (avg_temperature, temperature_std_deviation) = if temperature_samples == 0 {
+            (None, None)
+        } else {
+            let total = temperature_samples as f64;
+            let mean = temperature_stats
+                .iter()
+                .map(|s| s.avg_temperature * s.sample_count as f64)
+                .sum::<f64>()
+                / total;
+            let variance = temperature_stats
+                .iter()
+                .map(|s| s.sample_count as f64 * (s.std_deviation.powi(2) + (s.avg_temperature - mean).powi(2)))
+                .sum::<f64>()
+                / total;
+            (Some(mean), Some(variance.sqrt()))
+        };
+
+        let fan_samples: usize = fan_stats.iter().map(|s| s.sample_count).sum();
+        let avg_fan_speed_percent = (fan_samples > 0).then(|| {
+            fan_stats
+                .iter()
+                .map(|s| s.avg_speed_percent * s.sample_count as f64)
+                .sum::<f64>()
+                / fan_samples as f64
+        });
+
+        let efficiency_score = (!efficiency_scores.is_empty())
+            .then(|| efficiency_scores.iter().sum::<f64>() / efficiency_scores.len() as f64);
+
+        Self {
+            start,
+            end,
+            avg_temperature,
+            temperature_std_deviation,
+            temperature_samples,
+            avg_fan_speed_percent,
+            efficiency_score,
+            anomaly_count,
+        }
+    }
+}
+
+/// 变化显著性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Significance {
+    /// 变化显著
+    Significant,
+    /// 变化在正常波动范围内
+    NotSignificant,
+    /// 任一时间段缺少数据，无法判断
+    InsufficientData,
+}
+
+/// 单项指标的对比
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricDelta {
+    /// 基准时间段的值
+    pub baseline: Option<f64>,
+    /// 对比时间段的值
+    pub current: Option<f64>,
+    /// 对比时间段减基准时间段
+    pub delta: Option<f64>,
+    /// 显著性
+    pub significance: Significance,
+}
+
+impl MetricDelta {
+    /// 按变化量阈值判断显著性
+    ///
+    /// # 参数
+    /// * `baseline` - 基准值
+    /// * `current` - 对比值
+    /// * `threshold` - 视为显著的最小变化量
+    fn with_threshold(baseline: Option<f64>, current: Option<f64>, threshold: f64) -> Self {
+        let delta = baseline.zip(current).map(|(b, c)| c - b);
+        let significance = match delta {
+            None => Significance::InsufficientData,
+            Some(d) if d.abs() >= threshold => Significance::Significant,
+            Some(_) => Significance::NotSignificant,
+        };
+        Self { baseline, current, delta, significance }
+    }
+}
+
+/// 两个时间段的对比结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeComparison {
+    /// 基准时间段
+    pub baseline: RangeSummary,
+    /// 对比时间段
+    pub current: RangeSummary,
+    /// 平均温度变化（°C），按两段样本的均值差做z检验
+    pub avg_temperature: MetricDelta,
+    /// 风扇平均转速变化（百分点）
+    pub avg_fan_speed_percent: MetricDelta,
+    /// 能效评分变化
+    pub efficiency_score: MetricDelta,
+    /// 异常数量变化
+    pub anomaly_count: MetricDelta,
+}
+
+impl RangeComparison {
+    /// 对比两个时间段
+    ///
+    /// # 参数
+    /// * `baseline` - 基准时间段
+    /// * `current` - 对比时间段
+    pub fn between(baseline: RangeSummary, current: RangeSummary) -> Self {
+        let avg_temperature = {
+            let delta = baseline.avg_temperature.zip(current.avg_temperature).map(|(b, c)| c - b);
+            let significance = match (delta, baseline.temperature_std_deviation, current.temperature_std_deviation) {
+                (Some(delta), Some(sd_b), Some(sd_c))
+                    if baseline.temperature_samples >= 2 && current.temperature_samples >= 2 =>
+                {
+                    let standard_error = (sd_b.powi(2) / baseline.temperature_samples as f64
+                        + sd_c.powi(2) / current.temperature_samples as f64)
+                        .sqrt();
+                    let significant = if standard_error == 0.0 {
+                        delta != 0.0
+                    } else {
+                        (delta / standard_error).abs() >= TEMPERATURE_SIGNIFICANCE_Z
+                    };
+                    if significant {
+                        Significance::Significant
+                    } else {
+                        Significance::NotSignificant
+                    }
+                }
+                _ => Significance::InsufficientData,
+            };
+            MetricDelta {
+                baseline: baseline.avg_temperature,
+                current: current.avg_temperature,
+                delta,
+                significance,
+            }
+        };
+
+        let avg_fan_speed_percent = MetricDelta::with_threshold(
+            baseline.avg_fan_speed_percent,
+            current.avg_fan_speed_percent,
+            FAN_SPEED_SIGNIFICANT_DELTA,
+        );
+        let efficiency_score = MetricDelta::with_threshold(
+            baseline.efficiency_score,
+            current.efficiency_score,
+            EFFICIENCY_SIGNIFICANT_DELTA,
+        );
+        let anomaly_count = MetricDelta::with_threshold(
+            Some(baseline.anomaly_count as f64),
+            Some(current.anomaly_count as f64),
+            ANOMALY_SIGNIFICANT_DELTA,
+        );
+
+        Self {
+            baseline,
+            current,
+            avg_temperature,
+            avg_fan_speed_percent,
+            efficiency_score,
+            anomaly_count,
+        }
+    }
+}
+
 /// 建议优先级
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// 按声明顺序由低到高排序
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RecommendationPriority {
     /// 低
     Low,
@@ -842,6 +1485,143 @@ mod tests {
         assert!(power_100 > 20.0); // 100%转速时功耗应该超过20W
     }
 
+    fn recommendation(title: &str, priority: RecommendationPriority) -> OptimizationRecommendation {
+        OptimizationRecommendation {
+            id: title.to_string(),
+            category: "测试".to_string(),
+            priority,
+            title: title.to_string(),
+            description: String::new(),
+            action: format!("处理{}", title),
+            expected_impact: String::new(),
+            estimated_savings: None,
+        }
+    }
+
+    #[test]
+    fn test_periodic_report_includes_all_sections_and_sorts_recommendations() {
+        let end = Utc::now();
+        let start = end - Duration::hours(168);
+
+        let mut temperature = ReportSection::new(AnalysisType::TemperatureTrend, "温度趋势");
+        temperature.record("cpu1", Err(AppError::validation_error("sensor_id", "数据不足")));
+        let mut fans = ReportSection::new(AnalysisType::FanEfficiency, "风扇效率");
+        fans.record("fan1", Ok(AnalyticsResult::new(AnalysisType::FanEfficiency, serde_json::json!({}))));
+        let anomalies = ReportSection::new(AnalysisType::AnomalyDetection, "异常检测");
+        let performance = ReportSection::new(AnalysisType::PerformanceMetrics, "性能指标");
+
+        let recommendations = (0..7)
+            .map(|i| {
+                let priority = match i % 4 {
+                    0 => RecommendationPriority::Low,
+                    1 => RecommendationPriority::High,
+                    2 => RecommendationPriority::Medium,
+                    _ => RecommendationPriority::Critical,
+                };
+                recommendation(&format!("建议{}", i), priority)
+            })
+            .collect();
+
+        let report = PeriodicReport::assemble(
+            start,
+            end,
+            vec![temperature, fans, anomalies, performance],
+            recommendations,
+        );
+
+        let titles: Vec<&str> = report.sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["温度趋势", "风扇效率", "异常检测", "性能指标"]);
+        assert_eq!(report.sections[0].failures.len(), 1);
+        assert_eq!(report.sections[1].results.len(), 1);
+
+        let priorities: Vec<RecommendationPriority> = report.summary.top_recommendations
+            .iter()
+            .map(|r| r.priority.clone())
+            .collect();
+        assert_eq!(priorities.len(), TOP_RECOMMENDATION_COUNT);
+        assert!(priorities.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(priorities[0], RecommendationPriority::Critical);
+        assert_eq!(report.recommendations.len(), 7);
+        // 同优先级保持原有顺序
+        assert_eq!(report.summary.top_recommendations[0].title, "建议3");
+        assert_eq!(report.summary.top_recommendations[1].title, "建议1");
+
+        assert!(report.summary.key_findings.iter().any(|f| f.contains("温度趋势") && f.contains("失败")));
+
+        let markdown = report.to_markdown();
+        for title in titles {
+            assert!(markdown.contains(&format!("## {}", title)));
+        }
+        assert!(markdown.contains("1. **[Critical] 建议3**"));
+    }
+
+    /// 由种子读数计算一个传感器在时间段内的温度统计
+    fn seeded_temperature_stats(
+        sensor_id: &str,
+        values: &[f64],
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> TemperatureStats {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        TemperatureStats {
+            sensor_id: sensor_id.to_string(),
+            min_temperature: values.iter().cloned().fold(f64::INFINITY, f64::min),
+            max_temperature: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            avg_temperature: mean,
+            std_deviation: variance.sqrt(),
+            sample_count: values.len(),
+            normal_count: values.len(),
+            warning_count: 0,
+            critical_count: 0,
+            start_time,
+            end_time,
+        }
+    }
+
+    #[test]
+    fn test_hotter_second_week_yields_significant_positive_temperature_delta() {
+        let week1_start = Utc::now() - Duration::weeks(2);
+        let week1_end = week1_start + Duration::weeks(1);
+        let week2_end = week1_end + Duration::weeks(1);
+
+        // 每小时一个读数，第二周整体高约8°C
+        let wave = |base: f64| -> Vec<f64> {
+            (0..168).map(|hour| base + ((hour % 24) as f64 - 12.0) * 0.5).collect()
+        };
+        let summarize = |start, end, cpu_base: f64, board_base: f64, anomalies| {
+            let stats = vec![
+                seeded_temperature_stats("cpu1", &wave(cpu_base), start, end),
+                seeded_temperature_stats("board", &wave(board_base), start, end),
+            ];
+            let anomalies_from_stats: usize = stats
+                .iter()
+                .map(|s| AnalyticsService::temperature_anomalies_from_stats(&s.sensor_id, s).len())
+                .sum();
+            RangeSummary::from_stats(start, end, &stats, &[], &[80.0], anomalies + anomalies_from_stats)
+        };
+
+        let week1 = summarize(week1_start, week1_end, 55.0, 40.0, 0);
+        let week2 = summarize(week1_end, week2_end, 63.0, 48.0, 4);
+        let comparison = RangeComparison::between(week1, week2);
+
+        let delta = comparison.avg_temperature.delta.unwrap();
+        assert!((delta - 8.0).abs() < 1e-9);
+        assert_eq!(comparison.avg_temperature.significance, Significance::Significant);
+        assert_eq!(comparison.anomaly_count.delta, Some(4.0));
+        assert_eq!(comparison.anomaly_count.significance, Significance::Significant);
+        assert_eq!(comparison.efficiency_score.significance, Significance::NotSignificant);
+        assert_eq!(comparison.avg_fan_speed_percent.significance, Significance::InsufficientData);
+
+        // 两段相同时温度变化不显著
+        let same = RangeComparison::between(
+            summarize(week1_start, week1_end, 55.0, 40.0, 0),
+            summarize(week1_end, week2_end, 55.0, 40.0, 0),
+        );
+        assert_eq!(same.avg_temperature.delta, Some(0.0));
+        assert_eq!(same.avg_temperature.significance, Significance::NotSignificant);
+    }
+
     fn create_test_analytics_service() -> AnalyticsService {
         // 创建测试用的分析服务
         // 这里需要模拟的服务实例
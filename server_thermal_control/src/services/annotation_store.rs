@@ -0,0 +1,315 @@
+use crate::models::error::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use tokio::sync::RwLock;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// 单次查询最多返回的注释数
+pub const MAX_RANGE_ANNOTATIONS: usize = 1_000;
+
+/// 注释内容的最大字符数
+const MAX_ANNOTATION_TEXT_CHARS: usize = 1_000;
+
+/// 未指定开始时间时默认查询的时长（小时）
+const DEFAULT_QUERY_HOURS: i64 = 24;
+
+/// 操作人在时间序列上添加的注释，如"更换了3号风扇"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// 注释ID
+    pub id: Uuid,
+    /// 开始时间，时间点注释即为该时间点
+    pub start_time: DateTime<Utc>,
+    /// 结束时间，时间点注释为空
+    pub end_time: Option<DateTime<Utc>>,
+    /// 注释内容
+    pub text: String,
+    /// 添加人
+    pub author: String,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+}
+
+impl Annotation {
+    /// 注释是否与时间窗口 `[start, end]` 有交集
+    ///
+    /// # 参数
+    /// * `start` - 开始时间
+    /// * `end` - 结束时间
+    pub fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        self.start_time <= end && self.end_time.unwrap_or(self.start_time) >= start
+    }
+}
+
+/// 添加注释请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAnnotationRequest {
+    /// 时间点或时间段的开始，默认为当前时间
+    pub timestamp: Option<DateTime<Utc>>,
+    /// 时间段的结束，时间点注释不填
+    pub end_time: Option<DateTime<Utc>>,
+    /// 注释内容
+    pub text: String,
+    /// 添加人，携带JWT时以JWT主体为准
+    pub author: Option<String>,
+}
+
+impl CreateAnnotationRequest {
+    /// 验证请求并生成注释
+    ///
+    /// # 参数
+    /// * `author` - 添加人
+    /// * `now` - 当前时间
+    pub fn into_annotation(self, author: String, now: DateTime<Utc>) -> AppResult<Annotation> {
+        let text = self.text.trim();
+        if text.is_empty() {
+            return Err(AppError::validation_error("text", "注释内容不能为空"));
+        }
+        if text.chars().count() > MAX_ANNOTATION_TEXT_CHARS {
+            return Err(AppError::validation_error(
+                "text",
+                format!("注释内容不能超过 {} 个字符", MAX_ANNOTATION_TEXT_CHARS),
+            ));
+        }
+        let start_time = self.timestamp.unwrap_or(now);
+        if self.end_time.is_some_and(|end_time| end_time < start_time) {
+            return Err(AppError::validation_error("end_time", "结束时间不能早于开始时间"));
+        }
+
+        Ok(Annotation {
+            id: Uuid::new_v4(),
+            start_time,
+            end_time: self.end_time,
+            text: text.to_string(),
+            author,
+            created_at: now,
+        })
+    }
+}
+
+/// 注释查询参数
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnnotationQuery {
+    /// 开始时间，默认为结束时间前24小时
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间，默认为当前时间
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl AnnotationQuery {
+    /// 解析查询的时间窗口
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn window(&self, now: DateTime<Utc>) -> AppResult<(DateTime<Utc>, DateTime<Utc>)> {
+        let end = self.end_time.unwrap_or(now);
+        let start = self.start_time.unwrap_or(end - Duration::hours(DEFAULT_QUERY_HOURS));
+        if start > end {
+            return Err(AppError::validation_error("start_time", "开始时间不能晚于结束时间"));
+        }
+        Ok((start, end))
+    }
+}
+
+/// 叠加了注释的时间序列，供图表在数据旁标出操作事件
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnotatedSeries<T> {
+    /// 时间序列数据
+    pub data: Vec<T>,
+    /// 与查询窗口有交集的注释，按开始时间正序
+    pub annotations: Vec<Annotation>,
+}
+
+/// 为时间序列附上查询窗口内的注释
+///
+/// # 参数
+/// * `store` - 注释存储
+/// * `data` - 时间序列数据
+/// * `start` - 查询开始时间
+/// * `end` - 查询结束时间
+pub async fn annotate<T>(
+    store: &dyn AnnotationStore,
+    data: Vec<T>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> AppResult<AnnotatedSeries<T>> {
+    let annotations = store.range(start, end, MAX_RANGE_ANNOTATIONS).await?;
+    Ok(AnnotatedSeries { data, annotations })
+}
+
+/// 注释存储
+#[async_trait]
+pub trait AnnotationStore: Send + Sync {
+    /// 保存注释
+    ///
+    /// # 参数
+    /// * `annotation` - 注释
+    async fn save(&self, annotation: &Annotation) -> AppResult<()>;
+
+    /// 读取与时间窗口 `[start, end]` 有交集的注释，按开始时间正序
+    ///
+    /// # 参数
+    /// * `start` - 开始时间
+    /// * `end` - 结束时间
+    /// * `limit` - 最多返回的条数
+    async fn range(&self, start: DateTime<Utc>, end: DateTime<Utc>, limit: usize) -> AppResult<Vec<Annotation>>;
+}
+
+/// 进程内注释存储
+#[derive(Default)]
+pub struct InMemoryAnnotationStore {
+    annotations: RwLock<Vec<Annotation>>,
+}
+
+impl InMemoryAnnotationStore {
+    /// 创建进程内注释存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AnnotationStore for InMemoryAnnotationStore {
+    async fn save(&self, annotation: &Annotation) -> AppResult<()> {
+        self.annotations.write().await.push(annotation.clone());
+        Ok(())
+    }
+
+    async fn range(&self, start: DateTime<Utc>, end: DateTime<Utc>, limit: usize) -> AppResult<Vec<Annotation>> {
+        let mut annotations: Vec<Annotation> = self
+            .annotations
+            .read()
+            .await
+            .iter()
+            .filter(|annotation| annotation.overlaps(start, end))
+            .cloned()
+            .collect();
+        annotations.sort_by_key(|annotation| annotation.start_time);
+        annotations.truncate(limit);
+        Ok(annotations)
+    }
+}
+
+/// PostgreSQL注释存储
+///
+/// 写入 `scripts/init.sql` 中定义的 `annotations` 表
+pub struct PgAnnotationStore {
+    pool: PgPool,
+}
+
+impl PgAnnotationStore {
+    /// 创建数据库注释存储
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AnnotationStore for PgAnnotationStore {
+    #[instrument(level = "debug", name = "db.annotations.save", skip_all)]
+    async fn save(&self, annotation: &Annotation) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO annotations (id, start_time, end_time, text, author, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(annotation.id)
+        .bind(annotation.start_time)
+        .bind(annotation.end_time)
+        .bind(&annotation.text)
+        .bind(&annotation.author)
+        .bind(annotation.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "db.annotations.range", skip_all)]
+    async fn range(&self, start: DateTime<Utc>, end: DateTime<Utc>, limit: usize) -> AppResult<Vec<Annotation>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, start_time, end_time, text, author, created_at
+            FROM annotations
+            WHERE start_time <= $2 AND COALESCE(end_time, start_time) >= $1
+            ORDER BY start_time
+            LIMIT $3
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Annotation {
+                    id: row.try_get("id")?,
+                    start_time: row.try_get("start_time")?,
+                    end_time: row.try_get("end_time")?,
+                    text: row.try_get("text")?,
+                    author: row.try_get("author")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(timestamp: DateTime<Utc>, end_time: Option<DateTime<Utc>>, text: &str) -> CreateAnnotationRequest {
+        CreateAnnotationRequest {
+            timestamp: Some(timestamp),
+            end_time,
+            text: text.to_string(),
+            author: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_annotation_in_queried_range_is_returned_with_series() {
+        let now = Utc::now();
+        let store = InMemoryAnnotationStore::new();
+        let annotations = [
+            request(now - Duration::hours(3), None, "更换了3号风扇"),
+            request(now - Duration::hours(5), Some(now - Duration::minutes(90)), "负载高峰"),
+            request(now - Duration::hours(30), None, "窗口外"),
+        ];
+        for annotation in annotations {
+            let annotation = annotation.into_annotation("operator".to_string(), now).unwrap();
+            store.save(&annotation).await.unwrap();
+        }
+
+        let series = vec![(now - Duration::hours(2), 55.0), (now - Duration::hours(1), 57.5)];
+        let (start, end) = AnnotationQuery {
+            start_time: Some(now - Duration::hours(4)),
+            end_time: Some(now),
+        }
+        .window(now)
+        .unwrap();
+        let annotated = annotate(&store, series.clone(), start, end).await.unwrap();
+
+        assert_eq!(annotated.data, series);
+        let texts: Vec<&str> = annotated.annotations.iter().map(|a| a.text.as_str()).collect();
+        assert_eq!(texts, ["负载高峰", "更换了3号风扇"]);
+        assert_eq!(annotated.annotations[1].author, "operator");
+
+        assert!(request(now, Some(now - Duration::minutes(1)), "倒置")
+            .into_annotation("operator".to_string(), now)
+            .is_err());
+        assert!(request(now, None, "  ")
+            .into_annotation("operator".to_string(), now)
+            .is_err());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::models::error::{AppError, AppResult};
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 自动控制时段配置
+///
+/// 启用后控制循环只在 `windows` 内自动调速；时段外保持最后一次下发的转速，
+/// 配置了 `manual_profile` 时改为按该档案下发。最高温度达到临界温度时不论是否在时段内都执行紧急冷却
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AutoControlScheduleConfig {
+    /// 是否按时段启用自动控制，未启用时自动控制全天生效
+    #[serde(default)]
+    pub enabled: bool,
+    /// 自动控制时段
+    #[serde(default)]
+    pub windows: Vec<AutoControlWindow>,
+    /// 时段外下发的手动转速档案，为空时保持最后一次下发的转速
+    #[serde(default)]
+    pub manual_profile: Option<ManualFanProfile>,
+}
+
+/// 自动控制时段
+///
+/// 按本地时间定义，结束时间早于开始时间表示跨越午夜
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoControlWindow {
+    /// 时段名称，仅用于日志
+    pub name: String,
+    /// 开始时间（本地时间）
+    pub start: NaiveTime,
+    /// 结束时间（本地时间）
+    pub end: NaiveTime,
+    /// 生效的星期，为空表示每天
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+}
+
+/// 时段外的手动转速档案
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManualFanProfile {
+    /// 未单独配置的风扇使用的转速（百分比）
+    pub default_percent: f64,
+    /// 按风扇ID配置的转速（百分比）
+    #[serde(default)]
+    pub fans: BTreeMap<String, f64>,
+}
+
+impl ManualFanProfile {
+    /// 风扇在档案中的转速
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    pub fn percent_for(&self, fan_id: &str) -> f64 {
+        self.fans.get(fan_id).copied().unwrap_or(self.default_percent)
+    }
+
+    /// 校验各转速在0-100%之间
+    ///
+    /// # 参数
+    /// * `field` - 校验失败时报告的字段名
+    pub fn validate(&self, field: &str) -> AppResult<()> {
+        let percents = std::iter::once(("default_percent", self.default_percent))
+            .chain(self.fans.iter().map(|(fan_id, percent)| (fan_id.as_str(), *percent)));
+        for (name, percent) in percents {
+            if !(0.0..=100.0).contains(&percent) {
+                return Err(AppError::validation_error(field, format!("{} 的转速必须在0-100%之间", name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AutoControlWindow {
+    /// 时间是否落在该时段内，跨午夜时段在午夜之后的部分按开始那天的星期计算
+    ///
+    /// # 参数
+    /// * `at` - 本地时间
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        let time = at.time();
+        let (in_window, weekday) = if self.start < self.end {
+            (time >= self.start && time < self.end, at.weekday())
+        } else if time >= self.start {
+            (true, at.weekday())
+        } else {
+            (time < self.end, at.weekday().pred())
+        };
+
+        in_window && (self.weekdays.is_empty() || self.weekdays.contains(&weekday))
+    }
+}
+
+impl AutoControlScheduleConfig {
+    /// 校验时段和手动档案
+    pub fn validate(&self) -> AppResult<()> {
+        if self.enabled && self.windows.is_empty() {
+            return Err(AppError::validation_error(
+                "control.auto_schedule.windows",
+                "启用自动控制时段时至少需要配置一个时段",
+            ));
+        }
+        for window in &self.windows {
+            if window.start == window.end {
+                return Err(AppError::validation_error(
+                    "control.auto_schedule.windows",
+                    format!("时段 {} 的开始时间与结束时间不能相同", window.name),
+                ));
+            }
+        }
+        if let Some(profile) = &self.manual_profile {
+            profile.validate("control.auto_schedule.manual_profile")?;
+        }
+        Ok(())
+    }
+}
+
+/// 控制循环在某个周期应执行的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleDecision {
+    /// 时段内，正常自动调速
+    Adjust,
+    /// 时段外，保持当前转速不调节
+    Hold,
+    /// 时段外，按手动档案下发转速
+    ManualProfile,
+    /// 最高温度达到临界温度，执行紧急冷却
+    Emergency,
+}
+
+/// 自动控制时段
+#[derive(Debug, Clone)]
+pub struct AutoControlSchedule {
+    config: AutoControlScheduleConfig,
+    critical_temperature: f64,
+}
+
+impl AutoControlSchedule {
+    /// 创建自动控制时段
+    ///
+    /// # 参数
+    /// * `config` - 自动控制时段配置
+    /// * `critical_temperature` - 临界温度（°C），通常为 `control.emergency_exit.critical_temperature`
+    pub fn new(config: AutoControlScheduleConfig, critical_temperature: f64) -> Self {
+        Self {
+            config,
+            critical_temperature,
+        }
+    }
+
+    /// 自动控制时段配置
+    pub fn config(&self) -> &AutoControlScheduleConfig {
+        &self.config
+    }
+
+    /// 指定时间是否允许自动调速，未启用时段时总是允许
+    ///
+    /// # 参数
+    /// * `at` - 本地时间
+    pub fn is_active(&self, at: NaiveDateTime) -> bool {
+        !self.config.enabled || self.config.windows.iter().any(|window| window.contains(at))
+    }
+
+    /// 本周期应执行的动作，紧急冷却优先于时段
+    ///
+    /// # 参数
+    /// * `at` - 本地时间
+    /// * `max_temperature` - 所有传感器中的最高温度，读取失败时为空
+    pub fn decide(&self, at: NaiveDateTime, max_temperature: Option<f64>) -> ScheduleDecision {
+        if max_temperature.is_some_and(|temperature| temperature >= self.critical_temperature) {
+            ScheduleDecision::Emergency
+        } else if self.is_active(at) {
+            ScheduleDecision::Adjust
+        } else if self.config.manual_profile.is_some() {
+            ScheduleDecision::ManualProfile
+        } else {
+            ScheduleDecision::Hold
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        // 2024-01-01 为星期一
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_adjusts_inside_window_holds_outside_and_emergency_always_acts() {
+        let overnight = AutoControlWindow {
+            name: "overnight".to_string(),
+            start: NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            weekdays: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+        };
+        let mut config = AutoControlScheduleConfig {
+            enabled: true,
+            windows: vec![overnight],
+            manual_profile: None,
+        };
+        config.validate().unwrap();
+        let schedule = AutoControlSchedule::new(config.clone(), 85.0);
+
+        // 周一夜间和跨过午夜的周二凌晨在时段内
+        assert_eq!(schedule.decide(at(1, 22), Some(60.0)), ScheduleDecision::Adjust);
+        assert_eq!(schedule.decide(at(2, 3), Some(60.0)), ScheduleDecision::Adjust);
+        // 工作时间保持不调节
+        assert_eq!(schedule.decide(at(2, 10), Some(60.0)), ScheduleDecision::Hold);
+        assert_eq!(schedule.decide(at(2, 10), None), ScheduleDecision::Hold);
+        // 周五夜间的时段延续到周六凌晨，周六夜间不在时段内
+        assert_eq!(schedule.decide(at(6, 3), Some(60.0)), ScheduleDecision::Adjust);
+        assert_eq!(schedule.decide(at(6, 22), Some(60.0)), ScheduleDecision::Hold);
+
+        // 达到临界温度时时段内外都执行紧急冷却
+        assert_eq!(schedule.decide(at(1, 22), Some(85.0)), ScheduleDecision::Emergency);
+        assert_eq!(schedule.decide(at(2, 10), Some(90.0)), ScheduleDecision::Emergency);
+
+        // 配置手动档案后时段外按档案下发
+        config.manual_profile = Some(ManualFanProfile {
+            default_percent: 40.0,
+            fans: BTreeMap::from([("FAN1".to_string(), 55.0)]),
+        });
+        let schedule = AutoControlSchedule::new(config, 85.0);
+        assert_eq!(schedule.decide(at(2, 10), Some(60.0)), ScheduleDecision::ManualProfile);
+        let profile = schedule.config().manual_profile.as_ref().unwrap();
+        assert_eq!((profile.percent_for("FAN1"), profile.percent_for("FAN2")), (55.0, 40.0));
+
+        // 未启用时段时全天自动控制
+        let always = AutoControlSchedule::new(AutoControlScheduleConfig::default(), 85.0);
+        assert_eq!(always.decide(at(2, 10), Some(60.0)), ScheduleDecision::Adjust);
+    }
+}
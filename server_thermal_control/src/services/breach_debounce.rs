@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// 各（规则, 告警源）条件开始持续满足的时间
+pub type BreachStarts = HashMap<(String, String), DateTime<Utc>>;
+
+/// 记录条件开始满足的时间，条件解除时清除；持续时间达到要求时返回true
+///
+/// 告警规则和温差规则共用，条件中断后持续时间从下一次满足时重新计算
+///
+/// # 参数
+/// * `breach_starts` - 各条件开始满足的时间
+/// * `key` - （规则ID, 告警源）
+/// * `breached` - 本次读数是否满足条件
+/// * `duration_secs` - 需要持续的时间（秒）
+/// * `at` - 读数时间
+pub fn track_breach(
+    breach_starts: &mut BreachStarts,
+    key: (String, String),
+    breached: bool,
+    duration_secs: u64,
+    at: DateTime<Utc>,
+) -> bool {
+    if !breached {
+        breach_starts.remove(&key);
+        return false;
+    }
+
+    let started_at = *breach_starts.entry(key).or_insert(at);
+    at.signed_duration_since(started_at).num_seconds() >= duration_secs as i64
+}
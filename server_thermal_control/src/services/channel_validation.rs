@@ -0,0 +1,280 @@
+use crate::models::alert::{ChannelType, NotificationChannel};
+use crate::utils::network::NetworkUtils;
+use crate::utils::validation::ValidationUtils;
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::Value;
+
+/// 钉钉机器人默认地址，只配置 `access_token` 时按该地址探测
+const DINGTALK_API_HOST: &str = "oapi.dingtalk.com";
+
+/// 未配置 `smtp_port` 时探测的SMTP端口
+const DEFAULT_SMTP_PORT: u16 = 25;
+
+/// 通知渠道配置中的一个问题
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelProblem {
+    /// 出问题的字段，如 `config.url`
+    pub field: String,
+    /// 问题描述
+    pub message: String,
+}
+
+impl ChannelProblem {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// 连通性探测结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelProbe {
+    /// 探测的地址（host:port）
+    pub target: String,
+    /// 是否能建立TCP连接
+    pub reachable: bool,
+}
+
+/// 通知渠道配置的校验结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelValidation {
+    /// 配置是否有效，连通性探测失败也视为无效
+    pub valid: bool,
+    /// 配置中的全部问题
+    pub problems: Vec<ChannelProblem>,
+    /// 连通性探测结果，未要求探测、配置有误或渠道类型无需探测时为空
+    pub probe: Option<ChannelProbe>,
+}
+
+/// 检查通知渠道配置，返回全部问题
+///
+/// 各渠道读取的配置项与发送时一致：Webhook为 `url`，Slack为 `webhook_url`，
+/// 钉钉为 `webhook_url` 或 `access_token`，邮件为 `email`，短信为 `phone`
+///
+/// # 参数
+/// * `channel` - 通知渠道
+pub fn check_channel(channel: &NotificationChannel) -> Vec<ChannelProblem> {
+    let mut problems = Vec::new();
+    let Some(config) = channel.config.as_object() else {
+        problems.push(ChannelProblem::new("config", "渠道配置必须是JSON对象"));
+        return problems;
+    };
+    for (key, value) in config {
+        if !value.is_string() && !value.is_number() {
+            problems.push(ChannelProblem::new(format!("config.{}", key), "配置项必须是字符串或数字"));
+        }
+    }
+
+    match channel.channel_type {
+        ChannelType::Webhook => {
+            if let Some(url) = required(config, "url", &mut problems) {
+                check_url("config.url", &url, &mut problems);
+            }
+        }
+        ChannelType::Slack => {
+            if let Some(url) = required(config, "webhook_url", &mut problems) {
+                check_url("config.webhook_url", &url, &mut problems);
+            }
+        }
+        ChannelType::DingTalk => check_dingtalk(config, &mut problems),
+        ChannelType::Email => {
+            if let Some(emails) = required(config, "email", &mut problems) {
+                for email in emails.split(',').map(str::trim) {
+                    if ValidationUtils::validate_email(email).is_err() {
+                        problems.push(ChannelProblem::new("config.email", format!("无效的电子邮件地址: {}", email)));
+                    }
+                }
+            }
+            if let Some(port) = string_value(config, "smtp_port") {
+                if port.parse::<u16>().map_or(true, |port| port == 0) {
+                    problems.push(ChannelProblem::new("config.smtp_port", "SMTP端口必须在1-65535之间"));
+                }
+            }
+        }
+        ChannelType::Sms => {
+            if let Some(phone) = required(config, "phone", &mut problems) {
+                let digits = phone.strip_prefix('+').unwrap_or(&phone);
+                if !(6..=15).contains(&digits.len()) || !digits.chars().all(|c| c.is_ascii_digit()) {
+                    problems.push(ChannelProblem::new("config.phone", "手机号须为6-15位数字，可带+前缀"));
+                }
+            }
+        }
+    }
+    problems
+}
+
+/// 钉钉：`webhook_url` 与 `access_token` 至少配置一个，令牌为64位十六进制，加签密钥以 `SEC` 开头
+fn check_dingtalk(config: &serde_json::Map<String, Value>, problems: &mut Vec<ChannelProblem>) {
+    let webhook_url = string_value(config, "webhook_url");
+    let mut access_token = string_value(config, "access_token");
+    if let Some(url) = &webhook_url {
+        if let Some(parsed) = check_url("config.webhook_url", url, problems) {
+            let token = parsed
+                .query_pairs()
+                .find(|(key, _)| key == "access_token")
+                .map(|(_, value)| value.into_owned());
+            match token {
+                Some(token) => access_token = access_token.or(Some(token)),
+                None => problems.push(ChannelProblem::new("config.webhook_url", "钉钉机器人地址缺少access_token参数")),
+            }
+        }
+    } else if access_token.is_none() {
+        problems.push(ChannelProblem::new("config.access_token", "钉钉渠道需要配置webhook_url或access_token"));
+    }
+
+    if let Some(token) = access_token {
+        if token.len() != 64 || !token.chars().all(|c| c.is_ascii_hexdigit()) {
+            problems.push(ChannelProblem::new("config.access_token", "钉钉access_token应为64位十六进制字符串"));
+        }
+    }
+    if let Some(secret) = string_value(config, "secret") {
+        if !secret.starts_with("SEC") {
+            problems.push(ChannelProblem::new("config.secret", "钉钉加签密钥应以SEC开头"));
+        }
+    }
+}
+
+/// 读取必填配置项，缺失或为空时记录问题
+fn required(config: &serde_json::Map<String, Value>, key: &str, problems: &mut Vec<ChannelProblem>) -> Option<String> {
+    let value = string_value(config, key);
+    if value.is_none() {
+        problems.push(ChannelProblem::new(format!("config.{}", key), "缺少必填配置项"));
+    }
+    value
+}
+
+/// 读取非空配置项，数字按字符串处理
+fn string_value(config: &serde_json::Map<String, Value>, key: &str) -> Option<String> {
+    let value = match config.get(key)? {
+        Value::String(value) => value.trim().to_string(),
+        Value::Number(value) => value.to_string(),
+        _ => return None,
+    };
+    (!value.is_empty()).then_some(value)
+}
+
+/// 校验HTTP(S)地址，有效时返回解析结果
+fn check_url(field: &str, url: &str, problems: &mut Vec<ChannelProblem>) -> Option<Url> {
+    let parsed = ValidationUtils::validate_url(url)
+        .ok()
+        .and_then(|_| Url::parse(url).ok())
+        .filter(|parsed| parsed.host_str().is_some());
+    if parsed.is_none() {
+        problems.push(ChannelProblem::new(field, format!("无效的HTTP(S)地址: {}", url)));
+    }
+    parsed
+}
+
+/// 渠道发送时连接的地址，短信渠道没有可探测的地址
+///
+/// # 参数
+/// * `channel` - 配置有效的通知渠道
+pub fn probe_target(channel: &NotificationChannel) -> Option<(String, u16)> {
+    let config = channel.config.as_object()?;
+    let url_target = |key: &str| {
+        let url = Url::parse(&string_value(config, key)?).ok()?;
+        Some((url.host_str()?.to_string(), url.port_or_known_default()?))
+    };
+
+    match channel.channel_type {
+        ChannelType::Webhook => url_target("url"),
+        ChannelType::Slack => url_target("webhook_url"),
+        ChannelType::DingTalk => url_target("webhook_url").or_else(|| Some((DINGTALK_API_HOST.to_string(), 443))),
+        ChannelType::Email => {
+            let host = string_value(config, "smtp_host")?;
+            let port = string_value(config, "smtp_port")
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(DEFAULT_SMTP_PORT);
+            Some((host, port))
+        }
+        ChannelType::Sms => None,
+    }
+}
+
+/// 校验通知渠道配置，不保存；`probe` 为true且配置无误时再探测渠道地址能否连通
+///
+/// # 参数
+/// * `channel` - 待校验的通知渠道
+/// * `probe` - 是否探测连通性
+/// * `timeout_ms` - 探测超时（毫秒）
+pub async fn validate_channel(channel: &NotificationChannel, probe: bool, timeout_ms: u64) -> ChannelValidation {
+    let problems = check_channel(channel);
+    let probe = match probe_target(channel) {
+        Some((host, port)) if probe && problems.is_empty() => Some(ChannelProbe {
+            target: format!("{}:{}", host, port),
+            reachable: NetworkUtils::check_host_reachable(&host, port, timeout_ms).await,
+        }),
+        _ => None,
+    };
+
+    ChannelValidation {
+        valid: problems.is_empty() && probe.as_ref().is_none_or(|probe| probe.reachable),
+        problems,
+        probe,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn webhook(url: &str) -> NotificationChannel {
+        NotificationChannel {
+            channel_type: ChannelType::Webhook,
+            config: json!({ "url": url }),
+            enabled: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_malformed_webhook_url_is_reported_and_valid_one_passes() {
+        for url in ["not a url", "ftp://hooks.example.com/alert", "https://"] {
+            let result = validate_channel(&webhook(url), true, 500).await;
+            assert!(!result.valid, "{}", url);
+            assert_eq!(result.problems.len(), 1);
+            assert_eq!(result.problems[0].field, "config.url");
+            // 配置有误时不探测
+            assert!(result.probe.is_none());
+        }
+
+        let result = validate_channel(&webhook("https://hooks.example.com/alert?team=ops"), false, 500).await;
+        assert!(result.valid);
+        assert!(result.problems.is_empty());
+        assert!(result.probe.is_none());
+
+        // 探测连接到本地监听端口
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let result = validate_channel(&webhook(&format!("http://127.0.0.1:{}/hook", port)), true, 500).await;
+        assert!(result.valid);
+        assert_eq!(
+            result.probe,
+            Some(ChannelProbe { target: format!("127.0.0.1:{}", port), reachable: true })
+        );
+    }
+
+    #[test]
+    fn test_dingtalk_token_format() {
+        let channel = |config: Value| NotificationChannel {
+            channel_type: ChannelType::DingTalk,
+            config,
+            enabled: true,
+        };
+        let token = "a".repeat(64);
+        assert!(check_channel(&channel(json!({ "access_token": token, "secret": "SECabc" }))).is_empty());
+        assert!(check_channel(&channel(json!({
+            "webhook_url": format!("https://oapi.dingtalk.com/robot/send?access_token={}", token)
+        })))
+        .is_empty());
+
+        let fields: Vec<String> = check_channel(&channel(json!({ "access_token": "short", "secret": "abc" })))
+            .into_iter()
+            .map(|problem| problem.field)
+            .collect();
+        assert_eq!(fields, ["config.access_token", "config.secret"]);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::models::alert::AlertRule;
+use crate::models::error::{AppError, AppResult};
+use crate::services::alert_rules::AlertRuleStore;
+use crate::services::alert_thresholds::{AlertThresholdStore, SensorAlertThreshold};
+use crate::services::sensor_blacklist::SensorBlacklist;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// 当前配置快照格式版本
+pub const CONFIG_SNAPSHOT_VERSION: u32 = 1;
+
+/// 运行时可调配置的完整快照
+///
+/// 包含告警规则、按传感器设置的告警阈值和传感器黑名单。控制参数、风扇曲线和通知渠道
+/// 来自配置文件，运行时不可修改，随 `GET /api/v1/config/template` 导出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    /// 快照格式版本
+    pub version: u32,
+    /// 生成时间
+    pub created_at: DateTime<Utc>,
+    /// 告警规则（按规则ID）
+    pub alert_rules: BTreeMap<String, AlertRule>,
+    /// 告警阈值（按传感器ID）
+    pub alert_thresholds: BTreeMap<String, SensorAlertThreshold>,
+    /// 传感器黑名单
+    pub sensor_blacklist: Vec<String>,
+}
+
+impl ConfigSnapshot {
+    /// 验证快照，任何一部分无效时整个快照无效
+    pub fn validate(&self) -> AppResult<()> {
+        if self.version != CONFIG_SNAPSHOT_VERSION {
+            return Err(AppError::validation_error(
+                "version",
+                format!("不支持的快照版本 {}，当前版本为 {}", self.version, CONFIG_SNAPSHOT_VERSION),
+            ));
+        }
+
+        for (rule_id, rule) in &self.alert_rules {
+            if rule_id.trim().is_empty() {
+                return Err(AppError::validation_error("alert_rules", "规则ID不能为空"));
+            }
+            rule.validate()?;
+        }
+        for threshold in self.alert_thresholds.values() {
+            threshold.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// 配置快照服务
+///
+/// 导出和恢复告警与传感器的运行时配置，用于备份调优结果或迁移到新主机
+#[derive(Debug, Clone)]
+pub struct ConfigSnapshotService {
+    /// 告警规则
+    alert_rules: AlertRuleStore,
+    /// 按传感器设置的告警阈值
+    alert_thresholds: AlertThresholdStore,
+    /// 传感器黑名单
+    sensor_blacklist: SensorBlacklist,
+}
+
+impl ConfigSnapshotService {
+    /// 创建配置快照服务
+    ///
+    /// # 参数
+    /// * `alert_rules` - 告警规则
+    /// * `alert_thresholds` - 按传感器设置的告警阈值
+    /// * `sensor_blacklist` - 传感器黑名单
+    pub fn new(alert_rules: AlertRuleStore, alert_thresholds: AlertThresholdStore, sensor_blacklist: SensorBlacklist) -> Self {
+        Self {
+            alert_rules,
+            alert_thresholds,
+            sensor_blacklist,
+        }
+    }
+
+    /// 生成当前配置快照
+    pub fn snapshot(&self) -> ConfigSnapshot {
+        ConfigSnapshot {
+            version: CONFIG_SNAPSHOT_VERSION,
+            created_at: Utc::now(),
+            alert_rules: self.alert_rules.all(),
+            alert_thresholds: self.alert_thresholds.all(),
+            sensor_blacklist: self.sensor_blacklist.sensor_ids(),
+        }
+    }
+
+    /// 恢复配置快照，返回恢复后的配置
+    ///
+    /// 先整体验证，验证失败时不做任何修改；验证通过后各部分的替换不会失败
+    ///
+    /// # 参数
+    /// * `snapshot` - 配置快照
+    pub fn restore(&self, snapshot: ConfigSnapshot) -> AppResult<ConfigSnapshot> {
+        snapshot.validate()?;
+        info!("恢复配置快照（生成于 {}）", snapshot.created_at);
+
+        self.alert_rules.replace(snapshot.alert_rules);
+        self.alert_thresholds.replace(snapshot.alert_thresholds);
+        self.sensor_blacklist.replace(snapshot.sensor_blacklist);
+
+        info!("配置快照恢复完成");
+        Ok(self.snapshot())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::alert::AlertRulePatch;
+    use crate::services::ipmi_service::parse_temperature_sdr;
+    use crate::services::alert_thresholds::SensorSelector;
+
+    fn snapshot_service() -> ConfigSnapshotService {
+        ConfigSnapshotService::new(
+            AlertRuleStore::with_defaults(80.0, 1000),
+            AlertThresholdStore::default(),
+            SensorBlacklist::default(),
+        )
+    }
+
+    #[test]
+    fn test_snapshot_restores_onto_fresh_instance() {
+        let source = snapshot_service();
+        source
+            .alert_rules
+            .patch(
+                "high_temperature",
+                &AlertRulePatch {
+                    threshold: Some(72.0),
+                    enabled: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let sensors = parse_temperature_sdr("CPU1 Temp        | 0Eh | ok  |  3.1 | 52 degrees C\n", Utc::now());
+        let threshold = SensorAlertThreshold {
+            warning: 75.0,
+            critical: 85.0,
+        };
+        source
+            .alert_thresholds
+            .bulk_apply(&sensors, &SensorSelector::Location("cpu".to_string()), threshold)
+            .unwrap();
+        source.sensor_blacklist.replace(vec!["PSU2_TEMP".to_string()]);
+
+        // 经JSON往返，模拟下载后在新主机上传
+        let bundle = serde_json::to_string(&source.snapshot()).unwrap();
+        let target = snapshot_service();
+        let restored = target.restore(serde_json::from_str(&bundle).unwrap()).unwrap();
+
+        let rule = target.alert_rules.get("high_temperature").unwrap();
+        assert_eq!(rule.condition.threshold, 72.0);
+        assert!(!rule.enabled);
+        assert_eq!(
+            restored.alert_rules.keys().collect::<Vec<_>>(),
+            source.alert_rules.all().keys().collect::<Vec<_>>()
+        );
+        assert_eq!(target.alert_thresholds.get("CPU1_TEMP"), Some(threshold));
+        assert_eq!(target.sensor_blacklist.sensor_ids(), vec!["PSU2_TEMP"]);
+
+        // 无效快照整体拒绝，已恢复的配置保持不变
+        let mut invalid: ConfigSnapshot = serde_json::from_str(&bundle).unwrap();
+        invalid.sensor_blacklist.clear();
+        invalid.alert_rules.get_mut("high_temperature").unwrap().condition.threshold = 45.0;
+        invalid.alert_thresholds.get_mut("CPU1_TEMP").unwrap().warning = 90.0;
+        assert!(target.restore(invalid).is_err());
+        assert_eq!(target.alert_rules.get("high_temperature").unwrap().condition.threshold, 72.0);
+        assert_eq!(target.sensor_blacklist.sensor_ids(), vec!["PSU2_TEMP"]);
+
+        let mut future: ConfigSnapshot = serde_json::from_str(&bundle).unwrap();
+        future.version = CONFIG_SNAPSHOT_VERSION + 1;
+        assert!(target.restore(future).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::models::alert::AlertSeverity;
+use crate::models::error::AppResult;
+use crate::services::alert_history_store::AlertHistoryStore;
+use crate::services::alert_store::{AlertStore, NewAlert};
+use crate::services::control_write_retry::{ControlWriteAlertSink, FailedControlWrite};
+use crate::services::fan_degradation::{FanDegradation, FanDegradationAlertSink};
+use crate::services::power_cap::{PowerCapAlertSink, PowerCapDecision};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// 控制循环的告警出口
+///
+/// 把控制循环发现的问题写入告警存储，新告警同时保存到告警历史；
+/// 同一告警源未解决的告警不会重复创建
+#[derive(Clone)]
+pub struct ControlAlertSink {
+    alerts: AlertStore,
+    history: Arc<dyn AlertHistoryStore>,
+}
+
+impl ControlAlertSink {
+    /// 创建控制循环告警出口
+    ///
+    /// # 参数
+    /// * `alerts` - 告警存储
+    /// * `history` - 告警历史存储
+    pub fn new(alerts: AlertStore, history: Arc<dyn AlertHistoryStore>) -> Self {
+        Self { alerts, history }
+    }
+
+    async fn raise(&self, new: NewAlert) -> AppResult<()> {
+        if let Some((_, alert)) = self.alerts.raise(new) {
+            self.history.upsert(&alert).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PowerCapAlertSink for ControlAlertSink {
+    async fn raise_power_cap_alert(&self, fan_id: &str, decision: &PowerCapDecision) -> AppResult<()> {
+        let limited = decision.allowed_percent < decision.requested_percent;
+        self.raise(NewAlert {
+            alert_type: "power".to_string(),
+            severity: AlertSeverity::Warning,
+            title: if limited {
+                "风扇提速受功率上限限制".to_string()
+            } else {
+                "风扇提速与功率上限冲突".to_string()
+            },
+            message: format!(
+                "风扇 {} 提速至 {:.1}% 预计功耗 {:.0}W，接近功率上限 {:.0}W（当前 {:.0}W），实际下发 {:.1}%",
+                fan_id,
+                decision.requested_percent,
+                decision.projected_watts,
+                decision.cap_watts,
+                decision.power_watts,
+                decision.allowed_percent
+            ),
+            source: "功率封顶".to_string(),
+            source_id: fan_id.to_string(),
+            rule_id: None,
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ControlWriteAlertSink for ControlAlertSink {
+    async fn raise_control_write_alert(&self, failed: &FailedControlWrite) -> AppResult<()> {
+        self.raise(NewAlert {
+            alert_type: "fan".to_string(),
+            severity: AlertSeverity::Error,
+            title: "风扇转速下发失败".to_string(),
+            message: format!(
+                "风扇 {} 转速 {:.1}%（{}）连续 {} 个周期下发失败，自 {} 起风扇转速未受控制: {}",
+                failed.fan_id,
+                failed.speed_percent,
+                failed.reason,
+                failed.consecutive_failures,
+                failed.first_failed_at.to_rfc3339(),
+                failed.last_error
+            ),
+            source: "风扇控制".to_string(),
+            source_id: failed.fan_id.clone(),
+            rule_id: None,
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl FanDegradationAlertSink for ControlAlertSink {
+    async fn raise_fan_degradation_alert(&self, degradation: &FanDegradation) -> AppResult<()> {
+        self.raise(NewAlert {
+            alert_type: "fan".to_string(),
+            severity: AlertSeverity::Warning,
+            title: "风扇转速衰减".to_string(),
+            message: format!(
+                "风扇 {} 占空比 {:.1}% 实测转速 {:.0} RPM，偏离期望 {:.0} RPM {:.1}%（自 {} 起），可能老化",
+                degradation.fan_id,
+                degradation.duty_percent,
+                degradation.measured_rpm,
+                degradation.expected_rpm,
+                degradation.deviation_percent,
+                degradation.since.to_rfc3339()
+            ),
+            source: "风扇老化检测".to_string(),
+            source_id: degradation.fan_id.clone(),
+            rule_id: None,
+        })
+        .await
+    }
+}
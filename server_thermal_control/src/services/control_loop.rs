@@ -0,0 +1,1429 @@
+use crate::config::ControlConfig;
+use crate::models::control::FanKickstartConfig;
+use crate::models::control::{ControlActionType, ControlDecision, ControlParameters, ControlParametersPatch, PidParameters};
+use crate::models::error::{AppError, AppResult};
+use crate::services::control_supervisor::ControlLoopSupervisor;
+use crate::services::auto_control_schedule::{AutoControlSchedule, ManualFanProfile};
+use crate::services::control_write_retry::{ControlWriteAlertSink, ControlWriteRetry, FailedControlWrite};
+use crate::services::decision_store::ControlDecisionLog;
+use crate::services::emergency_exit::EmergencyExitGuard;
+use crate::services::fan_degradation::{FanDegradationAlertSink, FanDegradationMonitor};
+use crate::services::fan_kickstart::{self, KickstartOutcome};
+use crate::services::ipmi_service::TemperatureSensor;
+use crate::services::parameter_diff::{AppliedParameters, ParameterChangeSource, ParameterDiff, ParameterHistory};
+use crate::services::poll_ticker::{PollOverruns, PollTicker};
+use crate::services::power_cap::PowerCapCoordinator;
+use crate::services::quiet_hours::QuietHoursStore;
+use crate::services::safe_boot;
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::sensor_source::SensorSource;
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 与上次下发转速相差不超过该值（百分点）时视为相同指令
+const IDENTICAL_WRITE_TOLERANCE: f64 = 0.5;
+
+/// 未配置PID参数时使用的默认参数
+const DEFAULT_PID: PidParameters = PidParameters {
+    kp: 4.0,
+    ki: 0.2,
+    kd: 0.0,
+    integral_limit: 40.0,
+};
+
+/// 一次控制周期的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlTick {
+    /// 控制时间
+    pub timestamp: DateTime<Utc>,
+    /// 作为控制输入的传感器（最高温度）
+    pub sensor_id: String,
+    /// 输入温度（°C）
+    pub input_temperature: f64,
+    /// 本周期的控制动作，自动控制时段外保持转速时为空
+    pub action_type: Option<ControlActionType>,
+    /// 下发的转速（%），手动档案为其默认转速，保持转速时为空
+    pub output_percent: Option<f64>,
+    /// 是否处于紧急冷却
+    pub emergency: bool,
+    /// 本周期实际写入的风扇
+    pub written_fans: Vec<String>,
+    /// 起转脉冲后仍无转速的风扇，可能已损坏
+    pub stalled_fans: Vec<String>,
+    /// 本周期检测到转速衰减（可能老化）的风扇
+    pub degraded_fans: Vec<String>,
+}
+
+/// 控制循环运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlLoopLiveness {
+    /// 未启动（控制关闭或只读模式）
+    Disabled,
+    /// 已启动，尚未完成第一个控制周期
+    Starting,
+    /// 最近两个控制周期内完成过控制周期
+    Running,
+    /// 超过两个控制周期没有完成控制周期，风扇转速不再跟随温度
+    Stalled,
+}
+
+impl ControlLoopLiveness {
+    /// 健康报告中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ControlLoopLiveness::Disabled => "disabled",
+            ControlLoopLiveness::Starting => "starting",
+            ControlLoopLiveness::Running => "running",
+            ControlLoopLiveness::Stalled => "stalled",
+        }
+    }
+}
+
+/// 控制循环心跳
+#[derive(Debug, Default)]
+struct Heartbeat {
+    started_at: Option<DateTime<Utc>>,
+    last_cycle: Option<DateTime<Utc>>,
+}
+
+/// 单个风扇转速指令的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FanSpeedCommand {
+    /// 风扇ID
+    pub fan_id: String,
+    /// 指令前的转速（%），优先取最后一次下发的转速
+    pub previous_percent: f64,
+    /// 指令转速（%）
+    pub speed_percent: f64,
+    /// 与上次下发的转速相同而未写入BMC时为false
+    pub written: bool,
+}
+
+/// 控制循环在周期之间保留的状态
+#[derive(Debug, Default)]
+struct LoopState {
+    integral: f64,
+    last_error: Option<f64>,
+    last_tick: Option<DateTime<Utc>>,
+    emergency: bool,
+    /// 紧急冷却的退出确认
+    exit_guard: EmergencyExitGuard,
+    /// 各风扇最后一次下发的转速（%）
+    commanded: HashMap<String, f64>,
+    /// 通过接口应用的手动档案，优先于自动控制时段
+    manual_override: Option<ManualFanProfile>,
+}
+
+/// 运行中的控制循环任务，重启时按原监督器重新启动
+struct LoopRunner {
+    supervisor: Arc<ControlLoopSupervisor>,
+    poll_overruns: PollOverruns,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl LoopState {
+    /// 切换控制方式时清空PID积分与微分历史
+    fn reset_pid(&mut self) {
+        self.integral = 0.0;
+        self.last_error = None;
+    }
+}
+
+/// 风扇自动控制循环
+///
+/// 每个周期以全部温度传感器中的最高温度为输入做PID调节，输出限制在 `[min_fan_speed, max_fan_speed]`
+/// 后下发到各可调速风扇，每个风扇记录一条控制决策。最高温度达到临界温度时全部风扇满速（紧急冷却），
+/// 温度低于 `临界温度 - 回差` 并持续确认时间后才恢复PID调节，避免在临界附近反复进出。
+/// 启用 `control.auto_schedule` 时只在自动控制时段内调速，时段外保持当前转速或按手动档案下发；
+/// 通过接口应用的手动档案优先于时段。
+/// 转速下发失败时按 `control.write_retry` 重试，连续失败时告警。
+/// 启用 `control.fan_degradation` 时按上个周期下发的占空比检测风扇转速衰减。
+/// 配置功率封顶协调时，自动调速的提速受功率余量限制；配置起转脉冲时，低速下停转的风扇先短暂提速再回到目标转速。
+/// 静音时段内自动调速的输出不超过时段的转速上限，温度达到静音计划的紧急温度时不受限制
+#[derive(Clone)]
+pub struct FanControlLoop {
+    source: Arc<dyn SensorSource>,
+    sensor_blacklist: SensorBlacklist,
+    parameters: Arc<RwLock<ControlParameters>>,
+    parameter_history: ParameterHistory,
+    decisions: ControlDecisionLog,
+    state: Arc<tokio::sync::Mutex<LoopState>>,
+    heartbeat: Arc<RwLock<Heartbeat>>,
+    runner: Arc<std::sync::Mutex<Option<LoopRunner>>>,
+    schedule: AutoControlSchedule,
+    power_cap: Option<PowerCapCoordinator>,
+    quiet_hours: QuietHoursStore,
+    kickstart: Option<FanKickstartConfig>,
+    write_retry: ControlWriteRetry,
+    degradation: FanDegradationMonitor,
+    interval: Duration,
+    suppress_identical_writes: bool,
+}
+
+impl FanControlLoop {
+    /// 创建控制循环
+    ///
+    /// 目标温度取 `control.temp_target`，容差取 `control.temp_hysteresis`，
+    /// 临界温度取 `control.emergency_exit.critical_temperature`
+    ///
+    /// # 参数
+    /// * `source` - 传感器数据源，也是风扇转速的出口
+    /// * `sensor_blacklist` - 传感器黑名单，黑名单中的传感器不参与控制
+    /// * `config` - 控制配置
+    /// * `decisions` - 控制决策记录
+    pub fn new(
+        source: Arc<dyn SensorSource>,
+        sensor_blacklist: SensorBlacklist,
+        config: &ControlConfig,
+        decisions: ControlDecisionLog,
+    ) -> Self {
+        let critical_temperature = config.emergency_exit.critical_temperature;
+        let parameters = ControlParameters {
+            target_temperature: config.temp_target,
+            temperature_tolerance: config.temp_hysteresis,
+            pid_params: Some(DEFAULT_PID),
+            max_fan_speed: 100,
+            min_fan_speed: i32::from(config.safe_boot_fan_percent.min(100)) / 2,
+            warning_temperature: config.temp_target.max(critical_temperature - 10.0),
+            critical_temperature,
+            emergency_temperature: critical_temperature + 10.0,
+        };
+
+        Self {
+            source,
+            sensor_blacklist,
+            parameters: Arc::new(RwLock::new(parameters)),
+            parameter_history: ParameterHistory::default(),
+            decisions,
+            state: Arc::new(tokio::sync::Mutex::new(LoopState {
+                exit_guard: EmergencyExitGuard::new(config.emergency_exit.clone()),
+                ..Default::default()
+            })),
+            heartbeat: Arc::default(),
+            runner: Arc::default(),
+            schedule: AutoControlSchedule::new(config.auto_schedule.clone(), critical_temperature),
+            power_cap: None,
+            quiet_hours: QuietHoursStore::default(),
+            kickstart: config.kickstart.clone(),
+            write_retry: ControlWriteRetry::new(config.write_retry.clone()),
+            degradation: FanDegradationMonitor::new(config.fan_degradation.clone()),
+            interval: Duration::from_secs(config.update_interval.max(1)),
+            suppress_identical_writes: config.suppress_identical_fan_writes,
+        }
+    }
+
+    /// 设置功率封顶协调，自动调速提速前按当前功耗限制转速
+    ///
+    /// # 参数
+    /// * `power_cap` - 功率封顶协调
+    pub fn with_power_cap(mut self, power_cap: PowerCapCoordinator) -> Self {
+        self.power_cap = Some(power_cap);
+        self
+    }
+
+    /// 设置静音时段计划
+    ///
+    /// # 参数
+    /// * `quiet_hours` - 静音时段计划
+    pub fn with_quiet_hours(mut self, quiet_hours: QuietHoursStore) -> Self {
+        self.quiet_hours = quiet_hours;
+        self
+    }
+
+    /// 静音时段计划
+    pub fn quiet_hours(&self) -> &QuietHoursStore {
+        &self.quiet_hours
+    }
+
+    /// 设置转速下发连续失败时的告警出口
+    ///
+    /// # 参数
+    /// * `sink` - 告警出口
+    pub fn with_write_alerts(mut self, sink: Arc<dyn ControlWriteAlertSink>) -> Self {
+        self.write_retry = self.write_retry.with_alert_sink(sink);
+        self
+    }
+
+    /// 设置风扇转速衰减时的告警出口
+    ///
+    /// # 参数
+    /// * `sink` - 告警出口
+    pub fn with_degradation_alerts(mut self, sink: Arc<dyn FanDegradationAlertSink>) -> Self {
+        self.degradation = self.degradation.with_alert_sink(sink);
+        self
+    }
+
+    /// 重试耗尽后仍未下发的转速（按风扇ID排序）
+    pub fn failed_writes(&self) -> Vec<FailedControlWrite> {
+        self.write_retry.dead_letters()
+    }
+
+    /// 当前控制参数
+    pub fn parameters(&self) -> ControlParameters {
+        self.parameters.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 更新控制参数并记录变更差异
+    ///
+    /// 在两个控制周期之间生效，PID积分与微分历史清零；临界温度同时用于紧急冷却的进入和退出
+    ///
+    /// # 参数
+    /// * `parameters` - 新的控制参数
+    /// * `source` - 变更来源
+    pub async fn update_parameters(
+        &self,
+        parameters: ControlParameters,
+        source: ParameterChangeSource,
+    ) -> AppResult<AppliedParameters<ControlParameters>> {
+        parameters.validate()?;
+        let mut state = self.state.lock().await;
+        Ok(self.swap_parameters(&mut state, parameters, source))
+    }
+
+    /// 将部分更新合并到当前控制参数并记录变更差异
+    ///
+    /// 合并与替换在同一把锁内完成，不会覆盖并发修改的其他字段
+    ///
+    /// # 参数
+    /// * `patch` - 部分更新
+    /// * `source` - 变更来源
+    pub async fn patch_parameters(
+        &self,
+        patch: &ControlParametersPatch,
+        source: ParameterChangeSource,
+    ) -> AppResult<AppliedParameters<ControlParameters>> {
+        let mut state = self.state.lock().await;
+        let parameters = patch.merged(&self.parameters())?;
+        Ok(self.swap_parameters(&mut state, parameters, source))
+    }
+
+    fn swap_parameters(
+        &self,
+        state: &mut LoopState,
+        parameters: ControlParameters,
+        source: ParameterChangeSource,
+    ) -> AppliedParameters<ControlParameters> {
+        let old = std::mem::replace(
+            &mut *self.parameters.write().unwrap_or_else(|e| e.into_inner()),
+            parameters.clone(),
+        );
+        state.exit_guard.set_critical_temperature(parameters.critical_temperature);
+        state.reset_pid();
+
+        let diff = self.parameter_history.record(source, Some(&old), &parameters);
+        AppliedParameters { parameters, diff }
+    }
+
+    /// 最近的控制参数变更，按时间倒序
+    ///
+    /// # 参数
+    /// * `limit` - 返回条数上限，为空时返回全部
+    pub fn parameter_history(&self, limit: Option<usize>) -> Vec<ParameterDiff> {
+        self.parameter_history.list(limit)
+    }
+
+    /// 控制周期
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// 控制循环运行状态
+    ///
+    /// 控制周期失败（如BMC读取失败）也算完成一个周期，卡住的控制周期则不算
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn liveness(&self, now: DateTime<Utc>) -> ControlLoopLiveness {
+        let heartbeat = self.heartbeat.read().unwrap_or_else(|e| e.into_inner());
+        let Some(started_at) = heartbeat.started_at else {
+            return ControlLoopLiveness::Disabled;
+        };
+        let deadline = chrono::Duration::from_std(self.interval * 2).unwrap_or(chrono::Duration::MAX);
+        match heartbeat.last_cycle {
+            Some(last_cycle) if now - last_cycle <= deadline => ControlLoopLiveness::Running,
+            None if now - started_at <= deadline => ControlLoopLiveness::Starting,
+            _ => ControlLoopLiveness::Stalled,
+        }
+    }
+
+    /// 执行一次控制周期
+    ///
+    /// # 参数
+    /// * `at` - 控制时间
+    pub async fn tick(&self, at: DateTime<Utc>) -> AppResult<ControlTick> {
+        let hottest = self.hottest_sensor()?;
+        let parameters = self.parameters();
+        let pid = parameters.pid_params.clone().unwrap_or(DEFAULT_PID);
+        let min_percent = f64::from(parameters.min_fan_speed.clamp(0, 100));
+        let max_percent = f64::from(parameters.max_fan_speed.clamp(0, 100)).max(min_percent);
+
+        let mut state = self.state.lock().await;
+        let dt = state
+            .last_tick
+            .map(|last| (at - last).num_milliseconds() as f64 / 1000.0)
+            .filter(|dt| *dt > 0.0)
+            .unwrap_or(self.interval.as_secs_f64());
+        state.last_tick = Some(at);
+
+        if !state.emergency && hottest.temperature >= parameters.critical_temperature {
+            warn!(
+                "{} 温度 {:.1}°C 达到临界温度 {:.1}°C，进入紧急冷却",
+                hottest.sensor_id, hottest.temperature, parameters.critical_temperature
+            );
+            state.emergency = true;
+            state.exit_guard.reset();
+            state.reset_pid();
+        }
+        if state.emergency {
+            state.exit_guard.observe(hottest.temperature, at);
+            if state.exit_guard.check_exit(at).is_ok() {
+                info!("{} 温度 {:.1}°C 持续回落，退出紧急冷却", hottest.sensor_id, hottest.temperature);
+                state.emergency = false;
+                state.reset_pid();
+            }
+        }
+        let emergency = state.emergency;
+
+        // 自动控制时段外保持当前转速，或按手动档案下发；紧急冷却不受时段和手动档案限制
+        let manual_profile = state
+            .manual_override
+            .clone()
+            .or_else(|| self.schedule.config().manual_profile.clone());
+        let manual_profile = manual_profile.as_ref();
+        let action_type = if emergency {
+            ControlActionType::EmergencyCooling
+        } else if state.manual_override.is_some() {
+            ControlActionType::ManualFanControl
+        } else if self.schedule.is_active(at.with_timezone(&Local).naive_local()) {
+            ControlActionType::AutomaticControl
+        } else if manual_profile.is_some() {
+            ControlActionType::ManualFanControl
+        } else {
+            // 保持转速期间补发死信中未能下发的转速
+            state.reset_pid();
+            let source = &self.source;
+            self.write_retry
+                .reconcile(|fan_id, percent| async move { write_fan_speed(source.as_ref(), &fan_id, percent) })
+                .await;
+            return Ok(ControlTick {
+                timestamp: at,
+                sensor_id: hottest.sensor_id,
+                input_temperature: hottest.temperature,
+                action_type: None,
+                output_percent: None,
+                emergency,
+                written_fans: Vec::new(),
+                stalled_fans: Vec::new(),
+                degraded_fans: Vec::new(),
+            });
+        };
+
+        let (proportional, integral, derivative, raw_output) = match action_type {
+            ControlActionType::AutomaticControl => {
+                // 容差范围内视为已达目标，不再累积积分
+                let error = hottest.temperature - parameters.target_temperature;
+                let error = if error.abs() <= parameters.temperature_tolerance { 0.0 } else { error };
+                state.integral =
+                    (state.integral + pid.ki * error * dt).clamp(-pid.integral_limit, pid.integral_limit);
+                let derivative = state.last_error.map_or(0.0, |last| pid.kd * (error - last) / dt);
+                state.last_error = Some(error);
+                let proportional = pid.kp * error;
+                (
+                    proportional,
+                    state.integral,
+                    derivative,
+                    min_percent + proportional + state.integral + derivative,
+                )
+            }
+            _ => {
+                state.reset_pid();
+                (0.0, 0.0, 0.0, 100.0)
+            }
+        };
+        let quiet_cap = match action_type {
+            ControlActionType::AutomaticControl => self.quiet_hours.fan_cap(at, hottest.temperature),
+            _ => None,
+        };
+        let output = match action_type {
+            ControlActionType::AutomaticControl => {
+                let output = raw_output.clamp(min_percent, max_percent);
+                quiet_cap.map_or(output, |cap| output.min(cap))
+            }
+            ControlActionType::ManualFanControl | ControlActionType::NoOp => {
+                manual_profile.map_or(0.0, |profile| profile.default_percent)
+            }
+            ControlActionType::EmergencyCooling => 100.0,
+        };
+        let percent_for = |fan_id: &str| match (action_type, manual_profile) {
+            (ControlActionType::ManualFanControl, Some(profile)) => profile.percent_for(fan_id),
+            _ => output,
+        };
+
+        let fans = self
+            .source
+            .get_fan_sensors()
+            .map_err(|e| AppError::ipmi_error(format!("读取风扇失败: {}", e)))?;
+        let mut written = Vec::new();
+        let mut stalled_fans = Vec::new();
+        let mut degraded_fans = Vec::new();
+        for fan in fans.iter().filter(|fan| fan.controllable) {
+            // 用上个周期下发的占空比比较本周期读到的转速，风扇已有一个周期稳定转速
+            if let Some(duty_percent) = state.commanded.get(&fan.fan_id).copied() {
+                let measured_rpm = f64::from(fan.speed_rpm);
+                if self.degradation.check(&fan.fan_id, duty_percent, measured_rpm, at).await.is_some() {
+                    degraded_fans.push(fan.fan_id.clone());
+                }
+            }
+            let mut percent = percent_for(&fan.fan_id);
+            if let (ControlActionType::AutomaticControl, Some(power_cap)) = (action_type, &self.power_cap) {
+                let current = state
+                    .commanded
+                    .get(&fan.fan_id)
+                    .copied()
+                    .unwrap_or(f64::from(fan.speed_percent));
+                percent = power_cap
+                    .coordinate(&fan.fan_id, current, percent, hottest.temperature)
+                    .await;
+            }
+            let unchanged = state
+                .commanded
+                .get(&fan.fan_id)
+                .is_some_and(|last| (last - percent).abs() <= IDENTICAL_WRITE_TOLERANCE);
+            if self.suppress_identical_writes && unchanged {
+                continue;
+            }
+            // 重试耗尽后记入死信，下一个周期照常重新下发
+            let source = &self.source;
+            let fan_id = fan.fan_id.as_str();
+            let result = self
+                .write_retry
+                .write(fan_id, percent, action_type.as_str(), || async move {
+                    write_fan_speed(source.as_ref(), fan_id, percent)
+                })
+                .await;
+            if result.is_none() {
+                continue;
+            }
+            state.commanded.insert(fan.fan_id.clone(), percent);
+            written.push((fan.fan_id.clone(), percent));
+
+            // 低速下停转的风扇先发起转脉冲，再回到目标转速
+            if let Some(kickstart) = self.kickstart.as_ref().filter(|_| fan.speed_rpm == 0) {
+                match fan_kickstart::kickstart_if_stalled(&self.source, kickstart, &fan.fan_id, percent).await {
+                    Ok(KickstartOutcome::NotNeeded) => {}
+                    Ok(KickstartOutcome::Recovered { rpm }) => {
+                        info!("风扇 {} 低速停转，起转脉冲后恢复转动: {:.0} RPM", fan.fan_id, rpm)
+                    }
+                    Ok(KickstartOutcome::Stalled) => {
+                        warn!("风扇 {} 起转脉冲后仍无转速，可能已损坏", fan.fan_id);
+                        stalled_fans.push(fan.fan_id.clone());
+                    }
+                    Err(e) => warn!("风扇 {} 起转检测失败: {}", fan.fan_id, e),
+                }
+            }
+        }
+        drop(state);
+
+        let written_fans: Vec<String> = written.iter().map(|(fan_id, _)| fan_id.clone()).collect();
+        let readback_percent = (!written_fans.is_empty())
+            .then(|| self.source.get_fan_sensors().ok())
+            .flatten()
+            .map(|fans| {
+                let readbacks: Vec<f64> = fans
+                    .iter()
+                    .filter(|fan| written_fans.contains(&fan.fan_id))
+                    .map(|fan| f64::from(fan.speed_percent))
+                    .collect();
+                readbacks.iter().sum::<f64>() / readbacks.len().max(1) as f64
+            });
+        for (fan_id, percent) in written {
+            let raw_output = match action_type {
+                ControlActionType::AutomaticControl => raw_output,
+                _ => percent,
+            };
+            self.decisions
+                .record(ControlDecision {
+                    timestamp: at,
+                    sensor_id: hottest.sensor_id.clone(),
+                    fan_id,
+                    input_temperature: hottest.temperature,
+                    setpoint: parameters.target_temperature,
+                    proportional,
+                    integral,
+                    derivative,
+                    raw_output,
+                    limited_output: percent,
+                    quiet_cap,
+                    readback_percent,
+                    action_type,
+                })
+                .await;
+        }
+
+        Ok(ControlTick {
+            timestamp: at,
+            sensor_id: hottest.sensor_id,
+            input_temperature: hottest.temperature,
+            action_type: Some(action_type),
+            output_percent: Some(output),
+            emergency,
+            written_fans,
+            stalled_fans,
+            degraded_fans,
+        })
+    }
+
+    /// 应用手动转速档案，为空时恢复自动控制
+    ///
+    /// 下发前记录各风扇当前转速，任一风扇下发失败时把已下发的风扇恢复到原转速并返回错误，
+    /// 不会留下部分风扇按新档案运行的状态。应用成功后控制循环按该档案下发，紧急冷却仍然优先
+    ///
+    /// # 参数
+    /// * `profile` - 手动转速档案
+    pub async fn apply_manual_profile(&self, profile: Option<ManualFanProfile>) -> AppResult<Vec<String>> {
+        let mut state = self.state.lock().await;
+        let Some(profile) = profile else {
+            if state.manual_override.take().is_some() {
+                info!("手动转速档案已撤销，恢复自动控制");
+                state.reset_pid();
+            }
+            return Ok(Vec::new());
+        };
+        profile.validate("profile")?;
+
+        let fans = self
+            .source
+            .get_fan_sensors()
+            .map_err(|e| AppError::ipmi_error(format!("读取风扇失败: {}", e)))?;
+        let mut applied: Vec<(String, f64, f64)> = Vec::new();
+        for fan in fans.iter().filter(|fan| fan.controllable) {
+            let previous = state
+                .commanded
+                .get(&fan.fan_id)
+                .copied()
+                .unwrap_or(f64::from(fan.speed_percent));
+            let percent = profile.percent_for(&fan.fan_id);
+            if let Err(e) = write_fan_speed(self.source.as_ref(), &fan.fan_id, percent) {
+                for (fan_id, previous, _) in applied.iter().rev() {
+                    if let Err(rollback_error) = write_fan_speed(self.source.as_ref(), fan_id, *previous) {
+                        warn!("风扇 {} 回滚到 {:.0}% 失败: {}", fan_id, previous, rollback_error);
+                    }
+                }
+                return Err(AppError::ipmi_error(format!(
+                    "风扇 {} 下发失败，已将 {} 个风扇恢复到原转速: {}",
+                    fan.fan_id,
+                    applied.len(),
+                    e
+                )));
+            }
+            applied.push((fan.fan_id.clone(), previous, percent));
+        }
+
+        for (fan_id, _, percent) in &applied {
+            state.commanded.insert(fan_id.clone(), *percent);
+        }
+        state.manual_override = Some(profile);
+        state.reset_pid();
+        Ok(applied.into_iter().map(|(fan_id, _, _)| fan_id).collect())
+    }
+
+    /// 下发单个风扇的转速
+    ///
+    /// 开启 `suppress_identical_fan_writes` 且与上次下发的转速相差不超过0.5%时不写入BMC，
+    /// 控制决策记为 [`ControlActionType::NoOp`]。手动档案生效时同时更新档案中该风扇的转速，
+    /// 否则下一个自动控制周期仍会按温度调节该风扇
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `percent` - 转速（百分比）
+    /// * `at` - 指令时间
+    pub async fn set_fan_speed(&self, fan_id: &str, percent: f64, at: DateTime<Utc>) -> AppResult<FanSpeedCommand> {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(AppError::validation_error("speed_percent", "转速必须在0-100%之间"));
+        }
+        let mut state = self.state.lock().await;
+        let fans = self
+            .source
+            .get_fan_sensors()
+            .map_err(|e| AppError::ipmi_error(format!("读取风扇失败: {}", e)))?;
+        let fan = fans
+            .iter()
+            .find(|fan| fan.fan_id == fan_id)
+            .ok_or_else(|| AppError::not_found_error("风扇", fan_id))?;
+        if !fan.controllable {
+            return Err(AppError::validation_error("fan_id", format!("风扇 {} 不可调速", fan_id)));
+        }
+
+        let last = state.commanded.get(fan_id).copied();
+        let unchanged = last.is_some_and(|last| (last - percent).abs() <= IDENTICAL_WRITE_TOLERANCE);
+        let written = !(self.suppress_identical_writes && unchanged);
+        if written {
+            write_fan_speed(self.source.as_ref(), fan_id, percent)?;
+            state.commanded.insert(fan_id.to_string(), percent);
+        }
+        if let Some(profile) = state.manual_override.as_mut() {
+            profile.fans.insert(fan_id.to_string(), percent);
+        }
+        drop(state);
+
+        let command = FanSpeedCommand {
+            fan_id: fan_id.to_string(),
+            previous_percent: last.unwrap_or(f64::from(fan.speed_percent)),
+            speed_percent: percent,
+            written,
+        };
+        match self.hottest_sensor() {
+            Ok(hottest) => {
+                self.decisions
+                    .record(ControlDecision {
+                        timestamp: at,
+                        sensor_id: hottest.sensor_id,
+                        fan_id: command.fan_id.clone(),
+                        input_temperature: hottest.temperature,
+                        setpoint: self.parameters().target_temperature,
+                        proportional: 0.0,
+                        integral: 0.0,
+                        derivative: 0.0,
+                        raw_output: percent,
+                        limited_output: percent,
+                        quiet_cap: None,
+                        readback_percent: None,
+                        action_type: if written {
+                            ControlActionType::ManualFanControl
+                        } else {
+                            ControlActionType::NoOp
+                        },
+                    })
+                    .await
+            }
+            Err(e) => warn!("风扇 {} 转速指令未记入控制决策: {}", fan_id, e),
+        }
+        Ok(command)
+    }
+
+    /// 各可调速风扇的当前转速
+    ///
+    /// 优先使用最后一次下发的转速，控制循环尚未下发过的风扇使用读数
+    pub async fn fan_speeds(&self) -> AppResult<BTreeMap<String, f64>> {
+        let state = self.state.lock().await;
+        let fans = self
+            .source
+            .get_fan_sensors()
+            .map_err(|e| AppError::ipmi_error(format!("读取风扇失败: {}", e)))?;
+        Ok(fans
+            .into_iter()
+            .filter(|fan| fan.controllable)
+            .map(|fan| {
+                let percent = state
+                    .commanded
+                    .get(&fan.fan_id)
+                    .copied()
+                    .unwrap_or(f64::from(fan.speed_percent));
+                (fan.fan_id, percent)
+            })
+            .collect())
+    }
+
+    /// 把风扇恢复到给定转速，之后按手动档案保持
+    ///
+    /// 任一风扇已不存在或不可调速时返回404且不下发任何风扇；下发与回滚同 [`Self::apply_manual_profile`]。
+    /// 不在转速表中的风扇（如之后新增的风扇）按表中的最高转速下发
+    ///
+    /// # 参数
+    /// * `speeds` - 按风扇ID的转速（百分比）
+    pub async fn restore_fan_speeds(&self, speeds: &BTreeMap<String, f64>) -> AppResult<Vec<String>> {
+        let fans = self
+            .source
+            .get_fan_sensors()
+            .map_err(|e| AppError::ipmi_error(format!("读取风扇失败: {}", e)))?;
+        if let Some(missing) = speeds
+            .keys()
+            .find(|fan_id| !fans.iter().any(|fan| fan.controllable && &fan.fan_id == *fan_id))
+        {
+            return Err(AppError::not_found_error("风扇", missing.as_str()));
+        }
+
+        let default_percent = speeds.values().copied().fold(0.0, f64::max);
+        self.apply_manual_profile(Some(ManualFanProfile {
+            default_percent,
+            fans: speeds.clone(),
+        }))
+        .await
+    }
+
+    /// 手动退出紧急冷却
+    ///
+    /// 温度未持续低于 `临界温度 - 回差` 时拒绝退出，`force` 为true时跳过检查。
+    /// 温度仍不低于临界温度时，下一个控制周期会重新进入紧急冷却
+    ///
+    /// # 参数
+    /// * `force` - 是否跳过温度确认
+    /// * `now` - 当前时间
+    pub async fn exit_emergency(&self, force: bool, now: DateTime<Utc>) -> AppResult<()> {
+        let mut state = self.state.lock().await;
+        if !state.emergency {
+            return Err(AppError::ConflictError {
+                message: "当前未处于紧急冷却".to_string(),
+            });
+        }
+        if !force {
+            state.exit_guard.check_exit(now)?;
+        }
+        state.emergency = false;
+        state.reset_pid();
+        Ok(())
+    }
+
+    /// 按控制周期循环执行，单个周期失败只记录警告
+    ///
+    /// # 参数
+    /// * `poll_overruns` - 控制周期超时的跳过计数
+    pub async fn run(self, poll_overruns: PollOverruns) {
+        let mut ticker = PollTicker::new("control_loop", self.interval, poll_overruns);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.tick(Utc::now()).await {
+                warn!("控制周期执行失败: {}", e);
+            }
+            self.heartbeat.write().unwrap_or_else(|e| e.into_inner()).last_cycle = Some(Utc::now());
+        }
+    }
+
+    /// 在监督下启动控制循环，控制循环panic时由监督器进入安全状态后重新启动
+    ///
+    /// # 参数
+    /// * `supervisor` - 控制循环监督器
+    /// * `poll_overruns` - 控制周期超时的跳过计数
+    pub fn start(&self, supervisor: Arc<ControlLoopSupervisor>, poll_overruns: PollOverruns) -> tokio::task::AbortHandle {
+        let handle = self.spawn(Arc::clone(&supervisor), poll_overruns.clone());
+        let abort = handle.abort_handle();
+        *self.runner.lock().unwrap_or_else(|e| e.into_inner()) = Some(LoopRunner {
+            supervisor,
+            poll_overruns,
+            handle,
+        });
+        abort
+    }
+
+    /// 重启控制循环，返回新的启动时间
+    ///
+    /// 等待进行中的控制周期完成后停止控制循环；间隙内风扇交还BMC自动控制，紧急冷却时保持全速。
+    /// 重启后PID历史清零，已下发的转速全部重新下发
+    pub async fn restart(&self) -> AppResult<DateTime<Utc>> {
+        let runner = self
+            .runner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .ok_or_else(|| AppError::ConflictError {
+                message: "控制循环未启动或正在重启".to_string(),
+            })?;
+
+        // 控制周期全程持有状态锁，拿到锁时没有进行中的控制周期
+        let mut state = self.state.lock().await;
+        runner.handle.abort();
+        let _ = runner.handle.await;
+        let released = if state.emergency {
+            safe_boot::SafeBootFans::set_all_fan_speeds(self.source.as_ref(), 100)
+        } else {
+            self.source.restore_automatic_fan_control()
+        };
+        if let Err(e) = released {
+            warn!("重启控制循环时交还风扇控制失败: {}", e);
+        }
+        state.reset_pid();
+        state.last_tick = None;
+        state.commanded.clear();
+        drop(state);
+
+        let handle = self.spawn(Arc::clone(&runner.supervisor), runner.poll_overruns.clone());
+        let started_at = self.heartbeat.read().unwrap_or_else(|e| e.into_inner()).started_at.unwrap_or_else(Utc::now);
+        *self.runner.lock().unwrap_or_else(|e| e.into_inner()) = Some(LoopRunner { handle, ..runner });
+        info!("控制循环已重启");
+        Ok(started_at)
+    }
+
+    fn spawn(&self, supervisor: Arc<ControlLoopSupervisor>, poll_overruns: PollOverruns) -> tokio::task::JoinHandle<()> {
+        *self.heartbeat.write().unwrap_or_else(|e| e.into_inner()) = Heartbeat {
+            started_at: Some(Utc::now()),
+            last_cycle: None,
+        };
+        let control_loop = self.clone();
+        supervisor.supervise(move || control_loop.clone().run(poll_overruns.clone()))
+    }
+
+    /// 全部未屏蔽的温度传感器中温度最高的一个
+    fn hottest_sensor(&self) -> AppResult<TemperatureSensor> {
+        self.source
+            .get_temperature_sensors()
+            .map_err(|e| AppError::ipmi_error(format!("读取温度传感器失败: {}", e)))?
+            .into_iter()
+            .filter(|sensor| !self.sensor_blacklist.contains(&sensor.sensor_id) && sensor.temperature.is_finite())
+            .max_by(|a, b| a.temperature.total_cmp(&b.temperature))
+            .ok_or_else(|| AppError::ipmi_error("没有可用的温度读数"))
+    }
+}
+
+/// 下发一个风扇的转速百分比
+fn write_fan_speed(source: &dyn SensorSource, fan_id: &str, percent: f64) -> AppResult<()> {
+    source
+        .set_fan_speed(fan_id, percent.clamp(0.0, 100.0).round() as u8)
+        .map_err(|e| AppError::ipmi_error(format!("设置风扇 {} 转速 {:.0}% 失败: {}", fan_id, percent, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::services::auto_control_schedule::{AutoControlScheduleConfig, AutoControlWindow};
+    use crate::models::control::{QuietHoursSchedule, QuietWindow};
+    use crate::services::alert_history_store::InMemoryAlertHistoryStore;
+    use crate::services::alert_store::AlertStore;
+    use crate::services::control_alert_sink::ControlAlertSink;
+    use crate::services::control_supervisor::ControlFailSafe;
+    use crate::services::control_write_retry::ControlWriteRetryConfig;
+    use crate::services::fan_degradation::{FanDegradationConfig, RpmCurvePoint};
+    use crate::services::power_cap::{PowerCapConfig, PowerReading};
+    use crate::services::decision_store::DEFAULT_RECENT_DECISIONS;
+    use crate::services::event_store::{InMemorySystemEventStore, SystemEventQuery, SystemEventStore};
+    use crate::services::ipmi_service::FanSensor;
+    use crate::test_harness::MockIpmiService;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn control_loop(ipmi: Arc<MockIpmiService>) -> FanControlLoop {
+        let mut config = AppConfig::default().control;
+        config.suppress_identical_fan_writes = true;
+        FanControlLoop::new(
+            ipmi,
+            SensorBlacklist::default(),
+            &config,
+            ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_tick_follows_hottest_sensor_and_goes_full_speed_at_critical() {
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        let control_loop = control_loop(ipmi.clone());
+        let start = Utc::now();
+
+        // 低于目标温度时保持最低转速，相同指令不重复写入
+        let tick = control_loop.tick(start).await.unwrap();
+        assert_eq!(tick.sensor_id, "CPU1_TEMP");
+        let floor = f64::from(control_loop.parameters().min_fan_speed);
+        assert_eq!(tick.output_percent, Some(floor));
+        assert_eq!(tick.written_fans, ["FAN1", "FAN2"]);
+        let tick = control_loop.tick(start + chrono::Duration::seconds(10)).await.unwrap();
+        assert!(tick.written_fans.is_empty());
+
+        // 高于目标温度时按偏差提速
+        ipmi.set_temperature("CPU2_TEMP", 75.0);
+        let tick = control_loop.tick(start + chrono::Duration::seconds(20)).await.unwrap();
+        assert_eq!(tick.sensor_id, "CPU2_TEMP");
+        let output = tick.output_percent.unwrap();
+        assert!(output > floor && output < 100.0);
+
+        ipmi.set_temperature("CPU2_TEMP", 90.0);
+        let tick = control_loop.tick(start + chrono::Duration::seconds(30)).await.unwrap();
+        assert!(tick.emergency);
+        assert_eq!(ipmi.fan_speed_writes().last(), Some(&("FAN2".to_string(), 100)));
+
+        let decisions = control_loop.decisions.recent(None).await;
+        assert_eq!(decisions.len(), 6);
+        assert_eq!(decisions[0].limited_output, 100.0);
+        assert_eq!(decisions[0].input_temperature, 90.0);
+    }
+
+    #[tokio::test]
+    async fn test_emergency_cooling_holds_until_sustained_cool_down() {
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        let control_loop = control_loop(ipmi.clone());
+        let start = Utc::now();
+
+        ipmi.set_temperature("CPU1_TEMP", 86.0);
+        assert!(control_loop.tick(start).await.unwrap().emergency);
+
+        // 低于临界但未低于 临界-回差，仍保持满速
+        ipmi.set_temperature("CPU1_TEMP", 82.0);
+        assert!(control_loop.tick(start + chrono::Duration::seconds(10)).await.unwrap().emergency);
+
+        ipmi.set_temperature("CPU1_TEMP", 78.0);
+        assert!(control_loop.tick(start + chrono::Duration::seconds(20)).await.unwrap().emergency);
+        assert!(control_loop.tick(start + chrono::Duration::seconds(50)).await.unwrap().emergency);
+        let tick = control_loop.tick(start + chrono::Duration::seconds(80)).await.unwrap();
+        assert!(!tick.emergency);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_holds_outside_window_while_emergency_still_acts() {
+        let start = Utc::now();
+        let local = start.with_timezone(&Local).naive_local();
+        let mut config = AppConfig::default().control;
+        config.auto_schedule = AutoControlScheduleConfig {
+            enabled: true,
+            windows: vec![AutoControlWindow {
+                name: "test".to_string(),
+                start: (local - chrono::Duration::hours(1)).time(),
+                end: (local + chrono::Duration::hours(1)).time(),
+                weekdays: Vec::new(),
+            }],
+            manual_profile: None,
+        };
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        let decisions = ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS);
+        let control_loop = FanControlLoop::new(ipmi.clone(), SensorBlacklist::default(), &config, decisions.clone());
+
+        // 时段内按温度调速
+        ipmi.set_temperature("CPU2_TEMP", 75.0);
+        let tick = control_loop.tick(start).await.unwrap();
+        assert_eq!(tick.action_type, Some(ControlActionType::AutomaticControl));
+        assert_eq!(tick.written_fans.len(), 2);
+
+        // 时段外保持当前转速
+        let outside = start + chrono::Duration::hours(3);
+        ipmi.set_temperature("CPU2_TEMP", 80.0);
+        let tick = control_loop.tick(outside).await.unwrap();
+        assert_eq!(tick.action_type, None);
+        assert!(tick.written_fans.is_empty());
+        assert_eq!(ipmi.fan_speed_writes().len(), 2);
+
+        // 时段外仍执行紧急冷却
+        ipmi.set_temperature("CPU2_TEMP", 90.0);
+        let tick = control_loop.tick(outside + chrono::Duration::seconds(10)).await.unwrap();
+        assert_eq!(tick.action_type, Some(ControlActionType::EmergencyCooling));
+        assert_eq!(ipmi.fan_speed_writes().last(), Some(&("FAN2".to_string(), 100)));
+
+        // 配置手动档案时，时段外按档案下发
+        config.auto_schedule.manual_profile = Some(ManualFanProfile {
+            default_percent: 35.0,
+            fans: [("FAN2".to_string(), 50.0)].into_iter().collect(),
+        });
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        let control_loop = FanControlLoop::new(ipmi.clone(), SensorBlacklist::default(), &config, decisions);
+        let tick = control_loop.tick(outside).await.unwrap();
+        assert_eq!(tick.action_type, Some(ControlActionType::ManualFanControl));
+        assert_eq!(ipmi.fan_speed_writes(), [("FAN1".to_string(), 35), ("FAN2".to_string(), 50)]);
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_cap_output_until_emergency_temperature() {
+        let start = Utc::now();
+        let local = start.with_timezone(&Local).naive_local();
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        let control_loop = control_loop(ipmi.clone());
+        control_loop
+            .quiet_hours()
+            .update(QuietHoursSchedule {
+                enabled: true,
+                windows: vec![QuietWindow {
+                    name: "night".to_string(),
+                    start: (local - chrono::Duration::hours(1)).time(),
+                    end: (local + chrono::Duration::hours(1)).time(),
+                    weekdays: Vec::new(),
+                    max_fan_percent: 40.0,
+                }],
+                emergency_temperature: 80.0,
+                updated_at: start,
+            })
+            .await
+            .unwrap();
+
+        // 时段内按偏差提速但不超过上限
+        ipmi.set_temperature("CPU2_TEMP", 79.0);
+        let tick = control_loop.tick(start).await.unwrap();
+        assert_eq!(tick.output_percent, Some(40.0));
+        let decisions = control_loop.decisions.recent(None).await;
+        assert_eq!(decisions[0].quiet_cap, Some(40.0));
+        assert!(decisions[0].raw_output > 40.0);
+
+        // 达到静音计划的紧急温度后不受上限约束
+        ipmi.set_temperature("CPU2_TEMP", 82.0);
+        let tick = control_loop.tick(start + chrono::Duration::seconds(10)).await.unwrap();
+        assert!(tick.output_percent.unwrap() > 40.0);
+        assert_eq!(control_loop.decisions.recent(Some(1)).await[0].quiet_cap, None);
+
+        // 时段外不限制
+        ipmi.set_temperature("CPU2_TEMP", 79.0);
+        let tick = control_loop.tick(start + chrono::Duration::hours(3)).await.unwrap();
+        assert!(tick.output_percent.unwrap() > 40.0);
+    }
+
+    struct FixedPower(f64);
+
+    impl PowerReading for FixedPower {
+        fn power_watts(&self) -> Result<f64, Box<dyn std::error::Error>> {
+            Ok(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_near_cap_power_limits_boost_and_raises_alert() {
+        let power_cap = PowerCapConfig {
+            enabled: true,
+            cap_watts: 500.0,
+            ..Default::default()
+        };
+        let boost = |power_watts: f64| {
+            let power_cap = power_cap.clone();
+            async move {
+                let ipmi = Arc::new(MockIpmiService::healthy_server());
+                let alerts = AlertStore::new(100);
+                let sink = ControlAlertSink::new(alerts.clone(), Arc::new(InMemoryAlertHistoryStore::new()));
+                let control_loop = control_loop(ipmi.clone()).with_power_cap(
+                    PowerCapCoordinator::new(power_cap, Arc::new(FixedPower(power_watts))).with_alert_sink(Arc::new(sink)),
+                );
+                ipmi.set_temperature("CPU2_TEMP", 80.0);
+                let decisions = control_loop.decisions.clone();
+                control_loop.tick(Utc::now()).await.unwrap();
+                (decisions.recent(None).await, alerts.list())
+            }
+        };
+
+        // 功耗远低于上限时按PID输出提速
+        let (decisions, alerts) = boost(300.0).await;
+        assert!(decisions.iter().all(|d| d.limited_output == 100.0));
+        assert!(alerts.is_empty());
+
+        // 接近上限时提速被限制在余量内，并发出功率告警
+        let (decisions, alerts) = boost(470.0).await;
+        assert!(decisions.iter().all(|d| d.limited_output < 100.0 && d.raw_output > 100.0));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].alert_type, "power");
+    }
+
+    /// 占空比达到50%才能起转的风扇
+    struct StickyFans(Arc<MockIpmiService>);
+
+    impl SensorSource for StickyFans {
+        fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+            self.0.get_temperature_sensors()
+        }
+
+        fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
+            self.0.get_fan_sensors()
+        }
+
+        fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+            if speed_percent >= 50 && fan_id != "FAN2" {
+                self.0.set_fan_rpm(fan_id, 600);
+            }
+            self.0.set_fan_speed(fan_id, speed_percent)
+        }
+
+        fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+            self.0.restore_automatic_fan_control()
+        }
+
+        fn test_connection(&self) -> Result<bool, Box<dyn std::error::Error>> {
+            self.0.test_connection()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kickstart_pulse_restarts_stalled_fan_at_low_speed() {
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        ipmi.set_fan_rpm("FAN1", 0);
+        // FAN2 脉冲后也不会转动
+        ipmi.set_fan_rpm("FAN2", 0);
+        let mut config = AppConfig::default().control;
+        config.kickstart = Some(FanKickstartConfig {
+            enabled: true,
+            low_speed_threshold: 30.0,
+            duty_percent: 80.0,
+            duration_ms: 1,
+        });
+        let control_loop = FanControlLoop::new(
+            Arc::new(StickyFans(ipmi.clone())),
+            SensorBlacklist::default(),
+            &config,
+            ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS),
+        );
+
+        let tick = control_loop.tick(Utc::now()).await.unwrap();
+        let floor = control_loop.parameters().min_fan_speed as u8;
+        // 先下发目标转速，停转时发脉冲再回到目标转速
+        assert_eq!(
+            ipmi.fan_speed_writes(),
+            [
+                ("FAN1".to_string(), floor),
+                ("FAN1".to_string(), 80),
+                ("FAN1".to_string(), floor),
+                ("FAN2".to_string(), floor),
+                ("FAN2".to_string(), 80),
+                ("FAN2".to_string(), floor),
+            ]
+        );
+        assert_eq!(tick.stalled_fans, ["FAN2"]);
+
+        // FAN1 已转动，不再发脉冲，相同转速也不重复下发
+        control_loop.tick(Utc::now()).await.unwrap();
+        assert_eq!(ipmi.fan_speed_writes().iter().filter(|(fan_id, _)| fan_id == "FAN1").count(), 3);
+    }
+
+    /// 可切换某个风扇下发是否失败的数据源
+    struct FlakyFans {
+        inner: Arc<MockIpmiService>,
+        failing_fan: &'static str,
+        failing: AtomicBool,
+    }
+
+    impl SensorSource for FlakyFans {
+        fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+            self.inner.get_temperature_sensors()
+        }
+
+        fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
+            self.inner.get_fan_sensors()
+        }
+
+        fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+            if fan_id == self.failing_fan && self.failing.load(Ordering::SeqCst) {
+                return Err("BMC busy".into());
+            }
+            self.inner.set_fan_speed(fan_id, speed_percent)
+        }
+
+        fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+            self.inner.restore_automatic_fan_control()
+        }
+
+        fn test_connection(&self) -> Result<bool, Box<dyn std::error::Error>> {
+            self.inner.test_connection()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_writes_are_retried_dead_lettered_and_alerted() {
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        let source = Arc::new(FlakyFans {
+            inner: ipmi.clone(),
+            failing_fan: "FAN2",
+            failing: AtomicBool::new(true),
+        });
+        let mut config = AppConfig::default().control;
+        config.write_retry = ControlWriteRetryConfig {
+            max_attempts: 2,
+            retry_delay_ms: 0,
+            alert_after_failures: 2,
+        };
+        let alerts = AlertStore::new(100);
+        let control_loop = FanControlLoop::new(
+            source.clone(),
+            SensorBlacklist::default(),
+            &config,
+            ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS),
+        )
+        .with_write_alerts(Arc::new(ControlAlertSink::new(
+            alerts.clone(),
+            Arc::new(InMemoryAlertHistoryStore::new()),
+        )));
+        let start = Utc::now();
+
+        let tick = control_loop.tick(start).await.unwrap();
+        assert_eq!(tick.written_fans, ["FAN1"]);
+        assert_eq!(control_loop.failed_writes()[0].fan_id, "FAN2");
+        assert!(alerts.list().is_empty());
+
+        // 连续两个周期重试耗尽后告警
+        control_loop.tick(start + chrono::Duration::seconds(10)).await.unwrap();
+        let failed = control_loop.failed_writes();
+        assert_eq!(failed[0].consecutive_failures, 2);
+        let raised = alerts.list();
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].source_id, "FAN2");
+
+        // 恢复后下一个周期重新下发并移出死信
+        source.failing.store(false, Ordering::SeqCst);
+        let tick = control_loop.tick(start + chrono::Duration::seconds(20)).await.unwrap();
+        assert_eq!(tick.written_fans, ["FAN2"]);
+        assert!(control_loop.failed_writes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_manual_profile_rolls_back_when_a_fan_write_fails() {
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        ipmi.set_fan_rpm("FAN3", 6000);
+        let source = Arc::new(FlakyFans {
+            inner: ipmi.clone(),
+            failing_fan: "FAN3",
+            failing: AtomicBool::new(true),
+        });
+        let control_loop = FanControlLoop::new(
+            source.clone(),
+            SensorBlacklist::default(),
+            &AppConfig::default().control,
+            ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS),
+        );
+        let profile = ManualFanProfile {
+            default_percent: 80.0,
+            fans: Default::default(),
+        };
+
+        let error = control_loop.apply_manual_profile(Some(profile.clone())).await.unwrap_err();
+        assert!(error.to_string().contains("FAN3"));
+        // 前两个风扇按相反顺序恢复到下发前的转速
+        let writes = ipmi.fan_speed_writes();
+        assert_eq!(
+            writes,
+            [
+                ("FAN1".to_string(), 80),
+                ("FAN2".to_string(), 80),
+                ("FAN2".to_string(), 40),
+                ("FAN1".to_string(), 40),
+            ]
+        );
+        let tick = control_loop.tick(Utc::now()).await.unwrap();
+        assert_eq!(tick.action_type, Some(ControlActionType::AutomaticControl));
+
+        source.failing.store(false, Ordering::SeqCst);
+        let applied = control_loop.apply_manual_profile(Some(profile)).await.unwrap();
+        assert_eq!(applied, ["FAN1", "FAN2", "FAN3"]);
+        let tick = control_loop.tick(Utc::now() + chrono::Duration::seconds(10)).await.unwrap();
+        assert_eq!(tick.action_type, Some(ControlActionType::ManualFanControl));
+        assert_eq!(tick.output_percent, Some(80.0));
+
+        assert!(control_loop.apply_manual_profile(None).await.unwrap().is_empty());
+        let tick = control_loop.tick(Utc::now() + chrono::Duration::seconds(20)).await.unwrap();
+        assert_eq!(tick.action_type, Some(ControlActionType::AutomaticControl));
+    }
+
+    #[tokio::test]
+    async fn test_slow_fan_is_flagged_against_commanded_duty() {
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        ipmi.set_fan_rpm("FAN1", 6000);
+        ipmi.set_fan_rpm("FAN2", 2000);
+        let mut config = AppConfig::default().control;
+        config.fan_degradation = FanDegradationConfig {
+            enabled: true,
+            rpm_curve: vec![
+                RpmCurvePoint { duty_percent: 0.0, rpm: 6000.0 },
+                RpmCurvePoint { duty_percent: 100.0, rpm: 6000.0 },
+            ],
+            deviation_percent: 25.0,
+            duration_secs: 10,
+            ..Default::default()
+        };
+        let alerts = AlertStore::new(100);
+        let control_loop = FanControlLoop::new(
+            ipmi.clone(),
+            SensorBlacklist::default(),
+            &config,
+            ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS),
+        )
+        .with_degradation_alerts(Arc::new(ControlAlertSink::new(
+            alerts.clone(),
+            Arc::new(InMemoryAlertHistoryStore::new()),
+        )));
+        let start = Utc::now();
+
+        // 首个周期尚无下发过的占空比，第二个周期开始计时，偏差持续10秒后告警
+        for seconds in [0, 10] {
+            let tick = control_loop.tick(start + chrono::Duration::seconds(seconds)).await.unwrap();
+            assert!(tick.degraded_fans.is_empty());
+        }
+        let tick = control_loop.tick(start + chrono::Duration::seconds(20)).await.unwrap();
+        assert_eq!(tick.degraded_fans, ["FAN2"]);
+
+        let raised = alerts.list();
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].alert_type, "fan");
+        assert_eq!(raised[0].source_id, "FAN2");
+    }
+
+    #[tokio::test]
+    async fn test_liveness_reports_stalled_after_two_missed_cycles() {
+        let control_loop = control_loop(Arc::new(MockIpmiService::healthy_server()));
+        let start = Utc::now();
+        assert_eq!(control_loop.liveness(start), ControlLoopLiveness::Disabled);
+
+        control_loop.heartbeat.write().unwrap().started_at = Some(start);
+        assert_eq!(control_loop.liveness(start + chrono::Duration::seconds(5)), ControlLoopLiveness::Starting);
+        assert_eq!(control_loop.liveness(start + chrono::Duration::seconds(25)), ControlLoopLiveness::Stalled);
+
+        control_loop.heartbeat.write().unwrap().last_cycle = Some(start + chrono::Duration::seconds(20));
+        assert_eq!(control_loop.liveness(start + chrono::Duration::seconds(40)), ControlLoopLiveness::Running);
+        assert_eq!(control_loop.liveness(start + chrono::Duration::seconds(41)), ControlLoopLiveness::Stalled);
+    }
+
+    /// 第一次读取温度时panic的数据源
+    struct PanicOnceSource {
+        inner: Arc<MockIpmiService>,
+        panicked: AtomicBool,
+    }
+
+    impl SensorSource for PanicOnceSource {
+        fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+            if !self.panicked.swap(true, Ordering::SeqCst) {
+                panic!("PID output is NaN");
+            }
+            self.inner.get_temperature_sensors()
+        }
+
+        fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
+            self.inner.get_fan_sensors()
+        }
+
+        fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+            self.inner.set_fan_speed(fan_id, speed_percent)
+        }
+
+        fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+            self.inner.restore_automatic_fan_control()
+        }
+
+        fn test_connection(&self) -> Result<bool, Box<dyn std::error::Error>> {
+            self.inner.test_connection()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_supervised_loop_enters_safe_state_on_panic_and_resumes() {
+        let ipmi = Arc::new(MockIpmiService::healthy_server());
+        let source = Arc::new(PanicOnceSource {
+            inner: ipmi.clone(),
+            panicked: AtomicBool::new(false),
+        });
+        let config = AppConfig::default().control;
+        let control_loop = FanControlLoop::new(
+            source.clone(),
+            SensorBlacklist::default(),
+            &config,
+            ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS),
+        );
+        let events = Arc::new(InMemorySystemEventStore::new());
+        let supervisor = Arc::new(
+            ControlLoopSupervisor::new(source, events.clone(), ControlFailSafe::FullSpeed, 40)
+                .with_respawn_delay(Duration::ZERO),
+        );
+
+        let handle = control_loop.start(supervisor, PollOverruns::default());
+        for _ in 0..100 {
+            if ipmi.fan_speed_writes().len() >= 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        handle.abort();
+
+        // panic后先全速并交还自动模式，重新启动的控制循环接着按温度调速
+        let writes = ipmi.fan_speed_writes();
+        assert_eq!(writes[0], ("all".to_string(), 100));
+        assert_eq!(ipmi.automatic_restores(), 1);
+        assert_eq!(writes[1].0, "FAN1");
+        let (recorded, _) = events
+            .query(&SystemEventQuery {
+                event_type: Some("control_loop_panic".to_string()),
+                page: 1,
+                page_size: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(recorded.len(), 1);
+    }
+}
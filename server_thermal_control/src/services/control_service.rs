@@ -1,30 +1,251 @@
 use crate::controllers::control_controller::{
-    ControlAction, ControlActionType, ControlOptimizationResult, ControlPerformanceMetrics,
-    ControlStatus,
+    ControlAction, ControlActionType, ControlOptimizationResult, ControlParameters as TuningParameters,
+    ControlPerformanceMetrics, ControlStatus, FanCurve,
 };
 use crate::models::{
+    api::PaginatedResponse,
     control::*,
     error::{AppError, AppResult},
     fan::{FanConfig, FanReading},
-    thermal::TemperatureReading,
+    thermal::{TemperatureQuery, TemperatureReading},
 };
+use crate::services::fan_cost::estimate_fan_power_watts;
+use crate::services::auto_control_schedule::{AutoControlSchedule, ManualFanProfile, ScheduleDecision};
+use crate::services::control_supervisor::ControlLoopSupervisor;
+use crate::services::control_write_retry::ControlWriteRetry;
+use crate::services::parameter_diff::{AppliedParameters, ParameterChangeSource, ParameterDiff, ParameterHistory};
+use crate::services::power_cap::PowerCapCoordinator;
+use crate::services::decision_store::{ControlDecisionLog, ControlDecisionStore};
+use crate::services::emergency_exit::{EmergencyExitConfig, EmergencyExitGuard};
+use crate::services::fan_kickstart::{kickstart_if_stalled, KickstartOutcome};
+use crate::services::fan_snapshot_store::{FanSnapshotStore, FanSpeedSnapshot, InMemoryFanSnapshotStore};
 use crate::services::{
-    fan_service::FanService, monitoring_service::MonitoringService, sensor_service::SensorService,
+    fan_service::{FanService, FanSpeedDriver, FanSpeedWrite}, monitoring_service::MonitoringService, sensor_service::SensorService,
     thermal_service::ThermalService,
 };
 use crate::utils::{
     logger::LoggerManager,
-    math::{MathUtils, PidController},
+    math::{MathUtils, PidController, PidTerms, SlidingWindowStats},
     time::TimeUtils,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
 use tokio::time::{interval, Duration, Instant};
 use tracing::{debug, error, info, warn};
 
+/// 控制循环存活状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoopLivenessState {
+    /// 最近两个控制周期内有执行
+    Healthy,
+    /// 超过两个控制周期未执行
+    Degraded,
+    /// 超过五个控制周期未执行，或从未执行
+    Critical,
+}
+
+/// 控制循环存活检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlLoopLiveness {
+    /// 存活状态
+    pub state: LoopLivenessState,
+    /// 最近一次执行时间
+    pub last_tick: Option<DateTime<Utc>>,
+    /// 距最近一次执行的秒数
+    pub tick_age_seconds: Option<i64>,
+    /// 控制周期（秒）
+    pub update_interval_secs: u64,
+    /// 检查时间
+    pub checked_at: DateTime<Utc>,
+}
+
+impl ControlLoopLiveness {
+    /// 根据最近一次执行时间评估存活状态
+    ///
+    /// # 参数
+    /// * `last_tick` - 最近一次执行时间
+    /// * `update_interval_secs` - 控制周期（秒）
+    /// * `now` - 当前时间
+    pub fn evaluate(
+        last_tick: Option<DateTime<Utc>>,
+        update_interval_secs: u64,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let interval = update_interval_secs.max(1) as i64;
+        let tick_age_seconds = last_tick.map(|tick| (now - tick).num_seconds().max(0));
+
+        let state = match tick_age_seconds {
+            Some(age) if age <= 2 * interval => LoopLivenessState::Healthy,
+            Some(age) if age <= 5 * interval => LoopLivenessState::Degraded,
+            _ => LoopLivenessState::Critical,
+        };
+
+        Self {
+            state,
+            last_tick,
+            tick_age_seconds,
+            update_interval_secs,
+            checked_at: now,
+        }
+    }
+
+    /// 控制循环是否正常
+    pub fn is_healthy(&self) -> bool {
+        self.state == LoopLivenessState::Healthy
+    }
+}
+
+/// 控制历史查询条件
+#[derive(Debug, Clone, Default)]
+pub struct ControlHistoryQuery {
+    /// 动作类型，为空表示不过滤
+    pub action_types: Vec<ControlActionType>,
+    /// 目标组件ID
+    pub target_id: Option<String>,
+    /// 开始时间
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间
+    pub end_time: Option<DateTime<Utc>>,
+    /// 页码（从1开始）
+    pub page: i32,
+    /// 每页大小
+    pub page_size: i32,
+}
+
+impl ControlHistoryQuery {
+    fn matches(&self, action: &ControlAction) -> bool {
+        (self.action_types.is_empty() || self.action_types.contains(&action.action_type))
+            && self
+                .target_id
+                .as_ref()
+                .map_or(true, |target| &action.target_component == target)
+            && self.start_time.map_or(true, |start| action.timestamp >= start)
+            && self.end_time.map_or(true, |end| action.timestamp <= end)
+    }
+
+    /// 对控制历史应用过滤条件并分页，按时间倒序
+    ///
+    /// # 参数
+    /// * `history` - 按时间顺序记录的控制历史
+    fn paginate(&self, history: &[ControlAction]) -> PaginatedResponse<ControlAction> {
+        let matched: Vec<&ControlAction> = history.iter().rev().filter(|a| self.matches(a)).collect();
+        let total = matched.len() as i64;
+
+        let items = matched
+            .into_iter()
+            .skip(((self.page.max(1) - 1) * self.page_size.max(0)) as usize)
+            .take(self.page_size.max(0) as usize)
+            .cloned()
+            .collect();
+
+        PaginatedResponse::new(items, total, self.page, self.page_size)
+    }
+}
+
+/// 风扇转速快照的默认有效期（小时）
+const DEFAULT_FAN_SNAPSHOT_TTL_HOURS: i64 = 24;
+
+/// 风扇曲线回放中转速分布的区间宽度（百分比）
+const SPEED_BUCKET_WIDTH_PERCENT: f64 = 10.0;
+
+/// 风扇曲线回放结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FanCurveReplay {
+    /// 风扇ID
+    pub fan_id: String,
+    /// 回放开始时间
+    pub start_time: DateTime<Utc>,
+    /// 回放结束时间
+    pub end_time: DateTime<Utc>,
+    /// 回放的采样周期数
+    pub evaluated_samples: usize,
+    /// 目标温度
+    pub target_temperature: f64,
+    /// 转速分布，按10%分段
+    pub speed_distribution: Vec<SpeedBucket>,
+    /// 平均转速百分比
+    pub average_speed_percent: f64,
+    /// 估算的平均功耗（瓦特）
+    pub estimated_average_power_watts: f64,
+    /// 温度高于目标而曲线未达最高转速的采样数
+    pub over_target_samples: usize,
+    /// 温度高于目标而曲线未达最高转速的连续时段
+    pub over_target_periods: Vec<OverTargetPeriod>,
+}
+
+/// 转速分布区间
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeedBucket {
+    /// 区间下限（含）
+    pub lower_percent: f64,
+    /// 区间上限，最后一个区间包含100%
+    pub upper_percent: f64,
+    /// 落入区间的采样数
+    pub samples: usize,
+    /// 占全部采样的比例（0-1）
+    pub ratio: f64,
+}
+
+/// 温度高于目标的连续时段
+#[derive(Debug, Clone, Serialize)]
+pub struct OverTargetPeriod {
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 时段内最高温度
+    pub peak_temperature: f64,
+    /// 时段内曲线给出的最高转速百分比
+    pub max_speed_percent: f64,
+}
+
+/// 控制输入平滑滤波
+///
+/// 按传感器保存最近的读数，控制输入取窗口内的移动平均；只影响送入PID的温度，
+/// 温度历史和控制记录中的原始读数不受影响
+#[derive(Debug, Default)]
+pub struct ControlInputFilter {
+    windows: HashMap<String, SlidingWindowStats>,
+}
+
+impl ControlInputFilter {
+    /// 加入一次原始读数并返回平滑后的控制输入
+    ///
+    /// 窗口不大于1时不平滑并清除该传感器的历史；窗口大小变化时从当前读数重新开始；
+    /// 非有限读数原样返回，不计入窗口
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    /// * `raw` - 原始温度读数
+    /// * `window` - 平滑窗口（采样数）
+    pub fn apply(&mut self, sensor_id: &str, raw: f64, window: usize) -> f64 {
+        if window <= 1 {
+            self.windows.remove(sensor_id);
+            return raw;
+        }
+        if !raw.is_finite() {
+            return raw;
+        }
+
+        let stats = match self.windows.get_mut(sensor_id) {
+            Some(stats) if stats.capacity() == window => stats,
+            _ => {
+                let Ok(stats) = SlidingWindowStats::new(window) else {
+                    return raw;
+                };
+                self.windows.insert(sensor_id.to_string(), stats);
+                self.windows.get_mut(sensor_id).expect("刚插入的窗口")
+            }
+        };
+        stats.add(raw);
+        stats.mean()
+    }
+}
+
 /// 控制服务
 ///
 /// 负责智能温度控制、风扇调节和系统优化
@@ -34,6 +255,8 @@ pub struct ControlService {
     thermal_service: Arc<ThermalService>,
     /// 风扇服务
     fan_service: Arc<FanService>,
+    /// 风扇转速读写接口，默认即风扇服务
+    fan_driver: Arc<dyn FanSpeedDriver>,
     /// 传感器服务
     sensor_service: Arc<SensorService>,
     /// 监控服务
@@ -44,8 +267,38 @@ pub struct ControlService {
     status: Arc<RwLock<ControlStatus>>,
     /// PID控制器集合
     pid_controllers: Arc<RwLock<HashMap<String, PidController>>>,
+    /// 控制输入平滑滤波
+    input_filter: Arc<RwLock<ControlInputFilter>>,
     /// 控制历史记录
     control_history: Arc<RwLock<Vec<ControlAction>>>,
+    /// 控制决策记录（最近决策的环形缓冲与可选的持久化存储）
+    decisions: ControlDecisionLog,
+    /// 静音时段计划
+    quiet_schedule: Arc<RwLock<QuietHoursSchedule>>,
+    /// 静音时段计划持久化文件路径
+    schedule_file_path: String,
+    /// 控制循环最近一次执行时间，用于存活检查
+    last_loop_tick: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// 控制循环最近一次启动时间
+    loop_started_at: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// 最近一次应用的调优参数（含温度阈值与风扇曲线）
+    tuning_parameters: Arc<RwLock<Option<TuningParameters>>>,
+    /// 调优参数的变更记录
+    parameter_history: ParameterHistory,
+    /// 风扇转速快照存储
+    snapshot_store: Arc<dyn FanSnapshotStore>,
+    /// 风扇转速快照有效期
+    snapshot_ttl: chrono::Duration,
+    /// 控制循环监督器，未设置时控制循环panic后不再运行
+    supervisor: Option<Arc<ControlLoopSupervisor>>,
+    /// 紧急模式退出确认
+    emergency_exit: Arc<RwLock<EmergencyExitGuard>>,
+    /// 转速下发重试与死信
+    write_retry: ControlWriteRetry,
+    /// 提速前的功率封顶协调，未设置时不检查功耗
+    power_cap: Option<PowerCapCoordinator>,
+    /// 自动控制时段，未设置时自动控制全天生效
+    auto_schedule: Option<AutoControlSchedule>,
     /// 控制任务句柄
     task_handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,
     /// 数学工具
@@ -68,18 +321,140 @@ impl ControlService {
     ) -> Self {
         Self {
             thermal_service,
+            fan_driver: fan_service.clone(),
             fan_service,
             sensor_service,
             monitoring_service,
             config: Arc::new(RwLock::new(ControlConfig::default())),
             status: Arc::new(RwLock::new(ControlStatus::default())),
             pid_controllers: Arc::new(RwLock::new(HashMap::new())),
+            input_filter: Arc::new(RwLock::new(ControlInputFilter::default())),
             control_history: Arc::new(RwLock::new(Vec::new())),
+            decisions: ControlDecisionLog::new(MAX_CONTROL_DECISIONS),
+            quiet_schedule: Arc::new(RwLock::new(QuietHoursSchedule::default())),
+            schedule_file_path: QUIET_SCHEDULE_FILE.to_string(),
+            last_loop_tick: Arc::new(RwLock::new(None)),
+            loop_started_at: Arc::new(RwLock::new(None)),
+            tuning_parameters: Arc::new(RwLock::new(None)),
+            parameter_history: ParameterHistory::default(),
+            snapshot_store: Arc::new(InMemoryFanSnapshotStore::new()),
+            snapshot_ttl: chrono::Duration::hours(DEFAULT_FAN_SNAPSHOT_TTL_HOURS),
+            supervisor: None,
+            emergency_exit: Arc::new(RwLock::new(EmergencyExitGuard::default())),
+            write_retry: ControlWriteRetry::default(),
+            power_cap: None,
+            auto_schedule: None,
             task_handles: Arc::new(Mutex::new(Vec::new())),
             math_utils: MathUtils,
         }
     }
 
+    /// 设置风扇转速读写接口
+    ///
+    /// # 参数
+    /// * `fan_driver` - 风扇转速读写接口
+    pub fn with_fan_driver(mut self, fan_driver: Arc<dyn FanSpeedDriver>) -> Self {
+        self.fan_driver = fan_driver;
+        self
+    }
+
+    /// 设置控制决策持久化存储，用于事后回放
+    ///
+    /// # 参数
+    /// * `store` - 决策存储，如 `PgControlDecisionStore`
+    pub fn with_decision_store(mut self, store: Arc<dyn ControlDecisionStore>) -> Self {
+        self.decisions = self.decisions.with_store(store);
+        self
+    }
+
+    /// 使用共享的决策记录，如 `AppState.control_decisions`，使支持包能导出最近的决策
+    ///
+    /// 应在 `with_decision_store` 之前调用，否则之前设置的持久化存储会被替换掉
+    ///
+    /// # 参数
+    /// * `decisions` - 决策记录
+    pub fn with_decision_log(mut self, decisions: ControlDecisionLog) -> Self {
+        self.decisions = decisions;
+        self
+    }
+
+    /// 设置风扇转速快照存储
+    ///
+    /// # 参数
+    /// * `snapshot_store` - 快照存储，如 `PgFanSnapshotStore`
+    pub fn with_snapshot_store(mut self, snapshot_store: Arc<dyn FanSnapshotStore>) -> Self {
+        self.snapshot_store = snapshot_store;
+        self
+    }
+
+    /// 设置风扇转速快照有效期
+    ///
+    /// # 参数
+    /// * `ttl` - 有效期
+    pub fn with_snapshot_ttl(mut self, ttl: chrono::Duration) -> Self {
+        self.snapshot_ttl = ttl;
+        self
+    }
+
+    /// 设置控制循环监督器
+    ///
+    /// # 参数
+    /// * `supervisor` - 控制循环panic时下发安全转速并重新启动循环
+    pub fn with_supervisor(mut self, supervisor: Arc<ControlLoopSupervisor>) -> Self {
+        self.supervisor = Some(supervisor);
+        self
+    }
+
+    /// 设置退出紧急模式的条件
+    ///
+    /// # 参数
+    /// * `config` - 退出阈值与确认时长
+    pub fn with_emergency_exit(mut self, config: EmergencyExitConfig) -> Self {
+        self.emergency_exit = Arc::new(RwLock::new(EmergencyExitGuard::new(config)));
+        self
+    }
+
+    /// 设置转速下发的重试与死信
+    ///
+    /// # 参数
+    /// * `write_retry` - 重试配置及连续失败时的告警出口
+    pub fn with_write_retry(mut self, write_retry: ControlWriteRetry) -> Self {
+        self.write_retry = write_retry;
+        self
+    }
+
+    /// 设置提速前的功率封顶协调
+    ///
+    /// # 参数
+    /// * `power_cap` - 功耗读数来源、功率上限配置及冲突时的告警出口
+    pub fn with_power_cap(mut self, power_cap: PowerCapCoordinator) -> Self {
+        self.power_cap = Some(power_cap);
+        self
+    }
+
+    /// 重试耗尽、等待下一周期补发的控制动作
+    /// 设置自动控制时段
+    ///
+    /// # 参数
+    /// * `schedule` - 自动控制时段，时段外保持当前转速或按手动档案下发
+    pub fn with_auto_schedule(mut self, schedule: AutoControlSchedule) -> Self {
+        self.auto_schedule = Some(schedule);
+        self
+    }
+
+    pub fn failed_control_writes(&self) -> Vec<crate::services::control_write_retry::FailedControlWrite> {
+        self.write_retry.dead_letters()
+    }
+
+    /// 设置静音时段计划的持久化文件路径
+    ///
+    /// # 参数
+    /// * `path` - 文件路径
+    pub fn with_schedule_file(mut self, path: impl Into<String>) -> Self {
+        self.schedule_file_path = path.into();
+        self
+    }
+
     /// 启动自动控制
     pub async fn start_auto_control(&self) -> AppResult<()> {
         info!("启动自动温度控制");
@@ -95,6 +470,9 @@ impl ControlService {
         // 初始化PID控制器
         self.initialize_pid_controllers().await?;
 
+        // 加载静音时段计划
+        self.load_quiet_schedule().await?;
+
         // 启动控制循环
         self.start_control_loop().await?;
 
@@ -126,6 +504,46 @@ impl ControlService {
         Ok(())
     }
 
+    /// 重启控制循环
+    ///
+    /// 等待进行中的控制周期结束后停止后台任务，间隙内风扇交还BMC自动控制
+    /// （紧急模式下保持当前的全速状态），随后重新启动控制循环与性能监控。
+    /// 自动控制的开关状态保持不变，返回新的循环启动时间
+    pub async fn restart_control_loop(&self) -> AppResult<DateTime<Utc>> {
+        info!("重启控制循环");
+
+        {
+            // 控制周期在整个执行期间持有控制器写锁，拿到锁即表示进行中的动作已完成
+            let _controllers = self.pid_controllers.write().await;
+            let mut handles = self.task_handles.lock().await;
+            for handle in handles.drain(..) {
+                handle.abort();
+            }
+        }
+
+        if !self.fan_service.is_read_only() && !self.status.read().await.emergency_mode {
+            if let Err(e) = self.fan_service.restore_automatic_control() {
+                warn!("重启控制循环期间交还BMC自动控制失败: {}", e);
+            }
+        }
+
+        self.start_control_loop().await?;
+        self.start_performance_monitoring().await?;
+
+        let started_at = self
+            .loop_started_at
+            .read()
+            .await
+            .unwrap_or_else(Utc::now);
+        info!("控制循环已重启: {}", started_at);
+        Ok(started_at)
+    }
+
+    /// 控制循环最近一次启动时间
+    pub async fn loop_started_at(&self) -> Option<DateTime<Utc>> {
+        *self.loop_started_at.read().await
+    }
+
     /// 获取控制状态
     pub async fn get_status(&self) -> ControlStatus {
         self.status.read().await.clone()
@@ -161,12 +579,320 @@ impl ControlService {
         Ok(())
     }
 
+    /// 部分更新控制配置
+    ///
+    /// 在写锁内基于最新配置合并，避免覆盖并发修改的其他字段
+    ///
+    /// # 参数
+    /// * `patch` - 需要修改的字段
+    pub async fn patch_config(&self, patch: ControlConfigPatch) -> AppResult<ControlConfig> {
+        info!("部分更新控制配置: {:?}", patch);
+
+        if patch.is_empty() {
+            return Err(AppError::validation_error("config", "至少需要指定一个配置字段"));
+        }
+
+        let merged = {
+            let mut config = self.config.write().await;
+            let merged = patch.merged(&config)?;
+            *config = merged.clone();
+            merged
+        };
+
+        // 重新初始化PID控制器
+        if self.status.read().await.is_auto_control_enabled {
+            self.initialize_pid_controllers().await?;
+        }
+
+        info!("控制配置部分更新完成");
+        Ok(merged)
+    }
+
+    /// 获取最近一次应用的调优参数
+    pub async fn get_tuning_parameters(&self) -> Option<TuningParameters> {
+        self.tuning_parameters.read().await.clone()
+    }
+
+    /// 获取调优参数的变更记录，按时间倒序
+    ///
+    /// # 参数
+    /// * `limit` - 返回条数上限
+    pub fn get_parameter_history(&self, limit: Option<usize>) -> Vec<ParameterDiff> {
+        self.parameter_history.list(limit)
+    }
+
+    /// 整体替换控制配置、调优参数和静音时段计划
+    ///
+    /// 全部校验通过后才写入；静音时段计划先持久化，写文件失败时内存中的配置保持不变
+    ///
+    /// # 参数
+    /// * `config` - 控制配置
+    /// * `tuning` - 调优参数，为空时清除已应用的调优参数
+    /// * `schedule` - 静音时段计划
+    pub async fn restore_settings(
+        &self,
+        config: ControlConfig,
+        tuning: Option<TuningParameters>,
+        schedule: QuietHoursSchedule,
+    ) -> AppResult<()> {
+        config.validate()?;
+        if let Some(tuning) = &tuning {
+            tuning.validate()?;
+        }
+        self.update_quiet_schedule(schedule).await?;
+
+        {
+            let mut current = self.config.write().await;
+            let mut controllers = self.pid_controllers.write().await;
+            *current = config;
+            if let Some(tuning) = &tuning {
+                Self::swap_parameters(&mut current, &mut controllers, tuning);
+            }
+            let mut tuning_parameters = self.tuning_parameters.write().await;
+            if let Some(tuning) = &tuning {
+                self.parameter_history
+                    .record(ParameterChangeSource::Restore, tuning_parameters.as_ref(), tuning);
+            }
+            *tuning_parameters = tuning;
+        }
+
+        if self.status.read().await.is_auto_control_enabled {
+            self.initialize_pid_controllers().await?;
+        }
+
+        info!("控制设置已整体替换");
+        Ok(())
+    }
+
+    /// 获取最近的控制决策
+    ///
+    /// 按时间倒序返回
+    ///
+    /// # 参数
+    /// * `limit` - 返回记录数限制
+    pub async fn get_recent_decisions(&self, limit: Option<usize>) -> Vec<ControlDecision> {
+        self.decisions.recent(limit).await
+    }
+
+    /// 回放时间窗口内的控制决策，按时间正序
+    ///
+    /// # 参数
+    /// * `start` - 开始时间
+    /// * `end` - 结束时间
+    pub async fn replay_decisions(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<ControlDecision>> {
+        self.decisions.replay(start, end).await
+    }
+
+    /// 用历史温度回放候选风扇曲线，不下发任何转速
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `curve` - 候选风扇曲线
+    /// * `start_time` - 回放开始时间
+    /// * `end_time` - 回放结束时间
+    pub async fn validate_fan_curve(
+        &self,
+        fan_id: &str,
+        curve: &FanCurve,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> AppResult<FanCurveReplay> {
+        if start_time >= end_time {
+            return Err(AppError::validation_error("start_time", "开始时间必须早于结束时间"));
+        }
+        curve.validate(fan_id)?;
+
+        let blacklist = self.thermal_service.sensor_blacklist();
+        let readings: Vec<TemperatureReading> = self
+            .thermal_service
+            .get_temperature_history(&TemperatureQuery {
+                start_time: Some(start_time),
+                end_time: Some(end_time),
+                limit: None,
+                ..Default::default()
+            })
+            .await?
+            .into_iter()
+            .filter(|reading| !blacklist.contains(&reading.sensor_id))
+            .collect();
+        let cfg = self.config.read().await.clone();
+
+        let replay = Self::replay_fan_curve(fan_id, curve, &cfg, &readings, start_time, end_time);
+        debug!(
+            "风扇曲线回放完成: {} 共 {} 个周期, 超出目标 {} 个",
+            fan_id, replay.evaluated_samples, replay.over_target_samples
+        );
+        Ok(replay)
+    }
+
+    /// 按控制配置将历史读数逐周期换算为曲线转速
+    ///
+    /// 同一秒内的读数视为一个控制周期。风扇所在分区的传感器组优先，其次为全局传感器聚合，
+    /// 都未配置时取最高温度；转速叠加风扇的最低转速
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `curve` - 候选风扇曲线
+    /// * `cfg` - 当前控制配置
+    /// * `readings` - 历史温度读数
+    /// * `start_time` - 回放开始时间
+    /// * `end_time` - 回放结束时间
+    fn replay_fan_curve(
+        fan_id: &str,
+        curve: &FanCurve,
+        cfg: &ControlConfig,
+        readings: &[TemperatureReading],
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> FanCurveReplay {
+        let aggregation = cfg
+            .fan_zones
+            .iter()
+            .find(|zone| zone.fan_ids.iter().any(|id| id == fan_id))
+            .map(|zone| zone.sensors.clone())
+            .or_else(|| cfg.sensor_aggregation.clone())
+            .unwrap_or(SensorAggregationConfig {
+                policy: AggregationPolicy::Max,
+                sensor_ids: Vec::new(),
+                weights: HashMap::new(),
+            });
+
+        let mut cycles: BTreeMap<i64, (DateTime<Utc>, Vec<(String, f64)>)> = BTreeMap::new();
+        for reading in readings
+            .iter()
+            .filter(|r| r.timestamp >= start_time && r.timestamp <= end_time)
+        {
+            cycles
+                .entry(reading.timestamp.timestamp())
+                .or_insert_with(|| (reading.timestamp, Vec::new()))
+                .1
+                .push((reading.sensor_id.clone(), reading.temperature));
+        }
+
+        let bucket_count = (100.0 / SPEED_BUCKET_WIDTH_PERCENT).ceil() as usize;
+        let mut bucket_samples = vec![0usize; bucket_count];
+        let mut speeds = Vec::new();
+        let mut over_target_samples = 0;
+        let mut over_target_periods: Vec<OverTargetPeriod> = Vec::new();
+        let mut in_period = false;
+
+        for (timestamp, cycle_readings) in cycles.into_values() {
+            let Some(temperature) = aggregation.aggregate(&cycle_readings) else {
+                continue;
+            };
+            let speed = cfg.floored_fan_speed(fan_id, curve.speed_at(temperature)).min(100.0);
+            speeds.push(speed);
+            let bucket = ((speed / SPEED_BUCKET_WIDTH_PERCENT) as usize).min(bucket_count - 1);
+            bucket_samples[bucket] += 1;
+
+            if temperature > cfg.target_temperature && speed < curve.max_speed as f64 {
+                over_target_samples += 1;
+                match over_target_periods.last_mut() {
+                    Some(period) if in_period => {
+                        period.end_time = timestamp;
+                        period.peak_temperature = period.peak_temperature.max(temperature);
+                        period.max_speed_percent = period.max_speed_percent.max(speed);
+                    }
+                    _ => over_target_periods.push(OverTargetPeriod {
+                        start_time: timestamp,
+                        end_time: timestamp,
+                        peak_temperature: temperature,
+                        max_speed_percent: speed,
+                    }),
+                }
+                in_period = true;
+            } else {
+                in_period = false;
+            }
+        }
+
+        let evaluated_samples = speeds.len();
+        let mean = |total: f64| if evaluated_samples == 0 { 0.0 } else { total / evaluated_samples as f64 };
+        let average_speed_percent = mean(speeds.iter().sum::<f64>());
+        let estimated_average_power_watts = mean(speeds.iter().map(|s| estimate_fan_power_watts(*s)).sum::<f64>());
+
+        let speed_distribution = bucket_samples
+            .into_iter()
+            .enumerate()
+            .map(|(i, samples)| SpeedBucket {
+                lower_percent: i as f64 * SPEED_BUCKET_WIDTH_PERCENT,
+                upper_percent: ((i + 1) as f64 * SPEED_BUCKET_WIDTH_PERCENT).min(100.0),
+                samples,
+                ratio: if evaluated_samples == 0 { 0.0 } else { samples as f64 / evaluated_samples as f64 },
+            })
+            .collect();
+
+        FanCurveReplay {
+            fan_id: fan_id.to_string(),
+            start_time,
+            end_time,
+            evaluated_samples,
+            target_temperature: cfg.target_temperature,
+            speed_distribution,
+            average_speed_percent,
+            estimated_average_power_watts,
+            over_target_samples,
+            over_target_periods,
+        }
+    }
+
+    /// 获取静音时段计划
+    pub async fn get_quiet_schedule(&self) -> QuietHoursSchedule {
+        self.quiet_schedule.read().await.clone()
+    }
+
+    /// 更新静音时段计划
+    ///
+    /// 计划会写入持久化文件，重启后自动加载
+    ///
+    /// # 参数
+    /// * `schedule` - 新的静音时段计划
+    pub async fn update_quiet_schedule(
+        &self,
+        mut schedule: QuietHoursSchedule,
+    ) -> AppResult<QuietHoursSchedule> {
+        info!("更新静音时段计划: {} 个时段", schedule.windows.len());
+
+        schedule.validate()?;
+        schedule.updated_at = Utc::now();
+
+        let content = serde_json::to_string_pretty(&schedule)?;
+        if let Some(parent) = std::path::Path::new(&self.schedule_file_path).parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&self.schedule_file_path, content).await?;
+
+        *self.quiet_schedule.write().await = schedule.clone();
+
+        info!("静音时段计划更新完成");
+        Ok(schedule)
+    }
+
+    /// 从持久化文件加载静音时段计划
+    ///
+    /// 文件不存在时保留当前计划
+    pub async fn load_quiet_schedule(&self) -> AppResult<()> {
+        let content = match tokio::fs::read_to_string(&self.schedule_file_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let schedule: QuietHoursSchedule = serde_json::from_str(&content)?;
+        schedule.validate()?;
+        *self.quiet_schedule.write().await = schedule;
+
+        debug!("静音时段计划已加载: {}", self.schedule_file_path);
+        Ok(())
+    }
+
     /// 手动设置风扇转速
     ///
     /// # 参数
     /// * `fan_id` - 风扇ID
     /// * `speed_percent` - 转速百分比
     pub async fn set_manual_fan_speed(&self, fan_id: &str, speed_percent: f64) -> AppResult<()> {
+        self.fan_service.ensure_writable("手动设置风扇转速")?;
         info!("手动设置风扇 {} 转速: {:.1}%", fan_id, speed_percent);
 
         // 验证转速范围
@@ -175,19 +901,23 @@ impl ControlService {
         }
 
         // 设置风扇转速
-        self.fan_service
+        let write = self.fan_service
             .set_fan_speed(fan_id, speed_percent)
             .await?;
 
         // 记录控制动作
+        let (action_type, reason) = match write {
+            FanSpeedWrite::Applied => (ControlActionType::ManualFanControl, "手动设置".to_string()),
+            FanSpeedWrite::Unchanged => (ControlActionType::NoOp, "手动设置: 转速未变化，跳过下发".to_string()),
+        };
         self.record_control_action(ControlAction {
             id: uuid::Uuid::new_v4().to_string(),
             timestamp: Utc::now(),
-            action_type: ControlActionType::ManualFanControl,
+            action_type,
             target_component: fan_id.to_string(),
             previous_value: 0.0, // 需要获取之前的值
             new_value: speed_percent,
-            reason: "手动设置".to_string(),
+            reason,
             success: true,
             error_message: None,
         })
@@ -197,79 +927,202 @@ impl ControlService {
         Ok(())
     }
 
+    /// 记录所有风扇的当前转速快照
+    ///
+    /// 同时清理已过期的快照
+    pub async fn snapshot_fan_speeds(&self) -> AppResult<FanSpeedSnapshot> {
+        let now = Utc::now();
+        let purged = self.snapshot_store.purge_expired(now).await?;
+        if purged > 0 {
+            debug!("已清理 {} 个过期的风扇转速快照", purged);
+        }
+
+        let snapshot = FanSpeedSnapshot {
+            id: uuid::Uuid::new_v4().to_string(),
+            speeds: Self::capture_fan_speeds(self.fan_driver.as_ref()).await?.into_iter().collect(),
+            created_at: now,
+            expires_at: now + self.snapshot_ttl,
+        };
+        self.snapshot_store.save(&snapshot).await?;
+
+        info!("已记录风扇转速快照 {}，共 {} 个风扇", snapshot.id, snapshot.speeds.len());
+        Ok(snapshot)
+    }
+
+    /// 将风扇恢复到快照中的转速
+    ///
+    /// 任一风扇下发失败时将已下发的风扇回滚到恢复前的转速；快照中的风扇已不存在时不做任何修改。
+    /// 自动控制开启时，控制循环之后仍会继续调节
+    ///
+    /// # 参数
+    /// * `snapshot_id` - 快照ID
+    pub async fn restore_fan_snapshot(&self, snapshot_id: &str) -> AppResult<FanSpeedSnapshot> {
+        self.fan_service.ensure_writable("恢复风扇转速快照")?;
+
+        let snapshot = self
+            .snapshot_store
+            .get(snapshot_id, Utc::now())
+            .await?
+            .ok_or_else(|| AppError::not_found_error("fan_snapshot", snapshot_id))?;
+
+        let current: HashMap<String, f64> = Self::capture_fan_speeds(self.fan_driver.as_ref())
+            .await?
+            .into_iter()
+            .collect();
+        let targets = snapshot
+            .speeds
+            .iter()
+            .map(|(fan_id, speed)| {
+                current
+                    .get(fan_id)
+                    .map(|previous| (fan_id.clone(), *previous, *speed))
+                    .ok_or_else(|| AppError::not_found_error("fan", fan_id.as_str()))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        let applied = Self::set_fan_speeds_with_rollback(self.fan_driver.as_ref(), targets, "恢复转速快照").await?;
+        for (fan_id, previous_speed, new_speed) in applied {
+            self.record_control_action(ControlAction {
+                id: uuid::Uuid::new_v4().to_string(),
+                timestamp: Utc::now(),
+                action_type: ControlActionType::ManualFanControl,
+                target_component: fan_id,
+                previous_value: previous_speed,
+                new_value: new_speed,
+                reason: format!("恢复转速快照: {}", snapshot.id),
+                success: true,
+                error_message: None,
+            })
+            .await;
+        }
+
+        info!("已恢复风扇转速快照 {}", snapshot.id);
+        Ok(snapshot)
+    }
+
     /// 应用温度控制策略
     ///
+    /// 先按新策略下发所有风扇转速，全部成功后才切换当前策略；任一风扇失败时
+    /// 将已下发的风扇回滚到原转速并返回错误，配置保持不变，可直接重试
+    ///
     /// # 参数
     /// * `strategy` - 控制策略
     pub async fn apply_control_strategy(&self, strategy: ControlStrategy) -> AppResult<()> {
+        self.fan_service.ensure_writable("应用控制策略")?;
         info!("应用控制策略: {:?}", strategy.strategy_type);
 
+        let mut new_config = self.config.read().await.clone();
         match strategy.strategy_type {
-            StrategyType::Temperature => {
-                self.apply_conservative_strategy(&strategy).await?;
-            }
-            StrategyType::Load => {
-                self.apply_balanced_strategy(&strategy).await?;
-            }
-            StrategyType::PowerSaving => {
-                self.apply_aggressive_strategy(&strategy).await?;
-            }
-            StrategyType::Performance => {
-                self.apply_custom_strategy(&strategy).await?;
-            }
+            StrategyType::Temperature => Self::apply_conservative_strategy(&mut new_config),
+            StrategyType::Load => Self::apply_balanced_strategy(&mut new_config),
+            StrategyType::PowerSaving => Self::apply_aggressive_strategy(&mut new_config),
+            StrategyType::Performance => Self::apply_custom_strategy(&mut new_config, &strategy),
         }
 
-        // 更新当前策略
-        {
-            let mut config = self.config.write().await;
-            config.current_strategy = Some(strategy);
+        let applied = Self::apply_fan_speeds_with_rollback(self.fan_driver.as_ref(), &new_config).await?;
+        for (fan_id, previous_speed, new_speed) in applied {
+            self.record_control_action(ControlAction {
+                id: uuid::Uuid::new_v4().to_string(),
+                timestamp: Utc::now(),
+                action_type: ControlActionType::StrategyApplication,
+                target_component: fan_id,
+                previous_value: previous_speed,
+                new_value: new_speed,
+                reason: format!("应用控制策略: {}", strategy.name),
+                success: true,
+                error_message: None,
+            })
+            .await;
         }
 
+        // 更新当前策略
+        new_config.current_strategy = Some(strategy);
+        *self.config.write().await = new_config;
+
         info!("控制策略应用完成");
         Ok(())
     }
 
     /// 执行紧急冷却
     pub async fn emergency_cooling(&self) -> AppResult<()> {
-        warn!("执行紧急冷却");
+        Self::enter_emergency_cooling(
+            &self.fan_service,
+            &self.control_history,
+            &self.status,
+            &self.emergency_exit,
+            "紧急冷却",
+        )
+        .await
+    }
+
+    /// 将所有风扇设为最大转速并进入紧急模式
+    ///
+    /// # 参数
+    /// * `reason` - 控制动作原因
+    async fn enter_emergency_cooling(
+        fan_service: &Arc<FanService>,
+        control_history: &Arc<RwLock<Vec<ControlAction>>>,
+        status: &Arc<RwLock<ControlStatus>>,
+        emergency_exit: &Arc<RwLock<EmergencyExitGuard>>,
+        reason: &str,
+    ) -> AppResult<()> {
+        fan_service.ensure_writable("紧急冷却")?;
+        warn!("执行紧急冷却: {}", reason);
 
         // 获取所有风扇
-        let fans = self.fan_service.get_fan_list().await?;
+        let fans = fan_service.get_controllable_fan_list().await?;
 
         // 将所有风扇设置为最大转速
         for fan_id in fans {
-            if let Err(e) = self.fan_service.set_fan_speed(&fan_id, 100.0).await {
+            if let Err(e) = fan_service.set_fan_speed(&fan_id, 100.0).await {
                 error!("设置风扇 {} 最大转速失败: {}", fan_id, e);
             } else {
                 // 记录紧急控制动作
-                self.record_control_action(ControlAction {
+                let mut history = control_history.write().await;
+                history.push(ControlAction {
                     id: uuid::Uuid::new_v4().to_string(),
                     timestamp: Utc::now(),
                     action_type: ControlActionType::EmergencyCooling,
                     target_component: fan_id.clone(),
                     previous_value: 0.0, // 需要获取之前的值
                     new_value: 100.0,
-                    reason: "紧急冷却".to_string(),
+                    reason: reason.to_string(),
                     success: true,
                     error_message: None,
-                })
-                .await;
+                });
+                if history.len() > 1000 {
+                    history.drain(0..100);
+                }
             }
         }
 
         // 更新状态
         {
-            let mut status = self.status.write().await;
+            let mut status = status.write().await;
             status.emergency_mode = true;
             status.last_emergency_time = Some(Utc::now());
         }
+        emergency_exit.write().await.reset();
 
         warn!("紧急冷却执行完成");
         Ok(())
     }
 
     /// 退出紧急模式
-    pub async fn exit_emergency_mode(&self) -> AppResult<()> {
+    ///
+    /// 最高温度需低于 临界温度-回差 并持续确认时长，否则拒绝退出
+    ///
+    /// # 参数
+    /// * `force` - 跳过温度确认强制退出
+    pub async fn exit_emergency_mode(&self, force: bool) -> AppResult<()> {
+        if force {
+            warn!("强制退出紧急模式，跳过温度确认");
+        } else {
+            if let Some(temperature) = Self::max_temperature(&self.thermal_service).await {
+                self.emergency_exit.write().await.observe(temperature, Utc::now());
+            }
+            self.emergency_exit.read().await.check_exit(Utc::now())?;
+        }
         info!("退出紧急模式");
 
         // 更新状态
@@ -278,24 +1131,43 @@ impl ControlService {
             status.emergency_mode = false;
         }
 
-        // 恢复正常控制
+        // 恢复正常控制；未启用自动控制时将风扇交还BMC
         if self.status.read().await.is_auto_control_enabled {
             self.execute_control_cycle().await?;
+        } else {
+            self.fan_service.restore_automatic_control()?;
         }
 
         info!("已退出紧急模式");
         Ok(())
     }
 
-    /// 获取控制历史
+    /// 按条件分页查询控制历史，按时间倒序
     ///
     /// # 参数
-    /// * `limit` - 返回记录数限制
-    pub async fn get_control_history(&self, limit: Option<usize>) -> Vec<ControlAction> {
+    /// * `query` - 查询条件
+    pub async fn get_control_history(
+        &self,
+        query: &ControlHistoryQuery,
+    ) -> AppResult<PaginatedResponse<ControlAction>> {
+        if query.page < 1 || !(1..=1000).contains(&query.page_size) {
+            return Err(AppError::validation_error(
+                "page_size",
+                "页码必须从1开始，每页大小必须在1-1000之间",
+            ));
+        }
+
         let history = self.control_history.read().await;
-        let limit = limit.unwrap_or(100);
+        Ok(query.paginate(&history))
+    }
 
-        history.iter().rev().take(limit).cloned().collect()
+    /// 检查控制循环是否存活
+    ///
+    /// 最近一次执行距今超过两个控制周期视为异常
+    pub async fn check_loop_liveness(&self) -> ControlLoopLiveness {
+        let update_interval = self.config.read().await.control_interval;
+        let last_tick = *self.last_loop_tick.read().await;
+        ControlLoopLiveness::evaluate(last_tick, update_interval, Utc::now())
     }
 
     /// 获取控制性能指标
@@ -423,47 +1295,85 @@ impl ControlService {
 
     /// 应用优化后的参数
     ///
+    /// 验证通过后在同一组写锁内替换控制配置与所有PID控制器的参数，
+    /// 运行中的控制循环从下一个周期起使用新参数
+    ///
+    /// 返回应用后的参数及与上次参数的差异，差异同时记入变更记录
+    ///
     /// # 参数
-    /// * `optimization_result` - 优化结果
+    /// * `parameters` - 控制参数（PID增益、温度阈值、风扇曲线、控制间隔）
+    /// * `source` - 变更来源
     pub async fn apply_optimized_parameters(
         &self,
-        optimization_result: &ControlOptimizationResult,
-    ) -> AppResult<()> {
+        parameters: TuningParameters,
+        source: ParameterChangeSource,
+    ) -> AppResult<AppliedParameters<TuningParameters>> {
         info!("应用优化后的控制参数");
+        parameters.validate()?;
 
-        // 更新PID控制器参数
-        let mut controllers = self.pid_controllers.write().await;
-
-        for (sensor_id, params) in &optimization_result.optimized_parameters {
-            if let Some(controller) = controllers.get_mut(sensor_id) {
-                if let (Some(&kp), Some(&ki), Some(&kd)) =
-                    (params.get("kp"), params.get("ki"), params.get("kd"))
-                {
-                    controller.set_gains(kp, ki, kd);
-                    info!(
-                        "更新传感器 {} 的PID参数: Kp={:.3}, Ki={:.3}, Kd={:.3}",
-                        sensor_id, kp, ki, kd
-                    );
-                }
-            }
-        }
+        // 与控制循环相同的加锁顺序：先配置后控制器
+        let (previous_kp, diff) = {
+            let mut config = self.config.write().await;
+            let mut controllers = self.pid_controllers.write().await;
+            let previous_kp = config.default_pid_kp;
+            Self::swap_parameters(&mut config, &mut controllers, &parameters);
+            let mut tuning_parameters = self.tuning_parameters.write().await;
+            let diff = self
+                .parameter_history
+                .record(source, tuning_parameters.as_ref(), &parameters);
+            *tuning_parameters = Some(parameters.clone());
+            (previous_kp, diff)
+        };
 
-        // 记录参数更新动作
+        let pid = &parameters.pid_parameters;
         self.record_control_action(ControlAction {
             id: uuid::Uuid::new_v4().to_string(),
             timestamp: Utc::now(),
             action_type: ControlActionType::ParameterOptimization,
             target_component: "system".to_string(),
-            previous_value: 0.0,
-            new_value: optimization_result.performance_improvement,
-            reason: "参数优化".to_string(),
+            previous_value: previous_kp,
+            new_value: pid.kp,
+            reason: format!(
+                "应用控制参数: Kp={:.3}, Ki={:.3}, Kd={:.3}, 目标温度={:.1}°C, 间隔={}ms",
+                pid.kp,
+                pid.ki,
+                pid.kd,
+                parameters.temperature_thresholds.target_temperature,
+                parameters.control_interval_ms
+            ),
             success: true,
             error_message: None,
         })
         .await;
 
-        info!("优化参数应用完成");
-        Ok(())
+        info!("优化参数应用完成，{} 个字段变化", diff.changes.len());
+        Ok(AppliedParameters { parameters, diff })
+    }
+
+    /// 将控制参数写入配置与PID控制器
+    ///
+    /// 增益变化后积分累积值不再适用，因此同时重置控制器状态
+    fn swap_parameters(
+        config: &mut ControlConfig,
+        controllers: &mut HashMap<String, PidController>,
+        parameters: &TuningParameters,
+    ) {
+        let pid = &parameters.pid_parameters;
+        let (min_output, max_output) = pid.output_limits;
+
+        config.default_pid_kp = pid.kp;
+        config.default_pid_ki = pid.ki;
+        config.default_pid_kd = pid.kd;
+        config.min_fan_speed = min_output;
+        config.max_fan_speed = max_output;
+        config.target_temperature = parameters.temperature_thresholds.target_temperature;
+        config.control_interval = parameters.control_interval_ms.div_ceil(1000);
+
+        for controller in controllers.values_mut() {
+            controller.set_gains(pid.kp, pid.ki, pid.kd);
+            controller.set_output_limits(min_output, max_output);
+            controller.reset();
+        }
     }
 
     // 私有方法
@@ -479,20 +1389,7 @@ impl ControlService {
         // 获取所有温度传感器
         if let Ok(sensors) = self.thermal_service.get_temperature_sensors().await {
             for sensor_id in sensors {
-                // 创建PID控制器
-                let mut pid = PidController::new(
-                    config.default_pid_kp,
-                    config.default_pid_ki,
-                    config.default_pid_kd,
-                );
-
-                // 设置输出限制
-                pid.set_output_limits(0.0, 100.0);
-
-                // 设置目标温度
-                pid.set_setpoint(config.target_temperature);
-
-                controllers.insert(sensor_id.clone(), pid);
+                controllers.insert(sensor_id.clone(), Self::new_pid_controller(&config));
                 debug!("为传感器 {} 创建PID控制器", sensor_id);
             }
         }
@@ -501,124 +1398,291 @@ impl ControlService {
         Ok(())
     }
 
+    /// 按当前配置创建PID控制器
+    fn new_pid_controller(config: &ControlConfig) -> PidController {
+        let mut pid = PidController::new(
+            config.default_pid_kp,
+            config.default_pid_ki,
+            config.default_pid_kd,
+        );
+
+        // 设置输出限制
+        pid.set_output_limits(0.0, 100.0);
+
+        // 设置目标温度
+        pid.set_setpoint(config.target_temperature);
+        pid
+    }
+
     /// 启动控制循环
     async fn start_control_loop(&self) -> AppResult<()> {
         let thermal_service = Arc::clone(&self.thermal_service);
         let fan_service = Arc::clone(&self.fan_service);
         let pid_controllers = Arc::clone(&self.pid_controllers);
+        let input_filter = Arc::clone(&self.input_filter);
         let config = Arc::clone(&self.config);
         let status = Arc::clone(&self.status);
         let control_history = Arc::clone(&self.control_history);
+        let decisions = self.decisions.clone();
+        let quiet_schedule = Arc::clone(&self.quiet_schedule);
+        let last_loop_tick = Arc::clone(&self.last_loop_tick);
+        let emergency_exit = Arc::clone(&self.emergency_exit);
+        let write_retry = self.write_retry.clone();
+        let power_cap = self.power_cap.clone();
+        let auto_schedule = self.auto_schedule.clone();
+
+        *self.loop_started_at.write().await = Some(Utc::now());
+
+        // 监督器在panic后重新调用该闭包创建控制循环
+        let control_loop = move || {
+            let thermal_service = Arc::clone(&thermal_service);
+            let fan_service = Arc::clone(&fan_service);
+            let pid_controllers = Arc::clone(&pid_controllers);
+            let input_filter = Arc::clone(&input_filter);
+            let config = Arc::clone(&config);
+            let status = Arc::clone(&status);
+            let control_history = Arc::clone(&control_history);
+            let decisions = decisions.clone();
+            let quiet_schedule = Arc::clone(&quiet_schedule);
+            let last_loop_tick = Arc::clone(&last_loop_tick);
+            let emergency_exit = Arc::clone(&emergency_exit);
+            let write_retry = write_retry.clone();
+            let power_cap = power_cap.clone();
+            let auto_schedule = auto_schedule.clone();
+
+            async move {
+                let mut interval = interval(Duration::from_secs(10)); // 默认10秒控制周期
+                let mut last_schedule_decision = None;
+
+                loop {
+                    interval.tick().await;
+
+                    // 记录心跳，自动控制关闭或紧急模式下循环仍视为存活
+                    *last_loop_tick.write().await = Some(Utc::now());
+
+                    // 检查是否启用自动控制
+                    if !status.read().await.is_auto_control_enabled {
+                        continue;
+                    }
 
-        let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(10)); // 默认10秒控制周期
-
-            loop {
-                interval.tick().await;
-
-                // 检查是否启用自动控制
-                if !status.read().await.is_auto_control_enabled {
-                    continue;
-                }
+                    // 紧急模式下只记录温度，供退出确认使用
+                    if status.read().await.emergency_mode {
+                        if let Some(temperature) = Self::max_temperature(&thermal_service).await {
+                            emergency_exit.write().await.observe(temperature, Utc::now());
+                        }
+                        continue;
+                    }
 
-                // 检查是否处于紧急模式
-                if status.read().await.emergency_mode {
-                    continue;
-                }
+                    // 按自动控制时段决定本周期是否调速，达到临界温度时不论时段都执行紧急冷却
+                    if let Some(ref schedule) = auto_schedule {
+                        let max_temperature = Self::max_temperature(&thermal_service).await;
+                        let decision = schedule.decide(chrono::Local::now().naive_local(), max_temperature);
+                        if last_schedule_decision.replace(decision) != Some(decision) {
+                            info!("自动控制时段: {:?}", decision);
+                        }
 
-                // 获取控制周期
-                let control_interval = {
-                    let cfg = config.read().await;
-                    cfg.control_interval
-                };
+                        match decision {
+                            ScheduleDecision::Adjust => {}
+                            ScheduleDecision::Hold => continue,
+                            ScheduleDecision::ManualProfile => {
+                                if let Some(ref profile) = schedule.config().manual_profile {
+                                    Self::apply_manual_profile(&fan_service, &write_retry, profile).await;
+                                }
+                                continue;
+                            }
+                            ScheduleDecision::Emergency => {
+                                if let Err(e) = Self::enter_emergency_cooling(
+                                    &fan_service,
+                                    &control_history,
+                                    &status,
+                                    &emergency_exit,
+                                    &format!("最高温度 {:.1}°C 达到临界温度", max_temperature.unwrap_or_default()),
+                                )
+                                .await
+                                {
+                                    error!("紧急冷却执行失败: {}", e);
+                                }
+                                continue;
+                            }
+                        }
+                    }
 
-                // 更新控制间隔
-                interval = interval(Duration::from_secs(control_interval));
+                    // 获取控制周期
+                    let control_interval = {
+                        let cfg = config.read().await;
+                        cfg.control_interval
+                    };
+
+                    // 更新控制间隔
+                    interval = interval(Duration::from_secs(control_interval));
+
+                    // 先补发上个周期重试耗尽的控制动作
+                    if !fan_service.is_read_only() {
+                        write_retry
+                            .reconcile(|fan_id, speed| {
+                                let fan_service = Arc::clone(&fan_service);
+                                async move { fan_service.set_fan_speed(&fan_id, speed).await.map(|_| ()) }
+                            })
+                            .await;
+                    }
 
-                // 执行控制循环
-                if let Err(e) = Self::execute_control_cycle_static(
-                    &thermal_service,
-                    &fan_service,
-                    &pid_controllers,
-                    &config,
-                    &control_history,
-                )
-                .await
-                {
-                    error!("控制循环执行失败: {}", e);
-                }
+                    // 执行控制循环
+                    if let Err(e) = Self::execute_control_cycle_static(
+                        &thermal_service,
+                        &fan_service,
+                        &pid_controllers,
+                        &input_filter,
+                        &config,
+                        &control_history,
+                        &decisions,
+                        &quiet_schedule,
+                        &write_retry,
+                        power_cap.as_ref(),
+                    )
+                    .await
+                    {
+                        error!("控制循环执行失败: {}", e);
+                    }
 
-                // 更新最后控制时间
-                {
-                    let mut s = status.write().await;
-                    s.last_control_action = Utc::now();
+                    // 更新最后控制时间
+                    {
+                        let mut s = status.write().await;
+                        s.last_control_action = Utc::now();
+                    }
                 }
             }
-        });
+        };
+
+        let handle = match &self.supervisor {
+            Some(supervisor) => Arc::clone(supervisor).supervise(control_loop),
+            None => tokio::spawn(control_loop()),
+        };
 
         self.task_handles.lock().await.push(handle);
         Ok(())
     }
 
+    /// 所有传感器中的最高温度，读取失败或没有读数时为空
+    async fn max_temperature(thermal_service: &ThermalService) -> Option<f64> {
+        thermal_service
+            .get_all_current_temperatures()
+            .await
+            .ok()?
+            .iter()
+            .map(|reading| reading.temperature)
+            .filter(|temperature| temperature.is_finite())
+            .reduce(f64::max)
+    }
+
     /// 执行控制循环（静态方法）
     async fn execute_control_cycle_static(
         thermal_service: &Arc<ThermalService>,
         fan_service: &Arc<FanService>,
         pid_controllers: &Arc<RwLock<HashMap<String, PidController>>>,
+        input_filter: &Arc<RwLock<ControlInputFilter>>,
         config: &Arc<RwLock<ControlConfig>>,
         control_history: &Arc<RwLock<Vec<ControlAction>>>,
+        decisions: &ControlDecisionLog,
+        quiet_schedule: &Arc<RwLock<QuietHoursSchedule>>,
+        write_retry: &ControlWriteRetry,
+        power_cap: Option<&PowerCapCoordinator>,
     ) -> AppResult<()> {
         let cfg = config.read().await;
+        let schedule = quiet_schedule.read().await;
         let mut controllers = pid_controllers.write().await;
+        let mut filter = input_filter.write().await;
+        let now_local = chrono::Local::now().naive_local();
+        let setpoint = Self::effective_setpoint(thermal_service, &cfg).await;
+
+        // 配置了风扇分区时，各分区独立计算控制输入并只驱动本分区风扇
+        if !cfg.fan_zones.is_empty() {
+            return Self::execute_zoned_cycle(
+                thermal_service,
+                fan_service,
+                &mut controllers,
+                &mut filter,
+                &cfg,
+                &schedule,
+                setpoint,
+                now_local,
+                control_history,
+                decisions,
+                write_retry,
+                power_cap,
+            )
+            .await;
+        }
+
+        // 配置了多传感器聚合时，由单一控制输入驱动所有风扇
+        if let Some(ref aggregation) = cfg.sensor_aggregation {
+            return Self::execute_aggregated_cycle(
+                thermal_service,
+                fan_service,
+                aggregation,
+                &mut controllers,
+                &mut filter,
+                &cfg,
+                &schedule,
+                setpoint,
+                now_local,
+                control_history,
+                decisions,
+                write_retry,
+                power_cap,
+            )
+            .await;
+        }
 
         // 获取所有温度传感器数据
         if let Ok(sensors) = thermal_service.get_temperature_sensors().await {
             for sensor_id in sensors {
+                if thermal_service.sensor_blacklist().contains(&sensor_id) {
+                    continue;
+                }
                 if let Ok(temp_reading) = thermal_service.get_current_temperature(&sensor_id).await
                 {
                     if let Some(controller) = controllers.get_mut(&sensor_id) {
-                        // 计算PID输出
-                        let output = controller.update(temp_reading.temperature);
-
-                        // 将输出映射到风扇转速
-                        let fan_speed = Self::map_pid_output_to_fan_speed(output, &cfg);
-
                         // 获取对应的风扇ID（简化处理，假设传感器ID对应风扇ID）
                         let fan_id = Self::get_fan_for_sensor(&sensor_id);
-
-                        // 设置风扇转速
-                        if let Ok(fans) = fan_service.get_fan_list().await {
-                            if fans.contains(&fan_id) {
-                                if let Err(e) = fan_service.set_fan_speed(&fan_id, fan_speed).await
-                                {
-                                    error!("设置风扇 {} 转速失败: {}", fan_id, e);
-                                } else {
-                                    // 记录控制动作
-                                    let action = ControlAction {
-                                        id: uuid::Uuid::new_v4().to_string(),
-                                        timestamp: Utc::now(),
-                                        action_type: ControlActionType::AutomaticControl,
-                                        target_component: fan_id.clone(),
-                                        previous_value: 0.0, // 需要获取之前的值
-                                        new_value: fan_speed,
-                                        reason: format!(
-                                            "温度控制: {:.1}°C -> {:.1}%",
-                                            temp_reading.temperature, fan_speed
-                                        ),
-                                        success: true,
-                                        error_message: None,
-                                    };
-
-                                    let mut history = control_history.write().await;
-                                    history.push(action);
-
-                                    // 限制历史记录数量
-                                    if history.len() > 1000 {
-                                        history.drain(0..100); // 删除最旧的100条记录
-                                    }
+                        let temperature = filter.apply(
+                            &sensor_id,
+                            temp_reading.temperature,
+                            cfg.smoothing_window_for(&sensor_id),
+                        );
+
+                        // 计算PID输出并记录决策依据
+                        let Some(mut decision) = Self::decide_outside_comfort_band(
+                            &sensor_id,
+                            &fan_id,
+                            temperature,
+                            setpoint,
+                            controller,
+                            &cfg,
+                            &schedule,
+                            now_local,
+                        ) else {
+                            continue;
+                        };
+
+                        // 只读模式下仅记录决策，不下发转速
+                        if !fan_service.is_read_only() {
+                            if let Ok(fans) = fan_service.get_controllable_fan_list().await {
+                                if fans.contains(&fan_id) {
+                                    decision.readback_percent = Self::apply_fan_speed(
+                                        fan_service,
+                                        control_history,
+                                        write_retry,
+                                        power_cap,
+                                        &cfg,
+                                        &fan_id,
+                                        decision.limited_output,
+                                        temp_reading.temperature,
+                                    )
+                                    .await;
                                 }
                             }
                         }
+                        Self::record_decision(decisions, decision).await;
                     }
                 }
             }
@@ -627,14 +1691,331 @@ impl ControlService {
         Ok(())
     }
 
+    /// 执行聚合控制周期
+    ///
+    /// 读取传感器组的温度并按策略聚合为单一控制输入，
+    /// 使用同一个PID控制器计算转速并下发到所有风扇
+    async fn execute_aggregated_cycle(
+        thermal_service: &Arc<ThermalService>,
+        fan_service: &Arc<FanService>,
+        aggregation: &SensorAggregationConfig,
+        controllers: &mut HashMap<String, PidController>,
+        filter: &mut ControlInputFilter,
+        cfg: &ControlConfig,
+        schedule: &QuietHoursSchedule,
+        setpoint: f64,
+        now_local: chrono::NaiveDateTime,
+        control_history: &Arc<RwLock<Vec<ControlAction>>>,
+        decisions: &ControlDecisionLog,
+        write_retry: &ControlWriteRetry,
+        power_cap: Option<&PowerCapCoordinator>,
+    ) -> AppResult<()> {
+        let mut readings = Vec::new();
+        for sensor_id in thermal_service.get_temperature_sensors().await? {
+            if !aggregation.includes(&sensor_id) || thermal_service.sensor_blacklist().contains(&sensor_id) {
+                continue;
+            }
+            match thermal_service.get_current_temperature(&sensor_id).await {
+                Ok(reading) => {
+                    let temperature = filter.apply(&sensor_id, reading.temperature, cfg.smoothing_window_for(&sensor_id));
+                    readings.push((sensor_id, temperature));
+                }
+                Err(e) => warn!("读取传感器 {} 失败，不参与本周期聚合: {}", sensor_id, e),
+            }
+        }
+
+        let Some(temperature) = aggregation.aggregate(&readings) else {
+            warn!("传感器组没有可用读数，跳过本周期聚合控制");
+            return Ok(());
+        };
+
+        let controller = controllers
+            .entry(AGGREGATE_CONTROLLER_ID.to_string())
+            .or_insert_with(|| Self::new_pid_controller(cfg));
+        let Some(mut decision) = Self::decide_outside_comfort_band(
+            AGGREGATE_CONTROLLER_ID,
+            "*",
+            temperature,
+            setpoint,
+            controller,
+            cfg,
+            schedule,
+            now_local,
+        ) else {
+            return Ok(());
+        };
+
+        // 只读模式下仅记录决策，不下发转速
+        if !fan_service.is_read_only() {
+            let mut readbacks = Vec::new();
+            for fan_id in fan_service.get_controllable_fan_list().await? {
+                readbacks.extend(
+                    Self::apply_fan_speed(
+                        fan_service,
+                        control_history,
+                        write_retry,
+                        power_cap,
+                        cfg,
+                        &fan_id,
+                        decision.limited_output,
+                        temperature,
+                    )
+                    .await,
+                );
+            }
+            decision.readback_percent = Self::mean_readback(&readbacks);
+        }
+        Self::record_decision(decisions, decision).await;
+        Ok(())
+    }
+
+    /// 执行分区控制周期
+    ///
+    /// 读取所有分区引用的传感器，按分区计算转速并下发到分区内的风扇，
+    /// 未划入任何分区的风扇保持不变
+    async fn execute_zoned_cycle(
+        thermal_service: &Arc<ThermalService>,
+        fan_service: &Arc<FanService>,
+        controllers: &mut HashMap<String, PidController>,
+        filter: &mut ControlInputFilter,
+        cfg: &ControlConfig,
+        schedule: &QuietHoursSchedule,
+        setpoint: f64,
+        now_local: chrono::NaiveDateTime,
+        control_history: &Arc<RwLock<Vec<ControlAction>>>,
+        decisions: &ControlDecisionLog,
+        write_retry: &ControlWriteRetry,
+        power_cap: Option<&PowerCapCoordinator>,
+    ) -> AppResult<()> {
+        let mut readings = Vec::new();
+        for sensor_id in thermal_service.get_temperature_sensors().await? {
+            if !cfg.fan_zones.iter().any(|zone| zone.sensors.includes(&sensor_id))
+                || thermal_service.sensor_blacklist().contains(&sensor_id)
+            {
+                continue;
+            }
+            match thermal_service.get_current_temperature(&sensor_id).await {
+                Ok(reading) => {
+                    let temperature = filter.apply(&sensor_id, reading.temperature, cfg.smoothing_window_for(&sensor_id));
+                    readings.push((sensor_id, temperature));
+                }
+                Err(e) => warn!("读取传感器 {} 失败，不参与本周期分区控制: {}", sensor_id, e),
+            }
+        }
+
+        let zone_decisions =
+            Self::decide_zone_speeds(&readings, controllers, cfg, schedule, setpoint, now_local);
+
+        // 只读模式下仅记录决策，不下发转速
+        let available_fans = if fan_service.is_read_only() {
+            None
+        } else {
+            Some(fan_service.get_controllable_fan_list().await?)
+        };
+
+        for (zone, mut decision) in zone_decisions {
+            if let Some(ref available_fans) = available_fans {
+                let mut readbacks = Vec::new();
+                for fan_id in zone.fan_ids.iter().filter(|id| available_fans.contains(id)) {
+                    readbacks.extend(
+                        Self::apply_fan_speed(
+                            fan_service,
+                            control_history,
+                            write_retry,
+                            power_cap,
+                            cfg,
+                            fan_id,
+                            decision.limited_output,
+                            decision.input_temperature,
+                        )
+                        .await,
+                    );
+                }
+                decision.readback_percent = Self::mean_readback(&readbacks);
+            }
+            Self::record_decision(decisions, decision).await;
+        }
+        Ok(())
+    }
+
+    /// 计算各风扇分区的转速决策
+    ///
+    /// 每个分区使用独立的PID控制器（ID为 `zone:<分区ID>`），没有可用读数的分区跳过
+    ///
+    /// # 参数
+    /// * `readings` - `(传感器ID, 温度)` 列表
+    /// * `controllers` - PID控制器集合
+    /// * `cfg` - 控制配置
+    /// * `schedule` - 静音时段计划
+    /// * `setpoint` - 目标温度
+    /// * `now_local` - 本地时间
+    fn decide_zone_speeds<'a>(
+        readings: &[(String, f64)],
+        controllers: &mut HashMap<String, PidController>,
+        cfg: &'a ControlConfig,
+        schedule: &QuietHoursSchedule,
+        setpoint: f64,
+        now_local: chrono::NaiveDateTime,
+    ) -> Vec<(&'a FanZoneConfig, ControlDecision)> {
+        let mut zone_decisions = Vec::new();
+        for zone in &cfg.fan_zones {
+            let Some(temperature) = zone.sensors.aggregate(readings) else {
+                warn!("风扇分区 {} 没有可用读数，跳过本周期控制", zone.zone_id);
+                continue;
+            };
+
+            let controller_id = format!("zone:{}", zone.zone_id);
+            let controller = controllers
+                .entry(controller_id.clone())
+                .or_insert_with(|| Self::new_pid_controller(cfg));
+            let Some(decision) = Self::decide_outside_comfort_band(
+                &controller_id,
+                &zone.fan_ids.join(","),
+                temperature,
+                setpoint,
+                controller,
+                cfg,
+                schedule,
+                now_local,
+            ) else {
+                continue;
+            };
+            zone_decisions.push((zone, decision));
+        }
+        zone_decisions
+    }
+
+    /// 下发风扇转速并记录控制动作
+    ///
+    /// 分区和聚合控制的决策作用于多个风扇，因此在下发时按各风扇的最低转速提升；
+    /// 配置了起转脉冲时检测低速停转。
+    /// 返回下发后回读的转速百分比，下发或回读失败时为空
+    async fn apply_fan_speed(
+        fan_service: &Arc<FanService>,
+        control_history: &Arc<RwLock<Vec<ControlAction>>>,
+        write_retry: &ControlWriteRetry,
+        power_cap: Option<&PowerCapCoordinator>,
+        cfg: &ControlConfig,
+        fan_id: &str,
+        fan_speed: f64,
+        temperature: f64,
+    ) -> Option<f64> {
+        // 提速前检查功率上限，当前转速读取失败时不做限制
+        let fan_speed = match power_cap {
+            Some(power_cap) => match fan_service.current_speed(fan_id).await {
+                Ok(current) => power_cap.coordinate(fan_id, current, fan_speed, temperature).await,
+                Err(e) => {
+                    warn!("读取风扇 {} 当前转速失败，跳过功率上限检查: {}", fan_id, e);
+                    fan_speed
+                }
+            },
+            None => fan_speed,
+        };
+        let fan_speed = cfg.floored_fan_speed(fan_id, fan_speed);
+        let reason = format!("温度控制: {:.1}°C -> {:.1}%", temperature, fan_speed);
+        // 重试耗尽时已记入死信，下一周期补发
+        let write = write_retry
+            .write(fan_id, fan_speed, &reason, || fan_service.set_fan_speed(fan_id, fan_speed))
+            .await?;
+
+        // 记录控制动作，转速未变化时记为空操作
+        let (action_type, reason) = match write {
+            FanSpeedWrite::Applied => (ControlActionType::AutomaticControl, reason),
+            FanSpeedWrite::Unchanged => (ControlActionType::NoOp, format!("{}，转速未变化", reason)),
+        };
+        let action = ControlAction {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            action_type,
+            target_component: fan_id.to_string(),
+            previous_value: 0.0, // 需要获取之前的值
+            new_value: fan_speed,
+            reason,
+            success: true,
+            error_message: None,
+        };
+
+        let mut history = control_history.write().await;
+        history.push(action);
+
+        // 限制历史记录数量
+        if history.len() > 1000 {
+            history.drain(0..100); // 删除最旧的100条记录
+        }
+        drop(history);
+
+        if let Some(ref kickstart) = cfg.kickstart {
+            match kickstart_if_stalled(fan_service.as_ref(), kickstart, fan_id, fan_speed).await {
+                Ok(KickstartOutcome::NotNeeded) => {}
+                Ok(KickstartOutcome::Recovered { rpm }) => {
+                    info!("风扇 {} 低速停转，起转脉冲后恢复转动: {:.0} RPM", fan_id, rpm)
+                }
+                Ok(KickstartOutcome::Stalled) => {
+                    error!("风扇 {} 起转脉冲 {:.0}% 后仍无转速，可能已损坏", fan_id, kickstart.duty_percent)
+                }
+                Err(e) => warn!("风扇 {} 起转检测失败: {}", fan_id, e),
+            }
+        }
+
+        match fan_service.current_speed(fan_id).await {
+            Ok(readback) => Some(readback),
+            Err(e) => {
+                warn!("回读风扇 {} 转速失败: {}", fan_id, e);
+                None
+            }
+        }
+    }
+
+    /// 按手动档案下发各风扇转速，与上次下发相同的转速不会重复写入BMC
+    ///
+    /// # 参数
+    /// * `profile` - 时段外的手动转速档案
+    async fn apply_manual_profile(
+        fan_service: &Arc<FanService>,
+        write_retry: &ControlWriteRetry,
+        profile: &ManualFanProfile,
+    ) {
+        if fan_service.is_read_only() {
+            return;
+        }
+        let fans = match fan_service.get_controllable_fan_list().await {
+            Ok(fans) => fans,
+            Err(e) => {
+                warn!("读取可控风扇列表失败，跳过手动档案: {}", e);
+                return;
+            }
+        };
+
+        for fan_id in fans {
+            let speed = profile.percent_for(&fan_id);
+            let reason = format!("自动控制时段外手动档案: {:.1}%", speed);
+            write_retry
+                .write(&fan_id, speed, &reason, || fan_service.set_fan_speed(&fan_id, speed))
+                .await;
+        }
+    }
+
+    /// 多台风扇回读转速的平均值，没有回读时为空
+    ///
+    /// # 参数
+    /// * `readbacks` - 各风扇回读的转速百分比
+    fn mean_readback(readbacks: &[f64]) -> Option<f64> {
+        (!readbacks.is_empty()).then(|| readbacks.iter().sum::<f64>() / readbacks.len() as f64)
+    }
+
     /// 执行控制循环
     async fn execute_control_cycle(&self) -> AppResult<()> {
         Self::execute_control_cycle_static(
             &self.thermal_service,
             &self.fan_service,
             &self.pid_controllers,
+            &self.input_filter,
             &self.config,
             &self.control_history,
+            &self.decisions,
+            &self.quiet_schedule,
+            &self.write_retry,
+            self.power_cap.as_ref(),
         )
         .await
     }
@@ -674,58 +2055,47 @@ impl ControlService {
     }
 
     /// 应用保守策略
-    async fn apply_conservative_strategy(&self, strategy: &ControlStrategy) -> AppResult<()> {
+    fn apply_conservative_strategy(config: &mut ControlConfig) {
         info!("应用保守控制策略");
 
         // 保守策略：较低的目标温度，较慢的响应
-        let mut config = self.config.write().await;
         config.target_temperature = 60.0; // 较低的目标温度
         config.max_fan_speed = 80.0; // 限制最大风扇转速
         config.control_interval = 30; // 较长的控制间隔
         config.default_pid_kp = 0.5; // 较小的比例增益
         config.default_pid_ki = 0.1;
         config.default_pid_kd = 0.05;
-
-        Ok(())
     }
 
     /// 应用平衡策略
-    async fn apply_balanced_strategy(&self, strategy: &ControlStrategy) -> AppResult<()> {
+    fn apply_balanced_strategy(config: &mut ControlConfig) {
         info!("应用平衡控制策略");
 
-        let mut config = self.config.write().await;
         config.target_temperature = 65.0; // 中等目标温度
         config.max_fan_speed = 90.0;
         config.control_interval = 15; // 中等控制间隔
         config.default_pid_kp = 1.0; // 中等PID参数
         config.default_pid_ki = 0.2;
         config.default_pid_kd = 0.1;
-
-        Ok(())
     }
 
     /// 应用激进策略
-    async fn apply_aggressive_strategy(&self, strategy: &ControlStrategy) -> AppResult<()> {
+    fn apply_aggressive_strategy(config: &mut ControlConfig) {
         info!("应用激进控制策略");
 
-        let mut config = self.config.write().await;
         config.target_temperature = 70.0; // 较高的目标温度
         config.max_fan_speed = 100.0; // 允许最大风扇转速
         config.control_interval = 5; // 较短的控制间隔
         config.default_pid_kp = 2.0; // 较大的比例增益
         config.default_pid_ki = 0.5;
         config.default_pid_kd = 0.2;
-
-        Ok(())
     }
 
     /// 应用自定义策略
-    async fn apply_custom_strategy(&self, strategy: &ControlStrategy) -> AppResult<()> {
+    fn apply_custom_strategy(config: &mut ControlConfig, strategy: &ControlStrategy) {
         info!("应用自定义控制策略");
 
         if let Some(params) = &strategy.parameters {
-            let mut config = self.config.write().await;
-
             if let Some(target_temp) = params.get("target_temperature") {
                 config.target_temperature = *target_temp;
             }
@@ -745,8 +2115,84 @@ impl ControlService {
                 config.default_pid_kd = *kd;
             }
         }
+    }
 
-        Ok(())
+    /// 按新配置的转速范围下发所有风扇转速，任一风扇失败时回滚已下发的风扇
+    ///
+    /// 各风扇的新转速为当前转速限制在新配置的转速上下限内（不低于该风扇的最低转速），
+    /// 之后由控制循环继续调节。
+    /// 成功时返回 `(风扇ID, 原转速, 新转速)` 列表
+    ///
+    /// # 参数
+    /// * `driver` - 风扇转速读写接口
+    /// * `config` - 新的控制配置
+    async fn apply_fan_speeds_with_rollback(
+        driver: &dyn FanSpeedDriver,
+        config: &ControlConfig,
+    ) -> AppResult<Vec<(String, f64, f64)>> {
+        // 下发前先记录全部风扇的转速快照，快照不完整时不做任何修改
+        let targets = Self::capture_fan_speeds(driver)
+            .await?
+            .into_iter()
+            .map(|(fan_id, previous_speed)| {
+                let new_speed =
+                    config.floored_fan_speed(&fan_id, Self::map_pid_output_to_fan_speed(previous_speed, config));
+                (fan_id, previous_speed, new_speed)
+            })
+            .collect();
+
+        Self::set_fan_speeds_with_rollback(driver, targets, "应用控制策略").await
+    }
+
+    /// 读取所有风扇的当前转速，任一风扇读取失败时返回错误
+    ///
+    /// # 参数
+    /// * `driver` - 风扇转速读写接口
+    async fn capture_fan_speeds(driver: &dyn FanSpeedDriver) -> AppResult<Vec<(String, f64)>> {
+        let mut speeds = Vec::new();
+        for fan_id in driver.fan_ids().await? {
+            let speed = driver.current_speed(&fan_id).await?;
+            speeds.push((fan_id, speed));
+        }
+        Ok(speeds)
+    }
+
+    /// 依次下发风扇转速，任一风扇失败时按相反顺序回滚已下发的风扇
+    ///
+    /// 成功时返回 `(风扇ID, 原转速, 新转速)` 列表
+    ///
+    /// # 参数
+    /// * `driver` - 风扇转速读写接口
+    /// * `targets` - `(风扇ID, 原转速, 新转速)` 列表
+    /// * `operation` - 操作描述，用于日志和错误消息
+    async fn set_fan_speeds_with_rollback(
+        driver: &dyn FanSpeedDriver,
+        targets: Vec<(String, f64, f64)>,
+        operation: &str,
+    ) -> AppResult<Vec<(String, f64, f64)>> {
+        let mut applied = Vec::with_capacity(targets.len());
+        for (fan_id, previous_speed, new_speed) in targets {
+            if let Err(e) = driver.set_speed(&fan_id, new_speed).await {
+                error!("风扇 {} {}失败，回滚已下发的 {} 个风扇: {}", fan_id, operation, applied.len(), e);
+
+                let mut rollback_failures = Vec::new();
+                for (applied_id, applied_previous, _) in applied.iter().rev() {
+                    if let Err(rollback_err) = driver.set_speed(applied_id, *applied_previous).await {
+                        error!("回滚风扇 {} 转速失败: {}", applied_id, rollback_err);
+                        rollback_failures.push(applied_id.as_str());
+                    }
+                }
+
+                let mut message = format!("风扇 {} {}失败: {}", fan_id, operation, e);
+                if !rollback_failures.is_empty() {
+                    message.push_str(&format!("；以下风扇回滚失败: {}", rollback_failures.join(", ")));
+                }
+                return Err(AppError::ipmi_error(message));
+            }
+            applied.push((fan_id, previous_speed, new_speed));
+        }
+
+        Ok(applied)
     }
 
     /// 记录控制动作
@@ -814,6 +2260,34 @@ impl ControlService {
         improvement.min(20.0) // 最大20%的改进
     }
 
+    /// 计算本周期的有效目标温度
+    ///
+    /// 启用进风温度调整时读取进风传感器，读取失败则回退到固定目标温度
+    async fn effective_setpoint(thermal_service: &Arc<ThermalService>, config: &ControlConfig) -> f64 {
+        let Some(ref ambient) = config.ambient_target else {
+            return config.target_temperature;
+        };
+        if !ambient.enabled {
+            return config.target_temperature;
+        }
+
+        let inlet = match thermal_service
+            .get_current_temperature(&ambient.inlet_sensor_id)
+            .await
+        {
+            Ok(reading) => Some(reading.temperature),
+            Err(e) => {
+                warn!(
+                    "读取进风传感器 {} 失败，使用固定目标温度: {}",
+                    ambient.inlet_sensor_id, e
+                );
+                None
+            }
+        };
+
+        ambient.effective_target(config.target_temperature, inlet)
+    }
+
     /// 将PID输出映射到风扇转速
     fn map_pid_output_to_fan_speed(pid_output: f64, config: &ControlConfig) -> f64 {
         // 将PID输出（0-100）映射到风扇转速
@@ -823,6 +2297,130 @@ impl ControlService {
         speed
     }
 
+    /// 计算单个传感器的风扇转速决策
+    ///
+    /// 风扇为反向作用：温度高于目标时需要提高转速，因此以温度作为设定值输入、
+    /// 目标温度作为过程值输入，使误差为正时输出增大
+    fn decide_fan_speed(
+        sensor_id: &str,
+        fan_id: &str,
+        temperature: f64,
+        setpoint: f64,
+        controller: &mut PidController,
+        config: &ControlConfig,
+        schedule: &QuietHoursSchedule,
+        at: chrono::NaiveDateTime,
+    ) -> ControlDecision {
+        let terms: PidTerms = controller.compute_terms(
+            temperature,
+            setpoint,
+            config.control_interval as f64,
+        );
+
+        // 将输出映射到风扇转速，静音时段内限制转速（紧急温度除外）
+        let fan_speed = Self::map_pid_output_to_fan_speed(terms.output, config);
+        let limited_output = Self::apply_quiet_hours(fan_speed, temperature, schedule, at);
+        let quiet_cap = if temperature < schedule.emergency_temperature {
+            schedule.fan_cap_at(at)
+        } else {
+            None
+        };
+
+        ControlDecision {
+            timestamp: Utc::now(),
+            sensor_id: sensor_id.to_string(),
+            fan_id: fan_id.to_string(),
+            input_temperature: temperature,
+            setpoint,
+            proportional: terms.proportional,
+            integral: terms.integral,
+            derivative: terms.derivative,
+            raw_output: terms.raw_output,
+            limited_output,
+            quiet_cap,
+            readback_percent: None,
+        }
+    }
+
+    /// 温度在舒适区内时保持当前转速：不计算PID、不下发转速，返回None；
+    /// 离开舒适区后按 [`Self::decide_fan_speed`] 计算决策
+    fn decide_outside_comfort_band(
+        sensor_id: &str,
+        fan_id: &str,
+        temperature: f64,
+        setpoint: f64,
+        controller: &mut PidController,
+        config: &ControlConfig,
+        schedule: &QuietHoursSchedule,
+        at: chrono::NaiveDateTime,
+    ) -> Option<ControlDecision> {
+        if config.within_comfort_band(temperature, setpoint) {
+            debug!(
+                "{} 温度 {:.1}°C 在舒适区 {:.1}±{:.1}°C 内，保持当前转速",
+                sensor_id, temperature, setpoint, config.comfort_band
+            );
+            return None;
+        }
+
+        Some(Self::decide_fan_speed(
+            sensor_id,
+            fan_id,
+            temperature,
+            setpoint,
+            controller,
+            config,
+            schedule,
+            at,
+        ))
+    }
+
+    /// 记录控制决策
+    ///
+    /// 输出结构化日志并写入决策记录，配置了持久化存储时同时落库
+    async fn record_decision(
+        decisions: &ControlDecisionLog,
+        decision: ControlDecision,
+    ) {
+        info!(
+            target: "control_decision",
+            sensor_id = %decision.sensor_id,
+            fan_id = %decision.fan_id,
+            input_temperature = decision.input_temperature,
+            setpoint = decision.setpoint,
+            p = decision.proportional,
+            i = decision.integral,
+            d = decision.derivative,
+            raw_output = decision.raw_output,
+            limited_output = decision.limited_output,
+            quiet_cap = ?decision.quiet_cap,
+            readback_percent = ?decision.readback_percent,
+            "控制决策: {:.1}°C -> {:.1}%",
+            decision.input_temperature,
+            decision.limited_output
+        );
+
+        decisions.record(decision).await;
+    }
+
+    /// 应用静音时段转速上限
+    ///
+    /// 温度达到紧急阈值时不做限制
+    fn apply_quiet_hours(
+        fan_speed: f64,
+        temperature: f64,
+        schedule: &QuietHoursSchedule,
+        at: chrono::NaiveDateTime,
+    ) -> f64 {
+        if temperature >= schedule.emergency_temperature {
+            return fan_speed;
+        }
+
+        match schedule.fan_cap_at(at) {
+            Some(cap) => fan_speed.min(cap),
+            None => fan_speed,
+        }
+    }
+
     /// 获取传感器对应的风扇ID
     fn get_fan_for_sensor(sensor_id: &str) -> String {
         // 简化处理，实际应该有传感器到风扇的映射配置
@@ -830,6 +2428,15 @@ impl ControlService {
     }
 }
 
+/// 控制决策环形缓冲容量
+const MAX_CONTROL_DECISIONS: usize = 500;
+
+/// 聚合控制使用的PID控制器ID，同时作为控制决策的传感器ID
+const AGGREGATE_CONTROLLER_ID: &str = "aggregate";
+
+/// 静音时段计划默认持久化文件
+const QUIET_SCHEDULE_FILE: &str = "data/control_schedule.json";
+
 /// 温度控制分析结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TemperatureControlAnalysis {
@@ -846,6 +2453,42 @@ struct TemperatureControlAnalysis {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::config::MonitoringConfig;
+    use crate::services::{alert_service::AlertService, ipmi_service::{IpmiConfig, IpmiService}};
+
+    fn test_control_service() -> ControlService {
+        let ipmi_service = Arc::new(IpmiService::new(IpmiConfig::default()));
+        let thermal_service = Arc::new(ThermalService::new(ipmi_service.clone(), MonitoringConfig::default()));
+        let fan_service = Arc::new(FanService::new(ipmi_service.clone(), MonitoringConfig::default()));
+        let sensor_service = Arc::new(SensorService::new(ipmi_service, MonitoringConfig::default()));
+        let monitoring_service = Arc::new(MonitoringService::new(
+            thermal_service.clone(),
+            fan_service.clone(),
+            sensor_service.clone(),
+            Arc::new(AlertService::new()),
+        ));
+        ControlService::new(thermal_service, fan_service, sensor_service, monitoring_service)
+    }
+
+    #[tokio::test]
+    async fn test_restart_control_loop_advances_start_and_resumes_ticking() {
+        let service = test_control_service();
+
+        let first = service.restart_control_loop().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let second = service.restart_control_loop().await.unwrap();
+        assert!(second > first);
+        assert_eq!(service.loop_started_at().await, Some(second));
+
+        // interval的首个tick立即完成，重启后的循环应很快记录心跳
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !service.last_loop_tick.read().await.is_some_and(|tick| tick >= second) {
+            assert!(Instant::now() < deadline, "控制循环重启后未恢复运行");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        service.stop_auto_control().await.unwrap();
+    }
 
     #[test]
     fn test_pid_output_mapping() {
@@ -861,6 +2504,643 @@ mod tests {
         assert_eq!(speed3, config.min_fan_speed);
     }
 
+    fn history_action(action_type: ControlActionType, target: &str) -> ControlAction {
+        ControlAction {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            action_type,
+            target_component: target.to_string(),
+            previous_value: 0.0,
+            new_value: 80.0,
+            reason: "test".to_string(),
+            success: true,
+            error_message: None,
+        }
+    }
+
+    fn sample_history() -> Vec<ControlAction> {
+        vec![
+            history_action(ControlActionType::AutomaticControl, "fan1"),
+            history_action(ControlActionType::EmergencyCooling, "fan3"),
+            history_action(ControlActionType::ManualFanControl, "fan3"),
+            history_action(ControlActionType::EmergencyCooling, "fan1"),
+            history_action(ControlActionType::EmergencyCooling, "fan3"),
+        ]
+    }
+
+    #[test]
+    fn test_control_history_filters_by_action_type() {
+        let query = ControlHistoryQuery {
+            action_types: vec!["emergency_cooling".parse().unwrap()],
+            page: 1,
+            page_size: 2,
+            ..Default::default()
+        };
+
+        let page = query.paginate(&sample_history());
+        assert_eq!(page.total, 3);
+        assert_eq!(page.total_pages, 2);
+        assert_eq!(page.items.len(), 2);
+        assert!(page
+            .items
+            .iter()
+            .all(|a| a.action_type == ControlActionType::EmergencyCooling));
+        assert!("cooling".parse::<ControlActionType>().is_err());
+    }
+
+    #[test]
+    fn test_control_history_filters_by_target_id() {
+        let query = ControlHistoryQuery {
+            target_id: Some("fan3".to_string()),
+            page: 1,
+            page_size: 100,
+            ..Default::default()
+        };
+
+        let page = query.paginate(&sample_history());
+        assert_eq!(page.total, 3);
+        assert!(page.items.iter().all(|a| a.target_component == "fan3"));
+        // 最新的动作排在最前
+        assert_eq!(page.items[0].action_type, ControlActionType::EmergencyCooling);
+        assert_eq!(page.items[1].action_type, ControlActionType::ManualFanControl);
+    }
+
+    fn sample_control_config() -> ControlConfig {
+        ControlConfig::new(
+            "default".to_string(),
+            ControlMode::Pid,
+            ControlParameters {
+                target_temperature: 65.0,
+                temperature_tolerance: 2.0,
+                pid_params: None,
+                max_fan_speed: 100,
+                min_fan_speed: 20,
+                warning_temperature: 75.0,
+                critical_temperature: 85.0,
+                emergency_temperature: 95.0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_aggregation_policy_changes_control_input() {
+        let readings = vec![
+            ("cpu1".to_string(), 70.0),
+            ("cpu2".to_string(), 60.0),
+            ("inlet".to_string(), 25.0),
+        ];
+        let mut aggregation = SensorAggregationConfig {
+            policy: AggregationPolicy::Max,
+            sensor_ids: vec!["cpu1".to_string(), "cpu2".to_string()],
+            weights: HashMap::new(),
+        };
+        assert_eq!(aggregation.aggregate(&readings), Some(70.0));
+
+        aggregation.policy = AggregationPolicy::Average;
+        assert_eq!(aggregation.aggregate(&readings), Some(65.0));
+
+        aggregation.policy = AggregationPolicy::WeightedMax;
+        aggregation.weights.insert("cpu2".to_string(), 1.25);
+        assert_eq!(aggregation.aggregate(&readings), Some(75.0));
+
+        aggregation.sensor_ids = vec!["missing".to_string()];
+        assert_eq!(aggregation.aggregate(&readings), None);
+    }
+
+    #[test]
+    fn test_fan_curve_replay_speed_distribution() {
+        use crate::controllers::control_controller::FanCurvePoint;
+
+        let curve = FanCurve {
+            points: [(40.0, 20.0), (60.0, 60.0), (80.0, 100.0)]
+                .iter()
+                .map(|(temperature, speed_percent)| FanCurvePoint {
+                    temperature: *temperature,
+                    speed_percent: *speed_percent,
+                })
+                .collect(),
+            min_speed: 20,
+            max_speed: 100,
+        };
+        let mut config = ControlConfig::default();
+        config.target_temperature = 65.0;
+        config.fan_speed_floors.insert("fan_1".to_string(), 35.0);
+
+        // 每个周期两个传感器，按最高温度换算
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let mut readings = Vec::new();
+        for (minute, temperature) in [35.0, 45.0, 50.0, 55.0, 60.0, 70.0, 70.0, 85.0].iter().enumerate() {
+            for (sensor_id, offset) in [("cpu1", 0.0), ("cpu2", -10.0)] {
+                readings.push(TemperatureReading {
+                    id: uuid::Uuid::new_v4(),
+                    sensor_id: sensor_id.to_string(),
+                    sensor_name: sensor_id.to_string(),
+                    temperature: temperature + offset,
+                    status: crate::models::thermal::TemperatureStatus::Normal,
+                    timestamp: start + chrono::Duration::minutes(minute as i64 + 1),
+                    server_id: "server-1".to_string(),
+                });
+            }
+        }
+
+        let replay = ControlService::replay_fan_curve(
+            "fan_1",
+            &curve,
+            &config,
+            &readings,
+            start,
+            start + chrono::Duration::hours(1),
+        );
+
+        assert_eq!(replay.evaluated_samples, 8);
+        // 转速依次为 35(下限) 35(下限) 40 50 60 80 80 100
+        let samples: Vec<usize> = replay.speed_distribution.iter().map(|b| b.samples).collect();
+        assert_eq!(samples, vec![0, 0, 0, 2, 1, 1, 1, 0, 2, 1]);
+        assert_eq!(replay.speed_distribution[9].upper_percent, 100.0);
+        assert!((replay.speed_distribution[3].ratio - 0.25).abs() < 1e-9);
+        assert!((replay.average_speed_percent - 60.0).abs() < 1e-9);
+        assert!(replay.estimated_average_power_watts > estimate_fan_power_watts(0.0));
+
+        // 70°C时曲线只给到80%，85°C时已达最高转速不计入
+        assert_eq!(replay.over_target_samples, 2);
+        assert_eq!(replay.over_target_periods.len(), 1);
+        assert_eq!(replay.over_target_periods[0].peak_temperature, 70.0);
+        assert_eq!(replay.over_target_periods[0].max_speed_percent, 80.0);
+    }
+
+    fn fan_zone(zone_id: &str, sensor_ids: &[&str], fan_ids: &[&str]) -> FanZoneConfig {
+        FanZoneConfig {
+            zone_id: zone_id.to_string(),
+            sensors: SensorAggregationConfig {
+                policy: AggregationPolicy::Max,
+                sensor_ids: sensor_ids.iter().map(|id| id.to_string()).collect(),
+                weights: HashMap::new(),
+            },
+            fan_ids: fan_ids.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_heating_one_zone_only_raises_its_fans() {
+        let mut config = ControlConfig::default();
+        config.fan_zones = vec![
+            fan_zone("cpu", &["temp_cpu1", "temp_cpu2"], &["fan_1", "fan_2"]),
+            fan_zone("pcie", &["temp_gpu"], &["fan_3"]),
+        ];
+        assert!(FanZoneConfig::validate_all(&config.fan_zones).is_ok());
+        let schedule = QuietHoursSchedule::default();
+        let setpoint = config.target_temperature;
+
+        let fan_speeds = |readings: &[(String, f64)]| {
+            let mut controllers = HashMap::new();
+            ControlService::decide_zone_speeds(
+                readings,
+                &mut controllers,
+                &config,
+                &schedule,
+                setpoint,
+                chrono::Local::now().naive_local(),
+            )
+            .into_iter()
+            .flat_map(|(zone, decision)| {
+                zone.fan_ids
+                    .iter()
+                    .map(move |fan_id| (fan_id.clone(), decision.limited_output))
+            })
+            .collect::<HashMap<String, f64>>()
+        };
+
+        let mut readings = vec![
+            ("temp_cpu1".to_string(), setpoint),
+            ("temp_cpu2".to_string(), setpoint - 5.0),
+            ("temp_gpu".to_string(), setpoint),
+        ];
+        let baseline = fan_speeds(&readings);
+        readings[2].1 = setpoint + 20.0;
+        let heated = fan_speeds(&readings);
+
+        assert_eq!(baseline.len(), 3);
+        assert!(heated["fan_3"] > baseline["fan_3"]);
+        assert_eq!(heated["fan_1"], baseline["fan_1"]);
+        assert_eq!(heated["fan_2"], baseline["fan_2"]);
+
+        // 同一风扇不能划入两个分区
+        let overlapping = vec![
+            fan_zone("cpu", &["temp_cpu1"], &["fan_1"]),
+            fan_zone("pcie", &["temp_gpu"], &["fan_1"]),
+        ];
+        assert!(FanZoneConfig::validate_all(&overlapping).is_err());
+    }
+
+    #[test]
+    fn test_config_patch_changes_only_target_field() {
+        let current = sample_control_config();
+        let patch = ControlConfigPatch {
+            target_temperature: Some(70.0),
+            ..Default::default()
+        };
+
+        let merged = patch.merged(&current).unwrap();
+        assert_eq!(merged.parameters.target_temperature, 70.0);
+        assert_eq!(merged.id, current.id);
+        assert_eq!(merged.name, current.name);
+        assert_eq!(merged.parameters.temperature_tolerance, 2.0);
+        assert_eq!(merged.parameters.min_fan_speed, 20);
+        assert_eq!(merged.parameters.max_fan_speed, 100);
+    }
+
+    #[test]
+    fn test_config_patch_rejects_invalid_merged_result() {
+        let current = sample_control_config();
+        // 单独看是合法数值，但合并后最小转速高于最大转速
+        let patch = ControlConfigPatch {
+            min_fan_speed: Some(90),
+            max_fan_speed: Some(50),
+            ..Default::default()
+        };
+
+        assert!(patch.merged(&current).is_err());
+        assert!(ControlConfigPatch::default().is_empty());
+    }
+
+    #[test]
+    fn test_quiet_hours_caps_fan_speed() {
+        let schedule = QuietHoursSchedule {
+            enabled: true,
+            windows: vec![QuietWindow {
+                name: "office".to_string(),
+                start: chrono::NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+                end: chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+                weekdays: Vec::new(),
+                max_fan_percent: 40.0,
+            }],
+            emergency_temperature: 85.0,
+            updated_at: Utc::now(),
+        };
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let working_hours = date.and_hms_opt(10, 30, 0).unwrap();
+        let overnight = date.and_hms_opt(23, 0, 0).unwrap();
+
+        // 静音时段内转速受限
+        let speed = ControlService::apply_quiet_hours(80.0, 60.0, &schedule, working_hours);
+        assert_eq!(speed, 40.0);
+
+        // 超过紧急温度时不受限
+        let speed = ControlService::apply_quiet_hours(80.0, 90.0, &schedule, working_hours);
+        assert_eq!(speed, 80.0);
+
+        // 静音时段外不受限
+        let speed = ControlService::apply_quiet_hours(80.0, 60.0, &schedule, overnight);
+        assert_eq!(speed, 80.0);
+    }
+
+    #[test]
+    fn test_comfort_band_holds_speed_until_temperature_leaves_band() {
+        let mut config = ControlConfig::default();
+        config.comfort_band = 3.0;
+        let schedule = QuietHoursSchedule::default();
+        let mut pid = PidController::new(2.0, 0.1, 0.05);
+        pid.set_output_limits(0.0, 100.0);
+        let setpoint = config.target_temperature;
+        let at = chrono::Local::now().naive_local();
+
+        // 目标温度±1°C的抖动不产生任何调速
+        let commands: Vec<ControlDecision> = [0.0, 1.0, -1.0, 0.5, -0.8, 1.0, -1.0]
+            .into_iter()
+            .filter_map(|offset| {
+                ControlService::decide_outside_comfort_band(
+                    "temp_cpu", "fan_cpu", setpoint + offset, setpoint, &mut pid, &config, &schedule, at,
+                )
+            })
+            .collect();
+        assert!(commands.is_empty());
+
+        // 离开舒适区后恢复调速
+        let decision = ControlService::decide_outside_comfort_band(
+            "temp_cpu", "fan_cpu", setpoint + 4.5, setpoint, &mut pid, &config, &schedule, at,
+        )
+        .expect("温度离开舒适区后应调速");
+        assert_eq!(decision.input_temperature, setpoint + 4.5);
+        assert!(decision.proportional > 0.0);
+
+        // 未启用舒适区时每个周期都调速
+        config.comfort_band = 0.0;
+        assert!(ControlService::decide_outside_comfort_band(
+            "temp_cpu", "fan_cpu", setpoint, setpoint, &mut pid, &config, &schedule, at,
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_smoothing_filter_steadies_noisy_control_input() {
+        let mut config = ControlConfig::default();
+        config.smoothing_window = 4;
+        config.sensor_smoothing_windows.insert("temp_inlet".to_string(), 1);
+        let mut filter = ControlInputFilter::default();
+
+        // 每周期上升0.1°C的趋势叠加±1.5°C的交替噪声
+        let raw: Vec<f64> = (0..40)
+            .map(|i| 60.0 + i as f64 * 0.1 + if i % 2 == 0 { 1.5 } else { -1.5 })
+            .collect();
+        let smoothed: Vec<f64> = raw
+            .iter()
+            .map(|&temperature| filter.apply("temp_cpu", temperature, config.smoothing_window_for("temp_cpu")))
+            .collect();
+
+        // 窗口填满后，相邻周期控制输入的变化远小于原始读数
+        let jitter = |series: &[f64]| series.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<f64>();
+        assert!(jitter(&smoothed[4..]) < jitter(&raw[4..]) / 10.0);
+        // 趋势仍然保留：最后的控制输入等于最近4次读数的平均
+        assert!((smoothed[39] - 63.75).abs() < 1e-9);
+        assert!(smoothed[39] > smoothed[4]);
+
+        // 窗口为1的传感器不平滑
+        assert_eq!(filter.apply("temp_inlet", 25.3, config.smoothing_window_for("temp_inlet")), 25.3);
+        assert_eq!(filter.apply("temp_inlet", 27.9, config.smoothing_window_for("temp_inlet")), 27.9);
+    }
+
+    #[tokio::test]
+    async fn test_control_tick_records_decision() {
+        let config = ControlConfig::default();
+        let schedule = QuietHoursSchedule::default();
+        let decisions = ControlDecisionLog::new(MAX_CONTROL_DECISIONS);
+        let mut pid = PidController::new(2.0, 0.1, 0.05);
+        pid.set_output_limits(0.0, 100.0);
+
+        let temperature = config.target_temperature + 10.0;
+        let decision = ControlService::decide_fan_speed(
+            "temp_cpu",
+            "fan_cpu",
+            temperature,
+            config.target_temperature,
+            &mut pid,
+            &config,
+            &schedule,
+            chrono::Local::now().naive_local(),
+        );
+        ControlService::record_decision(&decisions, decision).await;
+
+        let decisions = decisions.recent(None).await;
+        assert_eq!(decisions.len(), 1);
+        let decision = &decisions[0];
+        assert_eq!(decision.sensor_id, "temp_cpu");
+        assert_eq!(decision.fan_id, "fan_cpu");
+        assert_eq!(decision.input_temperature, temperature);
+        assert_eq!(decision.setpoint, config.target_temperature);
+        assert_eq!(decision.proportional, 20.0);
+        assert_eq!(
+            decision.raw_output,
+            decision.proportional + decision.integral + decision.derivative
+        );
+        assert!(decision.limited_output >= config.min_fan_speed);
+        assert!(decision.limited_output <= config.max_fan_speed);
+        assert!(decision.quiet_cap.is_none());
+    }
+
+    #[test]
+    fn test_cold_inlet_lowers_target_and_fan_duty() {
+        let config = ControlConfig::default();
+        let schedule = QuietHoursSchedule::default();
+        let ambient = AmbientTargetConfig {
+            enabled: true,
+            inlet_sensor_id: "temp_inlet".to_string(),
+            delta: 25.0,
+            min_target: 50.0,
+            max_target: 75.0,
+        };
+        // 相同热负载下，CPU温度比进风温度高30°C
+        let decide = |inlet: f64| {
+            let mut pid = PidController::new(10.0, 0.0, 0.0);
+            pid.set_output_limits(0.0, 100.0);
+            let setpoint = ambient.effective_target(config.target_temperature, Some(inlet));
+            ControlService::decide_fan_speed(
+                "temp_cpu",
+                "fan_cpu",
+                inlet + 30.0,
+                setpoint,
+                &mut pid,
+                &config,
+                &schedule,
+                chrono::Local::now().naive_local(),
+            )
+        };
+
+        let cold = decide(18.0);
+        let hot = decide(35.0);
+        assert_eq!(cold.setpoint, 50.0);
+        assert_eq!(hot.setpoint, 60.0);
+        assert!(cold.setpoint < hot.setpoint);
+        assert!(cold.limited_output < hot.limited_output);
+
+        // 未启用或进风温度不可用时回退到固定目标温度
+        assert_eq!(ambient.effective_target(65.0, None), 65.0);
+        let disabled = AmbientTargetConfig {
+            enabled: false,
+            ..ambient.clone()
+        };
+        assert_eq!(disabled.effective_target(65.0, Some(18.0)), 65.0);
+    }
+
+    #[test]
+    fn test_applied_pid_gains_change_next_decision() {
+        let mut config = ControlConfig::default();
+        let schedule = QuietHoursSchedule::default();
+        let mut controllers = HashMap::new();
+        let mut pid = PidController::new(1.0, 0.0, 0.0);
+        pid.set_output_limits(0.0, 100.0);
+        controllers.insert("temp_cpu".to_string(), pid);
+
+        let temperature = config.target_temperature + 15.0;
+        let decide = |controllers: &mut HashMap<String, PidController>, config: &ControlConfig| {
+            ControlService::decide_fan_speed(
+                "temp_cpu",
+                "fan_cpu",
+                temperature,
+                config.target_temperature,
+                controllers.get_mut("temp_cpu").unwrap(),
+                config,
+                &schedule,
+                chrono::Local::now().naive_local(),
+            )
+        };
+        let before = decide(&mut controllers, &config);
+
+        let parameters: TuningParameters = serde_json::from_value(serde_json::json!({
+            "pid_parameters": { "kp": 4.0, "ki": 0.0, "kd": 0.0, "output_limits": [0.0, 100.0] },
+            "temperature_thresholds": {
+                "target_temperature": config.target_temperature,
+                "warning_temperature": 75.0,
+                "critical_temperature": 85.0,
+                "emergency_temperature": 95.0
+            },
+            "fan_curves": {},
+            "control_interval_ms": 5000
+        }))
+        .unwrap();
+        parameters.validate().unwrap();
+        ControlService::swap_parameters(&mut config, &mut controllers, &parameters);
+        let after = decide(&mut controllers, &config);
+
+        assert_eq!(config.default_pid_kp, 4.0);
+        assert_eq!(config.control_interval, 5);
+        assert_eq!(after.proportional, before.proportional * 4.0);
+        assert!(after.limited_output > before.limited_output);
+
+        // 非法参数在写入前被拒绝
+        let mut invalid = parameters.clone();
+        invalid.pid_parameters.kp = -1.0;
+        assert!(invalid.validate().is_err());
+    }
+
+    /// 按顺序记录转速、在指定风扇上写入失败的风扇接口
+    struct FailingFanDriver {
+        speeds: std::sync::Mutex<Vec<(String, f64)>>,
+        failing_fan: String,
+    }
+
+    impl FailingFanDriver {
+        fn speed_of(&self, fan_id: &str) -> f64 {
+            let speeds = self.speeds.lock().unwrap();
+            speeds.iter().find(|(id, _)| id == fan_id).unwrap().1
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FanSpeedDriver for FailingFanDriver {
+        async fn fan_ids(&self) -> AppResult<Vec<String>> {
+            Ok(self.speeds.lock().unwrap().iter().map(|(id, _)| id.clone()).collect())
+        }
+
+        async fn current_speed(&self, fan_id: &str) -> AppResult<f64> {
+            Ok(self.speed_of(fan_id))
+        }
+
+        async fn set_speed(&self, fan_id: &str, speed_percent: f64) -> AppResult<()> {
+            if fan_id == self.failing_fan {
+                return Err(AppError::ipmi_error("BMC无响应"));
+            }
+            let mut speeds = self.speeds.lock().unwrap();
+            speeds.iter_mut().find(|(id, _)| id == fan_id).unwrap().1 = speed_percent;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strategy_failure_on_third_fan_rolls_back_first_two() {
+        let driver = FailingFanDriver {
+            speeds: std::sync::Mutex::new(
+                (1..=4).map(|i| (format!("fan_{}", i), 95.0)).collect(),
+            ),
+            failing_fan: "fan_3".to_string(),
+        };
+        let mut config = ControlConfig::default();
+        ControlService::apply_conservative_strategy(&mut config);
+
+        let err = ControlService::apply_fan_speeds_with_rollback(&driver, &config)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("fan_3"), "{}", err);
+        for fan_id in ["fan_1", "fan_2", "fan_3", "fan_4"] {
+            assert_eq!(driver.speed_of(fan_id), 95.0, "{} 未回滚", fan_id);
+        }
+
+        // 故障排除后重试，所有风扇按新策略的上限下发
+        let driver = FailingFanDriver {
+            failing_fan: String::new(),
+            ..driver
+        };
+        let applied = ControlService::apply_fan_speeds_with_rollback(&driver, &config)
+            .await
+            .unwrap();
+        assert_eq!(applied.len(), 4);
+        for fan_id in ["fan_1", "fan_2", "fan_3", "fan_4"] {
+            assert_eq!(driver.speed_of(fan_id), config.max_fan_speed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restoring_snapshot_returns_fans_to_captured_speeds() {
+        let driver = Arc::new(FailingFanDriver {
+            speeds: std::sync::Mutex::new(vec![
+                ("fan_1".to_string(), 30.0),
+                ("fan_2".to_string(), 45.0),
+                ("fan_3".to_string(), 60.0),
+            ]),
+            failing_fan: String::new(),
+        });
+        let service = test_control_service().with_fan_driver(driver.clone());
+
+        let snapshot = service.snapshot_fan_speeds().await.unwrap();
+        assert_eq!(snapshot.speeds.len(), 3);
+
+        for fan_id in ["fan_1", "fan_2", "fan_3"] {
+            driver.set_speed(fan_id, 100.0).await.unwrap();
+        }
+
+        service.restore_fan_snapshot(&snapshot.id).await.unwrap();
+        assert_eq!(driver.speed_of("fan_1"), 30.0);
+        assert_eq!(driver.speed_of("fan_2"), 45.0);
+        assert_eq!(driver.speed_of("fan_3"), 60.0);
+
+        // 过期快照不能再恢复
+        let expired = test_control_service()
+            .with_fan_driver(driver.clone())
+            .with_snapshot_ttl(chrono::Duration::zero());
+        let snapshot = expired.snapshot_fan_speeds().await.unwrap();
+        let err = expired.restore_fan_snapshot(&snapshot.id).await.unwrap_err();
+        assert_eq!(err.status_code(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_per_fan_floor_holds_fan_above_commanded_speed() {
+        // 低负载下控制输出为20%
+        let driver = FailingFanDriver {
+            speeds: std::sync::Mutex::new(vec![
+                ("fan_cpu".to_string(), 20.0),
+                ("fan_psu".to_string(), 20.0),
+            ]),
+            failing_fan: String::new(),
+        };
+        let mut config = ControlConfig::default();
+        config.fan_speed_floors.insert("fan_psu".to_string(), 35.0);
+
+        ControlService::apply_fan_speeds_with_rollback(&driver, &config)
+            .await
+            .unwrap();
+        assert_eq!(driver.speed_of("fan_cpu"), 20.0);
+        assert_eq!(driver.speed_of("fan_psu"), 35.0);
+
+        // 高于最低转速的输出不受影响
+        assert_eq!(config.floored_fan_speed("fan_psu", 80.0), 80.0);
+
+        // 最低转速不能超过最大风扇转速
+        let mut invalid = sample_control_config();
+        invalid.fan_speed_floors.insert("fan_psu".to_string(), 150.0);
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_stalled_control_loop_reports_unhealthy() {
+        let now = Utc::now();
+
+        let live = ControlLoopLiveness::evaluate(Some(now - chrono::Duration::seconds(15)), 10, now);
+        assert!(live.is_healthy());
+        assert_eq!(live.tick_age_seconds, Some(15));
+
+        let stalled = ControlLoopLiveness::evaluate(Some(now - chrono::Duration::seconds(30)), 10, now);
+        assert_eq!(stalled.state, LoopLivenessState::Degraded);
+        assert!(!stalled.is_healthy());
+
+        let deadlocked = ControlLoopLiveness::evaluate(Some(now - chrono::Duration::minutes(5)), 10, now);
+        assert_eq!(deadlocked.state, LoopLivenessState::Critical);
+
+        // 从未执行过
+        let never = ControlLoopLiveness::evaluate(None, 10, now);
+        assert_eq!(never.state, LoopLivenessState::Critical);
+        assert!(never.tick_age_seconds.is_none());
+    }
+
     #[test]
     fn test_fan_sensor_mapping() {
         let fan_id = ControlService::get_fan_for_sensor("temp_cpu");
@@ -0,0 +1,223 @@
+use crate::models::SystemEvent;
+use crate::services::event_store::SystemEventStore;
+use crate::services::safe_boot::{self, SafeBootFans};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// 控制循环崩溃后重新启动前的等待时间
+pub const DEFAULT_RESPAWN_DELAY: Duration = Duration::from_secs(1);
+
+/// 控制循环崩溃时下发的安全转速
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlFailSafe {
+    /// 启动保护转速（`control.safe_boot_fan_percent`）
+    #[default]
+    SafeFloor,
+    /// 全速
+    FullSpeed,
+}
+
+/// 控制循环监督器
+///
+/// 控制循环任务panic时风扇会停留在最后下发的转速。监督器在任务panic后立即下发安全转速、
+/// 交还BMC自动模式并记录严重系统事件，然后重新启动控制循环
+pub struct ControlLoopSupervisor {
+    /// 风扇操作
+    fans: Arc<dyn SafeBootFans>,
+    /// 系统事件存储
+    events: Arc<dyn SystemEventStore>,
+    /// 崩溃时的安全转速
+    fail_safe: ControlFailSafe,
+    /// 启动保护转速百分比
+    safe_floor_percent: u8,
+    /// 重新启动前的等待时间
+    respawn_delay: Duration,
+}
+
+impl ControlLoopSupervisor {
+    /// 创建控制循环监督器
+    ///
+    /// # 参数
+    /// * `fans` - 风扇操作
+    /// * `events` - 系统事件存储
+    /// * `fail_safe` - 崩溃时的安全转速
+    /// * `safe_floor_percent` - 启动保护转速百分比
+    pub fn new(
+        fans: Arc<dyn SafeBootFans>,
+        events: Arc<dyn SystemEventStore>,
+        fail_safe: ControlFailSafe,
+        safe_floor_percent: u8,
+    ) -> Self {
+        Self {
+            fans,
+            events,
+            fail_safe,
+            safe_floor_percent,
+            respawn_delay: DEFAULT_RESPAWN_DELAY,
+        }
+    }
+
+    /// 设置重新启动前的等待时间
+    ///
+    /// # 参数
+    /// * `delay` - 等待时间
+    pub fn with_respawn_delay(mut self, delay: Duration) -> Self {
+        self.respawn_delay = delay;
+        self
+    }
+
+    /// 崩溃时下发的转速百分比
+    pub fn fail_safe_percent(&self) -> u8 {
+        match self.fail_safe {
+            ControlFailSafe::SafeFloor => self.safe_floor_percent,
+            ControlFailSafe::FullSpeed => 100,
+        }
+    }
+
+    /// 在监督下运行控制循环
+    ///
+    /// 每次panic后由 `spawn_loop` 重新创建控制循环；控制循环正常结束或被取消时监督随之结束
+    ///
+    /// # 参数
+    /// * `spawn_loop` - 创建控制循环
+    pub fn supervise<F, Fut>(self: Arc<Self>, spawn_loop: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(async move {
+            loop {
+                // 监督任务被取消时一并取消当前的控制循环
+                let mut task = tokio::spawn(spawn_loop());
+                let _abort = AbortOnDrop(task.abort_handle());
+                match (&mut task).await {
+                    Err(e) if e.is_panic() => {
+                        self.enter_safe_state(&panic_message(e.into_panic())).await;
+                        tokio::time::sleep(self.respawn_delay).await;
+                        info!("Respawning control loop after panic");
+                    }
+                    _ => return,
+                }
+            }
+        })
+    }
+
+    /// 下发安全转速、交还BMC自动模式并记录严重系统事件
+    ///
+    /// # 参数
+    /// * `reason` - panic信息
+    async fn enter_safe_state(&self, reason: &str) {
+        let percent = self.fail_safe_percent();
+        error!("Control loop panicked: {}; commanding fans to {}%", reason, percent);
+        safe_boot::apply_safe_floor(self.fans.as_ref(), percent);
+
+        let event = SystemEvent {
+            id: uuid::Uuid::new_v4(),
+            event_type: "control_loop_panic".to_string(),
+            event_level: "critical".to_string(),
+            title: "控制循环崩溃，已进入安全状态".to_string(),
+            description: format!(
+                "控制循环panic: {}；已将风扇设为 {}% 并交还BMC自动模式，控制循环将重新启动",
+                reason, percent
+            ),
+            source: "control_service".to_string(),
+            metadata: Some(serde_json::json!({
+                "fail_safe": self.fail_safe,
+                "fan_percent": percent,
+            })),
+            created_at: Utc::now(),
+        };
+        if let Err(e) = self.events.record(event).await {
+            error!("Failed to record control loop panic event: {}", e);
+        }
+    }
+}
+
+/// 离开作用域时取消任务
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::event_store::{InMemorySystemEventStore, SystemEventQuery};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingFans {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl SafeBootFans for RecordingFans {
+        fn set_all_fan_speeds(&self, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+            self.events.lock().unwrap().push(format!("speed {}", speed_percent));
+            Ok(())
+        }
+
+        fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+            self.events.lock().unwrap().push("automatic".to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panicking_control_tick_commands_safe_state() {
+        let fans = Arc::new(RecordingFans::default());
+        let events = Arc::new(InMemorySystemEventStore::new());
+        let supervisor = Arc::new(
+            ControlLoopSupervisor::new(fans.clone(), events.clone(), ControlFailSafe::FullSpeed, 40)
+                .with_respawn_delay(Duration::ZERO),
+        );
+
+        // 第一次控制周期panic，重新启动后的循环正常结束
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let loop_ticks = ticks.clone();
+        supervisor
+            .supervise(move || {
+                let ticks = loop_ticks.clone();
+                async move {
+                    if ticks.fetch_add(1, Ordering::SeqCst) == 0 {
+                        panic!("PID output is NaN");
+                    }
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(ticks.load(Ordering::SeqCst), 2);
+        assert_eq!(*fans.events.lock().unwrap(), vec!["speed 100", "automatic"]);
+
+        let (recorded, total) = events
+            .query(&SystemEventQuery {
+                level: Some("critical".to_string()),
+                page: 1,
+                page_size: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(recorded[0].event_type, "control_loop_panic");
+        assert!(recorded[0].description.contains("PID output is NaN"));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::models::error::AppResult;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// 控制写入的重试与死信配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlWriteRetryConfig {
+    /// 单次下发的最多尝试次数（含首次）
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// 两次尝试之间的等待时间（毫秒）
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+    /// 同一风扇连续多少次下发失败后发出告警
+    #[serde(default = "default_alert_after_failures")]
+    pub alert_after_failures: u32,
+}
+
+impl Default for ControlWriteRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            retry_delay_ms: default_retry_delay_ms(),
+            alert_after_failures: default_alert_after_failures(),
+        }
+    }
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_retry_delay_ms() -> u64 {
+    200
+}
+
+fn default_alert_after_failures() -> u32 {
+    3
+}
+
+/// 重试耗尽后仍未下发的控制动作
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailedControlWrite {
+    /// 风扇ID
+    pub fan_id: String,
+    /// 应下发的转速百分比
+    pub speed_percent: f64,
+    /// 控制动作原因
+    pub reason: String,
+    /// 连续下发失败次数（每次重试耗尽计一次）
+    pub consecutive_failures: u32,
+    /// 最后一次失败的错误
+    pub last_error: String,
+    /// 首次失败时间
+    pub first_failed_at: DateTime<Utc>,
+    /// 最后一次失败时间
+    pub last_failed_at: DateTime<Utc>,
+}
+
+/// 控制写入连续失败时的告警出口
+#[async_trait]
+pub trait ControlWriteAlertSink: Send + Sync {
+    /// 发出控制写入失败告警
+    ///
+    /// # 参数
+    /// * `failed` - 死信中的控制动作
+    async fn raise_control_write_alert(&self, failed: &FailedControlWrite) -> AppResult<()>;
+}
+
+/// 控制写入重试与死信
+///
+/// 下发失败时按配置重试，重试耗尽后按风扇记入死信，只保留最新的目标转速；
+/// 下一个控制周期开始时先补发死信中的动作，任一次下发成功即移出死信。
+/// 连续失败次数达到阈值时发出一次告警
+#[derive(Clone, Default)]
+pub struct ControlWriteRetry {
+    config: ControlWriteRetryConfig,
+    dead_letters: Arc<RwLock<BTreeMap<String, FailedControlWrite>>>,
+    alert_sink: Option<Arc<dyn ControlWriteAlertSink>>,
+}
+
+impl ControlWriteRetry {
+    /// 创建控制写入重试
+    ///
+    /// # 参数
+    /// * `config` - 重试与告警配置
+    pub fn new(config: ControlWriteRetryConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// 设置连续失败时的告警出口
+    ///
+    /// # 参数
+    /// * `sink` - 告警出口，如 `ControlAlertSink`
+    pub fn with_alert_sink(mut self, sink: Arc<dyn ControlWriteAlertSink>) -> Self {
+        self.alert_sink = Some(sink);
+        self
+    }
+
+    /// 当前死信中的控制动作（按风扇ID排序）
+    pub fn dead_letters(&self) -> Vec<FailedControlWrite> {
+        self.dead_letters
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// 下发控制动作，失败时重试，重试耗尽后记入死信并返回None
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `speed_percent` - 转速百分比
+    /// * `reason` - 控制动作原因
+    /// * `write` - 执行一次下发
+    pub async fn write<T, F, Fut>(&self, fan_id: &str, speed_percent: f64, reason: &str, mut write: F) -> Option<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = AppResult<T>>,
+    {
+        let max_attempts = self.config.max_attempts.max(1);
+        let mut last_error = String::new();
+        for attempt in 1..=max_attempts {
+            match write().await {
+                Ok(value) => {
+                    if self.remove(fan_id).is_some() {
+                        info!("风扇 {} 转速下发恢复，移出死信", fan_id);
+                    }
+                    return Some(value);
+                }
+                Err(e) => {
+                    warn!("风扇 {} 转速下发失败（第{}/{}次）: {}", fan_id, attempt, max_attempts, e);
+                    last_error = e.to_string();
+                    if attempt < max_attempts {
+                        tokio::time::sleep(Duration::from_millis(self.config.retry_delay_ms)).await;
+                    }
+                }
+            }
+        }
+
+        self.dead_letter(fan_id, speed_percent, reason, last_error).await;
+        None
+    }
+
+    /// 补发死信中的控制动作，返回补发成功的数量
+    ///
+    /// # 参数
+    /// * `write` - 按风扇ID和转速百分比执行一次下发
+    pub async fn reconcile<F, Fut>(&self, mut write: F) -> usize
+    where
+        F: FnMut(String, f64) -> Fut,
+        Fut: Future<Output = AppResult<()>>,
+    {
+        let mut reconciled = 0;
+        for pending in self.dead_letters() {
+            let result = self
+                .write(&pending.fan_id, pending.speed_percent, &pending.reason, || {
+                    write(pending.fan_id.clone(), pending.speed_percent)
+                })
+                .await;
+            if result.is_some() {
+                reconciled += 1;
+            }
+        }
+        reconciled
+    }
+
+    fn remove(&self, fan_id: &str) -> Option<FailedControlWrite> {
+        self.dead_letters.write().unwrap_or_else(|e| e.into_inner()).remove(fan_id)
+    }
+
+    /// 记入死信，连续失败次数恰好达到阈值时发出告警
+    async fn dead_letter(&self, fan_id: &str, speed_percent: f64, reason: &str, last_error: String) {
+        let now = Utc::now();
+        let failed = {
+            let mut dead_letters = self.dead_letters.write().unwrap_or_else(|e| e.into_inner());
+            let entry = dead_letters.entry(fan_id.to_string()).or_insert_with(|| FailedControlWrite {
+                fan_id: fan_id.to_string(),
+                speed_percent,
+                reason: reason.to_string(),
+                consecutive_failures: 0,
+                last_error: String::new(),
+                first_failed_at: now,
+                last_failed_at: now,
+            });
+            entry.speed_percent = speed_percent;
+            entry.reason = reason.to_string();
+            entry.consecutive_failures += 1;
+            entry.last_error = last_error;
+            entry.last_failed_at = now;
+            entry.clone()
+        };
+        error!(
+            "风扇 {} 转速 {:.1}% 重试{}次仍失败，记入死信（连续失败{}次）: {}",
+            fan_id, speed_percent, self.config.max_attempts, failed.consecutive_failures, failed.last_error
+        );
+
+        if failed.consecutive_failures == self.config.alert_after_failures.max(1) {
+            if let Some(ref sink) = self.alert_sink {
+                if let Err(e) = sink.raise_control_write_alert(&failed).await {
+                    error!("发出风扇 {} 控制写入失败告警失败: {}", fan_id, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::error::AppError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        alerts: Mutex<Vec<FailedControlWrite>>,
+    }
+
+    #[async_trait]
+    impl ControlWriteAlertSink for RecordingSink {
+        async fn raise_control_write_alert(&self, failed: &FailedControlWrite) -> AppResult<()> {
+            self.alerts.lock().unwrap().push(failed.clone());
+            Ok(())
+        }
+    }
+
+    fn retry(sink: Arc<RecordingSink>) -> ControlWriteRetry {
+        ControlWriteRetry::new(ControlWriteRetryConfig {
+            max_attempts: 3,
+            retry_delay_ms: 0,
+            alert_after_failures: 2,
+        })
+        .with_alert_sink(sink)
+    }
+
+    #[tokio::test]
+    async fn test_write_succeeds_after_two_failed_attempts() {
+        let sink = Arc::new(RecordingSink::default());
+        let retry = retry(sink.clone());
+        let calls = AtomicU32::new(0);
+
+        let result = retry
+            .write("FAN1", 45.0, "温度控制", || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(AppError::ipmi_error("BMC busy")),
+                    _ => Ok("applied"),
+                }
+            })
+            .await;
+
+        assert_eq!(result, Some("applied"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(retry.dead_letters().is_empty());
+        assert!(sink.alerts.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_persistent_failure_is_dead_lettered_and_alerted() {
+        let sink = Arc::new(RecordingSink::default());
+        let retry = retry(sink.clone());
+        let calls = AtomicU32::new(0);
+        let failing = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(AppError::ipmi_error("BMC unreachable"))
+        };
+
+        assert_eq!(retry.write("FAN2", 60.0, "温度控制", failing).await, None);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        let dead_letters = retry.dead_letters();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].fan_id, "FAN2");
+        assert_eq!(dead_letters[0].speed_percent, 60.0);
+        assert_eq!(dead_letters[0].consecutive_failures, 1);
+        assert!(sink.alerts.lock().unwrap().is_empty());
+
+        // 下一周期补发仍失败，连续失败达到阈值后告警
+        let reconciled = retry
+            .reconcile(|_, _| async { Err(AppError::ipmi_error("BMC unreachable")) })
+            .await;
+        assert_eq!(reconciled, 0);
+        assert_eq!(retry.dead_letters()[0].consecutive_failures, 2);
+        let alerts = sink.alerts.lock().unwrap().clone();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].fan_id, "FAN2");
+
+        // BMC恢复后补发成功，移出死信
+        let written = Mutex::new(Vec::new());
+        let reconciled = retry
+            .reconcile(|fan_id, speed| {
+                written.lock().unwrap().push((fan_id, speed));
+                async { Ok(()) }
+            })
+            .await;
+        assert_eq!(reconciled, 1);
+        assert_eq!(*written.lock().unwrap(), vec![("FAN2".to_string(), 60.0)]);
+        assert!(retry.dead_letters().is_empty());
+    }
+}
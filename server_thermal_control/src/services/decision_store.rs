@@ -0,0 +1,434 @@
+use crate::models::control::{ControlActionType, ControlDecision};
+use crate::models::error::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, warn};
+
+/// 单次回放最多返回的决策数
+pub const MAX_REPLAY_DECISIONS: usize = 10_000;
+
+/// 环形缓冲默认保留的最近决策数
+pub const DEFAULT_RECENT_DECISIONS: usize = 500;
+
+/// 控制历史查询条件
+#[derive(Debug, Clone, Default)]
+pub struct ControlHistoryQuery {
+    /// 动作类型，为空表示不过滤，多个取并集
+    pub action_types: Vec<ControlActionType>,
+    /// 目标风扇ID
+    pub target_id: Option<String>,
+    /// 开始时间（含）
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间（不含）
+    pub end_time: Option<DateTime<Utc>>,
+    /// 页码（从1开始）
+    pub page: i32,
+    /// 每页大小
+    pub page_size: i32,
+}
+
+impl ControlHistoryQuery {
+    fn offset(&self) -> usize {
+        ((self.page.max(1) - 1) * self.page_size.max(0)) as usize
+    }
+
+    fn matches(&self, decision: &ControlDecision) -> bool {
+        (self.action_types.is_empty() || self.action_types.contains(&decision.action_type))
+            && self.target_id.as_ref().is_none_or(|target| &decision.fan_id == target)
+            && self.start_time.is_none_or(|start| decision.timestamp >= start)
+            && self.end_time.is_none_or(|end| decision.timestamp < end)
+    }
+
+    /// 对按时间正序排列的决策过滤并分页，最近的在前
+    fn paginate<'a>(&self, decisions: impl DoubleEndedIterator<Item = &'a ControlDecision>) -> (Vec<ControlDecision>, i64) {
+        let matched: Vec<&ControlDecision> = decisions.rev().filter(|d| self.matches(d)).collect();
+        let total = matched.len() as i64;
+        let page = matched
+            .into_iter()
+            .skip(self.offset())
+            .take(self.page_size.max(0) as usize)
+            .cloned()
+            .collect();
+        (page, total)
+    }
+}
+
+/// 控制决策持久化存储
+///
+/// 过期数据由数据保留服务按 `control_decisions` 表清理
+#[async_trait]
+pub trait ControlDecisionStore: Send + Sync {
+    /// 保存一条决策
+    ///
+    /// # 参数
+    /// * `decision` - 控制决策
+    async fn save(&self, decision: &ControlDecision) -> AppResult<()>;
+
+    /// 读取时间窗口 `[start, end)` 内的决策，按时间正序
+    ///
+    /// # 参数
+    /// * `start` - 开始时间
+    /// * `end` - 结束时间
+    /// * `limit` - 最多返回的条数
+    async fn range(&self, start: DateTime<Utc>, end: DateTime<Utc>, limit: usize) -> AppResult<Vec<ControlDecision>>;
+
+    /// 按条件分页查询决策，最近的在前，同时返回符合条件的总数
+    ///
+    /// # 参数
+    /// * `query` - 查询条件
+    async fn query(&self, query: &ControlHistoryQuery) -> AppResult<(Vec<ControlDecision>, i64)>;
+}
+
+/// 进程内决策存储
+#[derive(Default)]
+pub struct InMemoryControlDecisionStore {
+    decisions: RwLock<Vec<ControlDecision>>,
+}
+
+impl InMemoryControlDecisionStore {
+    /// 创建进程内决策存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ControlDecisionStore for InMemoryControlDecisionStore {
+    async fn save(&self, decision: &ControlDecision) -> AppResult<()> {
+        self.decisions.write().await.push(decision.clone());
+        Ok(())
+    }
+
+    async fn range(&self, start: DateTime<Utc>, end: DateTime<Utc>, limit: usize) -> AppResult<Vec<ControlDecision>> {
+        let mut decisions: Vec<ControlDecision> = self
+            .decisions
+            .read()
+            .await
+            .iter()
+            .filter(|decision| decision.timestamp >= start && decision.timestamp < end)
+            .cloned()
+            .collect();
+        decisions.sort_by_key(|decision| decision.timestamp);
+        decisions.truncate(limit);
+        Ok(decisions)
+    }
+
+    async fn query(&self, query: &ControlHistoryQuery) -> AppResult<(Vec<ControlDecision>, i64)> {
+        let mut decisions = self.decisions.read().await.clone();
+        decisions.sort_by_key(|decision| decision.timestamp);
+        Ok(query.paginate(decisions.iter()))
+    }
+}
+
+/// PostgreSQL决策存储
+///
+/// 写入 `scripts/init.sql` 中定义的 `control_decisions` 表
+pub struct PgControlDecisionStore {
+    pool: PgPool,
+}
+
+impl PgControlDecisionStore {
+    /// 创建数据库决策存储
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ControlDecisionStore for PgControlDecisionStore {
+    #[instrument(level = "debug", name = "db.control_decisions.save", skip_all)]
+    async fn save(&self, decision: &ControlDecision) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO control_decisions (
+                timestamp, sensor_id, fan_id, input_temperature, setpoint,
+                proportional, integral, derivative, raw_output, limited_output,
+                quiet_cap, readback_percent, action_type
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            "#,
+        )
+        .bind(decision.timestamp)
+        .bind(&decision.sensor_id)
+        .bind(&decision.fan_id)
+        .bind(decision.input_temperature)
+        .bind(decision.setpoint)
+        .bind(decision.proportional)
+        .bind(decision.integral)
+        .bind(decision.derivative)
+        .bind(decision.raw_output)
+        .bind(decision.limited_output)
+        .bind(decision.quiet_cap)
+        .bind(decision.readback_percent)
+        .bind(decision.action_type.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "db.control_decisions.range", skip_all)]
+    async fn range(&self, start: DateTime<Utc>, end: DateTime<Utc>, limit: usize) -> AppResult<Vec<ControlDecision>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT timestamp, sensor_id, fan_id, input_temperature, setpoint,
+                   proportional, integral, derivative, raw_output, limited_output,
+                   quiet_cap, readback_percent, action_type
+            FROM control_decisions
+            WHERE timestamp >= $1 AND timestamp < $2
+            ORDER BY timestamp ASC, id ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(decision_from_row).collect()
+    }
+
+    #[instrument(level = "debug", name = "db.control_decisions.query", skip_all)]
+    async fn query(&self, query: &ControlHistoryQuery) -> AppResult<(Vec<ControlDecision>, i64)> {
+        let action_types: Vec<&str> = query.action_types.iter().map(ControlActionType::as_str).collect();
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM control_decisions
+            WHERE (cardinality($1::text[]) = 0 OR action_type = ANY($1))
+              AND ($2::text IS NULL OR fan_id = $2)
+              AND ($3::timestamptz IS NULL OR timestamp >= $3)
+              AND ($4::timestamptz IS NULL OR timestamp < $4)
+            "#,
+        )
+        .bind(&action_types)
+        .bind(&query.target_id)
+        .bind(query.start_time)
+        .bind(query.end_time)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT timestamp, sensor_id, fan_id, input_temperature, setpoint,
+                   proportional, integral, derivative, raw_output, limited_output,
+                   quiet_cap, readback_percent, action_type
+            FROM control_decisions
+            WHERE (cardinality($1::text[]) = 0 OR action_type = ANY($1))
+              AND ($2::text IS NULL OR fan_id = $2)
+              AND ($3::timestamptz IS NULL OR timestamp >= $3)
+              AND ($4::timestamptz IS NULL OR timestamp < $4)
+            ORDER BY timestamp DESC, id DESC
+            LIMIT $5 OFFSET $6
+            "#,
+        )
+        .bind(&action_types)
+        .bind(&query.target_id)
+        .bind(query.start_time)
+        .bind(query.end_time)
+        .bind(query.page_size.max(0) as i64)
+        .bind(query.offset() as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let decisions = rows.into_iter().map(decision_from_row).collect::<AppResult<Vec<_>>>()?;
+        Ok((decisions, total))
+    }
+}
+
+/// 将 `control_decisions` 表的一行转换为控制决策
+fn decision_from_row(row: sqlx::postgres::PgRow) -> AppResult<ControlDecision> {
+    Ok(ControlDecision {
+        timestamp: row.try_get("timestamp")?,
+        sensor_id: row.try_get("sensor_id")?,
+        fan_id: row.try_get("fan_id")?,
+        input_temperature: row.try_get("input_temperature")?,
+        setpoint: row.try_get("setpoint")?,
+        proportional: row.try_get("proportional")?,
+        integral: row.try_get("integral")?,
+        derivative: row.try_get("derivative")?,
+        raw_output: row.try_get("raw_output")?,
+        limited_output: row.try_get("limited_output")?,
+        quiet_cap: row.try_get("quiet_cap")?,
+        readback_percent: row.try_get("readback_percent")?,
+        action_type: row.try_get::<String, _>("action_type")?.parse()?,
+    })
+}
+
+/// 控制决策记录
+///
+/// 最近的决策保存在定长环形缓冲中供实时查看；配置了持久化存储时同时写入存储，
+/// 供事后按时间窗口回放
+#[derive(Clone)]
+pub struct ControlDecisionLog {
+    recent: Arc<RwLock<VecDeque<ControlDecision>>>,
+    capacity: usize,
+    store: Option<Arc<dyn ControlDecisionStore>>,
+}
+
+impl ControlDecisionLog {
+    /// 创建决策记录
+    ///
+    /// # 参数
+    /// * `capacity` - 环形缓冲容量
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            recent: Arc::new(RwLock::new(VecDeque::with_capacity(capacity))),
+            capacity: capacity.max(1),
+            store: None,
+        }
+    }
+
+    /// 设置持久化存储
+    ///
+    /// # 参数
+    /// * `store` - 决策存储，如 `PgControlDecisionStore`
+    pub fn with_store(mut self, store: Arc<dyn ControlDecisionStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// 记录一条决策，同时输出 `control_decision` 目标的结构化日志；持久化失败只记录警告，不影响控制循环
+    ///
+    /// # 参数
+    /// * `decision` - 控制决策
+    pub async fn record(&self, decision: ControlDecision) {
+        debug!(
+            target: "control_decision",
+            fan_id = %decision.fan_id,
+            sensor_id = %decision.sensor_id,
+            action_type = decision.action_type.as_str(),
+            input_temperature = decision.input_temperature,
+            setpoint = decision.setpoint,
+            proportional = decision.proportional,
+            integral = decision.integral,
+            derivative = decision.derivative,
+            raw_output = decision.raw_output,
+            limited_output = decision.limited_output,
+            quiet_cap = ?decision.quiet_cap,
+            "风扇 {} 转速 {:.1}%",
+            decision.fan_id,
+            decision.limited_output
+        );
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save(&decision).await {
+                warn!("持久化控制决策失败: {}", e);
+            }
+        }
+
+        let mut recent = self.recent.write().await;
+        if recent.len() >= self.capacity {
+            recent.pop_front();
+        }
+        recent.push_back(decision);
+    }
+
+    /// 最近的决策，按时间倒序
+    ///
+    /// # 参数
+    /// * `limit` - 返回记录数限制，为空时返回全部
+    pub async fn recent(&self, limit: Option<usize>) -> Vec<ControlDecision> {
+        let recent = self.recent.read().await;
+        recent.iter().rev().take(limit.unwrap_or(self.capacity)).cloned().collect()
+    }
+
+    /// 按条件分页查询控制历史，最近的在前，同时返回符合条件的总数
+    ///
+    /// 未配置持久化存储时只能查询环形缓冲中仍保留的决策
+    ///
+    /// # 参数
+    /// * `query` - 查询条件
+    pub async fn history(&self, query: &ControlHistoryQuery) -> AppResult<(Vec<ControlDecision>, i64)> {
+        if query.page < 1 || !(1..=1000).contains(&query.page_size) {
+            return Err(AppError::validation_error("page_size", "页码必须从1开始，每页大小必须在1-1000之间"));
+        }
+
+        if let Some(store) = &self.store {
+            return store.query(query).await;
+        }
+
+        Ok(query.paginate(self.recent.read().await.iter()))
+    }
+
+    /// 回放时间窗口 `[start, end)` 内的决策，按时间正序
+    ///
+    /// 未配置持久化存储时只能回放环形缓冲中仍保留的决策
+    ///
+    /// # 参数
+    /// * `start` - 开始时间
+    /// * `end` - 结束时间
+    pub async fn replay(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<ControlDecision>> {
+        if start >= end {
+            return Err(AppError::validation_error("start", "开始时间必须早于结束时间"));
+        }
+
+        if let Some(store) = &self.store {
+            return store.range(start, end, MAX_REPLAY_DECISIONS).await;
+        }
+
+        let recent = self.recent.read().await;
+        Ok(recent
+            .iter()
+            .filter(|decision| decision.timestamp >= start && decision.timestamp < end)
+            .take(MAX_REPLAY_DECISIONS)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn decision(at: DateTime<Utc>, temperature: f64, output: f64, readback: Option<f64>) -> ControlDecision {
+        ControlDecision {
+            timestamp: at,
+            sensor_id: "CPU1_TEMP".to_string(),
+            fan_id: "FAN1".to_string(),
+            input_temperature: temperature,
+            setpoint: 65.0,
+            proportional: temperature - 65.0,
+            integral: 1.5,
+            derivative: 0.2,
+            raw_output: output + 3.0,
+            limited_output: output,
+            quiet_cap: None,
+            readback_percent: readback,
+            action_type: ControlActionType::AutomaticControl,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decisions_in_window_replayed_in_order_with_inputs_and_outputs() {
+        let store = Arc::new(InMemoryControlDecisionStore::new());
+        // 环形缓冲只保留2条，回放仍能从存储中取回整个窗口
+        let log = ControlDecisionLog::new(2).with_store(store);
+        let start = Utc::now();
+
+        log.record(decision(start - Duration::seconds(30), 60.0, 35.0, Some(35.0))).await;
+        log.record(decision(start, 70.0, 55.0, Some(54.0))).await;
+        log.record(decision(start + Duration::seconds(10), 78.0, 80.0, Some(79.0))).await;
+        log.record(decision(start + Duration::seconds(20), 74.0, 65.0, None)).await;
+        log.record(decision(start + Duration::seconds(60), 68.0, 45.0, Some(45.0))).await;
+
+        let replay = log.replay(start, start + Duration::seconds(60)).await.unwrap();
+        let timeline: Vec<(f64, f64, Option<f64>)> = replay
+            .iter()
+            .map(|d| (d.input_temperature, d.limited_output, d.readback_percent))
+            .collect();
+        assert_eq!(timeline, vec![(70.0, 55.0, Some(54.0)), (78.0, 80.0, Some(79.0)), (74.0, 65.0, None)]);
+        assert_eq!(replay[1].proportional, 13.0);
+        assert_eq!(replay[1].raw_output, 83.0);
+
+        assert_eq!(log.recent(None).await.len(), 2);
+        assert_eq!(log.replay(start, start).await.unwrap_err().status_code(), 400);
+    }
+}
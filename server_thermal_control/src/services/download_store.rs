@@ -0,0 +1,184 @@
+use crate::config::AppConfig;
+use crate::models::error::{AppError, AppResult};
+use crate::utils::crypto::{CryptoUtils, SecurityUtils};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// 下载路由前缀，令牌拼接在其后
+pub const DOWNLOAD_ROUTE: &str = "/api/v1/downloads";
+
+/// 已写入导出目录的文件及其下载链接
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedDownload {
+    /// 下载时使用的文件名
+    pub filename: String,
+    pub size_bytes: u64,
+    /// 带签名的下载地址，过期前可匿名访问
+    pub download_url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 导出文件存储
+///
+/// 文件写入导出目录，下载令牌为 `文件名.过期时间戳.签名`，签名为对前两段的HMAC-SHA256，
+/// 文件名和过期时间都受签名保护，篡改任一部分都会导致校验失败
+#[derive(Clone)]
+pub struct DownloadStore {
+    dir: PathBuf,
+    key: Vec<u8>,
+    ttl: Duration,
+}
+
+impl DownloadStore {
+    /// 创建导出文件存储
+    ///
+    /// # 参数
+    /// * `dir` - 导出目录
+    /// * `key` - 签名密钥
+    /// * `ttl_secs` - 下载链接有效期（秒）
+    pub fn new(dir: impl Into<PathBuf>, key: impl Into<Vec<u8>>, ttl_secs: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            key: key.into(),
+            ttl: Duration::seconds(ttl_secs as i64),
+        }
+    }
+
+    /// 按 `downloads` 配置创建，签名密钥使用 `security.jwt_secret`
+    ///
+    /// # 参数
+    /// * `config` - 应用配置
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new(
+            &config.downloads.dir,
+            config.security.jwt_secret.as_bytes(),
+            config.downloads.url_ttl_secs,
+        )
+    }
+
+    /// 写入导出文件并生成下载链接
+    ///
+    /// # 参数
+    /// * `filename` - 下载时使用的文件名，如 `alerts_20250925.csv`
+    /// * `contents` - 文件内容
+    pub async fn store(&self, filename: &str, contents: &[u8]) -> AppResult<SignedDownload> {
+        let filename = sanitize_filename(filename);
+        let stored_name = format!("{}-{}", Uuid::new_v4().simple(), filename);
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| AppError::file_system_error(format!("创建导出目录失败: {}", e)))?;
+        tokio::fs::write(self.dir.join(&stored_name), contents)
+            .await
+            .map_err(|e| AppError::file_system_error(format!("写入导出文件失败: {}", e)))?;
+
+        let expires_at = Utc::now() + self.ttl;
+        Ok(SignedDownload {
+            filename,
+            size_bytes: contents.len() as u64,
+            download_url: format!("{}/{}", DOWNLOAD_ROUTE, self.sign(&stored_name, expires_at.timestamp())),
+            expires_at,
+        })
+    }
+
+    /// 生成下载令牌
+    ///
+    /// # 参数
+    /// * `stored_name` - 导出目录中的文件名
+    /// * `expires_at` - 过期时间戳（秒）
+    pub fn sign(&self, stored_name: &str, expires_at: i64) -> String {
+        let payload = format!("{}.{}", CryptoUtils::base64_url_encode(stored_name.as_bytes()), expires_at);
+        let signature = SecurityUtils::hmac_sha256(&self.key, payload.as_bytes());
+        format!("{}.{}", payload, CryptoUtils::base64_url_encode(&signature))
+    }
+
+    /// 校验下载令牌并返回文件路径，签名无效或已过期时返回403
+    ///
+    /// # 参数
+    /// * `token` - 下载令牌
+    /// * `now` - 当前时间
+    pub fn resolve(&self, token: &str, now: DateTime<Utc>) -> AppResult<PathBuf> {
+        let forbidden = |message: &str| AppError::AuthorizationError {
+            message: message.to_string(),
+        };
+
+        let (payload, signature) = token.rsplit_once('.').ok_or_else(|| forbidden("下载链接无效"))?;
+        let signature = CryptoUtils::base64_url_decode(signature).map_err(|_| forbidden("下载链接无效"))?;
+        if !SecurityUtils::verify_hmac_sha256(&self.key, payload.as_bytes(), &signature) {
+            return Err(forbidden("下载链接签名无效"));
+        }
+
+        let (encoded_name, expires_at) = payload.split_once('.').ok_or_else(|| forbidden("下载链接无效"))?;
+        let expires_at = expires_at
+            .parse::<i64>()
+            .ok()
+            .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+            .ok_or_else(|| forbidden("下载链接无效"))?;
+        if now > expires_at {
+            return Err(forbidden("下载链接已过期"));
+        }
+
+        let stored_name = CryptoUtils::base64_url_decode(encoded_name)
+            .ok()
+            .and_then(|name| String::from_utf8(name).ok())
+            .filter(|name| sanitize_filename(name) == *name)
+            .ok_or_else(|| forbidden("下载链接无效"))?;
+        Ok(self.dir.join(stored_name))
+    }
+}
+
+/// 去掉文件名中的路径部分和不安全字符
+fn sanitize_filename(filename: &str) -> String {
+    let name: String = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    let name = name.trim_start_matches('.');
+    if name.is_empty() {
+        "export".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_valid_token_resolves_and_expired_or_tampered_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DownloadStore::new(dir.path(), b"secret".to_vec(), 60);
+
+        let download = store.store("../alerts 2025.csv", b"id,severity\n1,critical\n").await.unwrap();
+        assert_eq!(download.filename, "alerts_2025.csv");
+        assert_eq!(download.size_bytes, 23);
+        let token = download.download_url.strip_prefix("/api/v1/downloads/").unwrap();
+
+        let path = store.resolve(token, Utc::now()).unwrap();
+        assert!(path.starts_with(dir.path()));
+        assert_eq!(std::fs::read(&path).unwrap(), b"id,severity\n1,critical\n");
+
+        // 过期
+        let expired = store.resolve(token, download.expires_at + Duration::seconds(1)).unwrap_err();
+        assert_eq!(expired.status_code(), 403);
+
+        // 篡改过期时间或文件名
+        let (payload, signature) = token.rsplit_once('.').unwrap();
+        let (name, _) = payload.split_once('.').unwrap();
+        let extended = format!("{}.{}.{}", name, i64::MAX / 2, signature);
+        assert_eq!(store.resolve(&extended, Utc::now()).unwrap_err().status_code(), 403);
+        let other = CryptoUtils::base64_url_encode(b"../../etc/passwd");
+        let renamed = format!("{}.{}.{}", other, download.expires_at.timestamp(), signature);
+        assert_eq!(store.resolve(&renamed, Utc::now()).unwrap_err().status_code(), 403);
+
+        // 其他密钥签发的令牌
+        let foreign = DownloadStore::new(dir.path(), b"other".to_vec(), 60);
+        assert_eq!(foreign.resolve(token, Utc::now()).unwrap_err().status_code(), 403);
+    }
+}
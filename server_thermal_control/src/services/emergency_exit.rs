@@ -0,0 +1,173 @@
+use crate::models::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 退出紧急冷却的条件
+///
+/// 最高温度需低于 `critical_temperature - hysteresis` 并持续 `confirmation_secs` 秒后才允许退出，
+/// 避免温度仍在临界附近时退出又立即重新触发
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyExitConfig {
+    /// 临界温度（°C）
+    #[serde(default = "default_critical_temperature")]
+    pub critical_temperature: f64,
+    /// 退出回差（°C）
+    #[serde(default = "default_hysteresis")]
+    pub hysteresis: f64,
+    /// 温度低于退出阈值需持续的时间（秒）
+    #[serde(default = "default_confirmation_secs")]
+    pub confirmation_secs: u64,
+}
+
+impl Default for EmergencyExitConfig {
+    fn default() -> Self {
+        Self {
+            critical_temperature: default_critical_temperature(),
+            hysteresis: default_hysteresis(),
+            confirmation_secs: default_confirmation_secs(),
+        }
+    }
+}
+
+fn default_critical_temperature() -> f64 {
+    85.0
+}
+
+fn default_hysteresis() -> f64 {
+    5.0
+}
+
+fn default_confirmation_secs() -> u64 {
+    60
+}
+
+impl EmergencyExitConfig {
+    /// 退出阈值：临界温度减去回差
+    pub fn exit_threshold(&self) -> f64 {
+        self.critical_temperature - self.hysteresis
+    }
+}
+
+/// 紧急冷却退出确认
+///
+/// 紧急模式期间持续记录最高温度，温度回到退出阈值以上时重新计时
+#[derive(Debug, Clone, Default)]
+pub struct EmergencyExitGuard {
+    config: EmergencyExitConfig,
+    /// 最近一次读数的最高温度
+    last_temperature: Option<f64>,
+    /// 温度连续低于退出阈值的起始时间
+    below_since: Option<DateTime<Utc>>,
+}
+
+impl EmergencyExitGuard {
+    /// 创建退出确认
+    ///
+    /// # 参数
+    /// * `config` - 退出条件
+    pub fn new(config: EmergencyExitConfig) -> Self {
+        Self {
+            config,
+            last_temperature: None,
+            below_since: None,
+        }
+    }
+
+    /// 修改临界温度，退出阈值随之变化
+    ///
+    /// # 参数
+    /// * `critical_temperature` - 临界温度
+    pub fn set_critical_temperature(&mut self, critical_temperature: f64) {
+        self.config.critical_temperature = critical_temperature;
+    }
+
+    /// 进入紧急模式时清空计时
+    pub fn reset(&mut self) {
+        self.last_temperature = None;
+        self.below_since = None;
+    }
+
+    /// 记录一次读数
+    ///
+    /// # 参数
+    /// * `max_temperature` - 所有传感器中的最高温度
+    /// * `at` - 读数时间
+    pub fn observe(&mut self, max_temperature: f64, at: DateTime<Utc>) {
+        self.last_temperature = Some(max_temperature);
+        if max_temperature < self.config.exit_threshold() {
+            self.below_since.get_or_insert(at);
+        } else {
+            self.below_since = None;
+        }
+    }
+
+    /// 检查是否可以退出紧急模式，不满足条件时返回说明原因的冲突错误
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn check_exit(&self, now: DateTime<Utc>) -> AppResult<()> {
+        let threshold = self.config.exit_threshold();
+
+        let message = match (self.last_temperature, self.below_since) {
+            (None, _) => "尚无温度读数，无法确认温度已回落".to_string(),
+            (Some(temperature), None) => format!(
+                "最高温度 {:.1}°C 未低于退出阈值 {:.1}°C（临界 {:.1}°C - 回差 {:.1}°C）",
+                temperature, threshold, self.config.critical_temperature, self.config.hysteresis
+            ),
+            (Some(_), Some(since)) if Self::elapsed_secs(since, now) < self.config.confirmation_secs => format!(
+                "温度低于退出阈值 {:.1}°C 仅 {} 秒，需持续 {} 秒",
+                threshold,
+                Self::elapsed_secs(since, now),
+                self.config.confirmation_secs
+            ),
+            _ => return Ok(()),
+        };
+
+        Err(AppError::ConflictError {
+            message: format!("拒绝退出紧急模式: {}；确认安全后可使用 force=true 强制退出", message),
+        })
+    }
+
+    fn elapsed_secs(since: DateTime<Utc>, now: DateTime<Utc>) -> u64 {
+        (now - since).num_seconds().max(0) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_exit_refused_while_hot_and_allowed_after_sustained_cool_down() {
+        let mut guard = EmergencyExitGuard::new(EmergencyExitConfig {
+            critical_temperature: 85.0,
+            hysteresis: 5.0,
+            confirmation_secs: 60,
+        });
+        let start = Utc::now();
+
+        guard.observe(86.0, start);
+        let err = guard.check_exit(start).unwrap_err();
+        assert_eq!(err.status_code(), 409);
+        assert!(err.to_string().contains("86.0°C"));
+
+        // 低于临界但未低于 临界-回差 时仍拒绝
+        guard.observe(82.0, start + Duration::seconds(10));
+        assert!(guard.check_exit(start + Duration::seconds(10)).is_err());
+
+        // 低于退出阈值但持续时间不足
+        guard.observe(78.0, start + Duration::seconds(20));
+        guard.observe(77.5, start + Duration::seconds(50));
+        assert!(guard.check_exit(start + Duration::seconds(50)).is_err());
+
+        guard.observe(77.0, start + Duration::seconds(80));
+        assert!(guard.check_exit(start + Duration::seconds(80)).is_ok());
+
+        // 期间温度反弹会重新计时
+        guard.observe(81.0, start + Duration::seconds(90));
+        guard.observe(76.0, start + Duration::seconds(100));
+        assert!(guard.check_exit(start + Duration::seconds(130)).is_err());
+        assert!(guard.check_exit(start + Duration::seconds(160)).is_ok());
+    }
+}
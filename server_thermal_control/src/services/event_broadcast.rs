@@ -0,0 +1,121 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// 订阅者收到的推送项
+#[derive(Debug, Clone, PartialEq)]
+pub enum BroadcastItem<T> {
+    /// 一条事件
+    Event(T),
+    /// 订阅者落后，期间有若干条最旧的事件已被丢弃
+    Lagged(u64),
+}
+
+/// 有界事件广播
+///
+/// 发布方永不阻塞：每个订阅者最多积压 `capacity` 条事件，落后更多时丢弃最旧的事件，
+/// 订阅者下次接收时先得到 [`BroadcastItem::Lagged`] 与丢弃数量，之后从仍保留的最旧事件继续。
+/// 订阅者收到丢弃通知时，丢弃数量累计到 [`EventBroadcast::dropped_events`]，用于判断消费方是否跟不上
+pub struct EventBroadcast<T> {
+    sender: broadcast::Sender<T>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<T: Clone> EventBroadcast<T> {
+    /// 创建事件广播
+    ///
+    /// # 参数
+    /// * `capacity` - 每个订阅者最多积压的事件数，至少为1
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity.max(1)).0,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// 发布事件，返回收到该事件的订阅者数量；没有订阅者时直接丢弃，不计入丢弃数
+    ///
+    /// # 参数
+    /// * `event` - 事件
+    pub fn send(&self, event: T) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// 订阅之后发布的事件，订阅之前的事件不会补发
+    pub fn subscribe(&self) -> EventSubscriber<T> {
+        EventSubscriber {
+            receiver: self.sender.subscribe(),
+            dropped: Arc::clone(&self.dropped),
+        }
+    }
+
+    /// 所有订阅者因落后累计丢弃的事件数
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// 当前订阅者数量
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl<T> Clone for EventBroadcast<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            dropped: Arc::clone(&self.dropped),
+        }
+    }
+}
+
+/// 事件广播的订阅者
+pub struct EventSubscriber<T> {
+    receiver: broadcast::Receiver<T>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<T: Clone> EventSubscriber<T> {
+    /// 接收下一项，广播已关闭时返回None
+    pub async fn recv(&mut self) -> Option<BroadcastItem<T>> {
+        match self.receiver.recv().await {
+            Ok(event) => Some(BroadcastItem::Event(event)),
+            Err(RecvError::Lagged(skipped)) => {
+                self.dropped.fetch_add(skipped, Ordering::Relaxed);
+                Some(BroadcastItem::Lagged(skipped))
+            }
+            Err(RecvError::Closed) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_slow_subscriber_lags_instead_of_blocking_producer() {
+        let events = EventBroadcast::new(4);
+        let mut slow = events.subscribe();
+
+        // 发布方不等待订阅者，超出容量的部分直接覆盖最旧的事件
+        tokio::time::timeout(Duration::from_secs(1), async {
+            for i in 0..10 {
+                assert_eq!(events.send(i), 1);
+            }
+        })
+        .await
+        .expect("发布方被慢订阅者阻塞");
+        assert_eq!(events.dropped_events(), 0);
+
+        assert_eq!(slow.recv().await, Some(BroadcastItem::Lagged(6)));
+        assert_eq!(events.dropped_events(), 6);
+        for i in 6..10 {
+            assert_eq!(slow.recv().await, Some(BroadcastItem::Event(i)));
+        }
+
+        drop(events);
+        assert_eq!(slow.recv().await, None);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::models::{error::AppResult, SystemEvent};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+/// 进程内最多保留的系统事件数量
+const MAX_IN_MEMORY_EVENTS: usize = 10_000;
+
+/// 系统事件查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SystemEventQuery {
+    /// 事件级别（info/warning/critical）
+    pub level: Option<String>,
+    /// 事件类型
+    pub event_type: Option<String>,
+    /// 页码（从1开始）
+    pub page: i32,
+    /// 每页大小
+    pub page_size: i32,
+}
+
+impl SystemEventQuery {
+    fn offset(&self) -> usize {
+        ((self.page.max(1) - 1) * self.page_size.max(0)) as usize
+    }
+
+    fn matches(&self, event: &SystemEvent) -> bool {
+        self.level.as_ref().map_or(true, |level| &event.event_level == level)
+            && self
+                .event_type
+                .as_ref()
+                .map_or(true, |event_type| &event.event_type == event_type)
+    }
+}
+
+/// 系统事件存储
+///
+/// 抽象 `system_events` 的写入与查询，便于在进程内存储与数据库之间切换
+#[async_trait]
+pub trait SystemEventStore: Send + Sync {
+    /// 记录系统事件
+    ///
+    /// # 参数
+    /// * `event` - 系统事件
+    async fn record(&self, event: SystemEvent) -> AppResult<()>;
+
+    /// 按条件分页查询系统事件，按时间倒序
+    ///
+    /// 返回当前页事件与符合条件的总数
+    ///
+    /// # 参数
+    /// * `query` - 查询条件
+    async fn query(&self, query: &SystemEventQuery) -> AppResult<(Vec<SystemEvent>, i64)>;
+}
+
+/// 进程内系统事件存储
+#[derive(Default)]
+pub struct InMemorySystemEventStore {
+    events: RwLock<Vec<SystemEvent>>,
+}
+
+impl InMemorySystemEventStore {
+    /// 创建进程内事件存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SystemEventStore for InMemorySystemEventStore {
+    async fn record(&self, event: SystemEvent) -> AppResult<()> {
+        let mut events = self.events.write().await;
+        events.push(event);
+
+        if events.len() > MAX_IN_MEMORY_EVENTS {
+            let overflow = events.len() - MAX_IN_MEMORY_EVENTS;
+            events.drain(0..overflow);
+        }
+        Ok(())
+    }
+
+    async fn query(&self, query: &SystemEventQuery) -> AppResult<(Vec<SystemEvent>, i64)> {
+        let events = self.events.read().await;
+        let matched: Vec<&SystemEvent> = events.iter().rev().filter(|e| query.matches(e)).collect();
+        let total = matched.len() as i64;
+
+        let page = matched
+            .into_iter()
+            .skip(query.offset())
+            .take(query.page_size.max(0) as usize)
+            .cloned()
+            .collect();
+
+        Ok((page, total))
+    }
+}
+
+/// PostgreSQL系统事件存储
+///
+/// 写入 `scripts/init.sql` 中定义的 `system_events` 表，`event_level` 对应 `severity` 列
+pub struct PgSystemEventStore {
+    pool: PgPool,
+}
+
+impl PgSystemEventStore {
+    /// 创建数据库事件存储
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SystemEventStore for PgSystemEventStore {
+    #[instrument(level = "debug", name = "db.system_events.record", skip_all)]
+    async fn record(&self, event: SystemEvent) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO system_events
+                (id, event_type, event_category, title, description, severity, source, metadata, timestamp, created_at)
+            VALUES ($1, $2, 'alert', $3, $4, $5, $6, $7, $8, $8)
+            "#,
+        )
+        .bind(event.id)
+        .bind(&event.event_type)
+        .bind(&event.title)
+        .bind(&event.description)
+        .bind(&event.event_level)
+        .bind(&event.source)
+        .bind(&event.metadata)
+        .bind(event.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "db.system_events.query", skip_all)]
+    async fn query(&self, query: &SystemEventQuery) -> AppResult<(Vec<SystemEvent>, i64)> {
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM system_events
+            WHERE ($1::text IS NULL OR severity = $1)
+              AND ($2::text IS NULL OR event_type = $2)
+            "#,
+        )
+        .bind(&query.level)
+        .bind(&query.event_type)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let events = sqlx::query_as::<_, SystemEvent>(
+            r#"
+            SELECT id, event_type, severity AS event_level, title,
+                   COALESCE(description, '') AS description,
+                   COALESCE(source, '') AS source, metadata, created_at
+            FROM system_events
+            WHERE ($1::text IS NULL OR severity = $1)
+              AND ($2::text IS NULL OR event_type = $2)
+            ORDER BY created_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(&query.level)
+        .bind(&query.event_type)
+        .bind(query.page_size.max(0) as i64)
+        .bind(query.offset() as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((events, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn event(level: &str) -> SystemEvent {
+        SystemEvent {
+            id: Uuid::new_v4(),
+            event_type: "alert_created".to_string(),
+            event_level: level.to_string(),
+            title: "test".to_string(),
+            description: String::new(),
+            source: "test".to_string(),
+            metadata: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_query_filters_and_paginates() {
+        let store = InMemorySystemEventStore::new();
+        for level in ["info", "critical", "info", "warning", "info"] {
+            store.record(event(level)).await.unwrap();
+        }
+
+        let query = SystemEventQuery {
+            level: Some("info".to_string()),
+            page: 2,
+            page_size: 2,
+            ..Default::default()
+        };
+        let (events, total) = store.query(&query).await.unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_level, "info");
+    }
+}
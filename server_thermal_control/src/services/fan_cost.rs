@@ -0,0 +1,31 @@
+use crate::models::analytics::NoiseLevel;
+
+/// 估算单个风扇在给定转速下的功耗（瓦特）
+///
+/// 简化模型：5W基础功耗，加上与转速立方成正比的可变功耗，满速时共25W
+///
+/// # 参数
+/// * `speed_percent` - 转速百分比
+pub fn estimate_fan_power_watts(speed_percent: f64) -> f64 {
+    let base_power = 5.0;
+    let variable_power = (speed_percent / 100.0).powi(3) * 20.0;
+    base_power + variable_power
+}
+
+/// 按转速估算风扇噪音水平
+///
+/// # 参数
+/// * `speed_percent` - 转速百分比
+pub fn estimate_noise_level(speed_percent: f64) -> NoiseLevel {
+    if speed_percent < 10.0 {
+        NoiseLevel::Silent
+    } else if speed_percent < 30.0 {
+        NoiseLevel::Low
+    } else if speed_percent < 60.0 {
+        NoiseLevel::Medium
+    } else if speed_percent < 80.0 {
+        NoiseLevel::High
+    } else {
+        NoiseLevel::VeryHigh
+    }
+}
@@ -0,0 +1,286 @@
+use crate::models::error::{AppError, AppResult};
+use crate::models::fan::FanCurvePoint;
+use crate::services::fan_cost::estimate_fan_power_watts;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 转速分布的区间宽度（百分比）
+const SPEED_BUCKET_WIDTH_PERCENT: f64 = 10.0;
+
+/// 候选风扇曲线
+///
+/// 按温度在曲线点之间线性插值，超出曲线范围时取端点，结果限制在 `[min_speed, max_speed]` 内
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FanCurve {
+    /// 曲线点，温度须严格递增
+    pub points: Vec<FanCurvePoint>,
+    /// 最低转速（百分比）
+    #[serde(default)]
+    pub min_speed: f64,
+    /// 最高转速（百分比）
+    #[serde(default = "default_max_speed")]
+    pub max_speed: f64,
+}
+
+fn default_max_speed() -> f64 {
+    100.0
+}
+
+impl FanCurve {
+    /// 校验曲线点和转速范围
+    pub fn validate(&self) -> AppResult<()> {
+        if self.points.len() < 2 {
+            return Err(AppError::validation_error("curve.points", "风扇曲线至少需要两个点"));
+        }
+        if self.points.windows(2).any(|pair| pair[0].temperature >= pair[1].temperature) {
+            return Err(AppError::validation_error("curve.points", "曲线点的温度必须严格递增"));
+        }
+        let speeds = self
+            .points
+            .iter()
+            .map(|point| point.fan_speed_percentage)
+            .chain([self.min_speed, self.max_speed]);
+        if speeds.into_iter().any(|speed| !(0.0..=100.0).contains(&speed)) {
+            return Err(AppError::validation_error("curve", "转速必须在0-100%之间"));
+        }
+        if self.min_speed > self.max_speed {
+            return Err(AppError::validation_error("curve.min_speed", "最低转速不能高于最高转速"));
+        }
+        Ok(())
+    }
+
+    /// 温度对应的转速（百分比）
+    ///
+    /// # 参数
+    /// * `temperature` - 控制温度
+    pub fn speed_at(&self, temperature: f64) -> f64 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return self.max_speed;
+        };
+        let speed = if temperature <= first.temperature {
+            first.fan_speed_percentage
+        } else if temperature >= last.temperature {
+            last.fan_speed_percentage
+        } else {
+            self.points
+                .windows(2)
+                .find(|pair| temperature <= pair[1].temperature)
+                .map(|pair| {
+                    let ratio = (temperature - pair[0].temperature) / (pair[1].temperature - pair[0].temperature);
+                    pair[0].fan_speed_percentage + ratio * (pair[1].fan_speed_percentage - pair[0].fan_speed_percentage)
+                })
+                .unwrap_or(last.fan_speed_percentage)
+        };
+        speed.clamp(self.min_speed, self.max_speed)
+    }
+}
+
+/// 风扇曲线回放结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FanCurveReplay {
+    /// 风扇ID
+    pub fan_id: String,
+    /// 回放开始时间
+    pub start_time: DateTime<Utc>,
+    /// 回放结束时间
+    pub end_time: DateTime<Utc>,
+    /// 回放的采样周期数
+    pub evaluated_samples: usize,
+    /// 目标温度
+    pub target_temperature: f64,
+    /// 转速分布，按10%分段
+    pub speed_distribution: Vec<SpeedBucket>,
+    /// 平均转速百分比
+    pub average_speed_percent: f64,
+    /// 估算的平均功耗（瓦特）
+    pub estimated_average_power_watts: f64,
+    /// 温度高于目标而曲线未达最高转速的采样数
+    pub over_target_samples: usize,
+    /// 温度高于目标而曲线未达最高转速的连续时段
+    pub over_target_periods: Vec<OverTargetPeriod>,
+}
+
+/// 转速分布区间
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeedBucket {
+    /// 区间下限（含）
+    pub lower_percent: f64,
+    /// 区间上限，最后一个区间包含100%
+    pub upper_percent: f64,
+    /// 落入区间的采样数
+    pub samples: usize,
+    /// 占全部采样的比例（0-1）
+    pub ratio: f64,
+}
+
+/// 温度高于目标的连续时段
+#[derive(Debug, Clone, Serialize)]
+pub struct OverTargetPeriod {
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 时段内最高温度
+    pub peak_temperature: f64,
+    /// 时段内曲线给出的最高转速百分比
+    pub max_speed_percent: f64,
+}
+
+/// 用历史温度回放候选风扇曲线，不下发任何转速
+///
+/// 同一秒内的读数视为一个控制周期，与控制循环一样以最高温度为控制温度
+///
+/// # 参数
+/// * `fan_id` - 风扇ID
+/// * `curve` - 候选风扇曲线
+/// * `readings` - 各传感器按时间排列的读数
+/// * `target_temperature` - 目标温度
+/// * `start_time` - 回放开始时间
+/// * `end_time` - 回放结束时间
+pub fn replay(
+    fan_id: &str,
+    curve: &FanCurve,
+    readings: &BTreeMap<String, Vec<(DateTime<Utc>, f64)>>,
+    target_temperature: f64,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> AppResult<FanCurveReplay> {
+    if start_time >= end_time {
+        return Err(AppError::validation_error("start_time", "开始时间必须早于结束时间"));
+    }
+    curve.validate()?;
+
+    let mut cycles: BTreeMap<i64, (DateTime<Utc>, f64)> = BTreeMap::new();
+    for &(timestamp, temperature) in readings
+        .values()
+        .flatten()
+        .filter(|(at, _)| *at >= start_time && *at <= end_time)
+    {
+        let cycle = cycles
+            .entry(timestamp.timestamp())
+            .or_insert((timestamp, temperature));
+        cycle.1 = cycle.1.max(temperature);
+    }
+
+    let bucket_count = (100.0 / SPEED_BUCKET_WIDTH_PERCENT).ceil() as usize;
+    let mut bucket_samples = vec![0usize; bucket_count];
+    let mut speeds = Vec::with_capacity(cycles.len());
+    let mut over_target_samples = 0;
+    let mut over_target_periods: Vec<OverTargetPeriod> = Vec::new();
+    let mut in_period = false;
+
+    for (timestamp, temperature) in cycles.into_values() {
+        let speed = curve.speed_at(temperature);
+        speeds.push(speed);
+        let bucket = ((speed / SPEED_BUCKET_WIDTH_PERCENT) as usize).min(bucket_count - 1);
+        bucket_samples[bucket] += 1;
+
+        if temperature > target_temperature && speed < curve.max_speed {
+            over_target_samples += 1;
+            match over_target_periods.last_mut() {
+                Some(period) if in_period => {
+                    period.end_time = timestamp;
+                    period.peak_temperature = period.peak_temperature.max(temperature);
+                    period.max_speed_percent = period.max_speed_percent.max(speed);
+                }
+                _ => over_target_periods.push(OverTargetPeriod {
+                    start_time: timestamp,
+                    end_time: timestamp,
+                    peak_temperature: temperature,
+                    max_speed_percent: speed,
+                }),
+            }
+            in_period = true;
+        } else {
+            in_period = false;
+        }
+    }
+
+    let evaluated_samples = speeds.len();
+    let mean = |total: f64| if evaluated_samples == 0 { 0.0 } else { total / evaluated_samples as f64 };
+    let average_speed_percent = mean(speeds.iter().sum::<f64>());
+    let estimated_average_power_watts = mean(speeds.iter().map(|speed| estimate_fan_power_watts(*speed)).sum::<f64>());
+
+    let speed_distribution = bucket_samples
+        .into_iter()
+        .enumerate()
+        .map(|(i, samples)| SpeedBucket {
+            lower_percent: i as f64 * SPEED_BUCKET_WIDTH_PERCENT,
+            upper_percent: ((i + 1) as f64 * SPEED_BUCKET_WIDTH_PERCENT).min(100.0),
+            samples,
+            ratio: if evaluated_samples == 0 { 0.0 } else { samples as f64 / evaluated_samples as f64 },
+        })
+        .collect();
+
+    Ok(FanCurveReplay {
+        fan_id: fan_id.to_string(),
+        start_time,
+        end_time,
+        evaluated_samples,
+        target_temperature,
+        speed_distribution,
+        average_speed_percent,
+        estimated_average_power_watts,
+        over_target_samples,
+        over_target_periods,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn curve() -> FanCurve {
+        FanCurve {
+            points: [(40.0, 20.0), (60.0, 60.0), (80.0, 100.0)]
+                .iter()
+                .map(|(temperature, speed)| FanCurvePoint {
+                    temperature: *temperature,
+                    fan_speed_percentage: *speed,
+                })
+                .collect(),
+            min_speed: 35.0,
+            max_speed: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_replay_speed_distribution_follows_hottest_sensor() {
+        // 每个周期两个传感器，按最高温度换算
+        let start = Utc::now() - Duration::hours(1);
+        let mut readings: BTreeMap<String, Vec<(DateTime<Utc>, f64)>> = BTreeMap::new();
+        for (minute, temperature) in [35.0, 45.0, 50.0, 55.0, 60.0, 70.0, 70.0, 85.0].into_iter().enumerate() {
+            let at = start + Duration::minutes(minute as i64 + 1);
+            readings.entry("CPU1_TEMP".to_string()).or_default().push((at, temperature));
+            readings.entry("CPU2_TEMP".to_string()).or_default().push((at, temperature - 10.0));
+        }
+
+        let replay = replay("FAN1", &curve(), &readings, 65.0, start, start + Duration::hours(1)).unwrap();
+
+        assert_eq!(replay.evaluated_samples, 8);
+        // 转速依次为 35(下限) 35(下限) 40 50 60 80 80 100
+        let samples: Vec<usize> = replay.speed_distribution.iter().map(|bucket| bucket.samples).collect();
+        assert_eq!(samples, vec![0, 0, 0, 2, 1, 1, 1, 0, 2, 1]);
+        assert_eq!(replay.speed_distribution[9].upper_percent, 100.0);
+        assert!((replay.speed_distribution[3].ratio - 0.25).abs() < 1e-9);
+        assert!((replay.average_speed_percent - 60.0).abs() < 1e-9);
+        assert!(replay.estimated_average_power_watts > estimate_fan_power_watts(0.0));
+
+        // 70°C时曲线只给到80%，85°C时已达最高转速不计入
+        assert_eq!(replay.over_target_samples, 2);
+        assert_eq!(replay.over_target_periods.len(), 1);
+        assert_eq!(replay.over_target_periods[0].peak_temperature, 70.0);
+        assert_eq!(replay.over_target_periods[0].max_speed_percent, 80.0);
+    }
+
+    #[test]
+    fn test_replay_rejects_unordered_curve() {
+        let mut curve = curve();
+        curve.points.swap(0, 1);
+        let end = Utc::now();
+        let error = replay("FAN1", &curve, &BTreeMap::new(), 65.0, end - Duration::hours(1), end).unwrap_err();
+        assert!(error.to_string().contains("严格递增"));
+    }
+}
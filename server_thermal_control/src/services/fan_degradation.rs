@@ -0,0 +1,355 @@
+use crate::models::error::AppResult;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+
+/// 学习转速曲线时占空比的分桶宽度（百分点）
+const LEARNING_BUCKET_PERCENT: f64 = 5.0;
+
+/// 风扇老化检测配置
+///
+/// 实测转速与下发占空比应达到的转速偏差超过 `deviation_percent` 并持续 `duration_secs` 秒时告警。
+/// 配置了 `rpm_curve` 时按配置的曲线插值期望转速，否则按每个风扇运行初期学习到的曲线
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FanDegradationConfig {
+    /// 是否启用风扇老化检测
+    #[serde(default)]
+    pub enabled: bool,
+    /// 占空比-转速曲线，为空时自动学习
+    #[serde(default)]
+    pub rpm_curve: Vec<RpmCurvePoint>,
+    /// 学习曲线时每个占空比分桶采集的样本数，采满后该分桶的期望转速不再变化
+    #[serde(default = "default_learning_samples")]
+    pub learning_samples: u32,
+    /// 实测转速偏离期望转速的告警阈值（百分比）
+    #[serde(default = "default_deviation_percent")]
+    pub deviation_percent: f64,
+    /// 偏差持续多久后告警（秒）
+    #[serde(default = "default_duration_secs")]
+    pub duration_secs: u64,
+}
+
+impl Default for FanDegradationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rpm_curve: Vec::new(),
+            learning_samples: default_learning_samples(),
+            deviation_percent: default_deviation_percent(),
+            duration_secs: default_duration_secs(),
+        }
+    }
+}
+
+fn default_learning_samples() -> u32 {
+    20
+}
+
+fn default_deviation_percent() -> f64 {
+    25.0
+}
+
+fn default_duration_secs() -> u64 {
+    300
+}
+
+/// 占空比-转速曲线上的一点
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RpmCurvePoint {
+    /// 占空比（百分比）
+    pub duty_percent: f64,
+    /// 该占空比下的正常转速（RPM）
+    pub rpm: f64,
+}
+
+/// 检测到的风扇老化
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FanDegradation {
+    /// 风扇ID
+    pub fan_id: String,
+    /// 下发的占空比（百分比）
+    pub duty_percent: f64,
+    /// 期望转速（RPM）
+    pub expected_rpm: f64,
+    /// 实测转速（RPM）
+    pub measured_rpm: f64,
+    /// 偏差百分比，负数表示低于期望转速
+    pub deviation_percent: f64,
+    /// 偏差开始时间
+    pub since: DateTime<Utc>,
+}
+
+/// 风扇老化告警出口
+#[async_trait]
+pub trait FanDegradationAlertSink: Send + Sync {
+    /// 发出风扇老化告警
+    ///
+    /// # 参数
+    /// * `degradation` - 检测到的风扇老化
+    async fn raise_fan_degradation_alert(&self, degradation: &FanDegradation) -> AppResult<()>;
+}
+
+/// 学习中的占空比分桶
+#[derive(Debug, Clone, Copy, Default)]
+struct LearnedBucket {
+    mean_rpm: f64,
+    samples: u32,
+}
+
+/// 单个风扇的检测状态
+#[derive(Debug, Clone, Default)]
+struct FanState {
+    /// 按占空比分桶学习到的转速
+    learned: HashMap<i64, LearnedBucket>,
+    /// 偏差持续超过阈值的起始时间
+    deviating_since: Option<DateTime<Utc>>,
+    /// 本次偏差是否已告警
+    alerted: bool,
+}
+
+/// 风扇老化检测
+///
+/// 轴承磨损的风扇在同样的占空比下转速逐渐下降。每个采样周期比较实测转速与期望转速，
+/// 偏差连续超过阈值达到持续时间后告警一次，转速回到阈值内后重新计时
+#[derive(Clone, Default)]
+pub struct FanDegradationMonitor {
+    config: FanDegradationConfig,
+    fans: Arc<RwLock<HashMap<String, FanState>>>,
+    alert_sink: Option<Arc<dyn FanDegradationAlertSink>>,
+}
+
+impl FanDegradationMonitor {
+    /// 创建风扇老化检测
+    ///
+    /// # 参数
+    /// * `config` - 检测配置
+    pub fn new(config: FanDegradationConfig) -> Self {
+        let mut config = config;
+        config
+            .rpm_curve
+            .sort_by(|a, b| a.duty_percent.total_cmp(&b.duty_percent));
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// 设置告警出口
+    ///
+    /// # 参数
+    /// * `sink` - 告警出口，如 `ControlAlertSink`
+    pub fn with_alert_sink(mut self, sink: Arc<dyn FanDegradationAlertSink>) -> Self {
+        self.alert_sink = Some(sink);
+        self
+    }
+
+    /// 下发占空比对应的期望转速，学习曲线尚未覆盖该占空比时返回None
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `duty_percent` - 下发的占空比
+    pub fn expected_rpm(&self, fan_id: &str, duty_percent: f64) -> Option<f64> {
+        if !self.config.rpm_curve.is_empty() {
+            return Some(interpolate(&self.config.rpm_curve, duty_percent));
+        }
+        let fans = self.fans.read().unwrap_or_else(|e| e.into_inner());
+        fans.get(fan_id)?
+            .learned
+            .get(&bucket_of(duty_percent))
+            .filter(|bucket| bucket.samples >= self.config.learning_samples.max(1))
+            .map(|bucket| bucket.mean_rpm)
+    }
+
+    /// 记录一次采样，偏差持续达到告警时长时返回检测结果（同一次偏差只返回一次）
+    ///
+    /// 占空比为0或实测转速为0的采样不参与检测，停转由故障转移处理
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `duty_percent` - 下发的占空比
+    /// * `measured_rpm` - 实测转速
+    /// * `now` - 采样时间
+    pub fn observe(
+        &self,
+        fan_id: &str,
+        duty_percent: f64,
+        measured_rpm: f64,
+        now: DateTime<Utc>,
+    ) -> Option<FanDegradation> {
+        if !self.config.enabled || duty_percent <= 0.0 || measured_rpm <= 0.0 {
+            return None;
+        }
+
+        let expected_rpm = self.expected_rpm(fan_id, duty_percent);
+        let mut fans = self.fans.write().unwrap_or_else(|e| e.into_inner());
+        let state = fans.entry(fan_id.to_string()).or_default();
+
+        let Some(expected_rpm) = expected_rpm.filter(|rpm| *rpm > 0.0) else {
+            // 曲线未学习完成，继续采样
+            let bucket = state.learned.entry(bucket_of(duty_percent)).or_default();
+            bucket.samples += 1;
+            bucket.mean_rpm += (measured_rpm - bucket.mean_rpm) / bucket.samples as f64;
+            return None;
+        };
+
+        let deviation_percent = (measured_rpm - expected_rpm) / expected_rpm * 100.0;
+        if deviation_percent.abs() <= self.config.deviation_percent {
+            if state.alerted {
+                info!("风扇 {} 转速恢复正常: {:.0} RPM，期望 {:.0} RPM", fan_id, measured_rpm, expected_rpm);
+            }
+            state.deviating_since = None;
+            state.alerted = false;
+            return None;
+        }
+
+        let since = *state.deviating_since.get_or_insert(now);
+        if state.alerted || (now - since).num_seconds() < self.config.duration_secs as i64 {
+            return None;
+        }
+        state.alerted = true;
+
+        Some(FanDegradation {
+            fan_id: fan_id.to_string(),
+            duty_percent,
+            expected_rpm,
+            measured_rpm,
+            deviation_percent,
+            since,
+        })
+    }
+
+    /// 记录一次采样，检测到老化时发出告警
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `duty_percent` - 下发的占空比
+    /// * `measured_rpm` - 实测转速
+    /// * `now` - 采样时间
+    pub async fn check(
+        &self,
+        fan_id: &str,
+        duty_percent: f64,
+        measured_rpm: f64,
+        now: DateTime<Utc>,
+    ) -> Option<FanDegradation> {
+        let degradation = self.observe(fan_id, duty_percent, measured_rpm, now)?;
+        warn!(
+            "风扇 {} 占空比 {:.1}% 实测转速 {:.0} RPM，偏离期望 {:.0} RPM {:.1}%，可能老化",
+            fan_id, duty_percent, measured_rpm, degradation.expected_rpm, degradation.deviation_percent
+        );
+        if let Some(sink) = &self.alert_sink {
+            if let Err(e) = sink.raise_fan_degradation_alert(&degradation).await {
+                warn!("风扇 {} 老化告警发送失败: {}", fan_id, e);
+            }
+        }
+        Some(degradation)
+    }
+}
+
+/// 占空比所属的学习分桶
+fn bucket_of(duty_percent: f64) -> i64 {
+    (duty_percent / LEARNING_BUCKET_PERCENT).round() as i64
+}
+
+/// 在按占空比排序的曲线上线性插值，超出范围时取端点
+fn interpolate(curve: &[RpmCurvePoint], duty_percent: f64) -> f64 {
+    let first = curve[0];
+    let last = curve[curve.len() - 1];
+    if duty_percent <= first.duty_percent {
+        return first.rpm;
+    }
+    if duty_percent >= last.duty_percent {
+        return last.rpm;
+    }
+    curve
+        .windows(2)
+        .find(|pair| duty_percent <= pair[1].duty_percent)
+        .map(|pair| {
+            let (low, high) = (pair[0], pair[1]);
+            let span = high.duty_percent - low.duty_percent;
+            if span <= 0.0 {
+                return high.rpm;
+            }
+            low.rpm + (high.rpm - low.rpm) * (duty_percent - low.duty_percent) / span
+        })
+        .unwrap_or(last.rpm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        alerts: Mutex<Vec<FanDegradation>>,
+    }
+
+    #[async_trait]
+    impl FanDegradationAlertSink for RecordingSink {
+        async fn raise_fan_degradation_alert(&self, degradation: &FanDegradation) -> AppResult<()> {
+            self.alerts.lock().unwrap().push(degradation.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_fan_raises_degradation_alert_after_duration() {
+        let sink = Arc::new(RecordingSink::default());
+        let monitor = FanDegradationMonitor::new(FanDegradationConfig {
+            enabled: true,
+            rpm_curve: vec![
+                RpmCurvePoint { duty_percent: 100.0, rpm: 12000.0 },
+                RpmCurvePoint { duty_percent: 20.0, rpm: 3000.0 },
+            ],
+            deviation_percent: 25.0,
+            duration_secs: 60,
+            ..Default::default()
+        })
+        .with_alert_sink(sink.clone());
+        assert_eq!(monitor.expected_rpm("FAN1", 60.0), Some(7500.0));
+
+        // 60%占空比应达到7500 RPM，实测只有4000 RPM
+        let start = Utc::now() - Duration::seconds(120);
+        assert!(monitor.observe("FAN1", 60.0, 4000.0, start).is_none());
+        assert!(monitor.observe("FAN1", 60.0, 4000.0, start + Duration::seconds(30)).is_none());
+        // 正常风扇不受影响
+        assert!(monitor.observe("FAN2", 60.0, 7400.0, start + Duration::seconds(90)).is_none());
+
+        let degradation = monitor.check("FAN1", 60.0, 4000.0, Utc::now()).await.unwrap();
+        assert_eq!(degradation.expected_rpm, 7500.0);
+        assert!(degradation.deviation_percent < -45.0);
+        assert_eq!(degradation.since, start);
+        assert_eq!(sink.alerts.lock().unwrap().as_slice(), &[degradation]);
+
+        // 同一次偏差只告警一次，转速恢复后重新计时
+        assert!(monitor.check("FAN1", 60.0, 4000.0, Utc::now()).await.is_none());
+        let now = Utc::now();
+        assert!(monitor.observe("FAN1", 60.0, 7300.0, now).is_none());
+        assert!(monitor.observe("FAN1", 60.0, 4000.0, now).is_none());
+        assert_eq!(sink.alerts.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_learned_curve_detects_drift() {
+        let monitor = FanDegradationMonitor::new(FanDegradationConfig {
+            enabled: true,
+            learning_samples: 3,
+            duration_secs: 0,
+            ..Default::default()
+        });
+        let now = Utc::now();
+        for rpm in [6000.0, 6200.0, 6100.0] {
+            assert!(monitor.observe("FAN1", 50.0, rpm, now).is_none());
+        }
+        assert_eq!(monitor.expected_rpm("FAN1", 51.0), Some(6100.0));
+        assert!(monitor.expected_rpm("FAN1", 80.0).is_none());
+
+        let degradation = monitor.observe("FAN1", 50.0, 4000.0, now).unwrap();
+        assert_eq!(degradation.expected_rpm, 6100.0);
+    }
+}
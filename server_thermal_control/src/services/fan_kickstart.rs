@@ -0,0 +1,155 @@
+use crate::models::control::FanKickstartConfig;
+use crate::models::error::{AppError, AppResult};
+use crate::services::sensor_source::SensorSource;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 起转脉冲所需的风扇读写接口
+#[async_trait]
+pub trait KickstartFan: Send + Sync {
+    /// 读取风扇实际转速（RPM）
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    async fn rpm(&self, fan_id: &str) -> AppResult<f64>;
+
+    /// 设置风扇转速百分比
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `speed_percent` - 转速百分比 (0-100)
+    async fn set_duty(&self, fan_id: &str, speed_percent: f64) -> AppResult<()>;
+}
+
+#[async_trait]
+impl<S: SensorSource + ?Sized> KickstartFan for Arc<S> {
+    async fn rpm(&self, fan_id: &str) -> AppResult<f64> {
+        let fans = self
+            .get_fan_sensors()
+            .map_err(|e| AppError::ipmi_error(format!("读取风扇失败: {}", e)))?;
+        fans.iter()
+            .find(|fan| fan.fan_id == fan_id)
+            .map(|fan| f64::from(fan.speed_rpm))
+            .ok_or_else(|| AppError::not_found_error("风扇", fan_id))
+    }
+
+    async fn set_duty(&self, fan_id: &str, speed_percent: f64) -> AppResult<()> {
+        self.set_fan_speed(fan_id, speed_percent.clamp(0.0, 100.0).round() as u8)
+            .map_err(|e| AppError::ipmi_error(format!("设置风扇 {} 转速失败: {}", fan_id, e)))
+    }
+}
+
+/// 起转检测结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum KickstartOutcome {
+    /// 目标转速不在低速范围内，或风扇已在转动
+    NotNeeded,
+    /// 脉冲后风扇开始转动
+    Recovered {
+        /// 脉冲结束时的转速（RPM）
+        rpm: f64,
+    },
+    /// 脉冲后仍无转速，风扇可能已损坏
+    Stalled,
+}
+
+/// 低速停转时下发起转脉冲
+///
+/// 目标转速在低速范围内且回读转速为0时，按配置的占空比运行一段时间，
+/// 在脉冲结束时回读转速判断是否起转，无论结果如何都恢复到目标转速
+///
+/// # 参数
+/// * `fan` - 风扇读写接口
+/// * `config` - 起转脉冲配置
+/// * `fan_id` - 风扇ID
+/// * `target_percent` - 已下发的目标转速百分比
+pub async fn kickstart_if_stalled(
+    fan: &dyn KickstartFan,
+    config: &FanKickstartConfig,
+    fan_id: &str,
+    target_percent: f64,
+) -> AppResult<KickstartOutcome> {
+    if !config.applies_to(target_percent) || fan.rpm(fan_id).await? > 0.0 {
+        return Ok(KickstartOutcome::NotNeeded);
+    }
+
+    fan.set_duty(fan_id, config.duty_percent).await?;
+    tokio::time::sleep(Duration::from_millis(config.duration_ms)).await;
+    let rpm = fan.rpm(fan_id).await;
+    // 回读失败也先恢复目标转速，避免风扇停留在脉冲占空比
+    fan.set_duty(fan_id, target_percent).await?;
+
+    let rpm = rpm?;
+    if rpm > 0.0 {
+        Ok(KickstartOutcome::Recovered { rpm })
+    } else {
+        Ok(KickstartOutcome::Stalled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 低于 `start_duty` 时无法自行起转、起转后可在低速维持的风扇
+    struct StickyFan {
+        start_duty: f64,
+        spinning: Mutex<bool>,
+        writes: Mutex<Vec<f64>>,
+    }
+
+    #[async_trait]
+    impl KickstartFan for StickyFan {
+        async fn rpm(&self, _fan_id: &str) -> AppResult<f64> {
+            let spinning = *self.spinning.lock().unwrap();
+            Ok(if spinning { 600.0 } else { 0.0 })
+        }
+
+        async fn set_duty(&self, _fan_id: &str, speed_percent: f64) -> AppResult<()> {
+            self.writes.lock().unwrap().push(speed_percent);
+            if speed_percent >= self.start_duty {
+                *self.spinning.lock().unwrap() = true;
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kickstart_pulse_starts_stalled_fan() {
+        let config = FanKickstartConfig {
+            enabled: true,
+            low_speed_threshold: 30.0,
+            duty_percent: 80.0,
+            duration_ms: 1,
+        };
+        let fan = StickyFan {
+            start_duty: 50.0,
+            spinning: Mutex::new(false),
+            writes: Mutex::new(Vec::new()),
+        };
+
+        let outcome = kickstart_if_stalled(&fan, &config, "FAN1", 20.0).await.unwrap();
+        assert_eq!(outcome, KickstartOutcome::Recovered { rpm: 600.0 });
+        // 先下发脉冲占空比，再回到目标转速
+        assert_eq!(*fan.writes.lock().unwrap(), vec![80.0, 20.0]);
+
+        // 已在转动时不再发脉冲
+        let outcome = kickstart_if_stalled(&fan, &config, "FAN1", 20.0).await.unwrap();
+        assert_eq!(outcome, KickstartOutcome::NotNeeded);
+        assert_eq!(fan.writes.lock().unwrap().len(), 2);
+
+        // 脉冲也无法起转的风扇判定为停转，并恢复目标转速
+        let dead = StickyFan {
+            start_duty: f64::INFINITY,
+            spinning: Mutex::new(false),
+            writes: Mutex::new(Vec::new()),
+        };
+        let outcome = kickstart_if_stalled(&dead, &config, "FAN2", 20.0).await.unwrap();
+        assert_eq!(outcome, KickstartOutcome::Stalled);
+        assert_eq!(*dead.writes.lock().unwrap(), vec![80.0, 20.0]);
+    }
+}
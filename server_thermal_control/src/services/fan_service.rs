@@ -4,26 +4,42 @@ use crate::models::{
     error::{AppError, AppResult},
     fan::*,
 };
-use crate::services::ipmi_service::{FanSensor, IpmiService};
+use crate::services::fan_degradation::FanDegradationMonitor;
+use crate::services::fan_kickstart::KickstartFan;
+use crate::services::ipmi_service::FanSensor;
+use crate::services::sensor_source::SensorSource;
 use crate::utils::{
     ipmi::IpmiClient,
     math::{MathUtils, PidController},
     time::TimeUtils,
 };
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 
+/// 与上次下发转速相差不超过该值（百分点）时视为相同指令
+pub const IDENTICAL_SPEED_TOLERANCE_PERCENT: f64 = 0.5;
+
+/// 一次转速设置的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FanSpeedWrite {
+    /// 已向BMC下发新转速
+    Applied,
+    /// 与上次下发的转速相同，未写入BMC
+    Unchanged,
+}
+
 /// 风扇服务
 ///
 /// 负责风扇控制、监控和管理
 #[derive(Clone)]
 pub struct FanService {
     /// IPMI客户端
-    ipmi_service: Arc<IpmiService>,
+    ipmi_service: Arc<dyn SensorSource>,
     /// 风扇配置
     fan_configs: Arc<RwLock<HashMap<String, FanConfig>>>,
     /// 风扇历史数据
@@ -36,6 +52,22 @@ pub struct FanService {
     math_utils: (),
     /// 自动控制状态
     auto_control_enabled: Arc<RwLock<bool>>,
+    /// 风扇故障转移策略
+    failover_policy: Arc<RwLock<FanFailoverPolicy>>,
+    /// 各风扇的基础指令转速百分比
+    commanded_speeds: Arc<RwLock<HashMap<String, f64>>>,
+    /// 当前故障风扇
+    fan_failures: Arc<RwLock<HashMap<String, FanFailure>>>,
+    /// 各风扇最近一次实际下发到BMC的转速百分比
+    written_speeds: Arc<Mutex<HashMap<String, f64>>>,
+    /// 是否跳过与上次下发转速相同的指令
+    suppress_identical_writes: bool,
+    /// 统计所需的最少样本数
+    min_sample_count: usize,
+    /// 只读监控模式，启用后禁止一切风扇写操作
+    read_only: bool,
+    /// 风扇老化检测
+    degradation_monitor: Option<FanDegradationMonitor>,
 }
 
 impl FanService {
@@ -44,7 +76,7 @@ impl FanService {
     /// # 参数
     /// * `ipmi_client` - IPMI客户端
     /// * `control_config` - 风扇控制配置
-    pub fn new(ipmi_service: Arc<IpmiService>, control_config: MonitoringConfig) -> Self {
+    pub fn new(ipmi_service: Arc<dyn SensorSource>, control_config: MonitoringConfig) -> Self {
         Self {
             ipmi_service,
             fan_configs: Arc::new(RwLock::new(HashMap::new())),
@@ -53,7 +85,99 @@ impl FanService {
             pid_controllers: Arc::new(RwLock::new(HashMap::new())),
             math_utils: (),
             auto_control_enabled: Arc::new(RwLock::new(false)),
+            failover_policy: Arc::new(RwLock::new(FanFailoverPolicy::default())),
+            commanded_speeds: Arc::new(RwLock::new(HashMap::new())),
+            fan_failures: Arc::new(RwLock::new(HashMap::new())),
+            written_speeds: Arc::new(Mutex::new(HashMap::new())),
+            suppress_identical_writes: true,
+            min_sample_count: crate::config::DEFAULT_MIN_SAMPLE_COUNT,
+            read_only: false,
+            degradation_monitor: None,
+        }
+    }
+
+    /// 设置只读监控模式
+    ///
+    /// # 参数
+    /// * `read_only` - 为true时禁止向BMC写入风扇转速
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// 设置是否跳过重复的转速指令
+    ///
+    /// # 参数
+    /// * `suppress` - 为true时，与上次下发转速相差不超过
+    ///   `IDENTICAL_SPEED_TOLERANCE_PERCENT` 的指令不再写入BMC
+    pub fn with_suppress_identical_writes(mut self, suppress: bool) -> Self {
+        self.suppress_identical_writes = suppress;
+        self
+    }
+
+    /// 设置风扇统计所需的最少样本数
+    ///
+    /// # 参数
+    /// * `min_sample_count` - 最少样本数，取自 `analytics.min_sample_count`
+    pub fn with_min_sample_count(mut self, min_sample_count: usize) -> Self {
+        self.min_sample_count = min_sample_count;
+        self
+    }
+
+    /// 设置风扇老化检测
+    ///
+    /// # 参数
+    /// * `monitor` - 老化检测，每次读取风扇状态时比较实测转速与下发占空比
+    pub fn with_degradation_monitor(mut self, monitor: FanDegradationMonitor) -> Self {
+        self.degradation_monitor = Some(monitor);
+        self
+    }
+
+    /// 是否处于只读监控模式
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// 确认允许写操作，只读模式下返回403错误
+    ///
+    /// # 参数
+    /// * `operation` - 操作描述
+    pub fn ensure_writable(&self, operation: &str) -> AppResult<()> {
+        if self.read_only {
+            return Err(AppError::read_only_error(operation));
         }
+        Ok(())
+    }
+
+    /// 将风扇控制交还BMC自动模式
+    ///
+    /// 未曾切换到手动模式时不下发任何命令
+    pub fn restore_automatic_control(&self) -> AppResult<()> {
+        // BMC接管后实际转速不再由上次指令决定，下次设置必须重新下发
+        self.written_speeds.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.ipmi_service
+            .restore_automatic_fan_control()
+            .map_err(|e| AppError::ipmi_error(e.to_string()))
+    }
+
+    /// 记录下发到BMC的转速，下发失败时传入None清除记录
+    fn record_written_speed(&self, fan_id: &str, speed_percent: Option<f64>) {
+        let mut written = self.written_speeds.lock().unwrap_or_else(|e| e.into_inner());
+        match speed_percent {
+            Some(speed) => written.insert(fan_id.to_string(), speed),
+            None => written.remove(fan_id),
+        };
+    }
+
+    /// 目标转速是否与上次下发的转速相同
+    fn is_identical_write(&self, fan_id: &str, speed_percent: f64) -> bool {
+        self.suppress_identical_writes
+            && self
+                .written_speeds
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(fan_id)
+                .is_some_and(|last| (last - speed_percent).abs() <= IDENTICAL_SPEED_TOLERANCE_PERCENT)
     }
 
     /// 获取当前风扇状态
@@ -63,6 +187,7 @@ impl FanService {
     pub async fn get_current_fan_status(&self, fan_id: Option<&str>) -> AppResult<Vec<FanReading>> {
         let result = self.ipmi_service.get_fan_sensors();
         let mut readings = Vec::new();
+        let mut rpm_readings = HashMap::new();
 
         match result {
             Ok(fans) => {
@@ -74,6 +199,11 @@ impl FanService {
                         }
                     }
 
+                    rpm_readings.insert(
+                        fan_data.fan_id.clone(),
+                        fan_data.speed_rpm.unwrap_or(0) as f64,
+                    );
+
                     let config = self.get_fan_config(&id).await;
                     let status = self.determine_fan_status(&fan_data, config.as_ref());
 
@@ -94,15 +224,34 @@ impl FanService {
         // 更新历史数据
         self.update_fan_history(&readings).await;
 
+        // 检测风扇故障并重新分配负载
+        if let Err(e) = self.apply_failover(&rpm_readings).await {
+            log::warn!("风扇故障转移执行失败: {}", e);
+        }
+
+        // 按实际下发的占空比检测风扇老化
+        if let Some(monitor) = &self.degradation_monitor {
+            let written = self.written_speeds.lock().unwrap_or_else(|e| e.into_inner()).clone();
+            for (id, rpm) in &rpm_readings {
+                if let Some(duty_percent) = written.get(id) {
+                    monitor.check(id, *duty_percent, *rpm).await;
+                }
+            }
+        }
+
         Ok(readings)
     }
 
     /// 设置风扇转速
     ///
+    /// 启用重复指令抑制时，与上次下发转速相同的设置不写入BMC，返回 `FanSpeedWrite::Unchanged`
+    ///
     /// # 参数
     /// * `fan_id` - 风扇ID
     /// * `speed_percent` - 转速百分比 (0-100)
-    pub async fn set_fan_speed(&self, fan_id: &str, speed_percent: f64) -> AppResult<()> {
+    pub async fn set_fan_speed(&self, fan_id: &str, speed_percent: f64) -> AppResult<FanSpeedWrite> {
+        self.ensure_writable("设置风扇转速")?;
+
         // 验证转速范围
         if !(0.0..=100.0).contains(&speed_percent) {
             return Err(AppError::validation_error(
@@ -111,6 +260,14 @@ impl FanService {
             ));
         }
 
+        // 仅有转速计的风扇不响应转速命令
+        if self.ipmi_service.is_fan_controllable(fan_id) == Some(false) {
+            return Err(AppError::validation_error(
+                "fan_id",
+                format!("风扇 {} 仅有转速计，不支持设置转速", fan_id),
+            ));
+        }
+
         // 检查风扇配置
         let config = self.get_fan_config(fan_id).await;
         if let Some(ref cfg) = config {
@@ -128,14 +285,161 @@ impl FanService {
             }
         }
 
+        self.commanded_speeds
+            .write()
+            .await
+            .insert(fan_id.to_string(), speed_percent);
+
+        // 故障转移期间叠加分摊负载
+        let effective_percent = self
+            .failover_targets()
+            .await
+            .get(fan_id)
+            .copied()
+            .unwrap_or(speed_percent);
+
+        if self.is_identical_write(fan_id, effective_percent) {
+            log::debug!("风扇 {} 转速已为 {}%，跳过下发", fan_id, effective_percent);
+            return Ok(FanSpeedWrite::Unchanged);
+        }
+
         // 设置风扇转速
-        self.ipmi_service
-            .set_fan_speed(fan_id, speed_percent as u8)
-            .await?;
+        if let Err(e) = self.ipmi_service.set_fan_speed(fan_id, effective_percent as u8) {
+            self.record_written_speed(fan_id, None);
+            return Err(AppError::ipmi_error(e.to_string()));
+        }
+        self.record_written_speed(fan_id, Some(effective_percent));
 
         // 记录操作
-        log::info!("设置风扇 {} 转速为 {}%", fan_id, speed_percent);
+        log::info!("设置风扇 {} 转速为 {}%", fan_id, effective_percent);
+
+        Ok(FanSpeedWrite::Applied)
+    }
+
+    /// 设置风扇故障转移策略
+    ///
+    /// # 参数
+    /// * `policy` - 散热区域及负载分摊配置
+    pub async fn set_failover_policy(&self, policy: FanFailoverPolicy) -> AppResult<()> {
+        for zone in &policy.zones {
+            if zone.fan_ids.is_empty() {
+                return Err(AppError::validation_error(
+                    "zones",
+                    &format!("散热区域 {} 至少需要一个风扇", zone.id),
+                ));
+            }
+            if !(0.0..=100.0).contains(&zone.max_speed_percent) {
+                return Err(AppError::validation_error(
+                    "max_speed_percent",
+                    "转速百分比必须在0-100之间",
+                ));
+            }
+        }
+        if policy.boost_factor < 0.0 {
+            return Err(AppError::validation_error(
+                "boost_factor",
+                "负载分摊系数不能为负数",
+            ));
+        }
+
+        *self.failover_policy.write().await = policy;
+        Ok(())
+    }
+
+    /// 获取风扇故障转移策略
+    pub async fn get_failover_policy(&self) -> FanFailoverPolicy {
+        self.failover_policy.read().await.clone()
+    }
+
+    /// 获取当前故障风扇列表
+    pub async fn get_fan_failures(&self) -> Vec<FanFailure> {
+        self.fan_failures.read().await.values().cloned().collect()
+    }
+
+    /// 维护人员解除风扇故障
+    ///
+    /// 解除后同区域风扇恢复基础指令转速；若风扇仍不转动会在下次检测时重新记录
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    pub async fn resolve_fan_failure(&self, fan_id: &str) -> AppResult<()> {
+        let removed = self.fan_failures.write().await.remove(fan_id);
+        if removed.is_none() {
+            return Err(AppError::not_found_error("风扇故障", fan_id));
+        }
+
+        log::info!("风扇 {} 故障已由维护解除", fan_id);
+        self.push_failover_targets(&self.failover_targets().await)
+            .await
+    }
+
+    /// 根据实测转速执行故障转移
+    ///
+    /// # 参数
+    /// * `rpm_readings` - 各风扇的实测转速
+    async fn apply_failover(&self, rpm_readings: &HashMap<String, f64>) -> AppResult<()> {
+        let policy = self.failover_policy.read().await.clone();
+        if policy.zones.is_empty() {
+            return Ok(());
+        }
+
+        let commanded = self.commanded_speeds.read().await.clone();
+        let targets = {
+            let mut failures = self.fan_failures.write().await;
+            let before: Vec<String> = failures.keys().cloned().collect();
+            let targets = policy.evaluate(&commanded, rpm_readings, &mut failures);
+
+            for failure in failures.values() {
+                if !before.contains(&failure.fan_id) {
+                    log::warn!(
+                        "检测到风扇 {} 故障（指令 {:.1}%，转速为0），区域 {} 内其他风扇将分担负载",
+                        failure.fan_id,
+                        failure.commanded_percent,
+                        failure.zone_id
+                    );
+                }
+            }
+            for fan_id in before.iter().filter(|id| !failures.contains_key(*id)) {
+                log::info!("风扇 {} 已恢复转动，解除故障转移", fan_id);
+            }
+
+            targets
+        };
+
+        self.push_failover_targets(&targets).await
+    }
+
+    /// 计算当前故障状态下各风扇的实际指令转速
+    async fn failover_targets(&self) -> HashMap<String, f64> {
+        let policy = self.failover_policy.read().await;
+        let commanded = self.commanded_speeds.read().await;
+        let failures = self.fan_failures.read().await;
+
+        // 不传入实测转速，仅根据已有故障记录计算
+        let mut snapshot = failures.clone();
+        policy.evaluate(&commanded, &HashMap::new(), &mut snapshot)
+    }
+
+    /// 下发故障转移后的指令转速
+    ///
+    /// # 参数
+    /// * `targets` - 风扇ID到指令转速百分比的映射
+    async fn push_failover_targets(&self, targets: &HashMap<String, f64>) -> AppResult<()> {
+        // 只读模式下仍记录故障用于告警，但不下发转速
+        if self.read_only {
+            return Ok(());
+        }
 
+        let failures = self.fan_failures.read().await;
+        for (fan_id, percent) in targets {
+            if failures.contains_key(fan_id) {
+                continue;
+            }
+            self.ipmi_service
+                .set_fan_speed(fan_id, *percent as u8)
+                .await?;
+            self.record_written_speed(fan_id, Some(*percent));
+        }
         Ok(())
     }
 
@@ -144,6 +448,7 @@ impl FanService {
     /// # 参数
     /// * `speed_percent` - 转速百分比 (0-100)
     pub async fn set_all_fans_speed(&self, speed_percent: f64) -> AppResult<()> {
+        self.ensure_writable("设置所有风扇转速")?;
         let fans = self.get_current_fan_status(None).await?;
 
         for fan in fans {
@@ -228,7 +533,21 @@ impl FanService {
     pub async fn get_fan_stats(&self, fan_id: &str, duration_hours: u64) -> AppResult<FanStats> {
         let end_time = Utc::now();
         let start_time = end_time - chrono::Duration::hours(duration_hours as i64);
+        self.get_fan_stats_in_range(fan_id, start_time, end_time).await
+    }
 
+    /// 获取指定时间段内的风扇统计信息
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `start_time` - 开始时间
+    /// * `end_time` - 结束时间
+    pub async fn get_fan_stats_in_range(
+        &self,
+        fan_id: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> AppResult<FanStats> {
         let query = FanQueryParams {
             fan_ids: Some(vec![fan_id.to_string()]),
             start_time: Some(start_time),
@@ -241,8 +560,8 @@ impl FanService {
 
         let readings = self.get_fan_history(&query).await?;
 
-        if readings.is_empty() {
-            return Err(AppError::not_found("指定时间范围内没有风扇数据"));
+        if readings.len() < self.min_sample_count {
+            return Err(AppError::insufficient_data_error(self.min_sample_count, readings.len()));
         }
 
         let speed_values: Vec<f64> = readings.iter().map(|r| r.speed_percent).collect();
@@ -334,7 +653,7 @@ impl FanService {
     /// # 参数
     /// * `current_temperature` - 当前温度
     pub async fn execute_auto_control(&self, current_temperature: f64) -> AppResult<()> {
-        if !self.is_auto_control_enabled().await {
+        if !self.is_auto_control_enabled().await || self.read_only {
             return Ok(());
         }
 
@@ -411,6 +730,13 @@ impl FanService {
         Ok(fans.keys().cloned().collect())
     }
 
+    /// 获取可调速的风扇列表，排除仅有转速计的风扇
+    pub async fn get_controllable_fan_list(&self) -> AppResult<Vec<String>> {
+        let mut fans = self.get_fan_list().await?;
+        fans.retain(|fan_id| self.ipmi_service.is_fan_controllable(fan_id) != Some(false));
+        Ok(fans)
+    }
+
     /// 测试风扇
     ///
     /// # 参数
@@ -648,6 +974,62 @@ impl FanService {
     }
 }
 
+/// 风扇转速读写接口
+///
+/// 控制策略通过该接口批量下发转速，便于在失败时回滚
+#[async_trait]
+pub trait FanSpeedDriver: Send + Sync {
+    /// 获取所有可调速的风扇ID
+    async fn fan_ids(&self) -> AppResult<Vec<String>>;
+
+    /// 读取风扇当前转速百分比
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    async fn current_speed(&self, fan_id: &str) -> AppResult<f64>;
+
+    /// 设置风扇转速百分比
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `speed_percent` - 转速百分比 (0-100)
+    async fn set_speed(&self, fan_id: &str, speed_percent: f64) -> AppResult<()>;
+}
+
+#[async_trait]
+impl FanSpeedDriver for FanService {
+    async fn fan_ids(&self) -> AppResult<Vec<String>> {
+        self.get_controllable_fan_list().await
+    }
+
+    async fn current_speed(&self, fan_id: &str) -> AppResult<f64> {
+        self.get_current_fan_status(Some(fan_id))
+            .await?
+            .first()
+            .map(|reading| reading.speed_percent)
+            .ok_or_else(|| AppError::not_found_error("fan", fan_id))
+    }
+
+    async fn set_speed(&self, fan_id: &str, speed_percent: f64) -> AppResult<()> {
+        self.set_fan_speed(fan_id, speed_percent).await.map(|_| ())
+    }
+}
+
+#[async_trait]
+impl KickstartFan for FanService {
+    async fn rpm(&self, fan_id: &str) -> AppResult<f64> {
+        self.get_current_fan_status(Some(fan_id))
+            .await?
+            .first()
+            .map(|reading| reading.speed_rpm)
+            .ok_or_else(|| AppError::not_found_error("fan", fan_id))
+    }
+
+    async fn set_duty(&self, fan_id: &str, speed_percent: f64) -> AppResult<()> {
+        self.set_fan_speed(fan_id, speed_percent).await.map(|_| ())
+    }
+}
+
 /// 风扇测试结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FanTestResult {
@@ -680,6 +1062,7 @@ pub struct FanSpeedTest {
 mod tests {
     use super::*;
     use crate::models::config::IpmiConfig;
+    use crate::services::ipmi_service::{FanControlMode, IpmiService};
 
     #[tokio::test]
     async fn test_fan_service_creation() {
@@ -785,4 +1168,129 @@ mod tests {
         let speed_high = fan_service.calculate_fan_speed_from_curve(&curve, 90.0);
         assert_eq!(speed_high, 100.0); // 高于最高温度
     }
+
+    fn read_only_fan_service() -> FanService {
+        FanService::new(
+            Arc::new(IpmiService::new(crate::services::ipmi_service::IpmiConfig::default())),
+            MonitoringConfig::default(),
+        )
+        .with_read_only(true)
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_fan_writes() {
+        let fan_service = read_only_fan_service();
+
+        let err = fan_service.set_fan_speed("FAN1", 50.0).await.unwrap_err();
+        assert_eq!(err.status_code(), 403);
+        assert!(fan_service.commanded_speeds.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_commanding_current_speed_skips_ipmi_write() {
+        // 空命令序列使下发不调用ipmitool，仅通过控制模式标记观察是否发生写入
+        let ipmi_service = Arc::new(
+            IpmiService::new(crate::services::ipmi_service::IpmiConfig::default()).with_fan_control_commands(
+                crate::config::FanControlCommands {
+                    manual_mode: vec![],
+                    automatic_mode: vec![],
+                    set_speed: vec![],
+                },
+            ),
+        );
+        let fan_service = FanService::new(Arc::clone(&ipmi_service), MonitoringConfig::default());
+
+        assert_eq!(fan_service.set_fan_speed("FAN1", 50.0).await.unwrap(), FanSpeedWrite::Applied);
+        assert_eq!(ipmi_service.fan_control_mode(), Some(FanControlMode::Manual));
+
+        ipmi_service.set_fan_control_mode(FanControlMode::Automatic).unwrap();
+        assert_eq!(fan_service.set_fan_speed("FAN1", 50.3).await.unwrap(), FanSpeedWrite::Unchanged);
+        assert_eq!(ipmi_service.fan_control_mode(), Some(FanControlMode::Automatic));
+
+        assert_eq!(fan_service.set_fan_speed("FAN1", 60.0).await.unwrap(), FanSpeedWrite::Applied);
+        assert_eq!(ipmi_service.fan_control_mode(), Some(FanControlMode::Manual));
+
+        // 关闭抑制后相同转速仍会下发
+        let fan_service = fan_service.with_suppress_identical_writes(false);
+        ipmi_service.set_fan_control_mode(FanControlMode::Automatic).unwrap();
+        assert_eq!(fan_service.set_fan_speed("FAN1", 60.0).await.unwrap(), FanSpeedWrite::Applied);
+        assert_eq!(ipmi_service.fan_control_mode(), Some(FanControlMode::Manual));
+    }
+
+    #[tokio::test]
+    async fn test_setting_speed_on_tach_only_fan_is_rejected() {
+        let ipmi_service = Arc::new(IpmiService::new(crate::services::ipmi_service::IpmiConfig::default()));
+        let sdr = "PSU1 Fan         | 71h | ok  | 10.1 | 5200 RPM\n";
+        ipmi_service.record_fan_controllability(&crate::services::ipmi_service::parse_fan_sdr(sdr, Utc::now()));
+        let fan_service = FanService::new(Arc::clone(&ipmi_service), MonitoringConfig::default());
+
+        let err = fan_service.set_fan_speed("PSU1_FAN", 50.0).await.unwrap_err();
+        assert_eq!(err.status_code(), 400);
+        assert!(fan_service.commanded_speeds.read().await.is_empty());
+        assert_eq!(ipmi_service.fan_control_mode(), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_monitoring_still_detects_failures() {
+        let fan_service = read_only_fan_service();
+        fan_service
+            .set_failover_policy(FanFailoverPolicy {
+                zones: vec![CoolingZone {
+                    id: "cpu".to_string(),
+                    name: "CPU区域".to_string(),
+                    sensor_ids: vec![],
+                    fan_ids: vec!["FAN1".to_string(), "FAN2".to_string()],
+                    max_speed_percent: 100.0,
+                }],
+                boost_factor: 1.0,
+            })
+            .await
+            .unwrap();
+        fan_service
+            .commanded_speeds
+            .write()
+            .await
+            .insert("FAN1".to_string(), 40.0);
+
+        // 监控路径：检测故障用于告警，但不下发故障转移转速
+        let rpm = HashMap::from([("FAN1".to_string(), 0.0), ("FAN2".to_string(), 2400.0)]);
+        fan_service.apply_failover(&rpm).await.unwrap();
+
+        let failures = fan_service.get_fan_failures().await;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].fan_id, "FAN1");
+    }
+
+    #[test]
+    fn test_failover_boosts_remaining_fan_in_zone() {
+        let policy = FanFailoverPolicy {
+            zones: vec![CoolingZone {
+                id: "cpu".to_string(),
+                name: "CPU区域".to_string(),
+                sensor_ids: vec!["CPU1 Temp".to_string()],
+                fan_ids: vec!["FAN1".to_string(), "FAN2".to_string()],
+                max_speed_percent: 100.0,
+            }],
+            boost_factor: 1.0,
+        };
+        let commanded = HashMap::from([("FAN1".to_string(), 40.0), ("FAN2".to_string(), 40.0)]);
+        let mut failures = HashMap::new();
+
+        // 两个风扇正常运行时保持基础指令
+        let healthy_rpm = HashMap::from([("FAN1".to_string(), 2400.0), ("FAN2".to_string(), 2400.0)]);
+        let targets = policy.evaluate(&commanded, &healthy_rpm, &mut failures);
+        assert!(failures.is_empty());
+        assert_eq!(targets["FAN2"], 40.0);
+
+        // FAN1有指令但转速为0，FAN2承担其负载
+        let failed_rpm = HashMap::from([("FAN1".to_string(), 0.0), ("FAN2".to_string(), 2400.0)]);
+        let targets = policy.evaluate(&commanded, &failed_rpm, &mut failures);
+        assert!(failures.contains_key("FAN1"));
+        assert_eq!(targets["FAN2"], 80.0);
+
+        // FAN1恢复后FAN2回到基础指令
+        let targets = policy.evaluate(&commanded, &healthy_rpm, &mut failures);
+        assert!(failures.is_empty());
+        assert_eq!(targets["FAN2"], 40.0);
+    }
 }
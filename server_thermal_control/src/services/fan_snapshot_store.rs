@@ -0,0 +1,168 @@
+use crate::models::error::AppResult;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+/// 快照默认有效期（小时）
+pub const DEFAULT_FAN_SNAPSHOT_TTL_HOURS: i64 = 24;
+
+/// 风扇转速快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FanSpeedSnapshot {
+    /// 快照ID
+    pub id: String,
+    /// 各风扇的转速百分比
+    pub speeds: BTreeMap<String, f64>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 过期时间，过期后不能再恢复
+    pub expires_at: DateTime<Utc>,
+}
+
+impl FanSpeedSnapshot {
+    /// 快照在给定时间是否已过期
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// 风扇转速快照存储
+#[async_trait]
+pub trait FanSnapshotStore: Send + Sync {
+    /// 保存快照
+    ///
+    /// # 参数
+    /// * `snapshot` - 快照
+    async fn save(&self, snapshot: &FanSpeedSnapshot) -> AppResult<()>;
+
+    /// 读取快照，不存在或已过期时返回None
+    ///
+    /// # 参数
+    /// * `id` - 快照ID
+    /// * `now` - 当前时间
+    async fn get(&self, id: &str, now: DateTime<Utc>) -> AppResult<Option<FanSpeedSnapshot>>;
+
+    /// 删除已过期的快照，返回删除数量
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    async fn purge_expired(&self, now: DateTime<Utc>) -> AppResult<u64>;
+}
+
+/// 进程内快照存储
+#[derive(Default)]
+pub struct InMemoryFanSnapshotStore {
+    snapshots: RwLock<HashMap<String, FanSpeedSnapshot>>,
+}
+
+impl InMemoryFanSnapshotStore {
+    /// 创建进程内快照存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl FanSnapshotStore for InMemoryFanSnapshotStore {
+    async fn save(&self, snapshot: &FanSpeedSnapshot) -> AppResult<()> {
+        self.snapshots
+            .write()
+            .await
+            .insert(snapshot.id.clone(), snapshot.clone());
+        Ok(())
+    }
+
+    async fn get(&self, id: &str, now: DateTime<Utc>) -> AppResult<Option<FanSpeedSnapshot>> {
+        Ok(self
+            .snapshots
+            .read()
+            .await
+            .get(id)
+            .filter(|snapshot| !snapshot.is_expired(now))
+            .cloned())
+    }
+
+    async fn purge_expired(&self, now: DateTime<Utc>) -> AppResult<u64> {
+        let mut snapshots = self.snapshots.write().await;
+        let before = snapshots.len();
+        snapshots.retain(|_, snapshot| !snapshot.is_expired(now));
+        Ok((before - snapshots.len()) as u64)
+    }
+}
+
+/// PostgreSQL快照存储
+///
+/// 写入 `scripts/init.sql` 中定义的 `fan_speed_snapshots` 表，转速以JSON文本保存
+pub struct PgFanSnapshotStore {
+    pool: PgPool,
+}
+
+impl PgFanSnapshotStore {
+    /// 创建数据库快照存储
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl FanSnapshotStore for PgFanSnapshotStore {
+    #[instrument(level = "debug", name = "db.fan_speed_snapshots.save", skip_all)]
+    async fn save(&self, snapshot: &FanSpeedSnapshot) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO fan_speed_snapshots (id, speeds, created_at, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(&snapshot.id)
+        .bind(serde_json::to_string(&snapshot.speeds)?)
+        .bind(snapshot.created_at)
+        .bind(snapshot.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "db.fan_speed_snapshots.get", skip_all)]
+    async fn get(&self, id: &str, now: DateTime<Utc>) -> AppResult<Option<FanSpeedSnapshot>> {
+        let row = sqlx::query(
+            "SELECT id, speeds, created_at, expires_at FROM fan_speed_snapshots WHERE id = $1 AND expires_at > $2",
+        )
+        .bind(id)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| -> AppResult<FanSpeedSnapshot> {
+            let speeds: String = row.try_get("speeds")?;
+            Ok(FanSpeedSnapshot {
+                id: row.try_get("id")?,
+                speeds: serde_json::from_str(&speeds)?,
+                created_at: row.try_get("created_at")?,
+                expires_at: row.try_get("expires_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    #[instrument(level = "debug", name = "db.fan_speed_snapshots.purge_expired", skip_all)]
+    async fn purge_expired(&self, now: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM fan_speed_snapshots WHERE expires_at <= $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
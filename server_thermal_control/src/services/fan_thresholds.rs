@@ -0,0 +1,207 @@
+use crate::models::error::{AppError, AppResult};
+use crate::services::ipmi_service::{ReadOnlyIpmi, ReadOnlyIpmiCommand};
+use serde::Serialize;
+
+/// BMC为单个风扇设置的转速阈值（RPM），未设置的阈值为空
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BmcFanThresholds {
+    pub fan_id: String,
+    pub current_rpm: Option<f64>,
+    pub lower_non_recoverable: Option<f64>,
+    pub lower_critical: Option<f64>,
+    pub lower_non_critical: Option<f64>,
+    pub upper_non_critical: Option<f64>,
+    pub upper_critical: Option<f64>,
+    pub upper_non_recoverable: Option<f64>,
+}
+
+impl BmcFanThresholds {
+    /// 解析 `ipmitool sensor` 的输出，只保留单位为RPM的风扇传感器
+    ///
+    /// 每行格式为 `名称 | 读数 | 单位 | 状态 | lnr | lcr | lnc | unc | ucr | unr`，`na` 表示未设置
+    ///
+    /// # 参数
+    /// * `output` - `ipmitool sensor` 的原始输出
+    pub fn parse(output: &str) -> Vec<Self> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+                if fields.len() < 10 || !fields[2].eq_ignore_ascii_case("RPM") || fields[0].is_empty() {
+                    return None;
+                }
+                let value = |index: usize| fields[index].parse::<f64>().ok();
+                Some(Self {
+                    fan_id: fields[0].to_string(),
+                    current_rpm: value(1),
+                    lower_non_recoverable: value(4),
+                    lower_critical: value(5),
+                    lower_non_critical: value(6),
+                    upper_non_critical: value(7),
+                    upper_critical: value(8),
+                    upper_non_recoverable: value(9),
+                })
+            })
+            .collect()
+    }
+}
+
+/// 配置与BMC阈值冲突的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdConflictSeverity {
+    /// 触发BMC的非严重告警
+    Warning,
+    /// 触发BMC的严重或不可恢复告警，BMC可能自行拉高风扇转速
+    Critical,
+}
+
+/// 一项配置与BMC阈值的冲突
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FanThresholdConflict {
+    pub fan_id: String,
+    /// 冲突的BMC阈值，如 `lower_critical`
+    pub threshold: &'static str,
+    pub bmc_rpm: f64,
+    /// 冲突的配置项，`fan_min_speed` 或 `fan_max_speed`
+    pub config_key: &'static str,
+    pub configured_rpm: f64,
+    pub severity: ThresholdConflictSeverity,
+    pub detail: String,
+}
+
+/// 配置与BMC风扇阈值的对比结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FanThresholdReport {
+    pub configured_min_rpm: f64,
+    pub configured_max_rpm: f64,
+    pub fans: Vec<BmcFanThresholds>,
+    pub conflicts: Vec<FanThresholdConflict>,
+}
+
+/// 对比配置的风扇转速范围与BMC阈值
+///
+/// 配置的最低转速不高于BMC下限阈值时，风扇降到最低转速就会触发BMC告警；
+/// 配置的最高转速不低于BMC上限阈值时同理
+///
+/// # 参数
+/// * `fans` - BMC风扇阈值
+/// * `min_rpm` - 配置的最低转速（`control.fan_min_speed`）
+/// * `max_rpm` - 配置的最高转速（`control.fan_max_speed`）
+pub fn find_conflicts(fans: &[BmcFanThresholds], min_rpm: f64, max_rpm: f64) -> Vec<FanThresholdConflict> {
+    use ThresholdConflictSeverity::{Critical, Warning};
+
+    let mut conflicts = Vec::new();
+    for fan in fans {
+        let lower = [
+            ("lower_non_recoverable", fan.lower_non_recoverable, Critical),
+            ("lower_critical", fan.lower_critical, Critical),
+            ("lower_non_critical", fan.lower_non_critical, Warning),
+        ];
+        for (threshold, bmc_rpm, severity) in lower {
+            let Some(bmc_rpm) = bmc_rpm.filter(|rpm| *rpm >= min_rpm) else {
+                continue;
+            };
+            conflicts.push(FanThresholdConflict {
+                fan_id: fan.fan_id.clone(),
+                threshold,
+                bmc_rpm,
+                config_key: "fan_min_speed",
+                configured_rpm: min_rpm,
+                severity,
+                detail: format!(
+                    "风扇 {} 的BMC {} 为 {:.0} RPM，不低于配置的最低转速 {:.0} RPM",
+                    fan.fan_id, threshold, bmc_rpm, min_rpm
+                ),
+            });
+        }
+
+        let upper = [
+            ("upper_non_critical", fan.upper_non_critical, Warning),
+            ("upper_critical", fan.upper_critical, Critical),
+            ("upper_non_recoverable", fan.upper_non_recoverable, Critical),
+        ];
+        for (threshold, bmc_rpm, severity) in upper {
+            let Some(bmc_rpm) = bmc_rpm.filter(|rpm| *rpm <= max_rpm) else {
+                continue;
+            };
+            conflicts.push(FanThresholdConflict {
+                fan_id: fan.fan_id.clone(),
+                threshold,
+                bmc_rpm,
+                config_key: "fan_max_speed",
+                configured_rpm: max_rpm,
+                severity,
+                detail: format!(
+                    "风扇 {} 的BMC {} 为 {:.0} RPM，不高于配置的最高转速 {:.0} RPM",
+                    fan.fan_id, threshold, bmc_rpm, max_rpm
+                ),
+            });
+        }
+    }
+    conflicts
+}
+
+/// 读取BMC风扇阈值并与配置的转速范围对比
+///
+/// # 参数
+/// * `ipmi` - 只读IPMI执行器
+/// * `min_rpm` - 配置的最低转速
+/// * `max_rpm` - 配置的最高转速
+pub fn check_fan_thresholds(ipmi: &dyn ReadOnlyIpmi, min_rpm: f64, max_rpm: f64) -> AppResult<FanThresholdReport> {
+    let output = ipmi
+        .run_read_only(ReadOnlyIpmiCommand::Sensor)
+        .map_err(|e| AppError::ipmi_error(format!("读取BMC传感器阈值失败: {}", e)))?;
+    let fans = BmcFanThresholds::parse(&output);
+
+    Ok(FanThresholdReport {
+        configured_min_rpm: min_rpm,
+        configured_max_rpm: max_rpm,
+        conflicts: find_conflicts(&fans, min_rpm, max_rpm),
+        fans,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeIpmi(&'static str);
+
+    impl ReadOnlyIpmi for FakeIpmi {
+        fn run_read_only(&self, command: ReadOnlyIpmiCommand) -> Result<String, Box<dyn std::error::Error>> {
+            assert_eq!(command, ReadOnlyIpmiCommand::Sensor);
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn test_bmc_lower_critical_above_configured_min_is_reported() {
+        let ipmi = FakeIpmi(
+            "CPU Temp         | 45.000     | degrees C  | ok    | 0.000     | 0.000     | 0.000     | 85.000    | 90.000    | 105.000\n\
+             FAN1             | 3600.000   | RPM        | ok    | 300.000   | 500.000   | 700.000   | 25300.000 | 25400.000 | 25500.000\n\
+             FAN2             | 4100.000   | RPM        | ok    | 900.000   | 1200.000  | 1400.000  | na        | na        | na\n",
+        );
+
+        let report = check_fan_thresholds(&ipmi, 1000.0, 5000.0).unwrap();
+        assert_eq!(report.fans.len(), 2);
+        assert_eq!(report.fans[1].lower_critical, Some(1200.0));
+        assert_eq!(report.fans[1].upper_critical, None);
+
+        // FAN1 的阈值都在配置范围之外；FAN2 的 lcr、lnc 高于配置的最低转速
+        let found: Vec<(&str, &str, ThresholdConflictSeverity)> = report
+            .conflicts
+            .iter()
+            .map(|c| (c.fan_id.as_str(), c.threshold, c.severity))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("FAN2", "lower_critical", ThresholdConflictSeverity::Critical),
+                ("FAN2", "lower_non_critical", ThresholdConflictSeverity::Warning),
+            ]
+        );
+        assert_eq!(report.conflicts[0].bmc_rpm, 1200.0);
+        assert_eq!(report.conflicts[0].config_key, "fan_min_speed");
+    }
+}
@@ -0,0 +1,412 @@
+use crate::models::config::{IpmiConfig as ChassisIpmiConfig, ServerTarget};
+use crate::models::error::AppResult;
+use crate::models::sensor::{SensorStatus, SensorType};
+use crate::utils::ipmi::{IpmiClient, SensorData};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// 多机箱汇总配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetSummaryConfig {
+    /// 参与汇总的机箱BMC，为空时不提供汇总
+    #[serde(default)]
+    pub chassis: Vec<ServerTarget>,
+    /// 风扇满速转速（RPM），以RPM上报的风扇按此折算占空比
+    #[serde(default = "default_fan_max_rpm")]
+    pub fan_max_rpm: f64,
+    /// 同时轮询的机箱数上限
+    #[serde(default = "default_max_concurrent_polls")]
+    pub max_concurrent_polls: usize,
+}
+
+impl Default for FleetSummaryConfig {
+    fn default() -> Self {
+        Self {
+            chassis: Vec::new(),
+            fan_max_rpm: default_fan_max_rpm(),
+            max_concurrent_polls: default_max_concurrent_polls(),
+        }
+    }
+}
+
+fn default_fan_max_rpm() -> f64 {
+    12000.0
+}
+
+fn default_max_concurrent_polls() -> usize {
+    4
+}
+
+/// 机箱健康状况，按严重程度排序
+///
+/// 无法访问排在严重之前：确认的过热比暂时失联更需要处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChassisHealth {
+    /// 所有传感器正常
+    Healthy,
+    /// 有传感器超过非临界阈值
+    Warning,
+    /// BMC无法访问
+    Unreachable,
+    /// 有传感器超过临界阈值
+    Critical,
+}
+
+/// 温度最高的传感器
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HottestSensor {
+    /// 所在机箱ID
+    pub chassis_id: String,
+    /// 传感器ID
+    pub sensor_id: String,
+    /// 传感器名称
+    pub sensor_name: String,
+    /// 温度（°C）
+    pub temperature: f64,
+}
+
+/// 单个机箱的散热汇总
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChassisSummary {
+    /// 机箱ID
+    pub chassis_id: String,
+    /// 机箱名称
+    pub name: String,
+    /// 健康状况
+    pub health: ChassisHealth,
+    /// 温度最高的传感器，没有有效温度读数时为空
+    pub hottest_sensor: Option<HottestSensor>,
+    /// 风扇平均占空比（%），没有有效风扇读数时为空
+    pub average_fan_duty: Option<f64>,
+    /// 超过阈值的传感器数
+    pub active_alerts: usize,
+    /// 轮询失败的原因
+    pub error: Option<String>,
+}
+
+/// 全部机箱的汇总
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FleetRollup {
+    /// 机箱总数
+    pub chassis_total: usize,
+    /// 健康的机箱数
+    pub healthy: usize,
+    /// 告警的机箱数
+    pub warning: usize,
+    /// 严重的机箱数
+    pub critical: usize,
+    /// 无法访问的机箱数
+    pub unreachable: usize,
+    /// 全部机箱中温度最高的传感器
+    pub hottest_sensor: Option<HottestSensor>,
+    /// 全部风扇的平均占空比（%）
+    pub average_fan_duty: Option<f64>,
+    /// 超过阈值的传感器总数
+    pub active_alerts: usize,
+    /// 最差的机箱健康状况，没有机箱时为健康
+    pub health: ChassisHealth,
+}
+
+/// `GET /api/v1/summary` 的响应
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetSummary {
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+    /// 各机箱汇总，顺序与配置一致
+    pub chassis: Vec<ChassisSummary>,
+    /// 全部机箱的汇总
+    pub rollup: FleetRollup,
+}
+
+/// 机箱传感器轮询
+#[async_trait]
+pub trait ChassisPoller: Send + Sync {
+    /// 轮询所有机箱，返回 `(机箱ID, 传感器读数)`
+    async fn poll_chassis(&self) -> Vec<(String, AppResult<Vec<SensorData>>)>;
+}
+
+#[async_trait]
+impl ChassisPoller for IpmiClient {
+    async fn poll_chassis(&self) -> Vec<(String, AppResult<Vec<SensorData>>)> {
+        self.poll_all_servers().await
+    }
+}
+
+/// 多机箱散热汇总服务
+pub struct FleetSummaryService {
+    config: FleetSummaryConfig,
+    poller: Arc<dyn ChassisPoller>,
+}
+
+impl FleetSummaryService {
+    /// 创建汇总服务
+    ///
+    /// # 参数
+    /// * `config` - 汇总配置
+    /// * `poller` - 机箱轮询，如 `IpmiClient`
+    pub fn new(config: FleetSummaryConfig, poller: Arc<dyn ChassisPoller>) -> Self {
+        Self { config, poller }
+    }
+
+    /// 根据配置创建通过IPMI轮询机箱的汇总服务，未配置机箱时为空
+    ///
+    /// # 参数
+    /// * `config` - 汇总配置
+    /// * `ipmi` - 本机IPMI配置，沿用其超时与重试设置
+    pub fn from_config(config: &FleetSummaryConfig, ipmi: &crate::config::IpmiConfig) -> Option<Self> {
+        if config.chassis.is_empty() {
+            return None;
+        }
+
+        let client = IpmiClient::new(ChassisIpmiConfig {
+            servers: config.chassis.clone(),
+            ipmitool_path: "ipmitool".to_string(),
+            default_timeout: ipmi.timeout,
+            retry_count: ipmi.retries,
+            retry_interval: 1,
+            batch_sdr: true,
+            max_concurrent_polls: config.max_concurrent_polls.max(1),
+        });
+        Some(Self::new(config.clone(), Arc::new(client)))
+    }
+
+    /// 轮询所有机箱并生成汇总
+    pub async fn summary(&self) -> FleetSummary {
+        let chassis: Vec<ChassisAggregate> = self
+            .poller
+            .poll_chassis()
+            .await
+            .into_iter()
+            .map(|(chassis_id, result)| {
+                let name = self
+                    .config
+                    .chassis
+                    .iter()
+                    .find(|target| target.id == chassis_id)
+                    .map(|target| target.name.clone())
+                    .unwrap_or_else(|| chassis_id.clone());
+                summarize_chassis(chassis_id, name, result, self.config.fan_max_rpm)
+            })
+            .collect();
+
+        let fan_duties: Vec<f64> = chassis.iter().flat_map(|c| c.fan_duties.iter().copied()).collect();
+        let chassis: Vec<ChassisSummary> = chassis.into_iter().map(|c| c.summary).collect();
+        let rollup = rollup(&chassis, &fan_duties);
+
+        FleetSummary {
+            generated_at: Utc::now(),
+            chassis,
+            rollup,
+        }
+    }
+}
+
+/// 机箱汇总及其各风扇占空比，占空比用于计算全局平均值
+struct ChassisAggregate {
+    summary: ChassisSummary,
+    fan_duties: Vec<f64>,
+}
+
+/// 汇总单个机箱的传感器读数
+///
+/// 读数为错误或不可用的传感器不参与温度和占空比的计算
+fn summarize_chassis(
+    chassis_id: String,
+    name: String,
+    result: AppResult<Vec<SensorData>>,
+    fan_max_rpm: f64,
+) -> ChassisAggregate {
+    let sensors = match result {
+        Ok(sensors) => sensors,
+        Err(e) => {
+            return ChassisAggregate {
+                summary: ChassisSummary {
+                    chassis_id,
+                    name,
+                    health: ChassisHealth::Unreachable,
+                    hottest_sensor: None,
+                    average_fan_duty: None,
+                    active_alerts: 0,
+                    error: Some(e.to_string()),
+                },
+                fan_duties: Vec::new(),
+            };
+        }
+    };
+
+    let valid = |sensor: &&SensorData| !matches!(sensor.status, SensorStatus::Error | SensorStatus::NotAvailable);
+    let hottest_sensor = sensors
+        .iter()
+        .filter(valid)
+        .filter(|sensor| matches!(sensor.sensor_type, SensorType::Temperature))
+        .max_by(|a, b| a.current_value.total_cmp(&b.current_value))
+        .map(|sensor| HottestSensor {
+            chassis_id: chassis_id.clone(),
+            sensor_id: sensor.sensor_id.clone(),
+            sensor_name: sensor.sensor_name.clone(),
+            temperature: sensor.current_value as f64,
+        });
+    let fan_duties: Vec<f64> = sensors
+        .iter()
+        .filter(valid)
+        .filter(|sensor| matches!(sensor.sensor_type, SensorType::Fan))
+        .map(|sensor| fan_duty(sensor, fan_max_rpm))
+        .collect();
+
+    let critical = sensors.iter().filter(|s| matches!(s.status, SensorStatus::Critical)).count();
+    let warning = sensors.iter().filter(|s| matches!(s.status, SensorStatus::Warning)).count();
+    let health = if critical > 0 {
+        ChassisHealth::Critical
+    } else if warning > 0 {
+        ChassisHealth::Warning
+    } else {
+        ChassisHealth::Healthy
+    };
+
+    ChassisAggregate {
+        summary: ChassisSummary {
+            chassis_id,
+            name,
+            health,
+            hottest_sensor,
+            average_fan_duty: average(&fan_duties),
+            active_alerts: critical + warning,
+            error: None,
+        },
+        fan_duties,
+    }
+}
+
+/// 风扇占空比（%），以百分比上报的直接使用，以RPM上报的按满速转速折算
+fn fan_duty(sensor: &SensorData, fan_max_rpm: f64) -> f64 {
+    let value = sensor.current_value as f64;
+    let duty = if sensor.unit.contains('%') || sensor.unit.eq_ignore_ascii_case("percent") {
+        value
+    } else {
+        value / fan_max_rpm.max(f64::EPSILON) * 100.0
+    };
+    duty.clamp(0.0, 100.0)
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+/// 汇总全部机箱
+fn rollup(chassis: &[ChassisSummary], fan_duties: &[f64]) -> FleetRollup {
+    let count = |health: ChassisHealth| chassis.iter().filter(|c| c.health == health).count();
+
+    FleetRollup {
+        chassis_total: chassis.len(),
+        healthy: count(ChassisHealth::Healthy),
+        warning: count(ChassisHealth::Warning),
+        critical: count(ChassisHealth::Critical),
+        unreachable: count(ChassisHealth::Unreachable),
+        hottest_sensor: chassis
+            .iter()
+            .filter_map(|c| c.hottest_sensor.as_ref())
+            .max_by(|a, b| a.temperature.total_cmp(&b.temperature))
+            .cloned(),
+        average_fan_duty: average(fan_duties),
+        active_alerts: chassis.iter().map(|c| c.active_alerts).sum(),
+        health: chassis.iter().map(|c| c.health).max().unwrap_or(ChassisHealth::Healthy),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeChassis;
+
+    fn sensor(id: &str, sensor_type: SensorType, value: f32, unit: &str, status: SensorStatus) -> SensorData {
+        SensorData {
+            sensor_id: id.to_string(),
+            sensor_name: id.to_string(),
+            sensor_type,
+            current_value: value,
+            unit: unit.to_string(),
+            status,
+            thresholds: None,
+        }
+    }
+
+    #[async_trait]
+    impl ChassisPoller for FakeChassis {
+        async fn poll_chassis(&self) -> Vec<(String, AppResult<Vec<SensorData>>)> {
+            vec![
+                (
+                    "hot".to_string(),
+                    Ok(vec![
+                        sensor("CPU1 Temp", SensorType::Temperature, 92.0, "degrees C", SensorStatus::Critical),
+                        sensor("Inlet Temp", SensorType::Temperature, 41.0, "degrees C", SensorStatus::Warning),
+                        sensor("FAN1", SensorType::Fan, 10800.0, "RPM", SensorStatus::Ok),
+                        sensor("FAN2", SensorType::Fan, 12000.0, "RPM", SensorStatus::Ok),
+                    ]),
+                ),
+                (
+                    "cool".to_string(),
+                    Ok(vec![
+                        sensor("CPU1 Temp", SensorType::Temperature, 45.0, "degrees C", SensorStatus::Ok),
+                        sensor("FAN1", SensorType::Fan, 30.0, "percent", SensorStatus::Ok),
+                        sensor("FAN2", SensorType::Fan, 0.0, "RPM", SensorStatus::NotAvailable),
+                    ]),
+                ),
+            ]
+        }
+    }
+
+    fn target(id: &str, name: &str) -> ServerTarget {
+        ServerTarget {
+            id: id.to_string(),
+            name: name.to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 623,
+            username: "admin".to_string(),
+            password: "admin".to_string(),
+            interface: "lanplus".to_string(),
+            enabled: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_summary_of_hot_and_cool_chassis() {
+        let config = FleetSummaryConfig {
+            chassis: vec![target("hot", "Rack A"), target("cool", "Rack B")],
+            fan_max_rpm: 12000.0,
+            max_concurrent_polls: 2,
+        };
+        let summary = FleetSummaryService::new(config, Arc::new(FakeChassis)).summary().await;
+
+        let hot = &summary.chassis[0];
+        assert_eq!(hot.name, "Rack A");
+        assert_eq!(hot.health, ChassisHealth::Critical);
+        assert_eq!(hot.hottest_sensor.as_ref().unwrap().sensor_id, "CPU1 Temp");
+        assert_eq!(hot.hottest_sensor.as_ref().unwrap().temperature, 92.0);
+        assert_eq!(hot.average_fan_duty, Some(95.0));
+        assert_eq!(hot.active_alerts, 2);
+
+        // 不可用的风扇读数不参与平均
+        let cool = &summary.chassis[1];
+        assert_eq!(cool.health, ChassisHealth::Healthy);
+        assert_eq!(cool.hottest_sensor.as_ref().unwrap().temperature, 45.0);
+        assert_eq!(cool.average_fan_duty, Some(30.0));
+        assert_eq!(cool.active_alerts, 0);
+
+        let rollup = &summary.rollup;
+        assert_eq!(rollup.chassis_total, 2);
+        assert_eq!((rollup.healthy, rollup.warning, rollup.critical, rollup.unreachable), (1, 0, 1, 0));
+        assert_eq!(rollup.hottest_sensor.as_ref().unwrap().chassis_id, "hot");
+        // 三个有效风扇：90%、100%、30%
+        assert!((rollup.average_fan_duty.unwrap() - 220.0 / 3.0).abs() < 1e-9);
+        assert_eq!(rollup.active_alerts, 2);
+        assert_eq!(rollup.health, ChassisHealth::Critical);
+    }
+}
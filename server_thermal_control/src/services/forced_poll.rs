@@ -0,0 +1,49 @@
+use crate::models::error::{AppError, AppResult};
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
+
+/// 两次强制轮询之间的默认最小间隔（秒）
+pub const DEFAULT_FORCED_POLL_MIN_INTERVAL_SECS: u64 = 10;
+
+/// 强制轮询限流
+///
+/// 两次强制轮询之间至少间隔 `monitoring.forced_poll_min_interval_secs` 秒，避免频繁请求压垮BMC；
+/// 间隔从上一次被接受的请求开始计算，进行中的轮询也占用间隔
+#[derive(Debug, Clone)]
+pub struct ForcedPollGate {
+    min_interval: Duration,
+    last_accepted: Arc<Mutex<Option<DateTime<Utc>>>>,
+}
+
+impl ForcedPollGate {
+    /// 创建限流
+    ///
+    /// # 参数
+    /// * `min_interval_secs` - 最小间隔（秒），为0时不限流
+    pub fn new(min_interval_secs: u64) -> Self {
+        Self {
+            min_interval: Duration::seconds(min_interval_secs as i64),
+            last_accepted: Arc::default(),
+        }
+    }
+
+    /// 申请一次强制轮询，距上一次不足最小间隔时返回429
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn acquire(&self, now: DateTime<Utc>) -> AppResult<()> {
+        let mut last_accepted = self.last_accepted.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(last) = *last_accepted {
+            let next_allowed = last + self.min_interval;
+            if now < next_allowed {
+                let wait_secs = (next_allowed - now).num_milliseconds().max(0) as f64 / 1000.0;
+                return Err(AppError::rate_limit_error(format!(
+                    "强制轮询过于频繁，请在 {:.1} 秒后重试",
+                    wait_secs
+                )));
+            }
+        }
+        *last_accepted = Some(now);
+        Ok(())
+    }
+}
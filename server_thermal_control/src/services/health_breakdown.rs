@@ -0,0 +1,169 @@
+use crate::models::fan::FanStatus;
+use crate::models::thermal::TemperatureReading;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 健康分数满分
+pub const BASE_HEALTH_SCORE: f64 = 100.0;
+
+/// 一项健康扣分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthDeduction {
+    /// 扣分的组件，如 `temperature:CPU1_TEMP`、`fan:FAN2`、`alerts`
+    pub component: String,
+    /// 触发扣分的规则
+    pub rule: String,
+    /// 扣除的分数
+    pub points: f64,
+    /// 问题描述
+    pub detail: String,
+    /// 处理建议
+    pub remediation: String,
+}
+
+/// 健康分数明细
+///
+/// `base_score - 各项扣分之和 + floor_adjustment == score`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthBreakdown {
+    pub base_score: f64,
+    /// 逐项扣分，按温度、风扇、告警的顺序
+    pub deductions: Vec<HealthDeduction>,
+    /// 按组件汇总的扣分
+    pub component_deductions: BTreeMap<String, f64>,
+    /// 扣分超过满分时补回的分数，保证分数不低于0
+    pub floor_adjustment: f64,
+    pub score: f64,
+}
+
+impl HealthBreakdown {
+    /// 按监控数据逐条应用扣分规则
+    ///
+    /// # 参数
+    /// * `temperatures` - 最新温度读数
+    /// * `fans` - 最新风扇状态
+    /// * `active_alerts` - 活跃告警数
+    pub fn assess(temperatures: &[TemperatureReading], fans: &[FanStatus], active_alerts: usize) -> Self {
+        let mut deductions = Vec::new();
+
+        for reading in temperatures {
+            let component = format!("temperature:{}", reading.sensor_id);
+            if reading.temperature > 80.0 {
+                deductions.push(HealthDeduction {
+                    component,
+                    rule: "temperature_above_80c".to_string(),
+                    points: 10.0,
+                    detail: format!("传感器 {} 温度过高: {:.1}°C", reading.sensor_id, reading.temperature),
+                    remediation: "检查该传感器附近的风道和风扇，必要时提高风扇转速或降低负载".to_string(),
+                });
+            } else if reading.temperature > 70.0 {
+                deductions.push(HealthDeduction {
+                    component,
+                    rule: "temperature_above_70c".to_string(),
+                    points: 5.0,
+                    detail: format!("传感器 {} 温度偏高: {:.1}°C", reading.sensor_id, reading.temperature),
+                    remediation: "关注温度趋势，确认风扇曲线在该温度区间的转速是否足够".to_string(),
+                });
+            }
+        }
+
+        for fan in fans {
+            if fan.current_rpm <= 0.0 && fan.speed_percentage > 0.0 {
+                deductions.push(HealthDeduction {
+                    component: format!("fan:{}", fan.fan_id),
+                    rule: "fan_zero_rpm_while_driven".to_string(),
+                    points: 15.0,
+                    detail: format!(
+                        "风扇 {} 可能故障: 设置转速 {:.1}% 但RPM为0",
+                        fan.fan_id, fan.speed_percentage
+                    ),
+                    remediation: "检查风扇是否卡死、掉线或未插好，必要时更换风扇".to_string(),
+                });
+            }
+        }
+
+        if active_alerts > 0 {
+            deductions.push(HealthDeduction {
+                component: "alerts".to_string(),
+                rule: "active_alerts_5_each".to_string(),
+                points: active_alerts as f64 * 5.0,
+                detail: format!("存在 {} 个活跃告警", active_alerts),
+                remediation: "逐条处理活跃告警，已知问题确认后不再计入扣分".to_string(),
+            });
+        }
+
+        Self::from_deductions(deductions)
+    }
+
+    /// 由扣分项汇总分数
+    ///
+    /// # 参数
+    /// * `deductions` - 扣分项
+    pub fn from_deductions(deductions: Vec<HealthDeduction>) -> Self {
+        let mut component_deductions = BTreeMap::new();
+        for deduction in &deductions {
+            *component_deductions.entry(deduction.component.clone()).or_insert(0.0) += deduction.points;
+        }
+
+        let raw_score = BASE_HEALTH_SCORE - deductions.iter().map(|d| d.points).sum::<f64>();
+        let score = raw_score.clamp(0.0, BASE_HEALTH_SCORE);
+
+        Self {
+            base_score: BASE_HEALTH_SCORE,
+            deductions,
+            component_deductions,
+            floor_adjustment: score - raw_score,
+            score,
+        }
+    }
+
+    /// 问题描述列表
+    pub fn issues(&self) -> Vec<String> {
+        self.deductions.iter().map(|d| d.detail.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::thermal::TemperatureThreshold;
+
+    #[test]
+    fn test_breakdown_sums_to_final_score() {
+        let threshold = TemperatureThreshold::default();
+        let temperatures = vec![
+            TemperatureReading::new("CPU1_TEMP".into(), "CPU1 Temp".into(), 82.0, "local".into(), &threshold),
+            TemperatureReading::new("CPU2_TEMP".into(), "CPU2 Temp".into(), 72.5, "local".into(), &threshold),
+            TemperatureReading::new("INLET_TEMP".into(), "Inlet Temp".into(), 24.0, "local".into(), &threshold),
+        ];
+        let mut stalled = FanStatus::new("FAN2".into(), "FAN2".into(), 0.0, 3000.0, "local".into());
+        stalled.speed_percentage = 60.0;
+        let fans = vec![
+            FanStatus::new("FAN1".into(), "FAN1".into(), 3000.0, 3000.0, "local".into()),
+            stalled,
+        ];
+
+        let breakdown = HealthBreakdown::assess(&temperatures, &fans, 2);
+
+        let rules: Vec<&str> = breakdown.deductions.iter().map(|d| d.rule.as_str()).collect();
+        assert_eq!(
+            rules,
+            vec!["temperature_above_80c", "temperature_above_70c", "fan_zero_rpm_while_driven", "active_alerts_5_each"]
+        );
+        assert!(breakdown.deductions.iter().all(|d| !d.remediation.is_empty()));
+        assert_eq!(breakdown.component_deductions["fan:FAN2"], 15.0);
+        assert_eq!(breakdown.component_deductions["alerts"], 10.0);
+        assert!(!breakdown.component_deductions.contains_key("temperature:INLET_TEMP"));
+
+        let deducted: f64 = breakdown.component_deductions.values().sum();
+        assert_eq!(breakdown.score, 60.0);
+        assert_eq!(breakdown.base_score - deducted + breakdown.floor_adjustment, breakdown.score);
+        assert_eq!(breakdown.issues().len(), 4);
+
+        // 扣分超过满分时由补回分数保证等式成立
+        let breakdown = HealthBreakdown::assess(&temperatures, &fans, 30);
+        let deducted: f64 = breakdown.deductions.iter().map(|d| d.points).sum();
+        assert_eq!(breakdown.score, 0.0);
+        assert_eq!(breakdown.base_score - deducted + breakdown.floor_adjustment, breakdown.score);
+    }
+}
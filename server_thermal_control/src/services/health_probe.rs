@@ -0,0 +1,157 @@
+use crate::config::HealthProbeConfig;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// 单个HTTP探针的检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeResult {
+    /// 依赖名称
+    pub name: String,
+    /// 探测地址
+    pub url: String,
+    /// 是否可用：在超时内返回了期望的状态码
+    pub available: bool,
+    /// 响应时间（毫秒）
+    pub response_time_ms: u64,
+    /// 检查时间
+    pub checked_at: DateTime<Utc>,
+    /// 错误消息
+    pub error_message: Option<String>,
+}
+
+/// HTTP健康探针服务
+///
+/// 按 `health.probes` 配置探测部署相关的外部依赖（如指标后端），各探针并发执行
+#[derive(Debug, Clone)]
+pub struct HealthProbeService {
+    /// 探针配置
+    probes: Vec<HealthProbeConfig>,
+    /// HTTP客户端
+    client: reqwest::Client,
+}
+
+impl HealthProbeService {
+    /// 创建HTTP健康探针服务
+    ///
+    /// # 参数
+    /// * `probes` - 探针配置
+    pub fn new(probes: Vec<HealthProbeConfig>) -> Self {
+        Self {
+            probes,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// 执行所有探针，结果按配置顺序排列
+    pub async fn check_all(&self) -> Vec<ProbeResult> {
+        futures::future::join_all(self.probes.iter().map(|probe| self.check(probe))).await
+    }
+
+    /// 执行单个探针
+    ///
+    /// # 参数
+    /// * `probe` - 探针配置
+    pub async fn check(&self, probe: &HealthProbeConfig) -> ProbeResult {
+        let started = Instant::now();
+        let response = self
+            .client
+            .get(&probe.url)
+            .timeout(Duration::from_millis(probe.timeout_ms))
+            .send()
+            .await;
+        let response_time_ms = started.elapsed().as_millis() as u64;
+
+        let error_message = match response {
+            Ok(response) if response.status().as_u16() == probe.expected_status => None,
+            Ok(response) => Some(format!(
+                "状态码 {}，期望 {}",
+                response.status().as_u16(),
+                probe.expected_status
+            )),
+            Err(e) if e.is_timeout() => Some(format!("请求超时（{}ms）", probe.timeout_ms)),
+            Err(e) => Some(format!("请求失败: {}", e)),
+        };
+        if let Some(error) = &error_message {
+            warn!("健康探针 {} 不可用: {}", probe.name, error);
+        }
+
+        ProbeResult {
+            name: probe.name.clone(),
+            url: probe.url.clone(),
+            available: error_message.is_none(),
+            response_time_ms,
+            checked_at: Utc::now(),
+            error_message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 按请求路径返回固定响应的本地HTTP服务，`/slow` 在响应前等待
+    async fn mock_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..n]);
+                    let status = if request.starts_with("GET /ok ") {
+                        "200 OK"
+                    } else if request.starts_with("GET /slow ") {
+                        tokio::time::sleep(Duration::from_secs(2)).await;
+                        "200 OK"
+                    } else {
+                        "500 Internal Server Error"
+                    };
+                    let response = format!("HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn probe(name: &str, url: String) -> HealthProbeConfig {
+        HealthProbeConfig {
+            name: name.to_string(),
+            url,
+            expected_status: 200,
+            timeout_ms: 300,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_status_follows_response_code_and_timeout() {
+        let base = mock_server().await;
+        let service = HealthProbeService::new(vec![
+            probe("metrics", format!("{}/ok", base)),
+            probe("broken", format!("{}/error", base)),
+            probe("stalled", format!("{}/slow", base)),
+        ]);
+
+        let results = service.check_all().await;
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["metrics", "broken", "stalled"]);
+
+        assert!(results[0].available);
+        assert!(results[0].error_message.is_none());
+
+        assert!(!results[1].available);
+        assert!(results[1].error_message.as_deref().unwrap().contains("500"));
+
+        assert!(!results[2].available);
+        assert!(results[2].error_message.as_deref().unwrap().contains("超时"));
+    }
+}
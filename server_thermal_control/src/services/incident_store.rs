@@ -0,0 +1,330 @@
+use crate::models::error::{AppError, AppResult};
+use crate::models::Alert;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// 告警关联配置
+///
+/// 时间上相近、来自相关组件的告警归入同一个事件，运维人员按事件确认和解决
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentCorrelationConfig {
+    /// 是否启用关联，关闭时每条告警单独成为一个事件
+    #[serde(default = "default_correlation_enabled")]
+    pub enabled: bool,
+    /// 关联窗口（秒），告警与事件最近一条告警的间隔不超过该值时归入该事件
+    #[serde(default = "default_correlation_window_secs")]
+    pub window_secs: u64,
+    /// 相关组件分组（告警类型），同组告警可以相互关联；不在任何分组中的类型只与同类型关联
+    #[serde(default = "default_related_components")]
+    pub related_components: Vec<Vec<String>>,
+}
+
+impl Default for IncidentCorrelationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_correlation_enabled(),
+            window_secs: default_correlation_window_secs(),
+            related_components: default_related_components(),
+        }
+    }
+}
+
+fn default_correlation_enabled() -> bool {
+    true
+}
+
+fn default_correlation_window_secs() -> u64 {
+    120
+}
+
+/// 默认将散热相关的温度、风扇、电源告警视为同一组
+fn default_related_components() -> Vec<Vec<String>> {
+    vec![vec!["temperature".to_string(), "fan".to_string(), "power".to_string()]]
+}
+
+impl IncidentCorrelationConfig {
+    /// 组件所属的关联分组，不在任何分组中时以组件自身为一组
+    ///
+    /// # 参数
+    /// * `component` - 告警类型
+    fn group_of(&self, component: &str) -> BTreeSet<String> {
+        self.related_components
+            .iter()
+            .find(|group| group.iter().any(|c| c.eq_ignore_ascii_case(component)))
+            .map(|group| group.iter().map(|c| c.to_lowercase()).collect())
+            .unwrap_or_else(|| BTreeSet::from([component.to_lowercase()]))
+    }
+}
+
+/// 事件状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentStatus {
+    /// 未处理
+    Open,
+    /// 已确认
+    Acknowledged,
+    /// 已解决
+    Resolved,
+}
+
+/// 由关联告警汇总而成的事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    pub id: Uuid,
+    pub status: IncidentStatus,
+    /// 关联告警中最高的严重程度
+    pub severity: String,
+    pub title: String,
+    /// 涉及的组件（告警类型）
+    pub components: BTreeSet<String>,
+    /// 关联的告警ID，按告警时间排序
+    pub alert_ids: Vec<Uuid>,
+    pub first_alert_at: DateTime<Utc>,
+    pub last_alert_at: DateTime<Utc>,
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Incident {
+    /// 以一条告警开启新事件
+    fn open(alert: &Alert) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            status: IncidentStatus::Open,
+            severity: alert.severity.to_lowercase(),
+            title: alert.title.clone(),
+            components: BTreeSet::from([alert.alert_type.to_lowercase()]),
+            alert_ids: vec![alert.id],
+            first_alert_at: alert.created_at,
+            last_alert_at: alert.created_at,
+            acknowledged_by: None,
+            acknowledged_at: None,
+            resolved_by: None,
+            resolved_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// 将告警并入事件，严重程度取较高者
+    fn attach(&mut self, alert: &Alert) {
+        if severity_rank(&alert.severity) > severity_rank(&self.severity) {
+            self.severity = alert.severity.to_lowercase();
+        }
+        self.components.insert(alert.alert_type.to_lowercase());
+        self.alert_ids.push(alert.id);
+        self.first_alert_at = self.first_alert_at.min(alert.created_at);
+        self.last_alert_at = self.last_alert_at.max(alert.created_at);
+        self.updated_at = Utc::now();
+    }
+}
+
+/// 严重程度排序，未知级别最低
+pub(crate) fn severity_rank(severity: &str) -> u8 {
+    match severity.to_ascii_lowercase().as_str() {
+        "critical" => 4,
+        "error" => 3,
+        "warning" => 2,
+        "info" => 1,
+        _ => 0,
+    }
+}
+
+/// 事件存储与告警关联
+///
+/// 运行时保存，重启后清空
+#[derive(Debug, Clone, Default)]
+pub struct IncidentStore {
+    config: IncidentCorrelationConfig,
+    incidents: Arc<RwLock<Vec<Incident>>>,
+}
+
+impl IncidentStore {
+    /// 创建事件存储
+    ///
+    /// # 参数
+    /// * `config` - 告警关联配置
+    pub fn new(config: IncidentCorrelationConfig) -> Self {
+        Self {
+            config,
+            incidents: Arc::default(),
+        }
+    }
+
+    /// 将告警归入事件，返回事件ID
+    ///
+    /// 优先并入同一关联分组中未解决、且最近一条告警在关联窗口内的事件，否则开启新事件
+    ///
+    /// # 参数
+    /// * `alert` - 新产生的告警
+    pub fn correlate(&self, alert: &Alert) -> Uuid {
+        let mut incidents = self.incidents.write().unwrap_or_else(|e| e.into_inner());
+
+        if self.config.enabled {
+            let group = self.config.group_of(&alert.alert_type);
+            let matched = incidents
+                .iter_mut()
+                .filter(|incident| incident.status != IncidentStatus::Resolved)
+                .filter(|incident| incident.components.iter().any(|c| group.contains(c)))
+                .filter(|incident| {
+                    (alert.created_at - incident.last_alert_at).num_seconds().unsigned_abs() <= self.config.window_secs
+                })
+                .max_by_key(|incident| incident.last_alert_at);
+            if let Some(incident) = matched {
+                incident.attach(alert);
+                return incident.id;
+            }
+        }
+
+        let incident = Incident::open(alert);
+        let id = incident.id;
+        incidents.push(incident);
+        id
+    }
+
+    /// 事件列表，最近的在前
+    ///
+    /// # 参数
+    /// * `status` - 按状态过滤，为空时返回全部
+    pub fn list(&self, status: Option<IncidentStatus>) -> Vec<Incident> {
+        let incidents = self.incidents.read().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<Incident> = incidents
+            .iter()
+            .filter(|incident| status.is_none() || status == Some(incident.status))
+            .cloned()
+            .collect();
+        list.sort_by_key(|incident| std::cmp::Reverse(incident.last_alert_at));
+        list
+    }
+
+    /// 获取事件
+    ///
+    /// # 参数
+    /// * `id` - 事件ID
+    pub fn get(&self, id: Uuid) -> AppResult<Incident> {
+        self.incidents
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|incident| incident.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::not_found_error("incident", id.to_string()))
+    }
+
+    /// 确认事件，已解决的事件不能再确认
+    ///
+    /// # 参数
+    /// * `id` - 事件ID
+    /// * `actor` - 操作人
+    pub fn acknowledge(&self, id: Uuid, actor: String) -> AppResult<Incident> {
+        self.update(id, |incident| {
+            if incident.status == IncidentStatus::Resolved {
+                return Err(AppError::ConflictError {
+                    message: format!("事件 {} 已解决，不能再确认", id),
+                });
+            }
+            let now = Utc::now();
+            incident.status = IncidentStatus::Acknowledged;
+            incident.acknowledged_by = Some(actor);
+            incident.acknowledged_at = Some(now);
+            incident.updated_at = now;
+            Ok(())
+        })
+    }
+
+    /// 解决事件，之后的告警会开启新事件
+    ///
+    /// # 参数
+    /// * `id` - 事件ID
+    /// * `actor` - 操作人
+    pub fn resolve(&self, id: Uuid, actor: String) -> AppResult<Incident> {
+        self.update(id, |incident| {
+            if incident.status == IncidentStatus::Resolved {
+                return Err(AppError::ConflictError {
+                    message: format!("事件 {} 已解决", id),
+                });
+            }
+            let now = Utc::now();
+            incident.status = IncidentStatus::Resolved;
+            incident.resolved_by = Some(actor);
+            incident.resolved_at = Some(now);
+            incident.updated_at = now;
+            Ok(())
+        })
+    }
+
+    fn update(&self, id: Uuid, apply: impl FnOnce(&mut Incident) -> AppResult<()>) -> AppResult<Incident> {
+        let mut incidents = self.incidents.write().unwrap_or_else(|e| e.into_inner());
+        let incident = incidents
+            .iter_mut()
+            .find(|incident| incident.id == id)
+            .ok_or_else(|| AppError::not_found_error("incident", id.to_string()))?;
+        apply(incident)?;
+        Ok(incident.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AlertStatus;
+    use chrono::Duration;
+
+    fn alert(alert_type: &str, severity: &str, at: DateTime<Utc>) -> Alert {
+        Alert {
+            id: Uuid::new_v4(),
+            alert_type: alert_type.to_string(),
+            severity: severity.to_string(),
+            title: format!("{} alert", alert_type),
+            message: String::new(),
+            source: "CPU1".to_string(),
+            source_id: "CPU1_TEMP".to_string(),
+            rule_id: None,
+            status: AlertStatus::Triggered,
+            acknowledged: false,
+            acknowledged_by: None,
+            acknowledged_at: None,
+            resolved_at: None,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    #[test]
+    fn test_correlated_alerts_within_window_roll_up_into_one_incident() {
+        let store = IncidentStore::new(IncidentCorrelationConfig::default());
+        let start = Utc::now();
+        let alerts = [
+            alert("temperature", "warning", start),
+            alert("fan", "critical", start + Duration::seconds(30)),
+            alert("power", "warning", start + Duration::seconds(90)),
+        ];
+
+        let ids: BTreeSet<Uuid> = alerts.iter().map(|alert| store.correlate(alert)).collect();
+        assert_eq!(ids.len(), 1);
+
+        let incidents = store.list(None);
+        assert_eq!(incidents.len(), 1);
+        let incident = &incidents[0];
+        assert_eq!(incident.alert_ids, alerts.iter().map(|alert| alert.id).collect::<Vec<_>>());
+        assert_eq!(incident.severity, "critical");
+        assert_eq!(incident.components.len(), 3);
+
+        // 超出窗口或事件已解决后的告警开启新事件
+        let late = alert("temperature", "warning", start + Duration::seconds(600));
+        assert_ne!(store.correlate(&late), incident.id);
+        store.resolve(incident.id, "alice".to_string()).unwrap();
+        let after_resolve = alert("fan", "warning", start + Duration::seconds(95));
+        assert_ne!(store.correlate(&after_resolve), incident.id);
+        assert!(store.acknowledge(incident.id, "bob".to_string()).is_err());
+    }
+}
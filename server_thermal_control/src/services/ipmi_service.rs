@@ -1,7 +1,14 @@
+use crate::config::{CacheConfig, FanControlCommands, VendorProfile};
+use crate::services::sensor_replay::SensorReplay;
+use crate::services::sensor_source::SensorSource;
+use crate::services::sensor_units::{split_reading, SensorUnit, UnitNormalizer};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::process::Command;
-use tracing::error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, debug_span, error, info, instrument};
 use uuid::Uuid;
 
 /// IPMI配置结构
@@ -19,8 +26,15 @@ pub struct TemperatureSensor {
     pub id: String,
     pub sensor_id: String,
     pub temperature: f64,
+    /// 规范单位，温度统一换算为 `°C`
     pub unit: String,
+    /// BMC返回的原始单位写法，如 `degrees C`
+    #[serde(default)]
+    pub raw_unit: String,
+    /// 归一化后的位置：cpu、inlet、exhaust、psu、backplane、memory、system_board、other
     pub location: String,
+    /// SDR中的实体ID与实例（如 `3.1`），无法读取时为空
+    pub entity: String,
     pub status: String,
     pub timestamp: DateTime<Utc>,
 }
@@ -31,16 +45,24 @@ pub struct FanSensor {
     pub id: String,
     pub fan_id: String,
     pub speed_rpm: u32,
+    /// 转速的规范单位
+    #[serde(default = "default_fan_unit")]
+    pub unit: String,
+    /// BMC返回的原始单位写法，读数不带单位时为空
+    #[serde(default)]
+    pub raw_unit: String,
     pub speed_percent: u8,
     pub status: String,
     pub location: String,
     pub control_mode: String,
     pub target_temp: Option<f64>,
+    /// 是否可通过PWM调速，仅有转速计的风扇为false
+    pub controllable: bool,
     pub timestamp: DateTime<Utc>,
 }
 
 /// 系统信息结构
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemInfo {
     pub manufacturer: String,
     pub device_id: String,
@@ -50,21 +72,383 @@ pub struct SystemInfo {
     pub power_consumption: Option<f64>,
     pub voltage: Option<f64>,
     pub current: Option<f64>,
+    /// 电源读数的原始单位写法，按 `power_consumption`、`voltage`、`current` 索引
+    #[serde(default)]
+    pub raw_units: BTreeMap<String, String>,
+    /// `mc info` 的完整解析结果
+    pub mc_info: IpmiSystemInfo,
+}
+
+/// `ipmitool mc info` 解析结果
+///
+/// 未出现的字段保持为空
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IpmiSystemInfo {
+    pub device_id: String,
+    pub device_revision: String,
+    pub firmware_revision: String,
+    pub ipmi_version: String,
+    /// IANA企业编号，如Dell为674、Supermicro为10876
+    pub manufacturer_id: Option<u32>,
+    pub manufacturer_name: String,
+    pub product_id: Option<u32>,
+    pub product_name: String,
+    pub device_available: Option<bool>,
+    pub provides_device_sdrs: Option<bool>,
+    /// 支持的附加设备功能，如 `SDR Repository Device`、`SEL Device`
+    pub additional_device_support: Vec<String>,
+    /// 辅助固件版本字节，如 `0x00`
+    pub aux_firmware_revision: Vec<String>,
+}
+
+impl IpmiSystemInfo {
+    /// 解析 `ipmitool mc info` 输出
+    ///
+    /// `Additional Device Support` 与 `Aux Firmware Rev Info` 的取值在其后的缩进行中，逐行收集
+    ///
+    /// # 参数
+    /// * `output` - 命令输出
+    pub fn parse(output: &str) -> Self {
+        let mut info = Self::default();
+        let mut additional_device_support = Vec::new();
+        let mut aux_firmware_revision = Vec::new();
+        // 正在收集的多行字段
+        let mut collecting: Option<&mut Vec<String>> = None;
+
+        for line in output.lines().filter(|line| !line.trim().is_empty()) {
+            if line.starts_with(char::is_whitespace) {
+                if let Some(values) = collecting.as_mut() {
+                    values.push(line.trim().to_string());
+                }
+                continue;
+            }
+
+            collecting = None;
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "Device ID" => info.device_id = value.to_string(),
+                "Device Revision" => info.device_revision = value.to_string(),
+                "Firmware Revision" => info.firmware_revision = value.to_string(),
+                "IPMI Version" => info.ipmi_version = value.to_string(),
+                "Manufacturer ID" => info.manufacturer_id = Self::leading_number(value),
+                "Manufacturer Name" => info.manufacturer_name = value.to_string(),
+                "Product ID" => info.product_id = Self::leading_number(value),
+                "Product Name" => info.product_name = value.to_string(),
+                "Device Available" => info.device_available = Self::yes_no(value),
+                "Provides Device SDRs" => info.provides_device_sdrs = Self::yes_no(value),
+                "Additional Device Support" => collecting = Some(&mut additional_device_support),
+                "Aux Firmware Rev Info" => collecting = Some(&mut aux_firmware_revision),
+                _ => {}
+            }
+        }
+
+        info.additional_device_support = additional_device_support;
+        info.aux_firmware_revision = aux_firmware_revision;
+        info
+    }
+
+    /// 取值开头的十进制数字，如 `256 (0x0100)` 中的256
+    fn leading_number(value: &str) -> Option<u32> {
+        value.split_whitespace().next()?.parse().ok()
+    }
+
+    fn yes_no(value: &str) -> Option<bool> {
+        match value.to_lowercase().as_str() {
+            "yes" => Some(true),
+            "no" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// BMC自检结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BmcSelfTestResult {
+    /// 自检通过
+    Passed,
+    /// BMC未实现自检
+    NotImplemented,
+    /// 自检失败（设备错误、数据损坏或硬件故障）
+    Failed,
+}
+
+/// BMC自检状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BmcSelfTestStatus {
+    pub result: BmcSelfTestResult,
+    /// `Selftest:` 行的原始描述
+    pub summary: String,
+    /// 报告失败的组件
+    pub failed_components: Vec<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl BmcSelfTestStatus {
+    /// 解析 `ipmitool mc selftest` 输出
+    ///
+    /// # 参数
+    /// * `output` - 命令输出
+    pub fn parse(output: &str) -> Self {
+        let mut summary = String::new();
+        let mut failed_components = Vec::new();
+
+        for line in output.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(value) = line.strip_prefix("Selftest:") {
+                summary = value.trim().to_string();
+            } else if !line.starts_with("Failed components") {
+                failed_components.push(line.to_string());
+            }
+        }
+
+        let normalized = summary.to_lowercase();
+        let result = if normalized == "passed" {
+            BmcSelfTestResult::Passed
+        } else if normalized == "not implemented" {
+            BmcSelfTestResult::NotImplemented
+        } else {
+            BmcSelfTestResult::Failed
+        };
+
+        Self {
+            result,
+            summary,
+            failed_components,
+            timestamp: Utc::now(),
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.result == BmcSelfTestResult::Failed
+    }
+}
+
+/// 风扇控制模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FanControlMode {
+    /// 手动控制，BMC按下发的转速运行
+    Manual,
+    /// BMC自动控制
+    Automatic,
+}
+
+/// 允许通过API直接执行的只读ipmitool子命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadOnlyIpmiCommand {
+    Sdr,
+    Sensor,
+    McInfo,
+    SelList,
+    DcmiPowerReading,
+}
+
+impl ReadOnlyIpmiCommand {
+    /// 全部允许的子命令
+    pub const ALL: [Self; 5] = [
+        Self::Sdr,
+        Self::Sensor,
+        Self::McInfo,
+        Self::SelList,
+        Self::DcmiPowerReading,
+    ];
+
+    /// 解析子命令，按空白拆分后须与允许列表中的某一项逐词完全一致（不区分大小写），不接受额外参数
+    ///
+    /// # 参数
+    /// * `command` - 子命令，如 `sdr`、`mc info`
+    pub fn parse(command: &str) -> Option<Self> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        Self::ALL.into_iter().find(|allowed| {
+            let args = allowed.args();
+            args.len() == words.len()
+                && args.iter().zip(&words).all(|(arg, word)| arg.eq_ignore_ascii_case(word))
+        })
+    }
+
+    /// 传给ipmitool的参数
+    pub fn args(&self) -> &'static [&'static str] {
+        match self {
+            Self::Sdr => &["sdr"],
+            Self::Sensor => &["sensor"],
+            Self::McInfo => &["mc", "info"],
+            Self::SelList => &["sel", "list"],
+            Self::DcmiPowerReading => &["dcmi", "power", "reading"],
+        }
+    }
+
+    /// 以空格连接的子命令
+    pub fn as_command(&self) -> String {
+        self.args().join(" ")
+    }
+}
+
+/// 执行只读ipmitool子命令并返回原始输出
+pub trait ReadOnlyIpmi: Send + Sync {
+    /// 执行子命令
+    ///
+    /// # 参数
+    /// * `command` - 允许列表中的子命令
+    fn run_read_only(&self, command: ReadOnlyIpmiCommand) -> Result<String, Box<dyn std::error::Error>>;
+}
+
+impl ReadOnlyIpmi for IpmiService {
+    #[instrument(level = "debug", name = "ipmi.read_only", skip_all, fields(command = %command.as_command()))]
+    fn run_read_only(&self, command: ReadOnlyIpmiCommand) -> Result<String, Box<dyn std::error::Error>> {
+        self.execute_ipmi_command(command.args())
+    }
+}
+
+/// 应用状态中持有的IPMI操作
+///
+/// 处理函数通过 `Arc<dyn Ipmi>` 访问BMC，测试中可以换成按脚本返回读数的模拟实现
+pub trait Ipmi: SensorSource + ReadOnlyIpmi {
+    /// 获取系统基本信息
+    fn get_system_info(&self) -> Result<SystemInfo, Box<dyn std::error::Error>>;
+
+    /// 获取BMC自检状态
+    fn get_self_test_status(&self) -> Result<BmcSelfTestStatus, Box<dyn std::error::Error>>;
+}
+
+impl Ipmi for IpmiService {
+    fn get_system_info(&self) -> Result<SystemInfo, Box<dyn std::error::Error>> {
+        IpmiService::get_system_info(self)
+    }
+
+    fn get_self_test_status(&self) -> Result<BmcSelfTestStatus, Box<dyn std::error::Error>> {
+        IpmiService::get_self_test_status(self)
+    }
+}
+
+impl ReadOnlyIpmi for Arc<dyn Ipmi> {
+    fn run_read_only(&self, command: ReadOnlyIpmiCommand) -> Result<String, Box<dyn std::error::Error>> {
+        self.as_ref().run_read_only(command)
+    }
+}
+
+/// 带有效期的单值缓存
+///
+/// 有效期内直接返回缓存值，过期后调用获取函数刷新；获取失败时不缓存。
+/// 刷新期间持有同步锁，获取函数通常是阻塞的ipmitool调用，异步上下文中应通过 `web::block` 等在阻塞线程池中调用
+pub struct TtlCell<T> {
+    ttl: Duration,
+    entry: Mutex<Option<(Instant, T)>>,
+}
+
+impl<T: Clone> TtlCell<T> {
+    /// 创建缓存
+    ///
+    /// # 参数
+    /// * `ttl` - 有效期，为0时每次都重新获取
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entry: Mutex::new(None),
+        }
+    }
+
+    /// 获取缓存值，过期或为空时刷新
+    ///
+    /// # 参数
+    /// * `fetch` - 获取新值的函数
+    pub fn get_or_try_refresh<E>(&self, fetch: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+        // 刷新期间持有锁，避免并发请求同时触发多次获取；等待者同样阻塞，直到本次获取结束
+        let mut entry = self.entry.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((fetched_at, value)) = entry.as_ref() {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        let value = fetch()?;
+        *entry = Some((Instant::now(), value.clone()));
+        Ok(value)
+    }
 }
 
 /// IPMI服务结构
 pub struct IpmiService {
     config: IpmiConfig,
+    /// 系统信息缓存（厂商、型号、固件等几乎不变）
+    system_info_cache: TtlCell<SystemInfo>,
+    /// 风扇控制raw命令序列
+    fan_commands: FanControlCommands,
+    /// 最近一次下发的风扇控制模式，None表示尚未切换过（BMC默认自动）
+    fan_control_mode: Mutex<Option<FanControlMode>>,
+    /// 最近一次读取SDR得到的各风扇可控性
+    fan_controllability: Mutex<HashMap<String, bool>>,
+    /// 传感器单位归一化
+    units: UnitNormalizer,
+    /// 传感器回放，设置后代替ipmitool响应命令
+    replay: Option<Arc<SensorReplay>>,
 }
 
 impl IpmiService {
     /// 创建新的IPMI服务实例
     pub fn new(config: IpmiConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            system_info_cache: TtlCell::new(Duration::ZERO),
+            fan_commands: FanControlCommands::default(),
+            fan_control_mode: Mutex::new(None),
+            fan_controllability: Mutex::new(HashMap::new()),
+            units: UnitNormalizer::default(),
+            replay: None,
+        }
+    }
+
+    /// 以录制的传感器轨迹代替BMC
+    ///
+    /// # 参数
+    /// * `replay` - 传感器回放
+    pub fn with_replay(mut self, replay: Arc<SensorReplay>) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+
+    /// 设置厂商相关的风扇控制命令序列
+    ///
+    /// # 参数
+    /// * `commands` - 风扇控制raw命令序列
+    pub fn with_fan_control_commands(mut self, commands: FanControlCommands) -> Self {
+        self.fan_commands = commands;
+        self
+    }
+
+    /// 追加传感器单位别名
+    ///
+    /// # 参数
+    /// * `aliases` - 厂商单位写法到规范单位的映射
+    pub fn with_unit_aliases(mut self, aliases: &HashMap<String, SensorUnit>) -> Self {
+        self.units = self.units.with_aliases(aliases);
+        self
+    }
+
+    /// 按缓存配置启用系统信息缓存
+    ///
+    /// # 参数
+    /// * `cache_config` - 缓存配置，`enabled` 为false时不缓存
+    pub fn with_cache_config(mut self, cache_config: &CacheConfig) -> Self {
+        let ttl = if cache_config.enabled {
+            Duration::from_secs(cache_config.ttl)
+        } else {
+            Duration::ZERO
+        };
+        self.system_info_cache = TtlCell::new(ttl);
+        self
     }
 
     /// 执行IPMI命令
+    ///
+    /// 每次调用对应一个 `ipmi.command` span，只记录子命令参数，连接地址和凭据不进入span
     fn execute_ipmi_command(&self, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+        let _span = debug_span!("ipmi.command", operation = %args.join(" ")).entered();
+        if let Some(ref replay) = self.replay {
+            return Ok(replay.execute(args));
+        }
         let mut cmd = Command::new("ipmitool");
         cmd.args(&["-I", &self.config.interface])
             .args(&["-H", &self.config.host])
@@ -83,202 +467,110 @@ impl IpmiService {
     }
 
     /// 获取系统基本信息
+    ///
+    /// 在缓存有效期内复用上次结果，避免每次请求都调用ipmitool
     pub fn get_system_info(&self) -> Result<SystemInfo, Box<dyn std::error::Error>> {
-        let output = self.execute_ipmi_command(&["mc", "info"])?;
+        self.system_info_cache.get_or_try_refresh(|| self.fetch_system_info())
+    }
 
-        let mut manufacturer = "Unknown".to_string();
-        let mut device_id = "Unknown".to_string();
-        let mut firmware_version = "Unknown".to_string();
-        let mut ipmi_version = "Unknown".to_string();
-        let mut product_name = "Unknown".to_string();
-
-        for line in output.lines() {
-            if line.contains("Manufacturer Name") {
-                manufacturer = line
-                    .split(':')
-                    .nth(1)
-                    .unwrap_or("Unknown")
-                    .trim()
-                    .to_string();
-            } else if line.contains("Device ID") {
-                device_id = line
-                    .split(':')
-                    .nth(1)
-                    .unwrap_or("Unknown")
-                    .trim()
-                    .to_string();
-            } else if line.contains("Firmware Revision") {
-                firmware_version = line
-                    .split(':')
-                    .nth(1)
-                    .unwrap_or("Unknown")
-                    .trim()
-                    .to_string();
-            } else if line.contains("IPMI Version") {
-                ipmi_version = line
-                    .split(':')
-                    .nth(1)
-                    .unwrap_or("Unknown")
-                    .trim()
-                    .to_string();
-            } else if line.contains("Product Name") {
-                product_name = line
-                    .split(':')
-                    .nth(1)
-                    .unwrap_or("Unknown")
-                    .trim()
-                    .to_string();
+    /// 通过ipmitool读取系统基本信息
+    #[instrument(level = "debug", name = "ipmi.system_info", skip_all)]
+    fn fetch_system_info(&self) -> Result<SystemInfo, Box<dyn std::error::Error>> {
+        let output = self.execute_ipmi_command(&["mc", "info"])?;
+        let mc_info = IpmiSystemInfo::parse(&output);
+        let or_unknown = |value: &str| {
+            if value.is_empty() {
+                "Unknown".to_string()
+            } else {
+                value.to_string()
             }
-        }
+        };
 
         // 获取电源信息
-        let (power_consumption, voltage, current) =
-            self.get_power_info().unwrap_or((None, None, None));
+        let power = self.get_power_info().unwrap_or_default();
 
         Ok(SystemInfo {
-            manufacturer,
-            device_id,
-            firmware_version,
-            ipmi_version,
-            product_name,
-            power_consumption,
-            voltage,
-            current,
+            manufacturer: or_unknown(&mc_info.manufacturer_name),
+            device_id: or_unknown(&mc_info.device_id),
+            firmware_version: or_unknown(&mc_info.firmware_revision),
+            ipmi_version: or_unknown(&mc_info.ipmi_version),
+            product_name: or_unknown(&mc_info.product_name),
+            power_consumption: power.power_consumption,
+            voltage: power.voltage,
+            current: power.current,
+            raw_units: power.raw_units,
+            mc_info,
         })
     }
 
     /// 获取电源信息
-    fn get_power_info(
-        &self,
-    ) -> Result<(Option<f64>, Option<f64>, Option<f64>), Box<dyn std::error::Error>> {
+    #[instrument(level = "debug", name = "ipmi.power_info", skip_all)]
+    fn get_power_info(&self) -> Result<PowerReadings, Box<dyn std::error::Error>> {
         let output = self.execute_ipmi_command(&["sdr", "list", "full"])?;
-
-        let mut power_consumption = None;
-        let mut voltage = None;
-        let mut current = None;
-
-        for line in output.lines() {
-            if line.contains("Pwr Consumption") {
-                if let Some(value_str) = line.split('|').nth(1) {
-                    if let Ok(value) = value_str.trim().replace(" Watts", "").parse::<f64>() {
-                        power_consumption = Some(value);
-                    }
-                }
-            } else if line.contains("Voltage") && voltage.is_none() {
-                if let Some(value_str) = line.split('|').nth(1) {
-                    if let Ok(value) = value_str.trim().replace(" Volts", "").parse::<f64>() {
-                        voltage = Some(value);
-                    }
-                }
-            } else if line.contains("Current") && current.is_none() {
-                if let Some(value_str) = line.split('|').nth(1) {
-                    if let Ok(value) = value_str.trim().replace(" Amps", "").parse::<f64>() {
-                        current = Some(value);
-                    }
-                }
-            }
-        }
-
-        Ok((power_consumption, voltage, current))
+        Ok(parse_power_sdr(&output, &self.units))
     }
 
     /// 获取所有温度传感器数据
+    ///
+    /// 读取 `sdr elist full` 以获得每个传感器的实体ID，用于区分传感器所在位置
+    #[instrument(level = "debug", name = "ipmi.temperature_sensors", skip_all)]
     pub fn get_temperature_sensors(
         &self,
     ) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
-        let output = self.execute_ipmi_command(&["sdr", "list", "full"])?;
-        let mut sensors = Vec::new();
-        let timestamp = Utc::now();
-
-        for line in output.lines() {
-            if line.contains("Temp") && line.contains("degrees C") {
-                if let Some(parts) = self.parse_sensor_line(line) {
-                    let (name, value_str, status) = parts;
-
-                    if let Ok(temperature) =
-                        value_str.replace(" degrees C", "").trim().parse::<f64>()
-                    {
-                        let sensor_id = name.trim().replace(" ", "_").to_uppercase();
-                        let location = match sensor_id.as_str() {
-                            "INLET_TEMP" => "Server Inlet",
-                            "EXHAUST_TEMP" => "Server Exhaust",
-                            _ => "Internal Sensor",
-                        };
-
-                        sensors.push(TemperatureSensor {
-                            id: Uuid::new_v4().to_string(),
-                            sensor_id,
-                            temperature,
-                            unit: "°C".to_string(),
-                            location: location.to_string(),
-                            status: status.to_lowercase(),
-                            timestamp,
-                        });
-                    }
-                }
-            }
-        }
-
-        Ok(sensors)
+        let output = self.execute_ipmi_command(&["sdr", "elist", "full"])?;
+        Ok(parse_temperature_sdr_with(&output, Utc::now(), &self.units))
     }
 
     /// 获取所有风扇数据
+    #[instrument(level = "debug", name = "ipmi.fan_sensors", skip_all)]
     pub fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
-        let output = self.execute_ipmi_command(&["sdr", "list", "full"])?;
-        let mut fans = Vec::new();
-        let timestamp = Utc::now();
-
-        for line in output.lines() {
-            if line.contains("Fan") && line.contains("RPM") {
-                if let Some(parts) = self.parse_sensor_line(line) {
-                    let (name, value_str, status) = parts;
-
-                    if let Ok(speed_rpm) = value_str.replace(" RPM", "").trim().parse::<u32>() {
-                        let fan_id = name.trim().replace(" ", "_").to_uppercase();
-
-                        // 计算转速百分比 (假设最大转速为15000 RPM)
-                        let speed_percent = ((speed_rpm as f64 / 15000.0) * 100.0).min(100.0) as u8;
-
-                        let location = match fan_id.as_str() {
-                            "FAN1" | "FAN2" => "Front Intake",
-                            "FAN3" | "FAN4" => "CPU Cooling",
-                            "FAN5" | "FAN6" => "Rear Exhaust",
-                            _ => "Unknown Location",
-                        };
-
-                        fans.push(FanSensor {
-                            id: Uuid::new_v4().to_string(),
-                            fan_id,
-                            speed_rpm,
-                            speed_percent,
-                            status: status.to_lowercase(),
-                            location: location.to_string(),
-                            control_mode: "auto".to_string(),
-                            target_temp: None,
-                            timestamp,
-                        });
-                    }
-                }
-            }
-        }
-
+        let output = self.execute_ipmi_command(&["sdr", "elist", "full"])?;
+        let fans = parse_fan_sdr_with(&output, Utc::now(), &self.units);
+        self.record_fan_controllability(&fans);
         Ok(fans)
     }
 
-    /// 解析传感器数据行
-    fn parse_sensor_line(&self, line: &str) -> Option<(String, String, String)> {
-        let parts: Vec<&str> = line.split('|').collect();
-        if parts.len() >= 3 {
-            let name = parts[0].trim().to_string();
-            let value = parts[1].trim().to_string();
-            let status = parts[2].trim().to_string();
-            Some((name, value, status))
-        } else {
-            None
+    /// 记录风扇可控性，供下发转速前校验
+    ///
+    /// # 参数
+    /// * `fans` - 风扇读数
+    pub fn record_fan_controllability(&self, fans: &[FanSensor]) {
+        let mut controllability = self.fan_controllability.lock().unwrap_or_else(|e| e.into_inner());
+        for fan in fans {
+            controllability.insert(fan.fan_id.clone(), fan.controllable);
         }
     }
 
+    /// 风扇是否可调速，尚未从SDR读取到该风扇时返回None
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    pub fn is_fan_controllable(&self, fan_id: &str) -> Option<bool> {
+        self.fan_controllability
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(fan_id)
+            .copied()
+    }
+
+    /// 获取BMC自检状态
+    ///
+    /// BMC状态异常时传感器数据可能已不可信
+    #[instrument(level = "debug", name = "ipmi.self_test", skip_all)]
+    pub fn get_self_test_status(&self) -> Result<BmcSelfTestStatus, Box<dyn std::error::Error>> {
+        let output = self.execute_ipmi_command(&["mc", "selftest"])?;
+        Ok(BmcSelfTestStatus::parse(&output))
+    }
+
+    /// 读取DCMI瞬时功耗（W）
+    #[instrument(level = "debug", name = "ipmi.dcmi_power_reading", skip_all)]
+    pub fn get_dcmi_power_reading(&self) -> Result<f64, Box<dyn std::error::Error>> {
+        let output = self.execute_ipmi_command(ReadOnlyIpmiCommand::DcmiPowerReading.args())?;
+        parse_dcmi_power_reading(&output, &self.units).ok_or_else(|| "DCMI输出中没有瞬时功耗读数".into())
+    }
+
     /// 测试IPMI连接
+    #[instrument(level = "debug", name = "ipmi.test_connection", skip_all)]
     pub fn test_connection(&self) -> Result<bool, Box<dyn std::error::Error>> {
         match self.execute_ipmi_command(&["mc", "info"]) {
             Ok(_) => Ok(true),
@@ -289,6 +581,114 @@ impl IpmiService {
         }
     }
 
+    /// 根据BMC厂商识别风扇控制厂商配置档
+    ///
+    /// 优先按 `mc info` 中的厂商ID识别，厂商ID未知时再按厂商名称识别；
+    /// 无法读取系统信息或厂商未知时返回None
+    pub fn detect_vendor_profile(&self) -> Option<VendorProfile> {
+        match self.get_system_info() {
+            Ok(info) => info
+                .mc_info
+                .manufacturer_id
+                .and_then(VendorProfile::from_manufacturer_id)
+                .or_else(|| VendorProfile::detect(&info.manufacturer)),
+            Err(e) => {
+                error!("Failed to detect BMC vendor: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 当前风扇控制模式，尚未切换过时返回None
+    pub fn fan_control_mode(&self) -> Option<FanControlMode> {
+        *self.fan_control_mode.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 切换风扇控制模式
+    ///
+    /// # 参数
+    /// * `mode` - 目标控制模式
+    #[instrument(level = "debug", name = "ipmi.set_fan_control_mode", skip_all, fields(mode = ?mode))]
+    pub fn set_fan_control_mode(
+        &self,
+        mode: FanControlMode,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let commands = match mode {
+            FanControlMode::Manual => &self.fan_commands.manual_mode,
+            FanControlMode::Automatic => &self.fan_commands.automatic_mode,
+        };
+        for command in commands {
+            self.execute_raw_command(command)?;
+        }
+
+        *self.fan_control_mode.lock().unwrap_or_else(|e| e.into_inner()) = Some(mode);
+        info!("Fan control mode switched to {:?}", mode);
+        Ok(())
+    }
+
+    /// 设置风扇转速
+    ///
+    /// 未处于手动模式时先切换到手动模式，否则BMC会忽略转速命令
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `speed_percent` - 转速百分比（超过100按100处理）
+    #[instrument(level = "debug", name = "ipmi.set_fan_speed", skip_all, fields(fan_id = %fan_id, speed_percent = speed_percent))]
+    pub fn set_fan_speed(
+        &self,
+        fan_id: &str,
+        speed_percent: u8,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for command in self.fan_speed_command_plan(fan_id, speed_percent) {
+            self.execute_raw_command(&command)?;
+        }
+
+        *self.fan_control_mode.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some(FanControlMode::Manual);
+        Ok(())
+    }
+
+    /// 将风扇控制交还BMC自动模式
+    ///
+    /// 仅在曾切换到手动模式时下发命令，用于关闭服务或退出紧急模式
+    pub fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.fan_control_mode() == Some(FanControlMode::Manual) {
+            self.set_fan_control_mode(FanControlMode::Automatic)?;
+        }
+        Ok(())
+    }
+
+    /// 生成设置转速所需的raw命令，必要时以切换手动模式的命令开头
+    fn fan_speed_command_plan(&self, fan_id: &str, speed_percent: u8) -> Vec<String> {
+        let mut plan = Vec::new();
+        if self.fan_control_mode() != Some(FanControlMode::Manual) {
+            plan.extend(self.fan_commands.manual_mode.iter().cloned());
+        }
+
+        let fan = fan_id
+            .trim_start_matches(|c: char| !c.is_ascii_digit())
+            .parse::<u8>()
+            .ok()
+            .and_then(|index| index.checked_sub(1))
+            .map_or_else(|| "0xff".to_string(), |index| format!("0x{:02x}", index));
+        let speed = format!("0x{:02x}", speed_percent.min(100));
+
+        plan.extend(self.fan_commands.set_speed.iter().map(|command| {
+            command.replace("{fan}", &fan).replace("{speed}", &speed)
+        }));
+        plan
+    }
+
+    /// 执行 `ipmitool raw` 命令
+    ///
+    /// # 参数
+    /// * `command` - 空格分隔的字节序列
+    fn execute_raw_command(&self, command: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let mut args = vec!["raw"];
+        args.extend(command.split_whitespace());
+        self.execute_ipmi_command(&args)
+    }
+
     /// 获取特定温度传感器数据
     pub fn get_temperature_by_sensor(
         &self,
@@ -320,9 +720,298 @@ impl Default for IpmiConfig {
     }
 }
 
+/// 仅有转速计、不受BMC风扇命令控制的实体ID：电源（10）和电源模块（20），其风扇由电源固件调速
+const TACH_ONLY_ENTITY_IDS: &[u8] = &[10, 20];
+
+/// 解析 `ipmitool sdr elist full` 输出中的温度传感器
+///
+/// 每行格式为 `名称 | 传感器编号 | 状态 | 实体ID.实例 | 读数`，读数无法解析的传感器跳过
+///
+/// # 参数
+/// * `output` - ipmitool输出
+/// * `timestamp` - 读取时间
+pub fn parse_temperature_sdr(output: &str, timestamp: DateTime<Utc>) -> Vec<TemperatureSensor> {
+    parse_temperature_sdr_with(output, timestamp, &UnitNormalizer::default())
+}
+
+/// 按给定的单位归一化解析温度传感器
+///
+/// 读数单位归一化为温度单位的行即为温度传感器，华氏读数换算为摄氏度
+///
+/// # 参数
+/// * `output` - ipmitool输出
+/// * `timestamp` - 读取时间
+/// * `units` - 单位归一化
+pub fn parse_temperature_sdr_with(
+    output: &str,
+    timestamp: DateTime<Utc>,
+    units: &UnitNormalizer,
+) -> Vec<TemperatureSensor> {
+    let mut sensors = Vec::new();
+
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split('|').map(str::trim).collect();
+        let [name, _, status, entity, value] = parts[..] else {
+            continue;
+        };
+
+        let Some(reading) = units.normalize(value).filter(|reading| reading.unit.is_temperature()) else {
+            continue;
+        };
+
+        sensors.push(TemperatureSensor {
+            id: Uuid::new_v4().to_string(),
+            sensor_id: name.replace(" ", "_").to_uppercase(),
+            temperature: reading.value,
+            unit: reading.unit.symbol().to_string(),
+            raw_unit: reading.raw_unit,
+            location: classify_sensor_location(name, entity).to_string(),
+            entity: entity.to_string(),
+            status: status.to_lowercase(),
+            timestamp,
+        });
+    }
+
+    sensors
+}
+
+/// 按传感器名称和实体ID归类传感器位置
+///
+/// 名称中的关键字优先：不少BMC把进风、出风传感器挂在系统板实体（7）下，仅凭实体ID
+/// 无法区分；名称无法识别时再按IPMI规范的实体ID归类
+///
+/// # 参数
+/// * `name` - 传感器名称
+/// * `entity` - `实体ID.实例`
+pub fn classify_sensor_location(name: &str, entity: &str) -> &'static str {
+    let name = name.to_lowercase();
+    let by_name = [
+        ("inlet", "inlet"),
+        ("ambient", "inlet"),
+        ("exhaust", "exhaust"),
+        ("outlet", "exhaust"),
+        ("cpu", "cpu"),
+        ("proc", "cpu"),
+        ("psu", "psu"),
+        ("pwr supply", "psu"),
+        ("backplane", "backplane"),
+        ("hdd", "backplane"),
+        ("dimm", "memory"),
+        ("mem", "memory"),
+    ];
+    if let Some(location) = by_name
+        .iter()
+        .find_map(|&(keyword, location)| name.contains(keyword).then_some(location))
+    {
+        return location;
+    }
+
+    match entity.split('.').next().and_then(|id| id.parse::<u8>().ok()) {
+        Some(3) => "cpu",
+        Some(55) | Some(64) => "inlet",
+        Some(10) | Some(19) | Some(20) => "psu",
+        Some(15) => "backplane",
+        Some(8) | Some(32) => "memory",
+        Some(7) => "system_board",
+        _ => "other",
+    }
+}
+
+/// 解析 `ipmitool sdr elist full` 输出中的风扇
+///
+/// 每行格式为 `名称 | 传感器编号 | 状态 | 实体ID.实例 | 读数`，按实体ID区分可调速风扇与
+/// 仅有转速计的风扇；读数无法解析的风扇跳过
+///
+/// # 参数
+/// * `output` - ipmitool输出
+/// * `timestamp` - 读取时间
+pub fn parse_fan_sdr(output: &str, timestamp: DateTime<Utc>) -> Vec<FanSensor> {
+    parse_fan_sdr_with(output, timestamp, &UnitNormalizer::default())
+}
+
+/// 按给定的单位归一化解析风扇
+///
+/// 读数须不带单位或单位归一化为RPM
+///
+/// # 参数
+/// * `output` - ipmitool输出
+/// * `timestamp` - 读取时间
+/// * `units` - 单位归一化
+pub fn parse_fan_sdr_with(output: &str, timestamp: DateTime<Utc>, units: &UnitNormalizer) -> Vec<FanSensor> {
+    let mut fans = Vec::new();
+
+    for line in output.lines().filter(|line| line.contains("Fan") || line.contains("FAN")) {
+        let parts: Vec<&str> = line.split('|').map(str::trim).collect();
+        let [name, _, status, entity, value] = parts[..] else {
+            continue;
+        };
+
+        // 单个风扇读数无法解析时跳过该风扇，不影响本轮其余风扇
+        let rpm_reading = split_reading(value)
+            .filter(|(_, raw_unit)| raw_unit.is_empty() || units.unit(raw_unit) == Some(SensorUnit::Rpm));
+        let Some((speed_rpm, raw_unit)) =
+            rpm_reading.and_then(|(number, raw_unit)| Some((parse_rpm(number)?, raw_unit)))
+        else {
+            debug!("Skipping unreadable fan reading for {}: {:?}", name, value);
+            continue;
+        };
+
+        let fan_id = name.replace(" ", "_").to_uppercase();
+
+        // 计算转速百分比 (假设最大转速为15000 RPM)
+        let speed_percent = ((speed_rpm as f64 / 15000.0) * 100.0).min(100.0) as u8;
+
+        let location = match fan_id.as_str() {
+            "FAN1" | "FAN2" => "Front Intake",
+            "FAN3" | "FAN4" => "CPU Cooling",
+            "FAN5" | "FAN6" => "Rear Exhaust",
+            _ => "Unknown Location",
+        };
+
+        let entity_id = entity.split('.').next().and_then(|id| id.parse::<u8>().ok());
+        let controllable = !matches!(entity_id, Some(id) if TACH_ONLY_ENTITY_IDS.contains(&id));
+
+        fans.push(FanSensor {
+            id: Uuid::new_v4().to_string(),
+            fan_id,
+            speed_rpm,
+            unit: default_fan_unit(),
+            raw_unit: raw_unit.to_string(),
+            speed_percent,
+            status: status.to_lowercase(),
+            location: location.to_string(),
+            control_mode: "auto".to_string(),
+            target_temp: None,
+            controllable,
+            timestamp,
+        });
+    }
+
+    fans
+}
+
+fn default_fan_unit() -> String {
+    SensorUnit::Rpm.symbol().to_string()
+}
+
+/// 电源相关读数
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PowerReadings {
+    /// 功耗（W）
+    pub power_consumption: Option<f64>,
+    /// 电压（V），取第一个电压传感器
+    pub voltage: Option<f64>,
+    /// 电流（A），取第一个电流传感器
+    pub current: Option<f64>,
+    /// 各读数的原始单位写法
+    pub raw_units: BTreeMap<String, String>,
+}
+
+/// 解析 `ipmitool sdr list full` 输出中的功耗、电压和电流
+///
+/// 每行格式为 `名称 | 读数 | 状态`，单位须归一化为对应的规范单位
+///
+/// # 参数
+/// * `output` - ipmitool输出
+/// * `units` - 单位归一化
+pub fn parse_power_sdr(output: &str, units: &UnitNormalizer) -> PowerReadings {
+    let mut power = PowerReadings::default();
+
+    for line in output.lines() {
+        let (field, slot, unit) = if line.contains("Pwr Consumption") {
+            ("power_consumption", &mut power.power_consumption, SensorUnit::Watts)
+        } else if line.contains("Voltage") && power.voltage.is_none() {
+            ("voltage", &mut power.voltage, SensorUnit::Volts)
+        } else if line.contains("Current") && power.current.is_none() {
+            ("current", &mut power.current, SensorUnit::Amps)
+        } else {
+            continue;
+        };
+
+        let reading = line
+            .split('|')
+            .nth(1)
+            .and_then(|value| units.normalize(value))
+            .filter(|reading| reading.unit == unit);
+        if let Some(reading) = reading {
+            *slot = Some(reading.value);
+            power.raw_units.insert(field.to_string(), reading.raw_unit);
+        }
+    }
+
+    power
+}
+
+/// 解析 `ipmitool dcmi power reading` 输出中的瞬时功耗（W）
+///
+/// # 参数
+/// * `output` - ipmitool输出
+/// * `units` - 单位归一化
+pub fn parse_dcmi_power_reading(output: &str, units: &UnitNormalizer) -> Option<f64> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Instantaneous power reading:"))
+        .and_then(|value| units.normalize(value))
+        .filter(|reading| reading.unit == SensorUnit::Watts)
+        .map(|reading| reading.value)
+}
+
+/// 解析ipmitool输出中的风扇转速（RPM）
+///
+/// 支持千位分隔符（`1,200`）、单位后缀（`1200 RPM`）、小数（`1200.000`）和范围
+/// （`1100-1300 RPM`，取中值）。`na`、`no reading` 及格式错误的值返回 `None`
+///
+/// # 参数
+/// * `value` - 传感器读数列的原始文本
+pub fn parse_rpm(value: &str) -> Option<u32> {
+    let value = value.trim().to_ascii_lowercase();
+    let value = value.strip_suffix("rpm").unwrap_or(&value).trim();
+
+    let rpm = match value.split_once('-').or_else(|| value.split_once(" to ")) {
+        Some((low, high)) => {
+            let (low, high) = (parse_rpm_number(low)?, parse_rpm_number(high)?);
+            if low > high {
+                return None;
+            }
+            (low + high) / 2.0
+        }
+        None => parse_rpm_number(value)?,
+    };
+
+    (rpm <= u32::MAX as f64).then(|| rpm.round() as u32)
+}
+
+/// 解析单个非负转速数值，千位分隔符必须按三位一组出现
+fn parse_rpm_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+
+    let mut groups = integer.split(',');
+    let leading = groups.next()?;
+    let grouped: Vec<&str> = groups.collect();
+    if !grouped.is_empty() && (leading.is_empty() || leading.len() > 3 || grouped.iter().any(|g| g.len() != 3)) {
+        return None;
+    }
+
+    let digits = integer.replace(',', "");
+    if digits.is_empty()
+        || !digits.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    if fraction.is_empty() {
+        digits.parse().ok()
+    } else {
+        format!("{}.{}", digits, fraction).parse().ok()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
 
     #[test]
     fn test_ipmi_config_creation() {
@@ -339,4 +1028,338 @@ mod tests {
         // 基本的服务创建测试
         assert!(true); // 如果能创建服务就通过
     }
+
+    #[test]
+    fn test_ttl_cell_fetches_once_within_ttl() {
+        let cache = TtlCell::new(Duration::from_secs(60));
+        let invocations = std::cell::Cell::new(0);
+        let fetch = || -> Result<String, String> {
+            invocations.set(invocations.get() + 1);
+            Ok("Dell Inc.".to_string())
+        };
+
+        assert_eq!(cache.get_or_try_refresh(fetch).unwrap(), "Dell Inc.");
+        assert_eq!(cache.get_or_try_refresh(fetch).unwrap(), "Dell Inc.");
+        assert_eq!(invocations.get(), 1);
+
+        // 有效期为0时每次都重新获取
+        let uncached = TtlCell::new(Duration::ZERO);
+        uncached.get_or_try_refresh(fetch).unwrap();
+        uncached.get_or_try_refresh(fetch).unwrap();
+        assert_eq!(invocations.get(), 3);
+    }
+
+    #[test]
+    fn test_system_info_is_cached_per_cache_config() {
+        use crate::config::CacheBackend;
+        use crate::services::sensor_replay::{ReplayConfig, SensorReplay, SensorTrace, TraceReading, TraceTimestamp};
+
+        let replay = || {
+            let trace = SensorTrace::new(vec![TraceReading {
+                timestamp: TraceTimestamp::Seconds(0.0),
+                sensor: "CPU1 Temp".to_string(),
+                entity: "3.1".to_string(),
+                value: 52.0,
+                unit: "degrees C".to_string(),
+                status: "ok".to_string(),
+            }])
+            .unwrap();
+            Arc::new(SensorReplay::new(trace, &ReplayConfig::default()).unwrap())
+        };
+        let cache_config = |enabled| CacheConfig {
+            ttl: 60,
+            max_size: 1000,
+            enabled,
+            backend: CacheBackend::Memory,
+        };
+        let fetched_at = |service: &IpmiService| service.system_info_cache.entry.lock().unwrap().as_ref().unwrap().0;
+
+        // 通过 `Ipmi` trait 调用时同样命中缓存，有效期内不再重新读取
+        let service = Arc::new(IpmiService::new(IpmiConfig::default()).with_replay(replay()).with_cache_config(&cache_config(true)));
+        let ipmi: Arc<dyn Ipmi> = service.clone();
+        assert_eq!(ipmi.get_system_info().unwrap().manufacturer, "Sensor Replay");
+        let first_fetch = fetched_at(&service);
+        std::thread::sleep(Duration::from_millis(5));
+        ipmi.get_system_info().unwrap();
+        assert_eq!(fetched_at(&service), first_fetch);
+
+        let disabled = IpmiService::new(IpmiConfig::default()).with_replay(replay()).with_cache_config(&cache_config(false));
+        disabled.get_system_info().unwrap();
+        let first_fetch = fetched_at(&disabled);
+        std::thread::sleep(Duration::from_millis(5));
+        disabled.get_system_info().unwrap();
+        assert!(fetched_at(&disabled) > first_fetch);
+    }
+
+    #[test]
+    fn test_set_fan_speed_switches_to_manual_mode_first() {
+        let service = IpmiService::new(IpmiConfig::default());
+        assert_eq!(service.fan_control_mode(), None);
+
+        let plan = service.fan_speed_command_plan("Fan2", 40);
+        assert_eq!(plan, vec!["0x30 0x30 0x01 0x00", "0x30 0x30 0x02 0x01 0x28"]);
+
+        // 已处于手动模式时只下发转速命令
+        *service.fan_control_mode.lock().unwrap() = Some(FanControlMode::Manual);
+        let plan = service.fan_speed_command_plan("System Fan", 120);
+        assert_eq!(plan, vec!["0x30 0x30 0x02 0xff 0x64"]);
+
+        // 厂商命令序列可配置
+        let supermicro = IpmiService::new(IpmiConfig::default())
+            .with_fan_control_commands(FanControlCommands::supermicro());
+        let plan = supermicro.fan_speed_command_plan("FAN1", 50);
+        assert_eq!(plan[0], "0x30 0x45 0x01 0x01");
+        assert_eq!(plan[1], "0x30 0x70 0x66 0x01 0x00 0x32");
+    }
+
+    #[test]
+    fn test_vendor_profiles_emit_different_set_speed_bytes() {
+        let ipmi = crate::config::AppConfig::default().ipmi;
+        let (dell_profile, dell_commands) = ipmi
+            .resolve_fan_control(VendorProfile::detect("Dell Inc."))
+            .unwrap();
+        let (supermicro_profile, supermicro_commands) = ipmi
+            .resolve_fan_control(VendorProfile::detect("Supermicro"))
+            .unwrap();
+        assert_eq!(dell_profile, VendorProfile::Dell);
+        assert_eq!(supermicro_profile, VendorProfile::Supermicro);
+
+        let dell = IpmiService::new(IpmiConfig::default()).with_fan_control_commands(dell_commands);
+        let supermicro =
+            IpmiService::new(IpmiConfig::default()).with_fan_control_commands(supermicro_commands);
+        let dell_plan = dell.fan_speed_command_plan("Fan1", 50);
+        let supermicro_plan = supermicro.fan_speed_command_plan("Fan1", 50);
+
+        assert_eq!(dell_plan, vec!["0x30 0x30 0x01 0x00", "0x30 0x30 0x02 0x00 0x32"]);
+        assert_eq!(supermicro_plan, vec!["0x30 0x45 0x01 0x01", "0x30 0x70 0x66 0x01 0x00 0x32"]);
+        assert_ne!(dell_plan, supermicro_plan);
+    }
+
+    #[test]
+    fn test_parse_mc_info_captures_extended_fields() {
+        let output = "\
+Device ID                 : 32
+Device Revision           : 1
+Firmware Revision         : 2.65
+IPMI Version              : 2.0
+Manufacturer ID           : 674
+Manufacturer Name         : Unknown (0x2A2)
+Product ID                : 256 (0x0100)
+Product Name              : Unknown (0x100)
+Device Available          : yes
+Provides Device SDRs      : yes
+Additional Device Support :
+    Sensor Device
+    SDR Repository Device
+    SEL Device
+    FRU Inventory Device
+    IPMB Event Receiver
+    Bridge
+    Chassis Device
+Aux Firmware Rev Info     : 
+    0x00
+    0x04
+    0x41
+    0x00
+";
+        let info = IpmiSystemInfo::parse(output);
+
+        assert_eq!(info.device_id, "32");
+        assert_eq!(info.device_revision, "1");
+        assert_eq!(info.firmware_revision, "2.65");
+        assert_eq!(info.ipmi_version, "2.0");
+        assert_eq!(info.manufacturer_id, Some(674));
+        assert_eq!(info.product_id, Some(256));
+        assert_eq!(info.device_available, Some(true));
+        assert_eq!(info.provides_device_sdrs, Some(true));
+        assert_eq!(info.additional_device_support.len(), 7);
+        assert_eq!(info.additional_device_support[1], "SDR Repository Device");
+        assert_eq!(info.aux_firmware_revision, vec!["0x00", "0x04", "0x41", "0x00"]);
+
+        // 厂商名称无法识别时按厂商ID选择配置档
+        assert_eq!(VendorProfile::detect(&info.manufacturer_name), None);
+        assert_eq!(
+            info.manufacturer_id.and_then(VendorProfile::from_manufacturer_id),
+            Some(VendorProfile::Dell)
+        );
+        assert_eq!(VendorProfile::from_manufacturer_id(10876), Some(VendorProfile::Supermicro));
+    }
+
+    #[test]
+    fn test_custom_vendor_profile_requires_commands() {
+        let mut ipmi = crate::config::AppConfig::default().ipmi;
+        ipmi.vendor_profile = VendorProfile::Custom;
+        assert!(ipmi.resolve_fan_control(None).is_err());
+
+        let custom = FanControlCommands {
+            manual_mode: vec![],
+            automatic_mode: vec![],
+            set_speed: vec!["0x3a 0x01 {speed}".to_string()],
+        };
+        ipmi.fan_control = Some(custom.clone());
+        assert_eq!(
+            ipmi.resolve_fan_control(Some(VendorProfile::Dell)).unwrap(),
+            (VendorProfile::Custom, custom)
+        );
+    }
+
+    #[test]
+    fn test_parse_fan_sdr_classifies_tach_only_fans() {
+        let output = "\
+Fan1 RPM         | 30h | ok  |  7.1 | 3600 RPM
+Fan2 RPM         | 31h | ok  | 29.2 | 3480 RPM
+PSU1 Fan         | 71h | ok  | 10.1 | 5200 RPM
+PSU2 Fan         | 72h | ns  | 10.2 | No Reading
+Inlet Temp       | 04h | ok  |  7.1 | 23 degrees C
+";
+        let fans = parse_fan_sdr(output, Utc::now());
+        let classified: Vec<_> = fans.iter().map(|f| (f.fan_id.as_str(), f.speed_rpm, f.controllable)).collect();
+        assert_eq!(
+            classified,
+            vec![("FAN1_RPM", 3600, true), ("FAN2_RPM", 3480, true), ("PSU1_FAN", 5200, false)]
+        );
+
+        let service = IpmiService::new(IpmiConfig::default());
+        service.record_fan_controllability(&fans);
+        assert_eq!(service.is_fan_controllable("FAN1_RPM"), Some(true));
+        assert_eq!(service.is_fan_controllable("PSU1_FAN"), Some(false));
+        assert_eq!(service.is_fan_controllable("FAN9"), None);
+    }
+
+    #[test]
+    fn test_parse_sdr_normalizes_vendor_units() {
+        let output = "\
+CPU1 Temp        | 0Eh | ok  |  3.1 | 45 degrees C
+CPU2 Temp        | 0Fh | ok  |  3.2 | 46 °C
+Inlet Temp       | 04h | ok  |  7.1 | 23 Celsius
+Exhaust Temp     | 01h | ok  |  7.1 | 104 degrees F
+DIMM Temp        | 10h | ok  |  8.1 | 38 Deg. C
+Fan1             | 30h | ok  |  7.1 | 3600 RPM
+Fan2             | 31h | ok  |  7.1 | 3480 r/min
+Fan3             | 32h | ok  |  7.1 | 50 percent
+Fan4             | 33h | ok  |  7.1 | 3300
+";
+        let units = UnitNormalizer::default()
+            .with_aliases(&HashMap::from([("deg. c".to_string(), SensorUnit::Celsius)]));
+
+        let sensors = parse_temperature_sdr_with(output, Utc::now(), &units);
+        let readings: Vec<_> = sensors
+            .iter()
+            .map(|s| (s.sensor_id.as_str(), s.temperature, s.unit.as_str(), s.raw_unit.as_str()))
+            .collect();
+        assert_eq!(
+            readings,
+            vec![
+                ("CPU1_TEMP", 45.0, "°C", "degrees C"),
+                ("CPU2_TEMP", 46.0, "°C", "°C"),
+                ("INLET_TEMP", 23.0, "°C", "Celsius"),
+                ("EXHAUST_TEMP", 40.0, "°C", "degrees F"),
+                ("DIMM_TEMP", 38.0, "°C", "Deg. C"),
+            ]
+        );
+        // 未配置别名时无法识别的写法不当作温度
+        assert_eq!(parse_temperature_sdr(output, Utc::now()).len(), 4);
+
+        let fans: Vec<_> = parse_fan_sdr_with(output, Utc::now(), &units)
+            .into_iter()
+            .map(|f| (f.fan_id, f.speed_rpm, f.unit, f.raw_unit))
+            .collect();
+        assert_eq!(
+            fans,
+            vec![
+                ("FAN1".to_string(), 3600, "RPM".to_string(), "RPM".to_string()),
+                ("FAN2".to_string(), 3480, "RPM".to_string(), "r/min".to_string()),
+                ("FAN4".to_string(), 3300, "RPM".to_string(), String::new()),
+            ]
+        );
+
+        let power = parse_power_sdr(
+            "\
+Pwr Consumption  | 280 W             | ok
+Voltage 1        | 230 Volts         | ok
+Current 1        | 1.20 Amps         | ok
+",
+            &units,
+        );
+        assert_eq!(power.power_consumption, Some(280.0));
+        assert_eq!(power.voltage, Some(230.0));
+        assert_eq!(power.current, Some(1.2));
+        assert_eq!(power.raw_units.get("power_consumption").map(String::as_str), Some("W"));
+    }
+
+    #[test]
+    fn test_parse_rpm_formats() {
+        assert_eq!(parse_rpm("1,200 RPM"), Some(1200));
+        assert_eq!(parse_rpm("1200"), Some(1200));
+        assert_eq!(parse_rpm("1200.000"), Some(1200));
+        assert_eq!(parse_rpm("1,100-1,300 RPM"), Some(1200));
+        assert_eq!(parse_rpm("na"), None);
+        assert_eq!(parse_rpm("no reading"), None);
+        assert_eq!(parse_rpm("12x0 RPM"), None);
+        assert_eq!(parse_rpm("1,20"), None);
+        assert_eq!(parse_rpm("1500-1200"), None);
+    }
+
+    #[test]
+    fn test_parse_failing_self_test() {
+        let output = "Selftest: device error\nFailed components:\n SDR Repository empty\n BMC FRU device inaccessible\n";
+        let status = BmcSelfTestStatus::parse(output);
+        assert_eq!(status.result, BmcSelfTestResult::Failed);
+        assert!(status.is_failed());
+        assert_eq!(status.summary, "device error");
+        assert_eq!(
+            status.failed_components,
+            vec!["SDR Repository empty", "BMC FRU device inaccessible"]
+        );
+
+        let passed = BmcSelfTestStatus::parse("Selftest: passed\n");
+        assert_eq!(passed.result, BmcSelfTestResult::Passed);
+        assert!(passed.failed_components.is_empty());
+
+        let unsupported = BmcSelfTestStatus::parse("Selftest: not implemented\n");
+        assert_eq!(unsupported.result, BmcSelfTestResult::NotImplemented);
+    }
+
+    /// 记录新建span的名称和字段
+    #[derive(Clone, Default)]
+    struct SpanCapture(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SpanCapture {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _id: &tracing::span::Id,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = String::new();
+            attrs.record(&mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                fields.push_str(&format!("{}={:?};", field.name(), value));
+            });
+            self.0.lock().unwrap().push((attrs.metadata().name().to_string(), fields));
+        }
+    }
+
+    #[test]
+    fn test_ipmi_read_is_traced_without_credentials() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let capture = SpanCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        // open接口在没有本地BMC设备时立即失败，不会尝试网络连接
+        let service = IpmiService::new(IpmiConfig {
+            host: "10.0.0.1".to_string(),
+            username: "admin".to_string(),
+            password: "s3cret-pass".to_string(),
+            interface: "open".to_string(),
+        });
+        tracing::subscriber::with_default(subscriber, || {
+            let _ = service.get_fan_sensors();
+        });
+
+        let spans = capture.0.lock().unwrap().clone();
+        let names: Vec<&str> = spans.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["ipmi.fan_sensors", "ipmi.command"]);
+        assert_eq!(spans[1].1, "operation=sdr elist full;");
+        assert!(spans.iter().all(|(_, fields)| !fields.contains("s3cret-pass") && !fields.contains("admin")));
+    }
 }
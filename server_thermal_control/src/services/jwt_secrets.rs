@@ -0,0 +1,244 @@
+use crate::config::SecurityConfig;
+use crate::models::error::{AppError, AppResult};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+
+/// 新生成的签名密钥长度（字节）
+const SECRET_BYTES: usize = 32;
+
+/// 轮换后仍在宽限期内的旧密钥
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreviousJwtSecret {
+    /// 旧签名密钥
+    pub secret: String,
+    /// 旧密钥签发的令牌在该时间之后不再接受
+    pub valid_until: DateTime<Utc>,
+}
+
+/// 持久化的签名密钥
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct JwtSecretState {
+    /// 当前签名密钥
+    pub current: String,
+    /// 宽限期内的旧密钥
+    #[serde(default)]
+    pub previous: Option<PreviousJwtSecret>,
+    /// 最近一次轮换时间
+    #[serde(default)]
+    pub rotated_at: Option<DateTime<Utc>>,
+}
+
+/// 一次密钥轮换的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct JwtSecretRotation {
+    /// 新签名密钥，签发方需改用该密钥签名
+    pub secret: String,
+    /// 轮换时间
+    pub rotated_at: DateTime<Utc>,
+    /// 旧密钥签发的令牌在该时间之前仍然有效
+    pub previous_valid_until: DateTime<Utc>,
+}
+
+/// JWT签名密钥
+///
+/// 校验令牌时先用当前密钥，失败后在宽限期内再用轮换前的旧密钥，
+/// 轮换后已签发的令牌可以用到过期或宽限期结束，无需重启服务。
+/// 密钥保存在 `security.jwt_secret_file` 中，文件不存在时使用 `security.jwt_secret`
+#[derive(Clone)]
+pub struct JwtSecrets {
+    state: Arc<RwLock<JwtSecretState>>,
+    file_path: Option<PathBuf>,
+    grace: Duration,
+    rotation: Arc<Mutex<()>>,
+}
+
+impl JwtSecrets {
+    /// 创建签名密钥，不持久化
+    ///
+    /// # 参数
+    /// * `secret` - 当前签名密钥
+    /// * `grace_secs` - 轮换后旧密钥的宽限期（秒）
+    pub fn new(secret: impl Into<String>, grace_secs: u64) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(JwtSecretState {
+                current: secret.into(),
+                ..JwtSecretState::default()
+            })),
+            file_path: None,
+            grace: Duration::seconds(grace_secs as i64),
+            rotation: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// 按安全配置加载，密钥文件存在时以文件中的密钥为准
+    ///
+    /// # 参数
+    /// * `security` - 安全配置
+    pub fn load(security: &SecurityConfig) -> AppResult<Self> {
+        let mut secrets = Self::new(security.jwt_secret.clone(), security.jwt_rotation_grace_secs);
+        if security.jwt_secret_file.is_empty() {
+            return Ok(secrets);
+        }
+
+        let path = PathBuf::from(&security.jwt_secret_file);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                let state: JwtSecretState = serde_json::from_str(&content).map_err(|e| {
+                    AppError::file_system_error(format!("解析JWT密钥文件 {} 失败: {}", path.display(), e))
+                })?;
+                *secrets.state.write().unwrap_or_else(|e| e.into_inner()) = state;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(AppError::file_system_error(format!(
+                    "读取JWT密钥文件 {} 失败: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        }
+        secrets.file_path = Some(path);
+        Ok(secrets)
+    }
+
+    /// 当前密钥状态
+    pub fn state(&self) -> JwtSecretState {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 校验并解析令牌，当前密钥与宽限期内的旧密钥都无法校验时返回None
+    ///
+    /// # 参数
+    /// * `token` - JWT令牌
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Option<T> {
+        self.decode_at(token, Utc::now())
+    }
+
+    /// 以指定时间判断旧密钥是否仍在宽限期内
+    fn decode_at<T: DeserializeOwned>(&self, token: &str, now: DateTime<Utc>) -> Option<T> {
+        let state = self.state();
+        let previous = state
+            .previous
+            .iter()
+            .filter(|previous| previous.valid_until > now)
+            .map(|previous| previous.secret.as_str());
+        let validation = Validation::new(Algorithm::HS256);
+
+        std::iter::once(state.current.as_str())
+            .chain(previous)
+            .filter(|secret| !secret.is_empty())
+            .find_map(|secret| {
+                jsonwebtoken::decode::<T>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation).ok()
+            })
+            .map(|data| data.claims)
+    }
+
+    /// 生成新的签名密钥，当前密钥降为旧密钥并保留宽限期，两者一并写入密钥文件
+    ///
+    /// 写入失败时不切换密钥
+    pub async fn rotate(&self) -> AppResult<JwtSecretRotation> {
+        let _rotation = self.rotation.lock().await;
+
+        let rotated_at = Utc::now();
+        let previous_valid_until = rotated_at + self.grace;
+        let secret = general_purpose::URL_SAFE_NO_PAD.encode(rand::random::<[u8; SECRET_BYTES]>());
+        let state = JwtSecretState {
+            current: secret.clone(),
+            previous: Some(PreviousJwtSecret {
+                secret: self.state().current,
+                valid_until: previous_valid_until,
+            }),
+            rotated_at: Some(rotated_at),
+        };
+
+        if let Some(path) = &self.file_path {
+            save_state(path, &state).await?;
+        }
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = state;
+
+        Ok(JwtSecretRotation {
+            secret,
+            rotated_at,
+            previous_valid_until,
+        })
+    }
+}
+
+/// 写入密钥文件，先写临时文件再替换，避免中途失败留下损坏的文件
+async fn save_state(path: &Path, state: &JwtSecretState) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(state)?;
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::file_system_error(format!("创建JWT密钥目录失败: {}", e)))?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, content)
+        .await
+        .map_err(|e| AppError::file_system_error(format!("写入JWT密钥文件失败: {}", e)))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o600))
+            .await
+            .map_err(|e| AppError::file_system_error(format!("设置JWT密钥文件权限失败: {}", e)))?;
+    }
+    tokio::fs::rename(&tmp_path, path)
+        .await
+        .map_err(|e| AppError::file_system_error(format!("替换JWT密钥文件失败: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use serde_json::{json, Value};
+
+    fn sign(secret: &str) -> String {
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &json!({ "sub": "bob", "exp": Utc::now().timestamp() + 7200 }),
+            &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_previous_secret_validates_only_during_grace() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = AppConfig::default();
+        config.security.jwt_secret = "old-secret".to_string();
+        config.security.jwt_secret_file = dir.path().join("jwt_secrets.json").display().to_string();
+        config.security.jwt_rotation_grace_secs = 3600;
+
+        let secrets = JwtSecrets::load(&config.security).unwrap();
+        let old_token = sign("old-secret");
+        assert!(secrets.decode::<Value>(&old_token).is_some());
+
+        let rotation = secrets.rotate().await.unwrap();
+        assert_ne!(rotation.secret, "old-secret");
+        let new_token = sign(&rotation.secret);
+
+        // 宽限期内新旧密钥签发的令牌都有效
+        assert_eq!(secrets.decode::<Value>(&old_token).unwrap()["sub"], "bob");
+        assert!(secrets.decode::<Value>(&new_token).is_some());
+        assert!(secrets.decode::<Value>(&sign("other-secret")).is_none());
+
+        // 宽限期结束后旧令牌失效
+        let after_grace = rotation.previous_valid_until + Duration::seconds(1);
+        assert!(secrets.decode_at::<Value>(&old_token, after_grace).is_none());
+        assert!(secrets.decode_at::<Value>(&new_token, after_grace).is_some());
+
+        // 重启后从密钥文件恢复两个密钥
+        let reloaded = JwtSecrets::load(&config.security).unwrap();
+        assert_eq!(reloaded.state(), secrets.state());
+        assert!(reloaded.decode::<Value>(&old_token).is_some());
+    }
+}
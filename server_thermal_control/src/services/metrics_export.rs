@@ -0,0 +1,327 @@
+use crate::config::MetricsPushConfig;
+use crate::models::error::{AppError, AppResult};
+use crate::services::ipmi_service::{FanSensor, Ipmi, TemperatureSensor};
+use crate::services::poll_ticker::{PollOverruns, PollTicker};
+use crate::services::sensor_blacklist::SensorBlacklist;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// OpenMetrics文本格式的Content-Type
+pub const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// 读取IPMI传感器并生成OpenMetrics文本
+///
+/// 黑名单中的传感器不导出；任一类传感器读取失败时 `thermal_ipmi_up` 为0，
+/// 该类指标不输出
+///
+/// # 参数
+/// * `ipmi_service` - IPMI服务
+/// * `sensor_blacklist` - 传感器黑名单
+/// * `poll_overruns` - 各轮询任务的跳过计数
+pub fn collect_openmetrics(
+    ipmi_service: &dyn Ipmi,
+    sensor_blacklist: &SensorBlacklist,
+    poll_overruns: &PollOverruns,
+) -> String {
+    let temperatures = ipmi_service.get_temperature_sensors().map_err(|e| {
+        warn!("Failed to read temperature sensors for metrics: {}", e);
+    });
+    let fans = ipmi_service.get_fan_sensors().map_err(|e| {
+        warn!("Failed to read fan sensors for metrics: {}", e);
+    });
+
+    render_openmetrics(temperatures.ok().as_deref(), fans.ok().as_deref(), sensor_blacklist, poll_overruns)
+}
+
+/// 将传感器读数渲染为OpenMetrics文本
+///
+/// # 参数
+/// * `temperatures` - 温度读数，读取失败时为None
+/// * `fans` - 风扇读数，读取失败时为None
+/// * `sensor_blacklist` - 传感器黑名单
+/// * `poll_overruns` - 各轮询任务的跳过计数
+pub fn render_openmetrics(
+    temperatures: Option<&[TemperatureSensor]>,
+    fans: Option<&[FanSensor]>,
+    sensor_blacklist: &SensorBlacklist,
+    poll_overruns: &PollOverruns,
+) -> String {
+    let mut out = String::new();
+
+    let up = if temperatures.is_some() && fans.is_some() { 1 } else { 0 };
+    gauge_header(&mut out, "thermal_ipmi_up", "Whether the last IPMI sensor read succeeded");
+    let _ = writeln!(out, "thermal_ipmi_up {}", up);
+
+    if let Some(temperatures) = temperatures {
+        gauge_header(&mut out, "thermal_temperature_celsius", "Sensor temperature in degrees Celsius");
+        for sensor in temperatures.iter().filter(|s| !sensor_blacklist.contains(&s.sensor_id)) {
+            let _ = writeln!(
+                out,
+                "thermal_temperature_celsius{{sensor=\"{}\"}} {}",
+                escape_label(&sensor.sensor_id),
+                sensor.temperature
+            );
+        }
+    }
+
+    if let Some(fans) = fans {
+        let fans: Vec<_> = fans.iter().filter(|f| !sensor_blacklist.contains(&f.fan_id)).collect();
+        gauge_header(&mut out, "thermal_fan_speed_rpm", "Fan speed in RPM");
+        for fan in &fans {
+            let _ = writeln!(out, "thermal_fan_speed_rpm{{fan=\"{}\"}} {}", escape_label(&fan.fan_id), fan.speed_rpm);
+        }
+        gauge_header(&mut out, "thermal_fan_speed_percent", "Fan speed as a percentage of maximum");
+        for fan in &fans {
+            let _ = writeln!(
+                out,
+                "thermal_fan_speed_percent{{fan=\"{}\"}} {}",
+                escape_label(&fan.fan_id),
+                fan.speed_percent
+            );
+        }
+    }
+
+    let _ = writeln!(out, "# TYPE thermal_poll_overruns counter");
+    let _ = writeln!(
+        out,
+        "# HELP thermal_poll_overruns Poll intervals skipped because the previous poll was still running"
+    );
+    for (task, skipped) in poll_overruns.snapshot() {
+        let _ = writeln!(out, "thermal_poll_overruns_total{{task=\"{}\"}} {}", escape_label(&task), skipped);
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+fn gauge_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+}
+
+/// 转义标签值中的反斜杠、双引号和换行
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// 一次推送的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsPushReport {
+    /// 接收端地址
+    pub url: String,
+    /// 推送的样本数
+    pub samples: usize,
+    /// 推送时间
+    pub pushed_at: DateTime<Utc>,
+}
+
+/// 指标推送服务
+///
+/// 将OpenMetrics文本POST到 `metrics_push.url`，可定时执行也可手动触发
+#[derive(Debug, Clone)]
+pub struct MetricsPushService {
+    /// 推送配置
+    config: MetricsPushConfig,
+    /// HTTP客户端
+    client: reqwest::Client,
+}
+
+impl MetricsPushService {
+    /// 创建指标推送服务
+    ///
+    /// # 参数
+    /// * `config` - 推送配置
+    pub fn new(config: MetricsPushConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// 推送一段OpenMetrics文本
+    ///
+    /// # 参数
+    /// * `body` - OpenMetrics文本
+    pub async fn push(&self, body: String) -> AppResult<MetricsPushReport> {
+        let samples = body
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .count();
+
+        let push_error = |message: String| AppError::ExternalServiceError {
+            service: "metrics_push".to_string(),
+            message,
+        };
+        let response = self
+            .client
+            .post(&self.config.url)
+            .header(reqwest::header::CONTENT_TYPE, OPENMETRICS_CONTENT_TYPE)
+            .timeout(Duration::from_millis(self.config.timeout_ms))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| push_error(format!("推送指标失败: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(push_error(format!("接收端返回状态码 {}", response.status().as_u16())));
+        }
+
+        Ok(MetricsPushReport {
+            url: self.config.url.clone(),
+            samples,
+            pushed_at: Utc::now(),
+        })
+    }
+
+    /// 采集当前指标并推送
+    ///
+    /// # 参数
+    /// * `ipmi_service` - IPMI服务
+    /// * `sensor_blacklist` - 传感器黑名单
+    /// * `poll_overruns` - 各轮询任务的跳过计数
+    pub async fn push_current(
+        &self,
+        ipmi_service: Arc<dyn Ipmi>,
+        sensor_blacklist: SensorBlacklist,
+        poll_overruns: PollOverruns,
+    ) -> AppResult<MetricsPushReport> {
+        // ipmitool为阻塞调用，放到阻塞线程池中执行
+        let body = tokio::task::spawn_blocking(move || {
+            collect_openmetrics(ipmi_service.as_ref(), &sensor_blacklist, &poll_overruns)
+        })
+            .await
+            .map_err(|e| AppError::internal_server_error(format!("采集指标失败: {}", e)))?;
+        self.push(body).await
+    }
+
+    /// 启动定时推送任务
+    ///
+    /// # 参数
+    /// * `ipmi_service` - IPMI服务
+    /// * `sensor_blacklist` - 传感器黑名单
+    /// * `poll_overruns` - 各轮询任务的跳过计数，推送超过周期时也计入
+    pub fn start_push_task(
+        &self,
+        ipmi_service: Arc<dyn Ipmi>,
+        sensor_blacklist: SensorBlacklist,
+        poll_overruns: PollOverruns,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = PollTicker::new(
+                "metrics_push",
+                Duration::from_secs(service.config.interval_secs.max(1)),
+                poll_overruns.clone(),
+            );
+
+            loop {
+                ticker.tick().await;
+                let pushed = service
+                    .push_current(Arc::clone(&ipmi_service), sensor_blacklist.clone(), poll_overruns.clone())
+                    .await;
+                match pushed {
+                    Ok(report) => info!("Pushed {} metric samples to {}", report.samples, report.url),
+                    Err(e) => warn!("指标推送失败: {}", e),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 接收一次请求并返回204的本地远程写入端，返回地址和收到的原始请求
+    async fn mock_remote_write() -> (String, tokio::sync::oneshot::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // 读到请求体完整为止
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if n == 0 || text.ends_with("# EOF\n") {
+                    break;
+                }
+            }
+            let _ = socket
+                .write_all(b"HTTP/1.1 204 No Content\r\nconnection: close\r\n\r\n")
+                .await;
+            let _ = tx.send(String::from_utf8_lossy(&request).to_string());
+        });
+        (format!("http://{}/api/v1/import/prometheus", addr), rx)
+    }
+
+    fn temperature(sensor_id: &str, value: f64) -> TemperatureSensor {
+        TemperatureSensor {
+            id: sensor_id.to_string(),
+            sensor_id: sensor_id.to_string(),
+            temperature: value,
+            unit: "°C".to_string(),
+            raw_unit: "degrees C".to_string(),
+            location: "system_board".to_string(),
+            entity: "7.1".to_string(),
+            status: "ok".to_string(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn fan(fan_id: &str, speed_rpm: u32, speed_percent: u8) -> FanSensor {
+        FanSensor {
+            id: fan_id.to_string(),
+            fan_id: fan_id.to_string(),
+            speed_rpm,
+            unit: "RPM".to_string(),
+            raw_unit: "RPM".to_string(),
+            speed_percent,
+            status: "ok".to_string(),
+            location: "System".to_string(),
+            control_mode: "auto".to_string(),
+            target_temp: None,
+            controllable: true,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_push_sends_expected_series_to_remote_write() {
+        let (url, received) = mock_remote_write().await;
+        let service = MetricsPushService::new(MetricsPushConfig {
+            enabled: true,
+            url,
+            interval_secs: 60,
+            timeout_ms: 2000,
+        });
+
+        let body = render_openmetrics(
+            Some(&[temperature("CPU1 Temp", 55.5), temperature("phantom", 127.0)]),
+            Some(&[fan("FAN1", 1800, 45)]),
+            &SensorBlacklist::new(vec!["phantom".to_string()]),
+            &PollOverruns::default(),
+        );
+        let report = service.push(body).await.unwrap();
+        assert_eq!(report.samples, 4);
+
+        let request = received.await.unwrap();
+        assert!(request.starts_with("POST /api/v1/import/prometheus "));
+        assert!(request.contains(OPENMETRICS_CONTENT_TYPE));
+        assert!(request.contains("thermal_ipmi_up 1\n"));
+        assert!(request.contains("thermal_temperature_celsius{sensor=\"CPU1 Temp\"} 55.5\n"));
+        assert!(request.contains("thermal_fan_speed_rpm{fan=\"FAN1\"} 1800\n"));
+        assert!(request.contains("thermal_fan_speed_percent{fan=\"FAN1\"} 45\n"));
+        assert!(!request.contains("phantom"));
+        assert!(request.ends_with("# EOF\n"));
+    }
+}
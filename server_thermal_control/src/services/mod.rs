@@ -10,7 +10,71 @@
 // pub mod control_service;
 // pub mod alert_service;
 // pub mod config_service;
+pub mod alert_history_store;
+pub mod alert_monitor;
+pub mod alert_notifier;
+pub mod alert_preview;
+pub mod alert_rules;
+pub mod alert_stats_store;
+pub mod alert_store;
+pub mod alert_thresholds;
+pub mod annotation_store;
+pub mod auto_control_schedule;
+pub mod breach_debounce;
+pub mod channel_validation;
+pub mod config_snapshot;
+pub mod control_alert_sink;
+pub mod control_loop;
+pub mod control_supervisor;
+pub mod control_write_retry;
+pub mod decision_store;
+pub mod download_store;
+pub mod emergency_exit;
+pub mod event_broadcast;
+pub mod event_store;
+pub mod fan_cost;
+pub mod fan_curve_replay;
+pub mod fan_degradation;
+pub mod fan_kickstart;
+pub mod fan_thresholds;
+pub mod fan_snapshot_store;
+pub mod forced_poll;
+pub mod fleet_summary;
+pub mod health_breakdown;
+pub mod health_probe;
+pub mod incident_store;
 pub mod ipmi_service;
+pub mod jwt_secrets;
+pub mod metrics_export;
+pub mod parameter_diff;
+pub mod period_comparison;
+pub mod pid_autotune;
+pub mod poll_ticker;
+pub mod power_cap;
+pub mod quiet_hours;
+pub mod report_mailer;
+pub mod result_cache;
+pub mod retention_service;
+pub mod safe_boot;
+pub mod sensor_blacklist;
+pub mod sensor_cache;
+pub mod sensor_calibration;
+pub mod sensor_replay;
+pub mod sensor_source;
+pub mod sensor_units;
+pub mod shutdown_restore;
+pub mod stale_data_alert;
+pub mod startup_self_check;
+pub mod support_bundle;
+pub mod target_estimate;
+pub mod task_watchdog;
+pub mod temperature_histogram;
+pub mod temperature_history;
+pub mod thermal_gradient;
+pub mod template_store;
+pub mod threshold_learning;
+pub mod rule_state_store;
+pub mod webhook;
 mod test;
 // pub use fan_service::FanService;
 // pub use sensor_service::SensorService;
@@ -18,4 +82,5 @@ mod test;
 // pub use monitoring_service::MonitoringService;
 // pub use control_service::ControlService;
 // pub use alert_service::AlertService;
-// pub use config_service::ConfigService;
\ No newline at end of file
+// pub use config_service::ConfigService;
+pub use config_snapshot::ConfigSnapshotService;
\ No newline at end of file
@@ -1,9 +1,14 @@
+use crate::controllers::dashboard_controller::{
+    DashboardAlertSummary, DashboardControlSummary, DashboardHealthSummary, DashboardSummary,
+};
 use crate::controllers::monitoring_controller::{
-    HealthStatus, MonitoringDataCache, MonitoringDataPoint, MonitoringDataType, MonitoringMetrics,
+    HealthStatus, HistoricalDataParams, MonitoringDataCache, MonitoringDataPoint,
+    MonitoringDataType, MonitoringMetrics,
     MonitoringStatus, MonitoringTarget, RealtimeMonitoringData, SystemHealth, SystemHealthReport,
     SystemHealthStatus,
 };
 use crate::models::{
+    alert::{AlertSeverity, AlertType},
     config::MonitoringConfig,
     error::{AppError, AppResult},
     fan::FanReading,
@@ -13,18 +18,35 @@ use crate::models::{
     SensorType, TemperatureQuery,
 };
 use crate::services::{
-    alert_service::AlertService, fan_service::FanService, sensor_service::SensorService,
-    thermal_service::ThermalService,
+    alert_service::AlertService,
+    annotation_store::{self, AnnotatedSeries, AnnotationStore, InMemoryAnnotationStore, MAX_RANGE_ANNOTATIONS},
+    fan_service::FanService, health_breakdown::HealthBreakdown,
+    poll_ticker::{PollOverruns, PollTicker}, sensor_service::SensorService, stale_data_alert::{
+        StaleDataAlertConfig, StaleDataTransition, StaleDataWatch, STALE_DATA_ALERT_SOURCE,
+    },
+    task_watchdog::{Heartbeat, TaskWatchdog}, thermal_service::ThermalService,
 };
 use crate::utils::{logger::LoggerManager, time::TimeUtils};
+use chrono::Duration as ChronoDuration;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
+use std::future::Future;
 use tokio::time::{interval, Duration, Instant};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
+/// 两次强制轮询之间的默认最小间隔，避免频繁调用压垮BMC
+const DEFAULT_FORCED_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 停止时等待监控任务完成当前周期的最长时间，超时后强制中止
+const TASK_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 仪表盘显示最近多少小时内的注释
+const DASHBOARD_ANNOTATION_HOURS: i64 = 24;
+
 /// 监控服务
 ///
 /// 负责系统监控、数据收集和状态跟踪
@@ -44,10 +66,26 @@ pub struct MonitoringService {
     status: Arc<RwLock<MonitoringStatus>>,
     /// 监控任务句柄
     task_handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,
+    /// 监控任务的取消令牌，每次启动任务前替换
+    cancel_token: Arc<RwLock<CancellationToken>>,
     /// 监控数据缓存
     data_cache: Arc<RwLock<MonitoringDataCache>>,
     /// 性能指标
     metrics: Arc<RwLock<MonitoringMetrics>>,
+    /// 已读到过的温度传感器和风扇，键为 `temperature:<ID>` 或 `fan:<ID>`
+    seen_components: Arc<RwLock<HashSet<String>>>,
+    /// 两次强制轮询之间的最小间隔
+    forced_poll_interval: Duration,
+    /// 最近一次强制轮询的开始时间
+    last_forced_poll: Arc<Mutex<Option<Instant>>>,
+    /// 后台任务看门狗，设置后监控任务崩溃或卡住时自动重启
+    watchdog: Option<Arc<TaskWatchdog>>,
+    /// 监控数据过期告警配置，设置后监控数据长时间未更新时经通知渠道告警
+    stale_data_alert: Option<StaleDataAlertConfig>,
+    /// 采集任务因上一次采集未结束而跳过的周期数
+    poll_overruns: PollOverruns,
+    /// 操作注释，叠加在历史数据和仪表盘上
+    annotations: Arc<dyn AnnotationStore>,
 }
 
 impl MonitoringService {
@@ -72,11 +110,64 @@ impl MonitoringService {
             config: Arc::new(RwLock::new(MonitoringConfig::default())),
             status: Arc::new(RwLock::new(MonitoringStatus::default())),
             task_handles: Arc::new(Mutex::new(Vec::new())),
+            cancel_token: Arc::new(RwLock::new(CancellationToken::new())),
             data_cache: Arc::new(RwLock::new(MonitoringDataCache::default())),
             metrics: Arc::new(RwLock::new(MonitoringMetrics::default())),
+            seen_components: Arc::new(RwLock::new(HashSet::new())),
+            forced_poll_interval: DEFAULT_FORCED_POLL_INTERVAL,
+            last_forced_poll: Arc::new(Mutex::new(None)),
+            watchdog: None,
+            stale_data_alert: None,
+            poll_overruns: PollOverruns::default(),
+            annotations: Arc::new(InMemoryAnnotationStore::new()),
         }
     }
 
+    /// 设置两次强制轮询之间的最小间隔
+    ///
+    /// # 参数
+    /// * `interval` - 最小间隔
+    pub fn with_forced_poll_interval(mut self, interval: Duration) -> Self {
+        self.forced_poll_interval = interval;
+        self
+    }
+
+    /// 共享采集任务的跳过计数，用于指标导出
+    ///
+    /// # 参数
+    /// * `poll_overruns` - 跳过计数
+    pub fn with_poll_overruns(mut self, poll_overruns: PollOverruns) -> Self {
+        self.poll_overruns = poll_overruns;
+        self
+    }
+
+    /// 共享注释存储，与 `POST /api/v1/annotations` 添加的注释一致
+    ///
+    /// # 参数
+    /// * `annotations` - 注释存储
+    pub fn with_annotation_store(mut self, annotations: Arc<dyn AnnotationStore>) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    /// 设置后台任务看门狗
+    ///
+    /// # 参数
+    /// * `watchdog` - 监控任务panic或超过心跳超时未上报心跳时自动重启
+    pub fn with_watchdog(mut self, watchdog: Arc<TaskWatchdog>) -> Self {
+        self.watchdog = Some(watchdog);
+        self
+    }
+
+    /// 设置监控数据过期告警，配置未启用时不设置
+    ///
+    /// # 参数
+    /// * `config` - 过期告警配置
+    pub fn with_stale_data_alert(mut self, config: StaleDataAlertConfig) -> Self {
+        self.stale_data_alert = config.enabled.then_some(config);
+        self
+    }
+
     /// 启动监控服务
     pub async fn start(&self) -> AppResult<()> {
         info!("启动监控服务");
@@ -88,6 +179,7 @@ impl MonitoringService {
             status.started_at = Some(Utc::now());
             status.last_update = Some(Utc::now());
         }
+        *self.cancel_token.write().await = CancellationToken::new();
 
         // 启动各种监控任务
         self.start_temperature_monitoring().await?;
@@ -96,15 +188,20 @@ impl MonitoringService {
         self.start_system_health_monitoring().await?;
         self.start_performance_monitoring().await?;
         self.start_data_cleanup_task().await?;
+        self.start_stale_data_watch().await?;
 
         info!("监控服务启动完成");
         Ok(())
     }
 
     /// 停止监控服务
+    ///
+    /// 通知所有监控任务停止并等待其完成当前周期，超时仍未退出的任务被强制中止
     pub async fn stop(&self) -> AppResult<()> {
         info!("停止监控服务");
 
+        self.shutdown_tasks(TASK_STOP_TIMEOUT).await;
+
         // 更新状态
         {
             let mut status = self.status.write().await;
@@ -112,12 +209,6 @@ impl MonitoringService {
             status.stopped_at = Some(Utc::now());
         }
 
-        // 停止所有监控任务
-        let mut handles = self.task_handles.lock().await;
-        for handle in handles.drain(..) {
-            handle.abort();
-        }
-
         info!("监控服务已停止");
         Ok(())
     }
@@ -143,6 +234,10 @@ impl MonitoringService {
         new_config.validate()?;
 
         // 更新配置
+        self.data_cache
+            .write()
+            .await
+            .set_recent_capacity(new_config.recent_readings_capacity);
         {
             let mut config = self.config.write().await;
             *config = new_config;
@@ -195,8 +290,8 @@ impl MonitoringService {
                             .get_temperature_history(&TemperatureQuery {
                                 server_id: None,
                                 sensor_id: None,
-                                start_time: None,
-                                end_time: None,
+                                start_time: Some(start_time),
+                                end_time: Some(end_time),
                                 page: None,
                                 limit: None,
                             })
@@ -277,60 +372,153 @@ impl MonitoringService {
         Ok(data_points)
     }
 
-    /// 获取监控指标
-    pub async fn get_metrics(&self) -> MonitoringMetrics {
-        self.metrics.read().await.clone()
-    }
+    /// 按查询参数获取历史数据
+    ///
+    /// 校验时间范围与跨度上限，并在指定分辨率时按时间桶降采样；
+    /// 与查询时间范围有交集的注释随数据一起返回
+    ///
+    /// # 参数
+    /// * `params` - 历史数据查询参数
+    pub async fn query_historical_data(
+        &self,
+        params: HistoricalDataParams,
+    ) -> AppResult<AnnotatedSeries<MonitoringDataPoint>> {
+        let end_time = params.end_time.unwrap_or_else(Utc::now);
+        let start_time = params
+            .start_time
+            .unwrap_or(end_time - ChronoDuration::hours(1));
+
+        let max_span_hours = self.config.read().await.max_history_span_hours;
+        Self::validate_history_range(
+            start_time,
+            end_time,
+            ChronoDuration::hours(max_span_hours as i64),
+        )?;
+
+        let resolution = params
+            .resolution
+            .as_deref()
+            .map(TimeUtils::parse_duration)
+            .transpose()?;
+        if let Some(resolution) = resolution {
+            if resolution <= ChronoDuration::zero() {
+                return Err(AppError::validation_error("resolution", "分辨率必须大于0"));
+            }
+        }
 
-    /// 获取系统健康报告
-    pub async fn get_health_report(&self) -> AppResult<SystemHealthReport> {
-        let cache = self.data_cache.read().await;
-        let metrics = self.metrics.read().await;
+        let mut data_points = self
+            .get_historical_data(
+                start_time,
+                end_time,
+                params.data_type.unwrap_or(MonitoringDataType::All),
+            )
+            .await?;
 
-        // 计算整体健康分数
-        let mut health_score = 100.0;
-        let mut issues = Vec::new();
+        if let Some(resolution) = resolution {
+            data_points = Self::downsample_data_points(data_points, start_time, resolution);
+        }
+        if let Some(limit) = params.limit {
+            data_points.truncate(limit);
+        }
 
-        // 检查温度状态
-        if let Some(temp_data) = &cache.latest_temperature_data {
-            for reading in temp_data {
-                if reading.temperature > 80.0 {
-                    health_score -= 10.0;
-                    issues.push(format!(
-                        "传感器 {} 温度过高: {:.1}°C",
-                        reading.sensor_id, reading.temperature
-                    ));
-                } else if reading.temperature > 70.0 {
-                    health_score -= 5.0;
-                    issues.push(format!(
-                        "传感器 {} 温度偏高: {:.1}°C",
-                        reading.sensor_id, reading.temperature
-                    ));
-                }
-            }
+        annotation_store::annotate(self.annotations.as_ref(), data_points, start_time, end_time).await
+    }
+
+    /// 校验历史数据查询的时间范围
+    ///
+    /// # 参数
+    /// * `start_time` - 开始时间
+    /// * `end_time` - 结束时间
+    /// * `max_span` - 允许的最大跨度
+    fn validate_history_range(
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        max_span: ChronoDuration,
+    ) -> AppResult<()> {
+        if start_time >= end_time {
+            return Err(AppError::validation_error(
+                "start_time",
+                "开始时间必须早于结束时间",
+            ));
+        }
+        if end_time - start_time > max_span {
+            return Err(AppError::validation_error(
+                "end_time",
+                format!("查询时间跨度不能超过 {} 小时", max_span.num_hours()),
+            ));
         }
+        Ok(())
+    }
 
-        // 检查风扇状态
-        if let Some(fan_data) = &cache.latest_fan_data {
-            for reading in fan_data {
-                if reading.rpm == 0 && reading.speed_percent > 0.0 {
-                    health_score -= 15.0;
-                    issues.push(format!(
-                        "风扇 {} 可能故障: 设置转速 {:.1}% 但RPM为0",
-                        reading.fan_id, reading.speed_percent
-                    ));
+    /// 按时间桶对数据点降采样
+    ///
+    /// 同一组件、同一数据类型在同一时间桶内的数据取平均值，时间戳取桶的起始时间
+    ///
+    /// # 参数
+    /// * `data_points` - 原始数据点
+    /// * `origin` - 时间桶起点
+    /// * `bucket` - 时间桶长度
+    fn downsample_data_points(
+        data_points: Vec<MonitoringDataPoint>,
+        origin: DateTime<Utc>,
+        bucket: ChronoDuration,
+    ) -> Vec<MonitoringDataPoint> {
+        let bucket_ms = bucket.num_milliseconds().max(1);
+        let mut buckets: Vec<(MonitoringDataPoint, f64, usize)> = Vec::new();
+        let mut index: HashMap<(String, String, i64), usize> = HashMap::new();
+
+        for point in data_points {
+            let bucket_index = (point.timestamp - origin).num_milliseconds().div_euclid(bucket_ms);
+            let key = (
+                point.component_id.clone(),
+                format!("{:?}", point.data_type),
+                bucket_index,
+            );
+
+            match index.get(&key) {
+                Some(&i) => {
+                    let (representative, sum, count) = &mut buckets[i];
+                    *sum += point.value;
+                    *count += 1;
+                    // 状态取桶内最新的数据点
+                    representative.status = point.status;
+                }
+                None => {
+                    index.insert(key, buckets.len());
+                    let value = point.value;
+                    let mut representative = point;
+                    representative.timestamp =
+                        origin + ChronoDuration::milliseconds(bucket_index * bucket_ms);
+                    representative.metadata = None;
+                    buckets.push((representative, value, 1));
                 }
             }
         }
 
-        // 检查活跃告警
-        let active_alert_count = cache.active_alerts.len();
-        if active_alert_count > 0 {
-            health_score -= active_alert_count as f64 * 5.0;
-            issues.push(format!("存在 {} 个活跃告警", active_alert_count));
-        }
+        let mut result: Vec<MonitoringDataPoint> = buckets
+            .into_iter()
+            .map(|(mut point, sum, count)| {
+                point.value = sum / count as f64;
+                point
+            })
+            .collect();
+        result.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        result
+    }
 
-        health_score = health_score.max(0.0).min(100.0);
+    /// 获取监控指标
+    pub async fn get_metrics(&self) -> MonitoringMetrics {
+        self.metrics.read().await.clone()
+    }
+
+    /// 获取系统健康报告
+    pub async fn get_health_report(&self) -> AppResult<SystemHealthReport> {
+        let cache = self.data_cache.read().await;
+        let metrics = self.metrics.read().await;
+
+        let breakdown = Self::assess_health(&cache);
+        let health_score = breakdown.score;
+        let active_alert_count = cache.active_alerts.as_ref().map_or(0, Vec::len);
 
         let health_status = if health_score >= 90.0 {
             HealthStatus::Healthy
@@ -350,13 +538,90 @@ impl MonitoringService {
             health_status,
             component_count: metrics.monitored_components,
             active_alerts: active_alert_count,
-            issues,
+            issues: breakdown.issues(),
+            score_breakdown: breakdown,
             uptime_seconds: metrics.uptime_seconds,
             last_maintenance: None, // 需要从配置或数据库获取
             next_maintenance: None, // 需要从配置计算
         })
     }
 
+    /// 获取仪表盘汇总数据
+    ///
+    /// 只读取监控缓存，不触发IPMI调用
+    ///
+    /// # 参数
+    /// * `control` - 控制状态摘要
+    pub async fn get_dashboard(&self, control: DashboardControlSummary) -> DashboardSummary {
+        let now = Utc::now();
+        let mut dashboard = {
+            let cache = self.data_cache.read().await;
+            Self::build_dashboard(&cache, control, now)
+        };
+
+        // 注释读取失败不影响仪表盘其余部分
+        let since = now - ChronoDuration::hours(DASHBOARD_ANNOTATION_HOURS);
+        match self.annotations.range(since, now, MAX_RANGE_ANNOTATIONS).await {
+            Ok(annotations) => dashboard.annotations = annotations,
+            Err(e) => warn!("读取仪表盘注释失败: {}", e),
+        }
+        dashboard
+    }
+
+    /// 根据监控缓存构建仪表盘汇总数据
+    ///
+    /// # 参数
+    /// * `cache` - 监控数据缓存
+    /// * `control` - 控制状态摘要
+    /// * `generated_at` - 生成时间
+    fn build_dashboard(
+        cache: &MonitoringDataCache,
+        control: DashboardControlSummary,
+        generated_at: DateTime<Utc>,
+    ) -> DashboardSummary {
+        let mut alerts = DashboardAlertSummary::default();
+        for alert in cache.active_alerts.iter().flatten() {
+            alerts.total += 1;
+            *alerts.by_severity.entry(alert.severity.to_lowercase()).or_insert(0) += 1;
+        }
+
+        let breakdown = Self::assess_health(cache);
+        let (score, issues) = (breakdown.score, breakdown.issues());
+        let status = if score >= 75.0 {
+            SystemHealthStatus::Healthy
+        } else if score >= 40.0 {
+            SystemHealthStatus::Warning
+        } else {
+            SystemHealthStatus::Critical
+        };
+
+        DashboardSummary {
+            generated_at,
+            sensors: cache.latest_temperature_data.clone().unwrap_or_default(),
+            alerts,
+            control,
+            health: DashboardHealthSummary {
+                score,
+                status,
+                issues,
+            },
+            annotations: Vec::new(),
+        }
+    }
+
+    /// 根据监控缓存评估整体健康分数（0-100）及逐项扣分明细
+    ///
+    /// # 参数
+    /// * `cache` - 监控数据缓存
+    fn assess_health(cache: &MonitoringDataCache) -> HealthBreakdown {
+        let active_alert_count = cache.active_alerts.as_ref().map_or(0, Vec::len);
+        HealthBreakdown::assess(
+            cache.latest_temperature_data.as_deref().unwrap_or_default(),
+            cache.latest_fan_data.as_deref().unwrap_or_default(),
+            active_alert_count,
+        )
+    }
+
     /// 添加自定义监控目标
     ///
     /// # 参数
@@ -419,60 +684,171 @@ impl MonitoringService {
         Ok(())
     }
 
+    /// 立即执行一轮温度、风扇、传感器轮询，刷新缓存并返回最新数据
+    ///
+    /// 距上次强制轮询不足最小间隔时直接拒绝；并发调用中只有第一个会真正访问BMC
+    pub async fn force_poll(&self) -> AppResult<RealtimeMonitoringData> {
+        {
+            let mut last_forced_poll = self.last_forced_poll.lock().await;
+            let now = Instant::now();
+            if let Some(last) = *last_forced_poll {
+                let elapsed = now.duration_since(last);
+                if elapsed < self.forced_poll_interval {
+                    let remaining = self.forced_poll_interval - elapsed;
+                    return Err(AppError::rate_limit_error(format!(
+                        "强制轮询过于频繁，请在 {:.1} 秒后重试",
+                        remaining.as_secs_f64()
+                    )));
+                }
+            }
+            *last_forced_poll = Some(now);
+        }
+
+        info!("执行强制轮询");
+
+        self.collect_temperature_data().await?;
+        self.collect_fan_data().await?;
+        self.collect_sensor_data().await?;
+        self.update_system_health().await?;
+
+        let polled_at = Utc::now();
+        {
+            let mut metrics = self.metrics.write().await;
+            metrics.last_collection_time = Some(polled_at);
+            metrics.total_collections += 1;
+        }
+
+        let mut cache = self.data_cache.write().await;
+        cache.last_update = polled_at;
+
+        Ok(RealtimeMonitoringData {
+            timestamp: polled_at,
+            temperature_data: cache.latest_temperature_data.clone(),
+            fan_data: cache.latest_fan_data.clone(),
+            sensor_data: cache.latest_sensor_data.clone(),
+            health_data: None,
+            alert_data: None,
+        })
+    }
+
     // 私有方法
 
+    /// 启动后台监控任务并登记句柄
+    ///
+    /// 任务应在每个周期开始前检查取消令牌，已开始的周期执行完毕后再退出；
+    /// 设置了看门狗时任务在其监督下运行，每个周期开始时上报心跳
+    ///
+    /// # 参数
+    /// * `name` - 任务名称
+    /// * `heartbeat_timeout` - 心跳超时，为空时看门狗只检测panic
+    /// * `task` - 接收取消令牌和心跳并返回任务的闭包，看门狗重启任务时再次调用
+    async fn spawn_task<F, Fut>(&self, name: &str, heartbeat_timeout: Option<Duration>, task: F)
+    where
+        F: Fn(CancellationToken, Heartbeat) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let token = self.cancel_token.read().await.clone();
+        let handle = match &self.watchdog {
+            Some(watchdog) => watchdog.supervise(name, heartbeat_timeout, move |heartbeat| {
+                task(token.clone(), heartbeat)
+            }),
+            None => tokio::spawn(task(token, Heartbeat::default())),
+        };
+        self.task_handles.lock().await.push(handle);
+    }
+
+    /// 周期性监控任务的心跳超时，未设置看门狗时为空
+    fn heartbeat_timeout(&self) -> Option<Duration> {
+        self.watchdog.as_ref().map(|watchdog| watchdog.heartbeat_timeout())
+    }
+
+    /// 取消所有监控任务并等待退出
+    ///
+    /// # 参数
+    /// * `timeout` - 等待所有任务退出的总时长
+    async fn shutdown_tasks(&self, timeout: Duration) {
+        self.cancel_token.read().await.cancel();
+
+        let handles: Vec<_> = self.task_handles.lock().await.drain(..).collect();
+        let deadline = Instant::now() + timeout;
+        for mut handle in handles {
+            if tokio::time::timeout_at(deadline, &mut handle).await.is_err() {
+                warn!("监控任务未在 {:?} 内退出，强制中止", timeout);
+                handle.abort();
+            }
+        }
+    }
+
     /// 启动温度监控
     async fn start_temperature_monitoring(&self) -> AppResult<()> {
         let thermal_service = Arc::clone(&self.thermal_service);
         let data_cache = Arc::clone(&self.data_cache);
         let config = Arc::clone(&self.config);
         let metrics = Arc::clone(&self.metrics);
+        let status = Arc::clone(&self.status);
+        let seen_components = Arc::clone(&self.seen_components);
+        let poll_overruns = self.poll_overruns.clone();
+
+        self.spawn_task("temperature_monitoring", self.heartbeat_timeout(), move |token, heartbeat| {
+            let thermal_service = Arc::clone(&thermal_service);
+            let data_cache = Arc::clone(&data_cache);
+            let config = Arc::clone(&config);
+            let metrics = Arc::clone(&metrics);
+            let status = Arc::clone(&status);
+            let seen_components = Arc::clone(&seen_components);
+            let poll_overruns = poll_overruns.clone();
+            async move {
+                let mut ticker = PollTicker::new("temperature_monitoring", Duration::from_secs(30), poll_overruns);
+
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = token.cancelled() => break,
+                        _ = ticker.tick() => {}
+                    }
+                    heartbeat.beat();
 
-        let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(30)); // 默认30秒间隔
+                    // 检查配置的监控间隔
+                    let monitoring_interval = {
+                        let cfg = config.read().await;
+                        cfg.temperature_monitoring_interval
+                    };
 
-            loop {
-                interval.tick().await;
+                    // 更新间隔
+                    ticker.set_period(Duration::from_secs(monitoring_interval));
 
-                // 检查配置的监控间隔
-                let monitoring_interval = {
-                    let cfg = config.read().await;
-                    cfg.temperature_monitoring_interval
-                };
+                    // 收集温度数据
+                    if let Ok(sensors) = thermal_service.get_temperature_sensors().await {
+                        let mut temperature_readings = Vec::new();
 
-                // 更新间隔
-                interval = interval(Duration::from_secs(monitoring_interval));
+                        for sensor_id in sensors {
+                            if thermal_service.sensor_blacklist().contains(&sensor_id) {
+                                continue;
+                            }
+                            if let Ok(reading) =
+                                thermal_service.get_current_temperature(&sensor_id).await
+                            {
+                                temperature_readings.push(reading);
+                            }
+                        }
 
-                // 收集温度数据
-                if let Ok(sensors) = thermal_service.get_temperature_sensors().await {
-                    let mut temperature_readings = Vec::new();
+                        let component_ids = Self::temperature_component_ids(&temperature_readings);
 
-                    for sensor_id in sensors {
-                        if let Ok(reading) =
-                            thermal_service.get_current_temperature(&sensor_id).await
+                        // 更新缓存
+                        data_cache.write().await.update_temperature(temperature_readings);
+                        Self::record_components(&seen_components, &metrics, &status, component_ids).await;
+
+                        // 更新指标
                         {
-                            temperature_readings.push(reading);
+                            let mut m = metrics.write().await;
+                            m.temperature_collections += 1;
+                            m.last_collection_time = Some(Utc::now());
                         }
                     }
-
-                    // 更新缓存
-                    {
-                        let mut cache = data_cache.write().await;
-                        cache.latest_temperature_data = Some(temperature_readings);
-                        cache.last_update = Utc::now();
-                    }
-
-                    // 更新指标
-                    {
-                        let mut m = metrics.write().await;
-                        m.temperature_collections += 1;
-                        m.last_collection_time = Some(Utc::now());
-                    }
                 }
             }
-        });
-
-        self.task_handles.lock().await.push(handle);
+        })
+        .await;
         Ok(())
     }
 
@@ -482,47 +858,62 @@ impl MonitoringService {
         let data_cache = Arc::clone(&self.data_cache);
         let config = Arc::clone(&self.config);
         let metrics = Arc::clone(&self.metrics);
+        let status = Arc::clone(&self.status);
+        let seen_components = Arc::clone(&self.seen_components);
+        let poll_overruns = self.poll_overruns.clone();
+
+        self.spawn_task("fan_monitoring", self.heartbeat_timeout(), move |token, heartbeat| {
+            let fan_service = Arc::clone(&fan_service);
+            let data_cache = Arc::clone(&data_cache);
+            let config = Arc::clone(&config);
+            let metrics = Arc::clone(&metrics);
+            let status = Arc::clone(&status);
+            let seen_components = Arc::clone(&seen_components);
+            let poll_overruns = poll_overruns.clone();
+            async move {
+                let mut ticker = PollTicker::new("fan_monitoring", Duration::from_secs(30), poll_overruns);
+
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = token.cancelled() => break,
+                        _ = ticker.tick() => {}
+                    }
+                    heartbeat.beat();
 
-        let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(30));
-
-            loop {
-                interval.tick().await;
-
-                let monitoring_interval = {
-                    let cfg = config.read().await;
-                    cfg.fan_monitoring_interval
-                };
+                    let monitoring_interval = {
+                        let cfg = config.read().await;
+                        cfg.fan_monitoring_interval
+                    };
 
-                interval = interval(Duration::from_secs(monitoring_interval));
+                    ticker.set_period(Duration::from_secs(monitoring_interval));
 
-                // 收集风扇数据
-                if let Ok(fans) = fan_service.get_fan_list().await {
-                    let mut fan_readings = Vec::new();
+                    // 收集风扇数据
+                    if let Ok(fans) = fan_service.get_fan_list().await {
+                        let mut fan_readings = Vec::new();
+                        let mut component_ids = Vec::new();
 
-                    for fan_id in fans {
-                        if let Ok(reading) = fan_service.get_current_fan_status(&fan_id).await {
-                            fan_readings.push(reading);
+                        for fan_id in fans {
+                            if let Ok(reading) = fan_service.get_current_fan_status(&fan_id).await {
+                                fan_readings.push(reading);
+                                component_ids.push(Self::fan_component_id(&fan_id));
+                            }
                         }
-                    }
 
-                    // 更新缓存
-                    {
-                        let mut cache = data_cache.write().await;
-                        cache.latest_fan_data = Some(fan_readings);
-                        cache.last_update = Utc::now();
-                    }
+                        // 更新缓存
+                        data_cache.write().await.update_fans(fan_readings);
+                        Self::record_components(&seen_components, &metrics, &status, component_ids).await;
 
-                    // 更新指标
-                    {
-                        let mut m = metrics.write().await;
-                        m.fan_collections += 1;
+                        // 更新指标
+                        {
+                            let mut m = metrics.write().await;
+                            m.fan_collections += 1;
+                        }
                     }
                 }
             }
-        });
-
-        self.task_handles.lock().await.push(handle);
+        })
+        .await;
         Ok(())
     }
 
@@ -532,39 +923,150 @@ impl MonitoringService {
         let data_cache = Arc::clone(&self.data_cache);
         let config = Arc::clone(&self.config);
         let metrics = Arc::clone(&self.metrics);
+        let poll_overruns = self.poll_overruns.clone();
+
+        self.spawn_task("sensor_monitoring", self.heartbeat_timeout(), move |token, heartbeat| {
+            let sensor_service = Arc::clone(&sensor_service);
+            let data_cache = Arc::clone(&data_cache);
+            let config = Arc::clone(&config);
+            let metrics = Arc::clone(&metrics);
+            let poll_overruns = poll_overruns.clone();
+            async move {
+                let mut ticker = PollTicker::new("sensor_monitoring", Duration::from_secs(60), poll_overruns);
+
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = token.cancelled() => break,
+                        _ = ticker.tick() => {}
+                    }
+                    heartbeat.beat();
 
-        let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(60));
-
-            loop {
-                interval.tick().await;
+                    let monitoring_interval = {
+                        let cfg = config.read().await;
+                        cfg.sensor_monitoring_interval
+                    };
 
-                let monitoring_interval = {
-                    let cfg = config.read().await;
-                    cfg.sensor_monitoring_interval
-                };
+                    ticker.set_period(Duration::from_secs(monitoring_interval));
 
-                interval = interval(Duration::from_secs(monitoring_interval));
+                    // 收集传感器数据
+                    if let Ok(sensors) = sensor_service.get_all_sensor_data().await {
+                        // 更新缓存
+                        {
+                            let mut cache = data_cache.write().await;
+                            cache.latest_sensor_data = Some(sensors);
+                            cache.last_update = Utc::now();
+                        }
 
-                // 收集传感器数据
-                if let Ok(sensors) = sensor_service.get_all_sensor_data().await {
-                    // 更新缓存
-                    {
-                        let mut cache = data_cache.write().await;
-                        cache.latest_sensor_data = Some(sensors);
-                        cache.last_update = Utc::now();
+                        // 更新指标
+                        {
+                            let mut m = metrics.write().await;
+                            m.sensor_collections += 1;
+                        }
                     }
+                }
+            }
+        })
+        .await;
+        Ok(())
+    }
 
-                    // 更新指标
-                    {
-                        let mut m = metrics.write().await;
-                        m.sensor_collections += 1;
+    /// 启动监控数据过期检查
+    ///
+    /// 独立于各采集任务运行，采集任务卡住或退出后缓存不再更新，由该任务发出告警；
+    /// 刚启动时从启动时间开始计算，避免把启动前的旧缓存当作过期
+    async fn start_stale_data_watch(&self) -> AppResult<()> {
+        let Some(stale_data_alert) = self.stale_data_alert.clone() else {
+            return Ok(());
+        };
+        let alert_service = Arc::clone(&self.alert_service);
+        let data_cache = Arc::clone(&self.data_cache);
+        let config = Arc::clone(&self.config);
+        let status = Arc::clone(&self.status);
+
+        self.spawn_task("stale_data_watch", self.heartbeat_timeout(), move |token, heartbeat| {
+            let mut watch = StaleDataWatch::new(stale_data_alert.clone());
+            let mut active_alert = None;
+            let alert_service = Arc::clone(&alert_service);
+            let data_cache = Arc::clone(&data_cache);
+            let config = Arc::clone(&config);
+            let status = Arc::clone(&status);
+            async move {
+                let mut interval = interval(watch.check_interval());
+
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = token.cancelled() => break,
+                        _ = interval.tick() => {}
+                    }
+                    heartbeat.beat();
+
+                    let poll_interval = Duration::from_secs(config.read().await.collection_interval);
+                    let last_update = data_cache.read().await.last_update;
+                    let last_update = match status.read().await.started_at {
+                        Some(started_at) => last_update.max(started_at),
+                        None => last_update,
+                    };
+
+                    if let Some(transition) = watch.observe(last_update, poll_interval, Utc::now()) {
+                        if let Err(e) =
+                            Self::apply_stale_data_transition(&alert_service, transition, &mut active_alert).await
+                        {
+                            error!("发送监控数据过期告警失败: {}", e);
+                        }
                     }
                 }
             }
-        });
+        })
+        .await;
+        Ok(())
+    }
 
-        self.task_handles.lock().await.push(handle);
+    /// 数据过期时创建严重告警并经通知渠道发送，恢复更新后解决该告警
+    ///
+    /// # 参数
+    /// * `alert_service` - 告警服务
+    /// * `transition` - 过期状态的变化
+    /// * `active_alert` - 尚未解决的过期告警ID
+    async fn apply_stale_data_transition(
+        alert_service: &AlertService,
+        transition: StaleDataTransition,
+        active_alert: &mut Option<String>,
+    ) -> AppResult<()> {
+        match transition {
+            StaleDataTransition::Stale { last_update, age, threshold } => {
+                let mut details = HashMap::new();
+                details.insert("last_update".to_string(), last_update.to_rfc3339());
+                details.insert("age_secs".to_string(), age.num_seconds().to_string());
+                details.insert("threshold_secs".to_string(), threshold.num_seconds().to_string());
+
+                let alert_id = alert_service
+                    .create_alert(
+                        AlertType::System,
+                        AlertSeverity::Critical,
+                        STALE_DATA_ALERT_SOURCE.to_string(),
+                        format!(
+                            "监控数据已 {} 秒未更新（阈值 {} 秒），监控循环可能已停止",
+                            age.num_seconds(),
+                            threshold.num_seconds()
+                        ),
+                        Some(details),
+                    )
+                    .await?;
+                warn!("监控数据过期，已创建告警: {}", alert_id);
+                *active_alert = Some(alert_id);
+            }
+            StaleDataTransition::Recovered => {
+                if let Some(alert_id) = active_alert.take() {
+                    info!("监控数据已恢复更新，解决过期告警: {}", alert_id);
+                    // 告警可能已被人工解决
+                    if let Err(e) = alert_service.resolve_alert(&alert_id, "system".to_string()).await {
+                        warn!("解决监控数据过期告警失败: {} - {}", alert_id, e);
+                    }
+                }
+            }
+        }
         Ok(())
     }
 
@@ -574,87 +1076,96 @@ impl MonitoringService {
         let config = Arc::clone(&self.config);
         let alert_service = Arc::clone(&self.alert_service);
 
-        let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(60));
-
-            loop {
-                interval.tick().await;
+        self.spawn_task("system_health_monitoring", self.heartbeat_timeout(), move |token, heartbeat| {
+            let data_cache = Arc::clone(&data_cache);
+            let config = Arc::clone(&config);
+            let alert_service = Arc::clone(&alert_service);
+            async move {
+                let mut interval = interval(Duration::from_secs(60));
+
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = token.cancelled() => break,
+                        _ = interval.tick() => {}
+                    }
+                    heartbeat.beat();
 
-                let monitoring_interval = {
-                    let cfg = config.read().await;
-                    cfg.health_check_interval
-                };
+                    let monitoring_interval = {
+                        let cfg = config.read().await;
+                        cfg.health_check_interval
+                    };
 
-                interval = interval(Duration::from_secs(monitoring_interval));
+                    interval = interval(Duration::from_secs(monitoring_interval));
 
-                // 检查系统健康状态
-                let mut health_issues = Vec::new();
+                    // 检查系统健康状态
+                    let mut health_issues = Vec::new();
 
-                // 检查缓存中的数据
-                {
-                    let cache = data_cache.read().await;
-
-                    // 检查数据新鲜度
-                    let data_age = Utc::now()
-                        .signed_duration_since(cache.last_update)
-                        .num_seconds();
-                    if data_age > 300 {
-                        // 5分钟
-                        health_issues.push("监控数据过期".to_string());
-                    }
+                    // 检查缓存中的数据
+                    {
+                        let cache = data_cache.read().await;
+
+                        // 检查数据新鲜度
+                        let data_age = Utc::now()
+                            .signed_duration_since(cache.last_update)
+                            .num_seconds();
+                        if data_age > 300 {
+                            // 5分钟
+                            health_issues.push("监控数据过期".to_string());
+                        }
 
-                    // 检查温度异常
-                    if let Some(temp_data) = &cache.latest_temperature_data {
-                        for reading in temp_data {
-                            if reading.temperature > 85.0 {
-                                health_issues.push(format!(
-                                    "传感器 {} 温度过高: {:.1}°C",
-                                    reading.sensor_id, reading.temperature
-                                ));
+                        // 检查温度异常
+                        if let Some(temp_data) = &cache.latest_temperature_data {
+                            for reading in temp_data {
+                                if reading.temperature > 85.0 {
+                                    health_issues.push(format!(
+                                        "传感器 {} 温度过高: {:.1}°C",
+                                        reading.sensor_id, reading.temperature
+                                    ));
+                                }
                             }
                         }
-                    }
 
-                    // 检查风扇异常
-                    if let Some(fan_data) = &cache.latest_fan_data {
-                        for reading in fan_data {
-                            if reading.rpm == 0 && reading.speed_percent > 0.0 {
-                                health_issues.push(format!("风扇 {} 可能故障", reading.fan_id));
+                        // 检查风扇异常
+                        if let Some(fan_data) = &cache.latest_fan_data {
+                            for reading in fan_data {
+                                if reading.rpm == 0 && reading.speed_percent > 0.0 {
+                                    health_issues.push(format!("风扇 {} 可能故障", reading.fan_id));
+                                }
                             }
                         }
                     }
-                }
 
-                // 更新系统健康状态
-                let health_status = if health_issues.is_empty() {
-                    SystemHealthStatus::Healthy
-                } else if health_issues.len() <= 2 {
-                    SystemHealthStatus::Warning
-                } else {
-                    SystemHealthStatus::Critical
-                };
+                    // 更新系统健康状态
+                    let health_status = if health_issues.is_empty() {
+                        SystemHealthStatus::Healthy
+                    } else if health_issues.len() <= 2 {
+                        SystemHealthStatus::Warning
+                    } else {
+                        SystemHealthStatus::Critical
+                    };
 
-                {
-                    let mut cache = data_cache.write().await;
-                    cache.latest_system_health = Some(SystemHealth {
-                        timestamp: Utc::now(),
-                        status: health_status,
-                        issues: health_issues.clone(),
-                        uptime_seconds: 0, // 需要计算实际运行时间
-                    });
-                }
+                    {
+                        let mut cache = data_cache.write().await;
+                        cache.latest_system_health = Some(SystemHealth {
+                            timestamp: Utc::now(),
+                            status: health_status,
+                            issues: health_issues.clone(),
+                            uptime_seconds: 0, // 需要计算实际运行时间
+                        });
+                    }
 
-                // 如果有严重问题，触发告警
-                if !health_issues.is_empty() {
-                    for issue in health_issues {
-                        // 这里可以通过告警服务发送告警
-                        warn!("系统健康问题: {}", issue);
+                    // 如果有严重问题，触发告警
+                    if !health_issues.is_empty() {
+                        for issue in health_issues {
+                            // 这里可以通过告警服务发送告警
+                            warn!("系统健康问题: {}", issue);
+                        }
                     }
                 }
             }
-        });
-
-        self.task_handles.lock().await.push(handle);
+        })
+        .await;
         Ok(())
     }
 
@@ -663,34 +1174,37 @@ impl MonitoringService {
         let metrics = Arc::clone(&self.metrics);
         let status = Arc::clone(&self.status);
 
-        let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(60));
-            let start_time = Instant::now();
-
-            loop {
-                interval.tick().await;
-
-                // 更新性能指标
-                {
-                    let mut m = metrics.write().await;
-                    m.uptime_seconds = start_time.elapsed().as_secs();
-
-                    // 计算监控组件数量
-                    m.monitored_components = 0; // 需要实际计算
+        self.spawn_task("performance_monitoring", self.heartbeat_timeout(), move |token, heartbeat| {
+            let metrics = Arc::clone(&metrics);
+            let status = Arc::clone(&status);
+            async move {
+                let mut interval = interval(Duration::from_secs(60));
+
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = token.cancelled() => break,
+                        _ = interval.tick() => {}
+                    }
+                    heartbeat.beat();
 
-                    // 更新其他指标
-                    m.last_update = Utc::now();
-                }
+                    // 更新性能指标，组件数由采集周期维护
+                    {
+                        let started_at = status.read().await.started_at;
+                        let mut m = metrics.write().await;
+                        m.uptime_seconds = Self::uptime_seconds(started_at, Utc::now());
+                        m.last_update = Utc::now();
+                    }
 
-                // 更新状态
-                {
-                    let mut s = status.write().await;
-                    s.last_update = Utc::now();
+                    // 更新状态
+                    {
+                        let mut s = status.write().await;
+                        s.last_update = Utc::now();
+                    }
                 }
             }
-        });
-
-        self.task_handles.lock().await.push(handle);
+        })
+        .await;
         Ok(())
     }
 
@@ -698,26 +1212,33 @@ impl MonitoringService {
     async fn start_data_cleanup_task(&self) -> AppResult<()> {
         let data_cache = Arc::clone(&self.data_cache);
 
-        let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(3600)); // 每小时清理一次
+        self.spawn_task("data_cleanup", None, move |token, heartbeat| {
+            let data_cache = Arc::clone(&data_cache);
+            async move {
+                let mut interval = interval(Duration::from_secs(3600)); // 每小时清理一次
 
-            loop {
-                interval.tick().await;
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = token.cancelled() => break,
+                        _ = interval.tick() => {}
+                    }
+                    heartbeat.beat();
 
-                // 清理过期的告警
-                {
-                    let mut cache = data_cache.write().await;
-                    let cutoff_time = Utc::now() - chrono::Duration::hours(24);
-                    cache
-                        .active_alerts
-                        .retain(|alert| alert.timestamp > cutoff_time);
-                }
+                    // 清理过期的告警
+                    {
+                        let mut cache = data_cache.write().await;
+                        let cutoff_time = Utc::now() - chrono::Duration::hours(24);
+                        cache
+                            .active_alerts
+                            .retain(|alert| alert.timestamp > cutoff_time);
+                    }
 
-                debug!("数据清理任务执行完成");
+                    debug!("数据清理任务执行完成");
+                }
             }
-        });
-
-        self.task_handles.lock().await.push(handle);
+        })
+        .await;
         Ok(())
     }
 
@@ -726,12 +1247,8 @@ impl MonitoringService {
         info!("重启监控任务");
 
         // 停止现有任务
-        {
-            let mut handles = self.task_handles.lock().await;
-            for handle in handles.drain(..) {
-                handle.abort();
-            }
-        }
+        self.shutdown_tasks(TASK_STOP_TIMEOUT).await;
+        *self.cancel_token.write().await = CancellationToken::new();
 
         // 重新启动任务
         self.start_temperature_monitoring().await?;
@@ -760,9 +1277,9 @@ impl MonitoringService {
                 }
             }
 
-            let mut cache = self.data_cache.write().await;
-            cache.latest_temperature_data = Some(temperature_readings);
-            cache.last_update = Utc::now();
+            let component_ids = Self::temperature_component_ids(&temperature_readings);
+            self.data_cache.write().await.update_temperature(temperature_readings);
+            Self::record_components(&self.seen_components, &self.metrics, &self.status, component_ids).await;
         }
 
         Ok(())
@@ -772,21 +1289,69 @@ impl MonitoringService {
     async fn collect_fan_data(&self) -> AppResult<()> {
         if let Ok(fans) = self.fan_service.get_fan_list().await {
             let mut fan_readings = Vec::new();
+            let mut component_ids = Vec::new();
 
             for fan_id in fans {
                 if let Ok(reading) = self.fan_service.get_current_fan_status(Some(&fan_id)).await {
                     fan_readings.extend(reading);
+                    component_ids.push(Self::fan_component_id(&fan_id));
                 }
             }
 
-            let mut cache = self.data_cache.write().await;
-            cache.latest_fan_data = Some(fan_readings);
-            cache.last_update = Utc::now();
+            self.data_cache.write().await.update_fans(fan_readings);
+            Self::record_components(&self.seen_components, &self.metrics, &self.status, component_ids).await;
         }
 
         Ok(())
     }
 
+    /// 温度读数对应的组件键
+    fn temperature_component_ids(readings: &[TemperatureReading]) -> Vec<String> {
+        readings
+            .iter()
+            .map(|reading| format!("temperature:{}", reading.sensor_id))
+            .collect()
+    }
+
+    /// 风扇对应的组件键
+    fn fan_component_id(fan_id: &str) -> String {
+        format!("fan:{}", fan_id)
+    }
+
+    /// 登记本周期读到的组件，并刷新监控组件数和运行时间
+    ///
+    /// 组件数为启动以来读到过的不同温度传感器和风扇的数量
+    ///
+    /// # 参数
+    /// * `seen_components` - 已读到过的组件
+    /// * `metrics` - 性能指标
+    /// * `status` - 监控状态，运行时间从 `started_at` 起算
+    /// * `component_ids` - 本周期读到的组件键
+    async fn record_components(
+        seen_components: &RwLock<HashSet<String>>,
+        metrics: &RwLock<MonitoringMetrics>,
+        status: &RwLock<MonitoringStatus>,
+        component_ids: impl IntoIterator<Item = String>,
+    ) {
+        let monitored_components = {
+            let mut seen = seen_components.write().await;
+            seen.extend(component_ids);
+            seen.len()
+        };
+        let started_at = status.read().await.started_at;
+
+        let now = Utc::now();
+        let mut m = metrics.write().await;
+        m.monitored_components = monitored_components;
+        m.uptime_seconds = Self::uptime_seconds(started_at, now);
+        m.last_update = now;
+    }
+
+    /// 从启动时间计算运行秒数，未启动时为0
+    fn uptime_seconds(started_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> u64 {
+        started_at.map_or(0, |started_at| (now - started_at).num_seconds().max(0) as u64)
+    }
+
     /// 收集传感器数据
     async fn collect_sensor_data(&self) -> AppResult<()> {
         if let Ok(sensors) = self.sensor_service.get_all_sensors().await {
@@ -849,6 +1414,7 @@ impl MonitoringService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::controllers::monitoring_controller::MonitoringAlert;
 
     #[tokio::test]
     async fn test_monitoring_service_creation() {
@@ -857,6 +1423,257 @@ mod tests {
         assert!(true);
     }
 
+    #[test]
+    fn test_inverted_history_range_rejected() {
+        let now = Utc::now();
+        let max_span = ChronoDuration::hours(24);
+
+        let err = MonitoringService::validate_history_range(now, now - ChronoDuration::hours(1), max_span)
+            .unwrap_err();
+        assert_eq!(err.status_code(), 400);
+
+        let err = MonitoringService::validate_history_range(now - ChronoDuration::days(3), now, max_span)
+            .unwrap_err();
+        assert_eq!(err.status_code(), 400);
+
+        assert!(MonitoringService::validate_history_range(now - ChronoDuration::hours(2), now, max_span).is_ok());
+    }
+
+    #[test]
+    fn test_downsampled_history_has_fewer_points() {
+        let origin = Utc::now() - ChronoDuration::hours(1);
+        let raw: Vec<MonitoringDataPoint> = (0..60)
+            .map(|i| MonitoringDataPoint {
+                timestamp: origin + ChronoDuration::minutes(i),
+                component_id: "cpu".to_string(),
+                data_type: MonitoringDataType::Temperature,
+                value: 40.0 + (i % 10) as f64,
+                unit: "°C".to_string(),
+                status: "normal".to_string(),
+                metadata: None,
+            })
+            .collect();
+
+        let downsampled =
+            MonitoringService::downsample_data_points(raw.clone(), origin, ChronoDuration::minutes(10));
+
+        assert_eq!(downsampled.len(), 6);
+        assert!(downsampled.len() < raw.len());
+        assert_eq!(downsampled[0].timestamp, origin);
+        assert!((downsampled[0].value - 44.5).abs() < 1e-9);
+    }
+
+    fn monitoring_alert(severity: &str) -> MonitoringAlert {
+        MonitoringAlert {
+            id: uuid::Uuid::new_v4().to_string(),
+            severity: severity.to_string(),
+            status: "active".to_string(),
+            message: "test".to_string(),
+            component: "cpu".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_dashboard_reflects_cached_state() {
+        let reading = TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: "CPU1".to_string(),
+            sensor_name: "CPU1 Temp".to_string(),
+            temperature: 85.0,
+            status: crate::models::thermal::TemperatureStatus::Critical,
+            timestamp: Utc::now(),
+            server_id: "server-1".to_string(),
+        };
+        let cache = MonitoringDataCache {
+            latest_temperature_data: Some(vec![reading]),
+            active_alerts: Some(vec![
+                monitoring_alert("Critical"),
+                monitoring_alert("warning"),
+                monitoring_alert("warning"),
+            ]),
+            ..Default::default()
+        };
+        let control = DashboardControlSummary {
+            auto_control_enabled: true,
+            emergency_mode: true,
+            ..Default::default()
+        };
+
+        let dashboard = MonitoringService::build_dashboard(&cache, control, Utc::now());
+        let payload = serde_json::to_value(&dashboard).unwrap();
+        for section in ["sensors", "alerts", "control", "health"] {
+            assert!(payload.get(section).is_some(), "缺少 {}", section);
+        }
+
+        assert_eq!(dashboard.sensors.len(), 1);
+        assert_eq!(dashboard.sensors[0].sensor_id, "CPU1");
+        assert_eq!(dashboard.alerts.total, 3);
+        assert_eq!(dashboard.alerts.by_severity["critical"], 1);
+        assert_eq!(dashboard.alerts.by_severity["warning"], 2);
+        assert!(dashboard.control.emergency_mode);
+        // 过热扣10分，3个活跃告警扣15分
+        assert_eq!(dashboard.health.score, 75.0);
+        assert_eq!(dashboard.health.issues.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_forced_poll_refreshes_cache_and_debounces() {
+        use crate::models::config::AppConfig;
+        use crate::services::ipmi_service::IpmiService;
+
+        let ipmi_service = Arc::new(IpmiService::new(AppConfig::default().ipmi));
+        let service = MonitoringService::new(
+            Arc::new(ThermalService::new(ipmi_service.clone(), MonitoringConfig::default())),
+            Arc::new(FanService::new(ipmi_service.clone(), MonitoringConfig::default())),
+            Arc::new(SensorService::new(ipmi_service, MonitoringConfig::default())),
+            Arc::new(AlertService::new()),
+        )
+        .with_forced_poll_interval(Duration::from_secs(60));
+
+        let before = service.data_cache.read().await.last_update;
+        let data = service.force_poll().await.unwrap();
+        let after = service.data_cache.read().await.last_update;
+        assert!(after > before);
+        assert_eq!(data.timestamp, after);
+
+        // 防抖窗口内的第二次调用被拒绝，且不刷新缓存
+        let err = service.force_poll().await.unwrap_err();
+        assert_eq!(err.status_code(), 429);
+        assert_eq!(service.data_cache.read().await.last_update, after);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_monitor_triggers_stale_data_alert() {
+        let alert_service = AlertService::new();
+        let mut watch = StaleDataWatch::new(StaleDataAlertConfig::default());
+        let mut active_alert = None;
+        let poll_interval = Duration::from_secs(5);
+        let last_update = Utc::now();
+
+        // 缓存不再更新，按检查间隔推进时间
+        for secs in [5, 10, 15, 20, 60] {
+            let now = last_update + ChronoDuration::seconds(secs);
+            if let Some(transition) = watch.observe(last_update, poll_interval, now) {
+                MonitoringService::apply_stale_data_transition(&alert_service, transition, &mut active_alert)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let active = alert_service.get_active_alerts().await;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].source, STALE_DATA_ALERT_SOURCE);
+        assert_eq!(active[0].severity, AlertSeverity::Critical);
+        assert_eq!(active_alert.as_deref(), Some(active[0].id.as_str()));
+
+        // 监控恢复后告警自动解决
+        let now = last_update + ChronoDuration::seconds(65);
+        let transition = watch.observe(now, poll_interval, now).unwrap();
+        MonitoringService::apply_stale_data_transition(&alert_service, transition, &mut active_alert)
+            .await
+            .unwrap();
+        assert!(alert_service.get_active_alerts().await.is_empty());
+        assert!(active_alert.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stop_waits_for_in_flight_cycle() {
+        use crate::models::config::AppConfig;
+        use crate::services::ipmi_service::IpmiService;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let ipmi_service = Arc::new(IpmiService::new(AppConfig::default().ipmi));
+        let service = MonitoringService::new(
+            Arc::new(ThermalService::new(ipmi_service.clone(), MonitoringConfig::default())),
+            Arc::new(FanService::new(ipmi_service.clone(), MonitoringConfig::default())),
+            Arc::new(SensorService::new(ipmi_service, MonitoringConfig::default())),
+            Arc::new(AlertService::new()),
+        );
+        service.status.write().await.is_running = true;
+
+        let write_started = Arc::new(tokio::sync::Notify::new());
+        let write_finished = Arc::new(AtomicBool::new(false));
+        let started = Arc::clone(&write_started);
+        let finished = Arc::clone(&write_finished);
+        let data_cache = Arc::clone(&service.data_cache);
+        service
+            .spawn_task("slow_write", None, move |token, _| {
+                let (started, finished, data_cache) = (started.clone(), finished.clone(), data_cache.clone());
+                async move {
+                    let mut interval = interval(Duration::from_millis(10));
+                    loop {
+                        tokio::select! {
+                            biased;
+                            _ = token.cancelled() => break,
+                            _ = interval.tick() => {}
+                        }
+                        // 模拟一次耗时的分步写入
+                        started.notify_one();
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                        data_cache.write().await.last_update = Utc::now();
+                        finished.store(true, Ordering::SeqCst);
+                    }
+                }
+            })
+            .await;
+
+        write_started.notified().await;
+        service.stop().await.unwrap();
+
+        assert!(write_finished.load(Ordering::SeqCst));
+        assert!(!service.status.read().await.is_running);
+        assert!(service.task_handles.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_poll_cycles_count_distinct_components_and_uptime() {
+        use crate::models::config::AppConfig;
+        use crate::services::ipmi_service::IpmiService;
+
+        let ipmi_service = Arc::new(IpmiService::new(AppConfig::default().ipmi));
+        let service = MonitoringService::new(
+            Arc::new(ThermalService::new(ipmi_service.clone(), MonitoringConfig::default())),
+            Arc::new(FanService::new(ipmi_service.clone(), MonitoringConfig::default())),
+            Arc::new(SensorService::new(ipmi_service, MonitoringConfig::default())),
+            Arc::new(AlertService::new()),
+        );
+        service.status.write().await.started_at = Some(Utc::now() - ChronoDuration::seconds(5));
+
+        let temperatures: Vec<TemperatureReading> = ["CPU1", "CPU2", "Inlet"]
+            .iter()
+            .map(|sensor_id| TemperatureReading {
+                id: uuid::Uuid::new_v4(),
+                sensor_id: sensor_id.to_string(),
+                sensor_name: format!("{} Temp", sensor_id),
+                temperature: 45.0,
+                status: crate::models::thermal::TemperatureStatus::Normal,
+                timestamp: Utc::now(),
+                server_id: "server-1".to_string(),
+            })
+            .collect();
+        let fans = ["FAN1", "FAN2"];
+
+        // 连续两个周期读到同一批组件，不重复计数
+        for _ in 0..2 {
+            let component_ids = MonitoringService::temperature_component_ids(&temperatures)
+                .into_iter()
+                .chain(fans.iter().map(|fan_id| MonitoringService::fan_component_id(fan_id)));
+            MonitoringService::record_components(
+                &service.seen_components,
+                &service.metrics,
+                &service.status,
+                component_ids,
+            )
+            .await;
+        }
+
+        let metrics = service.metrics.read().await;
+        assert_eq!(metrics.monitored_components, 5);
+        assert!(metrics.uptime_seconds > 0);
+    }
+
     #[test]
     fn test_monitoring_config_validation() {
         let config = MonitoringConfig::default();
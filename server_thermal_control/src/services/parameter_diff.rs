@@ -0,0 +1,209 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::{Arc, RwLock};
+
+/// 内存中保留的参数变更记录条数
+pub const DEFAULT_PARAMETER_HISTORY_LIMIT: usize = 500;
+
+/// 参数变更来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterChangeSource {
+    /// 手动更新
+    #[default]
+    Manual,
+    /// 应用优化结果
+    Optimization,
+    /// 应用预设
+    Preset,
+    /// 从配置快照恢复
+    Restore,
+}
+
+/// 单个字段的变化
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterFieldChange {
+    /// 字段路径，如 `pid_parameters.kp`、`fan_curves.FAN1.points`
+    pub field: String,
+    /// 变更前的值，字段原先不存在时为空
+    pub old: Option<Value>,
+    /// 变更后的值，字段被移除时为空
+    pub new: Option<Value>,
+}
+
+/// 一次参数变更的结构化差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterDiff {
+    /// 变更ID
+    pub id: String,
+    /// 变更来源
+    pub source: ParameterChangeSource,
+    /// 变更时间
+    pub changed_at: DateTime<Utc>,
+    /// 发生变化的字段，按字段路径排序；参数未变化时为空
+    pub changes: Vec<ParameterFieldChange>,
+}
+
+/// 应用后的参数及本次变更的差异
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedParameters<T> {
+    pub parameters: T,
+    pub diff: ParameterDiff,
+}
+
+/// 比较变更前后的参数，只返回发生变化的字段
+///
+/// 嵌套对象按字段路径逐层展开，数组（如曲线点、输出限制）整体比较
+///
+/// # 参数
+/// * `old` - 变更前的参数，此前未设置过时为空
+/// * `new` - 变更后的参数
+pub fn diff_parameters<T: Serialize>(old: Option<&T>, new: &T) -> Vec<ParameterFieldChange> {
+    let old = old.and_then(|old| serde_json::to_value(old).ok()).unwrap_or(Value::Null);
+    let new = serde_json::to_value(new).unwrap_or(Value::Null);
+
+    let (mut old_fields, mut new_fields) = (Map::new(), Map::new());
+    flatten("", &old, &mut old_fields);
+    flatten("", &new, &mut new_fields);
+
+    let fields: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+    fields
+        .into_iter()
+        .filter(|field| old_fields.get(*field) != new_fields.get(*field))
+        .map(|field| ParameterFieldChange {
+            field: field.clone(),
+            old: old_fields.get(field).cloned(),
+            new: new_fields.get(field).cloned(),
+        })
+        .collect()
+}
+
+/// 把嵌套对象展开为 `路径 -> 值`，空值不计入
+fn flatten(prefix: &str, value: &Value, fields: &mut Map<String, Value>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&path, value, fields);
+            }
+        }
+        Value::Null => {}
+        value => {
+            fields.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+/// 参数变更记录
+///
+/// 每次改变控制参数的操作记录一条差异，只保留最近 `limit` 条
+#[derive(Debug, Clone)]
+pub struct ParameterHistory {
+    limit: usize,
+    entries: Arc<RwLock<VecDeque<ParameterDiff>>>,
+}
+
+impl Default for ParameterHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_PARAMETER_HISTORY_LIMIT)
+    }
+}
+
+impl ParameterHistory {
+    /// 创建参数变更记录
+    ///
+    /// # 参数
+    /// * `limit` - 保留条数，至少为1
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            entries: Arc::new(RwLock::new(VecDeque::new())),
+        }
+    }
+
+    /// 记录一次参数变更并返回差异
+    ///
+    /// # 参数
+    /// * `source` - 变更来源
+    /// * `old` - 变更前的参数
+    /// * `new` - 变更后的参数
+    pub fn record<T: Serialize>(&self, source: ParameterChangeSource, old: Option<&T>, new: &T) -> ParameterDiff {
+        let diff = ParameterDiff {
+            id: uuid::Uuid::new_v4().to_string(),
+            source,
+            changed_at: Utc::now(),
+            changes: diff_parameters(old, new),
+        };
+
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        entries.push_back(diff.clone());
+        while entries.len() > self.limit {
+            entries.pop_front();
+        }
+        diff
+    }
+
+    /// 最近的参数变更，按时间倒序
+    ///
+    /// # 参数
+    /// * `limit` - 返回条数上限，为空时返回全部
+    pub fn list(&self, limit: Option<usize>) -> Vec<ParameterDiff> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries
+            .iter()
+            .rev()
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parameters(kp: f64) -> Value {
+        json!({
+            "pid_parameters": { "kp": kp, "ki": 0.1, "kd": 0.05, "output_limits": [20.0, 100.0] },
+            "temperature_thresholds": {
+                "target_temperature": 65.0,
+                "warning_temperature": 75.0,
+                "critical_temperature": 85.0,
+                "emergency_temperature": 95.0
+            },
+            "fan_curves": { "FAN1": { "points": [], "min_speed": 20, "max_speed": 100 } },
+            "control_interval_ms": 10000
+        })
+    }
+
+    #[test]
+    fn test_changing_only_kp_lists_exactly_that_field() {
+        let history = ParameterHistory::new(10);
+        let initial = history.record(ParameterChangeSource::Manual, None, &parameters(2.0));
+        assert!(initial.changes.iter().all(|change| change.old.is_none()));
+        assert!(initial.changes.iter().any(|change| change.field == "fan_curves.FAN1.min_speed"));
+
+        let diff = history.record(ParameterChangeSource::Optimization, Some(&parameters(2.0)), &parameters(1.8));
+        assert_eq!(
+            diff.changes,
+            vec![ParameterFieldChange {
+                field: "pid_parameters.kp".to_string(),
+                old: Some(json!(2.0)),
+                new: Some(json!(1.8)),
+            }]
+        );
+
+        let listed = history.list(None);
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, diff.id);
+        assert_eq!(listed[0].source, ParameterChangeSource::Optimization);
+        assert_eq!(history.list(Some(1)).len(), 1);
+    }
+}
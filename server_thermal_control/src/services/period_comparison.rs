@@ -0,0 +1,254 @@
+use crate::models::error::{AppError, AppResult};
+use crate::services::alert_thresholds::{AlertThresholdStore, SensorAlertThreshold, ThresholdLevel};
+use crate::services::temperature_histogram::TemperatureHistogramStore;
+use crate::utils::math::MathUtils;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 平均温度差异显著的z值
+const SIGNIFICANT_Z: f64 = 1.96;
+/// 最高温度变化显著的幅度（°C）
+const SIGNIFICANT_MAX_DELTA: f64 = 2.0;
+/// 超阈值读数变化显著的数量
+const SIGNIFICANT_OVER_THRESHOLD_DELTA: f64 = 3.0;
+
+/// 一个时间段的温度汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodSummary {
+    /// 开始时间
+    pub start: DateTime<Utc>,
+    /// 结束时间
+    pub end: DateTime<Utc>,
+    /// 温度读数数量
+    pub temperature_samples: usize,
+    /// 平均温度，没有读数时为空
+    pub avg_temperature: Option<f64>,
+    /// 最高温度，没有读数时为空
+    pub max_temperature: Option<f64>,
+    /// 达到警告阈值的读数数量
+    pub over_threshold_samples: usize,
+    /// 各传感器的平均温度
+    pub sensor_averages: BTreeMap<String, f64>,
+    #[serde(skip)]
+    std_dev: f64,
+}
+
+/// 变化的显著性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Significance {
+    Significant,
+    NotSignificant,
+    /// 任一时间段没有读数
+    InsufficientData,
+}
+
+/// 单项指标的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricDelta {
+    pub baseline: Option<f64>,
+    pub current: Option<f64>,
+    /// 对比时间段减基准时间段
+    pub delta: Option<f64>,
+    pub significance: Significance,
+}
+
+/// 两个时间段的对比结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodComparison {
+    pub baseline: PeriodSummary,
+    pub current: PeriodSummary,
+    pub avg_temperature: MetricDelta,
+    pub max_temperature: MetricDelta,
+    pub over_threshold_samples: MetricDelta,
+    /// 两段都有读数的传感器的平均温度变化
+    pub sensor_deltas: BTreeMap<String, f64>,
+}
+
+/// 按温度分布采集保留的读数对比两个时间段
+///
+/// # 参数
+/// * `store` - 温度分布采集器
+/// * `alert_thresholds` - 按传感器设置的告警阈值
+/// * `default_threshold` - 未设置阈值的传感器使用的告警阈值
+/// * `baseline` - 基准时间段（开始, 结束）
+/// * `current` - 对比时间段（开始, 结束）
+pub fn compare_periods(
+    store: &TemperatureHistogramStore,
+    alert_thresholds: &AlertThresholdStore,
+    default_threshold: SensorAlertThreshold,
+    baseline: (DateTime<Utc>, DateTime<Utc>),
+    current: (DateTime<Utc>, DateTime<Utc>),
+) -> AppResult<PeriodComparison> {
+    let baseline = summarize(store, alert_thresholds, default_threshold, baseline, "baseline_start")?;
+    let current = summarize(store, alert_thresholds, default_threshold, current, "current_start")?;
+
+    // 两段样本均值差的z检验，两段读数都没有波动时任何变化都视为显著
+    let avg_temperature = metric_delta(baseline.avg_temperature, current.avg_temperature, |delta| {
+        let standard_error = (baseline.std_dev.powi(2) / baseline.temperature_samples as f64
+            + current.std_dev.powi(2) / current.temperature_samples as f64)
+            .sqrt();
+        if standard_error == 0.0 {
+            delta != 0.0
+        } else {
+            (delta / standard_error).abs() >= SIGNIFICANT_Z
+        }
+    });
+    let max_temperature = metric_delta(baseline.max_temperature, current.max_temperature, |delta| {
+        delta.abs() >= SIGNIFICANT_MAX_DELTA
+    });
+    let over_threshold_samples = metric_delta(
+        baseline.avg_temperature.map(|_| baseline.over_threshold_samples as f64),
+        current.avg_temperature.map(|_| current.over_threshold_samples as f64),
+        |delta| delta.abs() >= SIGNIFICANT_OVER_THRESHOLD_DELTA,
+    );
+    let sensor_deltas = current
+        .sensor_averages
+        .iter()
+        .filter_map(|(sensor_id, avg)| {
+            baseline
+                .sensor_averages
+                .get(sensor_id)
+                .map(|baseline_avg| (sensor_id.clone(), avg - baseline_avg))
+        })
+        .collect();
+
+    Ok(PeriodComparison {
+        baseline,
+        current,
+        avg_temperature,
+        max_temperature,
+        over_threshold_samples,
+        sensor_deltas,
+    })
+}
+
+/// 汇总一个时间段的读数
+fn summarize(
+    store: &TemperatureHistogramStore,
+    alert_thresholds: &AlertThresholdStore,
+    default_threshold: SensorAlertThreshold,
+    (start, end): (DateTime<Utc>, DateTime<Utc>),
+    field: &str,
+) -> AppResult<PeriodSummary> {
+    if start >= end {
+        return Err(AppError::validation_error(field, "开始时间必须早于结束时间"));
+    }
+
+    let readings = store.readings(start, end);
+    let mut values = Vec::new();
+    let mut over_threshold_samples = 0;
+    let mut sensor_averages = BTreeMap::new();
+    for (sensor_id, sensor_readings) in &readings {
+        let threshold = alert_thresholds.get(sensor_id).unwrap_or(default_threshold);
+        let sensor_values: Vec<f64> = sensor_readings.iter().map(|(_, value)| *value).collect();
+        over_threshold_samples += sensor_values
+            .iter()
+            .filter(|value| threshold.classify(**value) != ThresholdLevel::Within)
+            .count();
+        sensor_averages.insert(sensor_id.clone(), MathUtils::calculate_average(&sensor_values)?);
+        values.extend(sensor_values);
+    }
+
+    let (avg_temperature, max_temperature, std_dev) = if values.is_empty() {
+        (None, None, 0.0)
+    } else {
+        (
+            Some(MathUtils::calculate_average(&values)?),
+            values.iter().copied().reduce(f64::max),
+            MathUtils::standard_deviation(&values)?,
+        )
+    };
+
+    Ok(PeriodSummary {
+        start,
+        end,
+        temperature_samples: values.len(),
+        avg_temperature,
+        max_temperature,
+        over_threshold_samples,
+        sensor_averages,
+        std_dev,
+    })
+}
+
+fn metric_delta(baseline: Option<f64>, current: Option<f64>, significant: impl Fn(f64) -> bool) -> MetricDelta {
+    let delta = baseline.zip(current).map(|(baseline, current)| current - baseline);
+    let significance = match delta {
+        None => Significance::InsufficientData,
+        Some(delta) if significant(delta) => Significance::Significant,
+        Some(_) => Significance::NotSignificant,
+    };
+    MetricDelta {
+        baseline,
+        current,
+        delta,
+        significance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ipmi_service::parse_temperature_sdr;
+    use crate::services::temperature_histogram::TemperatureHistogramConfig;
+    use chrono::Duration;
+
+    #[test]
+    fn test_hotter_second_period_has_positive_significant_delta() {
+        let store = TemperatureHistogramStore::new(TemperatureHistogramConfig {
+            window_secs: 14 * 24 * 3600,
+            ..Default::default()
+        });
+        let now = Utc::now();
+        let week_1 = now - Duration::days(14);
+        let week_2 = now - Duration::days(7);
+        // 第二周CPU平均高8°C，并出现4次超过警告阈值的尖峰
+        for (start, cpu) in [(week_1, 47.0), (week_2, 55.0)] {
+            for hour in 1..(7 * 24) {
+                let at = start + Duration::hours(hour);
+                let cpu = if start == week_2 && hour % 40 == 0 { 82.0 } else { cpu + (hour % 3) as f64 };
+                let sdr = format!(
+                    "CPU1 Temp        | 0Eh | ok  |  3.1 | {} degrees C\nInlet Temp       | 04h | ok  |  7.1 | 24 degrees C\n",
+                    cpu
+                );
+                store.record(&parse_temperature_sdr(&sdr, at), at);
+            }
+        }
+
+        let comparison = compare_periods(
+            &store,
+            &AlertThresholdStore::default(),
+            SensorAlertThreshold {
+                warning: 80.0,
+                critical: 90.0,
+            },
+            (week_1, week_2),
+            (week_2, now),
+        )
+        .unwrap();
+
+        assert!(comparison.avg_temperature.delta.unwrap() > 0.0);
+        assert_eq!(comparison.avg_temperature.significance, Significance::Significant);
+        assert_eq!(comparison.over_threshold_samples.delta, Some(4.0));
+        assert_eq!(comparison.over_threshold_samples.significance, Significance::Significant);
+        assert!((comparison.sensor_deltas["INLET_TEMP"]).abs() < 1e-9);
+        assert!(comparison.sensor_deltas["CPU1_TEMP"] > 7.0);
+
+        // 采集窗口之外没有读数
+        let empty = compare_periods(
+            &store,
+            &AlertThresholdStore::default(),
+            SensorAlertThreshold {
+                warning: 80.0,
+                critical: 90.0,
+            },
+            (week_1 - Duration::days(7), week_1 - Duration::days(1)),
+            (week_2, now),
+        )
+        .unwrap();
+        assert_eq!(empty.avg_temperature.significance, Significance::InsufficientData);
+        assert_eq!(empty.avg_temperature.delta, None);
+    }
+}
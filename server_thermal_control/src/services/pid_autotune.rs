@@ -0,0 +1,533 @@
+use crate::models::error::{AppError, AppResult};
+use crate::services::ipmi_service::{Ipmi, TemperatureSensor};
+use crate::services::safe_boot::ALL_FANS;
+use crate::services::sensor_source::SensorSource;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+/// 继电器自整定请求
+///
+/// 风扇在 `low_percent` 与 `high_percent` 之间切换，使温度围绕 `setpoint` 振荡，
+/// 按振荡幅度和周期计算临界增益与临界周期
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayTuneRequest {
+    /// 观测的温度传感器，为空时取最高温度
+    #[serde(default)]
+    pub sensor_id: Option<String>,
+    /// 振荡中心温度（°C）
+    pub setpoint: f64,
+    /// 继电器低档转速（百分比）
+    #[serde(default = "default_low_percent")]
+    pub low_percent: u8,
+    /// 继电器高档转速（百分比）
+    #[serde(default = "default_high_percent")]
+    pub high_percent: u8,
+    /// 切换回差（°C），温度超出 `setpoint ± hysteresis` 才切换
+    #[serde(default = "default_hysteresis")]
+    pub hysteresis: f64,
+    /// 参与计算的完整振荡周期数，第一个周期视为过渡过程不计入
+    #[serde(default = "default_cycles")]
+    pub cycles: u32,
+    /// 采样间隔（毫秒）
+    #[serde(default = "default_sample_interval_ms")]
+    pub sample_interval_ms: u64,
+    /// 实验最长持续时间（秒），超时仍未完成则中止
+    #[serde(default = "default_max_duration_secs")]
+    pub max_duration_secs: u64,
+    /// 中止余量（°C），温度达到临界温度减去该值时立即中止
+    #[serde(default = "default_abort_margin")]
+    pub abort_margin: f64,
+}
+
+fn default_low_percent() -> u8 {
+    20
+}
+
+fn default_high_percent() -> u8 {
+    60
+}
+
+fn default_hysteresis() -> f64 {
+    0.5
+}
+
+fn default_cycles() -> u32 {
+    3
+}
+
+fn default_sample_interval_ms() -> u64 {
+    2000
+}
+
+fn default_max_duration_secs() -> u64 {
+    1800
+}
+
+fn default_abort_margin() -> f64 {
+    5.0
+}
+
+impl RelayTuneRequest {
+    /// 中止温度：临界温度减去中止余量
+    ///
+    /// # 参数
+    /// * `critical_temperature` - 临界温度（°C）
+    pub fn abort_temperature(&self, critical_temperature: f64) -> f64 {
+        critical_temperature - self.abort_margin
+    }
+
+    /// 验证请求，振荡上沿必须低于中止温度
+    ///
+    /// # 参数
+    /// * `critical_temperature` - 临界温度（°C）
+    pub fn validate(&self, critical_temperature: f64) -> AppResult<()> {
+        if self.low_percent >= self.high_percent || self.high_percent > 100 {
+            return Err(AppError::validation_error(
+                "high_percent",
+                "高档转速必须大于低档转速且不超过100%",
+            ));
+        }
+        if !self.hysteresis.is_finite() || self.hysteresis < 0.0 {
+            return Err(AppError::validation_error("hysteresis", "切换回差不能为负数"));
+        }
+        if !(1..=10).contains(&self.cycles) {
+            return Err(AppError::validation_error("cycles", "振荡周期数必须在1-10之间"));
+        }
+        if self.sample_interval_ms == 0 || self.max_duration_secs == 0 {
+            return Err(AppError::validation_error(
+                "sample_interval_ms",
+                "采样间隔和最长持续时间必须大于0",
+            ));
+        }
+        if !self.abort_margin.is_finite() || self.abort_margin < 0.0 {
+            return Err(AppError::validation_error("abort_margin", "中止余量不能为负数"));
+        }
+        let abort_temperature = self.abort_temperature(critical_temperature);
+        if !self.setpoint.is_finite() || self.setpoint + self.hysteresis >= abort_temperature {
+            return Err(AppError::validation_error(
+                "setpoint",
+                format!(
+                    "振荡上沿 {:.1}°C 必须低于中止温度 {:.1}°C",
+                    self.setpoint + self.hysteresis,
+                    abort_temperature
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 自整定所需的IPMI操作
+pub trait RelayTuneIpmi: Send + Sync {
+    /// 读取全部温度传感器
+    fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>>;
+
+    /// 将所有风扇设置为指定转速
+    ///
+    /// # 参数
+    /// * `speed_percent` - 转速百分比
+    fn set_all_fan_speeds(&self, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// 将风扇控制交还BMC自动模式
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+impl RelayTuneIpmi for Arc<dyn Ipmi> {
+    fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+        SensorSource::get_temperature_sensors(self.as_ref())
+    }
+
+    fn set_all_fan_speeds(&self, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+        SensorSource::set_fan_speed(self.as_ref(), ALL_FANS, speed_percent)
+    }
+
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+        SensorSource::restore_automatic_fan_control(self.as_ref())
+    }
+}
+
+/// 按Ziegler–Nichols经典整定规则建议的PID参数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SuggestedPidGains {
+    /// 比例系数（%/°C）
+    pub kp: f64,
+    /// 积分系数（%/(°C·s)）
+    pub ki: f64,
+    /// 微分系数（%·s/°C）
+    pub kd: f64,
+}
+
+impl SuggestedPidGains {
+    /// 由临界增益和临界周期计算
+    ///
+    /// # 参数
+    /// * `ultimate_gain` - 临界增益 Ku
+    /// * `ultimate_period_secs` - 临界周期 Tu（秒）
+    pub fn ziegler_nichols(ultimate_gain: f64, ultimate_period_secs: f64) -> Self {
+        let kp = 0.6 * ultimate_gain;
+        Self {
+            kp,
+            ki: kp / (ultimate_period_secs / 2.0),
+            kd: kp * ultimate_period_secs / 8.0,
+        }
+    }
+}
+
+/// 自整定结果，只作为建议返回，不会应用到控制配置
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayTuneResult {
+    /// 观测的温度传感器
+    pub sensor_id: String,
+    /// 振荡中心温度（°C）
+    pub setpoint: f64,
+    /// 参与计算的振荡周期数
+    pub cycles: u32,
+    /// 温度振荡幅度（°C，峰谷差的一半）
+    pub oscillation_amplitude: f64,
+    /// 临界增益 Ku（%/°C）
+    pub ultimate_gain: f64,
+    /// 临界周期 Tu（秒）
+    pub ultimate_period_secs: f64,
+    /// 建议的PID参数
+    pub gains: SuggestedPidGains,
+    /// 实验期间的最高温度（°C）
+    pub max_temperature: f64,
+    /// 实验持续时间（秒）
+    pub duration_secs: f64,
+}
+
+/// 继电器振荡实验
+///
+/// 温度高于 `setpoint + hysteresis` 切到高档，低于 `setpoint - hysteresis` 切到低档。
+/// 每次切到高档开始一个周期，记录周期内的最高、最低温度
+#[derive(Debug, Clone)]
+pub struct RelayExperiment {
+    setpoint: f64,
+    hysteresis: f64,
+    low_percent: u8,
+    high_percent: u8,
+    cycles: usize,
+    output: Option<u8>,
+    /// 当前周期的开始时间与温度范围，第一次切到高档之前为空
+    current: Option<(f64, f64, f64)>,
+    /// 已完成周期的（时长，最高温度，最低温度）
+    completed: Vec<(f64, f64, f64)>,
+    max_temperature: f64,
+}
+
+impl RelayExperiment {
+    /// 创建实验
+    ///
+    /// # 参数
+    /// * `request` - 自整定请求
+    pub fn new(request: &RelayTuneRequest) -> Self {
+        Self {
+            setpoint: request.setpoint,
+            hysteresis: request.hysteresis,
+            low_percent: request.low_percent,
+            high_percent: request.high_percent,
+            cycles: request.cycles as usize,
+            output: None,
+            current: None,
+            completed: Vec::new(),
+            max_temperature: f64::NEG_INFINITY,
+        }
+    }
+
+    /// 记录一次采样，返回应下发的转速百分比
+    ///
+    /// # 参数
+    /// * `elapsed_secs` - 距实验开始的秒数
+    /// * `temperature` - 温度（°C）
+    pub fn observe(&mut self, elapsed_secs: f64, temperature: f64) -> u8 {
+        self.max_temperature = self.max_temperature.max(temperature);
+        if let Some((_, max, min)) = &mut self.current {
+            *max = max.max(temperature);
+            *min = min.min(temperature);
+        }
+
+        let output = match self.output {
+            None if temperature >= self.setpoint => self.high_percent,
+            None => self.low_percent,
+            Some(output) if output == self.low_percent && temperature > self.setpoint + self.hysteresis => {
+                if let Some((started, max, min)) = self.current {
+                    self.completed.push((elapsed_secs - started, max, min));
+                }
+                self.current = Some((elapsed_secs, temperature, temperature));
+                self.high_percent
+            }
+            Some(output) if output == self.high_percent && temperature < self.setpoint - self.hysteresis => {
+                self.low_percent
+            }
+            Some(output) => output,
+        };
+        self.output = Some(output);
+        output
+    }
+
+    /// 完成所需周期数后计算结果，跳过第一个完整周期
+    ///
+    /// # 参数
+    /// * `sensor_id` - 观测的温度传感器
+    /// * `duration_secs` - 实验持续时间（秒）
+    pub fn result(&self, sensor_id: &str, duration_secs: f64) -> Option<RelayTuneResult> {
+        if self.completed.len() <= self.cycles {
+            return None;
+        }
+        let measured = &self.completed[self.completed.len() - self.cycles..];
+        let count = measured.len() as f64;
+        let period = measured.iter().map(|(period, _, _)| period).sum::<f64>() / count;
+        let amplitude = measured.iter().map(|(_, max, min)| (max - min) / 2.0).sum::<f64>() / count;
+        if amplitude <= 0.0 || period <= 0.0 {
+            return None;
+        }
+
+        // 回差使切换滞后，按 sqrt(a² - ε²) 修正振荡幅度
+        let effective_amplitude = (amplitude.powi(2) - self.hysteresis.powi(2)).sqrt();
+        if effective_amplitude.is_nan() || effective_amplitude <= 0.0 {
+            return None;
+        }
+        let relay_amplitude = (self.high_percent - self.low_percent) as f64 / 2.0;
+        let ultimate_gain = 4.0 * relay_amplitude / (PI * effective_amplitude);
+        Some(RelayTuneResult {
+            sensor_id: sensor_id.to_string(),
+            setpoint: self.setpoint,
+            cycles: self.cycles as u32,
+            oscillation_amplitude: amplitude,
+            ultimate_gain,
+            ultimate_period_secs: period,
+            gains: SuggestedPidGains::ziegler_nichols(ultimate_gain, period),
+            max_temperature: self.max_temperature,
+            duration_secs,
+        })
+    }
+}
+
+/// 执行继电器自整定
+///
+/// 按采样间隔读取温度并切换风扇转速，直到完成所需周期数。温度达到中止温度、
+/// 超过最长持续时间或读写BMC失败时中止；无论成功与否，结束时都交还BMC自动模式
+///
+/// # 参数
+/// * `ipmi` - IPMI操作
+/// * `request` - 自整定请求
+/// * `critical_temperature` - 临界温度（°C）
+pub async fn run(
+    ipmi: &dyn RelayTuneIpmi,
+    request: &RelayTuneRequest,
+    critical_temperature: f64,
+) -> AppResult<RelayTuneResult> {
+    request.validate(critical_temperature)?;
+    info!(
+        "Starting PID relay auto-tune around {:.1}°C ({}% <-> {}%)",
+        request.setpoint, request.low_percent, request.high_percent
+    );
+
+    let result = relay_oscillation(ipmi, request, critical_temperature).await;
+    if let Err(e) = ipmi.restore_automatic_fan_control() {
+        error!("Failed to restore automatic fan control after PID auto-tune: {}", e);
+    }
+    match &result {
+        Ok(result) => info!(
+            "PID auto-tune finished: Ku={:.2}, Tu={:.1}s, kp={:.2}, ki={:.3}, kd={:.2}",
+            result.ultimate_gain, result.ultimate_period_secs, result.gains.kp, result.gains.ki, result.gains.kd
+        ),
+        Err(e) => warn!("PID auto-tune aborted: {}", e),
+    }
+    result
+}
+
+async fn relay_oscillation(
+    ipmi: &dyn RelayTuneIpmi,
+    request: &RelayTuneRequest,
+    critical_temperature: f64,
+) -> AppResult<RelayTuneResult> {
+    let abort_temperature = request.abort_temperature(critical_temperature);
+    let interval = Duration::from_millis(request.sample_interval_ms);
+    let max_duration = Duration::from_secs(request.max_duration_secs);
+    let mut experiment = RelayExperiment::new(request);
+    let mut applied = None;
+    let started = Instant::now();
+
+    loop {
+        let elapsed = started.elapsed();
+        if elapsed > max_duration {
+            return Err(AppError::TimeoutError {
+                operation: format!("PID自整定（{}秒内未完成{}个振荡周期）", request.max_duration_secs, request.cycles),
+            });
+        }
+
+        let sensors = ipmi
+            .get_temperature_sensors()
+            .map_err(|e| AppError::ipmi_error(format!("读取温度传感器失败: {}", e)))?;
+        let (sensor_id, temperature) = observed_temperature(&sensors, request.sensor_id.as_deref())?;
+        if temperature >= abort_temperature {
+            return Err(AppError::BusinessLogicError {
+                message: format!(
+                    "{} 温度 {:.1}°C 达到中止温度 {:.1}°C，已中止自整定",
+                    sensor_id, temperature, abort_temperature
+                ),
+            });
+        }
+
+        let output = experiment.observe(elapsed.as_secs_f64(), temperature);
+        if applied != Some(output) {
+            ipmi.set_all_fan_speeds(output)
+                .map_err(|e| AppError::ipmi_error(format!("设置风扇转速 {}% 失败: {}", output, e)))?;
+            applied = Some(output);
+        }
+        if let Some(result) = experiment.result(&sensor_id, elapsed.as_secs_f64()) {
+            return Ok(result);
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// 取指定传感器的温度，未指定时取最高温度
+fn observed_temperature(sensors: &[TemperatureSensor], sensor_id: Option<&str>) -> AppResult<(String, f64)> {
+    let sensor = match sensor_id {
+        Some(id) => sensors
+            .iter()
+            .find(|s| s.sensor_id == id)
+            .ok_or_else(|| AppError::not_found_error("温度传感器", id))?,
+        None => sensors
+            .iter()
+            .max_by(|a, b| a.temperature.total_cmp(&b.temperature))
+            .ok_or_else(|| AppError::ipmi_error("未读到任何温度传感器"))?,
+    };
+    Ok((sensor.sensor_id.clone(), sensor.temperature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ipmi_service::parse_temperature_sdr;
+    use chrono::Utc;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    fn tune_request(setpoint: f64) -> RelayTuneRequest {
+        serde_json::from_value(serde_json::json!({ "setpoint": setpoint })).unwrap()
+    }
+
+    /// 一阶惯性加纯滞后的散热对象：平衡温度随风扇转速线性下降
+    struct ThermalPlant {
+        temperature: f64,
+        /// 转速为0时的平衡温度（°C）
+        base: f64,
+        /// 每1%转速降低的平衡温度（°C）
+        gain: f64,
+        /// 时间常数（秒）
+        tau: f64,
+        /// 滞后时间内下发的转速，按0.1秒一格
+        delayed: VecDeque<f64>,
+    }
+
+    impl ThermalPlant {
+        const STEP: f64 = 0.1;
+
+        fn new(temperature: f64, dead_time_secs: f64) -> Self {
+            Self {
+                temperature,
+                base: 70.0,
+                gain: 0.4,
+                tau: 60.0,
+                delayed: VecDeque::from(vec![0.0; (dead_time_secs / Self::STEP) as usize]),
+            }
+        }
+
+        fn advance(&mut self, secs: f64, percent: f64) {
+            for _ in 0..(secs / Self::STEP).round() as usize {
+                self.delayed.push_back(percent);
+                let effective = self.delayed.pop_front().unwrap();
+                let equilibrium = self.base - self.gain * effective;
+                self.temperature += (equilibrium - self.temperature) / self.tau * Self::STEP;
+            }
+        }
+    }
+
+    #[test]
+    fn test_relay_experiment_gains_match_simulated_plant() {
+        // 回差远小于振荡幅度时临界点估计才准确
+        let mut request = tune_request(54.0);
+        request.hysteresis = 0.2;
+        let mut experiment = RelayExperiment::new(&request);
+        let mut plant = ThermalPlant::new(55.0, 10.0);
+
+        let mut elapsed = 0.0;
+        let result = loop {
+            let output = experiment.observe(elapsed, plant.temperature);
+            if let Some(result) = experiment.result("CPU1_TEMP", elapsed) {
+                break result;
+            }
+            assert!(elapsed < 1800.0, "relay experiment did not converge");
+            plant.advance(1.0, output as f64);
+            elapsed += 1.0;
+        };
+
+        // 该对象的理论临界点：arctan(ωτ) + ωL = π，即 Tu≈37.6s，Ku≈25.2%/°C。
+        // 描述函数法对纯滞后对象本身有偏差，采样间隔也会增加滞后，只要求在同一量级
+        let tu_error = result.ultimate_period_secs / 37.6 - 1.0;
+        let ku_error = result.ultimate_gain / 25.2 - 1.0;
+        assert!(tu_error.abs() < 0.3, "Tu = {}", result.ultimate_period_secs);
+        assert!(ku_error.abs() < 0.4, "Ku = {}", result.ultimate_gain);
+        let gains = result.gains;
+        assert!((gains.kp - 0.6 * result.ultimate_gain).abs() < 1e-9);
+        assert!((gains.ki - 2.0 * gains.kp / result.ultimate_period_secs).abs() < 1e-9);
+        assert!((gains.kd - gains.kp * result.ultimate_period_secs / 8.0).abs() < 1e-9);
+        assert!(result.max_temperature < request.abort_temperature(85.0));
+    }
+
+    /// 风扇失效、温度持续上升的服务器
+    struct OverheatingIpmi {
+        readings: Mutex<VecDeque<f64>>,
+        writes: Mutex<Vec<u8>>,
+        restored: Mutex<bool>,
+    }
+
+    impl RelayTuneIpmi for OverheatingIpmi {
+        fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+            let temperature = self.readings.lock().unwrap().pop_front().ok_or("no reading")?;
+            let line = format!("CPU1 Temp        | 0Eh | ok  |  3.1 | {} degrees C\n", temperature);
+            Ok(parse_temperature_sdr(&line, Utc::now()))
+        }
+
+        fn set_all_fan_speeds(&self, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+            self.writes.lock().unwrap().push(speed_percent);
+            Ok(())
+        }
+
+        fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+            *self.restored.lock().unwrap() = true;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auto_tune_aborts_near_critical_temperature() {
+        let ipmi = OverheatingIpmi {
+            readings: Mutex::new(VecDeque::from(vec![66.0, 72.0, 77.0, 80.0, 83.0])),
+            writes: Mutex::new(Vec::new()),
+            restored: Mutex::new(false),
+        };
+        let mut request = tune_request(65.0);
+        request.sample_interval_ms = 1;
+
+        let result = run(&ipmi, &request, 85.0).await;
+        assert!(matches!(result, Err(AppError::BusinessLogicError { .. })));
+        // 80°C 达到中止温度（85 - 5），不再读取后续温度
+        assert_eq!(ipmi.readings.lock().unwrap().len(), 1);
+        assert_eq!(*ipmi.writes.lock().unwrap(), vec![60]);
+        assert!(*ipmi.restored.lock().unwrap());
+
+        // 振荡上沿不低于中止温度的请求直接拒绝，不触碰风扇
+        let too_hot = tune_request(80.0);
+        assert!(run(&ipmi, &too_hot, 85.0).await.is_err());
+        assert_eq!(ipmi.writes.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::warn;
+
+/// 各轮询任务因上一次轮询未结束而跳过的周期数
+#[derive(Debug, Clone, Default)]
+pub struct PollOverruns {
+    counts: Arc<Mutex<BTreeMap<String, u64>>>,
+}
+
+impl PollOverruns {
+    /// 登记轮询任务，未发生超时的任务计数为0
+    ///
+    /// # 参数
+    /// * `task` - 任务名称
+    pub fn register(&self, task: &str) {
+        self.lock().entry(task.to_string()).or_insert(0);
+    }
+
+    /// 累计跳过的周期数
+    ///
+    /// # 参数
+    /// * `task` - 任务名称
+    /// * `skipped` - 本次跳过的周期数
+    pub fn record(&self, task: &str, skipped: u64) {
+        *self.lock().entry(task.to_string()).or_insert(0) += skipped;
+    }
+
+    /// 单个任务累计跳过的周期数
+    ///
+    /// # 参数
+    /// * `task` - 任务名称
+    pub fn get(&self, task: &str) -> u64 {
+        self.lock().get(task).copied().unwrap_or(0)
+    }
+
+    /// 全部任务的计数，按任务名称排序
+    pub fn snapshot(&self) -> BTreeMap<String, u64> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, u64>> {
+        self.counts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 不重叠的轮询节拍
+///
+/// 轮询在两次 [`PollTicker::tick`] 之间同步执行，不会并发。一次轮询超过周期时
+/// （例如BMC响应缓慢），期间错过的节拍全部跳过并计入 [`PollOverruns`]，
+/// 下一次轮询在轮询结束后的第一个节拍开始，不会连续补跑
+pub struct PollTicker {
+    task: String,
+    period: Duration,
+    next: Instant,
+    poll_started: Option<Instant>,
+    overruns: PollOverruns,
+}
+
+impl PollTicker {
+    /// 创建轮询节拍，第一次 `tick` 立即返回
+    ///
+    /// # 参数
+    /// * `task` - 任务名称，用作超时计数的标签
+    /// * `period` - 轮询周期，至少1毫秒
+    /// * `overruns` - 超时计数
+    pub fn new(task: impl Into<String>, period: Duration, overruns: PollOverruns) -> Self {
+        let task = task.into();
+        overruns.register(&task);
+        Self {
+            task,
+            period: period.max(Duration::from_millis(1)),
+            next: Instant::now(),
+            poll_started: None,
+            overruns,
+        }
+    }
+
+    /// 修改轮询周期，从当前这次轮询开始计算下一个节拍
+    ///
+    /// # 参数
+    /// * `period` - 轮询周期，至少1毫秒
+    pub fn set_period(&mut self, period: Duration) {
+        self.period = period.max(Duration::from_millis(1));
+    }
+
+    /// 等待下一个节拍，返回后开始本次轮询
+    pub async fn tick(&mut self) {
+        if let Some(started) = self.poll_started.take() {
+            let elapsed = Instant::now().saturating_duration_since(started);
+            let skipped = (elapsed.as_nanos() / self.period.as_nanos()) as u64;
+            if skipped > 0 {
+                warn!(
+                    "轮询任务 {} 耗时 {:?}，超过周期 {:?}，跳过 {} 个周期",
+                    self.task, elapsed, self.period, skipped
+                );
+                self.overruns.record(&self.task, skipped);
+            }
+            let periods = u32::try_from(skipped + 1).unwrap_or(u32::MAX);
+            self.next = started + self.period * periods;
+        }
+        tokio::time::sleep_until(self.next).await;
+        self.poll_started = Some(self.next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_slow_poll_skips_ticks_instead_of_overlapping() {
+        let period = Duration::from_millis(40);
+        let overruns = PollOverruns::default();
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let polls = Arc::new(AtomicUsize::new(0));
+
+        let task = {
+            let (overruns, running, max_running, polls) =
+                (overruns.clone(), running.clone(), max_running.clone(), polls.clone());
+            tokio::spawn(async move {
+                let mut ticker = PollTicker::new("temperature", period, overruns);
+                loop {
+                    ticker.tick().await;
+                    let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now_running, Ordering::SeqCst);
+                    polls.fetch_add(1, Ordering::SeqCst);
+                    // 每次轮询耗时2.5个周期
+                    tokio::time::sleep(period * 5 / 2).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                }
+            })
+        };
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        task.abort();
+
+        // 500ms内按周期应有13个节拍，实际每3个周期轮询一次
+        let polls = polls.load(Ordering::SeqCst);
+        assert!((3..=5).contains(&polls), "polls = {}", polls);
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+        assert_eq!(overruns.get("temperature"), 2 * (polls as u64 - 1));
+        assert_eq!(overruns.snapshot().len(), 1);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::models::error::AppResult;
+use crate::services::ipmi_service::IpmiService;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tracing::{error, warn};
+
+/// 接近功率上限时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerCapMode {
+    /// 把提速限制在功率余量内，避免BMC为守住功率上限而给CPU降频
+    #[default]
+    Limit,
+    /// 照常提速，只发出功率上限冲突告警
+    AlertOnly,
+}
+
+/// 风扇控制与功率封顶的协调配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerCapConfig {
+    /// 是否启用，需要BMC支持DCMI功率读数
+    #[serde(default)]
+    pub enabled: bool,
+    /// 功率上限（W），与BMC上配置的功率封顶一致
+    #[serde(default)]
+    pub cap_watts: f64,
+    /// 预计功耗距上限不足该余量（W）即视为接近上限
+    #[serde(default = "default_margin_watts")]
+    pub margin_watts: f64,
+    /// 单个风扇满速时的功耗（W），按转速的三次方估算提速带来的功耗增量
+    #[serde(default = "default_fan_max_watts")]
+    pub fan_max_watts: f64,
+    /// 接近上限时的处理方式
+    #[serde(default)]
+    pub mode: PowerCapMode,
+    /// 温度达到该值（°C）后散热优先，不再限制提速
+    #[serde(default = "default_override_temperature")]
+    pub override_temperature: f64,
+}
+
+impl Default for PowerCapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cap_watts: 0.0,
+            margin_watts: default_margin_watts(),
+            fan_max_watts: default_fan_max_watts(),
+            mode: PowerCapMode::default(),
+            override_temperature: default_override_temperature(),
+        }
+    }
+}
+
+fn default_margin_watts() -> f64 {
+    20.0
+}
+
+fn default_fan_max_watts() -> f64 {
+    25.0
+}
+
+fn default_override_temperature() -> f64 {
+    85.0
+}
+
+/// 一次提速的功率封顶协调结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PowerCapDecision {
+    /// 控制器请求的转速百分比
+    pub requested_percent: f64,
+    /// 协调后允许的转速百分比
+    pub allowed_percent: f64,
+    /// 当前功耗（W）
+    pub power_watts: f64,
+    /// 按请求转速提速后的预计功耗（W）
+    pub projected_watts: f64,
+    /// 功率上限（W）
+    pub cap_watts: f64,
+    /// 预计功耗是否进入上限余量
+    pub near_cap: bool,
+}
+
+impl PowerCapConfig {
+    /// 按当前功耗决定本次提速允许的转速
+    ///
+    /// 降速或不变时不受限制；预计功耗进入余量时，`limit` 模式下把转速限制在余量内
+    /// （不低于当前转速），温度达到 `override_temperature` 或 `alert_only` 模式下照常提速
+    ///
+    /// # 参数
+    /// * `power_watts` - 当前功耗（W）
+    /// * `current_percent` - 风扇当前转速百分比
+    /// * `requested_percent` - 控制器请求的转速百分比
+    /// * `temperature` - 控制输入温度（°C）
+    pub fn decide(
+        &self,
+        power_watts: f64,
+        current_percent: f64,
+        requested_percent: f64,
+        temperature: f64,
+    ) -> PowerCapDecision {
+        let projected_watts = power_watts + self.fan_watts(requested_percent) - self.fan_watts(current_percent);
+        let budget_watts = self.cap_watts - self.margin_watts;
+        let near_cap = requested_percent > current_percent && projected_watts > budget_watts;
+
+        let allowed_percent = if near_cap && self.mode == PowerCapMode::Limit && temperature < self.override_temperature {
+            // 余量内可增加的风扇功耗折算为转速
+            let fan_budget = (budget_watts - power_watts + self.fan_watts(current_percent)).max(0.0);
+            let fitting = 100.0 * (fan_budget / self.fan_max_watts.max(f64::EPSILON)).cbrt();
+            fitting.clamp(current_percent, requested_percent)
+        } else {
+            requested_percent
+        };
+
+        PowerCapDecision {
+            requested_percent,
+            allowed_percent,
+            power_watts,
+            projected_watts,
+            cap_watts: self.cap_watts,
+            near_cap,
+        }
+    }
+
+    /// 单个风扇在指定转速下的估算功耗（W）
+    fn fan_watts(&self, percent: f64) -> f64 {
+        self.fan_max_watts * (percent.clamp(0.0, 100.0) / 100.0).powi(3)
+    }
+}
+
+/// 当前整机功耗读数
+pub trait PowerReading: Send + Sync {
+    /// 读取当前功耗（W）
+    fn power_watts(&self) -> Result<f64, Box<dyn std::error::Error>>;
+}
+
+impl PowerReading for IpmiService {
+    fn power_watts(&self) -> Result<f64, Box<dyn std::error::Error>> {
+        self.get_dcmi_power_reading()
+    }
+}
+
+/// 提速与功率上限冲突时的告警出口
+#[async_trait]
+pub trait PowerCapAlertSink: Send + Sync {
+    /// 发出功率上限冲突告警
+    ///
+    /// # 参数
+    /// * `fan_id` - 请求提速的风扇ID
+    /// * `decision` - 协调结果
+    async fn raise_power_cap_alert(&self, fan_id: &str, decision: &PowerCapDecision) -> AppResult<()>;
+}
+
+/// 风扇提速前的功率封顶协调
+///
+/// 每次提速前读取当前功耗，预计接近上限时按配置限制提速或仅告警；
+/// 进入冲突状态时告警一次，功耗回落后重新计数。功耗读取失败时不做限制
+#[derive(Clone)]
+pub struct PowerCapCoordinator {
+    config: PowerCapConfig,
+    power: Arc<dyn PowerReading>,
+    alert_sink: Option<Arc<dyn PowerCapAlertSink>>,
+    near_cap: Arc<Mutex<bool>>,
+}
+
+impl PowerCapCoordinator {
+    /// 创建功率封顶协调
+    ///
+    /// # 参数
+    /// * `config` - 协调配置
+    /// * `power` - 功耗读数来源，如 `IpmiService`
+    pub fn new(config: PowerCapConfig, power: Arc<dyn PowerReading>) -> Self {
+        Self {
+            config,
+            power,
+            alert_sink: None,
+            near_cap: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// 设置功率上限冲突时的告警出口
+    ///
+    /// # 参数
+    /// * `sink` - 告警出口，如 `ControlAlertSink`
+    pub fn with_alert_sink(mut self, sink: Arc<dyn PowerCapAlertSink>) -> Self {
+        self.alert_sink = Some(sink);
+        self
+    }
+
+    /// 协调一次转速下发，返回允许的转速百分比
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `current_percent` - 风扇当前转速百分比
+    /// * `requested_percent` - 控制器请求的转速百分比
+    /// * `temperature` - 控制输入温度（°C）
+    pub async fn coordinate(&self, fan_id: &str, current_percent: f64, requested_percent: f64, temperature: f64) -> f64 {
+        if !self.config.enabled || requested_percent <= current_percent {
+            return requested_percent;
+        }
+
+        let power_watts = match self.power.power_watts() {
+            Ok(power_watts) => power_watts,
+            Err(e) => {
+                warn!("读取功耗失败，风扇 {} 提速不受功率上限约束: {}", fan_id, e);
+                return requested_percent;
+            }
+        };
+
+        let decision = self.config.decide(power_watts, current_percent, requested_percent, temperature);
+        let entered = {
+            let mut near_cap = self.near_cap.lock().unwrap_or_else(|e| e.into_inner());
+            let entered = decision.near_cap && !*near_cap;
+            *near_cap = decision.near_cap;
+            entered
+        };
+
+        if decision.near_cap {
+            warn!(
+                "风扇 {} 提速至 {:.1}% 预计功耗 {:.0}W 接近上限 {:.0}W，实际下发 {:.1}%",
+                fan_id, requested_percent, decision.projected_watts, decision.cap_watts, decision.allowed_percent
+            );
+        }
+        if entered {
+            if let Some(ref sink) = self.alert_sink {
+                if let Err(e) = sink.raise_power_cap_alert(fan_id, &decision).await {
+                    error!("发出风扇 {} 功率上限冲突告警失败: {}", fan_id, e);
+                }
+            }
+        }
+
+        decision.allowed_percent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedPower(f64);
+
+    impl PowerReading for FixedPower {
+        fn power_watts(&self) -> Result<f64, Box<dyn std::error::Error>> {
+            Ok(self.0)
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingSink {
+        alerts: Mutex<Vec<(String, PowerCapDecision)>>,
+    }
+
+    #[async_trait]
+    impl PowerCapAlertSink for RecordingSink {
+        async fn raise_power_cap_alert(&self, fan_id: &str, decision: &PowerCapDecision) -> AppResult<()> {
+            self.alerts.lock().unwrap().push((fan_id.to_string(), decision.clone()));
+            Ok(())
+        }
+    }
+
+    fn coordinator(power_watts: f64, mode: PowerCapMode, sink: Arc<RecordingSink>) -> PowerCapCoordinator {
+        let config = PowerCapConfig {
+            enabled: true,
+            cap_watts: 500.0,
+            margin_watts: 20.0,
+            fan_max_watts: 25.0,
+            mode,
+            override_temperature: 85.0,
+        };
+        PowerCapCoordinator::new(config, Arc::new(FixedPower(power_watts))).with_alert_sink(sink)
+    }
+
+    #[tokio::test]
+    async fn test_near_cap_power_limits_fan_boost() {
+        // 功耗远低于上限时照常提速
+        let sink = Arc::new(RecordingSink::default());
+        let unconstrained = coordinator(300.0, PowerCapMode::Limit, sink.clone());
+        assert_eq!(unconstrained.coordinate("FAN1", 40.0, 100.0, 75.0).await, 100.0);
+        assert!(sink.alerts.lock().unwrap().is_empty());
+
+        // 接近上限时提速被限制在余量内，并只告警一次
+        let sink = Arc::new(RecordingSink::default());
+        let near_cap = coordinator(470.0, PowerCapMode::Limit, sink.clone());
+        let allowed = near_cap.coordinate("FAN1", 40.0, 100.0, 75.0).await;
+        assert!(allowed > 40.0 && allowed < 100.0, "allowed = {}", allowed);
+        // 470W + 提速后的风扇功耗不超过 500W - 20W 余量
+        let config = PowerCapConfig::default();
+        let fan_watts = |percent: f64| config.fan_max_watts * (percent / 100.0).powi(3);
+        assert!((470.0 - fan_watts(40.0) + fan_watts(allowed) - 480.0).abs() < 1e-6);
+        assert_eq!(near_cap.coordinate("FAN1", 40.0, 100.0, 75.0).await, allowed);
+        let alerts = sink.alerts.lock().unwrap().clone();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].0, "FAN1");
+        assert!(alerts[0].1.projected_watts > 480.0);
+
+        // 达到散热优先温度或仅告警模式时照常提速，降速不受影响
+        assert_eq!(near_cap.coordinate("FAN1", 40.0, 100.0, 90.0).await, 100.0);
+        let alert_only = coordinator(470.0, PowerCapMode::AlertOnly, Arc::new(RecordingSink::default()));
+        assert_eq!(alert_only.coordinate("FAN1", 40.0, 100.0, 75.0).await, 100.0);
+        assert_eq!(near_cap.coordinate("FAN1", 60.0, 30.0, 75.0).await, 30.0);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::models::control::QuietHoursSchedule;
+use crate::models::error::{AppError, AppResult};
+use chrono::{DateTime, Local, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+
+/// 静音时段计划
+///
+/// 控制循环自动调速时按当前本地时间取生效时段的转速上限，最高温度达到计划的紧急温度时不受上限约束。
+/// 计划保存在 `control.quiet_hours_file` 中，文件不存在时静音时段不启用
+#[derive(Clone, Default)]
+pub struct QuietHoursStore {
+    schedule: Arc<RwLock<QuietHoursSchedule>>,
+    file_path: Option<PathBuf>,
+    update: Arc<Mutex<()>>,
+}
+
+impl QuietHoursStore {
+    /// 从计划文件加载，路径为空时不持久化
+    ///
+    /// # 参数
+    /// * `file_path` - 计划文件路径
+    pub fn load(file_path: &str) -> AppResult<Self> {
+        let mut store = Self::default();
+        if file_path.is_empty() {
+            return Ok(store);
+        }
+
+        let path = PathBuf::from(file_path);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                let schedule: QuietHoursSchedule = serde_json::from_str(&content).map_err(|e| {
+                    AppError::file_system_error(format!("解析静音计划文件 {} 失败: {}", path.display(), e))
+                })?;
+                schedule.validate()?;
+                *store.schedule.write().unwrap_or_else(|e| e.into_inner()) = schedule;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(AppError::file_system_error(format!(
+                    "读取静音计划文件 {} 失败: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        }
+        store.file_path = Some(path);
+        Ok(store)
+    }
+
+    /// 当前静音计划
+    pub fn schedule(&self) -> QuietHoursSchedule {
+        self.schedule.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 校验并替换静音计划，写入计划文件成功后才生效
+    ///
+    /// # 参数
+    /// * `schedule` - 新的静音计划
+    pub async fn update(&self, mut schedule: QuietHoursSchedule) -> AppResult<QuietHoursSchedule> {
+        schedule.validate()?;
+        schedule.updated_at = Utc::now();
+
+        let _update = self.update.lock().await;
+        if let Some(path) = &self.file_path {
+            save_schedule(path, &schedule).await?;
+        }
+        *self.schedule.write().unwrap_or_else(|e| e.into_inner()) = schedule.clone();
+        Ok(schedule)
+    }
+
+    /// 指定时间生效的转速上限，温度达到计划的紧急温度时为空
+    ///
+    /// # 参数
+    /// * `at` - 控制时间
+    /// * `temperature` - 控制温度（°C）
+    pub fn fan_cap(&self, at: DateTime<Utc>, temperature: f64) -> Option<f64> {
+        let schedule = self.schedule.read().unwrap_or_else(|e| e.into_inner());
+        if temperature >= schedule.emergency_temperature {
+            return None;
+        }
+        schedule.fan_cap_at(at.with_timezone(&Local).naive_local())
+    }
+}
+
+/// 写入计划文件，先写临时文件再替换，避免中途失败留下损坏的文件
+async fn save_schedule(path: &Path, schedule: &QuietHoursSchedule) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(schedule)?;
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::file_system_error(format!("创建静音计划目录失败: {}", e)))?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, content)
+        .await
+        .map_err(|e| AppError::file_system_error(format!("写入静音计划文件失败: {}", e)))?;
+    tokio::fs::rename(&tmp_path, path)
+        .await
+        .map_err(|e| AppError::file_system_error(format!("替换静音计划文件失败: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::control::QuietWindow;
+    use chrono::NaiveTime;
+
+    #[tokio::test]
+    async fn test_updated_schedule_survives_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("quiet_hours.json").display().to_string();
+        let store = QuietHoursStore::load(&path).unwrap();
+        assert!(!store.schedule().enabled);
+
+        let window = QuietWindow {
+            name: "night".to_string(),
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            weekdays: Vec::new(),
+            max_fan_percent: 45.0,
+        };
+        store
+            .update(QuietHoursSchedule {
+                enabled: true,
+                windows: vec![window.clone()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let reloaded = QuietHoursStore::load(&path).unwrap();
+        assert!(reloaded.schedule().enabled);
+        assert_eq!(reloaded.schedule().windows[0].max_fan_percent, 45.0);
+
+        // 校验失败时不写入
+        let invalid = QuietHoursSchedule {
+            enabled: true,
+            windows: vec![QuietWindow { end: window.start, ..window }],
+            ..Default::default()
+        };
+        assert!(store.update(invalid).await.is_err());
+        let reloaded = QuietHoursStore::load(&path).unwrap();
+        assert_eq!(reloaded.schedule().windows[0].end, NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+    }
+}
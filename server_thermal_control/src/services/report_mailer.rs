@@ -0,0 +1,512 @@
+use crate::config::{EmailConfig, ReportEmailConfig};
+use crate::models::error::{AppError, AppResult};
+use crate::services::alert_thresholds::{AlertThresholdStore, SensorAlertThreshold, ThresholdLevel};
+use crate::services::temperature_histogram::{TemperatureHistogram, TemperatureHistogramStore};
+use chrono::{DateTime, Duration, Local, Utc};
+use lettre::message::{header, Mailbox, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// 报告中最多列出的建议数
+const MAX_TOP_RECOMMENDATIONS: usize = 3;
+
+/// 报告建议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportRecommendation {
+    /// 传感器ID
+    pub sensor_id: String,
+    /// 读数相对告警阈值的级别
+    pub level: ThresholdLevel,
+    /// 标题
+    pub title: String,
+    /// 建议的操作
+    pub action: String,
+}
+
+/// 周期散热报告
+///
+/// 由温度分布直方图生成，包含执行摘要和按严重程度排序的重点建议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThermalReport {
+    /// 报告ID
+    pub id: String,
+    /// 周期开始时间
+    pub period_start: DateTime<Utc>,
+    /// 周期结束时间
+    pub period_end: DateTime<Utc>,
+    /// 统计到读数的传感器数量
+    pub sensors: usize,
+    /// 关键发现
+    pub key_findings: Vec<String>,
+    /// 重点建议
+    pub top_recommendations: Vec<ReportRecommendation>,
+    /// 各传感器的温度分布详情
+    pub temperature_distribution: Vec<TemperatureHistogram>,
+}
+
+impl ThermalReport {
+    /// 按各传感器的温度分布生成报告
+    ///
+    /// p95达到告警阈值的传感器按级别给出建议，只有最高温度达到警告阈值的传感器给出提示
+    ///
+    /// # 参数
+    /// * `histograms` - 各传感器的温度分布
+    /// * `alert_thresholds` - 按传感器设置的告警阈值
+    /// * `default_threshold` - 未设置阈值的传感器使用的告警阈值
+    /// * `period_start` - 周期开始时间
+    /// * `period_end` - 周期结束时间
+    pub fn build(
+        histograms: &[TemperatureHistogram],
+        alert_thresholds: &AlertThresholdStore,
+        default_threshold: SensorAlertThreshold,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Self {
+        let mut key_findings = Vec::new();
+        let mut recommendations = Vec::new();
+
+        if let Some(hottest) = histograms.iter().max_by(|a, b| a.p95.total_cmp(&b.p95)) {
+            let samples: usize = histograms.iter().map(|histogram| histogram.samples).sum();
+            key_findings.push(format!("统计 {} 个温度传感器，共 {} 个读数", histograms.len(), samples));
+            key_findings.push(format!(
+                "最热传感器 {}：p95 {:.1}°C，最高 {:.1}°C",
+                hottest.sensor_id, hottest.p95, hottest.max
+            ));
+        }
+
+        for histogram in histograms {
+            let threshold = alert_thresholds.get(&histogram.sensor_id).unwrap_or(default_threshold);
+            let recommendation = match threshold.classify(histogram.p95) {
+                ThresholdLevel::Critical => Some((
+                    ThresholdLevel::Critical,
+                    format!("{} 经常处于严重温度（p95 {:.1}°C）", histogram.sensor_id, histogram.p95),
+                    "检查该位置的风道和散热器，并提高对应风扇曲线".to_string(),
+                )),
+                ThresholdLevel::Warning => Some((
+                    ThresholdLevel::Warning,
+                    format!("{} 经常超过警告阈值（p95 {:.1}°C）", histogram.sensor_id, histogram.p95),
+                    format!("提高对应风扇曲线或降低负载，使温度回到 {:.1}°C 以下", threshold.warning),
+                )),
+                ThresholdLevel::Within if threshold.classify(histogram.max) != ThresholdLevel::Within => Some((
+                    ThresholdLevel::Within,
+                    format!("{} 出现短时高温（最高 {:.1}°C）", histogram.sensor_id, histogram.max),
+                    "核对高温时段的负载峰值".to_string(),
+                )),
+                ThresholdLevel::Within => None,
+            };
+            if let Some((level, title, action)) = recommendation {
+                recommendations.push((histogram.p95 - threshold.warning, ReportRecommendation {
+                    sensor_id: histogram.sensor_id.clone(),
+                    level,
+                    title,
+                    action,
+                }));
+            }
+        }
+
+        let over_warning = recommendations
+            .iter()
+            .filter(|(_, rec)| rec.level != ThresholdLevel::Within)
+            .count();
+        if !histograms.is_empty() {
+            key_findings.push(if over_warning == 0 {
+                "所有传感器的p95温度均低于警告阈值".to_string()
+            } else {
+                format!("{} 个传感器的p95温度达到告警阈值", over_warning)
+            });
+        }
+
+        recommendations.sort_by(|(a_margin, a), (b_margin, b)| {
+            b.level.cmp(&a.level).then(b_margin.total_cmp(a_margin))
+        });
+        recommendations.truncate(MAX_TOP_RECOMMENDATIONS);
+
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            period_start,
+            period_end,
+            sensors: histograms.len(),
+            key_findings,
+            top_recommendations: recommendations.into_iter().map(|(_, rec)| rec).collect(),
+            temperature_distribution: histograms.to_vec(),
+        }
+    }
+
+    /// 周期内是否有温度读数
+    pub fn has_sufficient_data(&self) -> bool {
+        self.sensors > 0
+    }
+
+    /// 将执行摘要渲染为HTML，用于邮件正文
+    pub fn to_html_summary(&self) -> String {
+        let mut html = String::new();
+        let time_format = "%Y-%m-%d %H:%M UTC";
+
+        html.push_str("<html><body>\n<h1>散热周期报告</h1>\n");
+        html.push_str(&format!(
+            "<p>周期: {} ~ {}</p>\n",
+            self.period_start.format(time_format),
+            self.period_end.format(time_format),
+        ));
+
+        html.push_str("<h2>关键发现</h2>\n<ul>\n");
+        for finding in &self.key_findings {
+            html.push_str(&format!("<li>{}</li>\n", escape_html(finding)));
+        }
+        html.push_str("</ul>\n");
+
+        html.push_str("<h2>重点建议</h2>\n");
+        if self.top_recommendations.is_empty() {
+            html.push_str("<p>暂无建议</p>\n");
+        } else {
+            html.push_str("<ol>\n");
+            for rec in &self.top_recommendations {
+                html.push_str(&format!(
+                    "<li><strong>[{}] {}</strong> — {}</li>\n",
+                    level_label(rec.level),
+                    escape_html(&rec.title),
+                    escape_html(&rec.action)
+                ));
+            }
+            html.push_str("</ol>\n");
+        }
+
+        html.push_str("</body></html>\n");
+        html
+    }
+
+    /// 将报告渲染为Markdown，包含执行摘要和各传感器的温度分布
+    pub fn to_markdown(&self) -> String {
+        let time_format = "%Y-%m-%d %H:%M UTC";
+        let mut markdown = format!(
+            "# 散热周期报告\n\n周期: {} ~ {}\n\n## 关键发现\n\n",
+            self.period_start.format(time_format),
+            self.period_end.format(time_format),
+        );
+        if self.key_findings.is_empty() {
+            markdown.push_str("周期内没有温度读数\n");
+        }
+        for finding in &self.key_findings {
+            markdown.push_str(&format!("- {}\n", finding));
+        }
+
+        markdown.push_str("\n## 重点建议\n\n");
+        if self.top_recommendations.is_empty() {
+            markdown.push_str("暂无建议\n");
+        }
+        for (i, rec) in self.top_recommendations.iter().enumerate() {
+            markdown.push_str(&format!(
+                "{}. **[{}] {}** — {}\n",
+                i + 1,
+                level_label(rec.level),
+                rec.title,
+                rec.action
+            ));
+        }
+
+        markdown.push_str("\n## 温度分布\n\n| 传感器 | 读数 | 最低 | p50 | p95 | p99 | 最高 |\n|---|---|---|---|---|---|---|\n");
+        for histogram in &self.temperature_distribution {
+            markdown.push_str(&format!(
+                "| {} | {} | {:.1} | {:.1} | {:.1} | {:.1} | {:.1} |\n",
+                histogram.sensor_id,
+                histogram.samples,
+                histogram.min,
+                histogram.p50,
+                histogram.p95,
+                histogram.p99,
+                histogram.max
+            ));
+        }
+        markdown
+    }
+}
+
+/// 建议级别的显示名称
+fn level_label(level: ThresholdLevel) -> &'static str {
+    match level {
+        ThresholdLevel::Critical => "严重",
+        ThresholdLevel::Warning => "警告",
+        ThresholdLevel::Within => "提示",
+    }
+}
+
+/// 生成报告所需的数据来源
+#[derive(Debug, Clone)]
+pub struct ReportSource {
+    /// 温度分布采集器
+    pub histograms: TemperatureHistogramStore,
+    /// 按传感器设置的告警阈值
+    pub alert_thresholds: AlertThresholdStore,
+    /// 未设置阈值的传感器使用的告警阈值
+    pub default_threshold: SensorAlertThreshold,
+}
+
+impl ReportSource {
+    /// 生成截至 `now` 的报告
+    ///
+    /// 周期超过温度分布采集窗口时只覆盖采集窗口
+    ///
+    /// # 参数
+    /// * `period_hours` - 报告周期（小时）
+    /// * `now` - 周期结束时间
+    pub fn report(&self, period_hours: u64, now: DateTime<Utc>) -> AppResult<ThermalReport> {
+        let window_secs = (period_hours * 3600).min(self.histograms.config().window_secs);
+        let histograms = self.histograms.histograms(now, Some(window_secs))?;
+        Ok(ThermalReport::build(
+            &histograms,
+            &self.alert_thresholds,
+            self.default_threshold,
+            now - Duration::seconds(window_secs as i64),
+            now,
+        ))
+    }
+}
+
+/// 定时报告邮件服务
+///
+/// 按 `report_email` 配置的周期生成散热报告，将执行摘要和重点建议以HTML邮件发送
+pub struct ReportMailer<T> {
+    /// 邮件传输
+    transport: T,
+    /// 发件人
+    from: Mailbox,
+    /// 收件人
+    recipients: Vec<Mailbox>,
+    /// 发送计划
+    config: ReportEmailConfig,
+}
+
+impl ReportMailer<AsyncSmtpTransport<Tokio1Executor>> {
+    /// 使用告警邮件的SMTP配置创建报告邮件服务
+    ///
+    /// # 参数
+    /// * `email` - SMTP配置
+    /// * `config` - 报告邮件配置
+    pub fn from_smtp_config(email: &EmailConfig, config: ReportEmailConfig) -> AppResult<Self> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&email.smtp_host)
+            .map_err(|e| smtp_error(format!("无法连接SMTP服务器 {}: {}", email.smtp_host, e)))?
+            .port(email.smtp_port)
+            .credentials(Credentials::new(email.username.clone(), email.password.clone()))
+            .build();
+        Self::new(transport, &email.from, config)
+    }
+}
+
+impl<T> ReportMailer<T>
+where
+    T: AsyncTransport + Send + Sync + 'static,
+    T::Error: Display,
+{
+    /// 创建报告邮件服务
+    ///
+    /// # 参数
+    /// * `transport` - 邮件传输
+    /// * `from` - 发件人地址
+    /// * `config` - 报告邮件配置
+    pub fn new(transport: T, from: &str, config: ReportEmailConfig) -> AppResult<Self> {
+        if config.recipients.is_empty() {
+            return Err(AppError::validation_error("report_email.recipients", "至少需要一个收件人"));
+        }
+        let from = parse_mailbox("alert.email.from", from)?;
+        let recipients = config
+            .recipients
+            .iter()
+            .map(|address| parse_mailbox("report_email.recipients", address))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(Self {
+            transport,
+            from,
+            recipients,
+            config,
+        })
+    }
+
+    /// 由报告构造邮件
+    ///
+    /// # 参数
+    /// * `report` - 周期散热报告
+    pub fn build_message(&self, report: &ThermalReport) -> AppResult<Message> {
+        let mut builder = Message::builder().from(self.from.clone()).subject(format!(
+            "散热周期报告 {} ~ {}",
+            report.period_start.format("%Y-%m-%d"),
+            report.period_end.format("%Y-%m-%d")
+        ));
+        for recipient in &self.recipients {
+            builder = builder.to(recipient.clone());
+        }
+
+        // 正文含中文，单行常超过8bit允许的长度，统一以base64发送
+        let body = SinglePart::builder()
+            .header(header::ContentType::TEXT_HTML)
+            .header(header::ContentTransferEncoding::Base64)
+            .body(report.to_html_summary());
+
+        builder
+            .singlepart(body)
+            .map_err(|e| AppError::internal_server_error(format!("构造报告邮件失败: {}", e)))
+    }
+
+    /// 发送报告，数据不足时跳过并返回false
+    ///
+    /// # 参数
+    /// * `report` - 周期散热报告
+    pub async fn send_report(&self, report: &ThermalReport) -> AppResult<bool> {
+        if !report.has_sufficient_data() {
+            info!("报告周期内数据不足，跳过发送报告邮件");
+            return Ok(false);
+        }
+
+        let message = self.build_message(report)?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| smtp_error(format!("发送报告邮件失败: {}", e)))?;
+
+        info!("已向 {} 个收件人发送报告邮件 {}", self.recipients.len(), report.id);
+        Ok(true)
+    }
+
+    /// 生成覆盖一个发送周期的报告并发送
+    ///
+    /// # 参数
+    /// * `source` - 报告数据来源
+    pub async fn send_scheduled(&self, source: &ReportSource) -> AppResult<bool> {
+        let report = source.report(self.config.cadence.period_hours(), Utc::now())?;
+        self.send_report(&report).await
+    }
+
+    /// 启动定时发送任务
+    ///
+    /// # 参数
+    /// * `source` - 报告数据来源
+    pub fn start_task(self: Arc<Self>, source: ReportSource) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let now = Local::now().naive_local();
+                let next_run = self.config.next_run_after(now);
+                info!("下次发送报告邮件时间: {}", next_run);
+                tokio::time::sleep((next_run - now).to_std().unwrap_or_default()).await;
+
+                if let Err(e) = self.send_scheduled(&source).await {
+                    warn!("发送报告邮件失败: {}", e);
+                }
+            }
+        })
+    }
+}
+
+/// 转义HTML文本中的特殊字符
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub(crate) fn smtp_error(message: String) -> AppError {
+    AppError::ExternalServiceError {
+        service: "smtp".to_string(),
+        message,
+    }
+}
+
+pub(crate) fn parse_mailbox(field: &str, address: &str) -> AppResult<Mailbox> {
+    address
+        .parse()
+        .map_err(|e| AppError::validation_error(field, format!("邮件地址 {} 无效: {}", address, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ReportCadence;
+    use crate::services::ipmi_service::parse_temperature_sdr;
+    use crate::services::temperature_histogram::TemperatureHistogramConfig;
+    use base64::Engine;
+    use chrono::{NaiveDate, Weekday};
+    use lettre::transport::stub::AsyncStubTransport;
+
+    #[tokio::test]
+    async fn test_scheduled_report_email_carries_summary_at_cadence() {
+        let config = ReportEmailConfig {
+            enabled: true,
+            cadence: ReportCadence::Weekly,
+            send_at: chrono::NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            weekday: Weekday::Mon,
+            recipients: vec!["ops@example.com".to_string(), "lead@example.com".to_string()],
+        };
+
+        // 2026-10-14为周三，下一次发送在下周一08:00
+        let wednesday = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap().and_hms_opt(10, 0, 0).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap().and_hms_opt(8, 0, 0).unwrap();
+        assert_eq!(config.next_run_after(wednesday), monday);
+        assert_eq!(config.next_run_after(monday), monday + Duration::days(7));
+        assert_eq!(config.cadence.period_hours(), 168);
+
+        let daily = ReportEmailConfig { cadence: ReportCadence::Daily, ..config.clone() };
+        assert_eq!(daily.next_run_after(wednesday), monday - Duration::days(4));
+
+        let mailer = ReportMailer::new(AsyncStubTransport::new_ok(), "thermal@example.com", config).unwrap();
+        let source = ReportSource {
+            histograms: TemperatureHistogramStore::new(TemperatureHistogramConfig {
+                window_secs: 3600,
+                ..Default::default()
+            }),
+            alert_thresholds: AlertThresholdStore::default(),
+            default_threshold: SensorAlertThreshold {
+                warning: 80.0,
+                critical: 90.0,
+            },
+        };
+
+        // 没有任何温度读数时不发送
+        assert!(!mailer.send_scheduled(&source).await.unwrap());
+        assert!(mailer.transport.messages().await.is_empty());
+
+        let now = Utc::now();
+        for i in 0..10 {
+            let at = now - Duration::minutes(30) + Duration::minutes(i);
+            let sensors = parse_temperature_sdr(
+                &format!(
+                    "CPU1 Temp        | 0Eh | ok  |  3.1 | {} degrees C\nInlet Temp       | 04h | ok  |  7.1 | 24 degrees C\n",
+                    84 + i % 2
+                ),
+                at,
+            );
+            source.histograms.record(&sensors, at);
+        }
+
+        let report = source.report(168, now).unwrap();
+        assert_eq!(report.period_end - report.period_start, Duration::hours(1));
+        assert_eq!(report.top_recommendations.len(), 1);
+        assert_eq!(report.top_recommendations[0].sensor_id, "CPU1_TEMP");
+        assert_eq!(report.top_recommendations[0].level, ThresholdLevel::Warning);
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("1. **[警告] CPU1_TEMP 经常超过警告阈值"));
+        assert!(markdown.contains("## 温度分布"));
+        assert!(markdown.contains("| INLET_TEMP | 10 |"));
+
+        assert!(mailer.send_report(&report).await.unwrap());
+        let messages = mailer.transport.messages().await;
+        assert_eq!(messages.len(), 1);
+
+        let (envelope, raw) = &messages[0];
+        assert_eq!(envelope.to().len(), 2);
+        assert!(raw.contains("Content-Type: text/html"));
+
+        let (_, encoded) = raw.split_once("\r\n\r\n").unwrap();
+        let encoded: String = encoded.split_whitespace().collect();
+        let html = String::from_utf8(base64::engine::general_purpose::STANDARD.decode(encoded).unwrap()).unwrap();
+        assert!(html.contains("<h2>关键发现</h2>"));
+        for finding in &report.key_findings {
+            assert!(html.contains(finding.as_str()));
+        }
+        assert!(html.contains("CPU1_TEMP 经常超过警告阈值"));
+    }
+}
@@ -0,0 +1,589 @@
+use crate::config::{CacheBackend, CacheConfig, RedisConfig};
+use crate::models::{
+    analytics::AnalyticsResult,
+    error::{AppError, AppResult},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OnceCell, RwLock};
+use tracing::{info, warn};
+
+/// 分析结果缓存有效期（秒）
+///
+/// 与原先 `cleanup_cache` 中保留1小时的语义保持一致
+pub const ANALYSIS_CACHE_TTL_SECS: u64 = 3600;
+
+/// 缓存键过滤条件
+pub type CacheKeyFilter<'a> = dyn Fn(&str) -> bool + Send + Sync + 'a;
+
+/// 分析结果缓存
+///
+/// 抽象分析结果的存储后端，便于在进程内缓存与共享缓存之间切换
+#[async_trait]
+pub trait ResultCache: Send + Sync {
+    /// 获取缓存的分析结果
+    ///
+    /// # 参数
+    /// * `key` - 缓存键
+    async fn get(&self, key: &str) -> AppResult<Option<AnalyticsResult>>;
+
+    /// 写入分析结果
+    ///
+    /// # 参数
+    /// * `key` - 缓存键
+    /// * `result` - 分析结果
+    async fn set(&self, key: &str, result: &AnalyticsResult) -> AppResult<()>;
+
+    /// 清理过期条目，返回清理数量
+    async fn cleanup(&self) -> AppResult<usize>;
+
+    /// 删除缓存键满足条件的条目，返回删除数量
+    ///
+    /// # 参数
+    /// * `matches` - 缓存键过滤条件
+    async fn remove_matching(&self, matches: &CacheKeyFilter<'_>) -> AppResult<usize>;
+
+    /// 缓存后端名称
+    fn backend_name(&self) -> &'static str;
+}
+
+/// 进程内缓存实现
+pub struct InMemoryResultCache {
+    entries: RwLock<HashMap<String, (DateTime<Utc>, AnalyticsResult)>>,
+    ttl: Duration,
+}
+
+impl InMemoryResultCache {
+    /// 创建进程内缓存
+    ///
+    /// # 参数
+    /// * `ttl_secs` - 条目有效期（秒）
+    pub fn new(ttl_secs: u64) -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            ttl: Duration::seconds(ttl_secs as i64),
+        }
+    }
+}
+
+#[async_trait]
+impl ResultCache for InMemoryResultCache {
+    async fn get(&self, key: &str) -> AppResult<Option<AnalyticsResult>> {
+        let entries = self.entries.read().await;
+        let cutoff_time = Utc::now() - self.ttl;
+        Ok(entries
+            .get(key)
+            .filter(|(cached_at, _)| *cached_at > cutoff_time)
+            .map(|(_, result)| result.clone()))
+    }
+
+    async fn set(&self, key: &str, result: &AnalyticsResult) -> AppResult<()> {
+        let mut entries = self.entries.write().await;
+        // 写入时顺带淘汰过期条目，缓存键随查询参数增长，不依赖定时清理
+        let now = Utc::now();
+        let cutoff_time = now - self.ttl;
+        entries.retain(|_, (cached_at, _)| *cached_at > cutoff_time);
+        entries.insert(key.to_string(), (now, result.clone()));
+        Ok(())
+    }
+
+    async fn cleanup(&self) -> AppResult<usize> {
+        let mut entries = self.entries.write().await;
+        let cutoff_time = Utc::now() - self.ttl;
+
+        let original_size = entries.len();
+        entries.retain(|_, (cached_at, _)| *cached_at > cutoff_time);
+
+        Ok(original_size - entries.len())
+    }
+
+    async fn remove_matching(&self, matches: &CacheKeyFilter<'_>) -> AppResult<usize> {
+        let mut entries = self.entries.write().await;
+        let original_size = entries.len();
+        entries.retain(|key, _| !matches(key));
+        Ok(original_size - entries.len())
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "memory"
+    }
+}
+
+/// Redis缓存实现
+///
+/// 多实例部署时共享分析结果，过期由Redis的键TTL负责
+#[cfg(feature = "redis-cache")]
+pub struct RedisResultCache {
+    connection: redis::aio::ConnectionManager,
+    key_prefix: String,
+    ttl_secs: u64,
+}
+
+#[cfg(feature = "redis-cache")]
+impl RedisResultCache {
+    /// 连接Redis并创建缓存
+    ///
+    /// # 参数
+    /// * `config` - Redis配置
+    /// * `ttl_secs` - 条目有效期（秒）
+    pub async fn connect(config: &RedisConfig, ttl_secs: u64) -> AppResult<Self> {
+        let client = redis::Client::open(config.url.as_str())
+            .map_err(|e| AppError::config_error(format!("Redis地址无效: {}", e)))?;
+
+        let connection = tokio::time::timeout(
+            std::time::Duration::from_secs(config.connect_timeout),
+            redis::aio::ConnectionManager::new(client),
+        )
+        .await
+        .map_err(|_| AppError::TimeoutError {
+            operation: "redis connect".to_string(),
+        })?
+        .map_err(|e| AppError::ExternalServiceError {
+            service: "redis".to_string(),
+            message: e.to_string(),
+        })?;
+
+        Ok(Self {
+            connection,
+            key_prefix: "thermal:analytics:".to_string(),
+            ttl_secs,
+        })
+    }
+
+    fn redis_key(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+}
+
+#[cfg(feature = "redis-cache")]
+fn redis_error(err: redis::RedisError) -> AppError {
+    AppError::ExternalServiceError {
+        service: "redis".to_string(),
+        message: err.to_string(),
+    }
+}
+
+#[cfg(feature = "redis-cache")]
+#[async_trait]
+impl ResultCache for RedisResultCache {
+    async fn get(&self, key: &str) -> AppResult<Option<AnalyticsResult>> {
+        use redis::AsyncCommands;
+
+        let mut connection = self.connection.clone();
+        let payload: Option<String> = connection
+            .get(self.redis_key(key))
+            .await
+            .map_err(redis_error)?;
+
+        match payload {
+            Some(payload) => Ok(Some(serde_json::from_str(&payload)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: &str, result: &AnalyticsResult) -> AppResult<()> {
+        use redis::AsyncCommands;
+
+        let payload = serde_json::to_string(result)?;
+        let mut connection = self.connection.clone();
+        let _: () = connection
+            .set_ex(self.redis_key(key), payload, self.ttl_secs)
+            .await
+            .map_err(redis_error)?;
+
+        Ok(())
+    }
+
+    async fn cleanup(&self) -> AppResult<usize> {
+        // Redis按键TTL自动过期，无需手动清理
+        Ok(0)
+    }
+
+    async fn remove_matching(&self, matches: &CacheKeyFilter<'_>) -> AppResult<usize> {
+        use futures_util::StreamExt;
+        use redis::AsyncCommands;
+
+        let mut connection = self.connection.clone();
+        let keys: Vec<String> = {
+            let mut scan = connection
+                .scan_match::<_, String>(format!("{}*", self.key_prefix))
+                .await
+                .map_err(redis_error)?;
+            let mut keys = Vec::new();
+            while let Some(key) = scan.next().await {
+                if key.strip_prefix(&self.key_prefix).is_some_and(|key| matches(key)) {
+                    keys.push(key);
+                }
+            }
+            keys
+        };
+
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let removed: usize = connection.del(&keys).await.map_err(redis_error)?;
+        Ok(removed)
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "redis"
+    }
+}
+
+/// 按缓存键合并并发的相同分析
+///
+/// 同一缓存键的计算进行中时，后到的请求等待并共享其结果；
+/// 计算完成即从表中移除，结果的保留由 `ResultCache` 负责
+#[derive(Default)]
+pub struct SingleFlight {
+    in_flight: Mutex<HashMap<String, Arc<OnceCell<AppResult<AnalyticsResult>>>>>,
+}
+
+impl SingleFlight {
+    /// 创建合并器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 执行计算，若同一键已有计算在进行则等待其结果
+    ///
+    /// 进行中的计算被取消时，由下一个等待者重新执行
+    ///
+    /// # 参数
+    /// * `key` - 缓存键
+    /// * `compute` - 分析计算
+    pub async fn run<F, Fut>(&self, key: &str, compute: F) -> AppResult<AnalyticsResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<AnalyticsResult>>,
+    {
+        let cell = Arc::clone(self.in_flight.lock().await.entry(key.to_string()).or_default());
+        let result = cell.get_or_init(compute).await.clone();
+
+        let mut in_flight = self.in_flight.lock().await;
+        if in_flight.get(key).is_some_and(|current| Arc::ptr_eq(current, &cell)) {
+            in_flight.remove(key);
+        }
+        result
+    }
+}
+
+/// 先读缓存，未命中时合并并发的相同计算并写入缓存
+///
+/// 读写缓存失败只记录警告，不影响分析结果返回
+///
+/// # 参数
+/// * `cache` - 分析结果缓存
+/// * `single_flight` - 并发合并器
+/// * `key` - 缓存键
+/// * `compute` - 分析计算
+pub async fn get_or_compute<F, Fut>(
+    cache: &dyn ResultCache,
+    single_flight: &SingleFlight,
+    key: &str,
+    compute: F,
+) -> AppResult<AnalyticsResult>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = AppResult<AnalyticsResult>>,
+{
+    match cache.get(key).await {
+        Ok(Some(result)) => return Ok(result),
+        Ok(None) => {}
+        Err(e) => warn!("读取分析缓存失败 ({}): {}", cache.backend_name(), e),
+    }
+
+    single_flight
+        .run(key, || async {
+            let result = compute().await?;
+            if let Err(e) = cache.set(key, &result).await {
+                warn!("写入分析缓存失败 ({}): {}", cache.backend_name(), e);
+            }
+            Ok(result)
+        })
+        .await
+}
+
+/// 分析结果缓存及其并发合并器，由各分析接口共用
+#[derive(Clone)]
+pub struct AnalysisCache {
+    results: Arc<dyn ResultCache>,
+    single_flight: Arc<SingleFlight>,
+}
+
+impl AnalysisCache {
+    /// 创建分析缓存
+    ///
+    /// # 参数
+    /// * `results` - 分析结果缓存（由 `build_result_cache` 按配置创建）
+    pub fn new(results: Arc<dyn ResultCache>) -> Self {
+        Self {
+            results,
+            single_flight: Arc::new(SingleFlight::new()),
+        }
+    }
+
+    /// 先读缓存，未命中时合并并发的相同计算并写入缓存
+    ///
+    /// # 参数
+    /// * `key` - 缓存键
+    /// * `compute` - 分析计算
+    pub async fn get_or_compute<F, Fut>(&self, key: &str, compute: F) -> AppResult<AnalyticsResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<AnalyticsResult>>,
+    {
+        get_or_compute(self.results.as_ref(), &self.single_flight, key, compute).await
+    }
+
+    /// 删除清理范围内的缓存，返回删除数量
+    ///
+    /// # 参数
+    /// * `scope` - 清理范围
+    pub async fn clear(&self, scope: &CacheClearScope) -> AppResult<usize> {
+        scope.validate()?;
+        self.results.remove_matching(&|key: &str| scope.matches(key)).await
+    }
+
+    /// 缓存后端名称
+    pub fn backend_name(&self) -> &'static str {
+        self.results.backend_name()
+    }
+}
+
+/// 分析类型（`?type=` 的取值）及其缓存键前缀
+const ANALYSIS_CACHE_TYPES: &[(&str, &str)] = &[
+    ("report", "thermal_report"),
+    ("compare", "period_comparison"),
+];
+
+/// 手动清理分析缓存的范围
+///
+/// 未指定分析类型时清理全部分析缓存
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CacheClearScope {
+    /// 分析类型，如 `report`
+    #[serde(rename = "type")]
+    pub analysis_type: Option<String>,
+}
+
+impl CacheClearScope {
+    /// 校验分析类型
+    pub fn validate(&self) -> AppResult<()> {
+        let Some(analysis_type) = self.analysis_type.as_deref() else {
+            return Ok(());
+        };
+        if ANALYSIS_CACHE_TYPES.iter().any(|(name, _)| *name == analysis_type) {
+            return Ok(());
+        }
+
+        let supported: Vec<_> = ANALYSIS_CACHE_TYPES.iter().map(|(name, _)| *name).collect();
+        Err(AppError::validation_error(
+            "type",
+            format!("不支持的分析类型 {}，可选: {}", analysis_type, supported.join(", ")),
+        ))
+    }
+
+    /// 缓存键是否在清理范围内
+    ///
+    /// # 参数
+    /// * `key` - 缓存键
+    pub fn matches(&self, key: &str) -> bool {
+        ANALYSIS_CACHE_TYPES.iter().any(|&(name, prefix)| {
+            key.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('_'))
+                && self.analysis_type.as_deref().is_none_or(|analysis_type| analysis_type == name)
+        })
+    }
+
+    /// 清理范围涉及的分析类型
+    pub fn analysis_types(&self) -> Vec<String> {
+        ANALYSIS_CACHE_TYPES
+            .iter()
+            .filter(|(name, _)| self.analysis_type.as_deref().is_none_or(|analysis_type| analysis_type == *name))
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+}
+
+/// 根据配置创建分析结果缓存
+///
+/// # 参数
+/// * `cache_config` - 缓存配置
+/// * `redis_config` - Redis配置
+pub async fn build_result_cache(
+    cache_config: &CacheConfig,
+    redis_config: &RedisConfig,
+) -> AppResult<Arc<dyn ResultCache>> {
+    match cache_config.backend {
+        CacheBackend::Memory => Ok(Arc::new(InMemoryResultCache::new(ANALYSIS_CACHE_TTL_SECS))),
+        #[cfg(feature = "redis-cache")]
+        CacheBackend::Redis => {
+            let cache = RedisResultCache::connect(redis_config, ANALYSIS_CACHE_TTL_SECS).await?;
+            info!("Analytics result cache using redis at {}", redis_config.url);
+            Ok(Arc::new(cache))
+        }
+        #[cfg(not(feature = "redis-cache"))]
+        CacheBackend::Redis => {
+            info!(
+                "Redis cache backend requested for {} but feature `redis-cache` is disabled",
+                redis_config.url
+            );
+            Err(AppError::config_error(
+                "cache.backend = \"redis\" requires the `redis-cache` feature",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::analytics::AnalysisType;
+
+    fn sample_result() -> AnalyticsResult {
+        let now = Utc::now();
+        AnalyticsResult::new(
+            "server-1".to_string(),
+            AnalysisType::TemperatureTrend,
+            now - Duration::hours(1),
+            now,
+            serde_json::json!({"slope": 0.2}),
+            "温度上升".to_string(),
+            vec![],
+            90.0,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_cache_roundtrip() {
+        let cache = InMemoryResultCache::new(ANALYSIS_CACHE_TTL_SECS);
+        let result = sample_result();
+
+        assert!(cache.get("temp_trend_cpu_24").await.unwrap().is_none());
+
+        cache.set("temp_trend_cpu_24", &result).await.unwrap();
+        let cached = cache.get("temp_trend_cpu_24").await.unwrap().unwrap();
+        assert_eq!(cached.id, result.id);
+        assert_eq!(cache.backend_name(), "memory");
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_cache_cleanup_respects_ttl() {
+        let fresh = InMemoryResultCache::new(ANALYSIS_CACHE_TTL_SECS);
+        fresh.set("key", &sample_result()).await.unwrap();
+        assert_eq!(fresh.cleanup().await.unwrap(), 0);
+        assert!(fresh.get("key").await.unwrap().is_some());
+
+        let expired = InMemoryResultCache::new(0);
+        expired.set("key", &sample_result()).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert_eq!(expired.cleanup().await.unwrap(), 1);
+        assert!(expired.get("key").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_identical_analyses_run_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let single_flight = Arc::new(SingleFlight::new());
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..10)
+            .map(|_| {
+                let single_flight = Arc::clone(&single_flight);
+                let runs = Arc::clone(&runs);
+                tokio::spawn(async move {
+                    single_flight
+                        .run("temp_trend_cpu_24", || async {
+                            runs.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                            Ok(sample_result())
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        let mut ids = Vec::new();
+        for task in tasks {
+            ids.push(task.await.unwrap().unwrap().id);
+        }
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(ids.iter().all(|id| *id == ids[0]));
+
+        // 计算完成后不再合并，新的请求重新计算
+        single_flight
+            .run("temp_trend_cpu_24", || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(sample_result())
+            })
+            .await
+            .unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cleared_key_misses_and_recomputes() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = InMemoryResultCache::new(ANALYSIS_CACHE_TTL_SECS);
+        let single_flight = SingleFlight::new();
+        let runs = &AtomicUsize::new(0);
+        let compute = move || async move {
+            runs.fetch_add(1, Ordering::SeqCst);
+            Ok(sample_result())
+        };
+
+        let first = get_or_compute(&cache, &single_flight, "thermal_report_168", compute).await.unwrap();
+        cache.set("thermal_report_24", &sample_result()).await.unwrap();
+        cache.set("period_comparison_1_2_3_4", &sample_result()).await.unwrap();
+
+        // 命中缓存，不重新计算
+        let cached = get_or_compute(&cache, &single_flight, "thermal_report_168", compute).await.unwrap();
+        assert_eq!(cached.id, first.id);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        let scope = CacheClearScope {
+            analysis_type: Some("report".to_string()),
+        };
+        scope.validate().unwrap();
+        assert_eq!(scope.analysis_types(), vec!["report"]);
+        assert_eq!(cache.remove_matching(&|key: &str| scope.matches(key)).await.unwrap(), 2);
+        assert!(cache.get("thermal_report_168").await.unwrap().is_none());
+        assert!(cache.get("period_comparison_1_2_3_4").await.unwrap().is_some());
+
+        let recomputed = get_or_compute(&cache, &single_flight, "thermal_report_168", compute).await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert_ne!(recomputed.id, first.id);
+
+        let unknown = CacheClearScope {
+            analysis_type: Some("temperature_trend".to_string()),
+        };
+        assert!(unknown.validate().is_err());
+    }
+
+    /// 需要可用的Redis（如 `docker run -p 6379:6379 redis`），通过 `REDIS_URL` 指定
+    #[cfg(feature = "redis-cache")]
+    #[tokio::test]
+    #[ignore]
+    async fn test_redis_cache_roundtrip() {
+        let redis_config = RedisConfig {
+            url: std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379/0".to_string()),
+            max_connections: 1,
+            connect_timeout: 5,
+            command_timeout: 5,
+        };
+        let cache = RedisResultCache::connect(&redis_config, 60).await.unwrap();
+        let result = sample_result();
+
+        cache.set("test_roundtrip", &result).await.unwrap();
+        let cached = cache.get("test_roundtrip").await.unwrap().unwrap();
+        assert_eq!(cached.id, result.id);
+        assert_eq!(cache.backend_name(), "redis");
+    }
+}
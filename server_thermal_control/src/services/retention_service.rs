@@ -0,0 +1,302 @@
+use crate::models::error::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, instrument, warn};
+
+/// 单次删除的最大行数，分批删除以避免长时间锁表
+pub const RETENTION_BATCH_SIZE: u64 = 5_000;
+
+/// 自动清理的执行间隔
+const RETENTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 3600);
+
+/// 受保留期限约束的数据表
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionTable {
+    /// 温度数据
+    TemperatureData,
+    /// 风扇数据
+    FanData,
+    /// 监控指标
+    MonitoringMetrics,
+    /// 控制决策
+    ControlDecisions,
+}
+
+impl RetentionTable {
+    /// 所有受保留期限约束的数据表
+    pub const ALL: [RetentionTable; 4] = [
+        RetentionTable::TemperatureData,
+        RetentionTable::FanData,
+        RetentionTable::MonitoringMetrics,
+        RetentionTable::ControlDecisions,
+    ];
+
+    /// 数据库表名
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            RetentionTable::TemperatureData => "temperature_data",
+            RetentionTable::FanData => "fan_data",
+            RetentionTable::MonitoringMetrics => "monitoring_metrics",
+            RetentionTable::ControlDecisions => "control_decisions",
+        }
+    }
+}
+
+/// 过期数据删除存储
+#[async_trait]
+pub trait RetentionStore: Send + Sync {
+    /// 删除一批早于截止时间的数据，返回删除行数
+    ///
+    /// # 参数
+    /// * `table` - 数据表
+    /// * `cutoff` - 截止时间
+    /// * `limit` - 本批最多删除的行数
+    async fn delete_batch(&self, table: RetentionTable, cutoff: DateTime<Utc>, limit: u64) -> AppResult<u64>;
+}
+
+/// 进程内数据存储，仅记录各行的时间戳
+#[derive(Default)]
+pub struct InMemoryRetentionStore {
+    rows: RwLock<HashMap<RetentionTable, Vec<DateTime<Utc>>>>,
+}
+
+impl InMemoryRetentionStore {
+    /// 创建进程内数据存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入一行数据
+    ///
+    /// # 参数
+    /// * `table` - 数据表
+    /// * `timestamp` - 数据时间
+    pub async fn insert(&self, table: RetentionTable, timestamp: DateTime<Utc>) {
+        self.rows.write().await.entry(table).or_default().push(timestamp);
+    }
+
+    /// 获取数据表中的全部行时间戳
+    ///
+    /// # 参数
+    /// * `table` - 数据表
+    pub async fn timestamps(&self, table: RetentionTable) -> Vec<DateTime<Utc>> {
+        self.rows.read().await.get(&table).cloned().unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl RetentionStore for InMemoryRetentionStore {
+    async fn delete_batch(&self, table: RetentionTable, cutoff: DateTime<Utc>, limit: u64) -> AppResult<u64> {
+        let mut rows = self.rows.write().await;
+        let Some(timestamps) = rows.get_mut(&table) else {
+            return Ok(0);
+        };
+
+        let mut deleted = 0;
+        timestamps.retain(|timestamp| {
+            if deleted < limit && *timestamp < cutoff {
+                deleted += 1;
+                false
+            } else {
+                true
+            }
+        });
+        Ok(deleted)
+    }
+}
+
+/// PostgreSQL数据存储
+pub struct PgRetentionStore {
+    pool: PgPool,
+}
+
+impl PgRetentionStore {
+    /// 创建数据库存储
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RetentionStore for PgRetentionStore {
+    #[instrument(level = "debug", name = "db.retention.delete_batch", skip_all, fields(table = table.table_name()))]
+    async fn delete_batch(&self, table: RetentionTable, cutoff: DateTime<Utc>, limit: u64) -> AppResult<u64> {
+        // 表名来自枚举而非用户输入
+        let sql = format!(
+            "DELETE FROM {table} WHERE id IN (SELECT id FROM {table} WHERE timestamp < $1 LIMIT $2)",
+            table = table.table_name()
+        );
+        let result = sqlx::query(&sql)
+            .bind(cutoff)
+            .bind(limit as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// 一次数据清理的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    /// 保留天数
+    pub retention_days: u32,
+    /// 截止时间，早于该时间的数据被删除
+    pub cutoff: DateTime<Utc>,
+    /// 各数据表删除的行数
+    pub deleted: BTreeMap<String, u64>,
+    /// 删除总行数
+    pub total_deleted: u64,
+    /// 执行耗时（毫秒）
+    pub duration_ms: u64,
+}
+
+/// 数据保留服务
+///
+/// 按 `monitoring.retention_days` 删除过期的历史数据
+#[derive(Clone)]
+pub struct RetentionService {
+    /// 数据存储
+    store: Arc<dyn RetentionStore>,
+    /// 保留天数
+    retention_days: u32,
+    /// 单批删除行数
+    batch_size: u64,
+    /// 保证同一时间只有一次清理在执行
+    running: Arc<Mutex<()>>,
+}
+
+impl RetentionService {
+    /// 创建数据保留服务
+    ///
+    /// # 参数
+    /// * `store` - 数据存储
+    /// * `retention_days` - 保留天数
+    pub fn new(store: Arc<dyn RetentionStore>, retention_days: u32) -> Self {
+        Self {
+            store,
+            retention_days,
+            batch_size: RETENTION_BATCH_SIZE,
+            running: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// 设置单批删除行数
+    ///
+    /// # 参数
+    /// * `batch_size` - 单批删除行数
+    pub fn with_batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// 立即执行一次清理
+    pub async fn run(&self) -> AppResult<RetentionReport> {
+        self.run_at(Utc::now()).await
+    }
+
+    /// 以指定时间为基准执行一次清理
+    ///
+    /// # 参数
+    /// * `now` - 基准时间
+    pub async fn run_at(&self, now: DateTime<Utc>) -> AppResult<RetentionReport> {
+        if self.retention_days == 0 {
+            return Err(AppError::validation_error("retention_days", "数据保留天数必须大于0"));
+        }
+
+        let _guard = self.running.lock().await;
+        let started = std::time::Instant::now();
+        let cutoff = now - Duration::days(self.retention_days as i64);
+
+        let mut deleted = BTreeMap::new();
+        for table in RetentionTable::ALL {
+            let mut table_deleted = 0;
+            loop {
+                let batch = self.store.delete_batch(table, cutoff, self.batch_size).await?;
+                table_deleted += batch;
+                if batch < self.batch_size {
+                    break;
+                }
+                // 批次之间让出执行权，避免长时间占用连接
+                tokio::task::yield_now().await;
+            }
+            deleted.insert(table.table_name().to_string(), table_deleted);
+        }
+
+        let report = RetentionReport {
+            retention_days: self.retention_days,
+            cutoff,
+            total_deleted: deleted.values().sum(),
+            deleted,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        info!(
+            "数据清理完成，删除 {} 行早于 {} 的数据: {:?}",
+            report.total_deleted, report.cutoff, report.deleted
+        );
+        Ok(report)
+    }
+
+    /// 启动每日自动清理任务
+    pub fn start_retention_task(&self) -> tokio::task::JoinHandle<()> {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.run().await {
+                    warn!("清理过期数据失败: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_only_rows_older_than_retention_are_purged() {
+        let store = Arc::new(InMemoryRetentionStore::new());
+        let now = Utc::now();
+        let old = now - Duration::days(31);
+        let recent = now - Duration::days(29);
+
+        for _ in 0..5 {
+            store.insert(RetentionTable::TemperatureData, old).await;
+        }
+        store.insert(RetentionTable::TemperatureData, recent).await;
+        store.insert(RetentionTable::FanData, old).await;
+        store.insert(RetentionTable::FanData, recent).await;
+        store.insert(RetentionTable::MonitoringMetrics, recent).await;
+
+        // 小批次验证分批删除最终删完全部过期数据
+        let service = RetentionService::new(store.clone(), 30).with_batch_size(2);
+        let report = service.run_at(now).await.unwrap();
+
+        assert_eq!(report.deleted["temperature_data"], 5);
+        assert_eq!(report.deleted["fan_data"], 1);
+        assert_eq!(report.deleted["monitoring_metrics"], 0);
+        assert_eq!(report.total_deleted, 6);
+        for table in RetentionTable::ALL {
+            assert!(store.timestamps(table).await.iter().all(|timestamp| *timestamp == recent));
+        }
+        assert_eq!(store.timestamps(RetentionTable::TemperatureData).await.len(), 1);
+
+        // 保留天数为0时拒绝执行，避免清空全部数据
+        let err = RetentionService::new(store, 0).run().await.unwrap_err();
+        assert_eq!(err.status_code(), 400);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::models::error::AppResult;
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+/// 告警规则启用状态存储
+///
+/// 只持久化启停状态，服务重启后在默认规则之上恢复
+#[async_trait]
+pub trait AlertRuleStateStore: Send + Sync {
+    /// 读取所有已保存的规则启用状态
+    async fn load(&self) -> AppResult<HashMap<String, bool>>;
+
+    /// 保存规则启用状态
+    ///
+    /// # 参数
+    /// * `rule_id` - 规则ID
+    /// * `enabled` - 是否启用
+    async fn set_enabled(&self, rule_id: &str, enabled: bool) -> AppResult<()>;
+}
+
+/// 进程内规则状态存储
+#[derive(Default)]
+pub struct InMemoryAlertRuleStateStore {
+    states: RwLock<HashMap<String, bool>>,
+}
+
+impl InMemoryAlertRuleStateStore {
+    /// 创建进程内规则状态存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AlertRuleStateStore for InMemoryAlertRuleStateStore {
+    async fn load(&self) -> AppResult<HashMap<String, bool>> {
+        Ok(self.states.read().await.clone())
+    }
+
+    async fn set_enabled(&self, rule_id: &str, enabled: bool) -> AppResult<()> {
+        self.states.write().await.insert(rule_id.to_string(), enabled);
+        Ok(())
+    }
+}
+
+/// PostgreSQL规则状态存储
+///
+/// 写入 `scripts/init.sql` 中定义的 `alert_rule_states` 表
+pub struct PgAlertRuleStateStore {
+    pool: PgPool,
+}
+
+impl PgAlertRuleStateStore {
+    /// 创建数据库规则状态存储
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AlertRuleStateStore for PgAlertRuleStateStore {
+    #[instrument(level = "debug", name = "db.alert_rule_states.load", skip_all)]
+    async fn load(&self) -> AppResult<HashMap<String, bool>> {
+        let rows = sqlx::query("SELECT rule_id, enabled FROM alert_rule_states")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| -> AppResult<(String, bool)> {
+                Ok((row.try_get("rule_id")?, row.try_get("enabled")?))
+            })
+            .collect()
+    }
+
+    #[instrument(level = "debug", name = "db.alert_rule_states.set_enabled", skip_all)]
+    async fn set_enabled(&self, rule_id: &str, enabled: bool) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO alert_rule_states (rule_id, enabled, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (rule_id) DO UPDATE SET
+                enabled = EXCLUDED.enabled,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(rule_id)
+        .bind(enabled)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use crate::models::error::AppResult;
+use crate::services::sensor_source::SensorSource;
+use std::future::Future;
+use tracing::{error, info};
+
+/// 启动保护转速的默认值（百分比）
+pub const DEFAULT_SAFE_BOOT_FAN_PERCENT: u8 = 40;
+
+/// 表示全部风扇的风扇ID，生成的raw命令以 `0xff` 作用于所有风扇
+pub(crate) const ALL_FANS: &str = "all";
+
+/// 启动保护所需的风扇操作
+pub trait SafeBootFans: Send + Sync {
+    /// 将所有风扇设置为指定转速
+    ///
+    /// # 参数
+    /// * `speed_percent` - 转速百分比
+    fn set_all_fan_speeds(&self, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// 将风扇控制交还BMC自动模式
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+impl<S: SensorSource + ?Sized> SafeBootFans for S {
+    fn set_all_fan_speeds(&self, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+        self.set_fan_speed(ALL_FANS, speed_percent)
+    }
+
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+        SensorSource::restore_automatic_fan_control(self)
+    }
+}
+
+/// 下发启动保护转速
+///
+/// 上次运行若在手动模式下崩溃，风扇会停留在BMC最后收到的转速。启动时先将所有风扇
+/// 拉到保护转速，再交还BMC自动模式，由BMC按温度调节直到控制循环接管。
+/// 失败只记录日志，不阻止服务启动
+///
+/// # 参数
+/// * `fans` - 风扇操作
+/// * `floor_percent` - 保护转速百分比
+pub fn apply_safe_floor(fans: &dyn SafeBootFans, floor_percent: u8) {
+    match fans.set_all_fan_speeds(floor_percent) {
+        Ok(()) => info!("Safe-boot fan floor applied: {}%", floor_percent),
+        Err(e) => error!("Failed to apply safe-boot fan floor {}%: {}", floor_percent, e),
+    }
+
+    if let Err(e) = fans.restore_automatic_fan_control() {
+        error!("Failed to restore automatic fan control after safe boot: {}", e);
+    }
+}
+
+/// 启动保护流程：先下发保护转速，监控就绪后才启动控制循环
+///
+/// # 参数
+/// * `fans` - 风扇操作
+/// * `floor_percent` - 保护转速百分比
+/// * `monitoring_live` - 启动监控并在其开始产生数据后完成
+/// * `start_control_loop` - 启动控制循环
+pub async fn run<M, L>(
+    fans: &dyn SafeBootFans,
+    floor_percent: u8,
+    monitoring_live: M,
+    start_control_loop: L,
+) -> AppResult<()>
+where
+    M: Future<Output = AppResult<()>>,
+    L: Future<Output = AppResult<()>>,
+{
+    apply_safe_floor(fans, floor_percent);
+
+    monitoring_live.await?;
+    info!("Monitoring is live, handing fan control to the control loop");
+    start_control_loop.await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::error::AppError;
+    use std::sync::{Arc, Mutex};
+
+    /// 记录风扇操作及启动步骤的顺序
+    #[derive(Default)]
+    struct RecordingFans {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl SafeBootFans for RecordingFans {
+        fn set_all_fan_speeds(&self, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+            self.events.lock().unwrap().push(format!("floor {}", speed_percent));
+            Ok(())
+        }
+
+        fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+            self.events.lock().unwrap().push("automatic".to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_safe_floor_is_issued_before_control_loop_starts() {
+        let fans = RecordingFans::default();
+        let events = Arc::clone(&fans.events);
+
+        let monitoring_events = Arc::clone(&events);
+        let loop_events = Arc::clone(&events);
+        run(
+            &fans,
+            DEFAULT_SAFE_BOOT_FAN_PERCENT,
+            async move {
+                monitoring_events.lock().unwrap().push("monitoring live".to_string());
+                Ok(())
+            },
+            async move {
+                loop_events.lock().unwrap().push("control loop".to_string());
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["floor 40", "automatic", "monitoring live", "control loop"]
+        );
+
+        // 监控未能就绪时不启动控制循环，风扇保持在保护状态
+        let fans = RecordingFans::default();
+        let events = Arc::clone(&fans.events);
+        let loop_events = Arc::clone(&events);
+        let result = run(
+            &fans,
+            DEFAULT_SAFE_BOOT_FAN_PERCENT,
+            async { Err(AppError::internal_server_error("monitoring failed")) },
+            async move {
+                loop_events.lock().unwrap().push("control loop".to_string());
+                Ok(())
+            },
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(*events.lock().unwrap(), vec!["floor 40", "automatic"]);
+    }
+}
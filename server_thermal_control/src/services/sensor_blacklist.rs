@@ -0,0 +1,60 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, RwLock};
+
+/// 传感器黑名单
+///
+/// 部分BMC会暴露不存在或永久故障的传感器，列入黑名单的传感器不参与轮询、统计、
+/// 告警和控制输入聚合，但仍在传感器列表中以 `excluded` 标记列出
+#[derive(Debug, Clone, Default)]
+pub struct SensorBlacklist {
+    sensor_ids: Arc<RwLock<BTreeSet<String>>>,
+}
+
+impl SensorBlacklist {
+    /// 创建传感器黑名单
+    ///
+    /// # 参数
+    /// * `sensor_ids` - 排除的传感器ID，通常来自 `monitoring.sensor_blacklist`
+    pub fn new(sensor_ids: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            sensor_ids: Arc::new(RwLock::new(Self::normalize(sensor_ids))),
+        }
+    }
+
+    /// 传感器是否被排除
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    pub fn contains(&self, sensor_id: &str) -> bool {
+        self.sensor_ids
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(sensor_id)
+    }
+
+    /// 当前排除的传感器ID（按字典序）
+    pub fn sensor_ids(&self) -> Vec<String> {
+        self.sensor_ids
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// 替换黑名单
+    ///
+    /// # 参数
+    /// * `sensor_ids` - 新的排除传感器ID，空白项被忽略
+    pub fn replace(&self, sensor_ids: impl IntoIterator<Item = String>) {
+        *self.sensor_ids.write().unwrap_or_else(|e| e.into_inner()) = Self::normalize(sensor_ids);
+    }
+
+    fn normalize(sensor_ids: impl IntoIterator<Item = String>) -> BTreeSet<String> {
+        sensor_ids
+            .into_iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect()
+    }
+}
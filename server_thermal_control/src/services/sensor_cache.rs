@@ -0,0 +1,132 @@
+use crate::services::ipmi_service::TemperatureSensor;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::warn;
+
+/// 最后一次成功读数的默认保留时长（秒）
+pub const DEFAULT_LAST_GOOD_MAX_AGE_SECS: u64 = 300;
+
+/// 带新鲜度标记的温度读数
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedTemperature {
+    #[serde(flatten)]
+    pub sensor: TemperatureSensor,
+    /// 本次读取失败，使用的是最后一次成功读数
+    pub stale: bool,
+    /// 距最后一次成功读取的秒数
+    pub age_secs: i64,
+}
+
+impl Borrow<TemperatureSensor> for CachedTemperature {
+    fn borrow(&self) -> &TemperatureSensor {
+        &self.sensor
+    }
+}
+
+/// 传感器最后一次成功读数缓存
+///
+/// 每次成功轮询后按传感器记录读数；轮询失败或某个传感器本次缺失时，
+/// 返回保留时长内的最后一次成功读数并标记为 `stale`。
+/// 只供查询和健康检查使用，控制循环仍直接读取BMC，不会拿过期读数调速
+#[derive(Debug, Clone)]
+pub struct LastGoodSensorCache {
+    max_age: Duration,
+    readings: Arc<RwLock<HashMap<String, LastGoodReading>>>,
+}
+
+/// 单个传感器最后一次成功读数及读取时间
+#[derive(Debug, Clone)]
+struct LastGoodReading {
+    sensor: TemperatureSensor,
+    read_at: DateTime<Utc>,
+}
+
+impl LastGoodSensorCache {
+    /// 创建缓存
+    ///
+    /// # 参数
+    /// * `max_age_secs` - 最后一次成功读数的保留时长（秒），为0时不回退
+    pub fn new(max_age_secs: u64) -> Self {
+        Self {
+            max_age: Duration::seconds(max_age_secs as i64),
+            readings: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 读取传感器，成功时更新缓存，失败时回退到最后一次成功读数
+    ///
+    /// 没有可回退的读数时返回原错误
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    /// * `fetch` - 读取全部温度传感器
+    pub fn read_through<E: std::fmt::Display>(
+        &self,
+        now: DateTime<Utc>,
+        fetch: impl FnOnce() -> Result<Vec<TemperatureSensor>, E>,
+    ) -> Result<Vec<CachedTemperature>, E> {
+        match fetch() {
+            Ok(sensors) => {
+                let mut readings = self.readings.write().unwrap_or_else(|e| e.into_inner());
+                for sensor in &sensors {
+                    let reading = LastGoodReading {
+                        sensor: sensor.clone(),
+                        read_at: now,
+                    };
+                    readings.insert(sensor.sensor_id.clone(), reading);
+                }
+                drop(readings);
+
+                let mut fresh: Vec<CachedTemperature> = sensors
+                    .into_iter()
+                    .map(|sensor| CachedTemperature {
+                        sensor,
+                        stale: false,
+                        age_secs: 0,
+                    })
+                    .collect();
+                // 本次缺失的传感器补上最后一次成功读数
+                let missing: Vec<_> = self
+                    .last_good(now)
+                    .into_iter()
+                    .filter(|cached| !fresh.iter().any(|f| f.sensor.sensor_id == cached.sensor.sensor_id))
+                    .collect();
+                fresh.extend(missing);
+                Ok(fresh)
+            }
+            Err(e) => {
+                let cached = self.last_good(now);
+                if cached.is_empty() {
+                    return Err(e);
+                }
+                warn!("读取温度传感器失败，返回 {} 个传感器的最后一次成功读数: {}", cached.len(), e);
+                Ok(cached)
+            }
+        }
+    }
+
+    /// 保留时长内的最后一次成功读数，均标记为过期，按传感器ID排序
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn last_good(&self, now: DateTime<Utc>) -> Vec<CachedTemperature> {
+        if self.max_age <= Duration::zero() {
+            return Vec::new();
+        }
+        let readings = self.readings.read().unwrap_or_else(|e| e.into_inner());
+        let mut cached: Vec<CachedTemperature> = readings
+            .values()
+            .filter(|reading| now - reading.read_at <= self.max_age)
+            .map(|reading| CachedTemperature {
+                sensor: reading.sensor.clone(),
+                stale: true,
+                age_secs: (now - reading.read_at).num_seconds(),
+            })
+            .collect();
+        cached.sort_by(|a, b| a.sensor.sensor_id.cmp(&b.sensor.sensor_id));
+        cached
+    }
+}
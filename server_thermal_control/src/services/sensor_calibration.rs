@@ -0,0 +1,257 @@
+use crate::models::error::{AppError, AppResult};
+use crate::models::sensor::SensorCalibration;
+use crate::services::ipmi_service::{
+    BmcSelfTestStatus, FanSensor, Ipmi, ReadOnlyIpmi, ReadOnlyIpmiCommand, SystemInfo, TemperatureSensor,
+};
+use crate::services::sensor_source::SensorSource;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+
+/// 校准偏移量的上限（°C）
+const MAX_OFFSET: f64 = 50.0;
+/// 校准缩放因子的上限
+const MAX_SCALE_FACTOR: f64 = 10.0;
+
+/// 按传感器设置的温度校准参数
+///
+/// 校准值为 `原始读数 × scale_factor + offset`。参数保存在 `monitoring.sensor_calibration_file` 中，
+/// 文件不存在时不校准任何传感器
+#[derive(Clone, Default)]
+pub struct SensorCalibrations {
+    calibrations: Arc<RwLock<BTreeMap<String, SensorCalibration>>>,
+    file_path: Option<PathBuf>,
+    update: Arc<Mutex<()>>,
+}
+
+impl SensorCalibrations {
+    /// 从校准文件加载，路径为空时不持久化
+    ///
+    /// # 参数
+    /// * `file_path` - 校准文件路径
+    pub fn load(file_path: &str) -> AppResult<Self> {
+        let mut calibrations = Self::default();
+        if file_path.is_empty() {
+            return Ok(calibrations);
+        }
+
+        let path = PathBuf::from(file_path);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                let saved: BTreeMap<String, SensorCalibration> = serde_json::from_str(&content).map_err(|e| {
+                    AppError::file_system_error(format!("解析传感器校准文件 {} 失败: {}", path.display(), e))
+                })?;
+                for (sensor_id, calibration) in &saved {
+                    validate(sensor_id, calibration)?;
+                }
+                *calibrations.calibrations.write().unwrap_or_else(|e| e.into_inner()) = saved;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(AppError::file_system_error(format!(
+                    "读取传感器校准文件 {} 失败: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        }
+        calibrations.file_path = Some(path);
+        Ok(calibrations)
+    }
+
+    /// 全部校准参数，按传感器ID排序
+    pub fn list(&self) -> BTreeMap<String, SensorCalibration> {
+        self.calibrations.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 设置一个传感器的校准参数，写入校准文件成功后才生效
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    /// * `calibration` - 校准参数
+    pub async fn set(&self, sensor_id: &str, calibration: SensorCalibration) -> AppResult<SensorCalibration> {
+        validate(sensor_id, &calibration)?;
+        let mut calibrations = self.list();
+        calibrations.insert(sensor_id.to_string(), calibration.clone());
+        self.replace(calibrations).await?;
+        Ok(calibration)
+    }
+
+    /// 删除一个传感器的校准参数，未设置时返回None
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    pub async fn remove(&self, sensor_id: &str) -> AppResult<Option<SensorCalibration>> {
+        let mut calibrations = self.list();
+        let removed = calibrations.remove(sensor_id);
+        if removed.is_some() {
+            self.replace(calibrations).await?;
+        }
+        Ok(removed)
+    }
+
+    /// 按校准参数换算温度读数，未设置校准的传感器保持原值
+    ///
+    /// # 参数
+    /// * `sensors` - 温度读数
+    pub fn apply(&self, sensors: &mut [TemperatureSensor]) {
+        let calibrations = self.calibrations.read().unwrap_or_else(|e| e.into_inner());
+        if calibrations.is_empty() {
+            return;
+        }
+        for sensor in sensors {
+            if let Some(calibration) = calibrations.get(&sensor.sensor_id) {
+                sensor.temperature = calibration.apply(sensor.temperature);
+            }
+        }
+    }
+
+    async fn replace(&self, calibrations: BTreeMap<String, SensorCalibration>) -> AppResult<()> {
+        let _update = self.update.lock().await;
+        if let Some(path) = &self.file_path {
+            save_calibrations(path, &calibrations).await?;
+        }
+        *self.calibrations.write().unwrap_or_else(|e| e.into_inner()) = calibrations;
+        Ok(())
+    }
+}
+
+fn validate(sensor_id: &str, calibration: &SensorCalibration) -> AppResult<()> {
+    if sensor_id.trim().is_empty() {
+        return Err(AppError::validation_error("sensor_id", "传感器ID不能为空"));
+    }
+    if !calibration.offset.is_finite() || calibration.offset.abs() > MAX_OFFSET {
+        return Err(AppError::validation_error(
+            "offset",
+            format!("{} 的偏移量必须在±{}°C之间", sensor_id, MAX_OFFSET),
+        ));
+    }
+    if !(calibration.scale_factor > 0.0 && calibration.scale_factor <= MAX_SCALE_FACTOR) {
+        return Err(AppError::validation_error(
+            "scale_factor",
+            format!("{} 的缩放因子必须大于0且不超过{}", sensor_id, MAX_SCALE_FACTOR),
+        ));
+    }
+    Ok(())
+}
+
+/// 写入校准文件，先写临时文件再替换，避免中途失败留下损坏的文件
+async fn save_calibrations(path: &Path, calibrations: &BTreeMap<String, SensorCalibration>) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(calibrations)?;
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::file_system_error(format!("创建传感器校准目录失败: {}", e)))?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, content)
+        .await
+        .map_err(|e| AppError::file_system_error(format!("写入传感器校准文件失败: {}", e)))?;
+    tokio::fs::rename(&tmp_path, path)
+        .await
+        .map_err(|e| AppError::file_system_error(format!("替换传感器校准文件失败: {}", e)))
+}
+
+/// 读取温度时应用校准参数的IPMI操作
+///
+/// 监控、告警、控制循环和接口都通过它读取温度，看到的都是校准值；原始读数从内层IPMI读取
+#[derive(Clone)]
+pub struct CalibratedIpmi {
+    inner: Arc<dyn Ipmi>,
+    calibrations: SensorCalibrations,
+}
+
+impl CalibratedIpmi {
+    /// 创建校准后的IPMI操作
+    ///
+    /// # 参数
+    /// * `inner` - 返回原始读数的IPMI操作
+    /// * `calibrations` - 传感器校准参数
+    pub fn new(inner: Arc<dyn Ipmi>, calibrations: SensorCalibrations) -> Self {
+        Self { inner, calibrations }
+    }
+}
+
+impl ReadOnlyIpmi for CalibratedIpmi {
+    fn run_read_only(&self, command: ReadOnlyIpmiCommand) -> Result<String, Box<dyn std::error::Error>> {
+        self.inner.run_read_only(command)
+    }
+}
+
+impl Ipmi for CalibratedIpmi {
+    fn get_system_info(&self) -> Result<SystemInfo, Box<dyn std::error::Error>> {
+        self.inner.get_system_info()
+    }
+
+    fn get_self_test_status(&self) -> Result<BmcSelfTestStatus, Box<dyn std::error::Error>> {
+        self.inner.get_self_test_status()
+    }
+}
+
+impl SensorSource for CalibratedIpmi {
+    fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+        let mut sensors = self.inner.get_temperature_sensors()?;
+        self.calibrations.apply(&mut sensors);
+        Ok(sensors)
+    }
+
+    fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
+        self.inner.get_fan_sensors()
+    }
+
+    fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+        self.inner.set_fan_speed(fan_id, speed_percent)
+    }
+
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.inner.restore_automatic_fan_control()
+    }
+
+    fn test_connection(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        self.inner.test_connection()
+    }
+
+    fn is_fan_controllable(&self, fan_id: &str) -> Option<bool> {
+        self.inner.is_fan_controllable(fan_id)
+    }
+
+    fn get_temperature_by_sensor(
+        &self,
+        sensor_id: &str,
+    ) -> Result<Option<TemperatureSensor>, Box<dyn std::error::Error>> {
+        let mut sensor = self.inner.get_temperature_by_sensor(sensor_id)?;
+        self.calibrations.apply(sensor.as_mut_slice());
+        Ok(sensor)
+    }
+
+    fn get_fan_by_id(&self, fan_id: &str) -> Result<Option<FanSensor>, Box<dyn std::error::Error>> {
+        self.inner.get_fan_by_id(fan_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_calibrations_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sensor_calibration.json").display().to_string();
+        let calibrations = SensorCalibrations::load(&path).unwrap();
+        calibrations
+            .set("CPU1_TEMP", SensorCalibration::new(-1.5, Some(1.02), Some(50.0)))
+            .await
+            .unwrap();
+        assert!(calibrations.set("CPU2_TEMP", SensorCalibration::new(0.0, Some(0.0), None)).await.is_err());
+
+        let reloaded = SensorCalibrations::load(&path).unwrap();
+        let saved = reloaded.list();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved["CPU1_TEMP"].offset, -1.5);
+
+        assert!(reloaded.remove("CPU1_TEMP").await.unwrap().is_some());
+        assert!(SensorCalibrations::load(&path).unwrap().list().is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// 传感器回放配置，`ipmi.interface = "replay"` 时生效
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    /// 录制的传感器轨迹文件，`.csv` 按CSV解析，其余按JSON数组解析
+    #[serde(default)]
+    pub trace_path: String,
+    /// 回放倍速，2.0表示以两倍速度回放
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    /// 回放到结尾后是否从头循环，否则停留在最后的读数
+    #[serde(default)]
+    pub loop_trace: bool,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            trace_path: String::new(),
+            speed: default_speed(),
+            loop_trace: false,
+        }
+    }
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+/// 读数时间：录制时间或相对轨迹开始的秒数
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum TraceTimestamp {
+    /// 相对轨迹开始的秒数
+    Seconds(f64),
+    /// 录制时间
+    Time(DateTime<Utc>),
+}
+
+impl TraceTimestamp {
+    fn parse(value: &str) -> Option<Self> {
+        value
+            .parse::<f64>()
+            .ok()
+            .map(Self::Seconds)
+            .or_else(|| value.parse::<DateTime<Utc>>().ok().map(Self::Time))
+    }
+}
+
+/// 轨迹中的一条读数
+#[derive(Debug, Clone, Deserialize)]
+pub struct TraceReading {
+    /// 读数时间
+    pub timestamp: TraceTimestamp,
+    /// 传感器名称，与 `ipmitool sdr` 中的名称一致，如 `CPU1 Temp`、`Fan1`
+    pub sensor: String,
+    /// 实体ID与实例，如 `3.1`
+    #[serde(default)]
+    pub entity: String,
+    /// 读数值
+    pub value: f64,
+    /// 单位，如 `degrees C`、`RPM`
+    pub unit: String,
+    /// 传感器状态
+    #[serde(default = "default_status")]
+    pub status: String,
+}
+
+fn default_status() -> String {
+    "ok".to_string()
+}
+
+/// 按时间排序的传感器轨迹
+#[derive(Debug, Clone)]
+pub struct SensorTrace {
+    /// `(相对开始的时间, 读数)`
+    readings: Vec<(Duration, TraceReading)>,
+}
+
+impl SensorTrace {
+    /// 从读数创建轨迹，时间统一换算为相对最早读数的偏移
+    ///
+    /// # 参数
+    /// * `readings` - 轨迹读数，录制时间与相对秒数不能混用
+    pub fn new(readings: Vec<TraceReading>) -> Result<Self, String> {
+        if readings.is_empty() {
+            return Err("传感器轨迹为空".to_string());
+        }
+
+        let mixed = readings
+            .windows(2)
+            .any(|pair| matches!(pair[0].timestamp, TraceTimestamp::Seconds(_)) != matches!(pair[1].timestamp, TraceTimestamp::Seconds(_)));
+        if mixed {
+            return Err("传感器轨迹中录制时间与相对秒数不能混用".to_string());
+        }
+
+        let seconds = |timestamp: &TraceTimestamp| match timestamp {
+            TraceTimestamp::Seconds(secs) => *secs,
+            TraceTimestamp::Time(time) => time.timestamp_millis() as f64 / 1000.0,
+        };
+        let start = readings
+            .iter()
+            .map(|reading| seconds(&reading.timestamp))
+            .fold(f64::INFINITY, f64::min);
+        let mut readings: Vec<(Duration, TraceReading)> = readings
+            .into_iter()
+            .map(|reading| (Duration::from_secs_f64(seconds(&reading.timestamp) - start), reading))
+            .collect();
+        readings.sort_by_key(|(offset, _)| *offset);
+        Ok(Self { readings })
+    }
+
+    /// 从文件加载轨迹
+    ///
+    /// CSV首行为列名，需包含 `timestamp`、`sensor`、`value`、`unit`，可选 `entity`、`status`
+    ///
+    /// # 参数
+    /// * `path` - 轨迹文件路径
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| format!("读取传感器轨迹 {} 失败: {}", path.display(), e))?;
+        let is_csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let readings = if is_csv {
+            parse_csv(&content)?
+        } else {
+            serde_json::from_str(&content).map_err(|e| format!("解析传感器轨迹 {} 失败: {}", path.display(), e))?
+        };
+        Self::new(readings)
+    }
+
+    /// 轨迹时长
+    pub fn duration(&self) -> Duration {
+        self.readings.last().map(|(offset, _)| *offset).unwrap_or_default()
+    }
+
+    /// 各传感器在指定时刻的最新读数，按传感器首次出现的顺序排列；尚未出现的传感器不包含在内
+    ///
+    /// # 参数
+    /// * `position` - 相对轨迹开始的时间
+    pub fn frame_at(&self, position: Duration) -> Vec<&TraceReading> {
+        let mut frame: Vec<&TraceReading> = Vec::new();
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        for (_, reading) in self.readings.iter().take_while(|(offset, _)| *offset <= position) {
+            match index.get(reading.sensor.as_str()) {
+                Some(&i) => frame[i] = reading,
+                None => {
+                    index.insert(&reading.sensor, frame.len());
+                    frame.push(reading);
+                }
+            }
+        }
+        frame
+    }
+}
+
+/// 解析CSV格式的轨迹
+fn parse_csv(content: &str) -> Result<Vec<TraceReading>, String> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = lines
+        .next()
+        .ok_or("传感器轨迹为空")?
+        .split(',')
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let required = |name: &str| column(name).ok_or_else(|| format!("传感器轨迹缺少 {} 列", name));
+    let (timestamp, sensor, value, unit) = (required("timestamp")?, required("sensor")?, required("value")?, required("unit")?);
+    let (entity, status) = (column("entity"), column("status"));
+
+    lines
+        .enumerate()
+        .map(|(line_number, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let field = |index: usize| fields.get(index).copied().unwrap_or_default();
+            let invalid = |name: &str| format!("传感器轨迹第 {} 行的 {} 无效: {}", line_number + 2, name, line);
+            Ok(TraceReading {
+                timestamp: TraceTimestamp::parse(field(timestamp)).ok_or_else(|| invalid("timestamp"))?,
+                sensor: field(sensor).to_string(),
+                entity: entity.map(field).unwrap_or_default().to_string(),
+                value: field(value).parse().map_err(|_| invalid("value"))?,
+                unit: field(unit).to_string(),
+                status: status.map(field).filter(|s| !s.is_empty()).unwrap_or("ok").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 回放录制的传感器轨迹，代替ipmitool响应IPMI命令
+///
+/// `sdr` 命令按当前回放位置输出 `sdr elist full` 格式的读数，交给与真实BMC相同的解析与
+/// 告警、控制流程；调速等写命令只记录不执行
+pub struct SensorReplay {
+    trace: SensorTrace,
+    speed: f64,
+    loop_trace: bool,
+    /// `(计时起点, 起点对应的回放位置)`
+    clock: Mutex<(Instant, Duration)>,
+}
+
+impl SensorReplay {
+    /// 创建回放，从轨迹开始处回放
+    ///
+    /// # 参数
+    /// * `trace` - 传感器轨迹
+    /// * `config` - 回放配置
+    pub fn new(trace: SensorTrace, config: &ReplayConfig) -> Result<Self, String> {
+        if !config.speed.is_finite() || config.speed <= 0.0 {
+            return Err("ipmi.replay.speed 必须大于0".to_string());
+        }
+        Ok(Self {
+            trace,
+            speed: config.speed,
+            loop_trace: config.loop_trace,
+            clock: Mutex::new((Instant::now(), Duration::ZERO)),
+        })
+    }
+
+    /// 按配置加载轨迹文件并创建回放
+    ///
+    /// # 参数
+    /// * `config` - 回放配置
+    pub fn from_config(config: &ReplayConfig) -> Result<Self, String> {
+        if config.trace_path.is_empty() {
+            return Err("ipmi.interface = \"replay\" 需要配置 ipmi.replay.trace_path".to_string());
+        }
+        Self::new(SensorTrace::load(&config.trace_path)?, config)
+    }
+
+    /// 跳转到轨迹中的指定位置，之后按倍速继续回放
+    ///
+    /// # 参数
+    /// * `position` - 相对轨迹开始的时间
+    pub fn seek(&self, position: Duration) {
+        *self.clock.lock().unwrap_or_else(|e| e.into_inner()) = (Instant::now(), position);
+    }
+
+    /// 当前回放位置
+    pub fn position(&self) -> Duration {
+        let (started, base) = *self.clock.lock().unwrap_or_else(|e| e.into_inner());
+        let position = base + started.elapsed().mul_f64(self.speed);
+        let duration = self.trace.duration();
+        if self.loop_trace && !duration.is_zero() {
+            Duration::from_nanos((position.as_nanos() % (duration.as_nanos() + 1)) as u64)
+        } else {
+            position
+        }
+    }
+
+    /// 响应一条ipmitool命令
+    ///
+    /// # 参数
+    /// * `args` - ipmitool子命令参数
+    pub fn execute(&self, args: &[&str]) -> String {
+        match args {
+            ["sdr", ..] => self.sdr_output(),
+            ["mc", "info"] => "Manufacturer Name         : Sensor Replay\nProduct Name              : Sensor Replay\n".to_string(),
+            ["mc", "selftest"] => "Selftest: passed\n".to_string(),
+            _ => {
+                debug!("传感器回放忽略命令: {}", args.join(" "));
+                String::new()
+            }
+        }
+    }
+
+    /// 当前位置的读数，格式与 `ipmitool sdr elist full` 一致
+    fn sdr_output(&self) -> String {
+        self.trace
+            .frame_at(self.position())
+            .iter()
+            .enumerate()
+            .map(|(index, reading)| {
+                format!(
+                    "{} | {:02X}h | {} | {} | {} {}\n",
+                    reading.sensor, index + 1, reading.status, reading.entity, reading.value, reading.unit
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::alert_preview::preview_alerts;
+    use crate::services::alert_thresholds::{AlertThresholdStore, SensorAlertThreshold, ThresholdLevel};
+    use crate::services::ipmi_service::{IpmiConfig, IpmiService};
+    use crate::services::sensor_blacklist::SensorBlacklist;
+    use crate::services::sensor_cache::CachedTemperature;
+    use std::sync::Arc;
+
+    const SPIKE_TRACE: &str = "\
+timestamp,sensor,entity,value,unit
+0,CPU1 Temp,3.1,52,degrees C
+0,Fan1,29.1,4800,RPM
+30,CPU1 Temp,3.1,58,degrees C
+60,CPU1 Temp,3.1,91,degrees C
+60,Fan1,29.1,9600,RPM
+90,CPU1 Temp,3.1,63,degrees C
+";
+
+    /// 以回放代替BMC的IPMI服务，读数经过与真实BMC相同的SDR解析后预览告警
+    fn cpu_alert_level(ipmi: &IpmiService) -> ThresholdLevel {
+        let readings: Vec<CachedTemperature> = ipmi
+            .get_temperature_sensors()
+            .unwrap()
+            .into_iter()
+            .map(|sensor| CachedTemperature { sensor, stale: false, age_secs: 0 })
+            .collect();
+        let fans = ipmi.get_fan_sensors().unwrap();
+        let preview = preview_alerts(
+            &readings,
+            &fans,
+            &AlertThresholdStore::default(),
+            SensorAlertThreshold { warning: 80.0, critical: 85.0 },
+            1000,
+            &SensorBlacklist::new(Vec::new()),
+        );
+        preview.sensors.iter().find(|s| s.sensor_id == "CPU1_TEMP").unwrap().level
+    }
+
+    #[test]
+    fn test_replayed_temperature_spike_raises_critical_alert() {
+        let trace = SensorTrace::new(parse_csv(SPIKE_TRACE).unwrap()).unwrap();
+        assert_eq!(trace.duration(), Duration::from_secs(90));
+        let replay = Arc::new(SensorReplay::new(trace, &ReplayConfig::default()).unwrap());
+        let ipmi = IpmiService::new(IpmiConfig::default()).with_replay(Arc::clone(&replay));
+
+        assert_eq!(cpu_alert_level(&ipmi), ThresholdLevel::Within);
+        replay.seek(Duration::from_secs(45));
+        assert_eq!(cpu_alert_level(&ipmi), ThresholdLevel::Within);
+
+        replay.seek(Duration::from_secs(60));
+        assert_eq!(cpu_alert_level(&ipmi), ThresholdLevel::Critical);
+        assert_eq!(ipmi.get_fan_sensors().unwrap()[0].speed_rpm, 9600);
+        // 调速命令不下发到真实硬件
+        assert!(ipmi.set_fan_speed("FAN1", 100).is_ok());
+
+        replay.seek(Duration::from_secs(120));
+        assert_eq!(cpu_alert_level(&ipmi), ThresholdLevel::Within);
+    }
+}
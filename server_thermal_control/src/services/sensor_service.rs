@@ -4,7 +4,7 @@ use crate::models::{
     error::{AppError, AppResult},
     sensor::*,
 };
-use crate::services::ipmi_service::IpmiService;
+use crate::services::sensor_source::SensorSource;
 use crate::utils::{ipmi::IpmiClient, math::MathUtils, time::TimeUtils};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -12,19 +12,26 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// 传感器校准参数持久化文件
+const SENSOR_CALIBRATION_FILE: &str = "data/sensor_calibration.json";
+
 /// 传感器服务
 ///
 /// 负责传感器数据的收集、处理和管理
 #[derive(Clone)]
 pub struct SensorService {
     /// IPMI客户端
-    ipmi_service: Arc<IpmiService>,
+    ipmi_service: Arc<dyn SensorSource>,
     /// 传感器配置
     sensor_configs: Arc<RwLock<HashMap<String, SensorConfig>>>,
     /// 传感器历史数据
     sensor_history: Arc<RwLock<HashMap<String, Vec<SensorReading>>>>,
     /// 监控配置
     monitoring_config: Arc<RwLock<MonitoringConfig>>,
+    /// 传感器校准参数（传感器ID -> 校准）
+    calibrations: Arc<RwLock<HashMap<String, SensorCalibration>>>,
+    /// 校准参数持久化文件路径
+    calibration_file_path: String,
     /// 数学工具
     math_utils: MathUtils,
 }
@@ -35,33 +42,56 @@ impl SensorService {
     /// # 参数
     /// * `ipmi_client` - IPMI客户端
     /// * `monitoring_config` - 监控配置
-    pub fn new(ipmi_service: Arc<IpmiService>, monitoring_config: MonitoringConfig) -> Self {
+    pub fn new(ipmi_service: Arc<dyn SensorSource>, monitoring_config: MonitoringConfig) -> Self {
         Self {
             ipmi_service,
             sensor_configs: Arc::new(RwLock::new(HashMap::new())),
             sensor_history: Arc::new(RwLock::new(HashMap::new())),
             monitoring_config: Arc::new(RwLock::new(monitoring_config)),
+            calibrations: Arc::new(RwLock::new(HashMap::new())),
+            calibration_file_path: SENSOR_CALIBRATION_FILE.to_string(),
             math_utils: MathUtils,
         }
     }
 
+    /// 设置校准参数的持久化文件路径
+    ///
+    /// # 参数
+    /// * `path` - 文件路径
+    pub fn with_calibration_file(mut self, path: impl Into<String>) -> Self {
+        self.calibration_file_path = path.into();
+        self
+    }
+
     /// 获取所有传感器数据
     pub async fn get_all_sensors(&self) -> AppResult<Vec<SensorReading>> {
         let sensors = self.ipmi_service.get_fan_sensors().await?;
         let mut readings = Vec::new();
 
-        for (id, sensor_data) in sensors {
+        let calibrations = self.calibrations.read().await.clone();
+
+        for (id, mut sensor_data) in sensors {
+            // 先校准再判断状态，保证告警阈值作用于校准后的值
+            let raw_value = sensor_data.value;
+            let calibration = calibrations.get(&id);
+            if let Some(calibration) = calibration {
+                sensor_data.value = calibration.apply(raw_value);
+            }
+
             let config = self.get_sensor_config(&id).await;
             let sensor_type = self.determine_sensor_type(&sensor_data, config.as_ref());
             let status = self.determine_sensor_status(&sensor_data, config.as_ref());
 
-            let reading = SensorReading::new(
+            let mut reading = SensorReading::new(
                 id.clone(),
                 sensor_data.value,
                 sensor_data.unit.unwrap_or("unknown".to_string()),
                 sensor_type,
                 status,
             );
+            if calibration.is_some() {
+                reading.raw_value = Some(raw_value);
+            }
 
             readings.push(reading);
         }
@@ -388,18 +418,93 @@ impl SensorService {
         sensor_id: &str,
         calibration: SensorCalibration,
     ) -> AppResult<()> {
-        let mut configs = self.sensor_configs.write().await;
+        self.set_calibration(sensor_id, calibration).await?;
+        Ok(())
+    }
 
-        if let Some(config) = configs.get_mut(sensor_id) {
-            config.calibration = Some(calibration);
-        } else {
-            return Err(AppError::not_found(&format!(
-                "传感器配置 {} 不存在",
-                sensor_id
-            )));
+    /// 获取所有传感器校准参数
+    pub async fn get_calibrations(&self) -> HashMap<String, SensorCalibration> {
+        self.calibrations.read().await.clone()
+    }
+
+    /// 设置传感器校准参数并持久化
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    /// * `calibration` - 校准参数
+    pub async fn set_calibration(
+        &self,
+        sensor_id: &str,
+        calibration: SensorCalibration,
+    ) -> AppResult<SensorCalibration> {
+        if !calibration.offset.is_finite() {
+            return Err(AppError::validation_error("offset", "偏移量必须是有效数值"));
         }
+        if !calibration.scale_factor.is_finite() || calibration.scale_factor <= 0.0 {
+            return Err(AppError::validation_error("scale_factor", "缩放因子必须大于0"));
+        }
+
+        let snapshot = {
+            let mut calibrations = self.calibrations.write().await;
+            calibrations.insert(sensor_id.to_string(), calibration.clone());
+            calibrations.clone()
+        };
+        self.save_calibrations(&snapshot).await?;
 
-        log::info!("传感器 {} 校准完成", sensor_id);
+        log::info!(
+            "传感器 {} 校准完成: 偏移 {:+.2}, 缩放 {:.3}",
+            sensor_id,
+            calibration.offset,
+            calibration.scale_factor
+        );
+        Ok(calibration)
+    }
+
+    /// 删除传感器校准参数
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    pub async fn remove_calibration(&self, sensor_id: &str) -> AppResult<()> {
+        let snapshot = {
+            let mut calibrations = self.calibrations.write().await;
+            if calibrations.remove(sensor_id).is_none() {
+                return Err(AppError::not_found_error("传感器校准", sensor_id));
+            }
+            calibrations.clone()
+        };
+        self.save_calibrations(&snapshot).await
+    }
+
+    /// 从持久化文件加载校准参数
+    ///
+    /// 文件不存在时保留当前校准参数
+    pub async fn load_calibrations(&self) -> AppResult<()> {
+        let content = match tokio::fs::read_to_string(&self.calibration_file_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let calibrations: HashMap<String, SensorCalibration> = serde_json::from_str(&content)?;
+        *self.calibrations.write().await = calibrations;
+
+        log::debug!("传感器校准参数已加载: {}", self.calibration_file_path);
+        Ok(())
+    }
+
+    /// 写入校准参数持久化文件
+    ///
+    /// # 参数
+    /// * `calibrations` - 全部校准参数
+    async fn save_calibrations(
+        &self,
+        calibrations: &HashMap<String, SensorCalibration>,
+    ) -> AppResult<()> {
+        let content = serde_json::to_string_pretty(calibrations)?;
+        if let Some(parent) = std::path::Path::new(&self.calibration_file_path).parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&self.calibration_file_path, content).await?;
         Ok(())
     }
 
@@ -745,6 +850,7 @@ pub struct SensorHealthReport {
 mod tests {
     use super::*;
     use crate::models::config::IpmiConfig;
+    use crate::services::ipmi_service::IpmiService;
 
     #[tokio::test]
     async fn test_sensor_service_creation() {
@@ -834,4 +940,53 @@ mod tests {
         let fan_type = sensor_service.determine_sensor_type(&fan_sensor, None);
         assert_eq!(fan_type, SensorType::Fan);
     }
+
+    #[test]
+    fn test_calibration_offset_shifts_value_and_alert_status() {
+        let thresholds = AlertThresholds {
+            critical_low: None,
+            warning_low: None,
+            warning_high: Some(80.0),
+            critical_high: Some(90.0),
+        };
+        let calibration = SensorCalibration::new(2.0, None, Some(81.0));
+
+        // 原始读数79°C低于警告阈值，校准后81°C触发警告
+        let uncalibrated =
+            SensorReading::calibrated("CPU1 Temp".to_string(), 79.0, None, &thresholds, None);
+        assert_eq!(uncalibrated.value, 79.0);
+        assert!(uncalibrated.raw_value.is_none());
+        assert!(matches!(uncalibrated.status, SensorStatus::Ok));
+
+        let calibrated = SensorReading::calibrated(
+            "CPU1 Temp".to_string(),
+            79.0,
+            Some(&calibration),
+            &thresholds,
+            None,
+        );
+        assert_eq!(calibrated.value, 81.0);
+        assert_eq!(calibrated.raw_value, Some(79.0));
+        assert!(matches!(calibrated.status, SensorStatus::Warning));
+    }
+
+    #[tokio::test]
+    async fn test_calibrations_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sensor_calibration.json");
+        let monitoring_config = MonitoringConfig::default();
+        let ipmi_service = Arc::new(IpmiService::new(Default::default()));
+
+        let service = SensorService::new(ipmi_service.clone(), monitoring_config.clone())
+            .with_calibration_file(path.to_string_lossy());
+        service
+            .set_calibration("CPU1 Temp", SensorCalibration::new(2.0, None, None))
+            .await
+            .unwrap();
+
+        let reloaded = SensorService::new(ipmi_service, monitoring_config)
+            .with_calibration_file(path.to_string_lossy());
+        reloaded.load_calibrations().await.unwrap();
+        assert_eq!(reloaded.get_calibrations().await["CPU1 Temp"].offset, 2.0);
+    }
 }
@@ -0,0 +1,94 @@
+//! 传感器数据源
+//!
+//! 服务只依赖 [`SensorSource`] 读取温度、风扇并下发转速，不直接依赖ipmitool，
+//! BMC以外的来源（模拟、外部推送等）实现该trait即可接入
+
+use crate::services::ipmi_service::{FanSensor, IpmiService, TemperatureSensor};
+
+/// 温度和风扇读数的来源，以及风扇控制的出口
+pub trait SensorSource: Send + Sync {
+    /// 读取全部温度传感器
+    fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>>;
+
+    /// 读取全部风扇
+    fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>>;
+
+    /// 设置风扇转速
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `speed_percent` - 转速百分比
+    fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// 将风扇控制交还数据源自身的自动控制
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// 测试数据源是否可用
+    fn test_connection(&self) -> Result<bool, Box<dyn std::error::Error>>;
+
+    /// 风扇是否可调速，未知时返回None
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    fn is_fan_controllable(&self, _fan_id: &str) -> Option<bool> {
+        None
+    }
+
+    /// 获取特定温度传感器数据
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    fn get_temperature_by_sensor(
+        &self,
+        sensor_id: &str,
+    ) -> Result<Option<TemperatureSensor>, Box<dyn std::error::Error>> {
+        let sensors = self.get_temperature_sensors()?;
+        Ok(sensors.into_iter().find(|s| s.sensor_id == sensor_id))
+    }
+
+    /// 获取特定风扇数据
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    fn get_fan_by_id(&self, fan_id: &str) -> Result<Option<FanSensor>, Box<dyn std::error::Error>> {
+        let fans = self.get_fan_sensors()?;
+        Ok(fans.into_iter().find(|f| f.fan_id == fan_id))
+    }
+}
+
+impl SensorSource for IpmiService {
+    fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+        IpmiService::get_temperature_sensors(self)
+    }
+
+    fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
+        IpmiService::get_fan_sensors(self)
+    }
+
+    fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+        IpmiService::set_fan_speed(self, fan_id, speed_percent)
+    }
+
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+        IpmiService::restore_automatic_fan_control(self)
+    }
+
+    fn test_connection(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        IpmiService::test_connection(self)
+    }
+
+    fn is_fan_controllable(&self, fan_id: &str) -> Option<bool> {
+        IpmiService::is_fan_controllable(self, fan_id)
+    }
+
+    fn get_temperature_by_sensor(
+        &self,
+        sensor_id: &str,
+    ) -> Result<Option<TemperatureSensor>, Box<dyn std::error::Error>> {
+        IpmiService::get_temperature_by_sensor(self, sensor_id)
+    }
+
+    fn get_fan_by_id(&self, fan_id: &str) -> Result<Option<FanSensor>, Box<dyn std::error::Error>> {
+        IpmiService::get_fan_by_id(self, fan_id)
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 归一化后的传感器单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorUnit {
+    Celsius,
+    Fahrenheit,
+    Rpm,
+    Percent,
+    Volts,
+    Amps,
+    Watts,
+}
+
+impl SensorUnit {
+    /// 对外展示的规范单位符号
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            SensorUnit::Celsius => "°C",
+            SensorUnit::Fahrenheit => "°F",
+            SensorUnit::Rpm => "RPM",
+            SensorUnit::Percent => "%",
+            SensorUnit::Volts => "V",
+            SensorUnit::Amps => "A",
+            SensorUnit::Watts => "W",
+        }
+    }
+
+    /// 是否为温度单位
+    pub fn is_temperature(&self) -> bool {
+        matches!(self, SensorUnit::Celsius | SensorUnit::Fahrenheit)
+    }
+}
+
+/// 各厂商BMC常见的单位写法
+const DEFAULT_ALIASES: &[(&str, SensorUnit)] = &[
+    ("degrees c", SensorUnit::Celsius),
+    ("degree c", SensorUnit::Celsius),
+    ("deg c", SensorUnit::Celsius),
+    ("°c", SensorUnit::Celsius),
+    ("℃", SensorUnit::Celsius),
+    ("c", SensorUnit::Celsius),
+    ("celsius", SensorUnit::Celsius),
+    ("degrees celsius", SensorUnit::Celsius),
+    ("degrees f", SensorUnit::Fahrenheit),
+    ("degree f", SensorUnit::Fahrenheit),
+    ("deg f", SensorUnit::Fahrenheit),
+    ("°f", SensorUnit::Fahrenheit),
+    ("℉", SensorUnit::Fahrenheit),
+    ("f", SensorUnit::Fahrenheit),
+    ("fahrenheit", SensorUnit::Fahrenheit),
+    ("degrees fahrenheit", SensorUnit::Fahrenheit),
+    ("rpm", SensorUnit::Rpm),
+    ("r/min", SensorUnit::Rpm),
+    ("%", SensorUnit::Percent),
+    ("percent", SensorUnit::Percent),
+    ("volts", SensorUnit::Volts),
+    ("volt", SensorUnit::Volts),
+    ("v", SensorUnit::Volts),
+    ("amps", SensorUnit::Amps),
+    ("amp", SensorUnit::Amps),
+    ("amperes", SensorUnit::Amps),
+    ("a", SensorUnit::Amps),
+    ("watts", SensorUnit::Watts),
+    ("watt", SensorUnit::Watts),
+    ("w", SensorUnit::Watts),
+];
+
+/// 一次归一化后的读数
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedReading {
+    /// 换算到规范单位后的数值，温度统一为摄氏度
+    pub value: f64,
+    /// 规范单位
+    pub unit: SensorUnit,
+    /// BMC返回的原始单位文本
+    pub raw_unit: String,
+}
+
+/// 传感器单位归一化
+///
+/// 读数在解析时把厂商的单位写法（`degrees C`、`°C`、`Celsius`等）映射为规范单位，
+/// 原始写法另行保留。匹配时忽略大小写和多余空白，配置中的别名优先于内置别名
+#[derive(Debug, Clone)]
+pub struct UnitNormalizer {
+    aliases: HashMap<String, SensorUnit>,
+}
+
+impl Default for UnitNormalizer {
+    fn default() -> Self {
+        Self {
+            aliases: DEFAULT_ALIASES
+                .iter()
+                .map(|&(alias, unit)| (alias.to_string(), unit))
+                .collect(),
+        }
+    }
+}
+
+impl UnitNormalizer {
+    /// 在内置别名之上追加自定义别名
+    ///
+    /// # 参数
+    /// * `aliases` - 单位写法到规范单位的映射，如 `"deg. c" = "celsius"`
+    pub fn with_aliases(mut self, aliases: &HashMap<String, SensorUnit>) -> Self {
+        for (alias, unit) in aliases {
+            self.aliases.insert(alias_key(alias), *unit);
+        }
+        self
+    }
+
+    /// 查找单位写法对应的规范单位，无法识别时返回None
+    ///
+    /// # 参数
+    /// * `raw_unit` - 原始单位文本
+    pub fn unit(&self, raw_unit: &str) -> Option<SensorUnit> {
+        self.aliases.get(&alias_key(raw_unit)).copied()
+    }
+
+    /// 解析带单位的读数，温度换算为摄氏度
+    ///
+    /// 数值或单位无法识别时返回None
+    ///
+    /// # 参数
+    /// * `text` - 传感器读数列的原始文本，如 `23 degrees C`
+    pub fn normalize(&self, text: &str) -> Option<NormalizedReading> {
+        let (number, raw_unit) = split_reading(text)?;
+        let unit = self.unit(raw_unit)?;
+        let value = number.parse::<f64>().ok()?;
+        let (value, unit) = match unit {
+            SensorUnit::Fahrenheit => ((value - 32.0) * 5.0 / 9.0, SensorUnit::Celsius),
+            unit => (value, unit),
+        };
+        Some(NormalizedReading {
+            value,
+            unit,
+            raw_unit: raw_unit.to_string(),
+        })
+    }
+}
+
+/// 按最后一个数字拆分读数文本为数值部分和单位部分
+///
+/// 文本中没有数字（如 `no reading`）时返回None
+///
+/// # 参数
+/// * `text` - 传感器读数列的原始文本
+pub fn split_reading(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim();
+    let end = text.rfind(|c: char| c.is_ascii_digit())? + 1;
+    Some((text[..end].trim(), text[end..].trim()))
+}
+
+/// 别名匹配键：小写并合并空白
+fn alias_key(raw_unit: &str) -> String {
+    raw_unit
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_vendor_temperature_spellings() {
+        let normalizer = UnitNormalizer::default();
+        for text in ["23 degrees C", "23 Degrees  C", "23°C", "23 C", "23 Celsius", "23 ℃", "73.4 degrees F"] {
+            let reading = normalizer.normalize(text).unwrap_or_else(|| panic!("{:?} 未识别", text));
+            assert_eq!(reading.unit, SensorUnit::Celsius, "{:?}", text);
+            assert!((reading.value - 23.0).abs() < 1e-9, "{:?} => {}", text, reading.value);
+        }
+        assert_eq!(normalizer.normalize("23°C").unwrap().raw_unit, "°C");
+        assert_eq!(normalizer.normalize("12.10 Volts").unwrap().unit, SensorUnit::Volts);
+        assert_eq!(normalizer.normalize("1.2 Amps").unwrap().unit, SensorUnit::Amps);
+        assert_eq!(normalizer.normalize("280 Watts").unwrap().unit, SensorUnit::Watts);
+        assert_eq!(normalizer.normalize("no reading"), None);
+        assert_eq!(normalizer.normalize("23 furlongs"), None);
+
+        // 配置的别名补充内置写法
+        let aliases = HashMap::from([("Deg. C".to_string(), SensorUnit::Celsius)]);
+        let normalizer = normalizer.with_aliases(&aliases);
+        let reading = normalizer.normalize("41 deg. c").unwrap();
+        assert_eq!((reading.value, reading.unit), (41.0, SensorUnit::Celsius));
+        assert_eq!(reading.raw_unit, "deg. c");
+    }
+}
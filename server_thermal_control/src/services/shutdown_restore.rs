@@ -0,0 +1,116 @@
+use crate::services::safe_boot::SafeBootFans;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// 正常关闭时交给BMC的风扇状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownFanAction {
+    /// 交还BMC自动模式，由BMC按温度调速
+    #[default]
+    RestoreAutomatic,
+    /// 将所有风扇设为启动保护转速（`control.safe_boot_fan_percent`）并保持手动模式，
+    /// 适用于BMC自动模式转速过低的机型；重启后由启动保护流程接管
+    SafeFloor,
+}
+
+/// 关闭时的风扇恢复
+///
+/// 无论最后一条手动指令是什么，退出前都把风扇置于配置的安全状态。
+/// 只在第一次调用时下发，重复调用（例如信号与服务器停止先后到达）不会再次写入BMC
+pub struct ShutdownRestore {
+    fans: Arc<dyn SafeBootFans>,
+    action: ShutdownFanAction,
+    safe_floor_percent: u8,
+    done: AtomicBool,
+}
+
+impl ShutdownRestore {
+    /// 创建关闭时的风扇恢复
+    ///
+    /// # 参数
+    /// * `fans` - 风扇操作
+    /// * `action` - 关闭时的风扇状态
+    /// * `safe_floor_percent` - 启动保护转速百分比
+    pub fn new(fans: Arc<dyn SafeBootFans>, action: ShutdownFanAction, safe_floor_percent: u8) -> Self {
+        Self {
+            fans,
+            action,
+            safe_floor_percent,
+            done: AtomicBool::new(false),
+        }
+    }
+
+    /// 下发关闭时的风扇状态，返回本次调用是否下发；失败只记录日志，不阻止退出
+    pub fn restore(&self) -> bool {
+        if self.done.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        let result = match self.action {
+            ShutdownFanAction::RestoreAutomatic => self.fans.restore_automatic_fan_control(),
+            ShutdownFanAction::SafeFloor => self.fans.set_all_fan_speeds(self.safe_floor_percent),
+        };
+        match result {
+            Ok(()) => info!("Shutdown fan action applied: {:?}", self.action),
+            Err(e) => error!("Failed to apply shutdown fan action {:?}: {}", self.action, e),
+        }
+        true
+    }
+}
+
+/// 运行服务器直到其停止或收到关闭信号，随后下发关闭时的风扇状态
+///
+/// # 参数
+/// * `server` - 服务器运行任务
+/// * `shutdown_signal` - 关闭信号
+/// * `restore` - 关闭时的风扇恢复
+pub async fn serve_until_shutdown<S, E, G>(server: S, shutdown_signal: G, restore: &ShutdownRestore)
+where
+    S: Future<Output = Result<(), E>>,
+    E: Display,
+    G: Future,
+{
+    tokio::select! {
+        result = server => {
+            match result {
+                Ok(_) => info!("Server stopped normally"),
+                Err(e) => error!("Server error: {}", e),
+            }
+        }
+        _ = shutdown_signal => {
+            info!("Shutdown signal received, stopping server...");
+        }
+    }
+
+    restore.restore();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::MockIpmiService;
+
+    #[tokio::test]
+    async fn test_configured_action_is_issued_exactly_once_on_shutdown() {
+        let cases = [
+            (ShutdownFanAction::RestoreAutomatic, 1, vec![]),
+            (ShutdownFanAction::SafeFloor, 0, vec![("all".to_string(), 35)]),
+        ];
+        for (action, automatic_restores, fan_speed_writes) in cases {
+            let ipmi = Arc::new(MockIpmiService::healthy_server());
+            let restore = ShutdownRestore::new(Arc::clone(&ipmi) as Arc<dyn SafeBootFans>, action, 35);
+
+            // 收到关闭信号时服务器仍在运行
+            serve_until_shutdown(std::future::pending::<Result<(), String>>(), async {}, &restore).await;
+            assert!(!restore.restore());
+
+            assert_eq!(ipmi.automatic_restores(), automatic_restores, "{:?}", action);
+            assert_eq!(ipmi.fan_speed_writes(), fan_speed_writes, "{:?}", action);
+        }
+    }
+}
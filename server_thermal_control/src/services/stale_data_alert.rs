@@ -0,0 +1,333 @@
+use crate::models::alert::AlertSeverity;
+use crate::models::error::AppResult;
+use crate::services::alert_history_store::AlertHistoryStore;
+use crate::services::alert_monitor::AlertMonitor;
+use crate::services::alert_notifier::AlertNotifier;
+use crate::services::alert_store::{AlertStore, AlertTransition, NewAlert};
+use crate::services::poll_ticker::{PollOverruns, PollTicker};
+use crate::services::task_watchdog::Heartbeat;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// 监控数据过期告警的告警源
+pub const STALE_DATA_ALERT_SOURCE: &str = "monitoring.stale_data";
+
+/// 监控数据过期告警配置
+///
+/// 监控循环停止后缓存不再更新，普通告警也随之失效。缓存最后更新时间距今超过
+/// 轮询间隔的 `interval_multiple` 倍时创建严重告警并经通知渠道发送，数据恢复更新后自动解决
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleDataAlertConfig {
+    /// 是否启用
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 数据超过轮询间隔的多少倍未更新视为过期
+    #[serde(default = "default_interval_multiple")]
+    pub interval_multiple: u32,
+    /// 检查间隔（秒）
+    #[serde(default = "default_check_interval_secs")]
+    pub check_interval_secs: u64,
+}
+
+impl Default for StaleDataAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            interval_multiple: default_interval_multiple(),
+            check_interval_secs: default_check_interval_secs(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_interval_multiple() -> u32 {
+    3
+}
+
+fn default_check_interval_secs() -> u64 {
+    15
+}
+
+/// 过期状态的变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaleDataTransition {
+    /// 数据开始过期，应创建告警
+    Stale {
+        /// 缓存最后更新时间
+        last_update: DateTime<Utc>,
+        /// 距最后更新的时长
+        age: ChronoDuration,
+        /// 过期阈值
+        threshold: ChronoDuration,
+    },
+    /// 数据恢复更新，应解决告警
+    Recovered,
+}
+
+/// 监控数据过期检查
+///
+/// 只在状态变化时返回结果，持续过期期间不重复告警
+#[derive(Debug, Clone)]
+pub struct StaleDataWatch {
+    config: StaleDataAlertConfig,
+    stale: bool,
+}
+
+impl StaleDataWatch {
+    /// 创建过期检查
+    ///
+    /// # 参数
+    /// * `config` - 过期告警配置
+    pub fn new(config: StaleDataAlertConfig) -> Self {
+        Self { config, stale: false }
+    }
+
+    /// 过期告警配置
+    pub fn config(&self) -> &StaleDataAlertConfig {
+        &self.config
+    }
+
+    /// 检查间隔
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_secs.max(1))
+    }
+
+    /// 数据被视为过期的时长
+    ///
+    /// # 参数
+    /// * `poll_interval` - 监控轮询间隔
+    pub fn threshold(&self, poll_interval: Duration) -> ChronoDuration {
+        let threshold = poll_interval.saturating_mul(self.config.interval_multiple.max(1));
+        ChronoDuration::from_std(threshold).unwrap_or(ChronoDuration::MAX)
+    }
+
+    /// 检查监控数据是否过期，返回本次的状态变化
+    ///
+    /// # 参数
+    /// * `last_update` - 监控缓存最后更新时间
+    /// * `poll_interval` - 监控轮询间隔
+    /// * `now` - 当前时间
+    pub fn observe(
+        &mut self,
+        last_update: DateTime<Utc>,
+        poll_interval: Duration,
+        now: DateTime<Utc>,
+    ) -> Option<StaleDataTransition> {
+        let age = now.signed_duration_since(last_update);
+        let threshold = self.threshold(poll_interval);
+        let stale = age > threshold;
+        if stale == self.stale {
+            return None;
+        }
+
+        self.stale = stale;
+        Some(if stale {
+            StaleDataTransition::Stale { last_update, age, threshold }
+        } else {
+            StaleDataTransition::Recovered
+        })
+    }
+}
+
+/// 监控数据过期告警
+///
+/// 按检查间隔比较告警监控最后一次读到数据的时间，过期时创建严重告警、写入告警历史并经已启用的通知渠道发送，
+/// 数据恢复更新后解决该告警；克隆共享同一份过期状态，检查任务重启后仍能解决重启前创建的告警
+#[derive(Clone)]
+pub struct StaleDataMonitor {
+    watch: Arc<Mutex<StaleDataWatch>>,
+    alerts: AlertStore,
+    history: Arc<dyn AlertHistoryStore>,
+    notifier: AlertNotifier,
+    active_alert: Arc<Mutex<Option<Uuid>>>,
+}
+
+impl StaleDataMonitor {
+    /// 创建监控数据过期告警
+    ///
+    /// # 参数
+    /// * `config` - 过期告警配置
+    /// * `alerts` - 告警存储
+    /// * `history` - 告警历史存储
+    /// * `notifier` - 告警通知
+    pub fn new(
+        config: StaleDataAlertConfig,
+        alerts: AlertStore,
+        history: Arc<dyn AlertHistoryStore>,
+        notifier: AlertNotifier,
+    ) -> Self {
+        Self {
+            watch: Arc::new(Mutex::new(StaleDataWatch::new(config))),
+            alerts,
+            history,
+            notifier,
+            active_alert: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 检查一次，过期时告警，恢复时解决告警，返回本次的状态变化
+    ///
+    /// # 参数
+    /// * `last_update` - 监控数据最后更新时间
+    /// * `poll_interval` - 监控轮询间隔
+    /// * `now` - 当前时间
+    pub async fn check(
+        &self,
+        last_update: DateTime<Utc>,
+        poll_interval: Duration,
+        now: DateTime<Utc>,
+    ) -> Option<StaleDataTransition> {
+        let transition = self
+            .watch
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .observe(last_update, poll_interval, now)?;
+        if let Err(e) = self.apply(transition).await {
+            warn!("处理监控数据过期告警失败: {}", e);
+        }
+        Some(transition)
+    }
+
+    async fn apply(&self, transition: StaleDataTransition) -> AppResult<()> {
+        match transition {
+            StaleDataTransition::Stale { last_update, age, threshold } => {
+                let Some((transition, alert)) = self.alerts.raise(NewAlert {
+                    alert_type: "system".to_string(),
+                    severity: AlertSeverity::Critical,
+                    title: "监控数据过期".to_string(),
+                    message: format!(
+                        "监控数据自 {} 起已 {} 秒未更新（阈值 {} 秒），监控循环可能已停止，告警规则暂时失效",
+                        last_update.to_rfc3339(),
+                        age.num_seconds(),
+                        threshold.num_seconds()
+                    ),
+                    source: "监控自检".to_string(),
+                    source_id: STALE_DATA_ALERT_SOURCE.to_string(),
+                    rule_id: None,
+                }) else {
+                    return Ok(());
+                };
+                if transition == AlertTransition::Suppressed {
+                    return self.history.upsert(&alert).await;
+                }
+                warn!("监控数据过期，已创建告警: {}", alert.id);
+                *self.active_alert.lock().unwrap_or_else(|e| e.into_inner()) = Some(alert.id);
+                self.history.upsert(&alert).await?;
+
+                let channels = self.notifier.channels().iter().filter(|(_, channel)| channel.enabled);
+                for (channel_id, channel) in channels {
+                    if let Err(e) = self.notifier.deliver(channel_id, channel, &alert).await {
+                        warn!("监控数据过期告警发送到 {} 失败: {}", channel_id, e);
+                    }
+                }
+            }
+            StaleDataTransition::Recovered => {
+                let active_alert = self.active_alert.lock().unwrap_or_else(|e| e.into_inner()).take();
+                if let Some(id) = active_alert {
+                    info!("监控数据恢复更新，解决告警: {}", id);
+                    let alert = self.alerts.resolve(id)?;
+                    self.history.upsert(&alert).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 按检查间隔持续检查，每个周期上报一次心跳
+    ///
+    /// 独立于告警监控运行；刚启动时从启动时间开始计算，避免在第一次轮询前误报
+    ///
+    /// # 参数
+    /// * `monitor` - 告警监控
+    /// * `poll_interval` - 监控轮询间隔
+    /// * `poll_overruns` - 检查超过周期时的跳过计数
+    /// * `heartbeat` - 看门狗心跳
+    pub async fn run(
+        self,
+        monitor: AlertMonitor,
+        poll_interval: Duration,
+        poll_overruns: PollOverruns,
+        heartbeat: Heartbeat,
+    ) {
+        let started_at = Utc::now();
+        let check_interval = self.watch.lock().unwrap_or_else(|e| e.into_inner()).check_interval();
+        let mut ticker = PollTicker::new("stale_data_watch", check_interval, poll_overruns);
+        loop {
+            ticker.tick().await;
+            heartbeat.beat();
+            let last_update = monitor.last_update().map_or(started_at, |at| at.max(started_at));
+            self.check(last_update, poll_interval, Utc::now()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::models::alert::AlertStatus;
+    use crate::services::alert_history_store::InMemoryAlertHistoryStore;
+
+    #[test]
+    fn test_stalled_cache_turns_stale_once_and_recovers() {
+        let mut watch = StaleDataWatch::new(StaleDataAlertConfig::default());
+        let poll_interval = Duration::from_secs(5);
+        let last_update = Utc::now();
+        let at = |secs: i64| last_update + ChronoDuration::seconds(secs);
+
+        // 缓存停止更新，超过3倍轮询间隔后才视为过期
+        assert_eq!(watch.observe(last_update, poll_interval, at(15)), None);
+        assert_eq!(
+            watch.observe(last_update, poll_interval, at(16)),
+            Some(StaleDataTransition::Stale {
+                last_update,
+                age: ChronoDuration::seconds(16),
+                threshold: ChronoDuration::seconds(15),
+            })
+        );
+        // 持续过期不重复上报
+        assert_eq!(watch.observe(last_update, poll_interval, at(60)), None);
+
+        assert_eq!(watch.observe(at(60), poll_interval, at(61)), Some(StaleDataTransition::Recovered));
+        assert_eq!(watch.observe(at(61), poll_interval, at(62)), None);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_monitor_raises_and_resolves_stale_data_alert() {
+        let alerts = AlertStore::new(100);
+        let history = Arc::new(InMemoryAlertHistoryStore::new());
+        let stale_data = StaleDataMonitor::new(
+            StaleDataAlertConfig::default(),
+            alerts.clone(),
+            history.clone(),
+            AlertNotifier::from_config(&AppConfig::default().alert),
+        );
+        let poll_interval = Duration::from_secs(5);
+        let started_at = Utc::now();
+        let at = |secs: i64| started_at + ChronoDuration::seconds(secs);
+
+        // 监控任务一直没有更新数据
+        assert!(stale_data.check(started_at, poll_interval, at(10)).await.is_none());
+        assert!(stale_data.check(started_at, poll_interval, at(20)).await.is_some());
+        let raised = alerts.list();
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].severity, "Critical");
+        assert_eq!(raised[0].source_id, STALE_DATA_ALERT_SOURCE);
+        assert_eq!(history.count().await.unwrap(), 1);
+
+        assert_eq!(
+            stale_data.check(at(25), poll_interval, at(26)).await,
+            Some(StaleDataTransition::Recovered)
+        );
+        assert!(matches!(alerts.get(raised[0].id).unwrap().status, AlertStatus::Resolved));
+        let stored = history.get(&raised[0].id.to_string()).await.unwrap().unwrap();
+        assert!(matches!(stored.status, AlertStatus::Resolved));
+    }
+}
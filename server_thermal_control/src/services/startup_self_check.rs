@@ -0,0 +1,357 @@
+use crate::services::ipmi_service::{FanSensor, TemperatureSensor};
+use crate::services::sensor_source::SensorSource;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// 启动自检失败时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfCheckFailureAction {
+    /// 以只读监控模式启动，不向BMC下发风扇转速
+    #[default]
+    ReadOnly,
+    /// 终止启动
+    Abort,
+}
+
+/// 启动自检配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StartupSelfCheckConfig {
+    /// 是否在启动时执行自检
+    #[serde(default)]
+    pub enabled: bool,
+    /// 自检失败时的处理方式
+    #[serde(default)]
+    pub on_failure: SelfCheckFailureAction,
+    /// 试探调速幅度（百分比），在启动保护转速的基础上调高
+    #[serde(default = "default_nudge_percent")]
+    pub nudge_percent: u8,
+    /// 每次下发转速后等待风扇稳定的时间（秒）
+    #[serde(default = "default_settle_secs")]
+    pub settle_secs: u64,
+    /// 判定风扇已响应的最小转速变化（RPM）
+    #[serde(default = "default_min_rpm_delta")]
+    pub min_rpm_delta: u32,
+}
+
+fn default_nudge_percent() -> u8 {
+    10
+}
+
+fn default_settle_secs() -> u64 {
+    5
+}
+
+fn default_min_rpm_delta() -> u32 {
+    100
+}
+
+impl Default for StartupSelfCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            on_failure: SelfCheckFailureAction::default(),
+            nudge_percent: default_nudge_percent(),
+            settle_secs: default_settle_secs(),
+            min_rpm_delta: default_min_rpm_delta(),
+        }
+    }
+}
+
+/// 启动自检所需的IPMI操作
+pub trait SelfCheckIpmi: Send + Sync {
+    /// 读取全部温度传感器
+    fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>>;
+
+    /// 读取全部风扇
+    fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>>;
+
+    /// 设置单个风扇转速
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID
+    /// * `speed_percent` - 转速百分比
+    fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// 将风扇控制交还BMC自动模式
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+impl<S: SensorSource + ?Sized> SelfCheckIpmi for S {
+    fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+        SensorSource::get_temperature_sensors(self)
+    }
+
+    fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
+        SensorSource::get_fan_sensors(self)
+    }
+
+    fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+        SensorSource::set_fan_speed(self, fan_id, speed_percent)
+    }
+
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+        SensorSource::restore_automatic_fan_control(self)
+    }
+}
+
+/// 单个风扇的试探调速结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FanNudgeResult {
+    /// 风扇ID
+    pub fan_id: String,
+    /// 基准转速下的读数（RPM），未读到时为空
+    pub baseline_rpm: Option<u32>,
+    /// 调高转速后的读数（RPM），未读到时为空
+    pub nudged_rpm: Option<u32>,
+    /// 转速变化是否达到 `min_rpm_delta`
+    pub responded: bool,
+}
+
+/// 启动自检报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SelfCheckReport {
+    /// 读到的温度传感器数量
+    pub temperature_sensors: usize,
+    /// 读到的风扇数量
+    pub fan_sensors: usize,
+    /// 基准转速（百分比）
+    pub baseline_percent: u8,
+    /// 试探转速（百分比）
+    pub nudged_percent: u8,
+    /// 各可调速风扇的试探结果
+    pub fans: Vec<FanNudgeResult>,
+    /// 是否已交还BMC自动模式
+    pub restored: bool,
+    /// 失败原因，为空表示自检通过
+    pub failures: Vec<String>,
+}
+
+impl SelfCheckReport {
+    /// 自检是否通过
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// 逐项输出自检报告
+    pub fn log(&self) {
+        info!(
+            "Startup self-check: {} temperature sensor(s), {} fan(s), nudge {}% -> {}%",
+            self.temperature_sensors, self.fan_sensors, self.baseline_percent, self.nudged_percent
+        );
+        for fan in &self.fans {
+            info!(
+                "Startup self-check fan {}: {:?} RPM -> {:?} RPM ({})",
+                fan.fan_id,
+                fan.baseline_rpm,
+                fan.nudged_rpm,
+                if fan.responded { "ok" } else { "no response" }
+            );
+        }
+        if self.passed() {
+            info!("Startup self-check passed, automatic fan control restored");
+        } else {
+            for failure in &self.failures {
+                error!("Startup self-check failure: {}", failure);
+            }
+        }
+    }
+}
+
+/// 执行启动自检
+///
+/// 读取全部传感器后，将可调速风扇设为基准转速并记录读数，再调高 `nudge_percent` 确认转速随之上升，
+/// 最后交还BMC自动模式。只要下发过转速，无论结果如何都会交还自动模式
+///
+/// # 参数
+/// * `ipmi` - IPMI操作
+/// * `config` - 自检配置
+/// * `baseline_percent` - 基准转速百分比（启动保护转速）
+pub async fn run(ipmi: &dyn SelfCheckIpmi, config: &StartupSelfCheckConfig, baseline_percent: u8) -> SelfCheckReport {
+    let baseline_percent = baseline_percent.min(100);
+    let mut report = SelfCheckReport {
+        baseline_percent,
+        nudged_percent: baseline_percent.saturating_add(config.nudge_percent).min(100),
+        ..Default::default()
+    };
+
+    match ipmi.get_temperature_sensors() {
+        Ok(sensors) if sensors.is_empty() => report.failures.push("未读到任何温度传感器".to_string()),
+        Ok(sensors) => report.temperature_sensors = sensors.len(),
+        Err(e) => report.failures.push(format!("读取温度传感器失败: {}", e)),
+    }
+
+    let fan_ids: Vec<String> = match ipmi.get_fan_sensors() {
+        Ok(fans) => {
+            report.fan_sensors = fans.len();
+            fans.into_iter().filter(|fan| fan.controllable).map(|fan| fan.fan_id).collect()
+        }
+        Err(e) => {
+            report.failures.push(format!("读取风扇失败: {}", e));
+            Vec::new()
+        }
+    };
+    if fan_ids.is_empty() {
+        report.failures.push("没有可调速的风扇".to_string());
+    }
+    if report.nudged_percent == report.baseline_percent {
+        report.failures.push("试探转速与基准转速相同，无法确认风扇响应".to_string());
+    }
+    if !report.passed() {
+        return report;
+    }
+
+    let settle = Duration::from_secs(config.settle_secs);
+    let baseline = set_and_read(ipmi, &fan_ids, baseline_percent, settle, &mut report.failures).await;
+    let nudged = set_and_read(ipmi, &fan_ids, report.nudged_percent, settle, &mut report.failures).await;
+
+    match ipmi.restore_automatic_fan_control() {
+        Ok(()) => report.restored = true,
+        Err(e) => report.failures.push(format!("交还BMC自动模式失败: {}", e)),
+    }
+
+    for fan_id in fan_ids {
+        let baseline_rpm = baseline.get(&fan_id).copied();
+        let nudged_rpm = nudged.get(&fan_id).copied();
+        let responded = matches!(
+            (baseline_rpm, nudged_rpm),
+            (Some(before), Some(after)) if after >= before.saturating_add(config.min_rpm_delta)
+        );
+        if !responded {
+            report.failures.push(format!(
+                "风扇 {} 未响应调速: {:?} RPM -> {:?} RPM",
+                fan_id, baseline_rpm, nudged_rpm
+            ));
+        }
+        report.fans.push(FanNudgeResult {
+            fan_id,
+            baseline_rpm,
+            nudged_rpm,
+            responded,
+        });
+    }
+
+    report
+}
+
+/// 将风扇设为指定转速，等待稳定后读回各风扇转速
+async fn set_and_read(
+    ipmi: &dyn SelfCheckIpmi,
+    fan_ids: &[String],
+    speed_percent: u8,
+    settle: Duration,
+    failures: &mut Vec<String>,
+) -> BTreeMap<String, u32> {
+    for fan_id in fan_ids {
+        if let Err(e) = ipmi.set_fan_speed(fan_id, speed_percent) {
+            failures.push(format!("设置风扇 {} 转速 {}% 失败: {}", fan_id, speed_percent, e));
+        }
+    }
+    tokio::time::sleep(settle).await;
+
+    match ipmi.get_fan_sensors() {
+        Ok(fans) => fans.into_iter().map(|fan| (fan.fan_id, fan.speed_rpm)).collect(),
+        Err(e) => {
+            failures.push(format!("读回风扇转速失败: {}", e));
+            BTreeMap::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ipmi_service::{parse_fan_sdr, parse_temperature_sdr};
+    use chrono::Utc;
+    use std::sync::Mutex;
+
+    /// 转速按 `百分比 × 50` 响应的假BMC，`stuck_fan` 的转速始终不变
+    struct FakeIpmi {
+        speeds: Mutex<BTreeMap<String, u8>>,
+        stuck_fan: Option<&'static str>,
+        restored: Mutex<bool>,
+    }
+
+    impl FakeIpmi {
+        fn new(stuck_fan: Option<&'static str>) -> Self {
+            Self {
+                speeds: Mutex::new(BTreeMap::from([("FAN1".to_string(), 30), ("FAN2".to_string(), 30)])),
+                stuck_fan,
+                restored: Mutex::new(false),
+            }
+        }
+    }
+
+    impl SelfCheckIpmi for FakeIpmi {
+        fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+            Ok(parse_temperature_sdr(
+                "CPU1 Temp        | 0Eh | ok  |  3.1 | 52 degrees C\nInlet Temp       | 04h | ok  |  7.1 | 23 degrees C\n",
+                Utc::now(),
+            ))
+        }
+
+        fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
+            let output: String = self
+                .speeds
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(fan_id, percent)| {
+                    let rpm = if self.stuck_fan == Some(fan_id.as_str()) { 1500 } else { *percent as u32 * 50 };
+                    format!("{} | 30h | ok | 29.1 | {} RPM\n", fan_id, rpm)
+                })
+                .collect();
+            Ok(parse_fan_sdr(&output, Utc::now()))
+        }
+
+        fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+            self.speeds.lock().unwrap().insert(fan_id.to_string(), speed_percent);
+            Ok(())
+        }
+
+        fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+            *self.restored.lock().unwrap() = true;
+            Ok(())
+        }
+    }
+
+    fn config() -> StartupSelfCheckConfig {
+        StartupSelfCheckConfig {
+            enabled: true,
+            settle_secs: 0,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_self_check_passes_with_cooperating_bmc() {
+        let ipmi = FakeIpmi::new(None);
+
+        let report = run(&ipmi, &config(), 40).await;
+
+        assert!(report.passed(), "{:?}", report.failures);
+        assert_eq!(report.temperature_sensors, 2);
+        assert_eq!(report.nudged_percent, 50);
+        assert_eq!(report.fans.len(), 2);
+        assert!(report.fans.iter().all(|fan| fan.baseline_rpm == Some(2000) && fan.nudged_rpm == Some(2500)));
+        assert!(report.restored);
+        assert!(*ipmi.restored.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_self_check_fails_when_fan_does_not_respond() {
+        let ipmi = FakeIpmi::new(Some("FAN2"));
+
+        let report = run(&ipmi, &config(), 40).await;
+
+        assert!(!report.passed());
+        assert_eq!(report.failures.len(), 1);
+        assert!(report.failures[0].contains("FAN2"));
+        let stuck = report.fans.iter().find(|fan| fan.fan_id == "FAN2").unwrap();
+        assert!(!stuck.responded);
+        // 自检失败时同样交还BMC自动模式
+        assert!(*ipmi.restored.lock().unwrap());
+    }
+}
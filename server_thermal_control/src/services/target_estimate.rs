@@ -0,0 +1,183 @@
+use crate::models::analytics::NoiseLevel;
+use crate::models::control::ControlDecision;
+use crate::models::error::{AppError, AppResult};
+use crate::services::fan_cost::{estimate_fan_power_watts, estimate_noise_level};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 估算所需的最少控制决策数
+pub const MIN_ESTIMATE_DECISIONS: usize = 10;
+
+/// 推算控制增益时忽略的温差（°C），温差过小时比例项接近0，比值不可靠
+const MIN_GAIN_ERROR: f64 = 0.1;
+
+/// 目标温度估算请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetEstimateQuery {
+    /// 候选目标温度（°C）
+    pub temp_target: f64,
+}
+
+impl TargetEstimateQuery {
+    /// 验证请求，候选目标温度必须低于临界温度
+    ///
+    /// # 参数
+    /// * `critical_temperature` - 临界温度（°C）
+    pub fn validate(&self, critical_temperature: f64) -> AppResult<()> {
+        if !self.temp_target.is_finite() || self.temp_target >= critical_temperature {
+            return Err(AppError::validation_error(
+                "temp_target",
+                format!("目标温度必须低于临界温度 {:.1}°C", critical_temperature),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 一组风扇占空比下的代价
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OperatingCost {
+    /// 所有风扇的平均占空比（%）
+    pub average_fan_duty_percent: f64,
+    /// 全部风扇的估算功耗（W）
+    pub estimated_power_watts: f64,
+    /// 按平均占空比最高的风扇估算的噪音水平
+    pub noise_level: NoiseLevel,
+}
+
+/// 目标温度的功耗/噪音估算
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TargetEstimate {
+    /// 候选目标温度（°C）
+    pub temp_target: f64,
+    /// 参与估算的控制决策数
+    pub decisions: usize,
+    /// 参与估算的风扇数
+    pub fans: usize,
+    /// 由最近决策推算的控制增益：目标温度每降低1°C风扇占空比的增量（%）
+    pub duty_per_degree: f64,
+    /// 最近决策实际的代价
+    pub current: OperatingCost,
+    /// 候选目标温度下的估算代价
+    pub estimated: OperatingCost,
+}
+
+/// 根据最近的控制决策估算改用候选目标温度后的风扇占空比、功耗和噪音
+///
+/// 控制增益取各决策比例项与温差之比的中位数。每条决策的输出按目标温度的变化量
+/// 乘以增益平移，再限制在 `[0, 静音上限或100]` 内；功耗按风扇分别取平均后求和。
+/// 估算不考虑调速后温度本身的变化，只用于比较不同目标温度的相对代价
+///
+/// # 参数
+/// * `decisions` - 最近的控制决策
+/// * `temp_target` - 候选目标温度（°C）
+pub fn estimate(decisions: &[ControlDecision], temp_target: f64) -> AppResult<TargetEstimate> {
+    if decisions.len() < MIN_ESTIMATE_DECISIONS {
+        return Err(AppError::insufficient_data_error(MIN_ESTIMATE_DECISIONS, decisions.len()));
+    }
+    let mut gains: Vec<f64> = decisions
+        .iter()
+        .filter_map(|decision| {
+            let error = decision.input_temperature - decision.setpoint;
+            (error.abs() >= MIN_GAIN_ERROR).then(|| decision.proportional / error)
+        })
+        .filter(|gain| gain.is_finite() && *gain > 0.0)
+        .collect();
+    if gains.is_empty() {
+        return Err(AppError::insufficient_data_error(1, 0));
+    }
+    gains.sort_by(f64::total_cmp);
+    let duty_per_degree = gains[gains.len() / 2];
+
+    let mut current_duties: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    let mut estimated_duties: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for decision in decisions {
+        let ceiling = decision.quiet_cap.unwrap_or(100.0).min(100.0);
+        let estimated = decision.limited_output + duty_per_degree * (decision.setpoint - temp_target);
+        current_duties
+            .entry(decision.fan_id.as_str())
+            .or_default()
+            .push(decision.limited_output);
+        estimated_duties
+            .entry(decision.fan_id.as_str())
+            .or_default()
+            .push(estimated.clamp(0.0, ceiling));
+    }
+
+    Ok(TargetEstimate {
+        temp_target,
+        decisions: decisions.len(),
+        fans: current_duties.len(),
+        duty_per_degree,
+        current: operating_cost(&current_duties),
+        estimated: operating_cost(&estimated_duties),
+    })
+}
+
+/// 按各风扇的占空比样本计算代价
+fn operating_cost(duties: &BTreeMap<&str, Vec<f64>>) -> OperatingCost {
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let samples: Vec<f64> = duties.values().flatten().copied().collect();
+    let estimated_power_watts = duties
+        .values()
+        .map(|values| values.iter().map(|duty| estimate_fan_power_watts(*duty)).sum::<f64>() / values.len() as f64)
+        .sum();
+    let loudest = duties.values().map(|values| mean(values)).fold(0.0, f64::max);
+
+    OperatingCost {
+        average_fan_duty_percent: mean(&samples),
+        estimated_power_watts,
+        noise_level: estimate_noise_level(loudest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::control::ControlActionType;
+    use chrono::{Duration, Utc};
+
+    fn decision(fan_id: &str, temperature: f64, output: f64) -> ControlDecision {
+        let setpoint = 65.0;
+        ControlDecision {
+            timestamp: Utc::now() - Duration::seconds(10),
+            sensor_id: "CPU1_TEMP".to_string(),
+            fan_id: fan_id.to_string(),
+            input_temperature: temperature,
+            setpoint,
+            proportional: 4.0 * (temperature - setpoint),
+            integral: 0.0,
+            derivative: 0.0,
+            raw_output: output,
+            limited_output: output,
+            quiet_cap: None,
+            readback_percent: None,
+            action_type: ControlActionType::AutomaticControl,
+        }
+    }
+
+    #[test]
+    fn test_lower_target_costs_more_duty_and_power() {
+        let decisions: Vec<ControlDecision> = (0..12)
+            .map(|i| {
+                let temperature = 64.0 + (i % 4) as f64 * 0.5;
+                decision(if i % 2 == 0 { "FAN1" } else { "FAN2" }, temperature, 40.0 + (i % 4) as f64 * 2.0)
+            })
+            .collect();
+
+        let cooler = estimate(&decisions, 60.0).unwrap();
+        let warmer = estimate(&decisions, 70.0).unwrap();
+        assert_eq!(cooler.fans, 2);
+        assert_eq!(cooler.duty_per_degree, 4.0);
+        assert!(cooler.estimated.average_fan_duty_percent > warmer.estimated.average_fan_duty_percent);
+        assert!(cooler.estimated.estimated_power_watts > warmer.estimated.estimated_power_watts);
+        assert!(cooler.estimated.average_fan_duty_percent > cooler.current.average_fan_duty_percent);
+        assert_eq!(cooler.estimated.noise_level, NoiseLevel::High);
+        assert_eq!(warmer.estimated.noise_level, NoiseLevel::Low);
+
+        assert!(matches!(
+            estimate(&decisions[..3], 60.0),
+            Err(AppError::InsufficientDataError { .. })
+        ));
+    }
+}
@@ -0,0 +1,456 @@
+use crate::models::SystemEvent;
+use crate::services::event_store::SystemEventStore;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{error, warn};
+
+/// 后台任务看门狗配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskWatchdogConfig {
+    /// 是否启用，关闭时后台任务直接运行，崩溃或卡住后不再自动重启
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 超过该时长（秒）没有心跳即视为卡住
+    #[serde(default = "default_heartbeat_timeout_secs")]
+    pub heartbeat_timeout_secs: u64,
+    /// 检查心跳的间隔（秒）
+    #[serde(default = "default_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// 第一次重启前的等待时间（毫秒），之后每次连续重启翻倍
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// 重启等待时间上限（毫秒）
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    /// 统计窗口内允许的最大重启次数，超过后放弃重启该任务
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: usize,
+    /// 重启次数的统计窗口（秒）
+    #[serde(default = "default_restart_window_secs")]
+    pub restart_window_secs: u64,
+}
+
+impl Default for TaskWatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            heartbeat_timeout_secs: default_heartbeat_timeout_secs(),
+            check_interval_secs: default_check_interval_secs(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+            max_restarts: default_max_restarts(),
+            restart_window_secs: default_restart_window_secs(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_heartbeat_timeout_secs() -> u64 {
+    600
+}
+
+fn default_check_interval_secs() -> u64 {
+    10
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_max_backoff_ms() -> u64 {
+    60_000
+}
+
+fn default_max_restarts() -> usize {
+    5
+}
+
+fn default_restart_window_secs() -> u64 {
+    600
+}
+
+/// 后台任务的心跳
+///
+/// 任务在每个周期调用 `beat`，看门狗据此判断任务是否卡住
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    last: Arc<Mutex<Instant>>,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self {
+            last: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+}
+
+impl Heartbeat {
+    /// 记录一次心跳
+    pub fn beat(&self) {
+        *self.last.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    /// 距上次心跳的时长
+    pub fn elapsed(&self) -> Duration {
+        self.last.lock().unwrap_or_else(|e| e.into_inner()).elapsed()
+    }
+}
+
+/// 受监督任务的状态
+#[derive(Debug, Clone, Serialize)]
+pub struct SupervisedTaskStatus {
+    /// 任务名称
+    pub name: String,
+    /// 累计重启次数
+    pub restarts: u32,
+    /// 最近一次重启时间
+    pub last_restart_at: Option<DateTime<Utc>>,
+    /// 最近一次重启原因
+    pub last_restart_reason: Option<String>,
+    /// 重启过于频繁，已放弃重启
+    pub abandoned: bool,
+}
+
+/// 后台任务看门狗
+///
+/// 受监督的任务panic、被中止或超过心跳超时未上报心跳时，看门狗中止任务并按退避时间重新创建，
+/// 每次重启记录系统事件；统计窗口内重启次数超过上限时放弃该任务并记录严重事件。
+/// 任务收到取消信号后正常结束时监督随之结束
+pub struct TaskWatchdog {
+    config: TaskWatchdogConfig,
+    events: Arc<dyn SystemEventStore>,
+    tasks: Mutex<BTreeMap<String, SupervisedTaskStatus>>,
+}
+
+impl TaskWatchdog {
+    /// 创建后台任务看门狗
+    ///
+    /// # 参数
+    /// * `config` - 看门狗配置
+    /// * `events` - 系统事件存储
+    pub fn new(config: TaskWatchdogConfig, events: Arc<dyn SystemEventStore>) -> Self {
+        Self {
+            config,
+            events,
+            tasks: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// 配置的心跳超时
+    pub fn heartbeat_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.heartbeat_timeout_secs)
+    }
+
+    /// 所有受监督任务的状态，按名称排序
+    pub fn statuses(&self) -> Vec<SupervisedTaskStatus> {
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect()
+    }
+
+    /// 在看门狗监督下运行后台任务
+    ///
+    /// 每次重启都由 `spawn_task` 以新的心跳重新创建任务；看门狗未启用时直接运行一次
+    ///
+    /// # 参数
+    /// * `name` - 任务名称，用于状态和系统事件
+    /// * `heartbeat_timeout` - 心跳超时，为空时只检测panic和中止（适合周期很长的任务）
+    /// * `spawn_task` - 接收心跳并创建任务
+    pub fn supervise<F, Fut>(
+        self: &Arc<Self>,
+        name: &str,
+        heartbeat_timeout: Option<Duration>,
+        spawn_task: F,
+    ) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(Heartbeat) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if !self.config.enabled {
+            return tokio::spawn(spawn_task(Heartbeat::default()));
+        }
+
+        let watchdog = Arc::clone(self);
+        let name = name.to_string();
+        watchdog.tasks.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            name.clone(),
+            SupervisedTaskStatus {
+                name: name.clone(),
+                restarts: 0,
+                last_restart_at: None,
+                last_restart_reason: None,
+                abandoned: false,
+            },
+        );
+
+        tokio::spawn(async move {
+            let check_interval = Duration::from_secs(watchdog.config.check_interval_secs.max(1))
+                .min(heartbeat_timeout.unwrap_or(Duration::MAX));
+            let window = Duration::from_secs(watchdog.config.restart_window_secs);
+            let mut recent_restarts: VecDeque<Instant> = VecDeque::new();
+
+            loop {
+                let heartbeat = Heartbeat::default();
+                let mut task = tokio::spawn(spawn_task(heartbeat.clone()));
+                // 看门狗被取消时一并取消当前任务
+                let _abort = AbortOnDrop(task.abort_handle());
+
+                let reason = loop {
+                    tokio::select! {
+                        result = &mut task => match result {
+                            Ok(()) => return,
+                            Err(e) if e.is_panic() => break format!("panic: {}", panic_message(e.into_panic())),
+                            Err(_) => break "任务被中止".to_string(),
+                        },
+                        _ = tokio::time::sleep(check_interval) => {
+                            if let Some(timeout) = heartbeat_timeout {
+                                let elapsed = heartbeat.elapsed();
+                                if elapsed > timeout {
+                                    task.abort();
+                                    break format!("{:.0?} 未上报心跳", elapsed);
+                                }
+                            }
+                        }
+                    }
+                };
+
+                let now = Instant::now();
+                while recent_restarts.front().is_some_and(|at| now.duration_since(*at) > window) {
+                    recent_restarts.pop_front();
+                }
+                if recent_restarts.len() >= watchdog.config.max_restarts {
+                    watchdog.abandon(&name, &reason, recent_restarts.len()).await;
+                    return;
+                }
+
+                let backoff = watchdog.backoff(recent_restarts.len());
+                recent_restarts.push_back(now);
+                watchdog.record_restart(&name, &reason, backoff).await;
+                tokio::time::sleep(backoff).await;
+            }
+        })
+    }
+
+    /// 第 `attempt` 次连续重启前的等待时间
+    fn backoff(&self, attempt: usize) -> Duration {
+        let factor = 1u64.checked_shl(attempt.min(32) as u32).unwrap_or(u64::MAX);
+        Duration::from_millis(
+            self.config
+                .initial_backoff_ms
+                .saturating_mul(factor)
+                .min(self.config.max_backoff_ms),
+        )
+    }
+
+    /// 更新任务状态并记录重启事件
+    async fn record_restart(&self, name: &str, reason: &str, backoff: Duration) {
+        warn!("后台任务 {} 异常（{}），{:?} 后重启", name, reason, backoff);
+        let restarts = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            let status = tasks.entry(name.to_string()).or_insert_with(|| SupervisedTaskStatus {
+                name: name.to_string(),
+                restarts: 0,
+                last_restart_at: None,
+                last_restart_reason: None,
+                abandoned: false,
+            });
+            status.restarts += 1;
+            status.last_restart_at = Some(Utc::now());
+            status.last_restart_reason = Some(reason.to_string());
+            status.restarts
+        };
+
+        self.record_event(
+            "background_task_restarted",
+            "warning",
+            format!("后台任务 {} 已自动重启", name),
+            format!("后台任务 {} 异常（{}），{} 毫秒后重新启动", name, reason, backoff.as_millis()),
+            serde_json::json!({
+                "task": name,
+                "reason": reason,
+                "restarts": restarts,
+                "backoff_ms": backoff.as_millis() as u64,
+            }),
+        )
+        .await;
+    }
+
+    /// 标记任务已放弃并记录严重事件
+    async fn abandon(&self, name: &str, reason: &str, recent_restarts: usize) {
+        error!(
+            "后台任务 {} 在 {} 秒内已重启 {} 次，不再重启: {}",
+            name, self.config.restart_window_secs, recent_restarts, reason
+        );
+        if let Some(status) = self.tasks.lock().unwrap_or_else(|e| e.into_inner()).get_mut(name) {
+            status.abandoned = true;
+            status.last_restart_reason = Some(reason.to_string());
+        }
+
+        self.record_event(
+            "background_task_abandoned",
+            "critical",
+            format!("后台任务 {} 重启过于频繁，已停止", name),
+            format!(
+                "后台任务 {} 在 {} 秒内重启 {} 次后再次异常（{}），看门狗不再重启，需人工处理",
+                name, self.config.restart_window_secs, recent_restarts, reason
+            ),
+            serde_json::json!({
+                "task": name,
+                "reason": reason,
+                "recent_restarts": recent_restarts,
+            }),
+        )
+        .await;
+    }
+
+    async fn record_event(
+        &self,
+        event_type: &str,
+        event_level: &str,
+        title: String,
+        description: String,
+        metadata: serde_json::Value,
+    ) {
+        let event = SystemEvent {
+            id: uuid::Uuid::new_v4(),
+            event_type: event_type.to_string(),
+            event_level: event_level.to_string(),
+            title,
+            description,
+            source: "task_watchdog".to_string(),
+            metadata: Some(metadata),
+            created_at: Utc::now(),
+        };
+        if let Err(e) = self.events.record(event).await {
+            error!("Failed to record watchdog event: {}", e);
+        }
+    }
+}
+
+/// 离开作用域时取消任务
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::event_store::{InMemorySystemEventStore, SystemEventQuery};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_killed_task_is_respawned_and_heartbeat_resumes() {
+        let events = Arc::new(InMemorySystemEventStore::new());
+        let config = TaskWatchdogConfig {
+            heartbeat_timeout_secs: 1,
+            check_interval_secs: 1,
+            initial_backoff_ms: 10,
+            ..Default::default()
+        };
+        let watchdog = Arc::new(TaskWatchdog::new(config, events.clone()));
+
+        // 第一次运行panic，第二次运行卡住不再上报心跳，第三次起正常上报
+        let runs = Arc::new(AtomicUsize::new(0));
+        let beats = Arc::new(AtomicUsize::new(0));
+        let (task_runs, task_beats) = (runs.clone(), beats.clone());
+        let handle = watchdog.supervise("temperature_monitoring", Some(Duration::from_millis(200)), move |heartbeat| {
+            let run = task_runs.fetch_add(1, Ordering::SeqCst);
+            let beats = task_beats.clone();
+            async move {
+                match run {
+                    0 => panic!("sensor parse failed"),
+                    1 => std::future::pending::<()>().await,
+                    _ => loop {
+                        heartbeat.beat();
+                        beats.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                    },
+                }
+            }
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while beats.load(Ordering::SeqCst) < 3 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("任务重启后应恢复心跳");
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+        let statuses = watchdog.statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].restarts, 2);
+        assert!(!statuses[0].abandoned);
+        assert!(statuses[0].last_restart_reason.as_deref().unwrap().contains("未上报心跳"));
+
+        let (recorded, total) = events
+            .query(&SystemEventQuery {
+                event_type: Some("background_task_restarted".to_string()),
+                page: 1,
+                page_size: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+        assert!(recorded.iter().any(|event| event.description.contains("sensor parse failed")));
+
+        // 取消监督时一并停止任务
+        handle.abort();
+        let _ = handle.await;
+        let settled = beats.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(beats.load(Ordering::SeqCst), settled);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_restart_cap() {
+        let events = Arc::new(InMemorySystemEventStore::new());
+        let config = TaskWatchdogConfig {
+            initial_backoff_ms: 1,
+            max_restarts: 2,
+            ..Default::default()
+        };
+        let watchdog = Arc::new(TaskWatchdog::new(config, events.clone()));
+
+        watchdog
+            .supervise("alert_monitor", None, |_| async { panic!("boom") })
+            .await
+            .unwrap();
+
+        let status = &watchdog.statuses()[0];
+        assert_eq!(status.restarts, 2);
+        assert!(status.abandoned);
+        let (_, total) = events
+            .query(&SystemEventQuery {
+                level: Some("critical".to_string()),
+                page: 1,
+                page_size: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+    }
+}
@@ -0,0 +1,429 @@
+use crate::models::error::{AppError, AppResult};
+use crate::services::ipmi_service::TemperatureSensor;
+use crate::services::poll_ticker::{PollOverruns, PollTicker};
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::sensor_source::SensorSource;
+use crate::services::task_watchdog::Heartbeat;
+use crate::utils::math::MathUtils;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use tracing::warn;
+
+/// 温度分布直方图配置
+///
+/// 按间隔采集各温度传感器的读数，保留最近 `window_secs` 秒，按桶边界统计分布。
+/// 未配置 `boundaries` 时从 `exponential_start` 起按 `exponential_factor` 倍增生成
+/// `exponential_count` 个边界，低温段桶窄、高温段桶宽
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemperatureHistogramConfig {
+    /// 是否采集温度分布
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 统计窗口（秒），超出窗口的读数被丢弃
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    /// 采样间隔（秒）
+    #[serde(default = "default_sample_interval_secs")]
+    pub sample_interval_secs: u64,
+    /// 桶上边界（°C），为空时按指数生成
+    #[serde(default)]
+    pub boundaries: Vec<f64>,
+    /// 指数边界的第一个边界（°C）
+    #[serde(default = "default_exponential_start")]
+    pub exponential_start: f64,
+    /// 指数边界的倍增系数，须大于1
+    #[serde(default = "default_exponential_factor")]
+    pub exponential_factor: f64,
+    /// 指数边界的数量
+    #[serde(default = "default_exponential_count")]
+    pub exponential_count: usize,
+}
+
+impl Default for TemperatureHistogramConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            window_secs: default_window_secs(),
+            sample_interval_secs: default_sample_interval_secs(),
+            boundaries: Vec::new(),
+            exponential_start: default_exponential_start(),
+            exponential_factor: default_exponential_factor(),
+            exponential_count: default_exponential_count(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_window_secs() -> u64 {
+    86_400
+}
+
+fn default_sample_interval_secs() -> u64 {
+    60
+}
+
+fn default_exponential_start() -> f64 {
+    20.0
+}
+
+fn default_exponential_factor() -> f64 {
+    1.1
+}
+
+fn default_exponential_count() -> usize {
+    18
+}
+
+impl TemperatureHistogramConfig {
+    /// 生效的桶边界，升序、去重，忽略非有限值
+    pub fn bucket_boundaries(&self) -> Vec<f64> {
+        let mut boundaries: Vec<f64> = if self.boundaries.is_empty() && self.exponential_factor > 1.0 {
+            (0..self.exponential_count)
+                .map(|i| {
+                    let boundary = self.exponential_start * self.exponential_factor.powi(i as i32);
+                    (boundary * 10.0).round() / 10.0
+                })
+                .collect()
+        } else {
+            self.boundaries.clone()
+        };
+        boundaries.retain(|boundary| boundary.is_finite());
+        boundaries.sort_by(|a, b| a.total_cmp(b));
+        boundaries.dedup();
+        boundaries
+    }
+}
+
+/// 直方图的一个桶，统计 `lower < 温度 <= upper` 的读数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    /// 下边界（不含），第一个桶为空
+    pub lower: Option<f64>,
+    /// 上边界（含），最后一个桶为空
+    pub upper: Option<f64>,
+    pub count: usize,
+}
+
+/// 单个传感器在统计窗口内的温度分布
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemperatureHistogram {
+    pub sensor_id: String,
+    /// 窗口内的读数数量
+    pub samples: usize,
+    pub min: f64,
+    pub max: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+/// 按桶边界统计读数分布，分位数按原始读数线性插值计算
+///
+/// # 参数
+/// * `sensor_id` - 传感器ID
+/// * `values` - 温度读数
+/// * `boundaries` - 升序的桶上边界
+pub fn build_histogram(sensor_id: &str, values: &[f64], boundaries: &[f64]) -> AppResult<TemperatureHistogram> {
+    if values.is_empty() {
+        return Err(AppError::insufficient_data_error(1, 0));
+    }
+
+    let mut buckets: Vec<HistogramBucket> = (0..=boundaries.len())
+        .map(|i| HistogramBucket {
+            lower: i.checked_sub(1).map(|prev| boundaries[prev]),
+            upper: boundaries.get(i).copied(),
+            count: 0,
+        })
+        .collect();
+    for value in values {
+        let index = boundaries.partition_point(|boundary| boundary < value);
+        buckets[index].count += 1;
+    }
+
+    Ok(TemperatureHistogram {
+        sensor_id: sensor_id.to_string(),
+        samples: values.len(),
+        min: values.iter().copied().fold(f64::INFINITY, f64::min),
+        max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        p50: MathUtils::percentile(values, 50.0)?,
+        p95: MathUtils::percentile(values, 95.0)?,
+        p99: MathUtils::percentile(values, 99.0)?,
+        buckets,
+    })
+}
+
+/// 各传感器按时间排列的读数
+type SensorReadings = BTreeMap<String, VecDeque<(DateTime<Utc>, f64)>>;
+
+/// 温度分布采集器
+///
+/// 与阈值学习一样只采集状态为 `ok` 的读数；窗口按时间滚动，不受学习期限制
+#[derive(Debug, Clone)]
+pub struct TemperatureHistogramStore {
+    config: TemperatureHistogramConfig,
+    boundaries: Arc<Vec<f64>>,
+    samples: Arc<RwLock<SensorReadings>>,
+}
+
+impl TemperatureHistogramStore {
+    /// 创建温度分布采集器
+    ///
+    /// # 参数
+    /// * `config` - 温度分布直方图配置
+    pub fn new(config: TemperatureHistogramConfig) -> Self {
+        Self {
+            boundaries: Arc::new(config.bucket_boundaries()),
+            config,
+            samples: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// 直方图配置
+    pub fn config(&self) -> &TemperatureHistogramConfig {
+        &self.config
+    }
+
+    /// 生效的桶边界
+    pub fn boundaries(&self) -> &[f64] {
+        &self.boundaries
+    }
+
+    /// 记录一次采样并丢弃超出窗口的读数，返回计入的读数数量
+    ///
+    /// # 参数
+    /// * `sensors` - 温度传感器读数
+    /// * `now` - 采样时间
+    pub fn record(&self, sensors: &[TemperatureSensor], now: DateTime<Utc>) -> usize {
+        let cutoff = now - Duration::seconds(self.config.window_secs as i64);
+        let mut samples = self.samples.write().unwrap();
+        let mut recorded = 0;
+        for sensor in sensors {
+            if sensor.status != "ok" || !sensor.temperature.is_finite() {
+                continue;
+            }
+            samples
+                .entry(sensor.sensor_id.clone())
+                .or_default()
+                .push_back((now, sensor.temperature));
+            recorded += 1;
+        }
+        for values in samples.values_mut() {
+            while values.front().is_some_and(|(at, _)| *at <= cutoff) {
+                values.pop_front();
+            }
+        }
+        samples.retain(|_, values| !values.is_empty());
+        recorded
+    }
+
+    /// 各传感器最近 `window_secs` 秒的温度分布，按传感器ID排序
+    ///
+    /// # 参数
+    /// * `now` - 统计时间
+    /// * `window_secs` - 统计窗口（秒），为空或超过配置的窗口时使用配置值
+    pub fn histograms(&self, now: DateTime<Utc>, window_secs: Option<u64>) -> AppResult<Vec<TemperatureHistogram>> {
+        let window_secs = window_secs.map_or(self.config.window_secs, |secs| secs.min(self.config.window_secs));
+        let cutoff = now - Duration::seconds(window_secs as i64);
+        let samples = self.samples.read().unwrap();
+
+        let mut histograms = Vec::with_capacity(samples.len());
+        for (sensor_id, readings) in samples.iter() {
+            let values: Vec<f64> = readings
+                .iter()
+                .filter(|(at, _)| *at > cutoff && *at <= now)
+                .map(|(_, value)| *value)
+                .collect();
+            if !values.is_empty() {
+                histograms.push(build_histogram(sensor_id, &values, &self.boundaries)?);
+            }
+        }
+        Ok(histograms)
+    }
+
+    /// 各传感器在 `[start, end]` 内按时间排列的读数，窗口外的读数已被丢弃
+    ///
+    /// # 参数
+    /// * `start` - 开始时间
+    /// * `end` - 结束时间
+    pub fn readings(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> BTreeMap<String, Vec<(DateTime<Utc>, f64)>> {
+        let samples = self.samples.read().unwrap();
+        samples
+            .iter()
+            .map(|(sensor_id, readings)| {
+                let values: Vec<_> = readings
+                    .iter()
+                    .filter(|(at, _)| *at >= start && *at <= end)
+                    .copied()
+                    .collect();
+                (sensor_id.clone(), values)
+            })
+            .filter(|(_, values)| !values.is_empty())
+            .collect()
+    }
+
+    /// 按采样间隔持续采样，每个周期上报一次心跳
+    ///
+    /// # 参数
+    /// * `ipmi_service` - 传感器数据源
+    /// * `sensor_blacklist` - 传感器黑名单，黑名单中的传感器不参与统计
+    /// * `poll_overruns` - 采样超过周期时的跳过计数
+    /// * `heartbeat` - 看门狗心跳
+    pub async fn run_sampling(
+        self,
+        ipmi_service: Arc<dyn SensorSource>,
+        sensor_blacklist: SensorBlacklist,
+        poll_overruns: PollOverruns,
+        heartbeat: Heartbeat,
+    ) {
+        let mut ticker = PollTicker::new(
+            "temperature_histogram",
+            std::time::Duration::from_secs(self.config.sample_interval_secs.max(1)),
+            poll_overruns,
+        );
+
+        loop {
+            ticker.tick().await;
+            heartbeat.beat();
+            match ipmi_service.get_temperature_sensors() {
+                Ok(sensors) => {
+                    let sensors: Vec<TemperatureSensor> = sensors
+                        .into_iter()
+                        .filter(|sensor| !sensor_blacklist.contains(&sensor.sensor_id))
+                        .collect();
+                    self.record(&sensors, Utc::now());
+                }
+                Err(e) => warn!("温度分布采样读取温度传感器失败: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ipmi_service::FanSensor;
+
+    fn reading(sensor_id: &str, temperature: f64, status: &str, at: DateTime<Utc>) -> TemperatureSensor {
+        TemperatureSensor {
+            id: sensor_id.to_lowercase(),
+            sensor_id: sensor_id.to_string(),
+            temperature,
+            unit: "C".to_string(),
+            raw_unit: "C".to_string(),
+            location: "cpu".to_string(),
+            entity: "3.1".to_string(),
+            status: status.to_string(),
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_seeded_distribution_bucket_counts_and_percentiles() {
+        let store = TemperatureHistogramStore::new(TemperatureHistogramConfig {
+            window_secs: 3600,
+            boundaries: vec![60.0, 40.0, 50.0, 70.0, 80.0],
+            ..Default::default()
+        });
+        assert_eq!(store.boundaries(), [40.0, 50.0, 60.0, 70.0, 80.0]);
+
+        // 30.0 到 79.5°C、步长0.5的100个读数，按打乱的顺序每30秒一个
+        let start = Utc::now();
+        for i in 0..100u32 {
+            let temperature = 30.0 + ((i * 37) % 100) as f64 / 2.0;
+            let at = start + Duration::seconds(i as i64 * 30);
+            assert_eq!(store.record(&[reading("CPU1_TEMP", temperature, "ok", at)], at), 1);
+        }
+        // 越限读数不计入
+        assert_eq!(store.record(&[reading("CPU1_TEMP", 95.0, "cr", start)], start), 0);
+
+        let now = start + Duration::minutes(50);
+        let histograms = store.histograms(now, None).unwrap();
+        assert_eq!(histograms.len(), 1);
+        let cpu = &histograms[0];
+        assert_eq!(cpu.samples, 100);
+        assert_eq!((cpu.min, cpu.max), (30.0, 79.5));
+        let counts: Vec<usize> = cpu.buckets.iter().map(|bucket| bucket.count).collect();
+        // (-∞,40] 含40.0共21个，此后每桶20个，最高的读数79.5落在(70,80]
+        assert_eq!(counts, vec![21, 20, 20, 20, 19, 0]);
+        assert_eq!(cpu.buckets[0].lower, None);
+        assert_eq!(cpu.buckets[5], HistogramBucket { lower: Some(80.0), upper: None, count: 0 });
+        assert_eq!(cpu.p50, 54.75);
+        assert_eq!(cpu.p95, 77.025);
+        assert_eq!(cpu.p99, 79.005);
+
+        // 超出窗口的前50个读数被丢弃
+        let later = start + Duration::seconds(3600 + 49 * 30);
+        store.record(&[], later);
+        assert_eq!(store.histograms(later, None).unwrap()[0].samples, 50);
+    }
+
+    #[test]
+    fn test_exponential_boundaries() {
+        let config = TemperatureHistogramConfig::default();
+        let boundaries = config.bucket_boundaries();
+        assert_eq!(boundaries.len(), 18);
+        assert_eq!(&boundaries[..4], [20.0, 22.0, 24.2, 26.6]);
+        assert!(boundaries.windows(2).all(|pair| pair[1] > pair[0]));
+    }
+
+    /// 固定返回一组读数的数据源
+    struct FixedSource(Vec<TemperatureSensor>);
+
+    impl SensorSource for FixedSource {
+        fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+            Ok(self.0.clone())
+        }
+
+        fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
+            Ok(Vec::new())
+        }
+
+        fn set_fan_speed(&self, _fan_id: &str, _speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+            Err("只读数据源".into())
+        }
+
+        fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+            Ok(())
+        }
+
+        fn test_connection(&self) -> Result<bool, Box<dyn std::error::Error>> {
+            Ok(true)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sampling_task_reads_from_any_sensor_source() {
+        let now = Utc::now();
+        let source = FixedSource(vec![
+            reading("CPU1_TEMP", 55.0, "ok", now),
+            reading("PCH_TEMP", 70.0, "ok", now),
+        ]);
+        let store = TemperatureHistogramStore::new(TemperatureHistogramConfig::default());
+        let task = tokio::spawn(store.clone().run_sampling(
+            Arc::new(source),
+            SensorBlacklist::new(["PCH_TEMP".to_string()]),
+            PollOverruns::default(),
+            Heartbeat::default(),
+        ));
+
+        let mut histograms = Vec::new();
+        for _ in 0..100 {
+            histograms = store.histograms(Utc::now(), None).unwrap();
+            if !histograms.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        task.abort();
+
+        assert_eq!(histograms.len(), 1);
+        assert_eq!(histograms[0].sensor_id, "CPU1_TEMP");
+        assert_eq!(histograms[0].samples, 1);
+    }
+}
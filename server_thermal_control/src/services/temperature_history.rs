@@ -0,0 +1,117 @@
+use crate::models::error::{AppError, AppResult};
+use crate::services::temperature_histogram::TemperatureHistogramStore;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 历史温度单次查询允许的最大时间跨度默认值（小时）
+pub const DEFAULT_MAX_HISTORY_SPAN_HOURS: u32 = 24 * 7;
+
+/// 历史温度读数
+#[derive(Debug, Clone, Serialize)]
+pub struct TemperaturePoint {
+    /// 读数时间，降采样时为时间桶的起始时间
+    pub timestamp: DateTime<Utc>,
+    /// 温度（°C），降采样时为桶内平均值
+    pub temperature: f64,
+    /// 合并的原始读数数量
+    pub samples: usize,
+}
+
+/// 历史温度查询结果
+#[derive(Debug, Clone, Serialize)]
+pub struct TemperatureHistory {
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 降采样的时间桶长度（秒），未降采样时为空
+    pub resolution_secs: Option<i64>,
+    /// 各传感器按时间排列的读数
+    pub sensors: BTreeMap<String, Vec<TemperaturePoint>>,
+}
+
+/// 校验历史数据查询的时间范围
+///
+/// # 参数
+/// * `start_time` - 开始时间
+/// * `end_time` - 结束时间
+/// * `max_span_hours` - 允许的最大跨度（小时）
+pub fn validate_history_range(start_time: DateTime<Utc>, end_time: DateTime<Utc>, max_span_hours: u32) -> AppResult<()> {
+    if start_time >= end_time {
+        return Err(AppError::validation_error("start_time", "开始时间必须早于结束时间"));
+    }
+    if end_time - start_time > Duration::hours(i64::from(max_span_hours)) {
+        return Err(AppError::validation_error(
+            "end_time",
+            format!("查询时间跨度不能超过 {} 小时", max_span_hours),
+        ));
+    }
+    Ok(())
+}
+
+/// 按温度分布采集保留的读数查询历史温度
+///
+/// # 参数
+/// * `store` - 温度分布采集器
+/// * `range` - 查询时间段（开始, 结束），须已通过 [`validate_history_range`] 校验
+/// * `sensor_id` - 只返回该传感器的读数
+/// * `resolution` - 降采样的时间桶长度，为空时返回原始读数
+pub fn query_history(
+    store: &TemperatureHistogramStore,
+    (start_time, end_time): (DateTime<Utc>, DateTime<Utc>),
+    sensor_id: Option<&str>,
+    resolution: Option<Duration>,
+) -> AppResult<TemperatureHistory> {
+    if resolution.is_some_and(|resolution| resolution <= Duration::zero()) {
+        return Err(AppError::validation_error("resolution", "分辨率必须大于0"));
+    }
+
+    let sensors = store
+        .readings(start_time, end_time)
+        .into_iter()
+        .filter(|(id, _)| sensor_id.is_none_or(|sensor_id| sensor_id == id))
+        .map(|(id, readings)| {
+            let points = match resolution {
+                Some(resolution) => downsample(&readings, start_time, resolution),
+                None => readings
+                    .into_iter()
+                    .map(|(timestamp, temperature)| TemperaturePoint {
+                        timestamp,
+                        temperature,
+                        samples: 1,
+                    })
+                    .collect(),
+            };
+            (id, points)
+        })
+        .collect();
+
+    Ok(TemperatureHistory {
+        start_time,
+        end_time,
+        resolution_secs: resolution.map(|resolution| resolution.num_seconds()),
+        sensors,
+    })
+}
+
+/// 按时间桶对读数降采样，同一时间桶内的读数取平均值
+fn downsample(readings: &[(DateTime<Utc>, f64)], origin: DateTime<Utc>, bucket: Duration) -> Vec<TemperaturePoint> {
+    let bucket_ms = bucket.num_milliseconds().max(1);
+    let mut buckets: BTreeMap<i64, (f64, usize)> = BTreeMap::new();
+    for (timestamp, temperature) in readings {
+        let index = (*timestamp - origin).num_milliseconds().div_euclid(bucket_ms);
+        let (sum, count) = buckets.entry(index).or_default();
+        *sum += temperature;
+        *count += 1;
+    }
+
+    buckets
+        .into_iter()
+        .map(|(index, (sum, count))| TemperaturePoint {
+            timestamp: origin + Duration::milliseconds(index * bucket_ms),
+            temperature: sum / count as f64,
+            samples: count,
+        })
+        .collect()
+}
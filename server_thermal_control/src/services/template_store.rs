@@ -0,0 +1,304 @@
+use crate::models::alert::AlertTemplate;
+use crate::models::error::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+/// 告警模板列表排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertTemplateSort {
+    /// 按名称升序
+    #[default]
+    Name,
+    /// 按使用次数降序（最常用的模板在前）
+    UsageCount,
+}
+
+/// 告警模板存储
+///
+/// 抽象 `alert_templates` 的读写，使用次数的递增必须是原子的
+#[async_trait]
+pub trait AlertTemplateStore: Send + Sync {
+    /// 按指定方式排序列出模板
+    ///
+    /// # 参数
+    /// * `sort` - 排序方式
+    async fn list(&self, sort: AlertTemplateSort) -> AppResult<Vec<AlertTemplate>>;
+
+    /// 获取模板
+    ///
+    /// # 参数
+    /// * `template_id` - 模板ID
+    async fn get(&self, template_id: &str) -> AppResult<Option<AlertTemplate>>;
+
+    /// 新增或覆盖模板，不修改已有的使用次数
+    ///
+    /// # 参数
+    /// * `template` - 告警模板
+    async fn save(&self, template: &AlertTemplate) -> AppResult<()>;
+
+    /// 删除模板，返回是否存在
+    ///
+    /// # 参数
+    /// * `template_id` - 模板ID
+    async fn delete(&self, template_id: &str) -> AppResult<bool>;
+
+    /// 原子地将模板使用次数加一，返回递增后的次数
+    ///
+    /// # 参数
+    /// * `template_id` - 模板ID
+    async fn increment_usage(&self, template_id: &str) -> AppResult<u64>;
+}
+
+fn template_not_found(template_id: &str) -> AppError {
+    AppError::not_found_error("告警模板", template_id)
+}
+
+/// 进程内告警模板存储
+#[derive(Default)]
+pub struct InMemoryAlertTemplateStore {
+    templates: RwLock<HashMap<String, AlertTemplate>>,
+}
+
+impl InMemoryAlertTemplateStore {
+    /// 创建进程内模板存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AlertTemplateStore for InMemoryAlertTemplateStore {
+    async fn list(&self, sort: AlertTemplateSort) -> AppResult<Vec<AlertTemplate>> {
+        let templates = self.templates.read().await;
+        let mut list: Vec<AlertTemplate> = templates.values().cloned().collect();
+        match sort {
+            AlertTemplateSort::Name => list.sort_by(|a, b| a.name.cmp(&b.name)),
+            AlertTemplateSort::UsageCount => list.sort_by(|a, b| {
+                b.usage_count.cmp(&a.usage_count).then_with(|| a.name.cmp(&b.name))
+            }),
+        }
+        Ok(list)
+    }
+
+    async fn get(&self, template_id: &str) -> AppResult<Option<AlertTemplate>> {
+        Ok(self.templates.read().await.get(template_id).cloned())
+    }
+
+    async fn save(&self, template: &AlertTemplate) -> AppResult<()> {
+        let mut templates = self.templates.write().await;
+        let usage_count = templates.get(&template.id).map_or(template.usage_count, |t| t.usage_count);
+        templates.insert(
+            template.id.clone(),
+            AlertTemplate {
+                usage_count,
+                ..template.clone()
+            },
+        );
+        Ok(())
+    }
+
+    async fn delete(&self, template_id: &str) -> AppResult<bool> {
+        Ok(self.templates.write().await.remove(template_id).is_some())
+    }
+
+    async fn increment_usage(&self, template_id: &str) -> AppResult<u64> {
+        // 读取与递增在同一把写锁内完成
+        let mut templates = self.templates.write().await;
+        let template = templates
+            .get_mut(template_id)
+            .ok_or_else(|| template_not_found(template_id))?;
+        template.usage_count += 1;
+        Ok(template.usage_count)
+    }
+}
+
+/// PostgreSQL告警模板存储
+///
+/// 写入 `scripts/init.sql` 中定义的 `alert_templates` 表
+pub struct PgAlertTemplateStore {
+    pool: PgPool,
+}
+
+impl PgAlertTemplateStore {
+    /// 创建数据库模板存储
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    fn from_row(row: &sqlx::postgres::PgRow) -> AppResult<AlertTemplate> {
+        let severity: String = row.try_get("severity")?;
+        let usage_count: i64 = row.try_get("usage_count")?;
+
+        Ok(AlertTemplate {
+            id: row.try_get::<String, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            description: row.try_get::<Option<String>, _>("description")?,
+            alert_type: row.try_get::<String, _>("alert_type")?,
+            severity: serde_json::from_value(serde_json::Value::String(severity))?,
+            title_template: row.try_get::<String, _>("title_template")?,
+            message_template: row.try_get::<String, _>("message_template")?,
+            metadata_template: row.try_get::<Option<serde_json::Value>, _>("metadata_template")?,
+            created_at: row.try_get::<DateTime<Utc>, _>("created_at")?,
+            updated_at: row.try_get::<DateTime<Utc>, _>("updated_at")?,
+            usage_count: usage_count.max(0) as u64,
+        })
+    }
+}
+
+#[async_trait]
+impl AlertTemplateStore for PgAlertTemplateStore {
+    #[instrument(level = "debug", name = "db.alert_templates.list", skip_all)]
+    async fn list(&self, sort: AlertTemplateSort) -> AppResult<Vec<AlertTemplate>> {
+        let order_by = match sort {
+            AlertTemplateSort::Name => "name ASC",
+            AlertTemplateSort::UsageCount => "usage_count DESC, name ASC",
+        };
+        let rows = sqlx::query(&format!("SELECT * FROM alert_templates ORDER BY {}", order_by))
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    #[instrument(level = "debug", name = "db.alert_templates.get", skip_all)]
+    async fn get(&self, template_id: &str) -> AppResult<Option<AlertTemplate>> {
+        let row = sqlx::query("SELECT * FROM alert_templates WHERE id = $1")
+            .bind(template_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(Self::from_row).transpose()
+    }
+
+    #[instrument(level = "debug", name = "db.alert_templates.save", skip_all)]
+    async fn save(&self, template: &AlertTemplate) -> AppResult<()> {
+        let severity = serde_json::to_value(&template.severity)?
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO alert_templates
+                (id, name, description, alert_type, severity, title_template, message_template,
+                 metadata_template, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                description = EXCLUDED.description,
+                alert_type = EXCLUDED.alert_type,
+                severity = EXCLUDED.severity,
+                title_template = EXCLUDED.title_template,
+                message_template = EXCLUDED.message_template,
+                metadata_template = EXCLUDED.metadata_template,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(&template.id)
+        .bind(&template.name)
+        .bind(&template.description)
+        .bind(&template.alert_type)
+        .bind(severity)
+        .bind(&template.title_template)
+        .bind(&template.message_template)
+        .bind(&template.metadata_template)
+        .bind(template.created_at)
+        .bind(template.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "db.alert_templates.delete", skip_all)]
+    async fn delete(&self, template_id: &str) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM alert_templates WHERE id = $1")
+            .bind(template_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "db.alert_templates.increment_usage", skip_all)]
+    async fn increment_usage(&self, template_id: &str) -> AppResult<u64> {
+        // 由数据库完成读-改-写，并发创建告警时不会丢失计数
+        let usage_count: Option<i64> = sqlx::query_scalar(
+            "UPDATE alert_templates SET usage_count = usage_count + 1 WHERE id = $1 RETURNING usage_count",
+        )
+        .bind(template_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        usage_count
+            .map(|count| count.max(0) as u64)
+            .ok_or_else(|| template_not_found(template_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::alert::AlertSeverity;
+    use std::sync::Arc;
+
+    fn template(id: &str, name: &str) -> AlertTemplate {
+        let now = Utc::now();
+        AlertTemplate {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: None,
+            alert_type: "Temperature".to_string(),
+            severity: AlertSeverity::Warning,
+            title_template: "温度过高".to_string(),
+            message_template: "{{sensor}} 温度过高".to_string(),
+            metadata_template: None,
+            created_at: now,
+            updated_at: now,
+            usage_count: 0,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_increments_count_every_use() {
+        let store = Arc::new(InMemoryAlertTemplateStore::new());
+        store.save(&template("hot", "高温")).await.unwrap();
+        store.save(&template("fan", "风扇停转")).await.unwrap();
+
+        const N: usize = 32;
+        let tasks: Vec<_> = (0..N)
+            .map(|_| {
+                let store = Arc::clone(&store);
+                tokio::spawn(async move { store.increment_usage("hot").await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(store.get("hot").await.unwrap().unwrap().usage_count, N as u64);
+
+        // 覆盖保存模板不会清零使用次数
+        store.save(&template("hot", "高温告警")).await.unwrap();
+        let by_usage: Vec<String> = store
+            .list(AlertTemplateSort::UsageCount)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|template| template.id)
+            .collect();
+        assert_eq!(by_usage, ["hot", "fan"]);
+        assert_eq!(store.get("hot").await.unwrap().unwrap().usage_count, N as u64);
+
+        assert!(store.increment_usage("missing").await.is_err());
+    }
+}
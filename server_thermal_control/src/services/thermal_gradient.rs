@@ -0,0 +1,213 @@
+use crate::models::alert::AlertSeverity;
+use crate::models::error::{AppError, AppResult};
+use crate::services::breach_debounce::{track_breach, BreachStarts};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// 温差告警配置
+///
+/// 进风口与出风口、或两颗CPU之间温差过大通常意味着风道受阻或负载不均，
+/// 此时单个传感器可能都未超过告警阈值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThermalGradientConfig {
+    /// 温差规则
+    #[serde(default)]
+    pub rules: Vec<ThermalGradientRule>,
+}
+
+/// 两个传感器之间的温差规则
+///
+/// 两者最新读数之差的绝对值超过 `threshold` 并持续 `duration_secs` 秒时告警
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThermalGradientRule {
+    /// 规则ID
+    pub id: String,
+    /// 规则名称
+    pub name: String,
+    /// 第一个传感器ID，如进风口
+    pub sensor_a: String,
+    /// 第二个传感器ID，如出风口
+    pub sensor_b: String,
+    /// 温差阈值（°C）
+    pub threshold: f64,
+    /// 温差持续多久后告警（秒）
+    #[serde(default = "default_duration_secs")]
+    pub duration_secs: u64,
+    /// 告警级别
+    #[serde(default = "default_severity")]
+    pub severity: AlertSeverity,
+}
+
+fn default_duration_secs() -> u64 {
+    60
+}
+
+fn default_severity() -> AlertSeverity {
+    AlertSeverity::Warning
+}
+
+impl ThermalGradientConfig {
+    /// 校验规则：ID不重复，两个传感器不同，阈值为正数
+    pub fn validate(&self) -> AppResult<()> {
+        let mut ids = HashSet::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let field = |name: &str| format!("alert.gradient.rules[{}].{}", index, name);
+            if rule.id.trim().is_empty() || !ids.insert(rule.id.as_str()) {
+                return Err(AppError::validation_error(field("id"), "温差规则ID不能为空且不能重复"));
+            }
+            if rule.sensor_a.trim().is_empty() || rule.sensor_b.trim().is_empty() || rule.sensor_a == rule.sensor_b {
+                return Err(AppError::validation_error(field("sensor_b"), "温差规则需要两个不同的传感器"));
+            }
+            if !rule.threshold.is_finite() || rule.threshold <= 0.0 {
+                return Err(AppError::validation_error(field("threshold"), "温差阈值必须为正数"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ThermalGradientRule {
+    /// 告警源，由两个传感器ID组成
+    pub fn source(&self) -> String {
+        format!("{}/{}", self.sensor_a, self.sensor_b)
+    }
+}
+
+/// 持续超过阈值的温差
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThermalGradientBreach {
+    /// 触发的规则
+    pub rule: ThermalGradientRule,
+    /// 第一个传感器的温度
+    pub temperature_a: f64,
+    /// 第二个传感器的温度
+    pub temperature_b: f64,
+    /// 温差（绝对值）
+    pub gradient: f64,
+}
+
+#[derive(Debug, Default)]
+struct GradientState {
+    /// 各传感器最新温度
+    latest: HashMap<String, f64>,
+    breach_starts: BreachStarts,
+    /// 本次超限已告警的规则，温差回落后清除
+    alerted: HashSet<String>,
+}
+
+/// 温差检测
+///
+/// 每收到一个温度读数就重新计算包含该传感器的规则，温差持续超限时每次超限只返回一次
+#[derive(Debug, Default)]
+pub struct ThermalGradientMonitor {
+    rules: Vec<ThermalGradientRule>,
+    state: Mutex<GradientState>,
+}
+
+impl ThermalGradientMonitor {
+    /// 创建温差检测
+    ///
+    /// # 参数
+    /// * `config` - 温差告警配置
+    pub fn new(config: ThermalGradientConfig) -> Self {
+        Self {
+            rules: config.rules,
+            state: Mutex::default(),
+        }
+    }
+
+    /// 记录一个温度读数，返回温差持续超限达到告警时长的规则
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    /// * `temperature` - 温度（°C）
+    /// * `at` - 读数时间
+    pub fn observe(&self, sensor_id: &str, temperature: f64, at: DateTime<Utc>) -> Vec<ThermalGradientBreach> {
+        if self.rules.is_empty() || !temperature.is_finite() {
+            return Vec::new();
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.latest.insert(sensor_id.to_string(), temperature);
+
+        let mut breaches = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.sensor_a == sensor_id || rule.sensor_b == sensor_id) {
+            let (Some(&temperature_a), Some(&temperature_b)) =
+                (state.latest.get(&rule.sensor_a), state.latest.get(&rule.sensor_b))
+            else {
+                continue;
+            };
+            let gradient = (temperature_a - temperature_b).abs();
+            let breached = gradient > rule.threshold;
+            let held = track_breach(
+                &mut state.breach_starts,
+                (rule.id.clone(), rule.source()),
+                breached,
+                rule.duration_secs,
+                at,
+            );
+
+            if !breached {
+                state.alerted.remove(&rule.id);
+            } else if held && state.alerted.insert(rule.id.clone()) {
+                breaches.push(ThermalGradientBreach {
+                    rule: rule.clone(),
+                    temperature_a,
+                    temperature_b,
+                    gradient,
+                });
+            }
+        }
+        breaches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::alert_thresholds::{SensorAlertThreshold, ThresholdLevel};
+    use chrono::Duration;
+
+    #[test]
+    fn test_gradient_alert_fires_while_each_sensor_is_within_threshold() {
+        let rule = ThermalGradientRule {
+            id: "inlet_outlet".to_string(),
+            name: "进出风温差".to_string(),
+            sensor_a: "inlet".to_string(),
+            sensor_b: "outlet".to_string(),
+            threshold: 15.0,
+            duration_secs: 60,
+            severity: AlertSeverity::Warning,
+        };
+        let config = ThermalGradientConfig { rules: vec![rule] };
+        config.validate().unwrap();
+        let monitor = ThermalGradientMonitor::new(config);
+
+        // 两个传感器都低于单点告警阈值
+        let threshold = SensorAlertThreshold { warning: 75.0, critical: 85.0 };
+        let (inlet, outlet) = (35.0, 62.0);
+        assert_eq!(threshold.classify(inlet), ThresholdLevel::Within);
+        assert_eq!(threshold.classify(outlet), ThresholdLevel::Within);
+
+        let start = Utc::now();
+        let at = |seconds: i64| start + Duration::seconds(seconds);
+        assert!(monitor.observe("inlet", inlet, at(0)).is_empty());
+        // 温差27°C超过阈值，但持续时间不足
+        assert!(monitor.observe("outlet", outlet, at(0)).is_empty());
+        assert!(monitor.observe("outlet", outlet, at(30)).is_empty());
+
+        let breaches = monitor.observe("inlet", inlet, at(61));
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].rule.id, "inlet_outlet");
+        assert_eq!(breaches[0].gradient, 27.0);
+        // 持续超限期间不重复告警
+        assert!(monitor.observe("outlet", outlet, at(90)).is_empty());
+
+        // 温差回落后重新计时
+        assert!(monitor.observe("outlet", 45.0, at(100)).is_empty());
+        assert!(monitor.observe("outlet", outlet, at(110)).is_empty());
+        assert_eq!(monitor.observe("outlet", outlet, at(170)).len(), 1);
+    }
+}
@@ -3,13 +3,15 @@ use crate::models::sensor::TemperatureSensor;
 use crate::models::thermal::*;
 use crate::models::*;
 use crate::models::{AppError, AppResult};
-use crate::services::ipmi_service::IpmiService;
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::sensor_source::SensorSource;
 use crate::utils::math::*;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing::info;
 
 /// 温度服务
 ///
@@ -17,13 +19,19 @@ use tokio::sync::RwLock;
 #[derive(Clone)]
 pub struct ThermalService {
     /// IPMI服务
-    ipmi_service: Arc<IpmiService>,
+    ipmi_service: Arc<dyn SensorSource>,
     /// 温度阈值配置
     thresholds: Arc<RwLock<HashMap<String, TemperatureThreshold>>>,
     /// 温度历史数据
     temperature_history: Arc<RwLock<HashMap<String, Vec<TemperatureReading>>>>,
+    /// 压缩后的温度聚合数据（按传感器，按桶起始时间排序）
+    temperature_aggregates: Arc<RwLock<HashMap<String, Vec<TemperatureAggregate>>>>,
     /// 监控配置
     monitoring_config: Arc<RwLock<MonitoringConfig>>,
+    /// 传感器黑名单
+    sensor_blacklist: SensorBlacklist,
+    /// 统计和趋势计算所需的最少样本数
+    min_sample_count: usize,
     /// 数学工具
     math_utils: (),
 }
@@ -32,18 +40,56 @@ impl ThermalService {
     /// 创建新的温度服务
     ///
     /// # 参数
-    /// * `ipmi_service` - IPMI服务
+    /// * `ipmi_service` - 传感器数据源
     /// * `monitoring_config` - 监控配置
-    pub fn new(ipmi_service: Arc<IpmiService>, monitoring_config: MonitoringConfig) -> Self {
+    pub fn new(ipmi_service: Arc<dyn SensorSource>, monitoring_config: MonitoringConfig) -> Self {
         Self {
             ipmi_service,
             thresholds: Arc::new(RwLock::new(HashMap::new())),
             temperature_history: Arc::new(RwLock::new(HashMap::new())),
+            temperature_aggregates: Arc::new(RwLock::new(HashMap::new())),
             monitoring_config: Arc::new(RwLock::new(monitoring_config)),
+            sensor_blacklist: SensorBlacklist::default(),
+            min_sample_count: crate::config::DEFAULT_MIN_SAMPLE_COUNT,
             math_utils: (),
         }
     }
 
+    /// 设置统计所需的最少样本数
+    ///
+    /// # 参数
+    /// * `min_sample_count` - 最少样本数，取自 `analytics.min_sample_count`
+    pub fn with_min_sample_count(mut self, min_sample_count: usize) -> Self {
+        self.min_sample_count = min_sample_count;
+        self
+    }
+
+    /// 样本数不足时返回数据不足错误
+    ///
+    /// # 参数
+    /// * `required` - 最少样本数
+    /// * `actual` - 实际样本数
+    fn ensure_sample_count(required: usize, actual: usize) -> AppResult<()> {
+        if actual < required {
+            return Err(AppError::insufficient_data_error(required, actual));
+        }
+        Ok(())
+    }
+
+    /// 设置传感器黑名单
+    ///
+    /// # 参数
+    /// * `sensor_blacklist` - 传感器黑名单，与 `AppState` 共享以便运行时修改立即生效
+    pub fn with_sensor_blacklist(mut self, sensor_blacklist: SensorBlacklist) -> Self {
+        self.sensor_blacklist = sensor_blacklist;
+        self
+    }
+
+    /// 传感器黑名单，轮询和控制输入聚合时跳过其中的传感器
+    pub fn sensor_blacklist(&self) -> &SensorBlacklist {
+        &self.sensor_blacklist
+    }
+
     /// 获取当前温度读数
     ///
     /// # 参数
@@ -89,12 +135,17 @@ impl ThermalService {
     }
 
     /// 获取所有当前温度读数
+    ///
+    /// 黑名单中的传感器不读取，也不写入历史数据
     pub async fn get_all_current_temperatures(&self) -> AppResult<Vec<TemperatureReading>> {
         match self.ipmi_service.get_temperature_sensors().await {
             Ok(sensors) => {
                 let mut readings = Vec::new();
 
                 for sensor_data in sensors {
+                    if self.sensor_blacklist.contains(&sensor_data.sensor_id) {
+                        continue;
+                    }
                     let threshold = self.get_threshold(&sensor_data.sensor_id).await;
                     let status = if let Some(ref th) = threshold {
                         th.get_status(sensor_data.temperature)
@@ -211,7 +262,21 @@ impl ThermalService {
     ) -> AppResult<TemperatureStats> {
         let end_time = Utc::now();
         let start_time = end_time - chrono::Duration::hours(duration_hours as i64);
+        self.get_temperature_stats_in_range(sensor_id, start_time, end_time).await
+    }
 
+    /// 获取指定时间段内的温度统计信息
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    /// * `start_time` - 开始时间
+    /// * `end_time` - 结束时间
+    pub async fn get_temperature_stats_in_range(
+        &self,
+        sensor_id: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> AppResult<TemperatureStats> {
         let query = TemperatureQuery {
             sensor_ids: Some(vec![sensor_id.to_string()]),
             start_time: Some(start_time),
@@ -223,10 +288,7 @@ impl ThermalService {
         };
 
         let readings = self.get_temperature_history(&query).await?;
-
-        if readings.is_empty() {
-            return Err(AppError::not_found("指定时间范围内没有温度数据"));
-        }
+        Self::ensure_sample_count(self.min_sample_count, readings.len())?;
 
         let values: Vec<f64> = readings.iter().map(|r| r.value).collect();
 
@@ -289,12 +351,8 @@ impl ThermalService {
 
         let readings = self.get_temperature_history(&query).await?;
 
-        if readings.len() < 2 {
-            return Err(AppError::validation_error(
-                "data",
-                "数据点不足，无法计算趋势",
-            ));
-        }
+        // 线性回归至少需要两个点
+        Self::ensure_sample_count(self.min_sample_count.max(2), readings.len())?;
 
         // 准备线性回归数据
         let x_values: Vec<f64> = readings.iter().enumerate().map(|(i, _)| i as f64).collect();
@@ -376,6 +434,9 @@ impl ThermalService {
         let mut alerts = Vec::new();
 
         for reading in current_readings {
+            if self.sensor_blacklist.contains(&reading.sensor_id) {
+                continue;
+            }
             if matches!(
                 reading.status,
                 TemperatureStatus::Warning | TemperatureStatus::Critical
@@ -451,6 +512,200 @@ impl ThermalService {
         Ok(removed_count)
     }
 
+    /// 压缩历史温度数据
+    ///
+    /// 早于 `raw_retention_hours` 的原始读数压缩为小时聚合，
+    /// 早于 `hourly_retention_days` 的小时聚合合并为天聚合。返回被压缩的原始读数数量
+    pub async fn compact_history(&self) -> AppResult<usize> {
+        let (raw_cutoff, hourly_cutoff) = {
+            let config = self.monitoring_config.read().await;
+            let now = Utc::now();
+            (
+                now - chrono::Duration::hours(config.raw_retention_hours as i64),
+                now - chrono::Duration::days(config.hourly_retention_days as i64),
+            )
+        };
+
+        let mut history = self.temperature_history.write().await;
+        let mut aggregates = self.temperature_aggregates.write().await;
+        let mut compacted = 0;
+
+        for (sensor_id, readings) in history.iter_mut() {
+            let before = readings.len();
+            let hourly = Self::compact_readings(readings, raw_cutoff);
+            compacted += before - readings.len();
+
+            let sensor_aggregates = aggregates.entry(sensor_id.clone()).or_default();
+            Self::merge_aggregates(sensor_aggregates, hourly);
+            Self::rollup_aggregates(sensor_aggregates, hourly_cutoff);
+        }
+
+        if compacted > 0 {
+            info!("压缩历史温度读数: {} 条", compacted);
+        }
+        Ok(compacted)
+    }
+
+    /// 启动后台压缩任务
+    ///
+    /// 按 `compaction_interval` 周期执行 [`ThermalService::compact_history`]
+    pub async fn start_compaction_task(&self) -> tokio::task::JoinHandle<()> {
+        let service = self.clone();
+        let interval_secs = self.monitoring_config.read().await.compaction_interval.max(1);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.compact_history().await {
+                    tracing::warn!("压缩历史温度数据失败: {}", e);
+                }
+            }
+        })
+    }
+
+    /// 获取温度时间序列
+    ///
+    /// 近期范围返回原始读数，已压缩的早期范围返回聚合数据，结果按时间升序
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID
+    /// * `start_time` - 开始时间
+    /// * `end_time` - 结束时间
+    pub async fn get_temperature_series(
+        &self,
+        sensor_id: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> AppResult<Vec<TemperatureSeriesPoint>> {
+        if start_time >= end_time {
+            return Err(AppError::validation_error(
+                "start_time",
+                "开始时间必须早于结束时间",
+            ));
+        }
+
+        let history = self.temperature_history.read().await;
+        let aggregates = self.temperature_aggregates.read().await;
+
+        Ok(Self::build_series(
+            history.get(sensor_id).map(Vec::as_slice).unwrap_or_default(),
+            aggregates.get(sensor_id).map(Vec::as_slice).unwrap_or_default(),
+            start_time,
+            end_time,
+        ))
+    }
+
+    /// 将早于截止时间的原始读数压缩为小时聚合，并从原始读数中移除
+    ///
+    /// 截止时间向下对齐到整点，避免同一小时被拆成原始与聚合两部分
+    ///
+    /// # 参数
+    /// * `readings` - 原始读数
+    /// * `cutoff` - 截止时间
+    fn compact_readings(
+        readings: &mut Vec<TemperatureReading>,
+        cutoff: DateTime<Utc>,
+    ) -> Vec<TemperatureAggregate> {
+        let cutoff = AggregateResolution::Hourly.bucket_start(cutoff);
+        let mut buckets: Vec<TemperatureAggregate> = Vec::new();
+
+        readings.retain(|reading| {
+            if reading.timestamp >= cutoff {
+                return true;
+            }
+
+            let aggregate = TemperatureAggregate {
+                sensor_id: reading.sensor_id.clone(),
+                resolution: AggregateResolution::Hourly,
+                bucket_start: AggregateResolution::Hourly.bucket_start(reading.timestamp),
+                min: reading.temperature,
+                avg: reading.temperature,
+                max: reading.temperature,
+                sample_count: 1,
+            };
+            match buckets
+                .iter_mut()
+                .find(|bucket| bucket.bucket_start == aggregate.bucket_start)
+            {
+                Some(bucket) => bucket.merge(&aggregate),
+                None => buckets.push(aggregate),
+            }
+            false
+        });
+
+        buckets
+    }
+
+    /// 将新聚合合并到已有聚合中，保持按桶起始时间排序
+    ///
+    /// # 参数
+    /// * `existing` - 已有聚合
+    /// * `incoming` - 新聚合
+    fn merge_aggregates(existing: &mut Vec<TemperatureAggregate>, incoming: Vec<TemperatureAggregate>) {
+        for aggregate in incoming {
+            match existing.iter_mut().find(|a| {
+                a.resolution == aggregate.resolution && a.bucket_start == aggregate.bucket_start
+            }) {
+                Some(current) => current.merge(&aggregate),
+                None => existing.push(aggregate),
+            }
+        }
+        existing.sort_by_key(|a| a.bucket_start);
+    }
+
+    /// 将早于截止时间的小时聚合合并为天聚合
+    ///
+    /// # 参数
+    /// * `aggregates` - 单个传感器的聚合数据
+    /// * `cutoff` - 截止时间
+    fn rollup_aggregates(aggregates: &mut Vec<TemperatureAggregate>, cutoff: DateTime<Utc>) {
+        let cutoff = AggregateResolution::Daily.bucket_start(cutoff);
+        let (expired, kept): (Vec<_>, Vec<_>) = aggregates.drain(..).partition(|a| {
+            a.resolution == AggregateResolution::Hourly && a.bucket_start < cutoff
+        });
+        *aggregates = kept;
+
+        let daily = expired
+            .into_iter()
+            .map(|mut aggregate| {
+                aggregate.resolution = AggregateResolution::Daily;
+                aggregate.bucket_start = AggregateResolution::Daily.bucket_start(aggregate.bucket_start);
+                aggregate
+            })
+            .collect();
+        Self::merge_aggregates(aggregates, daily);
+    }
+
+    /// 组合原始读数与聚合数据为时间序列
+    ///
+    /// # 参数
+    /// * `raw` - 原始读数
+    /// * `aggregates` - 聚合数据
+    /// * `start_time` - 开始时间
+    /// * `end_time` - 结束时间
+    fn build_series(
+        raw: &[TemperatureReading],
+        aggregates: &[TemperatureAggregate],
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Vec<TemperatureSeriesPoint> {
+        let mut series: Vec<TemperatureSeriesPoint> = aggregates
+            .iter()
+            .filter(|a| a.bucket_start + a.resolution.duration() > start_time && a.bucket_start < end_time)
+            .map(TemperatureSeriesPoint::from)
+            .chain(
+                raw.iter()
+                    .filter(|r| r.timestamp >= start_time && r.timestamp <= end_time)
+                    .map(TemperatureSeriesPoint::from),
+            )
+            .collect();
+
+        series.sort_by_key(|point| point.timestamp);
+        series
+    }
+
     /// 更新单个温度历史记录
     async fn update_single_temperature_history(&self, reading: &TemperatureReading) {
         let mut history = self.temperature_history.write().await;
@@ -529,18 +784,7 @@ impl ThermalService {
             }
         }
 
-        if all_readings.is_empty() {
-            return Ok(TemperatureStats {
-                sensor_count: 0,
-                total_readings: 0,
-                average_temperature: 0.0,
-                min_temperature: 0.0,
-                max_temperature: 0.0,
-                temperature_trend: "stable".to_string(),
-                period_start: start_time.unwrap_or_else(Utc::now),
-                period_end: end_time.unwrap_or_else(Utc::now),
-            });
-        }
+        Self::ensure_sample_count(self.min_sample_count, all_readings.len())?;
 
         // 计算统计信息
         let temperatures: Vec<f64> = all_readings.iter().map(|r| r.temperature).collect();
@@ -725,6 +969,7 @@ pub enum AlertSeverity {
 mod tests {
     use super::*;
     use crate::models::config::IpmiConfig;
+    use crate::services::ipmi_service::IpmiService;
 
     #[tokio::test]
     async fn test_thermal_service_creation() {
@@ -759,9 +1004,114 @@ mod tests {
         assert_eq!(retrieved_threshold.unwrap().warning_temp, 70.0);
     }
 
+    fn reading_at(temperature: f64, timestamp: DateTime<Utc>) -> TemperatureReading {
+        TemperatureReading {
+            id: uuid::Uuid::new_v4(),
+            sensor_id: "CPU1".to_string(),
+            sensor_name: "CPU1 Temp".to_string(),
+            temperature,
+            status: TemperatureStatus::Normal,
+            timestamp,
+            server_id: "server-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_compaction_serves_aggregates_for_old_ranges() {
+        let now = AggregateResolution::Hourly.bucket_start(Utc::now());
+        let old_hour = now - chrono::Duration::hours(48);
+        let mut readings = vec![
+            reading_at(40.0, old_hour + chrono::Duration::minutes(5)),
+            reading_at(50.0, old_hour + chrono::Duration::minutes(20)),
+            reading_at(60.0, old_hour + chrono::Duration::minutes(40)),
+            reading_at(45.0, now - chrono::Duration::minutes(30)),
+            reading_at(47.0, now - chrono::Duration::minutes(10)),
+        ];
+
+        let mut aggregates = Vec::new();
+        let hourly = ThermalService::compact_readings(&mut readings, now - chrono::Duration::hours(24));
+        ThermalService::merge_aggregates(&mut aggregates, hourly);
+        ThermalService::rollup_aggregates(&mut aggregates, now - chrono::Duration::days(30));
+        assert_eq!(readings.len(), 2);
+        assert_eq!(aggregates.len(), 1);
+
+        let old = ThermalService::build_series(
+            &readings,
+            &aggregates,
+            old_hour - chrono::Duration::hours(1),
+            old_hour + chrono::Duration::hours(2),
+        );
+        assert_eq!(old.len(), 1);
+        assert_eq!(old[0].resolution, Some(AggregateResolution::Hourly));
+        assert_eq!(old[0].timestamp, old_hour);
+        assert_eq!((old[0].min, old[0].avg, old[0].max), (40.0, 50.0, 60.0));
+        assert_eq!(old[0].sample_count, 3);
+
+        let recent = ThermalService::build_series(&readings, &aggregates, now - chrono::Duration::hours(1), now);
+        assert_eq!(recent.len(), 2);
+        assert!(recent.iter().all(|point| point.resolution.is_none()));
+        assert_eq!(recent[0].avg, 45.0);
+    }
+
+    #[test]
+    fn test_rollup_merges_hourly_into_daily() {
+        let day = AggregateResolution::Daily.bucket_start(Utc::now()) - chrono::Duration::days(40);
+        let hourly = |hour: i64, avg: f64, count: u64| TemperatureAggregate {
+            sensor_id: "CPU1".to_string(),
+            resolution: AggregateResolution::Hourly,
+            bucket_start: day + chrono::Duration::hours(hour),
+            min: avg,
+            avg,
+            max: avg,
+            sample_count: count,
+        };
+        let mut aggregates = vec![hourly(1, 40.0, 1), hourly(2, 70.0, 2)];
+
+        ThermalService::rollup_aggregates(&mut aggregates, Utc::now() - chrono::Duration::days(30));
+        assert_eq!(aggregates.len(), 1);
+        assert_eq!(aggregates[0].resolution, AggregateResolution::Daily);
+        assert_eq!(aggregates[0].bucket_start, day);
+        assert_eq!(aggregates[0].avg, 60.0);
+        assert_eq!(aggregates[0].sample_count, 3);
+    }
+
     #[tokio::test]
     async fn test_temperature_stats_calculation() {
-        // 这里可以添加更多的单元测试
-        // 由于需要实际的IPMI连接，这些测试可能需要模拟数据
+        let ipmi_service = Arc::new(IpmiService::new(crate::services::ipmi_service::IpmiConfig::default()));
+        let thermal_service =
+            ThermalService::new(ipmi_service, MonitoringConfig::default()).with_min_sample_count(3);
+        let now = Utc::now();
+
+        thermal_service
+            .temperature_history
+            .write()
+            .await
+            .insert("CPU1".to_string(), vec![reading_at(50.0, now - chrono::Duration::minutes(3))]);
+
+        let err = thermal_service
+            .get_temperature_statistics(Some("CPU1"), None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::InsufficientDataError { required: 3, actual: 1 }));
+        assert_eq!(err.status_code(), 422);
+        let details = err.to_error_response(None).details.unwrap();
+        assert_eq!(details["insufficient_data"], true);
+
+        thermal_service.temperature_history.write().await.insert(
+            "CPU1".to_string(),
+            vec![
+                reading_at(50.0, now - chrono::Duration::minutes(3)),
+                reading_at(54.0, now - chrono::Duration::minutes(2)),
+                reading_at(58.0, now - chrono::Duration::minutes(1)),
+            ],
+        );
+
+        let stats = thermal_service
+            .get_temperature_statistics(Some("CPU1"), None, None)
+            .await
+            .unwrap();
+        assert_eq!(stats.total_readings, 3);
+        assert_eq!(stats.average_temperature, 54.0);
+        assert_eq!((stats.min_temperature, stats.max_temperature), (50.0, 58.0));
     }
 }
@@ -0,0 +1,430 @@
+use crate::models::error::AppResult;
+use crate::services::alert_thresholds::SensorAlertThreshold;
+use crate::services::ipmi_service::TemperatureSensor;
+use crate::services::poll_ticker::{PollOverruns, PollTicker};
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::sensor_source::SensorSource;
+use crate::services::task_watchdog::Heartbeat;
+use crate::services::temperature_histogram::TemperatureHistogram;
+use crate::utils::math::MathUtils;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+
+/// 阈值学习配置
+///
+/// 学习期内按间隔采集各温度传感器的读数，按分位数加余量给出告警阈值建议，
+/// 建议只供参考，需由运维人员确认后通过阈值接口应用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdLearningConfig {
+    /// 是否启用学习模式
+    #[serde(default)]
+    pub enabled: bool,
+    /// 学习期（天），从服务启动开始计算，期满后停止采样
+    #[serde(default = "default_learning_days")]
+    pub learning_days: u32,
+    /// 采样间隔（秒）
+    #[serde(default = "default_sample_interval_secs")]
+    pub sample_interval_secs: u64,
+    /// 警告阈值取用的分位数（0-100）
+    #[serde(default = "default_warning_percentile")]
+    pub warning_percentile: f64,
+    /// 严重阈值取用的分位数（0-100）
+    #[serde(default = "default_critical_percentile")]
+    pub critical_percentile: f64,
+    /// 警告阈值在分位数之上增加的余量（°C）
+    #[serde(default = "default_warning_margin")]
+    pub warning_margin: f64,
+    /// 严重阈值在分位数之上增加的余量（°C）
+    #[serde(default = "default_critical_margin")]
+    pub critical_margin: f64,
+    /// 给出建议所需的最少样本数，样本不足的传感器只报告进度
+    #[serde(default = "default_min_samples")]
+    pub min_samples: usize,
+}
+
+impl Default for ThresholdLearningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            learning_days: default_learning_days(),
+            sample_interval_secs: default_sample_interval_secs(),
+            warning_percentile: default_warning_percentile(),
+            critical_percentile: default_critical_percentile(),
+            warning_margin: default_warning_margin(),
+            critical_margin: default_critical_margin(),
+            min_samples: default_min_samples(),
+        }
+    }
+}
+
+fn default_learning_days() -> u32 {
+    7
+}
+
+fn default_sample_interval_secs() -> u64 {
+    60
+}
+
+fn default_warning_percentile() -> f64 {
+    95.0
+}
+
+fn default_critical_percentile() -> f64 {
+    99.0
+}
+
+fn default_warning_margin() -> f64 {
+    3.0
+}
+
+fn default_critical_margin() -> f64 {
+    5.0
+}
+
+fn default_min_samples() -> usize {
+    60
+}
+
+/// 阈值建议所依据的温度分布
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionSource {
+    /// 学习期内采集的样本
+    Learning,
+    /// 温度分布直方图统计窗口内的读数，使用其p95和p99
+    RecentWindow,
+}
+
+/// 单个传感器的阈值建议
+#[derive(Debug, Clone, Serialize)]
+pub struct ThresholdSuggestion {
+    pub sensor_id: String,
+    pub source: SuggestionSource,
+    /// 所依据分布的样本数
+    pub samples: usize,
+    pub min: f64,
+    pub max: f64,
+    /// 警告分位数对应的温度
+    pub warning_percentile_value: f64,
+    /// 严重分位数对应的温度
+    pub critical_percentile_value: f64,
+    /// 建议阈值，样本数不足 `min_samples` 时为空
+    pub suggested: Option<SensorAlertThreshold>,
+}
+
+/// 告警阈值学习器
+///
+/// 只采集状态为 `ok` 的读数，BMC已判定越限的读数不计入正常运行分布
+#[derive(Debug, Clone)]
+pub struct ThresholdLearner {
+    config: ThresholdLearningConfig,
+    started_at: DateTime<Utc>,
+    samples: Arc<RwLock<BTreeMap<String, Vec<f64>>>>,
+}
+
+impl ThresholdLearner {
+    /// 创建阈值学习器
+    ///
+    /// # 参数
+    /// * `config` - 阈值学习配置
+    /// * `started_at` - 学习期开始时间
+    pub fn new(config: ThresholdLearningConfig, started_at: DateTime<Utc>) -> Self {
+        Self {
+            config,
+            started_at,
+            samples: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// 学习模式配置
+    pub fn config(&self) -> &ThresholdLearningConfig {
+        &self.config
+    }
+
+    /// 学习期开始时间
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    /// 学习期结束时间
+    pub fn learning_until(&self) -> DateTime<Utc> {
+        self.started_at + Duration::days(self.config.learning_days as i64)
+    }
+
+    /// 指定时间是否处于学习期内
+    ///
+    /// # 参数
+    /// * `now` - 当前时间
+    pub fn is_learning(&self, now: DateTime<Utc>) -> bool {
+        self.config.enabled && now >= self.started_at && now < self.learning_until()
+    }
+
+    /// 单个传感器最多保留的样本数，按学习期和采样间隔计算
+    fn max_samples_per_sensor(&self) -> usize {
+        let period_secs = self.config.learning_days as u64 * 86_400;
+        (period_secs / self.config.sample_interval_secs.max(1)).max(1) as usize
+    }
+
+    /// 记录一次采样，返回计入的读数数量；学习期外不记录
+    ///
+    /// # 参数
+    /// * `sensors` - 温度传感器读数
+    /// * `now` - 采样时间
+    pub fn record(&self, sensors: &[TemperatureSensor], now: DateTime<Utc>) -> usize {
+        if !self.is_learning(now) {
+            return 0;
+        }
+
+        let max_samples = self.max_samples_per_sensor();
+        let mut samples = self.samples.write().unwrap();
+        let mut recorded = 0;
+        for sensor in sensors {
+            if sensor.status != "ok" || !sensor.temperature.is_finite() {
+                continue;
+            }
+            let values = samples.entry(sensor.sensor_id.clone()).or_default();
+            if values.len() < max_samples {
+                values.push(sensor.temperature);
+                recorded += 1;
+            }
+        }
+        recorded
+    }
+
+    /// 按已采集的分布计算各传感器的阈值建议，按传感器ID排序
+    ///
+    /// 警告阈值为警告分位数加警告余量，严重阈值为严重分位数加严重余量。
+    /// 学习样本不足 `min_samples` 而统计窗口内的读数足够时，改用窗口分布的p95和p99，
+    /// 未启用学习模式或学习期刚开始时也能给出建议
+    ///
+    /// # 参数
+    /// * `recent` - 各传感器最近统计窗口内的温度分布
+    pub fn suggest(&self, recent: &[TemperatureHistogram]) -> AppResult<Vec<ThresholdSuggestion>> {
+        let samples = self.samples.read().unwrap();
+        let recent: BTreeMap<&str, &TemperatureHistogram> =
+            recent.iter().map(|histogram| (histogram.sensor_id.as_str(), histogram)).collect();
+        let mut sensor_ids: Vec<&str> = samples.keys().map(String::as_str).chain(recent.keys().copied()).collect();
+        sensor_ids.sort_unstable();
+        sensor_ids.dedup();
+
+        let mut suggestions = Vec::with_capacity(sensor_ids.len());
+        for sensor_id in sensor_ids {
+            let learned = samples.get(sensor_id).filter(|values| !values.is_empty());
+            let window = recent.get(sensor_id).copied();
+            let learned_enough = learned.is_some_and(|values| values.len() >= self.config.min_samples);
+            let window_enough = window.is_some_and(|histogram| histogram.samples >= self.config.min_samples);
+
+            let suggestion = match (learned, window) {
+                (Some(values), _) if learned_enough || !window_enough => self.suggest_from_samples(sensor_id, values)?,
+                (_, Some(histogram)) => self.suggest_from_window(histogram),
+                _ => continue,
+            };
+            suggestions.push(suggestion);
+        }
+
+        Ok(suggestions)
+    }
+
+    /// 按学习样本给出建议
+    fn suggest_from_samples(&self, sensor_id: &str, values: &[f64]) -> AppResult<ThresholdSuggestion> {
+        let warning_percentile_value = MathUtils::percentile(values, self.config.warning_percentile)?;
+        let critical_percentile_value = MathUtils::percentile(values, self.config.critical_percentile)?;
+        Ok(self.suggestion(
+            sensor_id,
+            SuggestionSource::Learning,
+            values.len(),
+            (
+                values.iter().copied().fold(f64::INFINITY, f64::min),
+                values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            ),
+            warning_percentile_value,
+            critical_percentile_value,
+        ))
+    }
+
+    /// 按统计窗口的温度分布给出建议
+    fn suggest_from_window(&self, histogram: &TemperatureHistogram) -> ThresholdSuggestion {
+        self.suggestion(
+            &histogram.sensor_id,
+            SuggestionSource::RecentWindow,
+            histogram.samples,
+            (histogram.min, histogram.max),
+            histogram.p95,
+            histogram.p99,
+        )
+    }
+
+    fn suggestion(
+        &self,
+        sensor_id: &str,
+        source: SuggestionSource,
+        samples: usize,
+        (min, max): (f64, f64),
+        warning_percentile_value: f64,
+        critical_percentile_value: f64,
+    ) -> ThresholdSuggestion {
+        let suggested = SensorAlertThreshold {
+            warning: warning_percentile_value + self.config.warning_margin,
+            critical: critical_percentile_value + self.config.critical_margin,
+        };
+
+        ThresholdSuggestion {
+            sensor_id: sensor_id.to_string(),
+            source,
+            samples,
+            min,
+            max,
+            warning_percentile_value,
+            critical_percentile_value,
+            suggested: (samples >= self.config.min_samples && suggested.validate().is_ok()).then_some(suggested),
+        }
+    }
+
+    /// 在学习期内按采样间隔采样，每个周期上报一次心跳，学习期结束后返回
+    ///
+    /// # 参数
+    /// * `ipmi_service` - 传感器数据源
+    /// * `sensor_blacklist` - 传感器黑名单，黑名单中的传感器不参与学习
+    /// * `poll_overruns` - 采样超过周期时的跳过计数
+    /// * `heartbeat` - 看门狗心跳
+    pub async fn run_sampling(
+        self,
+        ipmi_service: Arc<dyn SensorSource>,
+        sensor_blacklist: SensorBlacklist,
+        poll_overruns: PollOverruns,
+        heartbeat: Heartbeat,
+    ) {
+        let mut ticker = PollTicker::new(
+            "threshold_learning",
+            std::time::Duration::from_secs(self.config.sample_interval_secs.max(1)),
+            poll_overruns,
+        );
+
+        loop {
+            ticker.tick().await;
+            heartbeat.beat();
+            let now = Utc::now();
+            if !self.is_learning(now) {
+                info!("告警阈值学习期已结束: {}", self.learning_until());
+                break;
+            }
+
+            match ipmi_service.get_temperature_sensors() {
+                Ok(sensors) => {
+                    let sensors: Vec<TemperatureSensor> = sensors
+                        .into_iter()
+                        .filter(|sensor| !sensor_blacklist.contains(&sensor.sensor_id))
+                        .collect();
+                    self.record(&sensors, now);
+                }
+                Err(e) => warn!("阈值学习读取温度传感器失败: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::temperature_histogram::build_histogram;
+
+    fn reading(sensor_id: &str, temperature: f64, status: &str, at: DateTime<Utc>) -> TemperatureSensor {
+        TemperatureSensor {
+            id: sensor_id.to_lowercase(),
+            sensor_id: sensor_id.to_string(),
+            temperature,
+            unit: "C".to_string(),
+            raw_unit: "C".to_string(),
+            location: "cpu".to_string(),
+            entity: "3.1".to_string(),
+            status: status.to_string(),
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_suggestions_are_percentiles_plus_margin() {
+        let started_at = Utc::now();
+        let learner = ThresholdLearner::new(
+            ThresholdLearningConfig {
+                enabled: true,
+                min_samples: 50,
+                ..Default::default()
+            },
+            started_at,
+        );
+        let now = started_at + Duration::hours(1);
+
+        // 40.0 到 90.0°C、步长0.5的101个读数，按打乱的顺序写入
+        for i in 0..=100u32 {
+            let temperature = 40.0 + ((i * 37) % 101) as f64 / 2.0;
+            assert_eq!(learner.record(&[reading("CPU1_TEMP", temperature, "ok", now)], now), 1);
+        }
+        // 越限读数和样本不足的传感器
+        assert_eq!(learner.record(&[reading("CPU1_TEMP", 105.0, "cr", now)], now), 0);
+        learner.record(&[reading("INLET_TEMP", 24.0, "ok", now)], now);
+
+        let suggestions = learner.suggest(&[]).unwrap();
+        let ids: Vec<&str> = suggestions.iter().map(|s| s.sensor_id.as_str()).collect();
+        assert_eq!(ids, vec!["CPU1_TEMP", "INLET_TEMP"]);
+
+        let cpu = &suggestions[0];
+        assert_eq!(cpu.source, SuggestionSource::Learning);
+        assert_eq!(cpu.samples, 101);
+        assert_eq!((cpu.min, cpu.max), (40.0, 90.0));
+        assert_eq!(cpu.warning_percentile_value, 87.5);
+        assert_eq!(cpu.critical_percentile_value, 89.5);
+        assert_eq!(
+            cpu.suggested,
+            Some(SensorAlertThreshold {
+                warning: 87.5 + 3.0,
+                critical: 89.5 + 5.0,
+            })
+        );
+        assert_eq!(suggestions[1].samples, 1);
+        assert!(suggestions[1].suggested.is_none());
+
+        // 学习期结束后不再记录
+        let after = learner.learning_until();
+        assert_eq!(learner.record(&[reading("CPU1_TEMP", 50.0, "ok", after)], after), 0);
+    }
+
+    #[test]
+    fn test_recent_window_percentiles_fill_in_when_learning_is_short() {
+        let now = Utc::now();
+        let learner = ThresholdLearner::new(
+            ThresholdLearningConfig {
+                enabled: true,
+                min_samples: 50,
+                ..Default::default()
+            },
+            now,
+        );
+        learner.record(&[reading("CPU1_TEMP", 60.0, "ok", now)], now);
+
+        let values: Vec<f64> = (0..100).map(|i| 30.0 + i as f64 / 2.0).collect();
+        let recent = vec![
+            build_histogram("CPU1_TEMP", &values, &[50.0]).unwrap(),
+            build_histogram("INLET_TEMP", &values[..10], &[50.0]).unwrap(),
+        ];
+
+        let suggestions = learner.suggest(&recent).unwrap();
+        let cpu = &suggestions[0];
+        assert_eq!(cpu.source, SuggestionSource::RecentWindow);
+        assert_eq!(cpu.samples, 100);
+        assert_eq!(cpu.warning_percentile_value, recent[0].p95);
+        assert_eq!(
+            cpu.suggested,
+            Some(SensorAlertThreshold {
+                warning: recent[0].p95 + 3.0,
+                critical: recent[0].p99 + 5.0,
+            })
+        );
+        // 窗口内读数同样不足时不给出建议
+        assert_eq!(suggestions[1].source, SuggestionSource::RecentWindow);
+        assert!(suggestions[1].suggested.is_none());
+    }
+}
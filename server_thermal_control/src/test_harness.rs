@@ -0,0 +1,440 @@
+//! 端到端测试工具
+//!
+//! 以 [`MockIpmiService`] 代替BMC构造完整的 `AppState`，按生产环境的路由启动actix应用，
+//! 测试中可以随时改写传感器读数，再通过HTTP接口断言结果
+
+use crate::config::{AppConfig, ConfigSources};
+use crate::middleware::auth::API_KEY_HEADER;
+use crate::services::alert_monitor::AlertMonitor;
+use crate::services::alert_notifier::AlertNotifier;
+use crate::services::alert_rules::AlertRuleStore;
+use crate::services::alert_store::AlertStore;
+use crate::services::alert_thresholds::AlertThresholdStore;
+use crate::services::alert_history_store::InMemoryAlertHistoryStore;
+use crate::services::alert_stats_store::InMemoryAlertStatisticsStore;
+use crate::services::annotation_store::InMemoryAnnotationStore;
+use crate::services::fan_snapshot_store::InMemoryFanSnapshotStore;
+use crate::services::event_store::{InMemorySystemEventStore, SystemEventStore};
+use crate::services::rule_state_store::InMemoryAlertRuleStateStore;
+use crate::services::control_loop::FanControlLoop;
+use crate::services::decision_store::{ControlDecisionLog, DEFAULT_RECENT_DECISIONS};
+use crate::services::download_store::DownloadStore;
+use crate::services::health_probe::HealthProbeService;
+use crate::services::incident_store::IncidentStore;
+use crate::services::ipmi_service::{
+    classify_sensor_location, BmcSelfTestStatus, FanSensor, Ipmi, ReadOnlyIpmi, ReadOnlyIpmiCommand, SystemInfo, TemperatureSensor,
+};
+use crate::services::jwt_secrets::JwtSecrets;
+use crate::services::poll_ticker::PollOverruns;
+use crate::services::result_cache::{AnalysisCache, InMemoryResultCache, ANALYSIS_CACHE_TTL_SECS};
+use crate::services::sensor_blacklist::SensorBlacklist;
+use crate::services::sensor_cache::LastGoodSensorCache;
+use crate::services::forced_poll::ForcedPollGate;
+use crate::services::sensor_calibration::{CalibratedIpmi, SensorCalibrations};
+use crate::services::sensor_source::SensorSource;
+use crate::services::task_watchdog::TaskWatchdog;
+use crate::services::temperature_histogram::TemperatureHistogramStore;
+use crate::services::threshold_learning::ThresholdLearner;
+use crate::utils::logging::RecentLogBuffer;
+use crate::{configure_routes, middleware, AppState};
+use actix_web::{http::StatusCode, test, web, App};
+use chrono::Utc;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// 测试使用的API密钥
+pub const TEST_API_KEY: &str = "test-api-key";
+
+/// 模拟BMC
+///
+/// 读数由测试脚本设置；风扇写入和交还自动模式的调用被记录下来供断言。
+/// 断开连接后所有读取都返回错误
+#[derive(Debug)]
+pub struct MockIpmiService {
+    state: Mutex<MockIpmiState>,
+}
+
+#[derive(Debug)]
+struct MockIpmiState {
+    connected: bool,
+    temperatures: Vec<TemperatureSensor>,
+    fans: Vec<FanSensor>,
+    /// 下发过的 (风扇ID, 转速百分比)
+    fan_speed_writes: Vec<(String, u8)>,
+    /// 交还自动模式的次数
+    automatic_restores: usize,
+    /// `ipmitool mc selftest` 的输出
+    self_test_output: String,
+}
+
+impl Default for MockIpmiService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockIpmiService {
+    /// 创建已连接、没有任何传感器的模拟BMC
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(MockIpmiState {
+                connected: true,
+                temperatures: Vec::new(),
+                fans: Vec::new(),
+                fan_speed_writes: Vec::new(),
+                automatic_restores: 0,
+                self_test_output: "Selftest: passed\n".to_string(),
+            }),
+        }
+    }
+
+    /// 一台正常运行的服务器：进风口、两颗CPU和两个风扇
+    pub fn healthy_server() -> Self {
+        let ipmi = Self::new();
+        ipmi.set_temperature("INLET_TEMP", 24.0);
+        ipmi.set_temperature("CPU1_TEMP", 52.0);
+        ipmi.set_temperature("CPU2_TEMP", 50.0);
+        ipmi.set_fan_rpm("FAN1", 6000);
+        ipmi.set_fan_rpm("FAN2", 6100);
+        ipmi
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockIpmiState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 设置温度读数，传感器不存在时新增，位置按名称归类
+    ///
+    /// # 参数
+    /// * `sensor_id` - 传感器ID，如 `CPU1_TEMP`
+    /// * `temperature` - 温度（°C）
+    pub fn set_temperature(&self, sensor_id: &str, temperature: f64) {
+        let mut state = self.lock();
+        match state.temperatures.iter_mut().find(|sensor| sensor.sensor_id == sensor_id) {
+            Some(sensor) => sensor.temperature = temperature,
+            None => state.temperatures.push(TemperatureSensor {
+                id: Uuid::new_v4().to_string(),
+                sensor_id: sensor_id.to_string(),
+                temperature,
+                unit: "°C".to_string(),
+                raw_unit: "degrees C".to_string(),
+                location: classify_sensor_location(sensor_id, "").to_string(),
+                entity: String::new(),
+                status: "ok".to_string(),
+                timestamp: Utc::now(),
+            }),
+        }
+    }
+
+    /// 设置风扇转速读数，风扇不存在时新增
+    ///
+    /// # 参数
+    /// * `fan_id` - 风扇ID，如 `FAN1`
+    /// * `speed_rpm` - 转速（RPM）
+    pub fn set_fan_rpm(&self, fan_id: &str, speed_rpm: u32) {
+        let mut state = self.lock();
+        let speed_percent = ((speed_rpm as f64 / 15000.0) * 100.0).min(100.0) as u8;
+        match state.fans.iter_mut().find(|fan| fan.fan_id == fan_id) {
+            Some(fan) => {
+                fan.speed_rpm = speed_rpm;
+                fan.speed_percent = speed_percent;
+            }
+            None => state.fans.push(FanSensor {
+                id: Uuid::new_v4().to_string(),
+                fan_id: fan_id.to_string(),
+                speed_rpm,
+                unit: "RPM".to_string(),
+                raw_unit: "RPM".to_string(),
+                speed_percent,
+                status: "ok".to_string(),
+                location: "Unknown Location".to_string(),
+                control_mode: "auto".to_string(),
+                target_temp: None,
+                controllable: true,
+                timestamp: Utc::now(),
+            }),
+        }
+    }
+
+    /// 模拟BMC连通或断开
+    pub fn set_connected(&self, connected: bool) {
+        self.lock().connected = connected;
+    }
+
+    /// 设置BMC自检输出
+    ///
+    /// # 参数
+    /// * `output` - `ipmitool mc selftest` 的输出
+    pub fn set_self_test_output(&self, output: &str) {
+        self.lock().self_test_output = output.to_string();
+    }
+
+    /// 下发过的风扇转速
+    pub fn fan_speed_writes(&self) -> Vec<(String, u8)> {
+        self.lock().fan_speed_writes.clone()
+    }
+
+    /// 交还自动模式的次数
+    pub fn automatic_restores(&self) -> usize {
+        self.lock().automatic_restores
+    }
+
+    /// 已连接时返回读数，断开时返回错误
+    fn read<T>(&self, read: impl FnOnce(&MockIpmiState) -> T) -> Result<T, Box<dyn std::error::Error>> {
+        let state = self.lock();
+        if !state.connected {
+            return Err("模拟BMC未连接".into());
+        }
+        Ok(read(&state))
+    }
+}
+
+impl ReadOnlyIpmi for MockIpmiService {
+    fn run_read_only(&self, command: ReadOnlyIpmiCommand) -> Result<String, Box<dyn std::error::Error>> {
+        self.read(|_| format!("mock output for `ipmitool {}`\n", command.as_command()))
+    }
+}
+
+impl Ipmi for MockIpmiService {
+    fn get_system_info(&self) -> Result<SystemInfo, Box<dyn std::error::Error>> {
+        self.read(|_| SystemInfo {
+            manufacturer: "Mock".to_string(),
+            device_id: "32".to_string(),
+            firmware_version: "1.0".to_string(),
+            ipmi_version: "2.0".to_string(),
+            product_name: "Mock Server".to_string(),
+            power_consumption: None,
+            voltage: None,
+            current: None,
+            raw_units: Default::default(),
+            mc_info: Default::default(),
+        })
+    }
+
+    fn get_self_test_status(&self) -> Result<BmcSelfTestStatus, Box<dyn std::error::Error>> {
+        self.read(|state| BmcSelfTestStatus::parse(&state.self_test_output))
+    }
+}
+
+impl SensorSource for MockIpmiService {
+    fn get_temperature_sensors(&self) -> Result<Vec<TemperatureSensor>, Box<dyn std::error::Error>> {
+        self.read(|state| state.temperatures.clone())
+    }
+
+    fn get_fan_sensors(&self) -> Result<Vec<FanSensor>, Box<dyn std::error::Error>> {
+        self.read(|state| state.fans.clone())
+    }
+
+    fn test_connection(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(self.lock().connected)
+    }
+
+    fn set_fan_speed(&self, fan_id: &str, speed_percent: u8) -> Result<(), Box<dyn std::error::Error>> {
+        self.read(|_| ())?;
+        self.lock().fan_speed_writes.push((fan_id.to_string(), speed_percent));
+        Ok(())
+    }
+
+    fn restore_automatic_fan_control(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.read(|_| ())?;
+        self.lock().automatic_restores += 1;
+        Ok(())
+    }
+}
+
+/// 端到端测试环境
+///
+/// 持有模拟BMC和应用状态，每次请求都以同一状态初始化应用，请求之间的缓存、阈值等状态保留
+pub struct TestHarness {
+    /// 模拟BMC
+    pub ipmi: Arc<MockIpmiService>,
+    /// 应用状态
+    pub state: AppState,
+}
+
+impl TestHarness {
+    /// 以默认配置和给定的模拟BMC创建测试环境，受保护端点使用 [`TEST_API_KEY`]
+    ///
+    /// # 参数
+    /// * `ipmi` - 模拟BMC
+    pub fn new(ipmi: MockIpmiService) -> Self {
+        let mut config = AppConfig::default();
+        config.security.api_key = TEST_API_KEY.to_string();
+        Self::with_config(ipmi, config)
+    }
+
+    /// 以给定配置创建测试环境
+    ///
+    /// # 参数
+    /// * `ipmi` - 模拟BMC
+    /// * `config` - 应用配置
+    pub fn with_config(ipmi: MockIpmiService, config: AppConfig) -> Self {
+        let ipmi = Arc::new(ipmi);
+        let sensor_calibrations = SensorCalibrations::default();
+        let calibrated = Arc::new(CalibratedIpmi::new(
+            Arc::clone(&ipmi) as Arc<dyn Ipmi>,
+            sensor_calibrations.clone(),
+        ));
+        let alert_rules = AlertRuleStore::with_defaults(
+            config.monitoring.alert_threshold_temp,
+            config.monitoring.alert_threshold_fan,
+        );
+        let alerts = AlertStore::new(config.alert.history_limit);
+        let incidents = IncidentStore::new(config.alert.correlation.clone());
+        let sensor_blacklist = SensorBlacklist::new(config.monitoring.sensor_blacklist.clone());
+        let control_decisions = ControlDecisionLog::new(DEFAULT_RECENT_DECISIONS);
+        let control_loop = FanControlLoop::new(
+            Arc::clone(&calibrated) as Arc<dyn SensorSource>,
+            sensor_blacklist.clone(),
+            &config.control,
+            control_decisions.clone(),
+        );
+        let system_events: Arc<dyn SystemEventStore> = Arc::new(InMemorySystemEventStore::new());
+        let state = AppState {
+            ipmi_service: calibrated,
+            raw_ipmi_service: Arc::clone(&ipmi) as Arc<dyn Ipmi>,
+            sensor_calibrations,
+            config_sources: Arc::new(ConfigSources::default()),
+            retention_service: None,
+            sensor_blacklist,
+            sensor_cache: LastGoodSensorCache::new(config.monitoring.last_good_max_age_secs),
+            forced_poll: ForcedPollGate::new(config.monitoring.forced_poll_min_interval_secs),
+            alert_thresholds: AlertThresholdStore::default(),
+            alert_monitor: AlertMonitor::new(alert_rules.clone(), alerts.clone(), incidents.clone()),
+            alert_notifier: AlertNotifier::from_config(&config.alert),
+            alert_rules,
+            alerts,
+            alert_history: Arc::new(InMemoryAlertHistoryStore::new()),
+            alert_statistics_store: Arc::new(InMemoryAlertStatisticsStore::new()),
+            alert_rule_states: Arc::new(InMemoryAlertRuleStateStore::new()),
+            system_events: Arc::clone(&system_events),
+            incidents,
+            annotations: Arc::new(InMemoryAnnotationStore::new()),
+            metrics_push: None,
+            downloads: DownloadStore::from_config(&config),
+            threshold_learner: ThresholdLearner::new(config.alert.threshold_learning.clone(), Utc::now()),
+            temperature_histograms: TemperatureHistogramStore::new(config.monitoring.temperature_histogram.clone()),
+            poll_overruns: PollOverruns::default(),
+            pid_autotune: Arc::new(tokio::sync::Mutex::new(())),
+            task_watchdog: Arc::new(TaskWatchdog::new(config.monitoring.watchdog.clone(), system_events)),
+            health_probes: HealthProbeService::new(config.health.probes.clone()),
+            analysis_cache: AnalysisCache::new(Arc::new(InMemoryResultCache::new(ANALYSIS_CACHE_TTL_SECS))),
+            fleet_summary: None,
+            jwt_secrets: JwtSecrets::new(config.security.jwt_secret.clone(), config.security.jwt_rotation_grace_secs),
+            recent_logs: RecentLogBuffer::new(16),
+            control_decisions,
+            control_loop,
+            fan_snapshots: Arc::new(InMemoryFanSnapshotStore::new()),
+            config: Arc::new(config),
+        };
+        Self { ipmi, state }
+    }
+
+    /// 发送请求，返回状态码和JSON响应体（非JSON响应体为 `Value::Null`）
+    ///
+    /// 请求都带有测试API密钥
+    ///
+    /// # 参数
+    /// * `request` - 测试请求
+    pub async fn call(&self, request: test::TestRequest) -> (StatusCode, Value) {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(self.state.clone()))
+                .wrap(middleware::fallback::method_not_allowed())
+                .configure(configure_routes)
+                .default_service(web::to(middleware::fallback::not_found)),
+        )
+        .await;
+
+        let request = request.insert_header((API_KEY_HEADER, TEST_API_KEY)).to_request();
+        let response = test::call_service(&app, request).await;
+        let status = response.status();
+        let body = test::read_body(response).await;
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    /// 发送GET请求
+    ///
+    /// # 参数
+    /// * `uri` - 请求路径
+    pub async fn get(&self, uri: &str) -> (StatusCode, Value) {
+        self.call(test::TestRequest::get().uri(uri)).await
+    }
+
+    /// 发送带JSON请求体的POST请求
+    ///
+    /// # 参数
+    /// * `uri` - 请求路径
+    /// * `body` - 请求体
+    pub async fn post_json(&self, uri: &str, body: Value) -> (StatusCode, Value) {
+        self.call(test::TestRequest::post().uri(uri).set_json(body)).await
+    }
+
+    /// 发送带JSON请求体的PUT请求
+    ///
+    /// # 参数
+    /// * `uri` - 请求路径
+    /// * `body` - 请求体
+    pub async fn put_json(&self, uri: &str, body: Value) -> (StatusCode, Value) {
+        self.call(test::TestRequest::put().uri(uri).set_json(body)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[actix_web::test]
+    async fn test_hot_sensor_surfaces_in_sensors_health_alerts_and_blocks_tuning() {
+        let harness = TestHarness::new(MockIpmiService::healthy_server());
+
+        let (status, body) = harness.get("/api/v1/system/health").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["overall_status"], "healthy");
+
+        // CPU1过热：超过临界温度85°C
+        harness.ipmi.set_temperature("CPU1_TEMP", 92.0);
+
+        let (status, body) = harness.get("/api/v1/sensors").await;
+        assert_eq!(status, StatusCode::OK);
+        let cpu1 = body["data"]["sensors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|sensor| sensor["sensor_id"] == "CPU1_TEMP")
+            .unwrap();
+        assert_eq!(cpu1["temperature"], 92.0);
+        assert_eq!(cpu1["location"], "cpu");
+
+        let (_, body) = harness.get("/api/v1/system/health").await;
+        assert_eq!(body["data"]["overall_status"], "critical");
+        assert!(body["data"]["issues"][0].as_str().unwrap().contains("CPU1_TEMP"));
+
+        let (status, body) = harness.get("/api/v1/alerts/preview").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["summary"]["critical"], 1);
+        let preview = body["data"]["sensors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|sensor| sensor["sensor_id"] == "CPU1_TEMP")
+            .unwrap();
+        assert_eq!(preview["level"], "critical");
+
+        // 温度已超过中止温度，自整定在下发任何转速前中止并交还自动模式
+        let (status, body) = harness
+            .post_json("/api/v1/control/pid/tune", json!({ "setpoint": 60.0 }))
+            .await;
+        assert!(!status.is_success());
+        assert_eq!(body["success"], false);
+        assert!(harness.ipmi.fan_speed_writes().is_empty());
+        assert_eq!(harness.ipmi.automatic_restores(), 1);
+
+        // 温度恢复后健康状态随之恢复
+        harness.ipmi.set_temperature("CPU1_TEMP", 55.0);
+        let (_, body) = harness.get("/api/v1/system/health").await;
+        assert_eq!(body["data"]["overall_status"], "healthy");
+    }
+}
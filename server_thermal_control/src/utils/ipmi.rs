@@ -9,8 +9,10 @@ use crate::models::{
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
 use std::process::{Command, Stdio};
 use std::time::Duration;
+use tokio::task::JoinSet;
 use tokio::time::timeout;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
@@ -704,6 +706,180 @@ impl IpmiClient {
         Ok(())
     }
 
+    /// 在指定服务器上执行ipmitool并返回标准输出，命令失败时返回错误
+    ///
+    /// # 参数
+    /// * `server` - 服务器配置
+    /// * `args` - ipmitool子命令及参数
+    async fn run_on(&self, server: &ServerTarget, args: &[&str]) -> AppResult<String> {
+        let mut cmd = Command::new(&self.config.ipmitool_path);
+        cmd.args([
+            "-I",
+            &server.interface,
+            "-H",
+            &server.host,
+            "-p",
+            &server.port.to_string(),
+            "-U",
+            &server.username,
+            "-P",
+            &server.password,
+        ]);
+        cmd.args(args);
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        let output = timeout(
+            Duration::from_secs(self.config.default_timeout),
+            tokio::task::spawn_blocking(move || cmd.output()),
+        )
+        .await
+        .map_err(|_| AppError::TimeoutError {
+            operation: "IPMI命令执行".to_string(),
+        })?
+        .map_err(|e| AppError::ipmi_error(format!("执行IPMI命令失败: {}", e)))?
+        .map_err(|e| AppError::ipmi_error(format!("IPMI命令执行错误: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(AppError::ipmi_error(format!(
+                "服务器 {} 执行 `ipmitool {}` 失败: {}",
+                server.id,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// 严格解析一次 `sdr list full` 的输出为全部传感器读数
+    ///
+    /// 任一行缺少列或读数既不是数值也不是 `na`/`disabled`/`no reading` 时整体失败，
+    /// 由调用方回退为逐个传感器读取
+    ///
+    /// # 参数
+    /// * `output` - `sdr list full` 输出
+    pub fn parse_sdr_batch(output: &str) -> AppResult<Vec<SensorData>> {
+        let mut sensors = Vec::new();
+
+        for line in output.lines().filter(|line| !line.trim().is_empty()) {
+            let parts: Vec<&str> = line.split('|').map(str::trim).collect();
+            if parts.len() < 3 || parts[0].is_empty() {
+                return Err(AppError::ipmi_error(format!("无法解析SDR行: {}", line.trim())));
+            }
+
+            let (sensor_name, value_str, status_str) = (parts[0], parts[1], parts[2]);
+            let readable = value_str
+                .split_whitespace()
+                .next()
+                .is_some_and(|value| value.parse::<f32>().is_ok());
+            let unavailable = matches!(value_str.to_lowercase().as_str(), "na" | "disabled" | "no reading");
+            if !readable && !unavailable {
+                return Err(AppError::ipmi_error(format!(
+                    "传感器 {} 的读数无法解析: {}",
+                    sensor_name, value_str
+                )));
+            }
+
+            let (value, unit) = Self::parse_sensor_value(value_str);
+            sensors.push(SensorData {
+                sensor_id: sensor_name.replace(' ', "_").to_lowercase(),
+                sensor_name: sensor_name.to_string(),
+                sensor_type: Self::determine_sensor_type(sensor_name),
+                current_value: value,
+                unit,
+                status: Self::parse_sensor_status(status_str),
+                thresholds: None,
+            });
+        }
+
+        if sensors.is_empty() {
+            return Err(AppError::ipmi_error("SDR输出中没有传感器读数"));
+        }
+        Ok(sensors)
+    }
+
+    /// 逐个读取单个传感器
+    ///
+    /// 解析 `sensor reading <名称>` 输出的 `名称 | 读数`
+    ///
+    /// # 参数
+    /// * `server` - 服务器配置
+    /// * `sensor_name` - 传感器名称
+    async fn read_sensor(&self, server: &ServerTarget, sensor_name: &str) -> AppResult<SensorData> {
+        let output = self.run_on(server, &["sensor", "reading", sensor_name]).await?;
+        let value_str = output
+            .lines()
+            .find_map(|line| line.split_once('|'))
+            .map(|(_, value)| value.trim())
+            .ok_or_else(|| AppError::ipmi_error(format!("传感器 {} 无读数", sensor_name)))?;
+
+        let (value, unit) = Self::parse_sensor_value(value_str);
+        let status = if value_str.parse::<f32>().is_ok() {
+            SensorStatus::Ok
+        } else {
+            SensorStatus::NotAvailable
+        };
+        Ok(SensorData {
+            sensor_id: sensor_name.replace(' ', "_").to_lowercase(),
+            sensor_name: sensor_name.to_string(),
+            sensor_type: Self::determine_sensor_type(sensor_name),
+            current_value: value,
+            unit,
+            status,
+            thresholds: None,
+        })
+    }
+
+    /// 读取一台服务器的全部传感器
+    ///
+    /// 启用 `batch_sdr` 时以单次 `sdr list full` 批量读取；批量解析失败或未启用时，
+    /// 按 `sdr list full` 中的传感器名称逐个读取，单个传感器失败只记录警告
+    ///
+    /// # 参数
+    /// * `server` - 服务器配置
+    pub async fn poll_sensors(&self, server: &ServerTarget) -> AppResult<Vec<SensorData>> {
+        let output = self.run_on(server, &["sdr", "list", "full"]).await?;
+
+        if self.config.batch_sdr {
+            match Self::parse_sdr_batch(&output) {
+                Ok(sensors) => return Ok(sensors),
+                Err(e) => warn!("服务器 {} 批量解析SDR失败，改为逐个读取传感器: {}", server.id, e),
+            }
+        }
+
+        let mut sensors = Vec::new();
+        for sensor_name in output
+            .lines()
+            .filter_map(|line| line.split('|').next())
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            match self.read_sensor(server, sensor_name).await {
+                Ok(sensor) => sensors.push(sensor),
+                Err(e) => warn!("服务器 {} 读取传感器 {} 失败: {}", server.id, sensor_name, e),
+            }
+        }
+        Ok(sensors)
+    }
+
+    /// 并发轮询所有已启用的服务器，并发数不超过 `max_concurrent_polls`
+    ///
+    /// 返回 `(服务器ID, 传感器读数)`，顺序与配置中的服务器顺序一致
+    pub async fn poll_all_servers(&self) -> Vec<(String, AppResult<Vec<SensorData>>)> {
+        let servers: Vec<ServerTarget> = self.config.servers.iter().filter(|s| s.enabled).cloned().collect();
+        let client = self.clone();
+
+        poll_concurrently(servers, self.config.max_concurrent_polls, move |server| {
+            let client = client.clone();
+            async move {
+                let sensors = client.poll_sensors(&server).await;
+                (server.id, sensors)
+            }
+        })
+        .await
+    }
+
     /// 断开连接
     ///
     /// 断开与当前服务器的连接
@@ -728,3 +904,114 @@ impl IpmiClient {
         self.current_server.is_some()
     }
 }
+
+/// 以有界并发轮询多个服务器，结果按输入顺序返回
+///
+/// # 参数
+/// * `servers` - 待轮询的服务器
+/// * `max_concurrent` - 最大并发数，为0时按1处理
+/// * `poll` - 单台服务器的轮询任务
+pub async fn poll_concurrently<T, F, Fut>(servers: Vec<ServerTarget>, max_concurrent: usize, poll: F) -> Vec<T>
+where
+    F: Fn(ServerTarget) -> Fut,
+    Fut: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let max_concurrent = max_concurrent.max(1);
+    let mut results: Vec<Option<T>> = (0..servers.len()).map(|_| None).collect();
+    let mut tasks = JoinSet::new();
+
+    for (index, server) in servers.into_iter().enumerate() {
+        if tasks.len() >= max_concurrent {
+            collect_poll(&mut tasks, &mut results).await;
+        }
+        let task = poll(server);
+        tasks.spawn(async move { (index, task.await) });
+    }
+    while !tasks.is_empty() {
+        collect_poll(&mut tasks, &mut results).await;
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+/// 等待一个轮询任务完成并写入对应位置，任务panic时记录错误并丢弃该服务器的结果
+async fn collect_poll<T: Send + 'static>(tasks: &mut JoinSet<(usize, T)>, results: &mut [Option<T>]) {
+    match tasks.join_next().await {
+        Some(Ok((index, result))) => results[index] = Some(result),
+        Some(Err(e)) => error!("服务器轮询任务异常退出: {}", e),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn server(id: &str) -> ServerTarget {
+        ServerTarget {
+            id: id.to_string(),
+            name: id.to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 623,
+            username: "admin".to_string(),
+            password: "admin".to_string(),
+            interface: "lanplus".to_string(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_batch_sdr_output_parsed_into_all_readings() {
+        let output = "\
+CPU1 Temp        | 45 degrees C      | ok
+Inlet Temp       | 23 degrees C      | ok
+FAN1             | 3600 RPM          | ok
+PSU2 Temp        | no reading        | ns
+12V              | 12.10 Volts       | ok
+";
+        let sensors = IpmiClient::parse_sdr_batch(output).unwrap();
+        assert_eq!(sensors.len(), 5);
+        assert_eq!(sensors[0].sensor_id, "cpu1_temp");
+        assert_eq!(sensors[0].current_value, 45.0);
+        assert!(matches!(sensors[2].sensor_type, SensorType::Fan));
+        assert_eq!(sensors[2].current_value, 3600.0);
+        assert_eq!(sensors[4].unit, "Volts");
+
+        // 无法解析的输出整体失败，交由逐个读取兜底
+        assert!(IpmiClient::parse_sdr_batch("CPU1 Temp | garbled | ok").is_err());
+        assert!(IpmiClient::parse_sdr_batch("Get SDR 0040 command failed").is_err());
+        assert!(IpmiClient::parse_sdr_batch("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_multi_chassis_polling_faster_than_sequential() {
+        let servers: Vec<ServerTarget> = ["chassis-a", "chassis-b", "chassis-c", "chassis-d"]
+            .into_iter()
+            .map(server)
+            .collect();
+        let slow_poll = |server: ServerTarget| async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            server.id
+        };
+
+        let started = Instant::now();
+        let sequential = poll_concurrently(servers.clone(), 1, slow_poll).await;
+        let sequential_elapsed = started.elapsed();
+
+        let started = Instant::now();
+        let concurrent = poll_concurrently(servers, 4, slow_poll).await;
+        let concurrent_elapsed = started.elapsed();
+
+        assert_eq!(sequential, concurrent);
+        assert_eq!(concurrent, vec!["chassis-a", "chassis-b", "chassis-c", "chassis-d"]);
+        assert!(sequential_elapsed >= Duration::from_millis(400));
+        assert!(
+            concurrent_elapsed * 2 < sequential_elapsed,
+            "concurrent {:?} vs sequential {:?}",
+            concurrent_elapsed,
+            sequential_elapsed
+        );
+    }
+}
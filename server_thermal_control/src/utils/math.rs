@@ -372,6 +372,18 @@ impl PidController {
         }
     }
 
+    /// 更新PID增益
+    ///
+    /// # 参数
+    /// * `kp` - 比例增益
+    /// * `ki` - 积分增益
+    /// * `kd` - 微分增益
+    pub fn set_gains(&mut self, kp: f64, ki: f64, kd: f64) {
+        self.kp = kp;
+        self.ki = ki;
+        self.kd = kd;
+    }
+
     /// 设置输出限制
     ///
     /// # 参数
@@ -397,6 +409,16 @@ impl PidController {
     /// * `process_value` - 当前过程值
     /// * `dt` - 时间间隔
     pub fn compute(&mut self, setpoint: f64, process_value: f64, dt: f64) -> f64 {
+        self.compute_terms(setpoint, process_value, dt).output
+    }
+
+    /// 计算控制输出并返回各项分量
+    ///
+    /// # 参数
+    /// * `setpoint` - 设定值
+    /// * `process_value` - 当前过程值
+    /// * `dt` - 时间间隔
+    pub fn compute_terms(&mut self, setpoint: f64, process_value: f64, dt: f64) -> PidTerms {
         let error = setpoint - process_value;
 
         // 比例项
@@ -422,14 +444,22 @@ impl PidController {
         self.previous_error = Some(error);
 
         // 计算总输出
-        let mut output = proportional + integral + derivative;
+        let raw_output = proportional + integral + derivative;
 
         // 应用输出限制
-        if let Some((min, max)) = self.output_limits {
-            output = output.clamp(min, max);
-        }
+        let output = match self.output_limits {
+            Some((min, max)) => raw_output.clamp(min, max),
+            None => raw_output,
+        };
 
-        output
+        PidTerms {
+            error,
+            proportional,
+            integral,
+            derivative,
+            raw_output,
+            output,
+        }
     }
 
     /// 重置控制器状态
@@ -449,6 +479,25 @@ impl PidController {
     }
 }
 
+/// PID计算分量
+///
+/// 单次PID计算中各项的贡献，便于调参时分析控制决策
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidTerms {
+    /// 误差（设定值 - 过程值）
+    pub error: f64,
+    /// 比例项贡献
+    pub proportional: f64,
+    /// 积分项贡献
+    pub integral: f64,
+    /// 微分项贡献
+    pub derivative: f64,
+    /// 限幅前输出
+    pub raw_output: f64,
+    /// 限幅后输出
+    pub output: f64,
+}
+
 /// 滑动窗口统计
 ///
 /// 维护一个固定大小的滑动窗口，并提供实时统计功能
@@ -547,6 +596,11 @@ impl SlidingWindowStats {
         self.window.len() == self.window_size
     }
 
+    /// 窗口大小
+    pub fn capacity(&self) -> usize {
+        self.window_size
+    }
+
     /// 清空窗口
     pub fn clear(&mut self) {
         self.window.clear();
@@ -13,6 +13,16 @@ pub mod config;
 /// 提供日志记录和管理功能
 // pub mod logger;
 
+/// 日志输出模块
+///
+/// 提供日志输出格式选择和敏感字段脱敏功能
+pub mod logging;
+
+/// 日志推送模块
+///
+/// 提供批量推送日志到HTTP日志采集端的功能
+pub mod log_shipper;
+
 /// 数学工具模块
 ///
 /// 提供数学计算和统计分析功能
@@ -23,6 +33,11 @@ pub mod math;
 /// 提供时间处理和格式化功能
 pub mod time;
 
+/// 环形缓冲模块
+///
+/// 提供按组件保留定长最近读数的缓冲
+pub mod ring_buffer;
+
 /// 验证工具模块
 ///
 /// 提供数据验证功能
@@ -33,6 +48,16 @@ pub mod validation;
 /// 提供加密和安全相关功能
 pub mod crypto;
 
+/// 内容协商模块
+///
+/// 按 `Accept` 头选择JSON或MessagePack响应编码
+pub mod negotiation;
+
+/// TLS模块
+///
+/// 提供HTTPS证书加载和最低协议版本配置
+pub mod tls;
+
 /// 网络工具模块
 ///
 /// 提供网络通信相关功能
@@ -0,0 +1,125 @@
+use crate::models::error::{AppError, AppResult};
+use actix_web::http::{header, StatusCode};
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+
+/// MessagePack响应的Content-Type
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// 响应编码格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    MessagePack,
+}
+
+impl ResponseFormat {
+    /// 按 `Accept` 头选择编码格式
+    ///
+    /// `Accept` 中列出 `application/msgpack`（或 `application/x-msgpack`）且 `q` 不为0时使用
+    /// MessagePack，其余情况一律返回JSON
+    ///
+    /// # 参数
+    /// * `req` - HTTP请求
+    /// * `msgpack_enabled` - 是否允许MessagePack（`server.msgpack_enabled`）
+    pub fn negotiate(req: &HttpRequest, msgpack_enabled: bool) -> Self {
+        if !msgpack_enabled {
+            return ResponseFormat::Json;
+        }
+
+        let accept = req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let wants_msgpack = accept.split(',').any(|item| {
+            let mut params = item.split(';').map(str::trim);
+            let media_type = params.next().unwrap_or_default();
+            let rejected = params.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q <= 0.0)
+            });
+            (media_type.eq_ignore_ascii_case(MSGPACK_CONTENT_TYPE)
+                || media_type.eq_ignore_ascii_case("application/x-msgpack"))
+                && !rejected
+        });
+
+        if wants_msgpack {
+            ResponseFormat::MessagePack
+        } else {
+            ResponseFormat::Json
+        }
+    }
+}
+
+/// 按协商结果编码响应体
+///
+/// MessagePack使用带字段名的map编码，解码后与JSON响应结构一致
+///
+/// # 参数
+/// * `req` - HTTP请求
+/// * `msgpack_enabled` - 是否允许MessagePack
+/// * `status` - 响应状态码
+/// * `body` - 响应体
+pub fn negotiated_response<T: Serialize>(
+    req: &HttpRequest,
+    msgpack_enabled: bool,
+    status: StatusCode,
+    body: &T,
+) -> AppResult<HttpResponse> {
+    let mut response = HttpResponse::build(status);
+    // 同一URL按Accept返回不同编码，缓存需要区分
+    response.insert_header((header::VARY, "Accept"));
+
+    match ResponseFormat::negotiate(req, msgpack_enabled) {
+        ResponseFormat::Json => Ok(response.json(body)),
+        ResponseFormat::MessagePack => {
+            let bytes = rmp_serde::to_vec_named(body)
+                .map_err(|e| AppError::serialization_error(format!("MessagePack编码失败: {}", e)))?;
+            Ok(response.content_type(MSGPACK_CONTENT_TYPE).body(bytes))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::api::ApiResponse;
+    use actix_web::body::to_bytes;
+    use actix_web::test::TestRequest;
+    use serde_json::json;
+
+    #[actix_web::test]
+    async fn test_msgpack_response_decodes_to_json_structure() {
+        let body = ApiResponse::success(json!({
+            "total": 2,
+            "sensors": [
+                {"sensor_id": "INLET_TEMP", "temperature": 23.5, "location": "inlet", "excluded": false},
+                {"sensor_id": "CPU1_TEMP", "temperature": 52.0, "location": "cpu", "excluded": true},
+            ],
+        }));
+
+        let json_req = TestRequest::default()
+            .insert_header((header::ACCEPT, "application/json"))
+            .to_http_request();
+        let json_resp = negotiated_response(&json_req, true, StatusCode::OK, &body).unwrap();
+        let json_bytes = to_bytes(json_resp.into_body()).await.unwrap();
+        let from_json: serde_json::Value = serde_json::from_slice(&json_bytes).unwrap();
+
+        let msgpack_req = TestRequest::default()
+            .insert_header((header::ACCEPT, "application/msgpack, application/json;q=0.5"))
+            .to_http_request();
+        let msgpack_resp = negotiated_response(&msgpack_req, true, StatusCode::OK, &body).unwrap();
+        assert_eq!(msgpack_resp.headers().get(header::CONTENT_TYPE).unwrap(), MSGPACK_CONTENT_TYPE);
+        let msgpack_bytes = to_bytes(msgpack_resp.into_body()).await.unwrap();
+        let from_msgpack: serde_json::Value = rmp_serde::from_slice(&msgpack_bytes).unwrap();
+
+        assert_eq!(from_msgpack, from_json);
+
+        // 关闭MessagePack后即使请求也返回JSON
+        let disabled = negotiated_response(&msgpack_req, false, StatusCode::OK, &body).unwrap();
+        assert_eq!(disabled.headers().get(header::CONTENT_TYPE).unwrap(), "application/json");
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::{HashMap, VecDeque};
+
+/// 每个组件默认保留的最近读数条数
+pub const DEFAULT_RECENT_READINGS_CAPACITY: usize = 120;
+
+/// 按组件分组的最近读数
+///
+/// 每个组件一个定长环形缓冲，写满后淘汰最旧的读数，内存占用与运行时长无关
+#[derive(Debug, Clone)]
+pub struct RecentReadings<T> {
+    capacity: usize,
+    buffers: HashMap<String, VecDeque<T>>,
+}
+
+impl<T> Default for RecentReadings<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECENT_READINGS_CAPACITY)
+    }
+}
+
+impl<T> RecentReadings<T> {
+    /// 创建最近读数缓冲
+    ///
+    /// # 参数
+    /// * `capacity` - 每个组件保留的读数条数，至少为1
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            buffers: HashMap::new(),
+        }
+    }
+
+    /// 每个组件保留的读数条数
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 修改每个组件保留的读数条数，缩小时立即丢弃多出的最旧读数
+    ///
+    /// # 参数
+    /// * `capacity` - 新的条数，至少为1
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        for buffer in self.buffers.values_mut() {
+            let excess = buffer.len().saturating_sub(self.capacity);
+            buffer.drain(..excess);
+            buffer.shrink_to(self.capacity);
+        }
+    }
+
+    /// 追加组件的一条读数，缓冲已满时淘汰最旧的读数
+    ///
+    /// # 参数
+    /// * `component_id` - 组件ID
+    /// * `reading` - 读数
+    pub fn push(&mut self, component_id: &str, reading: T) {
+        let capacity = self.capacity;
+        let buffer = self
+            .buffers
+            .entry(component_id.to_string())
+            .or_insert_with(|| VecDeque::with_capacity(capacity));
+        if buffer.len() == capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(reading);
+    }
+
+    /// 组件的最近读数，从旧到新
+    ///
+    /// # 参数
+    /// * `component_id` - 组件ID
+    pub fn recent(&self, component_id: &str) -> impl Iterator<Item = &T> {
+        self.buffers.get(component_id).into_iter().flatten()
+    }
+
+    /// 组件的最新一条读数
+    ///
+    /// # 参数
+    /// * `component_id` - 组件ID
+    pub fn latest(&self, component_id: &str) -> Option<&T> {
+        self.buffers.get(component_id).and_then(VecDeque::back)
+    }
+
+    /// 有读数的组件ID
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        self.buffers.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_most_recent_readings_are_retained() {
+        let mut readings = RecentReadings::new(3);
+        for value in 1..=10 {
+            readings.push("CPU1_TEMP", value);
+        }
+        readings.push("FAN1", 1800);
+
+        assert_eq!(readings.recent("CPU1_TEMP").copied().collect::<Vec<_>>(), vec![8, 9, 10]);
+        assert_eq!(readings.latest("CPU1_TEMP"), Some(&10));
+        assert_eq!(readings.recent("FAN1").copied().collect::<Vec<_>>(), vec![1800]);
+        assert_eq!(readings.recent("UNKNOWN").count(), 0);
+
+        // 缩小容量时丢弃最旧的读数
+        readings.set_capacity(2);
+        assert_eq!(readings.recent("CPU1_TEMP").copied().collect::<Vec<_>>(), vec![9, 10]);
+        readings.push("CPU1_TEMP", 11);
+        assert_eq!(readings.recent("CPU1_TEMP").copied().collect::<Vec<_>>(), vec![10, 11]);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::models::error::{AppError, AppResult};
+use rustls::crypto::ring;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::{version, ServerConfig, SupportedProtocolVersion};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// HTTPS配置
+///
+/// 启用后服务只以HTTPS监听，明文HTTP请求在握手阶段即被断开
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// 是否启用HTTPS
+    #[serde(default)]
+    pub enabled: bool,
+    /// PEM格式证书链路径
+    #[serde(default)]
+    pub cert_path: String,
+    /// PEM格式私钥路径
+    #[serde(default)]
+    pub key_path: String,
+    /// 允许的最低TLS版本
+    #[serde(default)]
+    pub min_version: TlsVersion,
+}
+
+/// TLS协议版本
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TlsVersion {
+    /// TLS 1.2
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+    /// TLS 1.3
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl TlsVersion {
+    /// 不低于该版本的协议
+    fn protocol_versions(self) -> &'static [&'static SupportedProtocolVersion] {
+        const TLS12_AND_LATER: &[&SupportedProtocolVersion] = &[&version::TLS13, &version::TLS12];
+        const TLS13_ONLY: &[&SupportedProtocolVersion] = &[&version::TLS13];
+        match self {
+            TlsVersion::Tls12 => TLS12_AND_LATER,
+            TlsVersion::Tls13 => TLS13_ONLY,
+        }
+    }
+}
+
+impl TlsConfig {
+    /// 校验证书和私钥路径
+    pub fn validate(&self) -> AppResult<()> {
+        if self.enabled && (self.cert_path.is_empty() || self.key_path.is_empty()) {
+            return Err(AppError::validation_error(
+                "server.tls",
+                "启用HTTPS时必须配置 cert_path 和 key_path",
+            ));
+        }
+        Ok(())
+    }
+
+    /// 加载证书和私钥，构建rustls服务端配置
+    pub fn load_server_config(&self) -> AppResult<ServerConfig> {
+        let certs = CertificateDer::pem_file_iter(&self.cert_path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| AppError::config_error(format!("读取TLS证书 {} 失败: {}", self.cert_path, e)))?;
+        if certs.is_empty() {
+            return Err(AppError::config_error(format!("TLS证书文件 {} 中没有证书", self.cert_path)));
+        }
+        let key = PrivateKeyDer::from_pem_file(&self.key_path)
+            .map_err(|e| AppError::config_error(format!("读取TLS私钥 {} 失败: {}", self.key_path, e)))?;
+
+        let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_protocol_versions(self.min_version.protocol_versions())
+            .map_err(|e| AppError::config_error(format!("TLS协议版本配置无效: {}", e)))?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|e| AppError::config_error(format!("TLS证书与私钥不匹配: {}", e)))?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use rustls::pki_types::ServerName;
+    use rustls::{ClientConfig, RootCertStore};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_rustls::TlsConnector;
+
+    const REQUEST: &[u8] = b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/tls/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[actix_web::test]
+    async fn test_tls_port_accepts_https_and_rejects_plain_http() {
+        let tls = TlsConfig {
+            enabled: true,
+            cert_path: fixture("cert.pem"),
+            key_path: fixture("key.pem"),
+            min_version: TlsVersion::Tls12,
+        };
+        tls.validate().unwrap();
+        let server = HttpServer::new(|| App::new().route("/health", web::get().to(|| async { HttpResponse::Ok().body("ok") })))
+            .workers(1)
+            .bind_rustls_0_23(("127.0.0.1", 0), tls.load_server_config().unwrap())
+            .unwrap();
+        let addr = server.addrs()[0];
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        // 信任测试证书的客户端可以完成握手并收到响应
+        let mut roots = RootCertStore::empty();
+        roots.add(CertificateDer::from_pem_file(fixture("cert.pem")).unwrap()).unwrap();
+        let client = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut https = TlsConnector::from(Arc::new(client))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+        https.write_all(REQUEST).await.unwrap();
+        let mut response = Vec::new();
+        let _ = https.read_to_end(&mut response).await;
+        let response = String::from_utf8_lossy(&response);
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("ok"), "{}", response);
+
+        // 明文请求在握手阶段被拒绝，收不到HTTP响应
+        let mut plain = TcpStream::connect(addr).await.unwrap();
+        plain.write_all(REQUEST).await.unwrap();
+        let mut response = Vec::new();
+        let _ = plain.read_to_end(&mut response).await;
+        assert!(!response.starts_with(b"HTTP/"), "{}", String::from_utf8_lossy(&response));
+
+        handle.stop(false).await;
+    }
+}
@@ -3,6 +3,8 @@
 //! 定义所有REST API端点和路由配置
 
 use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
     routing::{get, post, put, delete},
     Router,
 };
@@ -16,6 +18,20 @@ pub mod settings;
 
 use crate::AppState;
 use crate::handlers::test_script_handler;
+use crate::models::{ApiResponse, ValidationErrors};
+
+/// 验证失败时返回422，`data` 中携带全部字段错误
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        let body = ApiResponse {
+            success: false,
+            data: Some(self),
+            message: Some("请求参数验证失败".to_string()),
+            timestamp: chrono::Utc::now(),
+        };
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+    }
+}
 
 /// 创建API路由
 pub fn routes() -> Router<AppState> {
@@ -28,20 +44,28 @@ pub fn routes() -> Router<AppState> {
         // 测试用例管理路由
         .route("/test-cases", get(test_cases::list_test_cases))
         .route("/test-cases", post(test_cases::create_test_case))
+        .route("/test-cases/export", get(test_cases::export_test_cases))
+        .route("/test-cases/import", post(test_cases::import_test_cases))
         .route("/test-cases/:id", get(test_cases::get_test_case))
         .route("/test-cases/:id", put(test_cases::update_test_case))
         .route("/test-cases/:id", delete(test_cases::delete_test_case))
         .route("/test-cases/:id/run", post(test_cases::run_test_case))
+        .route("/test-cases/:id/runs/logs", get(test_cases::get_case_run_logs))
         
         // 测试运行记录路由
         .route("/test-runs", get(test_runs::list_test_runs))
         .route("/test-runs", post(test_runs::create_test_run))
         .route("/test-runs/:id", get(test_runs::get_test_run))
         .route("/test-runs/:id", put(test_runs::update_test_run))
+        .route("/test-runs/:id", delete(test_runs::delete_test_run))
         .route("/test-runs/:id/start", post(test_runs::start_test_run))
         .route("/test-runs/:id/stop", post(test_runs::stop_test_run))
+        .route("/test-runs/:id/priority", post(test_runs::set_test_run_priority))
         .route("/test-runs/:id/logs", get(test_runs::get_test_logs))
+        .route("/test-runs/:id/timeline", get(test_runs::get_test_timeline))
         .route("/test-runs/stats", get(test_runs::get_test_stats))
+        .route("/test-runs/queue", get(test_runs::get_test_queue))
+        .route("/test-runs/cancel-pending", post(test_runs::cancel_pending_test_runs))
         
         // 运行时管理器路由
         .route("/runtime-managers", get(runtime_managers::list_managers))
@@ -53,6 +77,7 @@ pub fn routes() -> Router<AppState> {
         .route("/runtime-managers/:id/test", post(runtime_managers::test_connection))
         .route("/runtime-managers/:id/info", get(runtime_managers::get_runtime_info))
         .route("/runtime-managers/:id/resources", get(runtime_managers::get_runtime_resources))
+        .route("/runtime-managers/:id/capabilities", get(runtime_managers::get_runtime_capabilities))
         .route("/runtime-managers/:id/health-check", post(runtime_managers::health_check))
         .route("/runtime-managers/platform-info", get(runtime_managers::get_platform_info))
         .route("/runtime-managers/setup-guide/:runtime_type", get(runtime_managers::get_setup_guide))
@@ -5,7 +5,7 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
 use uuid::Uuid;
 use serde_json::{json, Value};
@@ -13,13 +13,17 @@ use utoipa;
 use crate::{
     AppState,
     models::{
-        ApiResponse, PaginationParams, PaginatedResponse,
+        ApiResponse, PaginationParams, PaginatedResponse, ValidationErrors,
         runtime_manager::{
             RuntimeManager, CreateRuntimeManagerRequest, UpdateRuntimeManagerRequest, 
-            RuntimeManagerQuery, ManagerStatus
+            RuntimeManagerQuery, ManagerStatus, ProbeRefreshQuery, validate_runtime_config
         },
         RuntimeType
-    }
+    },
+    services::{
+        probe_cache::{ProbeCache, ProbeKind, ProbeResult, PLATFORM_SCOPE},
+        resource_headroom::ResourceHeadroom,
+    },
 };
 
 /// 分页获取运行时管理器列表
@@ -29,7 +33,7 @@ use crate::{
     tag = "runtime-managers",
     params(PaginationParams),
     responses(
-        (status = 200, description = "Runtime managers list", body = PaginatedResponse<RuntimeManager>),
+        (status = 200, description = "Runtime managers list", body = ApiResponse<PaginatedResponse<RuntimeManager>>),
         (status = 500, description = "Internal server error")
     )
 )]
@@ -37,7 +41,7 @@ pub async fn list_managers(
     Query(params): Query<PaginationParams>,
     Query(query): Query<RuntimeManagerQuery>,
     State(state): State<AppState>,
-) -> Result<Json<PaginatedResponse<RuntimeManager>>, StatusCode> {
+) -> Result<Json<ApiResponse<PaginatedResponse<RuntimeManager>>>, StatusCode> {
     let query_params = RuntimeManagerQuery {
         pagination: params,
         runtime_type: query.runtime_type,
@@ -59,7 +63,7 @@ pub async fn list_managers(
                     has_prev: result.pagination.page > 1,
                 },
             };
-            Ok(Json(response))
+            Ok(Json(ApiResponse::success(response)))
         }
         Err(e) => {
             tracing::error!("获取运行时管理器列表失败: {}", e);
@@ -76,17 +80,17 @@ pub async fn list_managers(
     request_body = CreateRuntimeManagerRequest,
     responses(
         (status = 201, description = "Runtime manager created", body = ApiResponse<RuntimeManager>),
-        (status = 400, description = "Invalid request"),
+        (status = 422, description = "Validation failed", body = ValidationErrors),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn create_manager(
     State(state): State<AppState>,
     Json(request): Json<CreateRuntimeManagerRequest>,
-) -> Result<Json<ApiResponse<RuntimeManager>>, StatusCode> {
+) -> Result<Json<ApiResponse<RuntimeManager>>, Response> {
     // 验证请求数据
-    if let Err(e) = validate_create_request(&request) {
-        return Ok(Json(ApiResponse::error(e)));
+    if let Err(errors) = validate_create_request(&request) {
+        return Err(errors.into_response());
     }
 
     match RuntimeManager::create(state.db.pool(), request).await {
@@ -100,7 +104,7 @@ pub async fn create_manager(
                 s if s.contains("UNIQUE constraint failed") => {
                     Ok(Json(ApiResponse::error("运行时管理器名称已存在".to_string())))
                 }
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR)
+                _ => Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
             }
         }
     }
@@ -145,32 +149,33 @@ pub async fn get_manager(
     responses(
         (status = 200, description = "Runtime manager updated", body = ApiResponse<RuntimeManager>),
         (status = 404, description = "Runtime manager not found", body = ApiResponse<String>),
-        (status = 400, description = "Invalid request", body = ApiResponse<String>)
+        (status = 422, description = "Validation failed", body = ValidationErrors)
     )
 )]
 pub async fn update_manager(
     Path(id): Path<Uuid>,
     State(state): State<AppState>,
     Json(request): Json<UpdateRuntimeManagerRequest>,
-) -> Result<Json<ApiResponse<RuntimeManager>>, StatusCode> {
-    // 验证请求数据
-    if let Err(e) = validate_update_request(&request) {
-        return Ok(Json(ApiResponse::error(e)));
-    }
-
+) -> Result<Json<ApiResponse<RuntimeManager>>, Response> {
     // 检查运行时管理器是否存在
-    match RuntimeManager::get_by_id(state.db.pool(), &id.to_string()).await {
-        Ok(Some(_manager)) => {},
+    let current = match RuntimeManager::get_by_id(state.db.pool(), &id.to_string()).await {
+        Ok(Some(manager)) => manager,
         Ok(None) => return Ok(Json(ApiResponse::error("运行时管理器不存在".to_string()))),
         Err(e) => {
             tracing::error!("检查运行时管理器存在性失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
+    };
+
+    // 验证请求数据
+    if let Err(errors) = validate_update_request(&request, &current) {
+        return Err(errors.into_response());
     }
 
     match RuntimeManager::update(state.db.pool(), &id.to_string(), request).await {
         Ok(manager) => {
             tracing::info!("更新运行时管理器成功: {} ({})", manager.name, manager.id);
+            state.probe_cache.invalidate(&manager.id);
             Ok(Json(ApiResponse::success(manager)))
         }
         Err(e) => {
@@ -179,7 +184,7 @@ pub async fn update_manager(
                 s if s.contains("UNIQUE constraint failed") => {
                     Ok(Json(ApiResponse::error("运行时管理器名称已存在".to_string())))
                 }
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR)
+                _ => Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
             }
         }
     }
@@ -217,6 +222,7 @@ pub async fn delete_manager(
     match RuntimeManager::delete(state.db.pool(), &id.to_string()).await {
         Ok(()) => {
             tracing::info!("删除运行时管理器成功: {}", id);
+            state.probe_cache.invalidate(&id.to_string());
             Ok(Json(ApiResponse::success("运行时管理器删除成功".to_string())))
         }
         Err(e) => {
@@ -261,7 +267,8 @@ pub async fn heartbeat(
     path = "/runtime-managers/{id}/test",
     tag = "runtime-managers",
     params(
-        ("id" = Uuid, Path, description = "Runtime manager ID")
+        ("id" = Uuid, Path, description = "Runtime manager ID"),
+        ProbeRefreshQuery
     ),
     responses(
         (status = 200, description = "Connection test result", body = ApiResponse<Value>),
@@ -270,6 +277,7 @@ pub async fn heartbeat(
 )]
 pub async fn test_connection(
     Path(id): Path<Uuid>,
+    Query(query): Query<ProbeRefreshQuery>,
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<Value>>, StatusCode> {
     // 获取运行时管理器信息
@@ -284,16 +292,22 @@ pub async fn test_connection(
         unreachable!()
     };
 
-    // 执行连接测试
-    let test_result = test_runtime_connection(&manager).await.unwrap_or_else(|e| {
-        tracing::error!("测试运行时连接失败: {}", e);
-        json!({
-                "success": false,
-                "message": format!("连接测试失败: {}", e),
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "details": null
+    // 执行连接测试，有效期内返回缓存的结果
+    let test_result = state
+        .probe_cache
+        .get_or_probe(&manager.id, ProbeKind::Connection, query.refresh, || async {
+            test_runtime_connection(&manager).await.unwrap_or_else(|e| {
+                tracing::error!("测试运行时连接失败: {}", e);
+                json!({
+                    "success": false,
+                    "message": format!("连接测试失败: {}", e),
+                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                    "details": null
+                })
             })
-    });
+        })
+        .await
+        .into_value();
 
     // 根据测试结果更新管理器状态
     let new_status = if test_result["success"].as_bool().unwrap_or(false) {
@@ -315,13 +329,17 @@ pub async fn test_connection(
     get,
     path = "/runtime-managers/platform-info",
     tag = "runtime-managers",
+    params(ProbeRefreshQuery),
     responses(
         (status = 200, description = "Platform information", body = ApiResponse<Value>)
     )
 )]
-pub async fn get_platform_info() -> Result<Json<ApiResponse<Value>>, StatusCode> {
-    let platform_info = detect_platform_capabilities().await;
-    Ok(Json(ApiResponse::success(platform_info)))
+pub async fn get_platform_info(
+    Query(query): Query<ProbeRefreshQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Value>>, StatusCode> {
+    let platform_info = cached_platform_capabilities(&state.probe_cache, query.refresh).await;
+    Ok(Json(ApiResponse::success(platform_info.into_value())))
 }
 
 /// 获取设置指引
@@ -356,7 +374,8 @@ pub async fn get_setup_guide(
     path = "/runtime-managers/{id}/health-check",
     tag = "runtime-managers",
     params(
-        ("id" = Uuid, Path, description = "Runtime manager ID")
+        ("id" = Uuid, Path, description = "Runtime manager ID"),
+        ProbeRefreshQuery
     ),
     responses(
         (status = 200, description = "Health check result", body = ApiResponse<Value>),
@@ -365,6 +384,7 @@ pub async fn get_setup_guide(
 )]
 pub async fn health_check(
     Path(id): Path<Uuid>,
+    Query(query): Query<ProbeRefreshQuery>,
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<Value>>, StatusCode> {
     let manager = match RuntimeManager::get_by_id(state.db.pool(), &id.to_string()).await {
@@ -376,8 +396,8 @@ pub async fn health_check(
         }
     };
 
-    let health_result = perform_health_check(&manager).await;
-    Ok(Json(ApiResponse::success(health_result)))
+    let health_result = cached_health_check(&state.probe_cache, &manager, query.refresh).await;
+    Ok(Json(ApiResponse::success(health_result.into_value())))
 }
 
 /// 获取运行时信息
@@ -440,50 +460,99 @@ pub async fn get_runtime_resources(
     Ok(Json(ApiResponse::success(resources)))
 }
 
-/// 验证创建请求
-fn validate_create_request(request: &CreateRuntimeManagerRequest) -> Result<(), String> {
-    if request.name.trim().is_empty() {
-        return Err("运行时管理器名称不能为空".to_string());
-    }
-    
-    if request.name.len() > 255 {
-        return Err("运行时管理器名称长度不能超过255个字符".to_string());
-    }
+/// 获取运行时能力，供调度决策使用
+#[utoipa::path(
+    get,
+    path = "/runtime-managers/{id}/capabilities",
+    tag = "runtime-managers",
+    params(
+        ("id" = Uuid, Path, description = "Runtime manager ID"),
+        ProbeRefreshQuery
+    ),
+    responses(
+        (status = 200, description = "Runtime capabilities", body = ApiResponse<Value>),
+        (status = 404, description = "Runtime manager not found", body = ApiResponse<String>)
+    )
+)]
+pub async fn get_runtime_capabilities(
+    Path(id): Path<Uuid>,
+    Query(query): Query<ProbeRefreshQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Value>>, StatusCode> {
+    let manager = match RuntimeManager::get_by_id(state.db.pool(), &id.to_string()).await {
+        Ok(Some(manager)) => manager,
+        Ok(None) => return Ok(Json(ApiResponse::error("运行时管理器不存在".to_string()))),
+        Err(e) => {
+            tracing::error!("获取运行时管理器失败: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let capabilities = state
+        .probe_cache
+        .get_or_probe(&manager.id, ProbeKind::Capabilities, query.refresh, || {
+            collect_runtime_capabilities(&state.probe_cache, &manager, query.refresh)
+        })
+        .await;
+    Ok(Json(ApiResponse::success(capabilities.into_value())))
+}
+
+/// 验证创建请求，收集所有字段错误
+fn validate_create_request(request: &CreateRuntimeManagerRequest) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+
+    validate_name(&request.name, &mut errors);
 
     // 验证运行时类型
     match request.runtime_type {
         RuntimeType::Local | RuntimeType::Docker | RuntimeType::Kubernetes => {},
     }
 
-    // 验证配置格式
     if let Some(ref config) = request.config {
-        if !config.is_object() {
-            return Err("配置必须是有效的JSON对象".to_string());
-        }
+        validate_config(&request.runtime_type, config, &mut errors);
     }
 
-    Ok(())
+    errors.into_result()
 }
 
-/// 验证更新请求
-fn validate_update_request(request: &UpdateRuntimeManagerRequest) -> Result<(), String> {
+/// 验证更新请求，收集所有字段错误
+///
+/// 配置按更新后的运行时类型验证；只修改运行时类型时，已保存的配置也需符合新类型
+fn validate_update_request(
+    request: &UpdateRuntimeManagerRequest,
+    current: &RuntimeManager,
+) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+
     if let Some(ref name) = request.name {
-        if name.trim().is_empty() {
-            return Err("运行时管理器名称不能为空".to_string());
-        }
-        if name.len() > 255 {
-            return Err("运行时管理器名称长度不能超过255个字符".to_string());
-        }
+        validate_name(name, &mut errors);
     }
 
-    // 验证配置格式
-    if let Some(ref config) = request.config {
-        if !config.is_object() {
-            return Err("配置必须是有效的JSON对象".to_string());
-        }
+    let runtime_type = request.runtime_type.as_ref().unwrap_or(&current.runtime_type);
+    let config = match request.config {
+        Some(ref config) => Some(config.clone()),
+        None if *runtime_type != current.runtime_type => current.get_config(),
+        None => None,
+    };
+    if let Some(ref config) = config {
+        validate_config(runtime_type, config, &mut errors);
+    }
+
+    errors.into_result()
+}
+
+fn validate_name(name: &str, errors: &mut ValidationErrors) {
+    if name.trim().is_empty() {
+        errors.add("name", "运行时管理器名称不能为空");
+    } else if name.len() > 255 {
+        errors.add("name", "运行时管理器名称长度不能超过255个字符");
     }
+}
 
-    Ok(())
+fn validate_config(runtime_type: &RuntimeType, config: &Value, errors: &mut ValidationErrors) {
+    if let Err(config_errors) = validate_runtime_config(runtime_type, config) {
+        errors.extend(config_errors);
+    }
 }
 
 /// 检查管理器是否正在使用
@@ -656,6 +725,13 @@ async fn test_k8s_runtime(manager: &RuntimeManager) -> anyhow::Result<Value> {
     }))
 }
 
+/// 缓存的平台能力检测结果，平台能力与运行时管理器无关，所有管理器共用
+async fn cached_platform_capabilities(cache: &ProbeCache, refresh: bool) -> ProbeResult {
+    cache
+        .get_or_probe(PLATFORM_SCOPE, ProbeKind::Platform, refresh, detect_platform_capabilities)
+        .await
+}
+
 /// 检测平台能力
 async fn detect_platform_capabilities() -> Value {
     use tokio::process::Command;
@@ -887,6 +963,13 @@ async fn get_local_setup_guide() -> Value {
     })
 }
 
+/// 缓存的健康检查结果
+async fn cached_health_check(cache: &ProbeCache, manager: &RuntimeManager, refresh: bool) -> ProbeResult {
+    cache
+        .get_or_probe(&manager.id, ProbeKind::Health, refresh, || perform_health_check(manager))
+        .await
+}
+
 /// 执行健康检查
 async fn perform_health_check(manager: &RuntimeManager) -> Value {
     let start_time = chrono::Utc::now();
@@ -936,7 +1019,50 @@ async fn perform_health_check(manager: &RuntimeManager) -> Value {
     health_status
 }
 
+/// 汇总运行时能力：可用运行时、资源余量和权限
+///
+/// 资源和权限取自健康检查的结果，避免重复采集；平台能力和健康检查优先使用缓存
+async fn collect_runtime_capabilities(cache: &ProbeCache, manager: &RuntimeManager, refresh: bool) -> Value {
+    let platform = cached_platform_capabilities(cache, refresh).await.value;
+    let health = cached_health_check(cache, manager, refresh).await.value;
+    let resources = &health["checks"]["resources"];
+    let permissions = &health["checks"]["permissions"];
+
+    let headroom = match &manager.runtime_type {
+        RuntimeType::Local => json!({
+            "cpu": {
+                "cores": resources["cpu"]["cores"],
+                "usage_percent": resources["cpu"]["usage_percent"],
+                "available_percent": resources["cpu"]["usage_percent"].as_f64().map(|usage| (100.0 - usage).max(0.0))
+            },
+            "memory": {
+                "total_bytes": resources["memory"]["total_bytes"],
+                "available_bytes": resources["memory"]["available_bytes"],
+                "usage_percent": resources["memory"]["usage_percent"]
+            }
+        }),
+        // 容器平台的资源由集群调度，这里原样返回采集结果
+        RuntimeType::Docker | RuntimeType::Kubernetes => resources.clone(),
+    };
+
+    let connection_ok = health["checks"]["connection"]["success"].as_bool().unwrap_or(false);
+    let permissions_ok = permissions["success"].as_bool().unwrap_or(false);
 
+    json!({
+        "manager_id": manager.id,
+        "manager_name": manager.name,
+        "runtime_type": manager.runtime_type,
+        "is_online": manager.is_online(),
+        "platform": platform["platform"],
+        "arch": platform["arch"],
+        "available_runtimes": platform["runtimes"],
+        "resources": headroom,
+        "permissions": permissions,
+        "connection": health["checks"]["connection"],
+        "schedulable": connection_ok && permissions_ok,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })
+}
 
 /// 检查本地资源
 async fn check_local_resources() -> Value {
@@ -1077,6 +1203,23 @@ async fn get_runtime_detailed_info(manager: &RuntimeManager) -> Value {
     info
 }
 
+/// 运行时所在主机的空闲资源，供启动测试运行前检查余量
+///
+/// 本地运行时取自资源检查的CPU和内存采样；容器平台的资源由集群调度，余量未知
+pub(crate) async fn runtime_headroom(runtime_type: &RuntimeType) -> ResourceHeadroom {
+    match runtime_type {
+        RuntimeType::Local => {
+            let cpu = get_local_cpu_usage().await;
+            let memory = get_local_memory_usage().await;
+            ResourceHeadroom {
+                available_memory_bytes: memory["available_bytes"].as_u64(),
+                available_cpu_percent: cpu["usage_percent"].as_f64().map(|usage| (100.0 - usage).max(0.0)),
+            }
+        }
+        RuntimeType::Docker | RuntimeType::Kubernetes => ResourceHeadroom::default(),
+    }
+}
+
 /// 检查运行时资源使用情况
 async fn check_runtime_resources(manager: &RuntimeManager) -> Value {
     let mut resources = json!({
@@ -1512,4 +1655,39 @@ async fn get_k8s_namespace_resources() -> Value {
             "timestamp": chrono::Utc::now().to_rfc3339()
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+
+    #[tokio::test]
+    async fn test_local_manager_capabilities_report_resources_and_permissions() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        let manager = RuntimeManager::create(
+            db.pool(),
+            CreateRuntimeManagerRequest {
+                name: "local-1".to_string(),
+                runtime_type: RuntimeType::Local,
+                config: None,
+                tags: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let cache = ProbeCache::new(std::time::Duration::ZERO);
+        let capabilities = collect_runtime_capabilities(&cache, &manager, false).await;
+
+        assert_eq!(capabilities["manager_id"], manager.id);
+        assert_eq!(capabilities["available_runtimes"]["local"]["available"], true);
+        assert!(capabilities["resources"]["cpu"]["cores"].as_u64().unwrap() >= 1);
+        assert!(capabilities["resources"]["cpu"]["available_percent"].is_number());
+        assert!(capabilities["resources"]["memory"]["total_bytes"].as_u64().unwrap() > 0);
+        assert!(capabilities["resources"]["memory"]["available_bytes"].is_u64());
+        assert!(capabilities["permissions"]["can_read"].is_boolean());
+        assert!(capabilities["permissions"]["can_write"].is_boolean());
+        assert!(capabilities["schedulable"].is_boolean());
+    }
+}
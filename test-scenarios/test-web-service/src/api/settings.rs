@@ -66,7 +66,7 @@ pub struct UpdateUserPreferenceRequest {
 pub async fn list_settings(
     Query(params): Query<PaginationParams>,
     State(_state): State<AppState>,
-) -> Result<Json<PaginatedResponse<Setting>>, StatusCode> {
+) -> Result<Json<ApiResponse<PaginatedResponse<Setting>>>, StatusCode> {
     let settings = vec![
         Setting {
             id: Uuid::new_v4().to_string(),
@@ -139,10 +139,10 @@ pub async fn list_settings(
         has_prev: false,
     };
     
-    Ok(Json(PaginatedResponse {
+    Ok(Json(ApiResponse::success(PaginatedResponse {
         data: settings,
         pagination,
-    }))
+    })))
 }
 
 /// 根据分类获取设置
@@ -28,6 +28,8 @@ pub async fn get_info() -> Result<Json<Value>, StatusCode> {
         "version": "v1",
         "description": "基于Rust Axum的AIOps测试用例管理和运行服务API",
         "base_url": "/api/v1",
+        "openapi_url": "/api/v1/openapi.json",
+        "swagger_ui": "/swagger-ui/",
         "endpoints": {
             "system": {
                 "GET /docs": "获取API文档",
@@ -222,7 +224,7 @@ async fn get_system_stats(state: &AppState) -> anyhow::Result<Value> {
     
     // 系统信息
     let system_info = json!({
-        "uptime": chrono::Utc::now().to_rfc3339(),
+        "info": state.system_info.snapshot(),
         "memory_usage": get_memory_usage(),
         "cpu_usage": get_cpu_usage(),
         "disk_usage": get_disk_usage()
@@ -270,8 +272,7 @@ fn get_memory_usage() -> Value {
 /// 获取CPU使用情况（简化版本）
 fn get_cpu_usage() -> Value {
     json!({
-        "usage_percent": "N/A",
-        "cores": std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+        "usage_percent": "N/A"
     })
 }
 
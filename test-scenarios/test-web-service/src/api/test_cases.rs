@@ -5,19 +5,28 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
 use uuid::Uuid;
 use utoipa;
 use crate::{
     AppState,
+    api::test_runs::{execute_test_run, queue_full_response, record_phase},
+    database::{self, Database},
     models::{
-        ApiResponse, PaginationParams, PaginatedResponse,
-        test_case::{TestCase, CreateTestCaseRequest, UpdateTestCaseRequest, TestCaseQuery, RunTestCaseRequest},
-        test_run::{TestRun, CreateTestRunRequest},
-        TestStatus, RuntimeType
-    }
+        ApiResponse, PaginationParams, PaginatedResponse, ValidationErrors,
+        test_case::{
+            TestCase, CreateTestCaseRequest, UpdateTestCaseRequest, TestCaseQuery, RunTestCaseRequest,
+            TestCaseExport, TestCaseExportQuery, ImportStrategy, ImportTestCasesParams,
+            ImportTestCasesReport, ImportItemResult, ImportItemStatus,
+        },
+        test_run::{TestRun, CreateTestRunRequest, CaseRunLogsQuery},
+        TestAssertions, ResourceLimits, RuntimeType, TimelinePhase, EnvVarInput, StoredEnv, is_valid_env_name,
+    },
+    services::{crypto::SecretCipher, log_storage::LogStorage, test_env},
 };
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 
 /// 分页获取测试用例列表
 #[utoipa::path(
@@ -26,7 +35,7 @@ use crate::{
     tag = "test-cases",
     params(TestCaseQuery),
     responses(
-        (status = 200, description = "Test case list", body = PaginatedResponse<TestCase>),
+        (status = 200, description = "Test case list", body = ApiResponse<PaginatedResponse<TestCase>>),
         (status = 500, description = "Internal server error")
     )
 )]
@@ -34,15 +43,15 @@ pub async fn list_test_cases(
     Query(params): Query<PaginationParams>,
     Query(query): Query<TestCaseQuery>,
     State(state): State<AppState>,
-) -> Result<Json<PaginatedResponse<TestCase>>, StatusCode> {
-    match TestCase::list(state.db.pool(), &params, &query).await {
-        Ok((data, pagination)) => Ok(Json(PaginatedResponse {
+) -> Result<Json<ApiResponse<PaginatedResponse<TestCase>>>, StatusCode> {
+    match state.db.timed(TestCase::list(state.db.pool(), &params, &query)).await {
+        Ok((data, pagination)) => Ok(Json(ApiResponse::success(PaginatedResponse {
             data,
             pagination,
-        })),
+        }))),
         Err(e) => {
             tracing::error!("获取测试用例列表失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
         }
     }
 }
@@ -55,20 +64,31 @@ pub async fn list_test_cases(
     request_body = CreateTestCaseRequest,
     responses(
         (status = 201, description = "Test case created", body = ApiResponse<TestCase>),
-        (status = 400, description = "Invalid request"),
+        (status = 422, description = "Validation failed", body = ValidationErrors),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn create_test_case(
     State(state): State<AppState>,
     Json(request): Json<CreateTestCaseRequest>,
-) -> Result<Json<ApiResponse<TestCase>>, StatusCode> {
+) -> Result<Json<ApiResponse<TestCase>>, Response> {
     // 验证请求数据
-    if let Err(e) = validate_create_request(&request) {
-        return Ok(Json(ApiResponse::<TestCase>::error(e)));
+    if let Err(errors) = validate_create_request(&request) {
+        return Err(errors.into_response());
     }
+    let env = match encode_request_env(&state, request.env.as_ref()) {
+        Ok(env) => env,
+        Err(errors) => return Err(errors.into_response()),
+    };
 
-    match TestCase::create(state.db.pool(), request).await {
+    let create = async {
+        let test_case = TestCase::create(state.db.pool(), request).await?;
+        match env {
+            Some(env) => TestCase::set_env(state.db.pool(), &test_case.id, &env).await,
+            None => Ok(test_case),
+        }
+    };
+    match state.db.timed(create).await {
         Ok(test_case) => {
             tracing::info!("创建测试用例成功: {} ({})", test_case.name, test_case.id);
             Ok(Json(ApiResponse::success(test_case)))
@@ -79,7 +99,7 @@ pub async fn create_test_case(
                 s if s.contains("UNIQUE constraint failed") => {
                     Ok(Json(ApiResponse::<TestCase>::error("测试用例名称已存在".to_string())))
                 }
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR)
+                _ => Err(database::error_status(&e).into_response())
             }
         }
     }
@@ -103,12 +123,12 @@ pub async fn get_test_case(
     Path(id): Path<Uuid>,
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<TestCase>>, StatusCode> {
-    match TestCase::get_by_id(state.db.pool(), &id.to_string()).await {
+    match state.db.timed(TestCase::get_by_id(state.db.pool(), &id.to_string())).await {
         Ok(Some(test_case)) => Ok(Json(ApiResponse::success(test_case))),
         Ok(None) => Ok(Json(ApiResponse::<TestCase>::error("测试用例不存在".to_string()))),
         Err(e) => {
             tracing::error!("获取测试用例失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
         }
     }
 }
@@ -125,7 +145,7 @@ pub async fn get_test_case(
     responses(
         (status = 200, description = "Test case updated", body = ApiResponse<TestCase>),
         (status = 404, description = "Test case not found"),
-        (status = 400, description = "Invalid request"),
+        (status = 422, description = "Validation failed", body = ValidationErrors),
         (status = 500, description = "Internal server error")
     )
 )]
@@ -133,23 +153,34 @@ pub async fn update_test_case(
     Path(id): Path<Uuid>,
     State(state): State<AppState>,
     Json(request): Json<UpdateTestCaseRequest>,
-) -> Result<Json<ApiResponse<TestCase>>, StatusCode> {
+) -> Result<Json<ApiResponse<TestCase>>, Response> {
     // 验证请求数据
-    if let Err(e) = validate_update_request(&request) {
-        return Ok(Json(ApiResponse::error(e)));
+    if let Err(errors) = validate_update_request(&request) {
+        return Err(errors.into_response());
     }
+    let env = match encode_request_env(&state, request.env.as_ref()) {
+        Ok(env) => env,
+        Err(errors) => return Err(errors.into_response()),
+    };
 
     // 检查测试用例是否存在
-    match TestCase::get_by_id(state.db.pool(), &id.to_string()).await {
+    match state.db.timed(TestCase::get_by_id(state.db.pool(), &id.to_string())).await {
         Ok(Some(_)) => {},
         Ok(None) => return Ok(Json(ApiResponse::error("测试用例不存在".to_string()))),
         Err(e) => {
             tracing::error!("检查测试用例存在性失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(database::error_status(&e).into_response());
         }
     }
 
-    match TestCase::update(state.db.pool(), &id.to_string(), request).await {
+    let update = async {
+        let test_case = TestCase::update(state.db.pool(), &id.to_string(), request).await?;
+        match env {
+            Some(env) => TestCase::set_env(state.db.pool(), &test_case.id, &env).await,
+            None => Ok(test_case),
+        }
+    };
+    match state.db.timed(update).await {
         Ok(test_case) => {
             tracing::info!("更新测试用例成功: {} ({})", test_case.name, test_case.id);
             Ok(Json(ApiResponse::success(test_case)))
@@ -160,7 +191,7 @@ pub async fn update_test_case(
                 s if s.contains("UNIQUE constraint failed") => {
                     Ok(Json(ApiResponse::error("测试用例名称已存在".to_string())))
                 }
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR)
+                _ => Err(database::error_status(&e).into_response())
             }
         }
     }
@@ -192,18 +223,18 @@ pub async fn delete_test_case(
         Ok(false) => {},
         Err(e) => {
             tracing::error!("检查运行中测试失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(database::error_status(&e));
         }
     }
 
-    match TestCase::delete(state.db.pool(), &id.to_string()).await {
+    match state.db.timed(TestCase::delete(state.db.pool(), &id.to_string())).await {
         Ok(()) => {
             tracing::info!("删除测试用例成功: {}", id);
             Ok(Json(ApiResponse::success("测试用例删除成功".to_string())))
         }
         Err(e) => {
             tracing::error!("删除测试用例失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
         }
     }
 }
@@ -221,6 +252,7 @@ pub async fn delete_test_case(
         (status = 200, description = "Test case run started", body = ApiResponse<TestRun>),
         (status = 404, description = "Test case not found"),
         (status = 400, description = "Invalid request"),
+        (status = 429, description = "Test run queue is full"),
         (status = 500, description = "Internal server error")
     )
 )]
@@ -228,27 +260,37 @@ pub async fn run_test_case(
     Path(id): Path<Uuid>,
     State(state): State<AppState>,
     Json(request): Json<RunTestCaseRequest>,
-) -> Result<Json<ApiResponse<TestRun>>, StatusCode> {
+) -> Result<Json<ApiResponse<TestRun>>, Response> {
     // 检查测试用例是否存在
-    let test_case = match TestCase::get_by_id(state.db.pool(), &id.to_string()).await {
+    let test_case = match state.db.timed(TestCase::get_by_id(state.db.pool(), &id.to_string())).await {
         Ok(Some(test_case)) => test_case,
         Ok(None) => return Ok(Json(ApiResponse::error("测试用例不存在".to_string()))),
         Err(e) => {
             tracing::error!("获取测试用例失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(database::error_status(&e).into_response());
         }
     };
 
-    // 检查并发限制
-    match check_concurrent_limit(&state).await {
-        Ok(false) => {
-            return Ok(Json(ApiResponse::error("已达到最大并发测试数量限制".to_string())));
-        }
-        Ok(true) => {},
+    // 用例环境变量与本次运行的覆盖值合并后随运行记录保存
+    let mut env_errors = ValidationErrors::new();
+    if let Some(ref env) = request.env {
+        validate_env(env, &mut env_errors);
+    }
+    let overrides = match env_errors.into_result().and_then(|_| encode_request_env(&state, request.env.as_ref())) {
+        Ok(overrides) => overrides.unwrap_or_default(),
+        Err(errors) => return Ok(Json(ApiResponse::error(errors.to_string()))),
+    };
+    let run_env = match test_env::merge_env(test_case.env.as_deref(), overrides) {
+        Ok(env) => env,
         Err(e) => {
-            tracing::error!("检查并发限制失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            tracing::error!("解析测试用例 {} 的环境变量失败: {}", test_case.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
+    };
+
+    // 磁盘空间严重不足时结果和日志无法写入
+    if let Err(reason) = state.disk_space.ensure_can_start_run() {
+        return Ok(Json(ApiResponse::error(reason)));
     }
 
     // 创建测试运行记录
@@ -257,7 +299,11 @@ pub async fn run_test_case(
         metadata: request.metadata,
     };
 
-    match TestRun::create(state.db.pool(), create_run_request).await {
+    let create_run = async {
+        let test_run = TestRun::create(state.db.pool(), create_run_request).await?;
+        TestRun::set_env(state.db.pool(), &test_run.id, &run_env).await
+    };
+    match state.db.timed(create_run).await {
         Ok(test_run) => {
             tracing::info!("创建测试运行记录成功: {} -> {}", test_case.name, test_run.id);
             
@@ -267,11 +313,23 @@ pub async fn run_test_case(
                 Ok(id) => id,
                 Err(e) => {
                     tracing::error!("解析测试运行ID失败: {}", e);
-                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+                }
+            };
+
+            // 排队已满且策略为拒绝时不保留这条运行记录
+            let ticket = match state.run_limiter.enqueue(test_run_id, test_run.priority) {
+                Ok(ticket) => ticket,
+                Err(err) => {
+                    if let Err(e) = TestRun::delete(state.db.pool(), &test_run_id).await {
+                        tracing::error!("删除被拒绝的测试运行记录失败: {}", e);
+                    }
+                    return Err(queue_full_response(err));
                 }
             };
+            record_phase(&state, &test_run_id, TimelinePhase::Queued, None).await;
             tokio::spawn(async move {
-                if let Err(e) = execute_test_case(state_clone, test_run_id, test_case).await {
+                if let Err(e) = execute_test_run(state_clone, test_run_id, test_case, ticket).await {
                     tracing::error!("执行测试用例失败: {}", e);
                 }
             });
@@ -280,75 +338,336 @@ pub async fn run_test_case(
         }
         Err(e) => {
             tracing::error!("创建测试运行记录失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e).into_response())
         }
     }
 }
 
-/// 验证创建请求
-fn validate_create_request(request: &CreateTestCaseRequest) -> Result<(), String> {
-    if request.name.trim().is_empty() {
-        return Err("测试用例名称不能为空".to_string());
+/// 导出测试用例
+///
+/// 返回的JSON数组可直接作为导入接口的请求体，因此不包裹 `ApiResponse`
+#[utoipa::path(
+    get,
+    path = "/api/v1/test-cases/export",
+    tag = "test-cases",
+    params(TestCaseExportQuery),
+    responses(
+        (status = 200, description = "Exported test cases", body = Vec<TestCaseExport>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn export_test_cases(
+    Query(query): Query<TestCaseExportQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<TestCaseExport>>, StatusCode> {
+    match state.db.timed(TestCase::export(state.db.pool(), &query)).await {
+        Ok(cases) => {
+            tracing::info!("导出测试用例: {} 条", cases.len());
+            Ok(Json(cases))
+        }
+        Err(e) => {
+            tracing::error!("导出测试用例失败: {}", e);
+            Err(database::error_status(&e))
+        }
     }
-    
-    if request.name.len() > 255 {
-        return Err("测试用例名称长度不能超过255个字符".to_string());
+}
+
+/// 批量导入测试用例
+///
+/// 逐条校验并写入，单条失败不影响其他条目，结果逐条返回
+#[utoipa::path(
+    post,
+    path = "/api/v1/test-cases/import",
+    tag = "test-cases",
+    params(ImportTestCasesParams),
+    request_body = Vec<TestCaseExport>,
+    responses(
+        (status = 200, description = "Import report", body = ApiResponse<ImportTestCasesReport>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn import_test_cases(
+    Query(params): Query<ImportTestCasesParams>,
+    State(state): State<AppState>,
+    Json(cases): Json<Vec<TestCaseExport>>,
+) -> Result<Json<ApiResponse<ImportTestCasesReport>>, StatusCode> {
+    let report = import_cases(&state.db, cases, params.strategy).await;
+    tracing::info!(
+        "导入测试用例: 新建 {}, 覆盖 {}, 跳过 {}, 失败 {}",
+        report.created, report.updated, report.skipped, report.failed
+    );
+    Ok(Json(ApiResponse::success(report)))
+}
+
+/// 汇总测试用例最近几次运行的日志
+///
+/// 按运行先后拼接各次运行的stdout/stderr，每次运行前后有边界标记并注明状态
+#[utoipa::path(
+    get,
+    path = "/api/v1/test-cases/{id}/runs/logs",
+    tag = "test-cases",
+    params(
+        ("id" = Uuid, Path, description = "Test case ID"),
+        CaseRunLogsQuery
+    ),
+    responses(
+        (status = 200, description = "Aggregated run logs", body = ApiResponse<Value>),
+        (status = 404, description = "Test case not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_case_run_logs(
+    Path(id): Path<Uuid>,
+    Query(query): Query<CaseRunLogsQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Value>>, StatusCode> {
+    let test_case = match state.db.timed(TestCase::get_by_id(state.db.pool(), &id.to_string())).await {
+        Ok(Some(test_case)) => test_case,
+        Ok(None) => return Ok(Json(ApiResponse::error("测试用例不存在".to_string()))),
+        Err(e) => {
+            tracing::error!("获取测试用例失败: {}", e);
+            return Err(database::error_status(&e));
+        }
+    };
+
+    let runs = match state
+        .db
+        .timed(TestRun::find_recent_by_case(state.db.pool(), &test_case.id, query.limit()))
+        .await
+    {
+        Ok(runs) => runs,
+        Err(e) => {
+            tracing::error!("获取测试用例运行记录失败: {}", e);
+            return Err(database::error_status(&e));
+        }
+    };
+
+    let storage = LogStorage::from_config(&state.config);
+    let total = runs.len();
+    let mut combined = String::new();
+    let mut entries = Vec::with_capacity(total);
+    for (index, run) in runs.into_iter().enumerate() {
+        let (stdout, stderr) = match tokio::try_join!(
+            storage.load(run.stdout.clone()),
+            storage.load(run.stderr.clone()),
+        ) {
+            Ok(logs) => logs,
+            Err(e) => {
+                tracing::error!("读取测试运行 {} 的日志失败: {}", run.id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        combined.push_str(&format_run_block(index + 1, total, &run, &stdout, &stderr));
+        entries.push(json!({
+            "test_run_id": run.id,
+            "status": run.status,
+            "exit_code": run.exit_code,
+            "start_time": run.start_time,
+            "end_time": run.end_time,
+            "stdout": stdout,
+            "stderr": stderr,
+        }));
+    }
+
+    Ok(Json(ApiResponse::success(json!({
+        "test_case_id": test_case.id,
+        "test_case_name": test_case.name,
+        "run_count": total,
+        "runs": entries,
+        "combined": combined,
+    }))))
+}
+
+/// 格式化单次运行的日志段，首尾为边界标记
+fn format_run_block(index: usize, total: usize, run: &TestRun, stdout: &str, stderr: &str) -> String {
+    let exit_code = run.exit_code.map(|code| code.to_string()).unwrap_or_else(|| "-".to_string());
+    let started = run.start_time.map(|time| time.to_rfc3339()).unwrap_or_else(|| "-".to_string());
+
+    let mut block = format!(
+        "===== run {}/{} {} | status: {} | exit_code: {} | started: {} =====\n",
+        index, total, run.id, run.status, exit_code, started
+    );
+    for (stream, content) in [("stdout", stdout), ("stderr", stderr)] {
+        block.push_str(&format!("--- {} ---\n", stream));
+        block.push_str(content);
+        if !content.is_empty() && !content.ends_with('\n') {
+            block.push('\n');
+        }
+    }
+    block.push_str(&format!("===== end of run {} =====\n", run.id));
+    block
+}
+
+/// 按策略逐条导入测试用例
+async fn import_cases(
+    db: &Database,
+    cases: Vec<TestCaseExport>,
+    strategy: ImportStrategy,
+) -> ImportTestCasesReport {
+    let mut report = ImportTestCasesReport::default();
+
+    for (index, case) in cases.into_iter().enumerate() {
+        let name = case.name.clone();
+        let result = match import_case(db, case, strategy).await {
+            Ok((status, id)) => ImportItemResult { index, name, status, id, error: None },
+            Err(e) => {
+                tracing::warn!("导入测试用例 {} 失败: {}", name, e);
+                ImportItemResult {
+                    index,
+                    name,
+                    status: ImportItemStatus::Failed,
+                    id: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        report.push(result);
     }
 
-    if request.script_path.trim().is_empty() {
-        return Err("脚本路径不能为空".to_string());
+    report
+}
+
+/// 导入单个测试用例，返回导入状态和测试用例ID
+async fn import_case(
+    db: &Database,
+    case: TestCaseExport,
+    strategy: ImportStrategy,
+) -> anyhow::Result<(ImportItemStatus, Option<String>)> {
+    let request = CreateTestCaseRequest::from(case);
+    validate_create_request(&request)?;
+
+    match db.timed(TestCase::find_by_name(db.pool(), &request.name)).await? {
+        Some(existing) if strategy == ImportStrategy::Skip => {
+            Ok((ImportItemStatus::Skipped, Some(existing.id)))
+        }
+        Some(existing) => {
+            let updated = db.timed(TestCase::replace(db.pool(), &existing.id, request)).await?;
+            Ok((ImportItemStatus::Updated, Some(updated.id)))
+        }
+        None => {
+            let created = db.timed(TestCase::create(db.pool(), request)).await?;
+            Ok((ImportItemStatus::Created, Some(created.id)))
+        }
     }
+}
+
+/// 验证创建请求，收集所有字段错误
+fn validate_create_request(request: &CreateTestCaseRequest) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+
+    validate_name(&request.name, &mut errors);
+    validate_script_path(&request.script_path, &mut errors);
 
     // 验证运行时类型
     match request.runtime_type {
         RuntimeType::Local | RuntimeType::Docker | RuntimeType::Kubernetes => {},
     }
 
-    // 验证标签
     if let Some(ref tags) = request.tags {
-        if tags.len() > 20 {
-            return Err("标签数量不能超过20个".to_string());
-        }
-        for tag in tags {
-            if tag.len() > 50 {
-                return Err("单个标签长度不能超过50个字符".to_string());
-            }
-        }
+        validate_tags(tags, &mut errors);
+    }
+
+    if let Some(ref assertions) = request.assertions {
+        validate_assertions(assertions, &mut errors);
+    }
+
+    if let Some(ref env) = request.env {
+        validate_env(env, &mut errors);
     }
 
-    Ok(())
+    validate_limits(&request.limits, &mut errors);
+
+    errors.into_result()
 }
 
-/// 验证更新请求
-fn validate_update_request(request: &UpdateTestCaseRequest) -> Result<(), String> {
+/// 验证更新请求，收集所有字段错误
+fn validate_update_request(request: &UpdateTestCaseRequest) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+
     if let Some(ref name) = request.name {
-        if name.trim().is_empty() {
-            return Err("测试用例名称不能为空".to_string());
-        }
-        if name.len() > 255 {
-            return Err("测试用例名称长度不能超过255个字符".to_string());
-        }
+        validate_name(name, &mut errors);
     }
 
     if let Some(ref script_path) = request.script_path {
-        if script_path.trim().is_empty() {
-            return Err("脚本路径不能为空".to_string());
-        }
+        validate_script_path(script_path, &mut errors);
     }
 
-    // 验证标签
     if let Some(ref tags) = request.tags {
-        if tags.len() > 20 {
-            return Err("标签数量不能超过20个".to_string());
-        }
-        for tag in tags {
-            if tag.len() > 50 {
-                return Err("单个标签长度不能超过50个字符".to_string());
-            }
-        }
+        validate_tags(tags, &mut errors);
+    }
+
+    if let Some(ref assertions) = request.assertions {
+        validate_assertions(assertions, &mut errors);
     }
 
-    Ok(())
+    if let Some(ref env) = request.env {
+        validate_env(env, &mut errors);
+    }
+
+    validate_limits(&request.limits, &mut errors);
+
+    errors.into_result()
+}
+
+fn validate_assertions(assertions: &TestAssertions, errors: &mut ValidationErrors) {
+    for (field, message) in assertions.validate() {
+        errors.add(format!("assertions.{}", field), message);
+    }
+}
+
+fn validate_limits(limits: &ResourceLimits, errors: &mut ValidationErrors) {
+    for (field, message) in limits.validate() {
+        errors.add(field, message);
+    }
+}
+
+fn validate_env(env: &BTreeMap<String, EnvVarInput>, errors: &mut ValidationErrors) {
+    for name in env.keys().filter(|name| !is_valid_env_name(name)) {
+        errors.add(format!("env.{}", name), "环境变量名只能包含字母、数字和下划线，且不能以数字开头");
+    }
+}
+
+/// 加密请求中的环境变量，包含密文变量但未配置 `secret_key` 时返回字段错误
+fn encode_request_env(
+    state: &AppState,
+    env: Option<&BTreeMap<String, EnvVarInput>>,
+) -> Result<Option<StoredEnv>, ValidationErrors> {
+    let Some(env) = env else {
+        return Ok(None);
+    };
+    test_env::encode_env(env, SecretCipher::from_config(&state.config).as_ref())
+        .map(Some)
+        .map_err(|e| {
+            let mut errors = ValidationErrors::new();
+            errors.add("env", e.to_string());
+            errors
+        })
+}
+
+fn validate_name(name: &str, errors: &mut ValidationErrors) {
+    if name.trim().is_empty() {
+        errors.add("name", "测试用例名称不能为空");
+    } else if name.len() > 255 {
+        errors.add("name", "测试用例名称长度不能超过255个字符");
+    }
+}
+
+fn validate_script_path(script_path: &str, errors: &mut ValidationErrors) {
+    if script_path.trim().is_empty() {
+        errors.add("script_path", "脚本路径不能为空");
+    }
+}
+
+fn validate_tags(tags: &[String], errors: &mut ValidationErrors) {
+    if tags.len() > 20 {
+        errors.add("tags", "标签数量不能超过20个");
+    }
+    for (index, tag) in tags.iter().enumerate() {
+        if tag.len() > 50 {
+            errors.add(format!("tags[{}]", index), "单个标签长度不能超过50个字符");
+        }
+    }
 }
 
 /// 检查是否有正在运行的测试
@@ -363,108 +682,162 @@ async fn check_running_tests(state: &AppState, test_case_id: &Uuid) -> anyhow::R
     Ok(running_count > 0)
 }
 
-/// 检查并发限制
-async fn check_concurrent_limit(state: &AppState) -> anyhow::Result<bool> {
-    let running_count: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM test_runs WHERE status IN ('pending', 'running')"
-    )
-    .fetch_one(state.db.pool())
-    .await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RuntimeType;
+
+    fn export_case(name: &str, tags: &[&str]) -> TestCaseExport {
+        TestCaseExport {
+            name: name.to_string(),
+            description: Some(format!("{} 描述", name)),
+            script_path: format!("scripts/{}.py", name),
+            config_path: None,
+            runtime_type: RuntimeType::Local,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            assertions: None,
+            limits: ResourceLimits::default(),
+        }
+    }
 
-    Ok(running_count < state.config.max_concurrent_tests as i64)
-}
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        for case in [export_case("cpu_stress", &["perf", "cpu"]), export_case("disk_io", &[])] {
+            TestCase::create(db.pool(), case.into()).await.unwrap();
+        }
 
-/// 执行测试用例
-async fn execute_test_case(
-    state: AppState,
-    test_run_id: Uuid,
-    test_case: TestCase,
-) -> anyhow::Result<()> {
-    // 更新状态为运行中
-    TestRun::update_status(state.db.pool(), &test_run_id, TestStatus::Running).await?;
-    
-    let start_time = chrono::Utc::now();
-    
-    // 根据运行时类型执行测试
-    let runtime_type = test_case.get_runtime_type()?;
-    let result = match runtime_type {
-        RuntimeType::Local => execute_local_test(&test_case).await,
-        RuntimeType::Docker => execute_docker_test(&test_case).await,
-        RuntimeType::Kubernetes => execute_k8s_test(&test_case).await,
-    };
+        let exported = TestCase::export(db.pool(), &TestCaseExportQuery::default()).await.unwrap();
+        assert_eq!(exported.len(), 2);
 
-    let end_time = chrono::Utc::now();
-    let duration_ms = (end_time - start_time).num_milliseconds();
+        // 经JSON序列化后清空数据库再导入
+        let payload = serde_json::to_string(&exported).unwrap();
+        sqlx::query("DELETE FROM test_cases").execute(db.pool()).await.unwrap();
 
-    // 更新测试运行结果
-    match result {
-        Ok((exit_code, stdout, stderr)) => {
-            let status = if exit_code == 0 { TestStatus::Success } else { TestStatus::Failed };
-            TestRun::update_result(
-                state.db.pool(),
-                &test_run_id,
-                status,
-                Some(start_time),
-                Some(end_time),
-                Some(duration_ms),
-                Some(exit_code),
-                Some(stdout),
-                Some(stderr),
-            ).await?;
-            
-            tracing::info!("测试执行完成: {} -> {} ({}ms)", test_case.name, 
-                         if exit_code == 0 { "成功" } else { "失败" }, duration_ms);
-        }
-        Err(e) => {
-            TestRun::update_result(
-                state.db.pool(),
-                &test_run_id,
-                TestStatus::Failed,
-                Some(start_time),
-                Some(end_time),
-                Some(duration_ms),
-                Some(-1),
-                None,
-                Some(format!("执行错误: {}", e)),
-            ).await?;
-            
-            tracing::error!("测试执行失败: {} -> {}", test_case.name, e);
-        }
+        let cases: Vec<TestCaseExport> = serde_json::from_str(&payload).unwrap();
+        let report = import_cases(&db, cases, ImportStrategy::Skip).await;
+        assert_eq!(report.created, 2);
+        assert_eq!(report.failed, 0);
+
+        let reimported = TestCase::export(db.pool(), &TestCaseExportQuery::default()).await.unwrap();
+        assert_eq!(reimported, exported);
     }
 
-    Ok(())
-}
+    #[tokio::test]
+    async fn test_import_merge_strategies_and_validation() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        TestCase::create(db.pool(), export_case("cpu_stress", &[]).into()).await.unwrap();
+
+        let mut changed = export_case("cpu_stress", &["updated"]);
+        changed.description = None;
+        let invalid = TestCaseExport {
+            script_path: " ".to_string(),
+            ..export_case("broken", &[])
+        };
+
+        let report = import_cases(&db, vec![changed.clone(), invalid.clone()], ImportStrategy::Skip).await;
+        assert_eq!((report.skipped, report.failed), (1, 1));
+        assert_eq!(report.results[1].status, ImportItemStatus::Failed);
+        assert!(report.results[1].error.is_some());
+
+        let report = import_cases(&db, vec![changed.clone()], ImportStrategy::Overwrite).await;
+        assert_eq!(report.updated, 1);
+
+        let stored = TestCase::find_by_name(db.pool(), "cpu_stress").await.unwrap().unwrap();
+        assert_eq!(stored.to_export().unwrap(), changed);
+        assert!(TestCase::find_by_name(db.pool(), "broken").await.unwrap().is_none());
+    }
 
-/// 执行本地测试
-async fn execute_local_test(test_case: &TestCase) -> anyhow::Result<(i32, String, String)> {
-    use tokio::process::Command;
-    
-    let mut cmd = Command::new("python");
-    cmd.arg(&test_case.script_path);
-    
-    if let Some(ref config_path) = test_case.config_path {
-        cmd.arg("--config").arg(config_path);
-    }
-    
-    let output = cmd.output().await?;
-    
-    Ok((
-        output.status.code().unwrap_or(-1),
-        String::from_utf8_lossy(&output.stdout).to_string(),
-        String::from_utf8_lossy(&output.stderr).to_string(),
-    ))
-}
+    #[tokio::test]
+    async fn test_validation_reports_all_invalid_fields() {
+        let mut request: CreateTestCaseRequest = export_case("placeholder", &[]).into();
+        request.name = "  ".to_string();
+        request.script_path = String::new();
+        request.tags = Some(vec!["ok".to_string(), "x".repeat(51)]);
+
+        let errors = validate_create_request(&request).unwrap_err();
+        let response = errors.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], false);
+        assert_eq!(
+            body["data"],
+            serde_json::json!({
+                "name": "测试用例名称不能为空",
+                "script_path": "脚本路径不能为空",
+                "tags[1]": "单个标签长度不能超过50个字符",
+            })
+        );
+    }
 
-/// 执行Docker测试
-async fn execute_docker_test(test_case: &TestCase) -> anyhow::Result<(i32, String, String)> {
-    // TODO: 实现Docker运行时支持
-    tracing::warn!("Docker运行时支持尚未实现: {}", test_case.name);
-    Err(anyhow::anyhow!("Docker运行时支持尚未实现"))
-}
+    #[tokio::test]
+    async fn test_case_run_logs_include_each_run_with_boundaries() {
+        let results_dir = tempfile::tempdir().unwrap();
+        let config = crate::config::AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            ..crate::config::AppConfig::default()
+        };
+        let state = AppState {
+            db: std::sync::Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: std::sync::Arc::new(crate::services::disk_space::DiskSpaceChecker::from_config(&config)),
+            run_limiter: std::sync::Arc::new(crate::services::run_limiter::RunLimiter::from_config(&config)),
+            resource_headroom: std::sync::Arc::new(
+                crate::services::resource_headroom::ResourceHeadroomChecker::from_config(&config),
+            ),
+            probe_cache: std::sync::Arc::new(crate::services::probe_cache::ProbeCache::from_config(&config)),
+            config: std::sync::Arc::new(config),
+            system_info: std::sync::Arc::new(crate::models::SystemInfo::collect()),
+        };
+        let test_case = TestCase::create(state.db.pool(), export_case("flaky", &[]).into()).await.unwrap();
+
+        let mut run_ids = Vec::new();
+        for (status, exit_code, stdout) in [
+            (crate::models::TestStatus::Failed, 1, "attempt one"),
+            (crate::models::TestStatus::Success, 0, "attempt two"),
+        ] {
+            let run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+                test_case_id: test_case.id.clone(),
+                metadata: None,
+            }).await.unwrap();
+            let id = Uuid::parse_str(&run.id).unwrap();
+            let now = chrono::Utc::now();
+            TestRun::update_result(
+                state.db.pool(), &id, status, Some(now), Some(now), Some(0),
+                Some(exit_code), Some(stdout.to_string()), None,
+            ).await.unwrap();
+            run_ids.push(run.id);
+        }
 
-/// 执行Kubernetes测试
-async fn execute_k8s_test(test_case: &TestCase) -> anyhow::Result<(i32, String, String)> {
-    // TODO: 实现Kubernetes运行时支持
-    tracing::warn!("Kubernetes运行时支持尚未实现: {}", test_case.name);
-    Err(anyhow::anyhow!("Kubernetes运行时支持尚未实现"))
-}
\ No newline at end of file
+        let Json(response) = get_case_run_logs(
+            Path(Uuid::parse_str(&test_case.id).unwrap()),
+            Query(CaseRunLogsQuery::default()),
+            State(state.clone()),
+        ).await.unwrap();
+        let logs = response.data.unwrap();
+        assert_eq!(logs["run_count"], 2);
+        assert_eq!(logs["runs"][0]["test_run_id"], run_ids[0]);
+        assert_eq!(logs["runs"][1]["status"], "success");
+
+        let combined = logs["combined"].as_str().unwrap();
+        let first = combined.find(&format!("===== run 1/2 {} | status: failed | exit_code: 1", run_ids[0])).unwrap();
+        let first_end = combined.find(&format!("===== end of run {} =====", run_ids[0])).unwrap();
+        let second = combined.find(&format!("===== run 2/2 {} | status: success | exit_code: 0", run_ids[1])).unwrap();
+        assert!(first < combined.find("attempt one").unwrap());
+        assert!(combined.find("attempt one").unwrap() < first_end);
+        assert!(first_end < second);
+        assert!(second < combined.find("attempt two").unwrap());
+        assert!(combined.ends_with(&format!("===== end of run {} =====\n", run_ids[1])));
+
+        // limit=1 只返回最近一次运行
+        let Json(response) = get_case_run_logs(
+            Path(Uuid::parse_str(&test_case.id).unwrap()),
+            Query(CaseRunLogsQuery { limit: Some(1) }),
+            State(state),
+        ).await.unwrap();
+        let logs = response.data.unwrap();
+        assert_eq!(logs["run_count"], 1);
+        assert_eq!(logs["runs"][0]["test_run_id"], run_ids[1]);
+    }
+}
@@ -5,18 +5,29 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
 use uuid::Uuid;
 use serde_json::{json, Value};
 use utoipa;
 use crate::{
     AppState,
+    database,
     models::{
         ApiResponse, PaginationParams, PaginatedResponse,
-        test_run::{TestRun, CreateTestRunRequest, UpdateTestRunRequest, TestRunQuery, TestRunStats},
-        TestStatus
-    }
+        test_run::{
+            TestRun, CreateTestRunRequest, UpdateTestRunRequest, TestRunQuery, TestRunStats,
+            CancelPendingQuery, CancelPendingResult, SetPriorityRequest,
+        },
+        AssertionReport, LimitExceeded, TestStatus, TimelineEvent, TimelinePhase,
+    },
+    execution::process_limits::apply_process_limits,
+    services::{
+        crypto::SecretCipher,
+        log_storage::LogStorage,
+        run_limiter::{QueueFull, QueueSnapshot, QueueTicket},
+        test_env::ResolvedEnv,
+    },
 };
 
 /// 分页获取测试运行记录列表
@@ -26,7 +37,7 @@ use crate::{
     tag = "test-runs",
     params(TestRunQuery),
     responses(
-        (status = 200, description = "Test run list", body = PaginatedResponse<TestRun>),
+        (status = 200, description = "Test run list", body = ApiResponse<PaginatedResponse<TestRun>>),
         (status = 500, description = "Internal server error")
     )
 )]
@@ -34,18 +45,18 @@ pub async fn list_test_runs(
     Query(params): Query<PaginationParams>,
     Query(query): Query<TestRunQuery>,
     State(state): State<AppState>,
-) -> Result<Json<PaginatedResponse<TestRun>>, StatusCode> {
-    match TestRun::list(state.db.pool(), &params, &query).await {
+) -> Result<Json<ApiResponse<PaginatedResponse<TestRun>>>, StatusCode> {
+    match state.db.timed(TestRun::list(state.db.pool(), &params, &query)).await {
         Ok((test_runs, pagination)) => {
             let response = PaginatedResponse {
                 data: test_runs,
                 pagination,
             };
-            Ok(Json(response))
+            Ok(Json(ApiResponse::success(response)))
         },
         Err(e) => {
             tracing::error!("获取测试运行记录列表失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
         }
     }
 }
@@ -66,23 +77,23 @@ pub async fn create_test_run(
     Json(request): Json<CreateTestRunRequest>,
 ) -> Result<Json<ApiResponse<TestRun>>, StatusCode> {
     // 验证测试用例是否存在
-    match crate::models::test_case::TestCase::get_by_id(state.db.pool(), &request.test_case_id).await {
+    match state.db.timed(crate::models::test_case::TestCase::get_by_id(state.db.pool(), &request.test_case_id)).await {
         Ok(Some(_)) => {},
         Ok(None) => return Ok(Json(ApiResponse::error("测试用例不存在".to_string()))),
         Err(e) => {
             tracing::error!("验证测试用例存在性失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(database::error_status(&e));
         }
     }
 
-    match TestRun::create(state.db.pool(), request).await {
+    match state.db.timed(TestRun::create(state.db.pool(), request)).await {
         Ok(test_run) => {
             tracing::info!("创建测试运行记录成功: {}", test_run.id);
             Ok(Json(ApiResponse::success(test_run)))
         }
         Err(e) => {
             tracing::error!("创建测试运行记录失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
         }
     }
 }
@@ -104,12 +115,12 @@ pub async fn get_test_run(
     Path(id): Path<Uuid>,
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<TestRun>>, StatusCode> {
-    match TestRun::get_by_id(state.db.pool(), &id).await {
+    match state.db.timed(TestRun::get_by_id(state.db.pool(), &id)).await {
         Ok(Some(test_run)) => Ok(Json(ApiResponse::success(test_run))),
         Ok(None) => Ok(Json(ApiResponse::error("测试运行记录不存在".to_string()))),
         Err(e) => {
             tracing::error!("获取测试运行记录失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
         }
     }
 }
@@ -135,23 +146,23 @@ pub async fn update_test_run(
     Json(request): Json<UpdateTestRunRequest>,
 ) -> Result<Json<ApiResponse<TestRun>>, StatusCode> {
     // 检查测试运行记录是否存在
-    match TestRun::get_by_id(state.db.pool(), &id).await {
+    match state.db.timed(TestRun::get_by_id(state.db.pool(), &id)).await {
         Ok(Some(_)) => {},
         Ok(None) => return Ok(Json(ApiResponse::error("测试运行记录不存在".to_string()))),
         Err(e) => {
             tracing::error!("检查测试运行记录存在性失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(database::error_status(&e));
         }
     }
 
-    match TestRun::update(state.db.pool(), &id.to_string(), request).await {
+    match state.db.timed(TestRun::update(state.db.pool(), &id.to_string(), request)).await {
         Ok(test_run) => {
             tracing::info!("更新测试运行记录成功: {}", test_run.id);
             Ok(Json(ApiResponse::success(test_run)))
         }
         Err(e) => {
             tracing::error!("更新测试运行记录失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
         }
     }
 }
@@ -167,20 +178,21 @@ pub async fn update_test_run(
     responses(
         (status = 200, description = "Started successfully", body = ApiResponse<String>),
         (status = 400, description = "Test is already running or status does not allow start", body = ApiResponse<String>),
-        (status = 404, description = "Test run record not found", body = ApiResponse<String>)
+        (status = 404, description = "Test run record not found", body = ApiResponse<String>),
+        (status = 429, description = "Test run queue is full")
     )
 )]
 pub async fn start_test_run(
     Path(id): Path<Uuid>,
     State(state): State<AppState>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
+) -> Result<Json<ApiResponse<String>>, Response> {
     // 获取测试运行记录
-    let test_run = match TestRun::get_by_id(state.db.pool(), &id).await {
+    let test_run = match state.db.timed(TestRun::get_by_id(state.db.pool(), &id)).await {
         Ok(Some(test_run)) => test_run,
         Ok(None) => return Ok(Json(ApiResponse::error("测试运行记录不存在".to_string()))),
         Err(e) => {
             tracing::error!("获取测试运行记录失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(database::error_status(&e).into_response());
         }
     };
 
@@ -191,16 +203,9 @@ pub async fn start_test_run(
         ))));
     }
 
-    // 检查并发限制
-    match check_concurrent_limit(&state).await {
-        Ok(false) => {
-            return Ok(Json(ApiResponse::error("已达到最大并发测试数量限制".to_string())));
-        }
-        Ok(true) => {},
-        Err(e) => {
-            tracing::error!("检查并发限制失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+    // 磁盘空间严重不足时结果和日志无法写入
+    if let Err(reason) = state.disk_space.ensure_can_start_run() {
+        return Ok(Json(ApiResponse::error(reason)));
     }
 
     // 获取测试用例信息
@@ -212,14 +217,18 @@ pub async fn start_test_run(
         Ok(None) => return Ok(Json(ApiResponse::error("关联的测试用例不存在".to_string()))),
         Err(e) => {
             tracing::error!("获取测试用例失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(database::error_status(&e).into_response());
         }
     };
 
+    // 登记排队，排队已满时按配置的策略处理
+    let ticket = state.run_limiter.enqueue(id, test_run.priority).map_err(queue_full_response)?;
+
     // 异步启动测试执行
+    record_phase(&state, &id, TimelinePhase::Queued, None).await;
     let state_clone = state.clone();
     tokio::spawn(async move {
-        if let Err(e) = execute_test_run(state_clone, id, test_case).await {
+        if let Err(e) = execute_test_run(state_clone, id, test_case, ticket).await {
             tracing::error!("执行测试运行失败: {}", e);
         }
     });
@@ -247,12 +256,12 @@ pub async fn stop_test_run(
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
     // 获取测试运行记录
-    let test_run = match TestRun::get_by_id(state.db.pool(), &id).await {
+    let test_run = match state.db.timed(TestRun::get_by_id(state.db.pool(), &id)).await {
         Ok(Some(test_run)) => test_run,
         Ok(None) => return Ok(Json(ApiResponse::error("测试运行记录不存在".to_string()))),
         Err(e) => {
             tracing::error!("获取测试运行记录失败: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(database::error_status(&e));
         }
     };
 
@@ -271,14 +280,87 @@ pub async fn stop_test_run(
     }
 
     // 更新状态为已取消
-    match TestRun::update_status(state.db.pool(), &id, TestStatus::Cancelled).await {
+    match state.db.timed(TestRun::update_status(state.db.pool(), &id, TestStatus::Cancelled)).await {
         Ok(_) => {
             tracing::info!("测试运行停止成功: {}", id);
             Ok(Json(ApiResponse::success("测试运行已停止".to_string())))
         }
         Err(e) => {
             tracing::error!("停止测试运行失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
+        }
+    }
+}
+
+/// 批量取消等待中的测试运行
+///
+/// 可按测试用例或标签筛选，已在运行或已结束的运行不受影响。
+/// 已进入排队的运行在拿到并发名额后会发现已被取消并直接退出
+#[utoipa::path(
+    post,
+    path = "/test-runs/cancel-pending",
+    tag = "test-runs",
+    params(CancelPendingQuery),
+    responses(
+        (status = 200, description = "Number of cancelled runs", body = ApiResponse<CancelPendingResult>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn cancel_pending_test_runs(
+    Query(query): Query<CancelPendingQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<CancelPendingResult>>, StatusCode> {
+    match state.db.timed(TestRun::cancel_pending(state.db.pool(), &query)).await {
+        Ok(cancelled) => {
+            tracing::info!("批量取消等待中的测试运行: {} 个, 筛选条件: {:?}", cancelled, query);
+            Ok(Json(ApiResponse::success(CancelPendingResult { cancelled })))
+        }
+        Err(e) => {
+            tracing::error!("批量取消测试运行失败: {}", e);
+            Err(database::error_status(&e))
+        }
+    }
+}
+
+/// 调整等待中测试运行的优先级
+///
+/// 优先级越大越先获得并发名额，已进入排队的运行立即按新优先级重新排序；
+/// 尚未启动的运行在启动时按该优先级排队
+#[utoipa::path(
+    post,
+    path = "/test-runs/{id}/priority",
+    tag = "test-runs",
+    params(
+        ("id" = Uuid, Path, description = "Test run record ID")
+    ),
+    request_body = SetPriorityRequest,
+    responses(
+        (status = 200, description = "Updated test run", body = ApiResponse<TestRun>),
+        (status = 400, description = "Test run is not pending", body = ApiResponse<String>),
+        (status = 404, description = "Test run record not found", body = ApiResponse<String>)
+    )
+)]
+pub async fn set_test_run_priority(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(request): Json<SetPriorityRequest>,
+) -> Result<Json<ApiResponse<TestRun>>, StatusCode> {
+    let update = async {
+        if !TestRun::set_priority(state.db.pool(), &id, request.priority).await? {
+            return Ok(None);
+        }
+        TestRun::get_by_id(state.db.pool(), &id).await
+    };
+    match state.db.timed(update).await {
+        Ok(Some(test_run)) => {
+            let queued = state.run_limiter.set_priority(id, request.priority);
+            tracing::info!("调整测试运行 {} 的优先级为 {} (排队中: {})", id, request.priority, queued);
+            Ok(Json(ApiResponse::success(test_run)))
+        }
+        Ok(None) => Ok(Json(ApiResponse::error("测试运行记录不存在或已不在等待中".to_string()))),
+        Err(e) => {
+            tracing::error!("调整测试运行优先级失败: {}", e);
+            Err(database::error_status(&e))
         }
     }
 }
@@ -300,8 +382,21 @@ pub async fn get_test_logs(
     Path(id): Path<Uuid>,
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<Value>>, StatusCode> {
-    match TestRun::get_by_id(state.db.pool(), &id).await {
+    match state.db.timed(TestRun::get_by_id(state.db.pool(), &id)).await {
         Ok(Some(test_run)) => {
+            let storage = LogStorage::from_config(&state.config);
+            let (stdout, stderr, assertions) = match tokio::try_join!(
+                storage.load(test_run.stdout),
+                storage.load(test_run.stderr),
+                AssertionReport::find_by_run(state.db.pool(), &id),
+            ) {
+                Ok(logs) => logs,
+                Err(e) => {
+                    tracing::error!("读取测试运行日志失败: {}", e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
+
             let logs = json!({
                 "test_run_id": test_run.id,
                 "status": test_run.status,
@@ -309,8 +404,9 @@ pub async fn get_test_logs(
                 "end_time": test_run.end_time,
                 "duration_ms": test_run.duration_ms,
                 "exit_code": test_run.exit_code,
-                "stdout": test_run.stdout.unwrap_or_default(),
-                "stderr": test_run.stderr.unwrap_or_default(),
+                "stdout": stdout,
+                "stderr": stderr,
+                "assertions": assertions,
                 "metadata": test_run.metadata
             });
             Ok(Json(ApiResponse::<Value>::success(logs)))
@@ -318,7 +414,42 @@ pub async fn get_test_logs(
         Ok(None) => Ok(Json(ApiResponse::<Value>::error("测试运行记录不存在".to_string()))),
         Err(e) => {
             tracing::error!("获取测试运行日志失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
+        }
+    }
+}
+
+/// 获取测试运行时间线
+#[utoipa::path(
+    get,
+    path = "/test-runs/{id}/timeline",
+    tag = "test-runs",
+    params(
+        ("id" = Uuid, Path, description = "Test run record ID")
+    ),
+    responses(
+        (status = 200, description = "Test run phase timeline", body = ApiResponse<Vec<TimelineEvent>>),
+        (status = 404, description = "Test run record not found", body = ApiResponse<String>)
+    )
+)]
+pub async fn get_test_timeline(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<TimelineEvent>>>, StatusCode> {
+    match state.db.timed(TestRun::get_by_id(state.db.pool(), &id)).await {
+        Ok(Some(_)) => {},
+        Ok(None) => return Ok(Json(ApiResponse::error("测试运行记录不存在".to_string()))),
+        Err(e) => {
+            tracing::error!("获取测试运行记录失败: {}", e);
+            return Err(database::error_status(&e));
+        }
+    }
+
+    match state.db.timed(TimelineEvent::find_by_run(state.db.pool(), &id)).await {
+        Ok(events) => Ok(Json(ApiResponse::success(events))),
+        Err(e) => {
+            tracing::error!("获取测试运行时间线失败: {}", e);
+            Err(database::error_status(&e))
         }
     }
 }
@@ -335,74 +466,259 @@ pub async fn get_test_logs(
 pub async fn get_test_stats(
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<TestRunStats>>, StatusCode> {
-    match TestRun::get_stats(state.db.pool()).await {
+    match state.db.timed(TestRun::get_stats(state.db.pool())).await {
         Ok(stats) => Ok(Json(ApiResponse::success(stats))),
         Err(e) => {
             tracing::error!("获取测试运行统计信息失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(database::error_status(&e))
         }
     }
 }
 
-/// 检查并发限制
-async fn check_concurrent_limit(state: &AppState) -> anyhow::Result<bool> {
-    let running_count: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM test_runs WHERE status IN ('pending', 'running')"
+/// 测试运行排队状态
+#[derive(Debug, serde::Serialize)]
+pub struct TestRunQueueStatus {
+    #[serde(flatten)]
+    pub queue: QueueSnapshot,
+    /// 按最近运行的平均时长估算的新提交运行的等待时间（毫秒），没有历史时长时为空
+    pub estimated_wait_ms: Option<i64>,
+}
+
+/// 估算新提交运行的等待时间
+///
+/// 排在前面的运行按并发上限分批执行，每批耗时取最近运行的平均时长
+///
+/// # 参数
+/// * `queue` - 排队状态
+/// * `average_duration_ms` - 最近运行的平均时长
+pub(crate) fn estimate_wait_ms(queue: &QueueSnapshot, average_duration_ms: Option<f64>) -> Option<i64> {
+    let average_duration_ms = average_duration_ms?;
+    if queue.running < queue.capacity && queue.depth == 0 {
+        return Some(0);
+    }
+    let batches = (queue.depth / queue.capacity.max(1)) + 1;
+    Some((batches as f64 * average_duration_ms).round() as i64)
+}
+
+/// 获取测试运行排队状态
+#[utoipa::path(
+    get,
+    path = "/test-runs/queue",
+    tag = "test-runs",
+    responses(
+        (status = 200, description = "Queue depth, policy and estimated wait", body = ApiResponse<Value>)
     )
-    .fetch_one(state.db.pool())
-    .await?;
+)]
+pub async fn get_test_queue(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<TestRunQueueStatus>>, StatusCode> {
+    let average_duration = state.db.timed(async {
+        Ok(sqlx::query_scalar::<_, Option<f64>>(
+            "SELECT AVG(duration_ms) FROM (SELECT duration_ms FROM test_runs WHERE duration_ms IS NOT NULL ORDER BY created_at DESC LIMIT 50)"
+        )
+        .fetch_one(state.db.pool())
+        .await?)
+    });
+    match average_duration.await {
+        Ok(average_duration_ms) => {
+            let queue = state.run_limiter.snapshot();
+            let estimated_wait_ms = estimate_wait_ms(&queue, average_duration_ms);
+            Ok(Json(ApiResponse::success(TestRunQueueStatus { queue, estimated_wait_ms })))
+        }
+        Err(e) => {
+            tracing::error!("获取测试运行平均时长失败: {}", e);
+            Err(database::error_status(&e))
+        }
+    }
+}
+
+/// 排队已满时的429响应
+pub(crate) fn queue_full_response(err: QueueFull) -> Response {
+    tracing::warn!("拒绝测试运行: {}", err);
+    let error_response = json!({
+        "success": false,
+        "message": err.to_string(),
+        "error": "Too Many Requests",
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+    (StatusCode::TOO_MANY_REQUESTS, Json(error_response)).into_response()
+}
 
-    Ok(running_count < state.config.max_concurrent_tests as i64)
+/// 记录时间线事件
+///
+/// 时间线仅用于排查，写入失败只记录日志，不影响测试运行
+///
+/// # 参数
+/// * `state` - 应用状态
+/// * `test_run_id` - 测试运行ID
+/// * `phase` - 阶段
+/// * `detail` - 补充说明
+pub(crate) async fn record_phase(
+    state: &AppState,
+    test_run_id: &Uuid,
+    phase: TimelinePhase,
+    detail: Option<String>,
+) {
+    if let Err(e) = TimelineEvent::record(state.db.pool(), test_run_id, phase, detail).await {
+        tracing::warn!("记录测试运行 {} 的时间线事件 {:?} 失败: {}", test_run_id, phase, e);
+    }
 }
 
 /// 执行测试运行
-async fn execute_test_run(
+///
+/// 本地运行在进程启动和首次输出时记录时间线；容器运行时只能观察到任务提交，
+/// 以 `container_pulling` 标记提交时间。运行记录保存了环境变量时使用运行记录的，
+/// 否则使用用例的，输出中出现的密文变量值在保存前替换为掩码。
+/// 调用前需先通过 `RunLimiter::enqueue` 登记排队，排队中被挤出时运行标记为已取消。
+/// 获得名额后运行时资源余量不足时保持pending并记录原因，余量恢复后再启动
+pub(crate) async fn execute_test_run(
     state: AppState,
     test_run_id: Uuid,
     test_case: crate::models::test_case::TestCase,
+    ticket: QueueTicket,
 ) -> anyhow::Result<()> {
     use crate::models::{RuntimeType, TestStatus};
     
-    // 更新状态为运行中
-    TestRun::update_status(state.db.pool(), &test_run_id, TestStatus::Running).await?;
+    let runtime_type = test_case.get_runtime_type()?;
+
+    // 等待并发名额期间保持pending状态，许可在运行结束后释放
+    let Some(_permit) = state.run_limiter.acquire_queued(ticket, &runtime_type).await else {
+        tracing::warn!("测试运行 {} 在排队中被挤出，已取消", test_run_id);
+        TestRun::update_status(state.db.pool(), &test_run_id, TestStatus::Cancelled).await?;
+        return Ok(());
+    };
+
+    if !wait_for_headroom(&state, &test_run_id, &runtime_type).await? {
+        tracing::info!("测试运行 {} 在等待资源期间已被取消，跳过执行", test_run_id);
+        return Ok(());
+    }
+
+    // 更新状态为运行中，排队期间已被取消的运行不再执行
+    if !TestRun::mark_running(state.db.pool(), &test_run_id).await? {
+        tracing::info!("测试运行 {} 在排队期间已被取消，跳过执行", test_run_id);
+        return Ok(());
+    }
     
     let start_time = chrono::Utc::now();
     
     // 根据运行时类型执行测试
-    let runtime_type = test_case.get_runtime_type()?;
-    let result = match runtime_type {
-        RuntimeType::Local => execute_local_test(&test_case).await,
-        RuntimeType::Docker => execute_docker_test(&test_case).await,
-        RuntimeType::Kubernetes => execute_k8s_test(&test_case).await,
+    if runtime_type != RuntimeType::Local {
+        record_phase(&state, &test_run_id, TimelinePhase::ContainerPulling, None).await;
+    }
+    let result = match resolve_run_env(&state, &test_run_id, &test_case).await {
+        Ok(env) => match runtime_type {
+            RuntimeType::Local => execute_local_test(&state, &test_run_id, &test_case, &env).await,
+            RuntimeType::Docker => execute_docker_test(&state, &test_case, &env).await,
+            RuntimeType::Kubernetes => execute_k8s_test(&state, &test_case, &env).await,
+        }
+        .map(|(exit_code, stdout, stderr)| (exit_code, env.mask(&stdout), env.mask(&stderr))),
+        Err(e) => Err(e),
     };
 
     let end_time = chrono::Utc::now();
+    record_run_outcome(&state, &test_run_id, &test_case, start_time, end_time, result).await
+}
+
+/// 等待运行时资源余量满足要求，返回运行是否仍应启动
+///
+/// 余量不足期间运行保持pending并把原因写入 `pending_reason`，按配置的间隔重新检查；
+/// 等待期间名额保持占用，运行被取消时返回false
+async fn wait_for_headroom(
+    state: &AppState,
+    test_run_id: &Uuid,
+    runtime_type: &crate::models::RuntimeType,
+) -> anyhow::Result<bool> {
+    let mut waiting = false;
+    loop {
+        let Err(reason) = state.resource_headroom.ensure_headroom(runtime_type).await else {
+            return Ok(true);
+        };
+        if !waiting {
+            tracing::warn!("测试运行 {} 暂不启动: {}", test_run_id, reason);
+            waiting = true;
+        }
+        if !TestRun::set_pending_reason(state.db.pool(), test_run_id, &reason).await? {
+            return Ok(false);
+        }
+        tokio::time::sleep(state.resource_headroom.recheck_interval()).await;
+    }
+}
+
+/// 解密本次运行的环境变量
+async fn resolve_run_env(
+    state: &AppState,
+    test_run_id: &Uuid,
+    test_case: &crate::models::test_case::TestCase,
+) -> anyhow::Result<ResolvedEnv> {
+    let run_env = TestRun::get_by_id(state.db.pool(), test_run_id)
+        .await?
+        .and_then(|test_run| test_run.env);
+    let cipher = SecretCipher::from_config(&state.config);
+    ResolvedEnv::resolve(run_env.as_deref().or(test_case.env.as_deref()), cipher.as_ref())
+}
+
+/// 判定并保存测试运行结果
+///
+/// 用例配置了断言时按断言判定成功/失败并将判定报告写入 `test_results`，
+/// 否则以退出码是否为0判定
+///
+/// # 参数
+/// * `state` - 应用状态
+/// * `test_run_id` - 测试运行ID
+/// * `test_case` - 测试用例
+/// * `start_time` - 开始时间
+/// * `end_time` - 结束时间
+/// * `result` - 执行结果（退出码, stdout, stderr）
+pub(crate) async fn record_run_outcome(
+    state: &AppState,
+    test_run_id: &Uuid,
+    test_case: &crate::models::test_case::TestCase,
+    start_time: chrono::DateTime<chrono::Utc>,
+    end_time: chrono::DateTime<chrono::Utc>,
+    result: anyhow::Result<(i32, String, String)>,
+) -> anyhow::Result<()> {
+    use crate::models::TestStatus;
+
     let duration_ms = (end_time - start_time).num_milliseconds();
 
-    // 更新测试运行结果
-    match result {
+    let outcome = match result {
         Ok((exit_code, stdout, stderr)) => {
-            let status = if exit_code == 0 { TestStatus::Success } else { TestStatus::Failed };
+            let status = match test_case.get_assertions()? {
+                Some(assertions) => {
+                    let report = assertions.evaluate(exit_code, &stdout, duration_ms);
+                    report.save(state.db.pool(), test_run_id).await?;
+                    if report.passed { TestStatus::Success } else { TestStatus::Failed }
+                }
+                None if exit_code == 0 => TestStatus::Success,
+                None => TestStatus::Failed,
+            };
+            let succeeded = status == TestStatus::Success;
+
+            let logs = LogStorage::from_config(&state.config);
+            let stdout = logs.store(test_run_id, "stdout", Some(stdout)).await?;
+            let stderr = logs.store(test_run_id, "stderr", Some(stderr)).await?;
             TestRun::update_result(
                 state.db.pool(),
-                &test_run_id,
-                status,
+                test_run_id,
+                status.clone(),
                 Some(start_time),
                 Some(end_time),
                 Some(duration_ms),
                 Some(exit_code),
-                Some(stdout),
-                Some(stderr),
+                stdout,
+                stderr,
             ).await?;
             
-            tracing::info!("测试运行完成: {} -> {} ({}ms)", test_run_id, 
-                         if exit_code == 0 { "成功" } else { "失败" }, duration_ms);
+            tracing::info!("测试运行完成: {} ({}) -> {} ({}ms)", test_run_id, test_case.name,
+                         if succeeded { "成功" } else { "失败" }, duration_ms);
+            status
         }
         Err(e) => {
+            let status = e.downcast_ref::<LimitExceeded>().map_or(TestStatus::Failed, LimitExceeded::status);
             TestRun::update_result(
                 state.db.pool(),
-                &test_run_id,
-                TestStatus::Failed,
+                test_run_id,
+                status.clone(),
                 Some(start_time),
                 Some(end_time),
                 Some(duration_ms),
@@ -411,58 +727,155 @@ async fn execute_test_run(
                 Some(format!("执行错误: {}", e)),
             ).await?;
             
-            tracing::error!("测试运行失败: {} -> {}", test_run_id, e);
+            tracing::error!("测试运行失败: {} ({}) -> {}", test_run_id, test_case.name, e);
+            status
         }
+    };
+
+    if let Err(e) = TimelineEvent::record_at(
+        state.db.pool(),
+        test_run_id,
+        TimelinePhase::Completed,
+        end_time,
+        Some(outcome.to_string()),
+    ).await {
+        tracing::warn!("记录测试运行 {} 的完成事件失败: {}", test_run_id, e);
     }
 
     Ok(())
 }
 
 /// 执行本地测试
+///
+/// 逐段读取stdout/stderr，进程启动和首次产生输出时记录时间线
 async fn execute_local_test(
-    test_case: &crate::models::test_case::TestCase
+    state: &AppState,
+    test_run_id: &Uuid,
+    test_case: &crate::models::test_case::TestCase,
+    env: &ResolvedEnv,
 ) -> anyhow::Result<(i32, String, String)> {
+    use std::process::Stdio;
+    use tokio::io::AsyncReadExt;
     use tokio::process::Command;
     
     let mut cmd = Command::new("python");
     cmd.arg(&test_case.script_path);
+    cmd.envs(&env.vars);
     
     if let Some(ref config_path) = test_case.config_path {
         cmd.arg("--config").arg(config_path);
     }
-    
-    // 设置超时时间（30分钟）
-    let timeout = std::time::Duration::from_secs(30 * 60);
-    
-    match tokio::time::timeout(timeout, cmd.output()).await {
-        Ok(Ok(output)) => {
-            Ok((
-                output.status.code().unwrap_or(-1),
-                String::from_utf8_lossy(&output.stdout).to_string(),
-                String::from_utf8_lossy(&output.stderr).to_string(),
-            ))
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).kill_on_drop(true);
+    apply_process_limits(&mut cmd, &test_case.limits);
+
+    let timeout = test_case.limits.timeout();
+
+    let mut child = cmd.spawn().map_err(|e| anyhow::anyhow!("命令执行失败: {}", e))?;
+    record_phase(state, test_run_id, TimelinePhase::Started, None).await;
+
+    let mut stdout_pipe = child.stdout.take().ok_or_else(|| anyhow::anyhow!("无法读取stdout"))?;
+    let mut stderr_pipe = child.stderr.take().ok_or_else(|| anyhow::anyhow!("无法读取stderr"))?;
+
+    let run = async {
+        let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+        let (mut stdout_buf, mut stderr_buf) = ([0u8; 8192], [0u8; 8192]);
+        let (mut stdout_open, mut stderr_open) = (true, true);
+        let mut seen_output = false;
+
+        loop {
+            let (read, is_stdout) = tokio::select! {
+                read = stdout_pipe.read(&mut stdout_buf), if stdout_open => (read?, true),
+                read = stderr_pipe.read(&mut stderr_buf), if stderr_open => (read?, false),
+                else => break,
+            };
+
+            if read == 0 {
+                if is_stdout { stdout_open = false } else { stderr_open = false }
+                continue;
+            }
+            if !seen_output {
+                seen_output = true;
+                record_phase(state, test_run_id, TimelinePhase::FirstOutput, None).await;
+            }
+            if is_stdout {
+                stdout.extend_from_slice(&stdout_buf[..read]);
+            } else {
+                stderr.extend_from_slice(&stderr_buf[..read]);
+            }
+        }
+
+        let status = child.wait().await?;
+        Ok::<_, anyhow::Error>((
+            status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&stdout).to_string(),
+            String::from_utf8_lossy(&stderr).to_string(),
+        ))
+    };
+
+    match tokio::time::timeout(timeout, run).await {
+        Ok(result) => result,
+        Err(_) => {
+            // 超时后立即终止进程，而不是等到child被释放
+            if let Err(e) = child.kill().await {
+                tracing::warn!("终止超时的测试进程失败: {} -> {}", test_case.name, e);
+            }
+            Err(LimitExceeded::Timeout(timeout.as_secs()).into())
         }
-        Ok(Err(e)) => Err(anyhow::anyhow!("命令执行失败: {}", e)),
-        Err(_) => Err(anyhow::anyhow!("测试执行超时")),
     }
 }
 
 /// 执行Docker测试
+///
+/// 使用第一个处于活跃状态的Docker运行时管理器的配置启动容器
 async fn execute_docker_test(
-    test_case: &crate::models::test_case::TestCase
+    state: &AppState,
+    test_case: &crate::models::test_case::TestCase,
+    env: &ResolvedEnv,
 ) -> anyhow::Result<(i32, String, String)> {
-    // TODO: 实现Docker运行时支持
-    tracing::warn!("Docker运行时支持尚未实现: {}", test_case.name);
-    Err(anyhow::anyhow!("Docker运行时支持尚未实现"))
+    use crate::execution::DockerExecutor;
+    use crate::models::RuntimeType;
+
+    let manager = find_active_manager(state, RuntimeType::Docker).await?;
+    tracing::info!("使用Docker运行时管理器 {} 执行测试: {}", manager.name, test_case.name);
+    DockerExecutor::from_manager(&manager)?.execute(test_case, &env.vars).await
 }
 
 /// 执行Kubernetes测试
+///
+/// 使用第一个处于活跃状态的Kubernetes运行时管理器的配置创建Job
 async fn execute_k8s_test(
-    test_case: &crate::models::test_case::TestCase
+    state: &AppState,
+    test_case: &crate::models::test_case::TestCase,
+    env: &ResolvedEnv,
 ) -> anyhow::Result<(i32, String, String)> {
-    // TODO: 实现Kubernetes运行时支持
-    tracing::warn!("Kubernetes运行时支持尚未实现: {}", test_case.name);
-    Err(anyhow::anyhow!("Kubernetes运行时支持尚未实现"))
+    use crate::execution::KubernetesExecutor;
+    use crate::models::RuntimeType;
+
+    let manager = find_active_manager(state, RuntimeType::Kubernetes).await?;
+    tracing::info!("使用Kubernetes运行时管理器 {} 执行测试: {}", manager.name, test_case.name);
+    KubernetesExecutor::from_manager(&manager)?.execute(test_case, &env.vars).await
+}
+
+/// 查找第一个处于活跃状态的指定类型运行时管理器
+async fn find_active_manager(
+    state: &AppState,
+    runtime_type: crate::models::RuntimeType,
+) -> anyhow::Result<crate::models::runtime_manager::RuntimeManager> {
+    use crate::models::runtime_manager::{RuntimeManager, RuntimeManagerQuery};
+
+    let query = RuntimeManagerQuery {
+        pagination: PaginationParams { page: 1, limit: 1 },
+        name: None,
+        runtime_type: Some(runtime_type.clone()),
+        status: Some("active".to_string()),
+        tags: None,
+    };
+    RuntimeManager::find_all(state.db.pool(), query)
+        .await?
+        .data
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("没有可用的{}运行时管理器", runtime_type))
 }
 
 /// 删除测试运行记录
@@ -482,25 +895,747 @@ pub async fn delete_test_run(
     Path(id): Path<Uuid>,
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    match TestRun::get_by_id(state.db.pool(), &id).await {
-        Ok(Some(test_run)) => {
-            let logs = json!({
-                "test_run_id": test_run.id,
-                "status": test_run.status,
-                "start_time": test_run.start_time,
-                "end_time": test_run.end_time,
-                "duration_ms": test_run.duration_ms,
-                "exit_code": test_run.exit_code,
-                "stdout": test_run.stdout.unwrap_or_default(),
-                "stderr": test_run.stderr.unwrap_or_default(),
-                "metadata": test_run.metadata
-            });
-            Ok(Json(ApiResponse::success(logs.to_string())))
-        }
-        Ok(None) => Ok(Json(ApiResponse::error("测试运行记录不存在".to_string()))),
+    let test_run = match state.db.timed(TestRun::get_by_id(state.db.pool(), &id)).await {
+        Ok(Some(test_run)) => test_run,
+        Ok(None) => return Ok(Json(ApiResponse::error("测试运行记录不存在".to_string()))),
         Err(e) => {
-            tracing::error!("获取测试运行日志失败: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            tracing::error!("获取测试运行记录失败: {}", e);
+            return Err(database::error_status(&e));
+        }
+    };
+
+    if let Err(e) = state.db.timed(TestRun::delete(state.db.pool(), &id)).await {
+        tracing::error!("删除测试运行记录失败: {}", e);
+        return Err(database::error_status(&e));
+    }
+
+    // 记录删除后再清理溢出文件，清理失败只留下孤立文件
+    let storage = LogStorage::from_config(&state.config);
+    for stored in [test_run.stdout.as_deref(), test_run.stderr.as_deref()] {
+        if let Err(e) = storage.remove(stored).await {
+            tracing::warn!("清理测试运行 {} 的溢出日志失败: {}", id, e);
+        }
+    }
+
+    tracing::info!("删除测试运行记录成功: {}", id);
+    Ok(Json(ApiResponse::success("测试运行记录删除成功".to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::AppConfig,
+        database::Database,
+        models::{
+            test_case::{CreateTestCaseRequest, TestCase},
+            ResourceLimits, RuntimeType, TestAssertions,
+        },
+        services::{
+            disk_space::{tests::FixedProbe, DiskSpace, DiskSpaceChecker, DiskStatus},
+            log_storage::SPILL_REF_PREFIX,
+            probe_cache::ProbeCache,
+            resource_headroom::ResourceHeadroomChecker,
+            run_limiter::RunLimiter,
+        },
+    };
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_large_output_spills_to_file_and_stays_readable() {
+        let results_dir = tempfile::tempdir().unwrap();
+        let config = AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            log_spill_threshold_bytes: Some(1024),
+            ..AppConfig::default()
+        };
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+        };
+
+        let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+            name: "verbose".to_string(),
+            description: None,
+            script_path: "scripts/verbose.py".to_string(),
+            config_path: None,
+            runtime_type: RuntimeType::Local,
+            tags: None,
+            assertions: None,
+            env: None,
+            limits: ResourceLimits::default(),
+        }).await.unwrap();
+        let test_run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+            test_case_id: test_case.id,
+            metadata: None,
+        }).await.unwrap();
+        let id = Uuid::parse_str(&test_run.id).unwrap();
+
+        let stdout = "line\n".repeat(1000);
+        let storage = LogStorage::from_config(&state.config);
+        TestRun::update_result(
+            state.db.pool(),
+            &id,
+            TestStatus::Success,
+            None,
+            None,
+            None,
+            Some(0),
+            storage.store(&id, "stdout", Some(stdout.clone())).await.unwrap(),
+            storage.store(&id, "stderr", Some("warn".to_string())).await.unwrap(),
+        ).await.unwrap();
+
+        let stored = TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap();
+        assert!(stored.stdout.as_deref().unwrap().starts_with(SPILL_REF_PREFIX));
+        assert_eq!(stored.stderr.as_deref(), Some("warn"));
+
+        let Json(logs) = get_test_logs(Path(id), State(state.clone())).await.unwrap();
+        let logs = logs.data.unwrap();
+        assert_eq!(logs["stdout"], stdout);
+        assert_eq!(logs["stderr"], "warn");
+
+        let spilled = results_dir.path().join("logs").join(format!("{}.stdout.log", id));
+        assert!(spilled.exists());
+        delete_test_run(Path(id), State(state.clone())).await.unwrap();
+        assert!(!spilled.exists());
+        assert!(TestRun::get_by_id(state.db.pool(), &id).await.unwrap().is_none());
+    }
+
+    /// 为带断言的用例创建一次运行，并以给定的退出码与输出完成它
+    async fn run_with_assertions(
+        state: &AppState,
+        name: &str,
+        assertions: TestAssertions,
+        exit_code: i32,
+        stdout: &str,
+    ) -> (TestRun, Option<AssertionReport>) {
+        let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+            name: name.to_string(),
+            description: None,
+            script_path: format!("scripts/{}.py", name),
+            config_path: None,
+            runtime_type: RuntimeType::Local,
+            tags: None,
+            assertions: Some(assertions),
+            env: None,
+            limits: ResourceLimits::default(),
+        }).await.unwrap();
+        let test_run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+            test_case_id: test_case.id.clone(),
+            metadata: None,
+        }).await.unwrap();
+        let id = Uuid::parse_str(&test_run.id).unwrap();
+
+        let start = chrono::Utc::now();
+        let end = start + chrono::Duration::milliseconds(50);
+        let result = Ok((exit_code, stdout.to_string(), String::new()));
+        record_run_outcome(state, &id, &test_case, start, end, result).await.unwrap();
+
+        (
+            TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap(),
+            AssertionReport::find_by_run(state.db.pool(), &id).await.unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_assertions_decide_run_status() {
+        let results_dir = tempfile::tempdir().unwrap();
+        let config = AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            ..AppConfig::default()
+        };
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+        };
+        let assertions = TestAssertions {
+            expected_exit_code: Some(0),
+            stdout_contains: vec![r"passed: \d+".to_string()],
+            stdout_not_contains: vec!["Traceback".to_string()],
+            max_duration_ms: Some(1000),
+        };
+
+        // 退出码为0，但stdout未满足包含断言
+        let (run, report) = run_with_assertions(&state, "missing-summary", assertions.clone(), 0, "done\n").await;
+        assert_eq!(run.status, TestStatus::Failed.to_string());
+        let report = report.unwrap();
+        assert!(!report.passed);
+        let failed: Vec<_> = report.results.iter().filter(|r| !r.passed).map(|r| r.assertion.as_str()).collect();
+        assert_eq!(failed, vec!["stdout_contains[0]"]);
+
+        // 全部断言通过
+        let (run, report) = run_with_assertions(&state, "all-green", assertions, 0, "passed: 12\n").await;
+        assert_eq!(run.status, TestStatus::Success.to_string());
+        let report = report.unwrap();
+        assert!(report.passed);
+        assert_eq!(report.results.len(), 4);
+
+        let id = Uuid::parse_str(&run.id).unwrap();
+        delete_test_run(Path(id), State(state.clone())).await.unwrap();
+        assert!(AssertionReport::find_by_run(state.db.pool(), &id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_completed_local_run_records_timeline_in_order() {
+        let results_dir = tempfile::tempdir().unwrap();
+        let script = results_dir.path().join("hello.py");
+        std::fs::write(&script, "print('hello')\n").unwrap();
+        let config = AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            ..AppConfig::default()
+        };
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+        };
+
+        let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+            name: "hello".to_string(),
+            description: None,
+            script_path: script.to_string_lossy().to_string(),
+            config_path: None,
+            runtime_type: RuntimeType::Local,
+            tags: None,
+            assertions: None,
+            env: None,
+            limits: ResourceLimits::default(),
+        }).await.unwrap();
+        let test_run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+            test_case_id: test_case.id,
+            metadata: None,
+        }).await.unwrap();
+        let id = Uuid::parse_str(&test_run.id).unwrap();
+
+        let Json(response) = start_test_run(Path(id), State(state.clone())).await.unwrap();
+        assert!(response.success);
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        loop {
+            let run = TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap();
+            if run.end_time.is_some() {
+                break;
+            }
+            assert!(std::time::Instant::now() < deadline, "测试运行未在限定时间内结束");
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        let Json(response) = get_test_timeline(Path(id), State(state.clone())).await.unwrap();
+        let phases: Vec<_> = response.data.unwrap().iter().map(|event| event.phase).collect();
+        let position = |phase| phases.iter().position(|p| *p == phase).unwrap();
+        assert!(position(TimelinePhase::Queued) < position(TimelinePhase::Started));
+        assert!(position(TimelinePhase::Started) < position(TimelinePhase::Completed));
+        assert_eq!(phases.last(), Some(&TimelinePhase::Completed));
+        assert!(!phases.contains(&TimelinePhase::ContainerPulling));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_marks_run_timeout_and_kills_process() {
+        let results_dir = tempfile::tempdir().unwrap();
+        let pid_file = results_dir.path().join("pid");
+        let script = results_dir.path().join("slow.py");
+        std::fs::write(
+            &script,
+            format!(
+                "import os, time\nopen({:?}, 'w').write(str(os.getpid()))\ntime.sleep(5)\nprint('done')\n",
+                pid_file.to_string_lossy()
+            ),
+        ).unwrap();
+        let config = AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            ..AppConfig::default()
+        };
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+        };
+
+        let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+            name: "slow".to_string(),
+            description: None,
+            script_path: script.to_string_lossy().to_string(),
+            config_path: None,
+            runtime_type: RuntimeType::Local,
+            tags: None,
+            assertions: None,
+            env: None,
+            limits: ResourceLimits { timeout_seconds: Some(1), ..Default::default() },
+        }).await.unwrap();
+        let test_run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+            test_case_id: test_case.id,
+            metadata: None,
+        }).await.unwrap();
+        let id = Uuid::parse_str(&test_run.id).unwrap();
+
+        let started = std::time::Instant::now();
+        let Json(response) = start_test_run(Path(id), State(state.clone())).await.unwrap();
+        assert!(response.success);
+
+        let deadline = started + std::time::Duration::from_secs(10);
+        let run = loop {
+            let run = TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap();
+            if run.end_time.is_some() {
+                break run;
+            }
+            assert!(std::time::Instant::now() < deadline, "测试运行未在限定时间内结束");
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        };
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(4), "超时未及时终止运行");
+        assert_eq!(run.status, TestStatus::Timeout.to_string());
+        assert!(run.stderr.unwrap().contains(&LimitExceeded::Timeout(1).to_string()));
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        assert!(!std::path::Path::new(&format!("/proc/{}", pid)).exists(), "超时的进程 {} 仍在运行", pid);
+    }
+
+    #[tokio::test]
+    async fn test_critical_disk_space_blocks_new_runs() {
+        let config = AppConfig::default();
+        // 可用空间1%，低于默认的2%严重阈值
+        let disk_space = DiskSpaceChecker::from_config(&config).with_probe(Arc::new(FixedProbe(DiskSpace {
+            available_bytes: 10,
+            total_bytes: 1000,
+        })));
+        assert_eq!(disk_space.check().status, DiskStatus::Critical);
+
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+            disk_space: Arc::new(disk_space),
+        };
+        let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+            name: "disk-full".to_string(),
+            description: None,
+            script_path: "scripts/disk_full.py".to_string(),
+            config_path: None,
+            runtime_type: RuntimeType::Local,
+            tags: None,
+            assertions: None,
+            env: None,
+            limits: ResourceLimits::default(),
+        }).await.unwrap();
+        let test_run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+            test_case_id: test_case.id,
+            metadata: None,
+        }).await.unwrap();
+        let id = Uuid::parse_str(&test_run.id).unwrap();
+
+        let Json(response) = start_test_run(Path(id), State(state.clone())).await.unwrap();
+        assert!(!response.success);
+        assert!(response.message.unwrap().contains("磁盘空间不足"));
+
+        let stored = TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap();
+        assert_eq!(stored.get_test_status().unwrap(), TestStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_low_memory_keeps_run_pending_until_headroom_recovers() {
+        use crate::services::resource_headroom::tests::AdjustableProbe;
+        use std::sync::atomic::Ordering;
+
+        let results_dir = tempfile::tempdir().unwrap();
+        let script = results_dir.path().join("heavy.py");
+        std::fs::write(&script, "print('done')\n").unwrap();
+        let config = AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            run_min_free_memory_mb: 512,
+            ..AppConfig::default()
+        };
+        // 空闲内存100MB，低于要求的512MB
+        let probe = Arc::new(AdjustableProbe::default());
+        probe.available_memory_bytes.store(100 * 1024 * 1024, Ordering::SeqCst);
+        let resource_headroom = ResourceHeadroomChecker::from_config(&config)
+            .with_probe(probe.clone())
+            .with_recheck_interval(std::time::Duration::from_millis(20));
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(resource_headroom),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+        };
+
+        let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+            name: "heavy".to_string(),
+            description: None,
+            script_path: script.to_string_lossy().to_string(),
+            config_path: None,
+            runtime_type: RuntimeType::Local,
+            tags: None,
+            assertions: None,
+            env: None,
+            limits: ResourceLimits::default(),
+        }).await.unwrap();
+        let test_run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+            test_case_id: test_case.id.clone(),
+            metadata: None,
+        }).await.unwrap();
+        let id = Uuid::parse_str(&test_run.id).unwrap();
+        let ticket = state.run_limiter.enqueue(id, 0).unwrap();
+        let run = tokio::spawn(execute_test_run(state.clone(), id, test_case, ticket));
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        let pending = loop {
+            let stored = TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap();
+            if stored.pending_reason.is_some() {
+                break stored;
+            }
+            assert!(std::time::Instant::now() < deadline, "未记录等待原因");
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        };
+        assert_eq!(pending.get_test_status().unwrap(), TestStatus::Pending);
+        assert!(pending.pending_reason.unwrap().contains("insufficient resources"));
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!run.is_finished());
+
+        // 空闲内存恢复后运行启动并清除等待原因
+        probe.available_memory_bytes.store(4096 * 1024 * 1024, Ordering::SeqCst);
+        tokio::time::timeout(std::time::Duration::from_secs(10), run)
+            .await
+            .expect("余量恢复后运行应启动")
+            .unwrap()
+            .unwrap();
+        let stored = TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap();
+        assert_eq!(stored.get_test_status().unwrap(), TestStatus::Success);
+        assert!(stored.pending_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_docker_limit_queues_docker_runs_without_blocking_local() {
+        let results_dir = tempfile::tempdir().unwrap();
+        let script = results_dir.path().join("hello.py");
+        std::fs::write(&script, "print('hello')\n").unwrap();
+        let mut config = AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            ..AppConfig::default()
+        };
+        config.runtime_concurrency.docker = Some(1);
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+        };
+
+        let create_run = |runtime_type: RuntimeType| {
+            let state = state.clone();
+            let script_path = script.to_string_lossy().to_string();
+            async move {
+                let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+                    name: format!("{}-case", runtime_type),
+                    description: None,
+                    script_path,
+                    config_path: None,
+                    runtime_type,
+                    tags: None,
+                    assertions: None,
+                    env: None,
+                    limits: ResourceLimits::default(),
+                }).await.unwrap();
+                let test_run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+                    test_case_id: test_case.id.clone(),
+                    metadata: None,
+                }).await.unwrap();
+                (Uuid::parse_str(&test_run.id).unwrap(), test_case)
+            }
+        };
+        let status = |id: Uuid| {
+            let state = state.clone();
+            async move {
+                TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap().get_test_status().unwrap()
+            }
+        };
+
+        // 第一个Docker运行占满Docker名额
+        let first_docker = state.run_limiter.acquire(&RuntimeType::Docker).await;
+
+        let (docker_id, docker_case) = create_run(RuntimeType::Docker).await;
+        let docker_ticket = state.run_limiter.enqueue(docker_id, 0).unwrap();
+        let docker_run = tokio::spawn(execute_test_run(state.clone(), docker_id, docker_case, docker_ticket));
+
+        let (local_id, local_case) = create_run(RuntimeType::Local).await;
+        let local_ticket = state.run_limiter.enqueue(local_id, 0).unwrap();
+        tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            execute_test_run(state.clone(), local_id, local_case, local_ticket),
+        )
+        .await
+        .expect("本地运行不应被Docker名额阻塞")
+        .unwrap();
+        assert_eq!(status(local_id).await, TestStatus::Success);
+        assert_eq!(status(docker_id).await, TestStatus::Pending);
+
+        // 释放名额后排队的Docker运行继续执行
+        drop(first_docker);
+        docker_run.await.unwrap().unwrap();
+        assert_ne!(status(docker_id).await, TestStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_raised_priority_runs_ahead_of_earlier_pending_run() {
+        let results_dir = tempfile::tempdir().unwrap();
+        let script = results_dir.path().join("quick.py");
+        std::fs::write(&script, "print('ok')\n").unwrap();
+        let config = AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            max_concurrent_tests: 1,
+            ..AppConfig::default()
+        };
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+        };
+
+        let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+            name: "quick".to_string(),
+            description: None,
+            script_path: script.to_string_lossy().to_string(),
+            config_path: None,
+            runtime_type: RuntimeType::Local,
+            tags: None,
+            assertions: None,
+            env: None,
+            limits: ResourceLimits::default(),
+        }).await.unwrap();
+
+        // 唯一的名额被占用，两个运行依次进入排队
+        let occupied = state.run_limiter.acquire(&RuntimeType::Local).await;
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let test_run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+                test_case_id: test_case.id.clone(),
+                metadata: None,
+            }).await.unwrap();
+            let id = Uuid::parse_str(&test_run.id).unwrap();
+            let Json(response) = start_test_run(Path(id), State(state.clone())).await.unwrap();
+            assert!(response.success);
+            runs.push(id);
+        }
+        let (earlier, later) = (runs[0], runs[1]);
+
+        let Json(response) = set_test_run_priority(
+            Path(later),
+            State(state.clone()),
+            Json(SetPriorityRequest { priority: 10 }),
+        ).await.unwrap();
+        assert_eq!(response.data.unwrap().priority, 10);
+
+        let Json(response) = get_test_queue(State(state.clone())).await.unwrap();
+        let queued: Vec<_> = response.data.unwrap().queue.queued
+            .iter()
+            .map(|queued| (queued.position, queued.test_run_id, queued.priority))
+            .collect();
+        assert_eq!(queued, vec![(1, later, 10), (2, earlier, 0)]);
+
+        drop(occupied);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        let finished = loop {
+            let mut finished = Vec::new();
+            for id in [earlier, later] {
+                let run = TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap();
+                if run.end_time.is_some() {
+                    finished.push(run);
+                }
+            }
+            if finished.len() == 2 {
+                break finished;
+            }
+            assert!(std::time::Instant::now() < deadline, "测试运行未在限定时间内结束");
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        };
+
+        // 同一时间只有一个名额，提高优先级的运行先执行完毕
+        let (earlier_run, later_run) = (&finished[0], &finished[1]);
+        assert!(later_run.end_time.unwrap() <= earlier_run.start_time.unwrap());
+
+        // 已开始的运行不能再调整优先级
+        let Json(response) = set_test_run_priority(
+            Path(later),
+            State(state.clone()),
+            Json(SetPriorityRequest { priority: 1 }),
+        ).await.unwrap();
+        assert!(!response.success);
+    }
+
+    #[tokio::test]
+    async fn test_run_env_reaches_local_script_and_secret_is_masked() {
+        use crate::api::test_cases::run_test_case;
+        use crate::models::{test_case::RunTestCaseRequest, EnvVarInput, SECRET_MASK};
+        use crate::services::test_env::encode_env;
+        use std::collections::BTreeMap;
+
+        let results_dir = tempfile::tempdir().unwrap();
+        let script = results_dir.path().join("env.py");
+        std::fs::write(
+            &script,
+            "import os\nprint('greeting=' + os.environ['GREETING'])\nprint('token=' + os.environ['API_TOKEN'])\n",
+        ).unwrap();
+        let config = AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            secret_key: Some("test-secret-key".to_string()),
+            ..AppConfig::default()
+        };
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+        };
+
+        let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+            name: "env".to_string(),
+            description: None,
+            script_path: script.to_string_lossy().to_string(),
+            config_path: None,
+            runtime_type: RuntimeType::Local,
+            tags: None,
+            assertions: None,
+            env: None,
+            limits: ResourceLimits::default(),
+        }).await.unwrap();
+        let case_env = BTreeMap::from([("GREETING".to_string(), EnvVarInput::Plain("hello-env".to_string()))]);
+        let test_case = TestCase::set_env(state.db.pool(), &test_case.id, &encode_env(&case_env, None).unwrap())
+            .await
+            .unwrap();
+
+        // 本次运行追加一个密文变量
+        let request = RunTestCaseRequest {
+            runtime_type: None,
+            config_override: None,
+            metadata: None,
+            env: Some(BTreeMap::from([(
+                "API_TOKEN".to_string(),
+                EnvVarInput::Detailed { value: "s3cr3t-token".to_string(), secret: true },
+            )])),
+        };
+        let Json(response) = run_test_case(
+            Path(Uuid::parse_str(&test_case.id).unwrap()),
+            State(state.clone()),
+            Json(request),
+        ).await.unwrap();
+        let id = Uuid::parse_str(&response.data.unwrap().id).unwrap();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        let run = loop {
+            let run = TestRun::get_by_id(state.db.pool(), &id).await.unwrap().unwrap();
+            if run.end_time.is_some() {
+                break run;
+            }
+            assert!(std::time::Instant::now() < deadline, "测试运行未在限定时间内结束");
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        };
+
+        assert_eq!(run.status, TestStatus::Success.to_string());
+        let stdout = run.stdout.clone().unwrap();
+        assert!(stdout.contains("greeting=hello-env"));
+        assert!(stdout.contains(&format!("token={}", SECRET_MASK)));
+        assert!(!stdout.contains("s3cr3t-token"));
+
+        // 落库的是密文，响应中显示掩码
+        assert!(!run.env.as_deref().unwrap().contains("s3cr3t-token"));
+        let record = serde_json::to_value(&run).unwrap();
+        assert_eq!(record["env"]["GREETING"], "hello-env");
+        assert_eq!(record["env"]["API_TOKEN"], SECRET_MASK);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_pending_leaves_running_runs_alone() {
+        let results_dir = tempfile::tempdir().unwrap();
+        let config = AppConfig {
+            results_dir: results_dir.path().to_string_lossy().to_string(),
+            ..AppConfig::default()
+        };
+        let state = AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+            run_limiter: Arc::new(RunLimiter::from_config(&config)),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+            probe_cache: Arc::new(ProbeCache::from_config(&config)),
+            config: Arc::new(config),
+            system_info: Arc::new(crate::models::SystemInfo::collect()),
+        };
+
+        let mut runs = Vec::new();
+        for (name, tags) in [("smoke", vec!["smoke", "nightly"]), ("load", vec!["smoke-extra"])] {
+            let test_case = TestCase::create(state.db.pool(), CreateTestCaseRequest {
+                name: name.to_string(),
+                description: None,
+                script_path: format!("scripts/{}.py", name),
+                config_path: None,
+                runtime_type: RuntimeType::Local,
+                tags: Some(tags.into_iter().map(str::to_string).collect()),
+                assertions: None,
+                env: None,
+                limits: ResourceLimits::default(),
+            }).await.unwrap();
+            for _ in 0..3 {
+                let test_run = TestRun::create(state.db.pool(), CreateTestRunRequest {
+                    test_case_id: test_case.id.clone(),
+                    metadata: None,
+                }).await.unwrap();
+                runs.push(Uuid::parse_str(&test_run.id).unwrap());
+            }
+        }
+        // smoke用例的第一个运行已在执行
+        assert!(TestRun::mark_running(state.db.pool(), &runs[0]).await.unwrap());
+
+        // 按标签完整匹配，`smoke-extra` 不算 `smoke`
+        let query = CancelPendingQuery { test_case_id: None, tag: Some("smoke".to_string()) };
+        let Json(response) = cancel_pending_test_runs(Query(query), State(state.clone())).await.unwrap();
+        assert_eq!(response.data.unwrap().cancelled, 2);
+
+        let pool = state.db.pool();
+        let status = |id: Uuid| async move { TestRun::get_by_id(pool, &id).await.unwrap().unwrap().status };
+        assert_eq!(status(runs[0]).await, TestStatus::Running.to_string());
+        for id in &runs[1..3] {
+            assert_eq!(status(*id).await, TestStatus::Cancelled.to_string());
         }
+        for id in &runs[3..] {
+            assert_eq!(status(*id).await, TestStatus::Pending.to_string());
+        }
+
+        // 不带筛选时取消剩余全部等待中的运行，已取消的运行不能再切换为运行中
+        let Json(response) = cancel_pending_test_runs(Query(CancelPendingQuery::default()), State(state.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.data.unwrap().cancelled, 3);
+        assert!(!TestRun::mark_running(pool, &runs[1]).await.unwrap());
+        assert_eq!(status(runs[0]).await, TestStatus::Running.to_string());
     }
-}
\ No newline at end of file
+}
@@ -144,7 +144,7 @@ pub async fn get_current_user(
 pub async fn list_users(
     Query(params): Query<PaginationParams>,
     State(_state): State<AppState>,
-) -> Result<Json<PaginatedResponse<User>>, StatusCode> {
+) -> Result<Json<ApiResponse<PaginatedResponse<User>>>, StatusCode> {
     let users = vec![
         User {
             id: Uuid::new_v4().to_string(),
@@ -179,10 +179,10 @@ pub async fn list_users(
         has_prev: false,
     };
     
-    Ok(Json(PaginatedResponse {
+    Ok(Json(ApiResponse::success(PaginatedResponse {
         data: users,
         pagination,
-    }))
+    })))
 }
 
 /// 创建用户
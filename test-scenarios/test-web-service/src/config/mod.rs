@@ -20,6 +20,250 @@ pub struct AppConfig {
     pub results_dir: String,
     /// 最大并发测试数
     pub max_concurrent_tests: usize,
+    /// 获取数据库连接超时（秒）
+    #[serde(default = "default_db_acquire_timeout_secs")]
+    pub db_acquire_timeout_secs: u64,
+    /// 单次数据库查询超时（秒）
+    #[serde(default = "default_db_query_timeout_secs")]
+    pub db_query_timeout_secs: u64,
+    /// 单个HTTP请求的处理超时（秒），流式端点不受限制
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// 测试日志溢出阈值（字节），超过时写入 `results_dir` 下的文件，未设置时始终存入数据库
+    #[serde(default)]
+    pub log_spill_threshold_bytes: Option<usize>,
+    /// 热控服务日志目录，设置后一并检查其磁盘空间
+    #[serde(default)]
+    pub thermal_log_dir: Option<String>,
+    /// 磁盘可用空间低于该百分比时健康检查降级
+    #[serde(default = "default_disk_degraded_free_percent")]
+    pub disk_degraded_free_percent: f64,
+    /// 磁盘可用空间低于该百分比时健康检查为严重，并拒绝启动新的测试运行
+    #[serde(default = "default_disk_critical_free_percent")]
+    pub disk_critical_free_percent: f64,
+    /// 按运行时类型的并发上限，与 `max_concurrent_tests` 同时生效
+    #[serde(default)]
+    pub runtime_concurrency: RuntimeConcurrencyConfig,
+    /// 等待并发名额的测试运行上限
+    #[serde(default = "default_max_queue_depth")]
+    pub max_queue_depth: usize,
+    /// 排队已满时的处理策略
+    #[serde(default)]
+    pub queue_full_policy: QueueFullPolicy,
+    /// 启动测试运行所需的最小空闲内存（MB），不足时运行保持pending，为0时不检查
+    #[serde(default = "default_run_min_free_memory_mb")]
+    pub run_min_free_memory_mb: u64,
+    /// 启动测试运行所需的最小空闲CPU百分比，为0时不检查
+    #[serde(default)]
+    pub run_min_free_cpu_percent: f64,
+    /// 资源余量不足时重新检查的间隔（秒）
+    #[serde(default = "default_resource_recheck_interval_secs")]
+    pub resource_recheck_interval_secs: u64,
+    /// 运行时管理器探测结果（平台能力、连接测试、健康检查）的缓存有效期（秒），为0时不缓存
+    #[serde(default = "default_runtime_probe_cache_ttl_secs")]
+    pub runtime_probe_cache_ttl_secs: u64,
+    /// 跨域配置
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// HTTPS配置，未配置证书时以HTTP监听
+    #[serde(default)]
+    pub tls: TlsConfig,
+    /// 加密密文环境变量的密钥，未设置时不能保存密文环境变量
+    #[serde(default, skip_serializing)]
+    pub secret_key: Option<String>,
+}
+
+fn default_db_acquire_timeout_secs() -> u64 {
+    crate::database::DEFAULT_ACQUIRE_TIMEOUT_SECS
+}
+
+fn default_db_query_timeout_secs() -> u64 {
+    crate::database::DEFAULT_QUERY_TIMEOUT_SECS
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+fn default_max_queue_depth() -> usize {
+    50
+}
+
+fn default_run_min_free_memory_mb() -> u64 {
+    256
+}
+
+fn default_resource_recheck_interval_secs() -> u64 {
+    5
+}
+
+fn default_runtime_probe_cache_ttl_secs() -> u64 {
+    30
+}
+
+fn default_disk_degraded_free_percent() -> f64 {
+    10.0
+}
+
+fn default_disk_critical_free_percent() -> f64 {
+    2.0
+}
+
+/// 按运行时类型的并发上限
+///
+/// 未设置的运行时只受全局 `max_concurrent_tests` 约束
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeConcurrencyConfig {
+    /// 本地运行并发上限
+    pub local: Option<usize>,
+    /// Docker运行并发上限
+    pub docker: Option<usize>,
+    /// Kubernetes运行并发上限
+    pub kubernetes: Option<usize>,
+}
+
+/// 测试运行排队已满时的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueFullPolicy {
+    /// 拒绝新提交的运行，返回429
+    #[default]
+    RejectNew,
+    /// 取消排队最久的运行，为新运行腾出位置
+    DropOldestPending,
+}
+
+impl std::str::FromStr for QueueFullPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "reject_new" => Ok(Self::RejectNew),
+            "drop_oldest_pending" => Ok(Self::DropOldestPending),
+            _ => anyhow::bail!("未知的排队策略: {}（可选 reject_new、drop_oldest_pending）", value),
+        }
+    }
+}
+
+/// 跨域（CORS）配置
+///
+/// 默认只允许本机前端来源且不携带凭据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsConfig {
+    /// 允许的来源，`*` 表示任意来源
+    pub allowed_origins: Vec<String>,
+    /// 允许的HTTP方法，`*` 表示任意方法
+    pub allowed_methods: Vec<String>,
+    /// 允许的请求头，`*` 表示任意请求头
+    pub allowed_headers: Vec<String>,
+    /// 是否允许携带凭据（Cookie、Authorization）
+    pub allow_credentials: bool,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec![
+                "http://localhost:8888".to_string(),
+                "http://127.0.0.1:8888".to_string(),
+            ],
+            allowed_methods: ["GET", "POST", "PUT", "DELETE", "OPTIONS"]
+                .iter()
+                .map(|m| m.to_string())
+                .collect(),
+            allowed_headers: vec!["content-type".to_string(), "authorization".to_string()],
+            allow_credentials: false,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// 验证跨域配置
+    ///
+    /// 按CORS规范，允许凭据时来源、方法和请求头都不能使用通配符
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.allow_credentials {
+            for (name, values) in [
+                ("allowed_origins", &self.allowed_origins),
+                ("allowed_methods", &self.allowed_methods),
+                ("allowed_headers", &self.allowed_headers),
+            ] {
+                if values.iter().any(|v| v == "*") {
+                    anyhow::bail!("启用allow_credentials时{}不能包含通配符 *", name);
+                }
+            }
+        }
+
+        for origin in self.allowed_origins.iter().filter(|o| o.as_str() != "*") {
+            if !(origin.starts_with("http://") || origin.starts_with("https://")) {
+                anyhow::bail!("无效的CORS来源: {}", origin);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// HTTPS配置
+///
+/// 同时配置证书和私钥时启用HTTPS，同一端口不再接受明文HTTP
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM格式证书链路径
+    pub cert_path: Option<String>,
+    /// PEM格式私钥路径
+    pub key_path: Option<String>,
+    /// 允许的最低TLS版本
+    #[serde(default)]
+    pub min_version: TlsVersion,
+}
+
+impl TlsConfig {
+    /// 是否启用HTTPS
+    pub fn is_enabled(&self) -> bool {
+        self.cert_path.is_some() && self.key_path.is_some()
+    }
+
+    /// 验证HTTPS配置，证书和私钥必须同时配置
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.cert_path.is_some() != self.key_path.is_some() {
+            anyhow::bail!("启用HTTPS需要同时配置 tls.cert_path 和 tls.key_path");
+        }
+        Ok(())
+    }
+}
+
+/// TLS协议版本
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TlsVersion {
+    /// TLS 1.2
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+    /// TLS 1.3
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl std::str::FromStr for TlsVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "1.2" => Ok(Self::Tls12),
+            "1.3" => Ok(Self::Tls13),
+            _ => anyhow::bail!("不支持的TLS版本: {}（可选 1.2、1.3）", value),
+        }
+    }
+}
+
+/// 解析逗号分隔的环境变量值
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
 }
 
 impl Default for AppConfig {
@@ -31,6 +275,23 @@ impl Default for AppConfig {
             test_scripts_dir: "../".to_string(),
             results_dir: "./results".to_string(),
             max_concurrent_tests: 5,
+            db_acquire_timeout_secs: default_db_acquire_timeout_secs(),
+            db_query_timeout_secs: default_db_query_timeout_secs(),
+            request_timeout_secs: default_request_timeout_secs(),
+            log_spill_threshold_bytes: None,
+            thermal_log_dir: None,
+            disk_degraded_free_percent: default_disk_degraded_free_percent(),
+            disk_critical_free_percent: default_disk_critical_free_percent(),
+            runtime_concurrency: RuntimeConcurrencyConfig::default(),
+            max_queue_depth: default_max_queue_depth(),
+            queue_full_policy: QueueFullPolicy::default(),
+            run_min_free_memory_mb: default_run_min_free_memory_mb(),
+            run_min_free_cpu_percent: 0.0,
+            resource_recheck_interval_secs: default_resource_recheck_interval_secs(),
+            runtime_probe_cache_ttl_secs: default_runtime_probe_cache_ttl_secs(),
+            cors: CorsConfig::default(),
+            tls: TlsConfig::default(),
+            secret_key: None,
         }
     }
 }
@@ -65,6 +326,100 @@ impl AppConfig {
             config.max_concurrent_tests = max_concurrent.parse().unwrap_or(config.max_concurrent_tests);
         }
 
+        if let Ok(timeout) = env::var("AIOPS_DB_ACQUIRE_TIMEOUT") {
+            config.db_acquire_timeout_secs = timeout.parse().unwrap_or(config.db_acquire_timeout_secs);
+        }
+
+        if let Ok(timeout) = env::var("AIOPS_DB_QUERY_TIMEOUT") {
+            config.db_query_timeout_secs = timeout.parse().unwrap_or(config.db_query_timeout_secs);
+        }
+
+        if let Ok(timeout) = env::var("AIOPS_REQUEST_TIMEOUT") {
+            config.request_timeout_secs = timeout.parse().unwrap_or(config.request_timeout_secs);
+        }
+
+        if let Ok(threshold) = env::var("AIOPS_LOG_SPILL_THRESHOLD") {
+            config.log_spill_threshold_bytes = threshold.parse().ok().or(config.log_spill_threshold_bytes);
+        }
+
+        if let Ok(dir) = env::var("AIOPS_THERMAL_LOG_DIR") {
+            config.thermal_log_dir = Some(dir);
+        }
+
+        if let Ok(percent) = env::var("AIOPS_DISK_DEGRADED_PERCENT") {
+            config.disk_degraded_free_percent = percent.parse().unwrap_or(config.disk_degraded_free_percent);
+        }
+
+        if let Ok(percent) = env::var("AIOPS_DISK_CRITICAL_PERCENT") {
+            config.disk_critical_free_percent = percent.parse().unwrap_or(config.disk_critical_free_percent);
+        }
+
+        for (name, limit) in [
+            ("AIOPS_MAX_CONCURRENT_LOCAL", &mut config.runtime_concurrency.local),
+            ("AIOPS_MAX_CONCURRENT_DOCKER", &mut config.runtime_concurrency.docker),
+            ("AIOPS_MAX_CONCURRENT_K8S", &mut config.runtime_concurrency.kubernetes),
+        ] {
+            if let Ok(value) = env::var(name) {
+                *limit = value.parse().ok().or(*limit);
+            }
+        }
+
+        if let Ok(depth) = env::var("AIOPS_MAX_QUEUE_DEPTH") {
+            config.max_queue_depth = depth.parse().unwrap_or(config.max_queue_depth);
+        }
+
+        if let Ok(policy) = env::var("AIOPS_QUEUE_FULL_POLICY") {
+            config.queue_full_policy = policy.parse()?;
+        }
+
+        if let Ok(memory) = env::var("AIOPS_RUN_MIN_FREE_MEMORY_MB") {
+            config.run_min_free_memory_mb = memory.parse().unwrap_or(config.run_min_free_memory_mb);
+        }
+
+        if let Ok(percent) = env::var("AIOPS_RUN_MIN_FREE_CPU_PERCENT") {
+            config.run_min_free_cpu_percent = percent.parse().unwrap_or(config.run_min_free_cpu_percent);
+        }
+
+        if let Ok(interval) = env::var("AIOPS_RESOURCE_RECHECK_INTERVAL") {
+            config.resource_recheck_interval_secs = interval.parse().unwrap_or(config.resource_recheck_interval_secs);
+        }
+
+        if let Ok(ttl) = env::var("AIOPS_RUNTIME_PROBE_CACHE_TTL") {
+            config.runtime_probe_cache_ttl_secs = ttl.parse().unwrap_or(config.runtime_probe_cache_ttl_secs);
+        }
+
+        if let Ok(origins) = env::var("AIOPS_CORS_ORIGINS") {
+            config.cors.allowed_origins = split_list(&origins);
+        }
+
+        if let Ok(methods) = env::var("AIOPS_CORS_METHODS") {
+            config.cors.allowed_methods = split_list(&methods);
+        }
+
+        if let Ok(headers) = env::var("AIOPS_CORS_HEADERS") {
+            config.cors.allowed_headers = split_list(&headers);
+        }
+
+        if let Ok(credentials) = env::var("AIOPS_CORS_ALLOW_CREDENTIALS") {
+            config.cors.allow_credentials = credentials.parse().unwrap_or(config.cors.allow_credentials);
+        }
+
+        if let Ok(cert) = env::var("AIOPS_TLS_CERT") {
+            config.tls.cert_path = Some(cert).filter(|path| !path.is_empty());
+        }
+
+        if let Ok(key) = env::var("AIOPS_TLS_KEY") {
+            config.tls.key_path = Some(key).filter(|path| !path.is_empty());
+        }
+
+        if let Ok(version) = env::var("AIOPS_TLS_MIN_VERSION") {
+            config.tls.min_version = version.parse()?;
+        }
+
+        if let Ok(secret_key) = env::var("AIOPS_SECRET_KEY") {
+            config.secret_key = Some(secret_key).filter(|key| !key.is_empty());
+        }
+
         Ok(config)
     }
 
@@ -82,6 +437,64 @@ impl AppConfig {
             anyhow::bail!("最大并发测试数不能为0");
         }
 
+        let limits = &self.runtime_concurrency;
+        if [limits.local, limits.docker, limits.kubernetes].contains(&Some(0)) {
+            anyhow::bail!("运行时并发上限不能为0");
+        }
+
+        if self.db_acquire_timeout_secs == 0 || self.db_query_timeout_secs == 0 {
+            anyhow::bail!("数据库超时不能为0");
+        }
+
+        if self.request_timeout_secs == 0 {
+            anyhow::bail!("请求超时不能为0");
+        }
+
+        if !(0.0..=100.0).contains(&self.disk_critical_free_percent)
+            || !(0.0..=100.0).contains(&self.disk_degraded_free_percent)
+        {
+            anyhow::bail!("磁盘空间阈值必须在0-100之间");
+        }
+
+        if self.disk_critical_free_percent > self.disk_degraded_free_percent {
+            anyhow::bail!("磁盘空间严重阈值不能高于降级阈值");
+        }
+
+        if !(0.0..=100.0).contains(&self.run_min_free_cpu_percent) {
+            anyhow::bail!("最小空闲CPU百分比必须在0-100之间");
+        }
+
+        if self.resource_recheck_interval_secs == 0 {
+            anyhow::bail!("资源余量重新检查间隔不能为0");
+        }
+
+        self.cors.validate()?;
+        self.tls.validate()?;
+
         Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_cors_is_restrictive() {
+        let config = AppConfig::default();
+        assert!(!config.cors.allowed_origins.iter().any(|o| o == "*"));
+        assert!(!config.cors.allow_credentials);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_credentials_with_wildcard_origin_rejected() {
+        let mut config = AppConfig::default();
+        config.cors.allowed_origins = vec!["*".to_string()];
+        assert!(config.validate().is_ok());
+
+        config.cors.allow_credentials = true;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("allowed_origins"));
+    }
 }
\ No newline at end of file
@@ -2,19 +2,69 @@
 //! 
 //! 管理SQLite数据库连接、表结构和基础操作
 
-use sqlx::{sqlite::SqlitePool, Row, Sqlite};
-use std::path::Path;
+use axum::http::StatusCode;
+use sqlx::{sqlite::{SqlitePool, SqlitePoolOptions}, Row, Sqlite};
+use std::{future::Future, path::Path, time::Duration};
 use tracing::{info, error, warn};
 
+pub mod seed;
+
+/// 默认获取连接超时（秒）
+pub const DEFAULT_ACQUIRE_TIMEOUT_SECS: u64 = 5;
+/// 默认单次查询超时（秒）
+pub const DEFAULT_QUERY_TIMEOUT_SECS: u64 = 10;
+
+/// 数据库操作错误
+#[derive(Debug, thiserror::Error)]
+pub enum DatabaseError {
+    /// 查询在限定时间内未完成
+    #[error("数据库操作超时（超过 {0:?}）")]
+    Timeout(Duration),
+}
+
+/// 将数据库相关错误映射为HTTP状态码
+///
+/// 查询超时或连接池获取超时返回503，其余返回500
+pub fn error_status(err: &anyhow::Error) -> StatusCode {
+    if err.downcast_ref::<DatabaseError>().is_some()
+        || matches!(err.downcast_ref::<sqlx::Error>(), Some(sqlx::Error::PoolTimedOut))
+    {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
 /// 数据库连接管理器
 #[derive(Debug, Clone)]
 pub struct Database {
     pool: SqlitePool,
+    /// 单次查询超时
+    query_timeout: Duration,
 }
 
 impl Database {
-    /// 创建新的数据库连接
+    /// 使用默认超时创建新的数据库连接
     pub async fn new(database_url: &str) -> anyhow::Result<Self> {
+        Self::with_timeouts(
+            database_url,
+            Duration::from_secs(DEFAULT_ACQUIRE_TIMEOUT_SECS),
+            Duration::from_secs(DEFAULT_QUERY_TIMEOUT_SECS),
+        )
+        .await
+    }
+
+    /// 创建新的数据库连接
+    ///
+    /// # 参数
+    /// * `database_url` - 数据库连接URL
+    /// * `acquire_timeout` - 从连接池获取连接的超时
+    /// * `query_timeout` - 通过 [`Database::timed`] 执行的单次查询超时
+    pub async fn with_timeouts(
+        database_url: &str,
+        acquire_timeout: Duration,
+        query_timeout: Duration,
+    ) -> anyhow::Result<Self> {
         info!("正在连接数据库: {}", database_url);
         
         // 确保数据库文件目录存在
@@ -28,14 +78,17 @@ impl Database {
 
         // 创建连接池
         info!("创建数据库连接池...");
-        let pool = SqlitePool::connect(database_url).await
+        let pool = SqlitePoolOptions::new()
+            .acquire_timeout(acquire_timeout)
+            .connect(database_url)
+            .await
             .map_err(|e| {
                 error!("数据库连接失败: {}", e);
                 e
             })?;
         
         info!("数据库连接池创建成功");
-        let db = Self { pool };
+        let db = Self { pool, query_timeout };
         
         // 初始化数据库表
         db.init_tables().await?;
@@ -48,6 +101,22 @@ impl Database {
         &self.pool
     }
 
+    /// 在查询超时内执行数据库操作
+    ///
+    /// 超时后放弃等待并返回 [`DatabaseError::Timeout`]，避免卡死的查询阻塞请求处理
+    pub async fn timed<T, F>(&self, operation: F) -> anyhow::Result<T>
+    where
+        F: Future<Output = anyhow::Result<T>>,
+    {
+        match tokio::time::timeout(self.query_timeout, operation).await {
+            Ok(result) => result,
+            Err(_) => {
+                warn!("数据库操作超时: {:?}", self.query_timeout);
+                Err(DatabaseError::Timeout(self.query_timeout).into())
+            }
+        }
+    }
+
     /// 初始化数据库表结构
     async fn init_tables(&self) -> anyhow::Result<()> {
         info!("初始化数据库表结构...");
@@ -63,6 +132,11 @@ impl Database {
                 config_path TEXT,
                 runtime_type TEXT NOT NULL DEFAULT 'local',
                 tags TEXT,
+                assertions TEXT,
+                env TEXT,
+                timeout_seconds INTEGER,
+                max_memory_mb INTEGER,
+                max_cpu REAL,
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                 updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
             )
@@ -85,6 +159,9 @@ impl Database {
                 stdout TEXT,
                 stderr TEXT,
                 metadata TEXT,
+                env TEXT,
+                pending_reason TEXT,
+                priority INTEGER NOT NULL DEFAULT 0,
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                 FOREIGN KEY (test_case_id) REFERENCES test_cases (id)
             )
@@ -120,6 +197,43 @@ impl Database {
         .await
         .ok(); // 忽略错误，因为字段可能已存在
 
+        // 为已存在的test_cases表添加assertions字段（如果不存在）
+        sqlx::query(
+            "ALTER TABLE test_cases ADD COLUMN assertions TEXT"
+        )
+        .execute(&self.pool)
+        .await
+        .ok(); // 忽略错误，因为字段可能已存在
+
+        // 为已存在的test_cases和test_runs表添加env字段（如果不存在）
+        for sql in [
+            "ALTER TABLE test_cases ADD COLUMN env TEXT",
+            "ALTER TABLE test_runs ADD COLUMN env TEXT",
+        ] {
+            sqlx::query(sql).execute(&self.pool).await.ok(); // 忽略错误，因为字段可能已存在
+        }
+
+        // 为已存在的test_cases表添加资源限制字段（如果不存在）
+        for sql in [
+            "ALTER TABLE test_cases ADD COLUMN timeout_seconds INTEGER",
+            "ALTER TABLE test_cases ADD COLUMN max_memory_mb INTEGER",
+            "ALTER TABLE test_cases ADD COLUMN max_cpu REAL",
+        ] {
+            sqlx::query(sql).execute(&self.pool).await.ok(); // 忽略错误，因为字段可能已存在
+        }
+
+        // 为已存在的test_runs表添加pending_reason字段（如果不存在）
+        sqlx::query("ALTER TABLE test_runs ADD COLUMN pending_reason TEXT")
+            .execute(&self.pool)
+            .await
+            .ok(); // 忽略错误，因为字段可能已存在
+
+        // 为已存在的test_runs表添加priority字段（如果不存在）
+        sqlx::query("ALTER TABLE test_runs ADD COLUMN priority INTEGER NOT NULL DEFAULT 0")
+            .execute(&self.pool)
+            .await
+            .ok(); // 忽略错误，因为字段可能已存在
+
         // 测试结果表
         sqlx::query(
             r#"
@@ -166,8 +280,10 @@ impl Database {
     pub async fn health_check(&self) -> anyhow::Result<bool> {
         info!("执行数据库健康检查...");
         
-        let result = sqlx::query("SELECT 1 as test")
-            .fetch_one(&self.pool)
+        let result = self
+            .timed(async {
+                Ok(sqlx::query("SELECT 1 as test").fetch_one(&self.pool).await?)
+            })
             .await
             .map_err(|e| {
                 error!("数据库健康检查失败: {}", e);
@@ -216,4 +332,41 @@ pub struct DatabaseStats {
     pub test_cases_count: u64,
     pub test_runs_count: u64,
     pub active_managers_count: u64,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_query_exceeding_timeout_returns_timeout_error() {
+        let db = Database::with_timeouts(
+            "sqlite::memory:",
+            Duration::from_secs(1),
+            Duration::from_millis(50),
+        )
+        .await
+        .unwrap();
+
+        let started = std::time::Instant::now();
+        let result: anyhow::Result<i64> = db
+            .timed(async {
+                // 模拟卡住的查询
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(sqlx::query_scalar("SELECT 1").fetch_one(db.pool()).await?)
+            })
+            .await;
+
+        let err = result.unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(matches!(err.downcast_ref::<DatabaseError>(), Some(DatabaseError::Timeout(_))));
+        assert_eq!(error_status(&err), StatusCode::SERVICE_UNAVAILABLE);
+
+        // 未超时的查询正常返回
+        let value: i64 = db
+            .timed(async { Ok(sqlx::query_scalar("SELECT 1").fetch_one(db.pool()).await?) })
+            .await
+            .unwrap();
+        assert_eq!(value, 1);
+    }
+}
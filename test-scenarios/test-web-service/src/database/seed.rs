@@ -0,0 +1,219 @@
+//! 示例数据
+//!
+//! 为本地开发和演示填充一组测试用例、运行时管理器和历史运行记录。
+//! 所有记录使用固定ID并以 `INSERT OR IGNORE` 写入，重复执行不会产生重复数据，
+//! 也不会覆盖已被修改过的示例记录
+
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+use tracing::info;
+
+use crate::models::{ManagerStatus, TestStatus};
+
+/// 示例测试用例：(ID, 名称, 描述, 脚本路径, 运行时类型, 标签)
+const SEED_TEST_CASES: &[(&str, &str, &str, &str, &str, &str)] = &[
+    (
+        "seed-case-cpu-stress",
+        "CPU满载温控响应",
+        "CPU满载10分钟，检查风扇转速能否在5分钟内把温度压回目标值",
+        "scripts/thermal/cpu_stress.py",
+        "local",
+        "thermal,stress",
+    ),
+    (
+        "seed-case-fan-failover",
+        "单风扇故障切换",
+        "模拟FAN2停转，检查其余风扇是否提升转速补偿",
+        "scripts/thermal/fan_failover.py",
+        "docker",
+        "thermal,fan,failover",
+    ),
+    (
+        "seed-case-ipmi-sdr",
+        "IPMI传感器读取",
+        "读取SDR并校验温度、风扇传感器齐全且读数在合理范围",
+        "scripts/ipmi/sdr_check.sh",
+        "local",
+        "ipmi,smoke",
+    ),
+    (
+        "seed-case-k8s-rollout",
+        "监控组件滚动升级",
+        "在测试集群中滚动升级采集组件，检查升级期间指标不断档",
+        "scripts/k8s/rollout_check.py",
+        "kubernetes",
+        "k8s,monitoring",
+    ),
+];
+
+/// 示例运行时管理器：(ID, 名称, 运行时类型, 状态)
+const SEED_RUNTIME_MANAGERS: &[(&str, &str, &str, ManagerStatus)] = &[
+    ("seed-manager-local", "seed-local", "local", ManagerStatus::Active),
+    ("seed-manager-docker", "seed-docker", "docker", ManagerStatus::Active),
+    ("seed-manager-k8s", "seed-k8s-staging", "kubernetes", ManagerStatus::Maintenance),
+];
+
+/// 每个示例测试用例的历史运行结果，按时间从早到晚
+const SEED_RUN_HISTORY: &[TestStatus] = &[
+    TestStatus::Success,
+    TestStatus::Failed,
+    TestStatus::Success,
+    TestStatus::Timeout,
+    TestStatus::Success,
+];
+
+/// 本次填充新插入的记录数，已存在的记录不计入
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize)]
+pub struct SeedSummary {
+    pub test_cases: u64,
+    pub runtime_managers: u64,
+    pub test_runs: u64,
+}
+
+/// 示例运行时管理器配置
+fn manager_config(runtime_type: &str) -> serde_json::Value {
+    match runtime_type {
+        "docker" => json!({ "docker": { "host": "unix:///var/run/docker.sock", "network": "aiops-test" } }),
+        "kubernetes" => json!({ "kubernetes": { "namespace": "aiops-test", "image": "python:3.11-slim", "timeout_seconds": 1800 } }),
+        _ => json!({ "local": { "python_path": "python3", "working_directory": "./test-scenarios", "timeout_seconds": 600 } }),
+    }
+}
+
+/// 填充示例数据
+///
+/// 在单个事务中写入，中途失败时不会留下部分数据
+///
+/// # 参数
+/// * `pool` - 数据库连接池，表结构需已初始化
+pub async fn seed(pool: &SqlitePool) -> anyhow::Result<SeedSummary> {
+    let mut summary = SeedSummary::default();
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+
+    for (id, name, runtime_type, status) in SEED_RUNTIME_MANAGERS {
+        let tags = serde_json::to_string(&["seed", *runtime_type])?;
+        let config = manager_config(runtime_type).to_string();
+        let created_at = now - Duration::days(30);
+
+        summary.runtime_managers += sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO runtime_managers (id, name, runtime_type, config, status, tags, last_heartbeat, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(name)
+        .bind(runtime_type)
+        .bind(&config)
+        .bind(status.to_string())
+        .bind(&tags)
+        .bind(now - Duration::minutes(1))
+        .bind(created_at)
+        .bind(created_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+
+    for (case_index, (id, name, description, script_path, runtime_type, tags)) in SEED_TEST_CASES.iter().enumerate() {
+        let created_at = now - Duration::days(14);
+
+        summary.test_cases += sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO test_cases (id, name, description, script_path, runtime_type, tags, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(name)
+        .bind(description)
+        .bind(script_path)
+        .bind(runtime_type)
+        .bind(tags)
+        .bind(created_at)
+        .bind(created_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        for (run_index, status) in SEED_RUN_HISTORY.iter().enumerate() {
+            let days_ago = (SEED_RUN_HISTORY.len() - run_index) as i64;
+            let start_time = now - Duration::days(days_ago) + Duration::minutes(case_index as i64 * 15);
+            let duration_ms: i64 = match status {
+                TestStatus::Timeout => 600_000,
+                _ => 45_000 + (case_index as i64 * 20_000) + (run_index as i64 * 3_000),
+            };
+            let (exit_code, stdout, stderr) = match status {
+                TestStatus::Success => (Some(0), format!("{}: 全部检查通过\n", name), String::new()),
+                TestStatus::Failed => (Some(1), format!("{}: 开始执行\n", name), "AssertionError: 温度未在期限内回落\n".to_string()),
+                _ => (None, format!("{}: 开始执行\n", name), "执行超时，已终止\n".to_string()),
+            };
+            let metadata = json!({ "seed": true, "trigger": "schedule" }).to_string();
+
+            summary.test_runs += sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO test_runs (id, test_case_id, status, start_time, end_time, duration_ms, exit_code, stdout, stderr, metadata, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(format!("seed-run-{}-{}", id.trim_start_matches("seed-case-"), run_index + 1))
+            .bind(id)
+            .bind(status.to_string())
+            .bind(start_time)
+            .bind(start_time + Duration::milliseconds(duration_ms))
+            .bind(duration_ms)
+            .bind(exit_code)
+            .bind(&stdout)
+            .bind(&stderr)
+            .bind(&metadata)
+            .bind(start_time)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+    }
+
+    tx.commit().await?;
+
+    info!(
+        "示例数据填充完成: 新增测试用例 {} 个、运行时管理器 {} 个、历史运行 {} 条",
+        summary.test_cases, summary.runtime_managers, summary.test_runs
+    );
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+
+    #[tokio::test]
+    async fn test_seeding_twice_does_not_duplicate_rows() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+
+        let first = seed(db.pool()).await.unwrap();
+        assert_eq!(
+            first,
+            SeedSummary {
+                test_cases: 4,
+                runtime_managers: 3,
+                test_runs: 20,
+            }
+        );
+
+        let second = seed(db.pool()).await.unwrap();
+        assert_eq!(second, SeedSummary::default());
+
+        let stats = db.get_stats().await.unwrap();
+        assert_eq!(stats.test_cases_count, 4);
+        assert_eq!(stats.test_runs_count, 20);
+        assert_eq!(stats.active_managers_count, 2);
+
+        let managers: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM runtime_managers")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(managers, 3);
+    }
+}
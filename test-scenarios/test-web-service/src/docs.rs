@@ -7,10 +7,19 @@ use utoipa_swagger_ui::SwaggerUi;
 
 use crate::models::{
     ApiResponse, PaginatedResponse, PaginationInfo, PaginationParams,
-    RuntimeType, TestStatus,
-    test_case::{TestCase, CreateTestCaseRequest, UpdateTestCaseRequest, RunTestCaseRequest, TestCaseQuery},
-    test_run::{TestRun, CreateTestRunRequest, UpdateTestRunRequest, TestRunQuery, TestRunStats},
+    RuntimeType, TestStatus, TestAssertions, AssertionResult, AssertionReport,
+    TimelineEvent, TimelinePhase, EnvVarInput,
+    test_case::{
+        TestCase, CreateTestCaseRequest, UpdateTestCaseRequest, RunTestCaseRequest, TestCaseQuery,
+        TestCaseExport, TestCaseExportQuery, ImportStrategy, ImportTestCasesParams,
+        ImportTestCasesReport, ImportItemResult, ImportItemStatus,
+    },
+    test_run::{
+        TestRun, CreateTestRunRequest, UpdateTestRunRequest, TestRunQuery, TestRunStats,
+        CancelPendingQuery, CancelPendingResult, SetPriorityRequest,
+    },
     TestCaseApiResponse, TestCasePaginatedResponse, TestRunApiResponse, StringApiResponse,
+    ValidationErrors,
 };
 
 /// API文档结构定义
@@ -32,16 +41,24 @@ use crate::models::{
         crate::api::test_cases::update_test_case,
         crate::api::test_cases::delete_test_case,
         crate::api::test_cases::run_test_case,
+        crate::api::test_cases::export_test_cases,
+        crate::api::test_cases::import_test_cases,
+        crate::api::test_cases::get_case_run_logs,
         
         // 测试运行记录
         crate::api::test_runs::list_test_runs,
         crate::api::test_runs::create_test_run,
         crate::api::test_runs::get_test_run,
         crate::api::test_runs::update_test_run,
+        crate::api::test_runs::delete_test_run,
         crate::api::test_runs::start_test_run,
         crate::api::test_runs::stop_test_run,
         crate::api::test_runs::get_test_logs,
+        crate::api::test_runs::get_test_timeline,
         crate::api::test_runs::get_test_stats,
+        crate::api::test_runs::get_test_queue,
+        crate::api::test_runs::cancel_pending_test_runs,
+        crate::api::test_runs::set_test_run_priority,
         
         // 运行时管理器
         crate::api::runtime_managers::list_managers,
@@ -55,11 +72,13 @@ use crate::models::{
         crate::api::runtime_managers::get_setup_guide,
         crate::api::runtime_managers::health_check,
         crate::api::runtime_managers::get_runtime_info,
-        crate::api::runtime_managers::get_runtime_resources
+        crate::api::runtime_managers::get_runtime_resources,
+        crate::api::runtime_managers::get_runtime_capabilities
     ),
     components(
         schemas(
             StringApiResponse,
+            ValidationErrors,
             TestCaseApiResponse,
             TestRunApiResponse,
             TestCasePaginatedResponse,
@@ -72,11 +91,27 @@ use crate::models::{
             UpdateTestCaseRequest,
             RunTestCaseRequest,
             TestCaseQuery,
+            TestCaseExport,
+            TestCaseExportQuery,
+            ImportStrategy,
+            ImportTestCasesParams,
+            ImportTestCasesReport,
+            ImportItemResult,
+            ImportItemStatus,
+            TestAssertions,
+            EnvVarInput,
+            AssertionResult,
+            AssertionReport,
+            TimelineEvent,
+            TimelinePhase,
             TestRun,
             CreateTestRunRequest,
             UpdateTestRunRequest,
             TestRunQuery,
             TestRunStats,
+            CancelPendingQuery,
+            CancelPendingResult,
+            SetPriorityRequest,
         )
     ),
     tags(
@@ -104,10 +139,18 @@ use crate::models::{
 )]
 pub struct ApiDoc;
 
+/// Swagger UI挂载路径
+///
+/// `/api/v1/docs` 由系统API返回文档入口JSON，Swagger UI不能与其重叠
+pub const SWAGGER_UI_PATH: &str = "/swagger-ui";
+
+/// OpenAPI JSON文档路径
+pub const OPENAPI_JSON_PATH: &str = "/api/v1/openapi.json";
+
 /// 创建Swagger UI服务
 pub fn create_swagger_ui() -> SwaggerUi {
-    SwaggerUi::new("/api/v1/docs")
-        .url("/api/v1/docs/openapi.json", ApiDoc::openapi())
+    SwaggerUi::new(SWAGGER_UI_PATH)
+        .url(OPENAPI_JSON_PATH, ApiDoc::openapi())
 }
 
 /// 获取OpenAPI JSON文档
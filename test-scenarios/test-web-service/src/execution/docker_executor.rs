@@ -0,0 +1,240 @@
+//! Docker容器执行引擎
+//!
+//! 在容器中用python执行测试脚本，脚本所在目录只读挂载为容器的工作目录，
+//! 用例的内存和CPU限制作为容器的资源限制
+
+use crate::models::runtime_manager::{DockerConfig, RuntimeConfig, RuntimeManager};
+use crate::models::test_case::TestCase;
+use crate::models::{LimitExceeded, ResourceLimits};
+use anyhow::Context;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::process::Command;
+use uuid::Uuid;
+
+/// 默认测试镜像
+const DEFAULT_IMAGE: &str = "python:3.11-slim";
+/// 容器内挂载脚本目录的工作目录
+const CONTAINER_WORKDIR: &str = "/workspace";
+
+/// Docker执行器
+pub struct DockerExecutor {
+    /// 运行时管理器中的Docker配置
+    config: DockerConfig,
+}
+
+impl DockerExecutor {
+    /// 创建新的Docker执行器
+    pub fn new(config: DockerConfig) -> Self {
+        Self { config }
+    }
+
+    /// 从运行时管理器配置创建执行器
+    ///
+    /// 配置既可以是完整的运行时配置（`{"docker": {...}}`），也可以直接是Docker配置
+    pub fn from_manager(manager: &RuntimeManager) -> anyhow::Result<Self> {
+        let config = match manager.get_config() {
+            Some(value) => match serde_json::from_value::<RuntimeConfig>(value.clone()) {
+                Ok(RuntimeConfig { docker: Some(docker), .. }) => docker,
+                _ => serde_json::from_value::<DockerConfig>(value)?,
+            },
+            None => DockerConfig::default(),
+        };
+        Ok(Self::new(config))
+    }
+
+    /// 执行测试用例，返回 (退出码, 标准输出, 标准错误)
+    ///
+    /// 超时或容器因超出内存限制被终止时返回 [`LimitExceeded`] 错误，容器在结束后删除
+    ///
+    /// # 参数
+    /// * `test_case` - 测试用例
+    /// * `env` - 注入容器的环境变量
+    pub async fn execute(
+        &self,
+        test_case: &TestCase,
+        env: &BTreeMap<String, String>,
+    ) -> anyhow::Result<(i32, String, String)> {
+        let name = container_name_for(&test_case.id);
+        let timeout = test_case.limits.timeout();
+
+        // 环境变量只在命令行中写变量名，值经docker客户端的环境传入容器，避免出现在进程列表中
+        let run = self
+            .docker()
+            .args(self.run_args(&name, test_case, env)?)
+            .envs(env)
+            .kill_on_drop(true)
+            .output();
+        let result = match tokio::time::timeout(timeout, run).await {
+            Ok(output) => {
+                let output = output.context("执行docker命令失败")?;
+                match test_case.limits.max_memory_mb {
+                    Some(max_memory_mb) if self.oom_killed(&name).await => Err(LimitExceeded::Memory(max_memory_mb).into()),
+                    _ => Ok((
+                        output.status.code().unwrap_or(-1),
+                        String::from_utf8_lossy(&output.stdout).to_string(),
+                        String::from_utf8_lossy(&output.stderr).to_string(),
+                    )),
+                }
+            }
+            Err(_) => Err(LimitExceeded::Timeout(timeout.as_secs()).into()),
+        };
+
+        if let Err(e) = self.remove_container(&name).await {
+            tracing::warn!("清理测试容器失败: {} -> {}", name, e);
+        }
+
+        result
+    }
+
+    /// 生成 `docker run` 参数
+    ///
+    /// 不使用 `--rm`，以便结束后检查容器是否因内存超限被终止
+    pub fn run_args(
+        &self,
+        name: &str,
+        test_case: &TestCase,
+        env: &BTreeMap<String, String>,
+    ) -> anyhow::Result<Vec<String>> {
+        let script = Path::new(&test_case.script_path);
+        let script_name = script
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("无效的脚本路径: {}", test_case.script_path))?;
+        let script_dir = script.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let script_dir = script_dir
+            .canonicalize()
+            .with_context(|| format!("脚本目录不存在: {}", script_dir.display()))?;
+
+        let mut args = vec!["run".to_string(), "--name".to_string(), name.to_string()];
+        if let Some(network) = &self.config.network {
+            args.push(format!("--network={}", network));
+        }
+        args.extend(limit_args(&test_case.limits));
+        for name in env.keys() {
+            args.push("--env".to_string());
+            args.push(name.clone());
+        }
+        args.push(format!("--volume={}:{}:ro", script_dir.display(), CONTAINER_WORKDIR));
+        args.push(format!("--workdir={}", CONTAINER_WORKDIR));
+        args.push(self.config.image.as_deref().unwrap_or(DEFAULT_IMAGE).to_string());
+        args.push("python".to_string());
+        args.push(script_name.to_string_lossy().to_string());
+        if let Some(config_path) = &test_case.config_path {
+            args.push("--config".to_string());
+            args.push(config_path.clone());
+        }
+
+        Ok(args)
+    }
+
+    /// 构造带有守护进程连接参数的docker命令
+    fn docker(&self) -> Command {
+        let mut cmd = Command::new("docker");
+        if let Some(host) = &self.config.host {
+            cmd.arg("--host").arg(host);
+        }
+        if self.config.tls_verify == Some(true) {
+            cmd.arg("--tlsverify");
+        }
+        if let Some(cert_path) = &self.config.cert_path {
+            cmd.env("DOCKER_CERT_PATH", cert_path);
+        }
+        if let Some(api_version) = &self.config.api_version {
+            cmd.env("DOCKER_API_VERSION", api_version);
+        }
+        cmd
+    }
+
+    /// 容器是否因超出内存限制被终止，查询失败时视为否
+    async fn oom_killed(&self, name: &str) -> bool {
+        self.docker()
+            .args(["inspect", "--format", "{{.State.OOMKilled}}", name])
+            .output()
+            .await
+            .is_ok_and(|output| output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "true")
+    }
+
+    /// 强制删除容器，超时后仍在运行的容器一并停止
+    async fn remove_container(&self, name: &str) -> anyhow::Result<()> {
+        let output = self.docker().args(["rm", "--force", name]).output().await?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 资源限制对应的 `docker run` 参数
+fn limit_args(limits: &ResourceLimits) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(max_memory_mb) = limits.max_memory_mb {
+        // swap上限与内存上限相同，即不允许使用swap
+        args.push(format!("--memory={}m", max_memory_mb));
+        args.push(format!("--memory-swap={}m", max_memory_mb));
+    }
+    if let Some(max_cpu) = limits.max_cpu {
+        args.push(format!("--cpus={}", max_cpu));
+    }
+    args
+}
+
+/// 生成容器名称
+fn container_name_for(test_case_id: &str) -> String {
+    let prefix: String = test_case_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(8)
+        .collect::<String>()
+        .to_lowercase();
+    let suffix = Uuid::new_v4().simple().to_string();
+    format!("aiops-test-{}-{}", prefix, &suffix[..8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_run_args_apply_case_limits() {
+        let scripts = tempfile::tempdir().unwrap();
+        let script = scripts.path().join("smoke.py");
+        std::fs::write(&script, "print('ok')\n").unwrap();
+        let test_case = TestCase {
+            id: Uuid::new_v4().to_string(),
+            name: "docker-smoke".to_string(),
+            description: None,
+            script_path: script.to_string_lossy().to_string(),
+            config_path: None,
+            runtime_type: "docker".to_string(),
+            tags: None,
+            assertions: None,
+            env: None,
+            limits: ResourceLimits {
+                timeout_seconds: Some(60),
+                max_memory_mb: Some(256),
+                max_cpu: Some(0.5),
+            },
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let executor = DockerExecutor::new(DockerConfig {
+            network: Some("aiops".to_string()),
+            ..Default::default()
+        });
+        let env = BTreeMap::from([("TOKEN".to_string(), "s3cr3t".to_string())]);
+
+        let args = executor.run_args("aiops-test-case", &test_case, &env).unwrap();
+        for expected in ["--network=aiops", "--memory=256m", "--memory-swap=256m", "--cpus=0.5", "TOKEN"] {
+            assert!(args.iter().any(|arg| arg == expected), "缺少参数 {}: {:?}", expected, args);
+        }
+        // 环境变量的值不出现在命令行中
+        assert!(!args.iter().any(|arg| arg.contains("s3cr3t")));
+        let volume = format!("--volume={}:{}:ro", scripts.path().canonicalize().unwrap().display(), CONTAINER_WORKDIR);
+        assert!(args.contains(&volume));
+        assert_eq!(&args[args.len() - 3..], [DEFAULT_IMAGE, "python", "smoke.py"]);
+    }
+}
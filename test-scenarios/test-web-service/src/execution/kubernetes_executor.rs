@@ -0,0 +1,605 @@
+//! Kubernetes Job执行引擎
+//!
+//! 将测试用例以Job的形式提交到集群执行，并收集Pod日志作为测试输出
+
+use crate::models::runtime_manager::{KubernetesConfig, RuntimeConfig, RuntimeManager};
+use crate::models::test_case::TestCase;
+use crate::models::{LimitExceeded, ResourceLimits, DEFAULT_RUN_TIMEOUT};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use uuid::Uuid;
+
+/// 默认测试镜像
+const DEFAULT_IMAGE: &str = "python:3.11-slim";
+/// 默认命名空间
+const DEFAULT_NAMESPACE: &str = "default";
+
+/// Job最终状态
+#[derive(Debug, Clone, PartialEq)]
+enum JobOutcome {
+    /// 执行成功
+    Succeeded,
+    /// 执行失败，附带失败原因
+    Failed(String),
+    /// 等待超时
+    TimedOut,
+}
+
+/// Kubernetes执行器
+pub struct KubernetesExecutor {
+    /// 运行时管理器中的Kubernetes配置
+    config: KubernetesConfig,
+    /// Job状态轮询间隔
+    poll_interval: Duration,
+}
+
+impl KubernetesExecutor {
+    /// 创建新的Kubernetes执行器
+    pub fn new(config: KubernetesConfig) -> Self {
+        Self {
+            config,
+            poll_interval: Duration::from_secs(2),
+        }
+    }
+
+    /// 从运行时管理器配置创建执行器
+    ///
+    /// 配置既可以是完整的运行时配置（`{"kubernetes": {...}}`），也可以直接是Kubernetes配置
+    pub fn from_manager(manager: &RuntimeManager) -> anyhow::Result<Self> {
+        let config = match manager.get_config() {
+            Some(value) => match serde_json::from_value::<RuntimeConfig>(value.clone()) {
+                Ok(RuntimeConfig { kubernetes: Some(k8s), .. }) => k8s,
+                _ => serde_json::from_value::<KubernetesConfig>(value)?,
+            },
+            None => KubernetesConfig::default(),
+        };
+        Ok(Self::new(config))
+    }
+
+    /// 设置状态轮询间隔
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// 目标命名空间
+    pub fn namespace(&self) -> &str {
+        self.config.namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE)
+    }
+
+    /// Job超时时间，用例设置的超时优先于运行时管理器的配置，都未设置时与本地执行一致
+    fn timeout(&self, limits: &ResourceLimits) -> Duration {
+        match (limits.timeout_seconds, self.config.timeout_seconds) {
+            (Some(secs), _) => Duration::from_secs(secs.into()),
+            (None, Some(secs)) => Duration::from_secs(secs),
+            (None, None) => DEFAULT_RUN_TIMEOUT,
+        }
+    }
+
+    /// 执行测试用例，返回 (退出码, 标准输出, 标准错误)
+    ///
+    /// 超时或容器因超出内存限制被终止时返回 [`LimitExceeded`] 错误
+    ///
+    /// # 参数
+    /// * `test_case` - 测试用例
+    /// * `env` - 注入容器的环境变量
+    pub async fn execute(
+        &self,
+        test_case: &TestCase,
+        env: &BTreeMap<String, String>,
+    ) -> anyhow::Result<(i32, String, String)> {
+        let job_name = job_name_for(&test_case.id);
+        let manifest = self.build_job_manifest(&job_name, test_case, env)?;
+        self.run_job(&job_name, manifest, &test_case.limits).await
+    }
+
+    /// 根据配置模板生成Job清单
+    ///
+    /// `env` 追加到每个容器的环境变量中，与模板中同名的变量以 `env` 为准；
+    /// 用例的资源限制写入每个容器的 `resources.limits`，用例设置的超时覆盖模板的 `activeDeadlineSeconds`
+    pub fn build_job_manifest(
+        &self,
+        job_name: &str,
+        test_case: &TestCase,
+        env: &BTreeMap<String, String>,
+    ) -> anyhow::Result<Value> {
+        let mut manifest = match &self.config.job_template {
+            Some(template) => {
+                let mut template = template.clone();
+                substitute_placeholders(&mut template, test_case);
+                template
+            }
+            None => self.default_job_manifest(test_case),
+        };
+
+        let metadata = manifest
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("Job模板必须是JSON对象"))?
+            .entry("metadata")
+            .or_insert_with(|| json!({}));
+        metadata["name"] = json!(job_name);
+        metadata["namespace"] = json!(self.namespace());
+        if metadata.get("labels").map_or(true, |labels| !labels.is_object()) {
+            metadata["labels"] = json!({});
+        }
+        metadata["labels"]["aiops/test-case-id"] = json!(test_case.id);
+
+        if manifest.pointer("/spec/template/spec").is_none() {
+            return Err(anyhow::anyhow!("Job模板缺少 spec.template.spec"));
+        }
+        if let Some(service_account) = &self.config.service_account {
+            manifest["spec"]["template"]["spec"]["serviceAccountName"] = json!(service_account);
+        }
+        if let Some(timeout_seconds) = test_case.limits.timeout_seconds {
+            manifest["spec"]["activeDeadlineSeconds"] = json!(timeout_seconds);
+        }
+        if let Some(containers) = manifest
+            .pointer_mut("/spec/template/spec/containers")
+            .and_then(Value::as_array_mut)
+        {
+            for container in containers {
+                inject_env(container, env);
+                inject_resource_limits(container, &test_case.limits);
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// 默认Job清单：在配置镜像中用python执行测试脚本
+    fn default_job_manifest(&self, test_case: &TestCase) -> Value {
+        let mut command = vec![json!("python"), json!(test_case.script_path)];
+        if let Some(config_path) = &test_case.config_path {
+            command.push(json!("--config"));
+            command.push(json!(config_path));
+        }
+
+        json!({
+            "apiVersion": "batch/v1",
+            "kind": "Job",
+            "metadata": {},
+            "spec": {
+                "backoffLimit": 0,
+                "activeDeadlineSeconds": self.timeout(&test_case.limits).as_secs(),
+                "template": {
+                    "spec": {
+                        "restartPolicy": "Never",
+                        "containers": [{
+                            "name": "test",
+                            "image": self.config.image.as_deref().unwrap_or(DEFAULT_IMAGE),
+                            "command": command
+                        }]
+                    }
+                }
+            }
+        })
+    }
+
+    /// 提交Job并等待完成，结束后清理Job
+    ///
+    /// # 参数
+    /// * `job_name` - Job名称
+    /// * `manifest` - Job清单
+    /// * `limits` - 用例的资源限制，决定等待时长和失败原因的判定
+    pub async fn run_job(
+        &self,
+        job_name: &str,
+        manifest: Value,
+        limits: &ResourceLimits,
+    ) -> anyhow::Result<(i32, String, String)> {
+        self.apply_job(&manifest).await?;
+        tracing::info!("Kubernetes Job已创建: {}/{}", self.namespace(), job_name);
+
+        let result = self.wait_and_collect(job_name, limits).await;
+
+        if let Err(e) = self.delete_job(job_name).await {
+            tracing::warn!("清理Kubernetes Job失败: {} -> {}", job_name, e);
+        }
+
+        result
+    }
+
+    /// 等待Job结束并收集日志
+    async fn wait_and_collect(&self, job_name: &str, limits: &ResourceLimits) -> anyhow::Result<(i32, String, String)> {
+        let timeout = self.timeout(limits);
+        let outcome = self.wait_for_completion(job_name, timeout).await?;
+        let logs = match self.collect_logs(job_name).await {
+            Ok(logs) => logs,
+            Err(e) => {
+                tracing::warn!("获取Job日志失败: {} -> {}", job_name, e);
+                String::new()
+            }
+        };
+
+        match outcome {
+            JobOutcome::Succeeded => Ok((0, logs, String::new())),
+            JobOutcome::Failed(reason) => match limits.max_memory_mb {
+                Some(max_memory_mb) if self.oom_killed(job_name).await => Err(LimitExceeded::Memory(max_memory_mb).into()),
+                _ => Ok((1, logs, format!("Job执行失败: {}", reason))),
+            },
+            JobOutcome::TimedOut => Err(LimitExceeded::Timeout(timeout.as_secs()).into()),
+        }
+    }
+
+    /// 构造带有集群连接参数的kubectl命令
+    fn kubectl(&self) -> Command {
+        let mut cmd = Command::new("kubectl");
+        if let Some(kubeconfig) = &self.config.kubeconfig_path {
+            cmd.arg("--kubeconfig").arg(kubeconfig);
+        }
+        if let Some(context) = &self.config.context {
+            cmd.arg("--context").arg(context);
+        }
+        if let Some(cluster) = &self.config.cluster {
+            cmd.arg("--cluster").arg(cluster);
+        }
+        cmd.arg("--namespace").arg(self.namespace());
+        cmd
+    }
+
+    /// 通过标准输入提交Job清单
+    async fn apply_job(&self, manifest: &Value) -> anyhow::Result<()> {
+        let mut child = self
+            .kubectl()
+            .args(["create", "-f", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(manifest.to_string().as_bytes()).await?;
+        }
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "创建Job失败: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    /// 轮询Job状态直到成功、失败或超时
+    async fn wait_for_completion(&self, job_name: &str, timeout: Duration) -> anyhow::Result<JobOutcome> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let output = self
+                .kubectl()
+                .args(["get", "job", job_name, "--output=json"])
+                .output()
+                .await?;
+            if !output.status.success() {
+                return Err(anyhow::anyhow!(
+                    "查询Job状态失败: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+
+            let job: Value = serde_json::from_slice(&output.stdout)?;
+            if let Some(outcome) = job_outcome(&job) {
+                return Ok(outcome);
+            }
+
+            if Instant::now() >= deadline {
+                return Ok(JobOutcome::TimedOut);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// 收集Job所属Pod的日志
+    async fn collect_logs(&self, job_name: &str) -> anyhow::Result<String> {
+        let output = self
+            .kubectl()
+            .args(["logs", &format!("job/{}", job_name), "--all-containers=true"])
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Job的Pod中是否有容器因超出内存限制被终止，查询失败时视为否
+    async fn oom_killed(&self, job_name: &str) -> bool {
+        let output = match self
+            .kubectl()
+            .args(["get", "pods", "--selector", &format!("job-name={}", job_name), "--output=json"])
+            .output()
+            .await
+        {
+            Ok(output) if output.status.success() => output,
+            _ => return false,
+        };
+
+        let Ok(pods) = serde_json::from_slice::<Value>(&output.stdout) else {
+            return false;
+        };
+        pods["items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|pod| pod.pointer("/status/containerStatuses").and_then(Value::as_array))
+            .flatten()
+            .any(|status| status.pointer("/state/terminated/reason").and_then(Value::as_str) == Some("OOMKilled"))
+    }
+
+    /// 删除Job及其Pod
+    async fn delete_job(&self, job_name: &str) -> anyhow::Result<()> {
+        let output = self
+            .kubectl()
+            .args([
+                "delete",
+                "job",
+                job_name,
+                "--ignore-not-found=true",
+                "--cascade=background",
+                "--wait=false",
+            ])
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 生成符合DNS-1123规范的Job名称
+fn job_name_for(test_case_id: &str) -> String {
+    let prefix: String = test_case_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(8)
+        .collect::<String>()
+        .to_lowercase();
+    let suffix = Uuid::new_v4().simple().to_string();
+    format!("aiops-test-{}-{}", prefix, &suffix[..8])
+}
+
+/// 根据Job状态判断是否已结束
+fn job_outcome(job: &Value) -> Option<JobOutcome> {
+    let status = job.get("status")?;
+
+    if let Some(conditions) = status.get("conditions").and_then(Value::as_array) {
+        for condition in conditions {
+            if condition.get("status").and_then(Value::as_str) != Some("True") {
+                continue;
+            }
+            match condition.get("type").and_then(Value::as_str) {
+                Some("Complete") => return Some(JobOutcome::Succeeded),
+                Some("Failed") => {
+                    let reason = condition.get("reason").and_then(Value::as_str).unwrap_or("Unknown");
+                    // activeDeadlineSeconds 触发时视为超时
+                    if reason == "DeadlineExceeded" {
+                        return Some(JobOutcome::TimedOut);
+                    }
+                    let message = condition.get("message").and_then(Value::as_str).unwrap_or("");
+                    return Some(JobOutcome::Failed(format!("{} {}", reason, message).trim().to_string()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if status.get("succeeded").and_then(Value::as_i64).unwrap_or(0) > 0 {
+        return Some(JobOutcome::Succeeded);
+    }
+    if status.get("failed").and_then(Value::as_i64).unwrap_or(0) > 0 {
+        return Some(JobOutcome::Failed("Pod执行失败".to_string()));
+    }
+    None
+}
+
+/// 向容器追加环境变量，替换同名的已有变量
+fn inject_env(container: &mut Value, env: &BTreeMap<String, String>) {
+    if env.is_empty() {
+        return;
+    }
+    let Some(container) = container.as_object_mut() else {
+        return;
+    };
+
+    let mut entries: Vec<Value> = match container.remove("env") {
+        Some(Value::Array(entries)) => entries
+            .into_iter()
+            .filter(|entry| !entry["name"].as_str().is_some_and(|name| env.contains_key(name)))
+            .collect(),
+        _ => Vec::new(),
+    };
+    entries.extend(env.iter().map(|(name, value)| json!({"name": name, "value": value})));
+    container.insert("env".to_string(), Value::Array(entries));
+}
+
+/// 按用例资源限制设置容器的 `resources.limits`，覆盖模板中的同名限制
+fn inject_resource_limits(container: &mut Value, limits: &ResourceLimits) {
+    let mut values = Vec::new();
+    if let Some(max_memory_mb) = limits.max_memory_mb {
+        values.push(("memory", json!(format!("{}Mi", max_memory_mb))));
+    }
+    if let Some(max_cpu) = limits.max_cpu {
+        values.push(("cpu", json!(max_cpu.to_string())));
+    }
+    let Some(container) = container.as_object_mut().filter(|_| !values.is_empty()) else {
+        return;
+    };
+
+    let resources = container.entry("resources").or_insert_with(|| json!({}));
+    if !resources.is_object() {
+        *resources = json!({});
+    }
+    if !resources["limits"].is_object() {
+        resources["limits"] = json!({});
+    }
+    for (name, value) in values {
+        resources["limits"][name] = value;
+    }
+}
+
+/// 替换模板字符串中的占位符
+fn substitute_placeholders(value: &mut Value, test_case: &TestCase) {
+    match value {
+        Value::String(s) => {
+            *s = s
+                .replace("{{test_case_id}}", &test_case.id)
+                .replace("{{test_case_name}}", &test_case.name)
+                .replace("{{script_path}}", &test_case.script_path)
+                .replace("{{config_path}}", test_case.config_path.as_deref().unwrap_or(""));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| substitute_placeholders(item, test_case)),
+        Value::Object(map) => map.values_mut().for_each(|item| substitute_placeholders(item, test_case)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn sample_test_case() -> TestCase {
+        TestCase {
+            id: Uuid::new_v4().to_string(),
+            name: "k8s-smoke".to_string(),
+            description: None,
+            script_path: "/tests/smoke.py".to_string(),
+            config_path: None,
+            runtime_type: "kubernetes".to_string(),
+            tags: None,
+            assertions: None,
+            env: None,
+            limits: ResourceLimits::default(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_job_manifest_from_template() {
+        let executor = KubernetesExecutor::new(KubernetesConfig {
+            namespace: Some("aiops".to_string()),
+            job_template: Some(json!({
+                "apiVersion": "batch/v1",
+                "kind": "Job",
+                "spec": {
+                    "template": {
+                        "spec": {
+                            "restartPolicy": "Never",
+                            "containers": [{
+                                "name": "runner",
+                                "image": "busybox",
+                                "command": ["sh", "-c", "run {{script_path}}"]
+                            }]
+                        }
+                    }
+                }
+            })),
+            ..Default::default()
+        });
+        let test_case = sample_test_case();
+
+        let manifest = executor
+            .build_job_manifest("aiops-test-job", &test_case, &BTreeMap::new())
+            .unwrap();
+        assert_eq!(manifest["metadata"]["name"], "aiops-test-job");
+        assert_eq!(manifest["metadata"]["namespace"], "aiops");
+        assert_eq!(manifest["metadata"]["labels"]["aiops/test-case-id"], json!(test_case.id));
+        assert_eq!(
+            manifest["spec"]["template"]["spec"]["containers"][0]["command"][2],
+            "run /tests/smoke.py"
+        );
+    }
+
+    #[test]
+    fn test_job_manifest_applies_case_limits() {
+        let executor = KubernetesExecutor::new(KubernetesConfig::default());
+        let test_case = TestCase {
+            limits: ResourceLimits {
+                timeout_seconds: Some(90),
+                max_memory_mb: Some(512),
+                max_cpu: Some(1.5),
+            },
+            ..sample_test_case()
+        };
+
+        let manifest = executor
+            .build_job_manifest("aiops-test-job", &test_case, &BTreeMap::new())
+            .unwrap();
+        assert_eq!(manifest["spec"]["activeDeadlineSeconds"], 90);
+        let limits = &manifest["spec"]["template"]["spec"]["containers"][0]["resources"]["limits"];
+        assert_eq!(limits["memory"], "512Mi");
+        assert_eq!(limits["cpu"], "1.5");
+    }
+
+    #[test]
+    fn test_job_outcome_mapping() {
+        assert_eq!(job_outcome(&json!({"status": {"active": 1}})), None);
+        assert_eq!(
+            job_outcome(&json!({"status": {"conditions": [{"type": "Complete", "status": "True"}]}})),
+            Some(JobOutcome::Succeeded)
+        );
+        assert_eq!(
+            job_outcome(&json!({"status": {"conditions": [{"type": "Failed", "status": "True", "reason": "DeadlineExceeded"}]}})),
+            Some(JobOutcome::TimedOut)
+        );
+        assert!(matches!(
+            job_outcome(&json!({"status": {"failed": 1}})),
+            Some(JobOutcome::Failed(_))
+        ));
+    }
+
+    /// 需要可用的集群（kubectl cluster-info 成功），否则跳过
+    #[tokio::test]
+    async fn test_trivial_job_captures_logs() {
+        let cluster_available = Command::new("kubectl")
+            .arg("cluster-info")
+            .output()
+            .await
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        if !cluster_available {
+            eprintln!("未检测到可用的Kubernetes集群，跳过测试");
+            return;
+        }
+
+        let executor = KubernetesExecutor::new(KubernetesConfig {
+            timeout_seconds: Some(120),
+            job_template: Some(json!({
+                "apiVersion": "batch/v1",
+                "kind": "Job",
+                "spec": {
+                    "backoffLimit": 0,
+                    "template": {
+                        "spec": {
+                            "restartPolicy": "Never",
+                            "containers": [{
+                                "name": "runner",
+                                "image": "busybox",
+                                "command": ["sh", "-c", "echo hello from {{test_case_name}}"]
+                            }]
+                        }
+                    }
+                }
+            })),
+            ..Default::default()
+        })
+        .with_poll_interval(Duration::from_millis(500));
+
+        let (exit_code, stdout, _stderr) = executor.execute(&sample_test_case(), &BTreeMap::new()).await.unwrap();
+        assert_eq!(exit_code, 0);
+        assert!(stdout.contains("hello from k8s-smoke"));
+    }
+}
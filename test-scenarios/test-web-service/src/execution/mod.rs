@@ -1,7 +1,12 @@
 //! 脚本执行模块
 //! 
-//! 提供多语言测试脚本的执行和结果验证功能
+//! 提供多语言测试脚本的执行和结果验证功能，以及Docker容器和Kubernetes Job方式的测试执行
 
+pub mod docker_executor;
+pub mod kubernetes_executor;
+pub mod process_limits;
 pub mod script_executor;
 
+pub use docker_executor::DockerExecutor;
+pub use kubernetes_executor::KubernetesExecutor;
 pub use script_executor::ScriptExecutor;
\ No newline at end of file
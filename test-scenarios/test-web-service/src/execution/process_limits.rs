@@ -0,0 +1,101 @@
+//! 本地进程资源限制
+//!
+//! 在子进程exec之前设置地址空间上限和CPU亲和性：内存上限通过 `RLIMIT_AS` 落实，
+//! 超出时分配失败而不是被系统OOM终止；CPU上限向上取整为允许运行的CPU数
+
+use crate::models::ResourceLimits;
+use tokio::process::Command;
+
+/// 为本地测试进程应用资源限制
+///
+/// # 参数
+/// * `cmd` - 尚未启动的命令
+/// * `limits` - 用例资源限制
+#[cfg(target_os = "linux")]
+pub fn apply_process_limits(cmd: &mut Command, limits: &ResourceLimits) {
+    let memory_bytes = limits
+        .max_memory_mb
+        .map(|mb| libc::rlim_t::from(mb) * 1024 * 1024);
+    // 在父进程中计算CPU集合，pre_exec中只做系统调用
+    let cpu_set = limits.max_cpu.and_then(cpu_set);
+    if memory_bytes.is_none() && cpu_set.is_none() {
+        return;
+    }
+
+    // SAFETY: 闭包在fork之后、exec之前运行，只调用异步信号安全的系统调用，不分配内存
+    unsafe {
+        cmd.pre_exec(move || {
+            if let Some(bytes) = memory_bytes {
+                let limit = libc::rlimit { rlim_cur: bytes, rlim_max: bytes };
+                if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            if let Some(set) = &cpu_set {
+                if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), set) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+/// 非Linux平台不支持本地进程资源限制，只记录警告
+#[cfg(not(target_os = "linux"))]
+pub fn apply_process_limits(_cmd: &mut Command, limits: &ResourceLimits) {
+    if limits.max_memory_mb.is_some() || limits.max_cpu.is_some() {
+        tracing::warn!("当前平台不支持本地运行的内存/CPU限制，仅运行时间限制生效");
+    }
+}
+
+/// 取当前进程可用CPU中的前 `ceil(max_cpu)` 个，获取失败时返回None
+#[cfg(target_os = "linux")]
+fn cpu_set(max_cpu: f64) -> Option<libc::cpu_set_t> {
+    let wanted = max_cpu.ceil().max(1.0) as usize;
+    // SAFETY: cpu_set_t是纯数据结构，全零即空集合
+    unsafe {
+        let mut allowed: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut allowed) != 0 {
+            tracing::warn!("获取CPU亲和性失败，不限制本地运行的CPU: {}", std::io::Error::last_os_error());
+            return None;
+        }
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        let mut taken = 0;
+        for cpu in 0..libc::CPU_SETSIZE as usize {
+            if taken == wanted {
+                break;
+            }
+            if libc::CPU_ISSET(cpu, &allowed) {
+                libc::CPU_SET(cpu, &mut set);
+                taken += 1;
+            }
+        }
+        Some(set)
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limits_apply_to_child_process() {
+        let limits = ResourceLimits {
+            timeout_seconds: None,
+            max_memory_mb: Some(64),
+            max_cpu: Some(1.0),
+        };
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "ulimit -v; grep Cpus_allowed_list /proc/self/status"]);
+        apply_process_limits(&mut cmd, &limits);
+
+        let output = cmd.output().await.unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        // ulimit -v 以KB为单位
+        assert_eq!(lines.next(), Some("65536"));
+        let cpus = lines.next().unwrap().split_whitespace().last().unwrap().to_string();
+        assert!(cpus.parse::<usize>().is_ok(), "应只允许一个CPU: {}", cpus);
+    }
+}
@@ -5,17 +5,19 @@
 
 use axum::{
     extract::State,
-    http::StatusCode,
+    http::{HeaderName, HeaderValue, Method, StatusCode},
+    middleware::{from_fn, from_fn_with_state},
     response::Json,
     routing::get,
     Router,
 };
+use clap::Parser;
 use serde_json::{json, Value};
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, path::Path, sync::Arc, time::Duration};
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
 use tower_http::{
-    cors::CorsLayer,
+    cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer},
     services::ServeDir,
     trace::TraceLayer,
 };
@@ -29,17 +31,45 @@ mod database;
 mod docs;
 mod execution;
 mod handlers;
+mod middleware;
 mod models;
 mod services;
 
-use config::AppConfig;
+use config::{AppConfig, CorsConfig};
 use database::Database;
+use models::SystemInfo;
+use services::disk_space::{DiskSpaceChecker, DiskStatus};
+use services::probe_cache::ProbeCache;
+use services::resource_headroom::ResourceHeadroomChecker;
+use services::run_limiter::RunLimiter;
+
+/// 静态资源目录
+const STATIC_DIR: &str = "static";
+
+/// 命令行参数
+#[derive(Debug, Parser)]
+#[command(version, about = "AIOps测试管理Web服务")]
+struct Cli {
+    /// 启动前填充示例测试用例、运行时管理器和历史运行记录，可重复执行
+    #[arg(long)]
+    seed: bool,
+}
 
 /// 应用程序状态
 #[derive(Clone)]
 pub struct AppState {
     pub db: Arc<Database>,
     pub config: Arc<AppConfig>,
+    /// 启动时采集的系统信息
+    pub system_info: Arc<SystemInfo>,
+    /// 结果目录等的磁盘空间检查
+    pub disk_space: Arc<DiskSpaceChecker>,
+    /// 测试运行并发限制
+    pub run_limiter: Arc<RunLimiter>,
+    /// 启动测试运行前的资源余量检查
+    pub resource_headroom: Arc<ResourceHeadroomChecker>,
+    /// 运行时管理器探测结果缓存
+    pub probe_cache: Arc<ProbeCache>,
 }
 
 /// 健康检查端点
@@ -54,16 +84,39 @@ pub struct AppState {
         (status = 500, description = "服务异常")
     )
 )]
-async fn health_check() -> Result<Json<Value>, StatusCode> {
+async fn health_check(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let disk = state.disk_space.check();
     Ok(Json(json!({
-        "status": "healthy",
+        "status": disk.status,
         "timestamp": chrono::Utc::now(),
-        "service": "aiops-web-service"
+        "service": "aiops-web-service",
+        "disk": disk
     })))
 }
 
+/// 就绪检查端点
+///
+/// 磁盘空间严重不足时返回503，此时不再接受新的测试运行
+async fn readiness_check(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let disk = state.disk_space.check();
+    let code = if disk.status == DiskStatus::Critical {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+
+    (
+        code,
+        Json(json!({
+            "ready": code == StatusCode::OK,
+            "status": disk.status,
+            "disk": disk
+        })),
+    )
+}
+
 /// 服务信息端点
-async fn service_info(State(_state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+async fn service_info(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     Ok(Json(json!({
         "name": "AIOps测试管理服务",
         "version": env!("CARGO_PKG_VERSION"),
@@ -76,12 +129,100 @@ async fn service_info(State(_state): State<AppState>) -> Result<Json<Value>, Sta
             "API接口"
         ],
         "database_status": "connected",
-        "uptime": chrono::Utc::now().to_rfc3339()
+        "system": state.system_info.snapshot()
     })))
 }
 
+/// 根据配置构建跨域中间件
+///
+/// 配置需先通过 [`CorsConfig::validate`]，否则凭据与通配符的组合会在运行时被拒绝
+fn build_cors_layer(cors: &CorsConfig) -> anyhow::Result<CorsLayer> {
+    let is_wildcard = |values: &[String]| values.iter().any(|v| v == "*");
+
+    let origins = if is_wildcard(&cors.allowed_origins) {
+        AllowOrigin::from(Any)
+    } else {
+        let origins = cors
+            .allowed_origins
+            .iter()
+            .map(|o| HeaderValue::from_str(o))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+
+    let methods = if is_wildcard(&cors.allowed_methods) {
+        AllowMethods::from(Any)
+    } else {
+        let methods = cors
+            .allowed_methods
+            .iter()
+            .map(|m| Method::from_bytes(m.to_uppercase().as_bytes()))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowMethods::list(methods)
+    };
+
+    let headers = if is_wildcard(&cors.allowed_headers) {
+        AllowHeaders::from(Any)
+    } else {
+        let headers = cors
+            .allowed_headers
+            .iter()
+            .map(|h| HeaderName::from_bytes(h.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowHeaders::list(headers)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(cors.allow_credentials))
+}
+
+/// 构建应用路由
+///
+/// 静态资源目录不存在时不挂载 `/static`，文档入口仍可通过 `/api/v1/docs` 访问。
+/// 未匹配的路径返回JSON格式的404，方法不匹配时返回带 `Allow` 头的405
+fn create_app(app_state: AppState, static_dir: &Path) -> anyhow::Result<Router> {
+    let cors = build_cors_layer(&app_state.config.cors)?;
+    let request_timeout = Duration::from_secs(app_state.config.request_timeout_secs);
+
+    let mut app = Router::new()
+        .route("/health", get(health_check))
+        .route("/ready", get(readiness_check))
+        .route("/info", get(service_info))
+        .nest("/api/v1", api::routes())
+        .merge(docs::create_swagger_ui())
+        .route("/api-docs/openapi.json", get(|| async {
+            Json(docs::get_openapi_json())
+        }));
+
+    if static_dir.is_dir() {
+        app = app.nest_service("/static", ServeDir::new(static_dir));
+    } else {
+        warn!(
+            "静态资源目录不存在: {}，/static 将不可用；API文档请访问 /api/v1/docs 或 /api/v1/openapi.json",
+            static_dir.display()
+        );
+    }
+
+    Ok(app
+        .fallback(middleware::not_found_handler)
+        .method_not_allowed_fallback(middleware::method_not_allowed_handler)
+        .layer(
+            ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http())
+                .layer(cors)
+                .layer(from_fn_with_state(request_timeout, middleware::request_timeout_middleware))
+                .layer(from_fn(middleware::require_json_content_type)),
+        )
+        .with_state(app_state))
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
     // 初始化日志
     tracing_subscriber::fmt()
         .with_target(false)
@@ -91,44 +232,184 @@ async fn main() -> anyhow::Result<()> {
     info!("启动AIOps测试管理Web服务...");
 
     // 加载配置
-    let config = Arc::new(AppConfig::load()?);
+    let config = AppConfig::load()?;
+    config.validate()?;
+    let config = Arc::new(config);
     info!("配置加载完成: 端口 {}", config.port);
 
     // 初始化数据库
-    let db = Arc::new(Database::new(&config.database_url).await?);
+    let db = Arc::new(
+        Database::with_timeouts(
+            &config.database_url,
+            Duration::from_secs(config.db_acquire_timeout_secs),
+            Duration::from_secs(config.db_query_timeout_secs),
+        )
+        .await?,
+    );
     info!("数据库连接成功");
 
+    if cli.seed {
+        database::seed::seed(db.pool()).await?;
+    }
+
     // 创建应用状态
     let app_state = AppState {
         db: db.clone(),
         config: config.clone(),
+        system_info: Arc::new(SystemInfo::collect()),
+        disk_space: Arc::new(DiskSpaceChecker::from_config(&config)),
+        run_limiter: Arc::new(RunLimiter::from_config(&config)),
+        resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&config)),
+        probe_cache: Arc::new(ProbeCache::from_config(&config)),
     };
 
     // 创建应用路由
-    let app = Router::new()
-        .route("/health", get(health_check))
-        .nest("/api/v1", api::routes())
-        .nest_service("/static", ServeDir::new("static"))
-        .merge(docs::create_swagger_ui())
-        .route("/api-docs/openapi.json", get(|| async {
-            Json(docs::get_openapi_json())
-        }))
-        .layer(
-            ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
-                .layer(CorsLayer::permissive())
-        )
-        .with_state(app_state);
+    let app = create_app(app_state, Path::new(STATIC_DIR))?;
 
     // 启动服务器
     let addr = format!("127.0.0.1:{}", config.port);
     let listener = TcpListener::bind(&addr).await?;
-    
-    info!("🚀 AIOps Web服务已启动: http://{}", addr);
-    info!("📖 API文档: http://{}/api/v1/docs", addr);
-    info!("💚 健康检查: http://{}/health", addr);
+    // 证书在绑定端口后立即加载，配置错误时启动失败
+    let tls = if config.tls.is_enabled() {
+        Some(services::tls::load_server_config(&config.tls)?)
+    } else {
+        None
+    };
+    let scheme = if tls.is_some() { "https" } else { "http" };
+
+    info!("🚀 AIOps Web服务已启动: {}://{}", scheme, addr);
+    info!("📖 API文档: {}://{}/api/v1/docs", scheme, addr);
+    info!("📘 Swagger UI: {}://{}{}/", scheme, addr, docs::SWAGGER_UI_PATH);
+    info!("💚 健康检查: {}://{}/health", scheme, addr);
 
-    axum::serve(listener, app).await?;
+    match tls {
+        Some(server_config) => services::tls::serve_tls(listener, app, server_config).await?,
+        None => axum::serve(listener, app).await?,
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use tower::Service;
+
+    async fn test_state() -> AppState {
+        AppState {
+            db: Arc::new(Database::new("sqlite::memory:").await.unwrap()),
+            config: Arc::new(AppConfig::default()),
+            system_info: Arc::new(SystemInfo::collect()),
+            disk_space: Arc::new(DiskSpaceChecker::from_config(&AppConfig::default())),
+            run_limiter: Arc::new(RunLimiter::from_config(&AppConfig::default())),
+            resource_headroom: Arc::new(ResourceHeadroomChecker::from_config(&AppConfig::default())),
+            probe_cache: Arc::new(ProbeCache::from_config(&AppConfig::default())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_docs_available_without_static_dir() {
+        let mut app = create_app(test_state().await, Path::new("./missing-static-dir")).unwrap();
+
+        let response = app
+            .call(Request::get("/api/v1/docs").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let docs: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(docs["openapi_url"], "/api/v1/openapi.json");
+    }
+
+    #[tokio::test]
+    async fn test_openapi_json_served() {
+        let mut app = create_app(test_state().await, Path::new("./missing-static-dir")).unwrap();
+
+        let response = app
+            .call(Request::get("/api/v1/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let spec: Value = serde_json::from_slice(&body).unwrap();
+        assert!(spec["paths"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_list_endpoint_wraps_page_in_api_response() {
+        let state = test_state().await;
+        database::seed::seed(state.db.pool()).await.unwrap();
+        let mut app = create_app(state, Path::new("./missing-static-dir")).unwrap();
+
+        let response = app
+            .call(Request::get("/api/v1/runtime-managers?page=1&limit=2").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], true);
+        assert!(body["timestamp"].is_string());
+        assert_eq!(body["data"]["data"].as_array().unwrap().len(), 2);
+        assert_eq!(body["data"]["pagination"]["total"], 3);
+        assert_eq!(body["data"]["pagination"]["has_next"], true);
+    }
+
+    fn preflight(origin: &str) -> Request<Body> {
+        Request::options("/health")
+            .header("origin", origin)
+            .header("access-control-request-method", "GET")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_rejects_disallowed_origin() {
+        let mut app = create_app(test_state().await, Path::new("./missing-static-dir")).unwrap();
+
+        let allowed = app.call(preflight("http://localhost:8888")).await.unwrap();
+        assert_eq!(
+            allowed.headers().get("access-control-allow-origin").unwrap(),
+            "http://localhost:8888"
+        );
+
+        let rejected = app.call(preflight("https://evil.example.com")).await.unwrap();
+        assert!(rejected.headers().get("access-control-allow-origin").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_path_returns_json_404() {
+        let mut app = create_app(test_state().await, Path::new("./missing-static-dir")).unwrap();
+
+        let response = app
+            .call(Request::get("/api/v1/no-such-endpoint").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["success"], false);
+        assert_eq!(error["message"], "未找到资源: GET /api/v1/no-such-endpoint");
+    }
+
+    #[tokio::test]
+    async fn test_wrong_method_returns_405_with_allow_header() {
+        let mut app = create_app(test_state().await, Path::new("./missing-static-dir")).unwrap();
+
+        let response = app
+            .call(Request::delete("/api/v1/test-cases").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()["allow"], "GET,HEAD,POST");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["success"], false);
+    }
+}
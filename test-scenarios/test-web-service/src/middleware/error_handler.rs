@@ -2,9 +2,10 @@
 //! 
 //! 提供统一的错误处理和日志记录功能
 
+use crate::models::ApiResponse;
 use axum::{
     extract::Request,
-    http::StatusCode,
+    http::{Method, StatusCode, Uri},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
@@ -126,4 +127,24 @@ pub async fn global_error_handler(err: Box<dyn std::error::Error + Send + Sync>)
     });
 
     (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-}
\ No newline at end of file
+}
+
+/// 未匹配任何路由时的处理器
+/// 
+/// 以统一的 `ApiResponse` 错误结构返回404
+pub async fn not_found_handler(method: Method, uri: Uri) -> impl IntoResponse {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ApiResponse::<()>::error(format!("未找到资源: {} {}", method, uri.path()))),
+    )
+}
+
+/// 路径存在但请求方法不受支持时的处理器
+/// 
+/// 返回405，`Allow` 头由路由按该路径已注册的方法填写
+pub async fn method_not_allowed_handler(method: Method, uri: Uri) -> impl IntoResponse {
+    (
+        StatusCode::METHOD_NOT_ALLOWED,
+        Json(ApiResponse::<()>::error(format!("{} 不支持 {} 方法", uri.path(), method))),
+    )
+}
@@ -0,0 +1,102 @@
+//! JSON请求体校验中间件
+//!
+//! 带请求体的POST/PUT/PATCH请求必须声明 `Content-Type: application/json`，
+//! 否则在解析请求体之前直接返回415，而不是反序列化失败的错误
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+/// 要求JSON请求体的中间件
+pub async fn require_json_content_type(request: Request, next: Next) -> Response {
+    if !requires_json_body(request.method(), request.headers()) {
+        return next.run(request).await;
+    }
+
+    let content_type = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if is_json_content_type(content_type) {
+        return next.run(request).await;
+    }
+
+    let error_response = json!({
+        "success": false,
+        "message": if content_type.is_empty() {
+            "请求体必须为JSON，缺少 Content-Type: application/json".to_string()
+        } else {
+            format!("请求体必须为JSON，不支持的Content-Type: {}", content_type)
+        },
+        "error": "Unsupported Media Type",
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+
+    (StatusCode::UNSUPPORTED_MEDIA_TYPE, Json(error_response)).into_response()
+}
+
+/// 是否为需要校验Content-Type的请求：带请求体的POST、PUT、PATCH
+fn requires_json_body(method: &Method, headers: &HeaderMap) -> bool {
+    if !matches!(*method, Method::POST | Method::PUT | Method::PATCH) {
+        return false;
+    }
+
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+    content_length.is_some_and(|length| length > 0) || headers.contains_key(header::TRANSFER_ENCODING)
+}
+
+/// Content-Type是否为 `application/json`，忽略大小写和 `charset` 等参数
+fn is_json_content_type(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::post, Router};
+    use tower::Service;
+
+    #[tokio::test]
+    async fn test_non_json_body_rejected_with_415() {
+        let mut app = Router::new()
+            .route("/echo", post(|Json(body): Json<serde_json::Value>| async move { Json(body) }))
+            .route("/run", post(|| async { "started" }))
+            .layer(middleware::from_fn(require_json_content_type));
+
+        let post = |content_type: &str, body: &'static str| {
+            Request::post("/echo")
+                .header(header::CONTENT_TYPE, content_type)
+                .header(header::CONTENT_LENGTH, body.len())
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = app.call(post("text/plain", "{\"name\":\"smoke\"}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let response = app
+            .call(post("application/json; charset=utf-8", "{\"name\":\"smoke\"}"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 不带请求体的POST不要求Content-Type
+        let response = app
+            .call(Request::post("/run").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
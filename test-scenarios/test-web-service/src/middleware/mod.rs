@@ -1,9 +1,13 @@
 //! 中间件模块
 //! 
-//! 提供HTTP请求处理中间件，包括错误处理、日志记录等功能
+//! 提供HTTP请求处理中间件，包括错误处理、日志记录、请求超时、请求体类型校验等功能
 
 pub mod error_handler;
+pub mod json_content_type;
 pub mod request_logger;
+pub mod request_timeout;
 
 pub use error_handler::*;
-pub use request_logger::*;
\ No newline at end of file
+pub use json_content_type::*;
+pub use request_logger::*;
+pub use request_timeout::*;
\ No newline at end of file
@@ -0,0 +1,103 @@
+//! 请求超时中间件
+//! 
+//! 限制单个HTTP请求的处理时间，超时后丢弃处理函数的future，取消其中尚未完成的数据库查询等操作
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::time::Duration;
+use tracing::warn;
+
+/// 流式端点路径后缀，这类连接会长期保持，不受请求超时限制
+const STREAMING_PATH_SUFFIX: &str = "/stream";
+
+/// 请求超时中间件
+/// 
+/// 处理函数在 `timeout` 内未返回时响应504
+pub async fn request_timeout_middleware(
+    State(timeout): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.uri().path().ends_with(STREAMING_PATH_SUFFIX) {
+        return next.run(request).await;
+    }
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!(
+                method = %method,
+                path = %path,
+                "请求处理超时（{:?}），已取消",
+                timeout
+            );
+
+            let error_response = json!({
+                "success": false,
+                "message": format!("请求处理超时（{}秒）", timeout.as_secs_f64()),
+                "error": "Gateway Timeout",
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            });
+
+            (StatusCode::GATEWAY_TIMEOUT, Json(error_response)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tower::Service;
+
+    #[tokio::test]
+    async fn test_slow_handler_times_out_with_504_and_is_cancelled() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let handler_finished = Arc::clone(&finished);
+
+        let mut app = Router::new()
+            .route(
+                "/slow",
+                get(move || async move {
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    handler_finished.store(true, Ordering::SeqCst);
+                }),
+            )
+            .route(
+                "/runs/stream",
+                get(|| async { tokio::time::sleep(Duration::from_millis(100)).await }),
+            )
+            .layer(middleware::from_fn_with_state(
+                Duration::from_millis(50),
+                request_timeout_middleware,
+            ));
+
+        let response = app
+            .call(Request::get("/slow").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        // 超时的处理函数被取消，不会在后台运行到结束
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+
+        // 流式端点不受超时限制
+        let response = app
+            .call(Request::get("/runs/stream").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
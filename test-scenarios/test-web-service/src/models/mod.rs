@@ -13,11 +13,21 @@ pub mod test_case;
 pub mod test_run;
 pub mod runtime_manager;
 pub mod test_script;
+pub mod test_assertion;
+pub mod test_timeline;
+pub mod test_env;
+pub mod resource_limits;
+pub mod system_info;
 
 pub use test_case::*;
 pub use test_run::*;
 pub use runtime_manager::*;
 pub use test_script::*;
+pub use test_assertion::*;
+pub use test_timeline::*;
+pub use test_env::*;
+pub use resource_limits::*;
+pub use system_info::*;
 
 /// 运行时类型枚举
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
@@ -289,4 +299,54 @@ impl<T> ApiResponse<T> {
             timestamp: Utc::now(),
         }
     }
-}
\ No newline at end of file
+}
+/// 字段级验证错误
+///
+/// 收集请求中所有不合法字段，序列化为 `字段 -> 错误信息` 的映射
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+#[serde(transparent)]
+pub struct ValidationErrors(std::collections::BTreeMap<String, String>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录字段错误，同一字段保留第一条错误信息
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.entry(field.into()).or_insert_with(|| message.into());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 合并另一组错误，已存在的字段保留原有错误信息
+    pub fn extend(&mut self, other: ValidationErrors) {
+        for (field, message) in other.0 {
+            self.add(field, message);
+        }
+    }
+
+    /// 无错误时返回 `Ok(())`
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<String> = self
+            .0
+            .iter()
+            .map(|(field, message)| format!("{}: {}", field, message))
+            .collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
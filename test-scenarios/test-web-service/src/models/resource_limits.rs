@@ -0,0 +1,74 @@
+//! 测试用例资源限制
+//!
+//! 限制单次运行的时长、内存和CPU，由各运行时的执行器落实：本地运行使用进程资源限制，
+//! Docker使用容器参数，Kubernetes使用Job的资源限制
+
+use super::TestStatus;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// 未设置超时的用例单次运行的最长时间
+pub const DEFAULT_RUN_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// 测试用例资源限制，未设置的项不限制
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ResourceLimits {
+    /// 单次运行的最长时间（秒），超过时终止运行并标记为超时，未设置时为30分钟
+    #[serde(default)]
+    pub timeout_seconds: Option<u32>,
+    /// 内存上限（MB）
+    #[serde(default)]
+    pub max_memory_mb: Option<u32>,
+    /// CPU上限（核数，可为小数，本地运行向上取整为可用的CPU数）
+    #[serde(default)]
+    pub max_cpu: Option<f64>,
+}
+
+impl ResourceLimits {
+    /// 验证资源限制，返回 (字段, 错误信息) 列表
+    pub fn validate(&self) -> Vec<(String, String)> {
+        let mut errors = Vec::new();
+
+        if self.timeout_seconds == Some(0) {
+            errors.push(("timeout_seconds".to_string(), "运行超时时间必须大于0".to_string()));
+        }
+        if self.max_memory_mb == Some(0) {
+            errors.push(("max_memory_mb".to_string(), "内存上限必须大于0".to_string()));
+        }
+        if self.max_cpu.is_some_and(|cpu| !cpu.is_finite() || cpu <= 0.0) {
+            errors.push(("max_cpu".to_string(), "CPU上限必须大于0".to_string()));
+        }
+
+        errors
+    }
+
+    /// 单次运行的最长时间
+    pub fn timeout(&self) -> Duration {
+        self.timeout_seconds
+            .map(|secs| Duration::from_secs(secs.into()))
+            .unwrap_or(DEFAULT_RUN_TIMEOUT)
+    }
+}
+
+/// 运行超出用例的资源限制，执行器以此错误结束运行
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum LimitExceeded {
+    /// 超过运行时间限制（秒）
+    #[error("运行超过 {0} 秒的时间限制，已终止")]
+    Timeout(u64),
+    /// 超过内存限制（MB）
+    #[error("运行超出 {0}MB 的内存限制，已终止")]
+    Memory(u32),
+}
+
+impl LimitExceeded {
+    /// 运行应记录的状态：超时为 `Timeout`，其余为 `Failed`
+    pub fn status(&self) -> TestStatus {
+        match self {
+            LimitExceeded::Timeout(_) => TestStatus::Timeout,
+            LimitExceeded::Memory(_) => TestStatus::Failed,
+        }
+    }
+}
@@ -2,9 +2,10 @@
 //! 
 //! 定义运行时管理器的数据结构和数据库操作
 
-use super::{RuntimeType, PaginationParams, PaginatedResponse, PaginationInfo};
+use super::{RuntimeType, PaginationParams, PaginatedResponse, PaginationInfo, ValidationErrors};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
 use sqlx::{FromRow, SqlitePool, Row, Pool, Sqlite};
 use uuid::Uuid;
 use utoipa::{ToSchema, IntoParams};
@@ -85,6 +86,14 @@ pub struct RuntimeManagerQuery {
     pub tags: Option<String>,
 }
 
+/// 运行时探测的刷新参数
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct ProbeRefreshQuery {
+    /// 忽略缓存的探测结果，立即重新探测
+    #[serde(default)]
+    pub refresh: bool,
+}
+
 /// 运行时管理器查询结果
 #[derive(Debug, Serialize)]
 pub struct RuntimeManagerQueryResult {
@@ -104,7 +113,7 @@ pub struct RuntimeConfig {
 }
 
 /// Docker运行时配置
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DockerConfig {
     pub host: Option<String>,
     pub tls_verify: Option<bool>,
@@ -112,16 +121,24 @@ pub struct DockerConfig {
     pub api_version: Option<String>,
     pub registry: Option<String>,
     pub network: Option<String>,
+    /// 测试容器使用的镜像
+    pub image: Option<String>,
 }
 
 /// Kubernetes运行时配置
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct KubernetesConfig {
     pub kubeconfig_path: Option<String>,
     pub namespace: Option<String>,
     pub context: Option<String>,
     pub cluster: Option<String>,
     pub service_account: Option<String>,
+    /// 测试Job使用的镜像
+    pub image: Option<String>,
+    /// Job模板（batch/v1 Job的JSON），字符串中的 `{{script_path}}` 等占位符会被替换
+    pub job_template: Option<serde_json::Value>,
+    /// Job执行超时时间（秒）
+    pub timeout_seconds: Option<u64>,
 }
 
 /// 本地运行时配置
@@ -133,6 +150,100 @@ pub struct LocalConfig {
     pub timeout_seconds: Option<u64>,
 }
 
+/// 运行时配置模式
+///
+/// 声明某一运行时类型允许的配置项与必填项，配置仍以JSON存储
+pub trait RuntimeConfigSchema: DeserializeOwned {
+    /// 完整运行时配置中对应的段名（如 `{"docker": {...}}`）
+    const SECTION: &'static str;
+    /// 允许的配置项
+    const FIELDS: &'static [&'static str];
+    /// 必填配置项
+    const REQUIRED: &'static [&'static str] = &[];
+
+    /// 按模式验证配置，字段错误以 `{prefix}.{字段}` 为键记录
+    ///
+    /// # 参数
+    /// * `config` - 配置JSON对象
+    /// * `prefix` - 错误字段前缀
+    /// * `errors` - 错误收集器
+    fn validate_schema(config: &serde_json::Map<String, Value>, prefix: &str, errors: &mut ValidationErrors) {
+        for (key, value) in config {
+            let field = format!("{}.{}", prefix, key);
+            if !Self::FIELDS.contains(&key.as_str()) {
+                errors.add(field, "未知的配置项");
+            } else if let Err(e) = serde_json::from_value::<Self>(json!({ key: value })) {
+                errors.add(field, format!("配置项类型无效: {}", e));
+            }
+        }
+
+        for key in Self::REQUIRED {
+            if config.get(*key).map_or(true, Value::is_null) {
+                errors.add(format!("{}.{}", prefix, key), "缺少必填配置项");
+            }
+        }
+    }
+}
+
+impl RuntimeConfigSchema for DockerConfig {
+    const SECTION: &'static str = "docker";
+    const FIELDS: &'static [&'static str] =
+        &["host", "tls_verify", "cert_path", "api_version", "registry", "network", "image"];
+    const REQUIRED: &'static [&'static str] = &["host"];
+}
+
+impl RuntimeConfigSchema for KubernetesConfig {
+    const SECTION: &'static str = "kubernetes";
+    const FIELDS: &'static [&'static str] = &[
+        "kubeconfig_path",
+        "namespace",
+        "context",
+        "cluster",
+        "service_account",
+        "image",
+        "job_template",
+        "timeout_seconds",
+    ];
+}
+
+impl RuntimeConfigSchema for LocalConfig {
+    const SECTION: &'static str = "local";
+    const FIELDS: &'static [&'static str] =
+        &["python_path", "working_directory", "environment_variables", "timeout_seconds"];
+}
+
+/// 按运行时类型验证管理器配置
+///
+/// 既接受直接的运行时配置，也接受只包含对应段的完整运行时配置（如 `{"docker": {...}}`）
+///
+/// # 参数
+/// * `runtime_type` - 运行时类型
+/// * `config` - 配置JSON
+pub fn validate_runtime_config(runtime_type: &RuntimeType, config: &Value) -> Result<(), ValidationErrors> {
+    fn validate<T: RuntimeConfigSchema>(config: &Value, errors: &mut ValidationErrors) {
+        let Some(object) = config.as_object() else {
+            errors.add("config", "配置必须是有效的JSON对象");
+            return;
+        };
+
+        match object.get(T::SECTION) {
+            Some(section) if object.len() == 1 => match section.as_object() {
+                Some(section) => T::validate_schema(section, &format!("config.{}", T::SECTION), errors),
+                None => errors.add(format!("config.{}", T::SECTION), "配置必须是有效的JSON对象"),
+            },
+            _ => T::validate_schema(object, "config", errors),
+        }
+    }
+
+    let mut errors = ValidationErrors::new();
+    match runtime_type {
+        RuntimeType::Local => validate::<LocalConfig>(config, &mut errors),
+        RuntimeType::Docker => validate::<DockerConfig>(config, &mut errors),
+        RuntimeType::Kubernetes => validate::<KubernetesConfig>(config, &mut errors),
+    }
+    errors.into_result()
+}
+
 impl RuntimeManager {
     /// 创建新的运行时管理器
     pub async fn create(
@@ -512,4 +623,35 @@ impl RuntimeManager {
             false
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_docker_config_accepted() {
+        let config = json!({
+            "host": "unix:///var/run/docker.sock",
+            "tls_verify": false,
+            "network": "aiops",
+        });
+        assert!(validate_runtime_config(&RuntimeType::Docker, &config).is_ok());
+
+        let wrapped = json!({ "docker": config });
+        assert!(validate_runtime_config(&RuntimeType::Docker, &wrapped).is_ok());
+    }
+
+    #[test]
+    fn test_docker_config_missing_required_field_rejected() {
+        let config = json!({
+            "tls_verify": "yes",
+            "namespace": "aiops",
+        });
+        let errors = validate_runtime_config(&RuntimeType::Docker, &config).unwrap_err();
+
+        let errors = serde_json::to_value(&errors).unwrap();
+        assert_eq!(errors["config.host"], "缺少必填配置项");
+        assert_eq!(errors["config.namespace"], "未知的配置项");
+        assert!(errors["config.tls_verify"].as_str().unwrap().starts_with("配置项类型无效"));
+    }
+}
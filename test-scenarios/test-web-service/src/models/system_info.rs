@@ -0,0 +1,114 @@
+//! 系统信息模型
+//!
+//! 启动时采集一次主机的静态信息，之后的请求直接读取，只有运行时长按需计算
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Instant;
+use sysinfo::System;
+use utoipa::ToSchema;
+
+/// 启动时采集的主机静态信息
+#[derive(Debug, Clone)]
+pub struct SystemInfo {
+    /// 主机名
+    pub hostname: String,
+    /// 操作系统名称及版本
+    pub os: String,
+    /// CPU架构
+    pub arch: String,
+    /// CPU核心数
+    pub cpu_cores: usize,
+    /// 物理内存总量（字节）
+    pub total_memory_bytes: u64,
+    /// 服务启动时间
+    pub started_at: DateTime<Utc>,
+    /// 服务启动时刻，用于计算运行时长
+    started: Instant,
+}
+
+/// 系统信息快照，借用 [`SystemInfo`] 中的静态字段
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SystemInfoSnapshot<'a> {
+    /// 主机名
+    pub hostname: &'a str,
+    /// 操作系统名称及版本
+    pub os: &'a str,
+    /// CPU架构
+    pub arch: &'a str,
+    /// CPU核心数
+    pub cpu_cores: usize,
+    /// 物理内存总量（字节）
+    pub total_memory_bytes: u64,
+    /// 服务启动时间
+    pub started_at: DateTime<Utc>,
+    /// 运行时长（秒）
+    pub uptime_seconds: u64,
+    /// 运行时长（毫秒）
+    pub uptime_ms: u64,
+}
+
+impl SystemInfo {
+    /// 采集主机信息，以当前时间作为服务启动时间
+    pub fn collect() -> Self {
+        let mut sys = System::new();
+        sys.refresh_memory();
+
+        let os = match (System::name(), System::os_version()) {
+            (Some(name), Some(version)) => format!("{} {}", name, version),
+            (Some(name), None) => name,
+            _ => std::env::consts::OS.to_string(),
+        };
+
+        Self {
+            hostname: System::host_name().unwrap_or_else(|| "unknown".to_string()),
+            os,
+            arch: System::cpu_arch().unwrap_or_else(|| std::env::consts::ARCH.to_string()),
+            cpu_cores: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            total_memory_bytes: sys.total_memory(),
+            started_at: Utc::now(),
+            started: Instant::now(),
+        }
+    }
+
+    /// 服务运行时长
+    pub fn uptime(&self) -> std::time::Duration {
+        self.started.elapsed()
+    }
+
+    /// 生成包含当前运行时长的快照
+    pub fn snapshot(&self) -> SystemInfoSnapshot<'_> {
+        let uptime = self.uptime();
+        SystemInfoSnapshot {
+            hostname: &self.hostname,
+            os: &self.os,
+            arch: &self.arch,
+            cpu_cores: self.cpu_cores,
+            total_memory_bytes: self.total_memory_bytes,
+            started_at: self.started_at,
+            uptime_seconds: uptime.as_secs(),
+            uptime_ms: uptime.as_millis() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_uptime_increases_while_static_fields_stay_constant() {
+        let info = SystemInfo::collect();
+        assert!(info.cpu_cores >= 1);
+        assert!(!info.hostname.is_empty());
+
+        let first = serde_json::to_value(info.snapshot()).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let second = serde_json::to_value(info.snapshot()).unwrap();
+
+        assert!(second["uptime_ms"].as_u64().unwrap() > first["uptime_ms"].as_u64().unwrap());
+        for field in ["hostname", "os", "arch", "cpu_cores", "total_memory_bytes", "started_at"] {
+            assert_eq!(first[field], second[field], "{} 不应变化", field);
+        }
+    }
+}
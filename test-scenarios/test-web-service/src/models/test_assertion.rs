@@ -0,0 +1,172 @@
+//! 测试断言模型
+//!
+//! 测试运行结束后按用例上配置的断言判定成功/失败，判定结果写入 `test_results`
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+/// 断言结果在 `test_results` 表中的结果类型
+pub const ASSERTION_RESULT_TYPE: &str = "assertions";
+
+/// 测试用例断言
+///
+/// 配置了断言的用例不再以退出码是否为0判定结果，而是要求所有断言通过
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TestAssertions {
+    /// 期望的退出码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_exit_code: Option<i32>,
+    /// stdout必须匹配的正则表达式
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stdout_contains: Vec<String>,
+    /// stdout不能匹配的正则表达式
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stdout_not_contains: Vec<String>,
+    /// 最长运行时间（毫秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_ms: Option<i64>,
+}
+
+/// 单条断言的判定结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AssertionResult {
+    /// 断言描述，如 `stdout_contains[0]`
+    pub assertion: String,
+    /// 是否通过
+    pub passed: bool,
+    /// 判定说明
+    pub message: String,
+}
+
+/// 一次测试运行的断言判定报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AssertionReport {
+    /// 是否全部通过
+    pub passed: bool,
+    /// 各断言的判定结果
+    pub results: Vec<AssertionResult>,
+}
+
+impl TestAssertions {
+    /// 校验断言配置，返回 (字段, 错误信息) 列表
+    pub fn validate(&self) -> Vec<(String, String)> {
+        let mut errors = Vec::new();
+
+        let patterns = [
+            ("stdout_contains", &self.stdout_contains),
+            ("stdout_not_contains", &self.stdout_not_contains),
+        ];
+        for (field, patterns) in patterns {
+            for (index, pattern) in patterns.iter().enumerate() {
+                if let Err(e) = Regex::new(pattern) {
+                    errors.push((format!("{}[{}]", field, index), format!("无效的正则表达式: {}", e)));
+                }
+            }
+        }
+
+        if self.max_duration_ms.is_some_and(|ms| ms <= 0) {
+            errors.push(("max_duration_ms".to_string(), "最长运行时间必须大于0".to_string()));
+        }
+
+        errors
+    }
+
+    /// 根据运行结果判定全部断言
+    ///
+    /// # 参数
+    /// * `exit_code` - 退出码
+    /// * `stdout` - 标准输出
+    /// * `duration_ms` - 运行时间（毫秒）
+    pub fn evaluate(&self, exit_code: i32, stdout: &str, duration_ms: i64) -> AssertionReport {
+        let mut results = Vec::new();
+
+        if let Some(expected) = self.expected_exit_code {
+            results.push(AssertionResult {
+                assertion: "expected_exit_code".to_string(),
+                passed: exit_code == expected,
+                message: format!("期望退出码 {}，实际 {}", expected, exit_code),
+            });
+        }
+
+        for (index, pattern) in self.stdout_contains.iter().enumerate() {
+            let (passed, message) = match Regex::new(pattern) {
+                Ok(re) if re.is_match(stdout) => (true, format!("stdout匹配 /{}/", pattern)),
+                Ok(_) => (false, format!("stdout未匹配 /{}/", pattern)),
+                Err(e) => (false, format!("无效的正则表达式: {}", e)),
+            };
+            results.push(AssertionResult {
+                assertion: format!("stdout_contains[{}]", index),
+                passed,
+                message,
+            });
+        }
+
+        for (index, pattern) in self.stdout_not_contains.iter().enumerate() {
+            let (passed, message) = match Regex::new(pattern) {
+                Ok(re) if re.is_match(stdout) => (false, format!("stdout不应匹配 /{}/", pattern)),
+                Ok(_) => (true, format!("stdout未匹配 /{}/", pattern)),
+                Err(e) => (false, format!("无效的正则表达式: {}", e)),
+            };
+            results.push(AssertionResult {
+                assertion: format!("stdout_not_contains[{}]", index),
+                passed,
+                message,
+            });
+        }
+
+        if let Some(max) = self.max_duration_ms {
+            results.push(AssertionResult {
+                assertion: "max_duration_ms".to_string(),
+                passed: duration_ms <= max,
+                message: format!("运行 {}ms，上限 {}ms", duration_ms, max),
+            });
+        }
+
+        AssertionReport {
+            passed: results.iter().all(|r| r.passed),
+            results,
+        }
+    }
+}
+
+impl AssertionReport {
+    /// 将断言报告写入 `test_results`
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    /// * `test_run_id` - 测试运行ID
+    pub async fn save(&self, pool: &SqlitePool, test_run_id: &Uuid) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO test_results (id, test_run_id, result_type, data) VALUES (?, ?, ?, ?)"
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(test_run_id.to_string())
+        .bind(ASSERTION_RESULT_TYPE)
+        .bind(serde_json::to_string(self)?)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 读取测试运行最近一次的断言报告
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    /// * `test_run_id` - 测试运行ID
+    pub async fn find_by_run(pool: &SqlitePool, test_run_id: &Uuid) -> anyhow::Result<Option<Self>> {
+        let data: Option<String> = sqlx::query_scalar(
+            "SELECT data FROM test_results WHERE test_run_id = ? AND result_type = ? ORDER BY timestamp DESC LIMIT 1"
+        )
+        .bind(test_run_id.to_string())
+        .bind(ASSERTION_RESULT_TYPE)
+        .fetch_optional(pool)
+        .await?;
+
+        data.map(|data| serde_json::from_str(&data).map_err(Into::into))
+            .transpose()
+    }
+}
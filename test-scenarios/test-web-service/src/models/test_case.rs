@@ -2,7 +2,9 @@
 //! 
 //! 定义测试用例的数据结构和数据库操作
 
-use super::{RuntimeType, PaginationParams, PaginatedResponse, PaginationInfo};
+use super::{RuntimeType, PaginationParams, PaginatedResponse, PaginationInfo, ResourceLimits, TestAssertions};
+use super::test_env::{serialize_masked_env, EnvVarInput, StoredEnv};
+use std::collections::BTreeMap;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
@@ -26,6 +28,18 @@ pub struct TestCase {
     pub runtime_type: String,
     /// 标签（JSON字符串）
     pub tags: Option<String>,
+    /// 断言（JSON字符串）
+    #[sqlx(default)]
+    pub assertions: Option<String>,
+    /// 环境变量（JSON字符串），响应中为 `变量名 -> 值` 映射，密文变量显示为掩码
+    #[sqlx(default)]
+    #[serde(serialize_with = "serialize_masked_env", skip_deserializing)]
+    #[schema(value_type = Option<Object>)]
+    pub env: Option<String>,
+    /// 资源限制
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub limits: ResourceLimits,
     /// 创建时间
     pub created_at: DateTime<Utc>,
     /// 更新时间
@@ -47,6 +61,15 @@ pub struct CreateTestCaseRequest {
     pub runtime_type: RuntimeType,
     /// 标签列表
     pub tags: Option<Vec<String>>,
+    /// 运行结果断言
+    #[serde(default)]
+    pub assertions: Option<TestAssertions>,
+    /// 注入执行环境的环境变量
+    #[serde(default)]
+    pub env: Option<BTreeMap<String, EnvVarInput>>,
+    /// 资源限制
+    #[serde(flatten)]
+    pub limits: ResourceLimits,
 }
 
 /// 更新测试用例请求
@@ -64,6 +87,15 @@ pub struct UpdateTestCaseRequest {
     pub runtime_type: Option<RuntimeType>,
     /// 标签列表
     pub tags: Option<Vec<String>>,
+    /// 运行结果断言
+    #[serde(default)]
+    pub assertions: Option<TestAssertions>,
+    /// 注入执行环境的环境变量，提供时整体替换原有环境变量
+    #[serde(default)]
+    pub env: Option<BTreeMap<String, EnvVarInput>>,
+    /// 资源限制，只更新提供的项
+    #[serde(flatten)]
+    pub limits: ResourceLimits,
 }
 
 /// 运行测试用例请求
@@ -75,6 +107,9 @@ pub struct RunTestCaseRequest {
     pub config_override: Option<String>,
     /// 元数据
     pub metadata: Option<serde_json::Value>,
+    /// 本次运行的环境变量，与用例环境变量同名时覆盖用例的值
+    #[serde(default)]
+    pub env: Option<BTreeMap<String, EnvVarInput>>,
 }
 
 /// 测试用例查询参数
@@ -91,6 +126,135 @@ pub struct TestCaseQuery {
     pub tags: Option<String>,
 }
 
+/// 测试用例导出格式
+///
+/// 不包含ID和时间戳，便于纳入版本管理并在其他环境中导入
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TestCaseExport {
+    /// 测试用例名称
+    pub name: String,
+    /// 测试用例描述
+    pub description: Option<String>,
+    /// 脚本路径
+    pub script_path: String,
+    /// 配置文件路径
+    pub config_path: Option<String>,
+    /// 运行时类型
+    pub runtime_type: RuntimeType,
+    /// 标签列表
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 运行结果断言
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assertions: Option<TestAssertions>,
+    /// 资源限制
+    #[serde(flatten)]
+    pub limits: ResourceLimits,
+}
+
+impl From<TestCaseExport> for CreateTestCaseRequest {
+    fn from(case: TestCaseExport) -> Self {
+        Self {
+            name: case.name,
+            description: case.description,
+            script_path: case.script_path,
+            config_path: case.config_path,
+            runtime_type: case.runtime_type,
+            tags: Some(case.tags),
+            assertions: case.assertions,
+            env: None,
+            limits: case.limits,
+        }
+    }
+}
+
+/// 测试用例导出筛选参数
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct TestCaseExportQuery {
+    /// 按名称筛选
+    pub name: Option<String>,
+    /// 按运行时类型筛选
+    pub runtime_type: Option<RuntimeType>,
+    /// 按标签筛选
+    pub tags: Option<String>,
+}
+
+/// 导入时同名测试用例的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStrategy {
+    /// 跳过已存在的同名测试用例
+    #[default]
+    Skip,
+    /// 覆盖已存在的同名测试用例
+    Overwrite,
+}
+
+/// 测试用例导入参数
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct ImportTestCasesParams {
+    /// 同名处理策略，默认跳过
+    #[serde(default)]
+    pub strategy: ImportStrategy,
+}
+
+/// 单个测试用例的导入结果状态
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportItemStatus {
+    /// 新建
+    Created,
+    /// 覆盖更新
+    Updated,
+    /// 已存在，跳过
+    Skipped,
+    /// 校验或写入失败
+    Failed,
+}
+
+/// 单个测试用例的导入结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportItemResult {
+    /// 在导入数组中的位置
+    pub index: usize,
+    /// 测试用例名称
+    pub name: String,
+    /// 导入结果
+    pub status: ImportItemStatus,
+    /// 测试用例ID（失败时为空）
+    pub id: Option<String>,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+/// 测试用例导入报告
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct ImportTestCasesReport {
+    /// 新建数量
+    pub created: usize,
+    /// 覆盖数量
+    pub updated: usize,
+    /// 跳过数量
+    pub skipped: usize,
+    /// 失败数量
+    pub failed: usize,
+    /// 逐条结果
+    pub results: Vec<ImportItemResult>,
+}
+
+impl ImportTestCasesReport {
+    /// 记录单条导入结果并更新计数
+    pub fn push(&mut self, result: ImportItemResult) {
+        match result.status {
+            ImportItemStatus::Created => self.created += 1,
+            ImportItemStatus::Updated => self.updated += 1,
+            ImportItemStatus::Skipped => self.skipped += 1,
+            ImportItemStatus::Failed => self.failed += 1,
+        }
+        self.results.push(result);
+    }
+}
+
 impl TestCase {
     /// 获取运行时类型
     pub fn get_runtime_type(&self) -> anyhow::Result<RuntimeType> {
@@ -180,12 +344,14 @@ impl TestCase {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now();
         let tags_str = req.tags.map(|tags| tags.join(","));
+        let assertions_str = req.assertions.as_ref().map(serde_json::to_string).transpose()?;
         let runtime_type_str = req.runtime_type.to_string();
 
         sqlx::query(
             r#"
-            INSERT INTO test_cases (id, name, description, script_path, config_path, runtime_type, tags, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO test_cases (id, name, description, script_path, config_path, runtime_type, tags, assertions,
+                                    timeout_seconds, max_memory_mb, max_cpu, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&id)
@@ -195,6 +361,10 @@ impl TestCase {
         .bind(&req.config_path)
         .bind(&runtime_type_str)
         .bind(&tags_str)
+        .bind(&assertions_str)
+        .bind(req.limits.timeout_seconds)
+        .bind(req.limits.max_memory_mb)
+        .bind(req.limits.max_cpu)
         .bind(&now)
         .bind(&now)
         .execute(pool)
@@ -314,6 +484,23 @@ impl TestCase {
             params.push(tags.join(","));
         }
 
+        if let Some(assertions) = &req.assertions {
+            updates.push("assertions = ?");
+            params.push(serde_json::to_string(assertions)?);
+        }
+
+        let limits = [
+            ("timeout_seconds = ?", req.limits.timeout_seconds.map(|v| v.to_string())),
+            ("max_memory_mb = ?", req.limits.max_memory_mb.map(|v| v.to_string())),
+            ("max_cpu = ?", req.limits.max_cpu.map(|v| v.to_string())),
+        ];
+        for (update, value) in limits {
+            if let Some(value) = value {
+                updates.push(update);
+                params.push(value);
+            }
+        }
+
         if updates.is_empty() {
             return Self::find_by_id(pool, id).await;
         }
@@ -337,6 +524,20 @@ impl TestCase {
         Self::find_by_id(pool, id).await
     }
 
+    /// 保存环境变量，调用方负责加密密文变量
+    pub async fn set_env(pool: &SqlitePool, id: &str, env: &StoredEnv) -> anyhow::Result<TestCase> {
+        let env_str = (!env.is_empty()).then(|| serde_json::to_string(env)).transpose()?;
+
+        sqlx::query("UPDATE test_cases SET env = ?, updated_at = ? WHERE id = ?")
+            .bind(&env_str)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Self::find_by_id(pool, id).await
+    }
+
     /// 删除测试用例
     pub async fn delete(pool: &SqlitePool, id: &str) -> anyhow::Result<()> {
         sqlx::query("DELETE FROM test_cases WHERE id = ?")
@@ -347,6 +548,105 @@ impl TestCase {
         Ok(())
     }
 
+    /// 根据名称查找测试用例
+    pub async fn find_by_name(pool: &SqlitePool, name: &str) -> anyhow::Result<Option<TestCase>> {
+        let test_case = sqlx::query_as::<_, TestCase>(
+            "SELECT * FROM test_cases WHERE name = ? ORDER BY created_at LIMIT 1"
+        )
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(test_case)
+    }
+
+    /// 导出符合条件的全部测试用例，按名称排序
+    pub async fn export(
+        pool: &SqlitePool,
+        query: &TestCaseExportQuery,
+    ) -> anyhow::Result<Vec<TestCaseExport>> {
+        let mut sql = "SELECT * FROM test_cases WHERE 1=1".to_string();
+        let mut params = Vec::new();
+
+        if let Some(name) = &query.name {
+            sql.push_str(" AND name LIKE ?");
+            params.push(format!("%{}%", name));
+        }
+
+        if let Some(runtime_type) = &query.runtime_type {
+            sql.push_str(" AND runtime_type = ?");
+            params.push(runtime_type.to_string());
+        }
+
+        if let Some(tags) = &query.tags {
+            sql.push_str(" AND tags LIKE ?");
+            params.push(format!("%{}%", tags));
+        }
+
+        sql.push_str(" ORDER BY name, created_at");
+
+        let mut data_query = sqlx::query_as::<_, TestCase>(&sql);
+        for param in &params {
+            data_query = data_query.bind(param);
+        }
+
+        data_query
+            .fetch_all(pool)
+            .await?
+            .iter()
+            .map(TestCase::to_export)
+            .collect()
+    }
+
+    /// 转换为导出格式
+    pub fn to_export(&self) -> anyhow::Result<TestCaseExport> {
+        Ok(TestCaseExport {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            script_path: self.script_path.clone(),
+            config_path: self.config_path.clone(),
+            runtime_type: self.get_runtime_type()?,
+            tags: self.get_tags(),
+            assertions: self.get_assertions()?,
+            limits: self.limits,
+        })
+    }
+
+    /// 用请求内容整体覆盖测试用例（未提供的可选字段会被清空）
+    pub async fn replace(
+        pool: &SqlitePool,
+        id: &str,
+        req: CreateTestCaseRequest,
+    ) -> anyhow::Result<TestCase> {
+        let tags_str = req.tags.map(|tags| tags.join(","));
+        let assertions_str = req.assertions.as_ref().map(serde_json::to_string).transpose()?;
+
+        sqlx::query(
+            r#"
+            UPDATE test_cases
+            SET name = ?, description = ?, script_path = ?, config_path = ?, runtime_type = ?, tags = ?, assertions = ?,
+                timeout_seconds = ?, max_memory_mb = ?, max_cpu = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&req.script_path)
+        .bind(&req.config_path)
+        .bind(req.runtime_type.to_string())
+        .bind(&tags_str)
+        .bind(&assertions_str)
+        .bind(req.limits.timeout_seconds)
+        .bind(req.limits.max_memory_mb)
+        .bind(req.limits.max_cpu)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Self::find_by_id(pool, id).await
+    }
+
     /// 获取测试用例的标签列表
     pub fn get_tags(&self) -> Vec<String> {
         self.tags
@@ -360,5 +660,13 @@ impl TestCase {
             .unwrap_or_default()
     }
 
+    /// 获取测试用例配置的断言
+    pub fn get_assertions(&self) -> anyhow::Result<Option<TestAssertions>> {
+        self.assertions
+            .as_deref()
+            .map(|assertions| serde_json::from_str(assertions).map_err(Into::into))
+            .transpose()
+    }
+
 
 }
\ No newline at end of file
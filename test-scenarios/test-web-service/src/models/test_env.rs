@@ -0,0 +1,88 @@
+//! 测试环境变量模型
+//!
+//! 测试用例和单次运行可以定义注入执行环境的环境变量，密文变量以加密形式落库，
+//! 在API响应中以掩码代替
+
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// 密文环境变量在响应和日志中的掩码
+pub const SECRET_MASK: &str = "******";
+
+/// 请求中的环境变量值
+///
+/// 普通变量直接写字符串，密文变量写成 `{"value": "...", "secret": true}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum EnvVarInput {
+    /// 普通变量
+    Plain(String),
+    /// 可标记为密文的变量
+    Detailed {
+        /// 变量值
+        value: String,
+        /// 是否为密文
+        #[serde(default)]
+        secret: bool,
+    },
+}
+
+impl EnvVarInput {
+    /// 变量值
+    pub fn value(&self) -> &str {
+        match self {
+            EnvVarInput::Plain(value) | EnvVarInput::Detailed { value, .. } => value,
+        }
+    }
+
+    /// 是否为密文变量
+    pub fn is_secret(&self) -> bool {
+        matches!(self, EnvVarInput::Detailed { secret: true, .. })
+    }
+}
+
+/// 落库的环境变量，密文变量的 `value` 为加密后的字符串
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredEnvVar {
+    /// 变量值（密文变量为密文）
+    pub value: String,
+    /// 是否为密文
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// 落库的环境变量表，按变量名排序
+pub type StoredEnv = BTreeMap<String, StoredEnvVar>;
+
+/// 环境变量名是否合法：字母或下划线开头，只包含字母、数字和下划线
+pub fn is_valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 解析 `env` 列中的JSON，空值返回空表
+pub fn parse_stored_env(raw: Option<&str>) -> anyhow::Result<StoredEnv> {
+    match raw {
+        Some(raw) if !raw.trim().is_empty() => Ok(serde_json::from_str(raw)?),
+        _ => Ok(StoredEnv::new()),
+    }
+}
+
+/// 序列化 `env` 列，输出 `变量名 -> 值` 映射，密文变量输出掩码
+pub fn serialize_masked_env<S: Serializer>(env: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    let env = match parse_stored_env(env.as_deref()) {
+        Ok(env) if !env.is_empty() => env,
+        _ => return serializer.serialize_none(),
+    };
+
+    let masked: BTreeMap<&str, &str> = env
+        .iter()
+        .map(|(name, var)| {
+            let value = if var.secret { SECRET_MASK } else { var.value.as_str() };
+            (name.as_str(), value)
+        })
+        .collect();
+    serializer.collect_map(masked)
+}
@@ -3,6 +3,7 @@
 //! 定义测试运行的数据结构和数据库操作
 
 use super::{TestStatus, PaginationParams, PaginatedResponse, PaginationInfo};
+use super::test_env::{serialize_masked_env, StoredEnv};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
@@ -32,6 +33,17 @@ pub struct TestRun {
     pub stderr: Option<String>,
     /// 元数据（JSON字符串）
     pub metadata: Option<String>,
+    /// 本次运行生效的环境变量（JSON字符串），响应中密文变量显示为掩码
+    #[sqlx(default)]
+    #[serde(serialize_with = "serialize_masked_env", skip_deserializing)]
+    #[schema(value_type = Option<Object>)]
+    pub env: Option<String>,
+    /// 等待中的运行暂未启动的原因，如资源余量不足，启动后清空
+    #[sqlx(default)]
+    pub pending_reason: Option<String>,
+    /// 调度优先级，等待中的运行按优先级从高到低获得并发名额
+    #[sqlx(default)]
+    pub priority: i32,
     /// 创建时间
     pub created_at: DateTime<Utc>,
 }
@@ -60,6 +72,13 @@ pub struct UpdateTestRunRequest {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// 调整测试运行优先级请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetPriorityRequest {
+    /// 新的优先级，越大越先获得并发名额，默认优先级为0
+    pub priority: i32,
+}
+
 /// 测试运行查询参数
 #[derive(Debug, Deserialize, ToSchema, IntoParams)]
 pub struct TestRunQuery {
@@ -78,6 +97,41 @@ pub struct TestRunQuery {
     pub end_date: Option<DateTime<Utc>>,
 }
 
+/// 批量取消等待中运行的筛选参数，都不提供时取消全部等待中的运行
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct CancelPendingQuery {
+    /// 只取消该测试用例的运行
+    pub test_case_id: Option<String>,
+    /// 只取消带有该标签的测试用例的运行
+    pub tag: Option<String>,
+}
+
+/// 批量取消结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CancelPendingResult {
+    /// 被取消的运行数量
+    pub cancelled: u64,
+}
+
+/// 测试用例运行日志汇总查询参数
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct CaseRunLogsQuery {
+    /// 汇总最近多少次运行，默认5，最大50
+    pub limit: Option<u32>,
+}
+
+impl CaseRunLogsQuery {
+    /// 默认汇总的运行次数
+    pub const DEFAULT_LIMIT: u32 = 5;
+    /// 最多汇总的运行次数
+    pub const MAX_LIMIT: u32 = 50;
+
+    /// 实际汇总的运行次数，限制在1到 `MAX_LIMIT` 之间
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT).clamp(1, Self::MAX_LIMIT)
+    }
+}
+
 /// 测试运行统计信息
 #[derive(Debug, Serialize, ToSchema)]
 pub struct TestRunStats {
@@ -182,6 +236,28 @@ impl TestRun {
         Ok(test_run)
     }
 
+    /// 获取测试用例最近的运行记录，按创建时间从早到晚排列
+    ///
+    /// # 参数
+    /// * `test_case_id` - 测试用例ID
+    /// * `limit` - 最多返回的运行次数
+    pub async fn find_recent_by_case(
+        pool: &SqlitePool,
+        test_case_id: &str,
+        limit: u32,
+    ) -> anyhow::Result<Vec<TestRun>> {
+        let mut runs = sqlx::query_as::<_, TestRun>(
+            "SELECT * FROM test_runs WHERE test_case_id = ? ORDER BY created_at DESC, rowid DESC LIMIT ?"
+        )
+        .bind(test_case_id)
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+        runs.reverse();
+        Ok(runs)
+    }
+
     /// 分页查询测试运行记录
     pub async fn find_all(
         pool: &SqlitePool,
@@ -477,6 +553,19 @@ impl TestRun {
         Self::find_by_id(pool, &id.to_string()).await
     }
 
+    /// 保存本次运行生效的环境变量，调用方负责加密密文变量
+    pub async fn set_env(pool: &SqlitePool, id: &str, env: &StoredEnv) -> anyhow::Result<TestRun> {
+        let env_str = (!env.is_empty()).then(|| serde_json::to_string(env)).transpose()?;
+
+        sqlx::query("UPDATE test_runs SET env = ? WHERE id = ?")
+            .bind(&env_str)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Self::find_by_id(pool, id).await
+    }
+
     /// 更新测试状态
     pub async fn update_status(
         pool: &sqlx::Pool<sqlx::Sqlite>,
@@ -494,6 +583,108 @@ impl TestRun {
         Self::find_by_id(pool, &id.to_string()).await
     }
 
+    /// 排队结束后将运行切换为运行中，返回是否切换成功
+    ///
+    /// 只切换仍处于等待状态的运行，排队期间已被取消的运行保持取消状态
+    pub async fn mark_running(
+        pool: &sqlx::Pool<sqlx::Sqlite>,
+        id: &uuid::Uuid,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query("UPDATE test_runs SET status = ?, pending_reason = NULL WHERE id = ? AND status = ?")
+            .bind(TestStatus::Running.to_string())
+            .bind(id.to_string())
+            .bind(TestStatus::Pending.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// 记录等待中的运行暂未启动的原因，返回运行是否仍在等待
+    ///
+    /// 已被取消或已开始的运行不受影响
+    pub async fn set_pending_reason(
+        pool: &sqlx::Pool<sqlx::Sqlite>,
+        id: &uuid::Uuid,
+        reason: &str,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query("UPDATE test_runs SET pending_reason = ? WHERE id = ? AND status = ?")
+            .bind(reason)
+            .bind(id.to_string())
+            .bind(TestStatus::Pending.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// 设置等待中运行的优先级，返回运行是否仍在等待
+    ///
+    /// 已开始或已结束的运行不受影响
+    pub async fn set_priority(
+        pool: &sqlx::Pool<sqlx::Sqlite>,
+        id: &uuid::Uuid,
+        priority: i32,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query("UPDATE test_runs SET priority = ? WHERE id = ? AND status = ?")
+            .bind(priority)
+            .bind(id.to_string())
+            .bind(TestStatus::Pending.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// 将匹配的等待中运行一次性标记为已取消，返回取消的数量
+    ///
+    /// 在单条UPDATE中完成，已在运行或已结束的运行不受影响
+    pub async fn cancel_pending(
+        pool: &sqlx::Pool<sqlx::Sqlite>,
+        query: &CancelPendingQuery,
+    ) -> anyhow::Result<u64> {
+        let mut sql = "UPDATE test_runs SET status = ? WHERE status = ?".to_string();
+        let mut params = vec![TestStatus::Cancelled.to_string(), TestStatus::Pending.to_string()];
+
+        if let Some(test_case_id) = &query.test_case_id {
+            sql.push_str(" AND test_case_id = ?");
+            params.push(test_case_id.clone());
+        }
+
+        // 标签以逗号分隔保存，前后补逗号后按完整标签匹配
+        if let Some(tag) = &query.tag {
+            sql.push_str(
+                " AND test_case_id IN (SELECT id FROM test_cases WHERE ',' || tags || ',' LIKE '%,' || ? || ',%')",
+            );
+            params.push(tag.trim().to_string());
+        }
+
+        let mut update = sqlx::query(&sql);
+        for param in params {
+            update = update.bind(param);
+        }
+
+        Ok(update.execute(pool).await?.rows_affected())
+    }
+
+    /// 删除测试运行记录
+    pub async fn delete(
+        pool: &sqlx::Pool<sqlx::Sqlite>,
+        id: &uuid::Uuid,
+    ) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM test_results WHERE test_run_id = ?")
+            .bind(id.to_string())
+            .execute(pool)
+            .await?;
+
+        sqlx::query("DELETE FROM test_runs WHERE id = ?")
+            .bind(id.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
     /// 根据ID获取测试运行记录（支持UUID参数）
     pub async fn get_by_id(
         pool: &sqlx::Pool<sqlx::Sqlite>,
@@ -0,0 +1,112 @@
+//! 测试运行时间线模型
+//!
+//! 记录测试运行各阶段（排队、拉取镜像、启动、首次输出、完成）的时间，写入 `test_results`
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+/// 时间线事件在 `test_results` 表中的结果类型
+pub const TIMELINE_RESULT_TYPE: &str = "timeline";
+
+/// 测试运行阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelinePhase {
+    /// 已提交执行，等待调度
+    Queued,
+    /// 容器运行时已提交任务，正在拉取镜像并启动容器
+    ContainerPulling,
+    /// 测试进程已启动
+    Started,
+    /// 测试进程产生了第一段输出
+    FirstOutput,
+    /// 测试运行结束
+    Completed,
+}
+
+/// 时间线事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TimelineEvent {
+    /// 阶段
+    pub phase: TimelinePhase,
+    /// 发生时间
+    pub timestamp: DateTime<Utc>,
+    /// 补充说明，如完成时的最终状态
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl TimelineEvent {
+    /// 以当前时间记录一个阶段
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    /// * `test_run_id` - 测试运行ID
+    /// * `phase` - 阶段
+    /// * `detail` - 补充说明
+    pub async fn record(
+        pool: &SqlitePool,
+        test_run_id: &Uuid,
+        phase: TimelinePhase,
+        detail: Option<String>,
+    ) -> anyhow::Result<Self> {
+        Self::record_at(pool, test_run_id, phase, Utc::now(), detail).await
+    }
+
+    /// 以指定时间记录一个阶段
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    /// * `test_run_id` - 测试运行ID
+    /// * `phase` - 阶段
+    /// * `timestamp` - 阶段发生时间
+    /// * `detail` - 补充说明
+    pub async fn record_at(
+        pool: &SqlitePool,
+        test_run_id: &Uuid,
+        phase: TimelinePhase,
+        timestamp: DateTime<Utc>,
+        detail: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let event = Self { phase, timestamp, detail };
+
+        sqlx::query(
+            "INSERT INTO test_results (id, test_run_id, result_type, data, timestamp) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(test_run_id.to_string())
+        .bind(TIMELINE_RESULT_TYPE)
+        .bind(serde_json::to_string(&event)?)
+        .bind(timestamp)
+        .execute(pool)
+        .await?;
+
+        Ok(event)
+    }
+
+    /// 获取测试运行的时间线，按发生时间排序
+    ///
+    /// # 参数
+    /// * `pool` - 数据库连接池
+    /// * `test_run_id` - 测试运行ID
+    pub async fn find_by_run(pool: &SqlitePool, test_run_id: &Uuid) -> anyhow::Result<Vec<Self>> {
+        let rows: Vec<String> = sqlx::query_scalar(
+            "SELECT data FROM test_results WHERE test_run_id = ? AND result_type = ?"
+        )
+        .bind(test_run_id.to_string())
+        .bind(TIMELINE_RESULT_TYPE)
+        .fetch_all(pool)
+        .await?;
+
+        let mut events = rows
+            .iter()
+            .map(|data| serde_json::from_str::<Self>(data))
+            .collect::<Result<Vec<_>, _>>()?;
+        // 同一时刻的事件按阶段先后排列
+        events.sort_by_key(|event| (event.timestamp, event.phase as u8));
+        Ok(events)
+    }
+}
@@ -0,0 +1,81 @@
+//! 敏感值加密
+//!
+//! 使用AES-256-GCM加密需要落库的敏感值，密钥由配置项 `secret_key` 经SHA-256派生，
+//! 密文格式为 `enc:v1:<base64(nonce || ciphertext)>`
+
+use crate::config::AppConfig;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+/// 密文前缀，带版本号便于日后更换算法
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+/// AES-GCM随机数长度（字节）
+const NONCE_LEN: usize = 12;
+
+/// 敏感值加解密器
+#[derive(Clone)]
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl SecretCipher {
+    /// 由密钥字符串创建加解密器
+    ///
+    /// # 参数
+    /// * `secret_key` - 密钥，任意长度
+    pub fn new(secret_key: &str) -> Self {
+        let key = Sha256::digest(secret_key.as_bytes());
+        Self {
+            cipher: Aes256Gcm::new(&key),
+        }
+    }
+
+    /// 按配置创建加解密器，未配置 `secret_key` 时返回 `None`
+    pub fn from_config(config: &AppConfig) -> Option<Self> {
+        config
+            .secret_key
+            .as_deref()
+            .filter(|key| !key.is_empty())
+            .map(Self::new)
+    }
+
+    /// 加密明文，每次使用新的随机数，相同明文的密文也不相同
+    ///
+    /// # 参数
+    /// * `plaintext` - 明文
+    pub fn encrypt(&self, plaintext: &str) -> anyhow::Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| anyhow::anyhow!("加密失败"))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload)))
+    }
+
+    /// 解密 `encrypt` 生成的密文
+    ///
+    /// # 参数
+    /// * `encrypted` - 密文
+    pub fn decrypt(&self, encrypted: &str) -> anyhow::Result<String> {
+        let payload = encrypted
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .ok_or_else(|| anyhow::anyhow!("不是受支持的密文格式"))?;
+        let payload = STANDARD.decode(payload)?;
+        if payload.len() < NONCE_LEN {
+            anyhow::bail!("密文长度不足");
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("解密失败，密钥可能已变更"))?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+}
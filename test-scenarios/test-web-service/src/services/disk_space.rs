@@ -0,0 +1,241 @@
+//! 磁盘空间检查
+//!
+//! 检查结果目录等可能持续增长的目录所在磁盘的剩余空间，磁盘写满时测试运行和日志写入会静默失败
+
+use crate::config::AppConfig;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use sysinfo::Disks;
+
+/// 磁盘空间状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiskStatus {
+    /// 剩余空间充足
+    Healthy,
+    /// 剩余空间低于降级阈值
+    Degraded,
+    /// 剩余空间低于严重阈值，拒绝启动新的测试运行
+    Critical,
+}
+
+/// 某个目录所在磁盘的空间
+#[derive(Debug, Clone, Copy)]
+pub struct DiskSpace {
+    /// 可用空间（字节）
+    pub available_bytes: u64,
+    /// 总空间（字节）
+    pub total_bytes: u64,
+}
+
+/// 磁盘空间探测
+pub trait DiskSpaceProbe: Send + Sync {
+    /// 获取目录所在磁盘的空间
+    ///
+    /// # 参数
+    /// * `path` - 目录路径，可以尚不存在
+    fn space(&self, path: &Path) -> anyhow::Result<DiskSpace>;
+}
+
+/// 通过系统挂载点获取磁盘空间
+#[derive(Debug, Default)]
+pub struct SystemDiskProbe;
+
+impl DiskSpaceProbe for SystemDiskProbe {
+    fn space(&self, path: &Path) -> anyhow::Result<DiskSpace> {
+        // 目录可能尚未创建，取最近一个存在的上级目录
+        let existing = path
+            .ancestors()
+            .find(|p| p.exists())
+            .unwrap_or_else(|| Path::new("."));
+        let path = existing.canonicalize()?;
+
+        let disks = Disks::new_with_refreshed_list();
+        let disk = disks
+            .iter()
+            .filter(|disk| path.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            .ok_or_else(|| anyhow::anyhow!("未找到 {} 所在的磁盘", path.display()))?;
+
+        Ok(DiskSpace {
+            available_bytes: disk.available_space(),
+            total_bytes: disk.total_space(),
+        })
+    }
+}
+
+/// 单个目录的检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectoryDiskSpace {
+    /// 目录用途
+    pub name: String,
+    /// 目录路径
+    pub path: String,
+    /// 状态，无法获取磁盘空间时为空
+    pub status: Option<DiskStatus>,
+    /// 可用空间（字节）
+    pub available_bytes: Option<u64>,
+    /// 总空间（字节）
+    pub total_bytes: Option<u64>,
+    /// 可用空间百分比
+    pub free_percent: Option<f64>,
+    /// 获取失败时的错误信息
+    pub error: Option<String>,
+}
+
+/// 磁盘空间检查报告
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskSpaceReport {
+    /// 所有目录中最差的状态
+    pub status: DiskStatus,
+    /// 各目录的检查结果
+    pub directories: Vec<DirectoryDiskSpace>,
+}
+
+/// 磁盘空间检查器
+#[derive(Clone)]
+pub struct DiskSpaceChecker {
+    /// 待检查的目录（用途, 路径）
+    directories: Vec<(String, PathBuf)>,
+    /// 降级阈值（可用空间百分比）
+    degraded_free_percent: f64,
+    /// 严重阈值（可用空间百分比）
+    critical_free_percent: f64,
+    /// 磁盘空间探测
+    probe: Arc<dyn DiskSpaceProbe>,
+}
+
+impl DiskSpaceChecker {
+    /// 根据应用配置创建检查器，检查结果目录，配置了热控日志目录时一并检查
+    pub fn from_config(config: &AppConfig) -> Self {
+        let mut directories = vec![("results_dir".to_string(), PathBuf::from(&config.results_dir))];
+        if let Some(dir) = &config.thermal_log_dir {
+            directories.push(("thermal_log_dir".to_string(), PathBuf::from(dir)));
+        }
+
+        Self {
+            directories,
+            degraded_free_percent: config.disk_degraded_free_percent,
+            critical_free_percent: config.disk_critical_free_percent,
+            probe: Arc::new(SystemDiskProbe),
+        }
+    }
+
+    /// 替换磁盘空间探测
+    ///
+    /// # 参数
+    /// * `probe` - 磁盘空间探测
+    pub fn with_probe(mut self, probe: Arc<dyn DiskSpaceProbe>) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// 根据可用空间百分比判断状态
+    ///
+    /// # 参数
+    /// * `free_percent` - 可用空间百分比
+    pub fn classify(&self, free_percent: f64) -> DiskStatus {
+        if free_percent < self.critical_free_percent {
+            DiskStatus::Critical
+        } else if free_percent < self.degraded_free_percent {
+            DiskStatus::Degraded
+        } else {
+            DiskStatus::Healthy
+        }
+    }
+
+    /// 检查所有目录
+    ///
+    /// 无法获取某个目录的磁盘空间时将其记为降级，不阻止测试运行
+    pub fn check(&self) -> DiskSpaceReport {
+        let directories: Vec<DirectoryDiskSpace> = self
+            .directories
+            .iter()
+            .map(|(name, path)| {
+                let mut result = DirectoryDiskSpace {
+                    name: name.clone(),
+                    path: path.display().to_string(),
+                    status: None,
+                    available_bytes: None,
+                    total_bytes: None,
+                    free_percent: None,
+                    error: None,
+                };
+                match self.probe.space(path) {
+                    Ok(space) => {
+                        let free_percent = if space.total_bytes == 0 {
+                            0.0
+                        } else {
+                            space.available_bytes as f64 / space.total_bytes as f64 * 100.0
+                        };
+                        result.status = Some(self.classify(free_percent));
+                        result.available_bytes = Some(space.available_bytes);
+                        result.total_bytes = Some(space.total_bytes);
+                        result.free_percent = Some(free_percent);
+                    }
+                    Err(e) => {
+                        tracing::warn!("获取 {} 磁盘空间失败: {}", path.display(), e);
+                        result.error = Some(e.to_string());
+                    }
+                }
+                result
+            })
+            .collect();
+
+        let status = directories
+            .iter()
+            .map(|dir| dir.status.unwrap_or(DiskStatus::Degraded))
+            .max()
+            .unwrap_or(DiskStatus::Healthy);
+
+        DiskSpaceReport { status, directories }
+    }
+
+    /// 磁盘空间是否允许启动新的测试运行，不允许时返回原因
+    pub fn ensure_can_start_run(&self) -> Result<(), String> {
+        let report = self.check();
+        if report.status != DiskStatus::Critical {
+            return Ok(());
+        }
+
+        let full: Vec<String> = report
+            .directories
+            .iter()
+            .filter(|dir| dir.status == Some(DiskStatus::Critical))
+            .map(|dir| format!("{} ({})", dir.name, dir.path))
+            .collect();
+        Err(format!("磁盘空间不足，拒绝启动测试运行: {}", full.join(", ")))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// 返回固定磁盘空间的探测
+    pub(crate) struct FixedProbe(pub DiskSpace);
+
+    impl DiskSpaceProbe for FixedProbe {
+        fn space(&self, _path: &Path) -> anyhow::Result<DiskSpace> {
+            Ok(self.0)
+        }
+    }
+
+    #[test]
+    fn test_status_follows_free_space_thresholds() {
+        let config = AppConfig::default();
+        let checker = |available_bytes| {
+            DiskSpaceChecker::from_config(&config).with_probe(Arc::new(FixedProbe(DiskSpace {
+                available_bytes,
+                total_bytes: 1000,
+            })))
+        };
+
+        assert_eq!(checker(500).check().status, DiskStatus::Healthy);
+        assert_eq!(checker(50).check().status, DiskStatus::Degraded);
+        assert_eq!(checker(10).check().status, DiskStatus::Critical);
+        assert!(checker(50).ensure_can_start_run().is_ok());
+        assert!(checker(10).ensure_can_start_run().unwrap_err().contains("results_dir"));
+    }
+}
@@ -0,0 +1,101 @@
+//! 测试运行日志存储
+//!
+//! 超过阈值的stdout/stderr写入 `results_dir` 下的文件，数据库中只保存文件引用
+
+use crate::config::AppConfig;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// 文件引用前缀，数据库中以此开头的日志内容指向 `results_dir/logs` 下的文件
+pub const SPILL_REF_PREFIX: &str = "@spilled:";
+
+/// 溢出日志所在的子目录
+const SPILL_SUBDIR: &str = "logs";
+
+/// 测试运行日志存储
+#[derive(Debug, Clone)]
+pub struct LogStorage {
+    /// 溢出文件目录
+    dir: PathBuf,
+    /// 溢出阈值（字节），`None` 表示始终内联存储
+    threshold: Option<usize>,
+}
+
+impl LogStorage {
+    /// 创建日志存储
+    ///
+    /// # 参数
+    /// * `results_dir` - 结果存储目录
+    /// * `threshold` - 溢出阈值（字节）
+    pub fn new(results_dir: impl AsRef<Path>, threshold: Option<usize>) -> Self {
+        Self {
+            dir: results_dir.as_ref().join(SPILL_SUBDIR),
+            threshold,
+        }
+    }
+
+    /// 根据应用配置创建日志存储
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new(&config.results_dir, config.log_spill_threshold_bytes)
+    }
+
+    /// 存储一段日志，返回应写入数据库的内容
+    ///
+    /// 未超过阈值时原样返回，否则写入文件并返回文件引用
+    ///
+    /// # 参数
+    /// * `run_id` - 测试运行ID
+    /// * `stream` - 输出流名称（stdout/stderr）
+    /// * `content` - 日志内容
+    pub async fn store(
+        &self,
+        run_id: &Uuid,
+        stream: &str,
+        content: Option<String>,
+    ) -> anyhow::Result<Option<String>> {
+        let content = match content {
+            Some(content) if self.threshold.is_some_and(|limit| content.len() > limit) => content,
+            other => return Ok(other),
+        };
+
+        let file_name = format!("{}.{}.log", run_id, stream);
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.dir.join(&file_name), content).await?;
+
+        Ok(Some(format!("{}{}", SPILL_REF_PREFIX, file_name)))
+    }
+
+    /// 读取日志内容，透明处理内联内容与文件引用
+    ///
+    /// # 参数
+    /// * `stored` - 数据库中保存的内容
+    pub async fn load(&self, stored: Option<String>) -> anyhow::Result<String> {
+        match stored.as_deref().and_then(|s| self.spill_path(s)) {
+            Some(path) => tokio::fs::read_to_string(&path)
+                .await
+                .map_err(|e| anyhow::anyhow!("读取溢出日志 {} 失败: {}", path.display(), e)),
+            None => Ok(stored.unwrap_or_default()),
+        }
+    }
+
+    /// 删除文件引用指向的溢出日志，文件不存在时忽略
+    ///
+    /// # 参数
+    /// * `stored` - 数据库中保存的内容
+    pub async fn remove(&self, stored: Option<&str>) -> anyhow::Result<()> {
+        if let Some(path) = stored.and_then(|s| self.spill_path(s)) {
+            match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 解析文件引用，只接受纯文件名以防止路径穿越
+    fn spill_path(&self, stored: &str) -> Option<PathBuf> {
+        let file_name = stored.strip_prefix(SPILL_REF_PREFIX)?;
+        let is_plain = Path::new(file_name).file_name().is_some_and(|n| n == file_name);
+        is_plain.then(|| self.dir.join(file_name))
+    }
+}
@@ -2,6 +2,14 @@
 //! 
 //! 提供业务逻辑处理和服务功能
 
+pub mod crypto;
+pub mod disk_space;
+pub mod log_storage;
+pub mod probe_cache;
+pub mod resource_headroom;
+pub mod run_limiter;
+pub mod test_env;
+pub mod tls;
 // pub mod test_executor; // 暂时注释掉，模块不存在
 // pub mod runtime_service; // 暂时注释掉，模块不存在
 // pub mod notification_service; // 暂时注释掉，模块不存在
@@ -0,0 +1,192 @@
+//! 运行时探测结果缓存
+//!
+//! 平台能力检测、连接测试和健康检查都要调用docker/kubectl，每个请求都执行会很慢。
+//! 按运行时管理器和探测类型缓存结果，有效期内直接返回缓存，需要最新状态时可强制刷新
+
+use crate::config::AppConfig;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 与具体运行时管理器无关的探测（如平台能力检测）使用的缓存范围
+pub const PLATFORM_SCOPE: &str = "platform";
+
+/// 探测类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProbeKind {
+    /// 平台能力检测
+    Platform,
+    /// 连接测试
+    Connection,
+    /// 健康检查
+    Health,
+    /// 运行时能力汇总
+    Capabilities,
+}
+
+/// 一次探测的结果
+#[derive(Debug, Clone)]
+struct CachedProbe {
+    value: Value,
+    probed_at: DateTime<Utc>,
+    expires_at: Instant,
+}
+
+/// 探测结果及其来源
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeResult {
+    /// 探测结果
+    pub value: Value,
+    /// 探测执行时间
+    pub probed_at: DateTime<Utc>,
+    /// 是否来自缓存
+    pub cached: bool,
+}
+
+impl ProbeResult {
+    /// 返回探测结果，对象类型的结果附带 `cache` 字段说明是否来自缓存及探测时间
+    pub fn into_value(self) -> Value {
+        let mut value = self.value;
+        if let Some(object) = value.as_object_mut() {
+            object.insert(
+                "cache".to_string(),
+                json!({
+                    "cached": self.cached,
+                    "probed_at": self.probed_at.to_rfc3339(),
+                }),
+            );
+        }
+        value
+    }
+}
+
+type ProbeSlot = Arc<tokio::sync::Mutex<Option<CachedProbe>>>;
+
+/// 运行时探测结果缓存
+///
+/// 同一管理器、同一类型的探测同时只执行一次，并发请求等待该次探测的结果
+pub struct ProbeCache {
+    /// 缓存有效期，为0时不缓存
+    ttl: Duration,
+    /// 按（缓存范围, 探测类型）保存的结果
+    slots: Mutex<HashMap<(String, ProbeKind), ProbeSlot>>,
+}
+
+impl ProbeCache {
+    /// 创建探测缓存
+    ///
+    /// # 参数
+    /// * `ttl` - 缓存有效期，为0时每次都重新探测
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 根据应用配置创建探测缓存
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new(Duration::from_secs(config.runtime_probe_cache_ttl_secs))
+    }
+
+    /// 缓存有效期
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// 返回有效期内的缓存结果，缓存不存在、已过期或要求刷新时执行探测并缓存
+    ///
+    /// # 参数
+    /// * `scope` - 缓存范围，通常为运行时管理器ID
+    /// * `kind` - 探测类型
+    /// * `refresh` - 忽略缓存强制重新探测
+    /// * `probe` - 执行探测
+    pub async fn get_or_probe<F, Fut>(&self, scope: &str, kind: ProbeKind, refresh: bool, probe: F) -> ProbeResult
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Value>,
+    {
+        let slot = {
+            let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+            Arc::clone(slots.entry((scope.to_string(), kind)).or_default())
+        };
+
+        let mut entry = slot.lock().await;
+        if !refresh {
+            if let Some(cached) = entry.as_ref().filter(|cached| cached.expires_at > Instant::now()) {
+                return ProbeResult {
+                    value: cached.value.clone(),
+                    probed_at: cached.probed_at,
+                    cached: true,
+                };
+            }
+        }
+
+        let value = probe().await;
+        let probed_at = Utc::now();
+        *entry = (!self.ttl.is_zero()).then(|| CachedProbe {
+            value: value.clone(),
+            probed_at,
+            expires_at: Instant::now() + self.ttl,
+        });
+
+        ProbeResult {
+            value,
+            probed_at,
+            cached: false,
+        }
+    }
+
+    /// 丢弃某个范围的全部缓存，运行时管理器更新或删除后调用
+    ///
+    /// # 参数
+    /// * `scope` - 缓存范围
+    pub fn invalidate(&self, scope: &str) {
+        self.slots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(slot_scope, _), _| slot_scope != scope);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_capability_queries_within_ttl_probe_once_until_refreshed() {
+        let cache = ProbeCache::new(Duration::from_secs(60));
+        let probes = AtomicUsize::new(0);
+        let query = |refresh: bool| {
+            cache.get_or_probe("manager-1", ProbeKind::Capabilities, refresh, || async {
+                let count = probes.fetch_add(1, Ordering::SeqCst) + 1;
+                json!({ "probe": count })
+            })
+        };
+
+        let first = query(false).await;
+        let second = query(false).await;
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+        assert!(!first.cached);
+        assert!(second.cached);
+        assert_eq!(second.value, json!({ "probe": 1 }));
+        assert_eq!(second.probed_at, first.probed_at);
+
+        // 强制刷新重新探测，之后的查询返回新结果
+        let refreshed = query(true).await;
+        assert_eq!(probes.load(Ordering::SeqCst), 2);
+        assert!(!refreshed.cached);
+        assert_eq!(query(false).await.value, json!({ "probe": 2 }));
+        assert_eq!(refreshed.into_value()["cache"]["cached"], false);
+
+        // 失效后重新探测
+        cache.get_or_probe("manager-1", ProbeKind::Health, false, || async { json!({}) }).await;
+        cache.invalidate("manager-1");
+        query(false).await;
+        assert_eq!(probes.load(Ordering::SeqCst), 3);
+    }
+}
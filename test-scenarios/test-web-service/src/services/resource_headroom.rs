@@ -0,0 +1,153 @@
+//! 运行时资源余量检查
+//!
+//! 在内存或CPU已经紧张的主机上启动重型测试会被OOM终止。启动测试运行前按运行时类型
+//! 查询资源检查的结果，空闲资源低于配置的余量时运行保持pending，余量恢复后再启动
+
+use crate::config::AppConfig;
+use crate::models::RuntimeType;
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 运行时所在主机的空闲资源，无法获取的项为空
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ResourceHeadroom {
+    /// 空闲内存（字节）
+    pub available_memory_bytes: Option<u64>,
+    /// 空闲CPU百分比
+    pub available_cpu_percent: Option<f64>,
+}
+
+/// 资源探测返回的Future
+pub type HeadroomFuture<'a> = Pin<Box<dyn Future<Output = ResourceHeadroom> + Send + 'a>>;
+
+/// 运行时资源探测
+pub trait ResourceProbe: Send + Sync {
+    /// 获取运行时所在主机的空闲资源
+    ///
+    /// # 参数
+    /// * `runtime_type` - 运行时类型
+    fn headroom<'a>(&'a self, runtime_type: &'a RuntimeType) -> HeadroomFuture<'a>;
+}
+
+/// 通过运行时管理器的资源检查获取空闲资源
+#[derive(Debug, Default)]
+pub struct RuntimeManagerProbe;
+
+impl ResourceProbe for RuntimeManagerProbe {
+    fn headroom<'a>(&'a self, runtime_type: &'a RuntimeType) -> HeadroomFuture<'a> {
+        Box::pin(crate::api::runtime_managers::runtime_headroom(runtime_type))
+    }
+}
+
+/// 资源余量检查器
+#[derive(Clone)]
+pub struct ResourceHeadroomChecker {
+    /// 启动运行所需的最小空闲内存（字节），为0时不检查
+    min_free_memory_bytes: u64,
+    /// 启动运行所需的最小空闲CPU百分比，为0时不检查
+    min_free_cpu_percent: f64,
+    /// 余量不足时重新检查的间隔
+    recheck_interval: Duration,
+    /// 资源探测
+    probe: Arc<dyn ResourceProbe>,
+}
+
+impl ResourceHeadroomChecker {
+    /// 根据应用配置创建检查器
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            min_free_memory_bytes: config.run_min_free_memory_mb * 1024 * 1024,
+            min_free_cpu_percent: config.run_min_free_cpu_percent,
+            recheck_interval: Duration::from_secs(config.resource_recheck_interval_secs),
+            probe: Arc::new(RuntimeManagerProbe),
+        }
+    }
+
+    /// 替换资源探测
+    ///
+    /// # 参数
+    /// * `probe` - 资源探测
+    pub fn with_probe(mut self, probe: Arc<dyn ResourceProbe>) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// 设置余量不足时重新检查的间隔
+    ///
+    /// # 参数
+    /// * `interval` - 重新检查间隔
+    pub fn with_recheck_interval(mut self, interval: Duration) -> Self {
+        self.recheck_interval = interval;
+        self
+    }
+
+    /// 余量不足时重新检查的间隔
+    pub fn recheck_interval(&self) -> Duration {
+        self.recheck_interval
+    }
+
+    /// 运行时的空闲资源是否允许启动新的测试运行，不允许时返回原因
+    ///
+    /// 无法获取的资源项不阻止启动
+    ///
+    /// # 参数
+    /// * `runtime_type` - 运行时类型
+    pub async fn ensure_headroom(&self, runtime_type: &RuntimeType) -> Result<(), String> {
+        if self.min_free_memory_bytes == 0 && self.min_free_cpu_percent <= 0.0 {
+            return Ok(());
+        }
+
+        let headroom = self.probe.headroom(runtime_type).await;
+        let mut shortages = Vec::new();
+        if let Some(available) = headroom.available_memory_bytes {
+            if available < self.min_free_memory_bytes {
+                shortages.push(format!(
+                    "空闲内存 {} MB，低于要求的 {} MB",
+                    available / 1024 / 1024,
+                    self.min_free_memory_bytes / 1024 / 1024
+                ));
+            }
+        }
+        if let Some(available) = headroom.available_cpu_percent {
+            if available < self.min_free_cpu_percent {
+                shortages.push(format!(
+                    "空闲CPU {:.1}%，低于要求的 {:.1}%",
+                    available, self.min_free_cpu_percent
+                ));
+            }
+        }
+
+        if shortages.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("insufficient resources: {} 运行时{}", runtime_type, shortages.join("，")))
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// 空闲内存可调整的探测，CPU未知
+    #[derive(Default)]
+    pub(crate) struct AdjustableProbe {
+        pub available_memory_bytes: AtomicU64,
+    }
+
+    impl ResourceProbe for AdjustableProbe {
+        fn headroom<'a>(&'a self, _runtime_type: &'a RuntimeType) -> HeadroomFuture<'a> {
+            let available = self.available_memory_bytes.load(Ordering::SeqCst);
+            Box::pin(async move {
+                ResourceHeadroom {
+                    available_memory_bytes: Some(available),
+                    available_cpu_percent: None,
+                }
+            })
+        }
+    }
+}
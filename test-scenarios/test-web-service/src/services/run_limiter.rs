@@ -0,0 +1,349 @@
+//! 测试运行并发限制
+//!
+//! 在全局并发上限之上按运行时类型分别限流，每种运行时使用独立的信号量，
+//! 某一运行时排队的运行不会占用其它运行时的名额。
+//! 等待名额的运行数受 `max_queue_depth` 限制，排队已满时按 `queue_full_policy` 处理。
+//! 排队中的运行按优先级从高到低、同优先级按登记先后获得名额；排在前面的运行
+//! 所属运行时已满时，不阻塞后面其它运行时的运行
+
+use crate::config::{AppConfig, QueueFullPolicy};
+use crate::models::RuntimeType;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{oneshot, Notify, OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+
+/// 运行许可，持有期间占用全局和所属运行时的名额
+pub struct RunPermit {
+    runtime: Option<OwnedSemaphorePermit>,
+    global: Option<OwnedSemaphorePermit>,
+    released: Arc<Notify>,
+}
+
+impl Drop for RunPermit {
+    fn drop(&mut self) {
+        // 先归还名额再唤醒排队中的运行，避免被唤醒的运行看不到空出的名额
+        self.runtime.take();
+        self.global.take();
+        self.released.notify_waiters();
+    }
+}
+
+/// 排队已满且策略为拒绝新运行
+#[derive(Debug, thiserror::Error)]
+#[error("测试运行排队已满（{max_depth} 个），请稍后重试")]
+pub struct QueueFull {
+    pub max_depth: usize,
+}
+
+/// 排队凭证，由 [`RunLimiter::enqueue`] 发放，凭此等待运行许可
+pub struct QueueTicket {
+    run_id: Uuid,
+    evicted: oneshot::Receiver<()>,
+}
+
+/// 排队中的运行
+#[derive(Debug)]
+struct QueuedRun {
+    run_id: Uuid,
+    queued_at: DateTime<Utc>,
+    priority: i32,
+    /// 开始等待名额时登记，登记前视为随时可以启动
+    runtime_type: Option<RuntimeType>,
+    /// 被挤出队列时通知等待中的运行
+    evict: oneshot::Sender<()>,
+}
+
+/// 排队中的运行信息
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedRunInfo {
+    pub test_run_id: Uuid,
+    /// 在队列中的位置，从1开始
+    pub position: usize,
+    pub priority: i32,
+    pub runtime_type: Option<RuntimeType>,
+    pub queued_at: DateTime<Utc>,
+}
+
+/// 排队状态快照
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueSnapshot {
+    /// 当前排队数
+    pub depth: usize,
+    pub max_depth: usize,
+    pub policy: QueueFullPolicy,
+    /// 全局并发上限
+    pub capacity: usize,
+    /// 正在运行的数量
+    pub running: usize,
+    /// 排队中的运行，排在前面的先获得名额
+    pub queued: Vec<QueuedRunInfo>,
+}
+
+/// 测试运行并发限制器
+#[derive(Debug)]
+pub struct RunLimiter {
+    global: Arc<Semaphore>,
+    local: Option<Arc<Semaphore>>,
+    docker: Option<Arc<Semaphore>>,
+    kubernetes: Option<Arc<Semaphore>>,
+    capacity: usize,
+    queue: Mutex<VecDeque<QueuedRun>>,
+    /// 名额释放或队列顺序变化时唤醒排队中的运行
+    changed: Arc<Notify>,
+    max_queue_depth: usize,
+    queue_full_policy: QueueFullPolicy,
+}
+
+impl RunLimiter {
+    /// 按配置创建限制器，未配置上限的运行时只受全局上限约束
+    pub fn from_config(config: &AppConfig) -> Self {
+        let limits = &config.runtime_concurrency;
+        let semaphore = |limit: Option<usize>| limit.map(|permits| Arc::new(Semaphore::new(permits)));
+        Self {
+            global: Arc::new(Semaphore::new(config.max_concurrent_tests)),
+            local: semaphore(limits.local),
+            docker: semaphore(limits.docker),
+            kubernetes: semaphore(limits.kubernetes),
+            capacity: config.max_concurrent_tests,
+            queue: Mutex::new(VecDeque::new()),
+            changed: Arc::new(Notify::new()),
+            max_queue_depth: config.max_queue_depth,
+            queue_full_policy: config.queue_full_policy,
+        }
+    }
+
+    /// 登记一个等待名额的运行
+    ///
+    /// 排队已满时按策略拒绝该运行，或挤出排队最久的运行；被挤出的运行在
+    /// [`RunLimiter::acquire_queued`] 中得到None
+    ///
+    /// # 参数
+    /// * `run_id` - 测试运行ID
+    /// * `priority` - 优先级，越大越先获得名额
+    pub fn enqueue(&self, run_id: Uuid, priority: i32) -> Result<QueueTicket, QueueFull> {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        // 仍有空闲名额时刚登记的运行马上会离开队列，不应因此被拒绝
+        if queue.len() >= self.max_queue_depth + self.global.available_permits() {
+            match self.queue_full_policy {
+                QueueFullPolicy::RejectNew => {
+                    return Err(QueueFull {
+                        max_depth: self.max_queue_depth,
+                    })
+                }
+                QueueFullPolicy::DropOldestPending => {
+                    if let Some(oldest) = queue.pop_front() {
+                        tracing::warn!("测试运行排队已满，取消排队最久的运行: {}", oldest.run_id);
+                        let _ = oldest.evict.send(());
+                    }
+                }
+            }
+        }
+
+        let (evict, evicted) = oneshot::channel();
+        queue.push_back(QueuedRun {
+            run_id,
+            queued_at: Utc::now(),
+            priority,
+            runtime_type: None,
+            evict,
+        });
+        Ok(QueueTicket { run_id, evicted })
+    }
+
+    /// 凭排队凭证等待运行许可，获得许可或被挤出队列时离开队列
+    ///
+    /// 轮到该运行（排在前面的运行都因所属运行时已满而无法启动）且名额空闲时获得许可
+    ///
+    /// # 参数
+    /// * `ticket` - 排队凭证
+    /// * `runtime_type` - 运行时类型
+    pub async fn acquire_queued(&self, ticket: QueueTicket, runtime_type: &RuntimeType) -> Option<RunPermit> {
+        let QueueTicket { run_id, mut evicted } = ticket;
+        self.update_queued(run_id, |queued| queued.runtime_type = Some(runtime_type.clone()));
+
+        loop {
+            // 先登记唤醒再检查，检查之后发生的变化不会被错过
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            if let Some(permit) = self.try_acquire_turn(run_id, runtime_type) {
+                return Some(permit);
+            }
+            tokio::select! {
+                _ = &mut changed => {}
+                _ = &mut evicted => {
+                    self.leave_queue(run_id);
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// 调整排队中运行的优先级，返回该运行是否在排队中
+    ///
+    /// # 参数
+    /// * `run_id` - 测试运行ID
+    /// * `priority` - 新的优先级
+    pub fn set_priority(&self, run_id: Uuid, priority: i32) -> bool {
+        self.update_queued(run_id, |queued| queued.priority = priority)
+    }
+
+    /// 当前排队状态
+    pub fn snapshot(&self) -> QueueSnapshot {
+        let queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        QueueSnapshot {
+            depth: queue.len(),
+            max_depth: self.max_queue_depth,
+            policy: self.queue_full_policy,
+            capacity: self.capacity,
+            running: self.capacity - self.global.available_permits(),
+            queued: in_order(&queue)
+                .into_iter()
+                .enumerate()
+                .map(|(index, queued)| QueuedRunInfo {
+                    test_run_id: queued.run_id,
+                    position: index + 1,
+                    priority: queued.priority,
+                    runtime_type: queued.runtime_type.clone(),
+                    queued_at: queued.queued_at,
+                })
+                .collect(),
+        }
+    }
+
+    /// 轮到该运行时尝试获取名额，获得后离开队列
+    fn try_acquire_turn(&self, run_id: Uuid, runtime_type: &RuntimeType) -> Option<RunPermit> {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        let blocked = in_order(&queue)
+            .into_iter()
+            .take_while(|queued| queued.run_id != run_id)
+            .any(|queued| queued.runtime_type.as_ref().is_none_or(|ahead| self.has_runtime_slot(ahead)));
+        if blocked {
+            return None;
+        }
+
+        let runtime = match self.runtime_semaphore(runtime_type) {
+            Some(semaphore) => Some(semaphore.clone().try_acquire_owned().ok()?),
+            None => None,
+        };
+        let global = self.global.clone().try_acquire_owned().ok()?;
+        queue.retain(|queued| queued.run_id != run_id);
+        drop(queue);
+
+        // 排在后面的运行可能随之轮到
+        self.changed.notify_waiters();
+        Some(self.permit(runtime, global))
+    }
+
+    /// 运行时是否还有空闲名额，未单独限流的运行时总是有
+    fn has_runtime_slot(&self, runtime_type: &RuntimeType) -> bool {
+        self.runtime_semaphore(runtime_type)
+            .is_none_or(|semaphore| semaphore.available_permits() > 0)
+    }
+
+    /// 修改排队中的运行并唤醒等待者，返回该运行是否在排队中
+    fn update_queued(&self, run_id: Uuid, update: impl FnOnce(&mut QueuedRun)) -> bool {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(queued) = queue.iter_mut().find(|queued| queued.run_id == run_id) else {
+            return false;
+        };
+        update(queued);
+        drop(queue);
+        self.changed.notify_waiters();
+        true
+    }
+
+    fn leave_queue(&self, run_id: Uuid) {
+        self.queue
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|queued| queued.run_id != run_id);
+        self.changed.notify_waiters();
+    }
+
+    fn permit(&self, runtime: Option<OwnedSemaphorePermit>, global: OwnedSemaphorePermit) -> RunPermit {
+        RunPermit {
+            runtime,
+            global: Some(global),
+            released: self.changed.clone(),
+        }
+    }
+
+    /// 等待并获取运行许可
+    ///
+    /// 先获取运行时名额再获取全局名额，等待运行时名额的运行不占用全局名额
+    ///
+    /// # 参数
+    /// * `runtime_type` - 运行时类型
+    pub async fn acquire(&self, runtime_type: &RuntimeType) -> RunPermit {
+        let runtime = match self.runtime_semaphore(runtime_type) {
+            Some(semaphore) => Some(semaphore.clone().acquire_owned().await.expect("运行时信号量不会关闭")),
+            None => None,
+        };
+        let global = self.global.clone().acquire_owned().await.expect("全局信号量不会关闭");
+        self.permit(runtime, global)
+    }
+
+    fn runtime_semaphore(&self, runtime_type: &RuntimeType) -> Option<&Arc<Semaphore>> {
+        match runtime_type {
+            RuntimeType::Local => self.local.as_ref(),
+            RuntimeType::Docker => self.docker.as_ref(),
+            RuntimeType::Kubernetes => self.kubernetes.as_ref(),
+        }
+    }
+}
+
+/// 按获得名额的先后排列：优先级高的在前，同优先级按登记先后
+fn in_order(queue: &VecDeque<QueuedRun>) -> Vec<&QueuedRun> {
+    let mut ordered: Vec<&QueuedRun> = queue.iter().collect();
+    ordered.sort_by_key(|queued| Reverse(queued.priority));
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(policy: QueueFullPolicy) -> RunLimiter {
+        RunLimiter::from_config(&AppConfig {
+            max_concurrent_tests: 1,
+            max_queue_depth: 2,
+            queue_full_policy: policy,
+            ..AppConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_third_submission_handled_by_queue_policy() {
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        // 名额已占满时排队2个后拒绝第3个
+        let reject = limiter(QueueFullPolicy::RejectNew);
+        let _running = reject.acquire(&RuntimeType::Local).await;
+        let _first = reject.enqueue(first, 0).unwrap();
+        let _second = reject.enqueue(second, 0).unwrap();
+        let err = reject.enqueue(third, 0).err().unwrap();
+        assert_eq!(err.max_depth, 2);
+        let snapshot = reject.snapshot();
+        assert_eq!((snapshot.depth, snapshot.running), (2, 1));
+
+        // 挤出排队最久的运行，新运行进入队尾
+        let drop_oldest = limiter(QueueFullPolicy::DropOldestPending);
+        let running = drop_oldest.acquire(&RuntimeType::Local).await;
+        let first_ticket = drop_oldest.enqueue(first, 0).unwrap();
+        let second_ticket = drop_oldest.enqueue(second, 0).unwrap();
+        let _third_ticket = drop_oldest.enqueue(third, 0).unwrap();
+        let queued: Vec<Uuid> = drop_oldest.snapshot().queued.iter().map(|q| q.test_run_id).collect();
+        assert_eq!(queued, vec![second, third]);
+        assert!(drop_oldest.acquire_queued(first_ticket, &RuntimeType::Local).await.is_none());
+
+        drop(running);
+        assert!(drop_oldest.acquire_queued(second_ticket, &RuntimeType::Local).await.is_some());
+        assert_eq!(drop_oldest.snapshot().depth, 1);
+    }
+}
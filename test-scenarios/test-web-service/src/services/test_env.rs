@@ -0,0 +1,93 @@
+//! 测试环境变量的加密与解析
+//!
+//! 保存时加密密文变量，执行前解密并合并出运行使用的环境变量
+
+use crate::models::test_env::{parse_stored_env, EnvVarInput, StoredEnv, StoredEnvVar, SECRET_MASK};
+use crate::services::crypto::SecretCipher;
+use std::collections::BTreeMap;
+
+/// 将请求中的环境变量转换为落库格式，密文变量加密保存
+///
+/// # 参数
+/// * `input` - 请求中的环境变量
+/// * `cipher` - 加解密器，未配置 `secret_key` 时为 `None`，此时不能保存密文变量
+pub fn encode_env(
+    input: &BTreeMap<String, EnvVarInput>,
+    cipher: Option<&SecretCipher>,
+) -> anyhow::Result<StoredEnv> {
+    input
+        .iter()
+        .map(|(name, var)| {
+            let stored = if var.is_secret() {
+                let cipher = cipher.ok_or_else(|| anyhow::anyhow!("未配置secret_key，无法保存密文环境变量 {}", name))?;
+                StoredEnvVar {
+                    value: cipher.encrypt(var.value())?,
+                    secret: true,
+                }
+            } else {
+                StoredEnvVar {
+                    value: var.value().to_string(),
+                    secret: false,
+                }
+            };
+            Ok((name.clone(), stored))
+        })
+        .collect()
+}
+
+/// 合并用例环境变量和单次运行的覆盖值，同名变量以覆盖值为准
+///
+/// # 参数
+/// * `base` - 用例 `env` 列
+/// * `overrides` - 本次运行的覆盖值
+pub fn merge_env(base: Option<&str>, overrides: StoredEnv) -> anyhow::Result<StoredEnv> {
+    let mut env = parse_stored_env(base)?;
+    env.extend(overrides);
+    Ok(env)
+}
+
+/// 解密后的运行环境变量
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedEnv {
+    /// 注入执行环境的变量
+    pub vars: BTreeMap<String, String>,
+    /// 密文变量的明文值，用于从输出中脱敏
+    secrets: Vec<String>,
+}
+
+impl ResolvedEnv {
+    /// 解密 `env` 列
+    ///
+    /// # 参数
+    /// * `raw` - `env` 列中的JSON
+    /// * `cipher` - 加解密器
+    pub fn resolve(raw: Option<&str>, cipher: Option<&SecretCipher>) -> anyhow::Result<Self> {
+        let mut resolved = Self::default();
+        for (name, var) in parse_stored_env(raw)? {
+            let value = if var.secret {
+                let cipher = cipher.ok_or_else(|| anyhow::anyhow!("未配置secret_key，无法解密环境变量 {}", name))?;
+                let value = cipher.decrypt(&var.value)?;
+                if !value.is_empty() {
+                    resolved.secrets.push(value.clone());
+                }
+                value
+            } else {
+                var.value
+            };
+            resolved.vars.insert(name, value);
+        }
+        // 先替换较长的值，避免一个密文是另一个的子串时残留部分明文
+        resolved.secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        Ok(resolved)
+    }
+
+    /// 将输出中出现的密文变量值替换为掩码
+    ///
+    /// # 参数
+    /// * `text` - 脚本输出
+    pub fn mask(&self, text: &str) -> String {
+        self.secrets
+            .iter()
+            .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), SECRET_MASK))
+    }
+}
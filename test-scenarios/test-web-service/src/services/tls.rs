@@ -0,0 +1,149 @@
+//! HTTPS监听
+//!
+//! 配置了证书和私钥时由rustls终止TLS，握手失败的连接（包括发往HTTPS端口的明文HTTP请求）直接关闭
+
+use crate::config::{TlsConfig, TlsVersion};
+use anyhow::Context;
+use axum::Router;
+use hyper::server::conn::http1;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::{version, ServerConfig, SupportedProtocolVersion};
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, warn};
+
+/// 根据HTTPS配置加载证书和私钥，构建rustls服务端配置
+///
+/// # 参数
+/// * `config` - HTTPS配置，需已启用
+pub fn load_server_config(config: &TlsConfig) -> anyhow::Result<Arc<ServerConfig>> {
+    let (Some(cert_path), Some(key_path)) = (&config.cert_path, &config.key_path) else {
+        anyhow::bail!("启用HTTPS需要同时配置 tls.cert_path 和 tls.key_path");
+    };
+
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("读取TLS证书失败: {}", cert_path))?;
+    if certs.is_empty() {
+        anyhow::bail!("TLS证书文件中没有证书: {}", cert_path);
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path).with_context(|| format!("读取TLS私钥失败: {}", key_path))?;
+
+    let versions: &[&SupportedProtocolVersion] = match config.min_version {
+        TlsVersion::Tls12 => &[&version::TLS13, &version::TLS12],
+        TlsVersion::Tls13 => &[&version::TLS13],
+    };
+    let mut server_config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_protocol_versions(versions)?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("TLS证书与私钥不匹配")?;
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(Arc::new(server_config))
+}
+
+/// 以HTTPS提供服务，直到监听出错
+///
+/// # 参数
+/// * `listener` - 已绑定的TCP监听
+/// * `app` - 应用路由
+/// * `server_config` - rustls服务端配置，通常由 [`load_server_config`] 构建
+pub async fn serve_tls(listener: TcpListener, app: Router, server_config: Arc<ServerConfig>) -> anyhow::Result<()> {
+    let acceptor = TlsAcceptor::from(server_config);
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                // 文件描述符耗尽等错误，稍后重试
+                warn!("接受连接失败: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    debug!("TLS握手失败 {}: {}", peer, e);
+                    return;
+                }
+            };
+
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .with_upgrades()
+                .await
+            {
+                debug!("HTTPS连接异常结束 {}: {}", peer, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+    use tokio_rustls::TlsConnector;
+
+    const REQUEST: &[u8] = b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/tls/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[tokio::test]
+    async fn test_tls_port_accepts_https_and_rejects_plain_http() {
+        let config = TlsConfig {
+            cert_path: Some(fixture("cert.pem")),
+            key_path: Some(fixture("key.pem")),
+            min_version: TlsVersion::Tls12,
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/health", get(|| async { "ok" }));
+        tokio::spawn(serve_tls(listener, app, load_server_config(&config).unwrap()));
+
+        // 信任测试证书的客户端可以完成握手并收到响应
+        let mut roots = RootCertStore::empty();
+        roots.add(CertificateDer::from_pem_file(fixture("cert.pem")).unwrap()).unwrap();
+        let client = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut https = TlsConnector::from(Arc::new(client))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+        https.write_all(REQUEST).await.unwrap();
+        let mut response = Vec::new();
+        let _ = https.read_to_end(&mut response).await;
+        let response = String::from_utf8_lossy(&response);
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("ok"), "{}", response);
+
+        // 明文请求在握手阶段被拒绝，收不到HTTP响应
+        let mut plain = TcpStream::connect(addr).await.unwrap();
+        plain.write_all(REQUEST).await.unwrap();
+        let mut response = Vec::new();
+        let _ = plain.read_to_end(&mut response).await;
+        assert!(!response.starts_with(b"HTTP/"), "{}", String::from_utf8_lossy(&response));
+    }
+}